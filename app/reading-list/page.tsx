@@ -121,6 +121,7 @@ export default function ReadingListPage() {
         added_at: Math.floor(Date.now() / 1000),
         read_at: null,
         last_opened_at: null,
+        thumbnail_placeholder: null,
       };
       await invoke('add_article', { article });
 
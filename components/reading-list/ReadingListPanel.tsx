@@ -19,6 +19,8 @@ export interface ReadingListItem {
   preview_text?: string;
   content?: string;
   thumbnail?: string;
+  /** Blurred data: URL shown until the thumbnail loads, or offline */
+  thumbnail_placeholder?: string;
   favicon?: string;
   tags: string[];
   reading_time_minutes?: number;
@@ -337,6 +337,8 @@ export const WorkflowCanvas: React.FC = () => {
           id: node.id,
           nodeType: node.type,
           data: node.data.config || {},
+          retry: node.data.retry,
+          continueOnError: node.data.continueOnError ?? false,
         },
       });
       log.debug('[Workflow] Node result:', nodeResult);
@@ -174,6 +174,26 @@ export interface CreateApiKeyRequest {
   expires_in_days?: number | null;
 }
 
+export interface MetricsSample {
+  /** Unix time in milliseconds */
+  timestamp: number;
+  cpuUsage: number;
+  memoryUsage: number;
+  requestsPerSecond: number;
+  activeSessions: number;
+}
+
+export interface MetricsStreamInfo {
+  url: string;
+  token: string;
+  intervalMs: number;
+  subscribers: number;
+}
+
+type MetricsFrame =
+  | { type: 'backfill'; samples: MetricsSample[]; intervalMs: number }
+  | { type: 'metrics'; sample: MetricsSample };
+
 // ============================================================================
 // User Management Service
 // ============================================================================
@@ -351,6 +371,65 @@ export const AdminMetricsService = {
   getServices: async (): Promise<ServiceStatus[]> => {
     return invoke<ServiceStatus[]>('admin_get_services');
   },
+
+  /**
+   * Get the live metrics stream endpoint (API server must be running)
+   */
+  getMetricsStream: async (): Promise<MetricsStreamInfo> => {
+    return invoke<MetricsStreamInfo>('api_server_get_metrics_stream');
+  },
+
+  /**
+   * Change the metrics sampling interval
+   */
+  setMetricsInterval: async (intervalMs: number): Promise<void> => {
+    return invoke('api_server_set_metrics_interval', { intervalMs });
+  },
+
+  /**
+   * Stream live server metrics. The backfill arrives first; dropped connections
+   * reconnect with backoff and only receive samples newer than the last one seen.
+   * Returns a function that stops the stream.
+   */
+  streamMetrics: async (
+    onSamples: (samples: MetricsSample[]) => void,
+    onError?: (error: string) => void
+  ): Promise<() => void> => {
+    const info = await invoke<MetricsStreamInfo>('api_server_get_metrics_stream');
+    let socket: WebSocket | null = null;
+    let lastSeen: number | null = null;
+    let retryMs = 1000;
+    let stopped = false;
+
+    const connect = () => {
+      const params = new URLSearchParams({ token: info.token });
+      if (lastSeen !== null) params.set('since', String(lastSeen));
+      socket = new WebSocket(`${info.url}?${params}`);
+      socket.onopen = () => {
+        retryMs = 1000;
+      };
+      socket.onmessage = (event) => {
+        const frame = JSON.parse(event.data) as MetricsFrame;
+        const samples = frame.type === 'backfill' ? frame.samples : [frame.sample];
+        if (samples.length > 0) {
+          lastSeen = samples[samples.length - 1].timestamp;
+          onSamples(samples);
+        }
+      };
+      socket.onclose = () => {
+        if (stopped) return;
+        onError?.(`Metrics stream closed, reconnecting in ${retryMs} ms`);
+        setTimeout(connect, retryMs);
+        retryMs = Math.min(retryMs * 2, 30000);
+      };
+    };
+
+    connect();
+    return () => {
+      stopped = true;
+      socket?.close();
+    };
+  },
 };
 
 // ============================================================================
@@ -6,6 +6,7 @@
  */
 
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { logger } from './logger-service';
 
 const log = logger.scope('AI');
@@ -75,6 +76,37 @@ export interface SelectorSuggestion {
   description: string;
 }
 
+export type AIStreamProvider = 'openai' | 'claude' | 'gemini';
+
+export interface AIStreamRequest {
+  prompt: string;
+  model?: string;
+  temperature?: number;
+  max_tokens?: number;
+}
+
+export interface AITokenEvent {
+  request_id: string;
+  delta: string;
+}
+
+export interface AIDoneEvent {
+  request_id: string;
+  model: string;
+  content: string;
+  prompt_tokens: number;
+  completion_tokens: number;
+  tokens_used: number;
+  finish_reason?: string;
+  cancelled: boolean;
+  error?: string;
+}
+
+export interface AIStreamHandlers {
+  onToken: (delta: string) => void;
+  onDone?: (event: AIDoneEvent) => void;
+}
+
 // ============================================================================
 // Chat Service
 // ============================================================================
@@ -157,6 +189,66 @@ export const OpenAIService = {
   },
 };
 
+// ============================================================================
+// AI Streaming Service
+// ============================================================================
+
+export const AIStreamService = {
+  /**
+   * Start a streaming completion. Resolves with the request id once the
+   * stream is running; tokens and the final usage arrive through handlers.
+   */
+  start: async (
+    provider: AIStreamProvider,
+    apiKey: string,
+    request: AIStreamRequest,
+    handlers: AIStreamHandlers
+  ): Promise<string> => {
+    // Listen before invoking so no early token is missed; events that arrive
+    // before the id is known are replayed once it is
+    let requestId: string | null = null;
+    const pending: Array<() => void> = [];
+    const dispatch = (id: string, handle: () => void) => {
+      if (requestId === null) {
+        pending.push(() => {
+          if (id === requestId) handle();
+        });
+      } else if (id === requestId) {
+        handle();
+      }
+    };
+
+    const unlistenToken = await listen<AITokenEvent>('ai-token', (event) =>
+      dispatch(event.payload.request_id, () => handlers.onToken(event.payload.delta))
+    );
+    const unlistenDone = await listen<AIDoneEvent>('ai-done', (event) =>
+      dispatch(event.payload.request_id, () => {
+        unlistenToken();
+        unlistenDone();
+        handlers.onDone?.(event.payload);
+      })
+    );
+
+    try {
+      requestId = await invoke<string>(`${provider}_completion_stream`, { apiKey, request });
+    } catch (error) {
+      unlistenToken();
+      unlistenDone();
+      log.error('Failed to start AI stream:', error);
+      throw error;
+    }
+    pending.splice(0).forEach((replay) => replay());
+    return requestId;
+  },
+
+  /**
+   * Cancel a running stream. Returns false if it had already finished.
+   */
+  cancel: async (requestId: string): Promise<boolean> => {
+    return invoke<boolean>('cancel_ai_stream', { requestId });
+  },
+};
+
 // ============================================================================
 // AI Workflow Service
 // ============================================================================
@@ -245,6 +337,7 @@ export const AIAnalysisService = {
 export const AIService = {
   Chat: ChatService,
   OpenAI: OpenAIService,
+  Stream: AIStreamService,
   Workflow: AIWorkflowService,
   Selector: AISelectorService,
   Analysis: AIAnalysisService,
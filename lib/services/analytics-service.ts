@@ -783,6 +783,33 @@ export interface AlertEvent {
   resolvedAt?: number;
 }
 
+export type AnomalySensitivity = 'low' | 'medium' | 'high';
+
+export interface MetricAnomaly {
+  timestamp: number;
+  value: number;
+  expected: number;
+  z_score: number;
+  severity: 'info' | 'warning' | 'error' | 'critical';
+}
+
+export interface AnomalyDetectionResult {
+  metric_id: string;
+  method: 'rolling_z_score' | { seasonal: { period_ms: number } };
+  points_analyzed: number;
+  threshold: number;
+  anomalies: MetricAnomaly[];
+  alert_events: {
+    id: string;
+    alert_id: string;
+    status: 'ok' | 'alerting' | 'pending' | 'nodata';
+    value: number;
+    message: string;
+    acknowledged: boolean;
+    created_at: number;
+  }[];
+}
+
 // ============================================================================
 // Dashboard Service
 // ============================================================================
@@ -1212,6 +1239,26 @@ export const MetricService = {
     });
   },
 
+  /**
+   * Detect anomalies in a recorded metric; sustained anomalies raise alert events
+   * and the metric keeps being monitored with these settings
+   */
+  detectAnomalies: async (
+    metricId: string,
+    options?: {
+      window?: number;
+      sensitivity?: AnomalySensitivity;
+      consecutivePoints?: number;
+    }
+  ): Promise<AnomalyDetectionResult> => {
+    return invoke<AnomalyDetectionResult>('metric_detect_anomalies', {
+      metricId,
+      window: options?.window,
+      sensitivity: options?.sensitivity,
+      consecutivePoints: options?.consecutivePoints,
+    });
+  },
+
   /**
    * List metrics (alias for getCurrent)
    */
@@ -89,6 +89,32 @@ export interface FlowExecution {
 
 export type ExecutionStatus = 'pending' | 'running' | 'completed' | 'failed' | 'cancelled';
 
+export type DatasetInput =
+  | { format: 'csv'; content: string }
+  | { format: 'json'; rows: Record<string, unknown>[] }
+  | { format: 'dataSource'; sourceId: string; table: string };
+
+export interface DatasetRowExecution {
+  rowIndex: number;
+  variables: Record<string, unknown>;
+  status: ExecutionStatus;
+  execution?: FlowExecution;
+  error?: string;
+}
+
+export interface DatasetExecution {
+  id: string;
+  flowId: string;
+  status: ExecutionStatus;
+  startedAt: string;
+  completedAt?: string;
+  totalRows: number;
+  succeeded: number;
+  failed: number;
+  skipped: number;
+  rows: DatasetRowExecution[];
+}
+
 export interface NodeResult {
   nodeId: string;
   status: NodeStatus;
@@ -182,6 +208,22 @@ export const FlowExecutionService = {
     return invoke<FlowExecution>('automation_execute_flow', { flow, variables });
   },
 
+  /**
+   * Execute a flow once per dataset row, binding columns as {{column}} variables
+   */
+  executeFlowDataset: async (
+    flow: Flow,
+    dataset: DatasetInput,
+    options?: { maxConcurrency?: number; continueOnError?: boolean }
+  ): Promise<DatasetExecution> => {
+    return invoke<DatasetExecution>('automation_execute_flow_dataset', {
+      flow,
+      dataset,
+      maxConcurrency: options?.maxConcurrency,
+      continueOnError: options?.continueOnError,
+    });
+  },
+
   /**
    * Execute a single node
    */
@@ -113,6 +113,34 @@ export interface TranslationResult {
   created_at: number;
 }
 
+export interface TranslatedBlock {
+  selector: string;
+  original_html: string;
+  translated_html: string;
+}
+
+export interface PageTranslation {
+  id: string;
+  url: string;
+  source_language: Language;
+  target_language: Language;
+  translated_html: string;
+  original_html: string;
+  blocks: TranslatedBlock[];
+  segments_translated: number;
+  segments_rejected: number;
+  batches: number;
+  model_used: AIModel;
+  created_at: number;
+  cached: boolean;
+}
+
+export interface PageRestore {
+  url: string;
+  original_html: string;
+  blocks: TranslatedBlock[];
+}
+
 export interface FormField {
   selector: string;
   field_type: FormFieldType;
@@ -495,9 +523,9 @@ class BrowserAIAssistantService {
     }
   }
 
-  public async translatePage(url: string, content: string, targetLanguage: Language): Promise<TranslationResult> {
+  public async translatePage(url: string, content: string, targetLanguage: Language): Promise<PageTranslation> {
     try {
-      return await invoke<TranslationResult>('ai_translate_page', {
+      return await invoke<PageTranslation>('ai_translate_page', {
         url,
         content,
         targetLanguage,
@@ -510,6 +538,17 @@ class BrowserAIAssistantService {
     }
   }
 
+  public async showOriginalPage(url: string): Promise<PageRestore> {
+    try {
+      return await invoke<PageRestore>('ai_show_original_page', { url });
+    } catch (error) {
+      if (error instanceof Error) {
+        throw new Error(`Failed to show original page: ${error.message}`);
+      }
+      throw new Error('Failed to show original page: Unknown error');
+    }
+  }
+
   public async translateToEnglish(text: string): Promise<TranslationResult> {
     try {
       return await invoke<TranslationResult>('ai_translate_to_english', { text });
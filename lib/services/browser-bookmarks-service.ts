@@ -22,6 +22,21 @@ export interface BookmarkSettings {
   max_recent: number;
   backup_enabled: boolean;
   backup_interval_hours: number;
+  url_normalization: UrlNormalizationRules;
+}
+
+/** How URLs are compared when looking for duplicates. A trailing `*` in a tracking param matches any suffix. */
+export interface UrlNormalizationRules {
+  ignore_scheme: boolean;
+  strip_trailing_slash: boolean;
+  strip_fragment: boolean;
+  tracking_params: string[];
+}
+
+export interface NormalizedUrl {
+  url: string;
+  normalized: string;
+  removed_params: string[];
 }
 
 export interface Bookmark {
@@ -92,6 +107,22 @@ export interface ImportResult {
   errors: string[];
 }
 
+export type DuplicateMergeStrategy = 'KeepInBar' | 'KeepOldestFolder' | 'KeepOldest' | 'KeepMostVisited';
+
+export interface MergedDuplicate {
+  canonical_url: string;
+  kept: Bookmark;
+  kept_before_merge: Bookmark;
+  removed: { bookmark: Bookmark; index: number }[];
+}
+
+export interface MergeReport {
+  strategy: DuplicateMergeStrategy;
+  merged: MergedDuplicate[];
+  removed_count: number;
+  removed_ids: string[];
+}
+
 // ==================== Settings ====================
 
 export async function getBookmarkSettings(): Promise<BookmarkSettings> {
@@ -315,6 +346,26 @@ export async function findDuplicates(): Promise<[Bookmark, Bookmark][]> {
   return await invoke<[Bookmark, Bookmark][]>('browser_bookmarks_find_duplicates');
 }
 
+export async function mergeDuplicates(strategy: DuplicateMergeStrategy): Promise<MergeReport> {
+  return await invoke<MergeReport>('browser_bookmarks_merge_duplicates', { strategy });
+}
+
+export async function undoMerge(report: MergeReport): Promise<number> {
+  return await invoke<number>('browser_bookmarks_undo_merge', { report });
+}
+
+export async function getNormalizationRules(): Promise<UrlNormalizationRules> {
+  return await invoke<UrlNormalizationRules>('browser_bookmarks_get_normalization_rules');
+}
+
+export async function setNormalizationRules(rules: UrlNormalizationRules): Promise<void> {
+  return await invoke<void>('browser_bookmarks_set_normalization_rules', { rules });
+}
+
+export async function previewUrlNormalization(url: string): Promise<NormalizedUrl> {
+  return await invoke<NormalizedUrl>('browser_bookmarks_preview_url_normalization', { url });
+}
+
 export async function cleanupOrphaned(): Promise<number> {
   return await invoke<number>('browser_bookmarks_cleanup_orphaned');
 }
@@ -556,6 +607,11 @@ export const bookmarksService = {
   // Utility
   checkUrlExists,
   findDuplicates,
+  mergeDuplicates,
+  undoMerge,
+  getNormalizationRules,
+  setNormalizationRules,
+  previewUrlNormalization,
   cleanupOrphaned,
   
   // Quick Actions
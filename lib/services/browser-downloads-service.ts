@@ -51,8 +51,45 @@ export type ScheduleType =
   | 'OnWifi'
   | 'OffPeakHours';
 
+export type TorrentPhase =
+  | 'fetching_metadata'
+  | 'checking'
+  | 'downloading'
+  | 'seeding'
+  | 'paused'
+  | 'finished';
+
 // ==================== Interfaces ====================
 
+export interface TorrentSettings {
+  /** Stop seeding once uploaded / downloaded reaches this (0 = don't seed) */
+  seed_ratio_limit: number;
+  seed_time_limit_minutes: number | null;
+  /** 0 = unlimited */
+  upload_limit_kbps: number;
+  listen_port: number;
+  max_peers: number;
+}
+
+export interface TorrentFileSelection {
+  index: number;
+  path: string;
+  length: number;
+  selected: boolean;
+}
+
+export interface TorrentDetails {
+  info_hash: string;
+  phase: TorrentPhase;
+  /** Empty until a magnet link's metadata arrives */
+  files: TorrentFileSelection[];
+  seeds: number;
+  peers: number;
+  uploaded_bytes: number;
+  upload_speed_bps: number;
+  ratio: number;
+}
+
 export interface DownloadSettings {
   enabled: boolean;
   default_directory: string;
@@ -76,6 +113,7 @@ export interface DownloadSettings {
   blocked_extensions: string[];
   blocked_domains: string[];
   download_history_days: number;
+  torrent: TorrentSettings;
 }
 
 export interface Download {
@@ -90,7 +128,8 @@ export interface Download {
   total_bytes: number;
   downloaded_bytes: number;
   speed_bps: number;
-  eta_seconds: number;
+  /** Null while the speed or total size is unknown */
+  eta_seconds: number | null;
   connections: number;
   resumable: boolean;
   created_at: number;
@@ -109,6 +148,23 @@ export interface Download {
   auto_extract: boolean;
   virus_scanned: boolean;
   virus_clean: boolean | null;
+  /** Set for .torrent and magnet downloads */
+  torrent: TorrentDetails | null;
+  /** Validators checked when a paused download resumes */
+  etag: string | null;
+  last_modified: string | null;
+  /** Byte ranges fetched in parallel into .part files */
+  segments: DownloadSegment[];
+  /** e.g. a resume that had to restart because the file changed */
+  warnings: string[];
+}
+
+export interface DownloadSegment {
+  start: number;
+  /** Inclusive; null while the size is unknown */
+  end: number | null;
+  downloaded: number;
+  complete: boolean;
 }
 
 export interface DownloadQueue {
@@ -167,8 +223,11 @@ export async function setDefaultDirectory(directory: string): Promise<void> {
   return invoke('download_set_default_directory', { directory });
 }
 
-export async function setMaxConcurrent(max: number): Promise<void> {
-  return invoke('download_set_max_concurrent', { max });
+/**
+ * Max concurrent downloads, or with a download id, the segments that file is fetched in
+ */
+export async function setMaxConcurrent(max: number, downloadId?: string): Promise<void> {
+  return invoke('download_set_max_concurrent', { max, downloadId: downloadId ?? null });
 }
 
 export async function setBandwidthLimit(enabled: boolean, limitKbps: number): Promise<void> {
@@ -187,6 +246,10 @@ export async function removeBlockedExtension(ext: string): Promise<void> {
   return invoke('download_remove_blocked_extension', { ext });
 }
 
+export async function setTorrentSettings(torrent: TorrentSettings): Promise<void> {
+  return invoke('download_set_torrent_settings', { torrent });
+}
+
 // ==================== Download Operations ====================
 
 export async function createDownload(
@@ -234,6 +297,17 @@ export async function setDownloadFailed(downloadId: string, error: string): Prom
   return invoke('download_set_failed', { downloadId, error });
 }
 
+// ==================== Torrents ====================
+
+/** Chooses which files of a multi-file torrent to download */
+export async function selectTorrentFiles(downloadId: string, fileIndices: number[]): Promise<Download> {
+  return invoke<Download>('download_torrent_select_files', { downloadId, fileIndices });
+}
+
+export async function stopSeeding(downloadId: string): Promise<Download> {
+  return invoke<Download>('download_torrent_stop_seeding', { downloadId });
+}
+
 // ==================== Download Management ====================
 
 export async function getDownload(downloadId: string): Promise<Download | null> {
@@ -349,6 +423,10 @@ export async function getTotalSpeed(): Promise<number> {
   return invoke<number>('download_get_total_speed');
 }
 
+export async function getDownloadEta(downloadId: string): Promise<number | null> {
+  return invoke<number | null>('download_get_eta', { downloadId });
+}
+
 export async function getCategoryStats(): Promise<Record<string, number>> {
   return invoke<Record<string, number>>('download_get_category_stats');
 }
@@ -423,7 +501,8 @@ export function formatSpeed(bytesPerSecond: number): string {
   return formatBytes(bytesPerSecond) + '/s';
 }
 
-export function formatEta(seconds: number): string {
+export function formatEta(seconds: number | null): string {
+  if (seconds === null) return '--';
   if (seconds < 60) return `${seconds}s`;
   if (seconds < 3600) return `${Math.floor(seconds / 60)}m ${seconds % 60}s`;
   const hours = Math.floor(seconds / 3600);
@@ -484,6 +563,7 @@ const BrowserDownloadsService = {
   setCategoryFolder,
   addBlockedExtension,
   removeBlockedExtension,
+  setTorrentSettings,
   
   // Operations
   create: createDownload,
@@ -496,6 +576,10 @@ const BrowserDownloadsService = {
   updateProgress: updateDownloadProgress,
   setFailed: setDownloadFailed,
   
+  // Torrents
+  selectTorrentFiles,
+  stopSeeding,
+  
   // Management
   get: getDownload,
   getAll: getAllDownloads,
@@ -531,6 +615,7 @@ const BrowserDownloadsService = {
   // Stats
   getStats: getDownloadStats,
   getTotalSpeed,
+  getDownloadEta,
   getCategoryStats,
   
   // Bulk
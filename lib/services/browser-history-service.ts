@@ -76,6 +76,7 @@ export interface HistorySettings {
   group_by_domain: boolean;
   show_previews: boolean;
   analytics_enabled: boolean;
+  semantic_search_enabled?: boolean;
 }
 
 export interface Visit {
@@ -283,10 +284,20 @@ export async function searchHistory(query: string): Promise<SearchResult[]> {
   return invoke<SearchResult[]>('history_search', { query });
 }
 
+/** Meaning-based search; falls back to keyword search when embeddings are disabled. */
+export async function semanticSearchHistory(query: string, k: number): Promise<SearchResult[]> {
+  return invoke<SearchResult[]>('history_semantic_search', { query, k });
+}
+
 export async function suggestUrls(query: string, limit: number): Promise<string[]> {
   return invoke<string[]>('history_suggest', { query, limit });
 }
 
+/** Rebuilds the full-text search index; returns the number of entries indexed. */
+export async function rebuildHistoryIndex(): Promise<number> {
+  return invoke<number>('history_rebuild_index');
+}
+
 // ==================== Tags Functions ====================
 
 export async function addHistoryTag(entryId: string, tag: string): Promise<void> {
@@ -577,6 +588,7 @@ const BrowserHistoryService = {
   // Search
   search: searchHistory,
   suggest: suggestUrls,
+  rebuildIndex: rebuildHistoryIndex,
   
   // Tags
   addTag: addHistoryTag,
@@ -22,6 +22,13 @@ export type CookiePolicy =
   | 'BlockAllCookies'
   | 'Custom';
 
+/** Per-site cookie lifetime rule, applied on top of `cookie_policy` */
+export type SiteCookiePolicy =
+  | { type: 'allow' }
+  | { type: 'session_only' }
+  | { type: 'auto_expire_after'; seconds: number }
+  | { type: 'block' };
+
 export type PermissionDefault = 'Allow' | 'Block' | 'Ask';
 
 export type SameSite = 'Strict' | 'Lax' | 'None';
@@ -41,6 +48,12 @@ export interface PrivacySettings {
   block_third_party_cookies: boolean;
   clear_cookies_on_exit: boolean;
   cookie_lifetime_days: number | null;
+  default_site_cookie_policy: SiteCookiePolicy;
+  /** Overrides by domain; a rule also covers the domain's subdomains */
+  site_cookie_policies: Record<string, SiteCookiePolicy>;
+  /** Exempt from the default policy and clearing on exit */
+  keep_signed_in_sites: string[];
+  cookie_sweep_interval_minutes: number;
   // Fingerprinting
   randomize_canvas: boolean;
   randomize_webgl: boolean;
@@ -268,6 +281,34 @@ export async function getCookieStats(): Promise<Record<string, number>> {
   return invoke<Record<string, number>>('privacy_get_cookie_stats');
 }
 
+// ==================== Cookie Policy Commands ====================
+
+export async function setCookiePolicy(domain: string, policy: SiteCookiePolicy): Promise<void> {
+  return invoke('privacy_set_cookie_policy', { domain, policy });
+}
+
+export async function clearCookiePolicy(domain: string): Promise<void> {
+  return invoke('privacy_clear_cookie_policy', { domain });
+}
+
+export async function setDefaultCookiePolicy(policy: SiteCookiePolicy): Promise<void> {
+  return invoke('privacy_set_default_cookie_policy', { policy });
+}
+
+/** The policy in force for a domain after overrides and the default */
+export async function getCookiePolicy(domain: string): Promise<SiteCookiePolicy> {
+  return invoke<SiteCookiePolicy>('privacy_get_cookie_policy', { domain });
+}
+
+export async function setKeepSignedIn(domain: string, keep: boolean): Promise<void> {
+  return invoke('privacy_set_keep_signed_in', { domain, keep });
+}
+
+/** Deletes expired and blocked cookies now; returns how many */
+export async function sweepCookies(): Promise<number> {
+  return invoke<number>('privacy_sweep_cookies');
+}
+
 // ==================== Fingerprint Commands ====================
 
 export async function getFingerprintProtection(): Promise<FingerprintProtection> {
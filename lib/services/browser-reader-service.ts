@@ -115,6 +115,21 @@ export interface ReadingSession {
   completed_at: number | null;
 }
 
+/** Reading progress and annotations exchanged between devices, matched by URL */
+export interface ReaderProgressSync {
+  url: string;
+  device_id: string;
+  scroll_position: number;
+  paragraph_index: number;
+  paragraph_progress: number;
+  paragraph_anchor: string;
+  paragraph_count: number;
+  time_spent_seconds: number;
+  annotations: Annotation[];
+  deleted_annotations: string[];
+  updated_at: number;
+}
+
 export interface TTSPlaybackState {
   is_playing: boolean;
   is_paused: boolean;
@@ -584,6 +599,24 @@ class BrowserReaderService {
     }
   }
 
+  /**
+   * Apply progress received from another device; the furthest position wins
+   */
+  public async applySyncedProgress(progress: ReaderProgressSync): Promise<ReadingSession | null> {
+    try {
+      const session = await invoke<ReadingSession | null>('reader_apply_synced_progress', { progress });
+      if (session) {
+        this.emit('progress-updated', { articleId: session.article_id, scrollPosition: session.scroll_position });
+      }
+      return session;
+    } catch (error) {
+      if (error instanceof Error) {
+        throw new Error(`Failed to apply synced progress: ${error.message}`);
+      }
+      throw new Error('Failed to apply synced progress: Unknown error');
+    }
+  }
+
   public async getHistory(limit: number = 50): Promise<ReadingSession[]> {
     try {
       return await invoke<ReadingSession[]>('reader_get_history', { limit });
@@ -812,9 +845,12 @@ class BrowserReaderService {
     }
   }
 
-  public async estimateReadingTime(wordCount: number): Promise<number> {
+  /**
+   * Minutes to read at the user's measured speed; with `articleId`, the minutes left in that article
+   */
+  public async estimateReadingTime(wordCount: number, articleId?: string): Promise<number> {
     try {
-      return await invoke<number>('reader_estimate_reading_time', { wordCount });
+      return await invoke<number>('reader_estimate_reading_time', { wordCount, articleId });
     } catch (error) {
       if (error instanceof Error) {
         throw new Error(`Failed to estimate reading time: ${error.message}`);
@@ -74,6 +74,7 @@ export interface SearchSettings {
   suggestion_delay_ms: number;
   enable_quick_keywords: boolean;
   enable_bang_commands: boolean;
+  keep_unknown_bangs?: boolean;
   enable_calculator: boolean;
   enable_unit_conversion: boolean;
   enable_currency_conversion: boolean;
@@ -114,6 +115,8 @@ export interface OmniboxResult {
   calculator_result: string | null;
   conversion_result: ConversionResult | null;
   matched_engine: SearchEngine | null;
+  bang: string | null;
+  search_url: string | null;
 }
 
 export interface ConversionResult {
@@ -352,6 +355,7 @@ export const DEFAULT_SEARCH_SETTINGS: SearchSettings = {
   suggestion_delay_ms: 150,
   enable_quick_keywords: true,
   enable_bang_commands: true,
+  keep_unknown_bangs: false,
   enable_calculator: true,
   enable_unit_conversion: true,
   enable_currency_conversion: true,
@@ -17,7 +17,8 @@ export type SyncDataType =
   | 'Autofill'
   | 'ReadingList'
   | 'Notes'
-  | 'Workspaces';
+  | 'Workspaces'
+  | 'Notifications';
 
 export type ConflictResolution = 'ServerWins' | 'ClientWins' | 'MostRecent' | 'Manual';
 
@@ -46,6 +47,7 @@ export interface SyncSettings {
   sync_reading_list: boolean;
   sync_notes: boolean;
   sync_workspaces: boolean;
+  sync_notifications: boolean;
   e2e_encryption_enabled: boolean;
   encryption_key_id: string | null;
   wifi_only: boolean;
@@ -100,6 +102,30 @@ export interface SyncConflict {
   resolution: ConflictResolution | null;
 }
 
+export interface BookmarkNode {
+  id: string;
+  parent_id: string | null;
+  title: string;
+  url: string | null;
+  is_folder: boolean;
+  position: number;
+  modified_at: string;
+  is_deleted: boolean;
+}
+
+export interface BookmarkMoveConflict {
+  item_id: string;
+  local: BookmarkNode;
+  remote: BookmarkNode;
+  applied: BookmarkNode;
+}
+
+export interface BookmarkMerge {
+  nodes: BookmarkNode[];
+  conflicts: BookmarkMoveConflict[];
+  merged_folders: Record<string, string>;
+}
+
 export interface SyncHistory {
   id: string;
   sync_type: SyncType;
@@ -260,6 +286,23 @@ export async function syncDataType(dataType: SyncDataType): Promise<string> {
   return invoke<string>('sync_data_type', { data_type: dataType });
 }
 
+export async function receiveSyncItems(items: SyncItem[]): Promise<number> {
+  return invoke<number>('sync_receive_items', { items });
+}
+
+// ==================== Bookmark Merge Commands ====================
+
+export async function mergeBookmarks(
+  local: BookmarkNode[],
+  remote: BookmarkNode[]
+): Promise<BookmarkMerge> {
+  return invoke<BookmarkMerge>('sync_merge_bookmarks', { local, remote });
+}
+
+export async function getBookmarkTree(): Promise<BookmarkNode[]> {
+  return invoke<BookmarkNode[]>('sync_get_bookmark_tree');
+}
+
 // ==================== Conflict Commands ====================
 
 export async function getSyncConflicts(): Promise<SyncConflict[]> {
@@ -370,6 +413,7 @@ export function formatDataType(type: SyncDataType): string {
     ReadingList: '📚 Reading List',
     Notes: '📔 Notes',
     Workspaces: '🗂️ Workspaces',
+    Notifications: '🔔 Notifications',
   };
   return types[type] || type;
 }
@@ -438,6 +482,7 @@ export function isDataTypeEnabled(settings: SyncSettings, type: SyncDataType): b
     ReadingList: 'sync_reading_list',
     Notes: 'sync_notes',
     Workspaces: 'sync_workspaces',
+    Notifications: 'sync_notifications',
   };
   return settings[mapping[type]] as boolean;
 }
@@ -453,6 +498,7 @@ export const ALL_SYNC_DATA_TYPES: SyncDataType[] = [
   'ReadingList',
   'Notes',
   'Workspaces',
+  'Notifications',
 ];
 
 export const DEFAULT_SYNC_SETTINGS: SyncSettings = {
@@ -470,6 +516,7 @@ export const DEFAULT_SYNC_SETTINGS: SyncSettings = {
   sync_reading_list: true,
   sync_notes: true,
   sync_workspaces: true,
+  sync_notifications: true,
   e2e_encryption_enabled: true,
   encryption_key_id: null,
   wifi_only: false,
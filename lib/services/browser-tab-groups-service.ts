@@ -68,6 +68,17 @@ export interface TabMetadata {
   playing_audio: boolean;
   created_at: number;
   last_accessed: number;
+  opener_id: string | null;
+}
+
+export type ChildCloseBehavior = 'close_children' | 'promote_children';
+
+export interface TabTreeNode {
+  tab_id: string;
+  tab: TabMetadata | null;
+  depth: number;
+  collapsed: boolean;
+  children: TabTreeNode[];
 }
 
 export interface GroupSuggestion {
@@ -105,6 +116,7 @@ export interface TabGroupsConfig {
   vertical_tabs_enabled: boolean;
   stacking_enabled: boolean;
   grouping_rules: GroupingRule[];
+  child_close_behavior: ChildCloseBehavior;
 }
 
 export interface TabGroupsStats {
@@ -286,9 +298,9 @@ class TabGroupsService {
 
   // ============ Tab Management ============
 
-  async registerTab(id: string, url: string, title: string): Promise<string> {
+  async registerTab(id: string, url: string, title: string, openerId?: string): Promise<string> {
     try {
-      return await invoke<string>('tab_groups_register_tab', { id, url, title });
+      return await invoke<string>('tab_groups_register_tab', { id, url, title, openerId: openerId ?? null });
     } catch (error) {
       log.error('Failed to register tab:', error);
       throw new Error(`Failed to register tab: ${error}`);
@@ -349,6 +361,45 @@ class TabGroupsService {
     }
   }
 
+  // ============ Tab Tree ============
+
+  async getTabTree(): Promise<TabTreeNode[]> {
+    try {
+      return await invoke<TabTreeNode[]>('tab_groups_get_tab_tree');
+    } catch (error) {
+      log.error('Failed to get tab tree:', error);
+      throw new Error(`Failed to get tab tree: ${error}`);
+    }
+  }
+
+  async setSubtreeCollapsed(tabId: string, collapsed: boolean): Promise<boolean> {
+    try {
+      return await invoke<boolean>('tab_groups_set_subtree_collapsed', { tabId, collapsed });
+    } catch (error) {
+      log.error('Failed to collapse subtree:', error);
+      throw new Error(`Failed to collapse subtree: ${error}`);
+    }
+  }
+
+  /** Returns the ids of every tab to close, the tab itself first */
+  async closeTab(tabId: string, behavior?: ChildCloseBehavior): Promise<string[]> {
+    try {
+      return await invoke<string[]>('tab_groups_close_tab', { tabId, behavior: behavior ?? null });
+    } catch (error) {
+      log.error('Failed to close tab:', error);
+      throw new Error(`Failed to close tab: ${error}`);
+    }
+  }
+
+  async closeSubtree(tabId: string): Promise<string[]> {
+    try {
+      return await invoke<string[]>('tab_groups_close_subtree', { tabId });
+    } catch (error) {
+      log.error('Failed to close subtree:', error);
+      throw new Error(`Failed to close subtree: ${error}`);
+    }
+  }
+
   // ============ Tab Stacking (Vivaldi-style) ============
 
   async stackTabs(tabIds: string[], groupId: string): Promise<string | null> {
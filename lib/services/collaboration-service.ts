@@ -683,6 +683,41 @@ export interface CollaborationSession {
   is_video_active: boolean;
   shared_workflow_id?: string;
   permissions: SessionPermissions;
+  audio_channel?: SessionAudioChannel | null;
+}
+
+export interface SessionAudioChannel {
+  channel_id: string;
+  ice_servers: {
+    urls: string[];
+    username?: string | null;
+    credential?: string | null;
+    credential_type?: string | null;
+  }[];
+  codec: string;
+  started_at: string;
+}
+
+export interface ParticipantAudio {
+  has_microphone: boolean;
+  muted: boolean;
+  push_to_talk: boolean;
+  speaking: boolean;
+  audio_level: number;
+  track?: {
+    track_id: string;
+    stream_id: string;
+    direction: 'sendrecv' | 'recvonly';
+  } | null;
+}
+
+export interface AudioStateChange {
+  session_id: string;
+  participant_id: string;
+  muted: boolean;
+  speaking: boolean;
+  listen_only: boolean;
+  recipients: string[];
 }
 
 export interface SessionParticipant {
@@ -696,6 +731,7 @@ export interface SessionParticipant {
   joined_at: string;
   last_activity: string;
   permissions: ParticipantPermissions;
+  audio?: ParticipantAudio;
 }
 
 export interface SessionCursorPosition {
@@ -1681,12 +1717,14 @@ export const SessionService = {
   create: async (
     name: string,
     hostName: string,
-    permissions: SessionPermissions
+    permissions: SessionPermissions,
+    hasMicrophone = true
   ): Promise<CollaborationSession> => {
     return invoke<CollaborationSession>('create_collaboration_session', {
       name,
       host_name: hostName,
       permissions,
+      has_microphone: hasMicrophone,
     });
   },
 
@@ -1695,11 +1733,13 @@ export const SessionService = {
    */
   join: async (
     sessionId: string,
-    participantName: string
+    participantName: string,
+    hasMicrophone = true
   ): Promise<CollaborationSession> => {
     return invoke<CollaborationSession>('join_collaboration_session', {
       session_id: sessionId,
       participant_name: participantName,
+      has_microphone: hasMicrophone,
     });
   },
 
@@ -1778,6 +1818,75 @@ export const SessionService = {
     });
   },
 
+  /**
+   * Turn the session voice channel on or off
+   */
+  toggleAudio: async (sessionId: string): Promise<CollaborationSession> => {
+    return invoke<CollaborationSession>('collaboration_toggle_audio', {
+      session_id: sessionId,
+    });
+  },
+
+  /**
+   * Mute or unmute a participant
+   */
+  setMute: async (
+    sessionId: string,
+    participantId: string,
+    muted: boolean
+  ): Promise<AudioStateChange> => {
+    return invoke<AudioStateChange>('collaboration_set_mute', {
+      session_id: sessionId,
+      participant_id: participantId,
+      muted,
+    });
+  },
+
+  /**
+   * Enable or disable push-to-talk
+   */
+  setPushToTalk: async (
+    sessionId: string,
+    participantId: string,
+    enabled: boolean
+  ): Promise<AudioStateChange> => {
+    return invoke<AudioStateChange>('collaboration_set_push_to_talk', {
+      session_id: sessionId,
+      participant_id: participantId,
+      enabled,
+    });
+  },
+
+  /**
+   * Push-to-talk key pressed or released
+   */
+  pushToTalk: async (
+    sessionId: string,
+    participantId: string,
+    pressed: boolean
+  ): Promise<AudioStateChange> => {
+    return invoke<AudioStateChange>('collaboration_push_to_talk', {
+      session_id: sessionId,
+      participant_id: participantId,
+      pressed,
+    });
+  },
+
+  /**
+   * Report the local microphone level (0-1) for speaking indicators
+   */
+  reportAudioLevel: async (
+    sessionId: string,
+    participantId: string,
+    level: number
+  ): Promise<void> => {
+    return invoke('collaboration_report_audio_level', {
+      session_id: sessionId,
+      participant_id: participantId,
+      level,
+    });
+  },
+
   /**
    * Share workflow in session
    */
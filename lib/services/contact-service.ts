@@ -37,6 +37,8 @@ export interface Contact {
   click_count: number;
   bounce_count: number;
   notes: string | null;
+  link_clicks: Record<string, number>;
+  tracking_opt_out: boolean;
 }
 
 export interface ContactList {
@@ -258,6 +260,7 @@ export const ContactService = {
     opened?: boolean;
     clicked?: boolean;
     bounced?: boolean;
+    link?: string;
   }): Promise<void> {
     return invoke('contacts_update_engagement', {
       email,
@@ -265,8 +268,16 @@ export const ContactService = {
       opened: params.opened,
       clicked: params.clicked,
       bounced: params.bounced,
+      link: params.link,
     });
   },
+
+  /**
+   * Opt a contact in or out of email open/click tracking
+   */
+  async setTrackingOptOut(contactId: string, optOut: boolean): Promise<Contact> {
+    return invoke<Contact>('contacts_set_tracking_opt_out', { contactId, optOut });
+  },
 };
 
 // =============================================================================
@@ -4,6 +4,7 @@
 
 import { invoke } from '@tauri-apps/api/core';
 import { logger } from './logger-service';
+import type { PrintOptions } from './cube-web-engine-service';
 
 const log = logger.scope('BrowserEngine');
 
@@ -66,6 +67,81 @@ export interface BrowserConfig {
   userDataDir?: string;
 }
 
+export type FormKind = 'login' | 'address' | 'payment';
+
+export interface CredentialOption {
+  entry_id: string;
+  name: string;
+  username: string;
+  last_used: number | null;
+}
+
+export interface ProfileOption {
+  profile_id: string;
+  name: string;
+  matched_fields: number;
+}
+
+/** Emitted as "cube-form-fill-offer" after navigation */
+export interface FillOffer {
+  tab_id: string;
+  site: string;
+  kind: FormKind;
+  accounts: CredentialOption[];
+  /** More than one stored account: show a chooser */
+  choose_account: boolean;
+  profiles: ProfileOption[];
+  requires_unlock: boolean;
+}
+
+export type SavePromptKind = { type: 'new' } | { type: 'update_password'; entry_id: string };
+
+/** Emitted as "cube-form-save-prompt" when a login form is submitted */
+export interface SavePrompt {
+  id: string;
+  tab_id: string;
+  site: string;
+  url: string;
+  username: string;
+  kind: SavePromptKind;
+  requires_unlock: boolean;
+}
+
+export type SavePromptResponse = 'save' | 'dismiss' | 'never';
+
+export interface SiteFormSettings {
+  offer_fill: boolean;
+  offer_save: boolean;
+  autofill: boolean;
+}
+
+export interface GeolocationCoords {
+  latitude: number;
+  longitude: number;
+  accuracy: number;
+}
+
+export interface LocationOverride {
+  geolocation: GeolocationCoords | null;
+  languages: string[] | null;
+  timezone: string | null;
+}
+
+export interface ScrollItem {
+  key: string;
+  text: string;
+}
+
+export interface LazyLoadResult {
+  scrolls: number;
+  complete: boolean;
+  virtualized: boolean;
+  items: ScrollItem[];
+  loaded_images: string[];
+  pending_images: number;
+  html: string;
+}
+
 // ============================================
 // CUBE Browser Engine Service
 // ============================================
@@ -586,6 +662,58 @@ export class CubeBrowserEngine {
     await invoke('cube_submit_form', { tabId: id, formSelector });
   }
 
+  // ============================================
+  // Password & Autofill Hooks
+  // ============================================
+
+  /**
+   * Re-scan the page for login, address and payment forms and get fill offers
+   */
+  async detectFillableForms(tabId?: string): Promise<FillOffer[]> {
+    this.ensureInitialized();
+    const id = tabId ?? this.activeTabId;
+    if (!id) throw new Error('No active tab');
+
+    return invoke<FillOffer[]>('cube_detect_fillable_forms', { tabId: id });
+  }
+
+  /**
+   * Fill the login form with the stored account chosen from a fill offer
+   */
+  async fillCredential(entryId: string, tabId?: string): Promise<void> {
+    this.ensureInitialized();
+    const id = tabId ?? this.activeTabId;
+    if (!id) throw new Error('No active tab');
+
+    await invoke('cube_fill_credential', { tabId: id, entryId });
+  }
+
+  /**
+   * Fill an address or payment form from an autofill profile
+   */
+  async fillAutofillProfile(kind: FormKind, profileId: string, tabId?: string): Promise<number> {
+    this.ensureInitialized();
+    const id = tabId ?? this.activeTabId;
+    if (!id) throw new Error('No active tab');
+
+    return invoke<number>('cube_fill_autofill_profile', { tabId: id, kind, profileId });
+  }
+
+  /**
+   * Answer a save prompt
+   */
+  async respondToSavePrompt(promptId: string, response: SavePromptResponse): Promise<void> {
+    await invoke('cube_respond_save_prompt', { promptId, response });
+  }
+
+  async getFormSiteSettings(url: string): Promise<SiteFormSettings> {
+    return invoke<SiteFormSettings>('cube_get_form_site_settings', { url });
+  }
+
+  async setFormSiteSettings(url: string, settings: SiteFormSettings): Promise<void> {
+    await invoke('cube_set_form_site_settings', { url, settings });
+  }
+
   // ============================================
   // Data Extraction
   // ============================================
@@ -613,6 +741,50 @@ export class CubeBrowserEngine {
     return invoke<string[][]>('cube_extract_table', { tabId: id, tableSelector });
   }
 
+  /**
+   * Scroll the page until lazy-loaded content stops appearing.
+   * Run before extraction on lazy-loading or infinite-scroll pages.
+   */
+  async scrollToLoadAll(maxScrolls?: number, tabId?: string): Promise<LazyLoadResult> {
+    this.ensureInitialized();
+    const id = tabId ?? this.activeTabId;
+    if (!id) throw new Error('No active tab');
+
+    return invoke<LazyLoadResult>('cube_engine_scroll_to_load_all', { tabId: id, maxScrolls });
+  }
+
+  // ============================================
+  // Location Overrides
+  // ============================================
+
+  /**
+   * Spoof geolocation for a tab, or for every tab when tabId is omitted.
+   * Pass null coords to remove the override.
+   */
+  async setGeolocationOverride(coords: GeolocationCoords | null, tabId?: string): Promise<void> {
+    this.ensureInitialized();
+    return invoke<void>('cube_engine_set_geolocation_override', { tabId, coords });
+  }
+
+  /**
+   * Spoof navigator.languages and timezone for a tab, or for every tab
+   */
+  async setLocaleOverride(languages: string[] | null, timezone: string | null, tabId?: string): Promise<void> {
+    this.ensureInitialized();
+    return invoke<void>('cube_engine_set_locale_override', { tabId, languages, timezone });
+  }
+
+  /**
+   * Get the location override in effect for a tab
+   */
+  async getLocationOverride(tabId?: string): Promise<LocationOverride> {
+    this.ensureInitialized();
+    const id = tabId ?? this.activeTabId;
+    if (!id) throw new Error('No active tab');
+
+    return invoke<LocationOverride>('cube_engine_get_location_override', { tabId: id });
+  }
+
   // ============================================
   // PDF Generation
   // ============================================
@@ -620,32 +792,23 @@ export class CubeBrowserEngine {
   /**
    * Generate PDF from page (returns base64)
    */
-  async printToPdf(tabId?: string): Promise<string> {
+  async printToPdf(tabId?: string, options?: Partial<PrintOptions>): Promise<string> {
     this.ensureInitialized();
     const id = tabId ?? this.activeTabId;
     if (!id) throw new Error('No active tab');
 
-    return invoke<string>('cube_print_to_pdf', { tabId: id });
+    return invoke<string>('cube_print_to_pdf', { tabId: id, options });
   }
 
   /**
-   * Download PDF to file
+   * Download PDF to file (streamed to disk by the backend)
    */
-  async savePdfToFile(filePath: string, tabId?: string): Promise<void> {
-    const pdfBase64 = await this.printToPdf(tabId);
-    
-    // Convert base64 to binary and save
-    const binary = atob(pdfBase64);
-    const bytes = new Uint8Array(binary.length);
-    for (let i = 0; i < binary.length; i++) {
-      bytes[i] = binary.charCodeAt(i);
-    }
+  async savePdfToFile(filePath: string, tabId?: string, options?: Partial<PrintOptions>): Promise<void> {
+    this.ensureInitialized();
+    const id = tabId ?? this.activeTabId;
+    if (!id) throw new Error('No active tab');
 
-    // Use Tauri filesystem API to write
-    await invoke('write_file_binary', {
-      path: filePath,
-      data: Array.from(bytes),
-    });
+    await invoke<string>('cube_print_to_pdf', { tabId: id, options, outputPath: filePath });
   }
 
   // ============================================
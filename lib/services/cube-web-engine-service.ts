@@ -49,6 +49,77 @@ export interface PageContent {
   domReady: boolean;
 }
 
+export interface HttpAuthPrompt {
+  url: string;
+  origin: string;
+  realm: string | null;
+  scheme: 'basic' | 'digest' | 'ntlm' | 'negotiate';
+  /** The credentials that were tried got rejected */
+  failed: boolean;
+}
+
+export interface DnsResolution {
+  host: string;
+  /** Connection order, first responding address family first */
+  addresses: string[];
+  resolver: string;
+  fromCache: boolean;
+  ttl: number;
+}
+
+export interface DohConfig {
+  enabled: boolean;
+  primary: string;
+  secondary: string | null;
+  tunnelResolvers: string[];
+}
+
+export type MediaFeature =
+  | 'prefers-reduced-motion'
+  | 'prefers-color-scheme'
+  | 'forced-colors'
+  | 'prefers-contrast';
+
+export interface MediaEmulation {
+  /** Emulated value per feature, e.g. { 'prefers-color-scheme': 'dark' } */
+  overrides: Partial<Record<MediaFeature, string>>;
+}
+
+export interface DeviceProfile {
+  id: string;
+  name: string;
+  /** CSS pixels in portrait orientation */
+  width: number;
+  height: number;
+  devicePixelRatio: number;
+  touch: boolean;
+  mobile: boolean;
+  userAgent: string;
+}
+
+export type Orientation = 'portrait' | 'landscape';
+
+export interface DeviceEmulation {
+  device: DeviceProfile;
+  orientation: Orientation;
+}
+
+export interface Viewport {
+  width: number;
+  height: number;
+  devicePixelRatio: number;
+}
+
+export interface Lqip {
+  url: string;
+  /** BlurHash of the image */
+  blurhash: string;
+  /** Tiny PNG as a data: URL */
+  dataUrl: string;
+  width: number;
+  height: number;
+}
+
 export interface CubeWebEngineConfig {
   javascriptEnabled: boolean;
   webglEnabled: boolean;
@@ -99,6 +170,15 @@ export interface PrintOptions {
   marginBottom: number;
   marginLeft: number;
   marginRight: number;
+  /** Named paper size ("A4", "Letter", ...) overriding paperWidth/paperHeight */
+  paperFormat?: string;
+  /** 1-based pages to print, e.g. "1-3, 5, 8-" */
+  pageRanges?: string;
+  /** Header HTML; {{page}}, {{pages}}, {{date}}, {{title}} and {{url}} are filled in per page */
+  headerTemplate?: string;
+  footerTemplate?: string;
+  /** Let a CSS @page size rule override the paper size */
+  preferCssPageSize?: boolean;
 }
 
 export interface DomCommand {
@@ -204,6 +284,32 @@ export async function fetchPage(url: string): Promise<PageContent> {
   return await invoke<PageContent>('cube_engine_fetch_page', { url });
 }
 
+/**
+ * Answer an HTTP auth prompt; credentials are cached for the session
+ */
+export async function provideHttpCredentials(
+  origin: string,
+  realm: string | null,
+  username: string,
+  password: string,
+  domain?: string
+): Promise<void> {
+  await invoke('cube_engine_provide_http_credentials', {
+    origin,
+    realm,
+    username,
+    password,
+    domain,
+  });
+}
+
+/**
+ * Forget all cached HTTP auth credentials
+ */
+export async function clearHttpAuth(): Promise<void> {
+  await invoke('cube_engine_clear_http_auth');
+}
+
 /**
  * Go back in history
  */
@@ -318,6 +424,100 @@ export async function setUserAgent(userAgent: string): Promise<void> {
   await invoke('cube_engine_set_user_agent', { userAgent });
 }
 
+/**
+ * Resolve a host through the engine's DNS-over-HTTPS resolver
+ */
+export async function resolveHost(host: string): Promise<DnsResolution> {
+  return invoke<DnsResolution>('cube_engine_resolve', { host });
+}
+
+/**
+ * Set the DoH provider by name (e.g. "Quad9") or URL, with an optional fallback
+ */
+export async function setDohProvider(provider: string, secondary?: string): Promise<DohConfig> {
+  return invoke<DohConfig>('cube_engine_set_doh', { provider, secondary });
+}
+
+// ============================================
+// Media Emulation
+// ============================================
+
+/**
+ * Emulate a media feature in a tab; pass null to clear the override
+ */
+export async function setMediaFeature(
+  tabId: string,
+  feature: MediaFeature,
+  value: string | null
+): Promise<MediaEmulation> {
+  return invoke<MediaEmulation>('cube_engine_set_media_feature', { tabId, feature, value });
+}
+
+/**
+ * Get the media features emulated in a tab, including the user-wide reduced motion
+ */
+export async function getMediaFeatures(tabId: string): Promise<MediaEmulation> {
+  return invoke<MediaEmulation>('cube_engine_get_media_features', { tabId });
+}
+
+/**
+ * Apply prefers-reduced-motion to every tab
+ */
+export async function setReducedMotion(enabled: boolean): Promise<void> {
+  await invoke('cube_engine_set_reduced_motion', { enabled });
+}
+
+// ============================================
+// Device Emulation
+// ============================================
+
+/**
+ * Get the built-in phone and tablet profiles
+ */
+export async function getDevicePresets(): Promise<DeviceProfile[]> {
+  return invoke<DeviceProfile[]>('cube_engine_get_device_presets');
+}
+
+/**
+ * Emulate a device in a tab, by preset id or custom profile; pass null to restore the desktop view
+ */
+export async function setDevice(
+  tabId: string,
+  device: string | DeviceProfile | null,
+  orientation?: Orientation
+): Promise<DeviceEmulation | null> {
+  return invoke<DeviceEmulation | null>('cube_engine_set_device', {
+    tabId,
+    device,
+    orientation: orientation ?? null,
+  });
+}
+
+/**
+ * Rotate the emulated device between portrait and landscape
+ */
+export async function toggleOrientation(tabId: string): Promise<DeviceEmulation> {
+  return invoke<DeviceEmulation>('cube_engine_toggle_orientation', { tabId });
+}
+
+/**
+ * Get the device emulated in a tab
+ */
+export async function getDevice(tabId: string): Promise<DeviceEmulation | null> {
+  return invoke<DeviceEmulation | null>('cube_engine_get_device', { tabId });
+}
+
+// ============================================
+// Image Placeholders
+// ============================================
+
+/**
+ * Generate a BlurHash and tiny base64 thumbnail to show while an image lazy-loads
+ */
+export async function generateLqip(imageUrl: string): Promise<Lqip> {
+  return invoke<Lqip>('cube_engine_generate_lqip', { imageUrl });
+}
+
 // ============================================
 // Zoom & Display
 // ============================================
@@ -393,6 +593,8 @@ export type CubeWebEventType =
   | 'cube-engine-reload'
   | 'cube-engine-stopped'
   | 'cube-engine-zoom-changed'
+  | 'cube-engine-media-emulation-changed'
+  | 'cube-engine-device-changed'
   | 'cube-engine-execute-script'
   | 'cube-engine-dom-command'
   | 'cube-engine-screenshot-request'
@@ -489,6 +691,17 @@ export async function onNavigationFailed(
   );
 }
 
+/**
+ * Listen for pages that need HTTP authentication
+ */
+export async function onAuthRequired(
+  callback: (prompt: HttpAuthPrompt) => void
+): Promise<UnlistenFn> {
+  return await listen<HttpAuthPrompt>('cube-engine-auth-required', (event) => {
+    callback(event.payload);
+  });
+}
+
 /**
  * Listen for all tabs closed events
  */
@@ -714,6 +927,8 @@ export interface CubeWebEngineService {
   navigate: typeof navigate;
   fetchUrl: typeof fetchUrl;
   fetchPage: typeof fetchPage;
+  provideHttpCredentials: typeof provideHttpCredentials;
+  clearHttpAuth: typeof clearHttpAuth;
   goBack: typeof goBack;
   goForward: typeof goForward;
   reload: typeof reload;
@@ -730,6 +945,22 @@ export interface CubeWebEngineService {
   setConfig: typeof setConfig;
   setHeaders: typeof setHeaders;
   setUserAgent: typeof setUserAgent;
+  resolveHost: typeof resolveHost;
+  setDohProvider: typeof setDohProvider;
+  
+  // Media emulation
+  setMediaFeature: typeof setMediaFeature;
+  getMediaFeatures: typeof getMediaFeatures;
+  setReducedMotion: typeof setReducedMotion;
+  
+  // Device emulation
+  getDevicePresets: typeof getDevicePresets;
+  setDevice: typeof setDevice;
+  toggleOrientation: typeof toggleOrientation;
+  getDevice: typeof getDevice;
+  
+  // Image placeholders
+  generateLqip: typeof generateLqip;
   
   // Zoom
   setZoom: typeof setZoom;
@@ -751,6 +982,7 @@ export interface CubeWebEngineService {
   onNavigationStarted: typeof onNavigationStarted;
   onNavigationCompleted: typeof onNavigationCompleted;
   onNavigationFailed: typeof onNavigationFailed;
+  onAuthRequired: typeof onAuthRequired;
   onAllTabsClosed: typeof onAllTabsClosed;
   
   // Rendering utilities
@@ -776,6 +1008,8 @@ export function createCubeWebEngineService(): CubeWebEngineService {
     navigate,
     fetchUrl,
     fetchPage,
+    provideHttpCredentials,
+    clearHttpAuth,
     goBack,
     goForward,
     reload,
@@ -788,6 +1022,16 @@ export function createCubeWebEngineService(): CubeWebEngineService {
     setConfig,
     setHeaders,
     setUserAgent,
+    resolveHost,
+    setDohProvider,
+    setMediaFeature,
+    getMediaFeatures,
+    setReducedMotion,
+    getDevicePresets,
+    setDevice,
+    toggleOrientation,
+    getDevice,
+    generateLqip,
     setZoom,
     getZoom,
     getHistory,
@@ -801,6 +1045,7 @@ export function createCubeWebEngineService(): CubeWebEngineService {
     onNavigationStarted,
     onNavigationCompleted,
     onNavigationFailed,
+    onAuthRequired,
     onAllTabsClosed,
     createRenderFrame,
     renderContent,
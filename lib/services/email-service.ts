@@ -80,6 +80,8 @@ export interface SendEmailParams {
   template_variables?: Record<string, string>;
   tracking_id?: string;
   campaign_id?: string;
+  /** Open/click tracking for this send (defaults to on) */
+  track?: boolean;
 }
 
 export interface EmailSendResult {
@@ -117,6 +119,25 @@ export interface EmailServiceStatus {
   emails_sent_this_hour: number;
 }
 
+export interface EmailTrackingConfig {
+  enabled: boolean;
+  base_url: string;
+  min_open_delay_seconds: number;
+}
+
+export interface TrackedSend {
+  token: string;
+  recipient: string;
+  tracking_id: string | null;
+  campaign_id: string | null;
+  links: string[];
+  sent_at: string;
+  opens: number;
+  ignored_opens: number;
+  first_opened_at: string | null;
+  link_clicks: Record<string, number>;
+}
+
 // ============================================================================
 // Configuration Service
 // ============================================================================
@@ -278,6 +299,7 @@ export const EmailSendService = {
     fromEmail?: string;
     fromName?: string;
     templateVariables?: Record<string, string>;
+    track?: boolean;
   }): Promise<EmailBatchResult> {
     return invoke<EmailBatchResult>('email_send_campaign', {
       campaignId: params.campaignId,
@@ -288,10 +310,38 @@ export const EmailSendService = {
       fromEmail: params.fromEmail || null,
       fromName: params.fromName || null,
       templateVariables: params.templateVariables || null,
+      track: params.track ?? null,
     });
   },
 };
 
+// ============================================================================
+// Tracking Service
+// ============================================================================
+
+export const EmailTrackingService = {
+  /**
+   * Get open/click tracking configuration
+   */
+  async getConfig(): Promise<EmailTrackingConfig> {
+    return invoke<EmailTrackingConfig>('email_tracking_get_config');
+  },
+
+  /**
+   * Update open/click tracking configuration
+   */
+  async setConfig(config: EmailTrackingConfig): Promise<EmailTrackingConfig> {
+    return invoke<EmailTrackingConfig>('email_tracking_set_config', { config });
+  },
+
+  /**
+   * Get tracked sends, optionally for one campaign
+   */
+  async getSends(campaignId?: string): Promise<TrackedSend[]> {
+    return invoke<TrackedSend[]>('email_tracking_get_sends', { campaignId: campaignId || null });
+  },
+};
+
 // ============================================================================
 // Status Service
 // ============================================================================
@@ -321,6 +371,7 @@ export const EmailService = {
   Test: EmailTestService,
   Send: EmailSendService,
   Status: EmailStatusService,
+  Tracking: EmailTrackingService,
 };
 
 export default EmailService;
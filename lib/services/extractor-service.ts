@@ -94,6 +94,14 @@ export interface PageAnalysis {
   }[];
 }
 
+export interface GeneratedSchema {
+  schema: ExtractionSchema;
+  generator: 'ai' | 'heuristic';
+  /** Values each field produced on the page */
+  preview: ExtractedData;
+  warnings: string[];
+}
+
 // ============================================================================
 // Schema Service
 // ============================================================================
@@ -208,10 +216,12 @@ export const AISuggestionService = {
   },
 
   /**
-   * Generate schema from natural language description
+   * Generate a ready-to-run schema from a natural language description.
+   * Uses the AI backend when an API key is configured, heuristics otherwise;
+   * every selector is checked against the loaded page.
    */
-  generateSchema: async (description: string, url?: string): Promise<ExtractionSchema> => {
-    return invoke<ExtractionSchema>('extractor_generate_schema', { description, url });
+  generateSchema: async (url: string, description: string): Promise<GeneratedSchema> => {
+    return invoke<GeneratedSchema>('extractor_generate_schema_from_prompt', { url, description });
   },
 
   /**
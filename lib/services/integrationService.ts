@@ -120,6 +120,29 @@ export interface DiscordEmbedField {
   inline: boolean;
 }
 
+export interface QueuedOutboundMessage {
+  id: string;
+  payload: Record<string, unknown> & { kind: 'whats_app_text' | 'monday_create_item' };
+  enqueued_at: number;
+  attempts: number;
+  last_error?: string | null;
+}
+
+export interface OutboundQueueStatus {
+  integration: 'monday' | 'whatsapp';
+  depth: number;
+  failed: number;
+  sent_total: number;
+  window_usage: number;
+  window_budget: number;
+  unique_recipients_today: number;
+  next_send_in_ms?: number | null;
+  paused_until?: number | null;
+  backoff_level: number;
+  oldest_enqueued_at?: number | null;
+  failed_messages: QueuedOutboundMessage[];
+}
+
 // ============================================================================
 // Integration Service Class
 // ============================================================================
@@ -378,6 +401,21 @@ class IntegrationService {
       throw new Error(`Failed to get history: ${error instanceof Error ? error.message : 'Unknown error'}`);
     }
   }
+
+  // ==========================================================================
+  // Outbound Queues
+  // ==========================================================================
+
+  /**
+   * Get rate-limited outbound queue status for Monday.com or WhatsApp
+   */
+  async getQueueStatus(integration: 'monday' | 'whatsapp'): Promise<OutboundQueueStatus> {
+    try {
+      return await invoke<OutboundQueueStatus>('integration_get_queue_status', { integration });
+    } catch (error) {
+      throw new Error(`Failed to get queue status: ${error instanceof Error ? error.message : 'Unknown error'}`);
+    }
+  }
 }
 
 // ============================================================================
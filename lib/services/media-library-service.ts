@@ -77,6 +77,20 @@ interface BackendMediaStats {
   most_played: BackendMediaItem[];
 }
 
+interface BackendPlaybackSettings {
+  gapless: boolean;
+  crossfade_seconds: number;
+}
+
+interface BackendTrackTransition {
+  from_media_id: string;
+  to_media_id: string;
+  prebuffer_at_seconds?: number | null;
+  start_next_at_seconds: number;
+  overlap_seconds: number;
+  count_play_at_seconds: number;
+}
+
 interface BackendMediaFilter {
   media_type?: string;
   playlist_id?: string;
@@ -190,11 +204,34 @@ const BackendMediaAPI = {
       return null;
     }
   },
+
+  async getPlaybackSettings(): Promise<BackendPlaybackSettings> {
+    return await invoke<BackendPlaybackSettings>('media_get_playback_settings');
+  },
+
+  async setCrossfade(seconds: number): Promise<BackendPlaybackSettings> {
+    return await invoke<BackendPlaybackSettings>('media_set_crossfade', { seconds });
+  },
+
+  async setGapless(enabled: boolean): Promise<BackendPlaybackSettings> {
+    return await invoke<BackendPlaybackSettings>('media_set_gapless', { enabled });
+  },
+
+  async planTransition(fromMediaId: string, toMediaId: string): Promise<BackendTrackTransition> {
+    return await invoke<BackendTrackTransition>('media_plan_transition', { fromMediaId, toMediaId });
+  },
 };
 
 // Export backend API
 export { BackendMediaAPI };
-export type { BackendMediaItem, BackendPlaylist, BackendMediaStats, BackendMediaFilter };
+export type {
+  BackendMediaItem,
+  BackendPlaylist,
+  BackendMediaStats,
+  BackendMediaFilter,
+  BackendPlaybackSettings,
+  BackendTrackTransition,
+};
 
 // ============================================================================
 // Types and Interfaces
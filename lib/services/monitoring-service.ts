@@ -94,6 +94,21 @@ export interface ExecutionMetrics {
     network_kb: number;
     disk_io_kb: number;
   };
+  node_metrics: NodeMetric[];
+}
+
+/** One node run; a node with a retry policy gets one entry per attempt */
+export interface NodeMetric {
+  node_id: string;
+  node_type: string;
+  start_time: string;
+  end_time?: string;
+  duration_ms: number;
+  success: boolean;
+  error?: string;
+  attempt: number;
+  /** A failed attempt that was followed by another one */
+  retried: boolean;
 }
 
 // ============================================
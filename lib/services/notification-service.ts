@@ -693,6 +693,74 @@ export const NotificationService = {
   },
 };
 
+// ============================================================================
+// Notification Center
+// ============================================================================
+
+export interface InAppNotification {
+  id: string;
+  notification_id: string;
+  user_id: string;
+  title: string;
+  body: string;
+  action_type: string | null;
+  action_data: Record<string, unknown> | null;
+  read: boolean;
+  read_at: number | null;
+  dismissed: boolean;
+  dismissed_at: number | null;
+  created_at: number;
+}
+
+/** Similar notifications collapsed into one entry ("3 new messages") */
+export interface NotificationGroup {
+  group_key: string;
+  category: string;
+  notification_type: string;
+  summary: string;
+  count: number;
+  unread_count: number;
+  latest_at: number;
+  /** Newest first */
+  notifications: InAppNotification[];
+}
+
+export interface NotificationCenter {
+  groups: NotificationGroup[];
+  unread_by_category: Record<string, number>;
+  total_unread: number;
+}
+
+/** Read state of one notification as synced between devices */
+export interface ReadStateChange {
+  notification_id: string;
+  read_at: number | null;
+  dismissed_at: number | null;
+}
+
+export const NotificationCenterService = {
+  /**
+   * Grouped notifications, ordered by latest activity, with unread counts per category
+   */
+  getGrouped: async (userId: string, limit?: number): Promise<NotificationCenter> => {
+    return invoke<NotificationCenter>('notification_get_grouped', { userId, limit });
+  },
+
+  /**
+   * Mark a category read here and queue the change for the user's other devices
+   */
+  markCategoryRead: async (userId: string, category: string): Promise<number> => {
+    return invoke<number>('notification_mark_category_read', { userId, category });
+  },
+
+  /**
+   * Apply read state received from another device
+   */
+  applySyncedReadState: async (changes: ReadStateChange[]): Promise<number> => {
+    return invoke<number>('notification_apply_synced_read_state', { changes });
+  },
+};
+
 // ============================================================================
 // Template Service
 // ============================================================================
@@ -1136,6 +1204,7 @@ export const EmailNotificationService = {
 
 export const NotificationServices = {
   Notification: NotificationService,
+  Center: NotificationCenterService,
   Template: NotificationTemplateService,
   Preferences: NotificationPreferencesService,
   Queue: NotificationQueueService,
@@ -78,6 +78,25 @@ export interface MasterPasswordConfig {
   is_set: boolean;
   created_at: number;
   updated_at: number;
+  kdf: KdfParams;
+}
+
+export type KdfAlgorithm = 'pbkdf2_sha256' | 'argon2id';
+
+export interface KdfParams {
+  algorithm: KdfAlgorithm;
+  iterations: number;
+  /** Argon2id only */
+  memory_kib: number;
+  /** Argon2id only */
+  parallelism: number;
+}
+
+export interface KdfStatus {
+  current: KdfParams;
+  recommended: KdfParams;
+  below_baseline: boolean;
+  warnings: string[];
 }
 
 export interface SavePasswordParams {
@@ -105,6 +124,65 @@ export interface UpdatePasswordParams {
   favorite?: boolean;
 }
 
+export interface SharePasswordOptions {
+  expires_in_hours?: number;
+  max_views?: number;
+  password?: string;
+}
+
+export interface PasswordShareLink {
+  token: string;
+  key: string;
+  url: string;
+  expires_at: number;
+  max_views: number;
+  password_protected: boolean;
+}
+
+export interface SharedPasswordPayload {
+  name: string;
+  username: string;
+  password: string;
+  url?: string;
+  notes?: string;
+}
+
+export type SharedPasswordRetrieval =
+  | { status: 'available'; payload: SharedPasswordPayload; views_remaining: number; expires_at: number }
+  | { status: 'password_required' }
+  | { status: 'gone'; reason: 'not_found' | 'expired' | 'views_exhausted' };
+
+export interface TotpCode {
+  code: string;
+  /** Seconds until the code changes, for the countdown ring */
+  seconds_remaining: number;
+  period: number;
+  algorithm: 'sha1' | 'sha256' | 'sha512';
+  issuer?: string;
+  account?: string;
+}
+
+export type EmergencyAccessLevel = 'view' | 'takeover';
+
+export type EmergencyAccessState =
+  | { state: 'idle' }
+  | { state: 'waiting'; requested_at: number; grants_at: number }
+  | { state: 'granted'; granted_at: number }
+  | { state: 'denied'; denied_at: number };
+
+export type EmergencyAccessStatus = EmergencyAccessState & {
+  id: string;
+  contact_email: string;
+  access_level: EmergencyAccessLevel;
+  wait_days: number;
+  export_updated_at: number;
+};
+
+export interface EmergencyVaultExport {
+  entries: SharedPasswordPayload[];
+  exported_at: number;
+}
+
 // ============================================
 // Master Password Service
 // ============================================
@@ -113,8 +191,8 @@ export const MasterPasswordService = {
   /**
    * Setup initial master password
    */
-  async setup(masterPassword: string): Promise<void> {
-    return invoke<void>('setup_master_password', { masterPassword });
+  async setup(masterPassword: string, kdf?: KdfParams): Promise<void> {
+    return invoke<void>('setup_master_password', { masterPassword, kdf: kdf ?? null });
   },
 
   /**
@@ -132,10 +210,40 @@ export const MasterPasswordService = {
   },
 
   /**
-   * Change master password (re-encrypts all passwords)
+   * Change master password (re-encrypts all passwords). Pass `kdf` to switch
+   * key derivation too, e.g. to upgrade a PBKDF2 vault to Argon2id.
    */
-  async change(oldPassword: string, newPassword: string): Promise<void> {
-    return invoke<void>('change_master_password', { oldPassword, newPassword });
+  async change(oldPassword: string, newPassword: string, kdf?: KdfParams): Promise<void> {
+    return invoke<void>('change_master_password', { oldPassword, newPassword, kdf: kdf ?? null });
+  },
+
+  /**
+   * Current KDF settings and any warnings against the security baseline
+   */
+  async getKdfStatus(): Promise<KdfStatus> {
+    return invoke<KdfStatus>('get_master_password_kdf_status');
+  },
+
+  /**
+   * Unlock the vault for this session so the browser can offer and save
+   * credentials. Resolves false when the master password is wrong.
+   */
+  async unlock(masterPassword: string): Promise<boolean> {
+    return invoke<boolean>('unlock_password_vault', { masterPassword });
+  },
+
+  /**
+   * Lock the vault, forgetting the session's master password
+   */
+  async lock(): Promise<void> {
+    return invoke<void>('lock_password_vault');
+  },
+
+  /**
+   * Whether the vault is unlocked for this session
+   */
+  async isUnlocked(): Promise<boolean> {
+    return invoke<boolean>('is_password_vault_unlocked');
   },
 };
 
@@ -411,6 +519,138 @@ export const PasswordGeneratorService = {
   },
 };
 
+// ============================================
+// Password Sharing Service
+// ============================================
+
+export const PasswordSharingService = {
+  /**
+   * Create a one-time encrypted link for a vault entry (vault must be unlocked)
+   * Backend: share_password
+   */
+  async share(entryId: string, options?: SharePasswordOptions): Promise<PasswordShareLink> {
+    return invoke<PasswordShareLink>('share_password', { entryId, options });
+  },
+
+  /**
+   * Open a shared link; `key` is the URL fragment
+   * Backend: retrieve_shared_password
+   */
+  async retrieve(token: string, key: string, password?: string): Promise<SharedPasswordRetrieval> {
+    return invoke<SharedPasswordRetrieval>('retrieve_shared_password', { token, key, password });
+  },
+
+  /**
+   * Split a share URL into its token and fragment key
+   */
+  parseLink(url: string): { token: string; key: string } | null {
+    const match = url.match(/\/send\/([^/#?]+)#(.+)$/);
+    return match ? { token: match[1], key: match[2] } : null;
+  },
+};
+
+// ============================================
+// TOTP Service
+// ============================================
+
+export const TotpService = {
+  /**
+   * Store an otpauth:// URI or base32 secret with an entry (vault must be
+   * unlocked). Resolves with the current code.
+   */
+  async save(entryId: string, secret: string): Promise<TotpCode> {
+    return invoke<TotpCode>('save_totp_secret', { entryId, secret });
+  },
+
+  /**
+   * Current code and seconds remaining, or null when the entry has no secret
+   */
+  async getCode(entryId: string): Promise<TotpCode | null> {
+    return invoke<TotpCode | null>('get_totp_code', { entryId });
+  },
+
+  async remove(entryId: string): Promise<void> {
+    return invoke<void>('delete_totp_secret', { entryId });
+  },
+};
+
+// ============================================
+// Emergency Access Service
+// ============================================
+
+export const EmergencyAccessService = {
+  /**
+   * This device's public key, to hand to owners naming you as a contact
+   * Backend: get_emergency_public_key
+   */
+  async getPublicKey(): Promise<string> {
+    return invoke<string>('get_emergency_public_key');
+  },
+
+  /**
+   * Name a trusted contact (vault must be unlocked); re-run to refresh the export
+   * Backend: grant_emergency_contact
+   */
+  async grant(
+    contactEmail: string,
+    contactPublicKey: string,
+    accessLevel: EmergencyAccessLevel,
+    waitDays: number
+  ): Promise<EmergencyAccessStatus> {
+    return invoke<EmergencyAccessStatus>('grant_emergency_contact', {
+      contactEmail,
+      contactPublicKey,
+      accessLevel,
+      waitDays,
+    });
+  },
+
+  /**
+   * Request access as the contact, starting the waiting period
+   * Backend: request_emergency_access
+   */
+  async request(grantId: string): Promise<EmergencyAccessStatus> {
+    return invoke<EmergencyAccessStatus>('request_emergency_access', { grantId });
+  },
+
+  /**
+   * Deny a pending request as the owner
+   * Backend: deny_emergency_access
+   */
+  async deny(grantId: string): Promise<EmergencyAccessStatus> {
+    return invoke<EmergencyAccessStatus>('deny_emergency_access', { grantId });
+  },
+
+  /**
+   * Backend: get_emergency_access_status
+   */
+  async getStatus(grantId: string): Promise<EmergencyAccessStatus> {
+    return invoke<EmergencyAccessStatus>('get_emergency_access_status', { grantId });
+  },
+
+  /**
+   * Backend: list_emergency_contacts
+   */
+  async list(): Promise<EmergencyAccessStatus[]> {
+    return invoke<EmergencyAccessStatus[]>('list_emergency_contacts');
+  },
+
+  /**
+   * Backend: revoke_emergency_contact
+   */
+  async revoke(grantId: string): Promise<void> {
+    return invoke('revoke_emergency_contact', { grantId });
+  },
+
+  /**
+   * Open a granted export; `importEntries` copies it into this vault (takeover only)
+   * Backend: open_emergency_vault
+   */
+  async open(grantId: string, importEntries = false): Promise<EmergencyVaultExport> {
+    return invoke<EmergencyVaultExport>('open_emergency_vault', { grantId, import: importEntries });
+  },
+};
+
 // ============================================
 // Combined Password Service Export
 // ============================================
@@ -419,6 +659,9 @@ export const PasswordService = {
   Master: MasterPasswordService,
   Vault: PasswordVaultService,
   Generator: PasswordGeneratorService,
+  Sharing: PasswordSharingService,
+  Totp: TotpService,
+  EmergencyAccess: EmergencyAccessService,
 };
 
 export default PasswordService;
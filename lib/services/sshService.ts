@@ -33,6 +33,7 @@ export interface SSHConfig {
   password?: string;
   privateKeyPath?: string;
   passphrase?: string;
+  jumpHost?: string; // ProxyJump: comma-separated [user@]host[:port] or config names
   lastConnected?: number; // Unix timestamp
 }
 
@@ -157,6 +158,30 @@ export async function getConfigs(): Promise<SSHConfig[]> {
   }
 }
 
+/**
+ * Import hosts from an OpenSSH client config
+ * 
+ * Host blocks become configs, including their ProxyJump chain. Hosts imported
+ * before are updated rather than duplicated.
+ * 
+ * @param path - Config file to read (default: ~/.ssh/config)
+ * @returns Promise resolving to the imported configs
+ * @throws Error if the file can't be read
+ * 
+ * @example
+ * ```typescript
+ * const imported = await sshService.importConfig();
+ * log.debug(`Imported ${imported.length} hosts`);
+ * ```
+ */
+export async function importConfig(path?: string): Promise<SSHConfig[]> {
+  try {
+    return await invoke<SSHConfig[]>('ssh_import_config', { path: path ?? null });
+  } catch (error) {
+    throw new Error(`Failed to import SSH config: ${error}`);
+  }
+}
+
 /**
  * Delete SSH configuration
  * 
@@ -532,6 +557,7 @@ export async function quickConnect(configName: string): Promise<string> {
 export const sshService = {
   createConfig,
   getConfigs,
+  importConfig,
   deleteConfig,
   connect,
   executeCommand,
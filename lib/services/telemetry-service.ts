@@ -74,9 +74,20 @@ interface BackendLogEntry {
   workflow_id?: string;
   execution_id?: string;
   node_id?: string;
+  source?: string;
   metadata: Record<string, string>;
 }
 
+type BackendFieldCondition =
+  | { op: 'eq'; field: string; value: string }
+  | { op: 'range'; field: string; min?: number; max?: number };
+
+interface BackendLogAggregateBucket {
+  key: string | null;
+  count: number;
+  value: number;
+}
+
 interface BackendLogFilter {
   level?: string;
   workflow_id?: string;
@@ -84,7 +95,10 @@ interface BackendLogFilter {
   start_time?: string;
   end_time?: string;
   search?: string;
+  keyword?: string;
   limit?: number;
+  source?: string;
+  fields?: BackendFieldCondition[];
 }
 
 interface BackendLogStats {
@@ -177,10 +191,12 @@ const BackendMonitoringAPI = {
     message: string,
     workflowId?: string,
     executionId?: string,
-    nodeId?: string
+    nodeId?: string,
+    source?: string,
+    fields?: Record<string, string>
   ): Promise<string> {
     try {
-      return await invoke<string>('logs_add', { level, message, workflowId, executionId, nodeId });
+      return await invoke<string>('logs_add', { level, message, workflowId, executionId, nodeId, source, fields });
     } catch (error) {
       log.warn('Backend logs_add failed:', error);
       return '';
@@ -196,6 +212,28 @@ const BackendMonitoringAPI = {
     }
   },
 
+  async queryLogs(filter: BackendLogFilter): Promise<BackendLogEntry[]> {
+    try {
+      return await invoke<BackendLogEntry[]>('logs_query', { filter });
+    } catch (error) {
+      log.warn('Backend logs_query failed:', error);
+      return [];
+    }
+  },
+
+  async aggregateLogs(
+    groupBy: string,
+    metric: 'count' | 'rate_per_minute',
+    filter?: BackendLogFilter
+  ): Promise<BackendLogAggregateBucket[]> {
+    try {
+      return await invoke<BackendLogAggregateBucket[]>('logs_aggregate', { groupBy, metric, filter });
+    } catch (error) {
+      log.warn('Backend logs_aggregate failed:', error);
+      return [];
+    }
+  },
+
   async getRecentLogs(count: number): Promise<BackendLogEntry[]> {
     try {
       return await invoke<BackendLogEntry[]>('logs_get_recent', { count });
@@ -78,6 +78,29 @@ export interface ConnectionLog {
   message: string;
 }
 
+/**
+ * Handshake latency of a server
+ */
+export interface LatencyMeasurement {
+  serverId: string;
+  reachable: boolean;
+  latencyMs: number | null;
+  measuredAt: number; // Unix timestamp
+  cached: boolean;
+}
+
+/**
+ * In-tunnel throughput report
+ */
+export interface SpeedTestResult {
+  serverId: string | null;
+  downloadMbps: number;
+  uploadMbps: number;
+  latencyMs: number;
+  jitterMs: number;
+  testedAt: number; // Unix timestamp
+}
+
 // ============================================================================
 // VPN SERVICE API
 // ============================================================================
@@ -344,6 +367,61 @@ export async function refreshServers(): Promise<VPNServer[]> {
   }
 }
 
+/**
+ * Measure handshake latency to servers (all servers when no IDs are given)
+ * 
+ * @param serverIds - Servers to test
+ * @returns Promise resolving to one measurement per server
+ * @throws Error if a server ID is unknown
+ * 
+ * @example
+ * ```typescript
+ * const results = await vpnService.testServerLatency(['us-ny-01', 'uk-ldn-01']);
+ * ```
+ */
+export async function testServerLatency(serverIds: string[] = []): Promise<LatencyMeasurement[]> {
+  try {
+    return await invoke<LatencyMeasurement[]>('vpn_test_server_latency', { serverIds });
+  } catch (error) {
+    throw new Error(`Failed to test VPN server latency: ${error}`);
+  }
+}
+
+/**
+ * Connect to the lowest-latency reachable server
+ * 
+ * @param country - Optional country to pick from
+ * @param preferLeastLoaded - Prefer less loaded servers among near-equal latencies
+ * @returns Promise resolving to new connection status
+ * @throws Error if no server is reachable or connection fails
+ * 
+ * @example
+ * ```typescript
+ * const status = await vpnService.connectFastest('Germany');
+ * ```
+ */
+export async function connectFastest(country?: string, preferLeastLoaded = false): Promise<VPNStatus> {
+  try {
+    return await invoke<VPNStatus>('vpn_connect_fastest', { country: country ?? null, preferLeastLoaded });
+  } catch (error) {
+    throw new Error(`Failed to connect to fastest VPN server: ${error}`);
+  }
+}
+
+/**
+ * Measure download/upload speed and latency through the tunnel
+ * 
+ * @returns Promise resolving to speed test result
+ * @throws Error if not connected or the test fails
+ */
+export async function speedTest(): Promise<SpeedTestResult> {
+  try {
+    return await invoke<SpeedTestResult>('vpn_speed_test');
+  } catch (error) {
+    throw new Error(`VPN speed test failed: ${error}`);
+  }
+}
+
 // ============================================================================
 // CONVENIENCE HELPERS
 // ============================================================================
@@ -430,6 +508,9 @@ export const vpnService = {
   getCurrentIp,
   getLogs,
   refreshServers,
+  testServerLatency,
+  connectFastest,
+  speedTest,
   getFastestServers,
   getServersByCountry,
   isConnected,
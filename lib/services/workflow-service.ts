@@ -26,6 +26,15 @@ export interface WorkflowEdge {
   id: string;
   source: string;
   target: string;
+  /** Branch handle on the source node, e.g. `approved` / `rejected` for approval nodes */
+  sourceHandle?: string;
+}
+
+export interface WorkflowParam {
+  name: string;
+  required?: boolean;
+  default?: unknown;
+  description?: string;
 }
 
 export interface Workflow {
@@ -33,6 +42,10 @@ export interface Workflow {
   name: string;
   nodes: WorkflowNode[];
   edges: WorkflowEdge[];
+  /** Parameters accepted when invoked from a `subWorkflow` node */
+  inputs?: WorkflowParam[];
+  /** Variables returned to the calling workflow */
+  outputs?: WorkflowParam[];
   createdAt: string;
   updatedAt: string;
 }
@@ -44,6 +57,29 @@ export interface NodeExecutionResult {
   error?: string;
 }
 
+export type ApprovalDecision = 'approved' | 'rejected';
+
+export interface PendingApproval {
+  execution_id: string;
+  node_id: string;
+  approver: string | null;
+  message: string;
+  requested_at: string;
+  expires_at: string | null;
+  default_action: ApprovalDecision;
+  workflow: Workflow;
+}
+
+export interface WorkflowRunResult {
+  workflow_id: string;
+  execution_id: string;
+  outputs: Record<string, unknown>;
+  variables: Record<string, unknown>;
+  node_results: [string, { success: boolean; data: unknown; error: string | null }][];
+  /** Set when the run is waiting at an approval node */
+  pending_approval: PendingApproval | null;
+}
+
 // ============================================
 // Types - Scheduler
 // ============================================
@@ -54,6 +90,8 @@ export interface ScheduledWorkflow {
   workflow_name: string;
   schedule_type: ScheduleType;
   cron_expression?: string;
+  /** IANA timezone cron expressions run in; defaults to UTC */
+  timezone?: string;
   enabled: boolean;
   last_run?: string;
   next_run?: string;
@@ -156,8 +194,22 @@ export const WorkflowCoreService = {
   /**
    * Execute a workflow
    */
-  async execute(workflowId: string): Promise<void> {
-    return invoke<void>('workflow_execute', { workflowId });
+  async execute(workflowId: string, inputs?: Record<string, unknown>): Promise<WorkflowRunResult> {
+    return invoke<WorkflowRunResult>('workflow_execute', { workflowId, inputs });
+  },
+
+  /**
+   * Approve or reject the step a paused execution is waiting on
+   */
+  async approveStep(executionId: string, decision: ApprovalDecision, note?: string): Promise<WorkflowRunResult> {
+    return invoke<WorkflowRunResult>('workflow_approve_step', { executionId, decision, note });
+  },
+
+  /**
+   * List executions waiting for an approval decision
+   */
+  async getPendingApprovals(): Promise<PendingApproval[]> {
+    return invoke<PendingApproval[]>('workflow_get_pending_approvals');
   },
 
   /**
@@ -225,8 +277,15 @@ export const SchedulerService = {
   /**
    * Validate a cron expression
    */
-  async validateCron(cronExpression: string): Promise<string[]> {
-    return invoke<string[]>('scheduler_validate_cron', { cronExpression });
+  async validateCron(cronExpression: string, timezone?: string): Promise<string[]> {
+    return invoke<string[]>('scheduler_validate_cron', { cronExpression, timezone });
+  },
+
+  /**
+   * Next fire times of a schedule, as ISO timestamps in its timezone
+   */
+  async getNextRuns(scheduleId: string, count: number): Promise<string[]> {
+    return invoke<string[]>('scheduler_get_next_runs', { scheduleId, count });
   },
 
   /**
@@ -0,0 +1,504 @@
+// Country-specific phone and postal code rules for autofill
+// Phone numbers are handled as a country dial code plus the national
+// significant number (the digits after the trunk prefix, e.g. the 0 in 020).
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Phone and postal conventions for one country
+pub struct CountryFormat {
+    /// ISO 3166-1 alpha-2 code
+    pub code: &'static str,
+    pub name: &'static str,
+    aliases: &'static [&'static str],
+    pub dial_code: &'static str,
+    /// Digit dialled before the significant number for national calls
+    trunk_prefix: Option<char>,
+    /// Allowed length of the national significant number
+    significant_lengths: (usize, usize),
+    format_national: fn(&str) -> String,
+    postal_pattern: &'static str,
+    pub postal_example: &'static str,
+    /// Formats an uppercase postal code with separators removed
+    format_postal: fn(&str) -> String,
+}
+
+/// A phone number resolved against a country's numbering plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhoneNumberFormat {
+    pub original_value: String,
+    pub country: Option<String>,
+    pub national: String,
+    pub e164: Option<String>,
+    pub valid: bool,
+    pub errors: Vec<String>,
+}
+
+lazy_static! {
+    static ref POSTAL_PATTERNS: Vec<(&'static str, Regex)> = COUNTRIES
+        .iter()
+        .map(|c| (c.code, Regex::new(c.postal_pattern).unwrap()))
+        .collect();
+    static ref GENERIC_POSTAL: Regex = Regex::new(r"^[A-Z0-9][A-Z0-9 -]{1,8}[A-Z0-9]$").unwrap();
+}
+
+fn grouped(digits: &str, groups: &[usize], separator: &str) -> String {
+    let mut parts = Vec::new();
+    let mut rest = digits;
+    for &size in groups {
+        if rest.len() <= size {
+            break;
+        }
+        parts.push(&rest[..size]);
+        rest = &rest[size..];
+    }
+    parts.push(rest);
+    parts.join(separator)
+}
+
+fn nanp_national(n: &str) -> String {
+    format!("({}) {}-{}", &n[..3], &n[3..6], &n[6..])
+}
+
+fn gb_national(n: &str) -> String {
+    if n.starts_with('2') {
+        format!("0{}", grouped(n, &[2, 4], " "))
+    } else if n.starts_with("11") || (n.starts_with('1') && n[2..].starts_with('1')) {
+        // 0113, 0161 and the other large-city codes
+        format!("0{}", grouped(n, &[3, 3], " "))
+    } else {
+        format!("0{}", grouped(n, &[4], " "))
+    }
+}
+
+fn de_national(n: &str) -> String {
+    let area = if ["30", "40", "69", "89"].iter().any(|a| n.starts_with(a)) {
+        2
+    } else if ["15", "16", "17"].iter().any(|a| n.starts_with(a)) {
+        3
+    } else {
+        4
+    };
+    format!("0{}", grouped(n, &[area], " "))
+}
+
+fn jp_national(n: &str) -> String {
+    if ["70", "80", "90"].iter().any(|a| n.starts_with(a)) {
+        format!("0{}", grouped(n, &[2, 4], "-"))
+    } else if n.starts_with('3') || n.starts_with('6') {
+        format!("0{}", grouped(n, &[1, 4], "-"))
+    } else {
+        format!("0{}", grouped(n, &[2, 3], "-"))
+    }
+}
+
+fn fr_national(n: &str) -> String {
+    format!("0{}", grouped(n, &[1, 2, 2, 2], " "))
+}
+
+fn au_national(n: &str) -> String {
+    if n.starts_with('4') {
+        format!("0{}", grouped(n, &[3, 3], " "))
+    } else {
+        format!("0{}", grouped(n, &[1, 4], " "))
+    }
+}
+
+fn es_national(n: &str) -> String {
+    grouped(n, &[3, 3], " ")
+}
+
+fn mx_national(n: &str) -> String {
+    grouped(n, &[2, 4], " ")
+}
+
+fn nl_national(n: &str) -> String {
+    if n.starts_with('6') {
+        format!("0{}", grouped(n, &[1], " "))
+    } else {
+        format!("0{}", grouped(n, &[2], " "))
+    }
+}
+
+fn br_national(n: &str) -> String {
+    let (area, rest) = n.split_at(2);
+    format!("({}) {}-{}", area, &rest[..rest.len() - 4], &rest[rest.len() - 4..])
+}
+
+fn in_national(n: &str) -> String {
+    grouped(n, &[5], " ")
+}
+
+fn postal_as_is(p: &str) -> String {
+    p.to_string()
+}
+
+fn postal_space_before_last_three(p: &str) -> String {
+    format!("{} {}", &p[..p.len() - 3], &p[p.len() - 3..])
+}
+
+fn postal_us(p: &str) -> String {
+    if p.len() == 9 {
+        format!("{}-{}", &p[..5], &p[5..])
+    } else {
+        p.to_string()
+    }
+}
+
+fn postal_jp(p: &str) -> String {
+    grouped(p, &[3], "-")
+}
+
+fn postal_nl(p: &str) -> String {
+    grouped(p, &[4], " ")
+}
+
+fn postal_br(p: &str) -> String {
+    grouped(p, &[5], "-")
+}
+
+static COUNTRIES: &[CountryFormat] = &[
+    CountryFormat {
+        code: "US",
+        name: "United States",
+        aliases: &["USA", "UNITED STATES OF AMERICA", "AMERICA"],
+        dial_code: "1",
+        trunk_prefix: Some('1'),
+        significant_lengths: (10, 10),
+        format_national: nanp_national,
+        postal_pattern: r"^\d{5}(\d{4})?$",
+        postal_example: "12345 or 12345-6789",
+        format_postal: postal_us,
+    },
+    CountryFormat {
+        code: "CA",
+        name: "Canada",
+        aliases: &["CAN"],
+        dial_code: "1",
+        trunk_prefix: Some('1'),
+        significant_lengths: (10, 10),
+        format_national: nanp_national,
+        postal_pattern: r"^[ABCEGHJ-NPRSTVXY]\d[ABCEGHJ-NPRSTV-Z]\d[ABCEGHJ-NPRSTV-Z]\d$",
+        postal_example: "K1A 0B1",
+        format_postal: postal_space_before_last_three,
+    },
+    CountryFormat {
+        code: "GB",
+        name: "United Kingdom",
+        aliases: &["UK", "GBR", "GREAT BRITAIN", "ENGLAND", "SCOTLAND", "WALES", "NORTHERN IRELAND"],
+        dial_code: "44",
+        trunk_prefix: Some('0'),
+        significant_lengths: (9, 10),
+        format_national: gb_national,
+        postal_pattern: r"^(GIR0AA|[A-PR-UWYZ]([0-9]{1,2}|[A-HK-Y][0-9]{1,2}|[0-9][A-HJKPSTUW]|[A-HK-Y][0-9][ABEHMNPRV-Y])[0-9][ABD-HJLNP-UW-Z]{2})$",
+        postal_example: "SW1A 1AA",
+        format_postal: postal_space_before_last_three,
+    },
+    CountryFormat {
+        code: "DE",
+        name: "Germany",
+        aliases: &["DEU", "DEUTSCHLAND"],
+        dial_code: "49",
+        trunk_prefix: Some('0'),
+        significant_lengths: (6, 11),
+        format_national: de_national,
+        postal_pattern: r"^\d{5}$",
+        postal_example: "10115",
+        format_postal: postal_as_is,
+    },
+    CountryFormat {
+        code: "JP",
+        name: "Japan",
+        aliases: &["JPN", "NIPPON", "NIHON"],
+        dial_code: "81",
+        trunk_prefix: Some('0'),
+        significant_lengths: (9, 10),
+        format_national: jp_national,
+        postal_pattern: r"^\d{7}$",
+        postal_example: "100-0001",
+        format_postal: postal_jp,
+    },
+    CountryFormat {
+        code: "FR",
+        name: "France",
+        aliases: &["FRA"],
+        dial_code: "33",
+        trunk_prefix: Some('0'),
+        significant_lengths: (9, 9),
+        format_national: fr_national,
+        postal_pattern: r"^\d{5}$",
+        postal_example: "75008",
+        format_postal: postal_as_is,
+    },
+    CountryFormat {
+        code: "AU",
+        name: "Australia",
+        aliases: &["AUS"],
+        dial_code: "61",
+        trunk_prefix: Some('0'),
+        significant_lengths: (9, 9),
+        format_national: au_national,
+        postal_pattern: r"^\d{4}$",
+        postal_example: "2000",
+        format_postal: postal_as_is,
+    },
+    CountryFormat {
+        code: "ES",
+        name: "Spain",
+        aliases: &["ESP", "ESPAÑA", "ESPANA"],
+        dial_code: "34",
+        trunk_prefix: None,
+        significant_lengths: (9, 9),
+        format_national: es_national,
+        postal_pattern: r"^(0[1-9]|[1-4]\d|5[0-2])\d{3}$",
+        postal_example: "28013",
+        format_postal: postal_as_is,
+    },
+    CountryFormat {
+        code: "MX",
+        name: "Mexico",
+        aliases: &["MEX", "MÉXICO"],
+        dial_code: "52",
+        trunk_prefix: None,
+        significant_lengths: (10, 10),
+        format_national: mx_national,
+        postal_pattern: r"^\d{5}$",
+        postal_example: "06600",
+        format_postal: postal_as_is,
+    },
+    CountryFormat {
+        code: "NL",
+        name: "Netherlands",
+        aliases: &["NLD", "HOLLAND", "THE NETHERLANDS", "NEDERLAND"],
+        dial_code: "31",
+        trunk_prefix: Some('0'),
+        significant_lengths: (9, 9),
+        format_national: nl_national,
+        postal_pattern: r"^[1-9]\d{3}[A-Z]{2}$",
+        postal_example: "1012 AB",
+        format_postal: postal_nl,
+    },
+    CountryFormat {
+        code: "BR",
+        name: "Brazil",
+        aliases: &["BRA", "BRASIL"],
+        dial_code: "55",
+        trunk_prefix: Some('0'),
+        significant_lengths: (10, 11),
+        format_national: br_national,
+        postal_pattern: r"^\d{8}$",
+        postal_example: "01310-100",
+        format_postal: postal_br,
+    },
+    CountryFormat {
+        code: "IN",
+        name: "India",
+        aliases: &["IND", "BHARAT"],
+        dial_code: "91",
+        trunk_prefix: Some('0'),
+        significant_lengths: (10, 10),
+        format_national: in_national,
+        postal_pattern: r"^[1-9]\d{5}$",
+        postal_example: "110001",
+        format_postal: postal_as_is,
+    },
+];
+
+/// Resolves an ISO code, ISO alpha-3 code or country name such as a form's
+/// country field value
+pub fn lookup_country(country: &str) -> Option<&'static CountryFormat> {
+    let key = country.trim().to_uppercase();
+    COUNTRIES.iter().find(|c| {
+        c.code == key || c.name.to_uppercase() == key || c.aliases.contains(&key.as_str())
+    })
+}
+
+/// Country for an international number's dial code. Shared dial codes resolve
+/// to the first listed country unless `preferred` shares it.
+fn country_for_dial_code(
+    digits: &str,
+    preferred: Option<&'static CountryFormat>,
+) -> Option<&'static CountryFormat> {
+    if let Some(country) = preferred.filter(|c| digits.starts_with(c.dial_code)) {
+        return Some(country);
+    }
+    COUNTRIES
+        .iter()
+        .filter(|c| digits.starts_with(c.dial_code))
+        .max_by_key(|c| c.dial_code.len())
+}
+
+fn compact_postal(value: &str) -> String {
+    value
+        .trim()
+        .to_uppercase()
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect()
+}
+
+impl CountryFormat {
+    pub fn postal_code_valid(&self, value: &str) -> bool {
+        let compact = compact_postal(value);
+        POSTAL_PATTERNS
+            .iter()
+            .find(|(code, _)| *code == self.code)
+            .is_some_and(|(_, pattern)| pattern.is_match(&compact))
+    }
+
+    /// Formats a valid postal code in the country's canonical layout
+    pub fn format_postal_code(&self, value: &str) -> Option<String> {
+        self.postal_code_valid(value)
+            .then(|| (self.format_postal)(&compact_postal(value)))
+    }
+
+    fn significant_number_errors(&self, significant: &str) -> Vec<String> {
+        let (min, max) = self.significant_lengths;
+        let mut errors = Vec::new();
+        if significant.len() < min {
+            errors.push(format!("Phone number too short for {}", self.name));
+        } else if significant.len() > max {
+            errors.push(format!("Phone number too long for {}", self.name));
+        } else if self.dial_code == "1"
+            && (significant.starts_with(['0', '1']) || significant[3..].starts_with(['0', '1']))
+        {
+            errors.push("Area code and exchange cannot start with 0 or 1".to_string());
+        }
+        errors
+    }
+}
+
+/// Generic postal code check for countries without specific rules
+pub fn postal_code_plausible(value: &str) -> bool {
+    GENERIC_POSTAL.is_match(&value.trim().to_uppercase())
+}
+
+/// Parses a phone number written nationally for `country` or internationally
+/// (+CC or 00CC), returning national and E.164 forms. Without a known country
+/// an international number is still accepted if it is 8-15 digits long.
+pub fn parse_phone(value: &str, country: Option<&'static CountryFormat>) -> PhoneNumberFormat {
+    let trimmed = value.trim();
+    let digits: String = trimmed.chars().filter(|c| c.is_ascii_digit()).collect();
+    let international = trimmed.starts_with('+') || (digits.starts_with("00") && !trimmed.starts_with('('));
+
+    let mut result = PhoneNumberFormat {
+        original_value: value.to_string(),
+        country: country.map(|c| c.code.to_string()),
+        national: digits.clone(),
+        e164: None,
+        valid: false,
+        errors: Vec::new(),
+    };
+    if digits.is_empty() {
+        result.errors.push("Phone number cannot be empty".to_string());
+        return result;
+    }
+
+    let (country, significant) = if international {
+        let number = if trimmed.starts_with('+') { &digits[..] } else { &digits[2..] };
+        match country_for_dial_code(number, country) {
+            Some(c) => {
+                // Drop a trunk prefix written in international form, e.g. +44 (0)20...
+                let rest = &number[c.dial_code.len()..];
+                let rest = match c.trunk_prefix {
+                    Some('0') if rest.starts_with('0') => &rest[1..],
+                    _ => rest,
+                };
+                (c, rest.to_string())
+            }
+            None => {
+                if (8..=15).contains(&number.len()) {
+                    result.e164 = Some(format!("+{}", number));
+                    result.national = format!("+{}", number);
+                    result.valid = true;
+                } else {
+                    result.errors.push("International numbers must have 8 to 15 digits".to_string());
+                }
+                result.country = None;
+                return result;
+            }
+        }
+    } else {
+        match country {
+            Some(c) => {
+                let (_, max) = c.significant_lengths;
+                let significant = match c.trunk_prefix {
+                    Some(trunk) if digits.starts_with(trunk) && (trunk == '0' || digits.len() > max) => &digits[1..],
+                    _ => &digits[..],
+                };
+                (c, significant.to_string())
+            }
+            None => {
+                if (7..=15).contains(&digits.len()) {
+                    result.valid = true;
+                } else {
+                    result.errors.push("Phone number should have 7 to 15 digits".to_string());
+                }
+                return result;
+            }
+        }
+    };
+
+    result.country = Some(country.code.to_string());
+    result.errors = country.significant_number_errors(&significant);
+    if result.errors.is_empty() {
+        result.national = (country.format_national)(&significant);
+        result.e164 = Some(format!("+{}{}", country.dial_code, significant));
+        result.valid = true;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_country_phone_and_postal_rules() {
+        let gb = lookup_country("United Kingdom").unwrap();
+        let phone = parse_phone("020 7946 0958", Some(gb));
+        assert!(phone.valid);
+        assert_eq!(phone.national, "020 7946 0958");
+        assert_eq!(phone.e164.as_deref(), Some("+442079460958"));
+        assert_eq!(parse_phone("07700900123", Some(gb)).national, "07700 900123");
+        assert_eq!(parse_phone("+44 (0)20 7946 0958", None).country.as_deref(), Some("GB"));
+        assert!(!parse_phone("020 794", Some(gb)).valid);
+        assert_eq!(gb.format_postal_code("sw1a1aa").as_deref(), Some("SW1A 1AA"));
+        assert!(gb.postal_code_valid("EC1A 1BB"));
+        assert!(!gb.postal_code_valid("12345"));
+
+        let ca = lookup_country("ca").unwrap();
+        let phone = parse_phone("1-613-555-0142", Some(ca));
+        assert_eq!(phone.national, "(613) 555-0142");
+        assert_eq!(phone.e164.as_deref(), Some("+16135550142"));
+        assert!(!parse_phone("613-055-0142", Some(ca)).valid);
+        assert_eq!(ca.format_postal_code("k1a0b1").as_deref(), Some("K1A 0B1"));
+        assert!(!ca.postal_code_valid("D1A 0B1"));
+
+        let de = lookup_country("Deutschland").unwrap();
+        let phone = parse_phone("030 12345678", Some(de));
+        assert_eq!(phone.national, "030 12345678");
+        assert_eq!(phone.e164.as_deref(), Some("+493012345678"));
+        assert_eq!(parse_phone("+49 1512 3456789", None).national, "0151 23456789");
+        assert_eq!(de.format_postal_code("10115").as_deref(), Some("10115"));
+        assert!(!de.postal_code_valid("1011"));
+
+        let jp = lookup_country("JPN").unwrap();
+        let phone = parse_phone("03-1234-5678", Some(jp));
+        assert_eq!(phone.national, "03-1234-5678");
+        assert_eq!(phone.e164.as_deref(), Some("+81312345678"));
+        assert_eq!(parse_phone("+81 90 1234 5678", Some(jp)).national, "090-1234-5678");
+        assert!(!parse_phone("03-1234", Some(jp)).valid);
+        assert_eq!(jp.format_postal_code("1000001").as_deref(), Some("100-0001"));
+        assert!(jp.postal_code_valid("100-0001"));
+        assert!(!jp.postal_code_valid("10000"));
+
+        // Unknown countries fall back to generic checks
+        assert!(lookup_country("Atlantis").is_none());
+        let phone = parse_phone("+999 1234 5678", None);
+        assert!(phone.valid && phone.country.is_none());
+        assert_eq!(phone.e164.as_deref(), Some("+99912345678"));
+        assert!(postal_code_plausible("AB-123"));
+    }
+}
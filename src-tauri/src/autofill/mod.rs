@@ -2,6 +2,7 @@
 // Re-exports production implementation from mod_v2
 
 // Import production implementation
+mod country_formats;
 mod mod_v2;
 
 // Import tests
@@ -30,8 +31,17 @@ pub use mod_v2::{
 
     FormatterResult,
     ValidationResult,
+
+    // Learning from form submissions
+    SavableChange,
+    SavableChangeKind,
+    SavableDataResult,
+    SubmittedField,
 };
 
+// Country-specific phone and postal code rules
+pub use country_formats::{lookup_country, CountryFormat, PhoneNumberFormat};
+
 // ============================================================================
 // HELPER FUNCTIONS
 // ============================================================================
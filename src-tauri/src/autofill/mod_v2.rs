@@ -9,6 +9,7 @@
 // - Thread-safe operations
 // - Comprehensive error handling
 
+use super::country_formats::{lookup_country, parse_phone, postal_code_plausible, PhoneNumberFormat};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -163,6 +164,47 @@ pub struct FormatterResult {
     pub changes_made: Vec<String>,
 }
 
+/// A value the user entered, captured when the form was submitted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmittedField {
+    pub metadata: FieldMetadata,
+    pub value: String,
+}
+
+/// Whether a submitted value is missing from the profile or differs from it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SavableChangeKind {
+    New,
+    Updated,
+}
+
+/// Submitted value worth offering to save
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavableChange {
+    pub profile_key: String,
+    pub field_type: FieldType,
+    /// Formatted the way autofill would write it back
+    pub value: String,
+    pub previous_value: Option<String>,
+    pub kind: SavableChangeKind,
+}
+
+/// Comparison of a submitted form against the saved profiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavableDataResult {
+    /// Whether to prompt "Save to profile?"
+    pub savable: bool,
+    /// Profile the submission belongs to; None when it matches no profile
+    pub profile_id: Option<String>,
+    /// Profile keys whose submitted values are already saved
+    pub matched_fields: Vec<String>,
+    pub changes: Vec<SavableChange>,
+}
+
+/// Submissions with fewer recognized values (a lone newsletter email, a
+/// search box) are not worth a save prompt
+const MIN_SAVABLE_FIELDS: usize = 2;
+
 // ============================================================================
 // FIELD DETECTOR
 // ============================================================================
@@ -441,6 +483,69 @@ impl FieldValidator {
         }
     }
 
+    /// Validate a field value using the conventions of `country` (ISO code or
+    /// name). Phone numbers and postal codes fall back to generic checks when
+    /// the country is missing or unknown.
+    pub fn validate_for_country(
+        &self,
+        value: &str,
+        field_type: &FieldType,
+        country: Option<&str>,
+    ) -> ValidationResult {
+        let country = country.map(str::trim).filter(|c| !c.is_empty());
+        let resolved = country.and_then(lookup_country);
+        let mut errors = Vec::new();
+        let mut suggestions = Vec::new();
+
+        let valid = match field_type {
+            FieldType::Phone | FieldType::Tel => {
+                let international = value.trim().starts_with('+') || value.trim().starts_with("00");
+                if country.is_none() && !international {
+                    return self.validate(value, field_type);
+                }
+                if let (Some(country), None) = (country, resolved) {
+                    suggestions.push(format!(
+                        "No phone rules for '{}'; include the country code, e.g. +44 20 7946 0958",
+                        country
+                    ));
+                }
+                let phone = parse_phone(value, resolved);
+                if let Some(e164) = phone.e164.as_ref().filter(|_| phone.valid) {
+                    suggestions.push(format!("International format: {}", e164));
+                }
+                errors.extend(phone.errors);
+                phone.valid
+            }
+            FieldType::PostalCode | FieldType::ZipCode => match (country, resolved) {
+                (None, _) => return self.validate(value, field_type),
+                (_, Some(format)) => {
+                    let valid = format.postal_code_valid(value);
+                    if !valid {
+                        errors.push(format!("Invalid postal code for {}", format.name));
+                        suggestions.push(format!("Expected format: {}", format.postal_example));
+                    }
+                    valid
+                }
+                (Some(country), None) => {
+                    let valid = postal_code_plausible(value);
+                    if !valid {
+                        errors.push("Invalid postal code format".to_string());
+                    }
+                    suggestions.push(format!("No postal code rules for '{}'; checked generic format", country));
+                    valid
+                }
+            },
+            _ => return self.validate(value, field_type),
+        };
+
+        ValidationResult {
+            valid,
+            field_type: field_type.clone(),
+            errors,
+            suggestions,
+        }
+    }
+
     fn validate_email(
         &self,
         value: &str,
@@ -676,6 +781,64 @@ impl FieldFormatter {
         }
     }
 
+    /// Format a field value using the conventions of `country`. Phone numbers
+    /// from another country are written in E.164 so they stay dialable.
+    pub fn format_for_country(
+        &self,
+        value: &str,
+        field_type: &FieldType,
+        country: Option<&str>,
+    ) -> FormatterResult {
+        let country = country.map(str::trim).filter(|c| !c.is_empty());
+        let resolved = country.and_then(lookup_country);
+        let mut changes_made = Vec::new();
+
+        let formatted_value = match (field_type, resolved) {
+            (FieldType::Phone | FieldType::Tel, _) if resolved.is_some() || value.trim().starts_with('+') => {
+                let phone = parse_phone(value, resolved);
+                match (phone.valid, phone.e164) {
+                    (true, Some(e164)) if phone.country.as_deref() != resolved.map(|c| c.code) => {
+                        changes_made.push("Formatted as E.164".to_string());
+                        e164
+                    }
+                    (true, _) => {
+                        changes_made.push(format!(
+                            "Formatted as {} national number",
+                            resolved.map(|c| c.name).unwrap_or("international")
+                        ));
+                        phone.national
+                    }
+                    (false, _) => return self.format(value, field_type),
+                }
+            }
+            (FieldType::PostalCode | FieldType::ZipCode, Some(format)) => {
+                match format.format_postal_code(value) {
+                    Some(formatted) => {
+                        if formatted != value {
+                            changes_made.push(format!("Formatted as {} postal code", format.name));
+                        }
+                        formatted
+                    }
+                    None => return self.format(value.trim(), &FieldType::Text),
+                }
+            }
+            _ => return self.format(value, field_type),
+        };
+
+        FormatterResult {
+            formatted_value,
+            original_value: value.to_string(),
+            field_type: field_type.clone(),
+            changes_made,
+        }
+    }
+
+    /// National and E.164 forms of a phone number for `country`, or for the
+    /// country of its dial code when written internationally
+    pub fn format_phone_number(&self, value: &str, country: Option<&str>) -> PhoneNumberFormat {
+        parse_phone(value, country.and_then(lookup_country))
+    }
+
     fn format_phone(&self, value: &str, changes_made: &mut Vec<String>) -> String {
         // Extract only digits
         let digits: String = value.chars().filter(|c| c.is_ascii_digit()).collect();
@@ -928,12 +1091,27 @@ impl AutofillEngine {
         &self,
         profile_id: &str,
         field_mappings: Vec<FieldMapping>,
+    ) -> Result<AutofillResult, String> {
+        self.autofill_with_country(profile_id, field_mappings, None)
+    }
+
+    /// Perform autofill, validating and formatting phone numbers and postal
+    /// codes for `country` (e.g. the form's country field), or for the
+    /// profile's address country when none is given
+    pub fn autofill_with_country(
+        &self,
+        profile_id: &str,
+        field_mappings: Vec<FieldMapping>,
+        country: Option<&str>,
     ) -> Result<AutofillResult, String> {
         let start_time = std::time::Instant::now();
 
         let profile = self
             .get_profile(profile_id)?
             .ok_or_else(|| format!("Profile not found: {}", profile_id))?;
+        let country = country
+            .filter(|c| !c.trim().is_empty())
+            .or(profile.fields.get("country").map(String::as_str));
 
         let mut fields_filled = 0;
         let mut fields_failed = 0;
@@ -943,11 +1121,15 @@ impl AutofillEngine {
         for mapping in &field_mappings {
             if let Some(value) = profile.fields.get(&mapping.profile_key) {
                 // Validate the value
-                let validation = self.validate_field(value, &mapping.field_type);
+                let validation = self
+                    .validator
+                    .validate_for_country(value, &mapping.field_type, country);
 
                 if validation.valid {
                     // Format the value
-                    let formatted = self.format_field(value, &mapping.field_type);
+                    let formatted = self
+                        .formatter
+                        .format_for_country(value, &mapping.field_type, country);
 
                     fields_filled += 1;
                     filled_fields.push(FilledField {
@@ -1003,6 +1185,146 @@ impl AutofillEngine {
         })
     }
 
+    // ========================================================================
+    // LEARNING FROM SUBMISSIONS
+    // ========================================================================
+
+    /// Compare a submitted form against the saved profiles and report the
+    /// new or changed values worth saving. Password fields are never
+    /// considered; they belong to the password manager.
+    pub fn detect_savable_data(
+        &self,
+        form_values: &[SubmittedField],
+    ) -> Result<SavableDataResult, String> {
+        let values = self.savable_values(form_values, None);
+        let profiles = self.get_all_profiles()?;
+
+        // The profile sharing the most submitted values, most recently used first
+        let profile = profiles
+            .iter()
+            .map(|profile| {
+                let matches = values
+                    .iter()
+                    .filter(|(key, field_type, value)| {
+                        profile
+                            .fields
+                            .get(key)
+                            .is_some_and(|saved| same_value(saved, value, field_type))
+                    })
+                    .count();
+                (matches, profile)
+            })
+            .filter(|(matches, _)| *matches > 0)
+            .max_by_key(|(matches, profile)| (*matches, profile.last_used.unwrap_or(0), profile.updated_at))
+            .map(|(_, profile)| profile);
+
+        let mut matched_fields = Vec::new();
+        let mut changes = Vec::new();
+        for (profile_key, field_type, value) in &values {
+            let saved = profile.and_then(|p| p.fields.get(profile_key));
+            match saved {
+                Some(saved) if same_value(saved, value, field_type) => {
+                    matched_fields.push(profile_key.clone());
+                }
+                _ => changes.push(SavableChange {
+                    profile_key: profile_key.clone(),
+                    field_type: field_type.clone(),
+                    value: value.clone(),
+                    previous_value: saved.cloned(),
+                    kind: if saved.is_some() {
+                        SavableChangeKind::Updated
+                    } else {
+                        SavableChangeKind::New
+                    },
+                }),
+            }
+        }
+
+        Ok(SavableDataResult {
+            savable: values.len() >= MIN_SAVABLE_FIELDS && !changes.is_empty(),
+            profile_id: profile.map(|p| p.id.clone()),
+            matched_fields,
+            changes,
+        })
+    }
+
+    /// Save the submitted values for the profile keys the user chose in the
+    /// save prompt
+    pub fn update_profile_from_form(
+        &self,
+        profile_id: &str,
+        form_values: &[SubmittedField],
+        fields: &[String],
+    ) -> Result<AutofillProfile, String> {
+        let profile = self
+            .get_profile(profile_id)?
+            .ok_or_else(|| format!("Profile not found: {}", profile_id))?;
+
+        let updates: HashMap<String, String> = self
+            .savable_values(form_values, profile.fields.get("country").map(String::as_str))
+            .into_iter()
+            .filter(|(key, _, _)| fields.contains(key))
+            .map(|(key, _, value)| (key, value))
+            .collect();
+        if updates.is_empty() {
+            return Ok(profile);
+        }
+
+        self.update_profile(profile_id, updates)?;
+        self.get_profile(profile_id)?
+            .ok_or_else(|| format!("Profile not found: {}", profile_id))
+    }
+
+    /// Recognized, valid values of a submission as (profile key, type,
+    /// formatted value), using the form's country or `fallback_country`
+    fn savable_values(
+        &self,
+        form_values: &[SubmittedField],
+        fallback_country: Option<&str>,
+    ) -> Vec<(String, FieldType, String)> {
+        let mut detected: Vec<(String, FieldType, &str)> = Vec::new();
+        for field in form_values {
+            let value = field.value.trim();
+            if value.is_empty() || field.metadata.element_type.eq_ignore_ascii_case("password") {
+                continue;
+            }
+            let (field_type, confidence) = self.detector.detect_field_type(&field.metadata);
+            if confidence < self.detector.confidence_threshold {
+                continue;
+            }
+            let profile_key = self.detector.generate_profile_key(&field_type);
+            // Free text can't be matched to a profile field
+            if matches!(profile_key.as_str(), "password" | "text")
+                || detected.iter().any(|(key, _, _)| *key == profile_key)
+            {
+                continue;
+            }
+            detected.push((profile_key, field_type, value));
+        }
+
+        let country = detected
+            .iter()
+            .find(|(key, _, _)| key == "country")
+            .map(|(_, _, value)| *value)
+            .or(fallback_country);
+
+        detected
+            .into_iter()
+            .filter(|(_, field_type, value)| {
+                self.validator
+                    .validate_for_country(value, field_type, country)
+                    .valid
+            })
+            .map(|(key, field_type, value)| {
+                let formatted = self
+                    .formatter
+                    .format_for_country(value, &field_type, country)
+                    .formatted_value;
+                (key, field_type, formatted)
+            })
+            .collect()
+    }
+
     fn preview_value(&self, value: &str) -> String {
         if value.len() <= 20 {
             value.to_string()
@@ -1018,6 +1340,37 @@ impl Default for AutofillEngine {
     }
 }
 
+/// Whether two values for a field are the same once formatting is ignored;
+/// phone numbers match with or without the country code
+fn same_value(saved: &str, submitted: &str, field_type: &FieldType) -> bool {
+    match field_type {
+        FieldType::Phone | FieldType::Tel => {
+            let digits = |value: &str| -> String { value.chars().filter(|c| c.is_ascii_digit()).collect() };
+            let (saved, submitted) = (digits(saved), digits(submitted));
+            let (shorter, longer) = if saved.len() <= submitted.len() {
+                (saved, submitted)
+            } else {
+                (submitted, saved)
+            };
+            shorter.len() >= 7 && longer.ends_with(&shorter)
+        }
+        FieldType::PostalCode | FieldType::ZipCode => {
+            let compact = |value: &str| -> String {
+                value
+                    .chars()
+                    .filter(|c| c.is_ascii_alphanumeric())
+                    .collect::<String>()
+                    .to_uppercase()
+            };
+            compact(saved) == compact(submitted)
+        }
+        _ => {
+            let normalize = |value: &str| value.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+            normalize(saved) == normalize(submitted)
+        }
+    }
+}
+
 // ============================================================================
 // AUTOFILL PROFILE IMPLEMENTATIONS
 // ============================================================================
@@ -1201,4 +1554,70 @@ mod tests {
         let deleted = engine.delete_profile(&profile.id).unwrap();
         assert!(deleted);
     }
+
+    #[test]
+    fn test_submitted_new_phone_is_savable_and_applied() {
+        fn submitted(autocomplete: &str, element_type: &str, value: &str) -> SubmittedField {
+            SubmittedField {
+                metadata: FieldMetadata {
+                    selector: format!("#{}", autocomplete),
+                    element_type: element_type.to_string(),
+                    name: None,
+                    id: None,
+                    placeholder: None,
+                    label: None,
+                    aria_label: None,
+                    autocomplete: Some(autocomplete.to_string()),
+                    required: false,
+                    pattern: None,
+                    min_length: None,
+                    max_length: None,
+                },
+                value: value.to_string(),
+            }
+        }
+
+        let engine = AutofillEngine::new();
+        let profile = engine.create_profile("Personal".to_string(), None).unwrap();
+        let mut fields = HashMap::new();
+        fields.insert("first_name".to_string(), "John".to_string());
+        fields.insert("last_name".to_string(), "Doe".to_string());
+        fields.insert("email".to_string(), "john@example.com".to_string());
+        fields.insert("phone".to_string(), "(555) 123-4567".to_string());
+        engine.update_profile(&profile.id, fields).unwrap();
+
+        let form = vec![
+            submitted("given-name", "text", "John"),
+            submitted("family-name", "text", "Doe"),
+            submitted("email", "email", " John@Example.com"),
+            submitted("tel", "tel", "555.987.6543"),
+            submitted("new-password", "password", "hunter22"),
+        ];
+
+        let result = engine.detect_savable_data(&form).unwrap();
+        assert!(result.savable);
+        assert_eq!(result.profile_id.as_deref(), Some(profile.id.as_str()));
+        assert_eq!(result.changes.len(), 1);
+        let change = &result.changes[0];
+        assert_eq!(change.profile_key, "phone");
+        assert_eq!(change.kind, SavableChangeKind::Updated);
+        assert_eq!(change.value, "(555) 987-6543");
+        assert_eq!(change.previous_value.as_deref(), Some("(555) 123-4567"));
+        assert!(result.matched_fields.contains(&"email".to_string()));
+        assert!(!result.matched_fields.contains(&"password".to_string()));
+
+        // A lone field is not worth a prompt
+        let lone = engine
+            .detect_savable_data(&[submitted("email", "email", "other@example.com")])
+            .unwrap();
+        assert!(!lone.savable);
+
+        let updated = engine
+            .update_profile_from_form(&profile.id, &form, &["phone".to_string()])
+            .unwrap();
+        assert_eq!(updated.fields.get("phone").map(String::as_str), Some("(555) 987-6543"));
+        assert_eq!(updated.fields.get("email").map(String::as_str), Some("john@example.com"));
+        assert!(!updated.fields.contains_key("password"));
+        assert!(!engine.detect_savable_data(&form).unwrap().savable);
+    }
 }
@@ -321,10 +321,69 @@ mod autofill_tests {
         }
     }
 
+    #[test]
+    fn test_validate_for_country() {
+        let validator = FieldValidator::new();
+
+        let cases = vec![
+            ("GB", "020 7946 0958", "SW1A 1AA", "12345"),
+            ("Canada", "613-555-0142", "K1A 0B1", "12345"),
+            ("DE", "030 12345678", "10115", "SW1A 1AA"),
+            ("Japan", "090-1234-5678", "100-0001", "1000"),
+        ];
+
+        for (country, phone, postal, wrong_postal) in cases {
+            let result = validator.validate_for_country(phone, &FieldType::Phone, Some(country));
+            assert!(result.valid, "Phone '{}' should be valid for {}", phone, country);
+
+            let result = validator.validate_for_country(postal, &FieldType::PostalCode, Some(country));
+            assert!(result.valid, "Postal code '{}' should be valid for {}", postal, country);
+
+            let result = validator.validate_for_country(wrong_postal, &FieldType::PostalCode, Some(country));
+            assert!(!result.valid, "Postal code '{}' should be invalid for {}", wrong_postal, country);
+        }
+
+        let result = validator.validate_for_country("0201", &FieldType::Phone, Some("GB"));
+        assert!(!result.valid);
+
+        // Unknown countries fall back to generic checks
+        let result = validator.validate_for_country("+354 551 2345", &FieldType::Phone, Some("Iceland"));
+        assert!(result.valid);
+        assert!(!result.suggestions.is_empty());
+        let result = validator.validate_for_country("101", &FieldType::PostalCode, Some("Iceland"));
+        assert!(result.valid);
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // FIELD FORMATTING TESTS
     // ═══════════════════════════════════════════════════════════════════════════
 
+    #[test]
+    fn test_format_for_country() {
+        let formatter = FieldFormatter::new();
+
+        let test_cases = vec![
+            ("GB", "02079460958", "020 7946 0958", "+442079460958", "sw1a1aa", "SW1A 1AA"),
+            ("CA", "6135550142", "(613) 555-0142", "+16135550142", "k1a0b1", "K1A 0B1"),
+            ("DE", "+49 30 12345678", "030 12345678", "+493012345678", "10115", "10115"),
+            ("JP", "0312345678", "03-1234-5678", "+81312345678", "1000001", "100-0001"),
+        ];
+
+        for (country, phone, national, e164, postal, formatted_postal) in test_cases {
+            let result = formatter.format_phone_number(phone, Some(country));
+            assert_eq!(result.national, national);
+            assert_eq!(result.e164.as_deref(), Some(e164));
+
+            let result = formatter.format_for_country(postal, &FieldType::PostalCode, Some(country));
+            assert_eq!(result.formatted_value, formatted_postal);
+        }
+
+        // A number from another country stays dialable
+        let result = formatter.format_for_country("+81 90 1234 5678", &FieldType::Phone, Some("GB"));
+        assert_eq!(result.formatted_value, "+819012345678");
+    }
+
+
     #[test]
     fn test_format_phone() {
         let formatter = FieldFormatter::new();
@@ -554,6 +613,54 @@ mod autofill_tests {
         assert_eq!(result.filled_fields.len(), 2);
     }
 
+    #[test]
+    fn test_autofill_uses_profile_country() {
+        let engine = AutofillEngine::new();
+
+        let profile = engine.create_profile("UK".to_string(), None).unwrap();
+        let mut updates = HashMap::new();
+        updates.insert("phone".to_string(), "07700900123".to_string());
+        updates.insert("postal_code".to_string(), "ec1a1bb".to_string());
+        updates.insert("country".to_string(), "United Kingdom".to_string());
+        engine.update_profile(&profile.id, updates).unwrap();
+
+        let mapping = |selector: &str, field_type: FieldType, key: &str| FieldMapping {
+            selector: selector.to_string(),
+            field_type,
+            profile_key: key.to_string(),
+            confidence: 0.95,
+            metadata: FieldMetadata {
+                selector: selector.to_string(),
+                element_type: "text".to_string(),
+                name: None,
+                id: None,
+                placeholder: None,
+                label: None,
+                aria_label: None,
+                autocomplete: None,
+                required: false,
+                pattern: None,
+                min_length: None,
+                max_length: None,
+            },
+        };
+        let field_mappings = vec![
+            mapping("#phone", FieldType::Phone, "phone"),
+            mapping("#postcode", FieldType::PostalCode, "postal_code"),
+        ];
+
+        let result = engine.autofill(&profile.id, field_mappings.clone()).unwrap();
+        assert_eq!(result.fields_filled, 2);
+        assert_eq!(result.filled_fields[0].value_preview, "07700 900123");
+        assert_eq!(result.filled_fields[1].value_preview, "EC1A 1BB");
+
+        // The form's country wins over the profile's
+        let result = engine
+            .autofill_with_country(&profile.id, field_mappings, Some("US"))
+            .unwrap();
+        assert_eq!(result.fields_failed, 2);
+    }
+
     #[test]
     fn test_autofill_with_missing_data() {
         let engine = AutofillEngine::new();
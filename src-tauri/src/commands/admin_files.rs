@@ -5,7 +5,8 @@
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use std::sync::Mutex;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc, Duration};
 use uuid::Uuid;
 
@@ -104,6 +105,39 @@ pub struct StorageStats {
     pub usage_by_type: HashMap<String, u64>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ArchiveFormat {
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar.gz")]
+    TarGz,
+}
+
+impl ArchiveFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Zip => "zip",
+            ArchiveFormat::TarGz => "tar.gz",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedArchiveItem {
+    pub file_id: String,
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveResult {
+    pub archive_path: String,
+    pub format: ArchiveFormat,
+    pub entries: Vec<String>,
+    pub skipped: Vec<SkippedArchiveItem>,
+    pub size_bytes: u64,
+}
+
 // ============================================================
 // STATE
 // ============================================================
@@ -114,6 +148,8 @@ pub struct FileManagerState {
     pub share_links: Mutex<Vec<ShareLink>>,
     pub uploads: Mutex<HashMap<String, UploadProgress>>,
     pub stats: Mutex<StorageStats>,
+    /// Uploaded file contents, one blob per file id
+    pub content_dir: PathBuf,
 }
 
 impl Default for FileManagerState {
@@ -204,10 +240,215 @@ impl Default for FileManagerState {
                     m
                 },
             }),
+            content_dir: dirs::data_local_dir()
+                .unwrap_or_else(std::env::temp_dir)
+                .join("cube-nexum")
+                .join("admin_files"),
         }
     }
 }
 
+impl FileManagerState {
+    fn content_path(&self, file_id: &str) -> PathBuf {
+        self.content_dir.join(file_id)
+    }
+}
+
+// ============================================================
+// ARCHIVES
+// ============================================================
+
+struct PlannedEntry {
+    file_id: String,
+    archive_path: String,
+    /// `None` for folders
+    source: Option<PathBuf>,
+}
+
+fn skipped_item(file: &FileItem, reason: &str) -> SkippedArchiveItem {
+    SkippedArchiveItem {
+        file_id: file.id.clone(),
+        path: file.path.clone(),
+        reason: reason.to_string(),
+    }
+}
+
+fn can_access(file: &FileItem, user_id: &str, team_member: bool) -> bool {
+    file.owner == user_id
+        || match file.permissions {
+            FilePermission::Public => true,
+            FilePermission::Team => team_member,
+            FilePermission::Private => false,
+        }
+}
+
+/// Archive path that doesn't collide with one already taken, e.g. `a/report (1).pdf`
+fn unique_archive_path(path: &str, taken: &mut HashSet<String>) -> String {
+    if taken.insert(path.to_string()) {
+        return path.to_string();
+    }
+    let (dir, name) = match path.rsplit_once('/') {
+        Some((dir, name)) => (format!("{}/", dir), name),
+        None => (String::new(), path),
+    };
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+        _ => (name, String::new()),
+    };
+    (1..)
+        .map(|n| format!("{}{} ({}){}", dir, stem, n, ext))
+        .find(|candidate| taken.insert(candidate.clone()))
+        .unwrap_or_default()
+}
+
+/// Work out the archive contents for the selection. Folders are expanded
+/// recursively; paths inside the archive are relative to each selected item.
+fn plan_archive(
+    files: &HashMap<String, FileItem>,
+    file_ids: &[String],
+    user_id: &str,
+    team_member: bool,
+    content_dir: &Path,
+) -> (Vec<PlannedEntry>, Vec<SkippedArchiveItem>) {
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    let mut included: HashSet<String> = HashSet::new();
+    let mut taken: HashSet<String> = HashSet::new();
+
+    for file_id in file_ids {
+        let Some(selected) = files.get(file_id) else {
+            skipped.push(SkippedArchiveItem {
+                file_id: file_id.clone(),
+                path: String::new(),
+                reason: "File not found".to_string(),
+            });
+            continue;
+        };
+        if !can_access(selected, user_id, team_member) {
+            skipped.push(skipped_item(selected, "Permission denied"));
+            continue;
+        }
+
+        let mut items: Vec<&FileItem> = vec![selected];
+        if selected.file_type == FileType::Folder {
+            let prefix = format!("{}/", selected.path.trim_end_matches('/'));
+            let mut children: Vec<&FileItem> = files.values().filter(|f| f.path.starts_with(&prefix)).collect();
+            children.sort_by(|a, b| a.path.cmp(&b.path));
+            items.extend(children);
+        }
+        let base = selected.path.rsplit_once('/').map(|(parent, _)| parent.len() + 1).unwrap_or(0);
+
+        // Contents of a folder the user can't open are skipped with it
+        let mut denied_folders: Vec<String> = Vec::new();
+        for item in items {
+            if included.contains(&item.id) {
+                continue;
+            }
+            if denied_folders.iter().any(|d| item.path.starts_with(d.as_str())) {
+                continue;
+            }
+            if !can_access(item, user_id, team_member) {
+                if item.file_type == FileType::Folder {
+                    denied_folders.push(format!("{}/", item.path));
+                }
+                skipped.push(skipped_item(item, "Permission denied"));
+                continue;
+            }
+
+            let relative = item.path.get(base..).unwrap_or(&item.name).trim_start_matches('/');
+            let source = match item.file_type {
+                FileType::Folder => None,
+                FileType::File => {
+                    let path = content_dir.join(&item.id);
+                    if !path.is_file() {
+                        skipped.push(skipped_item(item, "File content is not stored on this server"));
+                        continue;
+                    }
+                    Some(path)
+                }
+            };
+
+            included.insert(item.id.clone());
+            let archive_path = match source {
+                Some(_) => unique_archive_path(relative, &mut taken),
+                None => {
+                    taken.insert(relative.to_string());
+                    relative.to_string()
+                }
+            };
+            entries.push(PlannedEntry {
+                file_id: item.id.clone(),
+                archive_path,
+                source,
+            });
+        }
+    }
+
+    (entries, skipped)
+}
+
+/// Stream the planned entries into an archive at `dest`, one file at a time
+fn write_archive(entries: &[PlannedEntry], format: ArchiveFormat, dest: &Path) -> Result<u64, String> {
+    let out = std::fs::File::create(dest).map_err(|e| format!("Failed to create archive: {}", e))?;
+
+    match format {
+        ArchiveFormat::Zip => {
+            let mut zip = zip::ZipWriter::new(out);
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Deflated)
+                .large_file(true);
+            for entry in entries {
+                match &entry.source {
+                    Some(source) => {
+                        zip.start_file(entry.archive_path.as_str(), options)
+                            .map_err(|e| format!("Failed to add {}: {}", entry.archive_path, e))?;
+                        let mut input = std::fs::File::open(source)
+                            .map_err(|e| format!("Failed to read {}: {}", entry.archive_path, e))?;
+                        std::io::copy(&mut input, &mut zip)
+                            .map_err(|e| format!("Failed to add {}: {}", entry.archive_path, e))?;
+                    }
+                    None => {
+                        zip.add_directory(entry.archive_path.as_str(), options)
+                            .map_err(|e| format!("Failed to add {}: {}", entry.archive_path, e))?;
+                    }
+                }
+            }
+            zip.finish().map_err(|e| format!("Failed to finish archive: {}", e))?;
+        }
+        ArchiveFormat::TarGz => {
+            let encoder = flate2::write::GzEncoder::new(out, flate2::Compression::default());
+            let mut tar = tar::Builder::new(encoder);
+            let mtime = Utc::now().timestamp().max(0) as u64;
+            for entry in entries {
+                match &entry.source {
+                    Some(source) => {
+                        let mut input = std::fs::File::open(source)
+                            .map_err(|e| format!("Failed to read {}: {}", entry.archive_path, e))?;
+                        tar.append_file(&entry.archive_path, &mut input)
+                            .map_err(|e| format!("Failed to add {}: {}", entry.archive_path, e))?;
+                    }
+                    None => {
+                        let mut header = tar::Header::new_gnu();
+                        header.set_entry_type(tar::EntryType::Directory);
+                        header.set_size(0);
+                        header.set_mode(0o755);
+                        header.set_mtime(mtime);
+                        tar.append_data(&mut header, format!("{}/", entry.archive_path), std::io::empty())
+                            .map_err(|e| format!("Failed to add {}: {}", entry.archive_path, e))?;
+                    }
+                }
+            }
+            tar.into_inner()
+                .and_then(|gz| gz.finish())
+                .map_err(|e| format!("Failed to finish archive: {}", e))?;
+        }
+    }
+
+    std::fs::metadata(dest)
+        .map(|m| m.len())
+        .map_err(|e| format!("Failed to read archive: {}", e))
+}
+
 // ============================================================
 // COMMANDS
 // ============================================================
@@ -323,9 +564,21 @@ pub async fn files_upload(
     };
     
     let extension = request.name.split('.').last().map(|s| s.to_string());
+    let file_id = Uuid::new_v4().to_string();
+    
+    if let Some(content) = &request.content_base64 {
+        use base64::Engine;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(content)
+            .map_err(|e| format!("Invalid file content: {}", e))?;
+        std::fs::create_dir_all(&state.content_dir)
+            .map_err(|e| format!("Failed to create storage directory: {}", e))?;
+        std::fs::write(state.content_path(&file_id), bytes)
+            .map_err(|e| format!("Failed to store file: {}", e))?;
+    }
     
     let file = FileItem {
-        id: Uuid::new_v4().to_string(),
+        id: file_id,
         name: request.name,
         file_type: FileType::File,
         path: path.clone(),
@@ -382,6 +635,8 @@ pub async fn files_delete(
     let mut files = state.files.lock().map_err(|e| format!("Lock error: {}", e))?;
     
     if let Some(file) = files.remove(&file_id) {
+        let _ = std::fs::remove_file(state.content_path(&file_id));
+        
         // Update stats
         drop(files);
         let mut stats = state.stats.lock().map_err(|e| format!("Lock error: {}", e))?;
@@ -479,8 +734,15 @@ pub async fn files_copy(
         format!("{}/{}", dest_path, original.name)
     };
     
+    let copy_id = Uuid::new_v4().to_string();
+    let original_content = state.content_path(&original.id);
+    if original_content.is_file() {
+        std::fs::copy(&original_content, state.content_path(&copy_id))
+            .map_err(|e| format!("Failed to copy file content: {}", e))?;
+    }
+    
     let copy = FileItem {
-        id: Uuid::new_v4().to_string(),
+        id: copy_id,
         name: original.name,
         file_type: original.file_type,
         path: new_path,
@@ -693,3 +955,142 @@ pub async fn files_record_download(
     
     Ok(())
 }
+
+/// Bundle the selected files and folders into a ZIP or tar.gz in the temp directory.
+/// Items the user can't access are left out and listed in `skipped`.
+#[tauri::command]
+pub async fn files_create_archive(
+    state: State<'_, FileManagerState>,
+    file_ids: Vec<String>,
+    format: Option<ArchiveFormat>,
+    user_id: String,
+    team_member: Option<bool>,
+) -> Result<ArchiveResult, String> {
+    let format = format.unwrap_or(ArchiveFormat::Zip);
+
+    let (entries, skipped) = {
+        let files = state.files.lock().map_err(|e| format!("Lock error: {}", e))?;
+        plan_archive(&files, &file_ids, &user_id, team_member.unwrap_or(false), &state.content_dir)
+    };
+    if !entries.iter().any(|e| e.source.is_some()) {
+        return Err("None of the selected files can be archived".to_string());
+    }
+
+    let archive_dir = std::env::temp_dir().join("cube-nexum-archives");
+    std::fs::create_dir_all(&archive_dir).map_err(|e| format!("Failed to create archive directory: {}", e))?;
+    let dest = archive_dir.join(format!("files-{}.{}", Uuid::new_v4(), format.extension()));
+
+    let (entries, size_bytes) = tokio::task::spawn_blocking({
+        let dest = dest.clone();
+        move || write_archive(&entries, format, &dest).map(|size| (entries, size))
+    })
+    .await
+    .map_err(|e| format!("Archive task failed: {}", e))??;
+
+    let mut files = state.files.lock().map_err(|e| format!("Lock error: {}", e))?;
+    for entry in entries.iter().filter(|e| e.source.is_some()) {
+        if let Some(file) = files.get_mut(&entry.file_id) {
+            file.downloads += 1;
+        }
+    }
+
+    Ok(ArchiveResult {
+        archive_path: dest.to_string_lossy().to_string(),
+        format,
+        entries: entries.into_iter().map(|e| e.archive_path).collect(),
+        skipped,
+        size_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn item(name: &str, path: &str, file_type: FileType, owner: &str, permissions: FilePermission) -> FileItem {
+        FileItem {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            file_type,
+            path: path.to_string(),
+            size: None,
+            mime_type: None,
+            extension: None,
+            created_at: Utc::now(),
+            modified_at: Utc::now(),
+            owner: owner.to_string(),
+            permissions,
+            starred: false,
+            downloads: 0,
+            thumbnail_url: None,
+            preview_url: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_archive_nested_selection_with_permission_denial() {
+        let content_dir = std::env::temp_dir().join(format!("cube-files-test-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&content_dir).unwrap();
+
+        let items = [
+            item("Projects", "/Projects", FileType::Folder, "alice", FilePermission::Team),
+            item("plan.md", "/Projects/plan.md", FileType::File, "alice", FilePermission::Team),
+            item("Design", "/Projects/Design", FileType::Folder, "alice", FilePermission::Team),
+            item("logo.svg", "/Projects/Design/logo.svg", FileType::File, "alice", FilePermission::Team),
+            item("salaries.xlsx", "/Projects/salaries.xlsx", FileType::File, "bob", FilePermission::Private),
+            item("Other", "/Other", FileType::Folder, "alice", FilePermission::Team),
+            item("plan.md", "/Other/plan.md", FileType::File, "alice", FilePermission::Team),
+        ];
+        for file in items.iter().filter(|f| f.file_type == FileType::File) {
+            std::fs::write(content_dir.join(&file.id), format!("contents of {}", file.path)).unwrap();
+        }
+        let id = |path: &str| items.iter().find(|f| f.path == path).unwrap().id.clone();
+        let files: HashMap<String, FileItem> = items.iter().map(|f| (f.id.clone(), f.clone())).collect();
+
+        let selection = vec![id("/Projects"), id("/Other/plan.md")];
+        let (entries, skipped) = plan_archive(&files, &selection, "carol", true, &content_dir);
+
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].path, "/Projects/salaries.xlsx");
+        assert_eq!(skipped[0].reason, "Permission denied");
+
+        let dest = content_dir.join("out.zip");
+        write_archive(&entries, ArchiveFormat::Zip, &dest).unwrap();
+
+        let mut zip = zip::ZipArchive::new(std::fs::File::open(&dest).unwrap()).unwrap();
+        let mut names: Vec<String> = zip.file_names().map(String::from).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["Projects/", "Projects/Design/", "Projects/Design/logo.svg", "Projects/plan.md", "plan.md"]
+        );
+
+        let mut logo = String::new();
+        zip.by_name("Projects/Design/logo.svg").unwrap().read_to_string(&mut logo).unwrap();
+        assert_eq!(logo, "contents of /Projects/Design/logo.svg");
+
+        // The owner sees everything; two selected files with the same name don't collide
+        let selection = vec![id("/Projects/plan.md"), id("/Other/plan.md"), id("/Projects/salaries.xlsx")];
+        let (entries, skipped) = plan_archive(&files, &selection, "bob", false, &content_dir);
+        assert!(skipped.iter().all(|s| s.reason == "Permission denied") && skipped.len() == 2);
+        let paths: Vec<&str> = entries.iter().map(|e| e.archive_path.as_str()).collect();
+        assert_eq!(paths, vec!["salaries.xlsx"]);
+        let (entries, _) = plan_archive(&files, &selection, "alice", false, &content_dir);
+        let paths: Vec<&str> = entries.iter().map(|e| e.archive_path.as_str()).collect();
+        assert_eq!(paths, vec!["plan.md", "plan (1).md"]);
+
+        let tar_dest = content_dir.join("out.tar.gz");
+        write_archive(&entries, ArchiveFormat::TarGz, &tar_dest).unwrap();
+        let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(std::fs::File::open(&tar_dest).unwrap()));
+        let tar_names: Vec<String> = tar
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(tar_names, vec!["plan.md", "plan (1).md"]);
+
+        std::fs::remove_dir_all(&content_dir).unwrap();
+    }
+}
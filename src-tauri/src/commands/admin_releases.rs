@@ -110,6 +110,159 @@ pub struct ReleaseStats {
     pub update_success_rate: f64,
     pub downloads_by_platform: HashMap<String, u64>,
     pub downloads_by_channel: HashMap<String, u64>,
+    /// Health of every monitored rollout, keyed by release id
+    #[serde(default)]
+    pub rollouts: HashMap<String, RolloutHealth>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutPolicy {
+    /// Ramp percentages, e.g. [5, 25, 100]
+    pub stages: Vec<u8>,
+    /// Install results required before a stage can be judged
+    pub min_samples_per_stage: u64,
+    /// Failure rate (0.0 - 1.0) above which the rollout is halted
+    pub failure_threshold: f64,
+    pub auto_advance: bool,
+    pub auto_rollback: bool,
+}
+
+impl Default for RolloutPolicy {
+    fn default() -> Self {
+        Self {
+            stages: vec![5, 25, 100],
+            min_samples_per_stage: 50,
+            failure_threshold: 0.05,
+            auto_advance: true,
+            auto_rollback: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutHealthStatus {
+    Monitoring,
+    Completed,
+    Halted,
+    RolledBack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutHealth {
+    pub release_id: String,
+    pub policy: RolloutPolicy,
+    pub status: RolloutHealthStatus,
+    pub stage_index: usize,
+    pub cohort_percentage: u8,
+    pub stage_started_at: DateTime<Utc>,
+    /// Counters for the current stage's cohort only
+    pub cohort_downloads: u64,
+    pub cohort_successes: u64,
+    pub cohort_failures: u64,
+    pub failure_rate: f64,
+    pub halted_reason: Option<String>,
+    pub halted_at: Option<DateTime<Utc>>,
+    pub recent_errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RolloutDecision {
+    Continue,
+    Advanced(u8),
+    Completed,
+    Halted,
+    RolledBack,
+}
+
+const MAX_RECENT_INSTALL_ERRORS: usize = 20;
+
+impl RolloutHealth {
+    pub fn new(release_id: &str, policy: RolloutPolicy) -> Self {
+        let cohort_percentage = policy.stages.first().copied().unwrap_or(100);
+        Self {
+            release_id: release_id.to_string(),
+            policy,
+            status: RolloutHealthStatus::Monitoring,
+            stage_index: 0,
+            cohort_percentage,
+            stage_started_at: Utc::now(),
+            cohort_downloads: 0,
+            cohort_successes: 0,
+            cohort_failures: 0,
+            failure_rate: 0.0,
+            halted_reason: None,
+            halted_at: None,
+            recent_errors: Vec::new(),
+        }
+    }
+
+    fn start_stage(&mut self, stage_index: usize, percentage: u8) {
+        self.stage_index = stage_index;
+        self.cohort_percentage = percentage;
+        self.stage_started_at = Utc::now();
+        self.cohort_downloads = 0;
+        self.cohort_successes = 0;
+        self.cohort_failures = 0;
+        self.failure_rate = 0.0;
+    }
+
+    /// Records one install outcome for the current cohort and decides whether
+    /// the rollout should continue, move to the next stage, or stop.
+    pub fn record_install(&mut self, success: bool, error: Option<String>) -> RolloutDecision {
+        if self.status != RolloutHealthStatus::Monitoring {
+            return RolloutDecision::Continue;
+        }
+
+        if success {
+            self.cohort_successes += 1;
+        } else {
+            self.cohort_failures += 1;
+            if let Some(error) = error {
+                self.recent_errors.push(error);
+                if self.recent_errors.len() > MAX_RECENT_INSTALL_ERRORS {
+                    self.recent_errors.remove(0);
+                }
+            }
+        }
+
+        let samples = self.cohort_successes + self.cohort_failures;
+        self.failure_rate = self.cohort_failures as f64 / samples as f64;
+        if samples < self.policy.min_samples_per_stage {
+            return RolloutDecision::Continue;
+        }
+
+        if self.failure_rate > self.policy.failure_threshold {
+            self.halted_reason = Some(format!(
+                "Failure rate {:.1}% exceeded {:.1}% at {}% rollout ({} of {} installs failed)",
+                self.failure_rate * 100.0,
+                self.policy.failure_threshold * 100.0,
+                self.cohort_percentage,
+                self.cohort_failures,
+                samples,
+            ));
+            self.halted_at = Some(Utc::now());
+            return if self.policy.auto_rollback {
+                self.status = RolloutHealthStatus::RolledBack;
+                RolloutDecision::RolledBack
+            } else {
+                self.status = RolloutHealthStatus::Halted;
+                RolloutDecision::Halted
+            };
+        }
+
+        match self.policy.stages.get(self.stage_index + 1).copied() {
+            Some(next) if self.policy.auto_advance => {
+                self.start_stage(self.stage_index + 1, next);
+                RolloutDecision::Advanced(next)
+            }
+            None => {
+                self.status = RolloutHealthStatus::Completed;
+                RolloutDecision::Completed
+            }
+            _ => RolloutDecision::Continue,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -130,6 +283,7 @@ pub struct ReleaseState {
     pub releases: Mutex<HashMap<String, Release>>,
     pub settings: Mutex<UpdateSettings>,
     pub stats: Mutex<ReleaseStats>,
+    pub rollouts: Mutex<HashMap<String, RolloutHealth>>,
 }
 
 impl Default for ReleaseState {
@@ -240,7 +394,9 @@ impl Default for ReleaseState {
                     m.insert("stable".to_string(), 27560);
                     m
                 },
+                rollouts: HashMap::new(),
             }),
+            rollouts: Mutex::new(HashMap::new()),
         }
     }
 }
@@ -402,8 +558,53 @@ pub async fn admin_update_rollout(
     }
     
     release.rollout_percentage = percentage;
+    let release_clone = release.clone();
     
-    Ok(release.clone())
+    // A manual change starts a fresh cohort and resumes a halted rollout
+    drop(releases);
+    let mut rollouts = state.rollouts.lock().map_err(|e| format!("Lock error: {}", e))?;
+    if let Some(health) = rollouts.get_mut(&release_id) {
+        let stage_index = health.policy.stages.iter()
+            .position(|stage| *stage >= percentage)
+            .unwrap_or(health.policy.stages.len().saturating_sub(1));
+        health.start_stage(stage_index, percentage);
+        health.status = RolloutHealthStatus::Monitoring;
+        health.halted_reason = None;
+        health.halted_at = None;
+    }
+    
+    Ok(release_clone)
+}
+
+/// Start a monitored, staged rollout (e.g. 5% -> 25% -> 100%)
+#[tauri::command]
+pub async fn admin_configure_rollout(
+    state: State<'_, ReleaseState>,
+    release_id: String,
+    policy: RolloutPolicy,
+) -> Result<RolloutHealth, String> {
+    if policy.stages.is_empty() || policy.stages.iter().any(|stage| *stage == 0 || *stage > 100) {
+        return Err("Rollout stages must be between 1 and 100%".to_string());
+    }
+    if policy.stages.windows(2).any(|w| w[0] >= w[1]) {
+        return Err("Rollout stages must be strictly increasing".to_string());
+    }
+    if !(0.0..=1.0).contains(&policy.failure_threshold) {
+        return Err("Failure threshold must be between 0 and 1".to_string());
+    }
+    
+    let mut releases = state.releases.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let release = releases.get_mut(&release_id)
+        .ok_or_else(|| "Release not found".to_string())?;
+    
+    let health = RolloutHealth::new(&release_id, policy);
+    release.rollout_percentage = health.cohort_percentage;
+    
+    drop(releases);
+    let mut rollouts = state.rollouts.lock().map_err(|e| format!("Lock error: {}", e))?;
+    rollouts.insert(release_id, health.clone());
+    
+    Ok(health)
 }
 
 #[tauri::command]
@@ -481,8 +682,10 @@ pub async fn admin_add_platform_binary(
 pub async fn admin_get_release_stats(
     state: State<'_, ReleaseState>,
 ) -> Result<ReleaseStats, String> {
-    let stats = state.stats.lock().map_err(|e| format!("Lock error: {}", e))?;
-    Ok(stats.clone())
+    let mut stats = state.stats.lock().map_err(|e| format!("Lock error: {}", e))?.clone();
+    let rollouts = state.rollouts.lock().map_err(|e| format!("Lock error: {}", e))?;
+    stats.rollouts = rollouts.clone();
+    Ok(stats)
 }
 
 #[tauri::command]
@@ -530,5 +733,122 @@ pub async fn release_record_download(
     let platform_key = format!("{:?}", platform).to_lowercase();
     *stats.downloads_by_platform.entry(platform_key).or_insert(0) += 1;
     
+    drop(stats);
+    let mut rollouts = state.rollouts.lock().map_err(|e| format!("Lock error: {}", e))?;
+    if let Some(health) = rollouts.get_mut(&release_id) {
+        health.cohort_downloads += 1;
+    }
+    
     Ok(())
 }
+
+/// Clients report whether applying an update succeeded; failures beyond the
+/// rollout policy's threshold halt (or roll back) the release automatically.
+#[tauri::command]
+pub async fn release_record_install_result(
+    state: State<'_, ReleaseState>,
+    release_id: String,
+    platform: Platform,
+    success: bool,
+    error: Option<String>,
+) -> Result<Option<RolloutHealth>, String> {
+    let health = {
+        let mut rollouts = state.rollouts.lock().map_err(|e| format!("Lock error: {}", e))?;
+        rollouts.get_mut(&release_id).map(|health| {
+            let error = error.map(|e| format!("{:?}: {}", platform, e));
+            (health.record_install(success, error), health.clone())
+        })
+    };
+    
+    if let Some((decision, health)) = &health {
+        let mut releases = state.releases.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if let Some(release) = releases.get_mut(&release_id) {
+            match *decision {
+                RolloutDecision::Advanced(percentage) => release.rollout_percentage = percentage,
+                RolloutDecision::Halted => release.rollout_percentage = 0,
+                RolloutDecision::RolledBack => {
+                    release.rollout_percentage = 0;
+                    release.status = ReleaseStatus::Recalled;
+                    release.description = format!(
+                        "{}\n\n**RECALLED**: {}",
+                        release.description,
+                        health.halted_reason.clone().unwrap_or_default()
+                    );
+                }
+                RolloutDecision::Continue | RolloutDecision::Completed => {}
+            }
+        }
+    }
+    
+    let mut stats = state.stats.lock().map_err(|e| format!("Lock error: {}", e))?;
+    if success {
+        stats.active_installs += 1;
+    }
+    
+    Ok(health.map(|(_, health)| health))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RolloutPolicy {
+        RolloutPolicy {
+            stages: vec![5, 25, 100],
+            min_samples_per_stage: 10,
+            failure_threshold: 0.1,
+            auto_advance: true,
+            auto_rollback: false,
+        }
+    }
+
+    #[test]
+    fn test_rollout_halts_when_failures_breach_threshold() {
+        let mut health = RolloutHealth::new("r1", policy());
+
+        // Failures before the minimum sample don't decide anything yet
+        for _ in 0..3 {
+            assert_eq!(health.record_install(false, Some("checksum mismatch".to_string())), RolloutDecision::Continue);
+        }
+        for _ in 0..6 {
+            assert_eq!(health.record_install(true, None), RolloutDecision::Continue);
+        }
+
+        assert_eq!(health.record_install(true, None), RolloutDecision::Halted);
+        assert_eq!(health.status, RolloutHealthStatus::Halted);
+        assert!((health.failure_rate - 0.3).abs() < 1e-9);
+        assert!(health.halted_reason.is_some());
+
+        // Once halted, further results don't move the rollout
+        assert_eq!(health.record_install(true, None), RolloutDecision::Continue);
+    }
+
+    #[test]
+    fn test_rollout_ramps_through_stages() {
+        let mut health = RolloutHealth::new("r1", policy());
+        assert_eq!(health.cohort_percentage, 5);
+
+        let mut decisions = Vec::new();
+        for _ in 0..30 {
+            let decision = health.record_install(true, None);
+            if decision != RolloutDecision::Continue {
+                decisions.push(decision);
+            }
+        }
+
+        assert_eq!(
+            decisions,
+            vec![RolloutDecision::Advanced(25), RolloutDecision::Advanced(100), RolloutDecision::Completed]
+        );
+        assert_eq!(health.status, RolloutHealthStatus::Completed);
+    }
+
+    #[test]
+    fn test_rollout_auto_rollback() {
+        let mut health = RolloutHealth::new("r1", RolloutPolicy { auto_rollback: true, ..policy() });
+        for _ in 0..10 {
+            health.record_install(false, None);
+        }
+        assert_eq!(health.status, RolloutHealthStatus::RolledBack);
+    }
+}
@@ -1,5 +1,8 @@
+use crate::services::ai_streaming::{stream_completion, StreamProvider, StreamSummary};
 use serde::{Deserialize, Serialize};
-use tauri::command;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{command, AppHandle, Emitter, State};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AIRequest {
@@ -193,3 +196,210 @@ pub async fn gemini_completion(api_key: String, request: AIRequest) -> Result<AI
         tokens_used,
     })
 }
+
+// ============================================================================
+// Streaming completions
+// ============================================================================
+
+/// In-flight streaming completions by request id, so they can be cancelled
+#[derive(Default)]
+pub struct AiStreamState {
+    streams: Arc<Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
+}
+
+/// Payload of the `ai-token` event
+#[derive(Debug, Clone, Serialize)]
+pub struct AiTokenEvent {
+    pub request_id: String,
+    pub delta: String,
+}
+
+/// Payload of the `ai-done` event, sent once per stream however it ended
+#[derive(Debug, Clone, Serialize)]
+pub struct AiDoneEvent {
+    pub request_id: String,
+    pub model: String,
+    #[serde(flatten)]
+    pub summary: StreamSummary,
+    pub cancelled: bool,
+    pub error: Option<String>,
+}
+
+/// Runs the request on a background task that emits `ai-token` for each delta
+/// and `ai-done` at the end, and returns the request id the events carry
+fn start_stream(
+    app: AppHandle,
+    state: &AiStreamState,
+    provider: StreamProvider,
+    model: String,
+    request: reqwest::RequestBuilder,
+) -> String {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let streams = state.streams.clone();
+    let id = request_id.clone();
+
+    // Hold the lock until the handle is stored, so a stream that ends right
+    // away cannot try to remove itself first
+    let mut running = state.streams.lock().unwrap();
+    let task = tokio::spawn(async move {
+        let result = stream_completion(request, provider, |delta| {
+            let _ = app.emit(
+                "ai-token",
+                AiTokenEvent {
+                    request_id: id.clone(),
+                    delta: delta.to_string(),
+                },
+            );
+        })
+        .await;
+
+        streams.lock().unwrap().remove(&id);
+        let (summary, error) = match result {
+            Ok(summary) => (summary, None),
+            Err(error) => (StreamSummary::default(), Some(error)),
+        };
+        let _ = app.emit(
+            "ai-done",
+            AiDoneEvent {
+                request_id: id,
+                model,
+                summary,
+                cancelled: false,
+                error,
+            },
+        );
+    });
+    running.insert(request_id.clone(), task);
+    request_id
+}
+
+/// OpenAI completion streamed as `ai-token` events
+#[command]
+pub async fn openai_completion_stream(
+    app: AppHandle,
+    state: State<'_, AiStreamState>,
+    api_key: String,
+    request: AIRequest,
+) -> Result<String, String> {
+    let model = request.model.unwrap_or_else(|| "gpt-5.2".to_string());
+
+    let body = serde_json::json!({
+        "model": model,
+        "messages": [
+            {
+                "role": "user",
+                "content": request.prompt
+            }
+        ],
+        "temperature": request.temperature.unwrap_or(0.7),
+        "max_tokens": request.max_tokens.unwrap_or(2000),
+        "stream": true,
+        "stream_options": { "include_usage": true }
+    });
+
+    let http_request = reqwest::Client::new()
+        .post("https://api.openai.com/v1/chat/completions")
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("Content-Type", "application/json")
+        .json(&body);
+
+    Ok(start_stream(app, &state, StreamProvider::OpenAI, model, http_request))
+}
+
+/// Claude (Anthropic) completion streamed as `ai-token` events
+#[command]
+pub async fn claude_completion_stream(
+    app: AppHandle,
+    state: State<'_, AiStreamState>,
+    api_key: String,
+    request: AIRequest,
+) -> Result<String, String> {
+    let model = request
+        .model
+        .unwrap_or_else(|| "claude-3-5-sonnet-20241022".to_string());
+
+    let body = serde_json::json!({
+        "model": model,
+        "messages": [
+            {
+                "role": "user",
+                "content": request.prompt
+            }
+        ],
+        "max_tokens": request.max_tokens.unwrap_or(4000),
+        "temperature": request.temperature.unwrap_or(0.7),
+        "stream": true
+    });
+
+    let http_request = reqwest::Client::new()
+        .post("https://api.anthropic.com/v1/messages")
+        .header("x-api-key", api_key)
+        .header("anthropic-version", "2023-06-01")
+        .header("Content-Type", "application/json")
+        .json(&body);
+
+    Ok(start_stream(app, &state, StreamProvider::Claude, model, http_request))
+}
+
+/// Google Gemini completion streamed as `ai-token` events
+#[command]
+pub async fn gemini_completion_stream(
+    app: AppHandle,
+    state: State<'_, AiStreamState>,
+    api_key: String,
+    request: AIRequest,
+) -> Result<String, String> {
+    let model = request
+        .model
+        .unwrap_or_else(|| "gemini-2.0-flash-exp".to_string());
+
+    let body = serde_json::json!({
+        "contents": [{
+            "parts": [{
+                "text": request.prompt
+            }]
+        }],
+        "generationConfig": {
+            "temperature": request.temperature.unwrap_or(0.7),
+            "maxOutputTokens": request.max_tokens.unwrap_or(2000)
+        }
+    });
+
+    let url = format!(
+        "https://generativelanguage.googleapis.com/v1/models/{}:streamGenerateContent?alt=sse&key={}",
+        model, api_key
+    );
+
+    let http_request = reqwest::Client::new()
+        .post(&url)
+        .header("Content-Type", "application/json")
+        .json(&body);
+
+    Ok(start_stream(app, &state, StreamProvider::Gemini, model, http_request))
+}
+
+/// Aborts a streaming completion, closing its connection. Emits a cancelled
+/// `ai-done` and returns false if the stream had already finished.
+#[command]
+pub async fn cancel_ai_stream(
+    app: AppHandle,
+    state: State<'_, AiStreamState>,
+    request_id: String,
+) -> Result<bool, String> {
+    let Some(task) = state.streams.lock().unwrap().remove(&request_id) else {
+        return Ok(false);
+    };
+    task.abort();
+
+    let _ = app.emit(
+        "ai-done",
+        AiDoneEvent {
+            request_id,
+            model: String::new(),
+            summary: StreamSummary::default(),
+            cancelled: true,
+            error: None,
+        },
+    );
+    Ok(true)
+}
@@ -12,8 +12,9 @@ use chrono::Utc;
  */
 use enigo::{Axis, Button, Coordinate, Direction, Enigo, Keyboard, Mouse, Settings};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Mutex;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tauri::State;
 use uuid::Uuid;
@@ -134,6 +135,54 @@ pub struct ExecutionResult {
     pub completed_at: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DebugStatus {
+    /// Waiting before `next_step`; the page is left exactly as the last step left it
+    Paused,
+    Running,
+    Completed,
+}
+
+/// Variables and page state carried between steps of a debug run
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugContext {
+    pub variables: HashMap<String, serde_json::Value>,
+    pub current_url: Option<String>,
+    pub page_title: Option<String>,
+    pub steps_completed: usize,
+    pub steps_failed: usize,
+    pub errors: Vec<String>,
+}
+
+/// What one debug step did, with the screen and context right after it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepSnapshot {
+    pub step_index: usize,
+    pub step_id: String,
+    pub action_type: ActionType,
+    pub success: bool,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub screenshot: Option<String>,
+    pub context: DebugContext,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowDebugState {
+    pub session_id: String,
+    pub workflow_id: String,
+    pub status: DebugStatus,
+    pub next_step: usize,
+    pub total_steps: usize,
+    pub breakpoints: Vec<usize>,
+    pub context: DebugContext,
+    pub snapshots: Vec<StepSnapshot>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // STATE
 // ═══════════════════════════════════════════════════════════════════════════
@@ -142,6 +191,7 @@ pub struct AITrainerState {
     workflows: Mutex<HashMap<String, TrainingSequence>>,
     current_recording: Mutex<Option<RecordingSession>>,
     execution_history: Mutex<Vec<ExecutionResult>>,
+    debug_sessions: Mutex<HashMap<String, Arc<Mutex<WorkflowDebugSession>>>>,
 }
 
 impl AITrainerState {
@@ -150,6 +200,7 @@ impl AITrainerState {
             workflows: Mutex::new(HashMap::new()),
             current_recording: Mutex::new(None),
             execution_history: Mutex::new(Vec::new()),
+            debug_sessions: Mutex::new(HashMap::new()),
         }
     }
 }
@@ -574,28 +625,9 @@ fn execute_action_step_sync(
             return Err("Validate action requires browser integration".to_string());
         }
         ActionType::Screenshot => {
-            // Use screenshots crate for screen capture
-            let screens = screenshots::Screen::all()
-                .map_err(|e| format!("Failed to get screens: {}", e))?;
-
-            if let Some(screen) = screens.first() {
-                let image = screen
-                    .capture()
-                    .map_err(|e| format!("Failed to capture screenshot: {}", e))?;
-
-                // Save to temp directory
-                let timestamp = Utc::now().format("%Y%m%d_%H%M%S").to_string();
-                let screenshot_dir = std::env::temp_dir().join("cube_screenshots");
-                std::fs::create_dir_all(&screenshot_dir)
-                    .map_err(|e| format!("Failed to create screenshot directory: {}", e))?;
-
-                let path = screenshot_dir.join(format!("workflow_{}_{}.png", step.id, timestamp));
-                image
-                    .save(&path)
-                    .map_err(|e| format!("Failed to save screenshot: {}", e))?;
-
-                return Ok(Some(path.to_string_lossy().to_string()));
-            }
+            let timestamp = Utc::now().format("%Y%m%d_%H%M%S").to_string();
+            let path = screenshot_dir().join(format!("workflow_{}_{}.png", step.id, timestamp));
+            return capture_screen(&path);
         }
         ActionType::Custom => {
             // Custom actions - parse from value
@@ -649,6 +681,374 @@ fn execute_action_step_sync(
     Ok(None)
 }
 
+fn screenshot_dir() -> PathBuf {
+    std::env::temp_dir().join("cube_screenshots")
+}
+
+/// Capture the primary screen to `path`; `None` when there is no screen
+fn capture_screen(path: &std::path::Path) -> Result<Option<String>, String> {
+    // Use screenshots crate for screen capture
+    let screens = screenshots::Screen::all()
+        .map_err(|e| format!("Failed to get screens: {}", e))?;
+
+    let Some(screen) = screens.first() else {
+        return Ok(None);
+    };
+    let image = screen
+        .capture()
+        .map_err(|e| format!("Failed to capture screenshot: {}", e))?;
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create screenshot directory: {}", e))?;
+    }
+    image
+        .save(path)
+        .map_err(|e| format!("Failed to save screenshot: {}", e))?;
+
+    Ok(Some(path.to_string_lossy().to_string()))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// DEBUG EXECUTION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Executes steps and captures the screen for a debug run
+pub trait StepRunner: Send {
+    /// Run one step, returning the path of any screenshot the step itself took
+    fn run_step(&mut self, step: &ActionStep) -> Result<Option<String>, String>;
+    /// Capture the screen after a step
+    fn capture(&mut self, name: &str) -> Result<Option<String>, String>;
+}
+
+/// Drives the real desktop with enigo, like `execute_workflow`
+struct DesktopStepRunner;
+
+impl StepRunner for DesktopStepRunner {
+    fn run_step(&mut self, step: &ActionStep) -> Result<Option<String>, String> {
+        // enigo is not Send, so each step gets its own simulator
+        let mut enigo = Enigo::new(&Settings::default())
+            .map_err(|e| format!("Failed to initialize input simulator: {}", e))?;
+        execute_action_step_sync(&mut enigo, step)
+    }
+
+    fn capture(&mut self, name: &str) -> Result<Option<String>, String> {
+        capture_screen(&screenshot_dir().join(format!("{}.png", name)))
+    }
+}
+
+/// A workflow run that stops at breakpoints and between single steps.
+/// Nothing runs while it is paused, so the page stays as the last step
+/// left it for inspection.
+pub struct WorkflowDebugSession {
+    id: String,
+    workflow_id: String,
+    steps: Vec<ActionStep>,
+    breakpoints: BTreeSet<usize>,
+    next_step: usize,
+    status: DebugStatus,
+    context: DebugContext,
+    snapshots: Vec<StepSnapshot>,
+    started: Instant,
+    runner: Box<dyn StepRunner>,
+}
+
+impl WorkflowDebugSession {
+    pub fn new(
+        workflow: &TrainingSequence,
+        breakpoints: impl IntoIterator<Item = usize>,
+        variables: HashMap<String, serde_json::Value>,
+        runner: Box<dyn StepRunner>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            workflow_id: workflow.id.clone(),
+            steps: workflow.steps.clone(),
+            breakpoints: breakpoints.into_iter().collect(),
+            next_step: 0,
+            status: DebugStatus::Paused,
+            context: DebugContext { variables, ..Default::default() },
+            snapshots: Vec::new(),
+            started: Instant::now(),
+            runner,
+        }
+    }
+
+    pub fn set_breakpoints(&mut self, breakpoints: impl IntoIterator<Item = usize>) {
+        self.breakpoints = breakpoints.into_iter().collect();
+    }
+
+    /// Execute the next step and pause again
+    pub fn step(&mut self) -> Result<StepSnapshot, String> {
+        if self.status == DebugStatus::Completed {
+            return Err("La depuración ya terminó".to_string());
+        }
+        let index = self.next_step;
+        let step = self.resolve_step(&self.steps[index]);
+
+        self.status = DebugStatus::Running;
+        let started = Instant::now();
+        let result = self.runner.run_step(&step);
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        let error = match &result {
+            Ok(step_screenshot) => {
+                self.context.steps_completed += 1;
+                if let Some(url) = step.value.as_ref().filter(|_| matches!(step.action_type, ActionType::Navigate)) {
+                    self.context.current_url = Some(url.clone());
+                } else if !step.context.url.is_empty() {
+                    self.context.current_url = Some(step.context.url.clone());
+                }
+                self.context.page_title = Some(step.context.title.clone()).filter(|t| !t.is_empty());
+                self.context.variables.insert(
+                    format!("step{}", index + 1),
+                    serde_json::json!({
+                        "value": step.value,
+                        "screenshot": step_screenshot,
+                    }),
+                );
+                None
+            }
+            Err(e) => {
+                self.context.steps_failed += 1;
+                let message = format!("Step {} ({:?}) failed: {}", index + 1, step.action_type, e);
+                self.context.errors.push(message.clone());
+                Some(message)
+            }
+        };
+
+        let screenshot = match self.runner.capture(&format!("debug_{}_step{}", self.id, index + 1)) {
+            Ok(path) => path,
+            Err(e) => {
+                log::warn!("Debug screenshot failed: {}", e);
+                None
+            }
+        };
+
+        self.next_step += 1;
+        self.status = if self.next_step >= self.steps.len() {
+            DebugStatus::Completed
+        } else {
+            DebugStatus::Paused
+        };
+
+        let snapshot = StepSnapshot {
+            step_index: index,
+            step_id: step.id.clone(),
+            action_type: step.action_type.clone(),
+            success: error.is_none(),
+            error,
+            duration_ms,
+            screenshot,
+            context: self.context.clone(),
+        };
+        self.snapshots.push(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    /// Run until the next breakpoint, a failed step or the end. The
+    /// breakpoint step itself is not executed.
+    pub fn continue_run(&mut self) -> Result<Vec<StepSnapshot>, String> {
+        let mut snapshots = vec![self.step()?];
+        while self.status == DebugStatus::Paused
+            && snapshots.last().map_or(false, |s| s.success)
+            && !self.breakpoints.contains(&self.next_step)
+        {
+            snapshots.push(self.step()?);
+        }
+        Ok(snapshots)
+    }
+
+    pub fn state(&self) -> WorkflowDebugState {
+        WorkflowDebugState {
+            session_id: self.id.clone(),
+            workflow_id: self.workflow_id.clone(),
+            status: self.status.clone(),
+            next_step: self.next_step,
+            total_steps: self.steps.len(),
+            breakpoints: self.breakpoints.iter().copied().collect(),
+            context: self.context.clone(),
+            snapshots: self.snapshots.clone(),
+        }
+    }
+
+    /// Summary for the execution history once the run completes
+    pub fn execution_result(&self) -> ExecutionResult {
+        ExecutionResult {
+            workflow_id: self.workflow_id.clone(),
+            success: self.context.steps_failed == 0,
+            steps_completed: self.context.steps_completed,
+            steps_failed: self.context.steps_failed,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            errors: self.context.errors.clone(),
+            screenshots: self.snapshots.iter().filter_map(|s| s.screenshot.clone()).collect(),
+            completed_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Substitute `{{variable}}` references in the step's value
+    fn resolve_step(&self, step: &ActionStep) -> ActionStep {
+        let mut step = step.clone();
+        if let Some(value) = step.value.as_mut() {
+            for (name, variable) in &self.context.variables {
+                let replacement = match variable {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                *value = value.replace(&format!("{{{{{}}}}}", name), &replacement);
+            }
+        }
+        step
+    }
+}
+
+/**
+ * 8b. DEBUG WORKFLOW
+ * Inicia una ejecución paso a paso con breakpoints (índices de paso, base 0)
+ */
+#[tauri::command]
+pub async fn workflow_debug_start(
+    state: State<'_, AITrainerState>,
+    workflow_id: String,
+    breakpoints: Option<Vec<usize>>,
+    variables: Option<HashMap<String, serde_json::Value>>,
+) -> Result<WorkflowDebugState, String> {
+    let workflow = {
+        let workflows = state.workflows.lock().unwrap();
+        workflows
+            .get(&workflow_id)
+            .cloned()
+            .ok_or_else(|| format!("Workflow {} no encontrado", workflow_id))?
+    };
+    if workflow.steps.is_empty() {
+        return Err("El workflow debe tener al menos un paso".to_string());
+    }
+
+    let session = WorkflowDebugSession::new(
+        &workflow,
+        breakpoints.unwrap_or_default(),
+        variables.unwrap_or_default(),
+        Box::new(DesktopStepRunner),
+    );
+    let debug_state = session.state();
+    state
+        .debug_sessions
+        .lock()
+        .unwrap()
+        .insert(debug_state.session_id.clone(), Arc::new(Mutex::new(session)));
+
+    Ok(debug_state)
+}
+
+/// Run `action` on a debug session off the async runtime, recording the
+/// run in the execution history when it completes
+async fn with_debug_session<T: Send + 'static>(
+    state: &AITrainerState,
+    session_id: &str,
+    action: impl FnOnce(&mut WorkflowDebugSession) -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    let session = state
+        .debug_sessions
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| format!("Sesión de depuración {} no encontrada", session_id))?;
+
+    let (result, finished) = tokio::task::spawn_blocking(move || {
+        let mut session = session.lock().unwrap();
+        let was_completed = session.status == DebugStatus::Completed;
+        let result = action(&mut session);
+        let finished = (!was_completed && session.status == DebugStatus::Completed)
+            .then(|| session.execution_result());
+        (result, finished)
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))?;
+
+    if let Some(execution) = finished {
+        state.execution_history.lock().unwrap().push(execution);
+    }
+    result
+}
+
+/**
+ * 8c. DEBUG STEP
+ * Ejecuta un solo paso y vuelve a pausar
+ */
+#[tauri::command]
+pub async fn workflow_debug_step(
+    state: State<'_, AITrainerState>,
+    session_id: String,
+) -> Result<StepSnapshot, String> {
+    with_debug_session(&state, &session_id, |session| session.step()).await
+}
+
+/**
+ * 8d. DEBUG CONTINUE
+ * Ejecuta hasta el siguiente breakpoint, un paso fallido o el final
+ */
+#[tauri::command]
+pub async fn workflow_debug_continue(
+    state: State<'_, AITrainerState>,
+    session_id: String,
+) -> Result<Vec<StepSnapshot>, String> {
+    with_debug_session(&state, &session_id, |session| session.continue_run()).await
+}
+
+#[tauri::command]
+pub async fn workflow_debug_set_breakpoints(
+    state: State<'_, AITrainerState>,
+    session_id: String,
+    breakpoints: Vec<usize>,
+) -> Result<WorkflowDebugState, String> {
+    with_debug_session(&state, &session_id, move |session| {
+        session.set_breakpoints(breakpoints);
+        Ok(session.state())
+    })
+    .await
+}
+
+/// Current state; while a step is executing only the status is reported
+#[tauri::command]
+pub async fn workflow_debug_get_state(
+    state: State<'_, AITrainerState>,
+    session_id: String,
+) -> Result<WorkflowDebugState, String> {
+    let sessions = state.debug_sessions.lock().unwrap();
+    let session = sessions
+        .get(&session_id)
+        .ok_or_else(|| format!("Sesión de depuración {} no encontrada", session_id))?;
+    let debug_state = match session.try_lock() {
+        Ok(session) => session.state(),
+        Err(_) => WorkflowDebugState {
+            session_id: session_id.clone(),
+            workflow_id: String::new(),
+            status: DebugStatus::Running,
+            next_step: 0,
+            total_steps: 0,
+            breakpoints: Vec::new(),
+            context: DebugContext::default(),
+            snapshots: Vec::new(),
+        },
+    };
+    Ok(debug_state)
+}
+
+#[tauri::command]
+pub async fn workflow_debug_stop(
+    state: State<'_, AITrainerState>,
+    session_id: String,
+) -> Result<(), String> {
+    state
+        .debug_sessions
+        .lock()
+        .unwrap()
+        .remove(&session_id)
+        .ok_or_else(|| format!("Sesión de depuración {} no encontrada", session_id))?;
+    Ok(())
+}
+
 /**
  * 9. ANALYZE WORKFLOW WITH AI
  * Envía workflow a OpenAI GPT para análisis profesional y optimización
@@ -984,3 +1384,110 @@ pub async fn update_workflow(
 
     Ok(workflow.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records executed steps instead of driving the desktop
+    struct FakeRunner {
+        executed: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl StepRunner for FakeRunner {
+        fn run_step(&mut self, step: &ActionStep) -> Result<Option<String>, String> {
+            self.executed.lock().unwrap().push(step.value.clone().unwrap_or_default());
+            match step.action_type {
+                ActionType::Validate => Err("element missing".to_string()),
+                _ => Ok(None),
+            }
+        }
+
+        fn capture(&mut self, name: &str) -> Result<Option<String>, String> {
+            Ok(Some(format!("/tmp/{}.png", name)))
+        }
+    }
+
+    fn step(action_type: ActionType, value: &str, url: &str) -> ActionStep {
+        ActionStep {
+            id: Uuid::new_v4().to_string(),
+            action_type,
+            selector: None,
+            value: Some(value.to_string()),
+            position: None,
+            duration: None,
+            expected_result: None,
+            context: PageContext {
+                url: url.to_string(),
+                title: "Checkout".to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+                viewport: Viewport { width: 1280, height: 720 },
+                dom_snapshot: None,
+            },
+            timestamp: Utc::now().to_rfc3339(),
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_debug_session_pauses_at_breakpoint_and_snapshots_each_step() {
+        let workflow = TrainingSequence {
+            id: "wf-1".to_string(),
+            name: "Checkout".to_string(),
+            description: String::new(),
+            steps: vec![
+                step(ActionType::Navigate, "https://shop.example/cart", "https://shop.example/cart"),
+                step(ActionType::Type, "{{email}}", "https://shop.example/checkout"),
+                step(ActionType::Click, "pay", "https://shop.example/checkout"),
+                step(ActionType::Validate, "done", "https://shop.example/done"),
+            ],
+            category: "form_fill".to_string(),
+            tags: Vec::new(),
+            created_at: Utc::now().to_rfc3339(),
+            updated_at: Utc::now().to_rfc3339(),
+            execution_count: 0,
+            success_rate: 0.0,
+            average_duration: 0,
+            ai_analysis: None,
+        };
+        let executed = Arc::new(Mutex::new(Vec::new()));
+        let variables = HashMap::from([("email".to_string(), serde_json::json!("ana@example.com"))]);
+        let mut session = WorkflowDebugSession::new(
+            &workflow,
+            [2],
+            variables,
+            Box::new(FakeRunner { executed: executed.clone() }),
+        );
+        assert_eq!(session.state().status, DebugStatus::Paused);
+        assert!(executed.lock().unwrap().is_empty());
+
+        // Continue stops before the breakpoint step
+        let ran = session.continue_run().unwrap();
+        assert_eq!(ran.len(), 2);
+        let state = session.state();
+        assert_eq!((state.status, state.next_step), (DebugStatus::Paused, 2));
+        assert_eq!(*executed.lock().unwrap(), ["https://shop.example/cart", "ana@example.com"]);
+
+        let snapshot = &ran[1];
+        assert_eq!(snapshot.screenshot.as_deref(), Some(format!("/tmp/debug_{}_step2.png", state.session_id).as_str()));
+        assert_eq!(snapshot.context.current_url.as_deref(), Some("https://shop.example/checkout"));
+        assert_eq!(snapshot.context.steps_completed, 2);
+        assert_eq!(snapshot.context.variables["step2"]["value"], "ana@example.com");
+        assert_eq!(ran[0].context.current_url.as_deref(), Some("https://shop.example/cart"));
+
+        // Single step over the breakpoint, then a failing step ends the run
+        let stepped = session.step().unwrap();
+        assert_eq!(stepped.step_index, 2);
+        assert!(stepped.screenshot.is_some());
+        let failed = session.continue_run().unwrap();
+        assert_eq!(failed.len(), 1);
+        assert!(!failed[0].success);
+        assert_eq!(session.state().status, DebugStatus::Completed);
+        assert!(session.step().is_err());
+
+        let result = session.execution_result();
+        assert!(!result.success);
+        assert_eq!((result.steps_completed, result.steps_failed), (3, 1));
+        assert_eq!(result.screenshots.len(), 4);
+    }
+}
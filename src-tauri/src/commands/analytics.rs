@@ -7,8 +7,23 @@
 #![allow(unused_variables)]
 
 use serde::{Deserialize, Serialize};
-use tauri::command;
-use std::collections::HashMap;
+use tauri::{command, AppHandle, Emitter, Manager, State};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+/// Fastest a widget is pushed to unless it asks for something else
+const DEFAULT_MIN_PUSH_INTERVAL_MS: i64 = 250;
+/// How often the background ticker flushes throttled and interval pushes
+const PUSH_TICK_INTERVAL_MS: u64 = 100;
+/// Recorded points kept per metric; the oldest are dropped first
+const MAX_POINTS_PER_METRIC: usize = 20_000;
+const DAY_MS: i64 = 86_400_000;
+const WEEK_MS: i64 = 7 * DAY_MS;
+/// Autocorrelation at the daily/weekly lag needed to treat a metric as seasonal
+const MIN_SEASONAL_AUTOCORRELATION: f64 = 0.5;
+/// Smallest baseline a point is ever scored against; normally half the window
+const MIN_BASELINE_POINTS: usize = 3;
 
 // ============================================================================
 // Dashboard Types
@@ -532,6 +547,77 @@ pub struct AlertEvent {
     pub created_at: i64,
 }
 
+// ============================================================================
+// Anomaly Detection Types
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnomalySensitivity {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl AnomalySensitivity {
+    /// |z-score| at which a point counts as anomalous
+    pub fn z_threshold(&self) -> f64 {
+        match self {
+            AnomalySensitivity::Low => 4.0,
+            AnomalySensitivity::Medium => 3.0,
+            AnomalySensitivity::High => 2.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalySettings {
+    /// Baseline size in points for the rolling mean/stddev
+    pub window: usize,
+    pub sensitivity: AnomalySensitivity,
+    /// Consecutive anomalous points that raise an alert event
+    pub consecutive_points: usize,
+}
+
+impl Default for AnomalySettings {
+    fn default() -> Self {
+        Self {
+            window: 24,
+            sensitivity: AnomalySensitivity::Medium,
+            consecutive_points: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyMethod {
+    RollingZScore,
+    /// Compared against the same phase of the daily/weekly cycle
+    Seasonal { period_ms: i64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricAnomaly {
+    pub timestamp: i64,
+    pub value: f64,
+    pub expected: f64,
+    pub z_score: f64,
+    pub severity: AlertSeverity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnomalyDetectionResult {
+    pub metric_id: String,
+    pub method: AnomalyMethod,
+    pub points_analyzed: usize,
+    pub threshold: f64,
+    pub anomalies: Vec<MetricAnomaly>,
+    /// Alert events raised by this run for newly sustained anomalies
+    pub alert_events: Vec<AlertEvent>,
+}
+
 // ============================================================================
 // Export Types
 // ============================================================================
@@ -599,6 +685,487 @@ pub struct DateRange {
     pub end: i64,
 }
 
+// ============================================================================
+// Widget Push Subscriptions
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WidgetSubscriptionOptions {
+    /// Metric whose updates are pushed to the widget
+    pub metric_id: Option<String>,
+    /// Periodic refresh in seconds, independent of metric updates
+    pub refresh_interval: Option<i32>,
+    /// Max refresh rate: minimum milliseconds between two pushes
+    pub min_push_interval_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WidgetPushReason {
+    MetricUpdate,
+    Interval,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WidgetPush {
+    pub dashboard_id: String,
+    pub widget_id: String,
+    pub reason: WidgetPushReason,
+    pub data_point: Option<MetricDataPoint>,
+    pub pushed_at: i64,
+}
+
+#[derive(Debug, Clone)]
+struct WidgetSubscription {
+    dashboard_id: String,
+    widget_id: String,
+    metric_id: Option<String>,
+    refresh_interval_ms: Option<i64>,
+    min_push_interval_ms: i64,
+    last_push_at: Option<i64>,
+    last_refresh_at: i64,
+    latest: Option<MetricDataPoint>,
+    pending: bool,
+}
+
+impl WidgetSubscription {
+    fn push(&mut self, reason: WidgetPushReason, now: i64) -> WidgetPush {
+        self.last_push_at = Some(now);
+        self.last_refresh_at = now;
+        self.pending = false;
+        WidgetPush {
+            dashboard_id: self.dashboard_id.clone(),
+            widget_id: self.widget_id.clone(),
+            reason,
+            data_point: self.latest.clone(),
+            pushed_at: now,
+        }
+    }
+
+    fn throttled(&self, now: i64) -> bool {
+        self.last_push_at
+            .is_some_and(|last| now - last < self.min_push_interval_ms)
+    }
+}
+
+/// Tracks which widgets want push updates and decides when to push, so rapid
+/// metric updates are coalesced into at most one push per widget interval.
+#[derive(Default)]
+pub struct WidgetPushHub {
+    // dashboard_id -> widget_id -> subscription
+    subscriptions: HashMap<String, HashMap<String, WidgetSubscription>>,
+}
+
+impl WidgetPushHub {
+    pub fn subscribe(
+        &mut self,
+        dashboard_id: &str,
+        widget_id: &str,
+        options: WidgetSubscriptionOptions,
+        now: i64,
+    ) {
+        let subscription = WidgetSubscription {
+            dashboard_id: dashboard_id.to_string(),
+            widget_id: widget_id.to_string(),
+            metric_id: options.metric_id,
+            refresh_interval_ms: options.refresh_interval
+                .filter(|secs| *secs > 0)
+                .map(|secs| secs as i64 * 1000),
+            min_push_interval_ms: options.min_push_interval_ms
+                .unwrap_or(DEFAULT_MIN_PUSH_INTERVAL_MS)
+                .max(0),
+            last_push_at: None,
+            last_refresh_at: now,
+            latest: None,
+            pending: false,
+        };
+
+        self.subscriptions
+            .entry(dashboard_id.to_string())
+            .or_default()
+            .insert(widget_id.to_string(), subscription);
+    }
+
+    pub fn unsubscribe(&mut self, dashboard_id: &str, widget_id: &str) -> bool {
+        let Some(widgets) = self.subscriptions.get_mut(dashboard_id) else {
+            return false;
+        };
+        let removed = widgets.remove(widget_id).is_some();
+        if widgets.is_empty() {
+            self.subscriptions.remove(dashboard_id);
+        }
+        removed
+    }
+
+    pub fn unsubscribe_dashboard(&mut self, dashboard_id: &str) -> usize {
+        self.subscriptions
+            .remove(dashboard_id)
+            .map(|widgets| widgets.len())
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subscriptions.is_empty()
+    }
+
+    /// Records a new metric value, returning the pushes that may go out right
+    /// away. Throttled widgets keep the latest value and are flushed by `tick`.
+    pub fn on_metric(&mut self, data_point: &MetricDataPoint, now: i64) -> Vec<WidgetPush> {
+        let mut pushes = Vec::new();
+
+        for subscription in self.subscriptions.values_mut().flat_map(|w| w.values_mut()) {
+            if subscription.metric_id.as_deref() != Some(data_point.metric_id.as_str()) {
+                continue;
+            }
+
+            subscription.latest = Some(data_point.clone());
+            if subscription.throttled(now) {
+                subscription.pending = true;
+            } else {
+                pushes.push(subscription.push(WidgetPushReason::MetricUpdate, now));
+            }
+        }
+
+        pushes
+    }
+
+    /// Flushes throttled updates whose interval has elapsed and fires
+    /// periodic refreshes that are due.
+    pub fn tick(&mut self, now: i64) -> Vec<WidgetPush> {
+        let mut pushes = Vec::new();
+
+        for subscription in self.subscriptions.values_mut().flat_map(|w| w.values_mut()) {
+            if subscription.throttled(now) {
+                continue;
+            }
+
+            if subscription.pending {
+                pushes.push(subscription.push(WidgetPushReason::MetricUpdate, now));
+            } else if let Some(interval) = subscription.refresh_interval_ms {
+                if now - subscription.last_refresh_at >= interval {
+                    pushes.push(subscription.push(WidgetPushReason::Interval, now));
+                }
+            }
+        }
+
+        pushes
+    }
+}
+
+#[derive(Default)]
+pub struct DashboardStreamState {
+    pub hub: RwLock<WidgetPushHub>,
+    ticker_running: AtomicBool,
+}
+
+fn emit_widget_pushes(app: &AppHandle, pushes: &[WidgetPush]) {
+    for push in pushes {
+        let _ = app.emit("dashboard-widget-update", push);
+    }
+}
+
+/// Runs while any widget is subscribed; stops itself once the hub is empty.
+fn ensure_push_ticker(app: &AppHandle, state: &DashboardStreamState) {
+    if state.ticker_running.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_millis(PUSH_TICK_INTERVAL_MS)).await;
+
+            let state = app.state::<DashboardStreamState>();
+            let pushes = {
+                let Ok(mut hub) = state.hub.write() else { break };
+                if hub.is_empty() {
+                    state.ticker_running.store(false, Ordering::SeqCst);
+                    break;
+                }
+                hub.tick(chrono::Utc::now().timestamp_millis())
+            };
+            emit_widget_pushes(&app, &pushes);
+        }
+    });
+}
+
+// ============================================================================
+// Metric Storage & Anomaly Detection
+// ============================================================================
+
+/// Recorded metric series plus the anomaly monitors and alert events derived
+/// from them.
+#[derive(Default)]
+pub struct MetricStoreState {
+    series: RwLock<HashMap<String, Vec<MetricDataPoint>>>,
+    // metric_id -> settings of the last explicit detection run
+    monitors: RwLock<HashMap<String, AnomalySettings>>,
+    alert_events: RwLock<Vec<AlertEvent>>,
+    // (metric_id, first timestamp of the run) already alerted on
+    alerted_runs: RwLock<HashSet<(String, i64)>>,
+}
+
+impl MetricStoreState {
+    pub fn record(&self, data_point: &MetricDataPoint) -> Result<(), String> {
+        let mut series = self.series.write().map_err(|e| format!("Lock error: {}", e))?;
+        let points = series.entry(data_point.metric_id.clone()).or_default();
+
+        // Points usually arrive in order; keep the series sorted when they don't
+        let at = points.partition_point(|p| p.timestamp <= data_point.timestamp);
+        points.insert(at, data_point.clone());
+        if points.len() > MAX_POINTS_PER_METRIC {
+            let excess = points.len() - MAX_POINTS_PER_METRIC;
+            points.drain(..excess);
+        }
+        Ok(())
+    }
+
+    pub fn query(&self, metric_id: &str, start_time: i64, end_time: i64) -> Result<Vec<MetricDataPoint>, String> {
+        let series = self.series.read().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(series
+            .get(metric_id)
+            .map(|points| {
+                points
+                    .iter()
+                    .filter(|p| p.timestamp >= start_time && p.timestamp <= end_time)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    pub fn monitor(&self, metric_id: &str) -> Result<Option<AnomalySettings>, String> {
+        let monitors = self.monitors.read().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(monitors.get(metric_id).cloned())
+    }
+
+    /// Scores the recorded series and raises one alert event per sustained
+    /// anomaly that has not been alerted on before. The settings are kept so
+    /// later recordings of the metric are checked the same way.
+    pub fn detect(&self, metric_id: &str, settings: AnomalySettings) -> Result<AnomalyDetectionResult, String> {
+        let points = self.query(metric_id, i64::MIN, i64::MAX)?;
+        let (method, anomalies) = detect_anomalies(&points, &settings);
+
+        let mut alert_events = Vec::new();
+        {
+            let mut alerted = self.alerted_runs.write().map_err(|e| format!("Lock error: {}", e))?;
+            let mut events = self.alert_events.write().map_err(|e| format!("Lock error: {}", e))?;
+            for run in sustained_runs(&points, &anomalies, settings.consecutive_points) {
+                if !alerted.insert((metric_id.to_string(), run[0].timestamp)) {
+                    continue;
+                }
+                let event = anomaly_alert_event(metric_id, run);
+                events.push(event.clone());
+                alert_events.push(event);
+            }
+        }
+
+        self.monitors
+            .write()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .insert(metric_id.to_string(), settings.clone());
+
+        Ok(AnomalyDetectionResult {
+            metric_id: metric_id.to_string(),
+            method,
+            points_analyzed: points.len(),
+            threshold: settings.sensitivity.z_threshold(),
+            anomalies,
+            alert_events,
+        })
+    }
+
+    pub fn events(&self, alert_id: &str, limit: usize) -> Result<Vec<AlertEvent>, String> {
+        let events = self.alert_events.read().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(events
+            .iter()
+            .rev()
+            .filter(|e| e.alert_id == alert_id)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    pub fn acknowledge(&self, event_id: &str, user_id: &str) -> Result<(), String> {
+        let mut events = self.alert_events.write().map_err(|e| format!("Lock error: {}", e))?;
+        let event = events
+            .iter_mut()
+            .find(|e| e.id == event_id)
+            .ok_or("Alert event not found")?;
+        event.acknowledged = true;
+        event.acknowledged_by = Some(user_id.to_string());
+        event.acknowledged_at = Some(chrono::Utc::now().timestamp_millis());
+        Ok(())
+    }
+}
+
+/// Alert id under which anomaly events for a metric are recorded
+pub fn anomaly_alert_id(metric_id: &str) -> String {
+    format!("anomaly:{}", metric_id)
+}
+
+/// Flags points whose deviation from the baseline exceeds the sensitivity's
+/// z-score. Metrics with a strong daily or weekly cycle are deseasonalized
+/// first, so each point is compared against the same phase of the cycle.
+/// Flagged points are left out of later baselines, which keeps a sustained
+/// anomaly from becoming the new normal.
+pub fn detect_anomalies(points: &[MetricDataPoint], settings: &AnomalySettings) -> (AnomalyMethod, Vec<MetricAnomaly>) {
+    let values: Vec<f64> = points.iter().map(|p| p.value).collect();
+    let threshold = settings.sensitivity.z_threshold();
+    let window = settings.window.max(MIN_BASELINE_POINTS);
+    let min_baseline = (window / 2).max(MIN_BASELINE_POINTS);
+
+    let (method, seasonal) = match seasonal_period(points, &values) {
+        Some((period_ms, period)) => (AnomalyMethod::Seasonal { period_ms }, seasonal_profile(&values, period)),
+        None => (AnomalyMethod::RollingZScore, vec![0.0; values.len()]),
+    };
+    let residuals: Vec<f64> = values.iter().zip(&seasonal).map(|(v, s)| v - s).collect();
+
+    let mut baseline: std::collections::VecDeque<f64> = std::collections::VecDeque::with_capacity(window);
+    let mut anomalies = Vec::new();
+    for (i, residual) in residuals.iter().enumerate() {
+        if baseline.len() >= min_baseline {
+            let n = baseline.len() as f64;
+            let mean = baseline.iter().sum::<f64>() / n;
+            let variance = baseline.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            let std_dev = variance.sqrt().max(1e-6 * (1.0 + mean.abs()));
+            let z_score = (residual - mean) / std_dev;
+
+            if z_score.abs() >= threshold {
+                anomalies.push(MetricAnomaly {
+                    timestamp: points[i].timestamp,
+                    value: values[i],
+                    expected: mean + seasonal[i],
+                    z_score,
+                    severity: anomaly_severity(z_score.abs(), threshold),
+                });
+                continue;
+            }
+        }
+
+        if baseline.len() == window {
+            baseline.pop_front();
+        }
+        baseline.push_back(*residual);
+    }
+
+    (method, anomalies)
+}
+
+fn anomaly_severity(z: f64, threshold: f64) -> AlertSeverity {
+    if z >= threshold * 2.0 {
+        AlertSeverity::Critical
+    } else if z >= threshold * 1.5 {
+        AlertSeverity::Error
+    } else {
+        AlertSeverity::Warning
+    }
+}
+
+/// The daily or weekly period (ms, points) with the strongest autocorrelation,
+/// if any is strong enough and the series covers at least three cycles
+fn seasonal_period(points: &[MetricDataPoint], values: &[f64]) -> Option<(i64, usize)> {
+    let mut steps: Vec<i64> = points
+        .windows(2)
+        .map(|w| w[1].timestamp - w[0].timestamp)
+        .filter(|d| *d > 0)
+        .collect();
+    if steps.is_empty() {
+        return None;
+    }
+    steps.sort_unstable();
+    let step = steps[steps.len() / 2];
+
+    [DAY_MS, WEEK_MS]
+        .into_iter()
+        .filter_map(|period_ms| {
+            let lag = usize::try_from(period_ms / step).ok()?;
+            if lag < 2 || values.len() < lag * 3 {
+                return None;
+            }
+            let r = autocorrelation(values, lag);
+            (r >= MIN_SEASONAL_AUTOCORRELATION).then_some((period_ms, lag, r))
+        })
+        .max_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(period_ms, lag, _)| (period_ms, lag))
+}
+
+fn autocorrelation(values: &[f64], lag: usize) -> f64 {
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let denominator: f64 = values.iter().map(|v| (v - mean).powi(2)).sum();
+    if denominator == 0.0 {
+        return 0.0;
+    }
+    let numerator: f64 = values
+        .iter()
+        .zip(&values[lag..])
+        .map(|(a, b)| (a - mean) * (b - mean))
+        .sum();
+    numerator / denominator
+}
+
+/// Per-point seasonal component: the median of all values at the same phase.
+/// The median keeps spikes from leaking into the expected cycle.
+fn seasonal_profile(values: &[f64], period: usize) -> Vec<f64> {
+    let medians: Vec<f64> = (0..period)
+        .map(|phase| {
+            let mut same_phase: Vec<f64> = values.iter().skip(phase).step_by(period).copied().collect();
+            same_phase.sort_by(|a, b| a.total_cmp(b));
+            same_phase[same_phase.len() / 2]
+        })
+        .collect();
+    (0..values.len()).map(|i| medians[i % period]).collect()
+}
+
+/// Runs of at least `min_len` anomalies on consecutive recorded points
+fn sustained_runs<'a>(
+    points: &[MetricDataPoint],
+    anomalies: &'a [MetricAnomaly],
+    min_len: usize,
+) -> Vec<&'a [MetricAnomaly]> {
+    let index: HashMap<i64, usize> = points.iter().enumerate().map(|(i, p)| (p.timestamp, i)).collect();
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for i in 1..=anomalies.len() {
+        let continues = i < anomalies.len()
+            && index[&anomalies[i].timestamp] == index[&anomalies[i - 1].timestamp] + 1;
+        if !continues {
+            if i - start >= min_len.max(1) {
+                runs.push(&anomalies[start..i]);
+            }
+            start = i;
+        }
+    }
+    runs
+}
+
+fn anomaly_alert_event(metric_id: &str, run: &[MetricAnomaly]) -> AlertEvent {
+    let peak = run
+        .iter()
+        .max_by(|a, b| a.z_score.abs().total_cmp(&b.z_score.abs()))
+        .unwrap_or(&run[0]);
+    AlertEvent {
+        id: uuid::Uuid::new_v4().to_string(),
+        alert_id: anomaly_alert_id(metric_id),
+        status: AlertStatus::Alerting,
+        value: peak.value,
+        message: format!(
+            "{:?} anomaly on {}: {} consecutive points off the expected value (peak {:.2} vs {:.2}, z={:.1})",
+            peak.severity, metric_id, run.len(), peak.value, peak.expected, peak.z_score
+        ),
+        acknowledged: false,
+        acknowledged_by: None,
+        acknowledged_at: None,
+        created_at: run[run.len() - 1].timestamp,
+    }
+}
+
+fn emit_alert_events(app: &AppHandle, events: &[AlertEvent]) {
+    for event in events {
+        let _ = app.emit("alert-triggered", event);
+    }
+}
+
 // ============================================================================
 // Dashboard Commands
 // ============================================================================
@@ -707,6 +1274,49 @@ pub async fn dashboard_get_widget_data(
     Ok(serde_json::json!({}))
 }
 
+#[command]
+pub async fn dashboard_subscribe_widget(
+    app: AppHandle,
+    state: State<'_, DashboardStreamState>,
+    dashboard_id: String,
+    widget_id: String,
+    options: Option<WidgetSubscriptionOptions>,
+) -> Result<(), String> {
+    let options = options.unwrap_or(WidgetSubscriptionOptions {
+        metric_id: None,
+        refresh_interval: None,
+        min_push_interval_ms: None,
+    });
+
+    {
+        let mut hub = state.hub.write().map_err(|e| format!("Lock error: {}", e))?;
+        hub.subscribe(&dashboard_id, &widget_id, options, chrono::Utc::now().timestamp_millis());
+    }
+    ensure_push_ticker(&app, &state);
+
+    Ok(())
+}
+
+#[command]
+pub async fn dashboard_unsubscribe_widget(
+    state: State<'_, DashboardStreamState>,
+    dashboard_id: String,
+    widget_id: String,
+) -> Result<bool, String> {
+    let mut hub = state.hub.write().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(hub.unsubscribe(&dashboard_id, &widget_id))
+}
+
+/// Called when a dashboard view closes; drops every widget subscription on it.
+#[command]
+pub async fn dashboard_close(
+    state: State<'_, DashboardStreamState>,
+    dashboard_id: String,
+) -> Result<usize, String> {
+    let mut hub = state.hub.write().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(hub.unsubscribe_dashboard(&dashboard_id))
+}
+
 // ============================================================================
 // Report Commands
 // ============================================================================
@@ -830,31 +1440,105 @@ pub async fn metric_delete(metric_id: String) -> Result<(), String> {
 }
 
 #[command]
-pub async fn metric_record(data_point: MetricDataPoint) -> Result<(), String> {
-    Ok(())
+pub async fn metric_record(
+    app: AppHandle,
+    state: State<'_, DashboardStreamState>,
+    store: State<'_, MetricStoreState>,
+    data_point: MetricDataPoint,
+) -> Result<(), String> {
+    store.record(&data_point)?;
+    let pushes = {
+        let mut hub = state.hub.write().map_err(|e| format!("Lock error: {}", e))?;
+        hub.on_metric(&data_point, chrono::Utc::now().timestamp_millis())
+    };
+    emit_widget_pushes(&app, &pushes);
+    check_anomaly_monitors(&app, &store, [data_point.metric_id.as_str()])
 }
 
 #[command]
-pub async fn metric_record_batch(data_points: Vec<MetricDataPoint>) -> Result<i32, String> {
+pub async fn metric_record_batch(
+    app: AppHandle,
+    state: State<'_, DashboardStreamState>,
+    store: State<'_, MetricStoreState>,
+    data_points: Vec<MetricDataPoint>,
+) -> Result<i32, String> {
+    for point in &data_points {
+        store.record(point)?;
+    }
+    let pushes = {
+        let mut hub = state.hub.write().map_err(|e| format!("Lock error: {}", e))?;
+        let now = chrono::Utc::now().timestamp_millis();
+        data_points.iter().flat_map(|point| hub.on_metric(point, now)).collect::<Vec<_>>()
+    };
+    emit_widget_pushes(&app, &pushes);
+    let metric_ids: HashSet<&str> = data_points.iter().map(|p| p.metric_id.as_str()).collect();
+    check_anomaly_monitors(&app, &store, metric_ids)?;
     Ok(data_points.len() as i32)
 }
 
+/// Re-runs detection for metrics that have an anomaly monitor armed
+fn check_anomaly_monitors<'a>(
+    app: &AppHandle,
+    store: &MetricStoreState,
+    metric_ids: impl IntoIterator<Item = &'a str>,
+) -> Result<(), String> {
+    for metric_id in metric_ids {
+        if let Some(settings) = store.monitor(metric_id)? {
+            let result = store.detect(metric_id, settings)?;
+            emit_alert_events(app, &result.alert_events);
+        }
+    }
+    Ok(())
+}
+
 #[command]
-pub async fn metric_query(query: MetricQuery) -> Result<MetricQueryResult, String> {
+pub async fn metric_query(
+    store: State<'_, MetricStoreState>,
+    query: MetricQuery,
+) -> Result<MetricQueryResult, String> {
+    let data_points = store.query(&query.metric_id, query.start_time, query.end_time)?;
+    let values: Vec<f64> = data_points.iter().map(|p| p.value).collect();
+    let sum: f64 = values.iter().sum();
+    let summary = MetricSummary {
+        count: values.len() as i64,
+        sum,
+        avg: if values.is_empty() { 0.0 } else { sum / values.len() as f64 },
+        min: values.iter().copied().reduce(f64::min).unwrap_or(0.0),
+        max: values.iter().copied().reduce(f64::max).unwrap_or(0.0),
+        last: values.last().copied().unwrap_or(0.0),
+    };
+
     Ok(MetricQueryResult {
         metric_id: query.metric_id,
-        data_points: vec![],
-        summary: MetricSummary {
-            count: 0,
-            sum: 0.0,
-            avg: 0.0,
-            min: 0.0,
-            max: 0.0,
-            last: 0.0,
-        },
+        data_points,
+        summary,
     })
 }
 
+/// Flags anomalous points in a recorded metric and raises alert events for
+/// sustained anomalies. The metric stays monitored with these settings as new
+/// points are recorded.
+#[command]
+pub async fn metric_detect_anomalies(
+    app: AppHandle,
+    store: State<'_, MetricStoreState>,
+    metric_id: String,
+    window: Option<usize>,
+    sensitivity: Option<AnomalySensitivity>,
+    consecutive_points: Option<usize>,
+) -> Result<AnomalyDetectionResult, String> {
+    let defaults = AnomalySettings::default();
+    let settings = AnomalySettings {
+        window: window.unwrap_or(defaults.window),
+        sensitivity: sensitivity.unwrap_or(defaults.sensitivity),
+        consecutive_points: consecutive_points.unwrap_or(defaults.consecutive_points),
+    };
+
+    let result = store.detect(&metric_id, settings)?;
+    emit_alert_events(&app, &result.alert_events);
+    Ok(result)
+}
+
 #[command]
 pub async fn metric_get_latest(
     metric_id: String,
@@ -929,18 +1613,20 @@ pub struct AlertTestResult {
 
 #[command]
 pub async fn alert_get_events(
+    store: State<'_, MetricStoreState>,
     alert_id: String,
     limit: Option<i32>,
 ) -> Result<Vec<AlertEvent>, String> {
-    Ok(vec![])
+    store.events(&alert_id, limit.map(|l| l.max(0) as usize).unwrap_or(100))
 }
 
 #[command]
 pub async fn alert_acknowledge(
+    store: State<'_, MetricStoreState>,
     event_id: String,
     user_id: String,
 ) -> Result<(), String> {
-    Ok(())
+    store.acknowledge(&event_id, &user_id)
 }
 
 #[command]
@@ -1086,3 +1772,154 @@ pub struct RetentionPeriod {
     pub retained: i32,
     pub rate: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(metric_id: &str, value: f64) -> MetricDataPoint {
+        MetricDataPoint {
+            metric_id: metric_id.to_string(),
+            value,
+            timestamp: 0,
+            dimensions: None,
+        }
+    }
+
+    fn options(metric_id: &str, min_push_interval_ms: i64) -> WidgetSubscriptionOptions {
+        WidgetSubscriptionOptions {
+            metric_id: Some(metric_id.to_string()),
+            refresh_interval: None,
+            min_push_interval_ms: Some(min_push_interval_ms),
+        }
+    }
+
+    #[test]
+    fn test_metric_update_pushes_until_unsubscribed() {
+        let mut hub = WidgetPushHub::default();
+        hub.subscribe("d1", "w1", options("cpu", 0), 0);
+
+        let pushes = hub.on_metric(&point("cpu", 42.0), 1_000);
+        assert_eq!(pushes.len(), 1);
+        assert_eq!(pushes[0].widget_id, "w1");
+        assert_eq!(pushes[0].data_point.as_ref().unwrap().value, 42.0);
+
+        assert!(hub.on_metric(&point("memory", 1.0), 2_000).is_empty());
+
+        assert!(hub.unsubscribe("d1", "w1"));
+        assert!(hub.on_metric(&point("cpu", 43.0), 3_000).is_empty());
+        assert!(hub.is_empty());
+    }
+
+    #[test]
+    fn test_rapid_updates_are_coalesced() {
+        let mut hub = WidgetPushHub::default();
+        hub.subscribe("d1", "w1", options("cpu", 500), 0);
+
+        assert_eq!(hub.on_metric(&point("cpu", 1.0), 1_000).len(), 1);
+        assert!(hub.on_metric(&point("cpu", 2.0), 1_100).is_empty());
+        assert!(hub.on_metric(&point("cpu", 3.0), 1_200).is_empty());
+        assert!(hub.tick(1_300).is_empty());
+
+        // Only the latest value goes out once the interval has elapsed
+        let pushes = hub.tick(1_500);
+        assert_eq!(pushes.len(), 1);
+        assert_eq!(pushes[0].data_point.as_ref().unwrap().value, 3.0);
+        assert!(hub.tick(2_500).is_empty());
+    }
+
+    #[test]
+    fn test_interval_refresh_and_dashboard_close() {
+        let mut hub = WidgetPushHub::default();
+        let mut opts = options("cpu", 0);
+        opts.refresh_interval = Some(5);
+        hub.subscribe("d1", "w1", opts, 0);
+        hub.subscribe("d1", "w2", options("cpu", 0), 0);
+
+        assert!(hub.tick(4_999).is_empty());
+        let pushes = hub.tick(5_000);
+        assert_eq!(pushes.len(), 1);
+        assert!(matches!(pushes[0].reason, WidgetPushReason::Interval));
+
+        assert_eq!(hub.unsubscribe_dashboard("d1"), 2);
+        assert!(hub.on_metric(&point("cpu", 1.0), 6_000).is_empty());
+    }
+
+    fn hourly_series(metric_id: &str, values: &[f64]) -> Vec<MetricDataPoint> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| MetricDataPoint {
+                metric_id: metric_id.to_string(),
+                value: *v,
+                timestamp: 1_700_000_000_000 + i as i64 * 3_600_000,
+                dimensions: None,
+            })
+            .collect()
+    }
+
+    /// Deterministic noise in [-1, 1]
+    fn noise(i: usize) -> f64 {
+        ((i as f64 * 12.9898).sin() * 43_758.545).fract()
+    }
+
+    #[test]
+    fn test_injected_spike_is_flagged_and_normal_variation_is_not() {
+        let mut values: Vec<f64> = (0..200).map(|i| 100.0 + 3.0 * noise(i)).collect();
+        values[150] = 160.0;
+        let points = hourly_series("latency", &values);
+
+        let (method, anomalies) = detect_anomalies(&points, &AnomalySettings::default());
+        assert_eq!(method, AnomalyMethod::RollingZScore);
+        assert_eq!(anomalies.len(), 1, "{:?}", anomalies);
+        assert_eq!(anomalies[0].timestamp, points[150].timestamp);
+        assert!(matches!(anomalies[0].severity, AlertSeverity::Critical));
+
+        // A single spike is not sustained, so no alert event
+        let store = MetricStoreState::default();
+        points.iter().for_each(|p| store.record(p).unwrap());
+        let result = store.detect("latency", AnomalySettings::default()).unwrap();
+        assert_eq!(result.anomalies.len(), 1);
+        assert!(result.alert_events.is_empty());
+    }
+
+    #[test]
+    fn test_daily_seasonality_compares_same_phase() {
+        // Busy afternoons, quiet nights over two weeks of hourly points
+        let daily = |i: usize| 500.0 + 400.0 * (i as f64 * std::f64::consts::TAU / 24.0).sin();
+        let mut values: Vec<f64> = (0..336).map(|i| daily(i) + 10.0 * noise(i)).collect();
+        // A daytime-level reading in the middle of the night
+        let night = 24 * 13 + 18;
+        values[night] = 700.0;
+        let points = hourly_series("requests", &values);
+
+        let (method, anomalies) = detect_anomalies(&points, &AnomalySettings::default());
+        assert_eq!(method, AnomalyMethod::Seasonal { period_ms: DAY_MS });
+        assert_eq!(anomalies.iter().map(|a| a.timestamp).collect::<Vec<_>>(), vec![points[night].timestamp]);
+        assert!(anomalies[0].expected < 200.0);
+    }
+
+    #[test]
+    fn test_sustained_anomaly_raises_one_alert_event() {
+        let mut values: Vec<f64> = (0..120).map(|i| 50.0 + 2.0 * noise(i)).collect();
+        for v in values.iter_mut().skip(100).take(4) {
+            *v = 90.0;
+        }
+        let store = MetricStoreState::default();
+        hourly_series("errors", &values).iter().for_each(|p| store.record(p).unwrap());
+
+        let result = store.detect("errors", AnomalySettings::default()).unwrap();
+        assert_eq!(result.anomalies.len(), 4);
+        assert_eq!(result.alert_events.len(), 1);
+        assert_eq!(result.alert_events[0].alert_id, anomaly_alert_id("errors"));
+        assert!(matches!(result.alert_events[0].status, AlertStatus::Alerting));
+
+        // Re-running does not re-alert, and the event is queryable and acknowledgeable
+        assert!(store.detect("errors", AnomalySettings::default()).unwrap().alert_events.is_empty());
+        let events = store.events(&anomaly_alert_id("errors"), 10).unwrap();
+        assert_eq!(events.len(), 1);
+        store.acknowledge(&events[0].id, "ops").unwrap();
+        assert!(store.events(&anomaly_alert_id("errors"), 10).unwrap()[0].acknowledged);
+        assert!(store.monitor("errors").unwrap().is_some());
+    }
+}
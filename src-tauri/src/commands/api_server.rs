@@ -3,7 +3,9 @@ use std::sync::Arc;
 use tokio::sync::{RwLock, Mutex};
 use serde::{Deserialize, Serialize};
 
-use crate::services::api_server::ApiServer;
+use crate::services::admin_metrics_stream::{MetricsHub, SystemProbe, DEFAULT_INTERVAL_MS};
+use crate::services::api_server::{ApiServer, METRICS_STREAM_PATH};
+use crate::services::email_tracking::EmailTrackingService;
 use crate::services::scheduler::WorkflowScheduler;
 use crate::commands::scheduler::SchedulerState;
 
@@ -23,6 +25,8 @@ pub struct ApiServerState {
     pub running: Arc<RwLock<bool>>,
     /// Server configuration (port, secrets)
     pub config: Arc<RwLock<ApiServerConfig>>,
+    /// Metrics stream of the running server
+    pub metrics: Arc<RwLock<Option<Arc<MetricsHub>>>>,
 }
 
 impl ApiServerState {
@@ -33,7 +37,10 @@ impl ApiServerState {
             config: Arc::new(RwLock::new(ApiServerConfig {
                 port: 3001,
                 webhook_secret: "change-this-secret-key".to_string(),
+                metrics_token: uuid::Uuid::new_v4().to_string(),
+                metrics_interval_ms: DEFAULT_INTERVAL_MS,
             })),
+            metrics: Arc::new(RwLock::new(None)),
         }
     }
 }
@@ -43,6 +50,25 @@ impl ApiServerState {
 pub struct ApiServerConfig {
     pub port: u16,
     pub webhook_secret: String,
+    /// Token admin dashboards present to open the metrics stream
+    #[serde(default)]
+    pub metrics_token: String,
+    #[serde(default = "default_metrics_interval_ms")]
+    pub metrics_interval_ms: u64,
+}
+
+fn default_metrics_interval_ms() -> u64 {
+    DEFAULT_INTERVAL_MS
+}
+
+/// Where and how an admin dashboard connects to the metrics stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsStreamInfo {
+    pub url: String,
+    pub token: String,
+    pub interval_ms: u64,
+    pub subscribers: usize,
 }
 
 /// Status of API server
@@ -66,14 +92,17 @@ pub struct ApiServerStatus {
 /// # Arguments
 /// * `state` - API server state containing config and running status
 /// * `scheduler_state` - Shared scheduler state for workflow execution
+/// * `email_tracking` - Email tracking service behind the pixel and redirect endpoints
 /// 
 /// # Returns
 /// * `Ok(())` - Server started successfully
 /// * `Err(String)` - Error message if server failed to start
 #[tauri::command]
 pub async fn api_server_start(
+    app: tauri::AppHandle,
     state: State<'_, ApiServerState>,
     scheduler_state: State<'_, SchedulerState>,
+    email_tracking: State<'_, Arc<EmailTrackingService>>,
 ) -> Result<(), String> {
     let mut running_lock = state.running.write().await;
     
@@ -100,12 +129,18 @@ pub async fn api_server_start(
     // - ApiServer scheduler - Independent instance for HTTP API requests
     // - Both can coordinate via persistent storage (database/filesystem)
     let _ = scheduler_ref; // Acknowledge the shared scheduler exists
+    let email_tracking = email_tracking.inner().clone();
+    let metrics = Arc::new(MetricsHub::new(config.metrics_token.clone(), config.metrics_interval_ms));
+    *state.metrics.write().await = Some(metrics.clone());
     
     // Start server in a separate thread (actix-web handles its own runtime)
     let _server_handle = std::thread::spawn(move || {
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            let server = ApiServer::new(config.port, config.webhook_secret, scheduler_mutex);
+            tokio::spawn(metrics.clone().run(Box::new(SystemProbe::new())));
+            let server = ApiServer::new(config.port, config.webhook_secret, scheduler_mutex)
+                .with_email_tracking(email_tracking, app)
+                .with_metrics_stream(metrics);
             if let Err(e) = server.start().await {
                 eprintln!("API server error: {}", e);
             }
@@ -179,6 +214,14 @@ pub async fn api_server_configure(
         return Err("Webhook secret cannot be empty".to_string());
     }
     
+    // Configs saved before the metrics stream existed keep the generated token
+    let mut new_config = new_config;
+    if new_config.metrics_token.is_empty() {
+        new_config.metrics_token = state.config.read().await.metrics_token.clone();
+    } else if new_config.metrics_token.len() < 16 {
+        return Err("Metrics token must be at least 16 characters".to_string());
+    }
+    
     // Update configuration
     let mut config = state.config.write().await;
     *config = new_config;
@@ -186,6 +229,36 @@ pub async fn api_server_configure(
     Ok(())
 }
 
+/// Get the admin metrics stream endpoint and its token
+#[tauri::command]
+pub async fn api_server_get_metrics_stream(state: State<'_, ApiServerState>) -> Result<MetricsStreamInfo, String> {
+    if !*state.running.read().await {
+        return Err("API server is not running".to_string());
+    }
+    let config = state.config.read().await.clone();
+    let metrics = state.metrics.read().await.clone();
+    
+    Ok(MetricsStreamInfo {
+        url: format!("ws://localhost:{}{}", config.port, METRICS_STREAM_PATH),
+        token: config.metrics_token,
+        interval_ms: metrics.as_ref().map(|m| m.interval_ms()).unwrap_or(config.metrics_interval_ms),
+        subscribers: metrics.as_ref().map(|m| m.subscriber_count()).unwrap_or(0),
+    })
+}
+
+/// Change how often metrics are sampled, applied live to a running server
+#[tauri::command]
+pub async fn api_server_set_metrics_interval(
+    interval_ms: u64,
+    state: State<'_, ApiServerState>,
+) -> Result<(), String> {
+    state.config.write().await.metrics_interval_ms = interval_ms;
+    if let Some(metrics) = state.metrics.read().await.as_ref() {
+        metrics.set_interval_ms(interval_ms);
+    }
+    Ok(())
+}
+
 /// Test API endpoint connectivity
 #[tauri::command]
 pub async fn api_server_test_endpoint(
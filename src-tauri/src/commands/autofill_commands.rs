@@ -14,7 +14,7 @@
 use crate::autofill::{
     create_detector, create_engine, create_formatter, create_validator, AutofillEngine,
     AutofillProfile, AutofillResult, DetectionResult, FieldMapping, FieldMetadata, FieldType,
-    FilledField, FormatterResult, ValidationResult,
+    FilledField, FormatterResult, PhoneNumberFormat, ValidationResult,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -251,6 +251,7 @@ pub async fn af2_detect_field_type(metadata: FieldMetadata) -> Result<(String, f
 pub async fn af2_validate_field(
     value: String,
     field_type: String,
+    country: Option<String>,
     state: State<'_, AutofillCommandState>,
 ) -> Result<ValidationResult, String> {
     let engine = state
@@ -259,6 +260,9 @@ pub async fn af2_validate_field(
         .map_err(|e| format!("Failed to lock engine: {}", e))?;
 
     let field_type_enum = parse_field_type(&field_type)?;
+    if country.is_some() {
+        return Ok(create_validator().validate_for_country(&value, &field_type_enum, country.as_deref()));
+    }
     Ok(engine.validate_field(&value, &field_type_enum))
 }
 
@@ -269,11 +273,11 @@ pub async fn af2_validate_email(email: String) -> Result<ValidationResult, Strin
     Ok(validator.validate(&email, &FieldType::Email))
 }
 
-/// Validate phone number
+/// Validate phone number, optionally for a country (ISO code or name)
 #[tauri::command]
-pub async fn af2_validate_phone(phone: String) -> Result<ValidationResult, String> {
+pub async fn af2_validate_phone(phone: String, country: Option<String>) -> Result<ValidationResult, String> {
     let validator = create_validator();
-    Ok(validator.validate(&phone, &FieldType::Phone))
+    Ok(validator.validate_for_country(&phone, &FieldType::Phone, country.as_deref()))
 }
 
 /// Validate URL
@@ -283,11 +287,14 @@ pub async fn af2_validate_url(url: String) -> Result<ValidationResult, String> {
     Ok(validator.validate(&url, &FieldType::Url))
 }
 
-/// Validate postal code
+/// Validate postal code, optionally for a country (ISO code or name)
 #[tauri::command]
-pub async fn af2_validate_postal_code(postal_code: String) -> Result<ValidationResult, String> {
+pub async fn af2_validate_postal_code(
+    postal_code: String,
+    country: Option<String>,
+) -> Result<ValidationResult, String> {
     let validator = create_validator();
-    Ok(validator.validate(&postal_code, &FieldType::PostalCode))
+    Ok(validator.validate_for_country(&postal_code, &FieldType::PostalCode, country.as_deref()))
 }
 
 /// Validate date
@@ -306,6 +313,7 @@ pub async fn af2_validate_date(date: String) -> Result<ValidationResult, String>
 pub async fn af2_format_field(
     value: String,
     field_type: String,
+    country: Option<String>,
     state: State<'_, AutofillCommandState>,
 ) -> Result<FormatterResult, String> {
     let engine = state
@@ -314,21 +322,27 @@ pub async fn af2_format_field(
         .map_err(|e| format!("Failed to lock engine: {}", e))?;
 
     let field_type_enum = parse_field_type(&field_type)?;
+    if country.is_some() {
+        return Ok(create_formatter().format_for_country(&value, &field_type_enum, country.as_deref()));
+    }
     Ok(engine.format_field(&value, &field_type_enum))
 }
 
-/// Format phone number
+/// Format phone number in national and E.164 form
 #[tauri::command]
-pub async fn af2_format_phone(phone: String) -> Result<FormatterResult, String> {
+pub async fn af2_format_phone(phone: String, country: Option<String>) -> Result<PhoneNumberFormat, String> {
     let formatter = create_formatter();
-    Ok(formatter.format(&phone, &FieldType::Phone))
+    Ok(formatter.format_phone_number(&phone, country.as_deref()))
 }
 
-/// Format postal code
+/// Format postal code, optionally for a country (ISO code or name)
 #[tauri::command]
-pub async fn af2_format_postal_code(postal_code: String) -> Result<FormatterResult, String> {
+pub async fn af2_format_postal_code(
+    postal_code: String,
+    country: Option<String>,
+) -> Result<FormatterResult, String> {
     let formatter = create_formatter();
-    Ok(formatter.format(&postal_code, &FieldType::PostalCode))
+    Ok(formatter.format_for_country(&postal_code, &FieldType::PostalCode, country.as_deref()))
 }
 
 /// Format currency
@@ -361,6 +375,7 @@ pub async fn af2_format_name(name: String) -> Result<FormatterResult, String> {
 pub async fn af2_execute(
     profile_id: String,
     field_mappings: Vec<FieldMapping>,
+    country: Option<String>,
     state: State<'_, AutofillCommandState>,
 ) -> Result<AutofillResult, String> {
     let engine = state
@@ -368,7 +383,7 @@ pub async fn af2_execute(
         .lock()
         .map_err(|e| format!("Failed to lock engine: {}", e))?;
 
-    engine.autofill(&profile_id, field_mappings)
+    engine.autofill_with_country(&profile_id, field_mappings, country.as_deref())
 }
 
 /// Perform smart autofill with automatic field detection
@@ -376,6 +391,7 @@ pub async fn af2_execute(
 pub async fn af2_smart_execute(
     profile_id: String,
     fields_metadata: Vec<FieldMetadata>,
+    country: Option<String>,
     state: State<'_, AutofillCommandState>,
 ) -> Result<AutofillResult, String> {
     let engine = state
@@ -387,7 +403,7 @@ pub async fn af2_smart_execute(
     let detection = engine.detect_fields(fields_metadata);
 
     // Then perform autofill
-    engine.autofill(&profile_id, detection.detected_fields)
+    engine.autofill_with_country(&profile_id, detection.detected_fields, country.as_deref())
 }
 
 /// Preview autofill without applying
@@ -395,6 +411,7 @@ pub async fn af2_smart_execute(
 pub async fn af2_preview(
     profile_id: String,
     field_mappings: Vec<FieldMapping>,
+    country: Option<String>,
     state: State<'_, AutofillCommandState>,
 ) -> Result<Vec<FilledField>, String> {
     let engine = state
@@ -402,7 +419,7 @@ pub async fn af2_preview(
         .lock()
         .map_err(|e| format!("Failed to lock engine: {}", e))?;
 
-    let result = engine.autofill(&profile_id, field_mappings)?;
+    let result = engine.autofill_with_country(&profile_id, field_mappings, country.as_deref())?;
     Ok(result.filled_fields)
 }
 
@@ -17,6 +17,12 @@ pub struct AutofillSystemState {
     engine: Arc<AutofillEngine>,
 }
 
+impl AutofillSystemState {
+    pub fn engine(&self) -> &AutofillEngine {
+        &self.engine
+    }
+}
+
 impl Default for AutofillSystemState {
     fn default() -> Self {
         Self {
@@ -232,6 +238,32 @@ pub async fn autofill_quick_fill(
         .autofill(&profile_id, detection.detected_fields)
 }
 
+// ============================================================================
+// SAVE-NEW-DATA COMMANDS
+// ============================================================================
+
+/// After a form submission, report new or changed values worth saving
+#[tauri::command]
+pub async fn autofill_detect_savable_data(
+    form_values: Vec<SubmittedField>,
+    state: State<'_, AutofillSystemState>,
+) -> CommandResult<SavableDataResult> {
+    state.engine.detect_savable_data(&form_values)
+}
+
+/// Save the submitted values for the profile keys the user picked
+#[tauri::command]
+pub async fn autofill_update_profile_from_form(
+    profile_id: String,
+    form_values: Vec<SubmittedField>,
+    fields: Vec<String>,
+    state: State<'_, AutofillSystemState>,
+) -> CommandResult<AutofillProfile> {
+    state
+        .engine
+        .update_profile_from_form(&profile_id, &form_values, &fields)
+}
+
 // ============================================================================
 // UTILITY COMMANDS
 // ============================================================================
@@ -377,19 +377,54 @@ impl ExecutionContext {
         self.browser_tab_id = Some(tab_id);
         self
     }
+
+    /// Replace `{{variable_name}}` placeholders; null values become empty
+    fn resolve_template(&self, template: &str) -> String {
+        let mut resolved = template.to_string();
+        if !resolved.contains("{{") {
+            return resolved;
+        }
+        for (var_name, var_value) in &self.variables {
+            let placeholder = format!("{{{{{}}}}}", var_name);
+            if resolved.contains(&placeholder) {
+                let value_str = match var_value {
+                    serde_json::Value::String(s) => s.clone(),
+                    serde_json::Value::Null => String::new(),
+                    other => other.to_string(),
+                };
+                resolved = resolved.replace(&placeholder, &value_str);
+            }
+        }
+        resolved
+    }
+
+    /// Action config with variables substituted into selectors and inputs
+    fn resolve_config(&self, config: &NodeConfig) -> NodeConfig {
+        let resolve = |field: &Option<String>| field.as_deref().map(|v| self.resolve_template(v));
+        NodeConfig {
+            url: resolve(&config.url),
+            selector: resolve(&config.selector),
+            text: resolve(&config.text),
+            value: resolve(&config.value),
+            file_path: resolve(&config.file_path),
+            javascript: resolve(&config.javascript),
+            ..config.clone()
+        }
+    }
 }
 
 async fn execute_action(
     node: &FlowNode,
     context: &mut ExecutionContext,
 ) -> Result<serde_json::Value, String> {
-    let config = &node.data.config;
+    let config = &context.resolve_config(&node.data.config);
 
     match config.action_type.as_ref() {
         Some(ActionType::Navigate) => {
             let url = config
                 .url
                 .as_ref()
+                .filter(|url| !url.trim().is_empty())
                 .ok_or("URL is required for navigate action")?;
 
             // Try to use real browser if available in context
@@ -1050,45 +1085,27 @@ async fn execute_storage(
     Ok(result)
 }
 
-// ============================================================================
-// COMMANDS
-// ============================================================================
-
-#[tauri::command]
-pub async fn automation_execute_flow(
-    flow: Flow,
-    state: State<'_, AutomationState>,
-    browser: State<'_, Arc<BrowserService>>,
-) -> Result<FlowExecution, String> {
-    let execution_id = format!("exec_{}", chrono::Utc::now().timestamp_millis());
-    let started_at = chrono::Utc::now().to_rfc3339();
-
-    let mut execution = FlowExecution {
-        id: execution_id.clone(),
-        flow_id: flow.id.clone(),
-        status: ExecutionStatus::Running,
-        started_at: started_at.clone(),
-        completed_at: None,
-        duration: None,
-        steps: Vec::new(),
-        error: None,
-    };
-
-    // Store execution
-    {
-        let mut executions = state.executions.lock().unwrap();
-        executions.insert(execution_id.clone(), execution.clone());
+impl FlowExecution {
+    fn running(id: String, flow_id: String) -> Self {
+        Self {
+            id,
+            flow_id,
+            status: ExecutionStatus::Running,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            completed_at: None,
+            duration: None,
+            steps: Vec::new(),
+            error: None,
+        }
     }
+}
 
-    // Initialize browser tab for the flow
-    let tab_id = browser
-        .new_tab()
-        .map_err(|e| format!("Failed to create browser tab: {}", e))?;
-
-    // Build execution context with browser support
-    let mut context = ExecutionContext::new(flow.variables.clone())
-        .with_browser(browser.inner().clone(), tab_id.clone());
-
+/// Execute a flow's nodes in `context`, completing the given running execution
+async fn run_flow(
+    flow: &Flow,
+    mut context: ExecutionContext,
+    mut execution: FlowExecution,
+) -> FlowExecution {
     // Sort nodes in topological order (simple BFS for now)
     let mut executed_nodes = std::collections::HashSet::new();
     let nodes_to_execute = flow.nodes.clone();
@@ -1175,15 +1192,312 @@ pub async fn automation_execute_flow(
     let completed_at = chrono::Utc::now();
     execution.completed_at = Some(completed_at.to_rfc3339());
 
-    let started = chrono::DateTime::parse_from_rfc3339(&started_at)
-        .map_err(|e| format!("Failed to parse start time: {}", e))?;
-    let started_utc = started.with_timezone(&chrono::Utc);
-    execution.duration = Some((completed_at - started_utc).num_milliseconds() as u64);
+    if let Ok(started) = chrono::DateTime::parse_from_rfc3339(&execution.started_at) {
+        let started_utc = started.with_timezone(&chrono::Utc);
+        execution.duration = Some((completed_at - started_utc).num_milliseconds() as u64);
+    }
 
     if execution.status == ExecutionStatus::Running {
         execution.status = ExecutionStatus::Completed;
     }
 
+    execution
+}
+
+// ============================================================================
+// DATASET-DRIVEN EXECUTION
+// ============================================================================
+
+const MAX_DATASET_ROWS: usize = 10_000;
+const DEFAULT_DATASET_CONCURRENCY: usize = 1;
+const MAX_DATASET_CONCURRENCY: usize = 16;
+
+/// Input rows for a dataset run: inline CSV, a JSON array of objects, or a
+/// table from a SQLite data source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "camelCase")]
+pub enum DatasetInput {
+    Csv {
+        content: String,
+    },
+    Json {
+        rows: serde_json::Value,
+    },
+    DataSource {
+        #[serde(rename = "sourceId")]
+        source_id: String,
+        table: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataTable {
+    pub columns: Vec<String>,
+    pub rows: Vec<HashMap<String, serde_json::Value>>,
+}
+
+impl DataTable {
+    /// Parse CSV with a header row; quoted fields may contain commas, quotes
+    /// ("") and newlines. Short rows leave the missing columns null.
+    pub fn from_csv(content: &str) -> Result<Self, String> {
+        let mut records = parse_csv_records(content)?.into_iter();
+        let columns: Vec<String> = records
+            .next()
+            .ok_or("CSV has no header row")?
+            .into_iter()
+            .map(|c| c.trim().to_string())
+            .collect();
+        if columns.iter().any(|c| c.is_empty()) {
+            return Err("CSV header has an empty column name".to_string());
+        }
+
+        let mut rows = Vec::new();
+        for (i, record) in records.enumerate() {
+            if record.len() > columns.len() {
+                return Err(format!(
+                    "CSV row {} has {} fields, expected {}",
+                    i + 1,
+                    record.len(),
+                    columns.len()
+                ));
+            }
+            let mut row: HashMap<String, serde_json::Value> = columns
+                .iter()
+                .map(|c| (c.clone(), serde_json::Value::Null))
+                .collect();
+            for (column, value) in columns.iter().zip(record) {
+                row.insert(column.clone(), serde_json::Value::String(value));
+            }
+            rows.push(row);
+        }
+
+        Ok(Self { columns, rows })
+    }
+
+    /// Build from a JSON array of objects; columns keep first-seen order
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, String> {
+        let items = value.as_array().ok_or("JSON dataset must be an array of objects")?;
+        let mut columns: Vec<String> = Vec::new();
+        let mut rows = Vec::new();
+
+        for (i, item) in items.iter().enumerate() {
+            let object = item
+                .as_object()
+                .ok_or_else(|| format!("JSON dataset row {} is not an object", i + 1))?;
+            for key in object.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+            rows.push(object.iter().map(|(k, v)| (k.clone(), v.clone())).collect());
+        }
+
+        Ok(Self { columns, rows })
+    }
+}
+
+fn parse_csv_records(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) | ('\r', false) => {
+                record.push(std::mem::take(&mut field));
+                // Skip blank lines
+                if !(record.len() == 1 && record[0].is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                }
+                record.clear();
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("CSV has an unterminated quoted field".to_string());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetRowExecution {
+    #[serde(rename = "rowIndex")]
+    pub row_index: usize,
+    pub variables: HashMap<String, serde_json::Value>,
+    pub status: ExecutionStatus,
+    pub execution: Option<FlowExecution>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetExecution {
+    pub id: String,
+    #[serde(rename = "flowId")]
+    pub flow_id: String,
+    pub status: ExecutionStatus,
+    #[serde(rename = "startedAt")]
+    pub started_at: String,
+    #[serde(rename = "completedAt")]
+    pub completed_at: Option<String>,
+    #[serde(rename = "totalRows")]
+    pub total_rows: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub rows: Vec<DatasetRowExecution>,
+}
+
+/// Run `flow` once per row with the row's columns bound as variables (over
+/// the flow's own variables). At most `max_concurrency` rows run at once.
+/// Without `continue_on_error`, rows not yet started when one fails are skipped.
+async fn run_flow_dataset(
+    flow: &Flow,
+    table: DataTable,
+    max_concurrency: usize,
+    continue_on_error: bool,
+    browser: Option<Arc<BrowserService>>,
+) -> DatasetExecution {
+    use futures::stream::{self, StreamExt};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    let batch_id = format!("batch_{}", chrono::Utc::now().timestamp_millis());
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let total_rows = table.rows.len();
+    let aborted = AtomicBool::new(false);
+
+    let run_row = |row_index: usize, row: HashMap<String, serde_json::Value>| {
+        let browser = browser.clone();
+        let batch_id = &batch_id;
+        let aborted = &aborted;
+        async move {
+            let mut result = DatasetRowExecution {
+                row_index,
+                variables: row.clone(),
+                status: ExecutionStatus::Cancelled,
+                execution: None,
+                error: None,
+            };
+            if aborted.load(Ordering::SeqCst) {
+                return result;
+            }
+
+            let mut context = ExecutionContext::new(flow.variables.clone());
+            context.variables.extend(row);
+            let tab_id = match &browser {
+                Some(browser) => match browser.new_tab() {
+                    Ok(tab_id) => {
+                        context = context.with_browser(browser.clone(), tab_id.clone());
+                        Some(tab_id)
+                    }
+                    Err(e) => {
+                        result.status = ExecutionStatus::Failed;
+                        result.error = Some(format!("Failed to create browser tab: {}", e));
+                        aborted.fetch_or(!continue_on_error, Ordering::SeqCst);
+                        return result;
+                    }
+                },
+                None => None,
+            };
+
+            let execution = FlowExecution::running(format!("{}_row{}", batch_id, row_index), flow.id.clone());
+            let execution = run_flow(flow, context, execution).await;
+
+            if let (Some(browser), Some(tab_id)) = (&browser, &tab_id) {
+                if let Err(e) = browser.close_tab(tab_id) {
+                    eprintln!("Warning: Failed to close browser tab {}: {}", tab_id, e);
+                }
+            }
+
+            // A failed step only fails the row when the flow itself stops on errors
+            let failed_step = execution.steps.iter().find(|s| s.status == "error");
+            result.error = execution
+                .error
+                .as_ref()
+                .map(|e| e.message.clone())
+                .or_else(|| failed_step.and_then(|s| s.error.clone()));
+            result.status = if execution.status == ExecutionStatus::Failed || failed_step.is_some() {
+                ExecutionStatus::Failed
+            } else {
+                execution.status.clone()
+            };
+            if result.status == ExecutionStatus::Failed {
+                aborted.fetch_or(!continue_on_error, Ordering::SeqCst);
+            }
+            result.execution = Some(execution);
+            result
+        }
+    };
+
+    let mut rows: Vec<DatasetRowExecution> = stream::iter(table.rows.into_iter().enumerate())
+        .map(|(row_index, row)| run_row(row_index, row))
+        .buffer_unordered(max_concurrency.clamp(1, MAX_DATASET_CONCURRENCY))
+        .collect()
+        .await;
+    rows.sort_by_key(|r| r.row_index);
+
+    let succeeded = rows.iter().filter(|r| r.status == ExecutionStatus::Completed).count();
+    let failed = rows.iter().filter(|r| r.status == ExecutionStatus::Failed).count();
+
+    DatasetExecution {
+        id: batch_id.clone(),
+        flow_id: flow.id.clone(),
+        status: if failed > 0 { ExecutionStatus::Failed } else { ExecutionStatus::Completed },
+        started_at,
+        completed_at: Some(chrono::Utc::now().to_rfc3339()),
+        total_rows,
+        succeeded,
+        failed,
+        skipped: total_rows - succeeded - failed,
+        rows,
+    }
+}
+
+// ============================================================================
+// COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub async fn automation_execute_flow(
+    flow: Flow,
+    state: State<'_, AutomationState>,
+    browser: State<'_, Arc<BrowserService>>,
+) -> Result<FlowExecution, String> {
+    let execution_id = format!("exec_{}", chrono::Utc::now().timestamp_millis());
+    let execution = FlowExecution::running(execution_id.clone(), flow.id.clone());
+
+    // Store execution
+    {
+        let mut executions = state.executions.lock().unwrap();
+        executions.insert(execution_id.clone(), execution.clone());
+    }
+
+    // Initialize browser tab for the flow
+    let tab_id = browser
+        .new_tab()
+        .map_err(|e| format!("Failed to create browser tab: {}", e))?;
+
+    // Build execution context with browser support
+    let context = ExecutionContext::new(flow.variables.clone())
+        .with_browser(browser.inner().clone(), tab_id.clone());
+
+    let execution = run_flow(&flow, context, execution).await;
+
     // Cleanup: close browser tab
     if let Err(e) = browser.close_tab(&tab_id) {
         eprintln!("Warning: Failed to close browser tab {}: {}", tab_id, e);
@@ -1198,6 +1512,65 @@ pub async fn automation_execute_flow(
     Ok(execution)
 }
 
+/// Run a flow once per row of a CSV/JSON/data-source table, binding each
+/// row's columns as `{{column}}` variables. A failing row is reported without
+/// aborting the batch unless `continue_on_error` is false.
+#[tauri::command]
+pub async fn automation_execute_flow_dataset(
+    flow: Flow,
+    dataset: DatasetInput,
+    max_concurrency: Option<usize>,
+    continue_on_error: Option<bool>,
+    state: State<'_, AutomationState>,
+    data_sources: State<'_, crate::commands::data_sources::DataSourcesState>,
+    browser: State<'_, Arc<BrowserService>>,
+) -> Result<DatasetExecution, String> {
+    let table = match dataset {
+        DatasetInput::Csv { content } => DataTable::from_csv(&content)?,
+        DatasetInput::Json { rows } => DataTable::from_json(&rows)?,
+        DatasetInput::DataSource { source_id, table } => {
+            let path = {
+                let sources = data_sources.sources.lock().unwrap();
+                let source = sources
+                    .get(&source_id)
+                    .ok_or_else(|| format!("Data source not found: {}", source_id))?;
+                source
+                    .config
+                    .get("path")
+                    .and_then(|p| p.as_str())
+                    .ok_or("Data source has no database path")?
+                    .to_string()
+            };
+            let conn = rusqlite::Connection::open_with_flags(&path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+                .map_err(|e| format!("Failed to open database: {}", e))?;
+            let (columns, rows) =
+                crate::services::data_source_cdc::fetch_table(&conn, &table, MAX_DATASET_ROWS + 1)?;
+            DataTable { columns, rows }
+        }
+    };
+    if table.rows.len() > MAX_DATASET_ROWS {
+        return Err(format!("Dataset has more than {} rows", MAX_DATASET_ROWS));
+    }
+
+    let batch = run_flow_dataset(
+        &flow,
+        table,
+        max_concurrency.unwrap_or(DEFAULT_DATASET_CONCURRENCY),
+        continue_on_error.unwrap_or(true),
+        Some(browser.inner().clone()),
+    )
+    .await;
+
+    {
+        let mut executions = state.executions.lock().unwrap();
+        for execution in batch.rows.iter().filter_map(|r| r.execution.as_ref()) {
+            executions.insert(execution.id.clone(), execution.clone());
+        }
+    }
+
+    Ok(batch)
+}
+
 #[tauri::command]
 pub async fn automation_save_flow(
     flow: Flow,
@@ -1296,3 +1669,103 @@ pub async fn automation_record_action(
         None => Err("No recording session active".to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action_node(id: &str, action_type: &str, fields: serde_json::Value) -> FlowNode {
+        let mut config = serde_json::json!({ "action_type": action_type });
+        config.as_object_mut().unwrap().extend(fields.as_object().unwrap().clone());
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "type": "action",
+            "position": { "x": 0.0, "y": 0.0 },
+            "data": { "label": id, "config": config }
+        }))
+        .unwrap()
+    }
+
+    fn signup_flow() -> Flow {
+        Flow {
+            id: "flow_signup".to_string(),
+            name: "Signup".to_string(),
+            description: None,
+            nodes: vec![
+                action_node("open", "navigate", serde_json::json!({ "url": "{{url}}" })),
+                action_node("email", "type", serde_json::json!({ "selector": "#email", "text": "{{email}}" })),
+            ],
+            edges: Vec::new(),
+            variables: vec![FlowVariable { name: "email".to_string(), value: serde_json::json!("default@example.com") }],
+            secrets: Vec::new(),
+            settings: FlowSettings {
+                max_retries: 0,
+                retry_delay: 0,
+                timeout: 1000,
+                continue_on_error: false,
+                log_level: "info".to_string(),
+            },
+            created: None,
+            modified: None,
+            version: 1,
+        }
+    }
+
+    fn output<'a>(row: &'a DatasetRowExecution, node_id: &str) -> &'a serde_json::Value {
+        let execution = row.execution.as_ref().unwrap();
+        execution.steps.iter().find(|s| s.node_id == node_id).unwrap().output.as_ref().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_flow_runs_once_per_row_with_bound_variables() {
+        let table = DataTable::from_json(&serde_json::json!([
+            { "url": "https://a.example", "email": "ann@example.com" },
+            { "url": null, "email": "bob@example.com" },
+            { "url": "https://c.example", "email": "cy@example.com" },
+        ]))
+        .unwrap();
+
+        let batch = run_flow_dataset(&signup_flow(), table, 2, true, None).await;
+
+        assert_eq!(batch.total_rows, 3);
+        assert_eq!(batch.rows.len(), 3);
+        assert_eq!((batch.succeeded, batch.failed, batch.skipped), (2, 1, 0));
+        assert_eq!(batch.status, ExecutionStatus::Failed);
+
+        assert_eq!(batch.rows[0].status, ExecutionStatus::Completed);
+        assert_eq!(output(&batch.rows[0], "open")["url"], "https://a.example");
+        assert_eq!(output(&batch.rows[0], "email")["text"], "ann@example.com");
+        assert_eq!(output(&batch.rows[2], "open")["url"], "https://c.example");
+        assert_eq!(output(&batch.rows[2], "email")["text"], "cy@example.com");
+
+        // The row without a URL fails on its own
+        let failed = &batch.rows[1];
+        assert_eq!(failed.status, ExecutionStatus::Failed);
+        assert!(failed.error.as_deref().unwrap().contains("URL is required"));
+        let error = failed.execution.as_ref().unwrap().error.as_ref().unwrap();
+        assert_eq!(error.node_id.as_deref(), Some("open"));
+    }
+
+    #[tokio::test]
+    async fn test_stop_on_error_skips_remaining_rows() {
+        let table = DataTable::from_csv("url,email\n,ann@example.com\nhttps://b.example,bob@example.com\n").unwrap();
+        let batch = run_flow_dataset(&signup_flow(), table, 1, false, None).await;
+
+        assert_eq!((batch.succeeded, batch.failed, batch.skipped), (0, 1, 1));
+        assert_eq!(batch.rows[1].status, ExecutionStatus::Cancelled);
+        assert!(batch.rows[1].execution.is_none());
+    }
+
+    #[test]
+    fn test_csv_parsing_handles_quotes_and_short_rows() {
+        let table = DataTable::from_csv("name,note\r\n\"Doe, Jane\",\"said \"\"hi\"\"\"\r\n\r\nBob\r\n").unwrap();
+        assert_eq!(table.columns, vec!["name", "note"]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0]["name"], "Doe, Jane");
+        assert_eq!(table.rows[0]["note"], "said \"hi\"");
+        assert_eq!(table.rows[1]["note"], serde_json::Value::Null);
+
+        assert!(DataTable::from_csv("a\n1,2\n").is_err());
+        assert!(DataTable::from_csv("a\n\"open\n").is_err());
+    }
+}
@@ -2,20 +2,41 @@ use std::sync::Arc;
 use tauri::State;
 
 use crate::services::batch_queue_service::{
-    BatchQueueService, BatchQueueStatus, BatchResult, QueueItem,
+    BatchQueueService, BatchQueueStatus, BatchResult, QueueItem, QueueItemPriority,
 };
 
 #[tauri::command]
 pub async fn batch_add_to_queue(
     video_path: String,
     session_name: String,
+    priority: Option<QueueItemPriority>,
     batch_service: State<'_, Arc<BatchQueueService>>,
 ) -> Result<String, String> {
     batch_service
-        .add_to_queue(video_path, session_name)
+        .add_to_queue(video_path, session_name, priority.unwrap_or_default())
         .map_err(|e| format!("Failed to add to queue: {}", e))
 }
 
+#[tauri::command]
+pub async fn batch_set_item_priority(
+    item_id: String,
+    priority: QueueItemPriority,
+    batch_service: State<'_, Arc<BatchQueueService>>,
+) -> Result<QueueItem, String> {
+    batch_service
+        .set_item_priority(&item_id, priority)
+        .map_err(|e| format!("Failed to set priority: {}", e))
+}
+
+#[tauri::command]
+pub async fn batch_set_preemption(
+    enabled: bool,
+    batch_service: State<'_, Arc<BatchQueueService>>,
+) -> Result<(), String> {
+    batch_service.set_preemption(enabled);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn batch_remove_from_queue(
     item_id: String,
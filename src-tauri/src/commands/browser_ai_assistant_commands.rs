@@ -6,7 +6,7 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 use crate::services::browser_ai_assistant::{
     AIBrowserAssistant, AIAssistantSettings, AIModel, Language, SummaryLevel,
-    PageSummary, TranslationResult, FormFillSuggestion, SmartSearchResult,
+    PageSummary, TranslationResult, PageTranslation, PageRestore, FormFillSuggestion, SmartSearchResult,
     QuestionAnswer, ContentAnalysis, AITaskHistory, AIAssistantStats,
 };
 
@@ -126,11 +126,20 @@ pub fn ai_translate_page(
     url: String,
     content: String,
     target_language: Language,
-) -> Result<TranslationResult, String> {
+) -> Result<PageTranslation, String> {
     let assistant = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
     assistant.translate_page(&url, &content, target_language)
 }
 
+#[tauri::command]
+pub fn ai_show_original_page(
+    state: State<AIAssistantState>,
+    url: String,
+) -> Result<PageRestore, String> {
+    let assistant = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    assistant.show_original_page(&url)
+}
+
 #[tauri::command]
 pub fn ai_translate_to_english(
     state: State<AIAssistantState>,
@@ -1,13 +1,30 @@
 // CUBE Nexum - Bookmarks Commands
-// 55 Tauri commands for bookmark management
+// 58 Tauri commands for bookmark management
 
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::services::browser_bookmark_metadata::BookmarkMetadataService;
 use crate::services::browser_bookmarks::{
     BrowserBookmarksService, Bookmark, BookmarkSettings, BookmarkTag,
     BookmarkStats, BookmarkFilter, BookmarkTreeNode, ImportResult,
-    BookmarkType, SortOrder, ViewMode, BookmarkSource
+    BookmarkType, SortOrder, ViewMode, BookmarkSource, DuplicateMergeStrategy, MergeReport,
+    UrlNormalizationRules, NormalizedUrl
 };
 
+/// Fetch the page title and favicon for a new bookmark without blocking creation
+fn spawn_metadata_fetch(app: &AppHandle, bookmark_id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let service = app.state::<BrowserBookmarksService>();
+        let metadata = app.state::<BookmarkMetadataService>();
+        match metadata.populate(&service, &bookmark_id, false).await {
+            Ok(bookmark) => {
+                let _ = app.emit("bookmark-metadata-updated", &bookmark);
+            }
+            Err(e) => log::debug!("Bookmark metadata fetch failed for {}: {}", bookmark_id, e),
+        }
+    });
+}
+
 // ==================== Settings Commands ====================
 
 #[tauri::command]
@@ -32,9 +49,12 @@ pub fn browser_bookmarks_create(
     title: String,
     url: String,
     parent_id: Option<String>,
+    app: AppHandle,
     service: State<'_, BrowserBookmarksService>
 ) -> Result<Bookmark, String> {
-    service.create_bookmark(title, url, parent_id)
+    let bookmark = service.create_bookmark(title, url, parent_id)?;
+    spawn_metadata_fetch(&app, bookmark.id.clone());
+    Ok(bookmark)
 }
 
 #[tauri::command]
@@ -430,6 +450,45 @@ pub fn browser_bookmarks_find_duplicates(
     Ok(service.find_duplicates())
 }
 
+#[tauri::command]
+pub fn browser_bookmarks_merge_duplicates(
+    strategy: DuplicateMergeStrategy,
+    service: State<'_, BrowserBookmarksService>
+) -> Result<MergeReport, String> {
+    service.merge_duplicates(strategy)
+}
+
+#[tauri::command]
+pub fn browser_bookmarks_undo_merge(
+    report: MergeReport,
+    service: State<'_, BrowserBookmarksService>
+) -> Result<u32, String> {
+    service.undo_merge(report)
+}
+
+#[tauri::command]
+pub fn browser_bookmarks_get_normalization_rules(
+    service: State<'_, BrowserBookmarksService>
+) -> Result<UrlNormalizationRules, String> {
+    Ok(service.get_normalization_rules())
+}
+
+#[tauri::command]
+pub fn browser_bookmarks_set_normalization_rules(
+    rules: UrlNormalizationRules,
+    service: State<'_, BrowserBookmarksService>
+) -> Result<(), String> {
+    service.set_normalization_rules(rules)
+}
+
+#[tauri::command]
+pub fn browser_bookmarks_preview_url_normalization(
+    url: String,
+    service: State<'_, BrowserBookmarksService>
+) -> Result<NormalizedUrl, String> {
+    Ok(service.preview_url_normalization(&url))
+}
+
 #[tauri::command]
 pub fn browser_bookmarks_cleanup_orphaned(
     service: State<'_, BrowserBookmarksService>
@@ -442,18 +501,23 @@ pub fn browser_bookmarks_cleanup_orphaned(
 #[tauri::command]
 pub fn browser_bookmarks_quick_add(
     url: String,
-    title: String,
+    title: Option<String>,
+    app: AppHandle,
     service: State<'_, BrowserBookmarksService>
 ) -> Result<Bookmark, String> {
-    // Add to default folder (bookmarks bar)
-    service.create_bookmark(title, url, None)
+    // Add to default folder (bookmarks bar); the URL stands in until the page title arrives
+    let title = title.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| url.clone());
+    let bookmark = service.create_bookmark(title, url, None)?;
+    spawn_metadata_fetch(&app, bookmark.id.clone());
+    Ok(bookmark)
 }
 
 #[tauri::command]
 pub fn browser_bookmarks_quick_add_to_folder(
     url: String,
-    title: String,
+    title: Option<String>,
     folder_name: String,
+    app: AppHandle,
     service: State<'_, BrowserBookmarksService>
 ) -> Result<Bookmark, String> {
     // Find or create folder
@@ -471,7 +535,28 @@ pub fn browser_bookmarks_quick_add_to_folder(
         }
     };
     
-    service.create_bookmark(title, url, Some(target_folder))
+    let title = title.filter(|t| !t.trim().is_empty()).unwrap_or_else(|| url.clone());
+    let bookmark = service.create_bookmark(title, url, Some(target_folder))?;
+    spawn_metadata_fetch(&app, bookmark.id.clone());
+    Ok(bookmark)
+}
+
+#[tauri::command]
+pub async fn browser_bookmarks_refresh_favicon(
+    id: String,
+    service: State<'_, BrowserBookmarksService>,
+    metadata: State<'_, BookmarkMetadataService>
+) -> Result<Bookmark, String> {
+    metadata.populate(&service, &id, true).await
+}
+
+/// Re-fetch favicons that are missing or older than the cache lifetime
+#[tauri::command]
+pub async fn browser_bookmarks_refresh_stale_favicons(
+    service: State<'_, BrowserBookmarksService>,
+    metadata: State<'_, BookmarkMetadataService>
+) -> Result<u32, String> {
+    Ok(metadata.refresh_stale(&service).await)
 }
 
 // ==================== Batch Operations Commands ====================
@@ -1,11 +1,11 @@
 // CUBE Nexum - Downloads Manager Commands
 // Tauri commands for the downloads manager service
 
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 use crate::services::browser_downloads::{
     BrowserDownloadsService, DownloadSettings, Download, DownloadQueue,
     DownloadStats, DownloadFilter, DownloadStatus, DownloadPriority,
-    FileCategory, ScheduleType, BandwidthSchedule
+    FileCategory, ScheduleType, BandwidthSchedule, TorrentSettings
 };
 use std::collections::HashMap;
 
@@ -37,9 +37,14 @@ pub fn download_set_default_directory(
 #[tauri::command]
 pub fn download_set_max_concurrent(
     max: u32,
+    download_id: Option<String>,
     service: State<'_, BrowserDownloadsService>
 ) -> Result<(), String> {
-    service.set_max_concurrent(max)
+    // With a download id, sets how many segments that file is fetched in
+    match download_id {
+        Some(download_id) => service.set_max_segments(&download_id, max).map(|_| ()),
+        None => service.set_max_concurrent(max),
+    }
 }
 
 #[tauri::command]
@@ -76,6 +81,14 @@ pub fn download_remove_blocked_extension(
     service.remove_blocked_extension(ext)
 }
 
+#[tauri::command]
+pub fn download_set_torrent_settings(
+    torrent: TorrentSettings,
+    service: State<'_, BrowserDownloadsService>
+) -> Result<(), String> {
+    service.set_torrent_settings(torrent)
+}
+
 // ==================== Download Operations Commands ====================
 
 #[tauri::command]
@@ -107,9 +120,20 @@ pub fn download_pause(
 #[tauri::command]
 pub fn download_resume(
     download_id: String,
-    service: State<'_, BrowserDownloadsService>
+    app: AppHandle
 ) -> Result<Download, String> {
-    service.resume_download(&download_id)
+    let download = app.state::<BrowserDownloadsService>().resume_download(&download_id)?;
+    // Paused HTTP transfers stop, so continue from the .part files; torrents
+    // pick the status up themselves
+    if download.torrent.is_none() {
+        tauri::async_runtime::spawn(async move {
+            let service = app.state::<BrowserDownloadsService>();
+            if let Err(e) = service.transfer(&download_id).await {
+                log::error!("Download {} failed: {}", download_id, e);
+            }
+        });
+    }
+    Ok(download)
 }
 
 #[tauri::command]
@@ -157,6 +181,61 @@ pub fn download_set_failed(
     service.set_download_failed(&download_id, error)
 }
 
+/// Downloads the file in the background, hashing it as it streams
+#[tauri::command]
+pub fn download_start_transfer(
+    download_id: String,
+    app: AppHandle
+) -> Result<(), String> {
+    tauri::async_runtime::spawn(async move {
+        let service = app.state::<BrowserDownloadsService>();
+        if let Err(e) = service.transfer(&download_id).await {
+            log::error!("Download {} failed: {}", download_id, e);
+        }
+    });
+    Ok(())
+}
+
+// ==================== Torrent Commands ====================
+
+#[tauri::command]
+pub fn download_torrent_select_files(
+    download_id: String,
+    file_indices: Vec<usize>,
+    service: State<'_, BrowserDownloadsService>
+) -> Result<Download, String> {
+    service.select_torrent_files(&download_id, file_indices)
+}
+
+#[tauri::command]
+pub fn download_torrent_stop_seeding(
+    download_id: String,
+    service: State<'_, BrowserDownloadsService>
+) -> Result<Download, String> {
+    service.stop_seeding(&download_id)
+}
+
+// ==================== Integrity Commands ====================
+
+#[tauri::command]
+pub fn download_set_expected_checksum(
+    download_id: String,
+    algorithm: String,
+    value: String,
+    service: State<'_, BrowserDownloadsService>
+) -> Result<Download, String> {
+    service.set_expected_checksum(&download_id, &algorithm, &value)
+}
+
+#[tauri::command]
+pub async fn download_load_checksum_file(
+    download_id: String,
+    checksum_url: String,
+    service: State<'_, BrowserDownloadsService>
+) -> Result<Download, String> {
+    service.load_checksum_file(&download_id, &checksum_url).await
+}
+
 // ==================== Download Management Commands ====================
 
 #[tauri::command]
@@ -181,6 +260,16 @@ pub fn download_get_active(
     service.get_active_downloads()
 }
 
+/// Seconds until the download finishes, from its smoothed speed; null when
+/// the server didn't send a size or the download isn't running
+#[tauri::command]
+pub fn download_get_eta(
+    download_id: String,
+    service: State<'_, BrowserDownloadsService>
+) -> Result<Option<u64>, String> {
+    service.get_eta(&download_id)
+}
+
 #[tauri::command]
 pub fn download_get_by_status(
     status: DownloadStatus,
@@ -179,6 +179,15 @@ pub fn history_search(
     service.search(&query)
 }
 
+#[tauri::command]
+pub fn history_semantic_search(
+    query: String,
+    k: u32,
+    service: State<'_, BrowserHistoryService>
+) -> Vec<SearchResult> {
+    service.semantic_search(&query, k)
+}
+
 #[tauri::command]
 pub fn history_suggest(
     query: String,
@@ -188,6 +197,13 @@ pub fn history_suggest(
     service.suggest(&query, limit)
 }
 
+#[tauri::command]
+pub fn history_rebuild_index(
+    service: State<'_, BrowserHistoryService>
+) -> Result<u32, String> {
+    service.rebuild_index()
+}
+
 // ==================== Tags Commands ====================
 
 #[tauri::command]
@@ -374,6 +390,23 @@ pub fn history_clear(
     service.clear_history(time_range)
 }
 
+#[tauri::command]
+pub fn history_clear_range(
+    start: u64,
+    end: u64,
+    service: State<'_, BrowserHistoryService>
+) -> Result<u32, String> {
+    service.clear_range(start, end)
+}
+
+#[tauri::command]
+pub fn history_clear_last(
+    duration_secs: u64,
+    service: State<'_, BrowserHistoryService>
+) -> Result<u32, String> {
+    service.clear_last(duration_secs)
+}
+
 #[tauri::command]
 pub fn history_clear_domain(
     domain: String,
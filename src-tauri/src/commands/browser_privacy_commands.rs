@@ -6,7 +6,7 @@ use crate::services::browser_privacy::{
     PrivacyDashboardService, PrivacySettings, PrivacyLevel, TrackerType,
     Cookie, SameSite, FingerprintProtection, SitePermissions, PrivacyStats,
     PrivacyReport, DoHProvider, ClearDataOptions, ClearDataResult, BlockedTracker,
-    CookiePolicy, PermissionDefault, TimeRange,
+    CookiePolicy, PermissionDefault, TimeRange, SiteCookiePolicy,
 };
 use std::collections::HashMap;
 
@@ -134,6 +134,88 @@ pub fn privacy_get_cookie_stats(
     service.get_cookie_stats()
 }
 
+// ==================== Cookie Policy Commands ====================
+
+#[tauri::command]
+pub fn privacy_set_cookie_policy(
+    service: State<PrivacyDashboardService>,
+    domain: String,
+    policy: SiteCookiePolicy,
+) -> Result<(), String> {
+    service.set_cookie_policy(&domain, policy)
+}
+
+#[tauri::command]
+pub fn privacy_clear_cookie_policy(
+    service: State<PrivacyDashboardService>,
+    domain: String,
+) -> Result<(), String> {
+    service.clear_cookie_policy(&domain)
+}
+
+#[tauri::command]
+pub fn privacy_set_default_cookie_policy(
+    service: State<PrivacyDashboardService>,
+    policy: SiteCookiePolicy,
+) -> Result<(), String> {
+    service.set_default_cookie_policy(policy)
+}
+
+#[tauri::command]
+pub fn privacy_get_cookie_policy(
+    service: State<PrivacyDashboardService>,
+    domain: String,
+) -> SiteCookiePolicy {
+    service.site_cookie_policy(&domain)
+}
+
+#[tauri::command]
+pub fn privacy_set_keep_signed_in(
+    service: State<PrivacyDashboardService>,
+    domain: String,
+    keep: bool,
+) -> Result<(), String> {
+    service.set_keep_signed_in(&domain, keep)
+}
+
+/// Runs a cookie sweep now; returns how many cookies were deleted
+#[tauri::command]
+pub fn privacy_sweep_cookies(service: State<PrivacyDashboardService>) -> u32 {
+    let removed = service.sweep_cookies(chrono::Utc::now());
+    purge_engine_cookies(&removed);
+    removed.len() as u32
+}
+
+/// Sweeps expired and blocked cookies every `cookie_sweep_interval_minutes`
+pub fn start_cookie_sweep(app: tauri::AppHandle) {
+    use tauri::Manager;
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let minutes = app.state::<PrivacyDashboardService>().get_settings().cookie_sweep_interval_minutes;
+            tokio::time::sleep(std::time::Duration::from_secs(minutes.max(1) as u64 * 60)).await;
+
+            let removed = app.state::<PrivacyDashboardService>().sweep_cookies(chrono::Utc::now());
+            if !removed.is_empty() {
+                log::info!("Cookie sweep deleted {} cookies", removed.len());
+                purge_engine_cookies(&removed);
+            }
+        }
+    });
+}
+
+fn purge_engine_cookies(cookies: &[Cookie]) {
+    use crate::commands::cube_browser_commands::engine_cookies;
+    use crate::services::cube_browser_engine::CUBE_BROWSER;
+
+    let result = CUBE_BROWSER.lock()
+        .map_err(|e| format!("Lock error: {}", e))
+        .and_then(|browser| browser.delete_cookies(&engine_cookies(cookies)));
+    if let Err(e) = result {
+        log::warn!("Failed to delete swept cookies from the engine: {}", e);
+    }
+}
+
 // ==================== Fingerprint Commands ====================
 
 #[tauri::command]
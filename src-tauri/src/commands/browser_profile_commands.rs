@@ -15,6 +15,10 @@
 
 use crate::AppState;
 use crate::database::BrowserProfileRecord;
+use crate::services::cube_browser_engine::{
+    GeolocationCoords, LocaleDetection, LocationOverride, CUBE_BROWSER,
+};
+use crate::services::stealth::{navigator_languages, ProfileFingerprint};
 use serde::{Deserialize, Serialize};
 use tauri::{command, State};
 use std::collections::HashMap;
@@ -79,6 +83,43 @@ pub struct BrowserProfile {
     pub updated_at: String,
 }
 
+impl BrowserProfile {
+    /// Values the stealth script must report so its overrides and noise
+    /// agree with this profile
+    pub fn stealth_fingerprint(&self) -> ProfileFingerprint {
+        let fingerprint = &self.fingerprint;
+        ProfileFingerprint {
+            user_agent: Some(self.user_agent.clone()).filter(|ua| !ua.is_empty()),
+            language: Some(self.language.clone()),
+            timezone: Some(self.timezone.clone()),
+            screen_width: u32::try_from(fingerprint.screen_width).ok(),
+            screen_height: u32::try_from(fingerprint.screen_height).ok(),
+            color_depth: u32::try_from(fingerprint.color_depth).ok(),
+            hardware_concurrency: u32::try_from(fingerprint.hardware_concurrency).ok(),
+            device_memory: u32::try_from(fingerprint.device_memory).ok(),
+            webgl_vendor: Some(fingerprint.webgl_vendor.clone()),
+            webgl_renderer: Some(fingerprint.webgl_renderer.clone()),
+            canvas_noise: Some(fingerprint.canvas_noise),
+            webgl_noise: Some(fingerprint.webgl_noise),
+            audio_noise: Some(fingerprint.audio_noise),
+        }
+    }
+
+    /// Locale, timezone and position pages in this profile's session see;
+    /// languages match what the stealth fingerprint reports
+    pub fn location_override(&self) -> LocationOverride {
+        LocationOverride {
+            geolocation: self.geolocation.as_ref().map(|geo| GeolocationCoords {
+                latitude: geo.latitude,
+                longitude: geo.longitude,
+                accuracy: geo.accuracy,
+            }),
+            languages: Some(navigator_languages(&self.language)).filter(|_| !self.language.is_empty()),
+            timezone: Some(self.timezone.clone()).filter(|tz| !tz.is_empty()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeoLocation {
     pub latitude: f64,
@@ -367,6 +408,16 @@ pub struct UpdateFingerprintRequest {
     pub fingerprint: FingerprintConfig,
 }
 
+/// Result of `test_profile_locale`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileLocaleTest {
+    pub profile_id: String,
+    pub expected: LocationOverride,
+    pub detected: LocaleDetection,
+    pub mismatches: Vec<String>,
+    pub passed: bool,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SetProxyRequest {
     pub profile_id: String,
@@ -736,6 +787,13 @@ pub async fn launch_browser_profile(
     let profile = get_browser_profile(state.clone(), request.profile_id.clone()).await?;
     let now = Utc::now().to_rfc3339();
     
+    // Pages opened in the engine see the profile's timezone and languages
+    if let Ok(browser) = CUBE_BROWSER.lock() {
+        if browser.is_initialized() {
+            browser.set_location_override(None, profile.location_override())?;
+        }
+    }
+    
     // Create session record
     let session = ProfileSession {
         id: Uuid::new_v4().to_string(),
@@ -835,6 +893,35 @@ pub async fn update_profile_fingerprint(
     Ok(profile)
 }
 
+/// Open the profile's locale settings in an engine tab, load a detection
+/// page and compare what it reports (timezone, `Date` offset,
+/// `navigator.language(s)`, `Accept-Language`) with the profile
+#[command]
+pub async fn test_profile_locale(
+    state: State<'_, AppState>,
+    profile_id: String,
+) -> Result<ProfileLocaleTest, String> {
+    let profile = get_browser_profile(state, profile_id.clone()).await?;
+    let expected = profile.location_override();
+
+    let browser = CUBE_BROWSER.lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    let tab = browser.create_tab("about:blank")?;
+    let detected = browser.set_location_override(Some(tab.id.as_str()), expected.clone())
+        .and_then(|_| browser.detect_locale(&tab.id));
+    browser.close_tab(&tab.id)?;
+    let detected = detected?;
+
+    let mismatches = detected.mismatches(&expected);
+    Ok(ProfileLocaleTest {
+        profile_id,
+        expected,
+        detected,
+        passed: mismatches.is_empty(),
+        mismatches,
+    })
+}
+
 /// Generate random fingerprint
 #[command]
 pub async fn generate_random_fingerprint(platform: Option<String>) -> Result<FingerprintConfig, String> {
@@ -1409,6 +1496,7 @@ pub fn get_browser_profile_commands() -> Vec<&'static str> {
         "get_profile_active_sessions",
         // Fingerprint
         "update_profile_fingerprint",
+        "test_profile_locale",
         "generate_random_fingerprint",
         "get_fingerprint_templates",
         // Proxy
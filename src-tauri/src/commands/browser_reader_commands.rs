@@ -7,11 +7,26 @@ use crate::services::browser_reader::{
     BrowserReaderService, ReaderSettings, TTSSettings, CustomTheme,
     ReaderTheme, ReaderFont, TextAlignment, TTSSpeed,
     ParsedArticle, ReadingSession, Annotation, AnnotationType, HighlightColor,
-    TTSPlaybackState, ReaderStats,
+    TTSPlaybackState, ReaderStats, ReaderProgressSync,
 };
+use crate::services::browser_sync::{SyncDataType, SyncService};
 
 pub struct ReaderState(pub Mutex<BrowserReaderService>);
 
+/// Queues the article's progress and annotations for the other devices
+fn queue_progress_sync(service: &BrowserReaderService, sync: &SyncService, article_id: &str) -> Result<(), String> {
+    let settings = sync.get_settings();
+    if !settings.sync_enabled || !settings.sync_reading_list {
+        return Ok(());
+    }
+    let Some(progress) = service.progress_for_sync(article_id, sync.current_device_id()) else {
+        return Ok(());
+    };
+    let data = serde_json::json!({ "kind": "readerProgress", "progress": progress });
+    sync.queue_sync_item(SyncDataType::ReadingList, data)?;
+    Ok(())
+}
+
 // ==================== Settings Commands ====================
 
 #[tauri::command]
@@ -245,13 +260,24 @@ pub fn reader_get_session(
 #[tauri::command]
 pub fn reader_update_progress(
     state: State<ReaderState>,
+    sync: State<SyncService>,
     article_id: String,
     scroll_position: f32,
     time_spent: u64,
 ) -> Result<(), String> {
     let service = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
     service.update_progress(&article_id, scroll_position, time_spent);
-    Ok(())
+    queue_progress_sync(&service, &sync, &article_id)
+}
+
+/// Apply reading progress received from another device; the furthest position wins
+#[tauri::command]
+pub fn reader_apply_synced_progress(
+    state: State<ReaderState>,
+    progress: ReaderProgressSync,
+) -> Result<Option<ReadingSession>, String> {
+    let service = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(service.apply_synced_progress(progress))
 }
 
 #[tauri::command]
@@ -274,6 +300,7 @@ pub fn reader_get_in_progress(state: State<ReaderState>) -> Result<Vec<ReadingSe
 #[tauri::command]
 pub fn reader_create_annotation(
     state: State<ReaderState>,
+    sync: State<SyncService>,
     article_id: String,
     annotation_type: AnnotationType,
     color: HighlightColor,
@@ -284,7 +311,7 @@ pub fn reader_create_annotation(
     paragraph_index: u32,
 ) -> Result<Annotation, String> {
     let service = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-    Ok(service.create_annotation(
+    let annotation = service.create_annotation(
         &article_id,
         annotation_type,
         color,
@@ -293,12 +320,15 @@ pub fn reader_create_annotation(
         start_offset,
         end_offset,
         paragraph_index,
-    ))
+    );
+    queue_progress_sync(&service, &sync, &article_id)?;
+    Ok(annotation)
 }
 
 #[tauri::command]
 pub fn reader_update_annotation(
     state: State<ReaderState>,
+    sync: State<SyncService>,
     article_id: String,
     annotation_id: String,
     note: Option<String>,
@@ -306,17 +336,22 @@ pub fn reader_update_annotation(
 ) -> Result<(), String> {
     let service = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
     service.update_annotation(&article_id, &annotation_id, note, color);
-    Ok(())
+    queue_progress_sync(&service, &sync, &article_id)
 }
 
 #[tauri::command]
 pub fn reader_delete_annotation(
     state: State<ReaderState>,
+    sync: State<SyncService>,
     article_id: String,
     annotation_id: String,
 ) -> Result<bool, String> {
     let service = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-    Ok(service.delete_annotation(&article_id, &annotation_id))
+    let deleted = service.delete_annotation(&article_id, &annotation_id);
+    if deleted {
+        queue_progress_sync(&service, &sync, &article_id)?;
+    }
+    Ok(deleted)
 }
 
 #[tauri::command]
@@ -414,13 +449,19 @@ pub fn reader_generate_css(state: State<ReaderState>) -> Result<String, String>
     Ok(service.generate_css())
 }
 
+/// Minutes to read `word_count` words at the user's measured speed, or the
+/// minutes left in `article_id` when given
 #[tauri::command]
 pub fn reader_estimate_reading_time(
     state: State<ReaderState>,
     word_count: u32,
+    article_id: Option<String>,
 ) -> Result<u32, String> {
     let service = state.0.lock().map_err(|e| format!("Lock error: {}", e))?;
-    Ok(service.estimate_reading_time(word_count))
+    match article_id {
+        Some(id) => service.estimate_time_remaining(&id).ok_or_else(|| format!("Article not found: {}", id)),
+        None => Ok(service.estimate_reading_time(word_count)),
+    }
 }
 
 #[tauri::command]
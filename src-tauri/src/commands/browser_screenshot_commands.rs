@@ -355,6 +355,16 @@ pub async fn browser_screenshot_set_editor_pan(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn browser_screenshot_set_image_data(
+    state: State<'_, ScreenshotState>,
+    screenshot_id: String,
+    data_url: String,
+) -> Result<(), String> {
+    let mut service = state.0.lock().map_err(|e| e.to_string())?;
+    service.set_image_data(&screenshot_id, data_url)
+}
+
 // ==================== Annotation Commands ====================
 
 #[tauri::command]
@@ -7,6 +7,8 @@ use crate::services::browser_sync::{
     SyncAccount, SyncItem, SyncConflict, SyncHistory, SyncStats,
     EncryptionKey, ConflictResolution, SyncExportData,
 };
+use crate::services::sync_key_ring::WrappedDataKey;
+use crate::services::sync_bookmark_merge::{BookmarkMerge, BookmarkNode};
 use std::collections::HashMap;
 
 // ==================== Settings Commands ====================
@@ -180,6 +182,30 @@ pub fn sync_data_type(
     service.sync_data_type(data_type)
 }
 
+#[tauri::command]
+pub fn sync_receive_items(
+    service: State<SyncService>,
+    items: Vec<SyncItem>,
+) -> usize {
+    service.receive_remote_items(items)
+}
+
+// ==================== Bookmark Merge Commands ====================
+
+#[tauri::command]
+pub fn sync_merge_bookmarks(
+    service: State<SyncService>,
+    local: Vec<BookmarkNode>,
+    remote: Vec<BookmarkNode>,
+) -> Result<BookmarkMerge, String> {
+    service.merge_bookmarks(local, remote)
+}
+
+#[tauri::command]
+pub fn sync_get_bookmark_tree(service: State<SyncService>) -> Vec<BookmarkNode> {
+    service.get_bookmark_tree()
+}
+
 // ==================== Conflict Commands ====================
 
 #[tauri::command]
@@ -254,9 +280,26 @@ pub fn sync_get_active_key(service: State<SyncService>) -> Option<EncryptionKey>
     service.get_active_key()
 }
 
+/// Rotate one data type's key, or every key when no type is given
 #[tauri::command]
-pub fn sync_rotate_key(service: State<SyncService>) -> Result<EncryptionKey, String> {
-    service.rotate_encryption_key()
+pub fn sync_rotate_key(
+    service: State<SyncService>,
+    data_type: Option<SyncDataType>,
+) -> Result<EncryptionKey, String> {
+    match data_type {
+        Some(data_type) => service.rotate_data_type_key(data_type).map(|(key, _)| key),
+        None => service.rotate_encryption_key(),
+    }
+}
+
+/// Wrapped current key for a device still on an older version of it
+#[tauri::command]
+pub fn sync_fetch_data_type_key(
+    service: State<SyncService>,
+    device_id: String,
+    data_type: SyncDataType,
+) -> Result<Option<WrappedDataKey>, String> {
+    service.fetch_data_type_key(&device_id, data_type)
 }
 
 #[tauri::command]
@@ -6,7 +6,8 @@ use tauri::State;
 use std::sync::Mutex;
 use crate::services::browser_tab_groups::{
     CubeTabGroups, TabGroup, TabMetadata, TabGroupsConfig,
-    GroupSuggestion, GroupingRule, GroupColor, TabGroupsStats
+    GroupSuggestion, GroupingRule, GroupColor, TabGroupsStats,
+    ChildCloseBehavior, TabTreeNode
 };
 
 pub struct TabGroupsState(pub Mutex<CubeTabGroups>);
@@ -157,10 +158,12 @@ pub async fn tab_groups_register_tab(
     id: String,
     url: String,
     title: String,
+    opener_id: Option<String>,
     state: State<'_, TabGroupsState>
 ) -> Result<String, String> {
     let mut groups = state.0.lock().map_err(|e| e.to_string())?;
-    let tab = TabMetadata::new(id, url, title);
+    let mut tab = TabMetadata::new(id, url, title);
+    tab.opener_id = opener_id;
     Ok(groups.register_tab(tab))
 }
 
@@ -220,6 +223,46 @@ pub async fn tab_groups_get_ungrouped(
     Ok(groups.get_ungrouped_tabs().into_iter().cloned().collect())
 }
 
+// ============ Tab Tree Commands ============
+
+#[tauri::command]
+pub async fn tab_groups_get_tab_tree(
+    state: State<'_, TabGroupsState>
+) -> Result<Vec<TabTreeNode>, String> {
+    let groups = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(groups.get_tab_tree())
+}
+
+#[tauri::command]
+pub async fn tab_groups_set_subtree_collapsed(
+    tab_id: String,
+    collapsed: bool,
+    state: State<'_, TabGroupsState>
+) -> Result<bool, String> {
+    let mut groups = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(groups.set_subtree_collapsed(&tab_id, collapsed))
+}
+
+/// Returns the ids of every tab the caller should close
+#[tauri::command]
+pub async fn tab_groups_close_tab(
+    tab_id: String,
+    behavior: Option<ChildCloseBehavior>,
+    state: State<'_, TabGroupsState>
+) -> Result<Vec<String>, String> {
+    let mut groups = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(groups.close_tab(&tab_id, behavior))
+}
+
+#[tauri::command]
+pub async fn tab_groups_close_subtree(
+    tab_id: String,
+    state: State<'_, TabGroupsState>
+) -> Result<Vec<String>, String> {
+    let mut groups = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(groups.close_subtree(&tab_id))
+}
+
 // ============ Tab Stacking Commands ============
 
 #[tauri::command]
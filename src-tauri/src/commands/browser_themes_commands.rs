@@ -1,7 +1,8 @@
 // CUBE Nexum - Browser Themes Commands
 // Tauri commands for the theming system
 
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
+use crate::commands::cube_web_engine_commands::{apply_reduced_motion, CubeWebEngineGlobalState};
 use super::super::services::browser_themes::{
     BrowserThemesService, BrowserTheme, ThemeSettings,
     ThemeType, ThemeColors, ThemeFonts, ThemeUI, ThemeEffects,
@@ -20,10 +21,20 @@ pub fn themes_get_settings(
 #[tauri::command]
 pub fn themes_update_settings(
     settings: ThemeSettings,
-    service: State<'_, BrowserThemesService>
+    service: State<'_, BrowserThemesService>,
+    app: AppHandle
 ) -> Result<(), String> {
+    let reduce_motion = settings.reduce_motion;
     service.update_settings(settings);
-    Ok(())
+    sync_engine_reduced_motion(&app, reduce_motion)
+}
+
+/// Web pages in the engine follow the browser's reduced-motion setting
+fn sync_engine_reduced_motion(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    match app.try_state::<CubeWebEngineGlobalState>() {
+        Some(engine) => apply_reduced_motion(&engine, app, enabled),
+        None => Ok(()),
+    }
 }
 
 #[tauri::command]
@@ -339,12 +350,16 @@ pub fn themes_toggle_animations(
 
 #[tauri::command]
 pub fn themes_toggle_reduce_motion(
-    service: State<'_, BrowserThemesService>
+    service: State<'_, BrowserThemesService>,
+    app: AppHandle
 ) -> bool {
     let mut settings = service.get_settings();
     settings.reduce_motion = !settings.reduce_motion;
     let enabled = settings.reduce_motion;
     service.update_settings(settings);
+    if let Err(e) = sync_engine_reduced_motion(&app, enabled) {
+        eprintln!("Failed to apply reduced motion to web engine: {}", e);
+    }
     enabled
 }
 
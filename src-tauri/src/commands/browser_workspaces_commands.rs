@@ -5,10 +5,16 @@ use crate::services::browser_workspaces::{
     BrowserWorkspacesService, Workspace, WorkspaceSettings, WorkspaceTab,
     WorkspaceTemplate, WorkspaceSnapshot, WorkspaceStats, QuickSwitchItem,
     WorkspaceIcon, WorkspaceColor, WorkspaceLayout, SwitchAnimation, ProxyConfig,
+    WorkspaceSchedule, ScheduledSwitch,
 };
-use tauri::State;
+use crate::services::browser_tab_suspender::{BrowserTabSuspenderService, SuspendReason};
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, Manager, State};
 use std::sync::Mutex;
 
+/// How often the schedule runner checks for a boundary crossing
+const SCHEDULE_TICK: std::time::Duration = std::time::Duration::from_secs(30);
+
 pub struct WorkspacesState(pub Mutex<BrowserWorkspacesService>);
 
 // ==================== Settings Commands ====================
@@ -552,3 +558,88 @@ pub async fn workspaces_get_layouts() -> Result<Vec<&'static str>, String> {
 pub async fn workspaces_get_animations() -> Result<Vec<&'static str>, String> {
     Ok(vec!["None", "Fade", "Slide", "Scale"])
 }
+
+// ==================== Schedule Commands ====================
+
+#[tauri::command]
+pub async fn workspaces_set_schedule(
+    state: State<'_, WorkspacesState>,
+    workspace_id: String,
+    schedule: Option<WorkspaceSchedule>,
+) -> Result<(), String> {
+    let mut service = state.0.lock().map_err(|e| e.to_string())?;
+    service.set_schedule(&workspace_id, schedule)
+}
+
+#[tauri::command]
+pub async fn workspaces_get_schedules(
+    state: State<'_, WorkspacesState>,
+) -> Result<HashMap<String, WorkspaceSchedule>, String> {
+    let service = state.0.lock().map_err(|e| e.to_string())?;
+    Ok(service.get_schedules())
+}
+
+/// Accepts a switch the schedule proposed in confirmation mode.
+#[tauri::command]
+pub async fn workspaces_confirm_scheduled_switch(
+    app: AppHandle,
+    state: State<'_, WorkspacesState>,
+    workspace_id: String,
+) -> Result<ScheduledSwitch, String> {
+    let (outcome, tabs) = {
+        let mut service = state.0.lock().map_err(|e| e.to_string())?;
+        let outcome = service.apply_scheduled_switch(&workspace_id)?;
+        let tabs = hibernated_tabs(&service, &outcome);
+        (outcome, tabs)
+    };
+    suspend_tabs(&app, tabs);
+    Ok(outcome)
+}
+
+fn hibernated_tabs(service: &BrowserWorkspacesService, outcome: &ScheduledSwitch) -> Vec<WorkspaceTab> {
+    match outcome {
+        ScheduledSwitch::Switched { hibernated_workspace_id: Some(id), .. } => {
+            service.get_workspace(id).map(|w| w.tabs).unwrap_or_default()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Hands the sleeping workspace's tabs to the tab suspender when it is running.
+fn suspend_tabs(app: &AppHandle, tabs: Vec<WorkspaceTab>) {
+    let Some(suspender) = app.try_state::<BrowserTabSuspenderService>() else {
+        return;
+    };
+    for tab in tabs.iter().filter(|t| !t.pinned) {
+        if let Err(e) = suspender.suspend_tab(&tab.id, &tab.url, &tab.title, SuspendReason::Scheduled) {
+            log::debug!("Could not hibernate tab {}: {}", tab.id, e);
+        }
+    }
+}
+
+/// Background loop that applies workspace schedules against the local clock.
+pub fn spawn_schedule_runner(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(SCHEDULE_TICK);
+        loop {
+            interval.tick().await;
+            let Some(state) = app.try_state::<WorkspacesState>() else {
+                continue;
+            };
+
+            let (outcome, tabs) = {
+                let Ok(mut service) = state.0.lock() else {
+                    continue;
+                };
+                let Some(outcome) = service.run_schedule_tick(chrono::Local::now().naive_local()) else {
+                    continue;
+                };
+                let tabs = hibernated_tabs(&service, &outcome);
+                (outcome, tabs)
+            };
+
+            suspend_tabs(&app, tabs);
+            let _ = app.emit("workspace-schedule", &outcome);
+        }
+    });
+}
@@ -3,10 +3,11 @@
 // Tauri commands for the AI-powered virtual call center
 // that competes with RingCentral, Aircall, Five9, etc.
 
+use crate::services::call_transcription::{KeywordAlert, LiveTranscription, Speaker, TranscriptChunk};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::RwLock;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use chrono::{DateTime, Utc};
 
 // =============================================================================
@@ -289,6 +290,9 @@ pub struct CallCenterState {
     pub conversations: RwLock<HashMap<String, Conversation>>,
     pub agents: RwLock<HashMap<String, AIAgent>>,
     pub queues: RwLock<HashMap<String, Queue>>,
+    /// Phrases that alert supervisors when spoken on a call, per queue id
+    pub alert_phrases: RwLock<HashMap<String, Vec<String>>>,
+    pub transcriptions: RwLock<HashMap<String, LiveTranscription>>,
 }
 
 impl Default for CallCenterState {
@@ -298,6 +302,8 @@ impl Default for CallCenterState {
             conversations: RwLock::new(HashMap::new()),
             agents: RwLock::new(HashMap::new()),
             queues: RwLock::new(HashMap::new()),
+            alert_phrases: RwLock::new(HashMap::new()),
+            transcriptions: RwLock::new(HashMap::new()),
         }
     }
 }
@@ -1023,10 +1029,24 @@ pub async fn call_center_sentiment_trend(
             })))
             .collect();
         
+        // Per-speaker averages from call transcripts
+        let mut by_speaker: HashMap<String, (f32, u32)> = HashMap::new();
+        for message in conversation.messages.iter().filter(|m| m.message_type == "transcript") {
+            if let Some(score) = message.sentiment {
+                let entry = by_speaker.entry(message.sender_type.clone()).or_insert((0.0, 0));
+                entry.0 += score;
+                entry.1 += 1;
+            }
+        }
+        let by_speaker: HashMap<String, f32> = by_speaker.into_iter()
+            .map(|(speaker, (total, count))| (speaker, total / count as f32))
+            .collect();
+        
         Ok(serde_json::json!({
             "trend": trend,
             "average": conversation.sentiment.score,
-            "label": conversation.sentiment.label
+            "label": conversation.sentiment.label,
+            "bySpeaker": by_speaker
         }))
     } else {
         Err("Conversation not found".to_string())
@@ -1061,9 +1081,10 @@ pub async fn call_center_answer_call(
 #[tauri::command]
 pub async fn call_center_end_call(
     call_sid: String,
+    state: State<'_, CallCenterState>,
 ) -> Result<(), String> {
     // In production, this would use Twilio API
-    Ok(())
+    finish_transcription(&call_sid, &state)
 }
 
 #[tauri::command]
@@ -1078,14 +1099,182 @@ pub async fn call_center_transfer_call(
 #[tauri::command]
 pub async fn call_center_call_transcription(
     call_sid: String,
+    state: State<'_, CallCenterState>,
 ) -> Result<serde_json::Value, String> {
-    // In production, this would use speech-to-text API
+    let transcriptions = state.transcriptions.read().map_err(|e| e.to_string())?;
+    let transcription = transcriptions.get(&call_sid)
+        .ok_or_else(|| "No transcription for this call".to_string())?;
+    
     Ok(serde_json::json!({
-        "transcript": "Hello, thank you for calling...",
-        "segments": []
+        "transcript": transcription.transcript(),
+        "segments": transcription.segments,
+        "alerts": transcription.alerts,
+        "speakerSentiment": transcription.speaker_sentiment,
+        "finished": transcription.finished
     }))
 }
 
+/// Begin collecting streamed speech-to-text for a call
+#[tauri::command]
+pub async fn call_center_start_transcription(
+    call_sid: String,
+    conversation_id: Option<String>,
+    queue_id: Option<String>,
+    state: State<'_, CallCenterState>,
+) -> Result<(), String> {
+    let alert_phrases = match &queue_id {
+        Some(queue_id) => state.alert_phrases.read().map_err(|e| e.to_string())?
+            .get(queue_id)
+            .cloned()
+            .unwrap_or_default(),
+        None => Vec::new(),
+    };
+    
+    state.transcriptions.write().map_err(|e| e.to_string())?
+        .insert(call_sid.clone(), LiveTranscription::new(call_sid, conversation_id, queue_id, alert_phrases));
+    
+    Ok(())
+}
+
+/// Feed a recognizer fragment; alert phrases are pushed to supervisors as
+/// `call-center-keyword-alert` events
+#[tauri::command]
+pub async fn call_center_push_transcript_chunk(
+    app: AppHandle,
+    call_sid: String,
+    chunk: TranscriptChunk,
+    state: State<'_, CallCenterState>,
+) -> Result<Vec<KeywordAlert>, String> {
+    let alerts = {
+        let mut transcriptions = state.transcriptions.write().map_err(|e| e.to_string())?;
+        let transcription = transcriptions.get_mut(&call_sid)
+            .ok_or_else(|| "No transcription for this call".to_string())?;
+        let alerts = transcription.push_chunk(chunk);
+        let _ = app.emit("call-center-transcript-update", serde_json::json!({
+            "callSid": call_sid,
+            "segment": transcription.segments.last()
+        }));
+        alerts
+    };
+    
+    for alert in &alerts {
+        let _ = app.emit("call-center-keyword-alert", alert);
+    }
+    
+    Ok(alerts)
+}
+
+#[tauri::command]
+pub async fn call_center_set_alert_phrases(
+    queue_id: String,
+    phrases: Vec<String>,
+    state: State<'_, CallCenterState>,
+) -> Result<Vec<String>, String> {
+    let mut phrases: Vec<String> = phrases.into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    let mut seen = std::collections::HashSet::new();
+    phrases.retain(|p| seen.insert(p.to_lowercase()));
+    
+    state.alert_phrases.write().map_err(|e| e.to_string())?
+        .insert(queue_id.clone(), phrases.clone());
+    
+    // Calls already in progress on the queue pick up the new list
+    let mut transcriptions = state.transcriptions.write().map_err(|e| e.to_string())?;
+    for transcription in transcriptions.values_mut() {
+        if transcription.queue_id.as_deref() == Some(queue_id.as_str()) && !transcription.finished {
+            transcription.alert_phrases = phrases.clone();
+        }
+    }
+    
+    Ok(phrases)
+}
+
+#[tauri::command]
+pub async fn call_center_get_alert_phrases(
+    queue_id: String,
+    state: State<'_, CallCenterState>,
+) -> Result<Vec<String>, String> {
+    let alert_phrases = state.alert_phrases.read().map_err(|e| e.to_string())?;
+    Ok(alert_phrases.get(&queue_id).cloned().unwrap_or_default())
+}
+
+/// Score the finished call and append its transcript to the linked conversation
+fn finish_transcription(call_sid: &str, state: &CallCenterState) -> Result<(), String> {
+    let transcription = {
+        let mut transcriptions = state.transcriptions.write().map_err(|e| e.to_string())?;
+        match transcriptions.get_mut(call_sid) {
+            Some(transcription) if !transcription.finished => {
+                transcription.finish();
+                transcription.clone()
+            }
+            _ => return Ok(()),
+        }
+    };
+    
+    let Some(conversation_id) = transcription.conversation_id.as_ref() else {
+        return Ok(());
+    };
+    let mut conversations = state.conversations.write().map_err(|e| e.to_string())?;
+    let Some(conversation) = conversations.get_mut(conversation_id) else {
+        return Ok(());
+    };
+    
+    let now = Utc::now().to_rfc3339();
+    for segment in &transcription.segments {
+        let (sender_id, sender_type, sender_name) = match segment.speaker {
+            Speaker::Agent => (
+                conversation.agent.as_ref().map(|a| a.id.clone()).unwrap_or_else(|| "agent".to_string()),
+                "agent",
+                conversation.agent.as_ref().map(|a| a.name.clone()).unwrap_or_else(|| "Agent".to_string()),
+            ),
+            Speaker::Caller => (
+                conversation.customer.id.clone(),
+                "customer",
+                conversation.customer.name.clone().unwrap_or_else(|| "Caller".to_string()),
+            ),
+        };
+        let mut metadata = HashMap::new();
+        metadata.insert("callSid".to_string(), serde_json::json!(call_sid));
+        metadata.insert("startMs".to_string(), serde_json::json!(segment.start_ms));
+        metadata.insert("endMs".to_string(), serde_json::json!(segment.end_ms));
+        metadata.insert("overlap".to_string(), serde_json::json!(segment.overlap));
+        
+        conversation.messages.push(Message {
+            id: format!("msg_{}", uuid::Uuid::new_v4()),
+            conversation_id: conversation_id.clone(),
+            sender_id,
+            sender_type: sender_type.to_string(),
+            sender_name,
+            message_type: "transcript".to_string(),
+            content: segment.text.clone(),
+            attachments: Vec::new(),
+            status: "delivered".to_string(),
+            delivered_at: Some(now.clone()),
+            read_at: None,
+            ai_generated: false,
+            ai_confidence: None,
+            sentiment: segment.sentiment,
+            timestamp: now.clone(),
+            metadata,
+        });
+    }
+    
+    // The caller's mood drives the conversation's overall sentiment
+    if let Some(caller) = transcription.speaker_sentiment.iter().find(|s| s.speaker == Speaker::Caller) {
+        conversation.sentiment.score = caller.score;
+        conversation.sentiment.label = caller.label.clone();
+    }
+    conversation.metadata.insert(
+        "speakerSentiment".to_string(),
+        serde_json::to_value(&transcription.speaker_sentiment).map_err(|e| e.to_string())?,
+    );
+    conversation.last_message_at = now;
+    
+    Ok(())
+}
+
 // =============================================================================
 // COMMANDS - WHATSAPP
 // =============================================================================
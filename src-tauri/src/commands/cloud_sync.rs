@@ -33,6 +33,9 @@ pub struct BillingInfo {
     pub currency: String,
     pub payment_method: Option<PaymentMethod>,
     pub invoices: Vec<Invoice>,
+    /// Backup storage quota for the plan; falls back to the plan default when absent
+    #[serde(default)]
+    pub storage_quota_bytes: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,6 +77,67 @@ pub struct CloudBackup {
     pub is_automatic: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageUsage {
+    pub used_bytes: u64,
+    pub quota_bytes: u64,
+    pub available_bytes: u64,
+    pub backup_count: usize,
+    pub percent_used: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudBackupList {
+    pub backups: Vec<CloudBackup>,
+    pub usage: StorageUsage,
+}
+
+/// Limits applied after every backup; the oldest backups beyond either limit are pruned.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupRetentionPolicy {
+    pub max_backups: Option<usize>,
+    pub max_total_bytes: Option<u64>,
+}
+
+impl Default for BackupRetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_backups: Some(10),
+            max_total_bytes: None,
+        }
+    }
+}
+
+/// Returned (as a string) when a new backup would not fit in the plan quota.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaExceeded {
+    pub required_bytes: u64,
+    pub used_bytes: u64,
+    pub quota_bytes: u64,
+    pub suggested_deletions: Vec<String>,
+}
+
+impl std::fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "QuotaExceeded: backup needs {} bytes but only {} of {} bytes are available",
+            self.required_bytes,
+            self.quota_bytes.saturating_sub(self.used_bytes),
+            self.quota_bytes
+        )?;
+        if self.suggested_deletions.is_empty() {
+            write!(f, "; upgrade your plan to store larger backups")
+        } else {
+            write!(
+                f,
+                "; delete these old backups to free space: {}",
+                self.suggested_deletions.join(", ")
+            )
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncConfig {
     pub sync_enabled: bool,
@@ -128,6 +192,7 @@ pub struct CloudSyncState {
     pub user_profile: Mutex<Option<UserProfile>>,
     pub devices: Mutex<Vec<SyncedDevice>>,
     pub auth_token: Mutex<Option<String>>,
+    pub retention_policy: Mutex<BackupRetentionPolicy>,
 }
 
 impl Default for CloudSyncState {
@@ -137,6 +202,7 @@ impl Default for CloudSyncState {
             user_profile: Mutex::new(None),
             devices: Mutex::new(Vec::new()),
             auth_token: Mutex::new(None),
+            retention_policy: Mutex::new(BackupRetentionPolicy::default()),
         }
     }
 }
@@ -147,6 +213,7 @@ impl Default for CloudSyncState {
 
 const CLOUD_API_URL: &str = "https://admin.cube-elite.com/api/v1";
 const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+const MB: u64 = 1024 * 1024;
 
 // ============================================================================
 // HELPER FUNCTIONS
@@ -207,6 +274,168 @@ async fn get_auth_token(state: &State<'_, CloudSyncState>) -> Result<String, Str
     token.clone().ok_or("Not authenticated".to_string())
 }
 
+// ============================================================================
+// STORAGE ACCOUNTING
+// ============================================================================
+
+/// Quota from billing info, or the default for the plan when the server omits it
+fn plan_storage_quota(billing: &BillingInfo) -> u64 {
+    if let Some(quota) = billing.storage_quota_bytes {
+        return quota;
+    }
+    match billing.plan_id.to_lowercase().as_str() {
+        "elite" | "enterprise" => 50 * 1024 * MB,
+        "pro" | "professional" => 5 * 1024 * MB,
+        "starter" | "basic" => 1024 * MB,
+        _ => 100 * MB,
+    }
+}
+
+fn compute_storage_usage(backups: &[CloudBackup], quota_bytes: u64) -> StorageUsage {
+    let used_bytes: u64 = backups.iter().map(|b| b.size_bytes).sum();
+    let percent_used = if quota_bytes == 0 {
+        100.0
+    } else {
+        (used_bytes as f64 / quota_bytes as f64 * 100.0).min(100.0)
+    };
+    StorageUsage {
+        used_bytes,
+        quota_bytes,
+        available_bytes: quota_bytes.saturating_sub(used_bytes),
+        backup_count: backups.len(),
+        percent_used,
+    }
+}
+
+/// Backups ordered newest first; unparseable timestamps sort as oldest
+fn sort_newest_first(backups: &[CloudBackup]) -> Vec<&CloudBackup> {
+    let timestamp = |b: &CloudBackup| {
+        chrono::DateTime::parse_from_rfc3339(&b.created_at)
+            .map(|t| t.timestamp_millis())
+            .unwrap_or(i64::MIN)
+    };
+    let mut sorted: Vec<&CloudBackup> = backups.iter().collect();
+    sorted.sort_by(|a, b| {
+        timestamp(b)
+            .cmp(&timestamp(a))
+            .then_with(|| b.created_at.cmp(&a.created_at))
+    });
+    sorted
+}
+
+/// Rejects a backup of `required_bytes` that would not fit, suggesting the
+/// oldest backups whose removal would free enough space
+fn check_backup_quota(
+    backups: &[CloudBackup],
+    required_bytes: u64,
+    quota_bytes: u64,
+) -> Result<(), QuotaExceeded> {
+    let used_bytes: u64 = backups.iter().map(|b| b.size_bytes).sum();
+    if used_bytes.saturating_add(required_bytes) <= quota_bytes {
+        return Ok(());
+    }
+
+    let mut suggested_deletions = Vec::new();
+    if required_bytes <= quota_bytes {
+        let mut remaining = used_bytes;
+        for backup in sort_newest_first(backups).into_iter().rev() {
+            if remaining.saturating_add(required_bytes) <= quota_bytes {
+                break;
+            }
+            remaining = remaining.saturating_sub(backup.size_bytes);
+            suggested_deletions.push(backup.backup_id.clone());
+        }
+    }
+
+    Err(QuotaExceeded {
+        required_bytes,
+        used_bytes,
+        quota_bytes,
+        suggested_deletions,
+    })
+}
+
+/// IDs of the backups that fall outside the retention policy, keeping the newest
+fn backups_to_prune(backups: &[CloudBackup], policy: &BackupRetentionPolicy) -> Vec<String> {
+    let mut kept_bytes: u64 = 0;
+    let mut pruning = false;
+    sort_newest_first(backups)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, backup)| {
+            // Once a limit is hit every older backup goes too, so the kept set stays the newest N
+            pruning = pruning
+                || policy.max_backups.is_some_and(|max| index >= max)
+                || policy
+                    .max_total_bytes
+                    .is_some_and(|max| kept_bytes.saturating_add(backup.size_bytes) > max);
+            if pruning {
+                Some(backup.backup_id.clone())
+            } else {
+                kept_bytes += backup.size_bytes;
+                None
+            }
+        })
+        .collect()
+}
+
+async fn fetch_backups(client: &reqwest::Client, auth_token: &str) -> Result<Vec<CloudBackup>, String> {
+    let response = client
+        .get(format!("{}/backups", CLOUD_API_URL))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch backups: {}", e))?;
+    
+    if !response.status().is_success() {
+        return Err("Failed to fetch backups".to_string());
+    }
+    
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse backups: {}", e))
+}
+
+async fn fetch_storage_quota(client: &reqwest::Client, auth_token: &str) -> Result<u64, String> {
+    let response = client
+        .get(format!("{}/billing", CLOUD_API_URL))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch billing info: {}", e))?;
+    
+    if !response.status().is_success() {
+        return Err("Failed to fetch billing information".to_string());
+    }
+    
+    let billing: BillingInfo = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse billing info: {}", e))?;
+    
+    Ok(plan_storage_quota(&billing))
+}
+
+async fn delete_backup_remote(
+    client: &reqwest::Client,
+    auth_token: &str,
+    backup_id: &str,
+) -> Result<(), String> {
+    let response = client
+        .delete(format!("{}/backups/{}", CLOUD_API_URL, backup_id))
+        .header("Authorization", format!("Bearer {}", auth_token))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to delete backup: {}", e))?;
+    
+    if !response.status().is_success() {
+        return Err("Failed to delete backup".to_string());
+    }
+    
+    Ok(())
+}
+
 // ============================================================================
 // COMMANDS - AUTHENTICATION
 // ============================================================================
@@ -691,27 +920,15 @@ pub async fn remove_device(
 #[tauri::command]
 pub async fn get_cloud_backups(
     state: State<'_, CloudSyncState>,
-) -> Result<Vec<CloudBackup>, String> {
+) -> Result<CloudBackupList, String> {
     let auth_token = get_auth_token(&state).await?;
     
     let client = reqwest::Client::new();
-    let response = client
-        .get(format!("{}/backups", CLOUD_API_URL))
-        .header("Authorization", format!("Bearer {}", auth_token))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch backups: {}", e))?;
-    
-    if !response.status().is_success() {
-        return Err("Failed to fetch backups".to_string());
-    }
+    let backups = fetch_backups(&client, &auth_token).await?;
+    let quota_bytes = fetch_storage_quota(&client, &auth_token).await?;
+    let usage = compute_storage_usage(&backups, quota_bytes);
     
-    let backups: Vec<CloudBackup> = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse backups: {}", e))?;
-    
-    Ok(backups)
+    Ok(CloudBackupList { backups, usage })
 }
 
 #[tauri::command]
@@ -721,14 +938,22 @@ pub async fn create_cloud_backup(
     settings: SyncedSettings,
 ) -> Result<CloudBackup, String> {
     let auth_token = get_auth_token(&state).await?;
+    let size_bytes = serde_json::to_vec(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?
+        .len() as u64;
     
     let client = reqwest::Client::new();
+    let existing = fetch_backups(&client, &auth_token).await?;
+    let quota_bytes = fetch_storage_quota(&client, &auth_token).await?;
+    check_backup_quota(&existing, size_bytes, quota_bytes).map_err(|e| e.to_string())?;
+    
     let response = client
         .post(format!("{}/backups", CLOUD_API_URL))
         .header("Authorization", format!("Bearer {}", auth_token))
         .json(&serde_json::json!({
             "name": name,
             "settings": settings,
+            "size_bytes": size_bytes,
             "is_automatic": false
         }))
         .send()
@@ -739,14 +964,48 @@ pub async fn create_cloud_backup(
         return Err("Failed to create backup".to_string());
     }
     
-    let backup: CloudBackup = response
+    let mut backup: CloudBackup = response
         .json()
         .await
         .map_err(|e| format!("Failed to parse backup: {}", e))?;
+    if backup.size_bytes == 0 {
+        backup.size_bytes = size_bytes;
+    }
+    
+    let policy = state.retention_policy.lock().await.clone();
+    let mut all_backups = existing;
+    all_backups.retain(|b| b.backup_id != backup.backup_id);
+    all_backups.push(backup.clone());
+    for backup_id in backups_to_prune(&all_backups, &policy) {
+        if backup_id == backup.backup_id {
+            continue;
+        }
+        if let Err(e) = delete_backup_remote(&client, &auth_token, &backup_id).await {
+            log::warn!("Retention pruning of backup {} failed: {}", backup_id, e);
+        }
+    }
     
     Ok(backup)
 }
 
+#[tauri::command]
+pub async fn get_backup_retention_policy(
+    state: State<'_, CloudSyncState>,
+) -> Result<BackupRetentionPolicy, String> {
+    Ok(state.retention_policy.lock().await.clone())
+}
+
+#[tauri::command]
+pub async fn set_backup_retention_policy(
+    state: State<'_, CloudSyncState>,
+    policy: BackupRetentionPolicy,
+) -> Result<(), String> {
+    if policy.max_backups == Some(0) {
+        return Err("Retention policy must keep at least one backup".to_string());
+    }
+    *state.retention_policy.lock().await = policy;
+    Ok(())
+}
 #[tauri::command]
 pub async fn restore_cloud_backup(
     state: State<'_, CloudSyncState>,
@@ -782,16 +1041,67 @@ pub async fn delete_cloud_backup(
     let auth_token = get_auth_token(&state).await?;
     
     let client = reqwest::Client::new();
-    let response = client
-        .delete(format!("{}/backups/{}", CLOUD_API_URL, backup_id))
-        .header("Authorization", format!("Bearer {}", auth_token))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to delete backup: {}", e))?;
-    
-    if !response.status().is_success() {
-        return Err("Failed to delete backup".to_string());
+    delete_backup_remote(&client, &auth_token, &backup_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup(id: &str, created_at: &str, size_bytes: u64) -> CloudBackup {
+        CloudBackup {
+            backup_id: id.to_string(),
+            name: id.to_string(),
+            created_at: created_at.to_string(),
+            size_bytes,
+            data_types: vec!["settings".to_string()],
+            is_automatic: false,
+        }
+    }
+
+    fn sample_backups() -> Vec<CloudBackup> {
+        vec![
+            backup("b2", "2026-02-01T00:00:00Z", 300),
+            backup("b1", "2026-01-01T00:00:00Z", 200),
+            backup("b4", "2026-04-01T00:00:00Z", 100),
+            backup("b3", "2026-03-01T00:00:00Z", 400),
+        ]
+    }
+
+    #[test]
+    fn test_usage_totals() {
+        let usage = compute_storage_usage(&sample_backups(), 2000);
+        assert_eq!(usage.used_bytes, 1000);
+        assert_eq!(usage.available_bytes, 1000);
+        assert_eq!(usage.backup_count, 4);
+        assert!((usage.percent_used - 50.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_quota_rejection_suggests_oldest() {
+        let backups = sample_backups();
+        assert!(check_backup_quota(&backups, 500, 1500).is_ok());
+
+        let err = check_backup_quota(&backups, 700, 1500).unwrap_err();
+        assert_eq!(err.used_bytes, 1000);
+        assert_eq!(err.suggested_deletions, vec!["b1".to_string()]);
+        assert!(err.to_string().starts_with("QuotaExceeded"));
+
+        let err = check_backup_quota(&backups, 2000, 1500).unwrap_err();
+        assert!(err.suggested_deletions.is_empty());
+    }
+
+    #[test]
+    fn test_retention_keeps_newest() {
+        let backups = sample_backups();
+        let by_count = BackupRetentionPolicy { max_backups: Some(2), max_total_bytes: None };
+        let mut pruned = backups_to_prune(&backups, &by_count);
+        pruned.sort();
+        assert_eq!(pruned, vec!["b1".to_string(), "b2".to_string()]);
+
+        let by_size = BackupRetentionPolicy { max_backups: None, max_total_bytes: Some(700) };
+        let mut pruned = backups_to_prune(&backups, &by_size);
+        pruned.sort();
+        assert_eq!(pruned, vec!["b1".to_string(), "b2".to_string()]);
     }
-    
-    Ok(())
 }
@@ -11,10 +11,20 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 use log::info;
 use uuid::Uuid;
 
+use crate::commands::voip::VoIPState;
+use crate::services::media_voip_service::{IceServerConfig, VoIPConfig};
+
+/// Input level (0.0-1.0 RMS) above which a participant counts as speaking
+const SPEAKING_THRESHOLD: f32 = 0.05;
+
+/// How long the speaking indicator stays on after the level drops, so it
+/// does not flicker between words
+const SPEAKING_HOLD_MS: i64 = 400;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CollaborationSession {
     pub id: String,
@@ -27,6 +37,8 @@ pub struct CollaborationSession {
     pub is_video_active: bool,
     pub shared_workflow_id: Option<String>,
     pub permissions: SessionPermissions,
+    #[serde(default)]
+    pub audio_channel: Option<AudioChannel>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,6 +53,71 @@ pub struct Participant {
     pub joined_at: String,
     pub last_activity: String,
     pub permissions: ParticipantPermissions,
+    #[serde(default)]
+    pub audio: ParticipantAudio,
+}
+
+/// Voice channel of a session; media flows peer-to-peer over WebRTC using
+/// the VoIP ICE/TURN configuration
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AudioChannel {
+    pub channel_id: String,
+    pub ice_servers: Vec<IceServerConfig>,
+    pub codec: String,
+    pub started_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioDirection {
+    SendRecv,
+    /// No microphone: the participant only listens
+    RecvOnly,
+}
+
+/// Audio track negotiated for a participant
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AudioTrack {
+    pub track_id: String,
+    pub stream_id: String,
+    pub direction: AudioDirection,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ParticipantAudio {
+    pub has_microphone: bool,
+    pub muted: bool,
+    pub push_to_talk: bool,
+    pub speaking: bool,
+    pub audio_level: f32,
+    pub track: Option<AudioTrack>,
+    #[serde(skip)]
+    last_voice_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl Default for ParticipantAudio {
+    fn default() -> Self {
+        Self {
+            has_microphone: true,
+            muted: true,
+            push_to_talk: false,
+            speaking: false,
+            audio_level: 0.0,
+            track: None,
+            last_voice_at: None,
+        }
+    }
+}
+
+/// Audio state of one participant, broadcast to everyone else in the session
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AudioStateChange {
+    pub session_id: String,
+    pub participant_id: String,
+    pub muted: bool,
+    pub speaking: bool,
+    pub listen_only: bool,
+    pub recipients: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -137,6 +214,196 @@ impl CollaborationState {
             recordings: Mutex::new(HashMap::new()),
         }
     }
+
+    /// Add a participant, negotiating their audio track when voice is on
+    fn join(&self, session_id: &str, name: String, has_microphone: bool) -> Result<(CollaborationSession, String), String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions.get_mut(session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+
+        let participant_id = Uuid::new_v4().to_string();
+        let mut participant = Participant {
+            id: participant_id.clone(),
+            name,
+            avatar_url: None,
+            cursor_position: None,
+            is_host: false,
+            is_speaker: false,
+            is_screen_sharing: false,
+            joined_at: chrono::Utc::now().to_rfc3339(),
+            last_activity: chrono::Utc::now().to_rfc3339(),
+            permissions: ParticipantPermissions {
+                can_control_screen: false,
+                can_edit_workflow: session.permissions.allow_workflow_editing,
+                can_speak: false,
+                can_share_screen: false,
+                can_control_browser: false,
+            },
+            audio: ParticipantAudio {
+                has_microphone,
+                ..Default::default()
+            },
+        };
+        if session.audio_channel.is_some() {
+            negotiate_audio_track(&mut participant);
+        }
+
+        session.participants.push(participant);
+        Ok((session.clone(), participant_id))
+    }
+
+    /// Open the session's voice channel, or close it when already open
+    fn toggle_audio(&self, session_id: &str, ice_servers: Vec<IceServerConfig>) -> Result<CollaborationSession, String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions.get_mut(session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+
+        if session.audio_channel.take().is_some() {
+            for participant in &mut session.participants {
+                participant.audio.track = None;
+                participant.audio.speaking = false;
+                participant.audio.audio_level = 0.0;
+            }
+        } else {
+            session.audio_channel = Some(AudioChannel {
+                channel_id: Uuid::new_v4().to_string(),
+                ice_servers,
+                codec: "opus".to_string(),
+                started_at: chrono::Utc::now().to_rfc3339(),
+            });
+            for participant in &mut session.participants {
+                negotiate_audio_track(participant);
+            }
+        }
+
+        session.is_voice_active = session.audio_channel.is_some();
+        Ok(session.clone())
+    }
+
+    /// Mute or unmute a participant. Unmuting needs a microphone and the
+    /// speak permission.
+    fn set_muted(&self, session_id: &str, participant_id: &str, muted: bool) -> Result<AudioStateChange, String> {
+        self.update_audio(session_id, participant_id, |participant| {
+            if !muted {
+                if !participant.audio.has_microphone {
+                    return Err("Participant has no microphone (listen-only)".to_string());
+                }
+                if !participant.permissions.can_speak && !participant.is_host {
+                    return Err("Participant doesn't have permission to speak".to_string());
+                }
+            }
+            participant.audio.muted = muted;
+            if muted {
+                participant.audio.speaking = false;
+            }
+            Ok(())
+        })
+    }
+
+    /// Switch push-to-talk. While it is on the participant stays muted
+    /// except while the talk key is held.
+    fn set_push_to_talk(&self, session_id: &str, participant_id: &str, enabled: bool) -> Result<AudioStateChange, String> {
+        self.update_audio(session_id, participant_id, |participant| {
+            participant.audio.push_to_talk = enabled;
+            participant.audio.muted = true;
+            participant.audio.speaking = false;
+            Ok(())
+        })
+    }
+
+    fn push_to_talk(&self, session_id: &str, participant_id: &str, pressed: bool) -> Result<AudioStateChange, String> {
+        let push_to_talk = self.participant(session_id, participant_id)?.audio.push_to_talk;
+        if !push_to_talk {
+            return Err("Push-to-talk is not enabled".to_string());
+        }
+        self.set_muted(session_id, participant_id, !pressed)
+    }
+
+    /// Update the speaking indicator from a microphone level sample. Returns
+    /// a change only when the indicator flips.
+    fn report_audio_level(
+        &self,
+        session_id: &str,
+        participant_id: &str,
+        level: f32,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<AudioStateChange>, String> {
+        let was_speaking = self.participant(session_id, participant_id)?.audio.speaking;
+        let change = self.update_audio(session_id, participant_id, |participant| {
+            let audio = &mut participant.audio;
+            audio.audio_level = level.clamp(0.0, 1.0);
+
+            let transmitting = !audio.muted && audio.track.as_ref().is_some_and(|t| t.direction == AudioDirection::SendRecv);
+            if !transmitting {
+                audio.speaking = false;
+            } else if audio.audio_level >= SPEAKING_THRESHOLD {
+                audio.speaking = true;
+                audio.last_voice_at = Some(now);
+            } else {
+                let held = audio.last_voice_at.is_some_and(|at| (now - at).num_milliseconds() <= SPEAKING_HOLD_MS);
+                audio.speaking = held;
+            }
+            Ok(())
+        })?;
+
+        Ok((change.speaking != was_speaking).then_some(change))
+    }
+
+    fn participant(&self, session_id: &str, participant_id: &str) -> Result<Participant, String> {
+        let sessions = self.sessions.lock().unwrap();
+        let session = sessions.get(session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        session.participants.iter()
+            .find(|p| p.id == participant_id)
+            .cloned()
+            .ok_or_else(|| "Participant not found".to_string())
+    }
+
+    fn update_audio<F>(&self, session_id: &str, participant_id: &str, update: F) -> Result<AudioStateChange, String>
+    where
+        F: FnOnce(&mut Participant) -> Result<(), String>,
+    {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions.get_mut(session_id)
+            .ok_or_else(|| "Session not found".to_string())?;
+        if session.audio_channel.is_none() {
+            return Err("Voice is not active in this session".to_string());
+        }
+
+        let recipients = session.participants.iter()
+            .filter(|p| p.id != participant_id)
+            .map(|p| p.id.clone())
+            .collect();
+        let participant = session.participants.iter_mut()
+            .find(|p| p.id == participant_id)
+            .ok_or_else(|| "Participant not found".to_string())?;
+        update(participant)?;
+
+        Ok(AudioStateChange {
+            session_id: session_id.to_string(),
+            participant_id: participant_id.to_string(),
+            muted: participant.audio.muted,
+            speaking: participant.audio.speaking,
+            listen_only: !participant.audio.has_microphone,
+            recipients,
+        })
+    }
+}
+
+/// Give a participant their audio track; microphone-less participants
+/// receive only and stay muted
+fn negotiate_audio_track(participant: &mut Participant) {
+    let direction = if participant.audio.has_microphone {
+        AudioDirection::SendRecv
+    } else {
+        participant.audio.muted = true;
+        AudioDirection::RecvOnly
+    };
+    participant.audio.track = Some(AudioTrack {
+        track_id: format!("audio-{}", participant.id),
+        stream_id: format!("stream-{}", participant.id),
+        direction,
+    });
 }
 
 /// Create a new collaboration session
@@ -145,6 +412,7 @@ pub async fn create_collaboration_session(
     name: String,
     host_name: String,
     permissions: SessionPermissions,
+    has_microphone: Option<bool>,
     state: State<'_, Arc<CollaborationState>>,
 ) -> Result<CollaborationSession, String> {
     info!("🤝 Creating collaboration session: {}", name);
@@ -169,6 +437,10 @@ pub async fn create_collaboration_session(
             can_share_screen: true,
             can_control_browser: true,
         },
+        audio: ParticipantAudio {
+            has_microphone: has_microphone.unwrap_or(true),
+            ..Default::default()
+        },
     };
 
     let session = CollaborationSession {
@@ -182,6 +454,7 @@ pub async fn create_collaboration_session(
         is_video_active: false,
         shared_workflow_id: None,
         permissions,
+        audio_channel: None,
     };
 
     let mut sessions = state.sessions.lock().unwrap();
@@ -191,45 +464,21 @@ pub async fn create_collaboration_session(
     Ok(session)
 }
 
-/// Join an existing collaboration session
+/// Join an existing collaboration session. Participants without a
+/// microphone join the voice channel listen-only.
 #[tauri::command]
 pub async fn join_collaboration_session(
     session_id: String,
     participant_name: String,
+    has_microphone: Option<bool>,
     state: State<'_, Arc<CollaborationState>>,
 ) -> Result<CollaborationSession, String> {
     info!("👋 User joining session: {} - {}", session_id, participant_name);
 
-    let mut sessions = state.sessions.lock().unwrap();
-    
-    let session = sessions.get_mut(&session_id)
-        .ok_or_else(|| "Session not found".to_string())?;
-
-    let participant_id = Uuid::new_v4().to_string();
-    
-    let participant = Participant {
-        id: participant_id.clone(),
-        name: participant_name.clone(),
-        avatar_url: None,
-        cursor_position: None,
-        is_host: false,
-        is_speaker: false,
-        is_screen_sharing: false,
-        joined_at: chrono::Utc::now().to_rfc3339(),
-        last_activity: chrono::Utc::now().to_rfc3339(),
-        permissions: ParticipantPermissions {
-            can_control_screen: false,
-            can_edit_workflow: session.permissions.allow_workflow_editing,
-            can_speak: false,
-            can_share_screen: false,
-            can_control_browser: false,
-        },
-    };
-
-    session.participants.push(participant);
+    let (session, participant_id) = state.join(&session_id, participant_name.clone(), has_microphone.unwrap_or(true))?;
 
     info!("✅ Participant joined: {} - {}", participant_name, participant_id);
-    Ok(session.clone())
+    Ok(session)
 }
 
 /// Update cursor position for real-time tracking
@@ -531,3 +780,174 @@ pub async fn get_session_details(
         .cloned()
         .ok_or_else(|| "Session not found".to_string())
 }
+
+/// Turn the session's voice channel on or off. The channel reuses the VoIP
+/// ICE/TURN configuration; every participant gets an audio track to
+/// negotiate, receive-only for those without a microphone.
+#[tauri::command]
+pub async fn collaboration_toggle_audio(
+    session_id: String,
+    app: AppHandle,
+    state: State<'_, Arc<CollaborationState>>,
+    voip_state: State<'_, VoIPState>,
+) -> Result<CollaborationSession, String> {
+    let ice_servers = match voip_state.service.lock().await.as_ref() {
+        Some(service) => service.ice_server_configs(),
+        None => VoIPConfig::default().ice_servers,
+    };
+
+    let session = state.toggle_audio(&session_id, ice_servers)?;
+    info!("🎙️ Voice {} in session {}", if session.is_voice_active { "started" } else { "stopped" }, session_id);
+
+    app.emit("collaboration-audio-channel", &session)
+        .map_err(|e| format!("Failed to emit audio channel update: {}", e))?;
+    Ok(session)
+}
+
+/// Mute or unmute a participant and propagate it to the rest of the session
+#[tauri::command]
+pub async fn collaboration_set_mute(
+    session_id: String,
+    participant_id: String,
+    muted: bool,
+    app: AppHandle,
+    state: State<'_, Arc<CollaborationState>>,
+) -> Result<AudioStateChange, String> {
+    let change = state.set_muted(&session_id, &participant_id, muted)?;
+    emit_audio_state(&app, &change)?;
+    Ok(change)
+}
+
+/// Enable or disable push-to-talk for a participant
+#[tauri::command]
+pub async fn collaboration_set_push_to_talk(
+    session_id: String,
+    participant_id: String,
+    enabled: bool,
+    app: AppHandle,
+    state: State<'_, Arc<CollaborationState>>,
+) -> Result<AudioStateChange, String> {
+    let change = state.set_push_to_talk(&session_id, &participant_id, enabled)?;
+    emit_audio_state(&app, &change)?;
+    Ok(change)
+}
+
+/// Push-to-talk key pressed or released
+#[tauri::command]
+pub async fn collaboration_push_to_talk(
+    session_id: String,
+    participant_id: String,
+    pressed: bool,
+    app: AppHandle,
+    state: State<'_, Arc<CollaborationState>>,
+) -> Result<AudioStateChange, String> {
+    let change = state.push_to_talk(&session_id, &participant_id, pressed)?;
+    emit_audio_state(&app, &change)?;
+    Ok(change)
+}
+
+/// Report a participant's microphone level (0.0-1.0) for the speaking indicator
+#[tauri::command]
+pub async fn collaboration_report_audio_level(
+    session_id: String,
+    participant_id: String,
+    level: f32,
+    app: AppHandle,
+    state: State<'_, Arc<CollaborationState>>,
+) -> Result<(), String> {
+    if let Some(change) = state.report_audio_level(&session_id, &participant_id, level, chrono::Utc::now())? {
+        emit_audio_state(&app, &change)?;
+    }
+    Ok(())
+}
+
+fn emit_audio_state(app: &AppHandle, change: &AudioStateChange) -> Result<(), String> {
+    app.emit("collaboration-audio-state", change)
+        .map_err(|e| format!("Failed to emit audio state: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session_with_host(state: &CollaborationState) -> (String, String) {
+        let session_id = Uuid::new_v4().to_string();
+        let host_id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+        let session = CollaborationSession {
+            id: session_id.clone(),
+            name: "Design review".to_string(),
+            host_id: host_id.clone(),
+            participants: vec![Participant {
+                id: host_id.clone(),
+                name: "Host".to_string(),
+                avatar_url: None,
+                cursor_position: None,
+                is_host: true,
+                is_speaker: true,
+                is_screen_sharing: true,
+                joined_at: now.clone(),
+                last_activity: now.clone(),
+                permissions: ParticipantPermissions {
+                    can_control_screen: true,
+                    can_edit_workflow: true,
+                    can_speak: true,
+                    can_share_screen: true,
+                    can_control_browser: true,
+                },
+                audio: ParticipantAudio::default(),
+            }],
+            created_at: now,
+            is_screen_sharing: true,
+            is_voice_active: false,
+            is_video_active: false,
+            shared_workflow_id: None,
+            permissions: SessionPermissions {
+                allow_screen_control: true,
+                allow_workflow_editing: true,
+                allow_browser_control: false,
+                allow_file_sharing: false,
+                allow_recording: false,
+                require_approval_for_actions: false,
+            },
+            audio_channel: None,
+        };
+        state.sessions.lock().unwrap().insert(session_id.clone(), session);
+        (session_id, host_id)
+    }
+
+    #[test]
+    fn test_audio_track_negotiated_on_join_and_mute_propagates() {
+        let state = CollaborationState::new();
+        let (session_id, host_id) = session_with_host(&state);
+
+        let session = state.toggle_audio(&session_id, VoIPConfig::default().ice_servers).unwrap();
+        assert!(session.is_voice_active);
+        assert!(!session.audio_channel.unwrap().ice_servers.is_empty());
+
+        let (session, guest_id) = state.join(&session_id, "Guest".to_string(), true).unwrap();
+        let guest = session.participants.iter().find(|p| p.id == guest_id).unwrap();
+        assert_eq!(guest.audio.track.as_ref().unwrap().direction, AudioDirection::SendRecv);
+        let (session, listener_id) = state.join(&session_id, "Listener".to_string(), false).unwrap();
+        let listener = session.participants.iter().find(|p| p.id == listener_id).unwrap();
+        assert_eq!(listener.audio.track.as_ref().unwrap().direction, AudioDirection::RecvOnly);
+        assert!(state.set_muted(&session_id, &listener_id, false).is_err());
+
+        let change = state.set_muted(&session_id, &host_id, false).unwrap();
+        assert!(!change.muted);
+        assert_eq!(change.recipients.len(), 2);
+        assert!(change.recipients.contains(&guest_id) && change.recipients.contains(&listener_id));
+
+        // Speaking indicator follows the level, with a short hold
+        let now = chrono::Utc::now();
+        assert!(state.report_audio_level(&session_id, &host_id, 0.3, now).unwrap().unwrap().speaking);
+        assert!(state.report_audio_level(&session_id, &host_id, 0.0, now + chrono::Duration::milliseconds(100)).unwrap().is_none());
+        let quiet = state.report_audio_level(&session_id, &host_id, 0.0, now + chrono::Duration::seconds(1)).unwrap().unwrap();
+        assert!(!quiet.speaking);
+
+        // Everyone else sees the host muted
+        state.set_muted(&session_id, &host_id, true).unwrap();
+        let session = state.sessions.lock().unwrap().get(&session_id).cloned().unwrap();
+        assert!(session.participants.iter().find(|p| p.id == host_id).unwrap().audio.muted);
+    }
+}
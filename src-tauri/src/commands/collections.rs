@@ -365,13 +365,14 @@ pub async fn delete_share(
 #[tauri::command]
 pub async fn search_pages(
     query: String,
+    options: Option<PageSearchOptions>,
     state: State<'_, CollectionsState>,
-) -> Result<Vec<CollectionPage>, String> {
+) -> Result<Vec<PageSearchResult>, String> {
     state
         .service
         .lock()
         .map_err(|e| e.to_string())?
-        .search_pages(&query)
+        .search_pages(&query, &options.unwrap_or_default())
         .map_err(|e| e.to_string())
 }
 
@@ -5,7 +5,8 @@
 use crate::services::contact_service::{
     ContactServiceState, Contact, ContactList, ContactFilter, 
     PaginatedContacts, ContactStats, ImportResult, SubscriptionStatus,
-    Segment, SegmentRule, RuleOperator, RuleComparison
+    Segment, SegmentRule, RuleOperator, RuleComparison,
+    DuplicateCandidate, ContactMergeRecord
 };
 use std::collections::HashMap;
 use tauri::State;
@@ -175,15 +176,32 @@ pub async fn contacts_update_engagement(
     opened: Option<bool>,
     clicked: Option<bool>,
     bounced: Option<bool>,
+    link: Option<String>,
     state: State<'_, ContactServiceState>,
 ) -> Result<(), String> {
+    // A click on a known link is recorded per link as well as in the totals
+    let link_click = clicked.unwrap_or(false) && link.is_some();
     state.update_contact_engagement(
         &email,
         sent.unwrap_or(false),
         opened.unwrap_or(false),
-        clicked.unwrap_or(false),
+        clicked.unwrap_or(false) && !link_click,
         bounced.unwrap_or(false),
-    )
+    )?;
+    match link {
+        Some(url) if link_click => state.record_link_click(&email, &url),
+        _ => Ok(()),
+    }
+}
+
+/// Opt a contact in or out of email open/click tracking
+#[tauri::command]
+pub async fn contacts_set_tracking_opt_out(
+    contact_id: String,
+    opt_out: bool,
+    state: State<'_, ContactServiceState>,
+) -> Result<Contact, String> {
+    state.set_tracking_opt_out(&contact_id, opt_out)
 }
 
 // =============================================================================
@@ -330,6 +348,47 @@ pub async fn contacts_export_csv(
     state.export_contacts(filter, list_id)
 }
 
+// =============================================================================
+// Duplicate Commands
+// =============================================================================
+
+/// Find likely duplicate contacts (threshold 0.0 - 1.0, default 0.85)
+#[tauri::command]
+pub async fn contacts_find_duplicates(
+    threshold: Option<f64>,
+    state: State<'_, ContactServiceState>,
+) -> Result<Vec<DuplicateCandidate>, String> {
+    state.find_duplicates(threshold.unwrap_or(0.85))
+}
+
+/// Merge a duplicate contact into the primary one
+#[tauri::command]
+pub async fn contacts_merge(
+    primary_id: String,
+    duplicate_id: String,
+    state: State<'_, ContactServiceState>,
+) -> Result<ContactMergeRecord, String> {
+    state.merge_contacts(&primary_id, &duplicate_id)
+}
+
+/// Undo a merge, restoring both contacts
+#[tauri::command]
+pub async fn contacts_undo_merge(
+    merge_id: String,
+    state: State<'_, ContactServiceState>,
+) -> Result<Vec<Contact>, String> {
+    let (primary, duplicate) = state.undo_merge(&merge_id)?;
+    Ok(vec![primary, duplicate])
+}
+
+/// Get merges that can be undone
+#[tauri::command]
+pub async fn contacts_get_merge_log(
+    state: State<'_, ContactServiceState>,
+) -> Result<Vec<ContactMergeRecord>, String> {
+    state.get_merge_log()
+}
+
 // =============================================================================
 // Statistics Commands
 // =============================================================================
@@ -2,12 +2,26 @@
 // Exposes the full Chromium browser engine to the frontend
 // All commands provide complete access to DOM, cookies, storage, and more
 
+use crate::commands::autofill_system_v2::AutofillSystemState;
+use crate::commands::passwords_new::PasswordState;
+use crate::models::passwords::PasswordEntry;
+use crate::services::browser_privacy::{Cookie, PrivacyDashboardService, SameSite};
 use crate::services::cube_browser_engine::{
-    BrowserConfig, BrowserTab, CookieData, DOMElement, 
-    ScreenshotOptions, CUBE_BROWSER
+    BrowserConfig, BrowserTab, CookieData, DOMElement, GeolocationCoords, LazyLoadResult,
+    LocationOverride, ScreenshotOptions, CUBE_BROWSER
+};
+use crate::services::cube_web_engine::PrintOptions;
+use crate::services::cube_form_hooks::{
+    site_key, FillOffer, FormHooks, FormKind, SavePrompt, SavePromptKind,
+    SavePromptResponse, SiteFormSettings, StoredPassword,
 };
 use std::collections::HashMap;
+use std::sync::Mutex;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Password/autofill hooks for pages loaded in the engine
+pub struct CubeFormHooksState(pub Mutex<FormHooks>);
 
 // ============================================
 // Browser Lifecycle Commands
@@ -24,12 +38,18 @@ pub async fn cube_engine_init(config: Option<BrowserConfig>) -> Result<String, S
     Ok("CUBE Browser Engine initialized successfully".to_string())
 }
 
-/// Shutdown the CUBE Browser Engine
+/// Shutdown the CUBE Browser Engine, first deleting session-only cookies
 #[tauri::command]
-pub async fn cube_engine_shutdown() -> Result<String, String> {
+pub async fn cube_engine_shutdown(app: AppHandle) -> Result<String, String> {
     let mut browser = CUBE_BROWSER.lock()
         .map_err(|e| format!("Lock error: {}", e))?;
-    
+
+    if let Some(privacy) = app.try_state::<PrivacyDashboardService>() {
+        let removed = privacy.on_browser_closed();
+        if let Err(e) = browser.delete_cookies(&engine_cookies(&removed)) {
+            log::warn!("Failed to delete session cookies: {}", e);
+        }
+    }
     browser.shutdown()?;
     
     Ok("CUBE Browser Engine shutdown complete".to_string())
@@ -48,22 +68,45 @@ pub async fn cube_create_tab(url: String) -> Result<BrowserTab, String> {
     browser.create_tab(&url)
 }
 
-/// Navigate a tab to a URL
+/// Navigate a tab to a URL, apply the site's cookie policy, then offer to
+/// fill any login, address or payment form on the new page
 #[tauri::command]
-pub async fn cube_navigate(tab_id: String, url: String) -> Result<(), String> {
-    let browser = CUBE_BROWSER.lock()
-        .map_err(|e| format!("Lock error: {}", e))?;
-    
-    browser.navigate(&tab_id, &url)
+pub async fn cube_navigate(app: AppHandle, tab_id: String, url: String) -> Result<(), String> {
+    {
+        let browser = CUBE_BROWSER.lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+
+        browser.navigate(&tab_id, &url)?;
+    }
+
+    if let Err(e) = enforce_cookie_policies(&app, &tab_id) {
+        log::warn!("Cookie policies failed for tab {}: {}", tab_id, e);
+    }
+    if let Err(e) = run_fill_hooks(&app, &tab_id) {
+        log::warn!("Form fill hooks failed for tab {}: {}", tab_id, e);
+    }
+    Ok(())
 }
 
 /// Close a tab
 #[tauri::command]
-pub async fn cube_close_tab(tab_id: String) -> Result<(), String> {
+pub async fn cube_close_tab(app: AppHandle, tab_id: String) -> Result<(), String> {
     let browser = CUBE_BROWSER.lock()
         .map_err(|e| format!("Lock error: {}", e))?;
-    
-    browser.close_tab(&tab_id)
+
+    // Session-only cookies go while this tab can still reach the cookie store
+    if let Some(privacy) = app.try_state::<PrivacyDashboardService>() {
+        let removed = privacy.on_tab_closed(&tab_id);
+        if let Err(e) = browser.delete_cookies(&engine_cookies(&removed)) {
+            log::warn!("Failed to delete session cookies: {}", e);
+        }
+    }
+    browser.close_tab(&tab_id)?;
+
+    if let Some(hooks) = app.try_state::<CubeFormHooksState>() {
+        hooks.0.lock().map_err(|e| format!("Lock error: {}", e))?.forget_tab(&tab_id);
+    }
+    Ok(())
 }
 
 /// Go back in history
@@ -308,13 +351,346 @@ pub async fn cube_fill_form(tab_id: String, data: HashMap<String, String>) -> Re
     browser.fill_form(&tab_id, &data)
 }
 
-/// Submit a form
+/// Submit a form, prompting to save the credentials if it was a login
+/// with an account the password manager doesn't know yet
 #[tauri::command]
-pub async fn cube_submit_form(tab_id: String, form_selector: String) -> Result<(), String> {
+pub async fn cube_submit_form(app: AppHandle, tab_id: String, form_selector: String) -> Result<(), String> {
+    let prompt = save_prompt_for_submit(&app, &tab_id).unwrap_or_else(|e| {
+        log::warn!("Form save hook failed for tab {}: {}", tab_id, e);
+        None
+    });
+
+    {
+        let browser = CUBE_BROWSER.lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+
+        browser.submit_form(&tab_id, &form_selector)?;
+    }
+
+    if let Some(prompt) = prompt {
+        let _ = app.emit("cube-form-save-prompt", &prompt);
+    }
+    Ok(())
+}
+
+// ============================================
+// Password & Autofill Hooks
+// ============================================
+
+/// Re-scan the page for forms (e.g. after a single-page app renders its
+/// login form) and return the fill offers
+#[tauri::command]
+pub async fn cube_detect_fillable_forms(app: AppHandle, tab_id: String) -> Result<Vec<FillOffer>, String> {
+    run_fill_hooks(&app, &tab_id)
+}
+
+/// Fill the page's login form with a stored account picked from a fill offer
+#[tauri::command]
+pub async fn cube_fill_credential(
+    tab_id: String,
+    entry_id: String,
+    passwords: State<'_, PasswordState>,
+    hooks: State<'_, CubeFormHooksState>,
+) -> Result<(), String> {
+    let (username_selector, password_selector) = {
+        let hooks = hooks.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let form = hooks
+            .form(&tab_id, FormKind::Login)
+            .ok_or_else(|| "No login form detected on this page".to_string())?;
+        (form.username_selector.clone(), form.password_selector.clone())
+    };
+
+    let (username, password) = {
+        let service = passwords.service.lock().map_err(|e| e.to_string())?;
+        let entries = service.get_all_passwords().map_err(|e| e.to_string())?;
+        let entry = entries
+            .iter()
+            .find(|e| e.id == entry_id)
+            .ok_or_else(|| "Password entry not found".to_string())?;
+        let password = passwords
+            .decrypt_unlocked(&service, entry)
+            .ok_or_else(|| "Password vault is locked".to_string())?;
+        service.update_last_used(&entry_id).map_err(|e| e.to_string())?;
+        (entry.username.clone(), password)
+    };
+
+    let mut data = HashMap::new();
+    if let Some(selector) = username_selector {
+        data.insert(selector, username);
+    }
+    if let Some(selector) = password_selector {
+        data.insert(selector, password);
+    }
+
     let browser = CUBE_BROWSER.lock()
         .map_err(|e| format!("Lock error: {}", e))?;
-    
-    browser.submit_form(&tab_id, &form_selector)
+    browser.fill_form(&tab_id, &data)
+}
+
+/// Fill the page's address or payment form from an autofill profile,
+/// returning the number of fields filled
+#[tauri::command]
+pub async fn cube_fill_autofill_profile(
+    tab_id: String,
+    kind: FormKind,
+    profile_id: String,
+    autofill: State<'_, AutofillSystemState>,
+    hooks: State<'_, CubeFormHooksState>,
+) -> Result<usize, String> {
+    let profile = autofill
+        .engine()
+        .get_profile(&profile_id)?
+        .ok_or_else(|| format!("Profile not found: {}", profile_id))?;
+    let data = {
+        let hooks = hooks.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let form = hooks
+            .form(&tab_id, kind)
+            .ok_or_else(|| "No matching form detected on this page".to_string())?;
+        hooks.profile_values(form, &profile)
+    };
+
+    {
+        let browser = CUBE_BROWSER.lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        browser.fill_form(&tab_id, &data)?;
+    }
+    Ok(data.len())
+}
+
+/// Answer a save prompt. Saving needs the vault to be unlocked; "never"
+/// stops prompting on the site.
+#[tauri::command]
+pub async fn cube_respond_save_prompt(
+    prompt_id: String,
+    response: SavePromptResponse,
+    passwords: State<'_, PasswordState>,
+    hooks: State<'_, CubeFormHooksState>,
+) -> Result<(), String> {
+    if response == SavePromptResponse::Save && passwords.unlocked_master_password().is_none() {
+        return Err("Password vault is locked".to_string());
+    }
+
+    let saved = hooks
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .respond_to_save(&prompt_id, response)?;
+    let Some((prompt, password)) = saved else {
+        return Ok(());
+    };
+
+    let service = passwords.service.lock().map_err(|e| e.to_string())?;
+    let encrypted = passwords.encrypt_unlocked(&service, &password)?;
+    let strength = service.analyze_strength(&password);
+    let now = chrono::Utc::now().timestamp();
+
+    match prompt.kind {
+        SavePromptKind::New => {
+            let entry = PasswordEntry {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: prompt.site,
+                username: prompt.username,
+                encrypted_password: encrypted,
+                url: Some(prompt.url),
+                notes: None,
+                category: "login".to_string(),
+                tags: Vec::new(),
+                date_created: now,
+                date_modified: now,
+                last_used: Some(now),
+                favorite: false,
+                strength_score: strength.score,
+            };
+            service.save_password(&entry).map_err(|e| e.to_string())
+        }
+        SavePromptKind::UpdatePassword { entry_id } => {
+            let mut entry = service
+                .get_all_passwords()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .find(|e| e.id == entry_id)
+                .ok_or_else(|| "Password entry not found".to_string())?;
+            entry.encrypted_password = encrypted;
+            entry.strength_score = strength.score;
+            entry.date_modified = now;
+            service.update_password(&entry).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Get the password/autofill settings for a page's site
+#[tauri::command]
+pub async fn cube_get_form_site_settings(
+    url: String,
+    hooks: State<'_, CubeFormHooksState>,
+) -> Result<SiteFormSettings, String> {
+    let site = site_key(&url).ok_or_else(|| format!("Invalid URL: {}", url))?;
+    let hooks = hooks.0.lock().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(hooks.site_settings(&site))
+}
+
+/// Set the password/autofill settings for a page's site
+#[tauri::command]
+pub async fn cube_set_form_site_settings(
+    url: String,
+    settings: SiteFormSettings,
+    hooks: State<'_, CubeFormHooksState>,
+) -> Result<(), String> {
+    let site = site_key(&url).ok_or_else(|| format!("Invalid URL: {}", url))?;
+    hooks
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .set_site_settings(&site, settings)
+}
+
+/// Mirror the tab's cookies into the privacy cookie store, which applies
+/// each site's policy, and delete what it refuses or has expired
+fn enforce_cookie_policies(app: &AppHandle, tab_id: &str) -> Result<(), String> {
+    let Some(privacy) = app.try_state::<PrivacyDashboardService>() else {
+        return Ok(());
+    };
+    let browser = CUBE_BROWSER.lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+
+    let url = browser.get_url(tab_id)?;
+    privacy.on_tab_navigated(tab_id, &url);
+    let page_site = site_key(&url);
+
+    let mut refused = Vec::new();
+    for data in browser.get_cookies(tab_id)? {
+        let host = data.domain.trim_start_matches('.').to_lowercase();
+        let now = chrono::Utc::now();
+        let cookie = Cookie {
+            domain: data.domain.clone(),
+            name: data.name.clone(),
+            value: data.value.clone(),
+            path: data.path.clone(),
+            expires: data.expires
+                .filter(|e| *e > 0.0)
+                .and_then(|e| chrono::DateTime::from_timestamp(e as i64, 0)),
+            secure: data.secure,
+            http_only: data.http_only,
+            same_site: match data.same_site.as_deref() {
+                Some("Strict") => SameSite::Strict,
+                Some("None") => SameSite::None,
+                _ => SameSite::Lax,
+            },
+            is_third_party: page_site.as_deref().is_some_and(|site| {
+                host != site && !host.ends_with(&format!(".{}", site)) && !site.ends_with(&format!(".{}", host))
+            }),
+            created_at: now,
+            last_accessed: now,
+        };
+        if privacy.add_cookie(cookie).is_err() {
+            refused.push(data);
+        }
+    }
+    refused.extend(engine_cookies(&privacy.sweep_cookies(chrono::Utc::now())));
+    browser.delete_cookies(&refused)
+}
+
+/// Privacy store cookies as the engine addresses them for deletion
+pub(crate) fn engine_cookies(cookies: &[Cookie]) -> Vec<CookieData> {
+    cookies.iter().map(|c| CookieData {
+        name: c.name.clone(),
+        value: c.value.clone(),
+        domain: c.domain.clone(),
+        path: c.path.clone(),
+        expires: c.expires.map(|e| e.timestamp() as f64),
+        http_only: c.http_only,
+        secure: c.secure,
+        same_site: Some(format!("{:?}", c.same_site)),
+    }).collect()
+}
+
+/// Detect the tab's forms and emit a "cube-form-fill-offer" event for each
+/// one the stored credentials or autofill profiles can fill
+fn run_fill_hooks(app: &AppHandle, tab_id: &str) -> Result<Vec<FillOffer>, String> {
+    let Some(hooks) = app.try_state::<CubeFormHooksState>() else {
+        return Ok(Vec::new());
+    };
+
+    let (url, elements) = {
+        let browser = CUBE_BROWSER.lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        (browser.get_url(tab_id)?, browser.get_form_fields(tab_id)?)
+    };
+
+    let (credentials, unlocked) = match app.try_state::<PasswordState>() {
+        Some(passwords) => {
+            let service = passwords.service.lock().map_err(|e| e.to_string())?;
+            (
+                service.get_all_passwords().map_err(|e| e.to_string())?,
+                passwords.unlocked_master_password().is_some(),
+            )
+        }
+        None => (Vec::new(), false),
+    };
+    let profiles = match app.try_state::<AutofillSystemState>() {
+        Some(autofill) => autofill.engine().get_all_profiles()?,
+        None => Vec::new(),
+    };
+
+    let offers = hooks
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .on_navigation(tab_id, &url, &elements, &credentials, &profiles, unlocked);
+    for offer in &offers {
+        let _ = app.emit("cube-form-fill-offer", offer);
+    }
+    Ok(offers)
+}
+
+/// Read the credentials typed into the tab's login form, if any, and build
+/// a save prompt for them
+fn save_prompt_for_submit(app: &AppHandle, tab_id: &str) -> Result<Option<SavePrompt>, String> {
+    let (Some(hooks), Some(passwords)) = (
+        app.try_state::<CubeFormHooksState>(),
+        app.try_state::<PasswordState>(),
+    ) else {
+        return Ok(None);
+    };
+    let selectors = hooks
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .form(tab_id, FormKind::Login)
+        .map(|form| (form.username_selector.clone(), form.password_selector.clone()));
+    let Some((username_selector, Some(password_selector))) = selectors else {
+        return Ok(None);
+    };
+
+    let (url, username, password) = {
+        let browser = CUBE_BROWSER.lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        let read_value = |selector: &str| -> Result<String, String> {
+            let script = format!(
+                "document.querySelector('{}')?.value ?? ''",
+                selector.replace("'", "\\'")
+            );
+            Ok(browser.execute_script(tab_id, &script)?.as_str().unwrap_or_default().to_string())
+        };
+        let username = match username_selector {
+            Some(selector) => read_value(&selector)?,
+            None => String::new(),
+        };
+        (browser.get_url(tab_id)?, username, read_value(&password_selector)?)
+    };
+
+    let service = passwords.service.lock().map_err(|e| e.to_string())?;
+    let credentials = service.get_all_passwords().map_err(|e| e.to_string())?;
+    let decrypt = |entry: &PasswordEntry| passwords.decrypt_unlocked(&service, entry);
+    let stored_password: StoredPassword =
+        passwords.unlocked_master_password().map(|_| &decrypt as _);
+
+    let prompt = hooks
+        .0
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .on_submit(tab_id, &url, &username, &password, &credentials, stored_password);
+    Ok(prompt)
 }
 
 // ============================================
@@ -339,16 +715,79 @@ pub async fn cube_extract_table(tab_id: String, table_selector: String) -> Resul
     browser.extract_table(&tab_id, &table_selector)
 }
 
+/// Scroll the page until lazy-loaded content stops appearing, collecting
+/// items along the way. Run before extraction on infinite-scroll pages.
+#[tauri::command]
+pub async fn cube_engine_scroll_to_load_all(tab_id: String, max_scrolls: Option<u32>) -> Result<LazyLoadResult, String> {
+    let browser = CUBE_BROWSER.lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    
+    browser.scroll_to_load_all(&tab_id, max_scrolls.unwrap_or(50))
+}
+
+// ============================================
+// Location Override Commands
+// ============================================
+
+/// Spoof the geolocation reported to pages in a tab, or in every tab when
+/// `tab_id` is omitted. Omitting `coords` removes the override.
+#[tauri::command]
+pub async fn cube_engine_set_geolocation_override(
+    tab_id: Option<String>,
+    coords: Option<GeolocationCoords>,
+) -> Result<(), String> {
+    let browser = CUBE_BROWSER.lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    
+    browser.set_geolocation_override(tab_id.as_deref(), coords)
+}
+
+/// Spoof `navigator.languages` and the timezone for a tab, or every tab
+#[tauri::command]
+pub async fn cube_engine_set_locale_override(
+    tab_id: Option<String>,
+    languages: Option<Vec<String>>,
+    timezone: Option<String>,
+) -> Result<(), String> {
+    let browser = CUBE_BROWSER.lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    
+    browser.set_locale_override(tab_id.as_deref(), languages, timezone)
+}
+
+/// Get the location override in effect for a tab
+#[tauri::command]
+pub async fn cube_engine_get_location_override(tab_id: String) -> Result<LocationOverride, String> {
+    let browser = CUBE_BROWSER.lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    
+    Ok(browser.get_location_override(&tab_id))
+}
+
 // ============================================
 // PDF Generation Command
 // ============================================
 
-/// Generate PDF (returns base64)
+/// Generate PDF. Returns the PDF as base64, or the written path when
+/// `output_path` is set (preferred for long documents).
 #[tauri::command]
-pub async fn cube_print_to_pdf(tab_id: String) -> Result<String, String> {
+pub async fn cube_print_to_pdf(
+    tab_id: String,
+    options: Option<PrintOptions>,
+    output_path: Option<String>,
+) -> Result<String, String> {
     let browser = CUBE_BROWSER.lock()
         .map_err(|e| format!("Lock error: {}", e))?;
+    let options = options.unwrap_or_default();
     
-    let data = browser.print_to_pdf(&tab_id)?;
-    Ok(BASE64.encode(&data))
+    match output_path {
+        Some(path) => {
+            browser.print_to_pdf_file(&tab_id, &options, std::path::Path::new(&path))?;
+            Ok(path)
+        }
+        None => {
+            let data = browser.print_to_pdf(&tab_id, &options)?;
+            Ok(BASE64.encode(&data))
+        }
+    }
 }
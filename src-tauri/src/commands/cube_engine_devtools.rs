@@ -4,7 +4,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::RwLock;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::services::heap_snapshot::{HeapGraph, HeapSnapshot, HeapSummary, HEAP_WALKER_SCRIPT};
+use crate::services::websocket_inspector::{WebSocketConnection, WebSocketInspector, DEFAULT_FRAME_LIMIT};
 
 // ============================================
 // DevTools State
@@ -17,6 +20,8 @@ pub struct CubeDevToolsState {
     pub profiler_data: RwLock<HashMap<String, ProfilerSession>>,
     pub breakpoints: RwLock<HashMap<String, Vec<Breakpoint>>>,
     pub watches: RwLock<HashMap<String, Vec<WatchExpression>>>,
+    pub websockets: WebSocketInspector,
+    pub heap_summaries: RwLock<HashMap<String, HeapSummary>>,
     pub config: RwLock<DevToolsConfig>,
 }
 
@@ -29,6 +34,8 @@ impl Default for CubeDevToolsState {
             profiler_data: RwLock::new(HashMap::new()),
             breakpoints: RwLock::new(HashMap::new()),
             watches: RwLock::new(HashMap::new()),
+            websockets: WebSocketInspector::default(),
+            heap_summaries: RwLock::new(HashMap::new()),
             config: RwLock::new(DevToolsConfig::default()),
         }
     }
@@ -403,6 +410,9 @@ pub enum ScopeType {
 pub struct DevToolsConfig {
     pub network_log_limit: usize,
     pub console_log_limit: usize,
+    /// Frames kept per WebSocket connection; older frames are dropped
+    #[serde(default = "default_websocket_frame_limit")]
+    pub websocket_frame_limit: usize,
     pub preserve_log: bool,
     pub disable_cache: bool,
     pub emulate_offline: bool,
@@ -417,6 +427,7 @@ impl Default for DevToolsConfig {
         Self {
             network_log_limit: 1000,
             console_log_limit: 1000,
+            websocket_frame_limit: DEFAULT_FRAME_LIMIT,
             preserve_log: false,
             disable_cache: false,
             emulate_offline: false,
@@ -428,6 +439,10 @@ impl Default for DevToolsConfig {
     }
 }
 
+fn default_websocket_frame_limit() -> usize {
+    DEFAULT_FRAME_LIMIT
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ThrottlePreset {
     Slow3G,
@@ -523,6 +538,99 @@ pub async fn network_get_request(
     Ok(None)
 }
 
+// ============================================
+// Tauri Commands - WebSocket Inspector
+// ============================================
+
+/// Returns the loopback URL the tab should connect to so `url` is inspected
+#[tauri::command]
+pub async fn devtools_inspect_websocket(
+    state: State<'_, CubeDevToolsState>,
+    tab_id: String,
+    url: String,
+) -> Result<String, String> {
+    state.websockets.tap_url(&tab_id, &url).await
+}
+
+#[tauri::command]
+pub async fn devtools_get_websockets(
+    state: State<'_, CubeDevToolsState>,
+    tab_id: String,
+) -> Result<Vec<WebSocketConnection>, String> {
+    state.websockets.get_connections(&tab_id)
+}
+
+#[tauri::command]
+pub async fn devtools_clear_websockets(
+    state: State<'_, CubeDevToolsState>,
+    tab_id: String,
+) -> Result<(), String> {
+    state.websockets.clear(&tab_id)
+}
+
+// ============================================
+// Tauri Commands - Memory (Heap Snapshots)
+// ============================================
+
+/// Script the tab evaluates to report its object graph for a heap snapshot
+#[tauri::command]
+pub async fn devtools_get_heap_walker_script() -> Result<String, String> {
+    Ok(HEAP_WALKER_SCRIPT.to_string())
+}
+
+/// Streams the tab's object graph to a .heapsnapshot file loadable in Chrome
+/// DevTools' Memory tab and keeps its summary for `devtools_get_heap_summary`
+#[tauri::command]
+pub async fn devtools_take_heap_snapshot(
+    state: State<'_, CubeDevToolsState>,
+    app: AppHandle,
+    tab_id: String,
+    graph: HeapGraph,
+) -> Result<HeapSummary, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?
+        .join("heap_snapshots");
+    let file_name: String = tab_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let path = dir.join(format!("{}-{}.heapsnapshot", file_name, chrono::Utc::now().timestamp_millis()));
+
+    let summary = {
+        let tab_id = tab_id.clone();
+        tokio::task::spawn_blocking(move || {
+            let snapshot = HeapSnapshot::from_graph(graph);
+            snapshot.write_to_file(&path)?;
+            Ok::<_, String>(snapshot.summarize(&tab_id, &path.to_string_lossy()))
+        })
+        .await
+        .map_err(|e| format!("Heap snapshot task failed: {}", e))??
+    };
+
+    let mut summaries = state.heap_summaries.write().map_err(|e| format!("Lock error: {}", e))?;
+    summaries.insert(tab_id.clone(), summary.clone());
+
+    let _ = app.emit("devtools-heap-snapshot", serde_json::json!({
+        "tabId": tab_id,
+        "path": summary.snapshot_path,
+        "detachedNodes": summary.detached_nodes.len()
+    }));
+
+    Ok(summary)
+}
+
+/// Top retainers and detached DOM leaks from the tab's latest heap snapshot
+#[tauri::command]
+pub async fn devtools_get_heap_summary(
+    state: State<'_, CubeDevToolsState>,
+    tab_id: String,
+) -> Result<Option<HeapSummary>, String> {
+    let summaries = state.heap_summaries.read().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(summaries.get(&tab_id).cloned())
+}
+
 // ============================================
 // Tauri Commands - Console
 // ============================================
@@ -922,6 +1030,7 @@ pub async fn devtools_set_config(
     config: DevToolsConfig,
 ) -> Result<(), String> {
     let mut current = state.config.write().map_err(|e| format!("Lock error: {}", e))?;
+    state.websockets.set_frame_limit(config.websocket_frame_limit);
     *current = config;
     Ok(())
 }
@@ -3,8 +3,9 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
 use std::sync::RwLock;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
 
 // ============================================
 // Extensions State
@@ -34,6 +35,96 @@ impl Default for CubeExtensionsState {
     }
 }
 
+impl CubeExtensionsState {
+    pub fn install_extension(&self, manifest: ExtensionManifest, install_path: String) -> Result<Extension, String> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let ext_id = uuid::Uuid::new_v4().to_string();
+
+        let extension = Extension {
+            id: ext_id.clone(),
+            manifest: manifest.clone(),
+            status: ExtensionStatus::Loading,
+            install_path,
+            installed_at: now,
+            updated_at: now,
+            is_enabled: true,
+            error: None,
+        };
+
+        let mut extensions = self.extensions.write().map_err(|e| format!("Lock error: {}", e))?;
+        extensions.insert(ext_id.clone(), extension.clone());
+
+        let permissions = ExtensionPermissions {
+            extension_id: ext_id.clone(),
+            granted: manifest.permissions.clone(),
+            denied: Vec::new(),
+            host_permissions: manifest.host_permissions.clone(),
+            granted_host_permissions: Vec::new(),
+        };
+
+        let mut perms = self.permissions.write().map_err(|e| format!("Lock error: {}", e))?;
+        perms.insert(ext_id.clone(), permissions);
+
+        let storage = ExtensionStorage::new(ext_id.clone());
+        let mut storages = self.extension_storage.write().map_err(|e| format!("Lock error: {}", e))?;
+        storages.insert(ext_id, storage);
+
+        Ok(extension)
+    }
+
+    pub fn storage_get(
+        &self,
+        extension_id: &str,
+        area: &StorageArea,
+        keys: Option<Vec<String>>,
+    ) -> Result<HashMap<String, serde_json::Value>, String> {
+        let storages = self.extension_storage.read().map_err(|e| format!("Lock error: {}", e))?;
+
+        let storage = storages.get(extension_id).ok_or("Extension storage not found")?;
+
+        let data = match area {
+            StorageArea::Local => &storage.local,
+            StorageArea::Sync => &storage.sync,
+            StorageArea::Session => &storage.session,
+        };
+
+        if let Some(k) = keys {
+            let mut result = HashMap::new();
+            for key in k {
+                if let Some(value) = data.get(&key) {
+                    result.insert(key, value.clone());
+                }
+            }
+            return Ok(result);
+        }
+
+        Ok(data.clone())
+    }
+
+    pub fn storage_set(
+        &self,
+        extension_id: &str,
+        area: &StorageArea,
+        items: &HashMap<String, serde_json::Value>,
+    ) -> Result<(), String> {
+        let mut storages = self.extension_storage.write().map_err(|e| format!("Lock error: {}", e))?;
+
+        let storage = storages.get_mut(extension_id).ok_or("Extension storage not found")?;
+
+        let data = match area {
+            StorageArea::Local => &mut storage.local,
+            StorageArea::Sync => &mut storage.sync,
+            StorageArea::Session => &mut storage.session,
+        };
+
+        for (key, value) in items.iter() {
+            data.insert(key.clone(), value.clone());
+        }
+
+        Ok(())
+    }
+}
+
 // ============================================
 // Extension Manifest
 // ============================================
@@ -65,6 +156,9 @@ pub struct ExtensionManifest {
     pub background: Option<BackgroundConfig>,
     pub content_scripts: Vec<ContentScriptConfig>,
     pub browser_action: Option<BrowserAction>,
+    /// Manifest V3 replacement for `browser_action`
+    #[serde(default)]
+    pub action: Option<BrowserAction>,
     pub page_action: Option<PageAction>,
     pub options_page: Option<String>,
     pub options_ui: Option<OptionsUI>,
@@ -104,7 +198,7 @@ pub struct ContentScriptConfig {
     pub world: Option<ScriptWorld>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub enum RunAt {
     #[default]
     DocumentIdle,
@@ -112,6 +206,17 @@ pub enum RunAt {
     DocumentEnd,
 }
 
+impl RunAt {
+    /// Position in the document lifecycle: start < end < idle
+    pub fn lifecycle_rank(&self) -> u8 {
+        match self {
+            RunAt::DocumentStart => 0,
+            RunAt::DocumentEnd => 1,
+            RunAt::DocumentIdle => 2,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub enum ScriptWorld {
     #[default]
@@ -161,6 +266,8 @@ pub struct ContentSecurityPolicyConfig {
 pub struct ContentScript {
     pub id: String,
     pub extension_id: String,
+    /// Index of the manifest `content_scripts` entry this injection came from
+    pub config_index: usize,
     pub tab_id: String,
     pub frame_id: u32,
     pub url: String,
@@ -172,6 +279,228 @@ pub struct ContentScript {
     pub is_active: bool,
 }
 
+/// A frame reaching a lifecycle point, as reported by the webview
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameNavigation {
+    pub tab_id: String,
+    pub frame_id: u32,
+    pub url: String,
+    /// URL of the parent (or opener) frame, used for `match_about_blank`
+    pub parent_url: Option<String>,
+    pub lifecycle: RunAt,
+    /// Same-document navigation (history.pushState) that did not reload the page
+    #[serde(default)]
+    pub same_document: bool,
+}
+
+// ============================================
+// Match Patterns
+// ============================================
+
+const ALL_URLS_SCHEMES: &[&str] = &["http", "https", "ws", "wss", "ftp", "file"];
+const WILDCARD_SCHEMES: &[&str] = &["http", "https"];
+
+/// A compiled Chrome match pattern (`<scheme>://<host>/<path>` or `<all_urls>`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchPattern {
+    schemes: Vec<String>,
+    host: HostPattern,
+    port: Option<u16>,
+    path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum HostPattern {
+    Any,
+    Exact(String),
+    SubdomainsOf(String),
+}
+
+impl MatchPattern {
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        if pattern == "<all_urls>" {
+            return Ok(Self {
+                schemes: ALL_URLS_SCHEMES.iter().map(|s| s.to_string()).collect(),
+                host: HostPattern::Any,
+                port: None,
+                path: "/*".to_string(),
+            });
+        }
+
+        let invalid = |reason: &str| format!("Invalid match pattern '{}': {}", pattern, reason);
+        let (scheme, rest) = pattern.split_once("://").ok_or_else(|| invalid("missing scheme separator"))?;
+        let schemes = match scheme {
+            "*" => WILDCARD_SCHEMES.iter().map(|s| s.to_string()).collect(),
+            s if ALL_URLS_SCHEMES.contains(&s) => vec![s.to_string()],
+            _ => return Err(invalid("unsupported scheme")),
+        };
+
+        let path_start = rest.find('/').ok_or_else(|| invalid("missing path"))?;
+        let (authority, path) = rest.split_at(path_start);
+        if scheme == "file" {
+            if !authority.is_empty() {
+                return Err(invalid("file patterns cannot have a host"));
+            }
+            return Ok(Self { schemes, host: HostPattern::Any, port: None, path: path.to_string() });
+        }
+
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if port != "*" => {
+                (host, Some(port.parse::<u16>().map_err(|_| invalid("invalid port"))?))
+            }
+            Some((host, _)) => (host, None),
+            None => (authority, None),
+        };
+        let host = host.to_lowercase();
+        let host = if host == "*" {
+            HostPattern::Any
+        } else if let Some(domain) = host.strip_prefix("*.") {
+            if domain.is_empty() || domain.contains('*') {
+                return Err(invalid("'*' may only be the first host label"));
+            }
+            HostPattern::SubdomainsOf(domain.to_string())
+        } else if host.is_empty() || host.contains('*') {
+            return Err(invalid("'*' may only be the first host label"));
+        } else {
+            HostPattern::Exact(host)
+        };
+
+        Ok(Self { schemes, host, port, path: path.to_string() })
+    }
+
+    pub fn matches(&self, url: &url::Url) -> bool {
+        if !self.schemes.iter().any(|s| s == url.scheme()) {
+            return false;
+        }
+        if url.scheme() != "file" {
+            let host = url.host_str().unwrap_or_default().to_lowercase();
+            let host_ok = match &self.host {
+                HostPattern::Any => true,
+                HostPattern::Exact(expected) => host == *expected,
+                HostPattern::SubdomainsOf(domain) => {
+                    host == *domain || host.ends_with(&format!(".{}", domain))
+                }
+            };
+            if !host_ok || self.port.is_some_and(|p| url.port_or_known_default() != Some(p)) {
+                return false;
+            }
+        }
+
+        let mut path = url.path().to_string();
+        if let Some(query) = url.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+        wildcard_match(&self.path, &path, false)
+    }
+}
+
+/// `*` matches any run of characters; `?` matches one character when `question_mark` is set
+fn wildcard_match(pattern: &str, text: &str, question_mark: bool) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == text[t] || (question_mark && pattern[p] == '?')) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn any_pattern_matches(patterns: &[String], url: &url::Url) -> Result<bool, String> {
+    for pattern in patterns {
+        if MatchPattern::parse(pattern)?.matches(url) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Whether a manifest content script applies to a frame. `exclude_matches` and
+/// `exclude_globs` always win over `matches`/`include_globs`.
+fn content_script_applies(config: &ContentScriptConfig, frame: &FrameNavigation) -> Result<bool, String> {
+    if frame.frame_id != 0 && !config.all_frames.unwrap_or(false) {
+        return Ok(false);
+    }
+
+    let is_blank = frame.url == "about:blank" || frame.url == "about:srcdoc";
+    let effective_url = if is_blank {
+        match (&frame.parent_url, config.match_about_blank.unwrap_or(false)) {
+            (Some(parent), true) => parent.as_str(),
+            _ => return Ok(false),
+        }
+    } else {
+        frame.url.as_str()
+    };
+    let url = match url::Url::parse(effective_url) {
+        Ok(url) => url,
+        Err(_) => return Ok(false),
+    };
+
+    if let Some(excludes) = &config.exclude_matches {
+        if any_pattern_matches(excludes, &url)? {
+            return Ok(false);
+        }
+    }
+    if !any_pattern_matches(&config.matches, &url)? {
+        return Ok(false);
+    }
+    if let Some(globs) = &config.exclude_globs {
+        if globs.iter().any(|g| wildcard_match(g, url.as_str(), true)) {
+            return Ok(false);
+        }
+    }
+    if let Some(globs) = &config.include_globs {
+        if !globs.is_empty() && !globs.iter().any(|g| wildcard_match(g, url.as_str(), true)) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Manifest entries (extension id, config index) to inject into a frame that has reached
+/// `frame.lifecycle`, in lifecycle order. Entries whose `run_at` was passed before the
+/// frame was reported are caught up; entries already injected are skipped.
+fn plan_content_scripts(
+    extensions: &[&Extension],
+    injected: &[(String, usize)],
+    frame: &FrameNavigation,
+) -> Result<Vec<(String, usize, RunAt)>, String> {
+    let lifecycle = if frame.same_document { RunAt::DocumentIdle } else { frame.lifecycle };
+    let mut planned = Vec::new();
+
+    for extension in extensions.iter().filter(|e| e.is_enabled) {
+        for (index, config) in extension.manifest.content_scripts.iter().enumerate() {
+            let run_at = config.run_at.unwrap_or_default();
+            if run_at.lifecycle_rank() > lifecycle.lifecycle_rank() {
+                continue;
+            }
+            if injected.iter().any(|(id, i)| *id == extension.id && *i == index) {
+                continue;
+            }
+            if content_script_applies(config, frame)? {
+                planned.push((extension.id.clone(), index, run_at));
+            }
+        }
+    }
+
+    planned.sort_by_key(|(_, _, run_at)| run_at.lifecycle_rank());
+    Ok(planned)
+}
+
 // ============================================
 // Background Scripts
 // ============================================
@@ -330,6 +659,160 @@ pub struct TabInfo {
     pub active: bool,
 }
 
+// ============================================
+// Extension Pages (options page, action popup)
+// ============================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExtensionUiKind {
+    Options,
+    Popup,
+}
+
+/// An extension page resolved to a file inside the extension's install directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionUiPage {
+    pub extension_id: String,
+    pub kind: ExtensionUiKind,
+    /// Page path as declared in the manifest
+    pub page: String,
+    pub file_path: String,
+    pub url: String,
+    pub window_label: String,
+    /// `options_ui.open_in_tab`; the frontend may show the page in a tab instead
+    pub open_in_tab: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ExtensionUiResult {
+    Opened { page: ExtensionUiPage },
+    NotAvailable { reason: String },
+}
+
+/// Resolves an extension's options page or action popup. `Err` carries the reason
+/// the page is not available.
+pub fn resolve_extension_ui(extension: &Extension, kind: ExtensionUiKind) -> Result<ExtensionUiPage, String> {
+    let manifest = &extension.manifest;
+    if !extension.is_enabled {
+        return Err(format!("{} is disabled", manifest.name));
+    }
+
+    let (page, open_in_tab) = match kind {
+        ExtensionUiKind::Options => match (&manifest.options_ui, &manifest.options_page) {
+            (Some(ui), _) => (Some(ui.page.clone()), ui.open_in_tab.unwrap_or(false)),
+            (None, page) => (page.clone(), true),
+        },
+        ExtensionUiKind::Popup => (
+            manifest.action.as_ref()
+                .or(manifest.browser_action.as_ref())
+                .and_then(|a| a.default_popup.clone())
+                .or_else(|| manifest.page_action.as_ref().and_then(|a| a.default_popup.clone())),
+            false,
+        ),
+    };
+    let page = page.filter(|p| !p.trim().is_empty()).ok_or_else(|| match kind {
+        ExtensionUiKind::Options => format!("{} does not declare an options page", manifest.name),
+        ExtensionUiKind::Popup => format!("{} does not declare an action popup", manifest.name),
+    })?;
+
+    let file_path = extension_file(&extension.install_path, &page)?;
+    if !file_path.is_file() {
+        return Err(format!("{} declares {} but the file is missing", manifest.name, page));
+    }
+    let url = url::Url::from_file_path(&file_path)
+        .map_err(|_| format!("Cannot build a URL for {}", file_path.display()))?;
+
+    let suffix = match kind {
+        ExtensionUiKind::Options => "options",
+        ExtensionUiKind::Popup => "popup",
+    };
+    Ok(ExtensionUiPage {
+        extension_id: extension.id.clone(),
+        kind,
+        page,
+        file_path: file_path.to_string_lossy().to_string(),
+        url: url.to_string(),
+        window_label: format!("ext-{}-{}", suffix, extension.id),
+        open_in_tab,
+    })
+}
+
+/// Joins a manifest-relative path onto the install directory, refusing paths that
+/// would escape it
+fn extension_file(install_path: &str, relative: &str) -> Result<PathBuf, String> {
+    let relative = relative.split(['?', '#']).next().unwrap_or_default().trim_start_matches('/');
+    let relative = Path::new(relative);
+    if relative.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(format!("Extension page path escapes the extension: {}", relative.display()));
+    }
+    Ok(Path::new(install_path).join(relative))
+}
+
+/// `chrome.*` APIs for extension pages, backed by the extension storage commands
+pub fn extension_page_api_script(extension: &Extension) -> String {
+    let base = url::Url::from_directory_path(&extension.install_path)
+        .map(|u| u.to_string())
+        .unwrap_or_default();
+    let manifest = serde_json::to_string(&extension.manifest).unwrap_or_else(|_| "{}".to_string());
+    format!(
+        r#"(function() {{
+  var extensionId = {id};
+  var baseUrl = {base};
+  var manifest = {manifest};
+  function invoke(cmd, args) {{
+    return window.__TAURI__.core.invoke(cmd, args);
+  }}
+  function done(promise, callback) {{
+    if (typeof callback === 'function') {{ promise.then(callback); }}
+    return promise;
+  }}
+  function storageArea(area) {{
+    return {{
+      get: function(keys, callback) {{
+        if (typeof keys === 'function') {{ callback = keys; keys = null; }}
+        var defaults = {{}};
+        var names = null;
+        if (typeof keys === 'string') {{ names = [keys]; }}
+        else if (Array.isArray(keys)) {{ names = keys; }}
+        else if (keys && typeof keys === 'object') {{ defaults = keys; names = Object.keys(keys); }}
+        return done(invoke('ext_storage_get', {{ extensionId: extensionId, area: area, keys: names }}).then(function(items) {{
+          return Object.assign({{}}, defaults, items);
+        }}), callback);
+      }},
+      set: function(items, callback) {{
+        return done(invoke('ext_storage_set', {{ extensionId: extensionId, area: area, items: items }}), callback);
+      }},
+      remove: function(keys, callback) {{
+        return done(invoke('ext_storage_remove', {{ extensionId: extensionId, area: area, keys: [].concat(keys) }}), callback);
+      }},
+      clear: function(callback) {{
+        return done(invoke('ext_storage_clear', {{ extensionId: extensionId, area: area }}), callback);
+      }}
+    }};
+  }}
+  var chromeApi = window.chrome = window.chrome || {{}};
+  chromeApi.runtime = {{
+    id: extensionId,
+    getURL: function(path) {{ return baseUrl + String(path).replace(/^\//, ''); }},
+    getManifest: function() {{ return manifest; }},
+    openOptionsPage: function(callback) {{
+      return done(invoke('extension_open_options', {{ extensionId: extensionId }}), callback);
+    }}
+  }};
+  chromeApi.storage = {{
+    local: storageArea('Local'),
+    sync: storageArea('Sync'),
+    session: storageArea('Session')
+  }};
+  window.browser = window.browser || chromeApi;
+}})();"#,
+        id = serde_json::to_string(&extension.id).unwrap_or_default(),
+        base = serde_json::to_string(&base).unwrap_or_default(),
+        manifest = manifest,
+    )
+}
+
 // ============================================
 // Extensions Config
 // ============================================
@@ -370,41 +853,11 @@ pub async fn extension_install(
     manifest: ExtensionManifest,
     install_path: String,
 ) -> Result<String, String> {
-    let now = chrono::Utc::now().timestamp_millis();
-    let ext_id = uuid::Uuid::new_v4().to_string();
-    
-    let extension = Extension {
-        id: ext_id.clone(),
-        manifest: manifest.clone(),
-        status: ExtensionStatus::Loading,
-        install_path,
-        installed_at: now,
-        updated_at: now,
-        is_enabled: true,
-        error: None,
-    };
-    
-    let mut extensions = state.extensions.write().map_err(|e| format!("Lock error: {}", e))?;
-    extensions.insert(ext_id.clone(), extension.clone());
-    
-    let permissions = ExtensionPermissions {
-        extension_id: ext_id.clone(),
-        granted: manifest.permissions.clone(),
-        denied: Vec::new(),
-        host_permissions: manifest.host_permissions.clone(),
-        granted_host_permissions: Vec::new(),
-    };
-    
-    let mut perms = state.permissions.write().map_err(|e| format!("Lock error: {}", e))?;
-    perms.insert(ext_id.clone(), permissions);
-    
-    let storage = ExtensionStorage::new(ext_id.clone());
-    let mut storages = state.extension_storage.write().map_err(|e| format!("Lock error: {}", e))?;
-    storages.insert(ext_id.clone(), storage);
+    let extension = state.install_extension(manifest, install_path)?;
     
     let _ = app.emit("extension-installed", &extension);
     
-    Ok(ext_id)
+    Ok(extension.id)
 }
 
 #[tauri::command]
@@ -490,43 +943,93 @@ pub async fn extension_list(
 // Tauri Commands - Content Scripts
 // ============================================
 
+/// Injects the manifest content scripts that apply to a frame at its current lifecycle
+/// point. A new document resets the frame's injections; a same-document (SPA) navigation
+/// re-evaluates matches, deactivating scripts that no longer match.
 #[tauri::command]
 pub async fn content_script_inject(
     state: State<'_, CubeExtensionsState>,
     app: AppHandle,
-    extension_id: String,
-    tab_id: String,
-    frame_id: u32,
-    url: String,
-    js_files: Vec<String>,
-    css_files: Vec<String>,
-    run_at: Option<RunAt>,
-    world: Option<ScriptWorld>,
-) -> Result<String, String> {
-    let script_id = uuid::Uuid::new_v4().to_string();
+    frame: FrameNavigation,
+) -> Result<Vec<ContentScript>, String> {
     let now = chrono::Utc::now().timestamp_millis();
-    
-    let script = ContentScript {
-        id: script_id.clone(),
-        extension_id: extension_id.clone(),
-        tab_id,
-        frame_id,
-        url,
-        js_files,
-        css_files,
-        run_at: run_at.unwrap_or_default(),
-        world: world.unwrap_or_default(),
-        injected_at: now,
-        is_active: true,
-    };
-    
+    let extensions = state.extensions.read().map_err(|e| format!("Lock error: {}", e))?;
     let mut scripts = state.content_scripts.write().map_err(|e| format!("Lock error: {}", e))?;
-    let ext_scripts = scripts.entry(extension_id).or_insert_with(Vec::new);
-    ext_scripts.push(script.clone());
-    
-    let _ = app.emit("content-script-injected", &script);
-    
-    Ok(script_id)
+
+    let in_frame = |s: &ContentScript| s.tab_id == frame.tab_id && s.frame_id == frame.frame_id;
+    if frame.same_document {
+        for (extension_id, ext_scripts) in scripts.iter_mut() {
+            for script in ext_scripts.iter_mut().filter(|s| in_frame(s) && s.is_active) {
+                let config = extensions
+                    .get(extension_id)
+                    .and_then(|e| e.manifest.content_scripts.get(script.config_index));
+                let still_applies = match config {
+                    Some(config) => content_script_applies(config, &frame)?,
+                    None => false,
+                };
+                if !still_applies {
+                    script.is_active = false;
+                    let _ = app.emit("content-script-deactivated", &*script);
+                }
+            }
+        }
+    } else if frame.lifecycle == RunAt::DocumentStart {
+        for ext_scripts in scripts.values_mut() {
+            ext_scripts.retain(|s| !in_frame(s));
+        }
+    }
+
+    let injected: Vec<(String, usize)> = scripts
+        .values()
+        .flatten()
+        .filter(|s| in_frame(s) && s.is_active)
+        .map(|s| (s.extension_id.clone(), s.config_index))
+        .collect();
+    let candidates: Vec<&Extension> = extensions.values().collect();
+    let plan = plan_content_scripts(&candidates, &injected, &frame)?;
+
+    let mut result = Vec::new();
+    for (extension_id, config_index, run_at) in plan {
+        let config = &extensions[&extension_id].manifest.content_scripts[config_index];
+        let script = ContentScript {
+            id: uuid::Uuid::new_v4().to_string(),
+            extension_id: extension_id.clone(),
+            config_index,
+            tab_id: frame.tab_id.clone(),
+            frame_id: frame.frame_id,
+            url: frame.url.clone(),
+            js_files: config.js.clone().unwrap_or_default(),
+            css_files: config.css.clone().unwrap_or_default(),
+            run_at,
+            world: config.world.clone().unwrap_or_default(),
+            injected_at: now,
+            is_active: true,
+        };
+
+        let ext_scripts = scripts.entry(extension_id).or_insert_with(Vec::new);
+        ext_scripts.retain(|s| !(in_frame(s) && s.config_index == config_index));
+        ext_scripts.push(script.clone());
+        let _ = app.emit("content-script-injected", &script);
+        result.push(script);
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn extension_get_active_content_scripts(
+    state: State<'_, CubeExtensionsState>,
+    tab_id: String,
+) -> Result<Vec<ContentScript>, String> {
+    let scripts = state.content_scripts.read().map_err(|e| format!("Lock error: {}", e))?;
+    let mut active: Vec<ContentScript> = scripts
+        .values()
+        .flatten()
+        .filter(|s| s.tab_id == tab_id && s.is_active)
+        .cloned()
+        .collect();
+    active.sort_by_key(|s| (s.frame_id, s.run_at.lifecycle_rank(), s.injected_at));
+    Ok(active)
 }
 
 #[tauri::command]
@@ -622,27 +1125,7 @@ pub async fn ext_storage_get(
     area: StorageArea,
     keys: Option<Vec<String>>,
 ) -> Result<HashMap<String, serde_json::Value>, String> {
-    let storages = state.extension_storage.read().map_err(|e| format!("Lock error: {}", e))?;
-    
-    let storage = storages.get(&extension_id).ok_or("Extension storage not found")?;
-    
-    let data = match area {
-        StorageArea::Local => &storage.local,
-        StorageArea::Sync => &storage.sync,
-        StorageArea::Session => &storage.session,
-    };
-    
-    if let Some(k) = keys {
-        let mut result = HashMap::new();
-        for key in k {
-            if let Some(value) = data.get(&key) {
-                result.insert(key, value.clone());
-            }
-        }
-        return Ok(result);
-    }
-    
-    Ok(data.clone())
+    state.storage_get(&extension_id, &area, keys)
 }
 
 #[tauri::command]
@@ -653,19 +1136,7 @@ pub async fn ext_storage_set(
     area: StorageArea,
     items: HashMap<String, serde_json::Value>,
 ) -> Result<(), String> {
-    let mut storages = state.extension_storage.write().map_err(|e| format!("Lock error: {}", e))?;
-    
-    let storage = storages.get_mut(&extension_id).ok_or("Extension storage not found")?;
-    
-    let data = match area {
-        StorageArea::Local => &mut storage.local,
-        StorageArea::Sync => &mut storage.sync,
-        StorageArea::Session => &mut storage.session,
-    };
-    
-    for (key, value) in items.iter() {
-        data.insert(key.clone(), value.clone());
-    }
+    state.storage_set(&extension_id, &area, &items)?;
     
     let _ = app.emit("storage-changed", serde_json::json!({
         "extensionId": extension_id,
@@ -845,6 +1316,70 @@ pub async fn port_disconnect(
     Ok(())
 }
 
+// ============================================
+// Tauri Commands - Extension Pages
+// ============================================
+
+/// Opens the extension's options page (`options_ui.page` or `options_page`)
+#[tauri::command]
+pub async fn extension_open_options(
+    state: State<'_, CubeExtensionsState>,
+    app: AppHandle,
+    extension_id: String,
+) -> Result<ExtensionUiResult, String> {
+    open_extension_ui(&state, &app, &extension_id, ExtensionUiKind::Options)
+}
+
+/// Opens the extension's action popup (`action`/`browser_action` `default_popup`)
+#[tauri::command]
+pub async fn extension_open_popup(
+    state: State<'_, CubeExtensionsState>,
+    app: AppHandle,
+    extension_id: String,
+) -> Result<ExtensionUiResult, String> {
+    open_extension_ui(&state, &app, &extension_id, ExtensionUiKind::Popup)
+}
+
+fn open_extension_ui(
+    state: &CubeExtensionsState,
+    app: &AppHandle,
+    extension_id: &str,
+    kind: ExtensionUiKind,
+) -> Result<ExtensionUiResult, String> {
+    let extension = {
+        let extensions = state.extensions.read().map_err(|e| format!("Lock error: {}", e))?;
+        extensions.get(extension_id).cloned().ok_or("Extension not found")?
+    };
+    let page = match resolve_extension_ui(&extension, kind) {
+        Ok(page) => page,
+        Err(reason) => return Ok(ExtensionUiResult::NotAvailable { reason }),
+    };
+
+    if let Some(window) = app.get_webview_window(&page.window_label) {
+        let _ = window.set_focus();
+        return Ok(ExtensionUiResult::Opened { page });
+    }
+
+    let url = page.url.parse().map_err(|e| format!("Invalid extension page URL: {}", e))?;
+    let (title, width, height) = match kind {
+        ExtensionUiKind::Options => (format!("{} - Options", extension.manifest.name), 800.0, 600.0),
+        ExtensionUiKind::Popup => (extension.manifest.name.clone(), 400.0, 560.0),
+    };
+    WebviewWindowBuilder::new(app, &page.window_label, WebviewUrl::External(url))
+        .title(title)
+        .inner_size(width, height)
+        .resizable(kind == ExtensionUiKind::Options)
+        .always_on_top(kind == ExtensionUiKind::Popup)
+        .skip_taskbar(kind == ExtensionUiKind::Popup)
+        .initialization_script(&extension_page_api_script(&extension))
+        .build()
+        .map_err(|e| format!("Failed to open extension page: {}", e))?;
+
+    let _ = app.emit("extension-page-opened", &page);
+
+    Ok(ExtensionUiResult::Opened { page })
+}
+
 // ============================================
 // Tauri Commands - Extensions Config
 // ============================================
@@ -876,3 +1411,182 @@ pub async fn extensions_set_developer_mode(
     config.developer_mode = enabled;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> url::Url {
+        url::Url::parse(s).unwrap()
+    }
+
+    fn config(matches: &[&str], run_at: RunAt) -> ContentScriptConfig {
+        ContentScriptConfig {
+            matches: matches.iter().map(|m| m.to_string()).collect(),
+            exclude_matches: None,
+            include_globs: None,
+            exclude_globs: None,
+            js: Some(vec!["content.js".to_string()]),
+            css: None,
+            run_at: Some(run_at),
+            all_frames: None,
+            match_about_blank: None,
+            world: None,
+        }
+    }
+
+    fn extension(id: &str, content_scripts: Vec<ContentScriptConfig>) -> Extension {
+        Extension {
+            id: id.to_string(),
+            manifest: ExtensionManifest {
+                manifest_version: 3,
+                name: id.to_string(),
+                version: "1.0.0".to_string(),
+                description: None,
+                author: None,
+                homepage_url: None,
+                icons: None,
+                permissions: vec![],
+                optional_permissions: vec![],
+                host_permissions: vec![],
+                background: None,
+                content_scripts,
+                browser_action: None,
+                action: None,
+                page_action: None,
+                options_page: None,
+                options_ui: None,
+                web_accessible_resources: vec![],
+                content_security_policy: None,
+            },
+            status: ExtensionStatus::Enabled,
+            install_path: String::new(),
+            installed_at: 0,
+            updated_at: 0,
+            is_enabled: true,
+            error: None,
+        }
+    }
+
+    fn frame(url: &str, frame_id: u32, lifecycle: RunAt) -> FrameNavigation {
+        FrameNavigation {
+            tab_id: "tab-1".to_string(),
+            frame_id,
+            url: url.to_string(),
+            parent_url: None,
+            lifecycle,
+            same_document: false,
+        }
+    }
+
+    #[test]
+    fn test_match_pattern_compilation() {
+        let subdomains = MatchPattern::parse("*://*.example.com/*").unwrap();
+        assert!(subdomains.matches(&url("https://example.com/")));
+        assert!(subdomains.matches(&url("http://a.b.example.com/page?q=1")));
+        assert!(!subdomains.matches(&url("https://notexample.com/")));
+        assert!(!subdomains.matches(&url("ftp://example.com/")));
+
+        let path = MatchPattern::parse("https://docs.example.com/api/*").unwrap();
+        assert!(path.matches(&url("https://docs.example.com/api/v1")));
+        assert!(!path.matches(&url("https://docs.example.com/guide")));
+        assert!(!path.matches(&url("http://docs.example.com/api/v1")));
+
+        let port = MatchPattern::parse("http://localhost:3000/*").unwrap();
+        assert!(port.matches(&url("http://localhost:3000/app")));
+        assert!(!port.matches(&url("http://localhost:8080/app")));
+
+        let all = MatchPattern::parse("<all_urls>").unwrap();
+        assert!(all.matches(&url("file:///tmp/index.html")));
+        assert!(!all.matches(&url("chrome://settings/")));
+
+        assert!(MatchPattern::parse("https://*foo.com/*").is_err());
+        assert!(MatchPattern::parse("https://example.com").is_err());
+        assert!(MatchPattern::parse("gopher://example.com/*").is_err());
+    }
+
+    #[test]
+    fn test_run_at_ordering() {
+        let ext = extension("ext", vec![
+            config(&["<all_urls>"], RunAt::DocumentIdle),
+            config(&["<all_urls>"], RunAt::DocumentStart),
+            config(&["<all_urls>"], RunAt::DocumentEnd),
+        ]);
+        let extensions = vec![&ext];
+
+        let at_start = plan_content_scripts(&extensions, &[], &frame("https://a.com/", 0, RunAt::DocumentStart)).unwrap();
+        assert_eq!(at_start.iter().map(|p| p.1).collect::<Vec<_>>(), vec![1]);
+
+        let injected = vec![("ext".to_string(), 1)];
+        let at_end = plan_content_scripts(&extensions, &injected, &frame("https://a.com/", 0, RunAt::DocumentEnd)).unwrap();
+        assert_eq!(at_end.iter().map(|p| p.1).collect::<Vec<_>>(), vec![2]);
+
+        // A frame first reported at idle catches up in lifecycle order
+        let late = plan_content_scripts(&extensions, &[], &frame("https://a.com/", 0, RunAt::DocumentIdle)).unwrap();
+        assert_eq!(late.iter().map(|p| p.2).collect::<Vec<_>>(), vec![RunAt::DocumentStart, RunAt::DocumentEnd, RunAt::DocumentIdle]);
+    }
+
+    #[test]
+    fn test_exclude_matches_precedence_and_frames() {
+        let mut excluded = config(&["*://*.example.com/*"], RunAt::DocumentEnd);
+        excluded.exclude_matches = Some(vec!["*://admin.example.com/*".to_string()]);
+        excluded.exclude_globs = Some(vec!["*logout*".to_string()]);
+
+        assert!(content_script_applies(&excluded, &frame("https://www.example.com/", 0, RunAt::DocumentEnd)).unwrap());
+        assert!(!content_script_applies(&excluded, &frame("https://admin.example.com/", 0, RunAt::DocumentEnd)).unwrap());
+        assert!(!content_script_applies(&excluded, &frame("https://www.example.com/logout", 0, RunAt::DocumentEnd)).unwrap());
+        // Subframes need all_frames
+        assert!(!content_script_applies(&excluded, &frame("https://www.example.com/", 3, RunAt::DocumentEnd)).unwrap());
+
+        let mut blank = config(&["https://host.com/*"], RunAt::DocumentEnd);
+        blank.all_frames = Some(true);
+        let mut about_blank = frame("about:blank", 2, RunAt::DocumentEnd);
+        about_blank.parent_url = Some("https://host.com/page".to_string());
+        assert!(!content_script_applies(&blank, &about_blank).unwrap());
+        blank.match_about_blank = Some(true);
+        assert!(content_script_applies(&blank, &about_blank).unwrap());
+    }
+
+    #[test]
+    fn test_options_page_loads_and_storage_writes_persist() {
+        let dir = std::env::temp_dir().join(format!("cube_ext_options_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("ui")).unwrap();
+        std::fs::write(dir.join("ui/options.html"), "<html><body><input id=\"theme\"></body></html>").unwrap();
+
+        let mut manifest = extension("Dark Reader", vec![]).manifest;
+        manifest.options_ui = Some(OptionsUI {
+            page: "ui/options.html".to_string(),
+            open_in_tab: None,
+            browser_style: None,
+        });
+        let state = CubeExtensionsState::default();
+        let installed = state.install_extension(manifest, dir.to_string_lossy().to_string()).unwrap();
+
+        let page = resolve_extension_ui(&installed, ExtensionUiKind::Options).unwrap();
+        assert_eq!(page.page, "ui/options.html");
+        assert!(std::fs::read_to_string(&page.file_path).unwrap().contains("id=\"theme\""));
+        assert!(page.url.starts_with("file://") && page.url.ends_with("/ui/options.html"));
+        assert!(!page.open_in_tab);
+
+        let api = extension_page_api_script(&installed);
+        assert!(api.contains(&format!("var extensionId = \"{}\"", installed.id)));
+        assert!(api.contains("invoke('ext_storage_set'"));
+
+        // What the page's chrome.storage.sync.set() sends through ext_storage_set
+        let items = HashMap::from([("theme".to_string(), serde_json::json!("dark"))]);
+        state.storage_set(&installed.id, &StorageArea::Sync, &items).unwrap();
+        let stored = state.storage_get(&installed.id, &StorageArea::Sync, Some(vec!["theme".to_string()])).unwrap();
+        assert_eq!(stored["theme"], "dark");
+        assert!(state.storage_get(&installed.id, &StorageArea::Local, None).unwrap().is_empty());
+
+        // No popup declared, and a traversal path is never served
+        let popup = resolve_extension_ui(&installed, ExtensionUiKind::Popup).unwrap_err();
+        assert!(popup.contains("does not declare an action popup"));
+        let mut escaped = installed.clone();
+        escaped.manifest.options_ui = None;
+        escaped.manifest.options_page = Some("../secrets.html".to_string());
+        assert!(resolve_extension_ui(&escaped, ExtensionUiKind::Options).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
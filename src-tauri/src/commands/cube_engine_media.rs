@@ -56,6 +56,12 @@ pub struct MediaSession {
     pub active_subtitle: Option<String>,
     pub pip_active: bool,
     pub fullscreen: bool,
+    /// Media Session actions the page registered handlers for
+    #[serde(default)]
+    pub action_handlers: Vec<MediaSessionAction>,
+    /// Last time the user interacted with this session (play, PiP, media key)
+    #[serde(default)]
+    pub last_active_at: i64,
     pub created_at: i64,
     pub last_updated: i64,
 }
@@ -87,7 +93,7 @@ pub enum SourceType {
     Blob,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub enum PlaybackState {
     #[default]
     Idle,
@@ -120,6 +126,55 @@ pub struct MediaImage {
     pub image_type: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaSessionAction {
+    Play,
+    Pause,
+    Stop,
+    SeekBackward,
+    SeekForward,
+    SeekTo,
+    PreviousTrack,
+    NextTrack,
+    SkipAd,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MediaKey {
+    PlayPause,
+    Play,
+    Pause,
+    Stop,
+    NextTrack,
+    PreviousTrack,
+}
+
+/// What the OS now-playing controls show for the active session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NowPlayingInfo {
+    pub session_id: String,
+    pub tab_id: String,
+    pub metadata: MediaMetadata,
+    pub state: PlaybackState,
+    pub pip_active: bool,
+    pub actions: Vec<MediaSessionAction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaKeyDispatch {
+    pub session_id: String,
+    pub tab_id: String,
+    pub key: MediaKey,
+    pub action: MediaSessionAction,
+    /// True when the page registered a handler and must run the action itself
+    pub handled_by_page: bool,
+    pub state: PlaybackState,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoInfo {
     pub width: u32,
@@ -552,6 +607,8 @@ pub async fn media_create_session(
         active_subtitle: None,
         pip_active: false,
         fullscreen: false,
+        action_handlers: Vec::new(),
+        last_active_at: now,
         created_at: now,
         last_updated: now,
     };
@@ -575,6 +632,7 @@ pub async fn media_play(
     if let Some(session) = sessions.get_mut(&session_id) {
         session.state = PlaybackState::Playing;
         session.last_updated = chrono::Utc::now().timestamp_millis();
+        session.last_active_at = session.last_updated;
         
         let _ = app.emit("media-play", serde_json::json!({ "sessionId": session_id }));
     }
@@ -676,6 +734,9 @@ pub async fn media_toggle_pip(
     if let Some(session) = sessions.get_mut(&session_id) {
         session.pip_active = !session.pip_active;
         session.last_updated = chrono::Utc::now().timestamp_millis();
+        if session.pip_active {
+            session.last_active_at = session.last_updated;
+        }
         
         let _ = app.emit("media-pip-changed", serde_json::json!({
             "sessionId": session_id,
@@ -707,6 +768,216 @@ pub async fn media_destroy_session(
     Ok(())
 }
 
+// ============================================
+// Media Session API & OS Media Keys
+// ============================================
+
+/// Picks the session that OS media keys and now-playing controls target.
+/// A session in Picture-in-Picture wins, then anything playing, then paused
+/// sessions; ties go to the most recently active one.
+pub fn select_active_session(sessions: &HashMap<String, MediaSession>) -> Option<&MediaSession> {
+    sessions
+        .values()
+        .filter_map(|session| {
+            let rank = if session.pip_active {
+                3
+            } else {
+                match session.state {
+                    PlaybackState::Playing | PlaybackState::Buffering | PlaybackState::Loading => 2,
+                    PlaybackState::Paused => 1,
+                    _ => return None,
+                }
+            };
+            Some((rank, session))
+        })
+        .max_by(|(rank_a, a), (rank_b, b)| {
+            rank_a
+                .cmp(rank_b)
+                .then(a.last_active_at.cmp(&b.last_active_at))
+                .then(b.id.cmp(&a.id))
+        })
+        .map(|(_, session)| session)
+}
+
+pub fn now_playing(sessions: &HashMap<String, MediaSession>) -> Option<NowPlayingInfo> {
+    select_active_session(sessions).map(|session| NowPlayingInfo {
+        session_id: session.id.clone(),
+        tab_id: session.tab_id.clone(),
+        metadata: session.metadata.clone(),
+        state: session.state.clone(),
+        pip_active: session.pip_active,
+        actions: session.action_handlers.clone(),
+    })
+}
+
+fn sanitize_metadata(metadata: MediaMetadata) -> MediaMetadata {
+    let clean = |value: Option<String>| {
+        value
+            .map(|v| v.chars().filter(|c| !c.is_control()).collect::<String>().trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+
+    MediaMetadata {
+        title: clean(metadata.title),
+        artist: clean(metadata.artist),
+        album: clean(metadata.album),
+        artwork: metadata
+            .artwork
+            .into_iter()
+            .filter(|image| {
+                let src = image.src.trim();
+                src.starts_with("https://")
+                    || src.starts_with("http://")
+                    || src.starts_with("data:image/")
+                    || src.starts_with("blob:")
+            })
+            .collect(),
+    }
+}
+
+pub fn set_session_metadata(
+    sessions: &mut HashMap<String, MediaSession>,
+    session_id: &str,
+    metadata: MediaMetadata,
+) -> Result<(), String> {
+    let session = sessions
+        .get_mut(session_id)
+        .ok_or_else(|| format!("Media session not found: {}", session_id))?;
+    session.metadata = sanitize_metadata(metadata);
+    session.last_updated = chrono::Utc::now().timestamp_millis();
+    Ok(())
+}
+
+/// Routes a hardware media key to the active session. Play/pause fall back to
+/// the engine's own playback when the page has no handler; track and stop keys
+/// are only dispatched when the page registered a handler for them.
+pub fn dispatch_media_key(
+    sessions: &mut HashMap<String, MediaSession>,
+    key: MediaKey,
+) -> Option<MediaKeyDispatch> {
+    let session_id = select_active_session(sessions)?.id.clone();
+    let session = sessions.get_mut(&session_id)?;
+
+    let action = match key {
+        MediaKey::PlayPause => {
+            if session.state == PlaybackState::Paused {
+                MediaSessionAction::Play
+            } else {
+                MediaSessionAction::Pause
+            }
+        }
+        MediaKey::Play => MediaSessionAction::Play,
+        MediaKey::Pause => MediaSessionAction::Pause,
+        MediaKey::Stop => MediaSessionAction::Stop,
+        MediaKey::NextTrack => MediaSessionAction::NextTrack,
+        MediaKey::PreviousTrack => MediaSessionAction::PreviousTrack,
+    };
+
+    let handled_by_page = session.action_handlers.contains(&action);
+    match action {
+        MediaSessionAction::Play => session.state = PlaybackState::Playing,
+        MediaSessionAction::Pause => session.state = PlaybackState::Paused,
+        _ if !handled_by_page => return None,
+        _ => {}
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    session.last_updated = now;
+    session.last_active_at = now;
+
+    Some(MediaKeyDispatch {
+        session_id: session.id.clone(),
+        tab_id: session.tab_id.clone(),
+        key,
+        action,
+        handled_by_page,
+        state: session.state.clone(),
+    })
+}
+
+#[tauri::command]
+pub async fn media_set_session_metadata(
+    state: State<'_, CubeMediaState>,
+    app: AppHandle,
+    session_id: String,
+    metadata: MediaMetadata,
+) -> Result<(), String> {
+    let mut sessions = state.media_sessions.write().map_err(|e| format!("Lock error: {}", e))?;
+    set_session_metadata(&mut sessions, &session_id, metadata)?;
+
+    let _ = app.emit("media-now-playing-changed", now_playing(&sessions));
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn media_get_session_metadata(
+    state: State<'_, CubeMediaState>,
+    session_id: String,
+) -> Result<Option<MediaMetadata>, String> {
+    let sessions = state.media_sessions.read().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(sessions.get(&session_id).map(|s| s.metadata.clone()))
+}
+
+#[tauri::command]
+pub async fn media_set_action_handlers(
+    state: State<'_, CubeMediaState>,
+    app: AppHandle,
+    session_id: String,
+    actions: Vec<MediaSessionAction>,
+) -> Result<(), String> {
+    let mut sessions = state.media_sessions.write().map_err(|e| format!("Lock error: {}", e))?;
+
+    let session = sessions
+        .get_mut(&session_id)
+        .ok_or_else(|| format!("Media session not found: {}", session_id))?;
+    session.action_handlers.clear();
+    for action in actions {
+        if !session.action_handlers.contains(&action) {
+            session.action_handlers.push(action);
+        }
+    }
+    session.last_updated = chrono::Utc::now().timestamp_millis();
+
+    let _ = app.emit("media-now-playing-changed", now_playing(&sessions));
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn media_get_now_playing(
+    state: State<'_, CubeMediaState>,
+) -> Result<Option<NowPlayingInfo>, String> {
+    let sessions = state.media_sessions.read().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(now_playing(&sessions))
+}
+
+#[tauri::command]
+pub async fn media_handle_media_key(
+    state: State<'_, CubeMediaState>,
+    app: AppHandle,
+    key: MediaKey,
+) -> Result<Option<MediaKeyDispatch>, String> {
+    let mut sessions = state.media_sessions.write().map_err(|e| format!("Lock error: {}", e))?;
+
+    let dispatch = dispatch_media_key(&mut sessions, key);
+    if let Some(ref dispatch) = dispatch {
+        let _ = app.emit("media-session-action", dispatch);
+        match dispatch.action {
+            MediaSessionAction::Play => {
+                let _ = app.emit("media-play", serde_json::json!({ "sessionId": dispatch.session_id }));
+            }
+            MediaSessionAction::Pause => {
+                let _ = app.emit("media-pause", serde_json::json!({ "sessionId": dispatch.session_id }));
+            }
+            _ => {}
+        }
+        let _ = app.emit("media-now-playing-changed", now_playing(&sessions));
+    }
+
+    Ok(dispatch)
+}
+
 // ============================================
 // Tauri Commands - Download Manager
 // ============================================
@@ -1162,3 +1433,95 @@ pub async fn download_set_config(
     *current = config;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, state: PlaybackState, last_active_at: i64) -> MediaSession {
+        MediaSession {
+            id: id.to_string(),
+            tab_id: format!("tab-{}", id),
+            media_type: MediaType::Audio,
+            source: MediaSource {
+                url: format!("https://example.com/{}.mp3", id),
+                source_type: SourceType::Direct,
+                quality: None,
+                bitrate: None,
+            },
+            state,
+            duration: 180.0,
+            current_time: 0.0,
+            volume: 1.0,
+            muted: false,
+            playback_rate: 1.0,
+            buffered_ranges: Vec::new(),
+            metadata: MediaMetadata::default(),
+            video_info: None,
+            audio_info: None,
+            subtitles: Vec::new(),
+            active_subtitle: None,
+            pip_active: false,
+            fullscreen: false,
+            action_handlers: Vec::new(),
+            last_active_at,
+            created_at: 0,
+            last_updated: 0,
+        }
+    }
+
+    #[test]
+    fn test_session_metadata_exposed_and_media_key_reaches_active_session() {
+        let mut sessions = HashMap::new();
+        sessions.insert("old".to_string(), session("old", PlaybackState::Paused, 100));
+        sessions.insert("recent".to_string(), session("recent", PlaybackState::Paused, 200));
+
+        set_session_metadata(
+            &mut sessions,
+            "recent",
+            MediaMetadata {
+                title: Some("  Song\n".to_string()),
+                artist: Some("Artist".to_string()),
+                album: None,
+                artwork: vec![
+                    MediaImage { src: "https://example.com/cover.png".to_string(), sizes: Some("512x512".to_string()), image_type: None },
+                    MediaImage { src: "javascript:alert(1)".to_string(), sizes: None, image_type: None },
+                ],
+            },
+        )
+        .unwrap();
+
+        let info = now_playing(&sessions).unwrap();
+        assert_eq!(info.session_id, "recent");
+        assert_eq!(info.metadata.title.as_deref(), Some("Song"));
+        assert_eq!(info.metadata.artist.as_deref(), Some("Artist"));
+        assert_eq!(info.metadata.artwork.len(), 1);
+
+        let dispatch = dispatch_media_key(&mut sessions, MediaKey::Play).unwrap();
+        assert_eq!(dispatch.session_id, "recent");
+        assert_eq!(dispatch.action, MediaSessionAction::Play);
+        assert_eq!(sessions["recent"].state, PlaybackState::Playing);
+        assert_eq!(sessions["old"].state, PlaybackState::Paused);
+    }
+
+    #[test]
+    fn test_media_keys_prefer_pip_and_require_track_handlers() {
+        let mut sessions = HashMap::new();
+        sessions.insert("playing".to_string(), session("playing", PlaybackState::Playing, 500));
+        let mut pip = session("pip", PlaybackState::Paused, 100);
+        pip.pip_active = true;
+        sessions.insert("pip".to_string(), pip);
+
+        assert_eq!(select_active_session(&sessions).unwrap().id, "pip");
+        assert!(dispatch_media_key(&mut sessions, MediaKey::NextTrack).is_none());
+
+        sessions.get_mut("pip").unwrap().action_handlers = vec![MediaSessionAction::NextTrack];
+        let dispatch = dispatch_media_key(&mut sessions, MediaKey::NextTrack).unwrap();
+        assert!(dispatch.handled_by_page);
+        assert_eq!(dispatch.session_id, "pip");
+
+        let dispatch = dispatch_media_key(&mut sessions, MediaKey::PlayPause).unwrap();
+        assert_eq!(dispatch.action, MediaSessionAction::Play);
+        assert!(!dispatch.handled_by_page);
+    }
+}
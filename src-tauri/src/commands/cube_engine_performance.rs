@@ -1,10 +1,12 @@
 // CUBE Engine Performance Optimization
 // Resource caching, prefetch, memory management, process isolation
 
+use crate::services::browser_history::BrowserHistoryService;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
-use tauri::{AppHandle, Emitter, State};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
 
 // ============================================
 // Performance State
@@ -17,6 +19,8 @@ pub struct CubePerformanceState {
     pub process_info: RwLock<HashMap<String, ProcessInfo>>,
     pub performance_metrics: RwLock<HashMap<String, PerformanceMetrics>>,
     pub config: RwLock<PerformanceConfig>,
+    pub predictive_prefetch: RwLock<PredictivePrefetchConfig>,
+    pub prefetch_stats: RwLock<PrefetchStats>,
 }
 
 impl Default for CubePerformanceState {
@@ -28,6 +32,8 @@ impl Default for CubePerformanceState {
             process_info: RwLock::new(HashMap::new()),
             performance_metrics: RwLock::new(HashMap::new()),
             config: RwLock::new(PerformanceConfig::default()),
+            predictive_prefetch: RwLock::new(PredictivePrefetchConfig::default()),
+            prefetch_stats: RwLock::new(PrefetchStats::default()),
         }
     }
 }
@@ -58,6 +64,9 @@ pub struct CacheEntry {
     pub last_accessed: i64,
     pub access_count: u32,
     pub cache_control: CacheControl,
+    /// Stored by a speculative prefetch and not yet used by a navigation
+    #[serde(default)]
+    pub prefetched: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -98,6 +107,9 @@ pub struct PrefetchRequest {
     pub referrer: Option<String>,
     pub created_at: i64,
     pub status: PrefetchStatus,
+    /// Queued by hover intent or the history link graph rather than the page
+    #[serde(default)]
+    pub predictive: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -132,6 +144,88 @@ pub enum PrefetchStatus {
     Cancelled,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum PrefetchAggressiveness {
+    Conservative,
+    #[default]
+    Balanced,
+    Aggressive,
+}
+
+impl PrefetchAggressiveness {
+    /// Hover time after which a link counts as intent to navigate
+    pub fn hover_threshold_ms(&self) -> u64 {
+        match self {
+            PrefetchAggressiveness::Conservative => 300,
+            PrefetchAggressiveness::Balanced => 150,
+            PrefetchAggressiveness::Aggressive => 65,
+        }
+    }
+
+    /// Share of past navigations from a page a link needs to be prefetched
+    pub fn min_probability(&self) -> f64 {
+        match self {
+            PrefetchAggressiveness::Conservative => 0.5,
+            PrefetchAggressiveness::Balanced => 0.3,
+            PrefetchAggressiveness::Aggressive => 0.15,
+        }
+    }
+
+    pub fn max_predictions(&self) -> usize {
+        match self {
+            PrefetchAggressiveness::Conservative => 1,
+            PrefetchAggressiveness::Balanced => 2,
+            PrefetchAggressiveness::Aggressive => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PredictivePrefetchConfig {
+    pub enabled: bool,
+    pub aggressiveness: PrefetchAggressiveness,
+}
+
+impl Default for PredictivePrefetchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            aggressiveness: PrefetchAggressiveness::Balanced,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoverIntent {
+    pub url: String,
+    pub page_url: String,
+    pub hover_ms: u64,
+    /// Method the link or form submits with
+    pub method: Option<String>,
+    /// The link's `crossorigin` attribute
+    pub crossorigin: Option<String>,
+    /// The page's `Save-Data` signal
+    #[serde(default)]
+    pub save_data: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum HoverPrefetchAction {
+    Ignored,
+    /// Hover is building up: warm DNS and the TCP connection only
+    Preconnect { origin: String },
+    Prefetch { url: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PrefetchStats {
+    pub queued: u64,
+    pub completed: u64,
+    pub failed: u64,
+    pub hits: u64,
+    pub hit_rate: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PreloadHint {
     pub url: String,
@@ -310,6 +404,8 @@ pub struct PerformanceConfig {
     pub gpu_rasterization: bool,
     pub hardware_acceleration: bool,
     pub v8_lite_mode: bool,
+    #[serde(default)]
+    pub data_saver: bool,
 }
 
 impl Default for PerformanceConfig {
@@ -331,8 +427,314 @@ impl Default for PerformanceConfig {
             gpu_rasterization: true,
             hardware_acceleration: true,
             v8_lite_mode: false,
+            data_saver: false,
+        }
+    }
+}
+
+// ============================================
+// Predictive Prefetch
+// ============================================
+
+const PREFETCH_MAX_BYTES: usize = 5 * 1024 * 1024;
+
+impl CubePerformanceState {
+    /// Whether speculative prefetching is allowed right now
+    fn predictive_prefetch_allowed(&self, save_data: bool) -> Result<bool, String> {
+        let config = self.config.read().map_err(|e| format!("Lock error: {}", e))?;
+        if !config.prefetch_enabled || config.data_saver || save_data {
+            return Ok(false);
+        }
+        let predictive = self.predictive_prefetch.read().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(predictive.enabled)
+    }
+
+    fn aggressiveness(&self) -> Result<PrefetchAggressiveness, String> {
+        let predictive = self.predictive_prefetch.read().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(predictive.aggressiveness.clone())
+    }
+
+    /// Decide what to do for a link the user is hovering
+    pub fn handle_hover_intent(&self, intent: &HoverIntent) -> Result<HoverPrefetchAction, String> {
+        if !self.predictive_prefetch_allowed(intent.save_data)? {
+            return Ok(HoverPrefetchAction::Ignored);
+        }
+        if prefetch_ineligible(&intent.url, &intent.page_url, intent.method.as_deref(), intent.crossorigin.as_deref()).is_some() {
+            return Ok(HoverPrefetchAction::Ignored);
+        }
+
+        let threshold = self.aggressiveness()?.hover_threshold_ms();
+        if intent.hover_ms >= threshold {
+            return Ok(match self.queue_predictive(&intent.url, &intent.page_url, PrefetchPriority::High)? {
+                Some(request) => HoverPrefetchAction::Prefetch { url: request.url },
+                None => HoverPrefetchAction::Ignored,
+            });
+        }
+
+        let preconnect = self.config.read().map_err(|e| format!("Lock error: {}", e))?.preconnect_enabled;
+        match url::Url::parse(&intent.url) {
+            Ok(url) if preconnect && intent.hover_ms * 2 >= threshold => Ok(HoverPrefetchAction::Preconnect {
+                origin: url.origin().ascii_serialization(),
+            }),
+            _ => Ok(HoverPrefetchAction::Ignored),
+        }
+    }
+
+    /// Queue the likeliest next pages from `page_url`'s link transitions,
+    /// sorted by how often each was followed
+    pub fn queue_predicted_prefetches(&self, page_url: &str, transitions: &[(String, u32)], save_data: bool) -> Result<Vec<PrefetchRequest>, String> {
+        if !self.predictive_prefetch_allowed(save_data)? {
+            return Ok(vec![]);
+        }
+        let aggressiveness = self.aggressiveness()?;
+        let total: u32 = transitions.iter().map(|(_, count)| count).sum();
+        let mut queued = Vec::new();
+
+        for (url, count) in transitions {
+            if queued.len() >= aggressiveness.max_predictions() || total == 0 {
+                break;
+            }
+            if (*count as f64 / total as f64) < aggressiveness.min_probability() {
+                break;
+            }
+            if prefetch_ineligible(url, page_url, None, None).is_some() {
+                continue;
+            }
+            if let Some(request) = self.queue_predictive(url, page_url, PrefetchPriority::Medium)? {
+                queued.push(request);
+            }
+        }
+
+        Ok(queued)
+    }
+
+    fn queue_predictive(&self, url: &str, referrer: &str, priority: PrefetchPriority) -> Result<Option<PrefetchRequest>, String> {
+        if self.resource_cache.read().map_err(|e| format!("Lock error: {}", e))?.entries.contains_key(url) {
+            return Ok(None);
+        }
+
+        let mut queue = self.prefetch_queue.write().map_err(|e| format!("Lock error: {}", e))?;
+        let already_queued = queue
+            .iter()
+            .any(|r| r.url == url && !matches!(r.status, PrefetchStatus::Failed | PrefetchStatus::Cancelled));
+        if already_queued {
+            return Ok(None);
+        }
+
+        let request = PrefetchRequest {
+            url: url.to_string(),
+            priority,
+            resource_type: ResourceType::Document,
+            referrer: Some(referrer.to_string()),
+            created_at: chrono::Utc::now().timestamp_millis(),
+            status: PrefetchStatus::Pending,
+            predictive: true,
+        };
+        queue.push(request.clone());
+        drop(queue);
+
+        self.prefetch_stats.write().map_err(|e| format!("Lock error: {}", e))?.queued += 1;
+        Ok(Some(request))
+    }
+
+    fn set_prefetch_status(&self, url: &str, status: PrefetchStatus) -> Result<(), String> {
+        let mut queue = self.prefetch_queue.write().map_err(|e| format!("Lock error: {}", e))?;
+        if let Some(req) = queue.iter_mut().find(|r| r.url == url) {
+            req.status = status;
+        }
+        Ok(())
+    }
+
+    fn record_prefetch_result(&self, success: bool) -> Result<(), String> {
+        let mut stats = self.prefetch_stats.write().map_err(|e| format!("Lock error: {}", e))?;
+        if success {
+            stats.completed += 1;
+        } else {
+            stats.failed += 1;
+        }
+        stats.hit_rate = prefetch_hit_rate(&stats);
+        Ok(())
+    }
+
+    /// Cache lookup; the first use of a prefetched entry counts as a prefetch hit
+    fn lookup_cache(&self, url: &str) -> Result<Option<CacheEntry>, String> {
+        let mut cache = self.resource_cache.write().map_err(|e| format!("Lock error: {}", e))?;
+
+        let Some(entry) = cache.entries.get_mut(url) else {
+            cache.miss_count += 1;
+            return Ok(None);
+        };
+        entry.last_accessed = chrono::Utc::now().timestamp_millis();
+        entry.access_count += 1;
+        let prefetch_hit = std::mem::take(&mut entry.prefetched);
+        let entry_clone = entry.clone();
+        cache.hit_count += 1;
+        drop(cache);
+
+        if prefetch_hit {
+            let mut stats = self.prefetch_stats.write().map_err(|e| format!("Lock error: {}", e))?;
+            stats.hits += 1;
+            stats.hit_rate = prefetch_hit_rate(&stats);
+        }
+        Ok(Some(entry_clone))
+    }
+}
+
+fn prefetch_hit_rate(stats: &PrefetchStats) -> f64 {
+    if stats.completed > 0 {
+        stats.hits as f64 / stats.completed as f64
+    } else {
+        0.0
+    }
+}
+
+/// Why a link must not be fetched speculatively, if it must not
+fn prefetch_ineligible(url: &str, page_url: &str, method: Option<&str>, crossorigin: Option<&str>) -> Option<&'static str> {
+    let Ok(target) = url::Url::parse(url) else {
+        return Some("invalid URL");
+    };
+    if !matches!(target.scheme(), "http" | "https") {
+        return Some("unsupported scheme");
+    }
+    if let Some(method) = method {
+        if !method.eq_ignore_ascii_case("GET") && !method.eq_ignore_ascii_case("HEAD") {
+            return Some("non-idempotent method");
         }
     }
+
+    // GET links that change state on visit
+    let path = target.path().to_ascii_lowercase();
+    if ["logout", "log-out", "signout", "sign-out", "unsubscribe", "delete"]
+        .iter()
+        .any(|word| path.contains(word))
+    {
+        return Some("state-changing link");
+    }
+
+    let cross_origin = url::Url::parse(page_url)
+        .map(|page| page.origin() != target.origin())
+        .unwrap_or(true);
+    let credentialed = !target.username().is_empty()
+        || target.password().is_some()
+        || crossorigin.is_some_and(|c| c.eq_ignore_ascii_case("use-credentials"));
+    if cross_origin && credentialed {
+        return Some("cross-origin credentialed");
+    }
+
+    None
+}
+
+/// Warm DNS and the TCP path to the origin
+async fn preconnect_origin(origin: &str) {
+    let Ok(url) = url::Url::parse(origin) else { return };
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return;
+    };
+    let _ = tokio::time::timeout(Duration::from_secs(3), tokio::net::TcpStream::connect((host, port))).await;
+}
+
+fn parse_cache_control(header: &str) -> CacheControl {
+    let mut control = CacheControl::default();
+    for directive in header.split(',').map(|d| d.trim().to_ascii_lowercase()) {
+        let (name, value) = directive.split_once('=').unwrap_or((directive.as_str(), ""));
+        match name {
+            "no-cache" => control.no_cache = true,
+            "no-store" => control.no_store = true,
+            "must-revalidate" => control.must_revalidate = true,
+            "public" => control.public = true,
+            "private" => control.private = true,
+            "immutable" => control.immutable = true,
+            "max-age" => control.max_age = value.trim_matches('"').parse().ok(),
+            "s-maxage" => control.s_maxage = value.trim_matches('"').parse().ok(),
+            _ => {}
+        }
+    }
+    control
+}
+
+/// Fetch a document without credentials for the resource cache.
+/// `Ok(None)` when the response may not be stored.
+async fn fetch_for_prefetch(url: &str) -> Result<Option<CacheEntry>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let response = client
+        .get(url)
+        .header("Sec-Purpose", "prefetch")
+        .header("Purpose", "prefetch")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .error_for_status()
+        .map_err(|e| e.to_string())?;
+
+    if response.content_length().is_some_and(|len| len as usize > PREFETCH_MAX_BYTES) {
+        return Err("Response too large to prefetch".to_string());
+    }
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+    let cache_control = header("cache-control").map(|h| parse_cache_control(&h)).unwrap_or_default();
+    if cache_control.no_store || header("set-cookie").is_some() {
+        return Ok(None);
+    }
+    let content_type = header("content-type").unwrap_or_else(|| "text/html".to_string());
+    let etag = header("etag");
+    let last_modified = header("last-modified");
+
+    let data = response.bytes().await.map_err(|e| e.to_string())?.to_vec();
+    if data.len() > PREFETCH_MAX_BYTES {
+        return Err("Response too large to prefetch".to_string());
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    Ok(Some(CacheEntry {
+        url: url.to_string(),
+        content_type,
+        size_bytes: data.len(),
+        data,
+        etag,
+        last_modified,
+        max_age: cache_control.max_age,
+        created_at: now,
+        last_accessed: now,
+        access_count: 0,
+        cache_control,
+        prefetched: true,
+    }))
+}
+
+async fn run_prefetch(app: AppHandle, url: String) {
+    let state = app.state::<CubePerformanceState>();
+    let _ = state.set_prefetch_status(&url, PrefetchStatus::InProgress);
+
+    let result = fetch_for_prefetch(&url).await;
+    let success = result.is_ok();
+    match result {
+        Ok(Some(entry)) => {
+            if let Ok(mut cache) = state.resource_cache.write() {
+                cache.total_size_bytes += entry.size_bytes;
+                if let Some(old) = cache.entries.insert(url.clone(), entry) {
+                    cache.total_size_bytes -= old.size_bytes;
+                }
+            }
+            let _ = state.set_prefetch_status(&url, PrefetchStatus::Completed);
+        }
+        Ok(None) => {
+            let _ = state.set_prefetch_status(&url, PrefetchStatus::Cancelled);
+        }
+        Err(e) => {
+            log::debug!("Prefetch of {} failed: {}", url, e);
+            let _ = state.set_prefetch_status(&url, PrefetchStatus::Failed);
+        }
+    }
+    let _ = state.record_prefetch_result(success);
 }
 
 // ============================================
@@ -365,6 +767,7 @@ pub async fn cache_store(
         last_accessed: now,
         access_count: 1,
         cache_control: CacheControl::default(),
+        prefetched: false,
     };
     
     cache.entries.insert(url, entry);
@@ -378,18 +781,7 @@ pub async fn cache_get(
     state: State<'_, CubePerformanceState>,
     url: String,
 ) -> Result<Option<CacheEntry>, String> {
-    let mut cache = state.resource_cache.write().map_err(|e| format!("Lock error: {}", e))?;
-    
-    if let Some(entry) = cache.entries.get_mut(&url) {
-        entry.last_accessed = chrono::Utc::now().timestamp_millis();
-        entry.access_count += 1;
-        let entry_clone = entry.clone();
-        cache.hit_count += 1;
-        return Ok(Some(entry_clone));
-    }
-    
-    cache.miss_count += 1;
-    Ok(None)
+    state.lookup_cache(&url)
 }
 
 #[tauri::command]
@@ -491,6 +883,7 @@ pub async fn prefetch_add(
         referrer,
         created_at: chrono::Utc::now().timestamp_millis(),
         status: PrefetchStatus::Pending,
+        predictive: false,
     };
     
     let mut queue = state.prefetch_queue.write().map_err(|e| format!("Lock error: {}", e))?;
@@ -532,6 +925,50 @@ pub async fn prefetch_update_status(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn prefetch_report_hover(
+    state: State<'_, CubePerformanceState>,
+    app: AppHandle,
+    intent: HoverIntent,
+) -> Result<HoverPrefetchAction, String> {
+    let action = state.handle_hover_intent(&intent)?;
+    match &action {
+        HoverPrefetchAction::Prefetch { url } => {
+            tauri::async_runtime::spawn(run_prefetch(app, url.clone()));
+        }
+        HoverPrefetchAction::Preconnect { origin } => {
+            let origin = origin.clone();
+            tauri::async_runtime::spawn(async move { preconnect_origin(&origin).await });
+        }
+        HoverPrefetchAction::Ignored => {}
+    }
+    Ok(action)
+}
+
+#[tauri::command]
+pub async fn prefetch_predict_next(
+    state: State<'_, CubePerformanceState>,
+    history: State<'_, BrowserHistoryService>,
+    app: AppHandle,
+    page_url: String,
+    save_data: Option<bool>,
+) -> Result<Vec<PrefetchRequest>, String> {
+    let transitions = history.get_link_transitions(&page_url);
+    let queued = state.queue_predicted_prefetches(&page_url, &transitions, save_data.unwrap_or(false))?;
+    for request in &queued {
+        tauri::async_runtime::spawn(run_prefetch(app.clone(), request.url.clone()));
+    }
+    Ok(queued)
+}
+
+#[tauri::command]
+pub async fn prefetch_get_stats(
+    state: State<'_, CubePerformanceState>,
+) -> Result<PrefetchStats, String> {
+    let stats = state.prefetch_stats.read().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(stats.clone())
+}
+
 // ============================================
 // Tauri Commands - Memory
 // ============================================
@@ -783,3 +1220,153 @@ pub async fn perf_set_hardware_acceleration(
     config.gpu_rasterization = enabled;
     Ok(())
 }
+
+#[tauri::command]
+pub async fn perf_set_predictive_prefetch(
+    state: State<'_, CubePerformanceState>,
+    enabled: bool,
+    aggressiveness: PrefetchAggressiveness,
+) -> Result<(), String> {
+    let mut config = state.predictive_prefetch.write().map_err(|e| format!("Lock error: {}", e))?;
+    config.enabled = enabled;
+    config.aggressiveness = aggressiveness;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn perf_set_data_saver(
+    state: State<'_, CubePerformanceState>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut config = state.config.write().map_err(|e| format!("Lock error: {}", e))?;
+    config.data_saver = enabled;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hover(url: &str, hover_ms: u64) -> HoverIntent {
+        HoverIntent {
+            url: url.to_string(),
+            page_url: "https://news.example.com/".to_string(),
+            hover_ms,
+            method: None,
+            crossorigin: None,
+            save_data: false,
+        }
+    }
+
+    #[test]
+    fn test_hover_intent_queues_prefetch() {
+        let state = CubePerformanceState::default();
+
+        assert_eq!(state.handle_hover_intent(&hover("https://news.example.com/story", 20)).unwrap(), HoverPrefetchAction::Ignored);
+        assert_eq!(
+            state.handle_hover_intent(&hover("https://news.example.com/story", 100)).unwrap(),
+            HoverPrefetchAction::Preconnect { origin: "https://news.example.com".to_string() }
+        );
+        assert_eq!(
+            state.handle_hover_intent(&hover("https://news.example.com/story", 200)).unwrap(),
+            HoverPrefetchAction::Prefetch { url: "https://news.example.com/story".to_string() }
+        );
+
+        let queue = state.prefetch_queue.read().unwrap();
+        assert_eq!(queue.len(), 1);
+        assert!(queue[0].predictive);
+        assert!(matches!(queue[0].status, PrefetchStatus::Pending));
+        drop(queue);
+
+        // Hovering again doesn't queue a duplicate
+        assert_eq!(state.handle_hover_intent(&hover("https://news.example.com/story", 400)).unwrap(), HoverPrefetchAction::Ignored);
+        assert_eq!(state.prefetch_stats.read().unwrap().queued, 1);
+    }
+
+    #[test]
+    fn test_data_saver_disables_predictive_prefetch() {
+        let state = CubePerformanceState::default();
+        state.config.write().unwrap().data_saver = true;
+        assert_eq!(state.handle_hover_intent(&hover("https://news.example.com/a", 500)).unwrap(), HoverPrefetchAction::Ignored);
+        let transitions = vec![("https://news.example.com/a".to_string(), 10)];
+        assert!(state.queue_predicted_prefetches("https://news.example.com/", &transitions, false).unwrap().is_empty());
+
+        state.config.write().unwrap().data_saver = false;
+        let mut save_data = hover("https://news.example.com/a", 500);
+        save_data.save_data = true;
+        assert_eq!(state.handle_hover_intent(&save_data).unwrap(), HoverPrefetchAction::Ignored);
+        assert!(state.prefetch_queue.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unsafe_links_are_not_prefetched() {
+        let state = CubePerformanceState::default();
+
+        let mut post = hover("https://news.example.com/vote", 500);
+        post.method = Some("POST".to_string());
+        let mut credentialed = hover("https://cdn.other.com/private", 500);
+        credentialed.crossorigin = Some("use-credentials".to_string());
+        let mut same_origin_credentialed = hover("https://news.example.com/account", 500);
+        same_origin_credentialed.crossorigin = Some("use-credentials".to_string());
+
+        assert_eq!(state.handle_hover_intent(&post).unwrap(), HoverPrefetchAction::Ignored);
+        assert_eq!(state.handle_hover_intent(&credentialed).unwrap(), HoverPrefetchAction::Ignored);
+        assert_eq!(state.handle_hover_intent(&hover("https://news.example.com/logout", 500)).unwrap(), HoverPrefetchAction::Ignored);
+        assert!(matches!(state.handle_hover_intent(&same_origin_credentialed).unwrap(), HoverPrefetchAction::Prefetch { .. }));
+    }
+
+    #[test]
+    fn test_link_graph_predictions_and_hit_rate() {
+        let state = CubePerformanceState::default();
+        let transitions = vec![
+            ("https://news.example.com/top".to_string(), 6),
+            ("https://news.example.com/world".to_string(), 3),
+            ("https://news.example.com/sports".to_string(), 1),
+        ];
+        let queued = state.queue_predicted_prefetches("https://news.example.com/", &transitions, false).unwrap();
+        let urls: Vec<&str> = queued.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://news.example.com/top", "https://news.example.com/world"]);
+
+        *state.predictive_prefetch.write().unwrap() = PredictivePrefetchConfig {
+            enabled: true,
+            aggressiveness: PrefetchAggressiveness::Conservative,
+        };
+        state.prefetch_queue.write().unwrap().clear();
+        assert_eq!(state.queue_predicted_prefetches("https://news.example.com/", &transitions, false).unwrap().len(), 1);
+
+        // Two prefetches complete, one is used by a navigation
+        for url in ["https://news.example.com/top", "https://news.example.com/world"] {
+            state.resource_cache.write().unwrap().entries.insert(
+                url.to_string(),
+                CacheEntry {
+                    url: url.to_string(),
+                    content_type: "text/html".to_string(),
+                    data: vec![],
+                    size_bytes: 0,
+                    etag: None,
+                    last_modified: None,
+                    max_age: None,
+                    created_at: 0,
+                    last_accessed: 0,
+                    access_count: 0,
+                    cache_control: CacheControl::default(),
+                    prefetched: true,
+                },
+            );
+            state.record_prefetch_result(true).unwrap();
+        }
+        assert!(state.lookup_cache("https://news.example.com/top").unwrap().is_some());
+        assert!(state.lookup_cache("https://news.example.com/top").unwrap().is_some());
+
+        let stats = state.prefetch_stats.read().unwrap();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.hit_rate, 0.5);
+    }
+
+    #[test]
+    fn test_parse_cache_control() {
+        let control = parse_cache_control("public, max-age=600, no-store");
+        assert!(control.public && control.no_store);
+        assert_eq!(control.max_age, Some(600));
+    }
+}
@@ -1,7 +1,9 @@
 // CUBE Engine Security & Privacy
-// CSP, certificates, tracker blocking, fingerprint protection
+// CSP, SRI, certificates, tracker blocking, fingerprint protection
 
+use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha384, Sha512};
 use std::collections::{HashMap, HashSet};
 use std::sync::RwLock;
 use tauri::{AppHandle, Emitter, State};
@@ -17,7 +19,9 @@ pub struct CubeSecurityState {
     pub fingerprint_config: RwLock<FingerprintProtection>,
     pub permissions: RwLock<HashMap<String, SitePermissions>>,
     pub blocked_requests: RwLock<Vec<BlockedRequest>>,
+    pub sri_violations: RwLock<Vec<SriViolation>>,
     pub security_config: RwLock<SecurityConfig>,
+    pub clipboard: RwLock<Vec<ClipboardItem>>,
 }
 
 impl Default for CubeSecurityState {
@@ -29,11 +33,61 @@ impl Default for CubeSecurityState {
             fingerprint_config: RwLock::new(FingerprintProtection::default()),
             permissions: RwLock::new(HashMap::new()),
             blocked_requests: RwLock::new(Vec::new()),
+            sri_violations: RwLock::new(Vec::new()),
             security_config: RwLock::new(SecurityConfig::default()),
+            clipboard: RwLock::new(Vec::new()),
         }
     }
 }
 
+impl CubeSecurityState {
+    /// Returns the stored permissions for an origin, or the defaults when
+    /// the origin has never been configured.
+    pub fn site_permissions(&self, origin: &str) -> Result<SitePermissions, String> {
+        let permissions = self.permissions.read().map_err(|e| format!("Lock error: {}", e))?;
+
+        Ok(permissions.get(origin).cloned().unwrap_or_else(|| {
+            let mut perms = SitePermissions::default();
+            perms.origin = origin.to_string();
+            perms
+        }))
+    }
+
+    pub fn set_site_permission(
+        &self,
+        origin: &str,
+        permission_type: &str,
+        permission_state: PermissionState,
+    ) -> Result<(), String> {
+        let mut permissions = self.permissions.write().map_err(|e| format!("Lock error: {}", e))?;
+
+        let perms = permissions.entry(origin.to_string()).or_insert_with(|| {
+            let mut p = SitePermissions::default();
+            p.origin = origin.to_string();
+            p
+        });
+
+        match permission_type {
+            "camera" => perms.camera = permission_state,
+            "microphone" => perms.microphone = permission_state,
+            "geolocation" => perms.geolocation = permission_state,
+            "notifications" => perms.notifications = permission_state,
+            "clipboard_read" => perms.clipboard_read = permission_state,
+            "clipboard_write" => perms.clipboard_write = permission_state,
+            "midi" => perms.midi = permission_state,
+            "usb" => perms.usb = permission_state,
+            "serial" => perms.serial = permission_state,
+            "bluetooth" => perms.bluetooth = permission_state,
+            "storage_access" => perms.storage_access = permission_state,
+            "autoplay" => perms.autoplay = permission_state,
+            "popups" => perms.popups = permission_state,
+            _ => return Err(format!("Unknown permission type: {}", permission_type)),
+        }
+
+        Ok(())
+    }
+}
+
 // ============================================
 // Content Security Policy
 // ============================================
@@ -57,6 +111,9 @@ pub struct ContentSecurityPolicy {
     pub block_all_mixed_content: bool,
     pub report_uri: Option<String>,
     pub report_only: bool,
+    /// Resource types ("script", "style") that must carry a valid integrity attribute
+    #[serde(default)]
+    pub require_sri_for: Vec<String>,
 }
 
 impl Default for ContentSecurityPolicy {
@@ -79,6 +136,7 @@ impl Default for ContentSecurityPolicy {
             block_all_mixed_content: true,
             report_uri: None,
             report_only: false,
+            require_sri_for: Vec::new(),
         }
     }
 }
@@ -94,6 +152,137 @@ pub struct CSPViolation {
     pub timestamp: i64,
 }
 
+// ============================================
+// Subresource Integrity
+// ============================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SriAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl SriAlgorithm {
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix.to_ascii_lowercase().as_str() {
+            "sha256" => Some(Self::Sha256),
+            "sha384" => Some(Self::Sha384),
+            "sha512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha256 => Sha256::digest(data).to_vec(),
+            Self::Sha384 => Sha384::digest(data).to_vec(),
+            Self::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SriCheckRequest {
+    pub document_uri: String,
+    pub resource_url: String,
+    pub resource_type: String,
+    pub integrity: Option<String>,
+    pub crossorigin: Option<String>,
+    pub content: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SriFailureReason {
+    HashMismatch,
+    MissingIntegrity,
+    MissingCrossOrigin,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SriCheckResult {
+    pub allowed: bool,
+    pub matched_algorithm: Option<SriAlgorithm>,
+    pub failure: Option<SriFailureReason>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SriViolation {
+    pub document_uri: String,
+    pub resource_url: String,
+    pub resource_type: String,
+    pub integrity: Option<String>,
+    pub actual_hash: Option<String>,
+    pub reason: SriFailureReason,
+    pub timestamp: i64,
+}
+
+/// Parses an integrity attribute, keeping only the tokens that use the
+/// strongest supported algorithm present (per the SRI spec). Unknown
+/// algorithms and malformed tokens are ignored.
+fn parse_integrity(integrity: &str) -> Vec<(SriAlgorithm, String)> {
+    let tokens: Vec<(SriAlgorithm, String)> = integrity
+        .split_whitespace()
+        .filter_map(|token| {
+            // Strip any "?option" suffix
+            let token = token.split('?').next().unwrap_or(token);
+            let (alg, hash) = token.split_once('-')?;
+            Some((SriAlgorithm::from_prefix(alg)?, hash.to_string()))
+        })
+        .collect();
+
+    match tokens.iter().map(|(alg, _)| *alg).max() {
+        Some(strongest) => tokens.into_iter().filter(|(alg, _)| *alg == strongest).collect(),
+        None => Vec::new(),
+    }
+}
+
+fn is_cross_origin(document_uri: &str, resource_url: &str) -> bool {
+    match (url::Url::parse(document_uri), url::Url::parse(resource_url)) {
+        (Ok(doc), Ok(res)) => doc.origin() != res.origin(),
+        _ => false,
+    }
+}
+
+fn evaluate_sri(policy: Option<&ContentSecurityPolicy>, request: &SriCheckRequest) -> SriCheckResult {
+    let required = policy.is_some_and(|p| {
+        p.require_sri_for.iter().any(|t| t.eq_ignore_ascii_case(&request.resource_type))
+    });
+    let blocked = |failure| SriCheckResult {
+        allowed: false,
+        matched_algorithm: None,
+        failure: Some(failure),
+    };
+
+    let metadata = request.integrity.as_deref().map(parse_integrity).unwrap_or_default();
+    if metadata.is_empty() {
+        // No usable integrity metadata: only a policy requirement can block
+        if required {
+            return blocked(SriFailureReason::MissingIntegrity);
+        }
+        return SriCheckResult { allowed: true, matched_algorithm: None, failure: None };
+    }
+
+    // Cross-origin resources must be fetched in CORS mode for SRI to apply
+    let cors_mode = matches!(
+        request.crossorigin.as_deref().map(str::to_ascii_lowercase).as_deref(),
+        Some("" | "anonymous" | "use-credentials")
+    );
+    if is_cross_origin(&request.document_uri, &request.resource_url) && !cors_mode {
+        return blocked(SriFailureReason::MissingCrossOrigin);
+    }
+
+    let algorithm = metadata[0].0;
+    let actual = general_purpose::STANDARD.encode(algorithm.digest(&request.content));
+    if metadata.iter().any(|(_, expected)| *expected == actual) {
+        return SriCheckResult { allowed: true, matched_algorithm: Some(algorithm), failure: None };
+    }
+
+    blocked(SriFailureReason::HashMismatch)
+}
+
 // ============================================
 // Certificate Handling
 // ============================================
@@ -369,6 +558,146 @@ pub enum CookiePermission {
     SessionOnly,
 }
 
+// ============================================
+// Clipboard
+// ============================================
+
+/// Formats that can carry markup, file references or links and are only
+/// exposed to pages when listed in `ClipboardPolicy::allowed_sensitive_formats`.
+const SENSITIVE_CLIPBOARD_FORMATS: &[&str] = &[
+    "text/html",
+    "text/uri-list",
+    "text/rtf",
+    "application/rtf",
+    "image/svg+xml",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardPolicy {
+    pub max_payload_bytes: usize,
+    pub allowed_sensitive_formats: Vec<String>,
+}
+
+impl Default for ClipboardPolicy {
+    fn default() -> Self {
+        Self {
+            max_payload_bytes: 1024 * 1024,
+            allowed_sensitive_formats: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipboardItem {
+    pub mime_type: String,
+    pub data: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardWriteRequest {
+    pub origin: String,
+    pub items: Vec<ClipboardItem>,
+    /// Set by the page bridge when the call happened inside a user activation
+    pub user_gesture: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardBlockReason {
+    PermissionDenied,
+    PermissionPrompt,
+    UserGestureRequired,
+    PayloadTooLarge,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipboardAccessResult {
+    pub allowed: bool,
+    pub items: Vec<ClipboardItem>,
+    pub stripped_formats: Vec<String>,
+    pub blocked: Option<ClipboardBlockReason>,
+}
+
+impl ClipboardAccessResult {
+    fn blocked(reason: ClipboardBlockReason) -> Self {
+        Self { allowed: false, items: Vec::new(), stripped_formats: Vec::new(), blocked: Some(reason) }
+    }
+}
+
+fn is_sensitive_format(mime_type: &str) -> bool {
+    // Custom "web " formats are opaque to us, so treat them as sensitive too
+    mime_type.starts_with("web ")
+        || SENSITIVE_CLIPBOARD_FORMATS.iter().any(|f| f.eq_ignore_ascii_case(mime_type))
+}
+
+/// Drops sensitive formats the policy does not allow and cleans up the rest:
+/// control characters are removed and allowed HTML loses its scripts.
+fn sanitize_clipboard_items(
+    policy: &ClipboardPolicy,
+    items: &[ClipboardItem],
+) -> (Vec<ClipboardItem>, Vec<String>) {
+    let mut kept = Vec::new();
+    let mut stripped = Vec::new();
+
+    for item in items {
+        let mime_type = item.mime_type.trim().to_ascii_lowercase();
+        if is_sensitive_format(&mime_type)
+            && !policy.allowed_sensitive_formats.iter().any(|f| f.eq_ignore_ascii_case(&mime_type))
+        {
+            stripped.push(mime_type);
+            continue;
+        }
+
+        let mut data: String = item.data
+            .chars()
+            .filter(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+            .collect();
+        if mime_type == "text/html" {
+            let scripts = regex::Regex::new(r"(?is)<script\b.*?(</script\s*>|$)").unwrap();
+            data = scripts.replace_all(&data, "").into_owned();
+        }
+        kept.push(ClipboardItem { mime_type, data });
+    }
+
+    (kept, stripped)
+}
+
+fn evaluate_clipboard_read(
+    perms: &SitePermissions,
+    policy: &ClipboardPolicy,
+    clipboard: &[ClipboardItem],
+) -> ClipboardAccessResult {
+    match perms.clipboard_read {
+        PermissionState::Granted => {
+            let (items, stripped_formats) = sanitize_clipboard_items(policy, clipboard);
+            ClipboardAccessResult { allowed: true, items, stripped_formats, blocked: None }
+        }
+        PermissionState::Denied => ClipboardAccessResult::blocked(ClipboardBlockReason::PermissionDenied),
+        PermissionState::Prompt => ClipboardAccessResult::blocked(ClipboardBlockReason::PermissionPrompt),
+    }
+}
+
+fn evaluate_clipboard_write(
+    perms: &SitePermissions,
+    policy: &ClipboardPolicy,
+    request: &ClipboardWriteRequest,
+) -> ClipboardAccessResult {
+    if matches!(perms.clipboard_write, PermissionState::Denied) {
+        return ClipboardAccessResult::blocked(ClipboardBlockReason::PermissionDenied);
+    }
+    // Writes never prompt, but they must come from a user activation
+    if !request.user_gesture {
+        return ClipboardAccessResult::blocked(ClipboardBlockReason::UserGestureRequired);
+    }
+    let size: usize = request.items.iter().map(|item| item.data.len()).sum();
+    if size > policy.max_payload_bytes {
+        return ClipboardAccessResult::blocked(ClipboardBlockReason::PayloadTooLarge);
+    }
+
+    let (items, stripped_formats) = sanitize_clipboard_items(policy, &request.items);
+    ClipboardAccessResult { allowed: true, items, stripped_formats, blocked: None }
+}
+
 // ============================================
 // Security Config
 // ============================================
@@ -386,6 +715,8 @@ pub struct SecurityConfig {
     pub phishing_protection: bool,
     pub malware_protection: bool,
     pub ssl_error_override: bool,
+    #[serde(default)]
+    pub clipboard: ClipboardPolicy,
 }
 
 impl Default for SecurityConfig {
@@ -402,6 +733,7 @@ impl Default for SecurityConfig {
             phishing_protection: true,
             malware_protection: true,
             ssl_error_override: false,
+            clipboard: ClipboardPolicy::default(),
         }
     }
 }
@@ -483,6 +815,60 @@ pub async fn csp_report_violation(
     Ok(())
 }
 
+// ============================================
+// Tauri Commands - SRI
+// ============================================
+
+#[tauri::command]
+pub async fn sri_check_resource(
+    state: State<'_, CubeSecurityState>,
+    app: AppHandle,
+    origin: String,
+    request: SriCheckRequest,
+) -> Result<SriCheckResult, String> {
+    let result = {
+        let policies = state.csp_policies.read().map_err(|e| format!("Lock error: {}", e))?;
+        evaluate_sri(policies.get(&origin), &request)
+    };
+
+    if let Some(reason) = result.failure.clone() {
+        let actual_hash = request.integrity.as_deref()
+            .and_then(|integrity| parse_integrity(integrity).first().map(|(alg, _)| *alg))
+            .map(|alg| general_purpose::STANDARD.encode(alg.digest(&request.content)));
+
+        let violation = SriViolation {
+            document_uri: request.document_uri,
+            resource_url: request.resource_url,
+            resource_type: request.resource_type,
+            integrity: request.integrity,
+            actual_hash,
+            reason,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        };
+
+        let mut violations = state.sri_violations.write().map_err(|e| format!("Lock error: {}", e))?;
+        violations.push(violation.clone());
+
+        let _ = app.emit("sri-violation", &violation);
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn security_get_sri_violations(
+    state: State<'_, CubeSecurityState>,
+    document_uri: Option<String>,
+) -> Result<Vec<SriViolation>, String> {
+    let violations = state.sri_violations.read().map_err(|e| format!("Lock error: {}", e))?;
+
+    if let Some(uri) = document_uri {
+        Ok(violations.iter().filter(|v| v.document_uri == uri).cloned().collect())
+    } else {
+        Ok(violations.clone())
+    }
+}
+
 // ============================================
 // Tauri Commands - Certificates
 // ============================================
@@ -754,13 +1140,7 @@ pub async fn permission_get(
     state: State<'_, CubeSecurityState>,
     origin: String,
 ) -> Result<SitePermissions, String> {
-    let permissions = state.permissions.read().map_err(|e| format!("Lock error: {}", e))?;
-    
-    Ok(permissions.get(&origin).cloned().unwrap_or_else(|| {
-        let mut perms = SitePermissions::default();
-        perms.origin = origin;
-        perms
-    }))
+    state.site_permissions(&origin)
 }
 
 #[tauri::command]
@@ -770,32 +1150,7 @@ pub async fn permission_set(
     permission_type: String,
     permission_state: PermissionState,
 ) -> Result<(), String> {
-    let mut permissions = state.permissions.write().map_err(|e| format!("Lock error: {}", e))?;
-    
-    let perms = permissions.entry(origin.clone()).or_insert_with(|| {
-        let mut p = SitePermissions::default();
-        p.origin = origin;
-        p
-    });
-    
-    match permission_type.as_str() {
-        "camera" => perms.camera = permission_state,
-        "microphone" => perms.microphone = permission_state,
-        "geolocation" => perms.geolocation = permission_state,
-        "notifications" => perms.notifications = permission_state,
-        "clipboard_read" => perms.clipboard_read = permission_state,
-        "clipboard_write" => perms.clipboard_write = permission_state,
-        "midi" => perms.midi = permission_state,
-        "usb" => perms.usb = permission_state,
-        "serial" => perms.serial = permission_state,
-        "bluetooth" => perms.bluetooth = permission_state,
-        "storage_access" => perms.storage_access = permission_state,
-        "autoplay" => perms.autoplay = permission_state,
-        "popups" => perms.popups = permission_state,
-        _ => return Err(format!("Unknown permission type: {}", permission_type)),
-    }
-    
-    Ok(())
+    state.set_site_permission(&origin, &permission_type, permission_state)
 }
 
 #[tauri::command]
@@ -816,6 +1171,59 @@ pub async fn permission_get_all(
     Ok(permissions.values().cloned().collect())
 }
 
+// ============================================
+// Tauri Commands - Clipboard
+// ============================================
+
+#[tauri::command]
+pub async fn cube_engine_set_clipboard_permission(
+    security: State<'_, CubeSecurityState>,
+    origin: String,
+    state: PermissionState,
+) -> Result<(), String> {
+    security.set_site_permission(&origin, "clipboard_read", state.clone())?;
+    security.set_site_permission(&origin, "clipboard_write", state)
+}
+
+#[tauri::command]
+pub async fn cube_engine_clipboard_read(
+    state: State<'_, CubeSecurityState>,
+    app: AppHandle,
+    origin: String,
+) -> Result<ClipboardAccessResult, String> {
+    let perms = state.site_permissions(&origin)?;
+    let result = {
+        let config = state.security_config.read().map_err(|e| format!("Lock error: {}", e))?;
+        let clipboard = state.clipboard.read().map_err(|e| format!("Lock error: {}", e))?;
+        evaluate_clipboard_read(&perms, &config.clipboard, &clipboard)
+    };
+
+    if result.blocked == Some(ClipboardBlockReason::PermissionPrompt) {
+        let _ = app.emit("clipboard-permission-prompt", &origin);
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn cube_engine_clipboard_write(
+    state: State<'_, CubeSecurityState>,
+    request: ClipboardWriteRequest,
+) -> Result<ClipboardAccessResult, String> {
+    let perms = state.site_permissions(&request.origin)?;
+    let result = {
+        let config = state.security_config.read().map_err(|e| format!("Lock error: {}", e))?;
+        evaluate_clipboard_write(&perms, &config.clipboard, &request)
+    };
+
+    if result.allowed {
+        let mut clipboard = state.clipboard.write().map_err(|e| format!("Lock error: {}", e))?;
+        *clipboard = result.items.clone();
+    }
+
+    Ok(result)
+}
+
 // ============================================
 // Tauri Commands - Security Config
 // ============================================
@@ -876,3 +1284,159 @@ pub struct SafeBrowsingResult {
     pub threat_type: Option<String>,
     pub platform_type: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn integrity_for(alg: SriAlgorithm, data: &[u8]) -> String {
+        let prefix = match alg {
+            SriAlgorithm::Sha256 => "sha256",
+            SriAlgorithm::Sha384 => "sha384",
+            SriAlgorithm::Sha512 => "sha512",
+        };
+        format!("{}-{}", prefix, general_purpose::STANDARD.encode(alg.digest(data)))
+    }
+
+    fn request(integrity: Option<String>, content: &[u8]) -> SriCheckRequest {
+        SriCheckRequest {
+            document_uri: "https://example.com/index.html".to_string(),
+            resource_url: "https://example.com/app.js".to_string(),
+            resource_type: "script".to_string(),
+            integrity,
+            crossorigin: None,
+            content: content.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_matching_hash_is_allowed() {
+        let body = b"console.log('hello');";
+        for alg in [SriAlgorithm::Sha256, SriAlgorithm::Sha384, SriAlgorithm::Sha512] {
+            let result = evaluate_sri(None, &request(Some(integrity_for(alg, body)), body));
+            assert!(result.allowed);
+            assert_eq!(result.matched_algorithm, Some(alg));
+        }
+    }
+
+    #[test]
+    fn test_tampered_resource_is_blocked() {
+        let integrity = integrity_for(SriAlgorithm::Sha384, b"console.log('hello');");
+        let result = evaluate_sri(None, &request(Some(integrity), b"console.log('pwned');"));
+        assert!(!result.allowed);
+        assert_eq!(result.failure, Some(SriFailureReason::HashMismatch));
+    }
+
+    #[test]
+    fn test_multi_hash_fallback() {
+        let body = b"body { color: red; }";
+        let integrity = format!(
+            "{} {}",
+            integrity_for(SriAlgorithm::Sha384, b"previous release"),
+            integrity_for(SriAlgorithm::Sha384, body)
+        );
+        assert!(evaluate_sri(None, &request(Some(integrity), body)).allowed);
+
+        // Only the strongest algorithm is consulted, so a matching weaker hash doesn't pass
+        let integrity = format!(
+            "{} {}",
+            integrity_for(SriAlgorithm::Sha256, body),
+            integrity_for(SriAlgorithm::Sha512, b"something else")
+        );
+        assert!(!evaluate_sri(None, &request(Some(integrity), body)).allowed);
+    }
+
+    #[test]
+    fn test_policy_requires_integrity() {
+        let policy = ContentSecurityPolicy {
+            require_sri_for: vec!["script".to_string()],
+            ..Default::default()
+        };
+
+        let result = evaluate_sri(Some(&policy), &request(None, b"x"));
+        assert_eq!(result.failure, Some(SriFailureReason::MissingIntegrity));
+
+        // Unsupported algorithms count as absent integrity
+        let result = evaluate_sri(Some(&policy), &request(Some("md5-abc".to_string()), b"x"));
+        assert_eq!(result.failure, Some(SriFailureReason::MissingIntegrity));
+
+        // Without a requirement, absent integrity is fine
+        assert!(evaluate_sri(None, &request(None, b"x")).allowed);
+    }
+
+    #[test]
+    fn test_cross_origin_requires_cors_mode() {
+        let body = b"x";
+        let mut req = request(Some(integrity_for(SriAlgorithm::Sha256, body)), body);
+        req.resource_url = "https://cdn.example.net/x.js".to_string();
+        assert_eq!(
+            evaluate_sri(None, &req).failure,
+            Some(SriFailureReason::MissingCrossOrigin)
+        );
+
+        req.crossorigin = Some("anonymous".to_string());
+        assert!(evaluate_sri(None, &req).allowed);
+    }
+
+    fn clipboard_perms(read: PermissionState) -> SitePermissions {
+        SitePermissions { origin: "https://example.com".to_string(), clipboard_read: read, ..Default::default() }
+    }
+
+    fn clipboard_contents() -> Vec<ClipboardItem> {
+        vec![
+            ClipboardItem { mime_type: "text/plain".to_string(), data: "hello\u{0}".to_string() },
+            ClipboardItem { mime_type: "text/html".to_string(), data: "<b>hello</b>".to_string() },
+        ]
+    }
+
+    #[test]
+    fn test_granted_clipboard_read_succeeds() {
+        let result = evaluate_clipboard_read(
+            &clipboard_perms(PermissionState::Granted),
+            &ClipboardPolicy::default(),
+            &clipboard_contents(),
+        );
+        assert!(result.allowed);
+        assert_eq!(result.items, vec![ClipboardItem { mime_type: "text/plain".to_string(), data: "hello".to_string() }]);
+        assert_eq!(result.stripped_formats, vec!["text/html".to_string()]);
+
+        let policy = ClipboardPolicy { allowed_sensitive_formats: vec!["text/html".to_string()], ..Default::default() };
+        let result = evaluate_clipboard_read(&clipboard_perms(PermissionState::Granted), &policy, &clipboard_contents());
+        assert_eq!(result.items.len(), 2);
+    }
+
+    #[test]
+    fn test_ungranted_clipboard_read_is_blocked() {
+        let contents = clipboard_contents();
+        let prompt = evaluate_clipboard_read(&clipboard_perms(PermissionState::Prompt), &ClipboardPolicy::default(), &contents);
+        assert!(!prompt.allowed);
+        assert!(prompt.items.is_empty());
+        assert_eq!(prompt.blocked, Some(ClipboardBlockReason::PermissionPrompt));
+
+        let denied = evaluate_clipboard_read(&clipboard_perms(PermissionState::Denied), &ClipboardPolicy::default(), &contents);
+        assert_eq!(denied.blocked, Some(ClipboardBlockReason::PermissionDenied));
+    }
+
+    #[test]
+    fn test_clipboard_write_requires_user_gesture() {
+        let perms = clipboard_perms(PermissionState::Prompt);
+        let policy = ClipboardPolicy { max_payload_bytes: 16, allowed_sensitive_formats: vec!["text/html".to_string()] };
+        let mut request = ClipboardWriteRequest {
+            origin: perms.origin.clone(),
+            items: vec![ClipboardItem { mime_type: "text/html".to_string(), data: "<script>x()</script>ok".to_string() }],
+            user_gesture: false,
+        };
+
+        let result = evaluate_clipboard_write(&perms, &policy, &request);
+        assert_eq!(result.blocked, Some(ClipboardBlockReason::UserGestureRequired));
+
+        request.user_gesture = true;
+        let result = evaluate_clipboard_write(&perms, &policy, &request);
+        assert!(result.allowed);
+        assert_eq!(result.items[0].data, "ok");
+
+        request.items[0].data = "x".repeat(17);
+        let result = evaluate_clipboard_write(&perms, &policy, &request);
+        assert_eq!(result.blocked, Some(ClipboardBlockReason::PayloadTooLarge));
+    }
+}
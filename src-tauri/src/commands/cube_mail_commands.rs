@@ -22,8 +22,10 @@ use crate::services::cube_mail_service::{
     Email,
     MailLabel,
     ComposeDraft,
+    AccountSignatures,
     EmailAddress,
     EmailAttachment,
+    AttachmentScanStatus,
     ScreenerConfig,
     ScreenerDecision,
     ScreenerSender,
@@ -32,6 +34,7 @@ use crate::services::cube_mail_service::{
     ImapConfig,
     SmtpConfig,
 };
+use crate::services::mail_attachments::AttachmentScanConfig;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // ACCOUNT COMMANDS
@@ -312,6 +315,7 @@ pub async fn cube_mail_send_email(
     attachments: Option<Vec<AttachmentInput>>,
     encryption_enabled: Option<bool>,
     in_reply_to: Option<String>,
+    from_identity: Option<String>,
 ) -> Result<Email, String> {
     info!("📬 Sending email from account: {}", account_id);
     
@@ -330,6 +334,7 @@ pub async fn cube_mail_send_email(
         encryption_enabled: encryption_enabled.unwrap_or(false),
         read_receipt: false,
         scheduled_send: None,
+        from_identity,
         created_at: chrono::Utc::now(),
         updated_at: chrono::Utc::now(),
     };
@@ -337,6 +342,43 @@ pub async fn cube_mail_send_email(
     state.send_email(draft).await
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// SIGNATURE COMMANDS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Set the account's default signature, or an alias's when `identity` is given
+#[tauri::command]
+pub async fn cube_mail_set_signature(
+    state: State<'_, CubeMailServiceState>,
+    account_id: String,
+    html: String,
+    plain: String,
+    identity: Option<String>,
+) -> Result<AccountSignatures, String> {
+    info!("✍️ Setting signature for account: {}", account_id);
+    state.set_signature(&account_id, identity, html, plain).await
+}
+
+/// Get the signatures configured for an account
+#[tauri::command]
+pub async fn cube_mail_get_signatures(
+    state: State<'_, CubeMailServiceState>,
+    account_id: String,
+) -> Result<AccountSignatures, String> {
+    Ok(state.get_signatures(&account_id).await)
+}
+
+/// Choose whether signatures are added to new messages and to replies/forwards
+#[tauri::command]
+pub async fn cube_mail_set_signature_options(
+    state: State<'_, CubeMailServiceState>,
+    account_id: String,
+    use_for_new: Option<bool>,
+    use_for_replies: Option<bool>,
+) -> Result<AccountSignatures, String> {
+    state.set_signature_options(&account_id, use_for_new, use_for_replies).await
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // SCREENER COMMANDS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -520,6 +562,53 @@ pub async fn cube_mail_ai_summarize(
     Ok(state.ai_summarize(&email).await)
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// ATTACHMENT COMMANDS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Save an attachment to disk (blocked while quarantined)
+#[tauri::command]
+pub async fn cube_mail_save_attachment(
+    state: State<'_, CubeMailServiceState>,
+    account_id: String,
+    email_id: String,
+    attachment_id: String,
+    destination: String,
+) -> Result<u64, String> {
+    state
+        .save_attachment(&account_id, &email_id, &attachment_id, std::path::Path::new(&destination))
+        .await
+}
+
+/// Release an attachment flagged by the scanner
+#[tauri::command]
+pub async fn cube_mail_release_attachment(
+    state: State<'_, CubeMailServiceState>,
+    account_id: String,
+    email_id: String,
+    attachment_id: String,
+) -> Result<EmailAttachment, String> {
+    state.release_attachment(&account_id, &email_id, &attachment_id).await
+}
+
+/// Get attachment scanning configuration
+#[tauri::command]
+pub async fn cube_mail_get_attachment_scan_config(
+    state: State<'_, CubeMailServiceState>,
+) -> Result<AttachmentScanConfig, String> {
+    Ok(state.get_attachment_scan_config().await)
+}
+
+/// Update attachment scanning configuration
+#[tauri::command]
+pub async fn cube_mail_update_attachment_scan_config(
+    state: State<'_, CubeMailServiceState>,
+    config: AttachmentScanConfig,
+) -> Result<(), String> {
+    info!("Updating attachment scan engine: {:?}", config.engine);
+    state.update_attachment_scan_config(config).await
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // HELPERS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -576,6 +665,9 @@ impl From<AttachmentInput> for EmailAttachment {
             encrypted: false,
             download_url: None,
             local_path: None,
+            scan_status: AttachmentScanStatus::NotScanned,
+            scan_detail: None,
+            sha256: None,
         }
     }
 }
@@ -687,6 +779,30 @@ pub async fn cube_mail_oauth2_refresh(
     })
 }
 
+/// Store new OAuth2 tokens for an account whose refresh token was revoked
+#[tauri::command]
+pub async fn cube_mail_reauthorize_account(
+    mail_state: State<'_, CubeMailServiceState>,
+    account_id: String,
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+) -> Result<MailAccount, String> {
+    info!("🔐 Re-authorizing email account: {}", account_id);
+    
+    let tokens = OAuth2Tokens {
+        access_token,
+        refresh_token,
+        token_type: "Bearer".to_string(),
+        expires_in,
+        expires_at: expires_in.map(|secs| chrono::Utc::now() + chrono::Duration::seconds(secs)),
+        scope: None,
+        id_token: None,
+    };
+    
+    mail_state.reauthorize_account(&account_id, tokens).await
+}
+
 /// Add email account with OAuth2 tokens (complete flow)
 #[tauri::command]
 pub async fn cube_mail_add_account_with_oauth(
@@ -696,6 +812,7 @@ pub async fn cube_mail_add_account_with_oauth(
     provider: String,
     access_token: String,
     refresh_token: Option<String>,
+    expires_in: Option<i64>,
 ) -> Result<MailAccount, String> {
     info!("📬 Adding OAuth2 email account: {}", email);
     
@@ -732,7 +849,7 @@ pub async fn cube_mail_add_account_with_oauth(
         password: String::new(), // Not used with OAuth2
         oauth2_token: Some(access_token.clone()),
         oauth2_refresh_token: refresh_token.clone(),
-        oauth2_expires_at: None, // Should be set based on token response
+        oauth2_expires_at: expires_in.map(|secs| chrono::Utc::now() + chrono::Duration::seconds(secs)),
     };
     
     // Configure SMTP with OAuth2
@@ -1,27 +1,71 @@
 // CUBE Web Engine Commands - Tauri commands for the embedded browser engine
 // These commands interface between the frontend and the CUBE Web Engine
 
+use crate::commands::passwords_new::PasswordState;
+use crate::services::accessibility_tree::{A11yAuditReport, AccessibilityDocument, AxNode};
 use crate::services::cube_web_engine::{
-    CubeWebEngineConfig, CubeWebEngineState, CubeWebTab, DomCommand, FetchResponse,
-    JsExecutionResult, PageContent, PrintOptions, ScreenshotOptions, TabBounds, TabUpdate,
-    WebFetcher,
+    BfCacheStatus, CubeWebEngineConfig, CubeWebEngineState, CubeWebTab, DomCommand,
+    FetchResponse, JsExecutionResult, PageContent, PageSnapshot, PrintOptions,
+    ScreenshotOptions, TabBounds, TabUpdate, WebFetcher,
 };
+use crate::services::browser_privacy::PrivacyDashboardService;
+use crate::services::device_emulation::{
+    device_presets, DeviceEmulation, DeviceEmulationState, DeviceProfile, DeviceSelection, Orientation,
+};
+use crate::services::doh_resolver::{DohConfig, DohResolver, Resolution};
+use crate::services::http_auth::{self, AuthPrompt, HttpAuthCache, HttpCredentials};
+use crate::services::lqip::Lqip;
+use crate::services::media_emulation::{MediaEmulation, MediaEmulationState};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 use tauri::{AppHandle, Emitter, Manager, State};
 
 /// Global state for the CUBE Web Engine
 pub struct CubeWebEngineGlobalState {
     pub engine: CubeWebEngineState,
     pub fetcher: RwLock<Option<WebFetcher>>,
+    /// HTTP auth credentials cached for the session
+    pub auth: Arc<HttpAuthCache>,
+    /// DNS-over-HTTPS resolver shared by every fetcher
+    pub dns: Arc<DohResolver>,
+    /// Emulated media features, per tab and user-wide
+    pub media: MediaEmulationState,
+    /// Emulated phone/tablet per tab
+    pub devices: DeviceEmulationState,
+}
+
+impl CubeWebEngineGlobalState {
+    fn new_fetcher(&self, config: CubeWebEngineConfig) -> WebFetcher {
+        WebFetcher::with_resolver(config, self.dns.clone()).with_auth_cache(self.auth.clone())
+    }
+
+    /// The session fetcher, sending the user agent of the device emulated in `tab_id`
+    fn tab_fetcher(&self, tab_id: &str) -> Result<Option<WebFetcher>, String> {
+        let guard = self.fetcher.read().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(guard.clone().map(|fetcher| fetcher.with_user_agent(self.devices.user_agent(tab_id))))
+    }
+
+    /// Page HTML as the tab should render it, with media and device emulation applied
+    fn emulated_html(&self, tab_id: &str, html: &str) -> String {
+        self.devices.apply(tab_id, &self.media.apply(tab_id, html))
+    }
 }
 
 impl Default for CubeWebEngineGlobalState {
     fn default() -> Self {
+        let auth = Arc::new(HttpAuthCache::new());
+        let dns = Arc::new(DohResolver::new());
         Self {
             engine: CubeWebEngineState::new(),
-            fetcher: RwLock::new(Some(WebFetcher::new(CubeWebEngineConfig::default()))),
+            fetcher: RwLock::new(Some(
+                WebFetcher::with_resolver(CubeWebEngineConfig::default(), dns.clone())
+                    .with_auth_cache(auth.clone()),
+            )),
+            auth,
+            dns,
+            media: MediaEmulationState::new(),
+            devices: DeviceEmulationState::new(),
         }
     }
 }
@@ -73,6 +117,8 @@ pub async fn cube_engine_close_tab(
     println!("❌ [CUBE ENGINE] Closing tab: {}", tab_id);
 
     state.engine.close_tab(&tab_id)?;
+    state.media.clear_tab(&tab_id);
+    state.devices.clear(&tab_id);
 
     // Emit tab closed event
     let _ = app.emit("cube-engine-tab-closed", serde_json::json!({
@@ -164,9 +210,13 @@ pub async fn cube_engine_navigate(
     app: AppHandle,
     tab_id: String,
     url: String,
+    snapshot: Option<PageSnapshot>,
 ) -> Result<(), String> {
     println!("🔗 [CUBE ENGINE] Navigating {} to {}", tab_id, url);
 
+    // Must run before the new page replaces the cached content of the current one
+    leave_page(&state, &app, &tab_id, snapshot)?;
+
     // Update tab state to loading
     state.engine.update_tab(&tab_id, TabUpdate {
         url: Some(url.clone()),
@@ -181,13 +231,10 @@ pub async fn cube_engine_navigate(
     }));
 
     // Fetch the page content - clone fetcher to avoid holding lock across await
-    let fetcher_opt = {
-        let guard = state.fetcher.read().map_err(|e| format!("Lock error: {}", e))?;
-        guard.clone()
-    };
+    let fetcher_opt = state.tab_fetcher(&tab_id)?;
     
     if let Some(fetcher) = fetcher_opt.as_ref() {
-        match fetcher.fetch_page(&url).await {
+        match fetch_page_with_auth(&state, &app, fetcher, &url).await {
             Ok(content) => {
                 // Cache the page content
                 state.engine.cache_page(&tab_id, content.clone())?;
@@ -199,6 +246,7 @@ pub async fn cube_engine_navigate(
                 state.engine.update_tab(&tab_id, TabUpdate {
                     is_loading: Some(false),
                     can_go_back: Some(true),
+                    can_go_forward: Some(false),
                     ..Default::default()
                 })?;
 
@@ -206,7 +254,7 @@ pub async fn cube_engine_navigate(
                 let _ = app.emit("cube-engine-navigation-completed", serde_json::json!({
                     "tabId": tab_id,
                     "url": url,
-                    "html": content.html,
+                    "html": state.emulated_html(&tab_id, &content.html),
                     "baseUrl": content.base_url
                 }));
             }
@@ -236,6 +284,7 @@ pub async fn cube_engine_navigate(
 #[tauri::command]
 pub async fn cube_engine_fetch_url(
     state: State<'_, CubeWebEngineGlobalState>,
+    app: AppHandle,
     url: String,
     _headers: Option<HashMap<String, String>>,
 ) -> Result<FetchResponse, String> {
@@ -247,7 +296,15 @@ pub async fn cube_engine_fetch_url(
     };
     
     if let Some(fetcher) = fetcher_opt.as_ref() {
-        fetcher.fetch(&url).await
+        let response = fetcher.fetch(&url).await?;
+        if response.status == 401 && resolve_auth_prompt(&state, &app, &url) {
+            let retried = fetcher.fetch(&url).await?;
+            if retried.status == 401 {
+                resolve_auth_prompt(&state, &app, &url);
+            }
+            return Ok(retried);
+        }
+        Ok(response)
     } else {
         Err("Fetcher not initialized".to_string())
     }
@@ -257,6 +314,7 @@ pub async fn cube_engine_fetch_url(
 #[tauri::command]
 pub async fn cube_engine_fetch_page(
     state: State<'_, CubeWebEngineGlobalState>,
+    app: AppHandle,
     url: String,
 ) -> Result<PageContent, String> {
     println!("📄 [CUBE ENGINE] Fetching page: {}", url);
@@ -267,7 +325,7 @@ pub async fn cube_engine_fetch_page(
     };
     
     if let Some(fetcher) = fetcher_opt.as_ref() {
-        fetcher.fetch_page(&url).await
+        fetch_page_with_auth(&state, &app, fetcher, &url).await
     } else {
         Err("Fetcher not initialized".to_string())
     }
@@ -279,46 +337,135 @@ pub async fn cube_engine_go_back(
     state: State<'_, CubeWebEngineGlobalState>,
     app: AppHandle,
     tab_id: String,
+    snapshot: Option<PageSnapshot>,
 ) -> Result<(), String> {
     println!("⬅️ [CUBE ENGINE] Going back in tab: {}", tab_id);
-
-    // Get history
-    let history = state.engine.get_history(&tab_id)?;
-    
-    if history.len() > 1 {
-        // Navigate to previous entry
-        let prev_entry = &history[history.len() - 2];
-        
-        // Update tab
-        state.engine.update_tab(&tab_id, TabUpdate {
-            url: Some(prev_entry.url.clone()),
-            title: Some(prev_entry.title.clone()),
-            is_loading: Some(true),
-            ..Default::default()
-        })?;
-
-        // Emit event
-        let _ = app.emit("cube-engine-navigation-started", serde_json::json!({
-            "tabId": tab_id,
-            "url": prev_entry.url
-        }));
-    }
-
-    Ok(())
+    traverse_history(&state, &app, &tab_id, snapshot, -1).await
 }
 
 /// Go forward in history
 #[tauri::command]
 pub async fn cube_engine_go_forward(
-    _state: State<'_, CubeWebEngineGlobalState>,
-    _app: AppHandle,
+    state: State<'_, CubeWebEngineGlobalState>,
+    app: AppHandle,
     tab_id: String,
+    snapshot: Option<PageSnapshot>,
 ) -> Result<(), String> {
     println!("➡️ [CUBE ENGINE] Going forward in tab: {}", tab_id);
-    // Implementation similar to go_back but with forward navigation
+    traverse_history(&state, &app, &tab_id, snapshot, 1).await
+}
+
+/// Get back/forward cache status for a tab, including why pages were not cached
+#[tauri::command]
+pub async fn cube_engine_get_bfcache_status(
+    state: State<'_, CubeWebEngineGlobalState>,
+    tab_id: String,
+) -> Result<BfCacheStatus, String> {
+    state.engine.get_bfcache_status(&tab_id)
+}
+
+/// Offers the outgoing page to the bfcache and fires `pagehide`
+fn leave_page(
+    state: &CubeWebEngineGlobalState,
+    app: &AppHandle,
+    tab_id: &str,
+    snapshot: Option<PageSnapshot>,
+) -> Result<(), String> {
+    if state.engine.get_history_index(tab_id)?.is_none() {
+        return Ok(());
+    }
+
+    let persisted = match snapshot {
+        Some(snapshot) => state.engine.store_in_bfcache(tab_id, snapshot)?.stored,
+        None => false,
+    };
+
+    let _ = app.emit("cube-engine-pagehide", serde_json::json!({
+        "tabId": tab_id,
+        "persisted": persisted
+    }));
+
     Ok(())
 }
 
+/// Moves through history, restoring from the bfcache when possible and
+/// re-fetching the page otherwise
+async fn traverse_history(
+    state: &CubeWebEngineGlobalState,
+    app: &AppHandle,
+    tab_id: &str,
+    snapshot: Option<PageSnapshot>,
+    delta: isize,
+) -> Result<(), String> {
+    let history = state.engine.get_history(tab_id)?;
+    let Some(current) = state.engine.get_history_index(tab_id)? else {
+        return Ok(());
+    };
+    let target = current as isize + delta;
+    if target < 0 || target as usize >= history.len() {
+        return Ok(());
+    }
+
+    leave_page(state, app, tab_id, snapshot)?;
+    let navigation = state.engine.navigate_history(tab_id, delta)?;
+
+    if let Some(restored) = navigation.restored {
+        let _ = app.emit("cube-engine-bfcache-restored", serde_json::json!({
+            "tabId": tab_id,
+            "url": navigation.entry.url,
+            "snapshot": restored
+        }));
+        let _ = app.emit("cube-engine-pageshow", serde_json::json!({
+            "tabId": tab_id,
+            "url": navigation.entry.url,
+            "persisted": true
+        }));
+        return Ok(());
+    }
+
+    let _ = app.emit("cube-engine-navigation-started", serde_json::json!({
+        "tabId": tab_id,
+        "url": navigation.entry.url
+    }));
+
+    let Some(fetcher) = state.tab_fetcher(tab_id)? else {
+        return Err("Fetcher not initialized".to_string());
+    };
+
+    let result = fetch_page_with_auth(state, app, &fetcher, &navigation.entry.url).await;
+    state.engine.update_tab(tab_id, TabUpdate {
+        is_loading: Some(false),
+        ..Default::default()
+    })?;
+
+    match result {
+        Ok(content) => {
+            state.engine.cache_page(tab_id, content.clone())?;
+
+            let _ = app.emit("cube-engine-navigation-completed", serde_json::json!({
+                "tabId": tab_id,
+                "url": navigation.entry.url,
+                "html": state.emulated_html(tab_id, &content.html),
+                "baseUrl": content.base_url
+            }));
+            let _ = app.emit("cube-engine-pageshow", serde_json::json!({
+                "tabId": tab_id,
+                "url": navigation.entry.url,
+                "persisted": false
+            }));
+            Ok(())
+        }
+        Err(e) => {
+            let _ = app.emit("cube-engine-navigation-failed", serde_json::json!({
+                "tabId": tab_id,
+                "url": navigation.entry.url,
+                "error": e
+            }));
+            Err(e)
+        }
+    }
+}
+
 /// Reload current page
 #[tauri::command]
 pub async fn cube_engine_reload(
@@ -482,7 +629,7 @@ pub async fn cube_engine_set_config(
     // Recreate fetcher with new config
     {
         let mut fetcher = state.fetcher.write().map_err(|e| format!("Lock error: {}", e))?;
-        *fetcher = Some(WebFetcher::new(config));
+        *fetcher = Some(state.new_fetcher(config));
     }
 
     Ok(())
@@ -502,7 +649,7 @@ pub async fn cube_engine_set_headers(
     drop(config);
     
     let mut fetcher = state.fetcher.write().map_err(|e| format!("Lock error: {}", e))?;
-    *fetcher = Some(WebFetcher::new(new_config));
+    *fetcher = Some(state.new_fetcher(new_config));
 
     Ok(())
 }
@@ -521,11 +668,150 @@ pub async fn cube_engine_set_user_agent(
     drop(config);
     
     let mut fetcher = state.fetcher.write().map_err(|e| format!("Lock error: {}", e))?;
-    *fetcher = Some(WebFetcher::new(new_config));
+    *fetcher = Some(state.new_fetcher(new_config));
+
+    Ok(())
+}
+
+// ============================================
+// HTTP Authentication Commands
+// ============================================
 
+/// Fetch a page, retrying once with password-manager credentials when the
+/// server asks for HTTP authentication
+async fn fetch_page_with_auth(
+    state: &CubeWebEngineGlobalState,
+    app: &AppHandle,
+    fetcher: &WebFetcher,
+    url: &str,
+) -> Result<PageContent, String> {
+    let result = fetcher.fetch_page(url).await;
+    if result.is_err() && resolve_auth_prompt(state, app, url) {
+        let retried = fetcher.fetch_page(url).await;
+        if retried.is_err() {
+            resolve_auth_prompt(state, app, url);
+        }
+        return retried;
+    }
+    result
+}
+
+/// Handles the prompt left by a request that needed HTTP auth. Returns true
+/// when stored credentials were found and the request should be retried;
+/// otherwise asks the frontend for credentials.
+fn resolve_auth_prompt(state: &CubeWebEngineGlobalState, app: &AppHandle, url: &str) -> bool {
+    let Some(prompt) = state.auth.take_prompt(&http_auth::origin_of(url)) else {
+        return false;
+    };
+
+    if !prompt.failed {
+        if let Some(credentials) = vault_credentials(app, &prompt) {
+            println!("🔐 [CUBE ENGINE] Using saved credentials for {}", prompt.origin);
+            state.auth.store(&prompt.origin, prompt.realm.as_deref(), credentials);
+            return true;
+        }
+    }
+
+    let _ = app.emit("cube-engine-auth-required", &prompt);
+    false
+}
+
+/// Credentials saved in the (unlocked) password manager for the prompt's host
+fn vault_credentials(app: &AppHandle, prompt: &AuthPrompt) -> Option<HttpCredentials> {
+    let passwords = app.try_state::<PasswordState>()?;
+    let service = passwords.service.lock().ok()?;
+    let host = url::Url::parse(&prompt.origin).ok()?.host_str()?.to_ascii_lowercase();
+
+    let entries = service.get_all_passwords().ok()?;
+    let entry = entries.iter().find(|entry| {
+        entry
+            .url
+            .as_deref()
+            .and_then(|saved| {
+                url::Url::parse(saved)
+                    .or_else(|_| url::Url::parse(&format!("https://{}", saved)))
+                    .ok()
+            })
+            .and_then(|saved| saved.host_str().map(|h| h.eq_ignore_ascii_case(&host)))
+            .unwrap_or(false)
+    })?;
+
+    let password = passwords.decrypt_unlocked(&service, entry)?;
+    Some(HttpCredentials {
+        username: entry.username.clone(),
+        password,
+        domain: None,
+    })
+}
+
+/// Answer a `cube-engine-auth-required` prompt; the frontend then retries the navigation
+#[tauri::command]
+pub async fn cube_engine_provide_http_credentials(
+    state: State<'_, CubeWebEngineGlobalState>,
+    origin: String,
+    realm: Option<String>,
+    username: String,
+    password: String,
+    domain: Option<String>,
+) -> Result<(), String> {
+    if username.is_empty() {
+        return Err("Username is required".to_string());
+    }
+    let origin = http_auth::origin_of(&origin);
+    state.auth.store(&origin, realm.as_deref(), HttpCredentials { username, password, domain });
+    Ok(())
+}
+
+/// Forget every HTTP auth credential cached this session
+#[tauri::command]
+pub async fn cube_engine_clear_http_auth(
+    state: State<'_, CubeWebEngineGlobalState>,
+) -> Result<(), String> {
+    state.auth.clear();
     Ok(())
 }
 
+// ============================================
+// DNS Commands
+// ============================================
+
+/// Map a provider name from the privacy DoH list to its URL; URLs pass through
+fn doh_provider_url(provider: &str) -> String {
+    PrivacyDashboardService::get_doh_providers()
+        .into_iter()
+        .find(|p| p.name.eq_ignore_ascii_case(provider.trim()))
+        .map(|p| p.url)
+        .unwrap_or_else(|| provider.trim().to_string())
+}
+
+/// Resolve a host through the engine's DoH resolver
+#[tauri::command]
+pub async fn cube_engine_resolve(
+    state: State<'_, CubeWebEngineGlobalState>,
+    host: String,
+) -> Result<Resolution, String> {
+    state.dns.resolve(&host).await
+}
+
+/// Choose the DoH provider (by name or URL) and optionally its fallback
+#[tauri::command]
+pub async fn cube_engine_set_doh(
+    state: State<'_, CubeWebEngineGlobalState>,
+    app: AppHandle,
+    provider: String,
+    secondary: Option<String>,
+) -> Result<DohConfig, String> {
+    let primary = doh_provider_url(&provider);
+    let secondary = secondary.map(|s| doh_provider_url(&s));
+    let config = state.dns.set_providers(&primary, secondary.as_deref())?;
+
+    // Keep the privacy dashboard's DoH setting in step
+    if let Some(privacy) = app.try_state::<PrivacyDashboardService>() {
+        privacy.set_doh_provider(config.primary.clone())?;
+    }
+    Ok(config)
+}
+
 // ============================================
 // Zoom & Display Commands
 // ============================================
@@ -565,6 +851,171 @@ pub async fn cube_engine_get_zoom(
     }
 }
 
+// ============================================
+// Media Emulation Commands
+// ============================================
+
+/// Emulate a user-preference media feature in a tab. `value: None` clears the override.
+#[tauri::command]
+pub async fn cube_engine_set_media_feature(
+    state: State<'_, CubeWebEngineGlobalState>,
+    app: AppHandle,
+    tab_id: String,
+    feature: String,
+    value: Option<String>,
+) -> Result<MediaEmulation, String> {
+    if state.engine.get_tab(&tab_id)?.is_none() {
+        return Err("Tab not found".to_string());
+    }
+    state.media.set_feature(&tab_id, &feature, value.as_deref())?;
+    rerender_with_media(&state, &app, &tab_id)?;
+    Ok(state.media.effective(&tab_id))
+}
+
+/// Get the media features currently emulated in a tab
+#[tauri::command]
+pub async fn cube_engine_get_media_features(
+    state: State<'_, CubeWebEngineGlobalState>,
+    tab_id: String,
+) -> Result<MediaEmulation, String> {
+    Ok(state.media.effective(&tab_id))
+}
+
+/// Apply the user's reduced-motion preference to every tab
+#[tauri::command]
+pub async fn cube_engine_set_reduced_motion(
+    state: State<'_, CubeWebEngineGlobalState>,
+    app: AppHandle,
+    enabled: bool,
+) -> Result<(), String> {
+    apply_reduced_motion(&state, &app, enabled)
+}
+
+pub fn apply_reduced_motion(state: &CubeWebEngineGlobalState, app: &AppHandle, enabled: bool) -> Result<(), String> {
+    if state.media.reduced_motion() == enabled {
+        return Ok(());
+    }
+    state.media.set_reduced_motion(enabled)?;
+    for tab in state.engine.get_tabs()? {
+        rerender_with_media(state, app, &tab.id)?;
+    }
+    Ok(())
+}
+
+/// Push the cached page back to the frontend with the tab's current emulation
+fn rerender_with_media(state: &CubeWebEngineGlobalState, app: &AppHandle, tab_id: &str) -> Result<(), String> {
+    let Some(content) = state.engine.get_cached_page(tab_id)? else {
+        return Ok(());
+    };
+    let _ = app.emit("cube-engine-media-emulation-changed", serde_json::json!({
+        "tabId": tab_id,
+        "emulation": state.media.effective(tab_id),
+        "html": state.emulated_html(tab_id, &content.html),
+        "baseUrl": content.base_url
+    }));
+    Ok(())
+}
+
+// ============================================
+// Device Emulation Commands
+// ============================================
+
+/// Built-in phone and tablet profiles
+#[tauri::command]
+pub async fn cube_engine_get_device_presets() -> Result<Vec<DeviceProfile>, String> {
+    Ok(device_presets())
+}
+
+/// Emulate a device (preset id or custom profile) in a tab. `device: None` restores the desktop view.
+#[tauri::command]
+pub async fn cube_engine_set_device(
+    state: State<'_, CubeWebEngineGlobalState>,
+    app: AppHandle,
+    tab_id: String,
+    device: Option<DeviceSelection>,
+    orientation: Option<Orientation>,
+) -> Result<Option<DeviceEmulation>, String> {
+    let Some(tab) = state.engine.get_tab(&tab_id)? else {
+        return Err("Tab not found".to_string());
+    };
+    let previous_agent = state.devices.user_agent(&tab_id);
+    let emulation = match device {
+        Some(device) => Some(state.devices.set(&tab_id, device, orientation)?),
+        None => {
+            state.devices.clear(&tab_id);
+            None
+        }
+    };
+    emit_device_changed(&state, &app, &tab_id)?;
+
+    // Servers may sniff the user agent, so the page has to be fetched again
+    if state.devices.user_agent(&tab_id) != previous_agent && !tab.url.is_empty() {
+        let _ = app.emit("cube-engine-reload", serde_json::json!({
+            "tabId": tab_id,
+            "url": tab.url
+        }));
+    }
+    Ok(emulation)
+}
+
+/// Switch the emulated device between portrait and landscape
+#[tauri::command]
+pub async fn cube_engine_toggle_orientation(
+    state: State<'_, CubeWebEngineGlobalState>,
+    app: AppHandle,
+    tab_id: String,
+) -> Result<DeviceEmulation, String> {
+    let emulation = state.devices.toggle_orientation(&tab_id)?;
+    emit_device_changed(&state, &app, &tab_id)?;
+    Ok(emulation)
+}
+
+/// Get the device emulated in a tab, if any
+#[tauri::command]
+pub async fn cube_engine_get_device(
+    state: State<'_, CubeWebEngineGlobalState>,
+    tab_id: String,
+) -> Result<Option<DeviceEmulation>, String> {
+    Ok(state.devices.get(&tab_id))
+}
+
+/// Tell the frontend to resize the page frame and re-render the cached page
+fn emit_device_changed(state: &CubeWebEngineGlobalState, app: &AppHandle, tab_id: &str) -> Result<(), String> {
+    let emulation = state.devices.get(tab_id);
+    let content = state.engine.get_cached_page(tab_id)?;
+    let _ = app.emit("cube-engine-device-changed", serde_json::json!({
+        "tabId": tab_id,
+        "emulation": emulation,
+        "viewport": emulation.as_ref().map(DeviceEmulation::viewport),
+        "html": content.as_ref().map(|content| state.emulated_html(tab_id, &content.html)),
+        "baseUrl": content.map(|content| content.base_url)
+    }));
+    Ok(())
+}
+
+// ============================================
+// Image Placeholder Commands
+// ============================================
+
+/// Generate a BlurHash and tiny base64 thumbnail for an image, shown while the
+/// full image lazy-loads
+#[tauri::command]
+pub async fn cube_engine_generate_lqip(
+    state: State<'_, CubeWebEngineGlobalState>,
+    image_url: String,
+) -> Result<Lqip, String> {
+    let fetcher = state.fetcher.read().map_err(|e| format!("Lock error: {}", e))?.clone();
+    let Some(fetcher) = fetcher else {
+        return Err("Fetcher not initialized".to_string());
+    };
+    let response = fetcher.fetch(&image_url).await?;
+    if !(200..300).contains(&response.status) {
+        return Err(format!("Failed to fetch image: HTTP {}", response.status));
+    }
+    let content_type = response.headers.get("content-type").map(String::as_str).unwrap_or("");
+    Lqip::from_bytes(&response.url, content_type, &response.body)
+}
+
 // ============================================
 // History Commands
 // ============================================
@@ -602,17 +1053,30 @@ pub async fn cube_engine_clear_history(
 /// Take screenshot of tab (requires frontend coordination)
 #[tauri::command]
 pub async fn cube_engine_screenshot(
-    _state: State<'_, CubeWebEngineGlobalState>,
+    state: State<'_, CubeWebEngineGlobalState>,
     app: AppHandle,
     tab_id: String,
     options: Option<ScreenshotOptions>,
 ) -> Result<String, String> {
     let opts = options.unwrap_or_default();
+    // Capture at the emulated device's resolution
+    let viewport = state.devices.get(&tab_id).map(|emulation| {
+        let viewport = emulation.viewport();
+        let (pixel_width, pixel_height) = viewport.physical_size();
+        serde_json::json!({
+            "width": viewport.width,
+            "height": viewport.height,
+            "devicePixelRatio": viewport.device_pixel_ratio,
+            "pixelWidth": pixel_width,
+            "pixelHeight": pixel_height
+        })
+    });
     
     // Request screenshot from frontend
     let _ = app.emit("cube-engine-screenshot-request", serde_json::json!({
         "tabId": tab_id,
-        "options": opts
+        "options": opts,
+        "viewport": viewport
     }));
 
     // The actual screenshot is taken by the frontend and returned via event
@@ -627,7 +1091,7 @@ pub async fn cube_engine_print_to_pdf(
     tab_id: String,
     options: Option<PrintOptions>,
 ) -> Result<String, String> {
-    let opts = options.unwrap_or_default();
+    let opts = options.unwrap_or_default().normalized()?;
     
     let _ = app.emit("cube-engine-print-request", serde_json::json!({
         "tabId": tab_id,
@@ -685,3 +1149,47 @@ pub async fn cube_engine_devtools_get_console(
 
     Ok(serde_json::json!({ "requested": true }))
 }
+
+// ============================================
+// Accessibility Commands
+// ============================================
+
+fn accessibility_document(
+    state: &CubeWebEngineGlobalState,
+    tab_id: &str,
+) -> Result<AccessibilityDocument, String> {
+    let page = state.engine.get_cached_page(tab_id)?
+        .ok_or_else(|| "Page not cached".to_string())?;
+    Ok(AccessibilityDocument::parse(&page.html))
+}
+
+/// Get the accessibility tree of the tab's page
+#[tauri::command]
+pub async fn cube_engine_get_accessibility_tree(
+    state: State<'_, CubeWebEngineGlobalState>,
+    tab_id: String,
+) -> Result<AxNode, String> {
+    Ok(accessibility_document(&state, &tab_id)?.tree())
+}
+
+/// Find accessibility nodes by role and (partial) accessible name
+#[tauri::command]
+pub async fn cube_engine_find_by_role(
+    state: State<'_, CubeWebEngineGlobalState>,
+    tab_id: String,
+    role: String,
+    name: Option<String>,
+) -> Result<Vec<AxNode>, String> {
+    Ok(accessibility_document(&state, &tab_id)?.find(&role, name.as_deref()))
+}
+
+/// Audit the tab's page for common accessibility issues
+#[tauri::command]
+pub async fn cube_engine_a11y_audit(
+    state: State<'_, CubeWebEngineGlobalState>,
+    tab_id: String,
+) -> Result<A11yAuditReport, String> {
+    let report = accessibility_document(&state, &tab_id)?.audit();
+    println!("♿ [CUBE ENGINE] Accessibility audit for tab {}: {} issues", tab_id, report.issues.len());
+    Ok(report)
+}
@@ -1,6 +1,8 @@
 // DevTools Commands - Browser Developer Tools
+use crate::services::cube_browser_engine::{get_browser, CookieData};
 use crate::services::devtools_service::{
-    ConsoleMessage, DevToolsService, DomElement, NetworkRequest,
+    ConsoleMessage, DevToolsService, DomElement, IndexedDbDatabase, NetworkRequest, StorageArea,
+    StorageCookie, StoragePartition,
 };
 use std::collections::HashMap;
 use tauri::State;
@@ -79,3 +81,194 @@ pub async fn devtools_clear_network(
 ) -> Result<(), String> {
     devtools.clear_network(&tab_id).map_err(|e| e.to_string())
 }
+
+// ==================== Storage Panels ====================
+
+/// Applies an edit to the live page when the tab is open in the Cube engine,
+/// so the page sees the change on its next read.
+fn apply_to_live_tab(tab_id: &str, script: &str) {
+    let browser = get_browser();
+    if browser.is_initialized() {
+        if let Err(e) = browser.execute_script(tab_id, script) {
+            log::debug!("DevTools storage edit not mirrored to tab {}: {}", tab_id, e);
+        }
+    }
+}
+
+fn storage_object(area: StorageArea) -> &'static str {
+    match area {
+        StorageArea::Local => "localStorage",
+        StorageArea::Session => "sessionStorage",
+    }
+}
+
+fn js_string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+#[tauri::command]
+pub async fn devtools_attach_storage_context(
+    tab_id: String,
+    url: String,
+    profile_id: Option<String>,
+    devtools: State<'_, DevToolsService>,
+) -> Result<StoragePartition, String> {
+    devtools
+        .attach_tab(&tab_id, &url, profile_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn devtools_set_active_profile(
+    profile_id: String,
+    devtools: State<'_, DevToolsService>,
+) -> Result<(), String> {
+    devtools.set_active_profile(&profile_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn devtools_set_storage_item(
+    tab_id: String,
+    area: StorageArea,
+    key: String,
+    value: String,
+    devtools: State<'_, DevToolsService>,
+) -> Result<(), String> {
+    devtools
+        .set_storage_item(&tab_id, area, &key, &value)
+        .map_err(|e| e.to_string())?;
+    apply_to_live_tab(
+        &tab_id,
+        &format!("{}.setItem({}, {})", storage_object(area), js_string(&key), js_string(&value)),
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn devtools_delete_storage_item(
+    tab_id: String,
+    area: StorageArea,
+    key: String,
+    devtools: State<'_, DevToolsService>,
+) -> Result<bool, String> {
+    let removed = devtools
+        .delete_storage_item(&tab_id, area, &key)
+        .map_err(|e| e.to_string())?;
+    apply_to_live_tab(&tab_id, &format!("{}.removeItem({})", storage_object(area), js_string(&key)));
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn devtools_clear_storage(
+    tab_id: String,
+    area: StorageArea,
+    devtools: State<'_, DevToolsService>,
+) -> Result<(), String> {
+    devtools.clear_storage(&tab_id, area).map_err(|e| e.to_string())?;
+    apply_to_live_tab(&tab_id, &format!("{}.clear()", storage_object(area)));
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn devtools_get_cookies(
+    tab_id: String,
+    devtools: State<'_, DevToolsService>,
+) -> Result<Vec<StorageCookie>, String> {
+    devtools.list_cookies(&tab_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn devtools_set_cookie(
+    tab_id: String,
+    cookie: StorageCookie,
+    devtools: State<'_, DevToolsService>,
+) -> Result<StorageCookie, String> {
+    let cookie = devtools.set_cookie(&tab_id, cookie).map_err(|e| e.to_string())?;
+
+    // HttpOnly cookies are invisible to document.cookie, so only mirror the rest
+    if !cookie.http_only {
+        let browser = get_browser();
+        if browser.is_initialized() {
+            let live = CookieData {
+                name: cookie.name.clone(),
+                value: cookie.value.clone(),
+                domain: cookie.domain.clone(),
+                path: cookie.path.clone(),
+                expires: cookie.expires.map(|e| e as f64),
+                http_only: cookie.http_only,
+                secure: cookie.secure,
+                same_site: cookie.same_site.clone(),
+            };
+            if let Err(e) = browser.set_cookie(&tab_id, &live) {
+                log::debug!("DevTools cookie not mirrored to tab {}: {}", tab_id, e);
+            }
+        }
+    }
+    Ok(cookie)
+}
+
+#[tauri::command]
+pub async fn devtools_delete_cookie(
+    tab_id: String,
+    name: String,
+    domain: String,
+    path: String,
+    devtools: State<'_, DevToolsService>,
+) -> Result<bool, String> {
+    let removed = devtools
+        .delete_cookie(&tab_id, &name, &domain, &path)
+        .map_err(|e| e.to_string())?;
+
+    let browser = get_browser();
+    if removed && browser.is_initialized() {
+        let live = CookieData {
+            name,
+            value: String::new(),
+            domain,
+            path,
+            expires: None,
+            http_only: false,
+            secure: false,
+            same_site: None,
+        };
+        if let Err(e) = browser.delete_cookies(&[live]) {
+            log::debug!("DevTools cookie deletion not mirrored: {}", e);
+        }
+    }
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn devtools_report_indexeddb(
+    tab_id: String,
+    database: IndexedDbDatabase,
+    devtools: State<'_, DevToolsService>,
+) -> Result<(), String> {
+    devtools
+        .report_indexeddb(&tab_id, database)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn devtools_list_indexeddb(
+    origin: String,
+    profile_id: Option<String>,
+    devtools: State<'_, DevToolsService>,
+) -> Result<Vec<IndexedDbDatabase>, String> {
+    devtools
+        .list_indexeddb(&origin, profile_id.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn devtools_delete_indexeddb(
+    tab_id: String,
+    name: String,
+    devtools: State<'_, DevToolsService>,
+) -> Result<bool, String> {
+    let removed = devtools
+        .delete_indexeddb(&tab_id, &name)
+        .map_err(|e| e.to_string())?;
+    apply_to_live_tab(&tab_id, &format!("indexedDB.deleteDatabase({})", js_string(&name)));
+    Ok(removed)
+}
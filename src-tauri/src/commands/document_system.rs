@@ -9,7 +9,7 @@ use tauri::State;
 #[allow(deprecated)]
 use crate::document::{
     CacheStats, DocumentDownloader, DocumentParser, DocumentProcessor, DocumentType,
-    DocumentValidator, DownloadConfig, DownloadResult, ValidationResult,
+    DocumentValidator, DownloadConfig, DownloadResult, OfficeDocument, ValidationResult,
 };
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -251,6 +251,34 @@ pub async fn document_extract_text(
         .map_err(|e| format!("Text extraction failed: {}", e))
 }
 
+/// Extract paragraphs, headings and tables from a .docx, or per-sheet rows
+/// from a .xlsx, along with the embedded images
+///
+/// # Example
+/// ```typescript
+/// const doc = await invoke('document_extract_office', {
+///   path: '/path/to/statement.xlsx'
+/// });
+/// ```
+#[tauri::command]
+pub async fn document_extract_office(
+    path: String,
+    state: State<'_, DocumentState>,
+) -> Result<OfficeDocument, String> {
+    let validator = state
+        .validator
+        .lock()
+        .map_err(|e| format!("Failed to lock validator: {}", e))?
+        .clone();
+
+    let doc_type = validator.detect_type(path.clone()).await?;
+    tokio::task::spawn_blocking(move || {
+        crate::document::extract_office_file(std::path::Path::new(&path), doc_type)
+    })
+    .await
+    .map_err(|e| format!("Office extraction task failed: {}", e))?
+}
+
 /// Get cache statistics
 ///
 /// # Example
@@ -18,6 +18,9 @@ use crate::services::email_service::{
     EmailSendResult, EmailBatchResult, EmailTestResult,
     send_email, send_batch_emails, test_email_connection, send_test_email,
 };
+use crate::services::email_tracking::{EmailTrackingService, EmailTrackingConfig, TrackedSend};
+use crate::services::ContactServiceState;
+use std::sync::Arc;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // CONFIGURATION COMMANDS
@@ -224,6 +227,8 @@ pub struct SendEmailParams {
     pub template_variables: Option<std::collections::HashMap<String, String>>,
     pub tracking_id: Option<String>,
     pub campaign_id: Option<String>,
+    /// Open/click tracking for this send (defaults to on)
+    pub track: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -261,9 +266,12 @@ impl From<AttachmentInput> for EmailAttachment {
 #[command]
 pub async fn email_send(
     state: tauri::State<'_, EmailServiceState>,
+    tracking: tauri::State<'_, Arc<EmailTrackingService>>,
+    contacts: tauri::State<'_, ContactServiceState>,
     params: SendEmailParams,
 ) -> Result<EmailSendResult, String> {
-    let message = EmailMessage {
+    let track = params.track.unwrap_or(true);
+    let mut message = EmailMessage {
         to: params.to.into_iter().map(|r| r.into()).collect(),
         cc: params.cc.map(|v| v.into_iter().map(|r| r.into()).collect()),
         bcc: params.bcc.map(|v| v.into_iter().map(|r| r.into()).collect()),
@@ -278,7 +286,9 @@ pub async fn email_send(
         template_variables: params.template_variables,
         tracking_id: params.tracking_id,
         campaign_id: params.campaign_id,
+        tracking_opt_out: false,
     };
+    tracking.prepare_message(&mut message, &contacts, track)?;
     
     send_email(&state, message).await
 }
@@ -286,10 +296,13 @@ pub async fn email_send(
 #[command]
 pub async fn email_send_batch(
     state: tauri::State<'_, EmailServiceState>,
+    tracking: tauri::State<'_, Arc<EmailTrackingService>>,
+    contacts: tauri::State<'_, ContactServiceState>,
     emails: Vec<SendEmailParams>,
 ) -> Result<EmailBatchResult, String> {
     let messages: Vec<EmailMessage> = emails.into_iter().map(|params| {
-        EmailMessage {
+        let track = params.track.unwrap_or(true);
+        let mut message = EmailMessage {
             to: params.to.into_iter().map(|r| r.into()).collect(),
             cc: params.cc.map(|v| v.into_iter().map(|r| r.into()).collect()),
             bcc: params.bcc.map(|v| v.into_iter().map(|r| r.into()).collect()),
@@ -304,8 +317,11 @@ pub async fn email_send_batch(
             template_variables: params.template_variables,
             tracking_id: params.tracking_id,
             campaign_id: params.campaign_id,
-        }
-    }).collect();
+            tracking_opt_out: false,
+        };
+        tracking.prepare_message(&mut message, &contacts, track)?;
+        Ok(message)
+    }).collect::<Result<_, String>>()?;
     
     send_batch_emails(&state, messages).await
 }
@@ -341,6 +357,7 @@ pub async fn email_send_simple(
         template_variables: None,
         tracking_id: Some(format!("simple-{}", uuid::Uuid::new_v4())),
         campaign_id: None,
+        tracking_opt_out: false,
     };
     
     send_email(&state, message).await
@@ -353,6 +370,8 @@ pub async fn email_send_simple(
 #[command]
 pub async fn email_send_campaign(
     state: tauri::State<'_, EmailServiceState>,
+    tracking: tauri::State<'_, Arc<EmailTrackingService>>,
+    contacts: tauri::State<'_, ContactServiceState>,
     campaign_id: String,
     recipients: Vec<EmailRecipientInput>,
     subject: String,
@@ -361,7 +380,9 @@ pub async fn email_send_campaign(
     from_email: Option<String>,
     from_name: Option<String>,
     template_variables: Option<std::collections::HashMap<String, String>>,
+    track: Option<bool>,
 ) -> Result<EmailBatchResult, String> {
+    let track = track.unwrap_or(true);
     let messages: Vec<EmailMessage> = recipients.into_iter().map(|recipient| {
         // Each recipient gets their own message for personalization
         let mut vars = template_variables.clone().unwrap_or_default();
@@ -371,7 +392,7 @@ pub async fn email_send_campaign(
         }
         vars.insert("recipient_email".to_string(), recipient.email.clone());
         
-        EmailMessage {
+        let mut message = EmailMessage {
             to: vec![EmailRecipient {
                 email: recipient.email,
                 name: recipient.name,
@@ -389,8 +410,11 @@ pub async fn email_send_campaign(
             template_variables: Some(vars),
             tracking_id: Some(format!("campaign-{}-{}", campaign_id, uuid::Uuid::new_v4())),
             campaign_id: Some(campaign_id.clone()),
-        }
-    }).collect();
+            tracking_opt_out: false,
+        };
+        tracking.prepare_message(&mut message, &contacts, track)?;
+        Ok(message)
+    }).collect::<Result<_, String>>()?;
     
     send_batch_emails(&state, messages).await
 }
@@ -445,3 +469,30 @@ pub async fn email_reset_rate_counters(
     *state.last_hour_reset.lock().map_err(|e| e.to_string())? = chrono::Utc::now();
     Ok(())
 }
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TRACKING COMMANDS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[command]
+pub async fn email_tracking_get_config(
+    tracking: tauri::State<'_, Arc<EmailTrackingService>>,
+) -> Result<EmailTrackingConfig, String> {
+    tracking.get_config()
+}
+
+#[command]
+pub async fn email_tracking_set_config(
+    tracking: tauri::State<'_, Arc<EmailTrackingService>>,
+    config: EmailTrackingConfig,
+) -> Result<EmailTrackingConfig, String> {
+    tracking.set_config(config)
+}
+
+#[command]
+pub async fn email_tracking_get_sends(
+    tracking: tauri::State<'_, Arc<EmailTrackingService>>,
+    campaign_id: Option<String>,
+) -> Result<Vec<TrackedSend>, String> {
+    tracking.get_sends(campaign_id.as_deref())
+}
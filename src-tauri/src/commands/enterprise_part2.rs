@@ -7,8 +7,15 @@
 #![allow(unused_variables)]
 
 use serde::{Deserialize, Serialize};
-use tauri::command;
+use tauri::{command, State};
 use std::collections::HashMap;
+use std::sync::RwLock;
+use base64::{engine::general_purpose, Engine as _};
+use crate::services::license_seat_service::{LicenseSeatService, SeatLease};
+use crate::services::whitelabel_theme_service::{
+    WhiteLabelAsset, WhiteLabelAssetData, WhiteLabelAssetKind, WhiteLabelTheme,
+    WhiteLabelThemeBundle, WhiteLabelThemeDraft, WhiteLabelThemeService,
+};
 
 // ============================================================================
 // Tenant Types
@@ -232,6 +239,32 @@ pub struct LicenseValidationResult {
     pub warnings: Vec<String>,
 }
 
+/// Issued licenses and the seats currently leased against them
+#[derive(Default)]
+pub struct EnterpriseLicenseState {
+    pub licenses: RwLock<HashMap<String, EnterpriseLicense>>,
+    pub seats: LicenseSeatService,
+}
+
+impl EnterpriseLicenseState {
+    fn find_by_id(&self, license_id: &str) -> Result<EnterpriseLicense, String> {
+        self.licenses.read().map_err(|e| e.to_string())?
+            .get(license_id)
+            .cloned()
+            .ok_or_else(|| "License not found".to_string())
+    }
+
+    /// Mirror the live seat count onto the stored license
+    fn sync_seats_used(&self, license_id: &str, now_ms: i64) -> Result<Option<EnterpriseLicense>, String> {
+        let used = self.seats.active_seats(license_id, now_ms)?.len() as i32;
+        let mut licenses = self.licenses.write().map_err(|e| e.to_string())?;
+        Ok(licenses.get_mut(license_id).map(|license| {
+            license.seats_used = used;
+            license.clone()
+        }))
+    }
+}
+
 // ============================================================================
 // Audit Types
 // ============================================================================
@@ -595,45 +628,141 @@ pub async fn role_check_permission(
 // ============================================================================
 
 #[command]
-pub async fn license_create(license: EnterpriseLicense) -> Result<EnterpriseLicense, String> {
+pub async fn license_create(
+    license: EnterpriseLicense,
+    state: State<'_, EnterpriseLicenseState>,
+) -> Result<EnterpriseLicense, String> {
     let mut new_license = license;
     new_license.id = uuid::Uuid::new_v4().to_string();
     new_license.issued_at = chrono::Utc::now().timestamp_millis();
     new_license.status = LicenseStatus::PendingActivation;
+    new_license.seats_used = 0;
+    
+    state.licenses.write().map_err(|e| e.to_string())?
+        .insert(new_license.id.clone(), new_license.clone());
     
     Ok(new_license)
 }
 
 #[command]
-pub async fn license_get(license_id: String) -> Result<Option<EnterpriseLicense>, String> {
-    Ok(None)
+pub async fn license_get(
+    license_id: String,
+    state: State<'_, EnterpriseLicenseState>,
+) -> Result<Option<EnterpriseLicense>, String> {
+    state.sync_seats_used(&license_id, chrono::Utc::now().timestamp_millis())
 }
 
 #[command]
-pub async fn license_get_by_key(license_key: String) -> Result<Option<EnterpriseLicense>, String> {
-    Ok(None)
+pub async fn license_get_by_key(
+    license_key: String,
+    state: State<'_, EnterpriseLicenseState>,
+) -> Result<Option<EnterpriseLicense>, String> {
+    let license_id = state.licenses.read().map_err(|e| e.to_string())?
+        .values()
+        .find(|l| l.license_key == license_key)
+        .map(|l| l.id.clone());
+    
+    match license_id {
+        Some(id) => state.sync_seats_used(&id, chrono::Utc::now().timestamp_millis()),
+        None => Ok(None),
+    }
 }
 
 #[command]
 pub async fn license_get_for_organization(
     organization_id: String,
+    state: State<'_, EnterpriseLicenseState>,
 ) -> Result<Option<EnterpriseLicense>, String> {
-    Ok(None)
+    let license_id = state.licenses.read().map_err(|e| e.to_string())?
+        .values()
+        .find(|l| l.organization_id == organization_id)
+        .map(|l| l.id.clone());
+    
+    match license_id {
+        Some(id) => state.sync_seats_used(&id, chrono::Utc::now().timestamp_millis()),
+        None => Ok(None),
+    }
 }
 
+/// Activate on a device, leasing one of the license's concurrent seats. The
+/// client must call `license_heartbeat` while running to keep the seat.
 #[command]
 pub async fn license_activate(
     license_key: String,
     organization_id: String,
+    device_id: String,
+    device_name: Option<String>,
+    state: State<'_, EnterpriseLicenseState>,
 ) -> Result<EnterpriseLicense, String> {
-    Err("License not found".to_string())
+    let now = chrono::Utc::now().timestamp_millis();
+    let license = state.licenses.read().map_err(|e| e.to_string())?
+        .values()
+        .find(|l| l.license_key == license_key && l.organization_id == organization_id)
+        .cloned()
+        .ok_or_else(|| "License not found".to_string())?;
+    
+    match license.status {
+        LicenseStatus::Revoked => return Err("License has been revoked".to_string()),
+        LicenseStatus::Suspended => return Err("License is suspended".to_string()),
+        LicenseStatus::Expired => return Err("License has expired".to_string()),
+        LicenseStatus::Active | LicenseStatus::PendingActivation => {}
+    }
+    if license.expires_at.is_some_and(|expires_at| expires_at <= now) {
+        return Err("License has expired".to_string());
+    }
+    
+    state.seats.acquire(&license.id, license.seats, &device_id, device_name, now)?;
+    
+    {
+        let mut licenses = state.licenses.write().map_err(|e| e.to_string())?;
+        if let Some(stored) = licenses.get_mut(&license.id) {
+            stored.status = LicenseStatus::Active;
+            stored.activated_at.get_or_insert(now);
+        }
+    }
+    state.sync_seats_used(&license.id, now)?
+        .ok_or_else(|| "License not found".to_string())
+}
+
+/// Keep a device's seat alive; send every `HEARTBEAT_INTERVAL_MS`
+#[command]
+pub async fn license_heartbeat(
+    license_id: String,
+    device_id: String,
+    state: State<'_, EnterpriseLicenseState>,
+) -> Result<SeatLease, String> {
+    state.seats.heartbeat(&license_id, &device_id, chrono::Utc::now().timestamp_millis())
 }
 
+/// Release the seat held by `device_id`, or every seat when no device is given
 #[command]
-pub async fn license_deactivate(license_id: String) -> Result<(), String> {
+pub async fn license_deactivate(
+    license_id: String,
+    device_id: Option<String>,
+    state: State<'_, EnterpriseLicenseState>,
+) -> Result<(), String> {
+    state.find_by_id(&license_id)?;
+    match device_id {
+        Some(device_id) => {
+            state.seats.release(&license_id, &device_id)?;
+        }
+        None => {
+            state.seats.release_all(&license_id)?;
+        }
+    }
+    state.sync_seats_used(&license_id, chrono::Utc::now().timestamp_millis())?;
     Ok(())
 }
 
+#[command]
+pub async fn license_get_active_seats(
+    license_id: String,
+    state: State<'_, EnterpriseLicenseState>,
+) -> Result<Vec<SeatLease>, String> {
+    state.find_by_id(&license_id)?;
+    state.seats.active_seats(&license_id, chrono::Utc::now().timestamp_millis())
+}
+
 #[command]
 pub async fn license_validate(license_key: String) -> Result<LicenseValidationResult, String> {
     Ok(LicenseValidationResult {
@@ -682,11 +811,14 @@ pub async fn license_increment_usage(
 #[command]
 pub async fn license_get_usage_report(
     organization_id: String,
+    state: State<'_, EnterpriseLicenseState>,
 ) -> Result<LicenseUsageReport, String> {
+    let license = license_get_for_organization(organization_id, state).await?;
+    
     Ok(LicenseUsageReport {
-        seats_used: 0,
-        seats_total: 0,
-        features: vec![],
+        seats_used: license.as_ref().map(|l| l.seats_used).unwrap_or(0),
+        seats_total: license.as_ref().map(|l| l.seats).unwrap_or(0),
+        features: license.map(|l| l.features).unwrap_or_default(),
         api_requests: 0,
         storage_used: 0,
     })
@@ -822,20 +954,83 @@ pub async fn whitelabel_disable(organization_id: String) -> Result<(), String> {
 
 #[command]
 pub async fn whitelabel_update_branding(
+    themes: State<'_, WhiteLabelThemeService>,
     organization_id: String,
     branding: WhiteLabelBranding,
 ) -> Result<WhiteLabelBranding, String> {
+    let tokens: HashMap<String, String> = [
+        ("primary", &branding.primary_color),
+        ("secondary", &branding.secondary_color),
+        ("accent", &branding.accent_color),
+        ("background", &branding.background_color),
+        ("text", &branding.text_color),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value.clone()))
+    .collect();
+    themes.set_tokens(&organization_id, tokens)?;
     Ok(branding)
 }
 
 #[command]
 pub async fn whitelabel_update_customization(
+    themes: State<'_, WhiteLabelThemeService>,
     organization_id: String,
-    customization: WhiteLabelCustomization,
+    mut customization: WhiteLabelCustomization,
 ) -> Result<WhiteLabelCustomization, String> {
+    let theme = themes.set_custom_css(&organization_id, customization.custom_css.take())?;
+    customization.custom_css = theme.custom_css;
     Ok(customization)
 }
 
+#[command]
+pub async fn whitelabel_set_theme_tokens(
+    themes: State<'_, WhiteLabelThemeService>,
+    organization_id: String,
+    tokens: HashMap<String, String>,
+) -> Result<WhiteLabelTheme, String> {
+    themes.set_tokens(&organization_id, tokens)
+}
+
+#[command]
+pub async fn whitelabel_get_theme(
+    themes: State<'_, WhiteLabelThemeService>,
+    organization_id: String,
+) -> Result<WhiteLabelThemeBundle, String> {
+    themes.render(&organization_id)
+}
+
+#[command]
+pub async fn whitelabel_upload_asset(
+    themes: State<'_, WhiteLabelThemeService>,
+    organization_id: String,
+    kind: WhiteLabelAssetKind,
+    file_name: String,
+    data_base64: String,
+) -> Result<WhiteLabelAsset, String> {
+    let data = general_purpose::STANDARD.decode(data_base64.trim())
+        .map_err(|e| format!("Invalid asset data: {}", e))?;
+    themes.store_asset(&organization_id, kind, &file_name, &data)
+}
+
+#[command]
+pub async fn whitelabel_get_asset(
+    themes: State<'_, WhiteLabelThemeService>,
+    organization_id: String,
+    asset_id: String,
+) -> Result<WhiteLabelAssetData, String> {
+    themes.get_asset(&organization_id, &asset_id)
+}
+
+#[command]
+pub async fn whitelabel_delete_asset(
+    themes: State<'_, WhiteLabelThemeService>,
+    organization_id: String,
+    asset_id: String,
+) -> Result<(), String> {
+    themes.delete_asset(&organization_id, &asset_id)
+}
+
 #[command]
 pub async fn whitelabel_add_domain(
     organization_id: String,
@@ -919,15 +1114,33 @@ pub async fn whitelabel_update_legal(
 }
 
 #[command]
-pub async fn whitelabel_preview(organization_id: String) -> Result<WhiteLabelPreview, String> {
+pub async fn whitelabel_preview(
+    themes: State<'_, WhiteLabelThemeService>,
+    organization_id: String,
+    draft: Option<WhiteLabelThemeDraft>,
+) -> Result<WhiteLabelPreview, String> {
+    let (preview_id, theme, expires_at) = themes.preview(&organization_id, draft)?;
     Ok(WhiteLabelPreview {
-        preview_url: String::new(),
-        expires_at: chrono::Utc::now().timestamp_millis() + 3600000,
+        preview_url: format!("?whitelabelPreview={}", preview_id),
+        preview_id,
+        theme,
+        expires_at,
     })
 }
 
+#[command]
+pub async fn whitelabel_get_preview(
+    themes: State<'_, WhiteLabelThemeService>,
+    preview_id: String,
+) -> Result<WhiteLabelThemeBundle, String> {
+    themes.get_preview(&preview_id)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WhiteLabelPreview {
+    pub preview_id: String,
     pub preview_url: String,
+    /// Stylesheet scoped to `[data-whitelabel-preview="<preview_id>"]`
+    pub theme: WhiteLabelThemeBundle,
     pub expires_at: i64,
 }
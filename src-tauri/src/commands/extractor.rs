@@ -16,6 +16,7 @@ use tauri::State;
 pub enum SelectorType {
     Css,
     Xpath,
+    Regex,
     Text,
     Attribute,
     Smart,
@@ -43,6 +44,28 @@ pub struct Selector {
     pub confidence: Option<f32>,
     pub fallback: Option<Box<Selector>>,
     pub validation: Option<SelectorValidation>,
+    /// Options for `regex` selectors
+    #[serde(default)]
+    pub regex: Option<RegexSelectorOptions>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegexSource {
+    #[default]
+    Text,
+    Html,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegexSelectorOptions {
+    /// Match against the element's text or its HTML
+    #[serde(default)]
+    pub source: RegexSource,
+    /// Capture group to return; defaults to 1 when the pattern has groups, else the whole match
+    pub group: Option<usize>,
+    /// CSS selector for the element to match against; defaults to the whole document
+    pub scope: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -464,6 +487,16 @@ fn extract_field_with_browser<'a>(
     Box<dyn std::future::Future<Output = Result<serde_json::Value, String>> + Send + 'a>,
 > {
     Box::pin(async move {
+        // XPath and regex run against the parsed page source rather than the live DOM
+        if matches!(field.selector.selector_type, SelectorType::Xpath | SelectorType::Regex)
+            && !matches!(field.selector.strategy, SelectorStrategy::Nested)
+        {
+            let html = browser
+                .get_html(tab_id)
+                .map_err(|e| format!("Failed to read page HTML: {}", e))?;
+            return extract_from_html(&html, &field.selector);
+        }
+
         match field.selector.strategy {
             SelectorStrategy::Single => {
                 // Extract single element
@@ -768,6 +801,677 @@ fn apply_transforms(
     Ok(result)
 }
 
+// ============================================================================
+// XPATH & REGEX EXTRACTION
+// ============================================================================
+
+/// Evaluates an XPath or regex selector against a page's HTML
+fn extract_from_html(html: &str, selector: &Selector) -> Result<serde_json::Value, String> {
+    let document = scraper::Html::parse_document(html);
+
+    let values: Vec<String> = match selector.selector_type {
+        SelectorType::Xpath => {
+            let xpath = parse_xpath(&selector.value)?;
+            let nodes = evaluate_xpath(&document, &xpath);
+
+            if let SelectorStrategy::Table = selector.strategy {
+                let table = nodes
+                    .iter()
+                    .find_map(|node| match node {
+                        XPathNode::Element(el) => Some(*el),
+                        _ => None,
+                    })
+                    .ok_or_else(|| format!("No table matched XPath '{}'", selector.value))?;
+                return Ok(table_from_element(table));
+            }
+
+            nodes.iter().map(|node| node.string_value()).collect()
+        }
+        SelectorType::Regex => {
+            if let SelectorStrategy::Table = selector.strategy {
+                return Err("Table extraction requires a CSS or XPath selector".to_string());
+            }
+            let re = regex::Regex::new(&selector.value)
+                .map_err(|e| format!("Invalid regex '{}': {}", selector.value, e))?;
+            let options = selector.regex.clone().unwrap_or_default();
+            let haystack = regex_haystack(&document, &options)?;
+            let group = options
+                .group
+                .unwrap_or(if re.captures_len() > 1 { 1 } else { 0 });
+
+            re.captures_iter(&haystack)
+                .filter_map(|caps| caps.get(group).map(|m| m.as_str().to_string()))
+                .collect()
+        }
+        _ => return Err("Only XPath and regex selectors are evaluated against page HTML".to_string()),
+    };
+
+    match selector.strategy {
+        SelectorStrategy::Single => values
+            .into_iter()
+            .next()
+            .map(|v| serde_json::json!(v))
+            .ok_or_else(|| format!("No match for '{}'", selector.value)),
+        _ => Ok(serde_json::json!(values)),
+    }
+}
+
+fn regex_haystack(document: &scraper::Html, options: &RegexSelectorOptions) -> Result<String, String> {
+    let element = match &options.scope {
+        Some(scope) => {
+            let css = scraper::Selector::parse(scope)
+                .map_err(|e| format!("Invalid regex scope '{}': {:?}", scope, e))?;
+            document
+                .select(&css)
+                .next()
+                .ok_or_else(|| format!("Regex scope '{}' matched nothing", scope))?
+        }
+        None => document.root_element(),
+    };
+
+    Ok(match options.source {
+        RegexSource::Text => element.text().collect(),
+        RegexSource::Html => element.html(),
+    })
+}
+
+fn table_from_element(table: scraper::ElementRef) -> serde_json::Value {
+    let row_selector = scraper::Selector::parse("tr").unwrap();
+    let header_selector = scraper::Selector::parse("th, td").unwrap();
+    let cell_selector = scraper::Selector::parse("td").unwrap();
+
+    let rows: Vec<scraper::ElementRef> = table.select(&row_selector).collect();
+    let Some(header_row) = rows.first() else {
+        return serde_json::json!([]);
+    };
+    let headers: Vec<String> = header_row
+        .select(&header_selector)
+        .map(|h| h.text().collect::<String>().trim().to_string())
+        .collect();
+
+    let records: Vec<serde_json::Value> = rows[1..]
+        .iter()
+        .map(|row| {
+            let mut record = serde_json::Map::new();
+            for (i, cell) in row.select(&cell_selector).enumerate() {
+                let key = headers
+                    .get(i)
+                    .filter(|h| !h.is_empty())
+                    .cloned()
+                    .unwrap_or_else(|| format!("col{}", i));
+                record.insert(key, serde_json::json!(cell.text().collect::<String>().trim()));
+            }
+            serde_json::Value::Object(record)
+        })
+        .collect();
+
+    serde_json::json!(records)
+}
+
+// XPath 1.0 subset: location paths with the child, descendant(-or-self), parent,
+// ancestor, self, following/preceding-sibling and attribute axes, the `//`, `.`,
+// `..` and `@` abbreviations, `*`/`text()`/`node()` tests, and predicates for
+// position, `last()`, existence, `=`, `contains()` and `starts-with()`.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum XPathAxis {
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Parent,
+    Ancestor,
+    SelfNode,
+    FollowingSibling,
+    PrecedingSibling,
+    Attribute,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum XPathNodeTest {
+    Name(String),
+    AnyElement,
+    Text,
+    AnyNode,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum XPathOperand {
+    Attribute(String),
+    Text,
+    StringValue,
+    NormalizedSpace,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum XPathPredicate {
+    Position(usize),
+    Last,
+    Exists(XPathOperand),
+    Equals(XPathOperand, String),
+    Contains(XPathOperand, String),
+    StartsWith(XPathOperand, String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct XPathStep {
+    axis: XPathAxis,
+    test: XPathNodeTest,
+    predicates: Vec<XPathPredicate>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct XPathExpr {
+    steps: Vec<XPathStep>,
+}
+
+#[derive(Debug, Clone)]
+enum XPathNode<'a> {
+    Document,
+    Element(scraper::ElementRef<'a>),
+    Text(String),
+    Attribute(String),
+}
+
+impl XPathNode<'_> {
+    fn string_value(&self) -> String {
+        match self {
+            XPathNode::Document => String::new(),
+            XPathNode::Element(el) => el.text().collect(),
+            XPathNode::Text(text) | XPathNode::Attribute(text) => text.clone(),
+        }
+    }
+}
+
+fn parse_xpath(expr: &str) -> Result<XPathExpr, String> {
+    let expr = expr.trim();
+    if expr.is_empty() {
+        return Err("XPath expression is empty".to_string());
+    }
+
+    let mut steps = Vec::new();
+    let mut pos = 0;
+    while pos < expr.len() {
+        let rest = &expr[pos..];
+        if rest.starts_with("//") {
+            steps.push(XPathStep {
+                axis: XPathAxis::DescendantOrSelf,
+                test: XPathNodeTest::AnyNode,
+                predicates: Vec::new(),
+            });
+            pos += 2;
+        } else if rest.starts_with('/') {
+            pos += 1;
+        } else if pos > 0 {
+            return Err(format!("Unexpected '{}' in XPath '{}'", rest, expr));
+        }
+
+        let end = find_step_end(expr, pos)?;
+        let step = expr[pos..end].trim();
+        if step.is_empty() {
+            return Err(format!("Empty location step in XPath '{}'", expr));
+        }
+        steps.push(parse_xpath_step(step)?);
+        pos = end;
+    }
+
+    if let Some(index) = steps[..steps.len() - 1]
+        .iter()
+        .position(|s| s.axis == XPathAxis::Attribute || s.test == XPathNodeTest::Text)
+    {
+        return Err(format!(
+            "Attribute and text() steps must be last in XPath '{}' (step {})",
+            expr,
+            index + 1
+        ));
+    }
+
+    Ok(XPathExpr { steps })
+}
+
+/// Finds the `/` that ends the step starting at `start`, ignoring ones inside
+/// predicates, parentheses and string literals
+fn find_step_end(expr: &str, start: usize) -> Result<usize, String> {
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    for (offset, c) in expr[start..].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[' | '(') => depth += 1,
+            (None, ']' | ')') => {
+                depth -= 1;
+                if depth < 0 {
+                    return Err(format!("Unbalanced '{}' in XPath '{}'", c, expr));
+                }
+            }
+            (None, '/') if depth == 0 => return Ok(start + offset),
+            _ => {}
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("Unterminated string literal in XPath '{}'", expr));
+    }
+    if depth != 0 {
+        return Err(format!("Unbalanced brackets in XPath '{}'", expr));
+    }
+    Ok(expr.len())
+}
+
+fn parse_xpath_step(step: &str) -> Result<XPathStep, String> {
+    let (node_part, mut rest) = match step.find('[') {
+        Some(i) => (step[..i].trim(), &step[i..]),
+        None => (step, ""),
+    };
+
+    let (axis, test) = match node_part {
+        "." => (XPathAxis::SelfNode, XPathNodeTest::AnyNode),
+        ".." => (XPathAxis::Parent, XPathNodeTest::AnyNode),
+        _ if node_part.starts_with('@') => {
+            (XPathAxis::Attribute, XPathNodeTest::Name(parse_xpath_name(&node_part[1..])?))
+        }
+        _ => match node_part.split_once("::") {
+            Some((axis, test)) => {
+                let axis = match axis.trim() {
+                    "child" => XPathAxis::Child,
+                    "descendant" => XPathAxis::Descendant,
+                    "descendant-or-self" => XPathAxis::DescendantOrSelf,
+                    "parent" => XPathAxis::Parent,
+                    "ancestor" => XPathAxis::Ancestor,
+                    "self" => XPathAxis::SelfNode,
+                    "following-sibling" => XPathAxis::FollowingSibling,
+                    "preceding-sibling" => XPathAxis::PrecedingSibling,
+                    "attribute" => XPathAxis::Attribute,
+                    other => return Err(format!("Unsupported XPath axis '{}'", other)),
+                };
+                (axis, parse_xpath_node_test(test.trim())?)
+            }
+            None => (XPathAxis::Child, parse_xpath_node_test(node_part)?),
+        },
+    };
+
+    if axis == XPathAxis::Attribute && !matches!(test, XPathNodeTest::Name(_)) {
+        return Err("Attribute steps require an attribute name".to_string());
+    }
+
+    let mut predicates = Vec::new();
+    while !rest.is_empty() {
+        if !rest.starts_with('[') {
+            return Err(format!("Unexpected '{}' after XPath step", rest));
+        }
+        let close = find_predicate_end(rest)
+            .ok_or_else(|| format!("Unterminated predicate in '{}'", step))?;
+        predicates.push(parse_xpath_predicate(rest[1..close].trim())?);
+        rest = rest[close + 1..].trim_start();
+    }
+
+    Ok(XPathStep { axis, test, predicates })
+}
+
+/// Index of the `]` closing the predicate that opens at the start of `s`
+fn find_predicate_end(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '[') => depth += 1,
+            (None, ']') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_xpath_node_test(test: &str) -> Result<XPathNodeTest, String> {
+    match test {
+        "*" => Ok(XPathNodeTest::AnyElement),
+        "text()" => Ok(XPathNodeTest::Text),
+        "node()" => Ok(XPathNodeTest::AnyNode),
+        name => Ok(XPathNodeTest::Name(parse_xpath_name(name)?)),
+    }
+}
+
+fn parse_xpath_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'));
+    if valid {
+        Ok(name.to_ascii_lowercase())
+    } else {
+        Err(format!("Invalid XPath name '{}'", name))
+    }
+}
+
+fn parse_xpath_predicate(predicate: &str) -> Result<XPathPredicate, String> {
+    if predicate.is_empty() {
+        return Err("Empty XPath predicate".to_string());
+    }
+    if let Ok(position) = predicate.parse::<usize>() {
+        if position == 0 {
+            return Err("XPath positions start at 1".to_string());
+        }
+        return Ok(XPathPredicate::Position(position));
+    }
+    if predicate == "last()" {
+        return Ok(XPathPredicate::Last);
+    }
+
+    for function in ["contains", "starts-with"] {
+        let args = predicate
+            .strip_prefix(function)
+            .and_then(|a| a.trim_start().strip_prefix('('))
+            .and_then(|a| a.strip_suffix(')'));
+        if let Some(args) = args {
+            let (operand, literal) = split_top_level_comma(args)
+                .ok_or_else(|| format!("{}() takes two arguments", function))?;
+            let operand = parse_xpath_operand(operand)?;
+            let literal = parse_xpath_literal(literal)?;
+            return Ok(if function == "contains" {
+                XPathPredicate::Contains(operand, literal)
+            } else {
+                XPathPredicate::StartsWith(operand, literal)
+            });
+        }
+    }
+
+    if let Some(eq) = find_top_level_char(predicate, '=') {
+        let operand = parse_xpath_operand(&predicate[..eq])?;
+        let literal = parse_xpath_literal(&predicate[eq + 1..])?;
+        return Ok(XPathPredicate::Equals(operand, literal));
+    }
+
+    parse_xpath_operand(predicate)
+        .map(XPathPredicate::Exists)
+        .map_err(|_| format!("Unsupported XPath predicate '[{}]'", predicate))
+}
+
+fn find_top_level_char(s: &str, target: char) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, c) if c == target && depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn split_top_level_comma(s: &str) -> Option<(&str, &str)> {
+    find_top_level_char(s, ',').map(|i| (s[..i].trim(), s[i + 1..].trim()))
+}
+
+fn parse_xpath_operand(operand: &str) -> Result<XPathOperand, String> {
+    match operand.trim() {
+        "." | "string()" | "string(.)" => Ok(XPathOperand::StringValue),
+        "text()" => Ok(XPathOperand::Text),
+        "normalize-space()" | "normalize-space(.)" => Ok(XPathOperand::NormalizedSpace),
+        other => match other.strip_prefix('@') {
+            Some(name) => Ok(XPathOperand::Attribute(parse_xpath_name(name)?)),
+            None => Err(format!("Unsupported XPath operand '{}'", other)),
+        },
+    }
+}
+
+fn parse_xpath_literal(literal: &str) -> Result<String, String> {
+    let literal = literal.trim();
+    let quoted = literal.len() >= 2
+        && ((literal.starts_with('\'') && literal.ends_with('\''))
+            || (literal.starts_with('"') && literal.ends_with('"')));
+    if quoted {
+        Ok(literal[1..literal.len() - 1].to_string())
+    } else {
+        Err(format!("Expected a quoted string, found '{}'", literal))
+    }
+}
+
+fn evaluate_xpath<'a>(document: &'a scraper::Html, expr: &XPathExpr) -> Vec<XPathNode<'a>> {
+    let mut context = vec![XPathNode::Document];
+
+    for step in &expr.steps {
+        let mut next = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+
+        for node in &context {
+            let mut candidates: Vec<XPathNode<'a>> = xpath_axis(document, node, step)
+                .into_iter()
+                .filter(|candidate| xpath_test_matches(candidate, &step.test))
+                .collect();
+
+            for predicate in &step.predicates {
+                let size = candidates.len();
+                candidates = candidates
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(i, candidate)| xpath_predicate_matches(candidate, predicate, i + 1, size))
+                    .map(|(_, candidate)| candidate)
+                    .collect();
+            }
+
+            for candidate in candidates {
+                let unseen = match &candidate {
+                    XPathNode::Element(el) => seen.insert(Some(el.id())),
+                    XPathNode::Document => seen.insert(None),
+                    _ => true,
+                };
+                if unseen {
+                    next.push(candidate);
+                }
+            }
+        }
+
+        context = next;
+    }
+
+    context
+}
+
+/// Nodes along `step.axis` from `node`, nearest first for reverse axes
+fn xpath_axis<'a>(document: &'a scraper::Html, node: &XPathNode<'a>, step: &XPathStep) -> Vec<XPathNode<'a>> {
+    macro_rules! wrap {
+        ($nodes:expr) => {
+            $nodes
+                .filter_map(|n| {
+                    if let Some(el) = scraper::ElementRef::wrap(n) {
+                        Some(XPathNode::Element(el))
+                    } else if let Some(text) = n.value().as_text() {
+                        Some(XPathNode::Text(text.to_string()))
+                    } else if n.value().is_document() {
+                        Some(XPathNode::Document)
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+    }
+
+    match node {
+        XPathNode::Document => {
+            let root = document.tree.root();
+            match step.axis {
+                XPathAxis::Child => wrap!(root.children()),
+                XPathAxis::Descendant => wrap!(root.descendants().skip(1)),
+                XPathAxis::DescendantOrSelf => wrap!(root.descendants()),
+                XPathAxis::SelfNode => vec![XPathNode::Document],
+                _ => Vec::new(),
+            }
+        }
+        XPathNode::Element(el) => match step.axis {
+            XPathAxis::Child => wrap!(el.children()),
+            XPathAxis::Descendant => wrap!(el.descendants().skip(1)),
+            XPathAxis::DescendantOrSelf => wrap!(el.descendants()),
+            XPathAxis::Parent => wrap!(el.parent().into_iter()),
+            XPathAxis::Ancestor => wrap!(el.ancestors()),
+            XPathAxis::SelfNode => vec![node.clone()],
+            XPathAxis::FollowingSibling => wrap!(el.next_siblings()),
+            XPathAxis::PrecedingSibling => wrap!(el.prev_siblings()),
+            XPathAxis::Attribute => match &step.test {
+                XPathNodeTest::Name(name) => el
+                    .value()
+                    .attr(name)
+                    .map(|value| vec![XPathNode::Attribute(value.to_string())])
+                    .unwrap_or_default(),
+                _ => Vec::new(),
+            },
+        },
+        XPathNode::Text(_) | XPathNode::Attribute(_) => match step.axis {
+            XPathAxis::SelfNode => vec![node.clone()],
+            _ => Vec::new(),
+        },
+    }
+}
+
+fn xpath_test_matches(node: &XPathNode, test: &XPathNodeTest) -> bool {
+    match (test, node) {
+        (XPathNodeTest::AnyNode, _) => true,
+        (XPathNodeTest::Text, XPathNode::Text(_)) => true,
+        (XPathNodeTest::AnyElement, XPathNode::Element(_)) => true,
+        (XPathNodeTest::Name(name), XPathNode::Element(el)) => el.value().name().eq_ignore_ascii_case(name),
+        (XPathNodeTest::Name(_), XPathNode::Attribute(_)) => true,
+        _ => false,
+    }
+}
+
+fn xpath_operand_value(node: &XPathNode, operand: &XPathOperand) -> Option<String> {
+    match (operand, node) {
+        (XPathOperand::Attribute(name), XPathNode::Element(el)) => el.value().attr(name).map(str::to_string),
+        (XPathOperand::Attribute(_), _) => None,
+        (XPathOperand::Text, XPathNode::Element(el)) => {
+            let text: String = el
+                .children()
+                .filter_map(|n| n.value().as_text().map(|t| t.to_string()))
+                .collect();
+            (!text.is_empty()).then_some(text)
+        }
+        (XPathOperand::StringValue, _) | (XPathOperand::Text, _) => Some(node.string_value()),
+        (XPathOperand::NormalizedSpace, _) => {
+            Some(node.string_value().split_whitespace().collect::<Vec<_>>().join(" "))
+        }
+    }
+}
+
+fn xpath_predicate_matches(node: &XPathNode, predicate: &XPathPredicate, position: usize, size: usize) -> bool {
+    match predicate {
+        XPathPredicate::Position(n) => position == *n,
+        XPathPredicate::Last => position == size,
+        XPathPredicate::Exists(operand) => {
+            xpath_operand_value(node, operand).is_some_and(|v| !v.is_empty())
+        }
+        XPathPredicate::Equals(operand, literal) => {
+            xpath_operand_value(node, operand).is_some_and(|v| v == *literal)
+        }
+        XPathPredicate::Contains(operand, literal) => {
+            xpath_operand_value(node, operand).is_some_and(|v| v.contains(literal.as_str()))
+        }
+        XPathPredicate::StartsWith(operand, literal) => {
+            xpath_operand_value(node, operand).is_some_and(|v| v.starts_with(literal.as_str()))
+        }
+    }
+}
+
+// ============================================================================
+// SCHEMA VALIDATION
+// ============================================================================
+
+/// Checks every selector expression in a schema so bad ones fail at save time
+/// rather than silently extracting nothing
+fn validate_schema(schema: &ExtractionSchema) -> Result<(), String> {
+    let mut errors = Vec::new();
+    for field in &schema.fields {
+        validate_field(field, &field.name, &mut errors);
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Invalid extraction schema:\n- {}", errors.join("\n- ")))
+    }
+}
+
+fn validate_field(field: &ExtractionField, path: &str, errors: &mut Vec<String>) {
+    let mut selector = Some(&field.selector);
+    let mut label = "selector";
+    while let Some(current) = selector {
+        if let Err(e) = validate_selector(current) {
+            errors.push(format!("Field '{}' {}: {}", path, label, e));
+        }
+        selector = current.fallback.as_deref();
+        label = "fallback selector";
+    }
+
+    for transform in field.transform.iter().flatten() {
+        if let Err(e) = validate_transform(transform) {
+            errors.push(format!("Field '{}' transform: {}", path, e));
+        }
+    }
+
+    for child in field.children.iter().flatten() {
+        validate_field(child, &format!("{}.{}", path, child.name), errors);
+    }
+}
+
+fn validate_selector(selector: &Selector) -> Result<(), String> {
+    if let SelectorStrategy::Nested = selector.strategy {
+        return Ok(());
+    }
+
+    match selector.selector_type {
+        SelectorType::Css => scraper::Selector::parse(&selector.value)
+            .map(|_| ())
+            .map_err(|e| format!("invalid CSS selector '{}': {:?}", selector.value, e)),
+        SelectorType::Xpath => parse_xpath(&selector.value)
+            .map(|_| ())
+            .map_err(|e| format!("invalid XPath: {}", e)),
+        SelectorType::Regex => {
+            let re = regex::Regex::new(&selector.value)
+                .map_err(|e| format!("invalid regex '{}': {}", selector.value, e))?;
+            if let SelectorStrategy::Table = selector.strategy {
+                return Err("table strategy needs a CSS or XPath selector".to_string());
+            }
+            let options = selector.regex.clone().unwrap_or_default();
+            if let Some(group) = options.group {
+                if group >= re.captures_len() {
+                    return Err(format!("regex '{}' has no capture group {}", selector.value, group));
+                }
+            }
+            if let Some(scope) = &options.scope {
+                scraper::Selector::parse(scope)
+                    .map_err(|e| format!("invalid regex scope '{}': {:?}", scope, e))?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_transform(transform: &DataTransform) -> Result<(), String> {
+    let param = |key: &str| transform.params.as_ref().and_then(|p| p.get(key));
+
+    let is_regex = match transform.transform_type {
+        TransformType::Extract => true,
+        TransformType::Replace => param("regex").and_then(|v| v.as_bool()).unwrap_or(false),
+        _ => false,
+    };
+    if let (true, Some(pattern)) = (is_regex, param("pattern").and_then(|v| v.as_str())) {
+        regex::Regex::new(pattern).map_err(|e| format!("invalid regex '{}': {}", pattern, e))?;
+    }
+    Ok(())
+}
+
 // ============================================================================
 // AI SELECTOR SUGGESTIONS
 // ============================================================================
@@ -798,6 +1502,7 @@ async fn generate_selector_suggestions(
                 confidence: Some(0.98),
                 fallback: None,
                 validation: None,
+                regex: None,
             },
             reasoning: "ID selectors are unique and most reliable for element selection".to_string(),
             examples: vec![format!("document.querySelector('#{}')", id)],
@@ -830,6 +1535,7 @@ async fn generate_selector_suggestions(
                     confidence: Some(0.92),
                     fallback: None,
                     validation: None,
+                    regex: None,
                 },
                 reasoning: "Data attributes are often used specifically for automation and testing".to_string(),
                 examples: vec![format!("document.querySelector('[{}=\"{}\"]')", attr, v)],
@@ -864,6 +1570,7 @@ async fn generate_selector_suggestions(
                         confidence: Some(0.85),
                         fallback: None,
                         validation: None,
+                        regex: None,
                     },
                     reasoning: "Class selectors provide good balance of specificity and maintainability".to_string(),
                     examples: vec![format!("document.querySelector('{}{}')", tag.to_lowercase(), class_selector)],
@@ -888,6 +1595,7 @@ async fn generate_selector_suggestions(
                 confidence: Some(0.75),
                 fallback: None,
                 validation: None,
+                regex: None,
             },
             reasoning: "Text-based selectors are readable and work well for static content".to_string(),
             examples: vec![format!("document.evaluate(\"//{}[contains(text(),'{}')]\", ...)", tag.to_lowercase(), clean_text)],
@@ -909,6 +1617,7 @@ async fn generate_selector_suggestions(
                 confidence: Some(0.90),
                 fallback: None,
                 validation: None,
+                regex: None,
             },
             reasoning: "Aria labels are semantic and stable across UI changes".to_string(),
             examples: vec![format!("document.querySelector('[aria-label=\"{}\"]')", aria_label)],
@@ -1034,6 +1743,7 @@ Focus on:
                     confidence: Some(confidence),
                     fallback: None,
                     validation: None,
+                    regex: None,
                 },
                 reasoning: reasoning.to_string(),
                 examples: vec![],
@@ -1046,6 +1756,497 @@ Focus on:
     Ok(suggestions)
 }
 
+// ============================================================================
+// AI SCHEMA GENERATION
+// ============================================================================
+
+/// A schema generated from a natural-language description, checked against the page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedSchema {
+    pub schema: ExtractionSchema,
+    /// "ai" or "heuristic"
+    pub generator: String,
+    /// Values each field produced on the page during validation
+    pub preview: ExtractedData,
+    pub warnings: Vec<String>,
+}
+
+/// Element worth extracting, as shown to the AI and scored by the heuristic
+#[derive(Debug, Clone, Serialize)]
+struct CandidateElement {
+    selector: String,
+    tag: String,
+    /// id, classes, itemprop and aria-label, lowercased
+    hints: String,
+    sample: String,
+    count: usize,
+}
+
+/// Chat completion backend used to draft schemas
+#[async_trait::async_trait]
+trait SchemaAi: Send + Sync {
+    async fn complete(&self, system: &str, prompt: &str) -> Result<String, String>;
+}
+
+struct OpenAiSchemaAi {
+    api_key: String,
+}
+
+#[async_trait::async_trait]
+impl SchemaAi for OpenAiSchemaAi {
+    async fn complete(&self, system: &str, prompt: &str) -> Result<String, String> {
+        let request_body = serde_json::json!({
+            "model": "gpt-4-turbo-preview",
+            "messages": [
+                { "role": "system", "content": system },
+                { "role": "user", "content": prompt }
+            ],
+            "temperature": 0.2,
+            "max_tokens": 1500,
+            "response_format": { "type": "json_object" }
+        });
+
+        let response = reqwest::Client::new()
+            .post("https://api.openai.com/v1/chat/completions")
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|e| format!("OpenAI API error: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("OpenAI API request failed: {}", response.status()));
+        }
+
+        let response_json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        response_json
+            .pointer("/choices/0/message/content")
+            .and_then(|c| c.as_str())
+            .map(|c| c.to_string())
+            .ok_or_else(|| "Invalid response format".to_string())
+    }
+}
+
+const SKIPPED_TAGS: &[&str] = &["script", "style", "noscript", "head", "meta", "link", "svg", "template", "iframe"];
+const MAX_CANDIDATES: usize = 150;
+
+/// Collect text-bearing elements with a selector that finds them
+fn collect_candidates(document: &scraper::Html) -> Vec<CandidateElement> {
+    let mut candidates: Vec<CandidateElement> = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for element in document.root_element().descendants().filter_map(scraper::ElementRef::wrap) {
+        let el = element.value();
+        let tag = el.name();
+        if SKIPPED_TAGS.contains(&tag) || element.ancestors().filter_map(scraper::ElementRef::wrap).any(|a| SKIPPED_TAGS.contains(&a.value().name())) {
+            continue;
+        }
+        let sample = element.text().collect::<Vec<_>>().join(" ").split_whitespace().collect::<Vec<_>>().join(" ");
+        if sample.is_empty() || sample.len() > 200 {
+            continue;
+        }
+
+        let classes: Vec<&str> = el.classes().filter(|c| is_css_ident(c)).take(2).collect();
+        let selector = if let Some(id) = el.id().filter(|id| is_css_ident(id)) {
+            format!("#{}", id)
+        } else if let Some(prop) = el.attr("itemprop") {
+            format!("[itemprop=\"{}\"]", prop.replace('"', ""))
+        } else if !classes.is_empty() {
+            format!("{}.{}", tag, classes.join("."))
+        } else if matches!(tag, "h1" | "h2" | "h3" | "time" | "title") {
+            tag.to_string()
+        } else {
+            continue;
+        };
+        if !seen.insert(selector.clone()) {
+            continue;
+        }
+        let Ok(parsed) = scraper::Selector::parse(&selector) else {
+            continue;
+        };
+
+        let hints = [el.id(), el.attr("class"), el.attr("itemprop"), el.attr("aria-label"), el.attr("name")]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        candidates.push(CandidateElement {
+            count: document.select(&parsed).count(),
+            selector,
+            tag: tag.to_string(),
+            hints,
+            sample: sample.chars().take(80).collect(),
+        });
+        if candidates.len() >= MAX_CANDIDATES {
+            break;
+        }
+    }
+    candidates
+}
+
+fn is_css_ident(s: &str) -> bool {
+    !s.is_empty()
+        && !s.starts_with(|c: char| c.is_ascii_digit() || c == '-')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Indented tag.class outline of the body, enough for the AI to see the layout
+fn simplified_dom(document: &scraper::Html, max_lines: usize) -> String {
+    fn walk(element: scraper::ElementRef, depth: usize, lines: &mut Vec<String>, max_lines: usize) {
+        if lines.len() >= max_lines || depth > 12 || SKIPPED_TAGS.contains(&element.value().name()) {
+            return;
+        }
+        let el = element.value();
+        let mut line = format!("{}{}", "  ".repeat(depth), el.name());
+        if let Some(id) = el.id() {
+            line.push_str(&format!("#{}", id));
+        }
+        for class in el.classes().take(3) {
+            line.push_str(&format!(".{}", class));
+        }
+        lines.push(line);
+        for child in element.children().filter_map(scraper::ElementRef::wrap) {
+            walk(child, depth + 1, lines, max_lines);
+        }
+    }
+
+    let mut lines = Vec::new();
+    let body = scraper::Selector::parse("body").ok().and_then(|s| document.select(&s).next());
+    walk(body.unwrap_or_else(|| document.root_element()), 0, &mut lines, max_lines);
+    lines.join("\n")
+}
+
+/// Split "title, price and rating" into the fields the user asked for
+fn described_fields(description: &str) -> Vec<String> {
+    const FILLER: &[&str] = &["extract", "get", "scrape", "find", "the", "a", "an", "all", "each", "every", "of", "its", "their", "i", "want", "need", "please", "from", "page", "this", "list"];
+
+    let lowered = description.to_lowercase();
+    let mut fields = Vec::new();
+    for part in lowered.split([',', ';', '\n', '.']).flat_map(|p| p.split(" and ")) {
+        let words: Vec<&str> = part
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty() && !FILLER.contains(w))
+            .collect();
+        if words.is_empty() {
+            continue;
+        }
+        let name = words.join("_");
+        if !fields.contains(&name) {
+            fields.push(name);
+        }
+    }
+    fields
+}
+
+fn field_keywords(field: &str) -> Vec<String> {
+    let mut keywords: Vec<String> = field.split('_').map(|w| w.trim_end_matches('s').to_string()).filter(|w| w.len() > 1).collect();
+    let synonyms: &[(&str, &[&str])] = &[
+        ("price", &["price", "cost", "amount", "sale"]),
+        ("cost", &["price", "cost", "amount"]),
+        ("title", &["title", "name", "heading"]),
+        ("name", &["name", "title", "heading"]),
+        ("rating", &["rating", "stars", "score"]),
+        ("review", &["review", "rating", "comment"]),
+        ("date", &["date", "time", "published"]),
+        ("author", &["author", "byline", "by"]),
+        ("description", &["description", "desc", "summary", "details"]),
+        ("image", &["image", "img", "photo"]),
+    ];
+    for (word, extra) in synonyms {
+        if keywords.iter().any(|k| k == word) {
+            keywords.extend(extra.iter().map(|e| e.to_string()));
+        }
+    }
+    keywords.sort();
+    keywords.dedup();
+    keywords
+}
+
+fn field_data_type(field: &str) -> &'static str {
+    const NUMERIC: &[&str] = &["price", "cost", "amount", "rating", "score", "count", "quantity", "qty", "stars", "reviews", "total"];
+    const DATES: &[&str] = &["date", "time", "published", "updated"];
+    let words: Vec<&str> = field.split('_').collect();
+    if words.iter().any(|w| NUMERIC.contains(w)) {
+        "number"
+    } else if words.iter().any(|w| DATES.contains(w)) {
+        "date"
+    } else {
+        "string"
+    }
+}
+
+/// Best candidate for a field by keyword overlap with its hints, tag and sample
+fn heuristic_match<'a>(field: &str, candidates: &'a [CandidateElement]) -> Option<&'a CandidateElement> {
+    let keywords = field_keywords(field);
+    let data_type = field_data_type(field);
+    let has_digits = |s: &str| s.chars().any(|c| c.is_ascii_digit());
+
+    candidates
+        .iter()
+        .map(|candidate| {
+            let mut score = 0i32;
+            for keyword in &keywords {
+                if candidate.hints.contains(keyword.as_str()) {
+                    score += 3;
+                }
+            }
+            if score == 0 {
+                return (candidate, 0);
+            }
+            match data_type {
+                "number" if has_digits(&candidate.sample) && candidate.sample.len() < 40 => score += 2,
+                "number" => score -= 2,
+                "date" if candidate.tag == "time" => score += 2,
+                _ => {}
+            }
+            if keywords.iter().any(|k| k == "title" || k == "name") && matches!(candidate.tag.as_str(), "h1" | "h2" | "h3") {
+                score += 2;
+            }
+            (candidate, score)
+        })
+        .filter(|(_, score)| *score > 0)
+        .max_by_key(|(candidate, score)| (*score, std::cmp::Reverse(candidate.selector.len())))
+        .map(|(candidate, _)| candidate)
+}
+
+/// Run a CSS or XPath selector over the page and return the matched texts
+fn resolve_on_page(document: &scraper::Html, html: &str, selector: &Selector) -> Result<Vec<String>, String> {
+    match selector.selector_type {
+        SelectorType::Css => {
+            let parsed = scraper::Selector::parse(&selector.value)
+                .map_err(|e| format!("invalid CSS selector '{}': {:?}", selector.value, e))?;
+            Ok(document
+                .select(&parsed)
+                .map(|el| el.text().collect::<Vec<_>>().join(" ").split_whitespace().collect::<Vec<_>>().join(" "))
+                .collect())
+        }
+        SelectorType::Xpath => {
+            let mut multiple = selector.clone();
+            multiple.strategy = SelectorStrategy::Multiple;
+            let value = extract_from_html(html, &multiple)?;
+            Ok(value
+                .as_array()
+                .map(|values| values.iter().filter_map(|v| v.as_str().map(|s| s.trim().to_string())).collect())
+                .unwrap_or_default())
+        }
+        _ => Err("Generated selectors must be CSS or XPath".to_string()),
+    }
+}
+
+/// A field before its selector has been checked against the page
+struct FieldDraft {
+    name: String,
+    data_type: String,
+    selector_type: SelectorType,
+    selector: String,
+    confidence: f32,
+    reason: String,
+}
+
+impl FieldDraft {
+    fn heuristic(name: &str, candidate: &CandidateElement) -> Self {
+        Self {
+            name: name.to_string(),
+            data_type: field_data_type(name).to_string(),
+            selector_type: SelectorType::Css,
+            selector: candidate.selector.clone(),
+            confidence: 0.6,
+            reason: "Matched by keywords in the element's id, class or attributes".to_string(),
+        }
+    }
+
+    fn selector(&self, strategy: SelectorStrategy) -> Selector {
+        Selector {
+            id: format!("selector_{}", self.name),
+            selector_type: self.selector_type.clone(),
+            value: self.selector.clone(),
+            strategy,
+            label: self.name.clone(),
+            description: Some(self.reason.clone()),
+            confidence: Some(self.confidence),
+            fallback: None,
+            validation: Some(SelectorValidation {
+                required: true,
+                min_matches: Some(1),
+                max_matches: None,
+                pattern: None,
+                data_type: Some(self.data_type.clone()),
+            }),
+            regex: None,
+        }
+    }
+
+    /// Single or multiple depending on how many elements matched
+    fn into_field(self, index: usize, matches: usize) -> ExtractionField {
+        let transform = match self.data_type.as_str() {
+            "number" => TransformType::ParseNumber,
+            _ => TransformType::Trim,
+        };
+        let strategy = if matches > 1 { SelectorStrategy::Multiple } else { SelectorStrategy::Single };
+        ExtractionField {
+            id: format!("field_{}", index + 1),
+            selector: self.selector(strategy),
+            name: self.name,
+            transform: Some(vec![DataTransform { transform_type: transform, params: None }]),
+            validation: None,
+            children: None,
+        }
+    }
+}
+
+fn preview_value(field: &ExtractionField, values: Vec<String>) -> serde_json::Value {
+    let value = match field.selector.strategy {
+        SelectorStrategy::Single => serde_json::json!(values.into_iter().next().unwrap_or_default()),
+        _ => serde_json::json!(values),
+    };
+    match &field.transform {
+        Some(transforms) if value.is_string() => apply_transforms(value.clone(), transforms).unwrap_or(value),
+        _ => value,
+    }
+}
+
+/// Draft a schema with the AI when available, validate every selector on the
+/// page and fill the gaps heuristically
+async fn generate_schema_from_html(
+    url: &str,
+    html: &str,
+    description: &str,
+    ai: Option<&dyn SchemaAi>,
+) -> Result<GeneratedSchema, String> {
+    let document = scraper::Html::parse_document(html);
+    let candidates = collect_candidates(&document);
+    let wanted = described_fields(description);
+    let mut warnings = Vec::new();
+    let mut generator = "heuristic";
+    let mut schema_name = None;
+
+    let mut drafts: Vec<FieldDraft> = Vec::new();
+
+    if let Some(ai) = ai {
+        let page = serde_json::json!({
+            "url": url,
+            "simplifiedDom": simplified_dom(&document, 250),
+            "candidates": candidates,
+        });
+        let prompt = format!(
+            r#"The user wants to extract: "{}"
+
+Page structure and candidate elements:
+{}
+
+Return JSON: {{"name": "schema name", "fields": [{{"name": "snake_case", "selector": "CSS or XPath", "selectorType": "css" or "xpath", "dataType": "string" | "number" | "date", "reasoning": "why"}}]}}
+Only use selectors that match elements shown above. One field per thing the user asked for."#,
+            description,
+            serde_json::to_string(&page).unwrap_or_default()
+        );
+        match ai
+            .complete("You design web scraping schemas. Answer with JSON only.", &prompt)
+            .await
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).map_err(|e| format!("Invalid AI schema: {}", e)))
+        {
+            Ok(reply) => {
+                generator = "ai";
+                schema_name = reply.get("name").and_then(|n| n.as_str()).map(|n| n.to_string());
+                for field in reply.get("fields").and_then(|f| f.as_array()).into_iter().flatten() {
+                    let (Some(name), Some(selector)) = (
+                        field.get("name").and_then(|v| v.as_str()),
+                        field.get("selector").and_then(|v| v.as_str()),
+                    ) else {
+                        continue;
+                    };
+                    let selector_type = match field.get("selectorType").and_then(|v| v.as_str()) {
+                        Some("xpath") => SelectorType::Xpath,
+                        _ if selector.starts_with('/') => SelectorType::Xpath,
+                        _ => SelectorType::Css,
+                    };
+                    drafts.push(FieldDraft {
+                        name: name.to_string(),
+                        data_type: field
+                            .get("dataType")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_else(|| field_data_type(name))
+                            .to_string(),
+                        selector_type,
+                        selector: selector.to_string(),
+                        confidence: 0.9,
+                        reason: field.get("reasoning").and_then(|v| v.as_str()).unwrap_or("Suggested by AI").to_string(),
+                    });
+                }
+            }
+            Err(e) => warnings.push(format!("AI schema generation failed, using heuristics: {}", e)),
+        }
+    }
+
+    let mut fields = Vec::new();
+    let mut preview = HashMap::new();
+    let mut resolve = |draft: FieldDraft, values: Vec<String>, fields: &mut Vec<ExtractionField>| {
+        let field = draft.into_field(fields.len(), values.len());
+        preview.insert(field.name.clone(), preview_value(&field, values));
+        fields.push(field);
+    };
+
+    for draft in drafts {
+        let checked = resolve_on_page(&document, html, &draft.selector(SelectorStrategy::Multiple)).and_then(|values| {
+            if values.iter().any(|v| !v.is_empty()) {
+                Ok(values)
+            } else {
+                Err(format!("'{}' matched nothing", draft.selector))
+            }
+        });
+        match checked {
+            Ok(values) => resolve(draft, values, &mut fields),
+            Err(e) => match heuristic_match(&draft.name, &candidates) {
+                Some(candidate) => {
+                    warnings.push(format!("Field '{}': {}, using '{}' instead", draft.name, e, candidate.selector));
+                    let replacement = FieldDraft { data_type: draft.data_type, ..FieldDraft::heuristic(&draft.name, candidate) };
+                    let values = resolve_on_page(&document, html, &replacement.selector(SelectorStrategy::Multiple))?;
+                    resolve(replacement, values, &mut fields);
+                }
+                None => warnings.push(format!("Dropped field '{}': {}", draft.name, e)),
+            },
+        }
+    }
+
+    // Without the AI, the description itself names the fields
+    for name in wanted.iter().filter(|_| generator == "heuristic") {
+        if fields.iter().any(|f| &f.name == name) {
+            continue;
+        }
+        match heuristic_match(name, &candidates) {
+            Some(candidate) => {
+                let draft = FieldDraft::heuristic(name, candidate);
+                let values = resolve_on_page(&document, html, &draft.selector(SelectorStrategy::Multiple))?;
+                resolve(draft, values, &mut fields);
+            }
+            None => warnings.push(format!("No element on the page matches '{}'", name)),
+        }
+    }
+
+    if fields.is_empty() {
+        return Err("None of the described fields could be found on the page".to_string());
+    }
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let schema = ExtractionSchema {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: schema_name.unwrap_or_else(|| format!("Generated: {}", description.chars().take(60).collect::<String>())),
+        description: Some(description.to_string()),
+        url: url.to_string(),
+        fields,
+        pagination: None,
+        schedule: None,
+        created: now.clone(),
+        modified: now,
+        version: 1,
+    };
+    validate_schema(&schema)?;
+
+    Ok(GeneratedSchema { schema, generator: generator.to_string(), preview, warnings })
+}
+
 // ============================================================================
 // EXPORT
 // ============================================================================
@@ -1388,6 +2589,8 @@ pub async fn extractor_save_schema(
     schema: ExtractionSchema,
     state: State<'_, ExtractorState>,
 ) -> Result<String, String> {
+    validate_schema(&schema)?;
+
     // Save to disk
     save_schema_to_disk(&schema)?;
 
@@ -1460,6 +2663,41 @@ pub async fn extractor_suggest_selectors(
     generate_selector_suggestions(element).await
 }
 
+/// Describe the data in plain words and get a ready-to-run schema for the page
+#[tauri::command]
+pub async fn extractor_generate_schema_from_prompt(
+    url: String,
+    description: String,
+    browser: State<'_, Arc<BrowserService>>,
+) -> Result<GeneratedSchema, String> {
+    if description.trim().is_empty() {
+        return Err("Describe the data to extract".to_string());
+    }
+
+    let tab_id = browser
+        .new_tab()
+        .map_err(|e| format!("Failed to create browser tab: {}", e))?;
+    let html = async {
+        browser
+            .navigate(&tab_id, &url)
+            .map_err(|e| format!("Failed to navigate: {}", e))?;
+        tokio::time::sleep(tokio::time::Duration::from_millis(1500)).await;
+        // The live DOM, so selectors are validated against what scripts rendered
+        browser
+            .get_page_html(&tab_id)
+            .map_err(|e| format!("Failed to read page HTML: {}", e))
+    }
+    .await;
+    let _ = browser.close_tab(&tab_id);
+    let html = html?;
+
+    let ai = std::env::var("OPENAI_API_KEY")
+        .ok()
+        .filter(|key| !key.is_empty())
+        .map(|api_key| OpenAiSchemaAi { api_key });
+    generate_schema_from_html(&url, &html, &description, ai.as_ref().map(|ai| ai as &dyn SchemaAi)).await
+}
+
 #[tauri::command]
 pub async fn extractor_analyze_page(
     url: String,
@@ -1577,6 +2815,7 @@ pub async fn extractor_analyze_page(
                 confidence: Some(0.9),
                 fallback: None,
                 validation: None,
+                regex: None,
             },
             reasoning: "Table data detected - use table extraction strategy".to_string(),
             examples: vec!["table".to_string()],
@@ -1598,6 +2837,7 @@ pub async fn extractor_analyze_page(
                 confidence: Some(0.85),
                 fallback: None,
                 validation: None,
+                regex: None,
             },
             reasoning: format!(
                 "Found {} repeating elements with same class",
@@ -1647,3 +2887,179 @@ pub async fn extractor_export(
 ) -> Result<(), String> {
     export_data(&data, &config, &file_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selector(selector_type: SelectorType, value: &str, strategy: SelectorStrategy) -> Selector {
+        Selector {
+            id: "s1".to_string(),
+            selector_type,
+            value: value.to_string(),
+            strategy,
+            label: "test".to_string(),
+            description: None,
+            confidence: None,
+            fallback: None,
+            validation: None,
+            regex: None,
+        }
+    }
+
+    const PRODUCT_PAGE: &str = r#"
+        <html><body>
+            <dl class="specs">
+                <dt>Brand</dt><dd>Acme</dd>
+                <dt> Price </dt><dd>$1,299.50</dd>
+                <dt>Weight</dt><dd>2 kg</dd>
+            </dl>
+            <p class="summary">Now only <b>$1,299.50</b> (was $1,499.00) with free shipping.</p>
+        </body></html>
+    "#;
+
+    #[test]
+    fn test_xpath_following_sibling_after_label() {
+        let price = selector(
+            SelectorType::Xpath,
+            "//dt[normalize-space()='Price']/following-sibling::dd[1]",
+            SelectorStrategy::Single,
+        );
+        assert_eq!(extract_from_html(PRODUCT_PAGE, &price).unwrap(), serde_json::json!("$1,299.50"));
+
+        let labels = selector(SelectorType::Xpath, "//dl[@class='specs']/dt/text()", SelectorStrategy::Multiple);
+        assert_eq!(
+            extract_from_html(PRODUCT_PAGE, &labels).unwrap(),
+            serde_json::json!(["Brand", " Price ", "Weight"])
+        );
+
+        let last = selector(SelectorType::Xpath, "//dd[last()]", SelectorStrategy::Single);
+        assert_eq!(extract_from_html(PRODUCT_PAGE, &last).unwrap(), serde_json::json!("2 kg"));
+    }
+
+    #[test]
+    fn test_regex_captures_price_from_text() {
+        let mut price = selector(SelectorType::Regex, r"only \$([\d,]+\.\d{2})", SelectorStrategy::Single);
+        price.regex = Some(RegexSelectorOptions {
+            source: RegexSource::Text,
+            group: None,
+            scope: Some("p.summary".to_string()),
+        });
+
+        let value = extract_from_html(PRODUCT_PAGE, &price).unwrap();
+        assert_eq!(value, serde_json::json!("1,299.50"));
+
+        let transforms = vec![DataTransform { transform_type: TransformType::ParseNumber, params: None }];
+        assert_eq!(apply_transforms(value, &transforms).unwrap(), serde_json::json!(1299.50));
+    }
+
+    #[test]
+    fn test_validate_schema_reports_bad_expressions() {
+        let field = |name: &str, selector: Selector| ExtractionField {
+            id: name.to_string(),
+            name: name.to_string(),
+            selector,
+            transform: None,
+            validation: None,
+            children: None,
+        };
+        let mut schema = ExtractionSchema {
+            id: "schema".to_string(),
+            name: "Products".to_string(),
+            description: None,
+            url: "https://example.com".to_string(),
+            fields: vec![
+                field("price", selector(SelectorType::Regex, r"\$(\d+", SelectorStrategy::Single)),
+                field("spec", selector(SelectorType::Xpath, "//dt[", SelectorStrategy::Single)),
+                field("title", selector(SelectorType::Css, "h1.title", SelectorStrategy::Single)),
+            ],
+            pagination: None,
+            schedule: None,
+            created: String::new(),
+            modified: String::new(),
+            version: 1,
+        };
+
+        let err = validate_schema(&schema).unwrap_err();
+        assert!(err.contains("Field 'price' selector: invalid regex"));
+        assert!(err.contains("Field 'spec' selector: invalid XPath"));
+        assert!(!err.contains("'title'"));
+
+        schema.fields.drain(..2);
+        assert!(validate_schema(&schema).is_ok());
+    }
+
+    const LISTING_PAGE: &str = r#"
+        <html><body>
+            <h1>Garden tools</h1>
+            <div class="product">
+                <h2 class="product-title">Steel spade</h2>
+                <span class="price-now">$24.99</span>
+                <span class="stars" aria-label="rating">4.5</span>
+            </div>
+            <div class="product">
+                <h2 class="product-title">Hand rake</h2>
+                <span class="price-now">$12.50</span>
+                <span class="stars" aria-label="rating">4.1</span>
+            </div>
+        </body></html>
+    "#;
+
+    struct MockAi(String);
+
+    #[async_trait::async_trait]
+    impl SchemaAi for MockAi {
+        async fn complete(&self, _system: &str, prompt: &str) -> Result<String, String> {
+            assert!(prompt.contains("product-title"));
+            Ok(self.0.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_schema_from_prompt_validates_ai_selectors() {
+        let ai = MockAi(
+            serde_json::json!({
+                "name": "Garden tools",
+                "fields": [
+                    { "name": "title", "selector": "h2.product-title", "selectorType": "css", "dataType": "string" },
+                    { "name": "price", "selector": "//span[@class='price-now']", "selectorType": "xpath", "dataType": "number" },
+                    { "name": "rating", "selector": "div.reviews .score", "selectorType": "css", "dataType": "number" }
+                ]
+            })
+            .to_string(),
+        );
+        let generated = generate_schema_from_html(
+            "https://shop.example/tools",
+            LISTING_PAGE,
+            "product title, price and rating",
+            Some(&ai),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(generated.generator, "ai");
+        let names: Vec<&str> = generated.schema.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["title", "price", "rating"]);
+
+        // The made-up rating selector was replaced by one that exists on the page
+        let rating = &generated.schema.fields[2];
+        assert_eq!(rating.selector.value, "span.stars");
+        assert!(generated.warnings.iter().any(|w| w.contains("'rating'")));
+
+        let document = scraper::Html::parse_document(LISTING_PAGE);
+        for field in &generated.schema.fields {
+            assert!(matches!(field.selector.strategy, SelectorStrategy::Multiple));
+            let values = resolve_on_page(&document, LISTING_PAGE, &field.selector).unwrap();
+            assert_eq!(values.len(), 2, "{} should match both products", field.name);
+        }
+        assert_eq!(generated.preview["title"], serde_json::json!(["Steel spade", "Hand rake"]));
+        assert_eq!(generated.preview["price"], serde_json::json!(["$24.99", "$12.50"]));
+
+        // No AI: the description alone drives the heuristic
+        let heuristic = generate_schema_from_html("https://shop.example/tools", LISTING_PAGE, "price", None)
+            .await
+            .unwrap();
+        assert_eq!(heuristic.generator, "heuristic");
+        assert_eq!(heuristic.schema.fields[0].selector.value, "span.price-now");
+    }
+}
@@ -5,6 +5,7 @@
  * These commands handle all gamification backend operations.
  */
 
+use crate::services::gamification_leaderboard::{AccountFlag, Leaderboard, LeaderboardPage, LeaderboardPeriod};
 use serde::{Deserialize, Serialize};
 use tauri::State;
 use std::collections::HashMap;
@@ -122,7 +123,7 @@ pub struct GamificationState {
     pub challenges: Mutex<Vec<Challenge>>,
     pub user_stats: Mutex<GamificationStats>,
     pub xp_history: Mutex<Vec<XPGain>>,
-    pub leaderboard: Mutex<Vec<LeaderboardEntry>>,
+    pub leaderboard: Mutex<Leaderboard>,
     pub rewards: Mutex<Vec<Reward>>,
 }
 
@@ -154,7 +155,7 @@ impl Default for GamificationState {
                 total_points: 0,
             }),
             xp_history: Mutex::new(Vec::new()),
-            leaderboard: Mutex::new(Vec::new()),
+            leaderboard: Mutex::new(Leaderboard::new()),
             rewards: Mutex::new(generate_rewards()),
         }
     }
@@ -187,6 +188,8 @@ pub async fn gamification_add_xp(
     state: State<'_, GamificationState>,
     amount: u32,
     source: String,
+    user_id: Option<String>,
+    username: Option<String>,
 ) -> Result<XPGain, String> {
     let mut stats = state.user_stats.lock()
         .map_err(|e| format!("Failed to lock stats: {}", e))?;
@@ -194,6 +197,24 @@ pub async fn gamification_add_xp(
     // Apply streak multiplier
     let multiplied_amount = (amount as f64 * stats.daily_streak.streak_multiplier) as u32;
     
+    // Audit the award before it counts towards anything
+    let user_id = user_id.unwrap_or_else(|| LOCAL_USER_ID.to_string());
+    let now = Utc::now();
+    {
+        let mut leaderboard = state.leaderboard.lock()
+            .map_err(|e| format!("Failed to lock leaderboard: {}", e))?;
+        leaderboard.award(
+            &user_id,
+            username.as_deref().unwrap_or(&user_id),
+            multiplied_amount,
+            &source,
+            now,
+        )?;
+        stats.leaderboard_rank = leaderboard
+            .rank_of(LeaderboardPeriod::AllTime, &user_id, now)
+            .unwrap_or(0);
+    }
+    
     stats.user_level.current_xp += multiplied_amount;
     stats.user_level.total_xp += multiplied_amount;
     
@@ -213,7 +234,7 @@ pub async fn gamification_add_xp(
     let gain = XPGain {
         amount: multiplied_amount,
         source,
-        timestamp: Utc::now().timestamp(),
+        timestamp: now.timestamp(),
         level_up,
         new_level,
     };
@@ -382,13 +403,65 @@ pub async fn gamification_update_challenge_progress(
 #[tauri::command]
 pub async fn gamification_get_leaderboard(
     state: State<'_, GamificationState>,
+    period: Option<String>,
+    user_id: Option<String>,
+    page: Option<u32>,
     limit: Option<u32>,
 ) -> Result<Vec<LeaderboardEntry>, String> {
-    let leaderboard = state.leaderboard.lock()
+    let page = gamification_get_leaderboard_page(state, period, user_id, page, limit).await?;
+    
+    Ok(page.entries.into_iter()
+        .map(|standing| LeaderboardEntry {
+            user_id: standing.user_id,
+            username: standing.username,
+            avatar: None,
+            score: standing.xp.min(u32::MAX as u64) as u32,
+            rank: standing.rank,
+            level: level_for_total_xp(standing.total_xp),
+        })
+        .collect())
+}
+
+/// Leaderboard page with totals; defaults to the page holding `user_id`
+#[tauri::command]
+pub async fn gamification_get_leaderboard_page(
+    state: State<'_, GamificationState>,
+    period: Option<String>,
+    user_id: Option<String>,
+    page: Option<u32>,
+    limit: Option<u32>,
+) -> Result<LeaderboardPage, String> {
+    let mut leaderboard = state.leaderboard.lock()
         .map_err(|e| format!("Failed to lock leaderboard: {}", e))?;
     
-    let limit = limit.unwrap_or(100) as usize;
-    Ok(leaderboard.iter().take(limit).cloned().collect())
+    let period = period.as_deref().map(LeaderboardPeriod::parse).unwrap_or(LeaderboardPeriod::AllTime);
+    Ok(leaderboard.page(
+        period,
+        user_id.as_deref(),
+        page.map(|p| p as usize),
+        limit.unwrap_or(100) as usize,
+        Utc::now(),
+    ))
+}
+
+#[tauri::command]
+pub async fn gamification_get_flagged_accounts(
+    state: State<'_, GamificationState>,
+) -> Result<Vec<AccountFlag>, String> {
+    let leaderboard = state.leaderboard.lock()
+        .map_err(|e| format!("Failed to lock leaderboard: {}", e))?;
+    Ok(leaderboard.flagged_accounts())
+}
+
+/// Put a reviewed account back on the leaderboard
+#[tauri::command]
+pub async fn gamification_clear_flag(
+    state: State<'_, GamificationState>,
+    user_id: String,
+) -> Result<bool, String> {
+    let mut leaderboard = state.leaderboard.lock()
+        .map_err(|e| format!("Failed to lock leaderboard: {}", e))?;
+    Ok(leaderboard.clear_flag(&user_id, Utc::now()))
 }
 
 #[tauri::command]
@@ -574,11 +647,28 @@ pub async fn gamification_unfollow_user(
 // HELPER FUNCTIONS
 // ============================================================================
 
+/// Leaderboard id for XP awarded without an explicit user
+const LOCAL_USER_ID: &str = "local";
+
 fn calculate_xp_for_level(level: u32) -> u32 {
     // Exponential curve: 100 * 1.5^(level-1)
     (100.0 * 1.5_f64.powi((level - 1) as i32)) as u32
 }
 
+/// Level reached with `total_xp`, following the curve used by `gamification_add_xp`
+fn level_for_total_xp(total_xp: u64) -> u32 {
+    let mut level = 1;
+    let mut remaining = total_xp;
+    loop {
+        let needed = if level == 1 { 100 } else { calculate_xp_for_level(level + 1) as u64 };
+        if remaining < needed || level >= 200 {
+            return level;
+        }
+        remaining -= needed;
+        level += 1;
+    }
+}
+
 fn calculate_streak_multiplier(streak: u32) -> f64 {
     match streak {
         0..=6 => 1.0 + (streak as f64 * 0.05),  // 1.0 - 1.3x
@@ -743,6 +833,9 @@ pub fn register_gamification_commands(builder: tauri::Builder<tauri::Wry>) -> ta
             gamification_get_challenges,
             gamification_update_challenge_progress,
             gamification_get_leaderboard,
+            gamification_get_leaderboard_page,
+            gamification_get_flagged_accounts,
+            gamification_clear_flag,
             gamification_get_rewards,
             gamification_claim_reward,
             gamification_get_badges,
@@ -2,15 +2,21 @@
 // 🔌 INTEGRATION COMMANDS - WHATSAPP, MONDAY.COM, PLANIUS, FILES, PROFILES
 // ═══════════════════════════════════════════════════════════════════════════════
 
-use log::info;
+use chrono::Utc;
+use log::{error, info};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tauri::State;
-use tokio::sync::RwLock;
+use tokio::sync::{oneshot, Notify, RwLock};
 
 use crate::services::document_extractor::DocumentExtractor;
+use crate::services::integration_queue::{
+    Integration, OutboundPayload, OutboundQueue, QueueStatus, RateLimitPolicy, SendOutcome,
+    WhatsAppTier,
+};
 use crate::services::profile_auto_creator::{AutofillProfile, DocumentType, ProfileAutoCreator};
 use crate::services::project_management_service::{
     AIProjectAnalysis, Board, ColumnValue, Item, MondayConfig, PlaniusConfig,
@@ -31,20 +37,217 @@ pub struct IntegrationState {
     pub file_detector: Arc<RwLock<UniversalFileDetector>>,
     pub profile_creator: Arc<RwLock<ProfileAutoCreator>>,
     pub document_extractor: Arc<RwLock<DocumentExtractor>>,
+    pub queues: Arc<OutboundQueues>,
 }
 
 impl IntegrationState {
-    pub fn new() -> anyhow::Result<Self> {
+    /// `queue_dir` holds the persisted outbound queues
+    pub fn new(queue_dir: PathBuf) -> anyhow::Result<Self> {
         Ok(Self {
             whatsapp: Arc::new(RwLock::new(None)),
             project_mgmt: Arc::new(RwLock::new(None)),
             file_detector: Arc::new(RwLock::new(UniversalFileDetector::new()?)),
             profile_creator: Arc::new(RwLock::new(ProfileAutoCreator::new())),
             document_extractor: Arc::new(RwLock::new(DocumentExtractor::new())),
+            queues: Arc::new(OutboundQueues::open(queue_dir)),
         })
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+// OUTBOUND QUEUES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// How long a send command waits for delivery before reporting it as queued
+const QUEUE_WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Re-check interval while a persisted queue waits for its service to connect
+const DISCONNECTED_POLL: Duration = Duration::from_secs(5);
+
+type DeliveryResult = Result<Value, String>;
+
+pub struct OutboundQueues {
+    monday: Mutex<OutboundQueue>,
+    whatsapp: Mutex<OutboundQueue>,
+    monday_wake: Notify,
+    whatsapp_wake: Notify,
+    waiters: Mutex<HashMap<String, oneshot::Sender<DeliveryResult>>>,
+}
+
+pub enum Submission {
+    Delivered(Value),
+    Queued(String),
+}
+
+impl OutboundQueues {
+    fn open(queue_dir: PathBuf) -> Self {
+        Self {
+            monday: Mutex::new(OutboundQueue::open(
+                Integration::Monday,
+                RateLimitPolicy::monday(),
+                Some(queue_dir.join("monday.json")),
+            )),
+            whatsapp: Mutex::new(OutboundQueue::open(
+                Integration::WhatsApp,
+                RateLimitPolicy::whatsapp(WhatsAppTier::default()),
+                Some(queue_dir.join("whatsapp.json")),
+            )),
+            monday_wake: Notify::new(),
+            whatsapp_wake: Notify::new(),
+            waiters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn queue(&self, integration: Integration) -> &Mutex<OutboundQueue> {
+        match integration {
+            Integration::Monday => &self.monday,
+            Integration::WhatsApp => &self.whatsapp,
+        }
+    }
+
+    fn wake(&self, integration: Integration) -> &Notify {
+        match integration {
+            Integration::Monday => &self.monday_wake,
+            Integration::WhatsApp => &self.whatsapp_wake,
+        }
+    }
+
+    pub fn status(&self, integration: Integration) -> QueueStatus {
+        let queue = self.queue(integration).lock().unwrap();
+        queue.status(Utc::now().timestamp_millis())
+    }
+
+    /// Queue a payload and wait a bounded time for the worker to deliver it
+    pub async fn submit(&self, payload: OutboundPayload) -> Result<Submission, String> {
+        let integration = payload.integration();
+        let (tx, rx) = oneshot::channel();
+        let id = {
+            let mut queue = self.queue(integration).lock().unwrap();
+            let id = queue.enqueue(payload, Utc::now().timestamp_millis())?;
+            self.waiters.lock().unwrap().insert(id.clone(), tx);
+            id
+        };
+        self.wake(integration).notify_one();
+
+        match tokio::time::timeout(QUEUE_WAIT_TIMEOUT, rx).await {
+            Ok(Ok(result)) => result.map(Submission::Delivered),
+            Ok(Err(_)) => Err("Outbound queue worker stopped".to_string()),
+            Err(_) => {
+                self.waiters.lock().unwrap().remove(&id);
+                Ok(Submission::Queued(id))
+            }
+        }
+    }
+
+    fn resolve(&self, id: &str, result: DeliveryResult) {
+        if let Some(tx) = self.waiters.lock().unwrap().remove(id) {
+            let _ = tx.send(result);
+        }
+    }
+}
+
+/// Start one delivery worker per integration; each drains its queue at
+/// the pace the rate limit policy allows
+pub fn spawn_queue_workers(state: &IntegrationState) {
+    for integration in [Integration::Monday, Integration::WhatsApp] {
+        let queues = state.queues.clone();
+        let whatsapp = state.whatsapp.clone();
+        let project_mgmt = state.project_mgmt.clone();
+
+        tauri::async_runtime::spawn(async move {
+            loop {
+                let now = Utc::now().timestamp_millis();
+                let next = {
+                    let queue = queues.queue(integration).lock().unwrap();
+                    queue.front().cloned().zip(queue.next_send_at(now))
+                };
+                let Some((message, send_at)) = next else {
+                    queues.wake(integration).notified().await;
+                    continue;
+                };
+                if send_at > now {
+                    let delay = Duration::from_millis((send_at - now) as u64);
+                    let _ = tokio::time::timeout(delay, queues.wake(integration).notified()).await;
+                    continue;
+                }
+
+                let Some((outcome, value)) =
+                    deliver(&message.payload, &whatsapp, &project_mgmt).await
+                else {
+                    let _ = tokio::time::timeout(
+                        DISCONNECTED_POLL,
+                        queues.wake(integration).notified(),
+                    )
+                    .await;
+                    continue;
+                };
+
+                let finished = {
+                    let mut queue = queues.queue(integration).lock().unwrap();
+                    queue.complete(&message.id, outcome, Utc::now().timestamp_millis())
+                };
+                match finished {
+                    Some(Ok(())) => queues.resolve(&message.id, Ok(value)),
+                    Some(Err(e)) => {
+                        error!(
+                            "❌ Dropped {} message {}: {}",
+                            integration.as_str(),
+                            message.id,
+                            e
+                        );
+                        queues.resolve(&message.id, Err(e));
+                    }
+                    None => {}
+                }
+            }
+        });
+    }
+}
+
+/// Send one payload; `None` when its service is not connected yet
+async fn deliver(
+    payload: &OutboundPayload,
+    whatsapp: &RwLock<Option<WhatsAppService>>,
+    project_mgmt: &RwLock<Option<ProjectManagementService>>,
+) -> Option<(SendOutcome, Value)> {
+    let result = match payload {
+        OutboundPayload::WhatsAppText { to, message } => {
+            let service_lock = whatsapp.read().await;
+            let service = service_lock.as_ref()?;
+            service
+                .send_text_message(to, message)
+                .await
+                .map(Value::String)
+        }
+        OutboundPayload::MondayCreateItem {
+            board_id,
+            group_id,
+            item_name,
+            column_values,
+        } => {
+            let service_lock = project_mgmt.read().await;
+            let service = service_lock.as_ref()?;
+            service
+                .create_item(board_id, group_id, item_name, column_values.clone())
+                .await
+                .map(|item| serde_json::to_value(item).unwrap_or(Value::Null))
+        }
+    };
+
+    Some(match result {
+        Ok(value) => (SendOutcome::Sent, value),
+        Err(e) => (SendOutcome::from_error(&e.to_string()), Value::Null),
+    })
+}
+
+#[tauri::command]
+pub async fn integration_get_queue_status(
+    integration: String,
+    state: State<'_, IntegrationState>,
+) -> Result<QueueStatus, String> {
+    let integration = Integration::parse(&integration)?;
+    Ok(state.queues.status(integration))
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 // WHATSAPP COMMANDS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -54,10 +257,18 @@ pub async fn whatsapp_connect(
     config: WhatsAppConfig,
     state: State<'_, IntegrationState>,
 ) -> Result<String, String> {
+    let tier = config.messaging_tier;
     let service = WhatsAppService::new(config)
         .map_err(|e| format!("Failed to create WhatsApp service: {}", e))?;
 
     *state.whatsapp.write().await = Some(service);
+    state
+        .queues
+        .queue(Integration::WhatsApp)
+        .lock()
+        .unwrap()
+        .set_policy(RateLimitPolicy::whatsapp(tier));
+    state.queues.wake(Integration::WhatsApp).notify_one();
 
     Ok("WhatsApp connected successfully".to_string())
 }
@@ -68,15 +279,19 @@ pub async fn whatsapp_send_text(
     message: String,
     state: State<'_, IntegrationState>,
 ) -> Result<String, String> {
-    let service_lock = state.whatsapp.read().await;
-    let service = service_lock.as_ref().ok_or("WhatsApp not connected")?;
+    if state.whatsapp.read().await.is_none() {
+        return Err("WhatsApp not connected".to_string());
+    }
 
-    service
-        .send_text_message(&to, &message)
+    match state
+        .queues
+        .submit(OutboundPayload::WhatsAppText { to, message })
         .await
-        .map_err(|e| format!("Failed to send message: {}", e))?;
-
-    Ok("Message sent".to_string())
+        .map_err(|e| format!("Failed to send message: {}", e))?
+    {
+        Submission::Delivered(_) => Ok("Message sent".to_string()),
+        Submission::Queued(id) => Ok(format!("Message queued ({}) behind the rate limit", id)),
+    }
 }
 
 #[tauri::command]
@@ -164,6 +379,7 @@ pub async fn monday_connect(
         .map_err(|e| format!("Failed to create Monday.com service: {}", e))?;
 
     *state.project_mgmt.write().await = Some(service);
+    state.queues.wake(Integration::Monday).notify_one();
 
     Ok("Monday.com connected successfully".to_string())
 }
@@ -187,13 +403,29 @@ pub async fn monday_create_item(
     column_values: Option<HashMap<String, ColumnValue>>,
     state: State<'_, IntegrationState>,
 ) -> Result<Item, String> {
-    let service_lock = state.project_mgmt.read().await;
-    let service = service_lock.as_ref().ok_or("Monday.com not connected")?;
+    if state.project_mgmt.read().await.is_none() {
+        return Err("Monday.com not connected".to_string());
+    }
 
-    service
-        .create_item(&board_id, &group_id, &item_name, column_values)
+    let payload = OutboundPayload::MondayCreateItem {
+        board_id,
+        group_id,
+        item_name,
+        column_values,
+    };
+    match state
+        .queues
+        .submit(payload)
         .await
-        .map_err(|e| format!("Failed to create item: {}", e))
+        .map_err(|e| format!("Failed to create item: {}", e))?
+    {
+        Submission::Delivered(item) => serde_json::from_value(item)
+            .map_err(|e| format!("Failed to read created item: {}", e)),
+        Submission::Queued(id) => Err(format!(
+            "Item queued ({}) behind the Monday.com complexity limit; check integration_get_queue_status",
+            id
+        )),
+    }
 }
 
 #[tauri::command]
@@ -207,6 +207,37 @@ pub struct PortfolioSummary {
     pub performance_30d: f64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioPerformance {
+    pub investor_id: String,
+    pub period: String,
+    pub irr: Option<f64>,
+    /// "newton", or "bisection" when Newton's method failed to converge
+    pub irr_method: Option<String>,
+    pub irr_fallback_used: bool,
+    pub time_weighted_return: f64,
+    pub total_contributions: f64,
+    pub total_payouts: f64,
+    pub current_value: f64,
+    pub investments: Vec<InvestmentPerformance>,
+    pub value_series: Vec<PortfolioValuePoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvestmentPerformance {
+    pub investment_id: String,
+    pub amount: f64,
+    pub payouts_received: f64,
+    pub current_value: f64,
+    pub roi_percentage: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioValuePoint {
+    pub date: String,
+    pub value: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InvestmentOpportunity {
     pub id: String,
@@ -378,6 +409,299 @@ fn profile_to_record(profile: &InvestorProfile) -> InvestorRecord {
     }
 }
 
+// ============================================================================
+// PERFORMANCE CALCULATIONS
+// ============================================================================
+
+const IRR_TOLERANCE: f64 = 1e-9;
+const IRR_MAX_ITERATIONS: usize = 100;
+const DAYS_PER_YEAR: f64 = 365.0;
+
+/// A dated cash flow from the investor's perspective:
+/// negative = money invested, positive = money received (or terminal value).
+#[derive(Debug, Clone, Copy)]
+struct CashFlow {
+    date: DateTime<Utc>,
+    amount: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct IrrResult {
+    rate: f64,
+    fallback_used: bool,
+}
+
+fn parse_flow_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|dt| dt.and_utc())
+        })
+}
+
+fn year_fraction(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    (to - from).num_seconds() as f64 / 86_400.0 / DAYS_PER_YEAR
+}
+
+fn npv(flows: &[CashFlow], rate: f64) -> f64 {
+    let start = flows[0].date;
+    flows.iter()
+        .map(|f| f.amount / (1.0 + rate).powf(year_fraction(start, f.date)))
+        .sum()
+}
+
+fn npv_derivative(flows: &[CashFlow], rate: f64) -> f64 {
+    let start = flows[0].date;
+    flows.iter()
+        .map(|f| {
+            let t = year_fraction(start, f.date);
+            -t * f.amount / (1.0 + rate).powf(t + 1.0)
+        })
+        .sum()
+}
+
+fn irr_newton(flows: &[CashFlow], guess: f64) -> Option<f64> {
+    let mut rate = guess;
+    for _ in 0..IRR_MAX_ITERATIONS {
+        let value = npv(flows, rate);
+        let derivative = npv_derivative(flows, rate);
+        if !derivative.is_finite() || derivative.abs() < f64::EPSILON {
+            return None;
+        }
+
+        let next = rate - value / derivative;
+        if !next.is_finite() || next <= -1.0 {
+            return None;
+        }
+        if (next - rate).abs() < IRR_TOLERANCE {
+            return Some(next);
+        }
+        rate = next;
+    }
+    None
+}
+
+fn irr_bisection(flows: &[CashFlow]) -> Option<f64> {
+    let mut low = -0.999_999_9;
+    let mut high = 1.0;
+    let low_value = npv(flows, low);
+
+    // Widen the upper bound until the NPV changes sign
+    while npv(flows, high).signum() == low_value.signum() {
+        high *= 2.0;
+        if high > 1e6 {
+            return None;
+        }
+    }
+
+    for _ in 0..500 {
+        let mid = (low + high) / 2.0;
+        let mid_value = npv(flows, mid);
+        if mid_value.abs() < IRR_TOLERANCE || (high - low) / 2.0 < IRR_TOLERANCE {
+            return Some(mid);
+        }
+        if mid_value.signum() == low_value.signum() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some((low + high) / 2.0)
+}
+
+/// Annualised IRR for irregularly dated cash flows (XIRR). Newton's method is
+/// tried first; if it diverges the result comes from bisection and is flagged.
+fn compute_irr(flows: &[CashFlow]) -> Option<IrrResult> {
+    let mut flows = flows.to_vec();
+    flows.sort_by_key(|f| f.date);
+
+    let has_outflow = flows.iter().any(|f| f.amount < 0.0);
+    let has_inflow = flows.iter().any(|f| f.amount > 0.0);
+    if !has_outflow || !has_inflow {
+        return None;
+    }
+
+    if let Some(rate) = irr_newton(&flows, 0.1) {
+        return Some(IrrResult { rate, fallback_used: false });
+    }
+    irr_bisection(&flows).map(|rate| IrrResult { rate, fallback_used: true })
+}
+
+/// Time-weighted return from (value just before the flow, external flow) points
+/// in date order. Flows are positive for contributions, negative for withdrawals.
+fn compute_twr(points: &[(f64, f64)]) -> f64 {
+    let mut growth = 1.0;
+    for window in points.windows(2) {
+        let (start_before, start_flow) = window[0];
+        let (end_before, _) = window[1];
+        let start_value = start_before + start_flow;
+        if start_value > 0.0 {
+            growth *= end_before / start_value;
+        }
+    }
+    growth - 1.0
+}
+
+/// Value of an investment at `at`: principal plus simple accrued interest,
+/// less interest already paid out.
+fn investment_value_at(
+    investment: &InvestmentRecord,
+    payouts: &[(DateTime<Utc>, f64)],
+    at: DateTime<Utc>,
+) -> f64 {
+    let Some(start) = parse_flow_date(&investment.start_date) else { return 0.0 };
+    if at < start {
+        return 0.0;
+    }
+    let end = parse_flow_date(&investment.maturity_date).map_or(at, |m| m.min(at));
+    let accrued = investment.amount * investment.interest_rate / 100.0 * year_fraction(start, end).max(0.0);
+    let paid: f64 = payouts.iter().filter(|(date, _)| *date <= at).map(|(_, amount)| amount).sum();
+    (investment.amount + accrued - paid).max(0.0)
+}
+
+fn period_start(period: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    match period {
+        "7d" => Some(now - Duration::days(7)),
+        "30d" => Some(now - Duration::days(30)),
+        "90d" => Some(now - Duration::days(90)),
+        "1y" => Some(now - Duration::days(365)),
+        _ => None,
+    }
+}
+
+fn build_portfolio_performance(
+    investor_id: &str,
+    period: &str,
+    investments: &[InvestmentRecord],
+    payouts: &[PayoutRecord],
+    now: DateTime<Utc>,
+) -> PortfolioPerformance {
+    let funded: Vec<&InvestmentRecord> = investments.iter()
+        .filter(|i| i.status != "pending" && i.status != "cancelled")
+        .filter(|i| parse_flow_date(&i.start_date).is_some_and(|d| d <= now))
+        .collect();
+
+    let paid_by_investment = |investment_id: &str| -> Vec<(DateTime<Utc>, f64)> {
+        payouts.iter()
+            .filter(|p| p.investment_id == investment_id && p.status == "paid")
+            .filter_map(|p| {
+                let date = p.paid_date.as_deref().unwrap_or(&p.scheduled_date);
+                parse_flow_date(date).filter(|d| *d <= now).map(|d| (d, p.amount))
+            })
+            .collect()
+    };
+
+    let mut flows = Vec::new();
+    let mut investment_performance = Vec::new();
+    let mut events: Vec<(DateTime<Utc>, f64)> = Vec::new();
+
+    for investment in &funded {
+        let paid = paid_by_investment(&investment.id);
+        let start = parse_flow_date(&investment.start_date).unwrap_or(now);
+        let current_value = investment_value_at(investment, &paid, now);
+        let payouts_received: f64 = paid.iter().map(|(_, amount)| amount).sum();
+
+        flows.push(CashFlow { date: start, amount: -investment.amount });
+        events.push((start, investment.amount));
+        for (date, amount) in &paid {
+            flows.push(CashFlow { date: *date, amount: *amount });
+            events.push((*date, -amount));
+        }
+
+        let roi = if investment.amount > 0.0 {
+            (payouts_received + current_value - investment.amount) / investment.amount * 100.0
+        } else {
+            0.0
+        };
+        investment_performance.push(InvestmentPerformance {
+            investment_id: investment.id.clone(),
+            amount: investment.amount,
+            payouts_received,
+            current_value,
+            roi_percentage: roi,
+        });
+    }
+
+    let portfolio_value = |at: DateTime<Utc>| -> f64 {
+        funded.iter()
+            .map(|i| investment_value_at(i, &paid_by_investment(&i.id), at))
+            .sum()
+    };
+    let current_value = portfolio_value(now);
+    if current_value > 0.0 {
+        flows.push(CashFlow { date: now, amount: current_value });
+    }
+    let irr = compute_irr(&flows);
+
+    // TWR over the requested period: sub-periods split at each external flow
+    let window_start = period_start(period, now)
+        .or_else(|| events.iter().map(|(d, _)| *d).min())
+        .unwrap_or(now);
+    let value_before = |at: DateTime<Utc>| portfolio_value(at - Duration::seconds(1));
+    let flow_on = |at: DateTime<Utc>| -> f64 {
+        events.iter().filter(|(d, _)| *d == at).map(|(_, flow)| flow).sum()
+    };
+    let mut flow_dates: Vec<DateTime<Utc>> = events.iter()
+        .map(|(d, _)| *d)
+        .filter(|d| *d > window_start && *d <= now)
+        .collect();
+    flow_dates.sort();
+    flow_dates.dedup();
+
+    let mut twr_points = vec![(value_before(window_start), flow_on(window_start))];
+    twr_points.extend(flow_dates.into_iter().map(|d| (value_before(d), flow_on(d))));
+    twr_points.push((current_value, 0.0));
+
+    let step = match period {
+        "7d" | "30d" => Duration::days(1),
+        "90d" => Duration::days(7),
+        _ => Duration::days(30),
+    };
+    let mut value_series = Vec::new();
+    let mut cursor = window_start;
+    while cursor < now {
+        value_series.push(PortfolioValuePoint {
+            date: cursor.format("%Y-%m-%d").to_string(),
+            value: portfolio_value(cursor),
+        });
+        cursor += step;
+    }
+    value_series.push(PortfolioValuePoint {
+        date: now.format("%Y-%m-%d").to_string(),
+        value: current_value,
+    });
+
+    PortfolioPerformance {
+        investor_id: investor_id.to_string(),
+        period: period.to_string(),
+        irr: irr.map(|r| r.rate),
+        irr_method: irr.map(|r| if r.fallback_used { "bisection" } else { "newton" }.to_string()),
+        irr_fallback_used: irr.is_some_and(|r| r.fallback_used),
+        time_weighted_return: compute_twr(&twr_points),
+        total_contributions: funded.iter().map(|i| i.amount).sum(),
+        total_payouts: investment_performance.iter().map(|i| i.payouts_received).sum(),
+        current_value,
+        investments: investment_performance,
+        value_series,
+    }
+}
+
+fn load_portfolio_performance(
+    state: &AppState,
+    investor_id: &str,
+    period: &str,
+) -> Result<PortfolioPerformance, String> {
+    let investments = state.database.get_investor_investments(investor_id)
+        .map_err(|e| format!("Failed to load investments: {}", e))?;
+    let payouts = state.database.get_investor_payouts(investor_id)
+        .map_err(|e| format!("Failed to load payouts: {}", e))?;
+    Ok(build_portfolio_performance(investor_id, period, &investments, &payouts, Utc::now()))
+}
+
 // ============================================================================
 // INVESTOR MANAGEMENT COMMANDS
 // ============================================================================
@@ -579,8 +903,39 @@ pub async fn verify_investor_kyc(
 
 /// Get portfolio summary for investor
 #[command]
-pub async fn get_portfolio_summary(_investor_id: String) -> Result<PortfolioSummary, String> {
-    // Note: Calculate from database
+pub async fn get_portfolio_summary(
+    state: State<'_, AppState>,
+    investor_id: String,
+) -> Result<PortfolioSummary, String> {
+    let performance = load_portfolio_performance(&state, &investor_id, "30d")?;
+    if !performance.investments.is_empty() {
+        let payouts = state.database.get_investor_payouts(&investor_id)
+            .map_err(|e| format!("Failed to load payouts: {}", e))?;
+        let scheduled: Vec<&PayoutRecord> = payouts.iter().filter(|p| p.status == "scheduled").collect();
+        let total_returns = performance.total_payouts + performance.current_value - performance.total_contributions;
+        let tokens = state.database.get_investor(&investor_id).ok().flatten()
+            .map(|i| i.cube_tokens)
+            .unwrap_or(0.0);
+
+        return Ok(PortfolioSummary {
+            total_invested: performance.total_contributions,
+            current_value: performance.current_value,
+            total_returns,
+            roi_percentage: if performance.total_contributions > 0.0 {
+                total_returns / performance.total_contributions * 100.0
+            } else {
+                0.0
+            },
+            active_investments: performance.investments.len() as i32,
+            pending_payouts: scheduled.iter().map(|p| p.amount).sum(),
+            cube_tokens: tokens,
+            token_value: tokens * 2.5,
+            next_payout_date: scheduled.first().map(|p| p.scheduled_date.clone()),
+            performance_30d: performance.time_weighted_return * 100.0,
+        });
+    }
+    
+    // Fallback to mock data for demo purposes
     let summary = PortfolioSummary {
         total_invested: 250000.0,
         current_value: 292500.0,
@@ -600,11 +955,19 @@ pub async fn get_portfolio_summary(_investor_id: String) -> Result<PortfolioSumm
 /// Get detailed investment analytics
 #[command]
 pub async fn get_investment_analytics(
-    _investor_id: String,
-    _period: String,
+    state: State<'_, AppState>,
+    investor_id: String,
+    period: String,
 ) -> Result<HashMap<String, serde_json::Value>, String> {
     let mut analytics = HashMap::new();
     
+    let performance = load_portfolio_performance(&state, &investor_id, &period)?;
+    if !performance.investments.is_empty() {
+        analytics.insert("performance".to_string(), serde_json::to_value(&performance)
+            .map_err(|e| format!("Failed to serialize performance: {}", e))?);
+        return Ok(analytics);
+    }
+    
     // ROI over time
     analytics.insert("roi_history".to_string(), serde_json::json!([
         {"date": "2025-01", "roi": 2.1},
@@ -637,6 +1000,16 @@ pub async fn get_investment_analytics(
     Ok(analytics)
 }
 
+/// Get IRR, time-weighted return, per-investment ROI and a value time series
+#[command]
+pub async fn get_portfolio_performance(
+    state: State<'_, AppState>,
+    investor_id: String,
+    period: String,
+) -> Result<PortfolioPerformance, String> {
+    load_portfolio_performance(&state, &investor_id, &period)
+}
+
 // ============================================================================
 // INVESTMENT COMMANDS
 // ============================================================================
@@ -1553,6 +1926,7 @@ pub fn get_investor_commands() -> Vec<&'static str> {
         // Portfolio
         "get_portfolio_summary",
         "get_investment_analytics",
+        "get_portfolio_performance",
         // Investments
         "create_investment",
         "get_investment",
@@ -1588,3 +1962,98 @@ pub fn get_investor_commands() -> Vec<&'static str> {
         "get_investor_documents",
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(date: &str, amount: f64) -> CashFlow {
+        CashFlow { date: parse_flow_date(date).unwrap(), amount }
+    }
+
+    #[test]
+    fn test_irr_single_period() {
+        let flows = [flow("2024-01-01", -1000.0), flow("2024-12-31", 1100.0)];
+        let result = compute_irr(&flows).unwrap();
+        assert!((result.rate - 0.10).abs() < 1e-6);
+        assert!(!result.fallback_used);
+    }
+
+    #[test]
+    fn test_irr_irregular_dates() {
+        // Reference series from the spreadsheet XIRR documentation
+        let flows = [
+            flow("2008-01-01", -10000.0),
+            flow("2008-03-01", 2750.0),
+            flow("2008-10-30", 4250.0),
+            flow("2009-02-15", 3250.0),
+            flow("2009-04-01", 2750.0),
+        ];
+        let result = compute_irr(&flows).unwrap();
+        assert!((result.rate - 0.373362535).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_irr_bisection_fallback() {
+        // Newton's first step from 10% overshoots below -100%
+        let flows = [flow("2024-01-01", -1000.0), flow("2024-12-31", 1.0)];
+        let result = compute_irr(&flows).unwrap();
+        assert!(result.fallback_used);
+        assert!((result.rate - (-0.999)).abs() < 1e-6);
+
+        assert!(compute_irr(&[flow("2024-01-01", -1000.0)]).is_none());
+    }
+
+    #[test]
+    fn test_time_weighted_return() {
+        // +10% on 100, then 50 added, then +10% on 160
+        let points = [(0.0, 100.0), (110.0, 50.0), (176.0, 0.0)];
+        assert!((compute_twr(&points) - 0.21).abs() < 1e-9);
+
+        // Withdrawals don't distort the return
+        let points = [(0.0, 100.0), (120.0, -60.0), (66.0, 0.0)];
+        assert!((compute_twr(&points) - 0.32).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_portfolio_performance_roi() {
+        let investment = InvestmentRecord {
+            id: "inv_1".to_string(),
+            investor_id: "investor".to_string(),
+            tier: "seed".to_string(),
+            amount: 1000.0,
+            equity_percentage: 0.1,
+            interest_rate: 10.0,
+            term_months: 24,
+            status: "active".to_string(),
+            contract_id: None,
+            start_date: "2024-01-01T00:00:00Z".to_string(),
+            maturity_date: "2026-01-01T00:00:00Z".to_string(),
+            returns_to_date: 0.0,
+            next_payout_date: None,
+            created_at: None,
+            updated_at: None,
+        };
+        let payout = PayoutRecord {
+            id: "p1".to_string(),
+            investment_id: "inv_1".to_string(),
+            investor_id: "investor".to_string(),
+            amount: 50.0,
+            payout_type: "interest".to_string(),
+            scheduled_date: "2024-07-01".to_string(),
+            status: "paid".to_string(),
+            paid_date: Some("2024-07-01".to_string()),
+            transaction_id: None,
+            created_at: None,
+        };
+        let now = parse_flow_date("2024-12-31T00:00:00Z").unwrap();
+
+        let performance = build_portfolio_performance("investor", "all", &[investment], &[payout], now);
+        let roi = &performance.investments[0];
+        assert!((roi.payouts_received - 50.0).abs() < 1e-9);
+        assert!((roi.current_value - 1050.0).abs() < 1e-9);
+        assert!((roi.roi_percentage - 10.0).abs() < 1e-9);
+        assert!((performance.irr.unwrap() - 0.10).abs() < 0.005);
+        assert!(!performance.value_series.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::models::media::{MediaItem, Playlist, MediaStats, MediaFilter};
+use crate::models::media::{MediaItem, Playlist, MediaStats, MediaFilter, PlaybackSettings, TrackTransition};
 use crate::services::media_service::MediaService;
 use tauri::State;
 
@@ -97,3 +97,35 @@ pub async fn get_media_stats(
 ) -> Result<MediaStats, String> {
     media_service.get_stats()
 }
+
+#[tauri::command]
+pub async fn media_get_playback_settings(
+    media_service: State<'_, MediaService>,
+) -> Result<PlaybackSettings, String> {
+    media_service.get_playback_settings()
+}
+
+#[tauri::command]
+pub async fn media_set_crossfade(
+    seconds: f64,
+    media_service: State<'_, MediaService>,
+) -> Result<PlaybackSettings, String> {
+    media_service.set_crossfade(seconds)
+}
+
+#[tauri::command]
+pub async fn media_set_gapless(
+    enabled: bool,
+    media_service: State<'_, MediaService>,
+) -> Result<PlaybackSettings, String> {
+    media_service.set_gapless(enabled)
+}
+
+#[tauri::command]
+pub async fn media_plan_transition(
+    from_media_id: String,
+    to_media_id: String,
+    media_service: State<'_, MediaService>,
+) -> Result<TrackTransition, String> {
+    media_service.plan_transition(&from_media_id, &to_media_id)
+}
@@ -14,7 +14,7 @@ use std::sync::Arc;
 use std::path::PathBuf;
 
 use crate::services::metrics::{MetricsService, ExecutionMetrics, WorkflowStats, SystemStats};
-use crate::services::logs::{LogsService, LogEntry, LogFilter, LogLevel, LogStats};
+use crate::services::logs::{LogsService, LogEntry, LogFilter, LogLevel, LogStats, LogMetric, LogAggregateBucket};
 use crate::services::alerts::{AlertsService, AlertRule, AlertEvent};
 
 pub struct MonitoringState {
@@ -71,6 +71,8 @@ pub async fn logs_add(
     workflow_id: Option<String>,
     execution_id: Option<String>,
     node_id: Option<String>,
+    source: Option<String>,
+    fields: Option<std::collections::HashMap<String, String>>,
     state: State<'_, MonitoringState>,
 ) -> Result<String, String> {
     let log_level = match level.to_lowercase().as_str() {
@@ -87,7 +89,8 @@ pub async fn logs_add(
         workflow_id,
         execution_id,
         node_id,
-        std::collections::HashMap::new(),
+        source,
+        fields.unwrap_or_default(),
     )
 }
 
@@ -99,6 +102,24 @@ pub async fn logs_get(
     state.logs.get_logs(filter)
 }
 
+#[tauri::command]
+pub async fn logs_query(
+    filter: LogFilter,
+    state: State<'_, MonitoringState>,
+) -> Result<Vec<LogEntry>, String> {
+    state.logs.get_logs(filter)
+}
+
+#[tauri::command]
+pub async fn logs_aggregate(
+    group_by: String,
+    metric: LogMetric,
+    filter: Option<LogFilter>,
+    state: State<'_, MonitoringState>,
+) -> Result<Vec<LogAggregateBucket>, String> {
+    state.logs.aggregate(&group_by, metric, filter.unwrap_or_default())
+}
+
 #[tauri::command]
 pub async fn logs_get_recent(
     count: usize,
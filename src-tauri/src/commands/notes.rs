@@ -61,3 +61,21 @@ pub async fn get_notes_stats(state: State<'_, NotesState>) -> Result<NotesStats,
     let service = state.service.lock().map_err(|e| e.to_string())?;
     service.get_stats().map_err(|e| e.to_string())
 }
+
+#[tauri::command]
+pub async fn notes_render_markdown(note_id: String, state: State<'_, NotesState>) -> Result<RenderedNote, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    service
+        .render_markdown(&note_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Note {} not found", note_id))
+}
+
+#[tauri::command]
+pub async fn notes_toggle_task(note_id: String, task_index: usize, state: State<'_, NotesState>) -> Result<RenderedNote, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    service
+        .toggle_task_checkbox(&note_id, task_index)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Task {} not found in note {}", task_index, note_id))
+}
@@ -7,9 +7,26 @@
 // Suppress unused variable warnings for stub implementations
 #![allow(unused_variables)]
 
+use crate::services::sms_delivery::{
+    InboundSmsAction, SegmentInfo, SmsDeliveryRecord, SmsDeliveryState, SmsEncoding, SmsGateway,
+    SmsProviderConfig,
+};
+use crate::services::browser_sync::{SyncDataType, SyncService};
+use crate::services::notifications_service::{NotificationCenter, NotificationsService, ReadStateChange};
+use crate::services::template_engine::{self, EscapeMode};
 use serde::{Deserialize, Serialize};
-use tauri::command;
+use tauri::{command, State};
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+// ============================================================================
+// Template Store
+// ============================================================================
+
+#[derive(Default)]
+pub struct NotificationTemplateState {
+    pub templates: RwLock<HashMap<String, NotificationTemplate>>,
+}
 
 // ============================================================================
 // Notification Types
@@ -391,11 +408,49 @@ pub struct BulkSendError {
 
 #[command]
 pub async fn notification_send_from_template(
-    _template_id: String,
-    _user_id: String,
-    _variables: HashMap<String, serde_json::Value>,
+    state: State<'_, NotificationTemplateState>,
+    template_id: String,
+    user_id: String,
+    variables: HashMap<String, serde_json::Value>,
 ) -> Result<Notification, String> {
-    Err("Template not found".to_string())
+    let template = get_stored_template(&state, &template_id)?;
+
+    let missing: Vec<&str> = template.variables.iter()
+        .filter(|v| v.required && v.default_value.is_none() && !variables.contains_key(&v.name))
+        .map(|v| v.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing required variables: {}", missing.join(", ")));
+    }
+
+    let data = template_data(&template, &variables, false);
+    let (key, channel) = template.channels.iter()
+        .find(|(key, c)| c.enabled && is_in_app_channel(key))
+        .or_else(|| template.channels.iter().find(|(_, c)| c.enabled))
+        .ok_or_else(|| "Template has no enabled channels".to_string())?;
+    let (rendered, _) = render_channel(key, channel, &data)?;
+
+    Ok(Notification {
+        id: uuid::Uuid::new_v4().to_string(),
+        user_id,
+        organization_id: template.organization_id.clone(),
+        notification_type: NotificationType::Info,
+        category: template.category.clone(),
+        title: rendered.title,
+        message: rendered.body,
+        data: Some(variables),
+        priority: NotificationPriority::Normal,
+        read: false,
+        read_at: None,
+        action_url: channel.action_url.clone(),
+        action_label: channel.action_label.clone(),
+        icon: None,
+        image: None,
+        expires_at: None,
+        channels: vec![NotificationChannel::InApp],
+        delivery_status: HashMap::new(),
+        created_at: chrono::Utc::now().timestamp_millis(),
+    })
 }
 
 #[command]
@@ -460,12 +515,135 @@ pub async fn notification_get_unread_count(_user_id: String) -> Result<i64, Stri
     Ok(0)
 }
 
+// ============================================================================
+// Notification Center Commands
+// ============================================================================
+
+/// Queues read-state changes so the user's other devices clear the same notifications
+fn queue_read_state_sync(sync: &SyncService, changes: &[ReadStateChange]) -> Result<(), String> {
+    let settings = sync.get_settings();
+    if !settings.sync_enabled || !settings.sync_notifications {
+        return Ok(());
+    }
+    for change in changes {
+        let data = serde_json::to_value(change).map_err(|e| e.to_string())?;
+        sync.queue_sync_item(SyncDataType::Notifications, data)?;
+    }
+    Ok(())
+}
+
+/// Notification center contents: similar notifications collapsed into groups,
+/// plus unread counts per category
+#[command]
+pub async fn notification_get_grouped(
+    service: State<'_, Arc<NotificationsService>>,
+    user_id: String,
+    limit: Option<i32>,
+) -> Result<NotificationCenter, String> {
+    service
+        .get_grouped_notifications(&user_id, limit.unwrap_or(200))
+        .map_err(|e| e.to_string())
+}
+
+#[command]
+pub async fn notification_mark_category_read(
+    service: State<'_, Arc<NotificationsService>>,
+    sync: State<'_, SyncService>,
+    user_id: String,
+    category: String,
+) -> Result<usize, String> {
+    let changes = service
+        .mark_category_read(&user_id, &category)
+        .map_err(|e| e.to_string())?;
+    queue_read_state_sync(&sync, &changes)?;
+    Ok(changes.len())
+}
+
+/// Apply read state received from another device; read and dismissed only ever turn on
+#[command]
+pub async fn notification_apply_synced_read_state(
+    service: State<'_, Arc<NotificationsService>>,
+    changes: Vec<ReadStateChange>,
+) -> Result<usize, String> {
+    service.apply_read_state(&changes).map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // Template Commands
 // ============================================================================
 
+fn get_stored_template(
+    state: &NotificationTemplateState,
+    template_id: &str,
+) -> Result<NotificationTemplate, String> {
+    let templates = state.templates.read().map_err(|e| format!("Lock error: {}", e))?;
+    templates.get(template_id).cloned().ok_or_else(|| "Template not found".to_string())
+}
+
+fn is_in_app_channel(key: &str) -> bool {
+    matches!(key, "in_app" | "inapp")
+}
+
+/// Builds the render context: caller-supplied variables, then declared
+/// defaults, then (for previews) placeholder values by variable type.
+fn template_data(
+    template: &NotificationTemplate,
+    variables: &HashMap<String, serde_json::Value>,
+    with_samples: bool,
+) -> serde_json::Value {
+    let mut data: serde_json::Map<String, serde_json::Value> =
+        variables.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+
+    for variable in &template.variables {
+        if data.contains_key(&variable.name) {
+            continue;
+        }
+        let value = match (&variable.default_value, &variable.var_type) {
+            (Some(default), VariableType::Number) => default.parse::<f64>()
+                .map(serde_json::Value::from)
+                .unwrap_or_else(|_| serde_json::Value::from(default.clone())),
+            (Some(default), VariableType::Boolean) => serde_json::Value::from(default == "true"),
+            (Some(default), _) => serde_json::Value::from(default.clone()),
+            (None, _) if !with_samples => continue,
+            (None, VariableType::String) => serde_json::Value::from(format!("Sample {}", variable.name)),
+            (None, VariableType::Number) => serde_json::Value::from(42),
+            (None, VariableType::Boolean) => serde_json::Value::from(true),
+            (None, VariableType::Date) => serde_json::Value::from(chrono::Utc::now().timestamp_millis()),
+            (None, VariableType::Url) => serde_json::Value::from("https://example.com"),
+        };
+        data.insert(variable.name.clone(), value);
+    }
+
+    serde_json::Value::Object(data)
+}
+
+/// Renders one channel; HTML bodies are escaped for HTML, everything else is plain text.
+fn render_channel(
+    key: &str,
+    channel: &ChannelTemplate,
+    data: &serde_json::Value,
+) -> Result<(ChannelPreview, Vec<String>), String> {
+    let mut warnings = Vec::new();
+    let mut render = |source: &str, escape: EscapeMode| -> Result<String, String> {
+        let rendered = template_engine::render(source, data, escape)
+            .map_err(|e| format!("{} template: {}", key, e))?;
+        warnings.extend(rendered.warnings);
+        Ok(rendered.output)
+    };
+
+    let preview = ChannelPreview {
+        title: render(channel.subject.as_deref().unwrap_or(&channel.title), EscapeMode::Plain)?,
+        body: render(&channel.body, EscapeMode::Plain)?,
+        html_body: channel.html_body.as_deref()
+            .map(|html| render(html, EscapeMode::Html))
+            .transpose()?,
+    };
+    Ok((preview, warnings))
+}
+
 #[command]
 pub async fn notification_template_create(
+    state: State<'_, NotificationTemplateState>,
     template: NotificationTemplate,
 ) -> Result<NotificationTemplate, String> {
     let mut new_template = template;
@@ -473,46 +651,102 @@ pub async fn notification_template_create(
     new_template.created_at = chrono::Utc::now().timestamp_millis();
     new_template.updated_at = new_template.created_at;
     
+    let mut templates = state.templates.write().map_err(|e| format!("Lock error: {}", e))?;
+    templates.insert(new_template.id.clone(), new_template.clone());
+    
     Ok(new_template)
 }
 
 #[command]
 pub async fn notification_template_get(
-    _template_id: String,
+    state: State<'_, NotificationTemplateState>,
+    template_id: String,
 ) -> Result<Option<NotificationTemplate>, String> {
-    Ok(None)
+    let templates = state.templates.read().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(templates.get(&template_id).cloned())
 }
 
 #[command]
 pub async fn notification_template_list(
-    _organization_id: Option<String>,
+    state: State<'_, NotificationTemplateState>,
+    organization_id: Option<String>,
 ) -> Result<Vec<NotificationTemplate>, String> {
-    Ok(vec![])
+    let templates = state.templates.read().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(templates.values()
+        .filter(|t| organization_id.is_none() || t.organization_id == organization_id)
+        .cloned()
+        .collect())
 }
 
 #[command]
 pub async fn notification_template_update(
-    _template_id: String,
-    _updates: serde_json::Value,
+    state: State<'_, NotificationTemplateState>,
+    template_id: String,
+    updates: serde_json::Value,
 ) -> Result<NotificationTemplate, String> {
-    Err("Template not found".to_string())
+    let mut templates = state.templates.write().map_err(|e| format!("Lock error: {}", e))?;
+    let existing = templates.get(&template_id).ok_or_else(|| "Template not found".to_string())?;
+    
+    let mut merged = serde_json::to_value(existing).map_err(|e| e.to_string())?;
+    if let (Some(target), Some(changes)) = (merged.as_object_mut(), updates.as_object()) {
+        for (key, value) in changes {
+            if key != "id" && key != "created_at" {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    let mut updated: NotificationTemplate = serde_json::from_value(merged)
+        .map_err(|e| format!("Invalid template update: {}", e))?;
+    updated.updated_at = chrono::Utc::now().timestamp_millis();
+    
+    templates.insert(template_id, updated.clone());
+    Ok(updated)
 }
 
 #[command]
-pub async fn notification_template_delete(_template_id: String) -> Result<(), String> {
+pub async fn notification_template_delete(
+    state: State<'_, NotificationTemplateState>,
+    template_id: String,
+) -> Result<(), String> {
+    let mut templates = state.templates.write().map_err(|e| format!("Lock error: {}", e))?;
+    templates.remove(&template_id);
     Ok(())
 }
 
 #[command]
 pub async fn notification_template_preview(
-    _template_id: String,
-    _variables: HashMap<String, serde_json::Value>,
+    state: State<'_, NotificationTemplateState>,
+    template_id: String,
+    variables: HashMap<String, serde_json::Value>,
 ) -> Result<TemplatePreview, String> {
-    Ok(TemplatePreview {
+    let template = get_stored_template(&state, &template_id)?;
+    let data = template_data(&template, &variables, true);
+
+    let mut preview = TemplatePreview {
         in_app: None,
         email: None,
         push: None,
-    })
+        sms: None,
+        warnings: Vec::new(),
+    };
+
+    for (key, channel) in template.channels.iter().filter(|(_, c)| c.enabled) {
+        let (rendered, warnings) = render_channel(key, channel, &data)?;
+        for warning in warnings {
+            if !preview.warnings.contains(&warning) {
+                preview.warnings.push(warning);
+            }
+        }
+        match key.as_str() {
+            "email" => preview.email = Some(rendered),
+            "push" => preview.push = Some(rendered),
+            "sms" => preview.sms = Some(rendered),
+            k if is_in_app_channel(k) => preview.in_app = Some(rendered),
+            _ => {}
+        }
+    }
+
+    Ok(preview)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -520,6 +754,9 @@ pub struct TemplatePreview {
     pub in_app: Option<ChannelPreview>,
     pub email: Option<ChannelPreview>,
     pub push: Option<ChannelPreview>,
+    pub sms: Option<ChannelPreview>,
+    /// Undefined variables and formatter problems found while rendering
+    pub warnings: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -827,12 +1064,19 @@ pub struct EmailStatus {
 // ============================================================================
 
 #[command]
-pub async fn sms_send(_to: String, _message: String) -> Result<SmsSendResult, String> {
-    Ok(SmsSendResult {
-        message_id: uuid::Uuid::new_v4().to_string(),
-        status: "sent".to_string(),
-        segments: 1,
-    })
+pub async fn sms_send(
+    gateway: State<'_, SmsGateway>,
+    to: String,
+    message: String,
+) -> Result<SmsSendResult, String> {
+    let record = gateway.send(&to, &message).await?;
+    if record.state == SmsDeliveryState::Failed {
+        return Err(format!(
+            "SMS delivery failed on all providers: {}",
+            record.failover_errors.join("; ")
+        ));
+    }
+    Ok(SmsSendResult::from(record))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -840,18 +1084,51 @@ pub struct SmsSendResult {
     pub message_id: String,
     pub status: String,
     pub segments: i32,
+    #[serde(default)]
+    pub encoding: Option<SmsEncoding>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub estimated_cost: f64,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl From<SmsDeliveryRecord> for SmsSendResult {
+    fn from(record: SmsDeliveryRecord) -> Self {
+        Self {
+            message_id: record.message_id,
+            status: record.state.as_str().to_string(),
+            segments: record.segments.segments as i32,
+            encoding: Some(record.segments.encoding),
+            provider: record.provider,
+            estimated_cost: record.estimated_cost,
+            error: record.error,
+        }
+    }
 }
 
 #[command]
-pub async fn sms_send_bulk(messages: Vec<SmsMessage>) -> Result<Vec<SmsSendResult>, String> {
-    let results = messages
-        .iter()
-        .map(|_| SmsSendResult {
-            message_id: uuid::Uuid::new_v4().to_string(),
-            status: "sent".to_string(),
-            segments: 1,
-        })
-        .collect();
+pub async fn sms_send_bulk(
+    gateway: State<'_, SmsGateway>,
+    messages: Vec<SmsMessage>,
+) -> Result<Vec<SmsSendResult>, String> {
+    let mut results = Vec::with_capacity(messages.len());
+    for message in messages {
+        let result = match gateway.send(&message.to, &message.message).await {
+            Ok(record) => SmsSendResult::from(record),
+            Err(e) => SmsSendResult {
+                message_id: uuid::Uuid::new_v4().to_string(),
+                status: "rejected".to_string(),
+                segments: 0,
+                encoding: None,
+                provider: None,
+                estimated_cost: 0.0,
+                error: Some(e),
+            },
+        };
+        results.push(result);
+    }
     
     Ok(results)
 }
@@ -863,12 +1140,19 @@ pub struct SmsMessage {
 }
 
 #[command]
-pub async fn sms_get_delivery_status(message_id: String) -> Result<SmsStatus, String> {
+pub async fn sms_get_delivery_status(
+    gateway: State<'_, SmsGateway>,
+    message_id: String,
+) -> Result<SmsStatus, String> {
+    let record = gateway
+        .get_record(&message_id)
+        .ok_or_else(|| format!("SMS not found: {}", message_id))?;
+
     Ok(SmsStatus {
-        message_id,
-        status: "delivered".to_string(),
-        delivered_at: Some(chrono::Utc::now().timestamp_millis()),
-        error: None,
+        message_id: record.message_id,
+        status: record.state.as_str().to_string(),
+        delivered_at: record.delivered_at,
+        error: record.error,
     })
 }
 
@@ -879,3 +1163,52 @@ pub struct SmsStatus {
     pub delivered_at: Option<i64>,
     pub error: Option<String>,
 }
+
+/// Replaces the provider chain; the first entry is primary, the rest are failovers.
+#[command]
+pub async fn sms_configure_providers(
+    gateway: State<'_, SmsGateway>,
+    providers: Vec<SmsProviderConfig>,
+) -> Result<Vec<String>, String> {
+    gateway.configure(providers)
+}
+
+#[command]
+pub async fn sms_estimate_cost(
+    gateway: State<'_, SmsGateway>,
+    message: String,
+) -> Result<SmsCostEstimate, String> {
+    let (segments, estimated_cost) = gateway.estimate(&message);
+    Ok(SmsCostEstimate { segments, estimated_cost })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmsCostEstimate {
+    pub segments: SegmentInfo,
+    pub estimated_cost: f64,
+}
+
+/// Delivery-status callback relayed from Twilio or Vonage.
+#[command]
+pub async fn sms_process_status_webhook(
+    gateway: State<'_, SmsGateway>,
+    payload: serde_json::Value,
+) -> Result<SmsStatus, String> {
+    let record = gateway.apply_status_webhook(&payload)?;
+    Ok(SmsStatus {
+        message_id: record.message_id,
+        status: record.state.as_str().to_string(),
+        delivered_at: record.delivered_at,
+        error: record.error,
+    })
+}
+
+/// Inbound reply relayed from the provider; handles STOP/START keywords.
+#[command]
+pub async fn sms_process_inbound(
+    gateway: State<'_, SmsGateway>,
+    from: String,
+    body: String,
+) -> Result<InboundSmsAction, String> {
+    gateway.handle_inbound(&from, &body)
+}
@@ -2,7 +2,7 @@
 
 use crate::ocr::*;
 use base64::{engine::general_purpose, Engine as _};
-use tauri::command;
+use tauri::{command, AppHandle, Emitter};
 
 // ============================================================================
 // OCR EXTRACTION COMMANDS
@@ -73,6 +73,53 @@ pub async fn ocr_extract_from_region(
     }
 }
 
+/// Extract text from many image files concurrently, emitting `ocr-progress`
+/// as each one finishes. Results come back in input order and failures are
+/// reported per file.
+#[command]
+pub async fn ocr_extract_batch(
+    app: AppHandle,
+    paths: Vec<String>,
+    config: Option<OCRConfig>,
+    max_concurrency: Option<usize>,
+) -> Result<Vec<BatchItemResult>, String> {
+    let mut engine = OCREngine::new().map_err(|e| e.to_string())?;
+    if let Some(cfg) = config {
+        engine.set_config(cfg);
+    }
+    engine.validate_config().map_err(|e| e.to_string())?;
+
+    let workers = max_concurrency.unwrap_or(DEFAULT_BATCH_WORKERS);
+    Ok(engine
+        .extract_batch(paths, workers, |progress| {
+            let _ = app.emit("ocr-progress", &progress);
+        })
+        .await)
+}
+
+/// Run OCR and write a PDF that keeps the original page images with an
+/// invisible, selectable text layer over the recognized words
+#[command]
+pub async fn ocr_to_searchable_pdf(
+    input_path: String,
+    output_path: String,
+    config: Option<OCRConfig>,
+) -> Result<searchable_pdf::SearchablePdfResult, String> {
+    let mut engine = OCREngine::new().map_err(|e| e.to_string())?;
+    if let Some(cfg) = config {
+        engine.set_config(cfg);
+    }
+    engine.validate_config().map_err(|e| e.to_string())?;
+
+    engine
+        .to_searchable_pdf(
+            std::path::Path::new(&input_path),
+            std::path::Path::new(&output_path),
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // LANGUAGE MANAGEMENT COMMANDS
 // ============================================================================
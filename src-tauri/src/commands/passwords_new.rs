@@ -1,6 +1,13 @@
 // Password Manager Commands - Tauri Interface
 use crate::models::passwords::*;
 use crate::services::password_service::PasswordService;
+use crate::services::password_sharing::{
+    self, PasswordShareLink, SharePasswordOptions, SharedPasswordPayload, SharedPasswordRetrieval,
+};
+use crate::services::password_emergency_access::{
+    self, EmergencyAccessLevel, EmergencyAccessStatus, EmergencyVaultExport,
+};
+use crate::services::password_totp::{TotpCode, TotpSecret};
 use data_encoding::HEXLOWER;
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
@@ -8,22 +15,68 @@ use tauri::State;
 
 pub struct PasswordState {
     pub service: Mutex<PasswordService>,
+    /// Master password held while the vault is unlocked
+    pub unlocked: Mutex<Option<String>>,
+}
+
+impl PasswordState {
+    pub fn new(service: PasswordService) -> Self {
+        Self {
+            service: Mutex::new(service),
+            unlocked: Mutex::new(None),
+        }
+    }
+
+    pub fn unlocked_master_password(&self) -> Option<String> {
+        self.unlocked.lock().ok()?.clone()
+    }
+
+    /// Decrypt an entry with the unlocked master password
+    pub fn decrypt_unlocked(&self, service: &PasswordService, entry: &PasswordEntry) -> Option<String> {
+        self.decrypt_unlocked_value(service, &entry.encrypted_password)
+    }
+
+    /// Decrypt any value sealed with the vault key, such as a TOTP secret
+    pub fn decrypt_unlocked_value(&self, service: &PasswordService, encrypted_hex: &str) -> Option<String> {
+        let master_password = self.unlocked_master_password()?;
+        let config = service.get_master_password_config().ok()?;
+        let salt = HEXLOWER.decode(config.salt.as_bytes()).ok()?;
+        service
+            .decrypt_password_internal(encrypted_hex, &master_password, &salt, &config.kdf)
+            .ok()
+    }
+
+    /// Encrypt a password with the unlocked master password
+    pub fn encrypt_unlocked(&self, service: &PasswordService, password: &str) -> Result<String, String> {
+        let master_password = self
+            .unlocked_master_password()
+            .ok_or_else(|| "Password vault is locked".to_string())?;
+        let config = service.get_master_password_config().map_err(|e| e.to_string())?;
+        let salt = HEXLOWER
+            .decode(config.salt.as_bytes())
+            .map_err(|e| format!("Invalid salt: {}", e))?;
+        service
+            .encrypt_password_internal(password, &master_password, &salt, &config.kdf)
+            .map_err(|e| e.to_string())
+    }
 }
 
 // ============================================================================
 // MASTER PASSWORD COMMANDS
 // ============================================================================
 
+/// Create the vault. New vaults use Argon2id unless `kdf` says otherwise.
 #[tauri::command]
 pub async fn setup_master_password(
     master_password: String,
+    kdf: Option<KdfParams>,
     state: State<'_, PasswordState>,
 ) -> Result<(), String> {
     state
         .service
         .lock()
         .map_err(|e| e.to_string())?
-        .setup_master_password(&master_password)
+        .setup_master_password(&master_password, &kdf.unwrap_or_else(KdfParams::recommended))
         .map_err(|e| e.to_string())
 }
 
@@ -33,7 +86,10 @@ pub async fn verify_master_password(
     state: State<'_, PasswordState>,
 ) -> Result<bool, String> {
     let service = state.service.lock().map_err(|e| e.to_string())?;
-    
+    master_password_matches(&service, &master_password)
+}
+
+fn master_password_matches(service: &PasswordService, master_password: &str) -> Result<bool, String> {
     let config = service
         .get_master_password_config()
         .map_err(|e| e.to_string())?;
@@ -50,7 +106,7 @@ pub async fn verify_master_password(
             .decode(config.salt.as_bytes())
             .map_err(|e| format!("Invalid salt: {}", e))?;
 
-        match service.decrypt_password_internal(&entry.encrypted_password, &master_password, &salt) {
+        match service.decrypt_password_internal(&entry.encrypted_password, master_password, &salt, &config.kdf) {
             Ok(_) => Ok(true),
             Err(_) => Ok(false),
         }
@@ -60,6 +116,32 @@ pub async fn verify_master_password(
     }
 }
 
+/// Unlock the vault for the session so the browser engine can offer and
+/// save credentials without asking for the master password each time
+#[tauri::command]
+pub async fn unlock_password_vault(
+    master_password: String,
+    state: State<'_, PasswordState>,
+) -> Result<bool, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    if !master_password_matches(&service, &master_password)? {
+        return Ok(false);
+    }
+    *state.unlocked.lock().map_err(|e| e.to_string())? = Some(master_password);
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn lock_password_vault(state: State<'_, PasswordState>) -> Result<(), String> {
+    *state.unlocked.lock().map_err(|e| e.to_string())? = None;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn is_password_vault_unlocked(state: State<'_, PasswordState>) -> Result<bool, String> {
+    Ok(state.unlocked_master_password().is_some())
+}
+
 #[tauri::command]
 pub async fn get_master_password_config(
     state: State<'_, PasswordState>,
@@ -72,17 +154,38 @@ pub async fn get_master_password_config(
         .map_err(|e| e.to_string())
 }
 
+/// Re-encrypt the vault under a new master password. Passing `kdf` also
+/// switches the key derivation (e.g. a PBKDF2 vault opting in to Argon2id);
+/// without it the current settings are kept.
 #[tauri::command]
 pub async fn change_master_password(
     old_password: String,
     new_password: String,
+    kdf: Option<KdfParams>,
     state: State<'_, PasswordState>,
 ) -> Result<(), String> {
     state
         .service
         .lock()
         .map_err(|e| e.to_string())?
-        .change_master_password(&old_password, &new_password)
+        .change_master_password(&old_password, &new_password, kdf.as_ref())
+        .map_err(|e| e.to_string())?;
+
+    // The cached master password no longer decrypts anything
+    *state.unlocked.lock().map_err(|e| e.to_string())? = None;
+    Ok(())
+}
+
+/// Current KDF settings and whether they fall below the security baseline
+#[tauri::command]
+pub async fn get_master_password_kdf_status(
+    state: State<'_, PasswordState>,
+) -> Result<KdfStatus, String> {
+    state
+        .service
+        .lock()
+        .map_err(|e| e.to_string())?
+        .kdf_status()
         .map_err(|e| e.to_string())
 }
 
@@ -119,7 +222,7 @@ pub async fn save_password(
 
     // Encrypt the password
     let encrypted = service
-        .encrypt_password_internal(&password, &master_password, &salt)
+        .encrypt_password_internal(&password, &master_password, &salt, &config.kdf)
         .map_err(|e| e.to_string())?;
 
     // Analyze strength
@@ -152,7 +255,7 @@ pub async fn update_password_entry(
             .map_err(|e| format!("Invalid salt: {}", e))?;
 
         let encrypted = service
-            .encrypt_password_internal(&pwd, &master_password, &salt)
+            .encrypt_password_internal(&pwd, &master_password, &salt, &config.kdf)
             .map_err(|e| e.to_string())?;
 
         let strength = service.analyze_strength(&pwd);
@@ -205,7 +308,7 @@ pub async fn decrypt_password(
 
     // Decrypt
     service
-        .decrypt_password_internal(&entry.encrypted_password, &master_password, &salt)
+        .decrypt_password_internal(&entry.encrypted_password, &master_password, &salt, &config.kdf)
         .map_err(|e| e.to_string())
 }
 
@@ -222,6 +325,68 @@ pub async fn update_password_last_used(
         .map_err(|e| e.to_string())
 }
 
+// ============================================================================
+// TOTP COMMANDS
+// ============================================================================
+
+/// Store an authenticator secret (otpauth:// URI or bare base32) with an
+/// entry, encrypted with the unlocked vault. Returns the current code.
+#[tauri::command]
+pub async fn save_totp_secret(
+    entry_id: String,
+    secret: String,
+    state: State<'_, PasswordState>,
+) -> Result<TotpCode, String> {
+    let totp = TotpSecret::parse(&secret)?;
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    let encrypted = state.encrypt_unlocked(&service, &totp.to_uri())?;
+    if !service
+        .set_totp_secret(&entry_id, Some(&encrypted))
+        .map_err(|e| e.to_string())?
+    {
+        return Err("Password entry not found".to_string());
+    }
+    Ok(totp.code_at(unix_now()))
+}
+
+/// Current code and the seconds until it changes; `None` when the entry has
+/// no TOTP secret
+#[tauri::command]
+pub async fn get_totp_code(
+    entry_id: String,
+    state: State<'_, PasswordState>,
+) -> Result<Option<TotpCode>, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    let Some(encrypted) = service.get_totp_secret(&entry_id).map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    let uri = state
+        .decrypt_unlocked_value(&service, &encrypted)
+        .ok_or_else(|| "Password vault is locked".to_string())?;
+    Ok(Some(TotpSecret::parse(&uri)?.code_at(unix_now())))
+}
+
+#[tauri::command]
+pub async fn delete_totp_secret(
+    entry_id: String,
+    state: State<'_, PasswordState>,
+) -> Result<(), String> {
+    state
+        .service
+        .lock()
+        .map_err(|e| e.to_string())?
+        .set_totp_secret(&entry_id, None)
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
 // ============================================================================
 // CATEGORY COMMANDS
 // ============================================================================
@@ -389,3 +554,231 @@ pub struct ImportResult {
     pub failed: i32,
     pub errors: Vec<String>,
 }
+
+// ============================================================================
+// SHARING COMMANDS
+// ============================================================================
+
+/// Share a vault entry through a one-time encrypted link. Requires the vault
+/// to be unlocked; the returned link carries the key in its fragment.
+#[tauri::command]
+pub async fn share_password(
+    entry_id: String,
+    options: Option<SharePasswordOptions>,
+    state: State<'_, PasswordState>,
+) -> Result<PasswordShareLink, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+
+    let entries = service.get_all_passwords().map_err(|e| e.to_string())?;
+    let entry = entries
+        .iter()
+        .find(|e| e.id == entry_id)
+        .ok_or_else(|| "Password entry not found".to_string())?;
+    let password = state
+        .decrypt_unlocked(&service, entry)
+        .ok_or_else(|| "Password vault is locked".to_string())?;
+
+    let payload = SharedPasswordPayload {
+        name: entry.name.clone(),
+        username: entry.username.clone(),
+        password,
+        url: entry.url.clone(),
+        notes: entry.notes.clone(),
+    };
+    password_sharing::create_share(
+        &service,
+        &payload,
+        &options.unwrap_or_default(),
+        chrono::Utc::now().timestamp(),
+    )
+}
+
+/// Open a shared password link with the key from its fragment
+#[tauri::command]
+pub async fn retrieve_shared_password(
+    token: String,
+    key: String,
+    password: Option<String>,
+    state: State<'_, PasswordState>,
+) -> Result<SharedPasswordRetrieval, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    password_sharing::retrieve_share(
+        &service,
+        &token,
+        &key,
+        password.as_deref(),
+        chrono::Utc::now().timestamp(),
+    )
+}
+
+// ============================================================================
+// EMERGENCY ACCESS COMMANDS
+// ============================================================================
+
+/// This device's emergency access public key, created on first use. Give it to
+/// vault owners who want to name you as their emergency contact.
+#[tauri::command]
+pub async fn get_emergency_public_key(state: State<'_, PasswordState>) -> Result<String, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    if let Some(identity) = service.get_emergency_identity().map_err(|e| e.to_string())? {
+        return Ok(identity.public_key);
+    }
+
+    let (public_key, secret) = password_emergency_access::generate_identity();
+    let identity = EmergencyIdentity {
+        public_key: public_key.clone(),
+        encrypted_secret: state.encrypt_unlocked(&service, &HEXLOWER.encode(&secret))?,
+    };
+    service.save_emergency_identity(&identity).map_err(|e| e.to_string())?;
+    Ok(public_key)
+}
+
+/// Name a trusted contact who may request access to the vault. The whole vault
+/// is exported and sealed to the contact's public key; calling this again for
+/// the same contact refreshes the export.
+#[tauri::command]
+pub async fn grant_emergency_contact(
+    contact_email: String,
+    contact_public_key: String,
+    access_level: EmergencyAccessLevel,
+    wait_days: u32,
+    state: State<'_, PasswordState>,
+) -> Result<EmergencyAccessStatus, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    let entries = service
+        .get_all_passwords()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|entry| {
+            let password = state
+                .decrypt_unlocked(&service, entry)
+                .ok_or_else(|| "Password vault is locked".to_string())?;
+            Ok(SharedPasswordPayload {
+                name: entry.name.clone(),
+                username: entry.username.clone(),
+                password,
+                url: entry.url.clone(),
+                notes: entry.notes.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if entries.is_empty() && state.unlocked_master_password().is_none() {
+        return Err("Password vault is locked".to_string());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    password_emergency_access::grant_contact(
+        &service,
+        &contact_email,
+        &contact_public_key,
+        access_level,
+        wait_days,
+        &EmergencyVaultExport { entries, exported_at: now },
+        now,
+    )
+}
+
+/// Ask for emergency access as the contact, starting the waiting period
+#[tauri::command]
+pub async fn request_emergency_access(
+    grant_id: String,
+    state: State<'_, PasswordState>,
+) -> Result<EmergencyAccessStatus, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    password_emergency_access::request_access(&service, &grant_id, chrono::Utc::now().timestamp())
+}
+
+/// Turn down a pending request as the vault owner
+#[tauri::command]
+pub async fn deny_emergency_access(
+    grant_id: String,
+    state: State<'_, PasswordState>,
+) -> Result<EmergencyAccessStatus, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    password_emergency_access::deny_access(&service, &grant_id, chrono::Utc::now().timestamp())
+}
+
+#[tauri::command]
+pub async fn get_emergency_access_status(
+    grant_id: String,
+    state: State<'_, PasswordState>,
+) -> Result<EmergencyAccessStatus, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    password_emergency_access::access_status(&service, &grant_id, chrono::Utc::now().timestamp())
+}
+
+#[tauri::command]
+pub async fn list_emergency_contacts(
+    state: State<'_, PasswordState>,
+) -> Result<Vec<EmergencyAccessStatus>, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    password_emergency_access::list_access(&service, chrono::Utc::now().timestamp())
+}
+
+#[tauri::command]
+pub async fn revoke_emergency_contact(
+    grant_id: String,
+    state: State<'_, PasswordState>,
+) -> Result<(), String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    password_emergency_access::revoke_contact(&service, &grant_id)
+}
+
+/// Open a granted vault export with this device's emergency key. With takeover
+/// access and `import`, the entries are also saved into the local vault.
+#[tauri::command]
+pub async fn open_emergency_vault(
+    grant_id: String,
+    import: Option<bool>,
+    state: State<'_, PasswordState>,
+) -> Result<EmergencyVaultExport, String> {
+    let service = state.service.lock().map_err(|e| e.to_string())?;
+    let identity = service
+        .get_emergency_identity()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "No emergency access key on this device".to_string())?;
+    let secret_hex = state
+        .unlocked_master_password()
+        .and_then(|master_password| {
+            let config = service.get_master_password_config().ok()?;
+            let salt = HEXLOWER.decode(config.salt.as_bytes()).ok()?;
+            service
+                .decrypt_password_internal(&identity.encrypted_secret, &master_password, &salt, &config.kdf)
+                .ok()
+        })
+        .ok_or_else(|| "Password vault is locked".to_string())?;
+    let secret: [u8; 32] = HEXLOWER
+        .decode(secret_hex.as_bytes())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| "Invalid emergency access key".to_string())?;
+
+    let now = chrono::Utc::now().timestamp();
+    let (level, export) = password_emergency_access::open_export(&service, &grant_id, &secret, now)?;
+
+    if import.unwrap_or(false) {
+        if level != EmergencyAccessLevel::Takeover {
+            return Err("This emergency access only allows viewing".to_string());
+        }
+        for shared in &export.entries {
+            let entry = PasswordEntry {
+                id: uuid::Uuid::new_v4().to_string(),
+                name: shared.name.clone(),
+                username: shared.username.clone(),
+                encrypted_password: state.encrypt_unlocked(&service, &shared.password)?,
+                url: shared.url.clone(),
+                notes: shared.notes.clone(),
+                category: "general".to_string(),
+                tags: vec!["emergency-access".to_string()],
+                date_created: now,
+                date_modified: now,
+                last_used: None,
+                favorite: false,
+                strength_score: service.analyze_strength(&shared.password).score,
+            };
+            service.save_password(&entry).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(export)
+}
@@ -1,4 +1,5 @@
-use crate::models::reading_list::{Article, ArticleStats, ArticleFilter};
+use crate::models::reading_list::{Article, ArticleStats, ArticleFilter, ReadingListTag};
+use crate::services::lqip;
 use crate::services::reading_list_service::ReadingListService;
 use tauri::State;
 
@@ -85,6 +86,17 @@ pub async fn search_reading_list(
     state.search_articles(&filter)
 }
 
+/// Filters by nested `parent/child` tags; a parent also matches its
+/// descendants. `match_all` requires every tag instead of any.
+#[tauri::command]
+pub async fn search_reading_list_by_tags(
+    tags: Vec<String>,
+    match_all: bool,
+    state: State<'_, ReadingListService>,
+) -> Result<Vec<Article>, String> {
+    state.search_by_tags(&tags, match_all)
+}
+
 #[tauri::command]
 pub async fn get_reading_list_stats(
     state: State<'_, ReadingListService>,
@@ -95,6 +107,41 @@ pub async fn get_reading_list_stats(
 #[tauri::command]
 pub async fn get_reading_list_tags(
     state: State<'_, ReadingListService>,
-) -> Result<Vec<String>, String> {
+) -> Result<Vec<ReadingListTag>, String> {
     state.get_all_tags()
 }
+
+/// Prepares a saved article for offline reading: images in its content get
+/// lazy loading and blurred placeholders, and the thumbnail gets a placeholder
+/// of its own. Images that fail to load are left as they were.
+#[tauri::command]
+pub async fn cache_article_images(
+    id: String,
+    state: State<'_, ReadingListService>,
+) -> Result<Article, String> {
+    let mut article = state.get_article(&id)?
+        .ok_or_else(|| format!("Article not found: {}", id))?;
+
+    let mut found = Vec::new();
+    if let Some(content) = &article.content {
+        let (content, placeholders) = lqip::embed_placeholders(content, &article.url).await;
+        article.content = Some(content);
+        found = placeholders;
+    }
+
+    let thumbnail_url = article.thumbnail.as_deref()
+        .and_then(|thumbnail| url::Url::parse(&article.url).ok()?.join(thumbnail).ok());
+    let placeholder = match thumbnail_url {
+        Some(thumbnail_url) => match found.iter().find(|p| p.url == thumbnail_url.as_str()) {
+            Some(known) => Some(known.clone()),
+            None => lqip::fetch_lqip(&reqwest::Client::new(), thumbnail_url.as_str()).await.ok(),
+        },
+        None => found.into_iter().next(),
+    };
+    if let Some(placeholder) = placeholder {
+        article.thumbnail_placeholder = Some(placeholder.data_url);
+    }
+
+    state.update_article(&article)?;
+    Ok(article)
+}
@@ -12,6 +12,7 @@
 //
 // ═══════════════════════════════════════════════════════════════════════════════
 
+use crate::services::competitor_monitor::{schedule_interval, CompetitorChange, CompetitorMonitor};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Mutex;
@@ -310,12 +311,129 @@ pub struct SearchResult {
 pub struct ResearchState {
     pub projects: Mutex<Vec<ResearchProject>>,
     pub search_history: Mutex<Vec<SearchResult>>,
+    pub competitor_monitors: Mutex<HashMap<String, CompetitorMonitor>>,
+    pub competitor_changes: Mutex<Vec<CompetitorChange>>,
 }
 
+/// Detected competitor changes kept in memory
+const MAX_COMPETITOR_CHANGES: usize = 500;
+
 impl ResearchState {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Pages of every monitor whose schedule has come up; marks them checked
+    fn take_due_monitors(&self, now: DateTime<Utc>) -> Vec<(String, Vec<String>)> {
+        let Ok(mut monitors) = self.competitor_monitors.lock() else {
+            return Vec::new();
+        };
+        monitors
+            .values_mut()
+            .filter(|m| m.is_due(now))
+            .map(|m| {
+                m.mark_checked(now);
+                (m.competitor_id.clone(), m.pages.clone())
+            })
+            .collect()
+    }
+
+    /// Diff a fresh capture against the previous one and keep any changes
+    fn record_competitor_page(
+        &self,
+        competitor_id: &str,
+        url: &str,
+        html: &str,
+    ) -> Result<Vec<CompetitorChange>, String> {
+        let competitor_name = {
+            let mut projects = self.projects.lock().map_err(|e| e.to_string())?;
+            let competitor = projects
+                .iter_mut()
+                .flat_map(|p| p.competitors.iter_mut())
+                .find(|c| c.id == competitor_id)
+                .ok_or_else(|| format!("Competitor not found: {}", competitor_id))?;
+            competitor.last_updated = Utc::now().to_rfc3339();
+            competitor.name.clone()
+        };
+
+        let changes = {
+            let mut monitors = self.competitor_monitors.lock().map_err(|e| e.to_string())?;
+            let monitor = monitors
+                .get_mut(competitor_id)
+                .ok_or_else(|| format!("Competitor is not monitored: {}", competitor_id))?;
+            monitor.record_snapshot(&competitor_name, url, html)?
+        };
+
+        if !changes.is_empty() {
+            let mut log = self.competitor_changes.lock().map_err(|e| e.to_string())?;
+            log.extend(changes.iter().cloned());
+            let overflow = log.len().saturating_sub(MAX_COMPETITOR_CHANGES);
+            log.drain(..overflow);
+        }
+        Ok(changes)
+    }
+}
+
+/// How often the background task looks for due monitors
+const MONITOR_TICK_SECS: u64 = 60;
+
+/// Re-fetch monitored competitor pages on their schedule and emit
+/// `research-competitor-change` for every meaningful change
+pub fn start_competitor_monitoring(app: tauri::AppHandle) {
+    use tauri::{Emitter, Manager};
+
+    tauri::async_runtime::spawn(async move {
+        let client = match reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .user_agent("Mozilla/5.0 (compatible; CUBE-Research/1.0)")
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                log::error!("Competitor monitoring disabled: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(MONITOR_TICK_SECS)).await;
+
+            let due = app.state::<ResearchState>().take_due_monitors(Utc::now());
+            for (competitor_id, pages) in due {
+                for url in pages {
+                    let html = match client.get(&url).send().await {
+                        Ok(response) if response.status().is_success() => match response.text().await {
+                            Ok(html) => html,
+                            Err(e) => {
+                                log::warn!("Failed to read {}: {}", url, e);
+                                continue;
+                            }
+                        },
+                        Ok(response) => {
+                            log::warn!("Fetching {} returned {}", url, response.status());
+                            continue;
+                        }
+                        Err(e) => {
+                            log::warn!("Failed to fetch {}: {}", url, e);
+                            continue;
+                        }
+                    };
+
+                    match app
+                        .state::<ResearchState>()
+                        .record_competitor_page(&competitor_id, &url, &html)
+                    {
+                        Ok(changes) => {
+                            for change in changes {
+                                let _ = app.emit("research-competitor-change", &change);
+                            }
+                        }
+                        Err(e) => log::warn!("Competitor snapshot failed for {}: {}", url, e),
+                    }
+                }
+            }
+        }
+    });
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -688,6 +806,88 @@ pub async fn research_remove_competitor(
     Ok(project.competitors.len() < initial_len)
 }
 
+#[command]
+pub async fn research_set_competitor_monitor(
+    state: tauri::State<'_, ResearchState>,
+    competitor_id: String,
+    schedule: String,
+    selectors: Vec<String>,
+    pages: Option<Vec<String>>,
+    enabled: Option<bool>,
+) -> Result<CompetitorMonitor, String> {
+    schedule_interval(&schedule)?;
+    for selector in &selectors {
+        scraper::Selector::parse(selector).map_err(|e| format!("Invalid selector '{}': {:?}", selector, e))?;
+    }
+
+    let website = {
+        let projects = state.projects.lock().map_err(|e| e.to_string())?;
+        projects.iter()
+            .flat_map(|p| p.competitors.iter())
+            .find(|c| c.id == competitor_id)
+            .map(|c| c.website.clone())
+            .ok_or_else(|| format!("Competitor not found: {}", competitor_id))?
+    };
+    let pages = pages
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| vec![website]);
+
+    let mut monitors = state.competitor_monitors.lock().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    let monitor = monitors.entry(competitor_id.clone()).or_insert_with(|| CompetitorMonitor {
+        competitor_id: competitor_id.clone(),
+        pages: Vec::new(),
+        schedule: schedule.clone(),
+        selectors: Vec::new(),
+        enabled: true,
+        last_checked: None,
+        next_run: now.to_rfc3339(),
+        snapshots: HashMap::new(),
+    });
+
+    // A different selector set would make every page look changed
+    if monitor.selectors != selectors {
+        monitor.snapshots.clear();
+    }
+    monitor.snapshots.retain(|url, _| pages.contains(url));
+    if monitor.schedule != schedule {
+        monitor.next_run = now.to_rfc3339();
+    }
+    monitor.pages = pages;
+    monitor.schedule = schedule;
+    monitor.selectors = selectors;
+    monitor.enabled = enabled.unwrap_or(true);
+
+    Ok(monitor.clone())
+}
+
+#[command]
+pub async fn research_get_competitor_changes(
+    state: tauri::State<'_, ResearchState>,
+    competitor_id: Option<String>,
+    mark_read: Option<bool>,
+) -> Result<Vec<CompetitorChange>, String> {
+    let mut changes = state.competitor_changes.lock().map_err(|e| e.to_string())?;
+    let selected = |change: &CompetitorChange| match &competitor_id {
+        Some(id) => &change.competitor_id == id,
+        None => true,
+    };
+
+    let mut result: Vec<CompetitorChange> = changes.iter()
+        .filter(|c| selected(c))
+        .cloned()
+        .collect();
+    result.sort_by(|a, b| b.detected_at.cmp(&a.detected_at));
+
+    if mark_read.unwrap_or(false) {
+        for change in changes.iter_mut().filter(|c| selected(c)) {
+            change.read = true;
+        }
+    }
+
+    Ok(result)
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // REPORT COMMANDS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -999,6 +1199,20 @@ pub async fn research_get_notifications(
             }
         }
     }
+    drop(projects);
+
+    // Changes picked up by the competitor monitor
+    let changes = state.competitor_changes.lock().map_err(|e| e.to_string())?;
+    for change in changes.iter().filter(|c| !c.read) {
+        notifications.push(ResearchNotification {
+            id: format!("competitor-change-{}", change.id),
+            notification_type: "competitor_change".to_string(),
+            title: format!("{} changed their site", change.competitor_name),
+            message: change.summary.clone(),
+            timestamp: change.detected_at.clone(),
+            read: false,
+        });
+    }
     
     notifications.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
     notifications.truncate(10);
@@ -1,9 +1,11 @@
 // Scheduler Commands - Workflow scheduling with cron
 // Enables automated workflow execution on schedules
 
+use crate::services::cron_schedule::CronSchedule;
 use crate::services::scheduler::{
-    ExecutionQueueItem, ScheduledWorkflow, WorkflowScheduler,
+    parse_timezone, ExecutionQueueItem, ScheduledWorkflow, WorkflowScheduler,
 };
+use chrono::{DateTime, FixedOffset, Utc};
 use std::sync::Arc;
 use tauri::State;
 
@@ -41,6 +43,16 @@ pub async fn scheduler_get_schedules(
     Ok(state.0.get_schedules().await)
 }
 
+/// Next `count` fire times of a schedule, with the offset of its timezone
+#[tauri::command]
+pub async fn scheduler_get_next_runs(
+    state: State<'_, SchedulerState>,
+    schedule_id: String,
+    count: usize,
+) -> Result<Vec<DateTime<FixedOffset>>, String> {
+    state.0.get_next_runs(&schedule_id, count.min(100)).await
+}
+
 #[tauri::command]
 pub async fn scheduler_get_queue(
     state: State<'_, SchedulerState>,
@@ -75,19 +87,19 @@ pub async fn scheduler_cancel_execution(
 }
 
 #[tauri::command]
-pub async fn scheduler_validate_cron(cron_expression: String) -> Result<Vec<String>, String> {
-    use chrono::Utc;
-    use cron::Schedule;
-    use std::str::FromStr;
-
-    let schedule = Schedule::from_str(&cron_expression)
+pub async fn scheduler_validate_cron(
+    cron_expression: String,
+    timezone: Option<String>,
+) -> Result<Vec<String>, String> {
+    let schedule = CronSchedule::parse(&cron_expression)
         .map_err(|e| format!("Invalid cron expression: {}", e))?;
+    let tz = parse_timezone(timezone.as_deref().unwrap_or("UTC"))?;
 
     // Get next 5 occurrences
     let upcoming: Vec<String> = schedule
-        .upcoming(Utc)
-        .take(5)
-        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .upcoming(Utc::now(), tz, 5)
+        .into_iter()
+        .map(|dt| dt.with_timezone(&tz).format("%Y-%m-%d %H:%M:%S %Z").to_string())
         .collect();
 
     Ok(upcoming)
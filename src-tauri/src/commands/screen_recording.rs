@@ -4,7 +4,8 @@
  * Commands for screen recording functionality
  */
 use crate::services::screen_recorder::{
-    Quality, RecordingConfig, RecordingMode, RecordingSession, ScreenRecorder, VideoFormat,
+    CaptureDevices, Quality, RecordingConfig, RecordingMode, RecordingSession, ScreenRecorder,
+    VideoFormat, WebcamOverlay,
 };
 use anyhow::Result;
 use std::sync::Arc;
@@ -21,12 +22,15 @@ pub async fn screen_recording_start(
     microphone_enabled: bool,
     system_audio_enabled: bool,
     output_path: String,
+    webcam: Option<WebcamOverlay>,
+    devices: Option<CaptureDevices>,
     recorder: State<'_, Arc<ScreenRecorder>>,
 ) -> Result<String, String> {
     // Parse mode
     let recording_mode = match mode.as_str() {
         "fullscreen" => RecordingMode::Fullscreen,
         "window" => RecordingMode::Window,
+        "webcam" => RecordingMode::Webcam,
         mode_str if mode_str.starts_with("area:") => {
             // Format: "area:x,y,width,height"
             let parts: Vec<&str> = mode_str.strip_prefix("area:").unwrap().split(',').collect();
@@ -79,6 +83,8 @@ pub async fn screen_recording_start(
         microphone_enabled,
         system_audio_enabled,
         output_path,
+        webcam,
+        devices: devices.unwrap_or_default(),
     };
 
     recorder
@@ -119,6 +125,18 @@ pub async fn screen_recording_resume(
         .map_err(|e| format!("Failed to resume recording: {}", e))
 }
 
+/// Move or resize the webcam overlay while recording
+#[tauri::command]
+pub async fn screen_recording_update_webcam_overlay(
+    session_id: String,
+    overlay: WebcamOverlay,
+    recorder: State<'_, Arc<ScreenRecorder>>,
+) -> Result<RecordingSession, String> {
+    recorder
+        .update_webcam_overlay(&session_id, overlay)
+        .map_err(|e| format!("Failed to update webcam overlay: {}", e))
+}
+
 /// Get recording session
 #[tauri::command]
 pub async fn get_recording_session(
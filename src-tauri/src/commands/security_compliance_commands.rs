@@ -13,8 +13,12 @@
 
 #![allow(unused_variables)]
 
+use crate::services::siem_forwarder::{
+    SiemDeliveryStatus, SiemForwarder, SiemFormat, SiemRecord, SiemTarget, SiemTransport,
+    DEFAULT_BATCH_SIZE, DEFAULT_MAX_BUFFERED,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
 use tauri::State;
 
@@ -310,6 +314,128 @@ pub struct SIEMIntegration {
     pub events_sent: i64,
     pub last_error: Option<String>,
     pub created_at: i64,
+    /// Wire format; defaults to the one the SIEM type ingests natively
+    #[serde(default)]
+    pub format: Option<SiemFormat>,
+    /// Field name -> key in the target format; an empty key drops the field
+    #[serde(default)]
+    pub field_mapping: HashMap<String, String>,
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+    /// Events kept on disk while the SIEM is unreachable
+    #[serde(default)]
+    pub max_buffered: Option<usize>,
+}
+
+impl SIEMIntegration {
+    fn default_format(&self) -> SiemFormat {
+        match self.siem_type {
+            SIEMType::Splunk => SiemFormat::SplunkHec,
+            SIEMType::QRadar => SiemFormat::Leef,
+            SIEMType::Sentinel | SIEMType::ElasticSIEM => SiemFormat::Cef,
+            SIEMType::Chronicle | SIEMType::Custom => SiemFormat::Syslog,
+        }
+    }
+
+    fn target(&self) -> SiemTarget {
+        let (transport, address) = SiemTransport::from_endpoint(&self.endpoint);
+        SiemTarget {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            enabled: self.enabled,
+            format: self.format.unwrap_or_else(|| self.default_format()),
+            transport,
+            address,
+            token: Some(self.api_key.clone()).filter(|key| !key.is_empty()),
+            field_mapping: self.field_mapping.clone(),
+            event_types: self.event_types.clone(),
+            batch_size: self.batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+            max_buffered: self.max_buffered.unwrap_or(DEFAULT_MAX_BUFFERED),
+        }
+    }
+
+    /// Copy delivery counters from the forwarder
+    fn with_status(mut self, status: Option<SiemDeliveryStatus>) -> Self {
+        if let Some(status) = status {
+            self.events_sent = status.sent as i64;
+            self.last_sync_at = status.last_sync_at.or(self.last_sync_at);
+            self.last_error = status.last_error;
+        }
+        self
+    }
+}
+
+impl AlertSeverity {
+    /// CEF-style 0-10 severity
+    fn siem_severity(&self) -> u8 {
+        match self {
+            AlertSeverity::Critical => 10,
+            AlertSeverity::High => 8,
+            AlertSeverity::Medium => 5,
+            AlertSeverity::Low => 3,
+            AlertSeverity::Informational => 1,
+        }
+    }
+}
+
+impl SecurityAlert {
+    fn siem_record(&self) -> SiemRecord {
+        let mut fields = BTreeMap::from([
+            ("alert_id".to_string(), self.id.clone()),
+            ("status".to_string(), format!("{:?}", self.status).to_lowercase()),
+        ]);
+        if let Some(rule_id) = &self.detection_rule_id {
+            fields.insert("rule_id".to_string(), rule_id.clone());
+        }
+        if !self.tags.is_empty() {
+            fields.insert("tags".to_string(), self.tags.join(","));
+        }
+        SiemRecord {
+            id: self.id.clone(),
+            timestamp: self.created_at,
+            event_type: "alert".to_string(),
+            name: self.title.clone(),
+            severity: self.severity.siem_severity(),
+            source: self.source.clone(),
+            source_ip: self.source_ip.clone(),
+            user: None,
+            resource: self.target_resource.clone(),
+            action: None,
+            outcome: None,
+            fields,
+        }
+    }
+}
+
+impl SecurityEvent {
+    fn siem_record(&self) -> SiemRecord {
+        let outcome = match self.outcome {
+            EventOutcome::Success => "success",
+            EventOutcome::Failure => "failure",
+            EventOutcome::Unknown => "unknown",
+        };
+        SiemRecord {
+            id: self.id.clone(),
+            timestamp: self.timestamp,
+            event_type: self.event_type.clone(),
+            name: format!("{} {}", self.event_type, self.action),
+            severity: if matches!(self.outcome, EventOutcome::Failure) { 5 } else { 3 },
+            source: self.source.clone(),
+            source_ip: self.source_ip.clone(),
+            user: self.user_id.clone(),
+            resource: self.resource.clone(),
+            action: Some(self.action.clone()),
+            outcome: Some(outcome.to_string()),
+            fields: self
+                .details
+                .iter()
+                .map(|(key, value)| {
+                    let value = value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+                    (key.clone(), value)
+                })
+                .collect(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -423,6 +549,8 @@ pub struct SecurityComplianceState {
     pub siem_integrations: Arc<Mutex<HashMap<String, SIEMIntegration>>>,
     pub frameworks: Arc<Mutex<HashMap<String, ComplianceFramework>>>,
     pub evidence: Arc<Mutex<HashMap<String, ComplianceEvidence>>>,
+    /// Delivers alerts and events to the enabled SIEM integrations
+    pub siem: Arc<SiemForwarder>,
 }
 
 impl SecurityComplianceState {
@@ -437,6 +565,12 @@ impl SecurityComplianceState {
             siem_integrations: Arc::new(Mutex::new(HashMap::new())),
             frameworks: Arc::new(Mutex::new(HashMap::new())),
             evidence: Arc::new(Mutex::new(HashMap::new())),
+            siem: Arc::new(SiemForwarder::new(
+                dirs::data_local_dir()
+                    .unwrap_or_else(std::env::temp_dir)
+                    .join("cube-browser")
+                    .join("siem_buffer"),
+            )),
         }
     }
 }
@@ -460,6 +594,7 @@ pub async fn security_create_alert(
         .map_err(|e| format!("Failed to acquire lock: {}", e))?;
     
     let id = alert.id.clone();
+    state.siem.forward(alert.siem_record());
     alerts.insert(id.clone(), alert);
     
     Ok(id)
@@ -549,6 +684,22 @@ pub async fn security_add_alert_note(
 // SECURITY EVENT COMMANDS
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Store a security event and forward it to the SIEM integrations
+#[tauri::command]
+pub async fn security_record_event(
+    state: State<'_, SecurityComplianceState>,
+    event: SecurityEvent,
+) -> Result<String, String> {
+    let mut events = state.events.lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    
+    let id = event.id.clone();
+    state.siem.forward(event.siem_record());
+    events.insert(id.clone(), event);
+    
+    Ok(id)
+}
+
 #[tauri::command]
 pub async fn security_acknowledge_event(
     state: State<'_, SecurityComplianceState>,
@@ -888,6 +1039,7 @@ pub async fn security_create_siem_integration(
     let mut integrations = state.siem_integrations.lock()
         .map_err(|e| format!("Failed to acquire lock: {}", e))?;
     
+    state.siem.upsert_target(integration.target())?;
     let id = integration.id.clone();
     integrations.insert(id.clone(), integration);
     
@@ -904,6 +1056,7 @@ pub async fn security_get_siem_integration(
     
     integrations.get(&integration_id)
         .cloned()
+        .map(|integration| integration.with_status(state.siem.status(&integration_id)))
         .ok_or_else(|| format!("Integration not found: {}", integration_id))
 }
 
@@ -914,7 +1067,14 @@ pub async fn security_list_siem_integrations(
     let integrations = state.siem_integrations.lock()
         .map_err(|e| format!("Failed to acquire lock: {}", e))?;
     
-    Ok(integrations.values().cloned().collect())
+    Ok(integrations
+        .values()
+        .cloned()
+        .map(|integration| {
+            let status = state.siem.status(&integration.id);
+            integration.with_status(status)
+        })
+        .collect())
 }
 
 #[tauri::command]
@@ -927,10 +1087,43 @@ pub async fn security_delete_siem_integration(
     
     integrations.remove(&integration_id)
         .ok_or_else(|| format!("Integration not found: {}", integration_id))?;
+    state.siem.remove_target(&integration_id);
     
     Ok(())
 }
 
+/// Send a test event to the integration and report whether it was accepted
+#[tauri::command]
+pub async fn security_test_siem_integration(
+    state: State<'_, SecurityComplianceState>,
+    integration_id: String,
+) -> Result<SiemRecord, String> {
+    let result = state.siem.send_test(&integration_id).await;
+    
+    let mut integrations = state.siem_integrations.lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+    if let Some(integration) = integrations.get_mut(&integration_id) {
+        match &result {
+            Ok(record) => {
+                integration.last_sync_at = Some(record.timestamp);
+                integration.last_error = None;
+            }
+            Err(e) => integration.last_error = Some(e.clone()),
+        }
+    }
+    
+    result
+}
+
+#[tauri::command]
+pub async fn security_get_siem_delivery_status(
+    state: State<'_, SecurityComplianceState>,
+    integration_id: String,
+) -> Result<SiemDeliveryStatus, String> {
+    state.siem.status(&integration_id)
+        .ok_or_else(|| format!("Integration not found: {}", integration_id))
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // COMPLIANCE FRAMEWORK COMMANDS
 // ═══════════════════════════════════════════════════════════════════════════════
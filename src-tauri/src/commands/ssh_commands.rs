@@ -27,6 +27,23 @@ pub async fn create_ssh_config(
         .map_err(|e| e.to_string())
 }
 
+/// Import hosts from an OpenSSH client config (default ~/.ssh/config)
+#[tauri::command]
+pub async fn ssh_import_config(
+    path: Option<String>,
+    ssh_manager: State<'_, SshManager>,
+) -> Result<Vec<serde_json::Value>, String> {
+    ssh_manager
+        .import_ssh_config(path.map(std::path::PathBuf::from))
+        .map(|configs| {
+            configs
+                .into_iter()
+                .map(|c| serde_json::to_value(c).unwrap())
+                .collect()
+        })
+        .map_err(|e| e.to_string())
+}
+
 /// Connect SSH session
 #[tauri::command]
 pub async fn connect_ssh(
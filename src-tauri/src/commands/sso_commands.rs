@@ -13,9 +13,10 @@
  */
 
 use crate::AppState;
+use crate::commands::tenant_commands::TenantSettings;
 use crate::database::{
-    SSOProviderRecord, SSOSessionRecord, LDAPConfigRecord, 
-    LDAPGroupRecord, LDAPUserRecord,
+    Database, SSOProviderRecord, SSOSessionRecord, LDAPConfigRecord, 
+    LDAPGroupRecord, LDAPUserRecord, SSOIdentityRecord, TenantAuditRecord, TenantUserRecord,
 };
 use serde::{Deserialize, Serialize};
 use tauri::{command, State};
@@ -82,6 +83,9 @@ pub struct AttributeMapping {
     pub display_name: String,
     pub groups: Option<String>,
     pub custom: HashMap<String, String>,
+    /// IdP group name -> tenant role; the first matching group wins
+    #[serde(default)]
+    pub group_roles: HashMap<String, String>,
 }
 
 impl Default for AttributeMapping {
@@ -94,6 +98,7 @@ impl Default for AttributeMapping {
             display_name: "name".to_string(),
             groups: Some("groups".to_string()),
             custom: HashMap::new(),
+            group_roles: HashMap::new(),
         }
     }
 }
@@ -275,6 +280,29 @@ pub struct CompleteSSORequest {
     pub saml_response: Option<String>,
     pub code: Option<String>,
     pub state: Option<String>,
+    /// Attributes from the validated SAML assertion or OIDC ID token/userinfo
+    #[serde(default)]
+    pub attributes: HashMap<String, serde_json::Value>,
+}
+
+/// User fields extracted from an IdP assertion through the provider's attribute mapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MappedSSOUser {
+    pub subject: Option<String>,
+    pub email: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub display_name: Option<String>,
+    pub groups: Vec<String>,
+    /// Role granted by `group_roles`, if any of the user's groups is mapped
+    pub mapped_role: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SSOProvisionResult {
+    pub user_id: String,
+    pub role: String,
+    pub created: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -663,17 +691,40 @@ pub async fn complete_sso(
     // For SAML: Validate signature, extract assertions
     // For OIDC: Exchange code for tokens, validate ID token
     
+    let provider = state.database.get_sso_provider(&request.provider_id)
+        .map_err(|e| format!("Failed to fetch provider: {}", e))?
+        .map(record_to_provider)
+        .ok_or_else(|| format!("SSO provider not found: {}", request.provider_id))?;
+    if !provider.enabled {
+        return Err(format!("SSO provider is disabled: {}", provider.name));
+    }
+    
+    let user = map_sso_attributes(&provider, &request.attributes)?;
+    let require_existing = tenant_requires_existing_account(&state.database, &provider.tenant_id)?;
+    let provisioned = provision_sso_user(&state.database, &provider, &user, require_existing)?;
+    
     let session = SSOSession {
         id: Uuid::new_v4().to_string(),
-        user_id: "user_from_sso".to_string(),
+        user_id: provisioned.user_id.clone(),
         provider_id: request.provider_id.clone(),
-        session_index: Some("session_index_123".to_string()),
-        name_id: Some("user@corp.com".to_string()),
+        session_index: None,
+        name_id: Some(user.subject.clone().unwrap_or_else(|| user.email.clone())),
         attributes: {
             let mut attrs = HashMap::new();
-            attrs.insert("email".to_string(), "user@corp.com".to_string());
-            attrs.insert("first_name".to_string(), "John".to_string());
-            attrs.insert("last_name".to_string(), "Doe".to_string());
+            attrs.insert("email".to_string(), user.email.clone());
+            if let Some(first_name) = &user.first_name {
+                attrs.insert("first_name".to_string(), first_name.clone());
+            }
+            if let Some(last_name) = &user.last_name {
+                attrs.insert("last_name".to_string(), last_name.clone());
+            }
+            if let Some(display_name) = &user.display_name {
+                attrs.insert("display_name".to_string(), display_name.clone());
+            }
+            if !user.groups.is_empty() {
+                attrs.insert("groups".to_string(), user.groups.join(","));
+            }
+            attrs.insert("role".to_string(), provisioned.role.clone());
             attrs
         },
         ip_address: None,
@@ -699,6 +750,20 @@ pub async fn complete_sso(
     state.database.save_sso_session(&session_record)
         .map_err(|e| format!("Failed to save session: {}", e))?;
     
+    let _ = state.database.save_tenant_audit(&TenantAuditRecord {
+        id: Uuid::new_v4().to_string(),
+        tenant_id: provider.tenant_id.clone(),
+        user_id: Some(provisioned.user_id.clone()),
+        action: if provisioned.created { "sso.user_provisioned" } else { "sso.user_login" }.to_string(),
+        resource_type: Some("sso".to_string()),
+        resource_id: Some(provider.id.clone()),
+        old_values: None,
+        new_values: serde_json::to_string(&provisioned).ok(),
+        ip_address: None,
+        user_agent: None,
+        created_at: now.timestamp(),
+    });
+    
     Ok(session)
 }
 
@@ -1241,6 +1306,187 @@ pub async fn get_sso_audit_log(
 // HELPER FUNCTIONS
 // ============================================================================
 
+/// All string values of an assertion attribute (IdPs send single values or lists)
+fn attribute_values(attributes: &HashMap<String, serde_json::Value>, name: &str) -> Vec<String> {
+    let values = match attributes.get(name) {
+        Some(serde_json::Value::Array(items)) => items.iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        Some(serde_json::Value::String(value)) => vec![value.clone()],
+        Some(serde_json::Value::Null) | None => vec![],
+        Some(other) => vec![other.to_string()],
+    };
+    
+    values.into_iter()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+fn first_attribute(attributes: &HashMap<String, serde_json::Value>, name: &str) -> Option<String> {
+    if name.is_empty() {
+        return None;
+    }
+    attribute_values(attributes, name).into_iter().next()
+}
+
+/// Map IdP attributes to user fields using the provider's attribute mapping
+pub fn map_sso_attributes(
+    provider: &SSOProvider,
+    attributes: &HashMap<String, serde_json::Value>,
+) -> Result<MappedSSOUser, String> {
+    let mapping = &provider.attribute_mapping;
+    
+    if mapping.email.trim().is_empty() {
+        return Err(format!(
+            "Attribute mapping for SSO provider '{}' has no email attribute configured",
+            provider.name
+        ));
+    }
+    let email = first_attribute(attributes, &mapping.email)
+        .ok_or_else(|| format!(
+            "Attribute mapping for SSO provider '{}' is misconfigured: email attribute '{}' is missing from the assertion",
+            provider.name, mapping.email
+        ))?
+        .to_lowercase();
+    
+    let domain = match email.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && !domain.is_empty() => domain.to_string(),
+        _ => return Err(format!("Invalid email address from SSO provider: {}", email)),
+    };
+    if !provider.allowed_domains.is_empty()
+        && !provider.allowed_domains.iter().any(|d| d.eq_ignore_ascii_case(&domain))
+    {
+        return Err(format!("Email domain not allowed for SSO provider '{}': {}", provider.name, domain));
+    }
+    
+    let groups = mapping.groups.as_deref()
+        .map(|name| attribute_values(attributes, name))
+        .unwrap_or_default();
+    let mapped_role = groups.iter().find_map(|g| mapping.group_roles.get(g)).cloned();
+    
+    let first_name = first_attribute(attributes, &mapping.first_name);
+    let last_name = first_attribute(attributes, &mapping.last_name);
+    let display_name = first_attribute(attributes, &mapping.display_name).or_else(|| {
+        let full = [first_name.as_deref(), last_name.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        (!full.is_empty()).then_some(full)
+    });
+    
+    Ok(MappedSSOUser {
+        subject: first_attribute(attributes, &mapping.user_id),
+        email,
+        first_name,
+        last_name,
+        display_name,
+        groups,
+        mapped_role,
+    })
+}
+
+/// Whether the tenant disabled JIT provisioning for all of its SSO providers
+fn tenant_requires_existing_account(database: &Database, tenant_id: &str) -> Result<bool, String> {
+    let tenant = database.get_tenant(tenant_id)
+        .map_err(|e| format!("Failed to fetch tenant: {}", e))?
+        .ok_or_else(|| format!("Tenant not found: {}", tenant_id))?;
+    
+    let settings: TenantSettings = tenant.settings
+        .as_ref()
+        .and_then(|s| serde_json::from_str(s).ok())
+        .unwrap_or_default();
+    
+    Ok(settings.sso_require_existing_account)
+}
+
+/// Find the tenant account for an SSO user, creating it just-in-time when allowed,
+/// and refresh its attributes from the latest assertion.
+pub fn provision_sso_user(
+    database: &Database,
+    provider: &SSOProvider,
+    user: &MappedSSOUser,
+    require_existing: bool,
+) -> Result<SSOProvisionResult, String> {
+    let now = Utc::now().timestamp();
+    let jit_enabled = provider.jit_provisioning && !require_existing;
+    
+    let identity = database.get_sso_identity_by_email(&provider.tenant_id, &user.email)
+        .map_err(|e| format!("Failed to fetch SSO identity: {}", e))?;
+    let membership = match &identity {
+        Some(identity) => database.get_tenant_user(&provider.tenant_id, &identity.user_id)
+            .map_err(|e| format!("Failed to fetch tenant user: {}", e))?,
+        None => None,
+    };
+    
+    let (membership, created) = match membership {
+        Some(mut member) => {
+            if member.status != "active" {
+                return Err(format!("Account for {} is {}", user.email, member.status));
+            }
+            // Group mappings are authoritative; otherwise keep the role an admin assigned
+            if let Some(role) = &user.mapped_role {
+                member.role = role.clone();
+            }
+            member.last_active_at = Some(now);
+            (member, false)
+        }
+        None if !jit_enabled => {
+            return Err(format!(
+                "No account exists for {} in this tenant and just-in-time provisioning is disabled",
+                user.email
+            ));
+        }
+        None => {
+            let member = TenantUserRecord {
+                id: Uuid::new_v4().to_string(),
+                tenant_id: provider.tenant_id.clone(),
+                user_id: identity.as_ref()
+                    .map(|i| i.user_id.clone())
+                    .unwrap_or_else(|| Uuid::new_v4().to_string()),
+                role: user.mapped_role.clone().unwrap_or_else(|| provider.default_role.clone()),
+                permissions: None,
+                invited_by: None,
+                invited_at: None,
+                joined_at: Some(now),
+                status: "active".to_string(),
+                last_active_at: Some(now),
+                created_at: now,
+                updated_at: now,
+            };
+            (member, true)
+        }
+    };
+    
+    database.save_tenant_user(&membership)
+        .map_err(|e| format!("Failed to save tenant user: {}", e))?;
+    
+    let identity = SSOIdentityRecord {
+        id: identity.as_ref().map(|i| i.id.clone()).unwrap_or_else(|| Uuid::new_v4().to_string()),
+        tenant_id: provider.tenant_id.clone(),
+        user_id: membership.user_id.clone(),
+        provider_id: provider.id.clone(),
+        subject: user.subject.clone(),
+        email: user.email.clone(),
+        first_name: user.first_name.clone(),
+        last_name: user.last_name.clone(),
+        display_name: user.display_name.clone(),
+        groups: Some(serde_json::to_string(&user.groups).unwrap_or_default()),
+        last_login_at: Some(now),
+        created_at: identity.as_ref().map(|i| i.created_at).unwrap_or(now),
+        updated_at: now,
+    };
+    database.save_sso_identity(&identity)
+        .map_err(|e| format!("Failed to save SSO identity: {}", e))?;
+    
+    Ok(SSOProvisionResult {
+        user_id: membership.user_id,
+        role: membership.role,
+        created,
+    })
+}
+
 /// Convert LDAPConfigRecord to LDAPConfig
 fn ldap_record_to_config(record: LDAPConfigRecord) -> LDAPConfig {
     LDAPConfig {
@@ -1314,3 +1560,146 @@ pub fn get_sso_commands() -> Vec<&'static str> {
         "get_sso_audit_log",
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::TenantRecord;
+    use serde_json::json;
+    
+    fn test_database(require_existing: bool) -> (Database, std::path::PathBuf) {
+        let temp_dir = std::env::temp_dir().join(format!("cube_sso_jit_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db = Database::new(temp_dir.clone()).unwrap();
+        
+        let settings = TenantSettings { sso_require_existing_account: require_existing, ..Default::default() };
+        db.save_tenant(&TenantRecord {
+            id: "t1".to_string(),
+            name: "Acme".to_string(),
+            slug: "acme".to_string(),
+            domain: None,
+            logo: None,
+            primary_color: None,
+            status: "active".to_string(),
+            subscription_tier: "enterprise".to_string(),
+            max_users: 100,
+            max_storage_gb: 10,
+            features: None,
+            settings: Some(serde_json::to_string(&settings).unwrap()),
+            billing_email: None,
+            billing_address: None,
+            stripe_customer_id: None,
+            stripe_subscription_id: None,
+            trial_ends_at: None,
+            created_at: 1,
+            updated_at: 1,
+        }).unwrap();
+        
+        (db, temp_dir)
+    }
+    
+    fn test_provider() -> SSOProvider {
+        let mut attribute_mapping = AttributeMapping::default();
+        attribute_mapping.group_roles.insert("admins".to_string(), "admin".to_string());
+        
+        record_to_provider(SSOProviderRecord {
+            id: "p1".to_string(),
+            tenant_id: "t1".to_string(),
+            name: "Okta".to_string(),
+            protocol: "oidc".to_string(),
+            enabled: true,
+            entity_id: None,
+            sso_url: None,
+            slo_url: None,
+            certificate: None,
+            client_id: None,
+            client_secret: None,
+            authorization_url: None,
+            token_url: None,
+            userinfo_url: None,
+            scopes: None,
+            attribute_mapping: Some(serde_json::to_string(&attribute_mapping).unwrap()),
+            jit_provisioning: true,
+            default_role: Some("member".to_string()),
+            allowed_domains: Some("corp.com".to_string()),
+            created_at: 1,
+            updated_at: 1,
+        })
+    }
+    
+    fn assertion(name: &str, groups: &[&str]) -> HashMap<String, serde_json::Value> {
+        HashMap::from([
+            ("sub".to_string(), json!("00u1")),
+            ("email".to_string(), json!("Jane@Corp.com")),
+            ("given_name".to_string(), json!(name)),
+            ("family_name".to_string(), json!("Doe")),
+            ("groups".to_string(), json!(groups)),
+        ])
+    }
+    
+    #[test]
+    fn test_jit_creates_account_from_attributes() {
+        let (db, temp_dir) = test_database(false);
+        let provider = test_provider();
+        
+        let user = map_sso_attributes(&provider, &assertion("Jane", &["engineering"])).unwrap();
+        assert_eq!(user.email, "jane@corp.com");
+        assert_eq!(user.display_name.as_deref(), Some("Jane Doe"));
+        
+        let result = provision_sso_user(&db, &provider, &user, false).unwrap();
+        assert!(result.created);
+        assert_eq!(result.role, "member");
+        
+        let member = db.get_tenant_user("t1", &result.user_id).unwrap().unwrap();
+        assert_eq!(member.status, "active");
+        let identity = db.get_sso_identity_by_email("t1", "jane@corp.com").unwrap().unwrap();
+        assert_eq!(identity.user_id, result.user_id);
+        assert_eq!(identity.subject.as_deref(), Some("00u1"));
+        
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+    
+    #[test]
+    fn test_relogin_updates_attributes_and_role() {
+        let (db, temp_dir) = test_database(false);
+        let provider = test_provider();
+        
+        let first = map_sso_attributes(&provider, &assertion("Jane", &[])).unwrap();
+        let created = provision_sso_user(&db, &provider, &first, false).unwrap();
+        
+        let second = map_sso_attributes(&provider, &assertion("Janet", &["admins"])).unwrap();
+        let updated = provision_sso_user(&db, &provider, &second, false).unwrap();
+        assert!(!updated.created);
+        assert_eq!(updated.user_id, created.user_id);
+        assert_eq!(updated.role, "admin");
+        
+        let identity = db.get_sso_identity_by_email("t1", "jane@corp.com").unwrap().unwrap();
+        assert_eq!(identity.first_name.as_deref(), Some("Janet"));
+        assert_eq!(identity.groups.as_deref(), Some("[\"admins\"]"));
+        assert_eq!(db.get_tenant_users("t1").unwrap().len(), 1);
+        
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+    
+    #[test]
+    fn test_unknown_user_rejected_without_jit() {
+        let (db, temp_dir) = test_database(true);
+        let provider = test_provider();
+        
+        let require_existing = tenant_requires_existing_account(&db, "t1").unwrap();
+        assert!(require_existing);
+        
+        let user = map_sso_attributes(&provider, &assertion("Jane", &[])).unwrap();
+        let err = provision_sso_user(&db, &provider, &user, require_existing).unwrap_err();
+        assert!(err.contains("just-in-time provisioning is disabled"));
+        assert!(db.get_tenant_users("t1").unwrap().is_empty());
+        
+        // A mapping pointing at an attribute the IdP doesn't send is reported clearly
+        let mut misconfigured = provider.clone();
+        misconfigured.attribute_mapping.email = "mail".to_string();
+        let err = map_sso_attributes(&misconfigured, &assertion("Jane", &[])).unwrap_err();
+        assert!(err.contains("email attribute 'mail' is missing"));
+        
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+}
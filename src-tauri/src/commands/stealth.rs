@@ -8,9 +8,14 @@
  * - Rate limiting (delays, robots.txt)
  */
 
+use crate::AppState;
+use crate::commands::browser_profile_commands::get_browser_profile;
 use crate::services::{
     stealth::{StealthService, StealthConfig, BrowserFingerprint},
-    proxy::{ProxyService, ProxyConfig, ProxyType, RotationStrategy},
+    proxy::{
+        ProxyService, ProxyConfig, ProxyType, RotationStrategy,
+        ProxyGeo, GeoFallback, GeoProxySelection, GeoVerification,
+    },
     captcha::{
         CaptchaService, CaptchaConfig, 
         RecaptchaV2Request, RecaptchaV3Request,
@@ -48,11 +53,23 @@ pub async fn stealth_get_config(
     state.stealth.get_config()
 }
 
+/// Fingerprint for a browsing session. Reusing `session_id` returns the same
+/// fingerprint and noise seed; with `profile_id` the browser profile's
+/// fingerprint settings are applied.
 #[tauri::command]
 pub async fn stealth_generate_fingerprint(
     state: State<'_, StealthState>,
+    app_state: State<'_, AppState>,
+    session_id: Option<String>,
+    profile_id: Option<String>,
 ) -> Result<BrowserFingerprint, String> {
-    state.stealth.generate_fingerprint()
+    let profile = match &profile_id {
+        Some(id) => Some(get_browser_profile(app_state, id.clone()).await?.stealth_fingerprint()),
+        None => None,
+    };
+    let session_id = session_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+    state.stealth.generate_session_fingerprint(&session_id, profile_id.as_deref(), profile.as_ref())
 }
 
 #[tauri::command]
@@ -102,6 +119,8 @@ pub struct ProxyInfo {
     pub proxy_type: String,
     pub username: Option<String>,
     pub enabled: bool,
+    /// Hop URLs traversed before this proxy, entry first
+    pub chain: Vec<String>,
     pub total_requests: u64,
     pub failed_requests: u64,
     pub avg_response_time_ms: u64,
@@ -109,6 +128,8 @@ pub struct ProxyInfo {
     pub last_success: Option<String>,
     pub last_failure: Option<String>,
     pub is_healthy: bool,
+    pub geo: Option<ProxyGeo>,
+    pub geo_mismatch: bool,
 }
 
 #[tauri::command]
@@ -127,6 +148,7 @@ pub async fn proxy_list(
             },
             username: config.username.clone(),
             enabled: config.enabled,
+            chain: config.chain.iter().map(|hop| hop.url.clone()).collect(),
             total_requests: stats.total_requests,
             failed_requests: stats.failed_requests,
             avg_response_time_ms: stats.avg_response_time_ms,
@@ -134,6 +156,8 @@ pub async fn proxy_list(
             last_success: stats.last_success,
             last_failure: stats.last_failure,
             is_healthy: stats.is_healthy,
+            geo: config.geo.clone(),
+            geo_mismatch: stats.geo_mismatch,
         }
     }).collect())
 }
@@ -161,13 +185,32 @@ pub async fn proxy_get_next(
     state.proxy.get_next_proxy()
 }
 
+/// Next healthy proxy exiting in `country` (ISO code), preferring `city`;
+/// falls back to the nearest country unless `fallback` is `None`
+#[tauri::command]
+pub async fn proxy_get_next_geo(
+    state: State<'_, StealthState>,
+    country: String,
+    city: Option<String>,
+    fallback: Option<GeoFallback>,
+) -> Result<GeoProxySelection, String> {
+    state.proxy.get_next_proxy_geo(&country, city.as_deref(), fallback.unwrap_or_default())
+}
+
+#[tauri::command]
+pub async fn proxy_verify_location(
+    state: State<'_, StealthState>,
+    url: String,
+) -> Result<GeoVerification, String> {
+    state.proxy.verify_exit_location(url).await
+}
+
 #[tauri::command]
 pub async fn proxy_check_health(
     state: State<'_, StealthState>,
     url: String,
-) -> Result<(), String> {
-    state.proxy.check_proxy_health(url).await?;
-    Ok(())
+) -> Result<bool, String> {
+    state.proxy.check_proxy_health(url).await
 }
 
 #[tauri::command]
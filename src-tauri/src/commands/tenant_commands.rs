@@ -17,10 +17,11 @@ use crate::AppState;
 use crate::database::{
     TenantRecord, TenantUserRecord, TenantInvitationRecord, 
     TenantRoleRecord, TenantAuditRecord,
+    ErasureMode, UserDataExport, UserErasureReport,
 };
 use serde::{Deserialize, Serialize};
 use tauri::{command, State};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use chrono::{DateTime, Utc, Duration};
 use uuid::Uuid;
 
@@ -88,6 +89,9 @@ pub struct TenantSettings {
     pub session_timeout_minutes: i32,
     pub ip_whitelist: Vec<String>,
     pub allowed_email_domains: Vec<String>,
+    /// Reject SSO logins for users without an account instead of provisioning them
+    #[serde(default)]
+    pub sso_require_existing_account: bool,
 }
 
 impl Default for TenantSettings {
@@ -102,6 +106,7 @@ impl Default for TenantSettings {
             session_timeout_minutes: 480,
             ip_whitelist: vec![],
             allowed_email_domains: vec![],
+            sso_require_existing_account: false,
         }
     }
 }
@@ -1951,6 +1956,99 @@ pub async fn disable_white_label(tenant_id: String) -> Result<bool, String> {
     Ok(true)
 }
 
+// ============================================================================
+// DATA PRIVACY (GDPR) COMMANDS
+// ============================================================================
+
+/// How long an erasure confirmation token stays valid
+const ERASURE_CONFIRMATION_TTL_MINUTES: i64 = 10;
+
+struct PendingErasure {
+    user_id: String,
+    mode: ErasureMode,
+    expires_at: DateTime<Utc>,
+}
+
+// Erasure confirmation tokens awaiting their second call
+static PENDING_ERASURES: Lazy<Mutex<HashMap<String, PendingErasure>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum UserErasureOutcome {
+    /// Nothing was erased yet; call again with `confirmation_token` to proceed
+    ConfirmationRequired {
+        confirmation_token: String,
+        expires_at: String,
+        records: BTreeMap<String, usize>,
+    },
+    Erased {
+        report: UserErasureReport,
+    },
+}
+
+/// Export all of a user's records across modules as a portable JSON archive
+#[command]
+pub async fn tenant_export_user_data(
+    state: State<'_, AppState>,
+    user_id: String,
+) -> Result<UserDataExport, String> {
+    state.database.export_user_data(&user_id)
+        .map_err(|e| format!("Failed to export user data: {}", e))
+}
+
+/// Anonymize or hard-delete a user's records across modules.
+///
+/// The first call (without `confirmation_token`) erases nothing and returns a
+/// token plus the records that would be affected; repeating the call with
+/// that token within 10 minutes performs the erasure.
+#[command]
+pub async fn tenant_erase_user_data(
+    state: State<'_, AppState>,
+    user_id: String,
+    mode: ErasureMode,
+    confirmation_token: Option<String>,
+) -> Result<UserErasureOutcome, String> {
+    let now = Utc::now();
+    let mut pending = PENDING_ERASURES.lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    pending.retain(|_, erasure| erasure.expires_at > now);
+
+    let Some(token) = confirmation_token else {
+        let records = state.database.export_user_data(&user_id)
+            .map_err(|e| format!("Failed to inspect user data: {}", e))?
+            .record_counts();
+        let token = generate_invitation_token();
+        let expires_at = now + Duration::minutes(ERASURE_CONFIRMATION_TTL_MINUTES);
+        pending.insert(token.clone(), PendingErasure { user_id, mode, expires_at });
+        return Ok(UserErasureOutcome::ConfirmationRequired {
+            confirmation_token: token,
+            expires_at: expires_at.to_rfc3339(),
+            records,
+        });
+    };
+
+    match pending.get(&token) {
+        Some(erasure) if erasure.user_id == user_id && erasure.mode == mode => {
+            pending.remove(&token);
+        }
+        Some(_) => return Err("Confirmation token was issued for a different user or mode".to_string()),
+        None => return Err("Invalid or expired confirmation token".to_string()),
+    }
+    drop(pending);
+
+    let report = state.database.erase_user_data(&user_id, mode)
+        .map_err(|e| format!("Failed to erase user data: {}", e))?;
+    log::info!(
+        "🗑️ User data erasure {} completed ({:?}, {} tables affected)",
+        report.erasure_id,
+        report.mode,
+        report.affected.len()
+    );
+
+    Ok(UserErasureOutcome::Erased { report })
+}
+
 // ============================================================================
 // HELPER FUNCTIONS
 // ============================================================================
@@ -2128,5 +2226,8 @@ pub fn get_tenant_commands() -> Vec<&'static str> {
         "get_tenant_white_label_config",
         "update_tenant_white_label_config",
         "disable_white_label",
+        // Data Privacy
+        "tenant_export_user_data",
+        "tenant_erase_user_data",
     ]
 }
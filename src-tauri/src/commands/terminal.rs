@@ -1,4 +1,4 @@
-use crate::models::terminal::{TerminalSession, CommandHistory, TerminalConfig, TerminalStats};
+use crate::models::terminal::{TerminalSession, CommandHistory, TerminalConfig, TerminalStats, FuzzyHistoryMatch};
 use crate::services::terminal_service::TerminalService;
 use tauri::State;
 
@@ -74,6 +74,15 @@ pub async fn search_terminal_history(
     terminal_service.search_history(&query, limit)
 }
 
+#[tauri::command]
+pub async fn terminal_fuzzy_search(
+    query: String,
+    limit: i32,
+    terminal_service: State<'_, TerminalService>,
+) -> Result<Vec<FuzzyHistoryMatch>, String> {
+    terminal_service.fuzzy_search_history(&query, limit)
+}
+
 #[tauri::command]
 pub async fn clear_terminal_session_history(
     session_id: String,
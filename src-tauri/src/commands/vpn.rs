@@ -1,6 +1,9 @@
+use crate::services::vpn_dns_guard::{DnsLeakTestResult, DnsProtectionSettings, DnsProtectionStatus, LeakTestTargets, VpnDnsGuard};
+use crate::services::vpn_latency::{LatencyCandidate, LatencyMeasurement, SpeedTestResult, VpnLatencyTester};
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
-use tauri::State;
+use crate::commands::cube_web_engine_commands::CubeWebEngineGlobalState;
+use tauri::{AppHandle, Manager, State};
 
 // ============================================================================
 // TYPES & STRUCTURES
@@ -334,6 +337,8 @@ pub struct VPNConfig {
     pub protocol: String, // "OpenVPN" | "WireGuard"
     pub dns_servers: Vec<String>,
     pub split_tunneling: SplitTunnelConfig,
+    #[serde(default)]
+    pub dns_protection: DnsProtectionSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -364,6 +369,8 @@ pub struct VPNState {
     config: Mutex<VPNConfig>,
     servers: Mutex<Vec<VPNServer>>,
     connection_logs: Mutex<Vec<ConnectionLog>>,
+    dns_guard: VpnDnsGuard,
+    latency: VpnLatencyTester,
 }
 
 pub struct AdBlockerState {
@@ -688,9 +695,12 @@ impl Default for VPNState {
                     apps: vec![],
                     domains: vec![],
                 },
+                dns_protection: DnsProtectionSettings::default(),
             }),
             servers: Mutex::new(Self::get_default_servers()),
             connection_logs: Mutex::new(vec![]),
+            dns_guard: VpnDnsGuard::default(),
+            latency: VpnLatencyTester::default(),
         }
     }
 }
@@ -815,6 +825,37 @@ impl VPNState {
 // HELPER FUNCTIONS
 // ============================================================================
 
+/// Latency candidates for the given servers (all of them when `server_ids` is empty)
+fn latency_candidates(servers: &[VPNServer], server_ids: &[String]) -> Result<Vec<LatencyCandidate>, String> {
+    if let Some(missing) = server_ids.iter().find(|id| !servers.iter().any(|s| &s.id == *id)) {
+        return Err(format!("Server not found: {}", missing));
+    }
+
+    Ok(servers
+        .iter()
+        .filter(|s| server_ids.is_empty() || server_ids.contains(&s.id))
+        .map(|s| LatencyCandidate {
+            server_id: s.id.clone(),
+            address: s.ip.clone(),
+            load: s.load,
+        })
+        .collect())
+}
+
+/// Store measured latencies as the servers' ping
+fn apply_measurements(state: &VPNState, measurements: &[LatencyMeasurement]) -> Result<(), String> {
+    let mut servers = state
+        .servers
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    for m in measurements {
+        if let (Some(server), Some(latency)) = (servers.iter_mut().find(|s| s.id == m.server_id), m.latency_ms) {
+            server.ping = latency.min(u16::MAX as u32) as u16;
+        }
+    }
+    Ok(())
+}
+
 /// Get current public IP address
 async fn get_public_ip() -> Result<String, String> {
     // Try multiple services for redundancy
@@ -892,8 +933,10 @@ pub async fn get_vpn_status(state: State<'_, VPNState>) -> Result<VPNStatus, Str
 /// Connect to a VPN server
 #[tauri::command]
 pub async fn connect_vpn(
+    app: AppHandle,
     server_id: String,
     state: State<'_, VPNState>,
+    threat_state: State<'_, ThreatProtectionState>,
 ) -> Result<VPNStatus, String> {
     // Find the server
     let server = {
@@ -958,6 +1001,38 @@ pub async fn connect_vpn(
                 msg,
             );
 
+            // Force DNS through the tunnel
+            let (dns_protection, dns_servers) = {
+                let config = state
+                    .config
+                    .lock()
+                    .map_err(|e| format!("Lock error: {}", e))?;
+                (config.dns_protection.clone(), config.dns_servers.clone())
+            };
+            // The engine's own resolver must not bypass the tunnel either
+            if let Some(engine) = app.try_state::<CubeWebEngineGlobalState>() {
+                engine.dns.set_tunnel_resolvers(&dns_servers);
+            }
+            if dns_protection.enabled {
+                state
+                    .dns_guard
+                    .set_blocked_categories(threat_state.blocked_dns_categories());
+                match state.dns_guard.enable(dns_protection, &dns_servers).await {
+                    Ok(_) => state.add_log(
+                        String::from("dns_protection"),
+                        Some(server.name.clone()),
+                        true,
+                        String::from("DNS leak protection enabled"),
+                    ),
+                    Err(e) => state.add_log(
+                        String::from("dns_protection"),
+                        Some(server.name.clone()),
+                        false,
+                        format!("DNS leak protection failed: {}", e),
+                    ),
+                }
+            }
+
             Ok(new_status)
         }
         Err(e) => {
@@ -974,7 +1049,7 @@ pub async fn connect_vpn(
 
 /// Disconnect from VPN
 #[tauri::command]
-pub async fn disconnect_vpn(state: State<'_, VPNState>) -> Result<VPNStatus, String> {
+pub async fn disconnect_vpn(app: AppHandle, state: State<'_, VPNState>) -> Result<VPNStatus, String> {
     // Check if connected
     let was_connected = {
         let status = state
@@ -991,6 +1066,19 @@ pub async fn disconnect_vpn(state: State<'_, VPNState>) -> Result<VPNStatus, Str
     // Execute disconnection
     match execute_vpn_command("disconnect", None) {
         Ok(msg) => {
+            // Restore the DNS configuration saved on connect
+            if let Err(e) = state.dns_guard.disable() {
+                state.add_log(
+                    String::from("dns_protection"),
+                    None,
+                    false,
+                    format!("Failed to restore DNS: {}", e),
+                );
+            }
+            if let Some(engine) = app.try_state::<CubeWebEngineGlobalState>() {
+                engine.dns.clear_tunnel_resolvers();
+            }
+
             // Get new public IP (real one)
             let new_ip = get_public_ip()
                 .await
@@ -1026,6 +1114,101 @@ pub async fn disconnect_vpn(state: State<'_, VPNState>) -> Result<VPNStatus, Str
     }
 }
 
+/// Measure handshake latency to the given servers (all servers when empty).
+/// Unreachable servers are reported with `reachable: false`.
+#[tauri::command]
+pub async fn vpn_test_server_latency(
+    server_ids: Vec<String>,
+    state: State<'_, VPNState>,
+) -> Result<Vec<LatencyMeasurement>, String> {
+    let candidates = {
+        let servers = state
+            .servers
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        latency_candidates(&servers, &server_ids)?
+    };
+
+    let measurements = state.latency.measure(&candidates).await;
+    apply_measurements(&state, &measurements)?;
+    Ok(measurements)
+}
+
+/// Connect to the lowest-latency server, optionally limited to one country
+/// and preferring less loaded servers among near-equal latencies
+#[tauri::command]
+pub async fn vpn_connect_fastest(
+    app: AppHandle,
+    country: Option<String>,
+    prefer_least_loaded: Option<bool>,
+    state: State<'_, VPNState>,
+    threat_state: State<'_, ThreatProtectionState>,
+) -> Result<VPNStatus, String> {
+    let candidates = {
+        let servers = state
+            .servers
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        let in_country: Vec<VPNServer> = servers
+            .iter()
+            .filter(|s| match &country {
+                Some(c) => s.country.eq_ignore_ascii_case(c),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        if in_country.is_empty() {
+            return Err(format!("No servers in {}", country.unwrap_or_default()));
+        }
+        latency_candidates(&in_country, &[])?
+    };
+
+    let (server, measurement) = state
+        .latency
+        .fastest(&candidates, prefer_least_loaded.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())?;
+    apply_measurements(&state, std::slice::from_ref(&measurement))?;
+
+    state.add_log(
+        String::from("auto_select"),
+        Some(server.server_id.clone()),
+        true,
+        format!("Selected {} ({} ms)", server.server_id, measurement.latency_ms.unwrap_or_default()),
+    );
+
+    connect_vpn(app, server.server_id, state, threat_state).await
+}
+
+/// Measure download/upload throughput and latency through the tunnel
+#[tauri::command]
+pub async fn vpn_speed_test(state: State<'_, VPNState>) -> Result<SpeedTestResult, String> {
+    let server_id = {
+        let status = state
+            .current_status
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        if !status.connected {
+            return Err(String::from("Connect to a VPN server before running a speed test"));
+        }
+        status.server.as_ref().map(|s| s.id.clone())
+    };
+
+    let result = crate::services::vpn_latency::run_speed_test(server_id.clone())
+        .await
+        .map_err(|e| format!("Speed test failed: {}", e));
+    state.add_log(
+        String::from("speed_test"),
+        server_id,
+        result.is_ok(),
+        match &result {
+            Ok(r) => format!("{:.1} Mbps down, {:.1} Mbps up, {} ms", r.download_mbps, r.upload_mbps, r.latency_ms),
+            Err(e) => e.clone(),
+        },
+    );
+    result
+}
+
 /// Get current VPN configuration
 #[tauri::command]
 pub async fn get_vpn_config(state: State<'_, VPNState>) -> Result<VPNConfig, String> {
@@ -1058,6 +1241,41 @@ pub async fn update_vpn_config(
     Ok(config)
 }
 
+/// Check whether DNS queries leave the tunnel
+#[tauri::command]
+pub async fn vpn_test_dns_leak(state: State<'_, VPNState>) -> Result<DnsLeakTestResult, String> {
+    let (connected, server_ip) = {
+        let status = state
+            .current_status
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        (status.connected, status.server.as_ref().map(|s| s.ip.clone()))
+    };
+
+    let targets = tokio::task::spawn_blocking(LeakTestTargets::from_system)
+        .await
+        .map_err(|e| format!("Failed to read system DNS: {}", e))?;
+    let result = state
+        .dns_guard
+        .test_leak(targets, connected, &server_ip.into_iter().collect::<Vec<_>>())
+        .await;
+
+    state.add_log(
+        String::from("dns_leak_test"),
+        None,
+        !result.leak_detected,
+        result.message.clone(),
+    );
+
+    Ok(result)
+}
+
+/// Get DNS leak protection status
+#[tauri::command]
+pub async fn vpn_get_dns_protection_status(state: State<'_, VPNState>) -> Result<DnsProtectionStatus, String> {
+    Ok(state.dns_guard.status())
+}
+
 /// Toggle kill switch
 #[tauri::command]
 pub async fn toggle_kill_switch(enabled: bool, state: State<'_, VPNState>) -> Result<bool, String> {
@@ -1989,23 +2207,45 @@ pub async fn get_threat_protection_config(state: State<'_, ThreatProtectionState
     state.config.lock().map(|c| c.clone()).map_err(|e| format!("Lock error: {}", e))
 }
 
+impl ThreatProtectionState {
+    /// Category ids the VPN DNS stub should answer with NXDOMAIN
+    fn blocked_dns_categories(&self) -> Vec<String> {
+        self.config
+            .lock()
+            .map(|c| {
+                if !c.enabled {
+                    return vec![];
+                }
+                c.dns_categories.iter().filter(|cat| cat.blocked).map(|cat| cat.id.clone()).collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
 #[tauri::command]
-pub async fn toggle_threat_protection(enabled: bool, state: State<'_, ThreatProtectionState>) -> Result<bool, String> {
-    let mut config = state.config.lock().map_err(|e| format!("Lock error: {}", e))?;
-    config.enabled = enabled;
+pub async fn toggle_threat_protection(enabled: bool, state: State<'_, ThreatProtectionState>, vpn_state: State<'_, VPNState>) -> Result<bool, String> {
+    {
+        let mut config = state.config.lock().map_err(|e| format!("Lock error: {}", e))?;
+        config.enabled = enabled;
+    }
+    vpn_state.dns_guard.set_blocked_categories(state.blocked_dns_categories());
     Ok(enabled)
 }
 
 #[tauri::command]
-pub async fn toggle_dns_category(category_id: String, blocked: bool, state: State<'_, ThreatProtectionState>) -> Result<DNSCategory, String> {
-    let mut config = state.config.lock().map_err(|e| format!("Lock error: {}", e))?;
-    for cat in &mut config.dns_categories {
-        if cat.id == category_id {
-            cat.blocked = blocked;
-            return Ok(cat.clone());
-        }
-    }
-    Err(format!("Category not found: {}", category_id))
+pub async fn toggle_dns_category(category_id: String, blocked: bool, state: State<'_, ThreatProtectionState>, vpn_state: State<'_, VPNState>) -> Result<DNSCategory, String> {
+    let category = {
+        let mut config = state.config.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let cat = config
+            .dns_categories
+            .iter_mut()
+            .find(|cat| cat.id == category_id)
+            .ok_or_else(|| format!("Category not found: {}", category_id))?;
+        cat.blocked = blocked;
+        cat.clone()
+    };
+    vpn_state.dns_guard.set_blocked_categories(state.blocked_dns_categories());
+    Ok(category)
 }
 
 #[tauri::command]
@@ -1,7 +1,9 @@
 // Workflow Commands - Save, Load, Execute
 // Backend support for visual workflow builder
 
+use crate::commands::workflow_commands::subworkflow_reference_errors;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use tauri::{AppHandle, Manager};
@@ -154,10 +156,27 @@ pub async fn canvas_execute_workflow(
     })
 }
 
+/// Workflow ids called by the canvas's sub-workflow nodes
+fn canvas_subworkflow_references(nodes: &[serde_json::Value]) -> Vec<String> {
+    nodes
+        .iter()
+        .filter(|node| node.get("type").and_then(|t| t.as_str()) == Some("subWorkflow"))
+        .filter_map(|node| {
+            let data = node.get("data")?;
+            data.get("workflowId")
+                .or_else(|| data.get("config").and_then(|c| c.get("workflowId")))
+                .and_then(|id| id.as_str())
+                .map(str::to_string)
+        })
+        .collect()
+}
+
 #[tauri::command]
 pub async fn canvas_validate_workflow(
+    app: AppHandle,
     nodes: Vec<serde_json::Value>,
     edges: Vec<serde_json::Value>,
+    workflow_id: Option<String>,
 ) -> Result<Vec<String>, String> {
     let mut errors = Vec::new();
 
@@ -193,5 +212,19 @@ pub async fn canvas_validate_workflow(
     // Check for cycles (simplified check)
     // Real implementation would do proper cycle detection
 
+    // Check sub-workflow references against saved workflows, using the
+    // unsaved nodes for this workflow
+    let references = canvas_subworkflow_references(&nodes);
+    if !references.is_empty() {
+        let root = workflow_id.unwrap_or_else(|| "(this workflow)".to_string());
+        let mut graph: HashMap<String, Vec<String>> = canvas_list_workflows(app)
+            .await?
+            .into_iter()
+            .map(|w| (w.id.clone(), canvas_subworkflow_references(&w.nodes)))
+            .collect();
+        graph.insert(root.clone(), references);
+        errors.extend(subworkflow_reference_errors(&root, &graph));
+    }
+
     Ok(errors)
 }
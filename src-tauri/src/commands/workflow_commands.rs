@@ -1,16 +1,76 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::sync::Mutex;
 use crate::services::browser_service::BrowserService;
 use crate::services::ai_service::{AIService, AIRequest};
+use crate::services::metrics::{MetricsService, NodeMetric};
+use crate::services::notifications_service::{
+    Notification, NotificationChannel, NotificationPriority, NotificationsService,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowNode {
     pub id: String,
     pub node_type: String,
     pub data: serde_json::Value,
+    /// Retry transient failures instead of failing the run on the first one
+    #[serde(default)]
+    pub retry: Option<NodeRetryPolicy>,
+    /// A failed node (after any retries) is recorded and the run carries on
+    #[serde(default, alias = "continueOnError")]
+    pub continue_on_error: bool,
+}
+
+/// How a failing node is retried: up to `max_attempts` tries in total, waiting
+/// `base_delay_ms * backoff_multiplier^(attempt - 1)` between them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NodeRetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay_ms: u64,
+    pub backoff_multiplier: f64,
+    /// Up to this fraction of each delay is added at random so retries of
+    /// parallel runs don't line up
+    pub jitter: f64,
+    /// Only errors containing one of these (case-insensitive) are retried;
+    /// when empty every failure is
+    pub retryable_errors: Vec<String>,
+}
+
+impl Default for NodeRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 1000,
+            backoff_multiplier: 2.0,
+            jitter: 0.2,
+            retryable_errors: Vec::new(),
+        }
+    }
+}
+
+impl NodeRetryPolicy {
+    const MAX_DELAY_MS: f64 = 5.0 * 60.0 * 1000.0;
+
+    fn is_retryable(&self, error: &str) -> bool {
+        let error = error.to_lowercase();
+        self.retryable_errors.is_empty()
+            || self.retryable_errors.iter().any(|pattern| error.contains(&pattern.to_lowercase()))
+    }
+
+    /// How long to wait after failed attempt number `attempt`
+    fn delay_after(&self, attempt: u32) -> std::time::Duration {
+        let exponent = attempt.saturating_sub(1).min(32) as i32;
+        let delay = self.base_delay_ms as f64 * self.backoff_multiplier.max(1.0).powi(exponent);
+        let jitter = delay * self.jitter.clamp(0.0, 1.0) * rand::random::<f64>();
+        std::time::Duration::from_millis((delay + jitter).min(Self::MAX_DELAY_MS) as u64)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +78,10 @@ pub struct WorkflowEdge {
     pub id: String,
     pub source: String,
     pub target: String,
+    /// Output handle on the source node; branching nodes (e.g. `approval`)
+    /// only follow edges whose handle matches the branch they took
+    #[serde(default, alias = "sourceHandle")]
+    pub source_handle: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,10 +90,27 @@ pub struct Workflow {
     pub name: String,
     pub nodes: Vec<WorkflowNode>,
     pub edges: Vec<WorkflowEdge>,
+    /// Parameters a parent must (or may) pass when invoking this workflow as a sub-workflow
+    #[serde(default)]
+    pub inputs: Vec<WorkflowParam>,
+    /// Variables handed back to the parent when run as a sub-workflow
+    #[serde(default)]
+    pub outputs: Vec<WorkflowParam>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowParam {
+    pub name: String,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeResult {
     pub success: bool,
@@ -37,9 +118,99 @@ pub struct NodeResult {
     pub error: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowRunResult {
+    pub workflow_id: String,
+    pub outputs: serde_json::Map<String, serde_json::Value>,
+    pub variables: serde_json::Map<String, serde_json::Value>,
+    pub node_results: Vec<(String, NodeResult)>,
+    #[serde(default)]
+    pub execution_id: String,
+    /// Set when the run stopped at an approval node; continue it with `workflow_approve_step`
+    #[serde(default)]
+    pub pending_approval: Option<PendingApproval>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalDecision {
+    #[serde(alias = "approve")]
+    Approved,
+    #[serde(alias = "reject")]
+    Rejected,
+}
+
+impl ApprovalDecision {
+    /// Edge handle followed after this decision
+    pub fn branch(self) -> &'static str {
+        match self {
+            ApprovalDecision::Approved => "approved",
+            ApprovalDecision::Rejected => "rejected",
+        }
+    }
+}
+
+/// Progress of a run, enough to pick it up again after an approval pause
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkflowCheckpoint {
+    pub variables: serde_json::Map<String, serde_json::Value>,
+    pub node_results: Vec<(String, NodeResult)>,
+    /// Branch handle taken by each branching node
+    #[serde(default)]
+    pub branches: HashMap<String, String>,
+}
+
+/// A run parked at an approval node until the approver decides or the timeout passes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingApproval {
+    pub execution_id: String,
+    pub node_id: String,
+    pub approver: Option<String>,
+    pub message: String,
+    pub requested_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Decision applied once `expires_at` has passed
+    pub default_action: ApprovalDecision,
+    /// The workflow as it was when the run started
+    pub workflow: Workflow,
+    pub checkpoint: WorkflowCheckpoint,
+}
+
+impl PendingApproval {
+    fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|at| now >= at)
+    }
+
+    /// Records the decision as the approval node's result and returns where to resume
+    fn decide(self, decision: ApprovalDecision, note: Option<String>, decided_by: &str) -> (Workflow, WorkflowCheckpoint) {
+        let mut checkpoint = self.checkpoint;
+        let data = serde_json::json!({
+            "decision": decision,
+            "branch": decision.branch(),
+            "note": note,
+            "approver": self.approver,
+            "decidedBy": decided_by,
+            "decidedAt": Utc::now().to_rfc3339(),
+        });
+        checkpoint.branches.insert(self.node_id.clone(), decision.branch().to_string());
+        checkpoint.variables.insert(self.node_id.clone(), data.clone());
+        checkpoint.node_results.push((self.node_id, NodeResult { success: true, data, error: None }));
+        (self.workflow, checkpoint)
+    }
+}
+
+/// Maximum nesting of sub-workflow calls, guarding against runaway recursion
+pub const MAX_SUBWORKFLOW_DEPTH: usize = 8;
+
 pub struct WorkflowState {
     workflows: Arc<Mutex<HashMap<String, Workflow>>>,
     executions: Arc<Mutex<HashMap<String, Vec<NodeResult>>>>,
+    /// Runs waiting on an approver, keyed by execution id
+    approvals: Arc<Mutex<HashMap<String, PendingApproval>>>,
+    approvals_path: Option<PathBuf>,
+    notifications: Option<Arc<NotificationsService>>,
+    /// Where runs and each node attempt are reported (`metrics_get_execution`)
+    metrics: Option<Arc<MetricsService>>,
 }
 
 impl WorkflowState {
@@ -47,6 +218,272 @@ impl WorkflowState {
         Self {
             workflows: Arc::new(Mutex::new(HashMap::new())),
             executions: Arc::new(Mutex::new(HashMap::new())),
+            approvals: Arc::new(Mutex::new(HashMap::new())),
+            approvals_path: None,
+            notifications: None,
+            metrics: None,
+        }
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<MetricsService>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Keeps pending approvals in `path` so paused runs survive a restart, and
+    /// tells approvers about new requests through `notifications`
+    pub fn with_approval_storage(path: PathBuf, notifications: Option<Arc<NotificationsService>>) -> Self {
+        let approvals: HashMap<String, PendingApproval> = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| {
+                serde_json::from_str(&json)
+                    .map_err(|e| log::warn!("Ignoring unreadable pending approvals in {:?}: {}", path, e))
+                    .ok()
+            })
+            .unwrap_or_default();
+        if !approvals.is_empty() {
+            log::info!("Restored {} workflow run(s) waiting for approval", approvals.len());
+        }
+
+        Self {
+            approvals: Arc::new(Mutex::new(approvals)),
+            approvals_path: Some(path),
+            notifications,
+            ..Self::new()
+        }
+    }
+
+    pub async fn pending_approvals(&self) -> Vec<PendingApproval> {
+        self.approvals.lock().await.values().cloned().collect()
+    }
+
+    async fn run<'f, F>(
+        &self,
+        workflow_id: &str,
+        inputs: serde_json::Map<String, serde_json::Value>,
+        execute: &F,
+    ) -> Result<WorkflowRunResult, String>
+    where
+        F: Fn(WorkflowNode) -> NodeFuture<'f> + Sync,
+    {
+        let workflows = self.workflows.lock().await.clone();
+        let workflow = workflows
+            .get(workflow_id)
+            .ok_or_else(|| format!("Workflow not found: {}", workflow_id))?;
+        let inputs = bind_workflow_inputs(workflow, inputs)?;
+
+        let execution_id = uuid::Uuid::new_v4().to_string();
+        self.track_execution(&execution_id, workflow);
+        let record = |metric: NodeMetric| self.record_attempt(&execution_id, metric);
+        let retrying = |node: WorkflowNode| -> NodeFuture<'_> {
+            Box::pin(execute_with_retry(node, execute, &record))
+        };
+        let result = run_workflow(&workflows, workflow, inputs, vec![workflow_id.to_string()], &retrying).await;
+        self.finish(execution_id, result).await
+    }
+
+    async fn approve_step<'f, F>(
+        &self,
+        execution_id: &str,
+        decision: ApprovalDecision,
+        note: Option<String>,
+        now: DateTime<Utc>,
+        execute: &F,
+    ) -> Result<WorkflowRunResult, String>
+    where
+        F: Fn(WorkflowNode) -> NodeFuture<'f> + Sync,
+    {
+        let pending = {
+            let mut approvals = self.approvals.lock().await;
+            let pending = approvals
+                .get(execution_id)
+                .ok_or_else(|| format!("No approval is pending for execution {}", execution_id))?;
+            if pending.is_expired(now) {
+                return Err(format!(
+                    "Approval for execution {} timed out; the default action ({}) will be taken",
+                    execution_id,
+                    pending.default_action.branch()
+                ));
+            }
+            let pending = approvals.remove(execution_id).expect("checked above");
+            self.save_approvals(&approvals);
+            pending
+        };
+
+        log::info!("Approval for execution {} {}", execution_id, decision.branch());
+        self.resume(pending, decision, note, "approver", execute).await
+    }
+
+    /// Takes the default action on every approval whose timeout has passed by `now`
+    async fn resolve_expired_approvals<'f, F>(
+        &self,
+        now: DateTime<Utc>,
+        execute: &F,
+    ) -> Vec<Result<WorkflowRunResult, String>>
+    where
+        F: Fn(WorkflowNode) -> NodeFuture<'f> + Sync,
+    {
+        let expired: Vec<PendingApproval> = {
+            let mut approvals = self.approvals.lock().await;
+            let ids: Vec<String> = approvals.iter()
+                .filter(|(_, p)| p.is_expired(now))
+                .map(|(id, _)| id.clone())
+                .collect();
+            if ids.is_empty() {
+                return Vec::new();
+            }
+            let expired = ids.iter().filter_map(|id| approvals.remove(id)).collect();
+            self.save_approvals(&approvals);
+            expired
+        };
+
+        let mut results = Vec::with_capacity(expired.len());
+        for pending in expired {
+            log::info!(
+                "Approval for execution {} timed out, taking the {} branch",
+                pending.execution_id,
+                pending.default_action.branch()
+            );
+            let decision = pending.default_action;
+            results.push(self.resume(pending, decision, None, "timeout", execute).await);
+        }
+        results
+    }
+
+    async fn resume<'f, F>(
+        &self,
+        pending: PendingApproval,
+        decision: ApprovalDecision,
+        note: Option<String>,
+        decided_by: &str,
+        execute: &F,
+    ) -> Result<WorkflowRunResult, String>
+    where
+        F: Fn(WorkflowNode) -> NodeFuture<'f> + Sync,
+    {
+        let execution_id = pending.execution_id.clone();
+        let (workflow, checkpoint) = pending.decide(decision, note, decided_by);
+        let workflows = self.workflows.lock().await.clone();
+
+        self.track_execution(&execution_id, &workflow);
+        let record = |metric: NodeMetric| self.record_attempt(&execution_id, metric);
+        let retrying = |node: WorkflowNode| -> NodeFuture<'_> {
+            Box::pin(execute_with_retry(node, execute, &record))
+        };
+        let result = resume_workflow(&workflows, &workflow, checkpoint, vec![workflow.id.clone()], &retrying).await;
+        self.finish(execution_id, result).await
+    }
+
+    /// Parks a paused run (persisting it and notifying the approver) or records a finished one
+    async fn finish(
+        &self,
+        execution_id: String,
+        result: Result<WorkflowRunResult, String>,
+    ) -> Result<WorkflowRunResult, String> {
+        let mut result = match result {
+            Ok(result) => result,
+            Err(error) => {
+                self.complete_execution(&execution_id, Some(error.clone()));
+                return Err(error);
+            }
+        };
+        result.execution_id = execution_id.clone();
+
+        match result.pending_approval.as_mut() {
+            Some(pending) => {
+                pending.execution_id = execution_id.clone();
+                let mut approvals = self.approvals.lock().await;
+                approvals.insert(execution_id, pending.clone());
+                self.save_approvals(&approvals);
+                drop(approvals);
+                self.notify_approver(pending);
+            }
+            None => {
+                self.executions.lock().await.insert(
+                    result.workflow_id.clone(),
+                    result.node_results.iter().map(|(_, r)| r.clone()).collect(),
+                );
+                self.complete_execution(&execution_id, None);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Starts the metrics for a run, unless it is already tracked (a run resumed
+    /// after an approval in the same session)
+    fn track_execution(&self, execution_id: &str, workflow: &Workflow) {
+        let Some(metrics) = &self.metrics else { return };
+        if metrics.get_execution(execution_id).ok().flatten().is_some() {
+            return;
+        }
+        if let Err(e) = metrics.start_execution(
+            execution_id.to_string(),
+            workflow.id.clone(),
+            workflow.name.clone(),
+            workflow.nodes.len(),
+        ) {
+            log::warn!("Could not track execution {}: {}", execution_id, e);
+        }
+    }
+
+    fn record_attempt(&self, execution_id: &str, metric: NodeMetric) {
+        let Some(metrics) = &self.metrics else { return };
+        if metric.attempt == 1 {
+            let _ = metrics.update_current_node(execution_id, metric.node_id.clone());
+        }
+        if let Err(e) = metrics.record_node_attempt(execution_id, metric) {
+            log::warn!("Could not record node attempt for execution {}: {}", execution_id, e);
+        }
+    }
+
+    fn complete_execution(&self, execution_id: &str, error: Option<String>) {
+        let Some(metrics) = &self.metrics else { return };
+        if let Err(e) = metrics.complete_execution(execution_id, error.is_none(), error) {
+            log::warn!("Could not complete metrics for execution {}: {}", execution_id, e);
+        }
+    }
+
+    fn save_approvals(&self, approvals: &HashMap<String, PendingApproval>) {
+        let Some(path) = &self.approvals_path else { return };
+        let written = serde_json::to_string_pretty(approvals)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            log::error!("Failed to persist pending approvals to {:?}: {}", path, e);
+        }
+    }
+
+    fn notify_approver(&self, pending: &PendingApproval) {
+        let Some(notifications) = &self.notifications else { return };
+
+        let data = [
+            ("executionId", serde_json::json!(pending.execution_id)),
+            ("workflowId", serde_json::json!(pending.workflow.id)),
+            ("nodeId", serde_json::json!(pending.node_id)),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        let notification = Notification {
+            id: None,
+            user_id: pending.approver.clone().unwrap_or_else(|| "local".to_string()),
+            organization_id: None,
+            notification_type: "workflow_approval".to_string(),
+            channel: NotificationChannel::InApp,
+            priority: NotificationPriority::High,
+            category: "workflow".to_string(),
+            title: format!("Approval needed: {}", pending.workflow.name),
+            body: pending.message.clone(),
+            data: Some(data),
+            action_url: None,
+            image_url: None,
+            icon: None,
+            scheduled_at: None,
+            expires_at: pending.expires_at.map(|at| at.timestamp_millis()),
+            metadata: None,
+        };
+        if let Err(e) = notifications.send_notification(&notification) {
+            log::warn!("Failed to notify approver for execution {}: {}", pending.execution_id, e);
         }
     }
 }
@@ -54,12 +491,120 @@ impl WorkflowState {
 #[tauri::command]
 pub async fn execute_workflow_node(
     node: WorkflowNode,
-    _state: State<'_, WorkflowState>,
+    state: State<'_, WorkflowState>,
     browser: State<'_, Arc<BrowserService>>,
     ai_service: State<'_, AIService>,
 ) -> Result<NodeResult, String> {
     log::info!("Executing workflow node: {} (type: {})", node.id, node.node_type);
 
+    let (browser, ai_service): (&BrowserService, &AIService) = (&browser, &ai_service);
+    let execute = |node: WorkflowNode| -> NodeFuture<'_> {
+        Box::pin(execute_node(node, browser, ai_service))
+    };
+    if node.node_type == "subWorkflow" {
+        let workflows = state.workflows.lock().await.clone();
+        let mut variables = serde_json::Map::new();
+        return execute_subworkflow_node(&workflows, &node, &mut variables, &[], &execute).await;
+    }
+
+    execute_with_retry(node, &execute, &|_| {}).await
+}
+
+/// Run a whole saved workflow, including any sub-workflows it invokes. A run that
+/// reaches an approval node comes back with `pending_approval` set.
+#[tauri::command]
+pub async fn workflow_execute(
+    workflow_id: String,
+    inputs: Option<serde_json::Map<String, serde_json::Value>>,
+    app: AppHandle,
+    state: State<'_, WorkflowState>,
+    browser: State<'_, Arc<BrowserService>>,
+    ai_service: State<'_, AIService>,
+) -> Result<WorkflowRunResult, String> {
+    log::info!("Executing workflow: {}", workflow_id);
+
+    let (browser, ai_service): (&BrowserService, &AIService) = (&browser, &ai_service);
+    let execute = |node: WorkflowNode| -> NodeFuture<'_> {
+        Box::pin(execute_node(node, browser, ai_service))
+    };
+    let result = state.run(&workflow_id, inputs.unwrap_or_default(), &execute).await?;
+
+    emit_approval_request(&app, &result);
+    Ok(result)
+}
+
+/// Approve or reject the step a paused run is waiting on and continue down that branch
+#[tauri::command]
+pub async fn workflow_approve_step(
+    execution_id: String,
+    decision: ApprovalDecision,
+    note: Option<String>,
+    app: AppHandle,
+    state: State<'_, WorkflowState>,
+    browser: State<'_, Arc<BrowserService>>,
+    ai_service: State<'_, AIService>,
+) -> Result<WorkflowRunResult, String> {
+    let (browser, ai_service): (&BrowserService, &AIService) = (&browser, &ai_service);
+    let execute = |node: WorkflowNode| -> NodeFuture<'_> {
+        Box::pin(execute_node(node, browser, ai_service))
+    };
+    let result = state.approve_step(&execution_id, decision, note, Utc::now(), &execute).await?;
+
+    emit_approval_request(&app, &result);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn workflow_get_pending_approvals(
+    state: State<'_, WorkflowState>,
+) -> Result<Vec<PendingApproval>, String> {
+    Ok(state.pending_approvals().await)
+}
+
+fn emit_approval_request(app: &AppHandle, result: &WorkflowRunResult) {
+    if let Some(pending) = &result.pending_approval {
+        let _ = app.emit("workflow-approval-requested", pending);
+    }
+}
+
+/// Every 30 seconds, takes the default action on approvals whose timeout has
+/// passed, starting with any that expired while the app was closed
+pub fn spawn_approval_timeouts(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(30));
+        loop {
+            ticker.tick().await;
+
+            let (Some(state), Some(browser), Some(ai_service)) = (
+                app.try_state::<WorkflowState>(),
+                app.try_state::<Arc<BrowserService>>(),
+                app.try_state::<AIService>(),
+            ) else {
+                continue;
+            };
+            let (browser, ai_service): (&BrowserService, &AIService) = (&browser, &ai_service);
+            let execute = |node: WorkflowNode| -> NodeFuture<'_> {
+                Box::pin(execute_node(node, browser, ai_service))
+            };
+
+            for result in state.resolve_expired_approvals(Utc::now(), &execute).await {
+                match result {
+                    Ok(result) => {
+                        let _ = app.emit("workflow-approval-timed-out", &result);
+                        emit_approval_request(&app, &result);
+                    }
+                    Err(e) => log::error!("Workflow resumed after approval timeout failed: {}", e),
+                }
+            }
+        }
+    });
+}
+
+async fn execute_node(
+    node: WorkflowNode,
+    browser: &BrowserService,
+    ai_service: &AIService,
+) -> Result<NodeResult, String> {
     match node.node_type.as_str() {
         "browserAction" => execute_browser_action(node, browser).await,
         "dataExtraction" => execute_data_extraction(node, browser).await,
@@ -78,7 +623,7 @@ pub async fn execute_workflow_node(
 
 async fn execute_browser_action(
     node: WorkflowNode,
-    browser: State<'_, Arc<BrowserService>>,
+    browser: &BrowserService,
 ) -> Result<NodeResult, String> {
     let action = node.data.get("action").and_then(|v| v.as_str()).unwrap_or("");
     let target = node.data.get("target").and_then(|v| v.as_str()).unwrap_or("");
@@ -150,7 +695,7 @@ async fn execute_browser_action(
 
 async fn execute_data_extraction(
     node: WorkflowNode,
-    browser: State<'_, Arc<BrowserService>>,
+    browser: &BrowserService,
 ) -> Result<NodeResult, String> {
     let selector = node.data.get("selector").and_then(|v| v.as_str()).unwrap_or("");
     let attribute = node.data.get("attribute").and_then(|v| v.as_str()).unwrap_or("text");
@@ -198,7 +743,7 @@ async fn execute_data_extraction(
 
 async fn execute_ai_processing(
     node: WorkflowNode,
-    ai_service: State<'_, AIService>,
+    ai_service: &AIService,
 ) -> Result<NodeResult, String> {
     let prompt = node.data.get("prompt").and_then(|v| v.as_str()).unwrap_or("");
     let model = node.data.get("model").and_then(|v| v.as_str()).unwrap_or("gpt-5-mini");
@@ -282,6 +827,454 @@ async fn execute_loop(node: WorkflowNode) -> Result<NodeResult, String> {
     })
 }
 
+// ============================================
+// Workflow Runner & Sub-workflows
+// ============================================
+
+type NodeFuture<'a> = Pin<Box<dyn Future<Output = Result<NodeResult, String>> + Send + 'a>>;
+type RunFuture<'a> = Pin<Box<dyn Future<Output = Result<WorkflowRunResult, String>> + Send + 'a>>;
+
+/// Runs `workflow` node by node in edge order. `setVariable` and `subWorkflow` nodes
+/// are handled here; every other node goes to `execute`. `{{name}}` placeholders in
+/// node data are resolved against inputs, variables and earlier node results
+/// (stored under the node id).
+fn run_workflow<'a, 'f: 'a, F>(
+    workflows: &'a HashMap<String, Workflow>,
+    workflow: &'a Workflow,
+    inputs: serde_json::Map<String, serde_json::Value>,
+    call_stack: Vec<String>,
+    execute: &'a F,
+) -> RunFuture<'a>
+where
+    F: Fn(WorkflowNode) -> NodeFuture<'f> + Sync,
+{
+    let checkpoint = WorkflowCheckpoint { variables: inputs, ..Default::default() };
+    resume_workflow(workflows, workflow, checkpoint, call_stack, execute)
+}
+
+/// Continues `workflow` from `checkpoint`, skipping nodes that already ran. A node
+/// is skipped when none of its incoming edges is taken; an `approval` node stops
+/// the run and comes back as `pending_approval`.
+fn resume_workflow<'a, 'f: 'a, F>(
+    workflows: &'a HashMap<String, Workflow>,
+    workflow: &'a Workflow,
+    checkpoint: WorkflowCheckpoint,
+    call_stack: Vec<String>,
+    execute: &'a F,
+) -> RunFuture<'a>
+where
+    F: Fn(WorkflowNode) -> NodeFuture<'f> + Sync,
+{
+    Box::pin(async move {
+        let WorkflowCheckpoint { mut variables, mut node_results, branches } = checkpoint;
+        let mut skipped: HashSet<&str> = HashSet::new();
+
+        for node in execution_order(workflow)? {
+            if node_results.iter().any(|(id, _)| id == &node.id) {
+                continue;
+            }
+            let incoming: Vec<&WorkflowEdge> = workflow.edges.iter().filter(|e| e.target == node.id).collect();
+            if !incoming.is_empty() && !incoming.iter().any(|e| edge_taken(e, &skipped, &branches)) {
+                skipped.insert(&node.id);
+                continue;
+            }
+
+            let resolved = WorkflowNode {
+                data: resolve_templates(&node.data, &variables),
+                ..node.clone()
+            };
+
+            let result = match resolved.node_type.as_str() {
+                "setVariable" => {
+                    let name = resolved.data.get("name").and_then(|v| v.as_str())
+                        .ok_or_else(|| format!("Node '{}' is missing a variable name", node.id))?;
+                    let value = resolved.data.get("value").cloned().unwrap_or(serde_json::Value::Null);
+                    variables.insert(name.to_string(), value.clone());
+                    NodeResult {
+                        success: true,
+                        data: serde_json::json!({ "name": name, "value": value }),
+                        error: None,
+                    }
+                }
+                "subWorkflow" => {
+                    execute_subworkflow_node(workflows, &resolved, &mut variables, &call_stack, execute).await?
+                }
+                "approval" => {
+                    let checkpoint = WorkflowCheckpoint {
+                        variables: variables.clone(),
+                        node_results: node_results.clone(),
+                        branches,
+                    };
+                    let pending = approval_request(workflow, &resolved, checkpoint)?;
+                    log::info!("Workflow '{}' waiting for approval at node '{}'", workflow.name, node.id);
+                    return Ok(WorkflowRunResult {
+                        workflow_id: workflow.id.clone(),
+                        outputs: serde_json::Map::new(),
+                        variables,
+                        node_results,
+                        execution_id: String::new(),
+                        pending_approval: Some(pending),
+                    });
+                }
+                _ => match execute(resolved).await {
+                    Ok(result) => result,
+                    Err(error) if node.continue_on_error => NodeResult {
+                        success: false,
+                        data: serde_json::Value::Null,
+                        error: Some(error),
+                    },
+                    Err(error) => return Err(error),
+                },
+            };
+
+            if !result.success {
+                let error = result.error.clone().unwrap_or_else(|| "unknown error".to_string());
+                if !node.continue_on_error {
+                    return Err(format!("Node '{}' in workflow '{}' failed: {}", node.id, workflow.name, error));
+                }
+                log::warn!("Node '{}' in workflow '{}' failed, continuing: {}", node.id, workflow.name, error);
+            }
+
+            variables.insert(node.id.clone(), result.data.clone());
+            node_results.push((node.id.clone(), result));
+        }
+
+        let mut outputs = serde_json::Map::new();
+        for param in &workflow.outputs {
+            let value = variables
+                .get(&param.name)
+                .cloned()
+                .or_else(|| param.default.clone())
+                .ok_or_else(|| format!("Workflow '{}' did not produce output '{}'", workflow.name, param.name))?;
+            outputs.insert(param.name.clone(), value);
+        }
+
+        Ok(WorkflowRunResult {
+            workflow_id: workflow.id.clone(),
+            outputs,
+            variables,
+            node_results,
+            execution_id: String::new(),
+            pending_approval: None,
+        })
+    })
+}
+
+/// Runs a node under its retry policy, reporting every attempt to `on_attempt`.
+/// A failure is retried while attempts remain and its error is retryable; the
+/// last attempt's outcome is what the run sees.
+async fn execute_with_retry<'f, F>(
+    node: WorkflowNode,
+    execute: &F,
+    on_attempt: &(dyn Fn(NodeMetric) + Sync),
+) -> Result<NodeResult, String>
+where
+    F: Fn(WorkflowNode) -> NodeFuture<'f> + Sync,
+{
+    let mut attempt = 1;
+    loop {
+        let start_time = Utc::now();
+        let outcome = execute(node.clone()).await;
+        let end_time = Utc::now();
+
+        let error = match &outcome {
+            Ok(result) if result.success => None,
+            Ok(result) => Some(result.error.clone().unwrap_or_else(|| "unknown error".to_string())),
+            Err(error) => Some(error.clone()),
+        };
+        let retry_delay = match (&node.retry, &error) {
+            (Some(policy), Some(error)) if attempt < policy.max_attempts && policy.is_retryable(error) => {
+                Some(policy.delay_after(attempt))
+            }
+            _ => None,
+        };
+
+        on_attempt(NodeMetric {
+            node_id: node.id.clone(),
+            node_type: node.node_type.clone(),
+            start_time,
+            end_time: Some(end_time),
+            duration_ms: (end_time - start_time).num_milliseconds().max(0) as u64,
+            success: error.is_none(),
+            error: error.clone(),
+            attempt,
+            retried: retry_delay.is_some(),
+        });
+
+        let Some(delay) = retry_delay else {
+            return outcome;
+        };
+        log::warn!(
+            "Node '{}' failed on attempt {} ({}), retrying in {:?}",
+            node.id,
+            attempt,
+            error.unwrap_or_default(),
+            delay
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// An edge is taken when its source ran and, for a branching source, its handle
+/// matches the branch that was chosen
+fn edge_taken(edge: &WorkflowEdge, skipped: &HashSet<&str>, branches: &HashMap<String, String>) -> bool {
+    if skipped.contains(edge.source.as_str()) {
+        return false;
+    }
+    match (&edge.source_handle, branches.get(&edge.source)) {
+        (Some(handle), Some(branch)) => handle == branch,
+        _ => true,
+    }
+}
+
+/// Builds the pending state for an `approval` node. `data.approver` and
+/// `data.message` are shown to the approver; after `data.timeoutSeconds` the
+/// `data.defaultAction` (reject unless set) is taken.
+fn approval_request(
+    workflow: &Workflow,
+    node: &WorkflowNode,
+    checkpoint: WorkflowCheckpoint,
+) -> Result<PendingApproval, String> {
+    let default_action = match node.data.get("defaultAction") {
+        Some(action) => serde_json::from_value(action.clone())
+            .map_err(|_| format!("Approval node '{}' has an invalid defaultAction: {}", node.id, action))?,
+        None => ApprovalDecision::Rejected,
+    };
+    let requested_at = Utc::now();
+    let expires_at = node.data.get("timeoutSeconds")
+        .and_then(|v| v.as_u64())
+        .map(|secs| requested_at + chrono::Duration::seconds(secs as i64));
+
+    Ok(PendingApproval {
+        execution_id: String::new(),
+        node_id: node.id.clone(),
+        approver: node.data.get("approver").and_then(|v| v.as_str()).map(str::to_string),
+        message: node.data.get("message").and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Workflow '{}' is waiting for approval", workflow.name)),
+        requested_at,
+        expires_at,
+        default_action,
+        workflow: workflow.clone(),
+        checkpoint,
+    })
+}
+
+/// Invokes the workflow named by `data.workflowId`. Inputs are mapped explicitly
+/// (`data.inputs`: child input -> value) and so are outputs (`data.outputs`:
+/// parent variable -> child output); mapped outputs are written into `variables`.
+async fn execute_subworkflow_node<'a, 'f: 'a, F>(
+    workflows: &'a HashMap<String, Workflow>,
+    node: &WorkflowNode,
+    variables: &mut serde_json::Map<String, serde_json::Value>,
+    call_stack: &[String],
+    execute: &'a F,
+) -> Result<NodeResult, String>
+where
+    F: Fn(WorkflowNode) -> NodeFuture<'f> + Sync,
+{
+    let child_id = node.data.get("workflowId").and_then(|v| v.as_str())
+        .ok_or_else(|| format!("Sub-workflow node '{}' has no workflowId", node.id))?;
+
+    if call_stack.iter().any(|id| id == child_id) {
+        return Err(format!(
+            "Sub-workflow cycle detected: {} -> {}",
+            call_stack.join(" -> "),
+            child_id
+        ));
+    }
+    if call_stack.len() >= MAX_SUBWORKFLOW_DEPTH {
+        return Err(format!(
+            "Sub-workflow nesting exceeds the maximum depth of {}",
+            MAX_SUBWORKFLOW_DEPTH
+        ));
+    }
+
+    let child = workflows.get(child_id)
+        .ok_or_else(|| format!("Sub-workflow not found: {}", child_id))?;
+
+    let provided = node.data.get("inputs").and_then(|v| v.as_object()).cloned().unwrap_or_default();
+    let inputs = bind_workflow_inputs(child, provided)?;
+
+    let mut stack = call_stack.to_vec();
+    stack.push(child_id.to_string());
+    let result = run_workflow(workflows, child, inputs, stack, execute).await?;
+    if result.pending_approval.is_some() {
+        return Err(format!(
+            "Sub-workflow '{}' contains an approval node; approvals are only supported in top-level workflows",
+            child.name
+        ));
+    }
+
+    let mut mapped = serde_json::Map::new();
+    if let Some(mapping) = node.data.get("outputs").and_then(|v| v.as_object()) {
+        for (parent_var, child_output) in mapping {
+            let child_output = child_output.as_str()
+                .ok_or_else(|| format!("Output mapping for '{}' must name a child output", parent_var))?;
+            let value = result.outputs.get(child_output)
+                .ok_or_else(|| format!("Sub-workflow '{}' has no output '{}'", child.name, child_output))?;
+            variables.insert(parent_var.clone(), value.clone());
+            mapped.insert(parent_var.clone(), value.clone());
+        }
+    }
+
+    Ok(NodeResult {
+        success: true,
+        data: serde_json::json!({
+            "workflowId": child_id,
+            "outputs": mapped,
+        }),
+        error: None,
+    })
+}
+
+/// Checks provided inputs against the workflow's declared parameters and fills defaults
+fn bind_workflow_inputs(
+    workflow: &Workflow,
+    provided: serde_json::Map<String, serde_json::Value>,
+) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    if let Some(unknown) = provided.keys().find(|k| !workflow.inputs.iter().any(|p| &p.name == *k)) {
+        return Err(format!("Workflow '{}' has no input '{}'", workflow.name, unknown));
+    }
+
+    let mut inputs = provided;
+    for param in &workflow.inputs {
+        if inputs.contains_key(&param.name) {
+            continue;
+        }
+        match &param.default {
+            Some(default) => {
+                inputs.insert(param.name.clone(), default.clone());
+            }
+            None if param.required => {
+                return Err(format!("Missing required input '{}' for workflow '{}'", param.name, workflow.name));
+            }
+            None => {}
+        }
+    }
+    Ok(inputs)
+}
+
+/// Topological order of the workflow's nodes; ties keep the order nodes were declared in
+fn execution_order(workflow: &Workflow) -> Result<Vec<&WorkflowNode>, String> {
+    let mut in_degree: HashMap<&str, usize> = workflow.nodes.iter().map(|n| (n.id.as_str(), 0)).collect();
+    for edge in &workflow.edges {
+        if let Some(count) = in_degree.get_mut(edge.target.as_str()) {
+            *count += 1;
+        }
+    }
+
+    let mut order = Vec::with_capacity(workflow.nodes.len());
+    let mut done: HashSet<&str> = HashSet::new();
+    while order.len() < workflow.nodes.len() {
+        let next = workflow.nodes.iter()
+            .find(|n| !done.contains(n.id.as_str()) && in_degree[n.id.as_str()] == 0)
+            .ok_or_else(|| format!("Workflow '{}' contains a cycle", workflow.name))?;
+        done.insert(&next.id);
+        order.push(next);
+        for edge in workflow.edges.iter().filter(|e| e.source == next.id) {
+            if let Some(count) = in_degree.get_mut(edge.target.as_str()) {
+                *count = count.saturating_sub(1);
+            }
+        }
+    }
+    Ok(order)
+}
+
+fn resolve_templates(value: &serde_json::Value, variables: &serde_json::Map<String, serde_json::Value>) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(s) => resolve_template_string(s, variables),
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(|v| resolve_templates(v, variables)).collect())
+        }
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter().map(|(k, v)| (k.clone(), resolve_templates(v, variables))).collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// A string that is exactly one placeholder keeps the variable's JSON type;
+/// otherwise placeholders are interpolated as text
+fn resolve_template_string(s: &str, variables: &serde_json::Map<String, serde_json::Value>) -> serde_json::Value {
+    let placeholder = regex::Regex::new(r"\{\{\s*([\w.\-]+)\s*\}\}").unwrap();
+
+    if let Some(caps) = placeholder.captures(s.trim()) {
+        if caps.get(0).map(|m| m.as_str().len()) == Some(s.trim().len()) {
+            return lookup_variable(&caps[1], variables).unwrap_or(serde_json::Value::Null);
+        }
+    }
+
+    let interpolated = placeholder.replace_all(s, |caps: &regex::Captures| {
+        match lookup_variable(&caps[1], variables) {
+            Some(serde_json::Value::String(text)) => text,
+            Some(serde_json::Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        }
+    });
+    serde_json::Value::String(interpolated.into_owned())
+}
+
+fn lookup_variable(path: &str, variables: &serde_json::Map<String, serde_json::Value>) -> Option<serde_json::Value> {
+    let mut parts = path.split('.');
+    let mut current = variables.get(parts.next()?)?;
+    for part in parts {
+        current = match current {
+            serde_json::Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            other => other.get(part)?,
+        };
+    }
+    Some(current.clone())
+}
+
+/// Reports missing sub-workflows and reference cycles reachable from `root`.
+/// `references` maps each known workflow id to the ids its sub-workflow nodes call.
+pub(crate) fn subworkflow_reference_errors(
+    root: &str,
+    references: &HashMap<String, Vec<String>>,
+) -> Vec<String> {
+    fn visit(
+        id: &str,
+        references: &HashMap<String, Vec<String>>,
+        stack: &mut Vec<String>,
+        finished: &mut HashSet<String>,
+        errors: &mut Vec<String>,
+    ) {
+        stack.push(id.to_string());
+        for child in references.get(id).into_iter().flatten() {
+            if let Some(start) = stack.iter().position(|s| s == child) {
+                let mut cycle = stack[start..].to_vec();
+                cycle.push(child.clone());
+                let message = format!("Sub-workflow cycle: {}", cycle.join(" -> "));
+                if !errors.contains(&message) {
+                    errors.push(message);
+                }
+            } else if !references.contains_key(child) {
+                let message = format!("Sub-workflow '{}' called from '{}' does not exist", child, id);
+                if !errors.contains(&message) {
+                    errors.push(message);
+                }
+            } else if !finished.contains(child) {
+                visit(child, references, stack, finished, errors);
+            }
+        }
+        stack.pop();
+        finished.insert(id.to_string());
+    }
+
+    let mut errors = Vec::new();
+    visit(root, references, &mut Vec::new(), &mut HashSet::new(), &mut errors);
+    errors
+}
+
+fn subworkflow_references(workflow: &Workflow) -> Vec<String> {
+    workflow.nodes.iter()
+        .filter(|n| n.node_type == "subWorkflow")
+        .filter_map(|n| n.data.get("workflowId").and_then(|v| v.as_str()).map(str::to_string))
+        .collect()
+}
+
 #[tauri::command]
 pub async fn workflow_save(
     workflow: Workflow,
@@ -290,6 +1283,20 @@ pub async fn workflow_save(
     log::info!("Saving workflow: {} ({})", workflow.name, workflow.id);
 
     let mut workflows = state.workflows.lock().await;
+
+    // Missing sub-workflows may be saved later, but a cycle could never run
+    let mut references: HashMap<String, Vec<String>> = workflows.iter()
+        .map(|(id, w)| (id.clone(), subworkflow_references(w)))
+        .collect();
+    references.insert(workflow.id.clone(), subworkflow_references(&workflow));
+    let cycles: Vec<String> = subworkflow_reference_errors(&workflow.id, &references)
+        .into_iter()
+        .filter(|e| e.starts_with("Sub-workflow cycle"))
+        .collect();
+    if !cycles.is_empty() {
+        return Err(cycles.join("; "));
+    }
+
     workflows.insert(workflow.id.clone(), workflow);
 
     Ok(())
@@ -427,11 +1434,15 @@ pub async fn generate_workflow_from_description(
                                     id: "start-1".to_string(),
                                     node_type: "start".to_string(),
                                     data: serde_json::json!({}),
+                                    retry: None,
+                                    continue_on_error: false,
                                 },
                                 WorkflowNode {
                                     id: "end-1".to_string(),
                                     node_type: "end".to_string(),
                                     data: serde_json::json!({}),
+                                    retry: None,
+                                    continue_on_error: false,
                                 },
                             ]),
                         edges: json.get("edges")
@@ -440,7 +1451,10 @@ pub async fn generate_workflow_from_description(
                                 id: "edge-1".to_string(),
                                 source: "start-1".to_string(),
                                 target: "end-1".to_string(),
+                                source_handle: None,
                             }]),
+                        inputs: Vec::new(),
+                        outputs: Vec::new(),
                         created_at: chrono::Utc::now().to_rfc3339(),
                         updated_at: chrono::Utc::now().to_rfc3339(),
                     };
@@ -459,18 +1473,25 @@ pub async fn generate_workflow_from_description(
                                 id: "start-1".to_string(),
                                 node_type: "start".to_string(),
                                 data: serde_json::json!({}),
+                                retry: None,
+                                continue_on_error: false,
                             },
                             WorkflowNode {
                                 id: "end-1".to_string(),
                                 node_type: "end".to_string(),
                                 data: serde_json::json!({}),
+                                retry: None,
+                                continue_on_error: false,
                             },
                         ],
                         edges: vec![WorkflowEdge {
                             id: "edge-1".to_string(),
                             source: "start-1".to_string(),
                             target: "end-1".to_string(),
+                            source_handle: None,
                         }],
+                        inputs: Vec::new(),
+                        outputs: Vec::new(),
                         created_at: chrono::Utc::now().to_rfc3339(),
                         updated_at: chrono::Utc::now().to_rfc3339(),
                     })
@@ -483,3 +1504,298 @@ pub async fn generate_workflow_from_description(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::metrics::ExecutionStatus;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn node(id: &str, node_type: &str, data: serde_json::Value) -> WorkflowNode {
+        WorkflowNode {
+            id: id.to_string(),
+            node_type: node_type.to_string(),
+            data,
+            retry: None,
+            continue_on_error: false,
+        }
+    }
+
+    fn edge(source: &str, target: &str) -> WorkflowEdge {
+        WorkflowEdge {
+            id: format!("{}-{}", source, target),
+            source: source.to_string(),
+            target: target.to_string(),
+            source_handle: None,
+        }
+    }
+
+    fn branch_edge(source: &str, target: &str, handle: &str) -> WorkflowEdge {
+        WorkflowEdge { source_handle: Some(handle.to_string()), ..edge(source, target) }
+    }
+
+    fn param(name: &str, required: bool) -> WorkflowParam {
+        WorkflowParam { name: name.to_string(), required, default: None, description: None }
+    }
+
+    fn workflow(id: &str, nodes: Vec<WorkflowNode>, edges: Vec<WorkflowEdge>) -> Workflow {
+        Workflow {
+            id: id.to_string(),
+            name: id.to_string(),
+            nodes,
+            edges,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn echo(node: WorkflowNode) -> NodeFuture<'static> {
+        Box::pin(async move { Ok(NodeResult { success: true, data: node.data, error: None }) })
+    }
+
+    #[tokio::test]
+    async fn test_subworkflow_passes_inputs_and_outputs() {
+        let mut login = workflow(
+            "login",
+            vec![
+                node("fill", "browserAction", serde_json::json!({ "action": "type", "text": "{{username}}" })),
+                node("token", "setVariable", serde_json::json!({ "name": "session", "value": "token-for-{{fill.text}}" })),
+            ],
+            vec![edge("fill", "token")],
+        );
+        login.inputs = vec![param("username", true)];
+        login.outputs = vec![param("session", true)];
+
+        let parent = workflow(
+            "checkout",
+            vec![
+                node("user", "setVariable", serde_json::json!({ "name": "email", "value": "ada@example.com" })),
+                node("call", "subWorkflow", serde_json::json!({
+                    "workflowId": "login",
+                    "inputs": { "username": "{{email}}" },
+                    "outputs": { "authToken": "session" }
+                })),
+                node("use", "browserAction", serde_json::json!({ "action": "navigate", "target": "/cart?t={{authToken}}" })),
+            ],
+            vec![edge("user", "call"), edge("call", "use")],
+        );
+
+        let workflows: HashMap<String, Workflow> =
+            [login, parent.clone()].into_iter().map(|w| (w.id.clone(), w)).collect();
+        let result = run_workflow(&workflows, &parent, serde_json::Map::new(), vec!["checkout".to_string()], &echo)
+            .await
+            .unwrap();
+
+        assert_eq!(result.variables["authToken"], "token-for-ada@example.com");
+        let (_, last) = result.node_results.last().unwrap();
+        assert_eq!(last.data["target"], "/cart?t=token-for-ada@example.com");
+        // Child variables stay inside the child
+        assert!(!result.variables.contains_key("username"));
+
+        // Required inputs must be mapped explicitly
+        let mut missing = parent.clone();
+        missing.nodes[1].data["inputs"] = serde_json::json!({});
+        let err = run_workflow(&workflows, &missing, serde_json::Map::new(), vec![], &echo).await.unwrap_err();
+        assert!(err.contains("Missing required input 'username'"));
+    }
+
+    #[tokio::test]
+    async fn test_subworkflow_cycle_rejected() {
+        let a = workflow("a", vec![node("call_b", "subWorkflow", serde_json::json!({ "workflowId": "b" }))], vec![]);
+        let b = workflow("b", vec![node("call_a", "subWorkflow", serde_json::json!({ "workflowId": "a" }))], vec![]);
+        let workflows: HashMap<String, Workflow> =
+            [a.clone(), b].into_iter().map(|w| (w.id.clone(), w)).collect();
+
+        let err = run_workflow(&workflows, &a, serde_json::Map::new(), vec!["a".to_string()], &echo)
+            .await
+            .unwrap_err();
+        assert!(err.contains("cycle detected: a -> b -> a"), "{}", err);
+
+        let mut references: HashMap<String, Vec<String>> =
+            workflows.values().map(|w| (w.id.clone(), subworkflow_references(w))).collect();
+        references.insert("c".to_string(), vec!["a".to_string(), "missing".to_string()]);
+        let errors = subworkflow_reference_errors("c", &references);
+        assert_eq!(
+            errors,
+            vec![
+                "Sub-workflow cycle: a -> b -> a".to_string(),
+                "Sub-workflow 'missing' called from 'c' does not exist".to_string(),
+            ]
+        );
+    }
+
+    fn approval_workflow(approval_data: serde_json::Value) -> Workflow {
+        workflow(
+            "publish",
+            vec![
+                node("draft", "browserAction", serde_json::json!({ "action": "type", "text": "release notes" })),
+                node("review", "approval", approval_data),
+                node("publish", "browserAction", serde_json::json!({ "action": "click", "target": "#publish" })),
+                node("discard", "browserAction", serde_json::json!({ "action": "click", "target": "#discard" })),
+            ],
+            vec![
+                edge("draft", "review"),
+                branch_edge("review", "publish", "approved"),
+                branch_edge("review", "discard", "rejected"),
+            ],
+        )
+    }
+
+    fn ran(result: &WorkflowRunResult) -> Vec<&str> {
+        result.node_results.iter().map(|(id, _)| id.as_str()).collect()
+    }
+
+    fn approvals_path() -> PathBuf {
+        std::env::temp_dir().join(format!("workflow-approvals-{}.json", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_approval_survives_restart_and_resumes_on_branch() {
+        let path = approvals_path();
+        let state = WorkflowState::with_approval_storage(path.clone(), None);
+        let publish = approval_workflow(serde_json::json!({ "approver": "ops@example.com", "message": "Publish?" }));
+        state.workflows.lock().await.insert(publish.id.clone(), publish);
+
+        let paused = state.run("publish", serde_json::Map::new(), &echo).await.unwrap();
+        assert_eq!(ran(&paused), ["draft"]);
+        let pending = paused.pending_approval.clone().unwrap();
+        assert_eq!(pending.node_id, "review");
+        assert_eq!(pending.execution_id, paused.execution_id);
+        assert_eq!(pending.approver.as_deref(), Some("ops@example.com"));
+        drop(state);
+
+        // The paused run comes back from disk; the workflow is not re-saved
+        let restarted = WorkflowState::with_approval_storage(path.clone(), None);
+        assert_eq!(restarted.pending_approvals().await.len(), 1);
+
+        let resumed = restarted
+            .approve_step(&paused.execution_id, ApprovalDecision::Approved, Some("ship it".to_string()), Utc::now(), &echo)
+            .await
+            .unwrap();
+        assert!(resumed.pending_approval.is_none());
+        assert_eq!(ran(&resumed), ["draft", "review", "publish"]);
+        assert_eq!(resumed.variables["review"]["note"], "ship it");
+        assert_eq!(resumed.variables["review"]["decidedBy"], "approver");
+
+        assert!(restarted.approve_step(&paused.execution_id, ApprovalDecision::Rejected, None, Utc::now(), &echo)
+            .await
+            .is_err());
+        assert!(WorkflowState::with_approval_storage(path.clone(), None).pending_approvals().await.is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_approval_timeout_takes_default_action() {
+        let path = approvals_path();
+        let state = WorkflowState::with_approval_storage(path.clone(), None);
+        let publish = approval_workflow(serde_json::json!({ "timeoutSeconds": 60, "defaultAction": "reject" }));
+        state.workflows.lock().await.insert(publish.id.clone(), publish);
+
+        let paused = state.run("publish", serde_json::Map::new(), &echo).await.unwrap();
+        let expires_at = paused.pending_approval.unwrap().expires_at.unwrap();
+
+        assert!(state.resolve_expired_approvals(expires_at - chrono::Duration::seconds(1), &echo).await.is_empty());
+
+        let late = state
+            .approve_step(&paused.execution_id, ApprovalDecision::Approved, None, expires_at, &echo)
+            .await
+            .unwrap_err();
+        assert!(late.contains("timed out"), "{}", late);
+
+        let mut resolved = state.resolve_expired_approvals(expires_at, &echo).await;
+        assert_eq!(resolved.len(), 1);
+        let resumed = resolved.remove(0).unwrap();
+        assert_eq!(resumed.execution_id, paused.execution_id);
+        assert_eq!(ran(&resumed), ["draft", "review", "discard"]);
+        assert_eq!(resumed.variables["review"]["decision"], "rejected");
+        assert_eq!(resumed.variables["review"]["decidedBy"], "timeout");
+        assert!(state.pending_approvals().await.is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+
+    fn retry_policy(max_attempts: u32) -> NodeRetryPolicy {
+        NodeRetryPolicy {
+            max_attempts,
+            base_delay_ms: 1,
+            backoff_multiplier: 2.0,
+            jitter: 0.0,
+            retryable_errors: vec!["timed out".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flaky_node_succeeds_on_third_attempt() {
+        let metrics = Arc::new(MetricsService::new());
+        let state = WorkflowState::new().with_metrics(metrics.clone());
+        let mut fetch = node("fetch", "browserAction", serde_json::json!({ "action": "navigate", "target": "/orders" }));
+        fetch.retry = Some(retry_policy(3));
+        let sync = workflow("sync", vec![fetch, node("save", "browserAction", serde_json::json!({}))], vec![edge("fetch", "save")]);
+        state.workflows.lock().await.insert(sync.id.clone(), sync);
+
+        let calls = AtomicU32::new(0);
+        let execute = |node: WorkflowNode| -> NodeFuture<'_> {
+            let failing = node.id == "fetch" && calls.fetch_add(1, Ordering::SeqCst) < 2;
+            Box::pin(async move {
+                if failing {
+                    return Err("Navigation timed out".to_string());
+                }
+                Ok(NodeResult { success: true, data: node.data, error: None })
+            })
+        };
+        let result = state.run("sync", serde_json::Map::new(), &execute).await.unwrap();
+        assert_eq!(ran(&result), ["fetch", "save"]);
+
+        let execution = metrics.get_execution(&result.execution_id).unwrap().unwrap();
+        assert!(matches!(execution.status, ExecutionStatus::Completed));
+        let attempts: Vec<_> = execution.node_metrics.iter()
+            .filter(|m| m.node_id == "fetch")
+            .map(|m| (m.attempt, m.success, m.retried))
+            .collect();
+        assert_eq!(attempts, [(1, false, true), (2, false, true), (3, true, false)]);
+        assert_eq!(execution.nodes_failed, 0);
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_fail_the_run_unless_continue_on_error() {
+        let metrics = Arc::new(MetricsService::new());
+        let state = WorkflowState::new().with_metrics(metrics.clone());
+        let mut fetch = node("fetch", "browserAction", serde_json::json!({}));
+        fetch.retry = Some(retry_policy(2));
+        let mut notify = node("notify", "browserAction", serde_json::json!({}));
+        notify.retry = Some(retry_policy(5));
+        notify.continue_on_error = true;
+        for wf in [
+            workflow("strict", vec![fetch, node("save", "browserAction", serde_json::json!({}))], vec![edge("fetch", "save")]),
+            workflow("lenient", vec![notify, node("save", "browserAction", serde_json::json!({}))], vec![edge("notify", "save")]),
+        ] {
+            state.workflows.lock().await.insert(wf.id.clone(), wf);
+        }
+
+        let calls = AtomicU32::new(0);
+        let execute = |node: WorkflowNode| -> NodeFuture<'_> {
+            let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Box::pin(async move {
+                match node.id.as_str() {
+                    "fetch" => Err(format!("Request timed out (call {})", call)),
+                    // Not in the retryable list, so tried once
+                    "notify" => Ok(NodeResult { success: false, data: serde_json::Value::Null, error: Some("401 Unauthorized".to_string()) }),
+                    _ => Ok(NodeResult { success: true, data: node.data, error: None }),
+                }
+            })
+        };
+
+        let err = state.run("strict", serde_json::Map::new(), &execute).await.unwrap_err();
+        assert_eq!(err, "Request timed out (call 2)");
+        let stats = metrics.get_workflow_stats("strict").unwrap().unwrap();
+        assert_eq!((stats.total_executions, stats.failed_executions), (1, 1));
+
+        let result = state.run("lenient", serde_json::Map::new(), &execute).await.unwrap();
+        assert_eq!(ran(&result), ["notify", "save"]);
+        assert_eq!(result.node_results[0].1.error.as_deref(), Some("401 Unauthorized"));
+        let execution = metrics.get_execution(&result.execution_id).unwrap().unwrap();
+        let notify_attempts = execution.node_metrics.iter().filter(|m| m.node_id == "notify").count();
+        assert_eq!((notify_attempts, execution.nodes_failed), (1, 1));
+    }
+}
@@ -1,6 +1,7 @@
 use chrono::Utc;
 use rusqlite::{params, Connection, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
@@ -1734,6 +1735,77 @@ impl Database {
         Ok(rows > 0)
     }
 
+    // ========================================================================
+    // SSO IDENTITY DATABASE OPERATIONS
+    // ========================================================================
+
+    /// Save SSO identity
+    pub fn save_sso_identity(&self, identity: &SSOIdentityRecord) -> Result<()> {
+        let conn = self.conn.lock()
+            .map_err(|_| rusqlite::Error::ExecuteReturnedResults)?;
+
+        conn.execute(
+            r#"
+            INSERT OR REPLACE INTO sso_identities 
+            (id, tenant_id, user_id, provider_id, subject, email, first_name,
+             last_name, display_name, groups, last_login_at, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            params![
+                identity.id,
+                identity.tenant_id,
+                identity.user_id,
+                identity.provider_id,
+                identity.subject,
+                identity.email,
+                identity.first_name,
+                identity.last_name,
+                identity.display_name,
+                identity.groups,
+                identity.last_login_at,
+                identity.created_at,
+                identity.updated_at
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get SSO identity by tenant and (lowercased) email
+    pub fn get_sso_identity_by_email(&self, tenant_id: &str, email: &str) -> Result<Option<SSOIdentityRecord>> {
+        let conn = self.conn.lock()
+            .map_err(|_| rusqlite::Error::ExecuteReturnedResults)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, tenant_id, user_id, provider_id, subject, email, first_name,
+             last_name, display_name, groups, last_login_at, created_at, updated_at
+             FROM sso_identities WHERE tenant_id = ? AND email = ?"
+        )?;
+
+        let result = stmt.query_row(params![tenant_id, email], |row| {
+            Ok(SSOIdentityRecord {
+                id: row.get(0)?,
+                tenant_id: row.get(1)?,
+                user_id: row.get(2)?,
+                provider_id: row.get(3)?,
+                subject: row.get(4)?,
+                email: row.get(5)?,
+                first_name: row.get(6)?,
+                last_name: row.get(7)?,
+                display_name: row.get(8)?,
+                groups: row.get(9)?,
+                last_login_at: row.get(10)?,
+                created_at: row.get(11)?,
+                updated_at: row.get(12)?,
+            })
+        });
+
+        match result {
+            Ok(identity) => Ok(Some(identity)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     // ========================================================================
     // LDAP CONFIG DATABASE OPERATIONS
     // ========================================================================
@@ -2646,6 +2718,226 @@ impl Database {
 
         payouts.collect()
     }
+
+    // ========================================================================
+    // USER DATA EXPORT & ERASURE (GDPR)
+    // ========================================================================
+
+    /// Export every row attributable to `user_id`, keyed by table name
+    pub fn export_user_data(&self, user_id: &str) -> Result<UserDataExport> {
+        let conn = self.conn.lock()
+            .map_err(|_| rusqlite::Error::ExecuteReturnedResults)?;
+
+        let mut tables = BTreeMap::new();
+        for (table, user_column) in USER_DATA_TABLES {
+            let sql = format!("SELECT * FROM {} WHERE {} = ?", table, user_column);
+            tables.insert(table.to_string(), Self::rows_as_json(&conn, &sql, params![user_id])?);
+        }
+        for (table, parent_column, parent) in USER_DATA_CHILD_TABLES {
+            let sql = format!(
+                "SELECT * FROM {} WHERE {} IN (SELECT id FROM {} WHERE {} = ?)",
+                table, parent_column, parent, user_data_column(parent)
+            );
+            tables.insert(table.to_string(), Self::rows_as_json(&conn, &sql, params![user_id])?);
+        }
+
+        Ok(UserDataExport {
+            user_id: user_id.to_string(),
+            exported_at: Utc::now().timestamp(),
+            tables,
+        })
+    }
+
+    fn rows_as_json<P: rusqlite::Params>(conn: &Connection, sql: &str, params: P) -> Result<Vec<serde_json::Value>> {
+        use rusqlite::types::ValueRef;
+
+        let mut stmt = conn.prepare(sql)?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+        let rows = stmt.query_map(params, |row| {
+            let mut object = serde_json::Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                let value = match row.get_ref(i)? {
+                    ValueRef::Null => serde_json::Value::Null,
+                    ValueRef::Integer(n) => serde_json::json!(n),
+                    ValueRef::Real(n) => serde_json::json!(n),
+                    ValueRef::Text(text) => serde_json::json!(String::from_utf8_lossy(text)),
+                    ValueRef::Blob(bytes) => serde_json::json!(bytes),
+                };
+                object.insert(column.clone(), value);
+            }
+            Ok(serde_json::Value::Object(object))
+        })?;
+
+        rows.collect()
+    }
+
+    /// Erase everything attributable to `user_id` in one transaction.
+    ///
+    /// Rows other records depend on, or that must be retained (audit trails,
+    /// investments, commissions), are kept with their PII replaced by
+    /// tombstones and the user id swapped for a random pseudonym, so
+    /// aggregates and foreign keys stay intact. `HardDelete` additionally
+    /// deletes the user's memberships, browser profiles and directory entries.
+    /// A non-PII `user.data_erased` entry is written to each tenant's audit log.
+    pub fn erase_user_data(&self, user_id: &str, mode: ErasureMode) -> Result<UserErasureReport> {
+        let mut conn = self.conn.lock()
+            .map_err(|_| rusqlite::Error::ExecuteReturnedResults)?;
+        let tx = conn.transaction()?;
+
+        let erasure_id = uuid::Uuid::new_v4().to_string();
+        let pseudonym = format!("erased-{}", erasure_id);
+        let now = Utc::now().timestamp();
+
+        let tenant_ids: Vec<String> = {
+            let mut stmt = tx.prepare("SELECT DISTINCT tenant_id FROM tenant_users WHERE user_id = ?")?;
+            let ids = stmt.query_map(params![user_id], |row| row.get(0))?;
+            ids.collect::<Result<_>>()?
+        };
+
+        let mut statements: Vec<(&str, &str)> = vec![
+            // Browsing data and sessions have no aggregate value
+            ("browser_profile_sessions", "DELETE FROM browser_profile_sessions WHERE profile_id IN (SELECT id FROM browser_profiles WHERE user_id = ?1)"),
+            ("browser_profile_cookies", "DELETE FROM browser_profile_cookies WHERE profile_id IN (SELECT id FROM browser_profiles WHERE user_id = ?1)"),
+            ("browser_profile_storage", "DELETE FROM browser_profile_storage WHERE profile_id IN (SELECT id FROM browser_profiles WHERE user_id = ?1)"),
+            ("browser_profile_sync", "DELETE FROM browser_profile_sync WHERE profile_id IN (SELECT id FROM browser_profiles WHERE user_id = ?1)"),
+            ("investor_notifications", "DELETE FROM investor_notifications WHERE investor_id IN (SELECT id FROM investors WHERE user_id = ?1)"),
+            ("sso_sessions", "DELETE FROM sso_sessions WHERE user_id = ?1"),
+            ("sso_identities", "DELETE FROM sso_identities WHERE user_id = ?1"),
+            // Retained records: strip PII, keep amounts and references
+            ("investors", "UPDATE investors SET user_id = ?2, name = ?3, email = 'erased-' || id || '@invalid', company = NULL, wallet_address = NULL, bank_details = NULL, preferences = NULL WHERE user_id = ?1"),
+            ("affiliates", "UPDATE affiliates SET user_id = ?2, email = 'erased-' || id || '@invalid', first_name = ?3, last_name = ?3, company = NULL, website = NULL, custom_domain = NULL, payout_details = NULL WHERE user_id = ?1"),
+            ("referrals", "UPDATE referrals SET referred_user_id = ?2, referred_email = 'erased-' || id || '@invalid', ip_address = NULL, user_agent = NULL WHERE referred_user_id = ?1"),
+            ("tenant_audit_log", "UPDATE tenant_audit_log SET user_id = ?2, old_values = NULL, new_values = NULL, ip_address = NULL, user_agent = NULL WHERE user_id = ?1"),
+            ("tenant_audit_log", "UPDATE tenant_audit_log SET resource_id = ?2, old_values = NULL, new_values = NULL WHERE resource_id = ?1"),
+            ("sso_audit_log", "UPDATE sso_audit_log SET user_id = ?2, event_details = NULL, ip_address = NULL, user_agent = NULL, error_message = NULL WHERE user_id = ?1"),
+            ("tenant_invitations", "UPDATE tenant_invitations SET invited_by = ?2 WHERE invited_by = ?1"),
+            ("tenant_users", "UPDATE tenant_users SET invited_by = ?2 WHERE invited_by = ?1"),
+        ];
+        statements.extend(match mode {
+            ErasureMode::Anonymize => [
+                ("browser_profiles", "UPDATE browser_profiles SET user_id = ?2, name = ?3, description = NULL, avatar = NULL, proxy_config = NULL, user_agent = NULL, timezone = NULL, locale = NULL, geolocation = NULL, cookies_path = NULL, storage_path = NULL, fingerprint = NULL, startup_urls = NULL WHERE user_id = ?1"),
+                ("ldap_users", "UPDATE ldap_users SET local_user_id = ?2, distinguished_name = ?3, username = ?3, email = NULL, display_name = NULL, groups = NULL WHERE local_user_id = ?1"),
+                ("tenant_users", "UPDATE tenant_users SET user_id = ?2, permissions = NULL, status = 'erased' WHERE user_id = ?1"),
+            ],
+            ErasureMode::HardDelete => [
+                ("browser_profiles", "DELETE FROM browser_profiles WHERE user_id = ?1"),
+                ("ldap_users", "DELETE FROM ldap_users WHERE local_user_id = ?1"),
+                ("tenant_users", "DELETE FROM tenant_users WHERE user_id = ?1"),
+            ],
+        });
+
+        // ?1 = user id, ?2 = pseudonym, ?3 = tombstone; each statement binds only what it uses
+        let values: [&dyn rusqlite::ToSql; 3] = [&user_id, &pseudonym, &ERASED_TOMBSTONE];
+        let mut affected = BTreeMap::new();
+        for (table, sql) in statements {
+            let mut stmt = tx.prepare(sql)?;
+            let count = stmt.execute(&values[..stmt.parameter_count()])?;
+            *affected.entry(table.to_string()).or_insert(0) += count;
+        }
+        affected.retain(|_, count| *count > 0);
+
+        let details = serde_json::json!({ "mode": mode, "affected": affected }).to_string();
+        for tenant_id in &tenant_ids {
+            tx.execute(
+                r#"
+                INSERT INTO tenant_audit_log
+                (id, tenant_id, user_id, action, resource_type, resource_id,
+                 old_values, new_values, ip_address, user_agent, created_at)
+                VALUES (?, ?, NULL, 'user.data_erased', 'user', ?, NULL, ?, NULL, NULL, ?)
+                "#,
+                params![uuid::Uuid::new_v4().to_string(), tenant_id, erasure_id, details, now],
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(UserErasureReport {
+            erasure_id,
+            mode,
+            affected,
+            tenant_ids,
+            erased_at: now,
+        })
+    }
+}
+
+/// Value written over erased PII
+const ERASED_TOMBSTONE: &str = "[erased]";
+
+/// Tables holding rows attributable to a user, with the column naming the user
+const USER_DATA_TABLES: &[(&str, &str)] = &[
+    ("tenant_users", "user_id"),
+    ("tenant_invitations", "invited_by"),
+    ("tenant_audit_log", "user_id"),
+    ("sso_sessions", "user_id"),
+    ("sso_audit_log", "user_id"),
+    ("ldap_users", "local_user_id"),
+    ("browser_profiles", "user_id"),
+    ("investors", "user_id"),
+    ("affiliates", "user_id"),
+    ("referrals", "referred_user_id"),
+];
+
+/// Tables whose rows belong to a user through a parent row:
+/// (table, column referencing the parent, parent table)
+const USER_DATA_CHILD_TABLES: &[(&str, &str, &str)] = &[
+    ("browser_profile_sessions", "profile_id", "browser_profiles"),
+    ("browser_profile_cookies", "profile_id", "browser_profiles"),
+    ("browser_profile_storage", "profile_id", "browser_profiles"),
+    ("browser_profile_sync", "profile_id", "browser_profiles"),
+    ("investments", "investor_id", "investors"),
+    ("payout_schedule", "investor_id", "investors"),
+    ("token_transactions", "investor_id", "investors"),
+    ("investor_licenses", "investor_id", "investors"),
+    ("investor_notifications", "investor_id", "investors"),
+    ("affiliate_links", "affiliate_id", "affiliates"),
+    ("commissions", "affiliate_id", "affiliates"),
+    ("affiliate_payouts", "affiliate_id", "affiliates"),
+];
+
+fn user_data_column(table: &str) -> &'static str {
+    USER_DATA_TABLES.iter()
+        .find(|(name, _)| *name == table)
+        .map(|(_, column)| *column)
+        .unwrap_or("user_id")
+}
+
+/// Portable archive of a user's records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserDataExport {
+    pub user_id: String,
+    pub exported_at: i64,
+    pub tables: BTreeMap<String, Vec<serde_json::Value>>,
+}
+
+impl UserDataExport {
+    /// Number of exported rows per non-empty table
+    pub fn record_counts(&self) -> BTreeMap<String, usize> {
+        self.tables.iter()
+            .filter(|(_, rows)| !rows.is_empty())
+            .map(|(table, rows)| (table.clone(), rows.len()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErasureMode {
+    /// Replace PII with tombstones, keeping rows and aggregates
+    Anonymize,
+    /// Delete the user's own rows; retained records are anonymized
+    HardDelete,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserErasureReport {
+    /// Random id the audit entries reference instead of the user
+    pub erasure_id: String,
+    pub mode: ErasureMode,
+    /// Rows changed or deleted per table
+    pub affected: BTreeMap<String, usize>,
+    pub tenant_ids: Vec<String>,
+    pub erased_at: i64,
 }
 
 // ========================================================================
@@ -2932,6 +3224,23 @@ pub struct LDAPUserRecord {
     pub updated_at: i64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SSOIdentityRecord {
+    pub id: String,
+    pub tenant_id: String,
+    pub user_id: String,
+    pub provider_id: String,
+    pub subject: Option<String>,
+    pub email: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub display_name: Option<String>,
+    pub groups: Option<String>,
+    pub last_login_at: Option<i64>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
 // ========================================================================
 // BROWSER PROFILE DATABASE RECORD TYPES
 // ========================================================================
@@ -3089,4 +3398,118 @@ mod tests {
         // Cleanup
         std::fs::remove_dir_all(&temp_dir).unwrap();
     }
+
+    const ERASE_USER: &str = "user-42";
+
+    fn seeded_database() -> (Database, PathBuf) {
+        let temp_dir = env::temp_dir().join(format!("cube_elite_gdpr_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let db = Database::new(temp_dir.clone()).unwrap();
+
+        let conn = db.conn.lock().unwrap();
+        conn.execute_batch(&format!(
+            r#"
+            INSERT INTO tenants (id, name, slug, created_at, updated_at) VALUES ('t1', 'Acme', 'acme', 1, 1);
+            INSERT INTO tenant_users (id, tenant_id, user_id, role, created_at, updated_at) VALUES ('tu1', 't1', '{u}', 'admin', 1, 1);
+            INSERT INTO tenant_users (id, tenant_id, user_id, role, invited_by, created_at, updated_at) VALUES ('tu2', 't1', 'other-user', 'member', '{u}', 1, 1);
+            INSERT INTO tenant_invitations (id, tenant_id, email, invited_by, token, expires_at, created_at) VALUES ('inv1', 't1', 'friend@example.com', '{u}', 'tok', 9, 1);
+            INSERT INTO tenant_audit_log (id, tenant_id, user_id, action, new_values, ip_address, created_at) VALUES ('a1', 't1', '{u}', 'tenant.created', '{{"email":"jane@example.com"}}', '10.0.0.7', 1);
+            INSERT INTO browser_profiles (id, user_id, tenant_id, name, geolocation, created_at, updated_at) VALUES ('p1', '{u}', 't1', 'Jane Work', 'Berlin', 1, 1);
+            INSERT INTO browser_profile_cookies (id, profile_id, domain, name, value, created_at) VALUES ('c1', 'p1', 'example.com', 'sid', 'secret', 1);
+            INSERT INTO investors (id, user_id, name, email, total_invested, created_at, updated_at) VALUES ('i1', '{u}', 'Jane Doe', 'jane@example.com', 5000, 1, 1);
+            INSERT INTO investments (id, investor_id, tier, amount, interest_rate, term_months, start_date, maturity_date, created_at, updated_at) VALUES ('inv-1', 'i1', 'angel', 5000, 0.08, 12, '2026-01-01', '2027-01-01', 1, 1);
+            "#,
+            u = ERASE_USER
+        )).unwrap();
+        drop(conn);
+
+        (db, temp_dir)
+    }
+
+    /// Every text value in every table, for scanning for leftover PII
+    fn all_text(db: &Database) -> String {
+        let conn = db.conn.lock().unwrap();
+        let tables: Vec<String> = conn
+            .prepare("SELECT name FROM sqlite_master WHERE type = 'table'")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        let mut text = String::new();
+        for table in tables {
+            let rows = Database::rows_as_json(&conn, &format!("SELECT * FROM {}", table), []).unwrap();
+            for row in rows {
+                text.push_str(&row.to_string());
+            }
+        }
+        text
+    }
+
+    fn foreign_key_violations(db: &Database) -> usize {
+        let conn = db.conn.lock().unwrap();
+        let mut stmt = conn.prepare("PRAGMA foreign_key_check").unwrap();
+        let count = stmt.query_map([], |_| Ok(())).unwrap().count();
+        count
+    }
+
+    #[test]
+    fn test_export_user_data_is_complete() {
+        let (db, temp_dir) = seeded_database();
+
+        let export = db.export_user_data(ERASE_USER).unwrap();
+        let counts = export.record_counts();
+        assert_eq!(counts.get("tenant_users"), Some(&1));
+        assert_eq!(counts.get("tenant_invitations"), Some(&1));
+        assert_eq!(counts.get("tenant_audit_log"), Some(&1));
+        assert_eq!(counts.get("browser_profiles"), Some(&1));
+        assert_eq!(counts.get("browser_profile_cookies"), Some(&1));
+        assert_eq!(counts.get("investors"), Some(&1));
+        assert_eq!(counts.get("investments"), Some(&1));
+        assert_eq!(counts.len(), 7);
+        assert_eq!(export.tables["investors"][0]["email"], "jane@example.com");
+        assert_eq!(export.tables["investments"][0]["amount"], 5000.0);
+        assert!(export.tables["tenant_users"].iter().all(|row| row["user_id"] == ERASE_USER));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_erase_user_data_removes_pii_and_keeps_integrity() {
+        for mode in [ErasureMode::Anonymize, ErasureMode::HardDelete] {
+            let (db, temp_dir) = seeded_database();
+
+            let report = db.erase_user_data(ERASE_USER, mode).unwrap();
+            assert_eq!(report.tenant_ids, vec!["t1".to_string()]);
+
+            let text = all_text(&db);
+            for pii in [ERASE_USER, "jane@example.com", "Jane Doe", "Jane Work", "Berlin", "10.0.0.7", "secret"] {
+                assert!(!text.contains(pii), "{:?} left {} behind", mode, pii);
+            }
+            assert_eq!(foreign_key_violations(&db), 0);
+            assert!(db.export_user_data(ERASE_USER).unwrap().record_counts().is_empty());
+
+            let conn = db.conn.lock().unwrap();
+            // Aggregates and other people's records survive
+            let invested: f64 = conn.query_row("SELECT total_invested FROM investors WHERE id = 'i1'", [], |r| r.get(0)).unwrap();
+            assert_eq!(invested, 5000.0);
+            let investments: i64 = conn.query_row("SELECT COUNT(*) FROM investments WHERE investor_id = 'i1'", [], |r| r.get(0)).unwrap();
+            assert_eq!(investments, 1);
+            let invitations: i64 = conn.query_row("SELECT COUNT(*) FROM tenant_invitations", [], |r| r.get(0)).unwrap();
+            assert_eq!(invitations, 1);
+            let memberships: i64 = conn.query_row("SELECT COUNT(*) FROM tenant_users", [], |r| r.get(0)).unwrap();
+            assert_eq!(memberships, if mode == ErasureMode::Anonymize { 2 } else { 1 });
+
+            // The erasure is audited without naming the user
+            let logged: String = conn.query_row(
+                "SELECT resource_id FROM tenant_audit_log WHERE action = 'user.data_erased'",
+                [],
+                |r| r.get(0),
+            ).unwrap();
+            assert_eq!(logged, report.erasure_id);
+            drop(conn);
+
+            std::fs::remove_dir_all(&temp_dir).unwrap();
+        }
+    }
 }
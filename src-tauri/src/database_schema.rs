@@ -572,6 +572,30 @@ fn create_sso_tables(conn: &Connection) -> Result<()> {
         [],
     )?;
 
+    // SSO identities (accounts provisioned from IdP assertions)
+    conn.execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS sso_identities (
+            id TEXT PRIMARY KEY,
+            tenant_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            provider_id TEXT NOT NULL,
+            subject TEXT,
+            email TEXT NOT NULL,
+            first_name TEXT,
+            last_name TEXT,
+            display_name TEXT,
+            groups TEXT,
+            last_login_at INTEGER,
+            created_at INTEGER NOT NULL,
+            updated_at INTEGER NOT NULL,
+            FOREIGN KEY (tenant_id) REFERENCES tenants(id),
+            UNIQUE (tenant_id, email)
+        )
+        "#,
+        [],
+    )?;
+
     // SSO/LDAP Audit Log
     conn.execute(
         r#"
@@ -609,6 +633,10 @@ fn create_sso_tables(conn: &Connection) -> Result<()> {
         "CREATE INDEX IF NOT EXISTS idx_ldap_users_email ON ldap_users(email)",
         [],
     )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_sso_identities_user_id ON sso_identities(user_id)",
+        [],
+    )?;
 
     Ok(())
 }
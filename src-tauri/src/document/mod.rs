@@ -6,6 +6,7 @@
 
 // Export the production implementation
 mod mod_v2;
+mod office;
 
 // Re-export all public types and functions from mod_v2
 #[allow(unused_imports)] // These are re-exported for external use
@@ -28,6 +29,13 @@ pub use mod_v2::{
     ValidationResult,
 };
 
+// Office Open XML (DOCX/XLSX) extraction
+#[allow(unused_imports)]
+pub use office::{
+    detect_office_type, extract_docx, extract_office_file, extract_xlsx, stream_xlsx_rows,
+    DocxBlock, EmbeddedImage, OfficeDocument, SheetCell, SheetData, SheetRow, MAX_SHEET_ROWS,
+};
+
 // ═══════════════════════════════════════════════════════════════════════════
 // LEGACY SUPPORT (Deprecated - Use DocumentProcessor instead)
 // ═══════════════════════════════════════════════════════════════════════════
@@ -10,6 +10,7 @@
 // - Binary data support (Vec<u8> alternative API)
 // - Confidence scoring validators
 // - Excel/CSV parsing (calamine crate)
+// - DOCX/XLSX text and structure extraction (office module)
 // ═══════════════════════════════════════════════════════════════════════════
 
 use serde::{Deserialize, Serialize};
@@ -20,6 +21,8 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use super::office;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // TYPES & CONSTANTS
 // ═══════════════════════════════════════════════════════════════════════════
@@ -539,6 +542,9 @@ impl DocumentProcessor {
             DocumentType::HTML => self.extract_html(&path_buf)?,
             DocumentType::JSON => self.extract_json(&path_buf)?,
             DocumentType::Markdown => self.extract_text_file(&path_buf)?,
+            DocumentType::DOCX | DocumentType::XLSX => {
+                self.extract_office_text(&path_buf, doc_type)?
+            }
             _ => return Err(format!("Unsupported document type: {:?}", doc_type)),
         };
 
@@ -600,6 +606,25 @@ impl DocumentProcessor {
                     },
                 })
             }
+            DocumentType::DOCX | DocumentType::XLSX => {
+                let doc = match office::detect_office_type(std::io::Cursor::new(&data)) {
+                    Some(DocumentType::XLSX) => office::extract_xlsx(std::io::Cursor::new(&data))?,
+                    Some(DocumentType::DOCX) => office::extract_docx(&data)?,
+                    _ => return Err("ZIP archive is not a Word or Excel document".to_string()),
+                };
+
+                Ok(ExtractionResult {
+                    success: true,
+                    text: doc.text,
+                    page_count: None,
+                    confidence,
+                    metadata: DocumentMetadata {
+                        file_size: Some(data.len() as u64),
+                        mime_type: doc.document_type.mime_type().to_string(),
+                        ..Default::default()
+                    },
+                })
+            }
             _ => Err(format!("Unsupported binary document type: {:?}", doc_type)),
         }
     }
@@ -659,8 +684,27 @@ impl DocumentProcessor {
     pub async fn detect_type(&self, path: &str) -> Result<DocumentType, String> {
         let path_buf = PathBuf::from(path);
         let ext = path_buf.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let by_extension = DocumentType::from_extension(ext);
+
+        // Office files are ZIP packages; trust the parts inside over the name
+        if matches!(
+            by_extension,
+            DocumentType::DOCX | DocumentType::XLSX | DocumentType::Unknown
+        ) {
+            if let Ok(file) = fs::File::open(&path_buf) {
+                if let Some(detected) = office::detect_office_type(std::io::BufReader::new(file)) {
+                    return Ok(detected);
+                }
+            }
+        }
+
+        Ok(by_extension)
+    }
 
-        Ok(DocumentType::from_extension(ext))
+    /// Extract structured content from a .docx or .xlsx file
+    pub async fn extract_office(&self, path: &str) -> Result<office::OfficeDocument, String> {
+        let doc_type = self.detect_type(path).await?;
+        office::extract_office_file(Path::new(path), doc_type)
     }
 
     /// Get cache statistics
@@ -729,6 +773,28 @@ impl DocumentProcessor {
         })
     }
 
+    fn extract_office_text(
+        &self,
+        path: &Path,
+        doc_type: DocumentType,
+    ) -> Result<ExtractionResult, String> {
+        let doc = office::extract_office_file(path, doc_type)?;
+        let file_size = fs::metadata(path).ok().map(|m| m.len());
+
+        Ok(ExtractionResult {
+            success: true,
+            text: doc.text,
+            page_count: None,
+            confidence: 1.0,
+            metadata: DocumentMetadata {
+                title: path.file_stem().and_then(|s| s.to_str()).map(String::from),
+                file_size,
+                mime_type: doc_type.mime_type().to_string(),
+                ..Default::default()
+            },
+        })
+    }
+
     fn extract_json(&self, path: &Path) -> Result<ExtractionResult, String> {
         let json = fs::read_to_string(path).map_err(|e| format!("Failed to read JSON: {}", e))?;
 
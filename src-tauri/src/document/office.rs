@@ -0,0 +1,582 @@
+// ═══════════════════════════════════════════════════════════════════════════
+// CUBE Elite - Office Open XML Extraction
+// ═══════════════════════════════════════════════════════════════════════════
+// Text and structure from .docx (paragraphs, headings, tables) and cell data
+// from .xlsx (per-sheet rows, cached formula results). Embedded images are
+// listed, not decoded. Spreadsheet rows are streamed from the sheet XML so
+// large workbooks are never materialised as a whole range.
+// ═══════════════════════════════════════════════════════════════════════════
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::path::Path;
+
+use calamine::{Data, Reader, Xlsx};
+use docx_rs::{
+    DocumentChild, InsertChild, Paragraph, ParagraphChild, Run, RunChild, Table, TableCellContent,
+    TableChild, TableRowChild,
+};
+use serde::{Deserialize, Serialize};
+
+use super::mod_v2::DocumentType;
+
+/// Rows kept per sheet in an [`OfficeDocument`]; later rows are only counted
+pub const MAX_SHEET_ROWS: usize = 10_000;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TYPES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Structured content of a .docx or .xlsx file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OfficeDocument {
+    pub document_type: DocumentType,
+    /// Body of a .docx, in document order
+    pub blocks: Vec<DocxBlock>,
+    /// Worksheets of a .xlsx, in workbook order
+    pub sheets: Vec<SheetData>,
+    pub images: Vec<EmbeddedImage>,
+    /// Plain-text rendering of the blocks or sheets
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DocxBlock {
+    Heading { level: u8, text: String },
+    Paragraph { text: String },
+    Table { rows: Vec<Vec<String>> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SheetData {
+    pub name: String,
+    pub rows: Vec<SheetRow>,
+    /// Rows in the sheet, including any beyond `MAX_SHEET_ROWS`
+    pub total_rows: usize,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SheetRow {
+    /// Zero-based row index in the sheet
+    pub index: u32,
+    pub cells: Vec<SheetCell>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SheetCell {
+    /// A1-style reference
+    pub reference: String,
+    pub column: u32,
+    /// Displayed value; for formula cells the result cached by the authoring app
+    pub value: String,
+    pub formula: Option<String>,
+}
+
+/// An image stored in the package's media folder
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmbeddedImage {
+    pub path: String,
+    pub content_type: String,
+    pub size: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// DETECTION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// DOCX or XLSX from the package's part names; `None` for other ZIP files
+pub fn detect_office_type<R: Read + Seek>(reader: R) -> Option<DocumentType> {
+    let archive = zip::ZipArchive::new(reader).ok()?;
+    if archive.index_for_name("word/document.xml").is_some() {
+        Some(DocumentType::DOCX)
+    } else if archive.index_for_name("xl/workbook.xml").is_some() {
+        Some(DocumentType::XLSX)
+    } else {
+        None
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// EXTRACTION
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Extract a .docx or .xlsx file
+pub fn extract_office_file(path: &Path, doc_type: DocumentType) -> Result<OfficeDocument, String> {
+    match doc_type {
+        DocumentType::DOCX => {
+            let data = fs::read(path).map_err(|e| format!("Failed to read DOCX: {}", e))?;
+            extract_docx(&data)
+        }
+        DocumentType::XLSX => {
+            let file = fs::File::open(path).map_err(|e| format!("Failed to open XLSX: {}", e))?;
+            extract_xlsx(BufReader::new(file))
+        }
+        other => Err(format!("Not an Office Open XML document: {:?}", other)),
+    }
+}
+
+pub fn extract_docx(data: &[u8]) -> Result<OfficeDocument, String> {
+    let docx = docx_rs::read_docx(data).map_err(|e| format!("DOCX parsing failed: {}", e))?;
+
+    let mut blocks = Vec::new();
+    for child in &docx.document.children {
+        match child {
+            DocumentChild::Paragraph(paragraph) => {
+                let text = paragraph_text(paragraph);
+                if text.trim().is_empty() {
+                    continue;
+                }
+                match heading_level(paragraph) {
+                    Some(level) => blocks.push(DocxBlock::Heading { level, text }),
+                    None => blocks.push(DocxBlock::Paragraph { text }),
+                }
+            }
+            DocumentChild::Table(table) => blocks.push(DocxBlock::Table {
+                rows: table_rows(table),
+            }),
+            _ => {}
+        }
+    }
+
+    let text = blocks
+        .iter()
+        .map(|block| match block {
+            DocxBlock::Heading { level, text } => {
+                format!("{} {}", "#".repeat(*level as usize), text)
+            }
+            DocxBlock::Paragraph { text } => text.clone(),
+            DocxBlock::Table { rows } => rows
+                .iter()
+                .map(|row| row.join("\t"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    Ok(OfficeDocument {
+        document_type: DocumentType::DOCX,
+        blocks,
+        sheets: Vec::new(),
+        images: list_media(Cursor::new(data), "word/media/")?,
+        text,
+    })
+}
+
+pub fn extract_xlsx<R: Read + Seek>(mut reader: R) -> Result<OfficeDocument, String> {
+    let images = list_media(&mut reader, "xl/media/")?;
+    reader
+        .rewind()
+        .map_err(|e| format!("Failed to rewind XLSX: {}", e))?;
+
+    let mut sheets: Vec<SheetData> = Vec::new();
+    stream_xlsx_rows(reader, |sheet, row| {
+        if sheets.last().map(|s| s.name.as_str()) != Some(sheet) {
+            sheets.push(SheetData {
+                name: sheet.to_string(),
+                rows: Vec::new(),
+                total_rows: 0,
+                truncated: false,
+            });
+        }
+        let data = sheets.last_mut().expect("sheet was just pushed");
+        data.total_rows += 1;
+        if data.rows.len() < MAX_SHEET_ROWS {
+            data.rows.push(row);
+        } else {
+            data.truncated = true;
+        }
+    })?;
+
+    let text = sheets
+        .iter()
+        .map(|sheet| {
+            let mut out = format!("## {}", sheet.name);
+            for row in &sheet.rows {
+                out.push('\n');
+                out.push_str(&row_text(row));
+            }
+            out
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    Ok(OfficeDocument {
+        document_type: DocumentType::XLSX,
+        blocks: Vec::new(),
+        sheets,
+        images,
+        text,
+    })
+}
+
+/// Feed every non-empty row of every sheet to `on_row`, one row in memory at a time
+pub fn stream_xlsx_rows<R, F>(reader: R, mut on_row: F) -> Result<(), String>
+where
+    R: Read + Seek,
+    F: FnMut(&str, SheetRow),
+{
+    let mut workbook: Xlsx<R> =
+        Xlsx::new(reader).map_err(|e| format!("XLSX parsing failed: {}", e))?;
+
+    for name in workbook.sheet_names() {
+        // Formulas live in the same XML as the values but come out of a
+        // separate pass; only formula cells are kept
+        let mut formulas = HashMap::new();
+        {
+            let mut cells = workbook
+                .worksheet_cells_reader(&name)
+                .map_err(|e| format!("Failed to read sheet '{}': {}", name, e))?;
+            while let Some(cell) = cells
+                .next_formula()
+                .map_err(|e| format!("Failed to read formulas in '{}': {}", name, e))?
+            {
+                if !cell.get_value().is_empty() {
+                    formulas.insert(cell.get_position(), cell.get_value().clone());
+                }
+            }
+        }
+
+        let mut cells = workbook
+            .worksheet_cells_reader(&name)
+            .map_err(|e| format!("Failed to read sheet '{}': {}", name, e))?;
+        let mut current: Option<SheetRow> = None;
+        while let Some(cell) = cells
+            .next_cell()
+            .map_err(|e| format!("Failed to read cells in '{}': {}", name, e))?
+        {
+            let (row, column) = cell.get_position();
+            let value = Data::from(cell.get_value().clone()).to_string();
+            let formula = formulas.remove(&(row, column)).map(|f| format!("={}", f));
+            if value.is_empty() && formula.is_none() {
+                continue;
+            }
+
+            if current.as_ref().map(|r| r.index) != Some(row) {
+                if let Some(done) = current.take() {
+                    on_row(&name, done);
+                }
+                current = Some(SheetRow {
+                    index: row,
+                    cells: Vec::new(),
+                });
+            }
+            if let Some(current) = current.as_mut() {
+                current.cells.push(SheetCell {
+                    reference: cell_reference(row, column),
+                    column,
+                    value,
+                    formula,
+                });
+            }
+        }
+        if let Some(done) = current {
+            on_row(&name, done);
+        }
+    }
+
+    Ok(())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// HELPERS
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// "Heading2" → 2, "Title" → 1; style ids as written by Word and LibreOffice
+fn heading_level(paragraph: &Paragraph) -> Option<u8> {
+    let style = paragraph.property.style.as_ref()?.val.to_lowercase();
+    if style == "title" {
+        return Some(1);
+    }
+    style
+        .strip_prefix("heading")
+        .and_then(|level| level.trim().parse::<u8>().ok())
+        .filter(|level| (1..=9).contains(level))
+}
+
+fn paragraph_text(paragraph: &Paragraph) -> String {
+    let mut text = String::new();
+    push_children_text(&paragraph.children, &mut text);
+    text
+}
+
+fn push_children_text(children: &[ParagraphChild], out: &mut String) {
+    for child in children {
+        match child {
+            ParagraphChild::Run(run) => push_run_text(run, out),
+            ParagraphChild::Hyperlink(link) => push_children_text(&link.children, out),
+            ParagraphChild::Insert(insert) => {
+                for child in &insert.children {
+                    if let InsertChild::Run(run) = child {
+                        push_run_text(run, out);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn push_run_text(run: &Run, out: &mut String) {
+    for child in &run.children {
+        match child {
+            RunChild::Text(text) => out.push_str(&text.text),
+            RunChild::Tab(_) => out.push('\t'),
+            RunChild::Break(_) => out.push('\n'),
+            _ => {}
+        }
+    }
+}
+
+fn table_rows(table: &Table) -> Vec<Vec<String>> {
+    table
+        .rows
+        .iter()
+        .map(|TableChild::TableRow(row)| {
+            row.cells
+                .iter()
+                .map(|TableRowChild::TableCell(cell)| {
+                    cell.children
+                        .iter()
+                        .filter_map(|content| match content {
+                            TableCellContent::Paragraph(paragraph) => {
+                                Some(paragraph_text(paragraph))
+                            }
+                            // Nested tables are flattened into the cell
+                            TableCellContent::Table(nested) => Some(
+                                table_rows(nested)
+                                    .iter()
+                                    .map(|row| row.join(" "))
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            ),
+                            _ => None,
+                        })
+                        .filter(|text| !text.is_empty())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn row_text(row: &SheetRow) -> String {
+    // Keep column positions so sparse rows still line up
+    let mut out = String::new();
+    let mut column = 0;
+    for cell in &row.cells {
+        while column < cell.column {
+            out.push('\t');
+            column += 1;
+        }
+        out.push_str(&cell.value);
+    }
+    out
+}
+
+/// Zero-based (row, column) → "B3"
+fn cell_reference(row: u32, column: u32) -> String {
+    let mut letters = Vec::new();
+    let mut n = column + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        letters.push(b'A' + rem as u8);
+        n = (n - 1) / 26;
+    }
+    letters.reverse();
+    format!("{}{}", String::from_utf8_lossy(&letters), row + 1)
+}
+
+fn list_media<R: Read + Seek>(reader: R, prefix: &str) -> Result<Vec<EmbeddedImage>, String> {
+    let mut archive =
+        zip::ZipArchive::new(reader).map_err(|e| format!("Failed to open package: {}", e))?;
+    let mut images = Vec::new();
+    for index in 0..archive.len() {
+        let entry = archive
+            .by_index(index)
+            .map_err(|e| format!("Failed to read package entry: {}", e))?;
+        let name = entry.name();
+        if name.starts_with(prefix) && !entry.is_dir() {
+            images.push(EmbeddedImage {
+                path: name.to_string(),
+                content_type: image_content_type(name).to_string(),
+                size: entry.size(),
+            });
+        }
+    }
+    Ok(images)
+}
+
+fn image_content_type(name: &str) -> &'static str {
+    let ext = name.rsplit('.').next().unwrap_or("").to_lowercase();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "tif" | "tiff" => "image/tiff",
+        "svg" => "image/svg+xml",
+        "emf" => "image/x-emf",
+        "wmf" => "image/x-wmf",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn text_paragraph(text: &str) -> docx_rs::Paragraph {
+        docx_rs::Paragraph::new().add_run(docx_rs::Run::new().add_text(text))
+    }
+
+    fn sample_docx() -> Vec<u8> {
+        let cell = |text: &str| docx_rs::TableCell::new().add_paragraph(text_paragraph(text));
+        let table = docx_rs::Table::new(vec![
+            docx_rs::TableRow::new(vec![cell("Region"), cell("Revenue")]),
+            docx_rs::TableRow::new(vec![cell("North"), cell("1200")]),
+        ]);
+        let mut buf = Cursor::new(Vec::new());
+        docx_rs::Docx::new()
+            .add_paragraph(text_paragraph("Quarterly Report").style("Heading1"))
+            .add_paragraph(text_paragraph("Revenue grew in every region."))
+            .add_table(table)
+            .build()
+            .pack(&mut buf)
+            .unwrap();
+        buf.into_inner()
+    }
+
+    /// Two sheets, inline strings, a formula with its cached result and an image
+    fn sample_xlsx() -> Vec<u8> {
+        let sheet = |rows: &str| {
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>{}</sheetData></worksheet>"#,
+                rows
+            )
+        };
+        let parts = [
+            (
+                "[Content_Types].xml",
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/></Types>"#
+                    .to_string(),
+            ),
+            (
+                "xl/workbook.xml",
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets><sheet name="Totals" sheetId="1" r:id="rId1"/><sheet name="Notes" sheetId="2" r:id="rId2"/></sheets></workbook>"#
+                    .to_string(),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet2.xml"/></Relationships>"#
+                    .to_string(),
+            ),
+            (
+                "xl/worksheets/sheet1.xml",
+                sheet(
+                    r#"<row r="1"><c r="A1" t="inlineStr"><is><t>Item</t></is></c><c r="B1" t="inlineStr"><is><t>Amount</t></is></c></row><row r="2"><c r="A2" t="inlineStr"><is><t>Rent</t></is></c><c r="B2"><v>1500</v></c></row><row r="3"><c r="A3" t="inlineStr"><is><t>Total</t></is></c><c r="B3"><f>SUM(B2:B2)</f><v>1500</v></c></row>"#,
+                ),
+            ),
+            (
+                "xl/worksheets/sheet2.xml",
+                sheet(r#"<row r="2"><c r="C2" t="inlineStr"><is><t>Paid in full</t></is></c></row>"#),
+            ),
+            ("xl/media/image1.png", "not really a png".to_string()),
+        ];
+
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, body) in parts {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(body.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_docx_paragraphs_headings_and_table() {
+        let data = sample_docx();
+        assert_eq!(
+            detect_office_type(Cursor::new(&data)),
+            Some(DocumentType::DOCX)
+        );
+
+        let doc = extract_docx(&data).unwrap();
+        assert_eq!(
+            doc.blocks,
+            vec![
+                DocxBlock::Heading {
+                    level: 1,
+                    text: "Quarterly Report".to_string()
+                },
+                DocxBlock::Paragraph {
+                    text: "Revenue grew in every region.".to_string()
+                },
+                DocxBlock::Table {
+                    rows: vec![
+                        vec!["Region".to_string(), "Revenue".to_string()],
+                        vec!["North".to_string(), "1200".to_string()],
+                    ]
+                },
+            ]
+        );
+        assert_eq!(
+            doc.text,
+            "# Quarterly Report\n\nRevenue grew in every region.\n\nRegion\tRevenue\nNorth\t1200"
+        );
+        assert!(doc.images.is_empty());
+    }
+
+    #[test]
+    fn test_xlsx_sheets_formulas_and_images() {
+        let data = sample_xlsx();
+        assert_eq!(
+            detect_office_type(Cursor::new(&data)),
+            Some(DocumentType::XLSX)
+        );
+
+        let doc = extract_xlsx(Cursor::new(data)).unwrap();
+        assert_eq!(
+            doc.sheets
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Totals", "Notes"]
+        );
+
+        let totals = &doc.sheets[0];
+        assert_eq!(totals.total_rows, 3);
+        assert!(!totals.truncated);
+        let total = &totals.rows[2].cells[1];
+        assert_eq!(total.reference, "B3");
+        assert_eq!(total.value, "1500");
+        assert_eq!(total.formula.as_deref(), Some("=SUM(B2:B2)"));
+        assert_eq!(totals.rows[1].cells[1].formula, None);
+
+        let notes = &doc.sheets[1];
+        assert_eq!(notes.rows[0].index, 1);
+        assert_eq!(notes.rows[0].cells[0].reference, "C2");
+
+        assert_eq!(
+            doc.text,
+            "## Totals\nItem\tAmount\nRent\t1500\nTotal\t1500\n\n## Notes\n\t\tPaid in full"
+        );
+        assert_eq!(
+            doc.images,
+            vec![EmbeddedImage {
+                path: "xl/media/image1.png".to_string(),
+                content_type: "image/png".to_string(),
+                size: 16,
+            }]
+        );
+    }
+}
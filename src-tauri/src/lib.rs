@@ -94,6 +94,8 @@ pub fn run() {
             commands::notes::update_task,
             commands::notes::get_all_categories,
             commands::notes::get_notes_stats,
+            commands::notes::notes_render_markdown,
+            commands::notes::notes_toggle_task,
 
             // === AI SERVICE (OpenAI Integration) ===
             commands::services::set_ai_api_key,
@@ -183,8 +185,10 @@ pub fn run() {
             commands::reading_list::update_article_progress,
             commands::reading_list::toggle_article_favorite,
             commands::reading_list::search_reading_list,
+            commands::reading_list::search_reading_list_by_tags,
             commands::reading_list::get_reading_list_stats,
             commands::reading_list::get_reading_list_tags,
+            commands::reading_list::cache_article_images,
 
             // === MEDIA PLAYER ===
             commands::media::get_all_media,
@@ -199,6 +203,10 @@ pub fn run() {
             commands::media::add_to_playlist,
             commands::media::remove_from_playlist,
             commands::media::get_media_stats,
+            commands::media::media_get_playback_settings,
+            commands::media::media_set_crossfade,
+            commands::media::media_set_gapless,
+            commands::media::media_plan_transition,
 
             // === TERMINAL EMULATOR ===
             commands::terminal::create_terminal_session,
@@ -210,6 +218,7 @@ pub fn run() {
             commands::terminal::add_terminal_command_history,
             commands::terminal::get_terminal_session_history,
             commands::terminal::search_terminal_history,
+            commands::terminal::terminal_fuzzy_search,
             commands::terminal::clear_terminal_session_history,
             commands::terminal::get_terminal_config,
             commands::terminal::update_terminal_config,
@@ -253,11 +262,18 @@ pub fn run() {
             commands::passwords_new::verify_master_password,
             commands::passwords_new::get_master_password_config,
             commands::passwords_new::change_master_password,
+            commands::passwords_new::get_master_password_kdf_status,
+            commands::passwords_new::unlock_password_vault,
+            commands::passwords_new::lock_password_vault,
+            commands::passwords_new::is_password_vault_unlocked,
             commands::passwords_new::get_all_passwords,
             commands::passwords_new::save_password,
             commands::passwords_new::update_password_entry,
             commands::passwords_new::delete_password,
             commands::passwords_new::decrypt_password,
+            commands::passwords_new::save_totp_secret,
+            commands::passwords_new::get_totp_code,
+            commands::passwords_new::delete_totp_secret,
             commands::passwords_new::update_password_last_used,
             commands::passwords_new::get_password_categories,
             commands::passwords_new::get_password_stats,
@@ -266,6 +282,16 @@ pub fn run() {
             commands::passwords_new::search_passwords,
             commands::passwords_new::export_passwords,
             commands::passwords_new::import_passwords,
+            commands::passwords_new::share_password,
+            commands::passwords_new::retrieve_shared_password,
+            commands::passwords_new::get_emergency_public_key,
+            commands::passwords_new::grant_emergency_contact,
+            commands::passwords_new::request_emergency_access,
+            commands::passwords_new::deny_emergency_access,
+            commands::passwords_new::get_emergency_access_status,
+            commands::passwords_new::list_emergency_contacts,
+            commands::passwords_new::revoke_emergency_contact,
+            commands::passwords_new::open_emergency_vault,
 
             // === SESSION PERSISTENCE ===
             commands::session_persistence::save_browser_session,
@@ -312,6 +338,10 @@ pub fn run() {
             commands::ai::openai_completion,
             commands::ai::claude_completion,
             commands::ai::gemini_completion,
+            commands::ai::openai_completion_stream,
+            commands::ai::claude_completion_stream,
+            commands::ai::gemini_completion_stream,
+            commands::ai::cancel_ai_stream,
 
             // === AI SERVICE (Smart Selectors & NLP) ===
             commands::ai_commands::ai_suggest_selectors,
@@ -329,6 +359,8 @@ pub fn run() {
             commands::ocr_system::ocr_extract_from_file,
             commands::ocr_system::ocr_extract_from_base64,
             commands::ocr_system::ocr_extract_from_region,
+            commands::ocr_system::ocr_extract_batch,
+            commands::ocr_system::ocr_to_searchable_pdf,
             commands::ocr_system::ocr_get_available_languages,
             commands::ocr_system::ocr_get_installed_languages,
             commands::ocr_system::ocr_is_language_available,
@@ -367,6 +399,7 @@ pub fn run() {
             commands::screen_recording::screen_recording_stop,
             commands::screen_recording::screen_recording_pause,
             commands::screen_recording::screen_recording_resume,
+            commands::screen_recording::screen_recording_update_webcam_overlay,
             commands::screen_recording::get_recording_session,
             commands::screen_recording::list_recording_sessions,
             commands::screen_recording::delete_recording,
@@ -457,6 +490,12 @@ pub fn run() {
             commands::ai_trainer::update_workflow,
             commands::ai_trainer::delete_workflow,
             commands::ai_trainer::execute_workflow,
+            commands::ai_trainer::workflow_debug_start,
+            commands::ai_trainer::workflow_debug_step,
+            commands::ai_trainer::workflow_debug_continue,
+            commands::ai_trainer::workflow_debug_set_breakpoints,
+            commands::ai_trainer::workflow_debug_get_state,
+            commands::ai_trainer::workflow_debug_stop,
             commands::ai_trainer::analyze_workflow_with_ai,
             commands::ai_trainer::get_execution_history,
 
@@ -477,14 +516,18 @@ pub fn run() {
             // === WORKFLOW BUILDER - ELITE (BEATS ZAPIER) ===
             commands::workflow_commands::execute_workflow_node,
             commands::workflow_commands::workflow_save,
+            commands::workflow_commands::workflow_execute,
             commands::workflow_commands::workflow_load_all,
             commands::workflow_commands::workflow_load,
             commands::workflow_commands::workflow_delete,
+            commands::workflow_commands::workflow_approve_step,
+            commands::workflow_commands::workflow_get_pending_approvals,
             commands::workflow_commands::optimize_workflow_with_ai,
             commands::workflow_commands::generate_workflow_from_description,
 
             // === AUTOMATION STUDIO ===
             commands::automation::automation_execute_flow,
+            commands::automation::automation_execute_flow_dataset,
             commands::automation::automation_save_flow,
             commands::automation::automation_load_flows,
             commands::automation::automation_delete_flow,
@@ -499,6 +542,7 @@ pub fn run() {
             commands::extractor::extractor_preview,
             commands::extractor::extractor_extract,
             commands::extractor::extractor_suggest_selectors,
+            commands::extractor::extractor_generate_schema_from_prompt,
             commands::extractor::extractor_analyze_page,
             commands::extractor::extractor_export,
 
@@ -511,6 +555,8 @@ pub fn run() {
             commands::data_sources::test_data_source_connection,
             commands::data_sources::get_data_sources_status,
             commands::data_sources::execute_data_source_query,
+            commands::data_sources::data_source_fetch_incremental,
+            commands::data_sources::data_source_reset_cursor,
             commands::data_sources::fetch_from_api_source,
 
             // === VPN SYSTEM ===
@@ -525,6 +571,9 @@ pub fn run() {
             commands::vpn::get_current_ip,
             commands::vpn::get_vpn_logs,
             commands::vpn::refresh_vpn_servers,
+            commands::vpn::vpn_test_server_latency,
+            commands::vpn::vpn_connect_fastest,
+            commands::vpn::vpn_speed_test,
 
             // === AD BLOCKER ===
             commands::vpn::get_adblocker_config,
@@ -575,6 +624,8 @@ pub fn run() {
             commands::vpn::toggle_dns_category,
             commands::vpn::get_threat_stats,
             commands::vpn::get_threat_events,
+            commands::vpn::vpn_test_dns_leak,
+            commands::vpn::vpn_get_dns_protection_status,
 
             // ================================================================
             // PASSWORD ADVANCED COMMANDS
@@ -805,6 +856,7 @@ pub fn run() {
 
             // === SSH TERMINAL ===
             commands::ssh_commands::create_ssh_config,
+            commands::ssh_commands::ssh_import_config,
             commands::ssh_commands::connect_ssh,
             commands::ssh_commands::execute_ssh_command,
             commands::ssh_commands::disconnect_ssh,
@@ -975,6 +1027,8 @@ pub fn run() {
 
             // === BATCH VIDEO PROCESSING ===
             commands::batch_processing::batch_add_to_queue,
+            commands::batch_processing::batch_set_item_priority,
+            commands::batch_processing::batch_set_preemption,
             commands::batch_processing::batch_remove_from_queue,
             commands::batch_processing::batch_clear_queue,
             commands::batch_processing::batch_start_processing,
@@ -1015,6 +1069,7 @@ pub fn run() {
             commands::document_system::document_detect_type,
             commands::document_system::document_parse,
             commands::document_system::document_extract_text,
+            commands::document_system::document_extract_office,
             commands::document_system::document_get_info,
             commands::document_system::document_clear_expired_cache,
             commands::document_system::document_clear_cache,
@@ -1040,6 +1095,8 @@ pub fn run() {
             commands::autofill_system_v2::autofill_format_postal_code,
             commands::autofill_system_v2::autofill_execute,
             commands::autofill_system_v2::autofill_quick_fill,
+            commands::autofill_system_v2::autofill_detect_savable_data,
+            commands::autofill_system_v2::autofill_update_profile_from_form,
             commands::autofill_system_v2::autofill_field_type_to_string,
             commands::autofill_system_v2::autofill_get_profile_stats,
             commands::autofill_system_v2::autofill_get_system_stats,
@@ -1110,6 +1167,7 @@ pub fn run() {
             commands::integration_commands::monday_get_boards,
             commands::integration_commands::monday_create_item,
             commands::integration_commands::monday_update_item,
+            commands::integration_commands::integration_get_queue_status,
             commands::integration_commands::planius_analyze_project,
             commands::integration_commands::detect_files_from_html,
             commands::integration_commands::queue_file_download,
@@ -1157,6 +1215,7 @@ pub fn run() {
             commands::scheduler::scheduler_remove_schedule,
             commands::scheduler::scheduler_toggle_schedule,
             commands::scheduler::scheduler_get_schedules,
+            commands::scheduler::scheduler_get_next_runs,
             commands::scheduler::scheduler_get_queue,
             commands::scheduler::scheduler_start,
             commands::scheduler::scheduler_stop,
@@ -1174,6 +1233,8 @@ pub fn run() {
             // Logs Commands
             commands::monitoring::logs_add,
             commands::monitoring::logs_get,
+            commands::monitoring::logs_query,
+            commands::monitoring::logs_aggregate,
             commands::monitoring::logs_get_recent,
             commands::monitoring::logs_export_json,
             commands::monitoring::logs_export_csv,
@@ -1195,6 +1256,8 @@ pub fn run() {
             commands::api_server::api_server_get_status,
             commands::api_server::api_server_configure,
             commands::api_server::api_server_test_endpoint,
+            commands::api_server::api_server_get_metrics_stream,
+            commands::api_server::api_server_set_metrics_interval,
 
             // === GOOGLE SHEETS COMMANDS ===
             commands::google_sheets::google_sheets_configure,
@@ -1237,6 +1300,8 @@ pub fn run() {
             commands::stealth::proxy_list,
             commands::stealth::proxy_set_strategy,
             commands::stealth::proxy_get_next,
+            commands::stealth::proxy_get_next_geo,
+            commands::stealth::proxy_verify_location,
             commands::stealth::proxy_check_health,
             commands::stealth::proxy_toggle,
             commands::stealth::proxy_get_stats,
@@ -1306,6 +1371,17 @@ pub fn run() {
             commands::devtools::devtools_execute_script,
             commands::devtools::devtools_clear_console,
             commands::devtools::devtools_clear_network,
+            commands::devtools::devtools_attach_storage_context,
+            commands::devtools::devtools_set_active_profile,
+            commands::devtools::devtools_set_storage_item,
+            commands::devtools::devtools_delete_storage_item,
+            commands::devtools::devtools_clear_storage,
+            commands::devtools::devtools_get_cookies,
+            commands::devtools::devtools_set_cookie,
+            commands::devtools::devtools_delete_cookie,
+            commands::devtools::devtools_report_indexeddb,
+            commands::devtools::devtools_list_indexeddb,
+            commands::devtools::devtools_delete_indexeddb,
 
             // === BROWSER PROXY (BYPASS X-FRAME-OPTIONS) ===
             commands::browser_proxy::browser_proxy_start,
@@ -1344,8 +1420,18 @@ pub fn run() {
             commands::cube_browser_commands::cube_get_form_fields,
             commands::cube_browser_commands::cube_fill_form,
             commands::cube_browser_commands::cube_submit_form,
+            commands::cube_browser_commands::cube_detect_fillable_forms,
+            commands::cube_browser_commands::cube_fill_credential,
+            commands::cube_browser_commands::cube_fill_autofill_profile,
+            commands::cube_browser_commands::cube_respond_save_prompt,
+            commands::cube_browser_commands::cube_get_form_site_settings,
+            commands::cube_browser_commands::cube_set_form_site_settings,
             commands::cube_browser_commands::cube_extract_data,
             commands::cube_browser_commands::cube_extract_table,
+            commands::cube_browser_commands::cube_engine_scroll_to_load_all,
+            commands::cube_browser_commands::cube_engine_set_geolocation_override,
+            commands::cube_browser_commands::cube_engine_set_locale_override,
+            commands::cube_browser_commands::cube_engine_get_location_override,
             commands::cube_browser_commands::cube_print_to_pdf,
 
             // === CUBE SHIELD - AD/TRACKER BLOCKER (SUPERIOR TO BRAVE SHIELDS) ===
@@ -1399,6 +1485,10 @@ pub fn run() {
             commands::browser_tab_groups_commands::tab_groups_move_tab,
             commands::browser_tab_groups_commands::tab_groups_ungroup_tab,
             commands::browser_tab_groups_commands::tab_groups_get_ungrouped,
+            commands::browser_tab_groups_commands::tab_groups_get_tab_tree,
+            commands::browser_tab_groups_commands::tab_groups_set_subtree_collapsed,
+            commands::browser_tab_groups_commands::tab_groups_close_tab,
+            commands::browser_tab_groups_commands::tab_groups_close_subtree,
             commands::browser_tab_groups_commands::tab_groups_stack_tabs,
             commands::browser_tab_groups_commands::tab_groups_unstack_tabs,
             commands::browser_tab_groups_commands::tab_groups_add_to_stack,
@@ -1454,8 +1544,13 @@ pub fn run() {
             commands::cube_web_engine_commands::cube_engine_navigate,
             commands::cube_web_engine_commands::cube_engine_fetch_url,
             commands::cube_web_engine_commands::cube_engine_fetch_page,
+            commands::cube_web_engine_commands::cube_engine_provide_http_credentials,
+            commands::cube_web_engine_commands::cube_engine_clear_http_auth,
+            commands::cube_web_engine_commands::cube_engine_resolve,
+            commands::cube_web_engine_commands::cube_engine_set_doh,
             commands::cube_web_engine_commands::cube_engine_go_back,
             commands::cube_web_engine_commands::cube_engine_go_forward,
+            commands::cube_web_engine_commands::cube_engine_get_bfcache_status,
             commands::cube_web_engine_commands::cube_engine_reload,
             commands::cube_web_engine_commands::cube_engine_stop,
             commands::cube_web_engine_commands::cube_engine_execute_script,
@@ -1466,6 +1561,14 @@ pub fn run() {
             commands::cube_web_engine_commands::cube_engine_set_config,
             commands::cube_web_engine_commands::cube_engine_set_headers,
             commands::cube_web_engine_commands::cube_engine_set_user_agent,
+            commands::cube_web_engine_commands::cube_engine_set_media_feature,
+            commands::cube_web_engine_commands::cube_engine_get_media_features,
+            commands::cube_web_engine_commands::cube_engine_set_reduced_motion,
+            commands::cube_web_engine_commands::cube_engine_get_device_presets,
+            commands::cube_web_engine_commands::cube_engine_set_device,
+            commands::cube_web_engine_commands::cube_engine_toggle_orientation,
+            commands::cube_web_engine_commands::cube_engine_get_device,
+            commands::cube_web_engine_commands::cube_engine_generate_lqip,
             commands::cube_web_engine_commands::cube_engine_set_zoom,
             commands::cube_web_engine_commands::cube_engine_get_zoom,
             commands::cube_web_engine_commands::cube_engine_get_history,
@@ -1475,6 +1578,9 @@ pub fn run() {
             commands::cube_web_engine_commands::cube_engine_devtools_get_dom,
             commands::cube_web_engine_commands::cube_engine_devtools_get_network,
             commands::cube_web_engine_commands::cube_engine_devtools_get_console,
+            commands::cube_web_engine_commands::cube_engine_get_accessibility_tree,
+            commands::cube_web_engine_commands::cube_engine_find_by_role,
+            commands::cube_web_engine_commands::cube_engine_a11y_audit,
 
             // === CUBE ENGINE RENDERING (PHASE 1) ===
             commands::cube_engine_rendering::webgl_create_context,
@@ -1541,6 +1647,8 @@ pub fn run() {
             commands::cube_engine_security::csp_get_policy,
             commands::cube_engine_security::csp_check_request,
             commands::cube_engine_security::csp_report_violation,
+            commands::cube_engine_security::sri_check_resource,
+            commands::cube_engine_security::security_get_sri_violations,
             commands::cube_engine_security::cert_get_info,
             commands::cube_engine_security::cert_store_info,
             commands::cube_engine_security::cert_verify,
@@ -1558,6 +1666,9 @@ pub fn run() {
             commands::cube_engine_security::permission_set,
             commands::cube_engine_security::permission_reset,
             commands::cube_engine_security::permission_get_all,
+            commands::cube_engine_security::cube_engine_set_clipboard_permission,
+            commands::cube_engine_security::cube_engine_clipboard_read,
+            commands::cube_engine_security::cube_engine_clipboard_write,
             commands::cube_engine_security::security_get_config,
             commands::cube_engine_security::security_set_config,
             commands::cube_engine_security::security_set_https_only,
@@ -1575,6 +1686,9 @@ pub fn run() {
             commands::cube_engine_performance::prefetch_get_queue,
             commands::cube_engine_performance::prefetch_clear_queue,
             commands::cube_engine_performance::prefetch_update_status,
+            commands::cube_engine_performance::prefetch_report_hover,
+            commands::cube_engine_performance::prefetch_predict_next,
+            commands::cube_engine_performance::prefetch_get_stats,
             commands::cube_engine_performance::memory_get_stats,
             commands::cube_engine_performance::memory_update_stats,
             commands::cube_engine_performance::memory_update_tab,
@@ -1593,12 +1707,20 @@ pub fn run() {
             commands::cube_engine_performance::perf_set_config,
             commands::cube_engine_performance::perf_set_memory_saver,
             commands::cube_engine_performance::perf_set_hardware_acceleration,
+            commands::cube_engine_performance::perf_set_predictive_prefetch,
+            commands::cube_engine_performance::perf_set_data_saver,
 
             // === CUBE ENGINE DEVTOOLS (PHASE 5) ===
             commands::cube_engine_devtools::network_log_request,
             commands::cube_engine_devtools::network_get_logs,
             commands::cube_engine_devtools::network_clear_logs,
             commands::cube_engine_devtools::network_get_request,
+            commands::cube_engine_devtools::devtools_inspect_websocket,
+            commands::cube_engine_devtools::devtools_get_websockets,
+            commands::cube_engine_devtools::devtools_clear_websockets,
+            commands::cube_engine_devtools::devtools_get_heap_walker_script,
+            commands::cube_engine_devtools::devtools_take_heap_snapshot,
+            commands::cube_engine_devtools::devtools_get_heap_summary,
             commands::cube_engine_devtools::console_log_message,
             commands::cube_engine_devtools::console_get_logs,
             commands::cube_engine_devtools::console_clear,
@@ -1635,6 +1757,7 @@ pub fn run() {
             commands::cube_engine_extensions::content_script_inject,
             commands::cube_engine_extensions::content_script_remove,
             commands::cube_engine_extensions::content_script_list,
+            commands::cube_engine_extensions::extension_get_active_content_scripts,
             commands::cube_engine_extensions::background_start,
             commands::cube_engine_extensions::background_stop,
             commands::cube_engine_extensions::background_get,
@@ -1642,6 +1765,8 @@ pub fn run() {
             commands::cube_engine_extensions::ext_storage_set,
             commands::cube_engine_extensions::ext_storage_remove,
             commands::cube_engine_extensions::ext_storage_clear,
+            commands::cube_engine_extensions::extension_open_options,
+            commands::cube_engine_extensions::extension_open_popup,
             commands::cube_engine_extensions::permission_request,
             commands::cube_engine_extensions::permission_grant,
             commands::cube_engine_extensions::permission_revoke,
@@ -1663,6 +1788,11 @@ pub fn run() {
             commands::cube_engine_media::media_toggle_pip,
             commands::cube_engine_media::media_get_session,
             commands::cube_engine_media::media_destroy_session,
+            commands::cube_engine_media::media_set_session_metadata,
+            commands::cube_engine_media::media_get_session_metadata,
+            commands::cube_engine_media::media_set_action_handlers,
+            commands::cube_engine_media::media_get_now_playing,
+            commands::cube_engine_media::media_handle_media_key,
             commands::cube_engine_media::media_download_start,
             commands::cube_engine_media::media_download_pause,
             commands::cube_engine_media::media_download_resume,
@@ -1710,6 +1840,11 @@ pub fn run() {
             commands::collaboration::leave_collaboration_session,
             commands::collaboration::get_active_sessions,
             commands::collaboration::get_session_details,
+            commands::collaboration::collaboration_toggle_audio,
+            commands::collaboration::collaboration_set_mute,
+            commands::collaboration::collaboration_set_push_to_talk,
+            commands::collaboration::collaboration_push_to_talk,
+            commands::collaboration::collaboration_report_audio_level,
 
             // === TOOLBAR TOOLS - FLOATING TOOLBAR BACKEND ===
             commands::toolbar_tools::toolbar_take_screenshot,
@@ -1774,6 +1909,8 @@ pub fn run() {
             commands::cloud_sync::create_cloud_backup,
             commands::cloud_sync::restore_cloud_backup,
             commands::cloud_sync::delete_cloud_backup,
+            commands::cloud_sync::get_backup_retention_policy,
+            commands::cloud_sync::set_backup_retention_policy,
 
             // === ADMIN PANEL (Real backend for admin functionality) ===
             commands::admin::admin_create_user,
@@ -1811,12 +1948,14 @@ pub fn run() {
             commands::admin_releases::admin_publish_release,
             commands::admin_releases::admin_recall_release,
             commands::admin_releases::admin_update_rollout,
+            commands::admin_releases::admin_configure_rollout,
             commands::admin_releases::admin_delete_release,
             commands::admin_releases::admin_add_platform_binary,
             commands::admin_releases::admin_get_release_stats,
             commands::admin_releases::admin_get_update_settings,
             commands::admin_releases::admin_update_settings,
             commands::admin_releases::release_record_download,
+            commands::admin_releases::release_record_install_result,
 
             // === ADMIN AFFILIATES ===
             commands::admin_affiliates::admin_create_affiliate,
@@ -1867,6 +2006,7 @@ pub fn run() {
             commands::admin_files::files_get_starred,
             commands::admin_files::files_get_recent,
             commands::admin_files::files_record_download,
+            commands::admin_files::files_create_archive,
 
             // === CRM COMMANDS ===
             commands::crm::crm_create_contact,
@@ -1967,6 +2107,9 @@ pub fn run() {
             commands::email::email_send_batch,
             commands::email::email_send_simple,
             commands::email::email_send_campaign,
+            commands::email::email_tracking_get_config,
+            commands::email::email_tracking_set_config,
+            commands::email::email_tracking_get_sends,
             commands::email::email_get_status,
             commands::email::email_reset_rate_counters,
 
@@ -1983,6 +2126,7 @@ pub fn run() {
             commands::contacts::contacts_add_to_lists,
             commands::contacts::contacts_remove_from_lists,
             commands::contacts::contacts_update_engagement,
+            commands::contacts::contacts_set_tracking_opt_out,
             commands::contacts::contacts_get_lists,
             commands::contacts::contacts_get_list,
             commands::contacts::contacts_create_list,
@@ -1995,6 +2139,10 @@ pub fn run() {
             commands::contacts::contacts_export_csv,
             commands::contacts::contacts_get_stats,
             commands::contacts::contacts_get_tags,
+            commands::contacts::contacts_find_duplicates,
+            commands::contacts::contacts_merge,
+            commands::contacts::contacts_undo_merge,
+            commands::contacts::contacts_get_merge_log,
 
             // === SOCIAL MEDIA COMMANDS ===
             commands::social::social_connect_account,
@@ -2040,6 +2188,8 @@ pub fn run() {
             commands::research::research_get_stats,
             commands::research::research_get_quick_stats,
             commands::research::research_get_notifications,
+            commands::research::research_set_competitor_monitor,
+            commands::research::research_get_competitor_changes,
 
             // === SEARCH ENGINE COMMANDS ===
             commands::search::search_query,
@@ -2142,6 +2292,8 @@ pub fn run() {
             commands::enterprise_part2::license_get_for_organization,
             commands::enterprise_part2::license_activate,
             commands::enterprise_part2::license_deactivate,
+            commands::enterprise_part2::license_heartbeat,
+            commands::enterprise_part2::license_get_active_seats,
             commands::enterprise_part2::license_validate,
             commands::enterprise_part2::license_check_feature,
             commands::enterprise_part2::license_increment_usage,
@@ -2158,6 +2310,11 @@ pub fn run() {
             commands::enterprise_part2::whitelabel_disable,
             commands::enterprise_part2::whitelabel_update_branding,
             commands::enterprise_part2::whitelabel_update_customization,
+            commands::enterprise_part2::whitelabel_set_theme_tokens,
+            commands::enterprise_part2::whitelabel_get_theme,
+            commands::enterprise_part2::whitelabel_upload_asset,
+            commands::enterprise_part2::whitelabel_get_asset,
+            commands::enterprise_part2::whitelabel_delete_asset,
             commands::enterprise_part2::whitelabel_add_domain,
             commands::enterprise_part2::whitelabel_remove_domain,
             commands::enterprise_part2::whitelabel_verify_domain,
@@ -2166,6 +2323,7 @@ pub fn run() {
             commands::enterprise_part2::whitelabel_test_email,
             commands::enterprise_part2::whitelabel_update_legal,
             commands::enterprise_part2::whitelabel_preview,
+            commands::enterprise_part2::whitelabel_get_preview,
 
             // === ANALYTICS COMMANDS (Dashboards, Reports, Metrics, Alerts) ===
             commands::analytics::dashboard_create,
@@ -2180,6 +2338,9 @@ pub fn run() {
             commands::analytics::dashboard_remove_widget,
             commands::analytics::dashboard_reorder_widgets,
             commands::analytics::dashboard_get_widget_data,
+            commands::analytics::dashboard_subscribe_widget,
+            commands::analytics::dashboard_unsubscribe_widget,
+            commands::analytics::dashboard_close,
             commands::analytics::report_create,
             commands::analytics::report_get,
             commands::analytics::report_list,
@@ -2200,6 +2361,7 @@ pub fn run() {
             commands::analytics::metric_record_batch,
             commands::analytics::metric_query,
             commands::analytics::metric_get_latest,
+            commands::analytics::metric_detect_anomalies,
             commands::analytics::alert_create,
             commands::analytics::alert_get,
             commands::analytics::alert_list,
@@ -2233,6 +2395,9 @@ pub fn run() {
             commands::notifications::notification_delete,
             commands::notifications::notification_delete_all_read,
             commands::notifications::notification_get_unread_count,
+            commands::notifications::notification_get_grouped,
+            commands::notifications::notification_mark_category_read,
+            commands::notifications::notification_apply_synced_read_state,
             commands::notifications::notification_template_create,
             commands::notifications::notification_template_get,
             commands::notifications::notification_template_list,
@@ -2267,6 +2432,10 @@ pub fn run() {
             commands::notifications::sms_send,
             commands::notifications::sms_send_bulk,
             commands::notifications::sms_get_delivery_status,
+            commands::notifications::sms_configure_providers,
+            commands::notifications::sms_estimate_cost,
+            commands::notifications::sms_process_status_webhook,
+            commands::notifications::sms_process_inbound,
 
             // === CUBE MAIL COMMANDS (Full Email Client) ===
             commands::cube_mail_commands::cube_mail_add_account,
@@ -2283,6 +2452,9 @@ pub fn run() {
             commands::cube_mail_commands::cube_mail_archive_emails,
             commands::cube_mail_commands::cube_mail_delete_emails,
             commands::cube_mail_commands::cube_mail_send_email,
+            commands::cube_mail_commands::cube_mail_set_signature,
+            commands::cube_mail_commands::cube_mail_get_signatures,
+            commands::cube_mail_commands::cube_mail_set_signature_options,
             commands::cube_mail_commands::cube_mail_get_screener_config,
             commands::cube_mail_commands::cube_mail_update_screener_config,
             commands::cube_mail_commands::cube_mail_get_screener_pending,
@@ -2295,11 +2467,16 @@ pub fn run() {
             commands::cube_mail_commands::cube_mail_search_emails,
             commands::cube_mail_commands::cube_mail_ai_suggest_reply,
             commands::cube_mail_commands::cube_mail_ai_summarize,
+            commands::cube_mail_commands::cube_mail_save_attachment,
+            commands::cube_mail_commands::cube_mail_release_attachment,
+            commands::cube_mail_commands::cube_mail_get_attachment_scan_config,
+            commands::cube_mail_commands::cube_mail_update_attachment_scan_config,
             // OAuth2 Commands
             commands::cube_mail_commands::cube_mail_oauth2_register,
             commands::cube_mail_commands::cube_mail_oauth2_get_auth_url,
             commands::cube_mail_commands::cube_mail_oauth2_exchange_code,
             commands::cube_mail_commands::cube_mail_oauth2_refresh,
+            commands::cube_mail_commands::cube_mail_reauthorize_account,
             commands::cube_mail_commands::cube_mail_add_account_with_oauth,
             // Database Search Commands
             commands::cube_mail_commands::cube_mail_search_fts,
@@ -2323,6 +2500,9 @@ pub fn run() {
             commands::gamification_commands::gamification_get_challenges,
             commands::gamification_commands::gamification_update_challenge_progress,
             commands::gamification_commands::gamification_get_leaderboard,
+            commands::gamification_commands::gamification_get_leaderboard_page,
+            commands::gamification_commands::gamification_get_flagged_accounts,
+            commands::gamification_commands::gamification_clear_flag,
             commands::gamification_commands::gamification_get_rewards,
             commands::gamification_commands::gamification_claim_reward,
             commands::gamification_commands::gamification_get_badges,
@@ -2492,6 +2672,7 @@ pub fn run() {
             commands::browser_ai_assistant_commands::ai_get_key_points,
             commands::browser_ai_assistant_commands::ai_translate_text,
             commands::browser_ai_assistant_commands::ai_translate_page,
+            commands::browser_ai_assistant_commands::ai_show_original_page,
             commands::browser_ai_assistant_commands::ai_translate_to_english,
             commands::browser_ai_assistant_commands::ai_translate_to_spanish,
             commands::browser_ai_assistant_commands::ai_translate_to_french,
@@ -2548,6 +2729,7 @@ pub fn run() {
             commands::browser_reader_commands::reader_get_recent_articles,
             commands::browser_reader_commands::reader_get_session,
             commands::browser_reader_commands::reader_update_progress,
+            commands::browser_reader_commands::reader_apply_synced_progress,
             commands::browser_reader_commands::reader_get_history,
             commands::browser_reader_commands::reader_get_in_progress,
             commands::browser_reader_commands::reader_create_annotation,
@@ -2588,6 +2770,9 @@ pub fn run() {
             commands::browser_workspaces_commands::workspaces_get_active,
             commands::browser_workspaces_commands::workspaces_get_active_id,
             commands::browser_workspaces_commands::workspaces_switch,
+            commands::browser_workspaces_commands::workspaces_set_schedule,
+            commands::browser_workspaces_commands::workspaces_get_schedules,
+            commands::browser_workspaces_commands::workspaces_confirm_scheduled_switch,
             commands::browser_workspaces_commands::workspaces_update,
             commands::browser_workspaces_commands::workspaces_delete,
             commands::browser_workspaces_commands::workspaces_archive,
@@ -2665,6 +2850,7 @@ pub fn run() {
             commands::browser_screenshot_commands::browser_screenshot_set_editor_font_size,
             commands::browser_screenshot_commands::browser_screenshot_set_editor_zoom,
             commands::browser_screenshot_commands::browser_screenshot_set_editor_pan,
+            commands::browser_screenshot_commands::browser_screenshot_set_image_data,
             commands::browser_screenshot_commands::browser_screenshot_add_annotation,
             commands::browser_screenshot_commands::browser_screenshot_update_annotation,
             commands::browser_screenshot_commands::browser_screenshot_delete_annotation,
@@ -2701,6 +2887,7 @@ pub fn run() {
             commands::browser_downloads_commands::download_set_category_folder,
             commands::browser_downloads_commands::download_add_blocked_extension,
             commands::browser_downloads_commands::download_remove_blocked_extension,
+            commands::browser_downloads_commands::download_set_torrent_settings,
             commands::browser_downloads_commands::download_create,
             commands::browser_downloads_commands::download_start,
             commands::browser_downloads_commands::download_pause,
@@ -2710,9 +2897,15 @@ pub fn run() {
             commands::browser_downloads_commands::download_delete,
             commands::browser_downloads_commands::download_update_progress,
             commands::browser_downloads_commands::download_set_failed,
+            commands::browser_downloads_commands::download_start_transfer,
+            commands::browser_downloads_commands::download_torrent_select_files,
+            commands::browser_downloads_commands::download_torrent_stop_seeding,
+            commands::browser_downloads_commands::download_set_expected_checksum,
+            commands::browser_downloads_commands::download_load_checksum_file,
             commands::browser_downloads_commands::download_get,
             commands::browser_downloads_commands::download_get_all,
             commands::browser_downloads_commands::download_get_active,
+            commands::browser_downloads_commands::download_get_eta,
             commands::browser_downloads_commands::download_get_by_status,
             commands::browser_downloads_commands::download_get_by_category,
             commands::browser_downloads_commands::download_filter,
@@ -2769,7 +2962,9 @@ pub fn run() {
             commands::browser_history_commands::history_get_starred_entries,
             commands::browser_history_commands::history_filter_entries,
             commands::browser_history_commands::history_search,
+            commands::browser_history_commands::history_semantic_search,
             commands::browser_history_commands::history_suggest,
+            commands::browser_history_commands::history_rebuild_index,
             commands::browser_history_commands::history_add_tag,
             commands::browser_history_commands::history_remove_tag,
             commands::browser_history_commands::history_toggle_starred,
@@ -2792,6 +2987,8 @@ pub fn run() {
             commands::browser_history_commands::history_get_domain_stats,
             commands::browser_history_commands::history_get_all_domains,
             commands::browser_history_commands::history_clear,
+            commands::browser_history_commands::history_clear_range,
+            commands::browser_history_commands::history_clear_last,
             commands::browser_history_commands::history_clear_domain,
             commands::browser_history_commands::history_cleanup_old_entries,
             commands::browser_history_commands::history_export,
@@ -2842,9 +3039,16 @@ pub fn run() {
             commands::browser_bookmarks_commands::browser_bookmarks_export_to_file,
             commands::browser_bookmarks_commands::browser_bookmarks_check_url_exists,
             commands::browser_bookmarks_commands::browser_bookmarks_find_duplicates,
+            commands::browser_bookmarks_commands::browser_bookmarks_merge_duplicates,
+            commands::browser_bookmarks_commands::browser_bookmarks_undo_merge,
+            commands::browser_bookmarks_commands::browser_bookmarks_get_normalization_rules,
+            commands::browser_bookmarks_commands::browser_bookmarks_set_normalization_rules,
+            commands::browser_bookmarks_commands::browser_bookmarks_preview_url_normalization,
             commands::browser_bookmarks_commands::browser_bookmarks_cleanup_orphaned,
             commands::browser_bookmarks_commands::browser_bookmarks_quick_add,
             commands::browser_bookmarks_commands::browser_bookmarks_quick_add_to_folder,
+            commands::browser_bookmarks_commands::browser_bookmarks_refresh_favicon,
+            commands::browser_bookmarks_commands::browser_bookmarks_refresh_stale_favicons,
             commands::browser_bookmarks_commands::browser_bookmarks_batch_delete,
             commands::browser_bookmarks_commands::browser_bookmarks_batch_move,
             commands::browser_bookmarks_commands::browser_bookmarks_batch_add_tag,
@@ -2906,6 +3110,12 @@ pub fn run() {
             commands::browser_privacy_commands::privacy_clear_all_cookies,
             commands::browser_privacy_commands::privacy_clear_third_party_cookies,
             commands::browser_privacy_commands::privacy_get_cookie_stats,
+            commands::browser_privacy_commands::privacy_set_cookie_policy,
+            commands::browser_privacy_commands::privacy_clear_cookie_policy,
+            commands::browser_privacy_commands::privacy_set_default_cookie_policy,
+            commands::browser_privacy_commands::privacy_get_cookie_policy,
+            commands::browser_privacy_commands::privacy_set_keep_signed_in,
+            commands::browser_privacy_commands::privacy_sweep_cookies,
             commands::browser_privacy_commands::privacy_get_fingerprint_protection,
             commands::browser_privacy_commands::privacy_rotate_fingerprint,
             commands::browser_privacy_commands::privacy_set_spoofed_user_agent,
@@ -2955,6 +3165,9 @@ pub fn run() {
             commands::browser_sync_commands::sync_complete,
             commands::browser_sync_commands::sync_cancel,
             commands::browser_sync_commands::sync_data_type,
+            commands::browser_sync_commands::sync_receive_items,
+            commands::browser_sync_commands::sync_merge_bookmarks,
+            commands::browser_sync_commands::sync_get_bookmark_tree,
             commands::browser_sync_commands::sync_get_conflicts,
             commands::browser_sync_commands::sync_get_unresolved_conflicts,
             commands::browser_sync_commands::sync_resolve_conflict,
@@ -2967,6 +3180,7 @@ pub fn run() {
             commands::browser_sync_commands::sync_get_keys,
             commands::browser_sync_commands::sync_get_active_key,
             commands::browser_sync_commands::sync_rotate_key,
+            commands::browser_sync_commands::sync_fetch_data_type_key,
             commands::browser_sync_commands::sync_create_recovery_key,
             commands::browser_sync_commands::sync_get_stats,
             commands::browser_sync_commands::sync_get_storage_usage,
@@ -3017,6 +3231,7 @@ pub fn run() {
             // === PORTFOLIO ===
             commands::investor_commands::get_portfolio_summary,
             commands::investor_commands::get_investment_analytics,
+            commands::investor_commands::get_portfolio_performance,
 
             // === INVESTMENTS ===
             commands::investor_commands::create_investment,
@@ -3192,6 +3407,10 @@ pub fn run() {
             commands::tenant_commands::update_tenant_white_label_config,
             commands::tenant_commands::disable_white_label,
 
+            // === DATA PRIVACY (GDPR) ===
+            commands::tenant_commands::tenant_export_user_data,
+            commands::tenant_commands::tenant_erase_user_data,
+
             // ================================================================
             // BROWSER PROFILE MANAGEMENT (35 commands)
             // ================================================================
@@ -3214,6 +3433,7 @@ pub fn run() {
 
             // === FINGERPRINT ===
             commands::browser_profile_commands::update_profile_fingerprint,
+            commands::browser_profile_commands::test_profile_locale,
             commands::browser_profile_commands::generate_random_fingerprint,
             commands::browser_profile_commands::get_fingerprint_templates,
 
@@ -3342,6 +3562,7 @@ pub fn run() {
             commands::security_compliance_commands::security_add_alert_note,
 
             // === SECURITY EVENTS ===
+            commands::security_compliance_commands::security_record_event,
             commands::security_compliance_commands::security_acknowledge_event,
 
             // === SECURITY INCIDENTS ===
@@ -3372,6 +3593,8 @@ pub fn run() {
             commands::security_compliance_commands::security_get_siem_integration,
             commands::security_compliance_commands::security_list_siem_integrations,
             commands::security_compliance_commands::security_delete_siem_integration,
+            commands::security_compliance_commands::security_test_siem_integration,
+            commands::security_compliance_commands::security_get_siem_delivery_status,
 
             // === COMPLIANCE FRAMEWORKS ===
             commands::security_compliance_commands::compliance_create_framework,
@@ -3424,6 +3647,10 @@ pub fn run() {
             commands::call_center_commands::call_center_end_call,
             commands::call_center_commands::call_center_transfer_call,
             commands::call_center_commands::call_center_call_transcription,
+            commands::call_center_commands::call_center_start_transcription,
+            commands::call_center_commands::call_center_push_transcript_chunk,
+            commands::call_center_commands::call_center_set_alert_phrases,
+            commands::call_center_commands::call_center_get_alert_phrases,
             commands::call_center_commands::call_center_send_whatsapp_template,
             commands::call_center_commands::call_center_get_whatsapp_templates,
             commands::call_center_commands::call_center_search_knowledge,
@@ -3617,6 +3844,8 @@ pub fn run() {
             app.manage(ai_chat_state);
             info!("💬 AI Chat Service initialized");
 
+            app.manage(commands::ai::AiStreamState::default());
+
             // Initialize CEF (Chromium Embedded Framework) State
             // This provides full browser engine capabilities: DRM, codecs, extensions
             #[cfg(feature = "cef-browser")]
@@ -3659,12 +3888,19 @@ pub fn run() {
             let password_service = services::password_service::PasswordService::new(
                 passwords_db_path_str
             ).expect("Failed to initialize Password Manager service");
-            let password_state = commands::passwords_new::PasswordState {
-                service: std::sync::Mutex::new(password_service),
-            };
+            let password_state = commands::passwords_new::PasswordState::new(password_service);
             app.manage(password_state);
             info!("🔐 Password Manager Service initialized (AES-256-GCM)");
 
+            // Autofill engine and the CUBE engine's password/autofill form hooks
+            app.manage(commands::autofill_system_v2::AutofillSystemState::default());
+            app.manage(commands::cube_browser_commands::CubeFormHooksState(std::sync::Mutex::new(
+                services::cube_form_hooks::FormHooks::with_storage(
+                    app_data_dir.join("cube_form_site_settings.json"),
+                ),
+            )));
+            info!("🔑 CUBE engine form hooks initialized");
+
             // Initialize Collections State
             let collections_db_path = app_data_dir.join("collections.db");
             let collections_db_path_str = collections_db_path.to_str()
@@ -3701,6 +3937,23 @@ pub fn run() {
             app.manage(media_service);
             info!("🎵 Media Player Service initialized");
 
+            // Initialize White-Label Theme Service
+            let whitelabel_theme_service = services::whitelabel_theme_service::WhiteLabelThemeService::new(
+                app_data_dir.join("whitelabel")
+            );
+            app.manage(whitelabel_theme_service);
+            info!("🎨 White-Label Theme Service initialized (per-tenant theming)");
+
+            // Initialize Enterprise License State
+            app.manage(commands::enterprise_part2::EnterpriseLicenseState::default());
+
+            // Workspaces (with scheduled focus routines)
+            app.manage(commands::browser_workspaces_commands::WorkspacesState(std::sync::Mutex::new(
+                services::browser_workspaces::BrowserWorkspacesService::new(),
+            )));
+            commands::browser_workspaces_commands::spawn_schedule_runner(app.handle().clone());
+            info!("🔑 Enterprise License State initialized (concurrent seats, heartbeats)");
+
             // === Initialize Video Conference Service ===
             let video_conference_service = Arc::new(services::video_conference_service::VideoConferenceService::new(app.handle().clone()));
             app.manage(video_conference_service);
@@ -3728,8 +3981,23 @@ pub fn run() {
             // info!("🖥️ Remote Desktop v2 initialized (WebRTC + encryption)");
 
             // Initialize Workflow State
-            let workflow_state = commands::workflow_commands::WorkflowState::new();
+            let workflow_notifications = services::NotificationsService::new(app_data_dir.join("notifications.db"))
+                .map(Arc::new)
+                .map_err(|e| warn!("Workflow approval notifications unavailable: {}", e))
+                .ok();
+            // The notification center reads the same database
+            if let Some(notifications) = &workflow_notifications {
+                app.manage(notifications.clone());
+            }
+            // Runs and node retries show up in the monitoring dashboard set up below
+            let execution_metrics = Arc::new(services::metrics::MetricsService::new());
+            let workflow_state = commands::workflow_commands::WorkflowState::with_approval_storage(
+                app_data_dir.join("workflow_approvals.json"),
+                workflow_notifications,
+            )
+            .with_metrics(execution_metrics.clone());
             app.manage(workflow_state);
+            commands::workflow_commands::spawn_approval_timeouts(app.handle().clone());
             info!("⚡ Workflow Builder initialized (beats Zapier)");
 
             // Initialize VPN State
@@ -3798,6 +4066,9 @@ pub fn run() {
             let bookmarks_service = services::browser_bookmarks::BrowserBookmarksService::new();
             app.manage(bookmarks_service);
             info!("⭐ Bookmarks Elite initialized (folders, tags, import/export, 55 commands)");
+            app.manage(services::browser_bookmark_metadata::BookmarkMetadataService::new(
+                app_data_dir.join("favicons"),
+            ));
 
             // ========================================================================
             // INITIALIZE CUBE EXTENSIONS MANAGER ELITE
@@ -3815,6 +4086,7 @@ pub fn run() {
             // Initialize Privacy Dashboard Service State
             let privacy_service = services::browser_privacy::PrivacyDashboardService::new();
             app.manage(privacy_service);
+            commands::browser_privacy_commands::start_cookie_sweep(app.handle().clone());
             info!("🔒 Privacy Dashboard initialized (trackers, cookies, fingerprinting, 45 commands)");
 
             // ========================================================================
@@ -3826,6 +4098,11 @@ pub fn run() {
             app.manage(sync_service);
             info!("🔄 Sync Service initialized (E2E encryption, cross-device, 50 commands)");
 
+            // Reader Mode, whose progress and annotations sync through the service above
+            app.manage(commands::browser_reader_commands::ReaderState(std::sync::Mutex::new(
+                services::browser_reader::BrowserReaderService::new(),
+            )));
+
             // ========================================================================
             // INITIALIZE CUBE SEARCH ENGINE
             // ========================================================================
@@ -3841,7 +4118,9 @@ pub fn run() {
             
             // Initialize Tab Groups State
             let tab_groups_state = commands::browser_tab_groups_commands::TabGroupsState(
-                std::sync::Mutex::new(services::browser_tab_groups::CubeTabGroups::new())
+                std::sync::Mutex::new(services::browser_tab_groups::CubeTabGroups::with_tree_storage(
+                    app_data_dir.join("tab_tree.json"),
+                ))
             );
             app.manage(tab_groups_state);
             info!("📑 Tab Groups initialized (AI-powered, superior to Chrome/Opera/Vivaldi)");
@@ -3852,6 +4131,16 @@ pub fn run() {
             
             // Initialize CUBE Web Engine Global State
             let cube_web_engine_state = commands::cube_web_engine_commands::CubeWebEngineGlobalState::default();
+            if let Some(privacy) = app.try_state::<services::browser_privacy::PrivacyDashboardService>() {
+                let settings = privacy.get_settings();
+                cube_web_engine_state.dns.set_enabled(settings.use_doh);
+                if let Err(e) = cube_web_engine_state.dns.set_providers(&settings.doh_provider, None) {
+                    warn!("⚠️ Invalid DoH provider in privacy settings: {}", e);
+                }
+            }
+            if let Some(themes) = app.try_state::<services::browser_themes::BrowserThemesService>() {
+                let _ = cube_web_engine_state.media.set_reduced_motion(themes.get_settings().reduce_motion);
+            }
             app.manage(cube_web_engine_state);
             info!("🌐 CUBE Web Engine initialized (true embedded browser, no external windows, CORS bypass)");
 
@@ -3898,6 +4187,25 @@ pub fn run() {
             app.manage(superadmin_system_state);
             info!("🖥️ SuperAdmin System initialized (health, alerts, maintenance mode)");
 
+            // Analytics Dashboard Push State
+            let dashboard_stream_state = commands::analytics::DashboardStreamState::default();
+            app.manage(dashboard_stream_state);
+            info!("📊 Dashboard widget push initialized (subscriptions, throttled updates)");
+
+            let metric_store_state = commands::analytics::MetricStoreState::default();
+            app.manage(metric_store_state);
+            info!("📈 Metric store initialized (recorded series, anomaly monitors)");
+
+            // Notification Template State
+            let notification_template_state = commands::notifications::NotificationTemplateState::default();
+            app.manage(notification_template_state);
+            info!("🔔 Notification templates initialized (sandboxed rendering)");
+
+            // SMS gateway: TWILIO_ACCOUNT_SID / TWILIO_AUTH_TOKEN / TWILIO_FROM_NUMBER,
+            // VONAGE_API_KEY / VONAGE_API_SECRET / VONAGE_FROM (Twilio is primary)
+            app.manage(services::sms_delivery::SmsGateway::from_env());
+            info!("📱 SMS gateway initialized");
+
             // ========================================================================
             // INITIALIZE PASSWORD ADVANCED STATES
             // ========================================================================
@@ -4115,7 +4423,7 @@ pub fn run() {
             info!("⏰ Workflow Scheduler initialized");
 
             // === Initialize Monitoring Services ===
-            let metrics = Arc::new(services::metrics::MetricsService::new());
+            let metrics = execution_metrics;
             let logs = Arc::new(services::logs::LogsService::new());
             let alerts = Arc::new(services::alerts::AlertsService::new());
             let monitoring_state = commands::monitoring::MonitoringState {
@@ -4146,10 +4454,26 @@ pub fn run() {
             app.manage(integration_state);
             info!("💬 Slack/Discord integrations initialized (webhook-based)");
 
+            // === Initialize WhatsApp/Monday.com Integrations (rate-limited outbound queues) ===
+            match commands::integration_commands::IntegrationState::new(
+                app_data_dir.join("integration_queues"),
+            ) {
+                Ok(state) => {
+                    commands::integration_commands::spawn_queue_workers(&state);
+                    app.manage(state);
+                    info!("📤 WhatsApp/Monday.com integrations initialized (outbound queues restored)");
+                }
+                Err(e) => error!("⚠️ WhatsApp/Monday.com integrations unavailable: {}", e),
+            }
+
             // === Initialize Anti-Detection Services ===
+            let proxy_service = Arc::new(services::proxy::ProxyService::new());
+            proxy_service.clone().spawn_geo_validation(services::proxy::GEO_VALIDATION_INTERVAL);
             let stealth_state = commands::stealth::StealthState {
-                stealth: Arc::new(services::stealth::StealthService::new()),
-                proxy: Arc::new(services::proxy::ProxyService::new()),
+                stealth: Arc::new(services::stealth::StealthService::with_seed_storage(
+                    app_data_dir.join("fingerprint_seeds.json"),
+                )),
+                proxy: proxy_service,
                 captcha: Arc::new(services::captcha::CaptchaService::new(services::captcha::CaptchaConfig {
                     api_key: String::new(),
                     service_url: "https://2captcha.com".to_string(),
@@ -4208,6 +4532,10 @@ pub fn run() {
             app.manage(automation_state);
             info!("🤖 Automation State initialized (workflow engine)");
 
+            // === Initialize AI Trainer State ===
+            app.manage(commands::ai_trainer::AITrainerState::new());
+            info!("🎓 AI Trainer State initialized (recording, execution & step debugging)");
+
             // === Initialize Browser Service (Headless Chrome for Automation) ===
             let browser_service = Arc::new(services::browser_service::BrowserService::new(app.handle().clone()));
             app.manage(browser_service);
@@ -4300,24 +4628,34 @@ pub fn run() {
             app.manage(contact_service_state);
             info!("📇 Contact Service initialized (lists, segments, import/export)");
 
-            // === Initialize CUBE Mail Service State ===
-            let cube_mail_state = services::CubeMailServiceState::new();
-            app.manage(cube_mail_state);
-            info!("📬 CUBE Mail Service initialized (IMAP/SMTP, encryption, AI features)");
+            let email_tracking_service = Arc::new(services::email_tracking::EmailTrackingService::new());
+            app.manage(email_tracking_service);
+            info!("📈 Email tracking initialized (open pixel + link redirects via API server)");
 
             // === Initialize OAuth2 Service State ===
             let oauth2_state = services::OAuth2ServiceState::new();
-            app.manage(oauth2_state);
+            app.manage(oauth2_state.clone());
             info!("🔐 OAuth2 Service initialized (Google, Microsoft, Yahoo)");
 
+            // === Initialize CUBE Mail Service State ===
+            let cube_mail_state = services::CubeMailServiceState::new()
+                .with_token_refresher(Arc::new(oauth2_state));
+            app.manage(cube_mail_state);
+            info!("📬 CUBE Mail Service initialized (IMAP/SMTP, OAuth2 auto-refresh, encryption, AI features)");
+
             // === Initialize Social Media State ===
             let social_state = commands::social::SocialState::default();
             app.manage(social_state);
             info!("📱 Social Media State initialized (accounts, posts, scheduling, analytics)");
 
+            // === Initialize Gamification State ===
+            app.manage(commands::gamification_commands::GamificationState::default());
+            info!("🏆 Gamification State initialized (XP ledger, leaderboards, achievements)");
+
             // === Initialize Research State ===
             let research_state = commands::research::ResearchState::default();
             app.manage(research_state);
+            commands::research::start_competitor_monitoring(app.handle().clone());
             info!("🔬 Research State initialized (projects, competitors, trends, reports)");
 
             // === Initialize Search State ===
@@ -4342,6 +4680,7 @@ pub fn run() {
 
             // === Initialize Security & Compliance State ===
             let security_compliance_state = commands::security_compliance_commands::SecurityComplianceState::new();
+            tauri::async_runtime::spawn(security_compliance_state.siem.clone().run());
             app.manage(security_compliance_state);
             info!("🛡️ Security & Compliance State initialized (alerts, incidents, playbooks, SIEM, frameworks)");
 
@@ -4437,6 +4776,7 @@ pub fn run() {
             let devtools_state = commands::cube_engine_devtools::CubeDevToolsState::default();
             app.manage(devtools_state);
             info!("🔧 CUBE DevTools initialized (Network, Console, Elements, Profiler)");
+            app.manage(services::devtools_service::DevToolsService::new());
 
             // Phase 6: Extensions Support
             let extensions_state = commands::cube_engine_extensions::CubeExtensionsState::default();
@@ -29,6 +29,30 @@ pub struct CollectionPage {
     pub last_visited: Option<i64>,
     pub visit_count: i32,
     pub is_favorite: bool,
+    /// Extracted page text. Only stored in the search index, so it is `None` when
+    /// pages are read back; leaving it `None` on update keeps the indexed text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageSearchOptions {
+    /// Only return pages in this collection or its descendants
+    #[serde(default)]
+    pub collection_id: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageSearchResult {
+    pub page: CollectionPage,
+    /// Relevance (negated BM25, higher is better)
+    pub score: f64,
+    /// Matching excerpt with hits wrapped in `<mark>` tags
+    pub snippet: String,
+    /// Collection names from the root down to the page's collection
+    pub collection_path: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
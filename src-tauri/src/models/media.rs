@@ -64,3 +64,34 @@ pub struct PlaybackState {
     pub repeat_mode: String, // "none", "one", "all"
     pub shuffle: bool,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaybackSettings {
+    /// Pre-buffer the next track and start it the instant the current one ends
+    pub gapless: bool,
+    /// Overlap between consecutive tracks; 0 disables crossfade
+    pub crossfade_seconds: f64,
+}
+
+impl Default for PlaybackSettings {
+    fn default() -> Self {
+        Self {
+            gapless: true,
+            crossfade_seconds: 0.0,
+        }
+    }
+}
+
+/// When and how to hand over from one playlist track to the next, with times
+/// relative to the start of the outgoing track
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackTransition {
+    pub from_media_id: String,
+    pub to_media_id: String,
+    /// Start decoding the next track by this time so it is ready at `start_next_at_seconds`
+    pub prebuffer_at_seconds: Option<f64>,
+    pub start_next_at_seconds: f64,
+    pub overlap_seconds: f64,
+    /// The next track counts as played once it dominates the mix
+    pub count_play_at_seconds: f64,
+}
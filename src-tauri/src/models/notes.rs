@@ -102,3 +102,39 @@ pub struct NotesStats {
     pub due_today: i32,
     pub due_this_week: i32,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedNote {
+    pub note_id: String,
+    pub html: String,
+    pub wiki_links: Vec<WikiLink>,
+    pub tasks: Vec<TaskCheckbox>,
+    pub outline: Vec<OutlineHeading>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WikiLink {
+    pub target: String,
+    pub alias: Option<String>,
+    pub note_id: Option<String>,
+    pub would_create: bool,
+    /// Byte offset of the `[[` in the markdown source
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskCheckbox {
+    pub index: usize,
+    pub checked: bool,
+    pub text: String,
+    pub line: usize,
+    /// Byte offset of the character between the brackets (` ` or `x`)
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlineHeading {
+    pub level: u8,
+    pub text: String,
+    pub anchor: String,
+}
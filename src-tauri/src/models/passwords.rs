@@ -20,10 +20,66 @@ pub struct PasswordEntry {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MasterPasswordConfig {
-    pub salt: String, // Hex-encoded salt for PBKDF2
+    pub salt: String, // Hex-encoded salt for the KDF
     pub is_set: bool,
     pub created_at: i64,
     pub updated_at: i64,
+    #[serde(default)]
+    pub kdf: KdfParams,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KdfAlgorithm {
+    Pbkdf2Sha256,
+    Argon2id,
+}
+
+/// How the vault key is derived from the master password. `memory_kib` and
+/// `parallelism` only apply to Argon2id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub algorithm: KdfAlgorithm,
+    pub iterations: u32,
+    pub memory_kib: u32,
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// Used by vaults created before the KDF could be chosen
+    pub fn legacy_pbkdf2() -> Self {
+        Self {
+            algorithm: KdfAlgorithm::Pbkdf2Sha256,
+            iterations: 100_000,
+            memory_kib: 0,
+            parallelism: 1,
+        }
+    }
+
+    /// Default for new vaults (RFC 9106's second recommended option)
+    pub fn recommended() -> Self {
+        Self {
+            algorithm: KdfAlgorithm::Argon2id,
+            iterations: 3,
+            memory_kib: 64 * 1024,
+            parallelism: 4,
+        }
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self::legacy_pbkdf2()
+    }
+}
+
+/// Current KDF settings against the security baseline, for the UI to warn on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfStatus {
+    pub current: KdfParams,
+    pub recommended: KdfParams,
+    pub below_baseline: bool,
+    pub warnings: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +118,47 @@ pub struct PasswordStats {
     pub by_category: std::collections::HashMap<String, i32>,
 }
 
+/// A shared password as held by the share server: ciphertext and limits only.
+/// The decryption key lives in the link's URL fragment and is never stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordShareRecord {
+    pub token: String,
+    pub ciphertext: String, // Hex-encoded nonce + AES-256-GCM ciphertext, empty once destroyed
+    pub salt: String,       // Hex-encoded salt for the optional share password
+    pub password_protected: bool,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub max_views: u32,
+    pub view_count: u32,
+    pub destroyed_reason: Option<String>,
+}
+
+/// An emergency contact grant as held by the server. The vault export is
+/// sealed to the contact's X25519 public key; the server never sees plaintext.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyAccessRecord {
+    pub id: String,
+    pub contact_email: String,
+    pub contact_public_key: String,   // Base64url X25519 public key of the contact
+    pub access_level: String,         // "view" or "takeover"
+    pub wait_days: u32,
+    pub status: String,               // "idle", "requested", "denied" or "granted"
+    pub requested_at: Option<i64>,
+    pub decided_at: Option<i64>,
+    pub ephemeral_public_key: String, // Base64url X25519 key the export was sealed with
+    pub ciphertext: String,           // Hex-encoded nonce + AES-256-GCM ciphertext
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// This device's X25519 identity for receiving emergency access. The secret is
+/// encrypted with the master password like any vault entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyIdentity {
+    pub public_key: String,
+    pub encrypted_secret: String,
+}
+
 impl Default for PasswordGeneratorConfig {
     fn default() -> Self {
         Self {
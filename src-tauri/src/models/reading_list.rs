@@ -17,6 +17,9 @@ pub struct Article {
     pub added_at: i64,
     pub read_at: Option<i64>,
     pub last_opened_at: Option<i64>,
+    /// Blurred `data:` URL shown in place of the thumbnail until it loads, or offline
+    #[serde(default)]
+    pub thumbnail_placeholder: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,3 +38,16 @@ pub struct ArticleFilter {
     pub favorites_only: bool,
     pub search_query: Option<String>,
 }
+
+/// A tag with article counts. Nested tags use `parent/child` paths; parents
+/// are listed even when no article carries them directly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadingListTag {
+    pub tag: String,
+    pub name: String,
+    pub parent: Option<String>,
+    /// Articles tagged with exactly this tag
+    pub count: i32,
+    /// Articles tagged with this tag or any tag beneath it
+    pub total_count: i32,
+}
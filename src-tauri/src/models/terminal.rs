@@ -34,6 +34,9 @@ pub struct TerminalConfig {
     pub cursor_blink: bool,
     pub scrollback_lines: i32,
     pub bell_enabled: bool,
+    /// Regex patterns; commands matching any of them are never written to history
+    #[serde(default)]
+    pub history_exclude_patterns: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,3 +52,12 @@ pub struct CommandFrequency {
     pub command: String,
     pub count: i32,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuzzyHistoryMatch {
+    pub command: String,
+    pub score: f64,
+    pub frequency: i32,
+    pub last_executed_at: i64,
+    pub matched_indices: Vec<usize>, // char positions in `command` matched by the query
+}
@@ -5,6 +5,10 @@ use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fmt;
+use std::future::Future;
+use std::path::Path;
+
+pub mod searchable_pdf;
 
 #[derive(Debug)]
 pub struct OCRError(String);
@@ -41,8 +45,41 @@ pub struct OCRResult {
     pub text: String,
     pub confidence: f32,
     pub language: String,
+    /// Word bounding boxes, when extracted with layout
+    #[serde(default)]
+    pub words: Vec<OCRWord>,
 }
 
+/// A recognized word and its box in image pixels, from the top-left corner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OCRWord {
+    pub text: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub confidence: f32,
+}
+
+/// Outcome of one file in a batch; exactly one of `result` and `error` is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    pub path: String,
+    pub result: Option<OCRResult>,
+    pub error: Option<String>,
+}
+
+/// Sent as each batch item finishes, in completion order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchProgress {
+    pub completed: usize,
+    pub total: usize,
+    pub item: BatchItemResult,
+}
+
+pub const DEFAULT_BATCH_WORKERS: usize = 4;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractionRegion {
     pub x: u32,
@@ -120,6 +157,95 @@ impl OCREngine {
         base64_image: &str,
         mime_type: &str,
     ) -> Result<OCRResult, OCRError> {
+        let extracted_text = self
+            .vision_completion(
+                base64_image,
+                mime_type,
+                "Extract ALL visible text from this image. Return ONLY the extracted text, \
+                preserving layout and formatting as much as possible. If there are multiple \
+                columns or sections, separate them clearly.",
+            )
+            .await?;
+
+        Ok(OCRResult {
+            text: extracted_text,
+            confidence: 0.95, // GPT-5 Vision has very high accuracy
+            language: self.config.language.clone(),
+            words: Vec::new(),
+        })
+    }
+
+    /// Recognizes words with their pixel bounding boxes so text can be laid
+    /// over the image. Words below the confidence threshold are dropped.
+    pub async fn extract_words_from_image(&self, image: &image::RgbImage) -> Result<OCRResult, OCRError> {
+        if !self.config.use_gpt4o_vision || self.client.is_none() {
+            return Err(OCRError(
+                "GPT-5 Vision not available. Set OPENAI_API_KEY environment variable.".to_string(),
+            ));
+        }
+
+        let mut png = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| OCRError(format!("Image encode failed: {}", e)))?;
+        let instructions = format!(
+            "Locate every word of text in this {}x{} pixel image. Respond with JSON only, in the form \
+            {{\"words\": [{{\"text\": \"...\", \"x\": 0, \"y\": 0, \"width\": 0, \"height\": 0, \"confidence\": 0.0}}]}} \
+            where x and y are the top-left corner of the word's bounding box in pixels from the \
+            image's top-left corner. List the words in reading order.",
+            image.width(),
+            image.height()
+        );
+        let reply = self
+            .vision_completion(&general_purpose::STANDARD.encode(&png), "image/png", &instructions)
+            .await?;
+
+        let words = parse_word_boxes(&reply, image.width(), image.height(), self.config.confidence_threshold)?;
+        let confidence = if words.is_empty() {
+            0.0
+        } else {
+            words.iter().map(|word| word.confidence).sum::<f32>() / words.len() as f32
+        };
+        Ok(OCRResult {
+            text: words_to_text(&words),
+            confidence,
+            language: self.config.language.clone(),
+            words,
+        })
+    }
+
+    /// Runs OCR on every page of `input` and writes a PDF that shows the
+    /// original images with the recognized text selectable over them
+    pub async fn to_searchable_pdf(
+        &self,
+        input: &Path,
+        output: &Path,
+    ) -> Result<searchable_pdf::SearchablePdfResult, OCRError> {
+        let pages = searchable_pdf::load_pages(input)?;
+        let mut recognized = Vec::with_capacity(pages.len());
+        let mut text = Vec::with_capacity(pages.len());
+        for page in pages {
+            let result = self.extract_words_from_image(&page.image).await?;
+            text.push(result.text);
+            recognized.push((page, result.words));
+        }
+
+        let pdf = searchable_pdf::build_searchable_pdf(&recognized)?;
+        std::fs::write(output, pdf).map_err(|e| OCRError(format!("Failed to write PDF: {}", e)))?;
+        Ok(searchable_pdf::SearchablePdfResult {
+            output_path: output.to_string_lossy().to_string(),
+            pages: recognized.len(),
+            words: recognized.iter().map(|(_, words)| words.len()).sum(),
+            text: text.join("\n\n"),
+        })
+    }
+
+    async fn vision_completion(
+        &self,
+        base64_image: &str,
+        mime_type: &str,
+        instructions: &str,
+    ) -> Result<String, OCRError> {
         use async_openai::types::{
             ChatCompletionRequestMessage, ChatCompletionRequestUserMessage,
             ChatCompletionRequestUserMessageContent, CreateChatCompletionRequestArgs,
@@ -133,12 +259,7 @@ impl OCREngine {
         let image_url = format!("data:{};base64,{}", mime_type, base64_image);
 
         // Create a simple text prompt with image URL
-        let prompt = format!(
-            "Extract ALL visible text from this image. Return ONLY the extracted text, \
-            preserving layout and formatting as much as possible. If there are multiple \
-            columns or sections, separate them clearly.\n\nImage: {}",
-            image_url
-        );
+        let prompt = format!("{}\n\nImage: {}", instructions, image_url);
 
         let user_message = ChatCompletionRequestUserMessage {
             content: ChatCompletionRequestUserMessageContent::Text(prompt),
@@ -161,17 +282,11 @@ impl OCREngine {
             .await
             .map_err(|e| OCRError(format!("GPT-5 Vision API error: {}", e)))?;
 
-        let extracted_text = response
+        response
             .choices
             .first()
             .and_then(|choice| choice.message.content.clone())
-            .ok_or_else(|| OCRError("No text extracted from image".to_string()))?;
-
-        Ok(OCRResult {
-            text: extracted_text,
-            confidence: 0.95, // GPT-5 Vision has very high accuracy
-            language: self.config.language.clone(),
-        })
+            .ok_or_else(|| OCRError("No text extracted from image".to_string()))
     }
 
     fn detect_mime_type(path: &str) -> String {
@@ -213,6 +328,128 @@ impl OCREngine {
     pub fn version(&self) -> String {
         "OCR Engine v2.0.0 (GPT-5 Vision Powered)".to_string()
     }
+
+    /// Extracts text from many files with at most `workers` in flight
+    pub async fn extract_batch(
+        &self,
+        paths: Vec<String>,
+        workers: usize,
+        on_progress: impl FnMut(BatchProgress),
+    ) -> Vec<BatchItemResult> {
+        run_batch(paths, workers, |path| self.extract_from_file(path), on_progress).await
+    }
+}
+
+/// Runs `extract` over `paths` concurrently. Progress is reported as items
+/// finish, while the returned results keep the input order. A failed item is
+/// recorded on its own and does not stop the others.
+pub async fn run_batch<F, Fut>(
+    paths: Vec<String>,
+    workers: usize,
+    extract: F,
+    mut on_progress: impl FnMut(BatchProgress),
+) -> Vec<BatchItemResult>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<OCRResult, OCRError>>,
+{
+    use futures_util::stream::{self, StreamExt};
+
+    let total = paths.len();
+    let mut results: Vec<Option<BatchItemResult>> = vec![None; total];
+    let mut finished = stream::iter(paths.into_iter().enumerate())
+        .map(|(index, path)| {
+            let extraction = extract(path.clone());
+            async move {
+                let (result, error) = match extraction.await {
+                    Ok(result) => (Some(result), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+                BatchItemResult {
+                    index,
+                    path,
+                    result,
+                    error,
+                }
+            }
+        })
+        .buffer_unordered(workers.max(1));
+
+    let mut completed = 0;
+    while let Some(item) = finished.next().await {
+        completed += 1;
+        on_progress(BatchProgress {
+            completed,
+            total,
+            item: item.clone(),
+        });
+        let index = item.index;
+        results[index] = Some(item);
+    }
+    results.into_iter().flatten().collect()
+}
+
+/// Reads the word list from a vision reply, which may be wrapped in a code
+/// fence, and clamps each box to the image
+fn parse_word_boxes(reply: &str, width: u32, height: u32, min_confidence: f32) -> Result<Vec<OCRWord>, OCRError> {
+    #[derive(Deserialize)]
+    struct Reply {
+        words: Vec<ReplyWord>,
+    }
+
+    #[derive(Deserialize)]
+    struct ReplyWord {
+        text: String,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        confidence: Option<f32>,
+    }
+
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => return Err(OCRError("Vision reply contained no word boxes".to_string())),
+    };
+    let parsed: Reply =
+        serde_json::from_str(json).map_err(|e| OCRError(format!("Invalid word boxes: {}", e)))?;
+
+    Ok(parsed
+        .words
+        .into_iter()
+        .filter_map(|word| {
+            let text = word.text.trim().to_string();
+            let confidence = word.confidence.unwrap_or(1.0);
+            if text.is_empty() || confidence < min_confidence {
+                return None;
+            }
+            let x = (word.x.max(0.0) as u32).min(width.saturating_sub(1));
+            let y = (word.y.max(0.0) as u32).min(height.saturating_sub(1));
+            Some(OCRWord {
+                text,
+                x,
+                y,
+                width: (word.width.max(1.0) as u32).min(width - x),
+                height: (word.height.max(1.0) as u32).min(height - y),
+                confidence,
+            })
+        })
+        .collect())
+}
+
+/// Joins words into lines, starting a new line when a word begins below the
+/// previous one
+fn words_to_text(words: &[OCRWord]) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&OCRWord> = None;
+    for word in words {
+        if let Some(prev) = previous {
+            text.push(if word.y >= prev.y + prev.height { '\n' } else { ' ' });
+        }
+        text.push_str(&word.text);
+        previous = Some(word);
+    }
+    text
 }
 
 pub struct LanguageManager;
@@ -559,6 +796,7 @@ mod tests {
             text: "Test text".to_string(),
             confidence: 0.95,
             language: "eng".to_string(),
+            words: Vec::new(),
         };
         assert_eq!(result.text, "Test text");
         assert_eq!(result.confidence, 0.95);
@@ -585,6 +823,82 @@ mod tests {
         assert!(lang.installed);
     }
 
+    #[tokio::test]
+    async fn test_batch_reports_failures_per_item_in_input_order() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let dir = std::env::temp_dir().join(format!("cube_ocr_batch_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut paths = Vec::new();
+        for i in 0..5u32 {
+            let path = dir.join(format!("page-{}.png", i));
+            if i == 2 {
+                std::fs::write(&path, b"not an image").unwrap();
+            } else {
+                image::RgbImage::new(10 + i, 20).save(&path).unwrap();
+            }
+            paths.push(path.to_string_lossy().to_string());
+        }
+
+        // Stands in for the vision call: decodes the image and reports its
+        // size, with earlier pages taking longer so they finish out of order
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let extract = |path: String| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let running = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(running, Ordering::SeqCst);
+                let page: u64 = path.trim_end_matches(".png").rsplit('-').next().unwrap().parse().unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis((5 - page) * 15)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                let image = image::open(&path).map_err(|e| OCRError(format!("Unreadable image: {}", e)))?;
+                Ok(OCRResult {
+                    text: format!("{}x{}", image.width(), image.height()),
+                    confidence: 1.0,
+                    language: "eng".to_string(),
+                    words: Vec::new(),
+                })
+            }
+        };
+
+        let mut progress = Vec::new();
+        let results = run_batch(paths.clone(), 2, extract, |update| progress.push(update)).await;
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(results.iter().map(|r| r.path.clone()).collect::<Vec<_>>(), paths);
+        assert_eq!(results.iter().filter(|r| r.result.is_some()).count(), 4);
+        let failed: Vec<_> = results.iter().filter(|r| r.error.is_some()).collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].index, 2);
+        assert!(failed[0].result.is_none());
+        assert_eq!(results[3].result.as_ref().unwrap().text, "13x20");
+
+        assert_eq!(progress.iter().map(|p| p.completed).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        assert!(progress.iter().all(|p| p.total == 5));
+        assert_ne!(progress.iter().map(|p| p.item.index).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_parse_word_boxes_from_fenced_reply() {
+        let reply = "```json\n{\"words\": [\
+            {\"text\": \"Invoice\", \"x\": 12, \"y\": 8.6, \"width\": 90, \"height\": 20, \"confidence\": 0.98},\
+            {\"text\": \"#42\", \"x\": 110, \"y\": 8, \"width\": 500, \"height\": 20},\
+            {\"text\": \"smudge\", \"x\": 5, \"y\": 60, \"width\": 10, \"height\": 10, \"confidence\": 0.2},\
+            {\"text\": \"Paid\", \"x\": 12, \"y\": 40, \"width\": 40, \"height\": 18, \"confidence\": 0.9}\
+        ]}\n```";
+        let words = parse_word_boxes(reply, 200, 100, 0.7).unwrap();
+
+        assert_eq!(words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>(), vec!["Invoice", "#42", "Paid"]);
+        assert_eq!((words[0].x, words[0].y), (12, 8));
+        assert_eq!(words[1].width, 90, "box is clamped to the image");
+        assert_eq!(words_to_text(&words), "Invoice #42\nPaid");
+        assert!(parse_word_boxes("I could not read this image.", 200, 100, 0.7).is_err());
+    }
+
     // Integration tests (require OPENAI_API_KEY)
     #[tokio::test]
     #[ignore] // Run with: cargo test -- --ignored
@@ -0,0 +1,356 @@
+// Searchable PDF - Scanned pages with an invisible OCR text layer
+// Every page embeds the original image at its native pixel size and is sized
+// from the image's DPI; recognized words are drawn in text render mode 3
+// (invisible) over their bounding boxes so they can be selected and searched.
+
+use super::{OCRError, OCRWord};
+use image::{DynamicImage, RgbImage};
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Document, Object, Stream};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek};
+use std::path::Path;
+
+/// Used when the image does not record its density
+const DEFAULT_DPI: f32 = 72.0;
+
+/// Average Helvetica advance, as a fraction of the font size
+const AVERAGE_GLYPH_WIDTH: f32 = 0.5;
+
+/// One page of the input at its native resolution
+pub struct SourcePage {
+    pub image: RgbImage,
+    /// Horizontal and vertical dots per inch
+    pub dpi: (f32, f32),
+}
+
+impl SourcePage {
+    /// Page size in PDF points
+    pub fn size_points(&self) -> (f32, f32) {
+        (
+            self.image.width() as f32 * 72.0 / self.dpi.0,
+            self.image.height() as f32 * 72.0 / self.dpi.1,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchablePdfResult {
+    pub output_path: String,
+    pub pages: usize,
+    pub words: usize,
+    pub text: String,
+}
+
+/// Reads the pages of an image file; a multi-page TIFF gives one page per
+/// image directory
+pub fn load_pages(path: &Path) -> Result<Vec<SourcePage>, OCRError> {
+    let bytes = std::fs::read(path).map_err(|e| OCRError(format!("Failed to read image file: {}", e)))?;
+    if bytes.starts_with(b"II*\0") || bytes.starts_with(b"MM\0*") {
+        return load_tiff_pages(&bytes);
+    }
+
+    let image = image::load_from_memory(&bytes).map_err(|e| OCRError(format!("Image load failed: {}", e)))?;
+    Ok(vec![SourcePage {
+        image: image.to_rgb8(),
+        dpi: image_dpi(&bytes).unwrap_or((DEFAULT_DPI, DEFAULT_DPI)),
+    }])
+}
+
+fn load_tiff_pages(bytes: &[u8]) -> Result<Vec<SourcePage>, OCRError> {
+    use tiff::decoder::{Decoder, DecodingResult};
+    use tiff::ColorType;
+
+    let tiff_error = |e: tiff::TiffError| OCRError(format!("TIFF decode failed: {}", e));
+    let mut decoder = Decoder::new(std::io::Cursor::new(bytes)).map_err(tiff_error)?;
+    let mut pages = Vec::new();
+    loop {
+        let (width, height) = decoder.dimensions().map_err(tiff_error)?;
+        let color = decoder.colortype().map_err(tiff_error)?;
+        let dpi = tiff_dpi(&mut decoder);
+        let DecodingResult::U8(pixels) = decoder.read_image().map_err(tiff_error)? else {
+            return Err(OCRError("Only 8-bit TIFF pages are supported".to_string()));
+        };
+        let image = match color {
+            ColorType::Gray(8) => image::GrayImage::from_raw(width, height, pixels).map(DynamicImage::ImageLuma8),
+            ColorType::RGB(8) => RgbImage::from_raw(width, height, pixels).map(DynamicImage::ImageRgb8),
+            ColorType::RGBA(8) => image::RgbaImage::from_raw(width, height, pixels).map(DynamicImage::ImageRgba8),
+            other => return Err(OCRError(format!("Unsupported TIFF color type: {:?}", other))),
+        }
+        .ok_or_else(|| OCRError("TIFF page data is truncated".to_string()))?;
+
+        pages.push(SourcePage {
+            image: image.to_rgb8(),
+            dpi,
+        });
+        if !decoder.more_images() {
+            break;
+        }
+        decoder.next_image().map_err(tiff_error)?;
+    }
+    Ok(pages)
+}
+
+fn tiff_dpi<R: Read + Seek>(decoder: &mut tiff::decoder::Decoder<R>) -> (f32, f32) {
+    use tiff::decoder::ifd::Value;
+    use tiff::tags::Tag;
+
+    // ResolutionUnit: 1 = none, 2 = inch (the default), 3 = centimetre
+    let per_inch = match decoder.find_tag_unsigned::<u16>(Tag::ResolutionUnit) {
+        Ok(Some(1)) => return (DEFAULT_DPI, DEFAULT_DPI),
+        Ok(Some(3)) => 2.54,
+        _ => 1.0,
+    };
+    let mut resolution = |tag| match decoder.find_tag(tag) {
+        Ok(Some(Value::Rational(numerator, denominator))) if numerator > 0 && denominator > 0 => {
+            Some(numerator as f32 / denominator as f32 * per_inch)
+        }
+        _ => None,
+    };
+    let x = resolution(Tag::XResolution).unwrap_or(DEFAULT_DPI);
+    let y = resolution(Tag::YResolution).unwrap_or(x);
+    (x, y)
+}
+
+/// Density from a PNG pHYs chunk or a JPEG JFIF header
+fn image_dpi(bytes: &[u8]) -> Option<(f32, f32)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let mut offset = 8;
+        while offset + 8 <= bytes.len() {
+            let length = u32::from_be_bytes(bytes[offset..offset + 4].try_into().ok()?) as usize;
+            let kind = &bytes[offset + 4..offset + 8];
+            let data = bytes.get(offset + 8..offset + 8 + length)?;
+            if kind == b"pHYs" && length >= 9 {
+                // Unit 1 is pixels per metre; 0 only records the aspect ratio
+                if data[8] != 1 {
+                    return None;
+                }
+                let x = u32::from_be_bytes(data[0..4].try_into().ok()?) as f32 * 0.0254;
+                let y = u32::from_be_bytes(data[4..8].try_into().ok()?) as f32 * 0.0254;
+                return (x > 0.0 && y > 0.0).then_some((x, y));
+            }
+            // pHYs has to come before the image data
+            if kind == b"IDAT" {
+                return None;
+            }
+            offset += 12 + length;
+        }
+    } else if bytes.starts_with(&[0xFF, 0xD8]) {
+        // The JFIF APP0 segment directly follows the start-of-image marker
+        let app0 = bytes.get(2..18)?;
+        if app0[0..2] == [0xFF, 0xE0] && &app0[4..9] == b"JFIF\0" {
+            let scale = match app0[11] {
+                1 => 1.0,
+                2 => 2.54,
+                _ => return None,
+            };
+            let x = u16::from_be_bytes([app0[12], app0[13]]) as f32 * scale;
+            let y = u16::from_be_bytes([app0[14], app0[15]]) as f32 * scale;
+            return (x > 0.0 && y > 0.0).then_some((x, y));
+        }
+    }
+    None
+}
+
+/// Builds the PDF from each page and the words recognized on it
+pub fn build_searchable_pdf(pages: &[(SourcePage, Vec<OCRWord>)]) -> Result<Vec<u8>, OCRError> {
+    let pdf_error = |e: lopdf::Error| OCRError(format!("PDF generation failed: {}", e));
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+        "Encoding" => "WinAnsiEncoding",
+    });
+
+    let mut kids = Vec::with_capacity(pages.len());
+    for (page, words) in pages {
+        let (width, height) = page.size_points();
+
+        let mut image = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => page.image.width(),
+                "Height" => page.image.height(),
+                "ColorSpace" => "DeviceRGB",
+                "BitsPerComponent" => 8,
+            },
+            page.image.as_raw().clone(),
+        );
+        image.compress().map_err(pdf_error)?;
+        let image_id = doc.add_object(image);
+
+        let content = page_content(page, words).encode().map_err(pdf_error)?;
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content));
+
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), width.into(), height.into()],
+            "Contents" => content_id,
+            "Resources" => dictionary! {
+                "Font" => dictionary! { "F1" => font_id },
+                "XObject" => dictionary! { "Im1" => image_id },
+            },
+        });
+        kids.push(Object::from(page_id));
+    }
+
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => pages.len() as i64,
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+
+    let mut output = Vec::new();
+    doc.save_to(&mut output)
+        .map_err(|e| OCRError(format!("PDF generation failed: {}", e)))?;
+    Ok(output)
+}
+
+/// Draws the image over the whole page, then each word invisibly, sized and
+/// stretched to fill its bounding box
+fn page_content(page: &SourcePage, words: &[OCRWord]) -> Content {
+    let (width, height) = page.size_points();
+    let (scale_x, scale_y) = (72.0 / page.dpi.0, 72.0 / page.dpi.1);
+
+    let mut operations = vec![
+        Operation::new("q", vec![]),
+        Operation::new("cm", vec![width.into(), 0.into(), 0.into(), height.into(), 0.into(), 0.into()]),
+        Operation::new("Do", vec!["Im1".into()]),
+        Operation::new("Q", vec![]),
+        Operation::new("BT", vec![]),
+        Operation::new("Tr", vec![3.into()]),
+    ];
+    for (index, word) in words.iter().enumerate() {
+        let mut encoded = win_ansi(&word.text);
+        if encoded.is_empty() {
+            continue;
+        }
+        let size = (word.height as f32 * scale_y).max(1.0);
+        let natural_width = encoded.len() as f32 * AVERAGE_GLYPH_WIDTH * size;
+        // Extractors rely on an explicit space to split words on one line
+        if words.get(index + 1).is_some_and(|next| next.y < word.y + word.height) {
+            encoded.push(b' ');
+        }
+        let stretch = word.width as f32 * scale_x / natural_width * 100.0;
+        // Baseline sits above the box bottom by roughly the descender depth
+        let baseline = height - (word.y + word.height) as f32 * scale_y + size * 0.2;
+
+        operations.extend([
+            Operation::new("Tf", vec!["F1".into(), size.into()]),
+            Operation::new("Tz", vec![stretch.into()]),
+            Operation::new(
+                "Tm",
+                vec![1.into(), 0.into(), 0.into(), 1.into(), (word.x as f32 * scale_x).into(), baseline.into()],
+            ),
+            Operation::new("Tj", vec![Object::string_literal(encoded)]),
+        ]);
+    }
+    operations.push(Operation::new("ET", vec![]));
+    Content { operations }
+}
+
+/// WinAnsiEncoding matches Latin-1 outside 0x80-0x9F; anything else is
+/// replaced so the text layer stays aligned with the words
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x20..=0x7E | 0xA0..=0xFF => c as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, x: u32, y: u32, width: u32, height: u32) -> OCRWord {
+        OCRWord {
+            text: text.to_string(),
+            x,
+            y,
+            width,
+            height,
+            confidence: 0.9,
+        }
+    }
+
+    #[test]
+    fn test_text_layer_matches_recognized_words() {
+        let dir = std::env::temp_dir().join(format!("cube_ocr_pdf_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // A 150 DPI JPEG scan
+        let jpeg_path = dir.join("receipt.jpg");
+        let mut jpeg = Vec::new();
+        let mut encoder = image::codecs::jpeg::JpegEncoder::new(&mut jpeg);
+        encoder.set_pixel_density(image::codecs::jpeg::PixelDensity::dpi(150));
+        encoder.encode_image(&RgbImage::new(300, 150)).unwrap();
+        std::fs::write(&jpeg_path, &jpeg).unwrap();
+
+        // A two-page 200 DPI TIFF
+        let tiff_path = dir.join("contract.tiff");
+        let mut tiff_bytes = std::io::Cursor::new(Vec::new());
+        let mut tiff = tiff::encoder::TiffEncoder::new(&mut tiff_bytes).unwrap();
+        for shade in [255u8, 200] {
+            let mut page = tiff.new_image::<tiff::encoder::colortype::Gray8>(400, 200).unwrap();
+            page.resolution(tiff::tags::ResolutionUnit::Inch, tiff::encoder::Rational { n: 200, d: 1 });
+            page.write_data(&vec![shade; 400 * 200]).unwrap();
+        }
+        std::fs::write(&tiff_path, tiff_bytes.into_inner()).unwrap();
+
+        let jpeg_pages = load_pages(&jpeg_path).unwrap();
+        let tiff_pages = load_pages(&tiff_path).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(jpeg_pages.len(), 1);
+        assert_eq!(jpeg_pages[0].dpi, (150.0, 150.0));
+        assert_eq!(tiff_pages.len(), 2);
+        assert_eq!(tiff_pages[1].dpi, (200.0, 200.0));
+
+        let recognized = vec![
+            vec![word("Total", 20, 30, 80, 24), word("due:", 110, 30, 60, 24), word("42.00", 180, 30, 90, 24)],
+            vec![word("Signed", 40, 50, 120, 30), word("in", 170, 50, 30, 30), word("Zürich", 210, 50, 120, 30)],
+            vec![word("Page", 40, 50, 90, 30), word("two", 140, 50, 70, 30)],
+        ];
+        let pages: Vec<_> = jpeg_pages.into_iter().chain(tiff_pages).zip(recognized.clone()).collect();
+        let pdf = build_searchable_pdf(&pages).unwrap();
+
+        // Page sizes follow the source resolution and DPI
+        let doc = Document::load_mem(&pdf).unwrap();
+        let media_boxes: Vec<Vec<f32>> = doc
+            .get_pages()
+            .values()
+            .map(|id| {
+                let page = doc.get_dictionary(*id).unwrap();
+                page.get(b"MediaBox")
+                    .unwrap()
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|n| n.as_float().unwrap())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(media_boxes, vec![vec![0.0, 0.0, 144.0, 72.0], vec![0.0, 0.0, 144.0, 72.0], vec![0.0, 0.0, 144.0, 72.0]]);
+
+        let texts = pdf_extract::extract_text_from_mem_by_pages(&pdf).unwrap();
+        assert_eq!(texts.len(), 3);
+        for (text, words) in texts.iter().zip(&recognized) {
+            let expected: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+            assert_eq!(text.split_whitespace().collect::<Vec<_>>(), expected, "page text: {:?}", text);
+        }
+    }
+}
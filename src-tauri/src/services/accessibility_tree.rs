@@ -0,0 +1,906 @@
+// CUBE Web Engine - Accessibility Tree
+// Computes the accessibility tree (roles, names, states, relationships) of a
+// cached page and audits it for common WCAG issues. Styles are taken from
+// inline `style` attributes only, so contrast checks cover inline colors.
+
+use scraper::{ElementRef, Html, Node, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Elements that never produce accessibility nodes
+const SKIPPED_TAGS: &[&str] = &["head", "script", "style", "template", "noscript", "meta", "link", "title"];
+/// Roles whose accessible name is computed from their content
+const NAME_FROM_CONTENT: &[&str] = &[
+    "button", "link", "heading", "listitem", "cell", "columnheader", "rowheader", "tab", "menuitem",
+    "option", "checkbox", "radio", "switch", "treeitem",
+];
+/// Input types that are buttons rather than fields needing a label
+const BUTTON_INPUT_TYPES: &[&str] = &["button", "submit", "reset", "image"];
+const SNIPPET_MAX_CHARS: usize = 120;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AxRelation {
+    /// labelled_by, described_by, controls, owns or active_descendant
+    pub kind: String,
+    pub target_ref: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AxNode {
+    /// Document-order index of the backing element
+    pub node_id: usize,
+    /// CSS selector that resolves to the backing element
+    pub node_ref: String,
+    pub role: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub value: Option<String>,
+    pub level: Option<u32>,
+    pub states: Vec<String>,
+    pub relations: Vec<AxRelation>,
+    pub children: Vec<AxNode>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum A11ySeverity {
+    Critical,
+    Serious,
+    Moderate,
+    Minor,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct A11yIssue {
+    pub rule: String,
+    pub severity: A11ySeverity,
+    pub message: String,
+    pub node_id: usize,
+    pub node_ref: String,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct A11yAuditReport {
+    pub issues: Vec<A11yIssue>,
+    pub nodes_checked: usize,
+}
+
+enum Content {
+    Text(String),
+    Element(usize),
+}
+
+struct ElementInfo {
+    tag: String,
+    attrs: HashMap<String, String>,
+    parent: Option<usize>,
+    content: Vec<Content>,
+    node_ref: String,
+    hidden: bool,
+    snippet: String,
+}
+
+impl ElementInfo {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs.get(name).map(String::as_str)
+    }
+
+    fn has_attr(&self, name: &str) -> bool {
+        self.attrs.contains_key(name)
+    }
+
+    fn aria_true(&self, name: &str) -> bool {
+        self.attr(name).is_some_and(|v| v.eq_ignore_ascii_case("true"))
+    }
+
+    fn input_type(&self) -> String {
+        self.attr("type").unwrap_or("text").trim().to_ascii_lowercase()
+    }
+
+    fn style(&self, property: &str) -> Option<String> {
+        self.attr("style")?
+            .split(';')
+            .filter_map(|decl| decl.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(property))
+            .map(|(_, value)| value.trim().to_ascii_lowercase())
+    }
+}
+
+/// Parsed page ready for accessibility queries
+pub struct AccessibilityDocument {
+    title: String,
+    elements: Vec<ElementInfo>,
+    ids: HashMap<String, usize>,
+    label_for: HashMap<String, Vec<usize>>,
+}
+
+impl AccessibilityDocument {
+    pub fn parse(html: &str) -> Self {
+        let document = Html::parse_document(html);
+        let title = Selector::parse("title")
+            .ok()
+            .and_then(|s| document.select(&s).next().map(|t| t.text().collect::<String>()))
+            .map(|t| normalize_space(&t))
+            .unwrap_or_default();
+
+        let mut doc = Self {
+            title,
+            elements: Vec::new(),
+            ids: HashMap::new(),
+            label_for: HashMap::new(),
+        };
+        doc.walk(document.root_element(), None, false);
+        doc
+    }
+
+    /// Root `document` node of the accessibility tree
+    pub fn tree(&self) -> AxNode {
+        let mut children = Vec::new();
+        if !self.elements.is_empty() {
+            self.build_children(0, &mut children, false);
+        }
+        AxNode {
+            node_id: 0,
+            node_ref: self.elements.first().map(|e| e.node_ref.clone()).unwrap_or_default(),
+            role: "document".to_string(),
+            name: self.title.clone(),
+            description: None,
+            value: None,
+            level: None,
+            states: Vec::new(),
+            relations: Vec::new(),
+            children,
+        }
+    }
+
+    /// Nodes with `role` whose name contains `name` (case-insensitive)
+    pub fn find(&self, role: &str, name: Option<&str>) -> Vec<AxNode> {
+        let name = name.map(|n| n.to_lowercase());
+        let mut found = Vec::new();
+        let mut stack = vec![self.tree()];
+        while let Some(mut node) = stack.pop() {
+            let children = std::mem::take(&mut node.children);
+            stack.extend(children.into_iter().rev());
+            let name_matches = match &name {
+                Some(n) => node.name.to_lowercase().contains(n.as_str()),
+                None => true,
+            };
+            if node.role.eq_ignore_ascii_case(role) && name_matches {
+                found.push(node);
+            }
+        }
+        found
+    }
+
+    pub fn audit(&self) -> A11yAuditReport {
+        let mut issues = Vec::new();
+        let mut previous_heading: Option<u32> = None;
+
+        for (i, element) in self.elements.iter().enumerate() {
+            if element.hidden {
+                continue;
+            }
+            let role = self.role(i);
+
+            if element.tag == "img" || (element.tag == "input" && element.input_type() == "image") {
+                let presentational = role.is_none() && element.tag == "img";
+                if !presentational
+                    && !element.has_attr("alt")
+                    && !element.has_attr("aria-label")
+                    && !element.has_attr("aria-labelledby")
+                    && !element.has_attr("title")
+                {
+                    issues.push(self.issue(i, "image-alt", A11ySeverity::Critical, "Image has no alt text".to_string()));
+                }
+            }
+
+            if self.is_labelable(i) && !self.has_label(i) {
+                issues.push(self.issue(
+                    i,
+                    "label",
+                    A11ySeverity::Critical,
+                    format!("Form field <{}> has no associated label", element.tag),
+                ));
+            }
+
+            match role.as_deref() {
+                Some("link") if self.name(i, "link").is_empty() => {
+                    issues.push(self.issue(i, "link-name", A11ySeverity::Serious, "Link has no accessible name".to_string()));
+                }
+                Some("button") if self.name(i, "button").is_empty() => {
+                    issues.push(self.issue(i, "button-name", A11ySeverity::Critical, "Button has no accessible name".to_string()));
+                }
+                Some("heading") => {
+                    let level = self.heading_level(i).unwrap_or(2);
+                    if let Some(previous) = previous_heading {
+                        if level > previous + 1 {
+                            issues.push(self.issue(
+                                i,
+                                "heading-order",
+                                A11ySeverity::Moderate,
+                                format!("Heading level jumps from h{} to h{}", previous, level),
+                            ));
+                        }
+                    }
+                    previous_heading = Some(level);
+                }
+                _ => {}
+            }
+
+            if let Some(issue) = self.check_contrast(i) {
+                issues.push(issue);
+            }
+        }
+
+        A11yAuditReport {
+            issues,
+            nodes_checked: self.elements.iter().filter(|e| !e.hidden).count(),
+        }
+    }
+
+    fn walk(&mut self, element: ElementRef, parent: Option<usize>, parent_hidden: bool) -> usize {
+        let tag = element.value().name().to_ascii_lowercase();
+        let attrs: HashMap<String, String> = element
+            .value()
+            .attrs()
+            .map(|(k, v)| (k.to_ascii_lowercase(), v.to_string()))
+            .collect();
+        let index = self.elements.len();
+
+        let node_ref = match attrs.get("id").filter(|id| is_css_ident(id) && !self.ids.contains_key(*id)) {
+            Some(id) => format!("#{}", id),
+            None => match parent {
+                None => tag.clone(),
+                Some(p) => {
+                    let nth = element
+                        .prev_siblings()
+                        .filter_map(ElementRef::wrap)
+                        .filter(|s| s.value().name().eq_ignore_ascii_case(&tag))
+                        .count()
+                        + 1;
+                    format!("{} > {}:nth-of-type({})", self.elements[p].node_ref, tag, nth)
+                }
+            },
+        };
+        if let Some(id) = attrs.get("id") {
+            self.ids.entry(id.clone()).or_insert(index);
+        }
+        if tag == "label" {
+            if let Some(target) = attrs.get("for") {
+                self.label_for.entry(target.clone()).or_default().push(index);
+            }
+        }
+
+        let snippet = {
+            let mut open = format!("<{}", tag);
+            for (k, v) in element.value().attrs() {
+                open.push_str(&format!(" {}=\"{}\"", k, v));
+            }
+            open.push('>');
+            if open.chars().count() > SNIPPET_MAX_CHARS {
+                open = open.chars().take(SNIPPET_MAX_CHARS).collect::<String>() + "…";
+            }
+            open
+        };
+
+        let mut info = ElementInfo {
+            tag,
+            attrs,
+            parent,
+            content: Vec::new(),
+            node_ref,
+            hidden: parent_hidden,
+            snippet,
+        };
+        info.hidden |= SKIPPED_TAGS.contains(&info.tag.as_str())
+            || info.has_attr("hidden")
+            || info.aria_true("aria-hidden")
+            || (info.tag == "input" && info.input_type() == "hidden")
+            || info.style("display").is_some_and(|v| v == "none")
+            || info.style("visibility").is_some_and(|v| v == "hidden");
+        let hidden = info.hidden;
+        self.elements.push(info);
+
+        let mut content = Vec::new();
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => content.push(Content::Text(text.to_string())),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        content.push(Content::Element(self.walk(child, Some(index), hidden)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.elements[index].content = content;
+        index
+    }
+
+    fn build_children(&self, i: usize, out: &mut Vec<AxNode>, name_from_content: bool) {
+        for content in &self.elements[i].content {
+            match content {
+                Content::Text(text) => {
+                    let text = normalize_space(text);
+                    if !name_from_content && !text.is_empty() {
+                        out.push(AxNode {
+                            node_id: i,
+                            node_ref: self.elements[i].node_ref.clone(),
+                            role: "text".to_string(),
+                            name: text,
+                            description: None,
+                            value: None,
+                            level: None,
+                            states: Vec::new(),
+                            relations: Vec::new(),
+                            children: Vec::new(),
+                        });
+                    }
+                }
+                Content::Element(child) => {
+                    if self.elements[*child].hidden {
+                        continue;
+                    }
+                    match self.node(*child) {
+                        Some(node) => out.push(node),
+                        None => self.build_children(*child, out, name_from_content),
+                    }
+                }
+            }
+        }
+    }
+
+    fn node(&self, i: usize) -> Option<AxNode> {
+        let role = self.role(i)?;
+        let element = &self.elements[i];
+        let mut children = Vec::new();
+        self.build_children(i, &mut children, NAME_FROM_CONTENT.contains(&role.as_str()));
+
+        let name = self.name(i, &role);
+        let description = element
+            .attr("aria-describedby")
+            .map(|ids| self.text_of_ids(ids))
+            .filter(|d| !d.is_empty())
+            .or_else(|| element.attr("title").map(normalize_space).filter(|t| !t.is_empty() && *t != name));
+
+        Some(AxNode {
+            node_id: i,
+            node_ref: element.node_ref.clone(),
+            level: if role == "heading" { self.heading_level(i) } else { None },
+            value: self.value(i, &role),
+            states: self.states(i, &role),
+            relations: self.relations(i),
+            description,
+            name,
+            role,
+            children,
+        })
+    }
+
+    /// Explicit ARIA role or the element's implicit role; `None` for generic containers
+    fn role(&self, i: usize) -> Option<String> {
+        let element = &self.elements[i];
+        if let Some(role) = element.attr("role").and_then(|r| r.split_whitespace().next()) {
+            let role = role.to_ascii_lowercase();
+            return match role.as_str() {
+                "none" | "presentation" => None,
+                _ => Some(role),
+            };
+        }
+        let role = match element.tag.as_str() {
+            "a" | "area" if element.has_attr("href") => "link",
+            "button" | "summary" => "button",
+            "input" => match element.input_type().as_str() {
+                "checkbox" => "checkbox",
+                "radio" => "radio",
+                "range" => "slider",
+                "number" => "spinbutton",
+                "search" => "searchbox",
+                "hidden" => return None,
+                t if BUTTON_INPUT_TYPES.contains(&t) => "button",
+                _ => "textbox",
+            },
+            "textarea" => "textbox",
+            "select" => {
+                let size = element.attr("size").and_then(|s| s.parse::<u32>().ok()).unwrap_or(0);
+                if element.has_attr("multiple") || size > 1 { "listbox" } else { "combobox" }
+            }
+            "option" => "option",
+            "img" => match element.attr("alt") {
+                Some("") => return None,
+                _ => "img",
+            },
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => "heading",
+            "nav" => "navigation",
+            "main" => "main",
+            "header" => "banner",
+            "footer" => "contentinfo",
+            "aside" => "complementary",
+            "form" => "form",
+            "search" => "search",
+            "section" if element.has_attr("aria-label") || element.has_attr("aria-labelledby") => "region",
+            "article" => "article",
+            "ul" | "ol" | "menu" => "list",
+            "li" => "listitem",
+            "table" => "table",
+            "thead" | "tbody" | "tfoot" => "rowgroup",
+            "tr" => "row",
+            "td" => "cell",
+            "th" => "columnheader",
+            "dialog" => "dialog",
+            "p" => "paragraph",
+            "figure" => "figure",
+            "fieldset" | "details" => "group",
+            "progress" => "progressbar",
+            "hr" => "separator",
+            _ => return None,
+        };
+        Some(role.to_string())
+    }
+
+    fn name(&self, i: usize, role: &str) -> String {
+        let element = &self.elements[i];
+        if let Some(name) = element.attr("aria-labelledby").map(|ids| self.text_of_ids(ids)) {
+            if !name.is_empty() {
+                return name;
+            }
+        }
+        if let Some(label) = element.attr("aria-label").map(normalize_space) {
+            if !label.is_empty() {
+                return label;
+            }
+        }
+
+        let native = match element.tag.as_str() {
+            "input" if BUTTON_INPUT_TYPES.contains(&element.input_type().as_str()) => {
+                match element.input_type().as_str() {
+                    "image" => element.attr("alt").map(normalize_space),
+                    "submit" => Some(element.attr("value").map(normalize_space).unwrap_or_else(|| "Submit".to_string())),
+                    "reset" => Some(element.attr("value").map(normalize_space).unwrap_or_else(|| "Reset".to_string())),
+                    _ => element.attr("value").map(normalize_space),
+                }
+            }
+            "input" | "select" | "textarea" => Some(self.label_text(i)),
+            "img" | "area" => element.attr("alt").map(normalize_space),
+            "fieldset" => self.child_text(i, "legend"),
+            "table" => self.child_text(i, "caption"),
+            "figure" => self.child_text(i, "figcaption"),
+            _ => None,
+        };
+        if let Some(name) = native.filter(|n| !n.is_empty()) {
+            return name;
+        }
+
+        if NAME_FROM_CONTENT.contains(&role) {
+            let text = self.text_of(i);
+            if !text.is_empty() {
+                return text;
+            }
+        }
+        element
+            .attr("title")
+            .or_else(|| if role == "textbox" || role == "searchbox" { element.attr("placeholder") } else { None })
+            .map(normalize_space)
+            .unwrap_or_default()
+    }
+
+    /// Text of `<label for>` elements and any wrapping `<label>`
+    fn label_text(&self, i: usize) -> String {
+        let mut labels: Vec<usize> = self.elements[i]
+            .attr("id")
+            .and_then(|id| self.label_for.get(id))
+            .cloned()
+            .unwrap_or_default();
+        let mut ancestor = self.elements[i].parent;
+        while let Some(a) = ancestor {
+            if self.elements[a].tag == "label" {
+                labels.push(a);
+                break;
+            }
+            ancestor = self.elements[a].parent;
+        }
+        normalize_space(&labels.iter().map(|l| self.text_of(*l)).collect::<Vec<_>>().join(" "))
+    }
+
+    fn child_text(&self, i: usize, tag: &str) -> Option<String> {
+        self.elements[i].content.iter().find_map(|c| match c {
+            Content::Element(child) if self.elements[*child].tag == tag => Some(self.text_of(*child)),
+            _ => None,
+        })
+    }
+
+    fn text_of_ids(&self, ids: &str) -> String {
+        let parts: Vec<String> = ids
+            .split_whitespace()
+            .filter_map(|id| self.ids.get(id))
+            .map(|target| self.text_of(*target))
+            .collect();
+        normalize_space(&parts.join(" "))
+    }
+
+    /// Visible text of an element, using alt text for images
+    fn text_of(&self, i: usize) -> String {
+        let mut text = String::new();
+        self.collect_text(i, &mut text);
+        normalize_space(&text)
+    }
+
+    fn collect_text(&self, i: usize, out: &mut String) {
+        for content in &self.elements[i].content {
+            match content {
+                Content::Text(t) => out.push_str(t),
+                Content::Element(child) => {
+                    let element = &self.elements[*child];
+                    if element.hidden {
+                        continue;
+                    }
+                    out.push(' ');
+                    if let Some(label) = element.attr("aria-label") {
+                        out.push_str(label);
+                    } else if element.tag == "img" {
+                        out.push_str(element.attr("alt").unwrap_or(""));
+                    } else {
+                        self.collect_text(*child, out);
+                    }
+                    out.push(' ');
+                }
+            }
+        }
+    }
+
+    fn heading_level(&self, i: usize) -> Option<u32> {
+        let element = &self.elements[i];
+        element
+            .attr("aria-level")
+            .and_then(|l| l.trim().parse().ok())
+            .or_else(|| element.tag.strip_prefix('h').and_then(|l| l.parse().ok()))
+    }
+
+    fn value(&self, i: usize, role: &str) -> Option<String> {
+        let element = &self.elements[i];
+        match role {
+            "textbox" | "searchbox" | "spinbutton" | "slider" => {
+                if element.tag == "textarea" {
+                    Some(self.text_of(i))
+                } else if element.input_type() == "password" {
+                    element.attr("value").map(|v| "•".repeat(v.chars().count()))
+                } else {
+                    element.attr("value").map(str::to_string)
+                }
+            }
+            "combobox" | "listbox" => element.content.iter().find_map(|c| match c {
+                Content::Element(option) if self.elements[*option].has_attr("selected") => Some(self.text_of(*option)),
+                _ => None,
+            }),
+            "progressbar" => element.attr("aria-valuenow").or(element.attr("value")).map(str::to_string),
+            _ => element.attr("aria-valuetext").or(element.attr("aria-valuenow")).map(str::to_string),
+        }
+        .filter(|v| !v.is_empty())
+    }
+
+    fn states(&self, i: usize, role: &str) -> Vec<String> {
+        let element = &self.elements[i];
+        let mut states = Vec::new();
+        let form_control = matches!(element.tag.as_str(), "input" | "select" | "textarea" | "button");
+
+        let disabled = (form_control && element.has_attr("disabled")) || element.aria_true("aria-disabled");
+        let focusable = element.has_attr("tabindex")
+            || (!disabled && (form_control || (element.tag == "a" && element.has_attr("href"))));
+        if focusable {
+            states.push("focusable".to_string());
+        }
+        if disabled {
+            states.push("disabled".to_string());
+        }
+        if matches!(role, "checkbox" | "radio" | "switch") {
+            match element.attr("aria-checked") {
+                Some("mixed") => states.push("mixed".to_string()),
+                Some(v) if v.eq_ignore_ascii_case("true") => states.push("checked".to_string()),
+                None if element.has_attr("checked") => states.push("checked".to_string()),
+                _ => {}
+            }
+        }
+        match element.attr("aria-expanded") {
+            Some(v) if v.eq_ignore_ascii_case("true") => states.push("expanded".to_string()),
+            Some(_) => states.push("collapsed".to_string()),
+            None if element.tag == "details" => {
+                states.push(if element.has_attr("open") { "expanded" } else { "collapsed" }.to_string())
+            }
+            None => {}
+        }
+        if element.aria_true("aria-selected") || (element.tag == "option" && element.has_attr("selected")) {
+            states.push("selected".to_string());
+        }
+        if element.aria_true("aria-pressed") {
+            states.push("pressed".to_string());
+        }
+        if element.has_attr("required") || element.aria_true("aria-required") {
+            states.push("required".to_string());
+        }
+        if element.has_attr("readonly") || element.aria_true("aria-readonly") {
+            states.push("readonly".to_string());
+        }
+        if element.aria_true("aria-invalid") {
+            states.push("invalid".to_string());
+        }
+        if element.tag == "select" && element.has_attr("multiple") {
+            states.push("multiselectable".to_string());
+        }
+        states
+    }
+
+    fn relations(&self, i: usize) -> Vec<AxRelation> {
+        let element = &self.elements[i];
+        let mut relations = Vec::new();
+        for (attr, kind) in [
+            ("aria-labelledby", "labelled_by"),
+            ("aria-describedby", "described_by"),
+            ("aria-controls", "controls"),
+            ("aria-owns", "owns"),
+            ("aria-activedescendant", "active_descendant"),
+        ] {
+            for id in element.attr(attr).unwrap_or("").split_whitespace() {
+                if let Some(target) = self.ids.get(id) {
+                    relations.push(AxRelation {
+                        kind: kind.to_string(),
+                        target_ref: self.elements[*target].node_ref.clone(),
+                    });
+                }
+            }
+        }
+        if let Some(labels) = element.attr("id").and_then(|id| self.label_for.get(id)) {
+            for label in labels {
+                relations.push(AxRelation {
+                    kind: "labelled_by".to_string(),
+                    target_ref: self.elements[*label].node_ref.clone(),
+                });
+            }
+        }
+        relations
+    }
+
+    fn is_labelable(&self, i: usize) -> bool {
+        let element = &self.elements[i];
+        match element.tag.as_str() {
+            "select" | "textarea" => true,
+            "input" => !BUTTON_INPUT_TYPES.contains(&element.input_type().as_str()),
+            _ => false,
+        }
+    }
+
+    /// Whether a form field has a real label; placeholders do not count
+    fn has_label(&self, i: usize) -> bool {
+        let element = &self.elements[i];
+        element.attr("aria-labelledby").is_some_and(|ids| !self.text_of_ids(ids).is_empty())
+            || element.attr("aria-label").is_some_and(|l| !l.trim().is_empty())
+            || element.attr("title").is_some_and(|t| !t.trim().is_empty())
+            || !self.label_text(i).is_empty()
+    }
+
+    fn check_contrast(&self, i: usize) -> Option<A11yIssue> {
+        let element = &self.elements[i];
+        let has_text = element
+            .content
+            .iter()
+            .any(|c| matches!(c, Content::Text(t) if !t.trim().is_empty()));
+        if !has_text {
+            return None;
+        }
+
+        let foreground = self.inherited_style(i, &["color"]);
+        let background = self.inherited_style(i, &["background-color", "background"]);
+        if foreground.is_none() && background.is_none() {
+            return None;
+        }
+        let foreground = match foreground {
+            Some(value) => parse_color(&value)?,
+            None => (0, 0, 0),
+        };
+        let background = match background {
+            Some(value) => parse_color(&value)?,
+            None => (255, 255, 255),
+        };
+
+        let ratio = contrast_ratio(foreground, background);
+        let required = if self.is_large_text(i) { 3.0 } else { 4.5 };
+        if ratio >= required {
+            return None;
+        }
+        Some(self.issue(
+            i,
+            "color-contrast",
+            A11ySeverity::Serious,
+            format!("Text contrast ratio {:.2}:1 is below the required {}:1", ratio, required),
+        ))
+    }
+
+    fn inherited_style(&self, i: usize, properties: &[&str]) -> Option<String> {
+        let mut current = Some(i);
+        while let Some(c) = current {
+            if let Some(value) = properties.iter().find_map(|p| self.elements[c].style(p)) {
+                return Some(value);
+            }
+            current = self.elements[c].parent;
+        }
+        None
+    }
+
+    fn is_large_text(&self, i: usize) -> bool {
+        let tag = self.elements[i].tag.as_str();
+        let default_px = match tag {
+            "h1" => 32.0,
+            "h2" => 24.0,
+            "h3" => 18.72,
+            _ => 16.0,
+        };
+        let size = self
+            .inherited_style(i, &["font-size"])
+            .and_then(|v| parse_font_size(&v))
+            .unwrap_or(default_px);
+        let bold = matches!(tag, "b" | "strong" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6")
+            || self
+                .inherited_style(i, &["font-weight"])
+                .is_some_and(|w| w == "bold" || w == "bolder" || w.parse::<u32>().is_ok_and(|n| n >= 700));
+        size >= 24.0 || (bold && size >= 18.66)
+    }
+
+    fn issue(&self, i: usize, rule: &str, severity: A11ySeverity, message: String) -> A11yIssue {
+        A11yIssue {
+            rule: rule.to_string(),
+            severity,
+            message,
+            node_id: i,
+            node_ref: self.elements[i].node_ref.clone(),
+            snippet: self.elements[i].snippet.clone(),
+        }
+    }
+}
+
+fn normalize_space(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn is_css_ident(id: &str) -> bool {
+    !id.is_empty()
+        && !id.starts_with(|c: char| c.is_ascii_digit() || c == '-')
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn parse_color(value: &str) -> Option<(u8, u8, u8)> {
+    let value = value.split_whitespace().find(|v| v.starts_with('#') || v.starts_with("rgb")).unwrap_or(value);
+    if let Some(hex) = value.strip_prefix('#') {
+        let digits: Vec<u8> = hex.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect::<Option<_>>()?;
+        return match digits.len() {
+            3 | 4 => Some((digits[0] * 17, digits[1] * 17, digits[2] * 17)),
+            6 | 8 => Some((digits[0] * 16 + digits[1], digits[2] * 16 + digits[3], digits[4] * 16 + digits[5])),
+            _ => None,
+        };
+    }
+    if let Some(args) = value.strip_prefix("rgba(").or_else(|| value.strip_prefix("rgb(")) {
+        let channels: Vec<u8> = args
+            .trim_end_matches(')')
+            .split([',', ' ', '/'])
+            .filter(|p| !p.is_empty())
+            .take(3)
+            .map(|p| p.trim().parse::<f32>().ok().map(|v| v.clamp(0.0, 255.0) as u8))
+            .collect::<Option<_>>()?;
+        return (channels.len() == 3).then(|| (channels[0], channels[1], channels[2]));
+    }
+    match value {
+        "black" => Some((0, 0, 0)),
+        "white" => Some((255, 255, 255)),
+        "gray" | "grey" => Some((128, 128, 128)),
+        "silver" => Some((192, 192, 192)),
+        "lightgray" | "lightgrey" => Some((211, 211, 211)),
+        "red" => Some((255, 0, 0)),
+        "green" => Some((0, 128, 0)),
+        "blue" => Some((0, 0, 255)),
+        "yellow" => Some((255, 255, 0)),
+        "orange" => Some((255, 165, 0)),
+        _ => None,
+    }
+}
+
+fn parse_font_size(value: &str) -> Option<f32> {
+    if let Some(px) = value.strip_suffix("px") {
+        return px.trim().parse().ok();
+    }
+    if let Some(pt) = value.strip_suffix("pt") {
+        return pt.trim().parse::<f32>().ok().map(|pt| pt * 4.0 / 3.0);
+    }
+    value
+        .strip_suffix("rem")
+        .or_else(|| value.strip_suffix("em"))
+        .and_then(|em| em.trim().parse::<f32>().ok())
+        .map(|em| em * 16.0)
+}
+
+/// WCAG 2 contrast ratio between two sRGB colors
+fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f32 {
+    fn luminance((r, g, b): (u8, u8, u8)) -> f32 {
+        let channel = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        };
+        0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b)
+    }
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = r##"<!DOCTYPE html>
+<html><head><title>Checkout</title><style>p { color: red }</style></head>
+<body>
+  <header><nav aria-label="Primary"><a href="/">Home</a><a href="/cart"><img src="cart.png" alt="Cart"></a></nav></header>
+  <main>
+    <h1>Checkout</h1>
+    <img src="/banner.png">
+    <form>
+      <label for="email">Email</label>
+      <input id="email" type="email" required aria-describedby="email-hint">
+      <span id="email-hint">We never share it</span>
+      <input type="text" placeholder="Coupon code">
+      <label><input type="checkbox" checked> Subscribe</label>
+      <button type="submit" aria-expanded="false" aria-controls="summary">Pay now</button>
+    </form>
+    <h3>Order summary</h3>
+    <p id="summary" style="color: #aaaaaa; background-color: #ffffff">Total due today</p>
+    <div aria-hidden="true"><button>Hidden</button></div>
+  </main>
+</body></html>"##;
+
+    #[test]
+    fn test_tree_exposes_roles_names_states_and_relations() {
+        let doc = AccessibilityDocument::parse(FIXTURE);
+        let tree = doc.tree();
+        assert_eq!(tree.role, "document");
+        assert_eq!(tree.name, "Checkout");
+
+        let email = &doc.find("textbox", Some("email"))[0];
+        assert_eq!(email.node_ref, "#email");
+        assert_eq!(email.description.as_deref(), Some("We never share it"));
+        assert!(email.states.contains(&"required".to_string()));
+        assert!(email.relations.iter().any(|r| r.kind == "described_by" && r.target_ref == "#email-hint"));
+
+        let cart = &doc.find("link", Some("cart"))[0];
+        assert_eq!(cart.name, "Cart");
+        let subscribe = &doc.find("checkbox", None)[0];
+        assert_eq!(subscribe.name, "Subscribe");
+        assert!(subscribe.states.contains(&"checked".to_string()));
+
+        let pay = &doc.find("button", Some("pay"))[0];
+        assert!(pay.states.contains(&"collapsed".to_string()));
+        assert_eq!(pay.relations[0].target_ref, "#summary");
+        assert_eq!(doc.find("navigation", Some("primary")).len(), 1);
+        assert!(doc.find("button", Some("hidden")).is_empty());
+        assert_eq!(doc.find("heading", None).iter().map(|h| h.level).collect::<Vec<_>>(), [Some(1), Some(3)]);
+    }
+
+    #[test]
+    fn test_audit_reports_violations_with_node_refs() {
+        let report = AccessibilityDocument::parse(FIXTURE).audit();
+        let found = |rule: &str| -> Vec<String> {
+            report.issues.iter().filter(|i| i.rule == rule).map(|i| i.node_ref.clone()).collect()
+        };
+
+        assert_eq!(found("image-alt"), ["html > body:nth-of-type(1) > main:nth-of-type(1) > img:nth-of-type(1)"]);
+        assert_eq!(found("label"), ["html > body:nth-of-type(1) > main:nth-of-type(1) > form:nth-of-type(1) > input:nth-of-type(2)"]);
+        assert_eq!(found("heading-order"), ["html > body:nth-of-type(1) > main:nth-of-type(1) > h3:nth-of-type(1)"]);
+        assert_eq!(found("color-contrast"), ["#summary"]);
+        assert!(found("button-name").is_empty());
+        assert!(found("link-name").is_empty());
+
+        let image = report.issues.iter().find(|i| i.rule == "image-alt").unwrap();
+        assert_eq!(image.snippet, "<img src=\"/banner.png\">");
+        assert_eq!(image.severity, A11ySeverity::Critical);
+    }
+}
@@ -0,0 +1,281 @@
+// Admin metrics stream
+//
+// Samples CPU, memory, request rate and active sessions at a fixed interval,
+// keeps a ring buffer so newly connected dashboards get recent history right
+// away, and fans samples out to subscribed admin clients over the API
+// server's WebSocket endpoint.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use sysinfo::System;
+use tokio::sync::broadcast;
+
+pub const DEFAULT_INTERVAL_MS: u64 = 2_000;
+pub const MIN_INTERVAL_MS: u64 = 250;
+pub const MAX_INTERVAL_MS: u64 = 60_000;
+pub const DEFAULT_HISTORY: usize = 300;
+pub const DEFAULT_MAX_SUBSCRIBERS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSample {
+    /// Unix time in milliseconds
+    pub timestamp: i64,
+    /// Fraction of total CPU, 0.0 - 1.0
+    pub cpu_usage: f64,
+    /// Fraction of total memory, 0.0 - 1.0
+    pub memory_usage: f64,
+    pub requests_per_second: f64,
+    /// Open API server connections, including metric streams
+    pub active_sessions: u64,
+}
+
+/// What a subscribed client receives
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MetricsFrame {
+    #[serde(rename_all = "camelCase")]
+    Backfill { samples: Vec<MetricsSample>, interval_ms: u64 },
+    Metrics { sample: MetricsSample },
+}
+
+/// Supplies host readings for each sample
+pub trait MetricsProbe: Send {
+    fn cpu_usage(&mut self) -> f64;
+    fn memory_usage(&mut self) -> f64;
+}
+
+/// Reads CPU and memory only, never the process table, to keep sampling cheap
+pub struct SystemProbe {
+    system: System,
+}
+
+impl SystemProbe {
+    pub fn new() -> Self {
+        let mut system = System::new();
+        system.refresh_cpu_usage();
+        system.refresh_memory();
+        Self { system }
+    }
+}
+
+impl Default for SystemProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsProbe for SystemProbe {
+    fn cpu_usage(&mut self) -> f64 {
+        self.system.refresh_cpu_usage();
+        (f64::from(self.system.global_cpu_usage()) / 100.0).clamp(0.0, 1.0)
+    }
+
+    fn memory_usage(&mut self) -> f64 {
+        self.system.refresh_memory();
+        match self.system.total_memory() {
+            0 => 0.0,
+            total => self.system.used_memory() as f64 / total as f64,
+        }
+    }
+}
+
+/// Live samples plus the history the client has not seen yet
+pub struct MetricsSubscription {
+    pub backfill: Vec<MetricsSample>,
+    pub receiver: broadcast::Receiver<MetricsSample>,
+    _slot: SubscriberSlot,
+}
+
+struct SubscriberSlot(Arc<MetricsHub>);
+
+impl Drop for SubscriberSlot {
+    fn drop(&mut self) {
+        self.0.subscribers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Counts an open connection for as long as it is held
+pub struct SessionGuard(Arc<MetricsHub>);
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        self.0.sessions.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub struct MetricsHub {
+    token: String,
+    interval_ms: AtomicU64,
+    capacity: usize,
+    max_subscribers: usize,
+    history: Mutex<VecDeque<MetricsSample>>,
+    sender: broadcast::Sender<MetricsSample>,
+    subscribers: AtomicUsize,
+    requests: AtomicU64,
+    sessions: AtomicU64,
+}
+
+impl MetricsHub {
+    pub fn new(token: impl Into<String>, interval_ms: u64) -> Self {
+        Self::with_limits(token, interval_ms, DEFAULT_HISTORY, DEFAULT_MAX_SUBSCRIBERS)
+    }
+
+    pub fn with_limits(token: impl Into<String>, interval_ms: u64, capacity: usize, max_subscribers: usize) -> Self {
+        let (sender, _) = broadcast::channel(64);
+        Self {
+            token: token.into(),
+            interval_ms: AtomicU64::new(interval_ms.clamp(MIN_INTERVAL_MS, MAX_INTERVAL_MS)),
+            capacity: capacity.max(1),
+            max_subscribers,
+            history: Mutex::new(VecDeque::with_capacity(capacity.max(1))),
+            sender,
+            subscribers: AtomicUsize::new(0),
+            requests: AtomicU64::new(0),
+            sessions: AtomicU64::new(0),
+        }
+    }
+
+    pub fn interval_ms(&self) -> u64 {
+        self.interval_ms.load(Ordering::Relaxed)
+    }
+
+    pub fn set_interval_ms(&self, interval_ms: u64) {
+        self.interval_ms
+            .store(interval_ms.clamp(MIN_INTERVAL_MS, MAX_INTERVAL_MS), Ordering::Relaxed);
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.load(Ordering::SeqCst)
+    }
+
+    /// Check a presented token against the configured one
+    pub fn authorize(&self, presented: Option<&str>) -> bool {
+        match presented {
+            Some(token) if !self.token.is_empty() => {
+                constant_time_eq::constant_time_eq(token.as_bytes(), self.token.as_bytes())
+            }
+            _ => false,
+        }
+    }
+
+    pub fn record_request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn session(self: &Arc<Self>) -> SessionGuard {
+        self.sessions.fetch_add(1, Ordering::SeqCst);
+        SessionGuard(self.clone())
+    }
+
+    /// Reserve a subscriber slot. `since` (ms) trims the backfill after a reconnect.
+    pub fn subscribe(self: &Arc<Self>, since: Option<i64>) -> Result<MetricsSubscription, String> {
+        self.subscribers
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < self.max_subscribers).then_some(n + 1)
+            })
+            .map_err(|_| format!("Too many metric subscribers (max {})", self.max_subscribers))?;
+        let slot = SubscriberSlot(self.clone());
+
+        // Subscribe before snapshotting so nothing falls in between
+        let receiver = self.sender.subscribe();
+        let backfill = self
+            .history
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .iter()
+            .filter(|s| match since {
+                Some(since) => s.timestamp > since,
+                None => true,
+            })
+            .cloned()
+            .collect();
+
+        Ok(MetricsSubscription { backfill, receiver, _slot: slot })
+    }
+
+    pub fn history(&self) -> Vec<MetricsSample> {
+        self.history.lock().map(|h| h.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Take one sample, store it in the ring and publish it
+    pub fn sample(&self, probe: &mut dyn MetricsProbe, elapsed: Duration) -> MetricsSample {
+        let requests = self.requests.swap(0, Ordering::Relaxed);
+        let seconds = elapsed.as_secs_f64();
+        let sample = MetricsSample {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            cpu_usage: probe.cpu_usage(),
+            memory_usage: probe.memory_usage(),
+            requests_per_second: if seconds > 0.0 { requests as f64 / seconds } else { 0.0 },
+            active_sessions: self.sessions.load(Ordering::SeqCst),
+        };
+
+        if let Ok(mut history) = self.history.lock() {
+            if history.len() == self.capacity {
+                history.pop_front();
+            }
+            history.push_back(sample.clone());
+        }
+        // No receivers is fine, the ring still gets the sample
+        let _ = self.sender.send(sample.clone());
+        sample
+    }
+
+    /// Sample forever at the configured interval
+    pub async fn run(self: Arc<Self>, mut probe: Box<dyn MetricsProbe>) {
+        let mut last = Instant::now();
+        loop {
+            tokio::time::sleep(Duration::from_millis(self.interval_ms())).await;
+            let now = Instant::now();
+            self.sample(probe.as_mut(), now - last);
+            last = now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedProbe;
+
+    impl MetricsProbe for FixedProbe {
+        fn cpu_usage(&mut self) -> f64 {
+            0.25
+        }
+
+        fn memory_usage(&mut self) -> f64 {
+            0.5
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer_and_subscriber_cap() {
+        let hub = Arc::new(MetricsHub::with_limits("secret", 1_000, 3, 1));
+        for _ in 0..5 {
+            hub.record_request();
+            hub.record_request();
+            hub.sample(&mut FixedProbe, Duration::from_secs(1));
+        }
+        let history = hub.history();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].requests_per_second, 2.0);
+
+        let subscription = hub.subscribe(None).unwrap();
+        assert_eq!(subscription.backfill.len(), 3);
+        assert!(hub.subscribe(None).is_err());
+        drop(subscription);
+        assert_eq!(hub.subscriber_count(), 0);
+
+        let since = history[1].timestamp;
+        let resumed = hub.subscribe(Some(since)).unwrap();
+        assert!(resumed.backfill.iter().all(|s| s.timestamp > since));
+
+        assert!(hub.authorize(Some("secret")));
+        assert!(!hub.authorize(Some("guess")));
+        assert!(!hub.authorize(None));
+    }
+}
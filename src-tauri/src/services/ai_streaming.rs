@@ -0,0 +1,319 @@
+// AI Streaming - Token-by-token output from completion APIs
+// Decodes the server-sent event bodies of OpenAI, Anthropic and Gemini
+// streaming responses into text deltas plus the usage reported at the end.
+use futures_util::StreamExt;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamProvider {
+    OpenAI,
+    Claude,
+    Gemini,
+}
+
+impl StreamProvider {
+    fn name(self) -> &'static str {
+        match self {
+            Self::OpenAI => "OpenAI",
+            Self::Claude => "Claude",
+            Self::Gemini => "Gemini",
+        }
+    }
+
+    /// Folds one event into `summary` and returns the text it carries
+    fn apply(self, event: &SseEvent, summary: &mut StreamSummary) -> Result<Option<String>, String> {
+        // OpenAI terminates its stream with a sentinel rather than JSON
+        if event.data == "[DONE]" {
+            return Ok(None);
+        }
+        let json: serde_json::Value =
+            serde_json::from_str(&event.data).map_err(|e| format!("Invalid stream event: {}", e))?;
+        if let Some(error) = json.get("error").filter(|error| !error.is_null()) {
+            let message = error["message"].as_str().map(str::to_string).unwrap_or_else(|| error.to_string());
+            return Err(format!("{} API error: {}", self.name(), message));
+        }
+
+        let delta = match self {
+            Self::OpenAI => {
+                // Only the final chunk carries usage, when requested with include_usage
+                if let Some(usage) = json.get("usage").filter(|usage| !usage.is_null()) {
+                    summary.prompt_tokens = token_count(&usage["prompt_tokens"]);
+                    summary.completion_tokens = token_count(&usage["completion_tokens"]);
+                    summary.tokens_used = token_count(&usage["total_tokens"]);
+                }
+                let choice = &json["choices"][0];
+                if let Some(reason) = choice["finish_reason"].as_str() {
+                    summary.finish_reason = Some(reason.to_string());
+                }
+                choice["delta"]["content"].as_str().map(str::to_string)
+            }
+            Self::Claude => match json["type"].as_str() {
+                Some("message_start") => {
+                    summary.prompt_tokens = token_count(&json["message"]["usage"]["input_tokens"]);
+                    None
+                }
+                Some("content_block_delta") => json["delta"]["text"].as_str().map(str::to_string),
+                Some("message_delta") => {
+                    summary.completion_tokens = token_count(&json["usage"]["output_tokens"]);
+                    if let Some(reason) = json["delta"]["stop_reason"].as_str() {
+                        summary.finish_reason = Some(reason.to_string());
+                    }
+                    None
+                }
+                _ => None,
+            },
+            Self::Gemini => {
+                let usage = &json["usageMetadata"];
+                if usage.is_object() {
+                    summary.prompt_tokens = token_count(&usage["promptTokenCount"]);
+                    summary.completion_tokens = token_count(&usage["candidatesTokenCount"]);
+                    summary.tokens_used = token_count(&usage["totalTokenCount"]);
+                }
+                let candidate = &json["candidates"][0];
+                if let Some(reason) = candidate["finishReason"].as_str() {
+                    summary.finish_reason = Some(reason.to_string());
+                }
+                candidate["content"]["parts"].as_array().map(|parts| {
+                    parts.iter().filter_map(|part| part["text"].as_str()).collect::<String>()
+                })
+            }
+        };
+        Ok(delta)
+    }
+}
+
+fn token_count(value: &serde_json::Value) -> u32 {
+    value.as_u64().unwrap_or(0) as u32
+}
+
+/// Everything a finished stream produced
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StreamSummary {
+    pub content: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub tokens_used: u32,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// Incremental server-sent event parser. Bytes are held until a whole line has
+/// arrived, so a multibyte character split across network chunks is decoded
+/// in one piece.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseDecoder {
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            if let Some(event) = self.process_line(line.trim_end_matches(['\n', '\r'])) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Flushes an event the server left without a trailing blank line
+    pub fn finish(&mut self) -> Option<SseEvent> {
+        if !self.buffer.is_empty() {
+            let rest = std::mem::take(&mut self.buffer);
+            let rest = String::from_utf8_lossy(&rest);
+            if let Some(event) = self.process_line(rest.trim_end_matches('\r')) {
+                return Some(event);
+            }
+        }
+        self.dispatch()
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        // Comment lines are keep-alives
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(SseEvent {
+            event,
+            data: std::mem::take(&mut self.data).join("\n"),
+        })
+    }
+}
+
+/// Sends `request` and reads its event stream, calling `on_delta` with each
+/// piece of text as it arrives. Dropping the returned future closes the
+/// connection.
+pub async fn stream_completion(
+    request: reqwest::RequestBuilder,
+    provider: StreamProvider,
+    mut on_delta: impl FnMut(&str),
+) -> Result<StreamSummary, String> {
+    let response = request.send().await.map_err(|e| format!("Request failed: {}", e))?;
+    if !response.status().is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("{} API error: {}", provider.name(), error_text));
+    }
+
+    let mut decoder = SseDecoder::default();
+    let mut summary = StreamSummary::default();
+    let mut handle = |event: SseEvent, summary: &mut StreamSummary| -> Result<(), String> {
+        if let Some(delta) = provider.apply(&event, summary)? {
+            if !delta.is_empty() {
+                summary.content.push_str(&delta);
+                on_delta(&delta);
+            }
+        }
+        Ok(())
+    };
+
+    let mut body = response.bytes_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| format!("Stream interrupted: {}", e))?;
+        for event in decoder.push(&chunk) {
+            handle(event, &mut summary)?;
+        }
+    }
+    if let Some(event) = decoder.finish() {
+        handle(event, &mut summary)?;
+    }
+
+    if summary.tokens_used == 0 {
+        summary.tokens_used = summary.prompt_tokens + summary.completion_tokens;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn openai_body() -> String {
+        let mut body = String::new();
+        for piece in ["Héllo", ", wörld ", "👋", "!"] {
+            body.push_str(&format!(
+                "data: {}\n\n",
+                serde_json::json!({"choices": [{"delta": {"content": piece}, "finish_reason": null}]})
+            ));
+        }
+        body.push_str(": keep-alive\n\n");
+        body.push_str("data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n");
+        body.push_str("data: {\"choices\":[],\"usage\":{\"prompt_tokens\":9,\"completion_tokens\":4,\"total_tokens\":13}}\n\n");
+        body.push_str("data: [DONE]\n\n");
+        body
+    }
+
+    #[tokio::test]
+    async fn test_stream_reassembles_deltas_from_mock_server() {
+        let body = openai_body().into_bytes();
+        // Cut the body inside the emoji so its bytes arrive in separate writes
+        let emoji = body.windows(4).position(|w| w == "👋".as_bytes()).unwrap();
+        let chunks = vec![body[..emoji + 2].to_vec(), body[emoji + 2..].to_vec()];
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 4096];
+            let _ = socket.read(&mut request).await.unwrap();
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            for chunk in chunks {
+                socket.write_all(&chunk).await.unwrap();
+                socket.flush().await.unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        });
+
+        let request = reqwest::Client::new().post(format!("http://{}/v1/chat/completions", address));
+        let mut deltas = Vec::new();
+        let summary = stream_completion(request, StreamProvider::OpenAI, |delta| deltas.push(delta.to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(deltas, vec!["Héllo", ", wörld ", "👋", "!"]);
+        assert_eq!(deltas.concat(), summary.content);
+        assert_eq!(summary.content, "Héllo, wörld 👋!");
+        assert_eq!((summary.prompt_tokens, summary.completion_tokens, summary.tokens_used), (9, 4, 13));
+        assert_eq!(summary.finish_reason.as_deref(), Some("stop"));
+    }
+
+    #[test]
+    fn test_decoder_handles_any_chunk_boundary() {
+        let body = concat!(
+            "event: message_start\r\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":12}}}\r\n\r\n",
+            "event: content_block_delta\r\n",
+            "data: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"Grüße \"}}\r\n\r\n",
+            "event: content_block_delta\r\n",
+            "data: {\"type\":\"content_block_delta\",\"delta\":{\"type\":\"text_delta\",\"text\":\"日本\"}}\r\n\r\n",
+            "event: message_delta\r\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":5}}",
+        )
+        .as_bytes();
+
+        for split in 0..=body.len() {
+            let mut decoder = SseDecoder::default();
+            let mut events = decoder.push(&body[..split]);
+            events.extend(decoder.push(&body[split..]));
+            events.extend(decoder.finish());
+
+            let mut summary = StreamSummary::default();
+            let mut content = String::new();
+            for event in &events {
+                if let Some(delta) = StreamProvider::Claude.apply(event, &mut summary).unwrap() {
+                    content.push_str(&delta);
+                }
+            }
+            assert_eq!(events.len(), 4, "split at {}", split);
+            assert_eq!(events[1].event.as_deref(), Some("content_block_delta"));
+            assert_eq!(content, "Grüße 日本", "split at {}", split);
+            assert_eq!((summary.prompt_tokens, summary.completion_tokens), (12, 5));
+            assert_eq!(summary.finish_reason.as_deref(), Some("end_turn"));
+        }
+
+        let error = SseEvent {
+            event: Some("error".to_string()),
+            data: "{\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}".to_string(),
+        };
+        assert_eq!(
+            StreamProvider::Claude.apply(&error, &mut StreamSummary::default()),
+            Err("Claude API error: Overloaded".to_string())
+        );
+    }
+}
@@ -6,17 +6,23 @@
  * - Webhook receivers
  * - Status queries
  * - Result retrieval
+ * - Email open pixel and click redirects
+ * - Admin metrics stream over WebSocket
  * 
  * Runs on configurable port with CORS support.
  */
 
-use actix_web::{web, App, HttpResponse, HttpServer, middleware};
+use actix_web::{dev::Service, web, App, HttpRequest, HttpResponse, HttpServer, middleware};
 use actix_cors::Cors;
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, RwLock};
 use log::{info, error};
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
+use crate::services::admin_metrics_stream::{MetricsFrame, MetricsHub, MetricsSubscription};
 use crate::services::scheduler::WorkflowScheduler;
+use crate::services::contact_service::ContactServiceState;
+use crate::services::email_tracking::{EmailTrackingService, TRACKING_PIXEL_GIF};
+use tauri::Manager;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TriggerWorkflowRequest {
@@ -42,11 +48,24 @@ pub struct WebhookPayload {
     pub signature: Option<String>,
 }
 
+pub const METRICS_STREAM_PATH: &str = "/api/admin/metrics/stream";
+
+#[derive(Debug, Deserialize)]
+struct MetricsStreamQuery {
+    token: Option<String>,
+    /// Last sample timestamp a reconnecting client already has
+    since: Option<i64>,
+}
+
 #[derive(Clone)]
 pub struct ApiServerState {
     pub executions: Arc<RwLock<std::collections::HashMap<String, WorkflowStatusResponse>>>,
     pub webhook_secret: String,
     pub scheduler: Arc<Mutex<WorkflowScheduler>>,
+    /// Email tracking, with the app handle used to reach the contact store
+    pub email_tracking: Option<(Arc<EmailTrackingService>, tauri::AppHandle)>,
+    /// Server metrics pushed to admin dashboards
+    pub metrics: Option<Arc<MetricsHub>>,
 }
 
 pub struct ApiServer {
@@ -63,10 +82,24 @@ impl ApiServer {
                 executions: Arc::new(RwLock::new(std::collections::HashMap::new())),
                 webhook_secret,
                 scheduler,
+                email_tracking: None,
+                metrics: None,
             },
         }
     }
 
+    /// Serve the email open pixel and link redirects, crediting hits to contacts
+    pub fn with_email_tracking(mut self, tracking: Arc<EmailTrackingService>, app: tauri::AppHandle) -> Self {
+        self.state.email_tracking = Some((tracking, app));
+        self
+    }
+
+    /// Count requests and serve the admin metrics WebSocket stream
+    pub fn with_metrics_stream(mut self, metrics: Arc<MetricsHub>) -> Self {
+        self.state.metrics = Some(metrics);
+        self
+    }
+
     pub async fn start(self) -> Result<(), String> {
         let state = self.state.clone();
         
@@ -80,6 +113,22 @@ impl ApiServer {
             App::new()
                 .wrap(cors)
                 .wrap(middleware::Logger::default())
+                .wrap_fn(|req, srv| {
+                    // Feed request rate and open connections into the metrics stream
+                    let session = req
+                        .app_data::<web::Data<ApiServerState>>()
+                        .and_then(|state| state.metrics.as_ref())
+                        .map(|metrics| {
+                            metrics.record_request();
+                            metrics.session()
+                        });
+                    let response = srv.call(req);
+                    async move {
+                        let response = response.await;
+                        drop(session);
+                        response
+                    }
+                })
                 .app_data(web::Data::new(state.clone()))
                 .route("/", web::get().to(health_check))
                 .route("/health", web::get().to(health_check))
@@ -87,6 +136,9 @@ impl ApiServer {
                 .route("/api/workflows/{id}/status", web::get().to(get_workflow_status))
                 .route("/api/executions/{id}", web::get().to(get_execution_status))
                 .route("/api/webhooks/trigger", web::post().to(webhook_trigger))
+                .route("/t/o/{token}", web::get().to(email_open_pixel))
+                .route("/t/c/{token}/{index}", web::get().to(email_click_redirect))
+                .route(METRICS_STREAM_PATH, web::get().to(admin_metrics_stream))
         })
         .bind(("0.0.0.0", self.port))
         .map_err(|e| format!("Failed to bind server: {}", e))?
@@ -259,6 +311,145 @@ async fn webhook_trigger(
     }))
 }
 
+async fn email_open_pixel(
+    path: web::Path<String>,
+    state: web::Data<ApiServerState>,
+) -> HttpResponse {
+    let token = path.into_inner();
+    let token = token.trim_end_matches(".gif");
+
+    if let Some((tracking, app)) = &state.email_tracking {
+        if let Some(contacts) = app.try_state::<ContactServiceState>() {
+            if let Err(e) = tracking.record_open(token, &contacts, chrono::Utc::now()) {
+                error!("❌ Failed to record email open: {}", e);
+            }
+        }
+    }
+
+    // Always answer with the pixel so the message renders normally
+    HttpResponse::Ok()
+        .content_type("image/gif")
+        .insert_header(("Cache-Control", "no-store, no-cache, must-revalidate, private"))
+        .body(TRACKING_PIXEL_GIF)
+}
+
+async fn email_click_redirect(
+    path: web::Path<(String, usize)>,
+    state: web::Data<ApiServerState>,
+) -> HttpResponse {
+    let (token, index) = path.into_inner();
+
+    let Some((tracking, app)) = &state.email_tracking else {
+        return HttpResponse::NotFound().finish();
+    };
+    let Some(contacts) = app.try_state::<ContactServiceState>() else {
+        return HttpResponse::ServiceUnavailable().finish();
+    };
+
+    match tracking.record_click(&token, index, &contacts, chrono::Utc::now()) {
+        Ok(Some(target)) => HttpResponse::Found()
+            .insert_header(("Location", target))
+            .insert_header(("Cache-Control", "no-store"))
+            .finish(),
+        Ok(None) => HttpResponse::NotFound().finish(),
+        Err(e) => {
+            error!("❌ Failed to record email click: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+async fn admin_metrics_stream(
+    req: HttpRequest,
+    body: web::Payload,
+    query: web::Query<MetricsStreamQuery>,
+    state: web::Data<ApiServerState>,
+) -> actix_web::Result<HttpResponse> {
+    let Some(metrics) = state.metrics.clone() else {
+        return Ok(HttpResponse::NotFound().finish());
+    };
+
+    // Browsers cannot set headers on WebSocket connects, so the query token is accepted too
+    let bearer = req
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if !metrics.authorize(bearer.or(query.token.as_deref())) {
+        return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Invalid metrics token"
+        })));
+    }
+
+    let subscription = match metrics.subscribe(query.since) {
+        Ok(subscription) => subscription,
+        Err(e) => {
+            return Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                "error": e
+            })));
+        }
+    };
+
+    let (response, session, stream) = actix_ws::handle(&req, body)?;
+    actix_web::rt::spawn(stream_metrics(metrics, subscription, session, stream));
+    Ok(response)
+}
+
+/// Send the backfill, then every new sample until the client goes away
+async fn stream_metrics(
+    metrics: Arc<MetricsHub>,
+    mut subscription: MetricsSubscription,
+    mut session: actix_ws::Session,
+    mut stream: actix_ws::MessageStream,
+) {
+    let _session = metrics.session();
+    let last_seen = subscription.backfill.last().map(|s| s.timestamp);
+    let backfill = MetricsFrame::Backfill {
+        samples: std::mem::take(&mut subscription.backfill),
+        interval_ms: metrics.interval_ms(),
+    };
+    if send_frame(&mut session, &backfill).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            sample = subscription.receiver.recv() => match sample {
+                Ok(sample) => {
+                    if last_seen.is_some_and(|t| sample.timestamp <= t) {
+                        continue;
+                    }
+                    if send_frame(&mut session, &MetricsFrame::Metrics { sample }).await.is_err() {
+                        return;
+                    }
+                }
+                // A slow client just skips the samples it missed
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = stream.recv() => match message {
+                Some(Ok(actix_ws::Message::Ping(bytes))) => {
+                    if session.pong(&bytes).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Ok(actix_ws::Message::Close(reason))) => {
+                    let _ = session.close(reason).await;
+                    return;
+                }
+                Some(Ok(_)) => {}
+                Some(Err(_)) | None => return,
+            },
+        }
+    }
+    let _ = session.close(None).await;
+}
+
+async fn send_frame(session: &mut actix_ws::Session, frame: &MetricsFrame) -> Result<(), actix_ws::Closed> {
+    let text = serde_json::to_string(frame).unwrap_or_default();
+    session.text(text).await
+}
+
 fn verify_webhook_signature(payload: &WebhookPayload, signature: &str, secret: &str) -> bool {
     use hmac::{Hmac, Mac};
     use sha2::Sha256;
@@ -283,3 +474,94 @@ fn verify_webhook_signature(payload: &WebhookPayload, signature: &str, secret: &
     // Constant-time comparison
     signature == expected
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::admin_metrics_stream::{SystemProbe, MIN_INTERVAL_MS};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    /// Handshake by hand and return the status code with the open socket
+    async fn ws_connect(addr: std::net::SocketAddr, path: &str) -> (u16, TcpStream) {
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            path, addr
+        );
+        socket.write_all(request.as_bytes()).await.unwrap();
+
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(socket.read_u8().await.unwrap());
+        }
+        let status = String::from_utf8_lossy(&head)
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse().ok())
+            .unwrap();
+        (status, socket)
+    }
+
+    /// Read one unmasked server text frame
+    async fn read_text(socket: &mut TcpStream) -> serde_json::Value {
+        let opcode = socket.read_u8().await.unwrap() & 0x0F;
+        assert_eq!(opcode, 0x1);
+        let len = match socket.read_u8().await.unwrap() & 0x7F {
+            126 => socket.read_u16().await.unwrap() as usize,
+            127 => socket.read_u64().await.unwrap() as usize,
+            n => n as usize,
+        };
+        let mut payload = vec![0u8; len];
+        socket.read_exact(&mut payload).await.unwrap();
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_metrics_stream_backfill_periodic_frames_and_auth() {
+        let metrics = Arc::new(MetricsHub::new("admin-token", MIN_INTERVAL_MS));
+        metrics.sample(&mut SystemProbe::new(), Duration::from_secs(1));
+        actix_web::rt::spawn(metrics.clone().run(Box::new(SystemProbe::new())));
+
+        let state = ApiServer::new(0, "secret".to_string(), Arc::new(Mutex::new(WorkflowScheduler::new())))
+            .with_metrics_stream(metrics)
+            .state;
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(web::Data::new(state.clone()))
+                .route(METRICS_STREAM_PATH, web::get().to(admin_metrics_stream))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let addr = server.addrs()[0];
+        actix_web::rt::spawn(server.run());
+
+        let (status, _) = ws_connect(addr, &format!("{}?token=wrong", METRICS_STREAM_PATH)).await;
+        assert_eq!(status, 401);
+        let (status, _) = ws_connect(addr, METRICS_STREAM_PATH).await;
+        assert_eq!(status, 401);
+
+        let (status, mut socket) = ws_connect(addr, &format!("{}?token=admin-token", METRICS_STREAM_PATH)).await;
+        assert_eq!(status, 101);
+
+        let backfill = read_text(&mut socket).await;
+        assert_eq!(backfill["type"], "backfill");
+        assert_eq!(backfill["samples"].as_array().unwrap().len(), 1);
+        assert_eq!(backfill["intervalMs"], MIN_INTERVAL_MS);
+
+        let mut previous = backfill["samples"][0]["timestamp"].as_i64().unwrap();
+        for _ in 0..2 {
+            let frame = tokio::time::timeout(Duration::from_secs(5), read_text(&mut socket))
+                .await
+                .expect("no metric frame within 5s");
+            assert_eq!(frame["type"], "metrics");
+            let timestamp = frame["sample"]["timestamp"].as_i64().unwrap();
+            assert!(timestamp > previous);
+            assert!(frame["sample"]["cpuUsage"].is_number());
+            assert!(frame["sample"]["activeSessions"].as_u64().unwrap() >= 1);
+            previous = timestamp;
+        }
+    }
+}
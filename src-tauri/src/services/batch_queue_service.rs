@@ -3,12 +3,13 @@ use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
 use crate::services::training_data_manager::TrainingDataManager;
-use crate::services::video_processing::VideoProcessingService;
+use crate::services::video_processing::{ExtractedFrame, VideoProcessingService};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum QueueItemStatus {
@@ -19,12 +20,27 @@ pub enum QueueItemStatus {
     Paused,
 }
 
+/// Higher priorities are picked first; `Urgent` items may preempt running work
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QueueItemPriority {
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueueItem {
     pub id: String,
     pub video_path: String,
     pub session_name: String,
     pub status: QueueItemStatus,
+    #[serde(default)]
+    pub priority: QueueItemPriority,
+    /// Times this item was paused to make room for an urgent item
+    #[serde(default)]
+    pub preempted_count: u32,
     pub progress: f32,
     pub total_frames: i32,
     pub processed_frames: i32,
@@ -56,14 +72,116 @@ pub struct BatchResult {
     pub items: Vec<QueueItem>,
 }
 
-pub struct BatchQueueService {
+/// Work done between session creation and frame ingestion. Kept while an
+/// item is preempted so resuming skips straight to the next unprocessed frame.
+#[derive(Debug, Clone)]
+pub struct ItemCheckpoint {
+    pub session_id: i64,
+    pub frames: Vec<ExtractedFrame>,
+}
+
+/// The per-item work the queue schedules
+pub trait BatchItemProcessor: Send + Sync {
+    /// One-off setup for an item (session creation, frame extraction)
+    fn prepare(&self, item: &QueueItem) -> Result<ItemCheckpoint, String>;
+    fn process_frame(&self, checkpoint: &ItemCheckpoint, frame: &ExtractedFrame) -> Result<(), String>;
+    fn finish(&self, checkpoint: &ItemCheckpoint, success: bool);
+}
+
+/// Extracts frames with ffmpeg and stores them in a training session
+pub struct VideoFrameProcessor {
+    video_service: Arc<VideoProcessingService>,
+    training_manager: Arc<TrainingDataManager>,
+}
+
+impl BatchItemProcessor for VideoFrameProcessor {
+    fn prepare(&self, item: &QueueItem) -> Result<ItemCheckpoint, String> {
+        let session_id = self
+            .training_manager
+            .create_session(
+                item.session_name.clone(),
+                Some(format!("Batch processed from {}", item.video_path)),
+                item.video_path.clone(),
+            )
+            .map_err(|e| format!("Failed to create session: {}", e))?;
+
+        let config = crate::services::video_processing::FrameExtractionConfig {
+            fps: 1.0, // 1 frame per second
+            quality: 3,
+            output_format: "jpg".to_string(),
+            start_time: None,
+            duration: None,
+        };
+
+        match self.video_service.extract_frames(&item.video_path, config) {
+            Ok(result) => {
+                info!(
+                    "Extracted {} frames from {}",
+                    result.frames.len(),
+                    item.video_path
+                );
+                Ok(ItemCheckpoint {
+                    session_id,
+                    frames: result.frames,
+                })
+            }
+            Err(e) => {
+                self.finish(&ItemCheckpoint { session_id, frames: Vec::new() }, false);
+                Err(format!("Failed to extract frames: {}", e))
+            }
+        }
+    }
+
+    fn process_frame(&self, checkpoint: &ItemCheckpoint, frame: &ExtractedFrame) -> Result<(), String> {
+        if let Err(e) = self.training_manager.add_frame(
+            checkpoint.session_id,
+            frame.file_path.clone(),
+            frame.frame_number as i32,
+            frame.timestamp_seconds,
+            frame.file_size_bytes as i64,
+        ) {
+            warn!(
+                "Failed to add frame {} to session: {}",
+                frame.frame_number, e
+            );
+        }
+        Ok(())
+    }
+
+    fn finish(&self, checkpoint: &ItemCheckpoint, success: bool) {
+        let status = if success { "completed" } else { "failed" };
+        if let Err(e) = self
+            .training_manager
+            .update_session_status(checkpoint.session_id, status.to_string())
+        {
+            warn!("Failed to update session status: {}", e);
+        }
+    }
+}
+
+struct RunningItem {
+    priority: QueueItemPriority,
+    started_seq: u64,
+    preempt: Arc<AtomicBool>,
+}
+
+/// State shared between the service, the scheduler thread and item threads
+#[derive(Clone)]
+struct BatchWorker {
     queue: Arc<Mutex<VecDeque<QueueItem>>>,
     items: Arc<Mutex<HashMap<String, QueueItem>>>,
+    running: Arc<Mutex<HashMap<String, RunningItem>>>,
+    checkpoints: Arc<Mutex<HashMap<String, ItemCheckpoint>>>,
     is_running: Arc<Mutex<bool>>,
     is_paused: Arc<Mutex<bool>>,
+    preemption: Arc<AtomicBool>,
+    sequence: Arc<AtomicU64>,
     max_concurrent: usize,
-    video_service: Arc<VideoProcessingService>,
-    training_manager: Arc<TrainingDataManager>,
+    processor: Arc<dyn BatchItemProcessor>,
+}
+
+pub struct BatchQueueService {
+    worker: BatchWorker,
 }
 
 impl BatchQueueService {
@@ -74,26 +192,49 @@ impl BatchQueueService {
                 .map_err(|e| format!("Failed to initialize TrainingDataManager: {}", e))?,
         );
 
-        Ok(Self {
-            queue: Arc::new(Mutex::new(VecDeque::new())),
-            items: Arc::new(Mutex::new(HashMap::new())),
-            is_running: Arc::new(Mutex::new(false)),
-            is_paused: Arc::new(Mutex::new(false)),
+        Ok(Self::with_processor(
+            Arc::new(VideoFrameProcessor {
+                video_service,
+                training_manager,
+            }),
             max_concurrent,
-            video_service,
-            training_manager,
-        })
+        ))
+    }
+
+    pub fn with_processor(processor: Arc<dyn BatchItemProcessor>, max_concurrent: usize) -> Self {
+        Self {
+            worker: BatchWorker {
+                queue: Arc::new(Mutex::new(VecDeque::new())),
+                items: Arc::new(Mutex::new(HashMap::new())),
+                running: Arc::new(Mutex::new(HashMap::new())),
+                checkpoints: Arc::new(Mutex::new(HashMap::new())),
+                is_running: Arc::new(Mutex::new(false)),
+                is_paused: Arc::new(Mutex::new(false)),
+                preemption: Arc::new(AtomicBool::new(true)),
+                sequence: Arc::new(AtomicU64::new(0)),
+                max_concurrent: max_concurrent.max(1),
+                processor,
+            },
+        }
     }
 
     /// Add a video to the processing queue
-    pub fn add_to_queue(&self, video_path: String, session_name: String) -> Result<String, String> {
-        let item_id = format!("batch_{}", Utc::now().timestamp_millis());
+    pub fn add_to_queue(
+        &self,
+        video_path: String,
+        session_name: String,
+        priority: QueueItemPriority,
+    ) -> Result<String, String> {
+        let seq = self.worker.sequence.fetch_add(1, Ordering::SeqCst);
+        let item_id = format!("batch_{}_{}", Utc::now().timestamp_millis(), seq);
 
         let item = QueueItem {
             id: item_id.clone(),
             video_path,
             session_name,
             status: QueueItemStatus::Pending,
+            priority,
+            preempted_count: 0,
             progress: 0.0,
             total_frames: 0,
             processed_frames: 0,
@@ -103,20 +244,48 @@ impl BatchQueueService {
             created_at: Utc::now().to_rfc3339(),
         };
 
-        let mut queue = self.queue.lock().unwrap();
-        let mut items = self.items.lock().unwrap();
+        let mut queue = self.worker.queue.lock().unwrap();
+        let mut items = self.worker.items.lock().unwrap();
 
         queue.push_back(item.clone());
         items.insert(item_id.clone(), item);
 
-        info!("Added item {} to batch queue", item_id);
+        info!("Added item {} to batch queue ({:?})", item_id, priority);
         Ok(item_id)
     }
 
+    /// Change an item's priority. Queued items are re-ordered on the next pick;
+    /// a running item keeps running but becomes more or less preemptible.
+    pub fn set_item_priority(&self, item_id: &str, priority: QueueItemPriority) -> Result<QueueItem, String> {
+        let mut queue = self.worker.queue.lock().unwrap();
+        let mut items = self.worker.items.lock().unwrap();
+
+        let item = items.get_mut(item_id).ok_or("Item not found")?;
+        if matches!(item.status, QueueItemStatus::Completed | QueueItemStatus::Failed) {
+            return Err("Cannot change the priority of a finished item".to_string());
+        }
+        item.priority = priority;
+
+        if let Some(queued) = queue.iter_mut().find(|i| i.id == item_id) {
+            queued.priority = priority;
+        }
+        if let Some(running) = self.worker.running.lock().unwrap().get_mut(item_id) {
+            running.priority = priority;
+        }
+
+        info!("Set priority of item {} to {:?}", item_id, priority);
+        Ok(item.clone())
+    }
+
+    /// Allow urgent items to pause lower-priority running items when every slot is busy
+    pub fn set_preemption(&self, enabled: bool) {
+        self.worker.preemption.store(enabled, Ordering::SeqCst);
+    }
+
     /// Remove an item from the queue (only if pending)
     pub fn remove_from_queue(&self, item_id: &str) -> Result<(), String> {
-        let mut queue = self.queue.lock().unwrap();
-        let mut items = self.items.lock().unwrap();
+        let mut queue = self.worker.queue.lock().unwrap();
+        let mut items = self.worker.items.lock().unwrap();
 
         if let Some(item) = items.get(item_id) {
             match item.status {
@@ -135,8 +304,8 @@ impl BatchQueueService {
 
     /// Clear all pending items from the queue
     pub fn clear_queue(&self) -> Result<usize, String> {
-        let mut queue = self.queue.lock().unwrap();
-        let mut items = self.items.lock().unwrap();
+        let mut queue = self.worker.queue.lock().unwrap();
+        let mut items = self.worker.items.lock().unwrap();
 
         let pending_ids: Vec<String> = items
             .values()
@@ -157,8 +326,8 @@ impl BatchQueueService {
 
     /// Start processing the queue
     pub fn start_processing(&self) -> Result<(), String> {
-        let mut is_running = self.is_running.lock().unwrap();
-        let mut is_paused = self.is_paused.lock().unwrap();
+        let mut is_running = self.worker.is_running.lock().unwrap();
+        let mut is_paused = self.worker.is_paused.lock().unwrap();
 
         if *is_running {
             return Err("Batch processing already running".to_string());
@@ -171,35 +340,16 @@ impl BatchQueueService {
 
         info!("Started batch processing");
 
-        // Clone Arc references for the worker thread
-        let queue = Arc::clone(&self.queue);
-        let items = Arc::clone(&self.items);
-        let is_running = Arc::clone(&self.is_running);
-        let is_paused = Arc::clone(&self.is_paused);
-        let video_service = Arc::clone(&self.video_service);
-        let training_manager = Arc::clone(&self.training_manager);
-        let max_concurrent = self.max_concurrent;
-
-        // Spawn worker thread
-        thread::spawn(move || {
-            Self::worker_thread(
-                queue,
-                items,
-                is_running,
-                is_paused,
-                video_service,
-                training_manager,
-                max_concurrent,
-            );
-        });
+        let worker = self.worker.clone();
+        thread::spawn(move || worker.run());
 
         Ok(())
     }
 
     /// Pause processing (current items will finish)
     pub fn pause_processing(&self) -> Result<(), String> {
-        let is_running = self.is_running.lock().unwrap();
-        let mut is_paused = self.is_paused.lock().unwrap();
+        let is_running = self.worker.is_running.lock().unwrap();
+        let mut is_paused = self.worker.is_paused.lock().unwrap();
 
         if !*is_running {
             return Err("Batch processing not running".to_string());
@@ -212,8 +362,8 @@ impl BatchQueueService {
 
     /// Resume processing
     pub fn resume_processing(&self) -> Result<(), String> {
-        let is_running = self.is_running.lock().unwrap();
-        let mut is_paused = self.is_paused.lock().unwrap();
+        let is_running = self.worker.is_running.lock().unwrap();
+        let mut is_paused = self.worker.is_paused.lock().unwrap();
 
         if !*is_running {
             return Err("Batch processing not running".to_string());
@@ -230,7 +380,7 @@ impl BatchQueueService {
 
     /// Stop processing (graceful shutdown)
     pub fn stop_processing(&self) -> Result<(), String> {
-        let mut is_running = self.is_running.lock().unwrap();
+        let mut is_running = self.worker.is_running.lock().unwrap();
         *is_running = false;
         info!("Stopped batch processing");
         Ok(())
@@ -238,9 +388,9 @@ impl BatchQueueService {
 
     /// Get current queue status
     pub fn get_status(&self) -> BatchQueueStatus {
-        let is_running = *self.is_running.lock().unwrap();
-        let is_paused = *self.is_paused.lock().unwrap();
-        let items = self.items.lock().unwrap();
+        let is_running = *self.worker.is_running.lock().unwrap();
+        let is_paused = *self.worker.is_paused.lock().unwrap();
+        let items = self.worker.items.lock().unwrap();
 
         let total_items = items.len();
         let pending_items = items
@@ -280,19 +430,19 @@ impl BatchQueueService {
 
     /// Get all queue items
     pub fn get_all_items(&self) -> Vec<QueueItem> {
-        let items = self.items.lock().unwrap();
+        let items = self.worker.items.lock().unwrap();
         items.values().cloned().collect()
     }
 
     /// Get a specific item by ID
     pub fn get_item(&self, item_id: &str) -> Option<QueueItem> {
-        let items = self.items.lock().unwrap();
+        let items = self.worker.items.lock().unwrap();
         items.get(item_id).cloned()
     }
 
     /// Get batch results summary
     pub fn get_results(&self) -> BatchResult {
-        let items = self.items.lock().unwrap();
+        let items = self.worker.items.lock().unwrap();
 
         let all_items: Vec<QueueItem> = items.values().cloned().collect();
         let successful = all_items
@@ -351,25 +501,18 @@ impl BatchQueueService {
             items: all_items,
         }
     }
+}
 
-    /// Worker thread that processes the queue
-    fn worker_thread(
-        queue: Arc<Mutex<VecDeque<QueueItem>>>,
-        items: Arc<Mutex<HashMap<String, QueueItem>>>,
-        is_running: Arc<Mutex<bool>>,
-        is_paused: Arc<Mutex<bool>>,
-        video_service: Arc<VideoProcessingService>,
-        training_manager: Arc<TrainingDataManager>,
-        max_concurrent: usize,
-    ) {
+impl BatchWorker {
+    /// Scheduler loop: starts the highest-priority queued item whenever a slot is
+    /// free, and asks a lower-priority item to yield when an urgent one is waiting
+    fn run(self) {
         info!("Batch worker thread started");
 
-        let mut processing_count = 0;
-
         loop {
             // Check if we should stop
             {
-                let running = is_running.lock().unwrap();
+                let running = self.is_running.lock().unwrap();
                 if !*running {
                     info!("Worker thread stopping");
                     break;
@@ -378,176 +521,257 @@ impl BatchQueueService {
 
             // Check if paused
             {
-                let paused = is_paused.lock().unwrap();
+                let paused = self.is_paused.lock().unwrap();
                 if *paused {
                     thread::sleep(Duration::from_millis(500));
                     continue;
                 }
             }
 
-            // Check if we can process more items
-            if processing_count >= max_concurrent {
-                thread::sleep(Duration::from_millis(200));
-                continue;
-            }
+            let mut queue = self.queue.lock().unwrap();
+            let mut running = self.running.lock().unwrap();
 
-            // Get next pending item
-            let next_item = {
-                let mut queue = queue.lock().unwrap();
-                queue.pop_front()
-            };
+            // First queued item with the highest priority; preempted items are
+            // re-queued at the front so they win ties
+            let next_index = queue
+                .iter()
+                .enumerate()
+                .rev()
+                .max_by_key(|(_, item)| item.priority)
+                .map(|(index, _)| index);
 
-            if let Some(mut item) = next_item {
-                info!("Processing batch item: {}", item.id);
-
-                // Update status to processing
-                item.status = QueueItemStatus::Processing;
-                item.started_at = Some(Utc::now().to_rfc3339());
-                {
-                    let mut items = items.lock().unwrap();
-                    items.insert(item.id.clone(), item.clone());
-                }
-
-                processing_count += 1;
-
-                // Clone references for async processing
-                let item_id = item.id.clone();
-                let items_clone = Arc::clone(&items);
-                let video_service_clone = Arc::clone(&video_service);
-                let training_manager_clone = Arc::clone(&training_manager);
-
-                // Spawn thread to process this item
-                thread::spawn(move || {
-                    Self::process_item(
-                        item_id,
-                        item,
-                        items_clone,
-                        video_service_clone,
-                        training_manager_clone,
-                    );
-                });
-            } else {
-                // No more items in queue
-                if processing_count == 0 {
+            let Some(next_index) = next_index else {
+                if running.is_empty() {
                     // All done, stop processing
-                    let mut running = is_running.lock().unwrap();
-                    *running = false;
+                    *self.is_running.lock().unwrap() = false;
                     info!("Batch processing completed - no more items");
                     break;
                 }
 
                 // Wait for current items to finish
-                thread::sleep(Duration::from_millis(500));
+                drop((queue, running));
+                thread::sleep(Duration::from_millis(100));
+                continue;
+            };
+
+            if running.len() >= self.max_concurrent {
+                let urgent = queue[next_index].priority == QueueItemPriority::Urgent;
+                if urgent && self.preemption.load(Ordering::SeqCst) {
+                    let yielding = running.values().any(|r| r.preempt.load(Ordering::SeqCst));
+                    // Lowest priority first, most recently started among equals
+                    let victim = running
+                        .iter()
+                        .filter(|(_, r)| r.priority < QueueItemPriority::Urgent)
+                        .min_by_key(|(_, r)| (r.priority, u64::MAX - r.started_seq));
+                    if let (false, Some((id, r))) = (yielding, victim) {
+                        info!("Preempting batch item {} for an urgent item", id);
+                        r.preempt.store(true, Ordering::SeqCst);
+                    }
+                }
+                drop((queue, running));
+                thread::sleep(Duration::from_millis(50));
+                continue;
+            }
+
+            let mut item = queue.remove(next_index).unwrap();
+            info!("Processing batch item: {}", item.id);
+
+            // Update status to processing
+            item.status = QueueItemStatus::Processing;
+            if item.started_at.is_none() {
+                item.started_at = Some(Utc::now().to_rfc3339());
             }
+            self.items.lock().unwrap().insert(item.id.clone(), item.clone());
+
+            let preempt = Arc::new(AtomicBool::new(false));
+            running.insert(
+                item.id.clone(),
+                RunningItem {
+                    priority: item.priority,
+                    started_seq: self.sequence.fetch_add(1, Ordering::SeqCst),
+                    preempt: Arc::clone(&preempt),
+                },
+            );
+            drop((queue, running));
+
+            // Spawn thread to process this item
+            let worker = self.clone();
+            thread::spawn(move || worker.process_item(item, preempt));
         }
 
         info!("Batch worker thread finished");
     }
 
-    /// Process a single queue item
-    fn process_item(
-        item_id: String,
-        mut item: QueueItem,
-        items: Arc<Mutex<HashMap<String, QueueItem>>>,
-        video_service: Arc<VideoProcessingService>,
-        training_manager: Arc<TrainingDataManager>,
-    ) {
-        info!("Processing video: {}", item.video_path);
-
-        // Create training session
-        let session_result = training_manager.create_session(
-            item.session_name.clone(),
-            Some(format!("Batch processed from {}", item.video_path)),
-            item.video_path.clone(),
-        );
+    /// Process a single queue item, resuming from its checkpoint if it was preempted
+    fn process_item(&self, mut item: QueueItem, preempt: Arc<AtomicBool>) {
+        let item_id = item.id.clone();
+        let resumed = self.checkpoints.lock().unwrap().remove(&item_id);
 
-        let session_id = match session_result {
-            Ok(id) => id,
-            Err(e) => {
-                error!("Failed to create training session: {}", e);
+        let checkpoint = match resumed {
+            Some(checkpoint) => {
+                info!(
+                    "Resuming batch item {} at frame {}",
+                    item_id, item.processed_frames
+                );
+                checkpoint
+            }
+            None => {
+                info!("Processing video: {}", item.video_path);
+                match self.processor.prepare(&item) {
+                    Ok(checkpoint) => checkpoint,
+                    Err(e) => {
+                        error!("Failed to prepare batch item {}: {}", item_id, e);
+                        item.status = QueueItemStatus::Failed;
+                        item.error_message = Some(e);
+                        item.completed_at = Some(Utc::now().to_rfc3339());
+                        self.finish_item(item);
+                        return;
+                    }
+                }
+            }
+        };
+
+        let total = checkpoint.frames.len();
+        item.total_frames = total as i32;
+
+        // Save frames to training session
+        for idx in item.processed_frames.max(0) as usize..total {
+            if preempt.load(Ordering::SeqCst) {
+                info!("Batch item {} preempted at frame {}", item_id, idx);
+                item.status = QueueItemStatus::Paused;
+                item.preempted_count += 1;
+                self.checkpoints.lock().unwrap().insert(item_id.clone(), checkpoint);
+
+                // Re-queue at the front before freeing the slot so it resumes first
+                let mut queue = self.queue.lock().unwrap();
+                queue.push_front(item.clone());
+                self.items.lock().unwrap().insert(item_id.clone(), item);
+                self.running.lock().unwrap().remove(&item_id);
+                return;
+            }
+
+            if let Err(e) = self.processor.process_frame(&checkpoint, &checkpoint.frames[idx]) {
+                error!("Failed to process frame {} of {}: {}", idx, item_id, e);
                 item.status = QueueItemStatus::Failed;
-                item.error_message = Some(format!("Failed to create session: {}", e));
+                item.error_message = Some(e);
                 item.completed_at = Some(Utc::now().to_rfc3339());
-
-                let mut items = items.lock().unwrap();
-                items.insert(item_id, item);
+                self.processor.finish(&checkpoint, false);
+                self.finish_item(item);
                 return;
             }
-        };
 
-        // Extract frames from video
-        let config = crate::services::video_processing::FrameExtractionConfig {
-            fps: 1.0, // 1 frame per second
-            quality: 3,
-            output_format: "jpg".to_string(),
-            start_time: None,
-            duration: None,
-        };
+            // Update progress
+            item.progress = ((idx + 1) as f32 / total as f32) * 100.0;
+            item.processed_frames = (idx + 1) as i32;
+            self.items.lock().unwrap().insert(item_id.clone(), item.clone());
+        }
 
-        let extract_result = video_service.extract_frames(&item.video_path, config);
+        // Mark as completed
+        item.status = QueueItemStatus::Completed;
+        item.progress = 100.0;
+        item.completed_at = Some(Utc::now().to_rfc3339());
+        self.processor.finish(&checkpoint, true);
 
-        match extract_result {
-            Ok(result) => {
-                info!(
-                    "Extracted {} frames from {}",
-                    result.frames.len(),
-                    item.video_path
-                );
+        info!("Completed processing item: {}", item_id);
+        self.finish_item(item);
+    }
 
-                // Save frames to training session
-                for (idx, frame) in result.frames.iter().enumerate() {
-                    if let Err(e) = training_manager.add_frame(
-                        session_id,
-                        frame.file_path.clone(),
-                        frame.frame_number as i32,
-                        frame.timestamp_seconds,
-                        frame.file_size_bytes as i64,
-                    ) {
-                        warn!(
-                            "Failed to add frame {} to session: {}",
-                            frame.frame_number, e
-                        );
-                    }
+    fn finish_item(&self, item: QueueItem) {
+        let item_id = item.id.clone();
+        self.items.lock().unwrap().insert(item_id.clone(), item);
+        self.running.lock().unwrap().remove(&item_id);
+    }
+}
 
-                    // Update progress
-                    item.progress = ((idx + 1) as f32 / result.frames.len() as f32) * 100.0;
-                    item.processed_frames = (idx + 1) as i32;
-                    item.total_frames = result.frames.len() as i32;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
 
-                    let mut items = items.lock().unwrap();
-                    items.insert(item_id.clone(), item.clone());
-                }
+    const FRAMES: usize = 6;
 
-                // Mark as completed
-                item.status = QueueItemStatus::Completed;
-                item.progress = 100.0;
-                item.completed_at = Some(Utc::now().to_rfc3339());
+    /// Records which frames each item processed, in order
+    #[derive(Default)]
+    struct RecordingProcessor {
+        prepared: Mutex<Vec<String>>,
+        processed: Mutex<Vec<(String, u32)>>,
+        sessions: Mutex<HashMap<i64, String>>,
+    }
 
-                // Update session status
-                if let Err(e) =
-                    training_manager.update_session_status(session_id, "completed".to_string())
-                {
-                    warn!("Failed to update session status: {}", e);
-                }
+    impl BatchItemProcessor for RecordingProcessor {
+        fn prepare(&self, item: &QueueItem) -> Result<ItemCheckpoint, String> {
+            let mut sessions = self.sessions.lock().unwrap();
+            let session_id = sessions.len() as i64 + 1;
+            sessions.insert(session_id, item.session_name.clone());
+            self.prepared.lock().unwrap().push(item.session_name.clone());
+
+            let frames = (1..=FRAMES as u32)
+                .map(|n| ExtractedFrame {
+                    frame_number: n,
+                    timestamp_seconds: n as f64,
+                    file_path: format!("{}/frame_{}.jpg", item.session_name, n),
+                    file_size_bytes: 0,
+                })
+                .collect();
+            Ok(ItemCheckpoint { session_id, frames })
+        }
 
-                info!("Completed processing item: {}", item_id);
-            }
-            Err(e) => {
-                error!("Failed to extract frames: {}", e);
-                item.status = QueueItemStatus::Failed;
-                item.error_message = Some(format!("Failed to extract frames: {}", e));
-                item.completed_at = Some(Utc::now().to_rfc3339());
+        fn process_frame(&self, checkpoint: &ItemCheckpoint, frame: &ExtractedFrame) -> Result<(), String> {
+            let name = self.sessions.lock().unwrap()[&checkpoint.session_id].clone();
+            self.processed.lock().unwrap().push((name, frame.frame_number));
+            thread::sleep(Duration::from_millis(20));
+            Ok(())
+        }
 
-                // Update session status to failed
-                if let Err(e) =
-                    training_manager.update_session_status(session_id, "failed".to_string())
-                {
-                    warn!("Failed to update session status: {}", e);
-                }
-            }
+        fn finish(&self, _checkpoint: &ItemCheckpoint, _success: bool) {}
+    }
+
+    fn wait_until(condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out waiting for batch queue");
+            thread::sleep(Duration::from_millis(10));
         }
+    }
 
-        let mut items = items.lock().unwrap();
-        items.insert(item_id, item);
+    #[test]
+    fn test_priority_order_and_preempted_item_resumes() {
+        let processor = Arc::new(RecordingProcessor::default());
+        let service = BatchQueueService::with_processor(processor.clone(), 1);
+
+        service.add_to_queue("low.mp4".into(), "low".into(), QueueItemPriority::Low).unwrap();
+        service.add_to_queue("normal.mp4".into(), "normal".into(), QueueItemPriority::Normal).unwrap();
+        let bumped = service.add_to_queue("bumped.mp4".into(), "bumped".into(), QueueItemPriority::Low).unwrap();
+        service.add_to_queue("high.mp4".into(), "high".into(), QueueItemPriority::High).unwrap();
+        service.set_item_priority(&bumped, QueueItemPriority::High).unwrap();
+
+        service.start_processing().unwrap();
+        wait_until(|| !service.get_status().is_running);
+        assert_eq!(*processor.prepared.lock().unwrap(), vec!["bumped", "high", "normal", "low"]);
+
+        // One slot busy with background work; an urgent item takes it over
+        processor.prepared.lock().unwrap().clear();
+        processor.processed.lock().unwrap().clear();
+        let background = service.add_to_queue("bg.mp4".into(), "background".into(), QueueItemPriority::Normal).unwrap();
+        service.start_processing().unwrap();
+        wait_until(|| service.get_item(&background).is_some_and(|i| i.processed_frames >= 2));
+        service.add_to_queue("urgent.mp4".into(), "urgent".into(), QueueItemPriority::Urgent).unwrap();
+        wait_until(|| !service.get_status().is_running);
+
+        let background = service.get_item(&background).unwrap();
+        assert!(matches!(background.status, QueueItemStatus::Completed));
+        assert_eq!(background.preempted_count, 1);
+        assert_eq!(background.processed_frames, FRAMES as i32);
+
+        // Prepared once, every frame processed exactly once and in order, with the
+        // urgent item's frames in between
+        assert_eq!(*processor.prepared.lock().unwrap(), vec!["background", "urgent"]);
+        let processed = processor.processed.lock().unwrap();
+        let background_frames: Vec<u32> = processed.iter().filter(|(n, _)| n == "background").map(|(_, f)| *f).collect();
+        assert_eq!(background_frames, (1..=FRAMES as u32).collect::<Vec<_>>());
+        let first_urgent = processed.iter().position(|(n, _)| n == "urgent").unwrap();
+        let last_urgent = processed.iter().rposition(|(n, _)| n == "urgent").unwrap();
+        assert_eq!(last_urgent - first_urgent + 1, FRAMES);
+        assert!(first_urgent >= 2 && last_urgent < processed.len() - 1);
     }
 }
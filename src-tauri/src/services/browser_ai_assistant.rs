@@ -8,6 +8,10 @@ use std::sync::RwLock;
 use chrono::Utc;
 use uuid::Uuid;
 
+use super::browser_ai_page_translation::{
+    translate_in_batches, TranslatablePage, TranslatedBlock, CHARS_PER_TOKEN,
+};
+
 // ==================== Enums ====================
 
 /// AI model to use for different tasks
@@ -212,6 +216,34 @@ pub struct TranslationResult {
     pub created_at: i64,
 }
 
+/// Page translated in place: the whole page plus per-block patches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageTranslation {
+    pub id: String,
+    pub url: String,
+    pub source_language: Language,
+    pub target_language: Language,
+    pub translated_html: String,
+    pub original_html: String,
+    pub blocks: Vec<TranslatedBlock>,
+    pub segments_translated: u32,
+    /// Segments kept in the original because placeholders did not survive
+    pub segments_rejected: u32,
+    pub batches: u32,
+    pub model_used: AIModel,
+    pub created_at: i64,
+    pub cached: bool,
+}
+
+/// What the page needs to go back to its original text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageRestore {
+    pub url: String,
+    pub original_html: String,
+    /// Blocks to restore, `translated_html` being what is currently shown
+    pub blocks: Vec<TranslatedBlock>,
+}
+
 /// Form field for AI filling
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FormField {
@@ -266,11 +298,25 @@ pub struct QuestionAnswer {
     pub answer: String,
     pub confidence: f32,
     pub source_quotes: Vec<String>,
+    /// Page chunks the answer was drawn from, for highlighting in the page
+    pub citations: Vec<SourceCitation>,
+    /// False when the page does not contain an answer to the question
+    pub supported: bool,
     pub page_url: String,
     pub model_used: AIModel,
     pub created_at: i64,
 }
 
+/// A retrieved page chunk; offsets are character positions within the page context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceCitation {
+    pub chunk_index: usize,
+    pub start_offset: usize,
+    pub end_offset: usize,
+    pub text: String,
+    pub relevance_score: f32,
+}
+
 /// Content analysis result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContentAnalysis {
@@ -358,6 +404,8 @@ pub struct AIBrowserAssistant {
     settings: RwLock<AIAssistantSettings>,
     summaries_cache: RwLock<HashMap<String, PageSummary>>,
     translation_cache: RwLock<HashMap<String, TranslationResult>>,
+    /// Keyed by "url:target-language"
+    page_translations: RwLock<HashMap<String, PageTranslation>>,
     history: RwLock<Vec<AITaskHistory>>,
     stats: RwLock<AIAssistantStats>,
 }
@@ -368,6 +416,7 @@ impl AIBrowserAssistant {
             settings: RwLock::new(AIAssistantSettings::default()),
             summaries_cache: RwLock::new(HashMap::new()),
             translation_cache: RwLock::new(HashMap::new()),
+            page_translations: RwLock::new(HashMap::new()),
             history: RwLock::new(Vec::new()),
             stats: RwLock::new(AIAssistantStats::default()),
         }
@@ -594,13 +643,90 @@ impl AIBrowserAssistant {
         }
     }
     
+    /// Translate a page's HTML in place: text runs are sent in batches with
+    /// inline elements as placeholders and put back around the original markup
     pub fn translate_page(
         &self,
-        _url: &str,
+        url: &str,
         content: &str,
         target_language: Language,
-    ) -> Result<TranslationResult, String> {
-        self.translate_text(content, None, target_language)
+    ) -> Result<PageTranslation, String> {
+        let settings = self.settings.read().unwrap().clone();
+
+        let cache_key = format!("{}:{}", url, target_language.code());
+        if settings.cache_responses {
+            let cache = self.page_translations.read().unwrap();
+            if let Some(cached) = cache.get(&cache_key) {
+                self.record_cache_hit();
+                return Ok(PageTranslation {
+                    cached: true,
+                    ..cached.clone()
+                });
+            }
+        }
+
+        let page = TranslatablePage::parse(content);
+        let segments = page.segments();
+        let source_language = self.detect_language(&segments.join(" "));
+
+        // Leave room for the output, which is about as long as the input
+        let max_chars = (settings.max_tokens as usize / 2).max(1) * CHARS_PER_TOKEN;
+        let (translated, batches) = translate_in_batches(&segments, max_chars, |batch| {
+            Ok(batch
+                .iter()
+                .map(|segment| self.translate_segment(segment, &target_language))
+                .collect())
+        })?;
+        let rendered = page.render(&translated);
+
+        let result = PageTranslation {
+            id: Uuid::new_v4().to_string(),
+            url: url.to_string(),
+            source_language,
+            target_language,
+            translated_html: rendered.html,
+            original_html: content.to_string(),
+            blocks: rendered.blocks,
+            segments_translated: (segments.len() - rendered.rejected) as u32,
+            segments_rejected: rendered.rejected as u32,
+            batches: batches as u32,
+            model_used: settings.default_model,
+            created_at: Utc::now().timestamp(),
+            cached: false,
+        };
+
+        // Stored even with caching off so the page can be shown in its original again
+        self.page_translations
+            .write()
+            .unwrap()
+            .insert(cache_key, result.clone());
+
+        let characters: usize = segments.iter().map(|segment| segment.len()).sum();
+        self.record_task(AITaskType::Translate, (characters / CHARS_PER_TOKEN) as u32);
+
+        Ok(result)
+    }
+
+    /// Original content for the most recent translation of `url`
+    pub fn show_original_page(&self, url: &str) -> Result<PageRestore, String> {
+        let translations = self.page_translations.read().unwrap();
+        let latest = translations
+            .values()
+            .filter(|translation| translation.url == url)
+            .max_by_key(|translation| translation.created_at)
+            .ok_or_else(|| format!("No translation found for {}", url))?;
+
+        Ok(PageRestore {
+            url: url.to_string(),
+            original_html: latest.original_html.clone(),
+            blocks: latest.blocks.clone(),
+        })
+    }
+
+    /// Translate one placeholder segment, keeping `{n}`, `{/n}` and `{n/}` tokens
+    fn translate_segment(&self, segment: &str, target_language: &Language) -> String {
+        // Simulated translation - in production would call translation API
+        format!("[{}] {}", target_language.code(), segment)
     }
     
     // ==================== Form Filling ====================
@@ -721,25 +847,46 @@ impl AIBrowserAssistant {
     ) -> Result<QuestionAnswer, String> {
         let settings = self.settings.read().unwrap();
         
-        // Find relevant quotes from context
-        let source_quotes = self.find_relevant_quotes(question, context);
+        // Retrieve the most relevant chunks; only these are given to the model
+        let chunks = chunk_page(context);
+        let retrieved = retrieve_chunks(question, &chunks, QA_TOP_K);
         
-        // Generate answer (simulated)
-        let answer = if !source_quotes.is_empty() {
-            format!("Based on the content: {}", source_quotes.first().unwrap())
-        } else {
-            "I couldn't find a direct answer in the page content.".to_string()
-        };
+        // Generate answer (simulated extractive answer over the retrieved chunks)
+        let grounded = generate_grounded_answer(question, &retrieved);
         
-        let result = QuestionAnswer {
-            id: Uuid::new_v4().to_string(),
-            question: question.to_string(),
-            answer,
-            confidence: if source_quotes.is_empty() { 0.3 } else { 0.85 },
-            source_quotes,
-            page_url: url.to_string(),
-            model_used: settings.default_model,
-            created_at: Utc::now().timestamp(),
+        let result = match grounded {
+            Some((answer, cited)) => {
+                let coverage = question_coverage(question, &cited.text);
+                QuestionAnswer {
+                    id: Uuid::new_v4().to_string(),
+                    question: question.to_string(),
+                    answer: answer.clone(),
+                    confidence: (0.5 + coverage * 0.45).min(0.95),
+                    source_quotes: vec![answer],
+                    citations: retrieved
+                        .iter()
+                        .filter(|c| c.chunk_index == cited.chunk_index)
+                        .chain(retrieved.iter().filter(|c| c.chunk_index != cited.chunk_index))
+                        .cloned()
+                        .collect(),
+                    supported: true,
+                    page_url: url.to_string(),
+                    model_used: settings.default_model,
+                    created_at: Utc::now().timestamp(),
+                }
+            }
+            None => QuestionAnswer {
+                id: Uuid::new_v4().to_string(),
+                question: question.to_string(),
+                answer: "The page does not contain information that answers this question.".to_string(),
+                confidence: 0.0,
+                source_quotes: Vec::new(),
+                citations: Vec::new(),
+                supported: false,
+                page_url: url.to_string(),
+                model_used: settings.default_model,
+                created_at: Utc::now().timestamp(),
+            },
         };
         
         self.record_task(AITaskType::QuestionAnswer, context.len() as u32);
@@ -747,23 +894,6 @@ impl AIBrowserAssistant {
         Ok(result)
     }
     
-    fn find_relevant_quotes(&self, question: &str, context: &str) -> Vec<String> {
-        let question_lower = question.to_lowercase();
-        let question_words: Vec<&str> = question_lower
-            .split_whitespace()
-            .filter(|w| w.len() > 3)
-            .collect();
-        
-        context.split(|c| c == '.' || c == '!' || c == '?')
-            .filter(|sentence| {
-                let lower = sentence.to_lowercase();
-                question_words.iter().any(|w| lower.contains(w))
-            })
-            .take(3)
-            .map(|s| s.trim().to_string())
-            .collect()
-    }
-    
     // ==================== Content Analysis ====================
     
     pub fn analyze_content(&self, url: &str, content: &str) -> ContentAnalysis {
@@ -975,6 +1105,8 @@ impl AIBrowserAssistant {
         
         let mut translations = self.translation_cache.write().unwrap();
         translations.clear();
+
+        self.page_translations.write().unwrap().clear();
     }
     
     pub fn get_cache_size(&self) -> (usize, usize) {
@@ -990,6 +1122,146 @@ impl Default for AIBrowserAssistant {
     }
 }
 
+// ==================== Grounded Retrieval ====================
+
+const QA_TOP_K: usize = 3;
+const QA_MAX_CHUNK_WORDS: usize = 120;
+/// Share of the question's terms that must appear in a chunk for it to count as support
+const QA_MIN_COVERAGE: f32 = 0.5;
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "did", "do", "does", "for", "from", "how",
+    "in", "is", "it", "of", "on", "or", "that", "the", "this", "to", "was", "were", "what",
+    "when", "where", "which", "who", "why", "with",
+];
+
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+        .map(|w| if w.len() > 4 && w.ends_with('s') { w[..w.len() - 1].to_string() } else { w })
+        .collect()
+}
+
+/// Splits page text into paragraph chunks (long paragraphs are split on sentence
+/// boundaries) with character offsets into the original text.
+fn chunk_page(context: &str) -> Vec<SourceCitation> {
+    let chars: Vec<char> = context.chars().collect();
+    let mut chunks = Vec::new();
+    let mut push_chunk = |start: usize, end: usize| {
+        let raw: String = chars[start..end].iter().collect();
+        let leading = raw.chars().take_while(|c| c.is_whitespace()).count();
+        let text = raw.trim();
+        if !text.is_empty() {
+            let start_offset = start + leading;
+            chunks.push(SourceCitation {
+                chunk_index: chunks.len(),
+                start_offset,
+                end_offset: start_offset + text.chars().count(),
+                text: text.to_string(),
+                relevance_score: 0.0,
+            });
+        }
+    };
+
+    let mut window_start = 0;
+    let mut words_in_window = 0;
+    let mut i = 0;
+    while i <= chars.len() {
+        let at_end = i == chars.len();
+        if at_end || chars[i] == '\n' {
+            push_chunk(window_start, i);
+            window_start = i + 1;
+            words_in_window = 0;
+        } else {
+            if chars[i].is_whitespace() && i > 0 && !chars[i - 1].is_whitespace() {
+                words_in_window += 1;
+            }
+            let sentence_end = matches!(chars[i], '.' | '!' | '?')
+                && !matches!(chars.get(i + 1), Some(c) if !c.is_whitespace());
+            if sentence_end && words_in_window >= QA_MAX_CHUNK_WORDS {
+                push_chunk(window_start, i + 1);
+                window_start = i + 1;
+                words_in_window = 0;
+            }
+        }
+        i += 1;
+    }
+    chunks
+}
+
+/// Ranks chunks against the question with BM25 and returns the top `k` with a positive score
+fn retrieve_chunks(question: &str, chunks: &[SourceCitation], k: usize) -> Vec<SourceCitation> {
+    const K1: f32 = 1.2;
+    const B: f32 = 0.75;
+
+    let mut terms = tokenize(question);
+    terms.sort();
+    terms.dedup();
+    if terms.is_empty() || chunks.is_empty() {
+        return Vec::new();
+    }
+
+    let docs: Vec<Vec<String>> = chunks.iter().map(|c| tokenize(&c.text)).collect();
+    let avg_len = docs.iter().map(|d| d.len()).sum::<usize>() as f32 / docs.len() as f32;
+    let n = docs.len() as f32;
+
+    let mut scored: Vec<SourceCitation> = chunks
+        .iter()
+        .zip(&docs)
+        .map(|(chunk, doc)| {
+            let score: f32 = terms
+                .iter()
+                .map(|term| {
+                    let df = docs.iter().filter(|d| d.contains(term)).count() as f32;
+                    let tf = doc.iter().filter(|w| *w == term).count() as f32;
+                    if tf == 0.0 {
+                        return 0.0;
+                    }
+                    let idf = ((n - df + 0.5) / (df + 0.5) + 1.0).ln();
+                    idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * doc.len() as f32 / avg_len.max(1.0)))
+                })
+                .sum();
+            SourceCitation { relevance_score: score, ..chunk.clone() }
+        })
+        .filter(|c| c.relevance_score > 0.0)
+        .collect();
+
+    scored.sort_by(|a, b| b.relevance_score.total_cmp(&a.relevance_score));
+    scored.truncate(k);
+    scored
+}
+
+fn question_coverage(question: &str, text: &str) -> f32 {
+    let mut terms = tokenize(question);
+    terms.sort();
+    terms.dedup();
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let words = tokenize(text);
+    terms.iter().filter(|t| words.contains(t)).count() as f32 / terms.len() as f32
+}
+
+/// Picks the best-supported sentence from the retrieved chunks. Returns `None` when no
+/// chunk covers enough of the question to ground an answer.
+fn generate_grounded_answer(question: &str, retrieved: &[SourceCitation]) -> Option<(String, SourceCitation)> {
+    retrieved
+        .iter()
+        .flat_map(|chunk| {
+            chunk.text
+                .split_inclusive(['.', '!', '?'])
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(move |sentence| (sentence, chunk))
+        })
+        .map(|(sentence, chunk)| (question_coverage(question, sentence), sentence, chunk))
+        .filter(|(coverage, _, _)| *coverage >= QA_MIN_COVERAGE)
+        .max_by(|a, b| a.0.total_cmp(&b.0).then(a.2.relevance_score.total_cmp(&b.2.relevance_score)))
+        .map(|(_, sentence, chunk)| (sentence.to_string(), chunk.clone()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1019,6 +1291,48 @@ mod tests {
         assert!(result.unwrap().translated_text.contains("Spanish"));
     }
     
+    #[test]
+    fn test_translate_page_is_cached_and_restorable() {
+        let assistant = AIBrowserAssistant::new();
+        let html = r#"<p>Read <a href="/terms">the terms</a> first.</p>"#;
+
+        let page = assistant.translate_page("https://example.com", html, Language::French).unwrap();
+        assert!(!page.cached);
+        assert_eq!(
+            page.translated_html,
+            r#"<p>[fr] Read <a href="/terms">the terms</a> first.</p>"#
+        );
+
+        let again = assistant.translate_page("https://example.com", html, Language::French).unwrap();
+        assert!(again.cached);
+        assert_eq!(again.id, page.id);
+
+        let restore = assistant.show_original_page("https://example.com").unwrap();
+        assert_eq!(restore.original_html, html);
+        assert_eq!(restore.blocks[0].original_html, r#"Read <a href="/terms">the terms</a> first."#);
+        assert!(assistant.show_original_page("https://other.example").is_err());
+    }
+    
+    #[test]
+    fn test_answer_cites_source_chunk() {
+        let page = "The Eiffel Tower is a wrought-iron lattice tower in Paris.\n\n\
+            Construction began in 1887 and the Eiffel Tower was completed in 1889 for the World's Fair.\n\n\
+            Today the tower receives millions of visitors every year.";
+        let assistant = AIBrowserAssistant::new();
+
+        let answer = assistant.answer_question("When was the Eiffel Tower completed?", page, "https://example.com").unwrap();
+        assert!(answer.supported);
+        assert!(answer.answer.contains("1889"));
+        let cited = &answer.citations[0];
+        assert!(cited.text.contains("1889"));
+        let highlighted: String = page.chars().skip(cited.start_offset).take(cited.end_offset - cited.start_offset).collect();
+        assert_eq!(highlighted, cited.text);
+
+        let unsupported = assistant.answer_question("What is the population of Mars?", page, "https://example.com").unwrap();
+        assert!(!unsupported.supported);
+        assert!(unsupported.citations.is_empty());
+    }
+    
     #[test]
     fn test_sentiment_analysis() {
         let assistant = AIBrowserAssistant::new();
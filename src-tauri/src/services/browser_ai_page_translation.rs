@@ -0,0 +1,640 @@
+// CUBE Nexum - In-place Page Translation
+// Splits page HTML into translatable runs and puts translations back in place:
+// - A run is the inline content of a block (text, links, bold, ...) as one string,
+//   with inline elements replaced by placeholder tokens: "Read {1}the docs{/1}"
+// - Void and untranslatable inline elements become opaque tokens: "{2/}"
+// - Code, scripts, URLs and `translate="no"` content are never sent
+// - Translated runs are rebuilt around the original elements, so attributes
+//   (href, class, style) survive; a run whose tokens come back mangled keeps
+//   its original text
+
+use std::collections::{HashMap, HashSet};
+
+use ego_tree::{NodeId, NodeRef};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use scraper::{ElementRef, Html, Node};
+use serde::{Deserialize, Serialize};
+
+static TOKEN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{(/?)(\d+)(/?)\}").expect("valid token regex"));
+
+/// Rough characters per model token, used to size batches
+pub const CHARS_PER_TOKEN: usize = 4;
+
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "button", "cite", "data", "del", "dfn", "em", "font", "i",
+    "ins", "label", "mark", "q", "s", "small", "span", "strong", "sub", "sup", "time", "u",
+];
+
+/// Inline elements that stay in the run as a single opaque token
+const OPAQUE_INLINE_ELEMENTS: &[&str] = &[
+    "br", "code", "img", "input", "kbd", "math", "samp", "select", "svg", "var", "wbr",
+];
+
+/// Elements whose content is never translated
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "code", "iframe", "kbd", "math", "noscript", "object", "pre", "samp", "script", "select",
+    "style", "svg", "template", "textarea", "var",
+];
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// A block whose inner HTML changed, addressed by a CSS path from the page root
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TranslatedBlock {
+    pub selector: String,
+    pub original_html: String,
+    pub translated_html: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderedPage {
+    pub html: String,
+    pub blocks: Vec<TranslatedBlock>,
+    /// Runs left untranslated because their placeholders did not round-trip
+    pub rejected: usize,
+}
+
+enum Placeholder {
+    /// Paired `{n}...{/n}`, rebuilt from the original element
+    Element(NodeId),
+    /// `{n/}`, replaced by the original markup verbatim
+    Opaque(String),
+}
+
+/// Consecutive inline children of one parent, translated as a single string
+struct Run {
+    parent: NodeId,
+    children: Vec<NodeId>,
+    placeholders: Vec<Placeholder>,
+    source: String,
+}
+
+pub struct TranslatablePage {
+    html: Html,
+    is_fragment: bool,
+    runs: Vec<Run>,
+}
+
+impl TranslatablePage {
+    /// Parse a full document or a fragment such as `body.innerHTML`
+    pub fn parse(content: &str) -> Self {
+        let head = content[..content.len().min(1024)].to_lowercase();
+        let is_fragment = !(head.contains("<html") || head.contains("<!doctype"));
+        let html = if is_fragment {
+            Html::parse_fragment(content)
+        } else {
+            Html::parse_document(content)
+        };
+
+        let mut runs = Vec::new();
+        collect_runs(html.root_element().id(), &html, &mut runs);
+        Self {
+            html,
+            is_fragment,
+            runs,
+        }
+    }
+
+    /// Placeholder strings to translate, one per run
+    pub fn segments(&self) -> Vec<String> {
+        self.runs.iter().map(|run| run.source.clone()).collect()
+    }
+
+    /// Rebuild the page; `translations[i]` replaces segment `i`, `None` keeps it
+    pub fn render(&self, translations: &[Option<String>]) -> RenderedPage {
+        let mut replacements: HashMap<NodeId, (usize, String)> = HashMap::new();
+        let mut rejected = 0;
+        for (run, translation) in self.runs.iter().zip(translations) {
+            let Some(translation) = translation else {
+                continue;
+            };
+            match self.render_run(run, translation) {
+                Some(rendered) => {
+                    replacements.insert(run.children[0], (run.children.len(), rendered));
+                }
+                None => rejected += 1,
+            }
+        }
+
+        // Ancestors of replaced runs have to be written out node by node
+        let mut touched = HashSet::new();
+        let mut parents = Vec::new();
+        for run in &self.runs {
+            if !replacements.contains_key(&run.children[0]) || parents.contains(&run.parent) {
+                continue;
+            }
+            parents.push(run.parent);
+            let mut node = self.html.tree.get(run.parent);
+            while let Some(current) = node {
+                if !touched.insert(current.id()) {
+                    break;
+                }
+                node = current.parent();
+            }
+        }
+
+        let writer = Writer {
+            replacements: &replacements,
+            touched: &touched,
+        };
+        let blocks = parents
+            .iter()
+            .filter_map(|id| self.html.tree.get(*id))
+            .map(|parent| {
+                let mut translated_html = String::new();
+                writer.write_children(parent, &mut translated_html);
+                TranslatedBlock {
+                    selector: self.selector(parent),
+                    original_html: ElementRef::wrap(parent)
+                        .map(|element| element.inner_html())
+                        .unwrap_or_default(),
+                    translated_html,
+                }
+            })
+            .collect();
+
+        let mut html = String::new();
+        // A fragment's content sits inside the parser's synthetic <html>
+        let root = if self.is_fragment {
+            self.html.tree.get(self.html.root_element().id())
+        } else {
+            Some(self.html.tree.root())
+        };
+        if let Some(root) = root {
+            writer.write_children(root, &mut html);
+        }
+
+        RenderedPage {
+            html,
+            blocks,
+            rejected,
+        }
+    }
+
+    fn render_run(&self, run: &Run, translation: &str) -> Option<String> {
+        let paired: Vec<bool> = run
+            .placeholders
+            .iter()
+            .map(|placeholder| matches!(placeholder, Placeholder::Element(_)))
+            .collect();
+        let pieces = parse_tokens(translation, &paired)?;
+        let mut out = String::new();
+        self.render_pieces(&pieces, run, &mut out);
+        Some(out)
+    }
+
+    fn render_pieces(&self, pieces: &[Piece], run: &Run, out: &mut String) {
+        for piece in pieces {
+            match piece {
+                Piece::Text(text) => out.push_str(&escape_text(text)),
+                Piece::Opaque(n) => {
+                    if let Placeholder::Opaque(html) = &run.placeholders[*n - 1] {
+                        out.push_str(html);
+                    }
+                }
+                Piece::Element(n, children) => {
+                    if let Placeholder::Element(id) = &run.placeholders[*n - 1] {
+                        let element = self.html.tree.get(*id).and_then(ElementRef::wrap);
+                        if let Some(element) = element {
+                            push_start_tag(element, out);
+                            self.render_pieces(children, run, out);
+                            push_end_tag(element, out);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// `html > body:nth-child(2) > p:nth-child(3)`, or `:scope > ...` for fragments
+    fn selector(&self, node: NodeRef<Node>) -> String {
+        let root = self.html.root_element().id();
+        let mut parts = Vec::new();
+        let mut current = Some(node);
+        while let Some(node) = current {
+            let Some(element) = ElementRef::wrap(node) else {
+                break;
+            };
+            if node.id() == root {
+                parts.push(if self.is_fragment {
+                    ":scope".to_string()
+                } else {
+                    "html".to_string()
+                });
+                break;
+            }
+            let position = node
+                .prev_siblings()
+                .filter(|sibling| sibling.value().is_element())
+                .count()
+                + 1;
+            parts.push(format!(
+                "{}:nth-child({})",
+                element.value().name(),
+                position
+            ));
+            current = node.parent();
+        }
+        parts.reverse();
+        parts.join(" > ")
+    }
+}
+
+/// Translate `segments` in batches of at most `max_chars` characters each.
+/// Returns one entry per segment (`None` when the translator returned too few)
+/// and the number of batches sent.
+pub fn translate_in_batches<F>(
+    segments: &[String],
+    max_chars: usize,
+    mut translate: F,
+) -> Result<(Vec<Option<String>>, usize), String>
+where
+    F: FnMut(&[String]) -> Result<Vec<String>, String>,
+{
+    let mut results = Vec::with_capacity(segments.len());
+    let mut batches = 0;
+    let mut start = 0;
+    while start < segments.len() {
+        // A segment larger than the budget still goes out, alone
+        let mut end = start + 1;
+        let mut size = segments[start].len();
+        while end < segments.len() && size + segments[end].len() <= max_chars {
+            size += segments[end].len();
+            end += 1;
+        }
+
+        let translated = translate(&segments[start..end])?;
+        batches += 1;
+        let mut translated = translated.into_iter();
+        results.extend((start..end).map(|_| translated.next()));
+        start = end;
+    }
+    Ok((results, batches))
+}
+
+// ==================== Extraction ====================
+
+fn element_name<'a>(node: &NodeRef<'a, Node>) -> Option<&'a str> {
+    node.value().as_element().map(|element| element.name())
+}
+
+fn is_skipped(node: &NodeRef<Node>) -> bool {
+    let Some(element) = node.value().as_element() else {
+        return false;
+    };
+    SKIPPED_ELEMENTS.contains(&element.name())
+        || element
+            .attr("translate")
+            .is_some_and(|value| value.eq_ignore_ascii_case("no"))
+        || element.classes().any(|class| class == "notranslate")
+}
+
+fn has_block_descendant(node: &NodeRef<Node>) -> bool {
+    node.descendants().skip(1).any(|descendant| {
+        element_name(&descendant).is_some_and(|name| {
+            !INLINE_ELEMENTS.contains(&name) && !OPAQUE_INLINE_ELEMENTS.contains(&name)
+        })
+    })
+}
+
+/// Whether a child belongs in its parent's inline run
+fn is_inline(node: &NodeRef<Node>) -> bool {
+    match node.value() {
+        Node::Text(_) | Node::Comment(_) => true,
+        Node::Element(element) => {
+            let name = element.name();
+            OPAQUE_INLINE_ELEMENTS.contains(&name)
+                || (INLINE_ELEMENTS.contains(&name)
+                    && (is_skipped(node) || !has_block_descendant(node)))
+        }
+        _ => false,
+    }
+}
+
+fn collect_runs(id: NodeId, html: &Html, runs: &mut Vec<Run>) {
+    let Some(node) = html.tree.get(id) else {
+        return;
+    };
+    let mut pending: Vec<NodeId> = Vec::new();
+    for child in node.children() {
+        if is_inline(&child) {
+            pending.push(child.id());
+            continue;
+        }
+        flush_run(id, &mut pending, html, runs);
+        if child.value().is_element() && !is_skipped(&child) {
+            collect_runs(child.id(), html, runs);
+        }
+    }
+    flush_run(id, &mut pending, html, runs);
+}
+
+fn flush_run(parent: NodeId, pending: &mut Vec<NodeId>, html: &Html, runs: &mut Vec<Run>) {
+    let children = std::mem::take(pending);
+    if children.is_empty() {
+        return;
+    }
+
+    let mut run = Run {
+        parent,
+        children,
+        placeholders: Vec::new(),
+        source: String::new(),
+    };
+    let mut translatable = false;
+    for id in run.children.clone() {
+        if let Some(child) = html.tree.get(id) {
+            encode(child, &mut run, &mut translatable);
+        }
+    }
+    if translatable {
+        runs.push(run);
+    }
+}
+
+fn encode(node: NodeRef<Node>, run: &mut Run, translatable: &mut bool) {
+    match node.value() {
+        Node::Text(text) => encode_text(text, run, translatable),
+        Node::Comment(comment) => push_opaque(run, format!("<!--{}-->", &**comment)),
+        Node::Element(element) => {
+            let name = element.name();
+            if is_skipped(&node) || OPAQUE_INLINE_ELEMENTS.contains(&name) {
+                if let Some(element) = ElementRef::wrap(node) {
+                    push_opaque(run, element.html());
+                }
+                return;
+            }
+            run.placeholders.push(Placeholder::Element(node.id()));
+            let n = run.placeholders.len();
+            run.source.push_str(&format!("{{{}}}", n));
+            for child in node.children() {
+                encode(child, run, translatable);
+            }
+            run.source.push_str(&format!("{{/{}}}", n));
+        }
+        _ => {}
+    }
+}
+
+/// URLs and literal braces ride along as opaque tokens so they come back unchanged
+fn encode_text(text: &str, run: &mut Run, translatable: &mut bool) {
+    let mut plain = String::new();
+    for (i, word) in text.split(' ').enumerate() {
+        if i > 0 {
+            plain.push(' ');
+        }
+        if looks_like_url(word) {
+            run.source.push_str(&plain);
+            plain.clear();
+            push_opaque(run, escape_text(word));
+            continue;
+        }
+        for c in word.chars() {
+            if c == '{' || c == '}' {
+                run.source.push_str(&plain);
+                plain.clear();
+                push_opaque(run, c.to_string());
+            } else {
+                *translatable |= c.is_alphabetic();
+                plain.push(c);
+            }
+        }
+    }
+    run.source.push_str(&plain);
+}
+
+fn looks_like_url(word: &str) -> bool {
+    let word =
+        word.trim_matches(|c: char| matches!(c, '(' | ')' | ',' | '.' | ';' | ':' | '"' | '\''));
+    word.starts_with("http://")
+        || word.starts_with("https://")
+        || word.starts_with("www.")
+        || (word.contains('@') && word.contains('.') && !word.contains(' '))
+}
+
+fn push_opaque(run: &mut Run, html: String) {
+    run.placeholders.push(Placeholder::Opaque(html));
+    run.source
+        .push_str(&format!("{{{}/}}", run.placeholders.len()));
+}
+
+// ==================== Reassembly ====================
+
+enum Piece {
+    Text(String),
+    Element(usize, Vec<Piece>),
+    Opaque(usize),
+}
+
+/// Parse a translated run back into nested pieces. `None` unless every
+/// placeholder appears exactly once, in its original form (`paired[n - 1]`
+/// for `{n}...{/n}`, otherwise `{n/}`), and pairs nest properly.
+fn parse_tokens(translation: &str, paired: &[bool]) -> Option<Vec<Piece>> {
+    let mut seen = vec![false; paired.len()];
+    // Stack of (open token, pieces collected inside it)
+    let mut stack: Vec<(usize, Vec<Piece>)> = vec![(0, Vec::new())];
+    let mut last = 0;
+
+    for captures in TOKEN.captures_iter(translation) {
+        let whole = captures.get(0)?;
+        let text = &translation[last..whole.start()];
+        if !text.is_empty() {
+            stack.last_mut()?.1.push(Piece::Text(text.to_string()));
+        }
+        last = whole.end();
+
+        let n: usize = captures[2].parse().ok()?;
+        if n == 0 || n > paired.len() {
+            return None;
+        }
+        let closing = !captures[1].is_empty();
+        let opaque = !captures[3].is_empty();
+        if !closing && opaque == paired[n - 1] {
+            return None;
+        }
+        match (closing, opaque) {
+            (false, true) => {
+                if std::mem::replace(&mut seen[n - 1], true) {
+                    return None;
+                }
+                stack.last_mut()?.1.push(Piece::Opaque(n));
+            }
+            (false, false) => {
+                if std::mem::replace(&mut seen[n - 1], true) {
+                    return None;
+                }
+                stack.push((n, Vec::new()));
+            }
+            (true, false) => {
+                let (open, children) = stack.pop()?;
+                if open != n || stack.is_empty() {
+                    return None;
+                }
+                stack.last_mut()?.1.push(Piece::Element(n, children));
+            }
+            (true, true) => return None,
+        }
+    }
+
+    let text = &translation[last..];
+    if !text.is_empty() {
+        stack.last_mut()?.1.push(Piece::Text(text.to_string()));
+    }
+    if stack.len() != 1 || seen.iter().any(|seen| !seen) {
+        return None;
+    }
+    stack.pop().map(|(_, pieces)| pieces)
+}
+
+struct Writer<'a> {
+    /// First child of a run → (children in the run, translated markup)
+    replacements: &'a HashMap<NodeId, (usize, String)>,
+    /// Nodes with a replaced run somewhere below them
+    touched: &'a HashSet<NodeId>,
+}
+
+impl Writer<'_> {
+    fn write_children(&self, node: NodeRef<Node>, out: &mut String) {
+        let mut children = node.children();
+        while let Some(child) = children.next() {
+            if let Some((len, html)) = self.replacements.get(&child.id()) {
+                out.push_str(html);
+                for _ in 1..*len {
+                    children.next();
+                }
+                continue;
+            }
+            self.write_node(child, out);
+        }
+    }
+
+    fn write_node(&self, node: NodeRef<Node>, out: &mut String) {
+        match node.value() {
+            Node::Doctype(doctype) => out.push_str(&format!("<!DOCTYPE {}>", doctype.name())),
+            Node::Comment(comment) => out.push_str(&format!("<!--{}-->", &**comment)),
+            Node::Text(text) => {
+                // Raw-text parents were never split into runs, so this is regular text
+                out.push_str(&escape_text(text));
+            }
+            Node::Element(_) => {
+                let Some(element) = ElementRef::wrap(node) else {
+                    return;
+                };
+                if !self.touched.contains(&node.id()) {
+                    out.push_str(&element.html());
+                    return;
+                }
+                push_start_tag(element, out);
+                self.write_children(node, out);
+                push_end_tag(element, out);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn push_start_tag(element: ElementRef, out: &mut String) {
+    out.push('<');
+    out.push_str(element.value().name());
+    for (name, value) in element.value().attrs() {
+        out.push_str(&format!(" {}=\"{}\"", name, escape_attr(value)));
+    }
+    out.push('>');
+}
+
+fn push_end_tag(element: ElementRef, out: &mut String) {
+    let name = element.value().name();
+    if !VOID_ELEMENTS.contains(&name) {
+        out.push_str(&format!("</{}>", name));
+    }
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\u{a0}', "&nbsp;")
+}
+
+fn escape_attr(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Word-for-word English→Spanish that moves tokens with their words
+    fn spanish(segment: &str) -> String {
+        segment
+            .replace("Hello", "Hola")
+            .replace("bold world", "mundo audaz")
+            .replace(" and read ", " y lee ")
+            .replace("the guide", "la guía")
+            .replace("Run", "Ejecuta")
+            .replace("Contact", "Contacta")
+    }
+
+    #[test]
+    fn test_translation_preserves_inline_formatting() {
+        let page = TranslatablePage::parse(
+            r#"<div class="intro"><p>Hello <b>bold world</b> and read <a href="/guide?a=1&amp;b=2">the guide</a>.</p><pre>Hello code</pre><p>Run <code>cargo test</code> at https://example.com/docs</p><p translate="no">Hello brand</p></div><ul><li>Contact<br>us</li></ul>"#,
+        );
+
+        let segments = page.segments();
+        assert_eq!(
+            segments,
+            [
+                "Hello {1}bold world{/1} and read {2}the guide{/2}.",
+                "Run {1/} at {2/}",
+                "Contact{1/}us",
+            ]
+        );
+
+        // Budget fits two segments per batch
+        let (translated, batches) = translate_in_batches(&segments, 60, |batch| {
+            Ok(batch.iter().map(|segment| spanish(segment)).collect())
+        })
+        .unwrap();
+        assert_eq!(batches, 2);
+
+        let rendered = page.render(&translated);
+        assert_eq!(rendered.rejected, 0);
+        assert_eq!(
+            rendered.html,
+            r#"<div class="intro"><p>Hola <b>mundo audaz</b> y lee <a href="/guide?a=1&amp;b=2">la guía</a>.</p><pre>Hello code</pre><p>Ejecuta <code>cargo test</code> at https://example.com/docs</p><p translate="no">Hello brand</p></div><ul><li>Contacta<br>us</li></ul>"#
+        );
+        assert_eq!(rendered.blocks.len(), 3);
+        assert_eq!(
+            rendered.blocks[0].selector,
+            ":scope > div:nth-child(1) > p:nth-child(1)"
+        );
+        assert_eq!(
+            rendered.blocks[0].original_html,
+            r#"Hello <b>bold world</b> and read <a href="/guide?a=1&amp;b=2">the guide</a>."#
+        );
+        assert_eq!(
+            rendered.blocks[2].selector,
+            ":scope > ul:nth-child(2) > li:nth-child(1)"
+        );
+
+        // Reordered tokens are fine; a dropped one keeps the original run
+        let reordered = page.render(&[
+            Some("{2}La guía{/2} y {1}el mundo{/1}, hola.".to_string()),
+            Some("Ejecuta {1/}".to_string()),
+            None,
+        ]);
+        assert_eq!(reordered.rejected, 1);
+        assert!(reordered.html.starts_with(
+            r#"<div class="intro"><p><a href="/guide?a=1&amp;b=2">La guía</a> y <b>el mundo</b>, hola.</p>"#
+        ));
+        assert!(reordered
+            .html
+            .contains("<p>Run <code>cargo test</code> at https://example.com/docs</p>"));
+    }
+}
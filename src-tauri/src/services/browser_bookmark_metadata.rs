@@ -0,0 +1,420 @@
+// CUBE Nexum - Bookmark Metadata Fetching
+// Fills in page titles and favicons for new bookmarks in the background.
+// Favicons are cached on disk per registrable domain (eTLD+1) and concurrent
+// fetches for the same domain share one request.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::future::{BoxFuture, FutureExt, Shared};
+use scraper::{Html, Selector};
+use url::{Host, Url};
+
+use super::browser_bookmarks::{Bookmark, BookmarkType, BrowserBookmarksService, FAVICON_FETCHED_AT_KEY};
+
+/// Favicons older than this are refreshed by the batch refresh
+const FAVICON_MAX_AGE_DAYS: i64 = 30;
+const FAVICON_EXTENSIONS: &[&str] = &["ico", "png", "svg", "jpg", "gif", "webp"];
+
+/// Public suffixes spanning two labels; everything else is treated as a single-label TLD
+const MULTI_LABEL_SUFFIXES: &[&str] = &[
+    "co.uk", "org.uk", "ac.uk", "gov.uk", "me.uk", "com.au", "net.au", "org.au", "co.nz",
+    "co.jp", "ne.jp", "or.jp", "co.in", "co.za", "co.kr", "com.br", "com.mx", "com.ar",
+    "com.cn", "com.tr", "com.sg", "com.hk", "com.tw", "github.io", "gitlab.io",
+    "herokuapp.com", "blogspot.com", "appspot.com", "vercel.app", "netlify.app", "pages.dev",
+];
+
+/// Network access used for metadata, abstracted so tests can count requests
+#[async_trait::async_trait]
+pub trait PageFetcher: Send + Sync {
+    async fn fetch_text(&self, url: &str) -> Result<String, String>;
+    async fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>, String>;
+}
+
+pub struct HttpPageFetcher {
+    client: reqwest::Client,
+}
+
+impl HttpPageFetcher {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .user_agent("Mozilla/5.0 (compatible; CUBE Nexum Bookmarks)")
+            .build()
+            .unwrap_or_default();
+        Self { client }
+    }
+}
+
+impl Default for HttpPageFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl PageFetcher for HttpPageFetcher {
+    async fn fetch_text(&self, url: &str) -> Result<String, String> {
+        let response = self.client.get(url).send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+        response.text().await.map_err(|e| e.to_string())
+    }
+
+    async fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>, String> {
+        let response = self.client.get(url).send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP {}", response.status()));
+        }
+        // Servers often answer a missing /favicon.ico with an HTML page
+        let is_html = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("text/html"));
+        if is_html {
+            return Err("Not an image".to_string());
+        }
+        Ok(response.bytes().await.map_err(|e| e.to_string())?.to_vec())
+    }
+}
+
+type FaviconFuture = Shared<BoxFuture<'static, Option<String>>>;
+
+pub struct BookmarkMetadataService {
+    fetcher: Arc<dyn PageFetcher>,
+    cache_dir: PathBuf,
+    in_flight: Mutex<HashMap<String, FaviconFuture>>,
+}
+
+impl BookmarkMetadataService {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self::with_fetcher(Arc::new(HttpPageFetcher::new()), cache_dir)
+    }
+
+    pub fn with_fetcher(fetcher: Arc<dyn PageFetcher>, cache_dir: PathBuf) -> Self {
+        Self {
+            fetcher,
+            cache_dir,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Fetch the title (when the bookmark has none) and favicon for a bookmark.
+    /// `refresh_favicon` bypasses the on-disk cache.
+    pub async fn populate(
+        &self,
+        bookmarks: &BrowserBookmarksService,
+        id: &str,
+        refresh_favicon: bool,
+    ) -> Result<Bookmark, String> {
+        let bookmark = bookmarks.get_bookmark(id).ok_or("Bookmark not found")?;
+        let Some(raw_url) = bookmark.url.clone() else {
+            return Ok(bookmark);
+        };
+        let Ok(page_url) = Url::parse(&raw_url) else {
+            return Ok(bookmark);
+        };
+        let excluded = bookmarks.get_settings().metadata_excluded_domains;
+        if !should_fetch(&page_url, &excluded) {
+            return Ok(bookmark);
+        }
+        let Some(host) = page_url.host_str() else {
+            return Ok(bookmark);
+        };
+        let domain = registrable_domain(host);
+
+        let needs_title = needs_title(&bookmark);
+        let cached = if refresh_favicon { None } else { self.cached_favicon(&domain) };
+        let html = if needs_title || cached.is_none() {
+            self.fetcher.fetch_text(page_url.as_str()).await.ok()
+        } else {
+            None
+        };
+
+        let title = if needs_title { html.as_deref().and_then(extract_title) } else { None };
+        let favicon = match cached {
+            Some(path) => Some(path),
+            None => self.resolve_favicon(&domain, &page_url, html).await,
+        };
+
+        bookmarks.apply_page_metadata(id, title, favicon)
+    }
+
+    /// Refresh every URL bookmark whose favicon is missing or stale; returns how many were updated
+    pub async fn refresh_stale(&self, bookmarks: &BrowserBookmarksService) -> u32 {
+        let now = Utc::now();
+        let stale: Vec<String> = bookmarks
+            .get_all_bookmarks()
+            .into_iter()
+            .filter(|b| b.bookmark_type == BookmarkType::Url && favicon_is_stale(b, now))
+            .map(|b| b.id)
+            .collect();
+
+        let results = futures::future::join_all(
+            stale.iter().map(|id| self.populate(bookmarks, id, true)),
+        )
+        .await;
+        results
+            .into_iter()
+            .filter(|r| r.as_ref().is_ok_and(|b| b.favicon.is_some()))
+            .count() as u32
+    }
+
+    /// Fetch a domain's favicon, joining a fetch already in progress for that domain
+    async fn resolve_favicon(&self, domain: &str, page_url: &Url, html: Option<String>) -> Option<String> {
+        let future = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            in_flight
+                .entry(domain.to_string())
+                .or_insert_with(|| {
+                    let mut candidates = html.as_deref().map(|h| icon_links(h, page_url)).unwrap_or_default();
+                    if let Ok(fallback) = page_url.join("/favicon.ico") {
+                        candidates.push(fallback);
+                    }
+                    download_favicon(self.fetcher.clone(), self.cache_dir.clone(), domain.to_string(), candidates)
+                        .boxed()
+                        .shared()
+                })
+                .clone()
+        };
+
+        let favicon = future.await;
+        self.in_flight.lock().unwrap().remove(domain);
+        favicon
+    }
+
+    fn cached_favicon(&self, domain: &str) -> Option<String> {
+        FAVICON_EXTENSIONS
+            .iter()
+            .map(|ext| self.cache_dir.join(format!("{}.{}", domain, ext)))
+            .find(|path| path.exists())
+            .map(|path| path.to_string_lossy().to_string())
+    }
+}
+
+/// Try each candidate in order; `None` means the default favicon is shown
+async fn download_favicon(
+    fetcher: Arc<dyn PageFetcher>,
+    cache_dir: PathBuf,
+    domain: String,
+    candidates: Vec<Url>,
+) -> Option<String> {
+    for candidate in candidates {
+        let bytes = match fetcher.fetch_bytes(candidate.as_str()).await {
+            Ok(bytes) if !bytes.is_empty() => bytes,
+            _ => continue,
+        };
+        let ext = candidate
+            .path()
+            .rsplit('.')
+            .next()
+            .map(|e| e.to_lowercase())
+            .filter(|e| FAVICON_EXTENSIONS.contains(&e.as_str()))
+            .unwrap_or_else(|| "ico".to_string());
+        let path = cache_dir.join(format!("{}.{}", domain, ext));
+        if let Err(e) = tokio::fs::create_dir_all(&cache_dir).await {
+            log::warn!("Failed to create favicon cache: {}", e);
+            return None;
+        }
+        // Drop other formats cached earlier so lookups see the new icon
+        for other in FAVICON_EXTENSIONS.iter().filter(|other| **other != ext) {
+            let _ = tokio::fs::remove_file(cache_dir.join(format!("{}.{}", domain, other))).await;
+        }
+        if let Err(e) = tokio::fs::write(&path, bytes).await {
+            log::warn!("Failed to cache favicon for {}: {}", domain, e);
+            return None;
+        }
+        return Some(path.to_string_lossy().to_string());
+    }
+    None
+}
+
+/// A title still equal to the URL (or empty) was never really set
+fn needs_title(bookmark: &Bookmark) -> bool {
+    let title = bookmark.title.trim();
+    title.is_empty() || bookmark.url.as_deref() == Some(title)
+}
+
+fn favicon_is_stale(bookmark: &Bookmark, now: DateTime<Utc>) -> bool {
+    let fetched_at = bookmark
+        .metadata
+        .get(FAVICON_FETCHED_AT_KEY)
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc));
+    match (&bookmark.favicon, fetched_at) {
+        (Some(path), Some(at)) => {
+            !std::path::Path::new(path).exists() || now - at > chrono::Duration::days(FAVICON_MAX_AGE_DAYS)
+        }
+        _ => true,
+    }
+}
+
+/// Only public http(s) sites outside the excluded list are contacted
+pub fn should_fetch(url: &Url, excluded_domains: &[String]) -> bool {
+    if !matches!(url.scheme(), "http" | "https") {
+        return false;
+    }
+    let private = match url.host() {
+        Some(Host::Ipv4(ip)) => ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback() || ip.is_unspecified() || (ip.segments()[0] & 0xfe00) == 0xfc00,
+        Some(Host::Domain(host)) => {
+            let host = host.to_lowercase();
+            !host.contains('.')
+                || [".localhost", ".local", ".internal", ".lan", ".home.arpa"]
+                    .iter()
+                    .any(|suffix| host.ends_with(suffix))
+        }
+        None => true,
+    };
+    if private {
+        return false;
+    }
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    !excluded_domains.iter().any(|domain| {
+        let domain = domain.trim().trim_start_matches("*.").to_lowercase();
+        !domain.is_empty() && (host == domain || host.ends_with(&format!(".{}", domain)))
+    })
+}
+
+/// eTLD+1 for a host, e.g. `docs.example.co.uk` -> `example.co.uk`
+pub fn registrable_domain(host: &str) -> String {
+    let host = host.trim_end_matches('.').to_lowercase();
+    if host.parse::<std::net::IpAddr>().is_ok() || host.starts_with('[') {
+        return host;
+    }
+    let labels: Vec<&str> = host.split('.').collect();
+    if labels.len() <= 2 {
+        return host;
+    }
+    let last_two = labels[labels.len() - 2..].join(".");
+    let keep = if MULTI_LABEL_SUFFIXES.contains(&last_two.as_str()) { 3 } else { 2 };
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+fn extract_title(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("title").ok()?;
+    let title = document.select(&selector).next()?.text().collect::<String>();
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!title.is_empty()).then_some(title)
+}
+
+/// Declared icons in preference order: plain icons before apple-touch icons
+fn icon_links(html: &str, page_url: &Url) -> Vec<Url> {
+    let document = Html::parse_document(html);
+    let Ok(selector) = Selector::parse("link[rel][href]") else {
+        return Vec::new();
+    };
+    let mut icons: Vec<(bool, Url)> = document
+        .select(&selector)
+        .filter_map(|link| {
+            let rel = link.value().attr("rel")?.to_lowercase();
+            let tokens: Vec<&str> = rel.split_whitespace().collect();
+            let touch = tokens.iter().any(|t| t.starts_with("apple-touch-icon"));
+            if !touch && !tokens.contains(&"icon") {
+                return None;
+            }
+            let href = page_url.join(link.value().attr("href")?.trim()).ok()?;
+            Some((touch, href))
+        })
+        .collect();
+    icons.sort_by_key(|(touch, _)| *touch);
+    icons.into_iter().map(|(_, url)| url).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockFetcher {
+        pages: HashMap<String, String>,
+        icons: HashMap<String, Vec<u8>>,
+        page_fetches: AtomicUsize,
+        icon_fetches: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl PageFetcher for MockFetcher {
+        async fn fetch_text(&self, url: &str) -> Result<String, String> {
+            self.page_fetches.fetch_add(1, Ordering::SeqCst);
+            self.pages.get(url).cloned().ok_or_else(|| "HTTP 404".to_string())
+        }
+
+        async fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>, String> {
+            self.icon_fetches.fetch_add(1, Ordering::SeqCst);
+            // Keep the request open long enough for concurrent callers to pile up
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.icons.get(url).cloned().ok_or_else(|| "HTTP 404".to_string())
+        }
+    }
+
+    #[test]
+    fn test_registrable_domain_and_private_hosts() {
+        assert_eq!(registrable_domain("docs.example.com"), "example.com");
+        assert_eq!(registrable_domain("www.bbc.co.uk"), "bbc.co.uk");
+        assert_eq!(registrable_domain("127.0.0.1"), "127.0.0.1");
+
+        let excluded = vec!["bank.com".to_string()];
+        assert!(should_fetch(&Url::parse("https://example.com/a").unwrap(), &excluded));
+        assert!(!should_fetch(&Url::parse("https://login.bank.com/").unwrap(), &excluded));
+        assert!(!should_fetch(&Url::parse("http://192.168.1.10/admin").unwrap(), &excluded));
+        assert!(!should_fetch(&Url::parse("http://nas.local/").unwrap(), &excluded));
+        assert!(!should_fetch(&Url::parse("file:///tmp/page.html").unwrap(), &excluded));
+    }
+
+    #[tokio::test]
+    async fn test_quick_add_populates_title_and_coalesces_favicon_fetches() {
+        let page = |title: &str| {
+            format!(r#"<html><head><title> {} </title><link rel="icon" href="/static/icon.png"></head></html>"#, title)
+        };
+        let fetcher = Arc::new(MockFetcher {
+            pages: HashMap::from([
+                ("https://docs.example.com/guide".to_string(), page("Guide")),
+                ("https://www.example.com/blog".to_string(), page("Blog")),
+            ]),
+            icons: HashMap::from([
+                ("https://docs.example.com/static/icon.png".to_string(), vec![1, 2, 3]),
+                ("https://www.example.com/static/icon.png".to_string(), vec![1, 2, 3]),
+            ]),
+            page_fetches: AtomicUsize::new(0),
+            icon_fetches: AtomicUsize::new(0),
+        });
+        let cache_dir = std::env::temp_dir().join(format!("cube-favicons-{}", uuid::Uuid::new_v4()));
+        let metadata = BookmarkMetadataService::with_fetcher(fetcher.clone(), cache_dir.clone());
+        let bookmarks = BrowserBookmarksService::new();
+
+        // Quick-add without a title stores the URL as a placeholder
+        let guide_url = "https://docs.example.com/guide".to_string();
+        let blog_url = "https://www.example.com/blog".to_string();
+        let guide = bookmarks.create_bookmark(guide_url.clone(), guide_url, None).unwrap();
+        let blog = bookmarks.create_bookmark(blog_url.clone(), blog_url, None).unwrap();
+        let private = bookmarks.create_bookmark("Router".to_string(), "http://192.168.1.1/".to_string(), None).unwrap();
+
+        let (guide, blog) = futures::join!(
+            metadata.populate(&bookmarks, &guide.id, false),
+            metadata.populate(&bookmarks, &blog.id, false),
+        );
+        let (guide, blog) = (guide.unwrap(), blog.unwrap());
+        assert_eq!(guide.title, "Guide");
+        assert_eq!(blog.title, "Blog");
+        let icon = cache_dir.join("example.com.png").to_string_lossy().to_string();
+        assert_eq!(guide.favicon.as_deref(), Some(icon.as_str()));
+        assert_eq!(blog.favicon, guide.favicon);
+        assert!(guide.metadata.contains_key(FAVICON_FETCHED_AT_KEY));
+        // Both bookmarks share example.com, so only one icon request went out
+        assert_eq!(fetcher.icon_fetches.load(Ordering::SeqCst), 1);
+
+        // Private hosts are never contacted
+        let private = metadata.populate(&bookmarks, &private.id, false).await.unwrap();
+        assert!(private.favicon.is_none());
+        assert_eq!(fetcher.page_fetches.load(Ordering::SeqCst), 2);
+
+        let _ = std::fs::remove_dir_all(&cache_dir);
+    }
+}
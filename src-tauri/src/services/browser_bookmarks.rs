@@ -6,6 +6,9 @@ use std::sync::Mutex;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+/// Bookmark metadata key recording when the favicon was last resolved
+pub const FAVICON_FETCHED_AT_KEY: &str = "favicon_fetched_at";
+
 // ==================== Types ====================
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -37,6 +40,12 @@ pub struct BookmarkSettings {
     pub max_recent: u32,
     pub backup_enabled: bool,
     pub backup_interval_hours: u32,
+    /// Domains whose pages are never fetched for titles or favicons
+    #[serde(default)]
+    pub metadata_excluded_domains: Vec<String>,
+    /// How URLs are compared when looking for duplicates
+    #[serde(default)]
+    pub url_normalization: UrlNormalizationRules,
 }
 
 impl Default for BookmarkSettings {
@@ -53,10 +62,48 @@ impl Default for BookmarkSettings {
             max_recent: 50,
             backup_enabled: true,
             backup_interval_hours: 24,
+            metadata_excluded_domains: Vec::new(),
+            url_normalization: UrlNormalizationRules::default(),
+        }
+    }
+}
+
+/// Rules applied to bookmark URLs before comparing them for duplicates. The host is
+/// always lowercased and a port matching the scheme's default is always dropped.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UrlNormalizationRules {
+    /// Treat http:// and https:// as the same page
+    pub ignore_scheme: bool,
+    pub strip_trailing_slash: bool,
+    pub strip_fragment: bool,
+    /// Query parameters removed before comparing; a trailing `*` matches any suffix
+    pub tracking_params: Vec<String>,
+}
+
+impl Default for UrlNormalizationRules {
+    fn default() -> Self {
+        Self {
+            ignore_scheme: true,
+            strip_trailing_slash: true,
+            strip_fragment: true,
+            tracking_params: [
+                "utm_*", "fbclid", "gclid", "dclid", "msclkid", "mc_cid", "mc_eid", "igshid", "yclid", "_ga", "ref_src",
+            ]
+            .iter()
+            .map(|p| p.to_string())
+            .collect(),
         }
     }
 }
 
+/// A URL alongside the form used for duplicate comparison
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NormalizedUrl {
+    pub url: String,
+    pub normalized: String,
+    pub removed_params: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum SortOrder {
     Manual,
@@ -206,6 +253,42 @@ pub struct ImportResult {
     pub errors: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DuplicateMergeStrategy {
+    /// Keep the copy filed directly in the bookmarks bar, falling back to the oldest copy
+    KeepInBar,
+    /// Keep the copy whose folder was created first
+    KeepOldestFolder,
+    /// Keep the copy that was bookmarked first
+    KeepOldest,
+    /// Keep the most visited copy, falling back to the oldest
+    KeepMostVisited,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemovedDuplicate {
+    pub bookmark: Bookmark,
+    /// Index within the parent folder, used to put it back on undo
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergedDuplicate {
+    pub canonical_url: String,
+    pub kept: Bookmark,
+    pub kept_before_merge: Bookmark,
+    pub removed: Vec<RemovedDuplicate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeReport {
+    pub strategy: DuplicateMergeStrategy,
+    pub merged: Vec<MergedDuplicate>,
+    pub removed_count: u32,
+    #[serde(default)]
+    pub removed_ids: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BookmarkTreeNode {
     pub bookmark: Bookmark,
@@ -280,6 +363,29 @@ impl BrowserBookmarksService {
         Ok(())
     }
 
+    pub fn get_normalization_rules(&self) -> UrlNormalizationRules {
+        self.settings.lock().unwrap().url_normalization.clone()
+    }
+
+    pub fn set_normalization_rules(&self, mut rules: UrlNormalizationRules) -> Result<(), String> {
+        rules.tracking_params = rules.tracking_params.iter()
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty())
+            .collect();
+        if rules.tracking_params.iter().any(|p| p == "*") {
+            return Err("A tracking parameter pattern must name at least a prefix".to_string());
+        }
+        rules.tracking_params.sort();
+        rules.tracking_params.dedup();
+        self.settings.lock().unwrap().url_normalization = rules;
+        Ok(())
+    }
+
+    /// Shows how `url` is compared for duplicates under the current rules
+    pub fn preview_url_normalization(&self, url: &str) -> NormalizedUrl {
+        self.get_normalization_rules().normalize_detailed(url)
+    }
+
     // ==================== CRUD Operations ====================
 
     pub fn create_bookmark(&self, title: String, url: String, parent_id: Option<String>) -> Result<Bookmark, String> {
@@ -392,6 +498,26 @@ impl BrowserBookmarksService {
         }
     }
 
+    /// Store a fetched page title and favicon; the title only replaces a placeholder
+    pub fn apply_page_metadata(&self, id: &str, title: Option<String>, favicon: Option<String>) -> Result<Bookmark, String> {
+        let mut bookmarks = self.bookmarks.lock().unwrap();
+        let bookmark = bookmarks.get_mut(id).ok_or("Bookmark not found")?;
+
+        if let Some(title) = title {
+            let current = bookmark.title.trim();
+            if current.is_empty() || bookmark.url.as_deref() == Some(current) {
+                bookmark.title = title;
+            }
+        }
+        if favicon.is_some() || bookmark.favicon.is_none() {
+            bookmark.favicon = favicon;
+        }
+        bookmark.metadata.insert(FAVICON_FETCHED_AT_KEY.to_string(), Utc::now().to_rfc3339());
+        bookmark.modified_at = Utc::now();
+
+        Ok(bookmark.clone())
+    }
+
     pub fn delete_bookmark(&self, id: &str) -> Result<(), String> {
         // Don't allow deleting system folders
         if ["root", "bookmarks_bar", "other_bookmarks", "mobile_bookmarks"].contains(&id) {
@@ -966,16 +1092,18 @@ impl BrowserBookmarksService {
     }
 
     pub fn find_duplicates(&self) -> Vec<(Bookmark, Bookmark)> {
+        let rules = self.get_normalization_rules();
         let bookmarks = self.bookmarks.lock().unwrap();
         let mut duplicates = Vec::new();
         let mut seen: HashMap<String, &Bookmark> = HashMap::new();
         
         for bookmark in bookmarks.values() {
             if let Some(ref url) = bookmark.url {
-                if let Some(existing) = seen.get(url) {
+                let canonical = rules.normalize(url);
+                if let Some(existing) = seen.get(&canonical) {
                     duplicates.push(((*existing).clone(), bookmark.clone()));
                 } else {
-                    seen.insert(url.clone(), bookmark);
+                    seen.insert(canonical, bookmark);
                 }
             }
         }
@@ -983,6 +1111,158 @@ impl BrowserBookmarksService {
         duplicates
     }
 
+    /// Merges bookmarks sharing a canonical URL into a single bookmark. The kept copy
+    /// gets the union of tags, the summed visit count, the earliest creation date and
+    /// the most complete title; the returned report can be passed to `undo_merge`.
+    pub fn merge_duplicates(&self, strategy: DuplicateMergeStrategy) -> Result<MergeReport, String> {
+        let rules = self.get_normalization_rules();
+        let groups: Vec<(String, Vec<Bookmark>)> = {
+            let bookmarks = self.bookmarks.lock().unwrap();
+            let mut by_url: HashMap<String, Vec<Bookmark>> = HashMap::new();
+            for bookmark in bookmarks.values().filter(|b| b.bookmark_type == BookmarkType::Url) {
+                if let Some(ref url) = bookmark.url {
+                    by_url.entry(rules.normalize(url)).or_default().push(bookmark.clone());
+                }
+            }
+            let mut groups: Vec<_> = by_url.into_iter().filter(|(_, group)| group.len() > 1).collect();
+            groups.sort_by(|a, b| a.0.cmp(&b.0));
+            groups
+        };
+
+        let mut merged = Vec::new();
+        let mut removed_count = 0u32;
+        let mut removed_ids = Vec::new();
+        for (canonical_url, mut group) in groups {
+            group.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+            let keep_index = self.choose_keeper(&group, &strategy);
+            let kept_before_merge = group.remove(keep_index);
+
+            let mut kept = kept_before_merge.clone();
+            for duplicate in &group {
+                for tag in &duplicate.tags {
+                    if !kept.tags.contains(tag) {
+                        kept.tags.push(tag.clone());
+                    }
+                }
+                kept.visit_count += duplicate.visit_count;
+                kept.created_at = kept.created_at.min(duplicate.created_at);
+                kept.last_visited = kept.last_visited.max(duplicate.last_visited);
+                kept.is_favorite |= duplicate.is_favorite;
+                if kept.description.is_none() {
+                    kept.description = duplicate.description.clone();
+                }
+                if kept.favicon.is_none() {
+                    kept.favicon = duplicate.favicon.clone();
+                }
+                if title_completeness(&duplicate.title, &canonical_url, &rules) > title_completeness(&kept.title, &canonical_url, &rules) {
+                    kept.title = duplicate.title.clone();
+                }
+            }
+            kept.modified_at = Utc::now();
+
+            let mut removed = Vec::new();
+            for duplicate in group {
+                let index = duplicate.parent_id.as_ref()
+                    .and_then(|parent| {
+                        self.folder_children.lock().unwrap()
+                            .get(parent)
+                            .and_then(|children| children.iter().position(|c| *c == duplicate.id))
+                    })
+                    .unwrap_or(0);
+                self.delete_bookmark(&duplicate.id)?;
+                removed_ids.push(duplicate.id.clone());
+                removed.push(RemovedDuplicate { bookmark: duplicate, index });
+            }
+            removed_count += removed.len() as u32;
+
+            self.bookmarks.lock().unwrap().insert(kept.id.clone(), kept.clone());
+            merged.push(MergedDuplicate { canonical_url, kept, kept_before_merge, removed });
+        }
+
+        self.recount_tags();
+        Ok(MergeReport { strategy, merged, removed_count, removed_ids })
+    }
+
+    /// Reverses a `merge_duplicates` call, restoring removed copies to their folders
+    pub fn undo_merge(&self, report: MergeReport) -> Result<u32, String> {
+        let mut restored = 0u32;
+        for merge in report.merged {
+            {
+                let mut bookmarks = self.bookmarks.lock().unwrap();
+                if !bookmarks.contains_key(&merge.kept_before_merge.id) {
+                    return Err(format!("Merged bookmark {} no longer exists", merge.kept_before_merge.id));
+                }
+                bookmarks.insert(merge.kept_before_merge.id.clone(), merge.kept_before_merge);
+            }
+
+            for removed in merge.removed {
+                let parent = removed.bookmark.parent_id.clone()
+                    .unwrap_or_else(|| "other_bookmarks".to_string());
+                {
+                    let mut folder_children = self.folder_children.lock().unwrap();
+                    let children = folder_children.entry(parent).or_default();
+                    let index = removed.index.min(children.len());
+                    children.insert(index, removed.bookmark.id.clone());
+                }
+                self.bookmarks.lock().unwrap().insert(removed.bookmark.id.clone(), removed.bookmark);
+                restored += 1;
+            }
+        }
+
+        self.recount_tags();
+        Ok(restored)
+    }
+
+    fn choose_keeper(&self, group: &[Bookmark], strategy: &DuplicateMergeStrategy) -> usize {
+        match strategy {
+            DuplicateMergeStrategy::KeepInBar => group.iter()
+                .position(|b| b.parent_id.as_deref() == Some("bookmarks_bar"))
+                .unwrap_or(0),
+            DuplicateMergeStrategy::KeepOldestFolder => {
+                let bookmarks = self.bookmarks.lock().unwrap();
+                group.iter()
+                    .enumerate()
+                    .min_by_key(|(index, b)| {
+                        let folder_created = b.parent_id.as_ref()
+                            .and_then(|parent| bookmarks.get(parent))
+                            .map(|folder| folder.created_at);
+                        (folder_created.is_none(), folder_created, *index)
+                    })
+                    .map(|(index, _)| index)
+                    .unwrap_or(0)
+            }
+            // Groups arrive sorted oldest first
+            DuplicateMergeStrategy::KeepOldest => 0,
+            DuplicateMergeStrategy::KeepMostVisited => group.iter()
+                .enumerate()
+                .max_by_key(|(index, b)| (b.visit_count, std::cmp::Reverse(*index)))
+                .map(|(index, _)| index)
+                .unwrap_or(0),
+        }
+    }
+
+    fn recount_tags(&self) {
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for bookmark in self.bookmarks.lock().unwrap().values() {
+            for tag in &bookmark.tags {
+                *counts.entry(tag.clone()).or_insert(0) += 1;
+            }
+        }
+
+        let mut tags = self.tags.lock().unwrap();
+        for (name, tag) in tags.iter_mut() {
+            tag.bookmark_count = counts.get(name).copied().unwrap_or(0);
+        }
+        for (name, count) in counts {
+            tags.entry(name.clone()).or_insert_with(|| BookmarkTag {
+                name,
+                color: "#6366f1".to_string(),
+                bookmark_count: count,
+                created_at: Utc::now(),
+            });
+        }
+    }
+
     pub fn cleanup_orphaned(&self) -> u32 {
         let mut bookmarks = self.bookmarks.lock().unwrap();
         let folder_ids: HashSet<String> = bookmarks
@@ -1121,6 +1401,82 @@ impl BrowserBookmarksService {
     }
 }
 
+impl UrlNormalizationRules {
+    pub fn normalize(&self, url: &str) -> String {
+        self.normalize_detailed(url).normalized
+    }
+
+    pub fn normalize_detailed(&self, url: &str) -> NormalizedUrl {
+        let trimmed = url.trim();
+        let Ok(mut parsed) = url::Url::parse(trimmed) else {
+            let mut normalized = trimmed.to_lowercase();
+            if self.strip_trailing_slash {
+                normalized = normalized.trim_end_matches('/').to_string();
+            }
+            return NormalizedUrl { url: url.to_string(), normalized, removed_params: Vec::new() };
+        };
+
+        if self.ignore_scheme && parsed.scheme() == "http" {
+            // Also drops an explicit :443, which is https' default port
+            let _ = parsed.set_scheme("https");
+        }
+        if self.strip_fragment {
+            parsed.set_fragment(None);
+        }
+
+        let mut removed_params = Vec::new();
+        let mut query = Vec::new();
+        for (key, value) in parsed.query_pairs() {
+            if self.is_tracking_param(&key) {
+                removed_params.push(key.into_owned());
+            } else {
+                query.push((key.into_owned(), value.into_owned()));
+            }
+        }
+        if query.is_empty() {
+            parsed.set_query(None);
+        } else {
+            parsed.query_pairs_mut().clear().extend_pairs(query);
+        }
+
+        let mut normalized = parsed.to_string();
+        if self.strip_trailing_slash {
+            let path = parsed.path().trim_end_matches('/').to_string();
+            parsed.set_path(&path);
+            normalized = parsed.to_string().trim_end_matches('/').to_string();
+        }
+        NormalizedUrl { url: url.to_string(), normalized, removed_params }
+    }
+
+    fn is_tracking_param(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.tracking_params.iter().any(|pattern| {
+            let pattern = pattern.to_lowercase();
+            match pattern.strip_suffix('*') {
+                Some(prefix) => key.starts_with(prefix),
+                None => key == pattern,
+            }
+        })
+    }
+}
+
+/// Normalizes a URL for duplicate detection using the default rules: lowercases the
+/// host, treats http as https, drops the fragment, default port, tracking parameters
+/// and trailing slash.
+pub fn canonicalize_url(url: &str) -> String {
+    UrlNormalizationRules::default().normalize(url)
+}
+
+/// Higher is better: titles that are just the URL (or empty) rank below real titles
+fn title_completeness(title: &str, canonical_url: &str, rules: &UrlNormalizationRules) -> usize {
+    let trimmed = title.trim();
+    if trimmed.is_empty() || rules.normalize(trimmed) == canonical_url {
+        0
+    } else {
+        trimmed.chars().count() + 1
+    }
+}
+
 /// Decode HTML entities
 fn html_decode(s: &str) -> String {
     s.replace("&amp;", "&")
@@ -1130,3 +1486,105 @@ fn html_decode(s: &str) -> String {
         .replace("&#39;", "'")
         .replace("&apos;", "'")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_url() {
+        assert_eq!(
+            canonicalize_url("HTTPS://Example.com/Docs/?utm_source=news&id=7#section"),
+            canonicalize_url("https://example.com/Docs?id=7")
+        );
+        assert_eq!(canonicalize_url("https://example.com/"), canonicalize_url("https://EXAMPLE.com"));
+        assert_ne!(canonicalize_url("https://example.com/a"), canonicalize_url("https://example.com/b"));
+    }
+
+    #[test]
+    fn test_merge_duplicates_unions_tags_and_sums_visits() {
+        let service = BrowserBookmarksService::new();
+        let work = service.create_folder("Work".to_string(), Some("other_bookmarks".to_string())).unwrap();
+        let first = service.create_bookmark("https://example.com/".to_string(), "https://example.com/".to_string(), Some(work.id.clone())).unwrap();
+        let second = service.create_bookmark("Example Docs".to_string(), "https://Example.com?utm_campaign=x".to_string(), None).unwrap();
+        service.add_tag(&first.id, "work".to_string()).unwrap();
+        service.add_tag(&first.id, "docs".to_string()).unwrap();
+        service.add_tag(&second.id, "docs".to_string()).unwrap();
+        service.add_tag(&second.id, "reference".to_string()).unwrap();
+        for _ in 0..3 {
+            service.record_visit(&first.id).unwrap();
+        }
+        service.record_visit(&second.id).unwrap();
+
+        let report = service.merge_duplicates(DuplicateMergeStrategy::KeepInBar).unwrap();
+        assert_eq!(report.removed_count, 1);
+
+        let kept = service.get_bookmark(&second.id).unwrap();
+        assert!(service.get_bookmark(&first.id).is_none());
+        assert_eq!(kept.parent_id.as_deref(), Some("bookmarks_bar"));
+        assert_eq!(kept.tags, vec!["docs".to_string(), "reference".to_string(), "work".to_string()]);
+        assert_eq!(kept.visit_count, 4);
+        assert_eq!(kept.title, "Example Docs");
+        assert_eq!(kept.created_at, first.created_at.min(second.created_at));
+        let docs = service.get_all_tags().into_iter().find(|t| t.name == "docs").unwrap();
+        assert_eq!(docs.bookmark_count, 1);
+
+        assert_eq!(service.undo_merge(report).unwrap(), 1);
+        assert_eq!(service.get_bookmark(&first.id).unwrap().visit_count, 3);
+        assert_eq!(service.get_bookmark(&second.id).unwrap().visit_count, 1);
+        assert_eq!(service.get_folder_contents(&work.id).len(), 1);
+    }
+
+    #[test]
+    fn test_normalization_rules_fold_scheme_ports_and_tracking_params() {
+        let rules = UrlNormalizationRules::default();
+        let preview = rules.normalize_detailed("http://News.example.com:80/story/?utm_source=rss&utm_medium=feed&id=2#comments");
+        assert_eq!(preview.normalized, "https://news.example.com/story?id=2");
+        assert_eq!(preview.removed_params, vec!["utm_source".to_string(), "utm_medium".to_string()]);
+        assert_eq!(rules.normalize("https://news.example.com:443/story?id=2"), preview.normalized);
+        assert_ne!(rules.normalize("https://news.example.com/story?id=3"), preview.normalized);
+
+        let strict = UrlNormalizationRules {
+            ignore_scheme: false,
+            tracking_params: vec!["ref".to_string()],
+            ..UrlNormalizationRules::default()
+        };
+        assert_ne!(strict.normalize("http://example.com/a"), strict.normalize("https://example.com/a"));
+        assert_eq!(strict.normalize("https://example.com/a?ref=hn"), "https://example.com/a");
+        assert_eq!(strict.normalize("https://example.com/a?utm_source=x"), "https://example.com/a?utm_source=x");
+    }
+
+    #[test]
+    fn test_merge_duplicates_by_strategy_across_scheme_and_utm_variants() {
+        let service = BrowserBookmarksService::new();
+        let plain = service.create_bookmark("Story".to_string(), "http://news.example.com/story".to_string(), None).unwrap();
+        let from_feed = service.create_bookmark("Story".to_string(), "https://news.example.com/story/?utm_source=rss".to_string(), Some("other_bookmarks".to_string())).unwrap();
+        let from_mail = service.create_bookmark("Story".to_string(), "https://news.example.com/story?utm_campaign=weekly&utm_medium=email".to_string(), Some("mobile_bookmarks".to_string())).unwrap();
+        let other_page = service.create_bookmark("Other".to_string(), "https://news.example.com/story?page=2".to_string(), None).unwrap();
+        {
+            let mut bookmarks = service.bookmarks.lock().unwrap();
+            let base = Utc::now();
+            for (offset, id) in [&plain.id, &from_feed.id, &from_mail.id].into_iter().enumerate() {
+                bookmarks.get_mut(id).unwrap().created_at = base - chrono::Duration::days(10 - offset as i64);
+            }
+        }
+        service.add_tag(&plain.id, "news".to_string()).unwrap();
+        service.add_tag(&from_mail.id, "weekly".to_string()).unwrap();
+        for _ in 0..5 {
+            service.record_visit(&from_feed.id).unwrap();
+        }
+
+        let report = service.merge_duplicates(DuplicateMergeStrategy::KeepMostVisited).unwrap();
+        assert_eq!(report.merged.len(), 1);
+        assert_eq!(report.removed_count, 2);
+        assert_eq!(report.removed_ids, vec![plain.id.clone(), from_mail.id.clone()]);
+        let kept = service.get_bookmark(&from_feed.id).unwrap();
+        assert_eq!(kept.tags, vec!["news".to_string(), "weekly".to_string()]);
+        assert!(service.get_bookmark(&other_page.id).is_some());
+
+        service.undo_merge(report).unwrap();
+        let report = service.merge_duplicates(DuplicateMergeStrategy::KeepOldest).unwrap();
+        assert_eq!(report.merged[0].kept.id, plain.id);
+        assert_eq!(report.removed_ids, vec![from_feed.id.clone(), from_mail.id.clone()]);
+    }
+}
@@ -2,10 +2,26 @@
 // Superior to Chrome, Firefox, Safari, Brave download managers
 // Advanced download management with categories, scheduling, and bandwidth control
 
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::services::torrent_client::{
+    parse_magnet, Metainfo, TorrentConfig, TorrentFile, TorrentPhase, TorrentProgress, TorrentSession, TorrentSource,
+};
+
+/// How often a running torrent is synced with its download entry
+const TORRENT_SYNC_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+const MAX_TORRENT_FILE_SIZE: usize = 10 * 1024 * 1024;
+/// Files are only split into parallel segments of at least this size
+const MIN_SEGMENT_SIZE: u64 = 256 * 1024;
 
 // ==================== Enums ====================
 
@@ -63,6 +79,153 @@ pub enum SpeedUnit {
     Auto,
 }
 
+/// Result of comparing a finished download with its expected checksum
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityStatus {
+    #[default]
+    Unverified,
+    Verified,
+    ChecksumMismatch,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumAlgorithm {
+    Md5,
+    Sha256,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace(['-', '_'], "").as_str() {
+            "md5" => Some(Self::Md5),
+            "sha256" => Some(Self::Sha256),
+            "sha512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Guesses the algorithm from a bare hex digest
+    fn from_hex_len(len: usize) -> Option<Self> {
+        match len {
+            32 => Some(Self::Md5),
+            64 => Some(Self::Sha256),
+            128 => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    fn hex_len(&self) -> usize {
+        match self {
+            Self::Md5 => 32,
+            Self::Sha256 => 64,
+            Self::Sha512 => 128,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Md5 => "md5",
+            Self::Sha256 => "sha256",
+            Self::Sha512 => "sha512",
+        }
+    }
+}
+
+/// Incremental hasher fed with download chunks as they arrive
+enum ChecksumHasher {
+    Md5(md5::Context),
+    Sha256(Sha256),
+    Sha512(Sha512),
+}
+
+impl ChecksumHasher {
+    fn new(algorithm: ChecksumAlgorithm) -> Self {
+        match algorithm {
+            ChecksumAlgorithm::Md5 => Self::Md5(md5::Context::new()),
+            ChecksumAlgorithm::Sha256 => Self::Sha256(Sha256::new()),
+            ChecksumAlgorithm::Sha512 => Self::Sha512(Sha512::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Md5(ctx) => ctx.consume(data),
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Sha512(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finish(self) -> String {
+        match self {
+            Self::Md5(ctx) => format!("{:x}", ctx.compute()),
+            Self::Sha256(hasher) => hex::encode(hasher.finalize()),
+            Self::Sha512(hasher) => hex::encode(hasher.finalize()),
+        }
+    }
+}
+
+fn hash_file(path: &Path, algorithm: ChecksumAlgorithm) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut hasher = ChecksumHasher::new(algorithm);
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).map_err(|e| format!("Failed to read file: {}", e))?;
+        if n == 0 {
+            return Ok(hasher.finish());
+        }
+        hasher.update(&buf[..n]);
+    }
+}
+
+/// Finds the checksum for `filename` in a `.sha256`/`.md5` file or a SHASUMS
+/// listing. Handles GNU (`<hex>  name`, `<hex> *name`), BSD
+/// (`SHA256 (name) = <hex>`) and bare-digest files.
+pub fn parse_checksum_file(contents: &str, filename: &str) -> Option<(ChecksumAlgorithm, String)> {
+    let is_hex = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit());
+    let names_match = |name: &str| {
+        let name = name.trim().trim_start_matches('*').trim_start_matches("./");
+        name == filename || name.rsplit('/').next() == Some(filename)
+    };
+    let lines: Vec<&str> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .collect();
+
+    for line in &lines {
+        // BSD style
+        if let Some((tag, rest)) = line.split_once(" (") {
+            if let Some((name, digest)) = rest.split_once(") = ") {
+                if let Some(algorithm) = ChecksumAlgorithm::parse(tag) {
+                    if names_match(name) && is_hex(digest.trim()) {
+                        return Some((algorithm, digest.trim().to_lowercase()));
+                    }
+                }
+                continue;
+            }
+        }
+
+        // GNU style
+        if let Some((digest, name)) = line.split_once(char::is_whitespace) {
+            if is_hex(digest) && names_match(name) {
+                let algorithm = ChecksumAlgorithm::from_hex_len(digest.len())?;
+                return Some((algorithm, digest.to_lowercase()));
+            }
+        }
+    }
+
+    // A per-file checksum such as app.zip.sha256 may hold only the digest
+    match lines.as_slice() {
+        [digest] if is_hex(digest) => {
+            ChecksumAlgorithm::from_hex_len(digest.len()).map(|a| (a, digest.to_lowercase()))
+        }
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ScheduleType {
     Immediate,
@@ -98,6 +261,34 @@ pub struct DownloadSettings {
     pub blocked_extensions: Vec<String>,
     pub blocked_domains: Vec<String>,
     pub download_history_days: u32,
+    #[serde(default)]
+    pub torrent: TorrentSettings,
+}
+
+/// Seeding policy and limits for BitTorrent downloads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TorrentSettings {
+    /// Stop seeding once uploaded / downloaded reaches this (0 = don't seed)
+    pub seed_ratio_limit: f64,
+    /// Stop seeding after this many minutes even if the ratio isn't reached
+    pub seed_time_limit_minutes: Option<u64>,
+    /// 0 = unlimited
+    pub upload_limit_kbps: u64,
+    /// Falls back to any free port when taken; 0 always picks one
+    pub listen_port: u16,
+    pub max_peers: u32,
+}
+
+impl Default for TorrentSettings {
+    fn default() -> Self {
+        Self {
+            seed_ratio_limit: 1.0,
+            seed_time_limit_minutes: None,
+            upload_limit_kbps: 0,
+            listen_port: 6881,
+            max_peers: 50,
+        }
+    }
 }
 
 impl Default for DownloadSettings {
@@ -134,6 +325,7 @@ impl Default for DownloadSettings {
             blocked_extensions: vec!["exe".to_string(), "bat".to_string(), "cmd".to_string()],
             blocked_domains: Vec::new(),
             download_history_days: 30,
+            torrent: TorrentSettings::default(),
         }
     }
 }
@@ -160,8 +352,10 @@ pub struct Download {
     pub priority: DownloadPriority,
     pub total_bytes: u64,
     pub downloaded_bytes: u64,
+    /// Smoothed over the last few progress samples
     pub speed_bps: u64,
-    pub eta_seconds: u64,
+    /// None while the speed or the total size is unknown
+    pub eta_seconds: Option<u64>,
     pub connections: u32,
     pub resumable: bool,
     pub created_at: u64,
@@ -173,13 +367,72 @@ pub struct Download {
     pub referrer: Option<String>,
     pub source_tab_id: Option<String>,
     pub tags: Vec<String>,
+    /// Expected checksum and its algorithm, as published by the site
     pub checksum: Option<String>,
     pub checksum_type: Option<String>,
+    #[serde(default)]
+    pub computed_checksum: Option<String>,
+    #[serde(default)]
+    pub integrity: IntegrityStatus,
+    /// Where the file was moved after a checksum mismatch
+    #[serde(default)]
+    pub quarantine_path: Option<String>,
     pub schedule_type: ScheduleType,
     pub scheduled_time: Option<u64>,
     pub auto_extract: bool,
     pub virus_scanned: bool,
     pub virus_clean: Option<bool>,
+    /// Set for torrent and magnet downloads
+    #[serde(default)]
+    pub torrent: Option<TorrentDetails>,
+    /// Validators from the first response, checked when resuming
+    #[serde(default)]
+    pub etag: Option<String>,
+    #[serde(default)]
+    pub last_modified: Option<String>,
+    /// Byte ranges being fetched into `.part` files, merged on completion
+    #[serde(default)]
+    pub segments: Vec<DownloadSegment>,
+    /// Non-fatal problems, e.g. a resume that had to start over
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// One range of an HTTP download, written to its own `.part` file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DownloadSegment {
+    pub start: u64,
+    /// Inclusive; `None` while the size is unknown
+    pub end: Option<u64>,
+    pub downloaded: u64,
+    pub complete: bool,
+}
+
+impl DownloadSegment {
+    fn remaining(&self) -> Option<u64> {
+        self.end.map(|end| (end + 1).saturating_sub(self.start + self.downloaded))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TorrentDetails {
+    pub info_hash: String,
+    pub phase: TorrentPhase,
+    /// Empty until a magnet link's metadata arrives
+    pub files: Vec<TorrentFileSelection>,
+    pub seeds: u32,
+    pub peers: u32,
+    pub uploaded_bytes: u64,
+    pub upload_speed_bps: u64,
+    pub ratio: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TorrentFileSelection {
+    pub index: usize,
+    pub path: String,
+    pub length: u64,
+    pub selected: bool,
 }
 
 impl Download {
@@ -204,7 +457,7 @@ impl Download {
             total_bytes: 0,
             downloaded_bytes: 0,
             speed_bps: 0,
-            eta_seconds: 0,
+            eta_seconds: None,
             connections: 1,
             resumable: false,
             created_at: SystemTime::now()
@@ -221,11 +474,19 @@ impl Download {
             tags: Vec::new(),
             checksum: None,
             checksum_type: None,
+            computed_checksum: None,
+            integrity: IntegrityStatus::Unverified,
+            quarantine_path: None,
             schedule_type: ScheduleType::Immediate,
             scheduled_time: None,
             auto_extract: false,
             virus_scanned: false,
             virus_clean: None,
+            torrent: None,
+            etag: None,
+            last_modified: None,
+            segments: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 
@@ -269,6 +530,15 @@ impl Download {
         }.to_string()
     }
 
+    /// False for chunked responses sent without a Content-Length
+    pub fn total_known(&self) -> bool {
+        if self.segments.is_empty() {
+            self.total_bytes > 0
+        } else {
+            self.segments.iter().all(|s| s.end.is_some())
+        }
+    }
+
     pub fn percentage(&self) -> f64 {
         if self.total_bytes == 0 {
             0.0
@@ -324,6 +594,62 @@ pub struct DownloadFilter {
     pub tags: Vec<String>,
 }
 
+// ==================== Speed & ETA ====================
+
+/// Progress samples kept for a download's rolling speed average
+const SPEED_WINDOW_SAMPLES: usize = 10;
+
+/// Samples closer together than this are skipped, so a burst of small chunks
+/// can't shrink the window to a few milliseconds
+const MIN_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Rolling average of a download's speed over its last progress samples,
+/// which smooths out the spikes of bursty connections
+#[derive(Debug, Default)]
+pub struct SpeedWindow {
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl SpeedWindow {
+    /// Records the byte count seen at `at` and returns the smoothed speed
+    pub fn record(&mut self, at: Instant, downloaded: u64) -> u64 {
+        // The transfer started over, e.g. after a failed resume
+        if self.samples.back().is_some_and(|&(_, last)| downloaded < last) {
+            self.samples.clear();
+        }
+        let due = match self.samples.back() {
+            Some(&(last, _)) => at.saturating_duration_since(last) >= MIN_SAMPLE_INTERVAL,
+            None => true,
+        };
+        if due {
+            if self.samples.len() == SPEED_WINDOW_SAMPLES {
+                self.samples.pop_front();
+            }
+            self.samples.push_back((at, downloaded));
+        }
+        self.speed_bps()
+    }
+
+    /// Bytes per second from the oldest to the newest sample; 0 until there
+    /// are two
+    pub fn speed_bps(&self) -> u64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(&(start, from)), Some(&(end, to))) if end > start => {
+                ((to - from) as f64 / (end - start).as_secs_f64()) as u64
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// Seconds left at `speed_bps`, or None when the speed or total size is unknown
+pub fn estimate_eta(remaining: u64, speed_bps: u64, total_known: bool) -> Option<u64> {
+    if !total_known || speed_bps == 0 {
+        return None;
+    }
+    Some(remaining.div_ceil(speed_bps))
+}
+
 // ==================== Service ====================
 
 pub struct BrowserDownloadsService {
@@ -333,6 +659,26 @@ pub struct BrowserDownloadsService {
     bandwidth_schedule: Mutex<Vec<BandwidthSchedule>>,
     stats: Mutex<DownloadStats>,
     active_downloads: Mutex<Vec<String>>,
+    /// Parsed .torrent files and magnet links, by download id
+    torrent_sources: Mutex<HashMap<String, TorrentSource>>,
+    torrent_sessions: Mutex<HashMap<String, Arc<TorrentSession>>>,
+    /// HTTP downloads with a transfer task running
+    running_transfers: Mutex<HashSet<String>>,
+    /// Rolling speed averages of active downloads
+    speed_windows: Mutex<HashMap<String, SpeedWindow>>,
+}
+
+/// Why a segment stopped early
+enum SegmentError {
+    /// The partial data can't be used; start the whole file over
+    Restart(String),
+    Failed(String),
+}
+
+/// Shared by the segments of one transfer run
+struct TransferRun {
+    /// Set when one segment fails, so the others stop too
+    abort: AtomicBool,
 }
 
 impl BrowserDownloadsService {
@@ -355,6 +701,10 @@ impl BrowserDownloadsService {
                 category_stats: HashMap::new(),
             }),
             active_downloads: Mutex::new(Vec::new()),
+            torrent_sources: Mutex::new(HashMap::new()),
+            torrent_sessions: Mutex::new(HashMap::new()),
+            running_transfers: Mutex::new(HashSet::new()),
+            speed_windows: Mutex::new(HashMap::new()),
         }
     }
 
@@ -389,6 +739,20 @@ impl BrowserDownloadsService {
         Ok(())
     }
 
+    /// Number of parallel segments for one file. Takes effect the next time
+    /// the transfer starts from scratch.
+    pub fn set_max_segments(&self, download_id: &str, max: u32) -> Result<Download, String> {
+        let limit = self.settings.lock().unwrap().max_connections_per_download;
+        if max == 0 || max > limit {
+            return Err(format!("Segments per download must be between 1 and {}", limit));
+        }
+        let mut downloads = self.downloads.lock().unwrap();
+        let download = downloads.get_mut(download_id)
+            .ok_or("Download not found")?;
+        download.connections = max;
+        Ok(download.clone())
+    }
+
     pub fn set_bandwidth_limit(&self, enabled: bool, limit_kbps: u64) -> Result<(), String> {
         let mut settings = self.settings.lock().unwrap();
         settings.bandwidth_limit_enabled = enabled;
@@ -414,9 +778,23 @@ impl BrowserDownloadsService {
         Ok(())
     }
 
+    pub fn set_torrent_settings(&self, torrent: TorrentSettings) -> Result<(), String> {
+        if !torrent.seed_ratio_limit.is_finite() || torrent.seed_ratio_limit < 0.0 {
+            return Err("Seed ratio limit must be zero or more".to_string());
+        }
+        if torrent.max_peers == 0 {
+            return Err("Max peers must be at least 1".to_string());
+        }
+        self.settings.lock().unwrap().torrent = torrent;
+        Ok(())
+    }
+
     // ==================== Download Operations ====================
 
     pub fn create_download(&self, url: String, filename: Option<String>, directory: Option<String>) -> Result<Download, String> {
+        if is_torrent_url(&url) {
+            return self.create_torrent_download(url, filename, directory);
+        }
         let settings = self.settings.lock().unwrap();
         
         // Determine filename
@@ -458,6 +836,52 @@ impl BrowserDownloadsService {
         Ok(download)
     }
 
+    /// Magnet links and local .torrent files are parsed right away; remote
+    /// .torrent files are fetched when the transfer starts
+    fn create_torrent_download(&self, url: String, filename: Option<String>, directory: Option<String>) -> Result<Download, String> {
+        let source = load_local_torrent(&url)?;
+        let settings = self.settings.lock().unwrap().clone();
+
+        // Torrents keep their own folder layout, so they aren't sorted into
+        // category folders
+        let base_dir = directory.unwrap_or_else(|| settings.default_directory.clone());
+        let name = source
+            .as_ref()
+            .and_then(TorrentSource::name)
+            .or(filename)
+            .unwrap_or_else(|| {
+                let last = url.split(['?', '#']).next().unwrap_or("").rsplit('/').next().unwrap_or("");
+                last.trim_end_matches(".torrent").to_string()
+            });
+        let name = if name.trim().is_empty() { "torrent".to_string() } else { name };
+
+        let mut download = Download::new(url, name.clone(), format!("{}/{}", base_dir, name));
+        download.resumable = true;
+        download.category = FileCategory::Other;
+        download.torrent = Some(TorrentDetails {
+            info_hash: source.as_ref().map(|s| hex::encode(s.info_hash())).unwrap_or_default(),
+            phase: TorrentPhase::FetchingMetadata,
+            files: Vec::new(),
+            seeds: 0,
+            peers: 0,
+            uploaded_bytes: 0,
+            upload_speed_bps: 0,
+            ratio: 0.0,
+        });
+        if let Some(source) = &source {
+            apply_torrent_files(&mut download, source.files(), &settings.blocked_extensions)?;
+        }
+
+        let download_id = download.id.clone();
+        if let Some(source) = source {
+            self.torrent_sources.lock().unwrap().insert(download_id.clone(), source);
+        }
+        self.downloads.lock().unwrap().insert(download_id, download.clone());
+        self.stats.lock().unwrap().total_downloads += 1;
+
+        Ok(download)
+    }
+
     pub fn start_download(&self, download_id: &str) -> Result<Download, String> {
         let mut downloads = self.downloads.lock().unwrap();
         let download = downloads.get_mut(download_id)
@@ -498,7 +922,7 @@ impl BrowserDownloadsService {
         }
 
         download.status = DownloadStatus::Paused;
-        self.active_downloads.lock().unwrap().retain(|id| id != download_id);
+        self.deactivate(download_id);
 
         Ok(download.clone())
     }
@@ -515,6 +939,7 @@ impl BrowserDownloadsService {
         if !download.resumable {
             // Restart from beginning
             download.downloaded_bytes = 0;
+            download.segments.clear();
         }
 
         download.status = DownloadStatus::Downloading;
@@ -529,7 +954,13 @@ impl BrowserDownloadsService {
             .ok_or("Download not found")?;
 
         download.status = DownloadStatus::Cancelled;
-        self.active_downloads.lock().unwrap().retain(|id| id != download_id);
+        self.deactivate(download_id);
+
+        // A running transfer cleans up after itself
+        if !self.running_transfers.lock().unwrap().contains(download_id) {
+            remove_part_files(download);
+            download.segments.clear();
+        }
 
         Ok(())
     }
@@ -547,6 +978,8 @@ impl BrowserDownloadsService {
         download.retry_count += 1;
         download.error_message = None;
         download.downloaded_bytes = 0;
+        remove_part_files(download);
+        download.segments.clear();
 
         Ok(download.clone())
     }
@@ -555,7 +988,8 @@ impl BrowserDownloadsService {
         let download = self.downloads.lock().unwrap().remove(download_id)
             .ok_or("Download not found")?;
 
-        self.active_downloads.lock().unwrap().retain(|id| id != download_id);
+        self.deactivate(download_id);
+        remove_part_files(&download);
 
         if delete_file && download.status == DownloadStatus::Completed {
             // In a real implementation, delete the file from disk
@@ -566,36 +1000,723 @@ impl BrowserDownloadsService {
     }
 
     pub fn update_progress(&self, download_id: &str, downloaded: u64, total: u64, speed: u64) -> Result<(), String> {
+        self.update_progress_at(download_id, downloaded, total, speed, Instant::now())
+    }
+
+    fn update_progress_at(&self, download_id: &str, downloaded: u64, total: u64, speed: u64, at: Instant) -> Result<(), String> {
         let mut downloads = self.downloads.lock().unwrap();
         let download = downloads.get_mut(download_id)
             .ok_or("Download not found")?;
 
         download.downloaded_bytes = downloaded;
         download.total_bytes = total;
-        download.speed_bps = speed;
-        
-        if speed > 0 {
-            download.eta_seconds = (total - downloaded) / speed;
-        }
+        self.sample_speed(download, at, speed);
 
         if downloaded >= total && total > 0 {
+            drop(downloads);
+            // The file was written elsewhere, so hash it from disk
+            self.complete_download(download_id, total, None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Feeds the download's byte count into its rolling speed average and
+    /// refreshes the smoothed speed and ETA. `reported` is used until the
+    /// window has two samples.
+    fn sample_speed(&self, download: &mut Download, at: Instant, reported: u64) {
+        let smoothed = self.speed_windows.lock().unwrap()
+            .entry(download.id.clone())
+            .or_default()
+            .record(at, download.downloaded_bytes);
+        let speed = if smoothed > 0 { smoothed } else { reported };
+        download.speed_bps = speed;
+        download.eta_seconds = estimate_eta(
+            download.total_bytes.saturating_sub(download.downloaded_bytes),
+            speed,
+            download.total_known(),
+        );
+    }
+
+    /// Takes a download off the active list and forgets its speed samples
+    fn deactivate(&self, download_id: &str) {
+        self.active_downloads.lock().unwrap().retain(|id| id != download_id);
+        self.speed_windows.lock().unwrap().remove(download_id);
+    }
+
+    fn complete_download(
+        &self,
+        download_id: &str,
+        total: u64,
+        streamed_digest: Option<(ChecksumAlgorithm, String)>,
+    ) -> Result<Download, String> {
+        {
+            let mut downloads = self.downloads.lock().unwrap();
+            let download = downloads.get_mut(download_id)
+                .ok_or("Download not found")?;
+
             download.status = DownloadStatus::Completed;
+            download.downloaded_bytes = total;
+            download.total_bytes = total;
             download.completed_at = Some(SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_secs());
-            
-            drop(downloads);
-            self.active_downloads.lock().unwrap().retain(|id| id != download_id);
-            
-            // Update stats
+            if let Some((_, digest)) = &streamed_digest {
+                download.computed_checksum = Some(digest.clone());
+            }
+        }
+        self.deactivate(download_id);
+
+        // Update stats
+        {
             let mut stats = self.stats.lock().unwrap();
             stats.completed_downloads += 1;
             stats.total_bytes_downloaded += total;
             stats.bytes_today += total;
         }
 
-        Ok(())
+        self.verify_checksum(download_id, streamed_digest)
+    }
+
+    /// Compares a completed download with its expected checksum. A mismatch
+    /// moves the file into a `.quarantine` folder next to it.
+    fn verify_checksum(
+        &self,
+        download_id: &str,
+        streamed_digest: Option<(ChecksumAlgorithm, String)>,
+    ) -> Result<Download, String> {
+        let download = self.get_download(download_id).ok_or("Download not found")?;
+        let expected = download.checksum.as_ref().zip(
+            download.checksum_type.as_deref().and_then(ChecksumAlgorithm::parse),
+        );
+        let Some((expected, algorithm)) = expected else {
+            return Ok(download);
+        };
+        if download.status != DownloadStatus::Completed || download.quarantine_path.is_some() {
+            return Ok(download);
+        }
+
+        let computed = match streamed_digest.filter(|(a, _)| *a == algorithm) {
+            Some((_, digest)) => digest,
+            None => match hash_file(Path::new(&download.file_path), algorithm) {
+                Ok(digest) => digest,
+                Err(e) => {
+                    log::warn!("Could not verify download {}: {}", download_id, e);
+                    return Ok(download);
+                }
+            },
+        };
+
+        let matches = computed.eq_ignore_ascii_case(expected);
+        let quarantine_path = if matches {
+            None
+        } else {
+            let source = PathBuf::from(&download.file_path);
+            let dir = source.parent().unwrap_or(Path::new(".")).join(".quarantine");
+            let target = dir.join(&download.filename);
+            std::fs::create_dir_all(&dir)
+                .and_then(|_| std::fs::rename(&source, &target))
+                .map_err(|e| format!("Failed to quarantine download: {}", e))?;
+            log::warn!(
+                "Download {} failed {} verification, quarantined at {}",
+                download_id, algorithm.as_str(), target.display()
+            );
+            Some(target.to_string_lossy().to_string())
+        };
+
+        let mut downloads = self.downloads.lock().unwrap();
+        let download = downloads.get_mut(download_id)
+            .ok_or("Download not found")?;
+        download.computed_checksum = Some(computed);
+        if matches {
+            download.integrity = IntegrityStatus::Verified;
+        } else {
+            download.integrity = IntegrityStatus::ChecksumMismatch;
+            download.quarantine_path = quarantine_path;
+            download.error_message = Some(format!(
+                "{} checksum mismatch: file quarantined",
+                algorithm.as_str().to_uppercase()
+            ));
+        }
+        Ok(download.clone())
+    }
+
+    // ==================== Integrity ====================
+
+    /// Sets the checksum the site published for this download. A download
+    /// that already finished is verified right away.
+    pub fn set_expected_checksum(&self, download_id: &str, algorithm: &str, value: &str) -> Result<Download, String> {
+        let algorithm = ChecksumAlgorithm::parse(algorithm)
+            .ok_or_else(|| format!("Unsupported checksum algorithm: {}", algorithm))?;
+        let value = value.trim().to_lowercase();
+        if value.len() != algorithm.hex_len() || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!(
+                "Invalid {} checksum: expected {} hex characters",
+                algorithm.as_str(),
+                algorithm.hex_len()
+            ));
+        }
+
+        {
+            let mut downloads = self.downloads.lock().unwrap();
+            let download = downloads.get_mut(download_id)
+                .ok_or("Download not found")?;
+            download.checksum = Some(value);
+            download.checksum_type = Some(algorithm.as_str().to_string());
+            if download.quarantine_path.is_none() {
+                download.integrity = IntegrityStatus::Unverified;
+            }
+        }
+
+        let streamed = self
+            .get_download(download_id)
+            .and_then(|d| d.computed_checksum.map(|c| (ChecksumAlgorithm::from_hex_len(c.len()), c)))
+            .and_then(|(a, c)| a.map(|a| (a, c)));
+        self.verify_checksum(download_id, streamed)
+    }
+
+    /// Fetches a linked `.sha256`/`SHASUMS` file and uses the entry for this
+    /// download's filename as its expected checksum
+    pub async fn load_checksum_file(&self, download_id: &str, checksum_url: &str) -> Result<Download, String> {
+        let download = self.get_download(download_id).ok_or("Download not found")?;
+        let response = reqwest::get(checksum_url)
+            .await
+            .map_err(|e| format!("Failed to fetch checksum file: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch checksum file: HTTP {}", response.status()));
+        }
+        let contents = response.text().await.map_err(|e| e.to_string())?;
+
+        let (algorithm, digest) = parse_checksum_file(&contents, &download.filename)
+            .ok_or_else(|| format!("No checksum for {} in {}", download.filename, checksum_url))?;
+        self.set_expected_checksum(download_id, algorithm.as_str(), &digest)
+    }
+
+    /// Downloads the file over HTTP. Large files are fetched in parallel
+    /// segments; a paused transfer continues from its `.part` files with
+    /// range requests when it is resumed.
+    pub async fn transfer(&self, download_id: &str) -> Result<Download, String> {
+        if self.get_download(download_id).is_some_and(|d| d.torrent.is_some()) {
+            return self.transfer_torrent(download_id).await;
+        }
+        if !self.running_transfers.lock().unwrap().insert(download_id.to_string()) {
+            // Still winding down from a pause; it picks the resumed status up
+            return self.get_download(download_id).ok_or_else(|| "Download not found".to_string());
+        }
+        let result = self.transfer_http(download_id).await;
+        self.running_transfers.lock().unwrap().remove(download_id);
+        result
+    }
+
+    async fn transfer_http(&self, download_id: &str) -> Result<Download, String> {
+        // resume_download already marked it as downloading
+        let download = match self.get_download(download_id) {
+            Some(download) if download.status == DownloadStatus::Downloading => download,
+            _ => self.start_download(download_id)?,
+        };
+        if download.status != DownloadStatus::Downloading {
+            return Ok(download);
+        }
+
+        match self.download_segments(&download).await {
+            Ok(Some((total, digest))) => self.complete_download(download_id, total, Some(digest)),
+            Ok(None) => self.get_download(download_id).ok_or_else(|| "Download not found".to_string()),
+            Err(e) => {
+                self.set_download_failed(download_id, e.clone())?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Fetches every unfinished segment, then merges the parts into the
+    /// final file, hashing it on the way. `None` when the transfer was
+    /// paused or cancelled.
+    async fn download_segments(&self, download: &Download) -> Result<Option<(u64, (ChecksumAlgorithm, String))>, String> {
+        let path = PathBuf::from(&download.file_path);
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await.map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+
+        let client = reqwest::Client::new();
+        let mut restarted = false;
+        loop {
+            let current = self.get_download(&download.id).ok_or("Download not found")?;
+            let mut first = None;
+            if current.segments.is_empty() {
+                first = Some(self.plan_segments(&client, &current).await?);
+            }
+            let segment_count = self.get_download(&download.id).map_or(0, |d| d.segments.len());
+            let run = TransferRun {
+                abort: AtomicBool::new(false),
+            };
+
+            let results = futures_util::future::join_all((0..segment_count).map(|index| {
+                let response = if index == 0 { first.take() } else { None };
+                self.fetch_segment(&client, &download.id, index, response, &run)
+            }))
+            .await;
+
+            let mut restart = None;
+            for result in results {
+                match result {
+                    Ok(()) => {}
+                    Err(SegmentError::Restart(reason)) => restart = Some(reason),
+                    Err(SegmentError::Failed(e)) => return Err(e),
+                }
+            }
+            match restart {
+                Some(reason) if restarted => return Err(reason),
+                Some(reason) => {
+                    restarted = true;
+                    self.restart_transfer(&download.id, reason);
+                }
+                None => break,
+            }
+        }
+
+        let current = self.get_download(&download.id).ok_or("Download not found")?;
+        match current.status {
+            DownloadStatus::Downloading => {}
+            DownloadStatus::Paused => return Ok(None),
+            _ => {
+                remove_part_files(&current);
+                self.with_download(&download.id, |d| d.segments.clear());
+                return Ok(None);
+            }
+        }
+        if !current.segments.iter().all(|segment| segment.complete) {
+            return Err("Download ended before all segments finished".to_string());
+        }
+
+        let algorithm = download
+            .checksum_type
+            .as_deref()
+            .and_then(ChecksumAlgorithm::parse)
+            .unwrap_or(ChecksumAlgorithm::Sha256);
+        let (total, digest) = merge_part_files(&current, algorithm).await?;
+        self.with_download(&download.id, |d| d.segments.clear());
+        Ok(Some((total, (algorithm, digest))))
+    }
+
+    /// Opens the transfer with `Range: bytes=0-`. A `206` tells us the size
+    /// and that ranges work, so the file can be split; a `200` means one
+    /// segment that can't be resumed. The response feeds the first segment.
+    async fn plan_segments(&self, client: &reqwest::Client, download: &Download) -> Result<reqwest::Response, String> {
+        let mut request = client.get(&download.url).header(reqwest::header::RANGE, "bytes=0-");
+        if let Some(referrer) = &download.referrer {
+            request = request.header(reqwest::header::REFERER, referrer);
+        }
+        let response = request.send().await.map_err(|e| format!("Request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP error: {}", response.status()));
+        }
+
+        let ranged = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+        let total = if ranged {
+            header_value(&response, reqwest::header::CONTENT_RANGE)
+                .and_then(|range| range.rsplit('/').next()?.parse::<u64>().ok())
+        } else {
+            response.content_length()
+        };
+        let segments = match total {
+            Some(total) if ranged && total > 0 => {
+                let count = u64::from(download.connections.max(1)).min((total / MIN_SEGMENT_SIZE).max(1));
+                let size = total / count;
+                (0..count)
+                    .map(|i| DownloadSegment {
+                        start: i * size,
+                        end: Some(if i + 1 == count { total - 1 } else { (i + 1) * size - 1 }),
+                        downloaded: 0,
+                        complete: false,
+                    })
+                    .collect()
+            }
+            _ => vec![DownloadSegment {
+                start: 0,
+                end: total.filter(|t| *t > 0).map(|t| t - 1),
+                downloaded: 0,
+                complete: false,
+            }],
+        };
+
+        let etag = header_value(&response, reqwest::header::ETAG);
+        let last_modified = header_value(&response, reqwest::header::LAST_MODIFIED);
+        self.with_download(&download.id, |d| {
+            d.resumable = ranged;
+            d.etag = etag;
+            d.last_modified = last_modified;
+            d.total_bytes = total.unwrap_or(0);
+            d.downloaded_bytes = 0;
+            d.segments = segments;
+        });
+        Ok(response)
+    }
+
+    /// Streams one segment into its `.part` file, continuing where it left
+    /// off. Stops quietly when the download is paused or cancelled.
+    async fn fetch_segment(
+        &self,
+        client: &reqwest::Client,
+        download_id: &str,
+        index: usize,
+        response: Option<reqwest::Response>,
+        run: &TransferRun,
+    ) -> Result<(), SegmentError> {
+        let result = self.stream_segment(client, download_id, index, response, run).await;
+        if result.is_err() {
+            run.abort.store(true, Ordering::SeqCst);
+        }
+        result
+    }
+
+    async fn stream_segment(
+        &self,
+        client: &reqwest::Client,
+        download_id: &str,
+        index: usize,
+        response: Option<reqwest::Response>,
+        run: &TransferRun,
+    ) -> Result<(), SegmentError> {
+        let download = self
+            .get_download(download_id)
+            .ok_or_else(|| SegmentError::Failed("Download not found".to_string()))?;
+        let segment = download.segments[index].clone();
+        if segment.complete {
+            return Ok(());
+        }
+
+        let response = match response {
+            Some(response) => response,
+            None => self.request_segment(client, &download, &segment).await?,
+        };
+
+        let part = part_path(&download.file_path, index);
+        let mut file = if segment.downloaded == 0 {
+            tokio::fs::File::create(&part).await
+        } else {
+            // Drop anything written after the last recorded progress
+            match tokio::fs::OpenOptions::new().append(true).open(&part).await {
+                Ok(file) => file.set_len(segment.downloaded).await.map(|_| file),
+                Err(_) => return Err(SegmentError::Restart("Partial data was missing; restarted from the beginning".to_string())),
+            }
+        }
+        .map_err(|e| SegmentError::Failed(format!("Failed to open {}: {}", part.display(), e)))?;
+
+        let mut remaining = segment.remaining();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let status = self.get_download(download_id).map(|d| d.status);
+            if run.abort.load(Ordering::SeqCst) || status != Some(DownloadStatus::Downloading) {
+                break;
+            }
+
+            let chunk = chunk.map_err(|e| SegmentError::Failed(format!("Download error: {}", e)))?;
+            let take = remaining.map_or(chunk.len(), |r| chunk.len().min(r as usize));
+            file.write_all(&chunk[..take])
+                .await
+                .map_err(|e| SegmentError::Failed(format!("Write error: {}", e)))?;
+            if let Some(r) = remaining.as_mut() {
+                *r -= take as u64;
+            }
+            self.record_segment_progress(download_id, index, take as u64, remaining == Some(0));
+            if remaining == Some(0) {
+                break;
+            }
+        }
+        file.flush().await.map_err(|e| SegmentError::Failed(format!("Flush error: {}", e)))?;
+
+        let status = self.get_download(download_id).map(|d| d.status);
+        if run.abort.load(Ordering::SeqCst) || status != Some(DownloadStatus::Downloading) {
+            return Ok(());
+        }
+        match remaining {
+            // The size was unknown, so the end of the body is the end of the file
+            None => {
+                self.record_segment_progress(download_id, index, 0, true);
+                Ok(())
+            }
+            Some(0) => Ok(()),
+            Some(_) => Err(SegmentError::Failed("Connection closed before the download finished".to_string())),
+        }
+    }
+
+    /// Requests the rest of a segment. Range requests carry `If-Range`, so a
+    /// file that changed on the server comes back whole with `200`.
+    async fn request_segment(
+        &self,
+        client: &reqwest::Client,
+        download: &Download,
+        segment: &DownloadSegment,
+    ) -> Result<reqwest::Response, SegmentError> {
+        let mut request = client.get(&download.url);
+        if let Some(referrer) = &download.referrer {
+            request = request.header(reqwest::header::REFERER, referrer);
+        }
+        if download.resumable {
+            let offset = segment.start + segment.downloaded;
+            let range = match segment.end {
+                Some(end) => format!("bytes={}-{}", offset, end),
+                None => format!("bytes={}-", offset),
+            };
+            request = request.header(reqwest::header::RANGE, range);
+            if let Some(validator) = download.etag.as_ref().or(download.last_modified.as_ref()) {
+                request = request.header(reqwest::header::IF_RANGE, validator);
+            }
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| SegmentError::Failed(format!("Request failed: {}", e)))?;
+        let etag = header_value(&response, reqwest::header::ETAG);
+        let changed = download.etag.is_some() && etag.is_some() && download.etag != etag;
+        match response.status() {
+            reqwest::StatusCode::PARTIAL_CONTENT if changed => {
+                Err(SegmentError::Restart("File changed on the server; restarted from the beginning".to_string()))
+            }
+            reqwest::StatusCode::PARTIAL_CONTENT => Ok(response),
+            reqwest::StatusCode::OK if download.resumable => Err(SegmentError::Restart(if changed {
+                "File changed on the server; restarted from the beginning".to_string()
+            } else {
+                "Server ignored the range request; restarted from the beginning".to_string()
+            })),
+            status if status.is_success() => Ok(response),
+            status => Err(SegmentError::Failed(format!("HTTP error: {}", status))),
+        }
+    }
+
+    fn record_segment_progress(&self, download_id: &str, index: usize, written: u64, complete: bool) {
+        self.with_download(download_id, |d| {
+            if let Some(segment) = d.segments.get_mut(index) {
+                segment.downloaded += written;
+                segment.complete |= complete;
+            }
+            let downloaded: u64 = d.segments.iter().map(|s| s.downloaded).sum();
+            d.downloaded_bytes = downloaded;
+            d.total_bytes = d.total_bytes.max(downloaded);
+            self.sample_speed(d, Instant::now(), 0);
+        });
+    }
+
+    /// Throws the partial data away and records why
+    fn restart_transfer(&self, download_id: &str, reason: String) {
+        log::warn!("Download {}: {}", download_id, reason);
+        self.with_download(download_id, |d| {
+            remove_part_files(d);
+            d.segments.clear();
+            d.downloaded_bytes = 0;
+            d.warnings.push(reason);
+        });
+    }
+
+    fn with_download(&self, download_id: &str, update: impl FnOnce(&mut Download)) {
+        if let Some(download) = self.downloads.lock().unwrap().get_mut(download_id) {
+            update(download);
+        }
+    }
+
+    // ==================== Torrents ====================
+
+    /// Runs a torrent through download and seeding. Pausing, resuming,
+    /// cancelling and bandwidth limits go through the usual download entry.
+    async fn transfer_torrent(&self, download_id: &str) -> Result<Download, String> {
+        if self.torrent_sessions.lock().unwrap().contains_key(download_id) {
+            return Err("Torrent is already running".to_string());
+        }
+        let download = self.start_download(download_id)?;
+        if download.status != DownloadStatus::Downloading {
+            return Ok(download);
+        }
+
+        let result = self.run_torrent(download_id).await;
+        self.torrent_sessions.lock().unwrap().remove(download_id);
+        match result {
+            Ok(()) => self.get_download(download_id).ok_or_else(|| "Download not found".to_string()),
+            Err(e) => {
+                if self.get_download(download_id).is_some_and(|d| d.status == DownloadStatus::Completed) {
+                    // Only seeding failed; the files are complete
+                    log::warn!("Stopped seeding {}: {}", download_id, e);
+                    return self.get_download(download_id).ok_or_else(|| "Download not found".to_string());
+                }
+                self.set_download_failed(download_id, e.clone())?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn run_torrent(&self, download_id: &str) -> Result<(), String> {
+        let session = self.torrent_session(download_id).await?;
+        let runner = tokio::spawn(session.clone().run());
+
+        loop {
+            tokio::time::sleep(TORRENT_SYNC_INTERVAL).await;
+            let status = self.get_download(download_id).map(|d| d.status);
+            match status {
+                Some(DownloadStatus::Paused) => session.pause(),
+                Some(DownloadStatus::Cancelled) | Some(DownloadStatus::Failed) | None => session.stop(),
+                Some(_) => session.resume(),
+            }
+
+            let download_limit = self.get_current_bandwidth_limit().unwrap_or(0) * 1024;
+            let upload_limit = self.settings.lock().unwrap().torrent.upload_limit_kbps * 1024;
+            session.set_rate_limits(download_limit, upload_limit);
+
+            let progress = session.progress();
+            self.sync_torrent(download_id, &session, &progress);
+            let completed = status == Some(DownloadStatus::Completed);
+            if !completed && status.is_some() && progress.is_complete() {
+                self.complete_download(download_id, progress.selected_bytes, None)?;
+            }
+            if runner.is_finished() {
+                break;
+            }
+        }
+
+        let result = runner.await.map_err(|e| format!("Torrent task failed: {}", e))?;
+        let progress = session.progress();
+        self.sync_torrent(download_id, &session, &progress);
+        result
+    }
+
+    async fn torrent_session(&self, download_id: &str) -> Result<Arc<TorrentSession>, String> {
+        let existing = self.torrent_sources.lock().unwrap().get(download_id).cloned();
+        let source = match existing {
+            Some(source) => source,
+            None => {
+                let url = self.get_download(download_id).ok_or("Download not found")?.url;
+                let source = TorrentSource::Metainfo(Arc::new(fetch_torrent_file(&url).await?));
+                let blocked = self.settings.lock().unwrap().blocked_extensions.clone();
+                {
+                    let mut downloads = self.downloads.lock().unwrap();
+                    let download = downloads.get_mut(download_id).ok_or("Download not found")?;
+                    rename_torrent_download(download, source.name());
+                    apply_torrent_files(download, source.files(), &blocked)?;
+                    if let Some(torrent) = download.torrent.as_mut() {
+                        torrent.info_hash = hex::encode(source.info_hash());
+                    }
+                }
+                self.torrent_sources.lock().unwrap().insert(download_id.to_string(), source.clone());
+                source
+            }
+        };
+
+        let download = self.get_download(download_id).ok_or("Download not found")?;
+        let settings = self.settings.lock().unwrap().torrent.clone();
+        let root = Path::new(&download.file_path).parent().unwrap_or(Path::new(".")).to_path_buf();
+        let config = TorrentConfig {
+            listen_port: settings.listen_port,
+            max_peers: settings.max_peers.max(1) as usize,
+            seed_ratio_limit: settings.seed_ratio_limit,
+            seed_time_limit: settings.seed_time_limit_minutes.map(|m| std::time::Duration::from_secs(m * 60)),
+        };
+        let session = Arc::new(TorrentSession::new(source, root, config)?);
+        if let Some(selection) = download.torrent.as_ref().map(file_mask).filter(|m| !m.is_empty()) {
+            session.select_files(&selection)?;
+        }
+        self.torrent_sessions.lock().unwrap().insert(download_id.to_string(), session.clone());
+        Ok(session)
+    }
+
+    /// Copies a running torrent's progress into its download entry
+    fn sync_torrent(&self, download_id: &str, session: &TorrentSession, progress: &TorrentProgress) {
+        let blocked = self.settings.lock().unwrap().blocked_extensions.clone();
+        let mut new_selection = None;
+        {
+            let mut downloads = self.downloads.lock().unwrap();
+            let Some(download) = downloads.get_mut(download_id) else { return };
+
+            // A magnet link's metadata just arrived
+            let needs_files = download.torrent.as_ref().is_some_and(|t| t.files.is_empty());
+            if let Some(meta) = session.metainfo().filter(|_| needs_files) {
+                rename_torrent_download(download, Some(meta.name.clone()));
+                match apply_torrent_files(download, &meta.files, &blocked) {
+                    Ok(()) => new_selection = download.torrent.as_ref().map(file_mask),
+                    Err(e) => {
+                        session.stop();
+                        download.error_message = Some(e);
+                    }
+                }
+            }
+
+            let Some(torrent) = download.torrent.as_mut() else { return };
+            torrent.phase = progress.phase;
+            torrent.seeds = progress.seeds;
+            torrent.peers = progress.peers;
+            torrent.uploaded_bytes = progress.uploaded_bytes;
+            torrent.upload_speed_bps = progress.upload_speed;
+            torrent.ratio = if progress.selected_bytes == 0 {
+                0.0
+            } else {
+                progress.uploaded_bytes as f64 / progress.selected_bytes as f64
+            };
+
+            download.connections = progress.peers;
+            if download.status != DownloadStatus::Completed {
+                download.downloaded_bytes = progress.completed_bytes;
+                download.total_bytes = progress.selected_bytes;
+                self.sample_speed(download, Instant::now(), progress.download_speed);
+            }
+        }
+        if let Some(selection) = new_selection {
+            if let Err(e) = session.select_files(&selection) {
+                log::warn!("Could not apply torrent file selection: {}", e);
+            }
+        }
+    }
+
+    /// Chooses which files of a multi-file torrent to download. Adding files
+    /// to a finished torrent sends it back to pending so it can be restarted.
+    pub fn select_torrent_files(&self, download_id: &str, file_indices: Vec<usize>) -> Result<Download, String> {
+        if file_indices.is_empty() {
+            return Err("Select at least one file".to_string());
+        }
+        let (download, selection) = {
+            let mut downloads = self.downloads.lock().unwrap();
+            let download = downloads.get_mut(download_id)
+                .ok_or("Download not found")?;
+            let torrent = download.torrent.as_mut().ok_or("Not a torrent download")?;
+            if torrent.files.is_empty() {
+                return Err("Torrent metadata is not available yet".to_string());
+            }
+            if let Some(index) = file_indices.iter().find(|i| **i >= torrent.files.len()) {
+                return Err(format!("Torrent has no file {}", index));
+            }
+
+            let mut added = false;
+            for file in torrent.files.iter_mut() {
+                let selected = file_indices.contains(&file.index);
+                added |= selected && !file.selected;
+                file.selected = selected;
+            }
+            let selection = file_mask(torrent);
+            download.total_bytes = torrent.files.iter().filter(|f| f.selected).map(|f| f.length).sum();
+            if added && download.status == DownloadStatus::Completed {
+                download.status = DownloadStatus::Pending;
+                download.completed_at = None;
+            }
+            (download.clone(), selection)
+        };
+
+        if let Some(session) = self.torrent_sessions.lock().unwrap().get(download_id) {
+            session.select_files(&selection)?;
+        }
+        Ok(download)
+    }
+
+    /// Ends seeding of a completed torrent before its ratio or time limit
+    pub fn stop_seeding(&self, download_id: &str) -> Result<Download, String> {
+        let download = self.get_download(download_id).ok_or("Download not found")?;
+        if download.torrent.is_none() {
+            return Err("Not a torrent download".to_string());
+        }
+        if download.status != DownloadStatus::Completed {
+            return Err("Torrent is not seeding".to_string());
+        }
+        if let Some(session) = self.torrent_sessions.lock().unwrap().get(download_id) {
+            session.stop();
+        }
+        Ok(download)
     }
 
     pub fn set_download_failed(&self, download_id: &str, error: String) -> Result<(), String> {
@@ -607,7 +1728,7 @@ impl BrowserDownloadsService {
         download.error_message = Some(error);
         
         drop(downloads);
-        self.active_downloads.lock().unwrap().retain(|id| id != download_id);
+        self.deactivate(download_id);
         self.stats.lock().unwrap().failed_downloads += 1;
 
         Ok(())
@@ -623,6 +1744,17 @@ impl BrowserDownloadsService {
         self.downloads.lock().unwrap().values().cloned().collect()
     }
 
+    /// Seconds until an in-progress download finishes; None if it isn't
+    /// downloading or its speed or size is unknown
+    pub fn get_eta(&self, download_id: &str) -> Result<Option<u64>, String> {
+        let download = self.get_download(download_id).ok_or("Download not found")?;
+        Ok(match download.status {
+            DownloadStatus::Completed => Some(0),
+            DownloadStatus::Downloading => download.eta_seconds,
+            _ => None,
+        })
+    }
+
     pub fn get_active_downloads(&self) -> Vec<Download> {
         let downloads = self.downloads.lock().unwrap();
         let active_ids = self.active_downloads.lock().unwrap();
@@ -1051,8 +2183,622 @@ impl BrowserDownloadsService {
     }
 }
 
+fn part_path(file_path: &str, index: usize) -> PathBuf {
+    PathBuf::from(format!("{}.{}.part", file_path, index))
+}
+
+fn remove_part_files(download: &Download) {
+    for index in 0..download.segments.len() {
+        let _ = std::fs::remove_file(part_path(&download.file_path, index));
+    }
+}
+
+fn header_value(response: &reqwest::Response, name: reqwest::header::HeaderName) -> Option<String> {
+    response.headers().get(name)?.to_str().ok().map(str::to_string)
+}
+
+/// Concatenates the segments' `.part` files into the download's file
+async fn merge_part_files(download: &Download, algorithm: ChecksumAlgorithm) -> Result<(u64, String), String> {
+    let mut hasher = ChecksumHasher::new(algorithm);
+    let mut output = tokio::fs::File::create(&download.file_path)
+        .await
+        .map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut total = 0u64;
+
+    for index in 0..download.segments.len() {
+        let part = part_path(&download.file_path, index);
+        let mut input = tokio::fs::File::open(&part)
+            .await
+            .map_err(|e| format!("Failed to open {}: {}", part.display(), e))?;
+        loop {
+            let read = input.read(&mut buffer).await.map_err(|e| format!("Read error: {}", e))?;
+            if read == 0 {
+                break;
+            }
+            output.write_all(&buffer[..read]).await.map_err(|e| format!("Write error: {}", e))?;
+            hasher.update(&buffer[..read]);
+            total += read as u64;
+        }
+    }
+    output.flush().await.map_err(|e| format!("Flush error: {}", e))?;
+    remove_part_files(download);
+
+    Ok((total, hasher.finish()))
+}
+
+fn is_torrent_url(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or("");
+    url.starts_with("magnet:") || path.to_lowercase().ends_with(".torrent")
+}
+
+/// Parses magnet links and .torrent files on disk; remote .torrent URLs
+/// return None
+fn load_local_torrent(url: &str) -> Result<Option<TorrentSource>, String> {
+    if url.starts_with("magnet:") {
+        return parse_magnet(url).map(|m| Some(TorrentSource::Magnet(m)));
+    }
+    let path = match url::Url::parse(url) {
+        Ok(parsed) if parsed.scheme() == "file" => {
+            parsed.to_file_path().map_err(|_| "Invalid torrent file path".to_string())?
+        }
+        Ok(parsed) if parsed.scheme() == "http" || parsed.scheme() == "https" => return Ok(None),
+        _ => PathBuf::from(url),
+    };
+    let data = std::fs::read(&path).map_err(|e| format!("Failed to read torrent file: {}", e))?;
+    Metainfo::from_torrent_bytes(&data).map(|m| Some(TorrentSource::Metainfo(Arc::new(m))))
+}
+
+async fn fetch_torrent_file(url: &str) -> Result<Metainfo, String> {
+    let response = reqwest::get(url).await.map_err(|e| format!("Failed to fetch torrent: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch torrent: HTTP {}", response.status()));
+    }
+    let data = response.bytes().await.map_err(|e| format!("Failed to fetch torrent: {}", e))?;
+    if data.len() > MAX_TORRENT_FILE_SIZE {
+        return Err("Torrent file is too large".to_string());
+    }
+    Metainfo::from_torrent_bytes(&data)
+}
+
+/// Lists a torrent's files on its download, leaving files with blocked
+/// extensions unselected
+fn apply_torrent_files(download: &mut Download, files: &[TorrentFile], blocked_extensions: &[String]) -> Result<(), String> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    let selections: Vec<TorrentFileSelection> = files
+        .iter()
+        .map(|file| {
+            let ext = file.path.rsplit('/').next().unwrap_or("").rsplit('.').next().unwrap_or("").to_lowercase();
+            TorrentFileSelection {
+                index: file.index,
+                path: file.path.clone(),
+                length: file.length,
+                selected: !blocked_extensions.contains(&ext),
+            }
+        })
+        .collect();
+    if !selections.iter().any(|f| f.selected) {
+        return Err("Every file in this torrent has a blocked extension".to_string());
+    }
+
+    download.total_bytes = selections.iter().filter(|f| f.selected).map(|f| f.length).sum();
+    if let Some(torrent) = download.torrent.as_mut() {
+        torrent.files = selections;
+    }
+    Ok(())
+}
+
+fn rename_torrent_download(download: &mut Download, name: Option<String>) {
+    let Some(name) = name else { return };
+    let dir = Path::new(&download.file_path).parent().unwrap_or(Path::new(".")).to_path_buf();
+    download.file_path = dir.join(&name).to_string_lossy().to_string();
+    download.filename = name;
+}
+
+fn file_mask(torrent: &TorrentDetails) -> Vec<bool> {
+    torrent.files.iter().map(|f| f.selected).collect()
+}
+
 impl Default for BrowserDownloadsService {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    /// Serves `body` to a single request
+    async fn serve_once(body: &'static [u8]) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+            socket.write_all(head.as_bytes()).await.unwrap();
+            socket.write_all(body).await.unwrap();
+        });
+        format!("http://{}/app.zip", addr)
+    }
+
+    fn service_in(dir: &Path) -> BrowserDownloadsService {
+        let service = BrowserDownloadsService::new();
+        let mut settings = service.get_settings();
+        settings.default_directory = dir.to_string_lossy().to_string();
+        settings.organize_by_type = false;
+        service.update_settings(settings).unwrap();
+        service
+    }
+
+    #[tokio::test]
+    async fn test_matching_checksum_verifies_download() {
+        let dir = std::env::temp_dir().join(format!("cube_dl_ok_{}", uuid::Uuid::new_v4()));
+        let service = service_in(&dir);
+        let download = service.create_download(serve_once(b"hello world").await, None, None).unwrap();
+        service
+            .set_expected_checksum(&download.id, "SHA-256", "B94D27B9934D3E08A52E52D7DA7DABFAC484EFE37A5380EE9088F7ACE2EFCDE9")
+            .unwrap();
+
+        let done = service.transfer(&download.id).await.unwrap();
+        assert_eq!(done.status, DownloadStatus::Completed);
+        assert_eq!(done.integrity, IntegrityStatus::Verified);
+        assert_eq!(done.downloaded_bytes, 11);
+        assert!(done.quarantine_path.is_none());
+        assert_eq!(std::fs::read(&done.file_path).unwrap(), b"hello world");
+        assert_eq!(service.get_download(&download.id).unwrap().integrity, IntegrityStatus::Verified);
+
+        // Without an expected checksum the download stays unverified
+        let service = service_in(&dir);
+        let download = service.create_download(serve_once(b"other").await, Some("other.bin".to_string()), None).unwrap();
+        let done = service.transfer(&download.id).await.unwrap();
+        assert_eq!(done.integrity, IntegrityStatus::Unverified);
+        assert!(done.computed_checksum.is_some());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch_quarantines_download() {
+        let dir = std::env::temp_dir().join(format!("cube_dl_bad_{}", uuid::Uuid::new_v4()));
+        let service = service_in(&dir);
+        let download = service.create_download(serve_once(b"tampered bytes").await, None, None).unwrap();
+        service
+            .set_expected_checksum(&download.id, "md5", "5eb63bbbe01eeed093cb22bb8f5acdc3")
+            .unwrap();
+        assert!(service.set_expected_checksum(&download.id, "md5", "abc").is_err());
+        assert!(service.set_expected_checksum(&download.id, "crc32", "00000000").is_err());
+
+        let done = service.transfer(&download.id).await.unwrap();
+        assert_eq!(done.integrity, IntegrityStatus::ChecksumMismatch);
+        let quarantined = done.quarantine_path.clone().unwrap();
+        assert!(quarantined.contains(".quarantine"));
+        assert!(!Path::new(&done.file_path).exists());
+        assert_eq!(std::fs::read(&quarantined).unwrap(), b"tampered bytes");
+        assert_eq!(done.computed_checksum.as_deref(), Some(format!("{:x}", md5::compute(b"tampered bytes")).as_str()));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// The file behind a [`serve_ranges`] server, swappable mid-test
+    struct ServedFile {
+        body: Vec<u8>,
+        etag: &'static str,
+    }
+
+    /// Serves a file with ETag and byte-range support. Until `release` is set,
+    /// every response stalls halfway through its body. Returns the URL and
+    /// the `Range` header of each request.
+    async fn serve_ranges(file: Arc<Mutex<ServedFile>>, release: Arc<AtomicBool>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let ranges = Arc::new(Mutex::new(Vec::new()));
+        let seen = ranges.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let (file, release, seen) = (file.clone(), release.clone(), seen.clone());
+                tokio::spawn(async move {
+                    let mut head = Vec::new();
+                    let mut buf = [0u8; 1024];
+                    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                        match socket.read(&mut buf).await {
+                            Ok(0) | Err(_) => return,
+                            Ok(n) => head.extend_from_slice(&buf[..n]),
+                        }
+                    }
+                    let head = String::from_utf8_lossy(&head).to_string();
+                    let header = |name: &str| {
+                        head.lines().find_map(|line| {
+                            let (key, value) = line.split_once(':')?;
+                            key.eq_ignore_ascii_case(name).then(|| value.trim().to_string())
+                        })
+                    };
+                    let (body, etag) = {
+                        let file = file.lock().unwrap();
+                        (file.body.clone(), file.etag)
+                    };
+                    let range = header("range");
+                    seen.lock().unwrap().push(range.clone().unwrap_or_default());
+
+                    let fresh = header("if-range").filter(|validator| validator != etag).is_none();
+                    let span = range.filter(|_| fresh).and_then(|range| {
+                        let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+                        let start: usize = start.parse().ok()?;
+                        let end = end.parse::<usize>().map_or(body.len() - 1, |end| end.min(body.len() - 1));
+                        Some((start, end))
+                    });
+                    let (status, content_range, slice) = match span {
+                        Some((start, end)) => (
+                            "206 Partial Content",
+                            format!("Content-Range: bytes {}-{}/{}\r\n", start, end, body.len()),
+                            &body[start..=end],
+                        ),
+                        None => ("200 OK", String::new(), &body[..]),
+                    };
+                    let reply = format!(
+                        "HTTP/1.1 {}\r\nETag: {}\r\nAccept-Ranges: bytes\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                        status, etag, content_range, slice.len()
+                    );
+                    let half = slice.len() / 2;
+                    if socket.write_all(reply.as_bytes()).await.is_err() || socket.write_all(&slice[..half]).await.is_err() {
+                        return;
+                    }
+                    while !release.load(Ordering::SeqCst) {
+                        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                    }
+                    let _ = socket.write_all(&slice[half..]).await;
+                });
+            }
+        });
+        (format!("http://{}/disk.img", addr), ranges)
+    }
+
+    fn pseudo_random_bytes(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    /// Starts the transfer, waits until `paused_at` bytes are on disk and pauses it
+    async fn transfer_until_paused(service: &Arc<BrowserDownloadsService>, download_id: &str, paused_at: u64, release: &AtomicBool) {
+        let task = {
+            let (service, download_id) = (service.clone(), download_id.to_string());
+            tokio::spawn(async move { service.transfer(&download_id).await })
+        };
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while service.get_download(download_id).unwrap().downloaded_bytes < paused_at {
+            assert!(std::time::Instant::now() < deadline, "transfer stalled");
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        service.pause_download(download_id).unwrap();
+        release.store(true, Ordering::SeqCst);
+        let paused = task.await.unwrap().unwrap();
+        assert_eq!(paused.status, DownloadStatus::Paused);
+        assert_eq!(paused.downloaded_bytes, paused_at);
+    }
+
+    #[tokio::test]
+    async fn test_paused_segmented_download_resumes_with_ranges() {
+        let dir = std::env::temp_dir().join(format!("cube_dl_resume_{}", uuid::Uuid::new_v4()));
+        let service = Arc::new(service_in(&dir));
+        let body = pseudo_random_bytes(1024 * 1024, 7);
+        let file = Arc::new(Mutex::new(ServedFile { body: body.clone(), etag: "\"v1\"" }));
+        let release = Arc::new(AtomicBool::new(false));
+        let (url, ranges) = serve_ranges(file, release.clone()).await;
+
+        let download = service.create_download(url, None, None).unwrap();
+        service.set_max_segments(&download.id, 4).unwrap();
+        assert!(service.set_max_segments(&download.id, 64).is_err());
+
+        // The first segment rides the probe response to completion; the other
+        // three stall halfway through their 256 KiB
+        transfer_until_paused(&service, &download.id, 256 * 1024 + 3 * 128 * 1024, &release).await;
+        let paused = service.get_download(&download.id).unwrap();
+        assert!(paused.resumable);
+        assert_eq!(paused.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(paused.segments.len(), 4);
+        assert!(Path::new(&format!("{}.1.part", paused.file_path)).exists());
+
+        ranges.lock().unwrap().clear();
+        service.resume_download(&download.id).unwrap();
+        let done = service.transfer(&download.id).await.unwrap();
+        assert_eq!(done.status, DownloadStatus::Completed);
+        assert_eq!(done.downloaded_bytes, body.len() as u64);
+        assert!(done.warnings.is_empty());
+        assert!(std::fs::read(&done.file_path).unwrap() == body);
+        assert!(!Path::new(&format!("{}.1.part", done.file_path)).exists());
+
+        let mut resumed = ranges.lock().unwrap().clone();
+        resumed.sort();
+        assert_eq!(resumed, ["bytes=393216-524287", "bytes=655360-786431", "bytes=917504-1048575"]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_resume_restarts_when_file_changed_on_server() {
+        let dir = std::env::temp_dir().join(format!("cube_dl_changed_{}", uuid::Uuid::new_v4()));
+        let service = Arc::new(service_in(&dir));
+        let file = Arc::new(Mutex::new(ServedFile { body: pseudo_random_bytes(512 * 1024, 1), etag: "\"v1\"" }));
+        let release = Arc::new(AtomicBool::new(false));
+        let (url, ranges) = serve_ranges(file.clone(), release.clone()).await;
+
+        let download = service.create_download(url, None, None).unwrap();
+        transfer_until_paused(&service, &download.id, 256 * 1024, &release).await;
+
+        // The If-Range validator no longer matches, so the server answers 200
+        let updated = pseudo_random_bytes(512 * 1024, 2);
+        *file.lock().unwrap() = ServedFile { body: updated.clone(), etag: "\"v2\"" };
+        ranges.lock().unwrap().clear();
+        service.resume_download(&download.id).unwrap();
+        let done = service.transfer(&download.id).await.unwrap();
+
+        assert_eq!(done.status, DownloadStatus::Completed);
+        assert!(std::fs::read(&done.file_path).unwrap() == updated);
+        assert_eq!(done.etag.as_deref(), Some("\"v2\""));
+        assert_eq!(done.warnings.len(), 1);
+        assert!(done.warnings[0].contains("changed on the server"));
+        assert_eq!(*ranges.lock().unwrap(), ["bytes=262144-524287", "bytes=0-"]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    /// Minimal HTTP tracker that hands every announcing peer the others on
+    /// 127.0.0.1. Returns its announce URL and the ports announced so far.
+    async fn serve_tracker() -> (String, Arc<Mutex<Vec<u16>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let ports = Arc::new(Mutex::new(Vec::<u16>::new()));
+        let announced = ports.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let ports = ports.clone();
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 4096];
+                    let n = socket.read(&mut buf).await.unwrap_or(0);
+                    let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                    let port: u16 = request
+                        .split(['?', '&', ' '])
+                        .find_map(|p| p.strip_prefix("port="))
+                        .and_then(|p| p.parse().ok())
+                        .unwrap_or(0);
+                    let mut peers = Vec::new();
+                    {
+                        let mut ports = ports.lock().unwrap();
+                        for other in ports.iter().filter(|p| **p != port) {
+                            peers.extend_from_slice(&[127, 0, 0, 1]);
+                            peers.extend_from_slice(&other.to_be_bytes());
+                        }
+                        if !ports.contains(&port) {
+                            ports.push(port);
+                        }
+                    }
+                    let mut body = format!("d8:intervali30e5:peers{}:", peers.len()).into_bytes();
+                    body.extend_from_slice(&peers);
+                    body.push(b'e');
+                    let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+                    let _ = socket.write_all(head.as_bytes()).await;
+                    let _ = socket.write_all(&body).await;
+                });
+            }
+        });
+        (format!("http://{}/announce", addr), announced)
+    }
+
+    fn tiny_torrent(tracker: &str, name: &str, files: &[(&str, &[u8])]) -> Vec<u8> {
+        use crate::services::torrent_client::Bencode;
+        let piece_length = 16 * 1024;
+        let data: Vec<u8> = files.iter().flat_map(|(_, content)| content.iter().copied()).collect();
+        let pieces: Vec<u8> = data
+            .chunks(piece_length)
+            .flat_map(|chunk| sha1::Sha1::digest(chunk).to_vec())
+            .collect();
+        let file_list = files
+            .iter()
+            .map(|(path, content)| {
+                Bencode::dict(vec![
+                    ("length", Bencode::Int(content.len() as i64)),
+                    ("path", Bencode::List(vec![Bencode::string(path)])),
+                ])
+            })
+            .collect();
+        Bencode::dict(vec![
+            ("announce", Bencode::string(tracker)),
+            (
+                "info",
+                Bencode::dict(vec![
+                    ("files", Bencode::List(file_list)),
+                    ("name", Bencode::string(name)),
+                    ("piece length", Bencode::Int(piece_length as i64)),
+                    ("pieces", Bencode::Bytes(pieces)),
+                ]),
+            ),
+        ])
+        .encode()
+    }
+
+    fn torrent_service_in(dir: &Path, seed_ratio_limit: f64) -> BrowserDownloadsService {
+        let service = service_in(dir);
+        service
+            .set_torrent_settings(TorrentSettings {
+                seed_ratio_limit,
+                listen_port: 0,
+                ..TorrentSettings::default()
+            })
+            .unwrap();
+        service
+    }
+
+    #[tokio::test]
+    async fn test_torrent_download_with_file_selection_from_local_tracker() {
+        let dir = std::env::temp_dir().join(format!("cube_dl_torrent_{}", uuid::Uuid::new_v4()));
+        let (tracker, announced) = serve_tracker().await;
+
+        // The piece at 16-32KiB straddles both files
+        let notes: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        let data: Vec<u8> = (0..30_000u32).map(|i| (i * 7 % 253) as u8).collect();
+        std::fs::create_dir_all(dir.join("seed/pack")).unwrap();
+        std::fs::write(dir.join("seed/pack/notes.txt"), &notes).unwrap();
+        std::fs::write(dir.join("seed/pack/data.bin"), &data).unwrap();
+        let torrent_path = dir.join("pack.torrent");
+        std::fs::write(
+            &torrent_path,
+            tiny_torrent(&tracker, "pack", &[("notes.txt", &notes), ("data.bin", &data)]),
+        )
+        .unwrap();
+        let torrent_url = torrent_path.to_string_lossy().to_string();
+
+        let seeder = Arc::new(torrent_service_in(&dir.join("seed"), 100.0));
+        let seed = seeder.create_download(torrent_url.clone(), None, None).unwrap();
+        let seed_torrent = seed.torrent.clone().unwrap();
+        assert_eq!(seed_torrent.files.len(), 2);
+        assert_eq!(seed.total_bytes, 50_000);
+        let seeding = tokio::spawn({
+            let (seeder, id) = (seeder.clone(), seed.id.clone());
+            async move { seeder.transfer(&id).await }
+        });
+        while announced.lock().unwrap().is_empty() {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+
+        // Download only the second file
+        let leecher = torrent_service_in(&dir.join("leech"), 0.0);
+        let leech = leecher.create_download(torrent_url, None, None).unwrap();
+        assert!(leecher.select_torrent_files(&leech.id, vec![]).is_err());
+        assert!(leecher.select_torrent_files(&leech.id, vec![5]).is_err());
+        let selected = leecher.select_torrent_files(&leech.id, vec![1]).unwrap();
+        assert_eq!(selected.total_bytes, 30_000);
+        let done = tokio::time::timeout(std::time::Duration::from_secs(60), leecher.transfer(&leech.id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(done.status, DownloadStatus::Completed);
+        assert_eq!(done.downloaded_bytes, 30_000);
+        let torrent = done.torrent.unwrap();
+        assert_eq!(torrent.phase, TorrentPhase::Finished);
+        assert!(!torrent.files[0].selected && torrent.files[1].selected);
+        assert_eq!(std::fs::read(dir.join("leech/pack/data.bin")).unwrap(), data);
+        assert!(!dir.join("leech/pack/notes.txt").exists());
+
+        // A magnet link fetches the metadata from the seeder first
+        let magnet = format!("magnet:?xt=urn:btih:{}&dn=pack&tr={}", seed_torrent.info_hash, tracker);
+        let magnet_leecher = torrent_service_in(&dir.join("magnet"), 0.0);
+        let from_magnet = magnet_leecher.create_download(magnet, None, None).unwrap();
+        assert!(from_magnet.torrent.as_ref().unwrap().files.is_empty());
+        let done = tokio::time::timeout(std::time::Duration::from_secs(60), magnet_leecher.transfer(&from_magnet.id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(done.status, DownloadStatus::Completed);
+        assert_eq!(done.torrent.unwrap().files.len(), 2);
+        assert_eq!(std::fs::read(dir.join("magnet/pack/notes.txt")).unwrap(), notes);
+        assert_eq!(std::fs::read(dir.join("magnet/pack/data.bin")).unwrap(), data);
+
+        assert_eq!(seeder.get_download(&seed.id).unwrap().status, DownloadStatus::Completed);
+        seeder.stop_seeding(&seed.id).unwrap();
+        let seeded = seeding.await.unwrap().unwrap();
+        assert!(seeded.torrent.unwrap().uploaded_bytes >= 30_000);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_parse_shasums_file() {
+        let shasums = "\
+# release checksums
+b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9  app-1.0.tar.gz
+a948904f2f0f479b8f8197694b30184b0d2ed1c1cd2a1ec0fb85d299a192a447 *app-1.0.zip
+SHA512 (app-1.0.dmg) = 309ecc489c12d6eb4cc40f50c902f2b4d0ed77ee511a7c7a9bcd3ca86d4cd86f989dd35bc5ff499670da34255b45b0cfd830e81f605dcf7dc5542e93ae9cd76f
+";
+        assert_eq!(
+            parse_checksum_file(shasums, "app-1.0.zip"),
+            Some((ChecksumAlgorithm::Sha256, "a948904f2f0f479b8f8197694b30184b0d2ed1c1cd2a1ec0fb85d299a192a447".to_string()))
+        );
+        assert_eq!(parse_checksum_file(shasums, "app-1.0.tar.gz").unwrap().0, ChecksumAlgorithm::Sha256);
+        assert_eq!(parse_checksum_file(shasums, "app-1.0.dmg").unwrap().0, ChecksumAlgorithm::Sha512);
+        assert!(parse_checksum_file(shasums, "app-1.0.exe").is_none());
+
+        // Per-file .sha256 holding only the digest, and .md5 with a path
+        let single = "B94D27B9934D3E08A52E52D7DA7DABFAC484EFE37A5380EE9088F7ACE2EFCDE9\n";
+        assert_eq!(parse_checksum_file(single, "anything.bin").unwrap().1, "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9");
+        let md5 = "5eb63bbbe01eeed093cb22bb8f5acdc3  ./dist/app.zip";
+        assert_eq!(parse_checksum_file(md5, "app.zip").unwrap().0, ChecksumAlgorithm::Md5);
+    }
+
+    #[test]
+    fn test_speed_window_smooths_spikes() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut window = SpeedWindow::default();
+
+        assert_eq!(window.record(at(0), 0), 0);
+        assert_eq!(window.record(at(1_000), 1_000), 1_000);
+        // A one-second 11 KB burst is averaged with the steady samples
+        assert_eq!(window.record(at(2_000), 12_000), 6_000);
+        assert_eq!(window.record(at(3_000), 13_000), 4_333);
+        // Samples closer together than the minimum interval are skipped
+        assert_eq!(window.record(at(3_100), 13_500), 4_333);
+
+        // Once the burst leaves the ten-sample window the speed settles back
+        let mut downloaded = 13_000;
+        for second in 4..=12 {
+            downloaded += 1_000;
+            window.record(at(second * 1_000), downloaded);
+        }
+        assert_eq!(window.speed_bps(), 1_000);
+
+        // Starting over resets the window
+        assert_eq!(window.record(at(13_000), 0), 0);
+
+        assert_eq!(estimate_eta(10_000, 4_333, true), Some(3));
+        assert_eq!(estimate_eta(10_000, 0, true), None);
+        assert_eq!(estimate_eta(10_000, 4_333, false), None);
+    }
+
+    #[test]
+    fn test_eta_follows_smoothed_speed_and_is_null_without_size() {
+        let service = BrowserDownloadsService::new();
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        for (id, name) in [("dl_sized", "big.iso"), ("dl_chunked", "stream.log")] {
+            let mut download = Download::new(format!("https://example.com/{}", name), name.to_string(), format!("/tmp/{}", name));
+            download.id = id.to_string();
+            download.status = DownloadStatus::Downloading;
+            service.downloads.lock().unwrap().insert(id.to_string(), download);
+            service.active_downloads.lock().unwrap().push(id.to_string());
+        }
+
+        // The reported speed stands in until there are two samples
+        service.update_progress_at("dl_sized", 0, 100_000, 5_000, at(0)).unwrap();
+        assert_eq!(service.get_eta("dl_sized").unwrap(), Some(20));
+        service.update_progress_at("dl_sized", 2_000, 100_000, 90_000, at(1_000)).unwrap();
+        assert_eq!(service.get_eta("dl_sized").unwrap(), Some(49));
+        service.update_progress_at("dl_sized", 10_000, 100_000, 90_000, at(2_000)).unwrap();
+        let sized = service.get_download("dl_sized").unwrap();
+        assert_eq!((sized.speed_bps, sized.eta_seconds), (5_000, Some(18)));
+
+        // No Content-Length: the speed is known but the ETA is not
+        service.update_progress_at("dl_chunked", 0, 0, 0, at(0)).unwrap();
+        service.update_progress_at("dl_chunked", 3_000, 0, 0, at(1_000)).unwrap();
+        let chunked = service.get_download("dl_chunked").unwrap();
+        assert_eq!((chunked.speed_bps, chunked.eta_seconds), (3_000, None));
+        assert_eq!(service.get_eta("dl_chunked").unwrap(), None);
+
+        let active = service.get_active_downloads();
+        assert_eq!(active.iter().find(|d| d.id == "dl_sized").unwrap().eta_seconds, Some(18));
+        assert_eq!(service.get_total_speed(), 8_000);
+
+        service.pause_download("dl_sized").unwrap();
+        assert_eq!(service.get_eta("dl_sized").unwrap(), None);
+        assert!(!service.speed_windows.lock().unwrap().contains_key("dl_sized"));
+    }
+}
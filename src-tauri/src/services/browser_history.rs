@@ -7,6 +7,18 @@ use std::collections::HashMap;
 use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use super::history_fts::{query_terms, HistoryFtsIndex, FTS_CANDIDATE_POOL};
+use super::history_semantic::{HashingEmbedder, HnswIndex, TextEmbedder};
+
+/// Candidate list size for HNSW queries; larger trades speed for recall
+const SEMANTIC_SEARCH_EF: usize = 64;
+
+// Keyword search blends BM25 relevance with how recently and how often a page was visited
+const RELEVANCE_WEIGHT: f64 = 0.6;
+const RECENCY_WEIGHT: f64 = 0.25;
+const FREQUENCY_WEIGHT: f64 = 0.15;
+const RECENCY_HALF_LIFE_DAYS: f64 = 14.0;
+
 // ==================== Enums ====================
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -92,6 +104,9 @@ pub struct HistorySettings {
     pub group_by_domain: bool,
     pub show_previews: bool,
     pub analytics_enabled: bool,
+    /// Embed page content on-device for meaning-based search (off by default)
+    #[serde(default)]
+    pub semantic_search_enabled: bool,
 }
 
 impl Default for HistorySettings {
@@ -111,6 +126,7 @@ impl Default for HistorySettings {
             group_by_domain: true,
             show_previews: true,
             analytics_enabled: true,
+            semantic_search_enabled: false,
         }
     }
 }
@@ -317,6 +333,9 @@ pub struct BrowserHistoryService {
     recently_closed: Mutex<Vec<RecentlyClosed>>,
     current_session_id: Mutex<Option<String>>,
     domain_stats: Mutex<HashMap<String, DomainStats>>,
+    embedder: Box<dyn TextEmbedder>,
+    semantic_index: Mutex<HnswIndex>,
+    text_index: Mutex<HistoryFtsIndex>,
 }
 
 impl BrowserHistoryService {
@@ -328,6 +347,9 @@ impl BrowserHistoryService {
             recently_closed: Mutex::new(Vec::new()),
             current_session_id: Mutex::new(None),
             domain_stats: Mutex::new(HashMap::new()),
+            embedder: Box::new(HashingEmbedder),
+            semantic_index: Mutex::new(HnswIndex::default()),
+            text_index: Mutex::new(HistoryFtsIndex::new().expect("in-memory history index")),
         }
     }
 
@@ -352,7 +374,17 @@ impl BrowserHistoryService {
     }
 
     pub fn update_settings(&self, settings: HistorySettings) -> Result<(), String> {
-        *self.settings.lock().unwrap() = settings;
+        let semantic_enabled = settings.semantic_search_enabled;
+        let was_enabled = std::mem::replace(&mut *self.settings.lock().unwrap(), settings)
+            .semantic_search_enabled;
+
+        if semantic_enabled && !was_enabled {
+            self.rebuild_semantic_index();
+        } else if !semantic_enabled {
+            self.semantic_index.lock().unwrap().clear();
+        } else {
+            self.sync_semantic_index();
+        }
         Ok(())
     }
 
@@ -361,6 +393,8 @@ impl BrowserHistoryService {
         if !settings.excluded_domains.contains(&domain) {
             settings.excluded_domains.push(domain);
         }
+        drop(settings);
+        self.sync_semantic_index();
         Ok(())
     }
 
@@ -415,6 +449,7 @@ impl BrowserHistoryService {
             let entry = existing.clone();
             drop(entries);
             self.update_domain_stats(&entry.domain);
+            self.index_entry(&entry);
             return Ok(entry);
         }
 
@@ -437,6 +472,7 @@ impl BrowserHistoryService {
         
         drop(entries);
         self.update_domain_stats(&domain);
+        self.index_entry(&entry);
         
         Ok(entry)
     }
@@ -453,7 +489,10 @@ impl BrowserHistoryService {
         entry.preview_image = updates.preview_image;
         entry.preview_text = updates.preview_text;
         
-        Ok(entry.clone())
+        let entry = entry.clone();
+        drop(entries);
+        self.index_entry(&entry);
+        Ok(entry)
     }
 
     pub fn update_duration(&self, entry_id: &str, duration_ms: u64) -> Result<(), String> {
@@ -481,18 +520,18 @@ impl BrowserHistoryService {
     pub fn delete_entry(&self, entry_id: &str) -> Result<(), String> {
         self.entries.lock().unwrap().remove(entry_id)
             .ok_or("Entry not found")?;
+        self.unindex_entries(&[entry_id.to_string()]);
         Ok(())
     }
 
     pub fn delete_entries(&self, entry_ids: Vec<String>) -> Result<u32, String> {
         let mut entries = self.entries.lock().unwrap();
-        let mut count = 0;
-        for id in entry_ids {
-            if entries.remove(&id).is_some() {
-                count += 1;
-            }
-        }
-        Ok(count)
+        let removed: Vec<String> = entry_ids.into_iter()
+            .filter(|id| entries.remove(id).is_some())
+            .collect();
+        drop(entries);
+        self.unindex_entries(&removed);
+        Ok(removed.len() as u32)
     }
 
     fn update_domain_stats(&self, domain: &str) {
@@ -672,60 +711,42 @@ impl BrowserHistoryService {
 
     // ==================== Search ====================
 
+    /// Keyword search backed by the full-text index. BM25 picks the candidates;
+    /// they are then ranked by a blend of relevance, recency and visit count.
     pub fn search(&self, query: &str) -> Vec<SearchResult> {
-        let q = query.to_lowercase();
+        let hits = match self.text_index.lock().unwrap().search(query, FTS_CANDIDATE_POOL) {
+            Ok(hits) => hits,
+            Err(e) => {
+                log::warn!("History search failed: {}", e);
+                return Vec::new();
+            }
+        };
+        if hits.is_empty() {
+            return Vec::new();
+        }
+
+        let terms = query_terms(query);
+        let now = self.now();
         let entries = self.entries.lock().unwrap();
-        
-        let mut results: Vec<SearchResult> = entries.values()
-            .filter_map(|e| {
-                let mut score = 0.0;
-                let mut matched_fields = Vec::new();
-                
-                // Title match (highest weight)
-                if e.title.to_lowercase().contains(&q) {
-                    score += 10.0;
-                    matched_fields.push("title".to_string());
-                    if e.title.to_lowercase().starts_with(&q) {
-                        score += 5.0;
-                    }
-                }
-                
-                // URL match
-                if e.url.to_lowercase().contains(&q) {
-                    score += 5.0;
-                    matched_fields.push("url".to_string());
-                }
-                
-                // Domain match
-                if e.domain.to_lowercase().contains(&q) {
-                    score += 3.0;
-                    matched_fields.push("domain".to_string());
-                }
-                
-                // Tags match
-                if e.tags.iter().any(|t| t.to_lowercase().contains(&q)) {
-                    score += 4.0;
-                    matched_fields.push("tags".to_string());
-                }
-                
-                // Boost by visit count
-                score += (e.visit_count as f64).ln();
-                
-                // Boost recent entries
-                let age_days = (self.now() - e.last_visit) / 86400;
-                if age_days < 7 {
-                    score += 2.0;
-                }
+        let matched: Vec<_> = hits.into_iter()
+            .filter_map(|hit| entries.get(&hit.id).map(|e| (hit, e)))
+            .collect();
 
-                if score > 0.0 {
-                    Some(SearchResult {
-                        entry: e.clone(),
-                        score,
-                        matched_fields,
-                        snippet: e.preview_text.clone(),
-                    })
-                } else {
-                    None
+        let max_relevance = matched.iter().map(|(hit, _)| hit.relevance).fold(0.0, f64::max);
+        let max_visits = matched.iter().map(|(_, e)| e.visit_count).max().unwrap_or(0);
+
+        let mut results: Vec<SearchResult> = matched.into_iter()
+            .map(|(hit, e)| {
+                let relevance = if max_relevance > 0.0 { hit.relevance / max_relevance } else { 0.0 };
+                let score = RELEVANCE_WEIGHT * relevance
+                    + RECENCY_WEIGHT * recency_score(now, e.last_visit)
+                    + FREQUENCY_WEIGHT * frequency_score(e.visit_count, max_visits);
+
+                SearchResult {
+                    entry: e.clone(),
+                    score,
+                    matched_fields: matched_fields(e, &terms),
+                    snippet: hit.snippet.or_else(|| e.preview_text.clone()),
                 }
             })
             .collect();
@@ -735,17 +756,145 @@ impl BrowserHistoryService {
     }
 
     pub fn suggest(&self, query: &str, limit: u32) -> Vec<String> {
-        let q = query.to_lowercase();
+        self.search(query)
+            .into_iter()
+            .take(limit as usize)
+            .map(|r| r.entry.url)
+            .collect()
+    }
+
+    // ==================== Full-Text Index ====================
+
+    fn text_index_entry(index: &HistoryFtsIndex, entry: &HistoryEntry) -> Result<(), String> {
+        index.upsert(
+            &entry.id,
+            &entry.title,
+            &entry.url,
+            entry.preview_text.as_deref().unwrap_or(""),
+            &entry.tags.join(" "),
+        )
+    }
+
+    fn unindex_entries(&self, ids: &[String]) {
+        let mut semantic = self.semantic_index.lock().unwrap();
+        let text = self.text_index.lock().unwrap();
+        for id in ids {
+            semantic.remove(id);
+            if let Err(e) = text.remove(id) {
+                log::warn!("{}", e);
+            }
+        }
+    }
+
+    /// Rebuilds the full-text index (and the semantic index, when enabled) from
+    /// the stored entries. Returns the number of entries indexed.
+    pub fn rebuild_index(&self) -> Result<u32, String> {
         let entries = self.entries.lock().unwrap();
-        
-        let mut suggestions: Vec<(String, u32)> = entries.values()
-            .filter(|e| e.url.to_lowercase().contains(&q) || e.title.to_lowercase().contains(&q))
-            .map(|e| (e.url.clone(), e.visit_count))
-            .collect();
+        let index = self.text_index.lock().unwrap();
+        index.clear()?;
+        for entry in entries.values() {
+            Self::text_index_entry(&index, entry)?;
+        }
+        let count = entries.len() as u32;
+        drop(index);
+        drop(entries);
+
+        if self.settings.lock().unwrap().semantic_search_enabled {
+            self.rebuild_semantic_index();
+        }
+        Ok(count)
+    }
+
+    // ==================== Semantic Search ====================
 
-        suggestions.sort_by(|a, b| b.1.cmp(&a.1));
-        suggestions.truncate(limit as usize);
-        suggestions.into_iter().map(|(url, _)| url).collect()
+    fn embedding_text(entry: &HistoryEntry) -> String {
+        format!(
+            "{} {} {} {}",
+            entry.title,
+            entry.url,
+            entry.preview_text.as_deref().unwrap_or(""),
+            entry.tags.join(" ")
+        )
+    }
+
+    fn is_semantically_indexable(settings: &HistorySettings, entry: &HistoryEntry) -> bool {
+        settings.semantic_search_enabled && !settings.excluded_domains.contains(&entry.domain)
+    }
+
+    fn index_entry(&self, entry: &HistoryEntry) {
+        if let Err(e) = Self::text_index_entry(&self.text_index.lock().unwrap(), entry) {
+            log::warn!("{}", e);
+        }
+
+        let settings = self.settings.lock().unwrap();
+        let indexable = Self::is_semantically_indexable(&settings, entry);
+        drop(settings);
+
+        let mut index = self.semantic_index.lock().unwrap();
+        if indexable {
+            index.insert(&entry.id, self.embedder.embed(&Self::embedding_text(entry)));
+        } else {
+            index.remove(&entry.id);
+        }
+    }
+
+    fn rebuild_semantic_index(&self) {
+        let settings = self.settings.lock().unwrap().clone();
+        let entries = self.entries.lock().unwrap();
+        let mut index = self.semantic_index.lock().unwrap();
+
+        index.clear();
+        for entry in entries.values().filter(|e| Self::is_semantically_indexable(&settings, e)) {
+            index.insert(&entry.id, self.embedder.embed(&Self::embedding_text(entry)));
+        }
+    }
+
+    /// Drops index nodes whose entry was deleted or whose domain is now excluded.
+    fn sync_semantic_index(&self) {
+        let settings = self.settings.lock().unwrap().clone();
+        let entries = self.entries.lock().unwrap();
+        let mut index = self.semantic_index.lock().unwrap();
+
+        for id in index.ids() {
+            let keep = entries
+                .get(&id)
+                .map(|e| Self::is_semantically_indexable(&settings, e))
+                .unwrap_or(false);
+            if !keep {
+                index.remove(&id);
+            }
+        }
+    }
+
+    /// Nearest history entries to `query` by cosine similarity of on-device
+    /// embeddings. Falls back to keyword search when semantic search is disabled.
+    pub fn semantic_search(&self, query: &str, k: u32) -> Vec<SearchResult> {
+        if !self.settings.lock().unwrap().semantic_search_enabled {
+            let mut results = self.search(query);
+            results.truncate(k as usize);
+            return results;
+        }
+
+        self.sync_semantic_index();
+        let query_vector = self.embedder.embed(query);
+        let hits = self
+            .semantic_index
+            .lock()
+            .unwrap()
+            .search(&query_vector, k as usize, SEMANTIC_SEARCH_EF);
+
+        let entries = self.entries.lock().unwrap();
+        hits.into_iter()
+            .filter(|(_, similarity)| *similarity > 0.0)
+            .filter_map(|(id, similarity)| {
+                entries.get(&id).map(|e| SearchResult {
+                    entry: e.clone(),
+                    score: similarity as f64,
+                    matched_fields: vec!["semantic".to_string()],
+                    snippet: e.preview_text.clone(),
+                })
+            })
+            .collect()
     }
 
     // ==================== Tags ====================
@@ -757,6 +906,9 @@ impl BrowserHistoryService {
         if !entry.tags.contains(&tag) {
             entry.tags.push(tag);
         }
+        let entry = entry.clone();
+        drop(entries);
+        self.index_entry(&entry);
         Ok(())
     }
 
@@ -765,6 +917,9 @@ impl BrowserHistoryService {
         let entry = entries.get_mut(entry_id)
             .ok_or("Entry not found")?;
         entry.tags.retain(|t| t != tag);
+        let entry = entry.clone();
+        drop(entries);
+        self.index_entry(&entry);
         Ok(())
     }
 
@@ -945,6 +1100,26 @@ impl BrowserHistoryService {
             .collect()
     }
 
+    // ==================== Link Graph ====================
+
+    /// Pages reached from `from_url`, with how often each link was followed
+    pub fn get_link_transitions(&self, from_url: &str) -> Vec<(String, u32)> {
+        let entries = self.entries.lock().unwrap();
+
+        let mut transitions: Vec<(String, u32)> = entries.values()
+            .filter(|e| e.url != from_url)
+            .filter_map(|e| {
+                let followed = e.visits.iter()
+                    .filter(|v| v.from_url.as_deref() == Some(from_url))
+                    .count() as u32;
+                let followed = if followed == 0 && e.referrer.as_deref() == Some(from_url) { 1 } else { followed };
+                (followed > 0).then(|| (e.url.clone(), followed))
+            })
+            .collect();
+        transitions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        transitions
+    }
+
     // ==================== Statistics ====================
 
     pub fn get_stats(&self) -> HistoryStats {
@@ -1045,13 +1220,82 @@ impl BrowserHistoryService {
             .collect();
         
         let count = to_remove.len() as u32;
-        for id in to_remove {
-            entries.remove(&id);
+        for id in &to_remove {
+            entries.remove(id);
         }
+        drop(entries);
+        self.unindex_entries(&to_remove);
         
         Ok(count)
     }
 
+    /// Deletes every visit with `start <= timestamp < end` (Chrome's delete-between
+    /// semantics). Entries left without visits are removed; others have their counters
+    /// recomputed. Recently closed tabs and session references in the window go too.
+    /// Returns the number of entries removed or trimmed.
+    pub fn clear_range(&self, start: u64, end: u64) -> Result<u32, String> {
+        if start >= end {
+            return Err("Range start must be before its end".to_string());
+        }
+        let in_range = |timestamp: u64| timestamp >= start && timestamp < end;
+
+        let mut entries = self.entries.lock().unwrap();
+        let mut cleared = 0u32;
+        let mut removed_ids = Vec::new();
+        let mut touched_domains = Vec::new();
+
+        for entry in entries.values_mut() {
+            let before = entry.visits.len();
+            let affected = if before == 0 {
+                in_range(entry.last_visit)
+            } else {
+                entry.visits.retain(|v| !in_range(v.timestamp));
+                entry.visits.len() != before
+            };
+            if !affected {
+                continue;
+            }
+
+            cleared += 1;
+            touched_domains.push(entry.domain.clone());
+            if entry.visits.is_empty() {
+                removed_ids.push(entry.id.clone());
+            } else {
+                entry.visit_count = entry.visits.len() as u32;
+                entry.first_visit = entry.visits.iter().map(|v| v.timestamp).min().unwrap_or(entry.first_visit);
+                entry.last_visit = entry.visits.iter().map(|v| v.timestamp).max().unwrap_or(entry.last_visit);
+                entry.total_duration_ms = entry.visits.iter().map(|v| v.duration_ms).sum();
+            }
+        }
+
+        for id in &removed_ids {
+            entries.remove(id);
+        }
+        drop(entries);
+        self.unindex_entries(&removed_ids);
+
+        self.recently_closed.lock().unwrap().retain(|r| !in_range(r.closed_at));
+        for session in self.sessions.lock().unwrap().values_mut() {
+            session.entry_ids.retain(|id| !removed_ids.contains(id));
+        }
+
+        touched_domains.sort();
+        touched_domains.dedup();
+        for domain in touched_domains {
+            self.update_domain_stats(&domain);
+        }
+
+        Ok(cleared)
+    }
+
+    /// Clears everything visited in the last `duration_secs`, up to and including now.
+    pub fn clear_last(&self, duration_secs: u64) -> Result<u32, String> {
+        if duration_secs == 0 {
+            return Err("Duration must be greater than 0".to_string());
+        }
+        self.clear_range(self.now().saturating_sub(duration_secs), u64::MAX)
+    }
+
     pub fn clear_domain(&self, domain: &str) -> Result<u32, String> {
         let mut entries = self.entries.lock().unwrap();
         let to_remove: Vec<String> = entries.values()
@@ -1060,11 +1304,12 @@ impl BrowserHistoryService {
             .collect();
         
         let count = to_remove.len() as u32;
-        for id in to_remove {
-            entries.remove(&id);
+        for id in &to_remove {
+            entries.remove(id);
         }
-        
         drop(entries);
+        self.unindex_entries(&to_remove);
+        
         self.domain_stats.lock().unwrap().remove(domain);
         
         Ok(count)
@@ -1084,9 +1329,11 @@ impl BrowserHistoryService {
             .collect();
         
         let count = to_remove.len() as u32;
-        for id in to_remove {
-            entries.remove(&id);
+        for id in &to_remove {
+            entries.remove(id);
         }
+        drop(entries);
+        self.unindex_entries(&to_remove);
         
         Ok(count)
     }
@@ -1113,6 +1360,9 @@ impl BrowserHistoryService {
         for entry in imports {
             entries.insert(entry.id.clone(), entry);
         }
+        drop(entries);
+
+        self.rebuild_index()?;
         
         Ok(count)
     }
@@ -1123,3 +1373,233 @@ impl Default for BrowserHistoryService {
         Self::new()
     }
 }
+
+/// 1.0 for a visit just now, halving every `RECENCY_HALF_LIFE_DAYS`
+fn recency_score(now: u64, last_visit: u64) -> f64 {
+    let age_days = now.saturating_sub(last_visit) as f64 / 86400.0;
+    0.5f64.powf(age_days / RECENCY_HALF_LIFE_DAYS)
+}
+
+/// Visit count on a log scale, relative to the most visited candidate
+fn frequency_score(visit_count: u32, max_visits: u32) -> f64 {
+    if max_visits == 0 {
+        return 0.0;
+    }
+    (1.0 + visit_count as f64).ln() / (1.0 + max_visits as f64).ln()
+}
+
+fn matched_fields(entry: &HistoryEntry, terms: &[String]) -> Vec<String> {
+    let matches = |text: &str| {
+        let text = text.to_lowercase();
+        terms.iter().any(|t| text.contains(t.as_str()))
+    };
+
+    let mut fields = Vec::new();
+    if matches(&entry.title) {
+        fields.push("title".to_string());
+    }
+    if matches(&entry.url) {
+        fields.push("url".to_string());
+    }
+    if entry.preview_text.as_deref().is_some_and(matches) {
+        fields.push("content".to_string());
+    }
+    if entry.tags.iter().any(|t| matches(t)) {
+        fields.push("tags".to_string());
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visit(timestamp: u64) -> Visit {
+        Visit {
+            id: format!("visit_{}", timestamp),
+            timestamp,
+            visit_type: VisitType::Link,
+            duration_ms: 0,
+            from_url: None,
+            session_id: None,
+            tab_id: None,
+        }
+    }
+
+    fn insert_entry(service: &BrowserHistoryService, id: &str, url: &str, timestamps: &[u64]) {
+        let mut entry = HistoryEntry::new(url.to_string(), id.to_string());
+        entry.id = id.to_string();
+        entry.visits = timestamps.iter().map(|t| visit(*t)).collect();
+        entry.visit_count = timestamps.len() as u32;
+        entry.first_visit = *timestamps.iter().min().unwrap();
+        entry.last_visit = *timestamps.iter().max().unwrap();
+        service.index_entry(&entry);
+        service.entries.lock().unwrap().insert(id.to_string(), entry);
+    }
+
+    fn set_text(service: &BrowserHistoryService, id: &str, title: &str, preview: Option<&str>) {
+        let mut entries = service.entries.lock().unwrap();
+        let entry = entries.get_mut(id).unwrap();
+        entry.title = title.to_string();
+        entry.preview_text = preview.map(str::to_string);
+        let entry = entry.clone();
+        drop(entries);
+        service.index_entry(&entry);
+    }
+
+    #[test]
+    fn test_clear_range_boundaries() {
+        let service = BrowserHistoryService::new();
+        insert_entry(&service, "before", "https://a.com/1", &[999]);
+        insert_entry(&service, "at_start", "https://a.com/2", &[1000]);
+        insert_entry(&service, "inside", "https://b.com/", &[1500]);
+        insert_entry(&service, "at_end", "https://c.com/", &[2000]);
+
+        assert_eq!(service.clear_range(1000, 2000).unwrap(), 2);
+
+        let remaining: Vec<String> = {
+            let mut ids: Vec<String> = service.get_all_entries().into_iter().map(|e| e.id).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(remaining, vec!["at_end".to_string(), "before".to_string()]);
+        assert!(service.clear_range(2000, 2000).is_err());
+    }
+
+    #[test]
+    fn test_clear_range_trims_visits_and_related_data() {
+        let service = BrowserHistoryService::new();
+        insert_entry(&service, "mixed", "https://a.com/", &[100, 1500, 3000]);
+        service.recently_closed.lock().unwrap().extend([
+            RecentlyClosed {
+                id: "closed_in".to_string(),
+                url: "https://a.com/".to_string(),
+                title: "A".to_string(),
+                favicon_url: None,
+                closed_at: 1200,
+                tab_id: None,
+                session_id: None,
+            },
+            RecentlyClosed {
+                id: "closed_out".to_string(),
+                url: "https://a.com/".to_string(),
+                title: "A".to_string(),
+                favicon_url: None,
+                closed_at: 5000,
+                tab_id: None,
+                session_id: None,
+            },
+        ]);
+
+        assert_eq!(service.clear_range(1000, 2000).unwrap(), 1);
+
+        let entry = service.get_entry("mixed").unwrap();
+        assert_eq!(entry.visit_count, 2);
+        assert_eq!((entry.first_visit, entry.last_visit), (100, 3000));
+        assert_eq!(service.get_domain_stats("a.com").unwrap().visit_count, 2);
+
+        let closed: Vec<String> = service.get_recently_closed(10).into_iter().map(|r| r.id).collect();
+        assert_eq!(closed, vec!["closed_out".to_string()]);
+    }
+
+    #[test]
+    fn test_semantic_search_ranks_related_page_above_keyword_match() {
+        let service = BrowserHistoryService::new();
+        insert_entry(&service, "tokio", "https://tokio.rs/", &[1000]);
+        insert_entry(&service, "car_rust", "https://cars.example.com/rust", &[1000]);
+        insert_entry(&service, "writing", "https://blog.example.com/writing-an-article", &[1000]);
+        {
+            let mut entries = service.entries.lock().unwrap();
+            let tokio = entries.get_mut("tokio").unwrap();
+            tokio.title = "Tokio - An asynchronous Rust runtime".to_string();
+            tokio.preview_text = Some("Tokio is an event-driven, non-blocking I/O platform for writing asynchronous applications with Rust. It provides a runtime, executors and futures.".to_string());
+            let car = entries.get_mut("car_rust").unwrap();
+            car.title = "Removing rust from car parts".to_string();
+            car.preview_text = Some("Sanding and treating rust on your car body.".to_string());
+            entries.get_mut("writing").unwrap().title = "How to write a great article".to_string();
+        }
+
+        // Disabled: keyword search only finds the literal "rust" match
+        let keyword = service.semantic_search("rust", 5);
+        assert!(keyword.iter().all(|r| r.matched_fields != vec!["semantic".to_string()]));
+
+        let mut settings = service.get_settings();
+        settings.semantic_search_enabled = true;
+        service.update_settings(settings).unwrap();
+
+        let results = service.semantic_search("that article about rust async runtimes", 3);
+        assert_eq!(results[0].entry.id, "tokio");
+        let tokio_rank = results.iter().position(|r| r.entry.id == "tokio").unwrap();
+        let car_rank = results.iter().position(|r| r.entry.id == "car_rust").unwrap();
+        assert!(tokio_rank < car_rank);
+
+        service.add_excluded_domain("tokio.rs".to_string()).unwrap();
+        let results = service.semantic_search("rust async runtimes", 3);
+        assert!(results.iter().all(|r| r.entry.id != "tokio"));
+    }
+
+    #[test]
+    fn test_search_ranks_recent_title_match_above_stale_match() {
+        let service = BrowserHistoryService::new();
+        let now = service.now();
+        let year_ago = now - 400 * 86400;
+        insert_entry(&service, "book", "https://doc.rust-lang.org/book/", &[now - 3600]);
+        insert_entry(&service, "stale_title", "https://tips.example.com/", &[year_ago]);
+        insert_entry(&service, "stale_body", "https://forum.example.com/t/42",
+            &[year_ago, year_ago + 60, year_ago + 120, year_ago + 180]);
+        insert_entry(&service, "unrelated", "https://news.example.com/", &[now]);
+        set_text(&service, "book", "The Rust Programming Language", Some("An introductory book about Rust."));
+        set_text(&service, "stale_title", "Rust tips", None);
+        set_text(&service, "stale_body", "Forum thread", Some("Someone asked about rust on old bikes."));
+        set_text(&service, "unrelated", "Daily news", Some("Weather and sports."));
+
+        let results = service.search("rust");
+        let ids: Vec<&str> = results.iter().map(|r| r.entry.id.as_str()).collect();
+        assert_eq!(ids[0], "book");
+        assert_eq!(ids.len(), 3);
+        assert!(!ids.contains(&"unrelated"));
+        assert_eq!(results[0].matched_fields, vec!["title".to_string(), "url".to_string(), "content".to_string()]);
+
+        // Prefix match on the last term, and suggestions follow the same order
+        assert_eq!(service.suggest("programming lang", 5), vec!["https://doc.rust-lang.org/book/".to_string()]);
+        assert_eq!(service.suggest("rus", 1), vec!["https://doc.rust-lang.org/book/".to_string()]);
+
+        service.delete_entry("book").unwrap();
+        assert!(service.search("rust").iter().all(|r| r.entry.id != "book"));
+    }
+
+    #[test]
+    fn test_index_follows_add_entry_and_rebuild() {
+        let service = BrowserHistoryService::new();
+        let entry = service
+            .add_entry("https://tokio.rs/".to_string(), "Tokio runtime".to_string(), VisitType::Typed)
+            .unwrap();
+        assert_eq!(service.search("tokio")[0].entry.id, entry.id);
+
+        // Renamed title is searchable after a revisit
+        service
+            .add_entry("https://tokio.rs/".to_string(), "Asynchronous Rust".to_string(), VisitType::Link)
+            .unwrap();
+        assert!(service.search("runtime").is_empty());
+        assert_eq!(service.search("asynchronous").len(), 1);
+
+        service.text_index.lock().unwrap().clear().unwrap();
+        assert!(service.search("asynchronous").is_empty());
+        assert_eq!(service.rebuild_index().unwrap(), 1);
+        assert_eq!(service.search("asynchronous").len(), 1);
+        // FTS5 syntax in the query is matched literally
+        assert!(service.search("\"rust\" OR NEAR(").is_empty());
+    }
+
+    #[test]
+    fn test_clear_last_includes_now() {
+        let service = BrowserHistoryService::new();
+        let now = service.now();
+        insert_entry(&service, "old", "https://old.com/", &[now - 7200]);
+        insert_entry(&service, "recent", "https://new.com/", &[now - 3600, now]);
+
+        assert_eq!(service.clear_last(3600).unwrap(), 1);
+        assert!(service.get_entry("recent").is_none());
+        assert!(service.get_entry("old").is_some());
+    }
+}
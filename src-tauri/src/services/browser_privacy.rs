@@ -34,6 +34,19 @@ pub enum CookiePolicy {
     Custom,
 }
 
+/// Per-site cookie lifetime rule, applied on top of `cookie_policy`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SiteCookiePolicy {
+    #[default]
+    Allow,
+    /// Deleted when the site's last tab closes or the browser closes
+    SessionOnly,
+    /// Deleted this long after the cookie was first set
+    AutoExpireAfter { seconds: u64 },
+    Block,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PrivacySettings {
     pub privacy_level: PrivacyLevel,
@@ -48,6 +61,17 @@ pub struct PrivacySettings {
     pub block_third_party_cookies: bool,
     pub clear_cookies_on_exit: bool,
     pub cookie_lifetime_days: Option<u32>,
+    #[serde(default)]
+    pub default_site_cookie_policy: SiteCookiePolicy,
+    /// Overrides by domain; a rule also covers the domain's subdomains
+    #[serde(default)]
+    pub site_cookie_policies: HashMap<String, SiteCookiePolicy>,
+    /// "Keep me signed in" sites: the default policy and clearing on exit
+    /// skip them, but an explicit override still applies
+    #[serde(default)]
+    pub keep_signed_in_sites: Vec<String>,
+    #[serde(default = "default_cookie_sweep_interval")]
+    pub cookie_sweep_interval_minutes: u32,
     // Fingerprinting
     pub randomize_canvas: bool,
     pub randomize_webgl: bool,
@@ -96,6 +120,10 @@ impl Default for PrivacySettings {
             block_third_party_cookies: true,
             clear_cookies_on_exit: false,
             cookie_lifetime_days: None,
+            default_site_cookie_policy: SiteCookiePolicy::Allow,
+            site_cookie_policies: HashMap::new(),
+            keep_signed_in_sites: Vec::new(),
+            cookie_sweep_interval_minutes: default_cookie_sweep_interval(),
             randomize_canvas: true,
             randomize_webgl: true,
             randomize_audio: true,
@@ -127,6 +155,10 @@ impl Default for PrivacySettings {
     }
 }
 
+fn default_cookie_sweep_interval() -> u32 {
+    15
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum PermissionDefault {
     Allow,
@@ -260,6 +292,8 @@ pub struct PrivacyDashboardService {
     fingerprint_protection: Mutex<FingerprintProtection>,
     site_permissions: Mutex<HashMap<String, SitePermissions>>,
     stats: Mutex<PrivacyStats>,
+    /// Site open in each tab, for session-only cookies
+    open_tabs: Mutex<HashMap<String, String>>,
 }
 
 impl PrivacyDashboardService {
@@ -285,6 +319,7 @@ impl PrivacyDashboardService {
                 top_blocked_domains: Vec::new(),
                 protection_score: 85,
             }),
+            open_tabs: Mutex::new(HashMap::new()),
         }
     }
 
@@ -446,9 +481,30 @@ impl PrivacyDashboardService {
 
     // ==================== Cookie Management ====================
 
-    pub fn add_cookie(&self, cookie: Cookie) -> Result<(), String> {
+    /// Stores a cookie under its site's cookie policy: blocked sites are
+    /// refused, session-only cookies lose their expiry and auto-expiring ones
+    /// are capped. Re-adding a cookie keeps its original creation time.
+    pub fn add_cookie(&self, mut cookie: Cookie) -> Result<(), String> {
+        let policy = self.site_cookie_policy(&cookie.domain);
+        if policy == SiteCookiePolicy::Block {
+            self.stats.lock().unwrap().cookies_blocked_total += 1;
+            return Err(format!("Cookies are blocked for {}", cookie.domain));
+        }
+
         let key = format!("{}:{}", cookie.domain, cookie.name);
-        self.cookies.lock().unwrap().insert(key, cookie);
+        let mut cookies = self.cookies.lock().unwrap();
+        if let Some(existing) = cookies.get(&key) {
+            cookie.created_at = existing.created_at;
+        }
+        match policy {
+            SiteCookiePolicy::SessionOnly => cookie.expires = None,
+            SiteCookiePolicy::AutoExpireAfter { seconds } => {
+                let limit = cookie.created_at + Duration::seconds(seconds as i64);
+                cookie.expires = Some(cookie.expires.map_or(limit, |e| e.min(limit)));
+            }
+            SiteCookiePolicy::Allow | SiteCookiePolicy::Block => {}
+        }
+        cookies.insert(key, cookie);
         Ok(())
     }
 
@@ -565,6 +621,130 @@ impl PrivacyDashboardService {
         Ok(())
     }
 
+    // ==================== Cookie Policies ====================
+
+    /// Sets the cookie policy for a domain and its subdomains
+    pub fn set_cookie_policy(&self, domain: &str, policy: SiteCookiePolicy) -> Result<(), String> {
+        validate_cookie_policy(&policy)?;
+        let domain = normalize_site(domain).ok_or("Invalid domain")?;
+        self.settings.lock().unwrap().site_cookie_policies.insert(domain.clone(), policy.clone());
+        if policy == SiteCookiePolicy::Block {
+            self.delete_cookies_for_domain(&domain);
+        }
+        Ok(())
+    }
+
+    /// Removes a domain's override so the default policy applies again
+    pub fn clear_cookie_policy(&self, domain: &str) -> Result<(), String> {
+        let domain = normalize_site(domain).ok_or("Invalid domain")?;
+        self.settings.lock().unwrap().site_cookie_policies.remove(&domain)
+            .map(|_| ())
+            .ok_or_else(|| "No cookie policy for this domain".to_string())
+    }
+
+    pub fn set_default_cookie_policy(&self, policy: SiteCookiePolicy) -> Result<(), String> {
+        validate_cookie_policy(&policy)?;
+        self.settings.lock().unwrap().default_site_cookie_policy = policy;
+        Ok(())
+    }
+
+    pub fn set_keep_signed_in(&self, domain: &str, keep: bool) -> Result<(), String> {
+        let domain = normalize_site(domain).ok_or("Invalid domain")?;
+        let mut settings = self.settings.lock().unwrap();
+        settings.keep_signed_in_sites.retain(|s| s != &domain);
+        if keep {
+            settings.keep_signed_in_sites.push(domain);
+        }
+        Ok(())
+    }
+
+    /// The policy in force for a cookie domain: the most specific override,
+    /// then a "cookies" site permission of false, then the default unless
+    /// the site is kept signed in
+    pub fn site_cookie_policy(&self, domain: &str) -> SiteCookiePolicy {
+        let Some(domain) = normalize_site(domain) else {
+            return SiteCookiePolicy::Allow;
+        };
+        let settings = self.settings.lock().unwrap();
+        if let Some(policy) = domain_suffixes(&domain).find_map(|d| settings.site_cookie_policies.get(d)) {
+            return policy.clone();
+        }
+        let permissions = self.site_permissions.lock().unwrap();
+        if domain_suffixes(&domain).any(|d| permissions.get(d).is_some_and(|p| p.cookies == Some(false))) {
+            return SiteCookiePolicy::Block;
+        }
+        if is_kept_signed_in(&settings, &domain) {
+            return SiteCookiePolicy::Allow;
+        }
+        settings.default_site_cookie_policy.clone()
+    }
+
+    /// Records the site a tab has navigated to
+    pub fn on_tab_navigated(&self, tab_id: &str, url: &str) {
+        let site = url::Url::parse(url).ok()
+            .and_then(|u| u.host_str().and_then(normalize_site));
+        let mut tabs = self.open_tabs.lock().unwrap();
+        match site {
+            Some(site) => { tabs.insert(tab_id.to_string(), site); }
+            None => { tabs.remove(tab_id); }
+        }
+    }
+
+    /// Deletes the closed tab's session-only cookies once no other tab has
+    /// the site open. Returns the deleted cookies.
+    pub fn on_tab_closed(&self, tab_id: &str) -> Vec<Cookie> {
+        let (site, still_open) = {
+            let mut tabs = self.open_tabs.lock().unwrap();
+            let Some(site) = tabs.remove(tab_id) else { return Vec::new() };
+            (site, tabs.values().cloned().collect::<Vec<_>>())
+        };
+        self.remove_cookies_where(|cookie, policy| {
+            let cookie_site = cookie_site(cookie);
+            *policy == SiteCookiePolicy::SessionOnly
+                && same_site(&cookie_site, &site)
+                && !still_open.iter().any(|open| same_site(&cookie_site, open))
+        })
+    }
+
+    /// Deletes session-only cookies, and with `clear_cookies_on_exit` every
+    /// cookie not kept signed in. Returns the deleted cookies.
+    pub fn on_browser_closed(&self) -> Vec<Cookie> {
+        self.open_tabs.lock().unwrap().clear();
+        let settings = self.get_settings();
+        self.remove_cookies_where(|cookie, policy| {
+            *policy == SiteCookiePolicy::SessionOnly
+                || (settings.clear_cookies_on_exit && !is_kept_signed_in(&settings, &cookie_site(cookie)))
+        })
+    }
+
+    /// Deletes expired and blocked cookies, including ones whose policy was
+    /// tightened after they were set. Returns the deleted cookies.
+    pub fn sweep_cookies(&self, now: DateTime<Utc>) -> Vec<Cookie> {
+        self.remove_cookies_where(|cookie, policy| {
+            cookie.expires.is_some_and(|expires| expires <= now)
+                || match policy {
+                    SiteCookiePolicy::Block => true,
+                    SiteCookiePolicy::AutoExpireAfter { seconds } => {
+                        cookie.created_at + Duration::seconds(*seconds as i64) <= now
+                    }
+                    SiteCookiePolicy::Allow | SiteCookiePolicy::SessionOnly => false,
+                }
+        })
+    }
+
+    fn remove_cookies_where(&self, should_remove: impl Fn(&Cookie, &SiteCookiePolicy) -> bool) -> Vec<Cookie> {
+        let snapshot = self.get_cookies();
+        let doomed: Vec<Cookie> = snapshot
+            .into_iter()
+            .filter(|cookie| should_remove(cookie, &self.site_cookie_policy(&cookie.domain)))
+            .collect();
+        let mut cookies = self.cookies.lock().unwrap();
+        for cookie in &doomed {
+            cookies.remove(&format!("{}:{}", cookie.domain, cookie.name));
+        }
+        doomed
+    }
+
     // ==================== Site Permissions ====================
 
     pub fn get_site_permissions(&self, domain: &str) -> Option<SitePermissions> {
@@ -800,6 +980,40 @@ impl PrivacyDashboardService {
     }
 }
 
+fn validate_cookie_policy(policy: &SiteCookiePolicy) -> Result<(), String> {
+    match policy {
+        SiteCookiePolicy::AutoExpireAfter { seconds } if *seconds == 0 || *seconds > i64::MAX as u64 / 1000 => {
+            Err("Auto-expiry duration is out of range".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Lowercased host without a leading dot or "www."
+fn normalize_site(domain: &str) -> Option<String> {
+    let domain = domain.trim().trim_start_matches('.').to_lowercase();
+    let domain = domain.strip_prefix("www.").unwrap_or(&domain).to_string();
+    (!domain.is_empty()).then_some(domain)
+}
+
+/// "a.example.com", "example.com", "com"
+fn domain_suffixes(domain: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(domain), |d| d.split_once('.').map(|(_, rest)| rest))
+}
+
+fn cookie_site(cookie: &Cookie) -> String {
+    normalize_site(&cookie.domain).unwrap_or_default()
+}
+
+/// Whether two hosts share cookies: one is the other or its subdomain
+fn same_site(a: &str, b: &str) -> bool {
+    domain_suffixes(a).any(|d| d == b) || domain_suffixes(b).any(|d| d == a)
+}
+
+fn is_kept_signed_in(settings: &PrivacySettings, domain: &str) -> bool {
+    domain_suffixes(domain).any(|d| settings.keep_signed_in_sites.iter().any(|s| s == d))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClearDataOptions {
     pub history: bool,
@@ -829,3 +1043,89 @@ pub struct ClearDataResult {
     pub form_data_cleared: u64,
     pub passwords_cleared: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookie(domain: &str, name: &str, expires: Option<DateTime<Utc>>) -> Cookie {
+        let now = Utc::now();
+        Cookie {
+            domain: domain.to_string(),
+            name: name.to_string(),
+            value: "v".to_string(),
+            path: "/".to_string(),
+            expires,
+            secure: true,
+            http_only: true,
+            same_site: SameSite::Lax,
+            is_third_party: false,
+            created_at: now,
+            last_accessed: now,
+        }
+    }
+
+    fn names(cookies: &[Cookie]) -> Vec<String> {
+        let mut names: Vec<String> = cookies.iter().map(|c| c.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_session_only_cookies_are_deleted_when_the_site_closes() {
+        let service = PrivacyDashboardService::new();
+        service.set_cookie_policy("shop.example", SiteCookiePolicy::SessionOnly).unwrap();
+        service.add_cookie(cookie(".shop.example", "cart", Some(Utc::now() + Duration::days(30)))).unwrap();
+        service.add_cookie(cookie("news.test", "prefs", None)).unwrap();
+        assert!(service.get_cookies_for_domain("shop.example")[0].expires.is_none());
+
+        service.on_tab_navigated("t1", "https://www.shop.example/basket");
+        service.on_tab_navigated("t2", "https://shop.example/");
+        service.on_tab_navigated("t3", "https://news.test/");
+        assert!(service.on_tab_closed("t1").is_empty());
+        assert_eq!(names(&service.on_tab_closed("t2")), vec!["cart"]);
+        assert!(service.on_tab_closed("t3").is_empty());
+        assert_eq!(names(&service.get_cookies()), vec!["prefs"]);
+
+        // Browser close takes session-only cookies of sites still open, and
+        // with clear-on-exit everything not kept signed in
+        service.add_cookie(cookie("shop.example", "cart", None)).unwrap();
+        service.add_cookie(cookie("mail.test", "sid", None)).unwrap();
+        service.on_tab_navigated("t4", "https://shop.example/");
+        service.set_keep_signed_in("mail.test", true).unwrap();
+        let mut settings = service.get_settings();
+        settings.clear_cookies_on_exit = true;
+        service.update_settings(settings).unwrap();
+        assert_eq!(names(&service.on_browser_closed()), vec!["cart", "prefs"]);
+        assert_eq!(names(&service.get_cookies()), vec!["sid"]);
+    }
+
+    #[test]
+    fn test_auto_expire_policy_deletes_cookies_after_the_duration() {
+        let service = PrivacyDashboardService::new();
+        service.set_default_cookie_policy(SiteCookiePolicy::AutoExpireAfter { seconds: 3600 }).unwrap();
+        assert!(service.set_cookie_policy("x.test", SiteCookiePolicy::AutoExpireAfter { seconds: 0 }).is_err());
+        service.set_keep_signed_in("bank.test", true).unwrap();
+
+        let set_at = Utc::now();
+        service.add_cookie(cookie("tracker.test", "id", Some(set_at + Duration::days(365)))).unwrap();
+        service.add_cookie(cookie("bank.test", "session", Some(set_at + Duration::days(365)))).unwrap();
+        let capped = service.get_cookies_for_domain("tracker.test")[0].expires.unwrap();
+        assert!(capped <= set_at + Duration::seconds(3601));
+
+        // Re-adding the cookie doesn't restart its clock
+        service.add_cookie(cookie("tracker.test", "id", Some(set_at + Duration::days(365)))).unwrap();
+        assert!(service.sweep_cookies(set_at + Duration::minutes(30)).is_empty());
+        assert_eq!(names(&service.sweep_cookies(set_at + Duration::minutes(61))), vec!["id"]);
+        assert_eq!(names(&service.get_cookies()), vec!["session"]);
+
+        // Blocking refuses new cookies and removes existing ones
+        service.set_cookie_policy("bank.test", SiteCookiePolicy::Block).unwrap();
+        assert!(service.get_cookies().is_empty());
+        assert!(service.add_cookie(cookie("login.bank.test", "sid", None)).is_err());
+        assert_eq!(service.get_stats().cookies_blocked_total, 1);
+        service.clear_cookie_policy("bank.test").unwrap();
+        assert_eq!(service.site_cookie_policy("login.bank.test"), SiteCookiePolicy::Allow);
+        assert_eq!(service.site_cookie_policy("other.test"), SiteCookiePolicy::AutoExpireAfter { seconds: 3600 });
+    }
+}
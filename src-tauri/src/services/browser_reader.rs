@@ -3,7 +3,7 @@
 // Superior to Safari/Firefox reader modes with AI-powered features
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::RwLock;
 use chrono::Utc;
 use uuid::Uuid;
@@ -288,6 +288,33 @@ impl Default for ReaderStats {
     }
 }
 
+/// Reading progress and annotations for one article as exchanged between devices.
+/// Articles are matched by URL because ids are assigned per device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReaderProgressSync {
+    pub url: String,
+    pub device_id: String,
+    pub scroll_position: f32,
+    /// Paragraph reached, how far into it, and its opening words so the position
+    /// can be re-anchored when the article differs on the receiving device
+    pub paragraph_index: u32,
+    pub paragraph_progress: f32,
+    pub paragraph_anchor: String,
+    pub paragraph_count: u32,
+    pub time_spent_seconds: u64,
+    pub annotations: Vec<Annotation>,
+    #[serde(default)]
+    pub deleted_annotations: Vec<String>,
+    pub updated_at: i64,
+}
+
+/// Default reading speed until enough sessions have been measured
+const DEFAULT_WORDS_PER_MINUTE: u32 = 200;
+/// Sessions shorter than this say little about reading speed
+const MIN_MEASURED_SECONDS: u64 = 30;
+/// Anchor word overlap needed to treat two paragraphs as the same
+const ANCHOR_MATCH_THRESHOLD: f32 = 0.6;
+
 // ==================== Service ====================
 
 pub struct BrowserReaderService {
@@ -299,6 +326,9 @@ pub struct BrowserReaderService {
     sessions: RwLock<HashMap<String, ReadingSession>>,
     tts_state: RwLock<Option<TTSPlaybackState>>,
     stats: RwLock<ReaderStats>,
+    /// Synced progress for articles not opened on this device yet, keyed by URL
+    pending_sync: RwLock<HashMap<String, ReaderProgressSync>>,
+    deleted_annotations: RwLock<HashSet<String>>,
 }
 
 impl BrowserReaderService {
@@ -355,6 +385,8 @@ impl BrowserReaderService {
             sessions: RwLock::new(HashMap::new()),
             tts_state: RwLock::new(None),
             stats: RwLock::new(ReaderStats::default()),
+            pending_sync: RwLock::new(HashMap::new()),
+            deleted_annotations: RwLock::new(HashSet::new()),
         }
     }
     
@@ -479,7 +511,7 @@ impl BrowserReaderService {
         let content = self.extract_content(html);
         let text_content = self.strip_html(&content);
         let word_count = text_content.split_whitespace().count() as u32;
-        let reading_time = (word_count / 200).max(1);
+        let reading_time = self.estimate_reading_time(word_count);
         
         let article = ParsedArticle {
             id: Uuid::new_v4().to_string(),
@@ -504,6 +536,12 @@ impl BrowserReaderService {
         
         // Create reading session
         self.create_session(&article);
+
+        // Pick up where another device left off
+        let synced = self.pending_sync.write().unwrap().remove(url);
+        if let Some(remote) = synced {
+            self.merge_synced_progress(&article, &remote);
+        }
         
         Ok(article)
     }
@@ -781,6 +819,8 @@ impl BrowserReaderService {
             article_annotations.retain(|a| a.id != annotation_id);
             
             if article_annotations.len() < len_before {
+                self.deleted_annotations.write().unwrap().insert(annotation_id.to_string());
+
                 // Update session annotation count
                 let mut sessions = self.sessions.write().unwrap();
                 if let Some(session) = sessions.get_mut(article_id) {
@@ -829,6 +869,163 @@ impl BrowserReaderService {
         export
     }
     
+    // ==================== Progress Sync ====================
+
+    /// Snapshot of this device's progress on an article, ready to queue for sync
+    pub fn progress_for_sync(&self, article_id: &str, device_id: &str) -> Option<ReaderProgressSync> {
+        let article = self.get_article(article_id)?;
+        let session = self.get_session(article_id)?;
+        let paragraphs = self.paragraphs(&article);
+        let (index, progress) = position_to_paragraph(&paragraphs, session.scroll_position);
+
+        let annotations = self.get_annotations(article_id);
+        let mut deleted_annotations: Vec<String> = self.deleted_annotations.read().unwrap().iter().cloned().collect();
+        deleted_annotations.sort();
+
+        Some(ReaderProgressSync {
+            url: article.url.clone(),
+            device_id: device_id.to_string(),
+            scroll_position: session.scroll_position,
+            paragraph_index: index as u32,
+            paragraph_progress: progress,
+            paragraph_anchor: paragraphs.get(index).map(|p| anchor_words(p)).unwrap_or_default(),
+            paragraph_count: paragraphs.len() as u32,
+            time_spent_seconds: session.time_spent_seconds,
+            annotations,
+            deleted_annotations,
+            updated_at: session.last_read_at,
+        })
+    }
+
+    /// Merges progress synced from another device. The furthest position wins; if
+    /// the article has not been opened here yet, the progress is applied when it is.
+    pub fn apply_synced_progress(&self, remote: ReaderProgressSync) -> Option<ReadingSession> {
+        let article = self.articles.read().unwrap()
+            .values()
+            .filter(|a| a.url == remote.url)
+            .max_by_key(|a| a.parsed_at)
+            .cloned();
+
+        match article {
+            Some(article) => self.merge_synced_progress(&article, &remote),
+            None => {
+                let mut pending = self.pending_sync.write().unwrap();
+                let keep_existing = pending.get(&remote.url)
+                    .is_some_and(|existing| existing.scroll_position >= remote.scroll_position);
+                if !keep_existing {
+                    pending.insert(remote.url.clone(), remote);
+                }
+                None
+            }
+        }
+    }
+
+    fn merge_synced_progress(&self, article: &ParsedArticle, remote: &ReaderProgressSync) -> Option<ReadingSession> {
+        let paragraphs = self.paragraphs(article);
+        let index = reanchor(
+            &paragraphs,
+            &remote.paragraph_anchor,
+            remote.paragraph_index as usize,
+            remote.paragraph_count as usize,
+        );
+        let remote_position = paragraph_to_position(&paragraphs, index, remote.paragraph_progress);
+
+        self.merge_synced_annotations(article, &paragraphs, remote);
+        let annotations_count = self.get_annotations(&article.id).len() as u32;
+
+        let mut sessions = self.sessions.write().unwrap();
+        let session = sessions.get_mut(&article.id)?;
+        session.annotations_count = annotations_count;
+        session.time_spent_seconds = session.time_spent_seconds.max(remote.time_spent_seconds);
+        if remote_position > session.scroll_position {
+            session.scroll_position = remote_position;
+            session.last_read_at = session.last_read_at.max(remote.updated_at);
+            if remote_position >= 0.95 {
+                if session.status != ReadingStatus::Completed {
+                    session.status = ReadingStatus::Completed;
+                    session.completed_at = Some(remote.updated_at);
+                }
+            } else if session.status == ReadingStatus::NotStarted {
+                session.status = ReadingStatus::InProgress;
+            }
+        }
+        Some(session.clone())
+    }
+
+    /// Unions annotations by id (newest edit wins), honouring deletions from either side
+    fn merge_synced_annotations(&self, article: &ParsedArticle, paragraphs: &[String], remote: &ReaderProgressSync) {
+        let mut deleted = self.deleted_annotations.write().unwrap();
+        deleted.extend(remote.deleted_annotations.iter().cloned());
+
+        let mut annotations = self.annotations.write().unwrap();
+        let local = annotations.entry(article.id.clone()).or_default();
+        local.retain(|a| !deleted.contains(&a.id));
+
+        for incoming in &remote.annotations {
+            if deleted.contains(&incoming.id) {
+                continue;
+            }
+            let mut incoming = incoming.clone();
+            incoming.article_id = article.id.clone();
+            incoming.paragraph_index = reanchor_selection(
+                paragraphs,
+                &incoming.selected_text,
+                incoming.paragraph_index as usize,
+                remote.paragraph_count as usize,
+            ) as u32;
+
+            match local.iter_mut().find(|a| a.id == incoming.id) {
+                Some(existing) if existing.updated_at < incoming.updated_at => *existing = incoming,
+                Some(_) => {}
+                None => local.push(incoming),
+            }
+        }
+    }
+
+    /// Article text split into the paragraphs the reader view renders
+    fn paragraphs(&self, article: &ParsedArticle) -> Vec<String> {
+        let block_end = regex::Regex::new(r"(?i)</(p|h[1-6]|li|blockquote|pre)>").unwrap();
+        let paragraphs: Vec<String> = block_end
+            .split(&article.content)
+            .map(|chunk| self.strip_html(chunk))
+            .filter(|text| !text.is_empty())
+            .collect();
+
+        if paragraphs.is_empty() && !article.text_content.is_empty() {
+            vec![article.text_content.clone()]
+        } else {
+            paragraphs
+        }
+    }
+
+    /// Words per minute measured from past sessions, once there is enough to go on
+    pub fn measured_words_per_minute(&self) -> Option<u32> {
+        let articles = self.articles.read().unwrap();
+        let sessions = self.sessions.read().unwrap();
+
+        let (words, seconds) = sessions.values()
+            .filter(|s| s.time_spent_seconds >= MIN_MEASURED_SECONDS)
+            .filter_map(|s| {
+                let article = articles.get(&s.article_id)?;
+                Some((article.word_count as f64 * s.scroll_position as f64, s.time_spent_seconds))
+            })
+            .fold((0.0, 0u64), |(w, t), (words, secs)| (w + words, t + secs));
+
+        if seconds < 60 || words < 1.0 {
+            return None;
+        }
+        Some(((words * 60.0 / seconds as f64).round() as u32).clamp(80, 1000))
+    }
+
+    /// Minutes left in an article at the user's own reading speed
+    pub fn estimate_time_remaining(&self, article_id: &str) -> Option<u32> {
+        let article = self.get_article(article_id)?;
+        let position = self.get_session(article_id).map(|s| s.scroll_position).unwrap_or(0.0);
+        let remaining_words = article.word_count as f32 * (1.0 - position);
+        let wpm = self.measured_words_per_minute().unwrap_or(DEFAULT_WORDS_PER_MINUTE);
+        Some((remaining_words / wpm as f32).ceil() as u32)
+    }
+
     // ==================== TTS Control ====================
     
     pub fn start_tts(&self, article_id: &str) -> Result<TTSPlaybackState, String> {
@@ -947,8 +1144,9 @@ impl BrowserReaderService {
     }
     
     pub fn estimate_reading_time(&self, word_count: u32) -> u32 {
-        // Average reading speed: 200-250 words per minute
-        (word_count / 200).max(1)
+        // Personal speed when measured, otherwise the 200 wpm average
+        let wpm = self.measured_words_per_minute().unwrap_or(DEFAULT_WORDS_PER_MINUTE);
+        (word_count / wpm).max(1)
     }
     
     pub fn format_reading_time(&self, minutes: u32) -> String {
@@ -968,6 +1166,102 @@ impl Default for BrowserReaderService {
     }
 }
 
+// ==================== Paragraph Anchoring ====================
+
+/// Paragraph containing `position` (a 0.0-1.0 fraction of the words) and how far into it
+fn position_to_paragraph(paragraphs: &[String], position: f32) -> (usize, f32) {
+    let lengths: Vec<usize> = paragraphs.iter().map(|p| p.split_whitespace().count()).collect();
+    let total: usize = lengths.iter().sum();
+    if total == 0 {
+        return (0, 0.0);
+    }
+
+    let target = position.clamp(0.0, 1.0) * total as f32;
+    let mut before = 0.0;
+    for (index, &len) in lengths.iter().enumerate() {
+        let end = before + len as f32;
+        if target < end || index == lengths.len() - 1 {
+            let progress = if len == 0 { 0.0 } else { ((target - before) / len as f32).clamp(0.0, 1.0) };
+            return (index, progress);
+        }
+        before = end;
+    }
+    (0, 0.0)
+}
+
+fn paragraph_to_position(paragraphs: &[String], index: usize, progress: f32) -> f32 {
+    let lengths: Vec<usize> = paragraphs.iter().map(|p| p.split_whitespace().count()).collect();
+    let total: usize = lengths.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    let index = index.min(lengths.len() - 1);
+    let before: usize = lengths[..index].iter().sum();
+    ((before as f32 + progress.clamp(0.0, 1.0) * lengths[index] as f32) / total as f32).clamp(0.0, 1.0)
+}
+
+/// First dozen normalised words of a paragraph
+fn anchor_words(text: &str) -> String {
+    text.split_whitespace()
+        .map(|w| w.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase())
+        .filter(|w| !w.is_empty())
+        .take(12)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn anchor_similarity(a: &str, b: &str) -> f32 {
+    let a: HashSet<&str> = a.split(' ').filter(|w| !w.is_empty()).collect();
+    let b: HashSet<&str> = b.split(' ').filter(|w| !w.is_empty()).collect();
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f32 / a.union(&b).count() as f32
+}
+
+/// Where a paragraph index from a device with `remote_count` paragraphs falls here
+fn expected_index(paragraphs: &[String], remote_index: usize, remote_count: usize) -> usize {
+    if paragraphs.is_empty() {
+        return 0;
+    }
+    let scaled = if remote_count == 0 || remote_count == paragraphs.len() {
+        remote_index
+    } else {
+        ((remote_index as f32 + 0.5) * paragraphs.len() as f32 / remote_count as f32) as usize
+    };
+    scaled.min(paragraphs.len() - 1)
+}
+
+/// Local paragraph matching `anchor`, preferring the one nearest the expected index;
+/// falls back to the proportional position when the paragraph is gone
+fn reanchor(paragraphs: &[String], anchor: &str, remote_index: usize, remote_count: usize) -> usize {
+    let expected = expected_index(paragraphs, remote_index, remote_count);
+    paragraphs.iter()
+        .enumerate()
+        .map(|(index, text)| (index, anchor_similarity(&anchor_words(text), anchor)))
+        .filter(|(_, similarity)| *similarity >= ANCHOR_MATCH_THRESHOLD)
+        .max_by(|(ia, sa), (ib, sb)| {
+            sa.total_cmp(sb).then_with(|| ib.abs_diff(expected).cmp(&ia.abs_diff(expected)))
+        })
+        .map(|(index, _)| index)
+        .unwrap_or(expected)
+}
+
+/// Local paragraph containing an annotation's selected text, nearest the expected index
+fn reanchor_selection(paragraphs: &[String], selected_text: &str, remote_index: usize, remote_count: usize) -> usize {
+    let expected = expected_index(paragraphs, remote_index, remote_count);
+    let needle = selected_text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if needle.is_empty() {
+        return expected;
+    }
+    paragraphs.iter()
+        .enumerate()
+        .filter(|(_, text)| text.contains(&needle))
+        .min_by_key(|(index, _)| index.abs_diff(expected))
+        .map(|(index, _)| index)
+        .unwrap_or(expected)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -995,4 +1289,51 @@ mod tests {
         assert_eq!(HighlightColor::Yellow.hex_value(), "#fef08a");
         assert_eq!(HighlightColor::Purple.hex_value(), "#ddd6fe");
     }
+
+    fn article_html(paragraphs: &[usize]) -> String {
+        let body: String = paragraphs.iter()
+            .map(|i| {
+                let words: Vec<String> = (0..20).map(|w| format!("p{}w{}", i, w)).collect();
+                format!("<p>{}</p>", words.join(" "))
+            })
+            .collect();
+        format!("<html><title>Long read</title><body><article>{}</article></body></html>", body)
+    }
+
+    #[test]
+    fn test_synced_progress_furthest_position_wins() {
+        let url = "https://example.com/long-read";
+        let desktop = BrowserReaderService::new();
+        let phone = BrowserReaderService::new();
+        let on_desktop = desktop.parse_article(url, &article_html(&[0, 1, 2, 3, 4])).unwrap();
+        let on_phone = phone.parse_article(url, &article_html(&[0, 1, 2, 3, 4])).unwrap();
+
+        desktop.update_progress(&on_desktop.id, 0.7, 300);
+        let note = desktop.create_annotation(
+            &on_desktop.id, AnnotationType::Highlight, HighlightColor::Yellow, "p3w1 p3w2", None, 5, 14, 3,
+        );
+        phone.update_progress(&on_phone.id, 0.3, 100);
+
+        let from_desktop = desktop.progress_for_sync(&on_desktop.id, "desktop").unwrap();
+        let from_phone = phone.progress_for_sync(&on_phone.id, "phone").unwrap();
+        let merged_on_phone = phone.apply_synced_progress(from_desktop.clone()).unwrap();
+        let merged_on_desktop = desktop.apply_synced_progress(from_phone).unwrap();
+
+        assert!((merged_on_phone.scroll_position - 0.7).abs() < 0.01);
+        assert!((merged_on_desktop.scroll_position - 0.7).abs() < 0.01);
+        assert_eq!(merged_on_phone.time_spent_seconds, 300);
+        assert_eq!(phone.get_annotations(&on_phone.id)[0].id, note.id);
+
+        // A tablet whose copy gained an intro paragraph resumes at the same paragraph
+        let tablet = BrowserReaderService::new();
+        assert!(tablet.apply_synced_progress(from_desktop).is_none());
+        let on_tablet = tablet.parse_article(url, &article_html(&[9, 0, 1, 2, 3, 4])).unwrap();
+        let session = tablet.get_session(&on_tablet.id).unwrap();
+        let paragraphs = tablet.paragraphs(&on_tablet);
+        let (index, _) = position_to_paragraph(&paragraphs, session.scroll_position);
+        let desktop_paragraphs = desktop.paragraphs(&on_desktop);
+        let (desktop_index, _) = position_to_paragraph(&desktop_paragraphs, 0.7);
+        assert_eq!(paragraphs[index], desktop_paragraphs[desktop_index]);
+        assert_eq!(tablet.get_annotations(&on_tablet.id)[0].paragraph_index, 4);
+    }
 }
@@ -2,6 +2,8 @@
 // Superior to Chrome, Firefox, Edge screenshot tools
 // Full-page, region, element capture with annotations
 
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use image::{ImageEncoder, Rgba, RgbaImage};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -57,11 +59,25 @@ pub enum AnnotationType {
     Highlight,
     Blur,
     Pixelate,
+    Redact,
     Emoji,
     Number,
     Crop,
 }
 
+impl AnnotationType {
+    /// Redaction annotations are baked into the pixels on export instead of drawn as overlays
+    pub fn is_redaction(&self) -> bool {
+        matches!(self, AnnotationType::Blur | AnnotationType::Pixelate | AnnotationType::Redact)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum BlurStyle {
+    Smooth,
+    Pixelate,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ScreenshotAction {
     SaveToFile,
@@ -226,6 +242,11 @@ pub struct Annotation {
     pub number: Option<u32>,
     pub arrow_head: Option<bool>,
     pub opacity: f64,
+    #[serde(default)]
+    pub blur_style: Option<BlurStyle>,
+    /// Blur radius or pixel block size in image pixels (1-100)
+    #[serde(default)]
+    pub intensity: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -637,6 +658,7 @@ impl BrowserScreenshotService {
             can_undo: false,
             can_redo: false,
         };
+        self.record_annotation_snapshot(screenshot_id);
 
         Ok(self.editor_state.clone())
     }
@@ -677,8 +699,16 @@ impl BrowserScreenshotService {
     // ==================== Annotation Operations ====================
 
     pub fn add_annotation(&mut self, screenshot_id: &str, annotation: Annotation) -> Result<String, String> {
+        if !self.screenshots.contains_key(screenshot_id) {
+            return Err("Screenshot not found".to_string());
+        }
+        if annotation.annotation_type.is_redaction() && (annotation.width == 0.0 || annotation.height == 0.0) {
+            return Err("Redaction region must have a non-zero size".to_string());
+        }
+
         // Generate ID first to avoid borrow conflicts
-        let new_id = self.generate_id("ann");
+        self.annotation_counter += 1;
+        let new_id = format!("{}_{}", self.generate_id("ann"), self.annotation_counter);
         
         let screenshot = self.screenshots.get_mut(screenshot_id)
             .ok_or_else(|| "Screenshot not found".to_string())?;
@@ -687,6 +717,7 @@ impl BrowserScreenshotService {
         new_annotation.id = new_id.clone();
         
         screenshot.annotations.push(new_annotation);
+        self.record_annotation_snapshot(screenshot_id);
 
         Ok(new_id)
     }
@@ -711,7 +742,11 @@ impl BrowserScreenshotService {
         annotation.text = updates.text;
         annotation.font_size = updates.font_size;
         annotation.opacity = updates.opacity;
+        annotation.blur_radius = updates.blur_radius;
+        annotation.blur_style = updates.blur_style;
+        annotation.intensity = updates.intensity;
 
+        self.record_annotation_snapshot(screenshot_id);
         Ok(())
     }
 
@@ -720,6 +755,7 @@ impl BrowserScreenshotService {
             .ok_or_else(|| "Screenshot not found".to_string())?;
 
         screenshot.annotations.retain(|a| a.id != annotation_id);
+        self.record_annotation_snapshot(screenshot_id);
         Ok(())
     }
 
@@ -728,6 +764,7 @@ impl BrowserScreenshotService {
             .ok_or_else(|| "Screenshot not found".to_string())?;
 
         screenshot.annotations.clear();
+        self.record_annotation_snapshot(screenshot_id);
         Ok(())
     }
 
@@ -745,6 +782,7 @@ impl BrowserScreenshotService {
             self.editor_state.history_index -= 1;
             self.editor_state.can_undo = self.editor_state.history_index > 0;
             self.editor_state.can_redo = true;
+            self.restore_annotation_snapshot();
             true
         } else {
             false
@@ -752,16 +790,45 @@ impl BrowserScreenshotService {
     }
 
     pub fn redo(&mut self) -> bool {
-        if self.editor_state.history_index < self.editor_state.history.len() - 1 {
+        if self.editor_state.history_index + 1 < self.editor_state.history.len() {
             self.editor_state.history_index += 1;
-            self.editor_state.can_redo = self.editor_state.history_index < self.editor_state.history.len() - 1;
+            self.editor_state.can_redo = self.editor_state.history_index + 1 < self.editor_state.history.len();
             self.editor_state.can_undo = true;
+            self.restore_annotation_snapshot();
             true
         } else {
             false
         }
     }
 
+    /// Pushes the annotation list of the screenshot open in the editor onto the history
+    fn record_annotation_snapshot(&mut self, screenshot_id: &str) {
+        if self.editor_state.screenshot_id.as_deref() != Some(screenshot_id) {
+            return;
+        }
+        let Some(screenshot) = self.screenshots.get(screenshot_id) else {
+            return;
+        };
+        if let Ok(snapshot) = serde_json::to_string(&screenshot.annotations) {
+            self.add_to_history(snapshot);
+        }
+    }
+
+    /// Applies the history entry at the current index when it is an annotation snapshot
+    fn restore_annotation_snapshot(&mut self) {
+        let Some(screenshot_id) = self.editor_state.screenshot_id.clone() else {
+            return;
+        };
+        let Some(entry) = self.editor_state.history.get(self.editor_state.history_index) else {
+            return;
+        };
+        if let Ok(annotations) = serde_json::from_str::<Vec<Annotation>>(entry) {
+            if let Some(screenshot) = self.screenshots.get_mut(&screenshot_id) {
+                screenshot.annotations = annotations;
+            }
+        }
+    }
+
     pub fn add_to_history(&mut self, state_json: String) {
         // Remove any future states if we're not at the end
         self.editor_state.history.truncate(self.editor_state.history_index + 1);
@@ -773,6 +840,34 @@ impl BrowserScreenshotService {
 
     // ==================== Export Operations ====================
 
+    /// Stores the captured pixels (a base64 `data:` URL) as the editor source image
+    pub fn set_image_data(&mut self, screenshot_id: &str, data_url: String) -> Result<(), String> {
+        let image = decode_data_url(&data_url)?;
+        let screenshot = self.screenshots.get_mut(screenshot_id)
+            .ok_or_else(|| "Screenshot not found".to_string())?;
+
+        screenshot.width = image.width();
+        screenshot.height = image.height();
+        screenshot.file_size = data_url.len() as u64;
+        screenshot.data_url = Some(data_url);
+        Ok(())
+    }
+
+    /// Renders the screenshot with every redaction baked into the pixels.
+    /// The result holds no trace of the redacted source pixels.
+    pub fn flatten(&self, screenshot_id: &str) -> Result<RgbaImage, String> {
+        let screenshot = self.screenshots.get(screenshot_id)
+            .ok_or_else(|| "Screenshot not found".to_string())?;
+        let data_url = screenshot.data_url.as_ref()
+            .ok_or_else(|| "Screenshot has no image data".to_string())?;
+
+        let mut image = decode_data_url(data_url)?;
+        for annotation in screenshot.annotations.iter().filter(|a| a.annotation_type.is_redaction()) {
+            apply_redaction(&mut image, annotation);
+        }
+        Ok(image)
+    }
+
     pub fn save_to_file(&self, screenshot_id: &str, path: Option<String>) -> Result<String, String> {
         let screenshot = self.screenshots.get(screenshot_id)
             .ok_or_else(|| "Screenshot not found".to_string())?;
@@ -781,7 +876,25 @@ impl BrowserScreenshotService {
             format!("{}/{}", self.settings.save_directory, self.generate_filename(&screenshot.format))
         });
 
-        // In real implementation, would save the actual image data
+        if screenshot.data_url.is_some() {
+            let quality = match screenshot.format {
+                ImageFormat::JPEG => self.settings.jpeg_quality,
+                ImageFormat::WEBP => self.settings.webp_quality,
+                _ => 100,
+            };
+            let bytes = self.export_as_format(screenshot_id, screenshot.format.clone(), quality)?;
+            let resolved = match file_path.strip_prefix("~/") {
+                Some(rest) => dirs::home_dir()
+                    .map(|home| home.join(rest))
+                    .unwrap_or_else(|| std::path::PathBuf::from(&file_path)),
+                None => std::path::PathBuf::from(&file_path),
+            };
+            if let Some(parent) = resolved.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            std::fs::write(&resolved, bytes).map_err(|e| e.to_string())?;
+        }
+
         Ok(file_path)
     }
 
@@ -793,12 +906,32 @@ impl BrowserScreenshotService {
         Ok(())
     }
 
-    pub fn export_as_format(&self, screenshot_id: &str, _format: ImageFormat, _quality: u8) -> Result<Vec<u8>, String> {
-        let _screenshot = self.screenshots.get(screenshot_id)
-            .ok_or_else(|| "Screenshot not found".to_string())?;
+    pub fn export_as_format(&self, screenshot_id: &str, format: ImageFormat, quality: u8) -> Result<Vec<u8>, String> {
+        let image = self.flatten(screenshot_id)?;
+        let (width, height) = image.dimensions();
+        let mut bytes = Vec::new();
+
+        match format {
+            ImageFormat::PNG => {
+                image::codecs::png::PngEncoder::new(&mut bytes)
+                    .write_image(image.as_raw(), width, height, image::ExtendedColorType::Rgba8)
+                    .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+            }
+            ImageFormat::JPEG => {
+                let rgb = image::DynamicImage::ImageRgba8(image).to_rgb8();
+                image::codecs::jpeg::JpegEncoder::new_with_quality(&mut bytes, quality.clamp(1, 100))
+                    .write_image(rgb.as_raw(), width, height, image::ExtendedColorType::Rgb8)
+                    .map_err(|e| format!("Failed to encode JPEG: {}", e))?;
+            }
+            ImageFormat::WEBP => {
+                image::codecs::webp::WebPEncoder::new_lossless(&mut bytes)
+                    .write_image(image.as_raw(), width, height, image::ExtendedColorType::Rgba8)
+                    .map_err(|e| format!("Failed to encode WebP: {}", e))?;
+            }
+            ImageFormat::PDF => return Err("PDF export is not supported for screenshots".to_string()),
+        }
 
-        // In real implementation, would convert and return image data
-        Ok(vec![])
+        Ok(bytes)
     }
 
     pub fn upload(&self, screenshot_id: &str, _destination: UploadDestination) -> Result<UploadResult, String> {
@@ -971,6 +1104,7 @@ impl BrowserScreenshotService {
             AnnotationType::Highlight,
             AnnotationType::Blur,
             AnnotationType::Pixelate,
+            AnnotationType::Redact,
             AnnotationType::Emoji,
             AnnotationType::Number,
             AnnotationType::Crop,
@@ -990,3 +1124,244 @@ impl Default for BrowserScreenshotService {
         Self::new()
     }
 }
+
+// ==================== Redaction ====================
+
+fn decode_data_url(data_url: &str) -> Result<RgbaImage, String> {
+    let encoded = match data_url.split_once(',') {
+        Some((header, payload)) if header.starts_with("data:") => payload,
+        _ => data_url,
+    };
+    let bytes = BASE64.decode(encoded.trim())
+        .map_err(|e| format!("Invalid image data: {}", e))?;
+    image::load_from_memory(&bytes)
+        .map(|img| img.to_rgba8())
+        .map_err(|e| format!("Failed to decode image: {}", e))
+}
+
+fn parse_hex_color(color: &str) -> Rgba<u8> {
+    let hex = color.trim_start_matches('#');
+    let channel = |i: usize| hex.get(i..i + 2).and_then(|c| u8::from_str_radix(c, 16).ok());
+    match (channel(0), channel(2), channel(4)) {
+        (Some(r), Some(g), Some(b)) => Rgba([r, g, b, 255]),
+        _ => Rgba([0, 0, 0, 255]),
+    }
+}
+
+/// Clamps an annotation rectangle (which may have negative extents) to the image bounds
+fn redaction_bounds(image: &RgbaImage, annotation: &Annotation) -> Option<(u32, u32, u32, u32)> {
+    let x0 = annotation.x.min(annotation.x + annotation.width).floor().max(0.0);
+    let y0 = annotation.y.min(annotation.y + annotation.height).floor().max(0.0);
+    let x1 = annotation.x.max(annotation.x + annotation.width).ceil().min(image.width() as f64);
+    let y1 = annotation.y.max(annotation.y + annotation.height).ceil().min(image.height() as f64);
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+    Some((x0 as u32, y0 as u32, x1 as u32, y1 as u32))
+}
+
+fn apply_redaction(image: &mut RgbaImage, annotation: &Annotation) {
+    let Some(bounds) = redaction_bounds(image, annotation) else {
+        return;
+    };
+    let intensity = annotation.intensity
+        .or_else(|| annotation.blur_radius.map(|r| r.round().max(1.0) as u32))
+        .unwrap_or(10)
+        .clamp(1, 100);
+
+    match (&annotation.annotation_type, &annotation.blur_style) {
+        (AnnotationType::Redact, _) => {
+            let fill = parse_hex_color(annotation.fill.as_deref().unwrap_or("#000000"));
+            fill_region(image, bounds, fill);
+        }
+        (AnnotationType::Pixelate, _) | (AnnotationType::Blur, Some(BlurStyle::Pixelate)) => {
+            pixelate_region(image, bounds, intensity.max(2));
+        }
+        _ => blur_region(image, bounds, intensity),
+    }
+}
+
+fn fill_region(image: &mut RgbaImage, (x0, y0, x1, y1): (u32, u32, u32, u32), color: Rgba<u8>) {
+    for y in y0..y1 {
+        for x in x0..x1 {
+            image.put_pixel(x, y, color);
+        }
+    }
+}
+
+/// Replaces each block with its average colour so only one value per block survives
+fn pixelate_region(image: &mut RgbaImage, (x0, y0, x1, y1): (u32, u32, u32, u32), block: u32) {
+    for by in (y0..y1).step_by(block as usize) {
+        for bx in (x0..x1).step_by(block as usize) {
+            let (ex, ey) = ((bx + block).min(x1), (by + block).min(y1));
+            let mut sum = [0u64; 4];
+            for y in by..ey {
+                for x in bx..ex {
+                    for (acc, value) in sum.iter_mut().zip(image.get_pixel(x, y).0) {
+                        *acc += value as u64;
+                    }
+                }
+            }
+            let count = ((ex - bx) * (ey - by)) as u64;
+            let average = Rgba(sum.map(|v| (v / count) as u8));
+            fill_region(image, (bx, by, ex, ey), average);
+        }
+    }
+}
+
+/// Three box-blur passes over the region, sampling only pixels inside it
+fn blur_region(image: &mut RgbaImage, (x0, y0, x1, y1): (u32, u32, u32, u32), radius: u32) {
+    let (width, height) = ((x1 - x0) as usize, (y1 - y0) as usize);
+    let mut pixels: Vec<[f32; 4]> = (y0..y1)
+        .flat_map(|y| (x0..x1).map(move |x| (x, y)))
+        .map(|(x, y)| image.get_pixel(x, y).0.map(|c| c as f32))
+        .collect();
+    let r = radius as isize;
+
+    for _ in 0..3 {
+        for horizontal in [true, false] {
+            let source = pixels.clone();
+            for y in 0..height {
+                for x in 0..width {
+                    let mut sum = [0f32; 4];
+                    let mut count = 0f32;
+                    for offset in -r..=r {
+                        let (sx, sy) = if horizontal {
+                            (x as isize + offset, y as isize)
+                        } else {
+                            (x as isize, y as isize + offset)
+                        };
+                        if sx < 0 || sy < 0 || sx >= width as isize || sy >= height as isize {
+                            continue;
+                        }
+                        let pixel = source[sy as usize * width + sx as usize];
+                        for (acc, value) in sum.iter_mut().zip(pixel) {
+                            *acc += value;
+                        }
+                        count += 1.0;
+                    }
+                    pixels[y * width + x] = sum.map(|v| v / count);
+                }
+            }
+        }
+    }
+
+    for (index, pixel) in pixels.into_iter().enumerate() {
+        let (x, y) = (x0 + (index % width) as u32, y0 + (index / width) as u32);
+        image.put_pixel(x, y, Rgba(pixel.map(|c| c.round().clamp(0.0, 255.0) as u8)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard_data_url(size: u32) -> String {
+        let image = RgbaImage::from_fn(size, size, |x, y| {
+            if (x + y) % 2 == 0 { Rgba([255, 255, 255, 255]) } else { Rgba([10, 200, 30, 255]) }
+        });
+        let mut bytes = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut bytes)
+            .write_image(image.as_raw(), size, size, image::ExtendedColorType::Rgba8)
+            .unwrap();
+        format!("data:image/png;base64,{}", BASE64.encode(bytes))
+    }
+
+    fn redaction(annotation_type: AnnotationType, x: f64, y: f64, size: f64) -> Annotation {
+        Annotation {
+            id: String::new(),
+            annotation_type,
+            x,
+            y,
+            width: size,
+            height: size,
+            rotation: 0.0,
+            color: "#000000".to_string(),
+            stroke_width: 0.0,
+            fill: Some("#000000".to_string()),
+            text: None,
+            font_size: None,
+            font_family: None,
+            points: vec![],
+            blur_radius: None,
+            emoji: None,
+            number: None,
+            arrow_head: None,
+            opacity: 1.0,
+            blur_style: None,
+            intensity: Some(4),
+        }
+    }
+
+    fn service_with_image() -> (BrowserScreenshotService, String, String) {
+        let mut service = BrowserScreenshotService::new();
+        let screenshot = service.capture_visible_area("https://example.com", "Example").unwrap();
+        let data_url = checkerboard_data_url(32);
+        service.set_image_data(&screenshot.id, data_url.clone()).unwrap();
+        (service, screenshot.id, data_url)
+    }
+
+    #[test]
+    fn test_redaction_is_baked_into_export() {
+        let (mut service, id, data_url) = service_with_image();
+        service.add_annotation(&id, redaction(AnnotationType::Redact, 0.0, 0.0, 8.0)).unwrap();
+        service.add_annotation(&id, redaction(AnnotationType::Pixelate, 16.0, 16.0, 8.0)).unwrap();
+
+        let png = service.export_as_format(&id, ImageFormat::PNG, 100).unwrap();
+        let exported = image::load_from_memory(&png).unwrap().to_rgba8();
+        let original = decode_data_url(&data_url).unwrap();
+
+        for y in 0..8 {
+            for x in 0..8 {
+                assert_eq!(*exported.get_pixel(x, y), Rgba([0, 0, 0, 255]));
+                assert_ne!(exported.get_pixel(x, y), original.get_pixel(x, y));
+            }
+        }
+        // Pixelated blocks collapse to a single averaged colour
+        let block = *exported.get_pixel(16, 16);
+        assert!((16..20).all(|x| (16..20).all(|y| *exported.get_pixel(x, y) == block)));
+        assert_ne!(block, *original.get_pixel(16, 16));
+        // Pixels outside every region are untouched
+        assert_eq!(exported.get_pixel(12, 3), original.get_pixel(12, 3));
+
+        // The annotation layer itself carries only geometry, never source pixels
+        let layer = serde_json::to_string(&service.get_annotations(&id).unwrap()).unwrap();
+        assert!(!layer.contains("base64"));
+        assert!(layer.len() < 2048);
+    }
+
+    #[test]
+    fn test_blur_changes_region() {
+        let (mut service, id, data_url) = service_with_image();
+        service.add_annotation(&id, redaction(AnnotationType::Blur, 4.0, 4.0, 12.0)).unwrap();
+
+        let flattened = service.flatten(&id).unwrap();
+        let original = decode_data_url(&data_url).unwrap();
+        let changed = (4..16)
+            .flat_map(|y| (4..16).map(move |x| (x, y)))
+            .filter(|&(x, y)| flattened.get_pixel(x, y) != original.get_pixel(x, y))
+            .count();
+        assert_eq!(changed, 144);
+    }
+
+    #[test]
+    fn test_redaction_undo_redo() {
+        let (mut service, id, _) = service_with_image();
+        service.open_editor(&id).unwrap();
+        service.add_annotation(&id, redaction(AnnotationType::Redact, 0.0, 0.0, 8.0)).unwrap();
+        service.add_annotation(&id, redaction(AnnotationType::Blur, 8.0, 8.0, 8.0)).unwrap();
+
+        assert!(service.undo());
+        assert_eq!(service.get_annotations(&id).unwrap().len(), 1);
+        assert!(service.undo());
+        assert!(service.get_annotations(&id).unwrap().is_empty());
+        assert!(!service.undo());
+
+        let original = service.flatten(&id).unwrap();
+        assert_eq!(*original.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+
+        assert!(service.redo());
+        assert_eq!(service.get_annotations(&id).unwrap().len(), 1);
+        assert_eq!(*service.flatten(&id).unwrap().get_pixel(0, 0), Rgba([0, 0, 0, 255]));
+    }
+}
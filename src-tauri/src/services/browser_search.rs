@@ -52,6 +52,8 @@ pub struct SearchSettings {
     pub suggestion_delay_ms: u32,
     pub enable_quick_keywords: bool,
     pub enable_bang_commands: bool,         // !g, !yt style
+    #[serde(default)]
+    pub keep_unknown_bangs: bool,           // Search "!foo cats" verbatim instead of "cats"
     pub enable_calculator: bool,
     pub enable_unit_conversion: bool,
     pub enable_currency_conversion: bool,
@@ -73,6 +75,7 @@ impl Default for SearchSettings {
             suggestion_delay_ms: 150,
             enable_quick_keywords: true,
             enable_bang_commands: true,
+            keep_unknown_bangs: false,
             enable_calculator: true,
             enable_unit_conversion: true,
             enable_currency_conversion: true,
@@ -155,6 +158,8 @@ pub struct OmniboxResult {
     pub calculator_result: Option<String>,
     pub conversion_result: Option<ConversionResult>,
     pub matched_engine: Option<SearchEngine>,
+    pub bang: Option<String>,               // Bang found in the input, without the "!"
+    pub search_url: Option<String>,         // Where a bang query routes to
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -715,6 +720,8 @@ impl SearchEngineService {
             calculator_result: None,
             conversion_result: None,
             matched_engine: None,
+            bang: None,
+            search_url: None,
         };
         
         let settings = self.get_settings();
@@ -728,13 +735,20 @@ impl SearchEngineService {
         }
         
         // Check for search engine keyword (@keyword query)
-        if input_lower.starts_with('@') || input_lower.starts_with('!') {
+        if input_lower.starts_with('@') {
             let parts: Vec<&str> = input.splitn(2, ' ').collect();
             if let Some(engine) = self.get_engine_by_keyword(parts[0]) {
                 result.matched_engine = Some(engine);
             }
         }
         
+        // Check for bang command (!g cats, cats !g)
+        if settings.enable_bang_commands {
+            if let Some((bang, query)) = Self::split_bang(input) {
+                self.route_bang(&mut result, &settings, input, &bang, &query);
+            }
+        }
+        
         // Check for calculator
         if settings.enable_calculator {
             if let Some(calc_result) = self.try_calculate(input) {
@@ -764,6 +778,56 @@ impl SearchEngineService {
         result
     }
 
+    /// Split a leading or trailing "!bang" off the query. A bare "!g" with
+    /// nothing to search for yet is not treated as a bang.
+    fn split_bang(input: &str) -> Option<(String, String)> {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        if words.len() < 2 {
+            return None;
+        }
+        
+        let is_bang = |w: &str| w.len() > 1 && w.starts_with('!');
+        let (bang, rest) = if is_bang(words[0]) {
+            (words[0], &words[1..])
+        } else if is_bang(words[words.len() - 1]) {
+            (words[words.len() - 1], &words[..words.len() - 1])
+        } else {
+            return None;
+        };
+        
+        Some((bang[1..].to_lowercase(), rest.join(" ")))
+    }
+
+    /// Resolve a bang to a search URL. User-defined bangs (quick actions with a
+    /// "!keyword" and an OpenUrl template) win over the engines' built-in ones,
+    /// and unknown bangs fall back to the default engine.
+    fn route_bang(&self, result: &mut OmniboxResult, settings: &SearchSettings, input: &str, bang: &str, query: &str) {
+        let encoded = urlencoding::encode(query);
+        result.bang = Some(bang.to_string());
+        
+        if let Some(action) = self.find_quick_action(&format!("!{}", bang)) {
+            if let QuickActionType::OpenUrl(template) = &action.action_type {
+                result.search_url = Some(template.replace("%s", &encoded));
+                return;
+            }
+        }
+        
+        let engine = self.engines.lock().unwrap()
+            .values()
+            .find(|e| e.is_enabled && e.keyword.trim_start_matches('@').eq_ignore_ascii_case(bang))
+            .cloned();
+        if let Some(engine) = engine {
+            result.search_url = Some(engine.search_url.replace("%s", &encoded));
+            result.matched_engine = Some(engine);
+            return;
+        }
+        
+        let fallback = if settings.keep_unknown_bangs { input.trim() } else { query };
+        if let Some(engine) = self.get_engine(&settings.default_engine_id) {
+            result.search_url = Some(engine.search_url.replace("%s", &urlencoding::encode(fallback)));
+        }
+    }
+
     fn find_quick_action(&self, keyword: &str) -> Option<QuickAction> {
         self.quick_actions.lock().unwrap()
             .values()
@@ -808,7 +872,16 @@ impl SearchEngineService {
 
     // ==================== Quick Actions ====================
 
-    pub fn add_quick_action(&self, action: QuickAction) -> Result<String, String> {
+    pub fn add_quick_action(&self, mut action: QuickAction) -> Result<String, String> {
+        // Bangs are matched case-insensitively and need somewhere to send the query
+        if action.keyword.starts_with('!') {
+            action.keyword = action.keyword.to_lowercase();
+            match &action.action_type {
+                QuickActionType::OpenUrl(url) if url.contains("%s") => {}
+                _ => return Err("Bang actions must open a URL containing %s".to_string()),
+            }
+        }
+        
         let id = action.id.clone();
         self.quick_actions.lock().unwrap().insert(id.clone(), action);
         Ok(id)
@@ -891,3 +964,71 @@ impl SearchEngineService {
         Ok(count)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_bang_routes_to_engine() {
+        let service = SearchEngineService::new();
+
+        let result = service.process_omnibox_input("!g cats");
+        assert_eq!(result.bang.as_deref(), Some("g"));
+        assert_eq!(result.matched_engine.map(|e| e.id).as_deref(), Some("google"));
+        assert_eq!(result.search_url.as_deref(), Some("https://www.google.com/search?q=cats"));
+
+        // Trailing bangs work the same way
+        let result = service.process_omnibox_input("rust lifetimes !W");
+        assert_eq!(result.matched_engine.map(|e| e.id).as_deref(), Some("wikipedia"));
+        assert!(result.search_url.unwrap().contains("rust%20lifetimes"));
+
+        // Still typing: nothing to search for yet
+        assert!(service.process_omnibox_input("!g").search_url.is_none());
+    }
+
+    #[test]
+    fn test_user_defined_bang() {
+        let service = SearchEngineService::new();
+        service.add_quick_action(QuickAction {
+            id: "crates".to_string(),
+            name: "crates.io".to_string(),
+            keyword: "!Crate".to_string(),
+            action_type: QuickActionType::OpenUrl("https://crates.io/search?q=%s".to_string()),
+            icon: None,
+            is_enabled: true,
+        }).unwrap();
+
+        let result = service.process_omnibox_input("!crate serde json");
+        assert_eq!(result.bang.as_deref(), Some("crate"));
+        assert!(result.matched_engine.is_none());
+        assert_eq!(result.search_url.as_deref(), Some("https://crates.io/search?q=serde%20json"));
+
+        let invalid = service.add_quick_action(QuickAction {
+            id: "broken".to_string(),
+            name: "Broken".to_string(),
+            keyword: "!broken".to_string(),
+            action_type: QuickActionType::NewTab,
+            icon: None,
+            is_enabled: true,
+        });
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn test_unknown_bang_falls_back_to_default_engine() {
+        let service = SearchEngineService::new();
+        service.set_default_engine("duckduckgo").unwrap();
+
+        let result = service.process_omnibox_input("!nope cats");
+        assert_eq!(result.bang.as_deref(), Some("nope"));
+        assert!(result.matched_engine.is_none());
+        assert_eq!(result.search_url.as_deref(), Some("https://duckduckgo.com/?q=cats"));
+
+        let mut settings = service.get_settings();
+        settings.keep_unknown_bangs = true;
+        service.update_settings(settings).unwrap();
+        let result = service.process_omnibox_input("cats !nope");
+        assert_eq!(result.search_url.as_deref(), Some("https://duckduckgo.com/?q=cats%20%21nope"));
+    }
+}
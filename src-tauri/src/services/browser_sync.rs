@@ -1,10 +1,12 @@
 // CUBE Nexum - Sync Service
 // Cross-device sync with E2E encryption - Superior to Chrome/Firefox/Safari
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc, Duration};
+use super::sync_key_ring::{SealedPayload, SyncKeyRing, WrappedDataKey};
+use super::sync_bookmark_merge::{merge_bookmark_trees, BookmarkMerge, BookmarkNode};
 
 // ==================== Types ====================
 
@@ -17,7 +19,7 @@ pub enum SyncStatus {
     Offline,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum SyncDataType {
     Tabs,
     Bookmarks,
@@ -29,6 +31,7 @@ pub enum SyncDataType {
     ReadingList,
     Notes,
     Workspaces,
+    Notifications,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +51,8 @@ pub struct SyncSettings {
     pub sync_reading_list: bool,
     pub sync_notes: bool,
     pub sync_workspaces: bool,
+    #[serde(default = "default_sync_notifications")]
+    pub sync_notifications: bool,
     // Encryption
     pub e2e_encryption_enabled: bool,
     pub encryption_key_id: Option<String>,
@@ -75,6 +80,7 @@ impl Default for SyncSettings {
             sync_reading_list: true,
             sync_notes: true,
             sync_workspaces: true,
+            sync_notifications: true,
             e2e_encryption_enabled: true,
             encryption_key_id: None,
             wifi_only: false,
@@ -84,6 +90,10 @@ impl Default for SyncSettings {
     }
 }
 
+fn default_sync_notifications() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ConflictResolution {
     ServerWins,
@@ -103,6 +113,9 @@ pub struct SyncDevice {
     pub is_current: bool,
     pub sync_enabled: bool,
     pub created_at: DateTime<Utc>,
+    /// Data-type key versions this device has been given
+    #[serde(default)]
+    pub key_versions: HashMap<SyncDataType, u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -145,6 +158,9 @@ pub struct SyncItem {
     pub device_id: String,
     pub is_deleted: bool,
     pub checksum: String,
+    /// Version of the data-type key that sealed `data`; `None` if stored in clear
+    #[serde(default)]
+    pub key_version: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -211,6 +227,11 @@ pub struct EncryptionKey {
     pub created_at: DateTime<Utc>,
     pub expires_at: Option<DateTime<Utc>>,
     pub is_active: bool,
+    /// Set for per-data-type keys derived from the primary key
+    #[serde(default)]
+    pub data_type: Option<SyncDataType>,
+    #[serde(default)]
+    pub version: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -218,6 +239,7 @@ pub enum KeyType {
     Primary,
     Recovery,
     Device,
+    DataType,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -241,7 +263,10 @@ pub struct SyncService {
     conflicts: Mutex<HashMap<String, SyncConflict>>,
     sync_history: Mutex<Vec<SyncHistory>>,
     encryption_keys: Mutex<HashMap<String, EncryptionKey>>,
+    key_ring: Mutex<SyncKeyRing>,
     stats: Mutex<SyncStats>,
+    /// Bookmark tree as of the last merge, the common ancestor for the next one
+    bookmark_base: Mutex<Vec<BookmarkNode>>,
     current_device_id: String,
 }
 
@@ -256,6 +281,7 @@ impl SyncService {
             conflicts: Mutex::new(HashMap::new()),
             sync_history: Mutex::new(Vec::new()),
             encryption_keys: Mutex::new(HashMap::new()),
+            key_ring: Mutex::new(SyncKeyRing::generate()),
             stats: Mutex::new(SyncStats {
                 total_syncs: 0,
                 successful_syncs: 0,
@@ -268,6 +294,7 @@ impl SyncService {
                 average_sync_duration_ms: 0,
                 items_by_type: HashMap::new(),
             }),
+            bookmark_base: Mutex::new(Vec::new()),
             current_device_id: Self::generate_device_id(),
         }
     }
@@ -322,6 +349,7 @@ impl SyncService {
             SyncDataType::ReadingList => settings.sync_reading_list = enabled,
             SyncDataType::Notes => settings.sync_notes = enabled,
             SyncDataType::Workspaces => settings.sync_workspaces = enabled,
+            SyncDataType::Notifications => settings.sync_notifications = enabled,
         }
         Ok(())
     }
@@ -392,6 +420,7 @@ impl SyncService {
             is_current: true,
             sync_enabled: true,
             created_at: Utc::now(),
+            key_versions: HashMap::new(),
         };
         
         self.devices.lock().unwrap().insert(device.device_id.clone(), device.clone());
@@ -406,6 +435,10 @@ impl SyncService {
         self.devices.lock().unwrap().get(&self.current_device_id).cloned()
     }
 
+    pub fn current_device_id(&self) -> &str {
+        &self.current_device_id
+    }
+
     pub fn get_device(&self, device_id: &str) -> Option<SyncDevice> {
         self.devices.lock().unwrap().get(device_id).cloned()
     }
@@ -443,22 +476,53 @@ impl SyncService {
 
     pub fn queue_sync_item(&self, data_type: SyncDataType, data: serde_json::Value) -> Result<String, String> {
         let id = Self::generate_id();
+        let checksum = Self::calculate_checksum(&data);
+        let (data, key_version) = if self.settings.lock().unwrap().e2e_encryption_enabled {
+            let sealed = Self::seal_item_data(&mut self.key_ring.lock().unwrap(), &data_type, &data)?;
+            (serde_json::to_value(&sealed).map_err(|e| e.to_string())?, Some(sealed.key_version))
+        } else {
+            (data, None)
+        };
         let item = SyncItem {
             id: id.clone(),
             data_type,
-            checksum: Self::calculate_checksum(&data),
+            checksum,
             data,
             version: 1,
             created_at: Utc::now(),
             modified_at: Utc::now(),
             device_id: self.current_device_id.clone(),
             is_deleted: false,
+            key_version,
         };
         
         self.sync_queue.lock().unwrap().push(item);
         Ok(id)
     }
 
+    /// Plaintext of a queued item, decrypting it if it was sealed
+    pub fn read_sync_item(&self, item: &SyncItem) -> Result<serde_json::Value, String> {
+        if item.key_version.is_none() {
+            return Ok(item.data.clone());
+        }
+        let sealed: SealedPayload = serde_json::from_value(item.data.clone()).map_err(|e| e.to_string())?;
+        let plaintext = self.key_ring.lock().unwrap().open(&item.data_type, &sealed)?;
+        serde_json::from_slice(&plaintext).map_err(|e| e.to_string())
+    }
+
+    fn seal_item_data(ring: &mut SyncKeyRing, data_type: &SyncDataType, data: &serde_json::Value) -> Result<SealedPayload, String> {
+        let plaintext = serde_json::to_vec(data).map_err(|e| e.to_string())?;
+        ring.seal(data_type, &plaintext)
+    }
+
+    /// Queue items downloaded from other devices so the next sync merges them
+    pub fn receive_remote_items(&self, items: Vec<SyncItem>) -> usize {
+        let mut queue = self.sync_queue.lock().unwrap();
+        let before = queue.len();
+        queue.extend(items.into_iter().filter(|item| item.device_id != self.current_device_id));
+        queue.len() - before
+    }
+
     pub fn get_sync_queue(&self) -> Vec<SyncItem> {
         self.sync_queue.lock().unwrap().clone()
     }
@@ -564,7 +628,7 @@ impl SyncService {
         let history_id = Self::generate_id();
         let history = SyncHistory {
             id: history_id.clone(),
-            sync_type: SyncType::DataType(data_type),
+            sync_type: SyncType::DataType(data_type.clone()),
             started_at: Utc::now(),
             completed_at: None,
             status: SyncResultStatus::InProgress,
@@ -576,10 +640,110 @@ impl SyncService {
         };
         
         self.sync_history.lock().unwrap().push(history);
+
+        if data_type == SyncDataType::Bookmarks {
+            let result = self.sync_queued_bookmarks();
+            let (items_down, errors) = match &result {
+                Ok(count) => (*count, Vec::new()),
+                Err(e) => (0, vec![e.clone()]),
+            };
+            let mut history_list = self.sync_history.lock().unwrap();
+            if let Some(history) = history_list.iter_mut().find(|h| h.id == history_id) {
+                history.completed_at = Some(Utc::now());
+                history.status = if errors.is_empty() { SyncResultStatus::Success } else { SyncResultStatus::Failed };
+                history.items_downloaded = items_down;
+                history.errors = errors;
+            }
+            drop(history_list);
+            result?;
+        }
         
         Ok(history_id)
     }
 
+    // ==================== Bookmark Merge ====================
+
+    /// Merge the queued bookmark changes of this device with those received from
+    /// others, then drop them from the queue. Returns how many remote changes were applied.
+    fn sync_queued_bookmarks(&self) -> Result<u32, String> {
+        let queued: Vec<SyncItem> = self.sync_queue.lock().unwrap()
+            .iter()
+            .filter(|item| item.data_type == SyncDataType::Bookmarks)
+            .cloned()
+            .collect();
+        if queued.is_empty() {
+            return Ok(0);
+        }
+
+        let mut local: HashMap<String, BookmarkNode> = HashMap::new();
+        let mut remote: HashMap<String, BookmarkNode> = HashMap::new();
+        for item in &queued {
+            let node: BookmarkNode = serde_json::from_value(self.read_sync_item(item)?)
+                .map_err(|e| format!("Invalid bookmark sync item {}: {}", item.id, e))?;
+            let side = if item.device_id == self.current_device_id { &mut local } else { &mut remote };
+            // Several changes to the same bookmark collapse into the latest one
+            let is_latest = match side.get(&node.id) {
+                Some(existing) => existing.modified_at <= node.modified_at,
+                None => true,
+            };
+            if is_latest {
+                side.insert(node.id.clone(), node);
+            }
+        }
+        let remote_count = remote.len() as u32;
+
+        self.merge_bookmarks(local.into_values().collect(), remote.into_values().collect())?;
+        let merged_ids: HashSet<&str> = queued.iter().map(|item| item.id.as_str()).collect();
+        self.sync_queue.lock().unwrap().retain(|item| !merged_ids.contains(item.id.as_str()));
+        Ok(remote_count)
+    }
+
+    /// Three-way merge of local and remote bookmark changes against the last merged
+    /// tree. Items moved to different folders on both sides are recorded as conflicts.
+    pub fn merge_bookmarks(&self, local: Vec<BookmarkNode>, remote: Vec<BookmarkNode>) -> Result<BookmarkMerge, String> {
+        let mut base = self.bookmark_base.lock().unwrap();
+        let merge = merge_bookmark_trees(&base, &local, &remote);
+        *base = merge.nodes.clone();
+        drop(base);
+
+        let mut conflicts = self.conflicts.lock().unwrap();
+        for conflict in &merge.conflicts {
+            // A newer move of the same item replaces the pending conflict
+            conflicts.retain(|_, c| c.resolved || c.data_type != SyncDataType::Bookmarks || c.item_id != conflict.item_id);
+            let version = |node: &BookmarkNode, device_id: &str| -> Result<SyncItem, String> {
+                let data = serde_json::to_value(node).map_err(|e| e.to_string())?;
+                Ok(SyncItem {
+                    id: node.id.clone(),
+                    data_type: SyncDataType::Bookmarks,
+                    checksum: Self::calculate_checksum(&data),
+                    data,
+                    version: 1,
+                    created_at: node.modified_at,
+                    modified_at: node.modified_at,
+                    device_id: device_id.to_string(),
+                    is_deleted: node.is_deleted,
+                    key_version: None,
+                })
+            };
+            let sync_conflict = SyncConflict {
+                id: Self::generate_id(),
+                item_id: conflict.item_id.clone(),
+                data_type: SyncDataType::Bookmarks,
+                local_version: version(&conflict.local, &self.current_device_id)?,
+                server_version: version(&conflict.remote, "server")?,
+                detected_at: Utc::now(),
+                resolved: false,
+                resolution: None,
+            };
+            conflicts.insert(sync_conflict.id.clone(), sync_conflict);
+        }
+        Ok(merge)
+    }
+
+    pub fn get_bookmark_tree(&self) -> Vec<BookmarkNode> {
+        self.bookmark_base.lock().unwrap().clone()
+    }
+
     // ==================== Conflicts ====================
 
     pub fn get_conflicts(&self) -> Vec<SyncConflict> {
@@ -644,6 +808,8 @@ impl SyncService {
             created_at: Utc::now(),
             expires_at: None,
             is_active: true,
+            data_type: None,
+            version: 1,
         };
         
         let mut settings = self.settings.lock().unwrap();
@@ -654,29 +820,107 @@ impl SyncService {
         Ok(key)
     }
 
+    /// Primary/recovery keys plus every version of each data-type key
     pub fn get_encryption_keys(&self) -> Vec<EncryptionKey> {
-        self.encryption_keys.lock().unwrap().values().cloned().collect()
+        let mut keys: Vec<EncryptionKey> = self.encryption_keys.lock().unwrap().values().cloned().collect();
+        let ring = self.key_ring.lock().unwrap();
+        let mut type_keys = ring.all_versions();
+        type_keys.sort_by(|a, b| format!("{:?}", a.0).cmp(&format!("{:?}", b.0)).then(a.1.version.cmp(&b.1.version)));
+        for (data_type, version) in type_keys {
+            let latest = ring.latest_version(&data_type);
+            keys.push(EncryptionKey {
+                key_id: version.key_id,
+                key_type: KeyType::DataType,
+                created_at: version.created_at,
+                expires_at: None,
+                is_active: latest == Some(version.version),
+                data_type: Some(data_type),
+                version: version.version,
+            });
+        }
+        keys
     }
 
     pub fn get_active_key(&self) -> Option<EncryptionKey> {
         self.encryption_keys.lock().unwrap()
             .values()
-            .find(|k| k.is_active)
+            .find(|k| k.is_active && k.key_type == KeyType::Primary)
             .cloned()
     }
 
+    /// Rotate the primary key and every data-type key, re-encrypting all queued items
     pub fn rotate_encryption_key(&self) -> Result<EncryptionKey, String> {
         // Deactivate current key
         let mut keys = self.encryption_keys.lock().unwrap();
         for key in keys.values_mut() {
-            key.is_active = false;
+            if key.key_type == KeyType::Primary {
+                key.is_active = false;
+            }
         }
         drop(keys);
+
+        let data_types = self.key_ring.lock().unwrap().data_types();
+        for data_type in data_types {
+            self.rotate_data_type_key(data_type)?;
+        }
         
         // Generate new key
         self.generate_encryption_key()
     }
 
+    /// Rotate one data type's key; only that type's queued items are re-encrypted.
+    /// Returns the new key and how many items were re-sealed.
+    pub fn rotate_data_type_key(&self, data_type: SyncDataType) -> Result<(EncryptionKey, u32), String> {
+        let mut ring = self.key_ring.lock().unwrap();
+        let mut queue = self.sync_queue.lock().unwrap();
+
+        // Open everything first so a failure leaves the queue on the old key
+        let mut reopened = Vec::new();
+        for (index, item) in queue.iter().enumerate() {
+            if item.data_type != data_type || item.key_version.is_none() {
+                continue;
+            }
+            let sealed: SealedPayload = serde_json::from_value(item.data.clone()).map_err(|e| e.to_string())?;
+            reopened.push((index, ring.open(&data_type, &sealed)?));
+        }
+
+        let version = ring.rotate(&data_type);
+        let count = reopened.len() as u32;
+        for (index, plaintext) in reopened {
+            let sealed = ring.seal(&data_type, &plaintext)?;
+            let item = &mut queue[index];
+            item.key_version = Some(sealed.key_version);
+            item.data = serde_json::to_value(&sealed).map_err(|e| e.to_string())?;
+            item.modified_at = Utc::now();
+        }
+        log::info!("Rotated {:?} sync key to v{} ({} items re-encrypted)", data_type, version.version, count);
+
+        let key = EncryptionKey {
+            key_id: version.key_id,
+            key_type: KeyType::DataType,
+            created_at: version.created_at,
+            expires_at: None,
+            is_active: true,
+            data_type: Some(data_type),
+            version: version.version,
+        };
+        Ok((key, count))
+    }
+
+    /// Bring a device up to the current key for a type. Returns the new key wrapped
+    /// under the key-wrapping key, or `None` if the device already has it.
+    pub fn fetch_data_type_key(&self, device_id: &str, data_type: SyncDataType) -> Result<Option<WrappedDataKey>, String> {
+        let mut devices = self.devices.lock().unwrap();
+        let device = devices.get_mut(device_id).ok_or("Device not found")?;
+        let known = device.key_versions.get(&data_type).copied().unwrap_or(0);
+
+        let wrapped = self.key_ring.lock().unwrap().wrapped_key_for(&data_type, known)?;
+        if let Some(wrapped) = &wrapped {
+            device.key_versions.insert(data_type, wrapped.key_version);
+        }
+        Ok(wrapped)
+    }
+
     pub fn create_recovery_key(&self) -> Result<EncryptionKey, String> {
         let key = EncryptionKey {
             key_id: Self::generate_id(),
@@ -684,6 +928,8 @@ impl SyncService {
             created_at: Utc::now(),
             expires_at: None,
             is_active: true,
+            data_type: None,
+            version: 1,
         };
         
         self.encryption_keys.lock().unwrap().insert(key.key_id.clone(), key.clone());
@@ -744,3 +990,104 @@ pub struct SyncExportData {
     pub stats: SyncStats,
     pub exported_at: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotating_one_data_type_key_reencrypts_only_that_type() {
+        let service = SyncService::new();
+        service.login("user@example.com".to_string(), "user-1".to_string()).unwrap();
+        let extension_data = serde_json::json!({"id": "ext-1", "settings": {"theme": "dark"}});
+        service.queue_sync_item(SyncDataType::Extensions, extension_data.clone()).unwrap();
+        service.queue_sync_item(SyncDataType::Extensions, serde_json::json!({"id": "ext-2"})).unwrap();
+        service.queue_sync_item(SyncDataType::Passwords, serde_json::json!({"site": "bank.com"})).unwrap();
+        let before = service.get_sync_queue();
+        assert!(before.iter().all(|item| item.key_version == Some(1)));
+
+        let (key, reencrypted) = service.rotate_data_type_key(SyncDataType::Extensions).unwrap();
+        assert_eq!(key.version, 2);
+        assert_eq!(reencrypted, 2);
+
+        let after = service.get_sync_queue();
+        for (old, new) in before.iter().zip(&after) {
+            if new.data_type == SyncDataType::Extensions {
+                assert_eq!(new.key_version, Some(2));
+                assert_ne!(new.data, old.data);
+            } else {
+                // Passwords keep their key and ciphertext
+                assert_eq!(new.key_version, Some(1));
+                assert_eq!(new.data, old.data);
+            }
+        }
+        assert_eq!(service.read_sync_item(&after[0]).unwrap(), extension_data);
+        assert_eq!(service.read_sync_item(&after[2]).unwrap(), serde_json::json!({"site": "bank.com"}));
+
+        let keys = service.get_encryption_keys();
+        let active_version = |data_type: SyncDataType| {
+            keys.iter()
+                .find(|k| k.is_active && k.data_type.as_ref() == Some(&data_type))
+                .map(|k| k.version)
+        };
+        assert_eq!(active_version(SyncDataType::Extensions), Some(2));
+        assert_eq!(active_version(SyncDataType::Passwords), Some(1));
+
+        // A device that never saw v2 gets it wrapped once
+        let device_id = service.get_current_device().unwrap().device_id;
+        let wrapped = service.fetch_data_type_key(&device_id, SyncDataType::Extensions).unwrap().unwrap();
+        assert_eq!(wrapped.key_version, 2);
+        assert!(service.fetch_data_type_key(&device_id, SyncDataType::Extensions).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_bookmark_sync_reports_move_move_conflict() {
+        let service = SyncService::new();
+        service.login("user@example.com".to_string(), "user-1".to_string()).unwrap();
+        let t0 = Utc::now();
+        let node = |id: &str, parent: Option<&str>, folder: bool, at: DateTime<Utc>| BookmarkNode {
+            id: id.to_string(),
+            parent_id: parent.map(str::to_string),
+            title: id.to_string(),
+            url: (!folder).then(|| format!("https://{}.example", id)),
+            is_folder: folder,
+            position: 0,
+            modified_at: at,
+            is_deleted: false,
+        };
+        let base = vec![
+            node("work", None, true, t0),
+            node("home", None, true, t0),
+            node("doc", None, false, t0),
+        ];
+        service.merge_bookmarks(base, Vec::new()).unwrap();
+
+        let local_move = node("doc", Some("work"), false, t0 + Duration::seconds(1));
+        service.queue_sync_item(SyncDataType::Bookmarks, serde_json::to_value(&local_move).unwrap()).unwrap();
+        let remote_data = serde_json::to_value(node("doc", Some("home"), false, t0 + Duration::seconds(2))).unwrap();
+        let received = service.receive_remote_items(vec![SyncItem {
+            id: "remote-1".to_string(),
+            data_type: SyncDataType::Bookmarks,
+            checksum: SyncService::calculate_checksum(&remote_data),
+            data: remote_data,
+            version: 1,
+            created_at: t0,
+            modified_at: t0,
+            device_id: "other-device".to_string(),
+            is_deleted: false,
+            key_version: None,
+        }]);
+        assert_eq!(received, 1);
+
+        service.sync_data_type(SyncDataType::Bookmarks).unwrap();
+        assert!(service.get_sync_queue().is_empty());
+
+        let conflicts = service.get_unresolved_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].item_id, "doc");
+        assert_eq!(conflicts[0].data_type, SyncDataType::Bookmarks);
+        let tree = service.get_bookmark_tree();
+        let doc = tree.iter().find(|n| n.id == "doc").unwrap();
+        assert_eq!(doc.parent_id.as_deref(), Some("home"));
+    }
+}
@@ -4,6 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
@@ -227,6 +229,9 @@ pub struct TabMetadata {
     pub playing_audio: bool,
     pub created_at: i64,
     pub last_accessed: i64,
+    /// Tab this one was opened from (link click, middle click, window.open)
+    #[serde(default)]
+    pub opener_id: Option<String>,
 }
 
 impl TabMetadata {
@@ -247,6 +252,7 @@ impl TabMetadata {
             playing_audio: false,
             created_at: now,
             last_accessed: now,
+            opener_id: None,
         }
     }
 
@@ -260,6 +266,170 @@ impl TabMetadata {
     }
 }
 
+// ============ Tab Tree ============
+
+/// What happens to a tab's children when it is closed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChildCloseBehavior {
+    /// Close the whole subtree
+    CloseChildren,
+    /// Children take the closed tab's place under its parent
+    #[default]
+    PromoteChildren,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TabTreeEntry {
+    parent_id: Option<String>,
+    children: Vec<String>,
+    collapsed: bool,
+}
+
+/// Opener relationships for the vertical tab tree: a tab opened from
+/// another tab becomes its child
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TabTree {
+    entries: HashMap<String, TabTreeEntry>,
+    roots: Vec<String>,
+}
+
+/// A tab and its subtree as shown in the vertical tab strip
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TabTreeNode {
+    pub tab_id: String,
+    /// `None` for a tab restored in the tree but not registered yet this session
+    pub tab: Option<TabMetadata>,
+    pub depth: usize,
+    pub collapsed: bool,
+    pub children: Vec<TabTreeNode>,
+}
+
+impl TabTree {
+    /// Add a tab under its opener, or as a root. Tabs already in the tree
+    /// (restored from a previous session) keep their place.
+    pub fn insert(&mut self, tab_id: &str, opener_id: Option<&str>) {
+        if self.entries.contains_key(tab_id) {
+            return;
+        }
+        let parent_id = opener_id
+            .filter(|opener| *opener != tab_id && self.entries.contains_key(*opener))
+            .map(String::from);
+        self.siblings_mut(parent_id.as_deref()).push(tab_id.to_string());
+        self.entries.insert(
+            tab_id.to_string(),
+            TabTreeEntry {
+                parent_id,
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn contains(&self, tab_id: &str) -> bool {
+        self.entries.contains_key(tab_id)
+    }
+
+    pub fn parent_of(&self, tab_id: &str) -> Option<&str> {
+        self.entries.get(tab_id)?.parent_id.as_deref()
+    }
+
+    pub fn children_of(&self, tab_id: &str) -> &[String] {
+        self.entries
+            .get(tab_id)
+            .map(|entry| entry.children.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn roots(&self) -> &[String] {
+        &self.roots
+    }
+
+    /// The tab followed by all of its descendants, depth first
+    pub fn subtree(&self, tab_id: &str) -> Vec<String> {
+        if !self.entries.contains_key(tab_id) {
+            return Vec::new();
+        }
+        let mut ids = vec![tab_id.to_string()];
+        for child in self.children_of(tab_id) {
+            ids.extend(self.subtree(child));
+        }
+        ids
+    }
+
+    /// Remove a tab; returns every tab that left the tree, the tab itself first
+    pub fn remove(&mut self, tab_id: &str, behavior: ChildCloseBehavior) -> Vec<String> {
+        let Some(entry) = self.entries.get(tab_id).cloned() else {
+            return Vec::new();
+        };
+
+        let (replacement, removed) = match behavior {
+            ChildCloseBehavior::CloseChildren => (Vec::new(), self.subtree(tab_id)),
+            ChildCloseBehavior::PromoteChildren => {
+                // Orphans go to the grandparent (or become roots)
+                for child in &entry.children {
+                    if let Some(child) = self.entries.get_mut(child) {
+                        child.parent_id = entry.parent_id.clone();
+                    }
+                }
+                (entry.children.clone(), vec![tab_id.to_string()])
+            }
+        };
+
+        let siblings = self.siblings_mut(entry.parent_id.as_deref());
+        if let Some(position) = siblings.iter().position(|id| id == tab_id) {
+            siblings.splice(position..position + 1, replacement);
+        }
+        for id in &removed {
+            self.entries.remove(id);
+        }
+        removed
+    }
+
+    pub fn set_collapsed(&mut self, tab_id: &str, collapsed: bool) -> bool {
+        match self.entries.get_mut(tab_id) {
+            Some(entry) => {
+                entry.collapsed = collapsed;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn nodes(&self, tabs: &HashMap<String, TabMetadata>) -> Vec<TabTreeNode> {
+        self.roots
+            .iter()
+            .map(|id| self.node(id, 0, tabs))
+            .collect()
+    }
+
+    fn node(&self, tab_id: &str, depth: usize, tabs: &HashMap<String, TabMetadata>) -> TabTreeNode {
+        TabTreeNode {
+            tab_id: tab_id.to_string(),
+            tab: tabs.get(tab_id).cloned(),
+            depth,
+            collapsed: self
+                .entries
+                .get(tab_id)
+                .map(|entry| entry.collapsed)
+                .unwrap_or(false),
+            children: self
+                .children_of(tab_id)
+                .iter()
+                .map(|child| self.node(child, depth + 1, tabs))
+                .collect(),
+        }
+    }
+
+    fn siblings_mut(&mut self, parent_id: Option<&str>) -> &mut Vec<String> {
+        match parent_id {
+            Some(parent_id) if self.entries.contains_key(parent_id) => {
+                &mut self.entries.get_mut(parent_id).expect("checked above").children
+            }
+            _ => &mut self.roots,
+        }
+    }
+}
+
 /// AI-suggested grouping
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupSuggestion {
@@ -308,6 +478,9 @@ pub struct TabGroupsConfig {
     pub vertical_tabs_enabled: bool,
     pub stacking_enabled: bool,
     pub grouping_rules: Vec<GroupingRule>,
+    /// Default for closing a tab that has children in the tab tree
+    #[serde(default)]
+    pub child_close_behavior: ChildCloseBehavior,
 }
 
 impl Default for TabGroupsConfig {
@@ -324,6 +497,7 @@ impl Default for TabGroupsConfig {
             vertical_tabs_enabled: false,
             stacking_enabled: true,
             grouping_rules: Self::default_rules(),
+            child_close_behavior: ChildCloseBehavior::default(),
         }
     }
 }
@@ -411,6 +585,9 @@ pub struct CubeTabGroups {
     ungrouped_tabs: Vec<String>,
     config: TabGroupsConfig,
     domain_categories: Vec<DomainCategory>,
+    tree: TabTree,
+    /// Where the tab tree is persisted between sessions
+    tree_path: Option<PathBuf>,
 }
 
 impl CubeTabGroups {
@@ -421,6 +598,27 @@ impl CubeTabGroups {
             ungrouped_tabs: Vec::new(),
             config: TabGroupsConfig::default(),
             domain_categories: Self::init_domain_categories(),
+            tree: TabTree::default(),
+            tree_path: None,
+        }
+    }
+
+    /// Manager whose tab tree is loaded from and saved to `tree_path`
+    pub fn with_tree_storage(tree_path: PathBuf) -> Self {
+        let tree = fs::read_to_string(&tree_path)
+            .ok()
+            .and_then(|json| match serde_json::from_str(&json) {
+                Ok(tree) => Some(tree),
+                Err(e) => {
+                    log::warn!("Ignoring unreadable tab tree {:?}: {}", tree_path, e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            tree,
+            tree_path: Some(tree_path),
+            ..Self::new()
         }
     }
 
@@ -596,6 +794,8 @@ impl CubeTabGroups {
 
     pub fn register_tab(&mut self, tab: TabMetadata) -> String {
         let tab_id = tab.id.clone();
+        self.tree.insert(&tab_id, tab.opener_id.as_deref());
+        self.save_tree();
         
         // Auto-group if enabled
         if self.config.auto_group_enabled {
@@ -620,7 +820,54 @@ impl CubeTabGroups {
         tab_id
     }
 
+    /// A tab went away on its own; its children move up to its parent
     pub fn unregister_tab(&mut self, tab_id: &str) -> bool {
+        if !self
+            .tree
+            .remove(tab_id, ChildCloseBehavior::PromoteChildren)
+            .is_empty()
+        {
+            self.save_tree();
+        }
+        self.forget_tab(tab_id)
+    }
+
+    /// Close a tab, handling its children per `behavior` (or the configured
+    /// default). Returns the ids of every tab to close, the tab itself first.
+    pub fn close_tab(&mut self, tab_id: &str, behavior: Option<ChildCloseBehavior>) -> Vec<String> {
+        let behavior = behavior.unwrap_or(self.config.child_close_behavior);
+        let mut closed = self.tree.remove(tab_id, behavior);
+        if closed.is_empty() {
+            if self.tabs.contains_key(tab_id) {
+                closed.push(tab_id.to_string());
+            }
+        } else {
+            self.save_tree();
+        }
+        for id in &closed {
+            self.forget_tab(id);
+        }
+        closed
+    }
+
+    /// Close a tab and everything opened from it
+    pub fn close_subtree(&mut self, tab_id: &str) -> Vec<String> {
+        self.close_tab(tab_id, Some(ChildCloseBehavior::CloseChildren))
+    }
+
+    pub fn get_tab_tree(&self) -> Vec<TabTreeNode> {
+        self.tree.nodes(&self.tabs)
+    }
+
+    pub fn set_subtree_collapsed(&mut self, tab_id: &str, collapsed: bool) -> bool {
+        let changed = self.tree.set_collapsed(tab_id, collapsed);
+        if changed {
+            self.save_tree();
+        }
+        changed
+    }
+
+    fn forget_tab(&mut self, tab_id: &str) -> bool {
         if let Some(tab) = self.tabs.remove(tab_id) {
             // Remove from group if any
             if let Some(group_id) = &tab.group_id {
@@ -638,6 +885,16 @@ impl CubeTabGroups {
         }
     }
 
+    fn save_tree(&self) {
+        let Some(path) = &self.tree_path else { return };
+        let result = serde_json::to_string_pretty(&self.tree)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(path, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            log::warn!("Failed to save tab tree to {:?}: {}", path, e);
+        }
+    }
+
     pub fn get_tab(&self, tab_id: &str) -> Option<&TabMetadata> {
         self.tabs.get(tab_id)
     }
@@ -1091,3 +1348,69 @@ impl Default for TabGroupsService {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(groups: &mut CubeTabGroups, id: &str, opener: Option<&str>) {
+        let mut tab = TabMetadata::new(id.to_string(), format!("https://example.com/{}", id), id.to_string());
+        tab.opener_id = opener.map(String::from);
+        groups.register_tab(tab);
+    }
+
+    fn shape(nodes: &[TabTreeNode]) -> String {
+        nodes
+            .iter()
+            .map(|node| {
+                if node.children.is_empty() {
+                    node.tab_id.clone()
+                } else {
+                    format!("{}({})", node.tab_id, shape(&node.children))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_tab_tree_nesting_and_reparenting() {
+        let path = std::env::temp_dir().join(format!("cube-tab-tree-{}.json", Uuid::new_v4()));
+        let mut groups = CubeTabGroups::with_tree_storage(path.clone());
+
+        // root → article → {comments → reply, related}; other is unrelated
+        open(&mut groups, "root", None);
+        open(&mut groups, "article", Some("root"));
+        open(&mut groups, "comments", Some("article"));
+        open(&mut groups, "reply", Some("comments"));
+        open(&mut groups, "related", Some("article"));
+        open(&mut groups, "other", None);
+        assert_eq!(shape(&groups.get_tab_tree()), "root(article(comments(reply) related)) other");
+        assert_eq!(groups.get_tab_tree()[0].children[0].children[0].children[0].depth, 3);
+
+        // Closing a parent promotes its children into its place under the grandparent
+        assert_eq!(groups.close_tab("article", None), ["article"]);
+        assert_eq!(shape(&groups.get_tab_tree()), "root(comments(reply) related) other");
+        assert_eq!(groups.tree.parent_of("comments"), Some("root"));
+
+        // A tab that disappears on its own does the same
+        assert!(groups.unregister_tab("root"));
+        assert_eq!(shape(&groups.get_tab_tree()), "comments(reply) related other");
+
+        assert!(groups.set_subtree_collapsed("comments", true));
+        assert!(groups.get_tab_tree()[0].collapsed);
+
+        // The tree survives a restart
+        let restored = CubeTabGroups::with_tree_storage(path.clone());
+        let nodes = restored.get_tab_tree();
+        assert_eq!(shape(&nodes), "comments(reply) related other");
+        assert!(nodes[0].collapsed);
+        assert!(nodes[0].tab.is_none());
+
+        assert_eq!(groups.close_subtree("comments"), ["comments", "reply"]);
+        assert!(groups.get_tab("reply").is_none());
+        assert_eq!(shape(&groups.get_tab_tree()), "related other");
+
+        let _ = fs::remove_file(path);
+    }
+}
@@ -2,6 +2,7 @@
 // Superior to Chrome Profiles, Arc Spaces, and Vivaldi Workspaces
 // Organize tabs by project, context, or client with full isolation
 
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -89,6 +90,15 @@ pub enum SwitchAnimation {
     Scale,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub enum ScheduleSwitchMode {
+    /// Ask the user before switching
+    #[default]
+    Confirm,
+    /// Switch without prompting
+    Silent,
+}
+
 // ==================== Structs ====================
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +118,13 @@ pub struct WorkspaceSettings {
     pub show_tab_count: bool,
     pub keyboard_shortcuts: bool,
     pub quick_switch_enabled: bool,
+    /// Scheduled switches are skipped for this long after a manual switch
+    #[serde(default = "default_schedule_override_minutes")]
+    pub schedule_override_minutes: u32,
+}
+
+fn default_schedule_override_minutes() -> u32 {
+    60
 }
 
 impl Default for WorkspaceSettings {
@@ -128,6 +145,7 @@ impl Default for WorkspaceSettings {
             show_tab_count: true,
             keyboard_shortcuts: true,
             quick_switch_enabled: true,
+            schedule_override_minutes: default_schedule_override_minutes(),
         }
     }
 }
@@ -233,6 +251,43 @@ pub struct WorkspaceStats {
     pub tabs_opened_today: u32,
 }
 
+/// A recurring time window in local wall-clock time. `end_time` before
+/// `start_time` spans midnight (e.g. 22:00-06:00).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleWindow {
+    pub days: Vec<Weekday>,
+    pub start_time: String, // "HH:MM"
+    pub end_time: String,   // "HH:MM"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSchedule {
+    pub enabled: bool,
+    pub windows: Vec<ScheduleWindow>,
+    /// Higher wins when windows of several workspaces overlap
+    #[serde(default)]
+    pub priority: i32,
+    /// Active whenever no other workspace's window is ("Personal otherwise")
+    #[serde(default)]
+    pub fallback: bool,
+    #[serde(default)]
+    pub mode: ScheduleSwitchMode,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "outcome", rename_all = "camelCase")]
+pub enum ScheduledSwitch {
+    #[serde(rename_all = "camelCase")]
+    Switched {
+        workspace_id: String,
+        hibernated_workspace_id: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    NeedsConfirmation { workspace_id: String },
+    #[serde(rename_all = "camelCase")]
+    SkippedManualOverride { workspace_id: String },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuickSwitchItem {
     pub workspace_id: String,
@@ -255,6 +310,11 @@ pub struct BrowserWorkspacesService {
     stats: WorkspaceStats,
     switches_today: u32,
     tabs_opened_today: u32,
+    schedules: HashMap<String, WorkspaceSchedule>,
+    last_manual_switch: Option<NaiveDateTime>,
+    /// Workspace the schedule pointed at on the previous tick; switches only
+    /// happen when this changes, i.e. when a boundary is crossed
+    last_scheduled_target: Option<String>,
 }
 
 impl BrowserWorkspacesService {
@@ -278,6 +338,9 @@ impl BrowserWorkspacesService {
             },
             switches_today: 0,
             tabs_opened_today: 0,
+            schedules: HashMap::new(),
+            last_manual_switch: None,
+            last_scheduled_target: None,
         };
 
         // Create default workspace
@@ -559,7 +622,18 @@ impl BrowserWorkspacesService {
         self.active_workspace_id.clone()
     }
 
+    /// User-initiated switch; holds off scheduled switches for a while.
     pub fn switch_workspace(&mut self, workspace_id: &str) -> Result<Workspace, String> {
+        self.switch_workspace_at(workspace_id, chrono::Local::now().naive_local())
+    }
+
+    pub fn switch_workspace_at(&mut self, workspace_id: &str, at: NaiveDateTime) -> Result<Workspace, String> {
+        let workspace = self.activate_workspace(workspace_id)?;
+        self.last_manual_switch = Some(at);
+        Ok(workspace)
+    }
+
+    fn activate_workspace(&mut self, workspace_id: &str) -> Result<Workspace, String> {
         let workspace = self.workspaces
             .get_mut(workspace_id)
             .ok_or_else(|| "Workspace not found".to_string())?;
@@ -621,6 +695,7 @@ impl BrowserWorkspacesService {
 
         self.workspaces.remove(workspace_id);
         self.snapshots.remove(workspace_id);
+        self.schedules.remove(workspace_id);
 
         // Switch to another workspace if deleting active
         if self.active_workspace_id.as_deref() == Some(workspace_id) {
@@ -1052,6 +1127,125 @@ impl BrowserWorkspacesService {
         self.switch_workspace(&prev_id).ok()
     }
 
+    // ==================== Schedules ====================
+
+    pub fn set_schedule(&mut self, workspace_id: &str, schedule: Option<WorkspaceSchedule>) -> Result<(), String> {
+        if !self.workspaces.contains_key(workspace_id) {
+            return Err("Workspace not found".to_string());
+        }
+
+        match schedule {
+            Some(schedule) => {
+                if !schedule.fallback && schedule.windows.is_empty() {
+                    return Err("Schedule needs at least one time window".to_string());
+                }
+                for window in &schedule.windows {
+                    let start = parse_schedule_time(&window.start_time)?;
+                    let end = parse_schedule_time(&window.end_time)?;
+                    if start == end {
+                        return Err("Schedule window start and end must differ".to_string());
+                    }
+                    if window.days.is_empty() {
+                        return Err("Schedule window needs at least one day".to_string());
+                    }
+                }
+                self.schedules.insert(workspace_id.to_string(), schedule);
+            }
+            None => {
+                self.schedules.remove(workspace_id);
+            }
+        }
+
+        // Re-evaluate on the next tick
+        self.last_scheduled_target = None;
+        Ok(())
+    }
+
+    pub fn get_schedules(&self) -> HashMap<String, WorkspaceSchedule> {
+        self.schedules.clone()
+    }
+
+    /// The workspace the schedules say should be active at `now`. Overlapping
+    /// windows resolve by priority, then by workspace position.
+    pub fn scheduled_workspace_at(&self, now: NaiveDateTime) -> Option<String> {
+        let candidates = self.schedules.iter().filter(|(id, schedule)| {
+            schedule.enabled
+                && self
+                    .workspaces
+                    .get(*id)
+                    .map(|w| w.status != WorkspaceStatus::Archived)
+                    .unwrap_or(false)
+        });
+
+        let pick = |fallback: bool| {
+            candidates
+                .clone()
+                .filter(|(_, schedule)| {
+                    if fallback {
+                        schedule.fallback
+                    } else {
+                        schedule.windows.iter().any(|w| window_contains(w, now))
+                    }
+                })
+                .max_by(|(a_id, a), (b_id, b)| {
+                    a.priority.cmp(&b.priority).then_with(|| {
+                        let position = |id: &str| self.workspaces.get(id).map(|w| w.position).unwrap_or(usize::MAX);
+                        position(b_id).cmp(&position(a_id))
+                    })
+                })
+                .map(|(id, _)| id.clone())
+        };
+
+        pick(false).or_else(|| pick(true))
+    }
+
+    /// Advances the scheduler to `now`. Returns what happened when a schedule
+    /// boundary was crossed; `None` when nothing changed.
+    pub fn run_schedule_tick(&mut self, now: NaiveDateTime) -> Option<ScheduledSwitch> {
+        let target = self.scheduled_workspace_at(now);
+        if target == self.last_scheduled_target {
+            return None;
+        }
+        self.last_scheduled_target = target.clone();
+        let workspace_id = target?;
+
+        if self.active_workspace_id.as_deref() == Some(workspace_id.as_str()) {
+            return None;
+        }
+
+        let override_window = Duration::minutes(self.settings.schedule_override_minutes as i64);
+        if let Some(manual) = self.last_manual_switch {
+            if now >= manual && now - manual < override_window {
+                return Some(ScheduledSwitch::SkippedManualOverride { workspace_id });
+            }
+        }
+
+        if self.schedules.get(&workspace_id).map(|s| &s.mode) == Some(&ScheduleSwitchMode::Confirm) {
+            return Some(ScheduledSwitch::NeedsConfirmation { workspace_id });
+        }
+
+        self.apply_scheduled_switch(&workspace_id).ok()
+    }
+
+    /// Performs a scheduled switch (directly in silent mode, or once the user
+    /// confirmed). The previous workspace goes to sleep through auto-sleep.
+    pub fn apply_scheduled_switch(&mut self, workspace_id: &str) -> Result<ScheduledSwitch, String> {
+        let previous = self.active_workspace_id.clone().filter(|id| id != workspace_id);
+        self.activate_workspace(workspace_id)?;
+
+        let hibernated_workspace_id = previous.filter(|id| {
+            self.workspaces
+                .get(id)
+                .map(|w| w.status == WorkspaceStatus::Sleeping)
+                .unwrap_or(false)
+        });
+
+        Ok(ScheduledSwitch::Switched {
+            workspace_id: workspace_id.to_string(),
+            hibernated_workspace_id,
+        })
+    }
+
     // ==================== Statistics ====================
 
     fn update_stats(&mut self) {
@@ -1122,3 +1316,112 @@ impl Default for BrowserWorkspacesService {
         Self::new()
     }
 }
+
+fn parse_schedule_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .map_err(|_| format!("Invalid schedule time '{}', expected HH:MM", value))
+}
+
+fn window_contains(window: &ScheduleWindow, now: NaiveDateTime) -> bool {
+    let (Ok(start), Ok(end)) = (parse_schedule_time(&window.start_time), parse_schedule_time(&window.end_time)) else {
+        return false;
+    };
+    let time = now.time();
+    let today = now.weekday();
+
+    if start < end {
+        window.days.contains(&today) && time >= start && time < end
+    } else {
+        // Overnight: the part after midnight belongs to the previous day's window
+        (window.days.contains(&today) && time >= start)
+            || (window.days.contains(&today.pred()) && time < end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2024-01-01 was a Monday
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn weekdays_nine_to_five() -> WorkspaceSchedule {
+        WorkspaceSchedule {
+            enabled: true,
+            windows: vec![ScheduleWindow {
+                days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+                start_time: "09:00".to_string(),
+                end_time: "17:00".to_string(),
+            }],
+            priority: 0,
+            fallback: false,
+            mode: ScheduleSwitchMode::Silent,
+        }
+    }
+
+    fn setup() -> (BrowserWorkspacesService, String, String) {
+        let mut service = BrowserWorkspacesService::new();
+        // Ids are second-resolution timestamps, so build both from one workspace
+        let base = service.create_workspace("Work".to_string(), None).unwrap();
+        service.workspaces.remove(&base.id);
+        let work = Workspace { id: "ws_work".to_string(), position: 1, ..base.clone() };
+        let personal = Workspace { id: "ws_personal".to_string(), name: "Personal".to_string(), position: 2, ..base };
+        service.workspaces.insert(work.id.clone(), work);
+        service.workspaces.insert(personal.id.clone(), personal);
+
+        service.set_schedule("ws_work", Some(weekdays_nine_to_five())).unwrap();
+        service
+            .set_schedule("ws_personal", Some(WorkspaceSchedule { windows: vec![], fallback: true, ..weekdays_nine_to_five() }))
+            .unwrap();
+        (service, "ws_work".to_string(), "ws_personal".to_string())
+    }
+
+    #[test]
+    fn test_schedule_boundary_switches_and_hibernates_previous() {
+        let (mut service, work, personal) = setup();
+
+        // Fast clock: tick every 15 minutes across Monday morning
+        let mut time = at(1, 8, 0);
+        let mut switches = Vec::new();
+        while time <= at(1, 10, 0) {
+            if let Some(outcome) = service.run_schedule_tick(time) {
+                switches.push((time, outcome));
+            }
+            time += Duration::minutes(15);
+        }
+
+        assert_eq!(switches.len(), 2);
+        assert_eq!(switches[0].0, at(1, 8, 0));
+        assert_eq!(
+            switches[1],
+            (at(1, 9, 0), ScheduledSwitch::Switched { workspace_id: work.clone(), hibernated_workspace_id: Some(personal.clone()) })
+        );
+        assert_eq!(service.get_active_workspace_id(), Some(work));
+        assert_eq!(service.get_workspace(&personal).unwrap().status, WorkspaceStatus::Sleeping);
+    }
+
+    #[test]
+    fn test_recent_manual_switch_skips_schedule_and_priority_breaks_overlaps() {
+        let (mut service, work, personal) = setup();
+        service.run_schedule_tick(at(1, 8, 0));
+        service.switch_workspace_at(&personal, at(1, 8, 30)).unwrap();
+
+        assert_eq!(
+            service.run_schedule_tick(at(1, 9, 0)),
+            Some(ScheduledSwitch::SkippedManualOverride { workspace_id: work.clone() })
+        );
+        assert_eq!(service.get_active_workspace_id(), Some(personal.clone()));
+        // No boundary crossed since, so the override sticks for the rest of the window
+        assert_eq!(service.run_schedule_tick(at(1, 11, 0)), None);
+
+        let mut focus = weekdays_nine_to_five();
+        focus.priority = 5;
+        focus.mode = ScheduleSwitchMode::Confirm;
+        service.set_schedule(&personal, Some(focus)).unwrap();
+        assert_eq!(service.scheduled_workspace_at(at(2, 10, 0)), Some(personal.clone()));
+        assert_eq!(service.scheduled_workspace_at(at(6, 10, 0)), None);
+    }
+}
@@ -0,0 +1,331 @@
+// CUBE Nexum - Live Call Transcription
+//
+// Folds streamed speech-to-text fragments from a two-leg call into speaker
+// attributed segments, raises keyword alerts as phrases are spoken and scores
+// each speaker's sentiment once the call ends.
+
+use serde::{Deserialize, Serialize};
+
+/// Input level below which a call leg is treated as silent
+const SPEECH_LEVEL_THRESHOLD: f32 = 0.05;
+/// Both legs are talking when the quieter one is at least this share of the louder
+const OVERLAP_RATIO: f32 = 0.5;
+/// A pause longer than this starts a new segment even for the same speaker
+const SEGMENT_GAP_MS: u64 = 1500;
+
+const POSITIVE_WORDS: &[&str] = &[
+    "thanks", "thank", "great", "perfect", "excellent", "happy", "appreciate", "helpful", "love",
+    "awesome", "good", "glad", "resolved", "wonderful",
+];
+const NEGATIVE_WORDS: &[&str] = &[
+    "cancel", "angry", "frustrated", "terrible", "awful", "worst", "hate", "unacceptable",
+    "problem", "broken", "refund", "disappointed", "complaint", "ridiculous", "bad",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Speaker {
+    Agent,
+    Caller,
+}
+
+impl Speaker {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Speaker::Agent => "Agent",
+            Speaker::Caller => "Caller",
+        }
+    }
+}
+
+/// One finalized fragment from the streaming recognizer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptChunk {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// RMS input level of the agent leg while the fragment was spoken (0.0 - 1.0)
+    pub agent_level: f32,
+    /// RMS input level of the caller leg while the fragment was spoken (0.0 - 1.0)
+    pub caller_level: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptSegment {
+    pub speaker: Speaker,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+    /// Both legs were talking; the text is attributed to the louder one
+    pub overlap: bool,
+    pub sentiment: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeywordAlert {
+    pub id: String,
+    pub call_sid: String,
+    pub conversation_id: Option<String>,
+    pub queue_id: Option<String>,
+    pub phrase: String,
+    pub speaker: Speaker,
+    pub context: String,
+    pub offset_ms: u64,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeakerSentiment {
+    pub speaker: Speaker,
+    pub score: f32,
+    pub label: String,
+    pub word_count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveTranscription {
+    pub call_sid: String,
+    pub conversation_id: Option<String>,
+    pub queue_id: Option<String>,
+    pub segments: Vec<TranscriptSegment>,
+    pub alerts: Vec<KeywordAlert>,
+    pub alert_phrases: Vec<String>,
+    pub speaker_sentiment: Vec<SpeakerSentiment>,
+    pub finished: bool,
+    /// Trailing words of the current segment, so phrases split across fragments still match
+    #[serde(skip)]
+    tail: Vec<String>,
+}
+
+impl LiveTranscription {
+    pub fn new(
+        call_sid: String,
+        conversation_id: Option<String>,
+        queue_id: Option<String>,
+        alert_phrases: Vec<String>,
+    ) -> Self {
+        Self {
+            call_sid,
+            conversation_id,
+            queue_id,
+            segments: Vec::new(),
+            alerts: Vec::new(),
+            alert_phrases,
+            speaker_sentiment: Vec::new(),
+            finished: false,
+            tail: Vec::new(),
+        }
+    }
+
+    /// Attribute a fragment to a speaker and return any alerts it raised
+    pub fn push_chunk(&mut self, chunk: TranscriptChunk) -> Vec<KeywordAlert> {
+        let text = chunk.text.trim();
+        if self.finished || text.is_empty() {
+            return Vec::new();
+        }
+        let (speaker, overlap) = diarize(chunk.agent_level, chunk.caller_level);
+
+        let continues = self.segments.last().is_some_and(|last| {
+            last.speaker == speaker && chunk.start_ms.saturating_sub(last.end_ms) <= SEGMENT_GAP_MS
+        });
+        if continues {
+            if let Some(last) = self.segments.last_mut() {
+                last.text.push(' ');
+                last.text.push_str(text);
+                last.end_ms = last.end_ms.max(chunk.end_ms);
+                last.overlap |= overlap;
+            }
+        } else {
+            self.tail.clear();
+            self.segments.push(TranscriptSegment {
+                speaker,
+                start_ms: chunk.start_ms,
+                end_ms: chunk.end_ms,
+                text: text.to_string(),
+                overlap,
+                sentiment: None,
+            });
+        }
+
+        let alerts = self.match_phrases(speaker, text, chunk.start_ms);
+        self.alerts.extend(alerts.iter().cloned());
+        alerts
+    }
+
+    /// Close the call and score each speaker's sentiment
+    pub fn finish(&mut self) -> Vec<SpeakerSentiment> {
+        if self.finished {
+            return self.speaker_sentiment.clone();
+        }
+        self.finished = true;
+
+        for segment in &mut self.segments {
+            segment.sentiment = Some(score_sentiment(&segment.text));
+        }
+        self.speaker_sentiment = [Speaker::Agent, Speaker::Caller]
+            .iter()
+            .filter_map(|speaker| {
+                let (weighted, words) = self
+                    .segments
+                    .iter()
+                    .filter(|s| s.speaker == *speaker)
+                    .fold((0.0_f32, 0_usize), |(weighted, words), s| {
+                        let count = s.text.split_whitespace().count();
+                        (weighted + s.sentiment.unwrap_or(0.0) * count as f32, words + count)
+                    });
+                if words == 0 {
+                    return None;
+                }
+                let score = weighted / words as f32;
+                Some(SpeakerSentiment {
+                    speaker: *speaker,
+                    score,
+                    label: sentiment_label(score).to_string(),
+                    word_count: words,
+                })
+            })
+            .collect();
+        self.speaker_sentiment.clone()
+    }
+
+    pub fn transcript(&self) -> String {
+        self.segments
+            .iter()
+            .map(|s| format!("{}: {}", s.speaker.label(), s.text))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn match_phrases(&mut self, speaker: Speaker, text: &str, offset_ms: u64) -> Vec<KeywordAlert> {
+        let fresh = normalize_words(text);
+        let carried = self.tail.len();
+        let mut words = std::mem::take(&mut self.tail);
+        words.extend(fresh);
+
+        let mut alerts = Vec::new();
+        let mut longest = 1;
+        for phrase in &self.alert_phrases {
+            let needle = normalize_words(phrase);
+            if needle.is_empty() {
+                continue;
+            }
+            longest = longest.max(needle.len());
+            // Only report matches that end in this fragment; earlier ones already fired
+            let hit = words
+                .windows(needle.len())
+                .enumerate()
+                .any(|(start, window)| window == needle.as_slice() && start + needle.len() > carried);
+            if hit {
+                alerts.push(KeywordAlert {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    call_sid: self.call_sid.clone(),
+                    conversation_id: self.conversation_id.clone(),
+                    queue_id: self.queue_id.clone(),
+                    phrase: phrase.clone(),
+                    speaker,
+                    context: text.to_string(),
+                    offset_ms,
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                });
+            }
+        }
+
+        let keep = longest - 1;
+        self.tail = words.split_off(words.len().saturating_sub(keep));
+        alerts
+    }
+}
+
+/// Dominant speaker for a fragment and whether both legs were talking
+pub fn diarize(agent_level: f32, caller_level: f32) -> (Speaker, bool) {
+    let speaker = if agent_level >= caller_level { Speaker::Agent } else { Speaker::Caller };
+    let (loud, quiet) = if agent_level >= caller_level {
+        (agent_level, caller_level)
+    } else {
+        (caller_level, agent_level)
+    };
+    let overlap = quiet >= SPEECH_LEVEL_THRESHOLD && quiet >= loud * OVERLAP_RATIO;
+    (speaker, overlap)
+}
+
+/// Lexicon sentiment in -1.0 ..= 1.0
+pub fn score_sentiment(text: &str) -> f32 {
+    let words = normalize_words(text);
+    let positive = words.iter().filter(|w| POSITIVE_WORDS.contains(&w.as_str())).count();
+    let negative = words.iter().filter(|w| NEGATIVE_WORDS.contains(&w.as_str())).count();
+    if positive + negative == 0 {
+        return 0.0;
+    }
+    (positive as f32 - negative as f32) / (positive + negative) as f32
+}
+
+pub fn sentiment_label(score: f32) -> &'static str {
+    if score > 0.2 {
+        "positive"
+    } else if score < -0.2 {
+        "negative"
+    } else {
+        "neutral"
+    }
+}
+
+fn normalize_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(start_ms: u64, text: &str, agent_level: f32, caller_level: f32) -> TranscriptChunk {
+        TranscriptChunk {
+            start_ms,
+            end_ms: start_ms + 900,
+            text: text.to_string(),
+            agent_level,
+            caller_level,
+        }
+    }
+
+    #[test]
+    fn test_two_speaker_call_is_diarized_and_alerts_fire() {
+        let mut call = LiveTranscription::new(
+            "CA123".to_string(),
+            Some("conv_1".to_string()),
+            Some("billing".to_string()),
+            vec!["cancel my subscription".to_string(), "Acme Telecom".to_string()],
+        );
+
+        assert!(call.push_chunk(chunk(0, "Thanks for calling, how can I help?", 0.6, 0.01)).is_empty());
+        assert!(call.push_chunk(chunk(1000, "Hi, I want to cancel my", 0.02, 0.7)).is_empty());
+        // Phrase completes in the next fragment
+        let alerts = call.push_chunk(chunk(2000, "subscription, Acme Telecom is cheaper.", 0.03, 0.8));
+        // Agent talks over the caller but the caller is louder
+        call.push_chunk(chunk(3000, "this is terrible", 0.4, 0.7));
+        call.push_chunk(chunk(4000, "I understand, let me help.", 0.5, 0.0));
+
+        let phrases: Vec<&str> = alerts.iter().map(|a| a.phrase.as_str()).collect();
+        assert_eq!(phrases, ["cancel my subscription", "Acme Telecom"]);
+        assert!(alerts.iter().all(|a| a.speaker == Speaker::Caller));
+
+        let speakers: Vec<Speaker> = call.segments.iter().map(|s| s.speaker).collect();
+        assert_eq!(speakers, [Speaker::Agent, Speaker::Caller, Speaker::Agent]);
+        assert!(call.segments[1].overlap);
+        assert!(call.segments[1].text.ends_with("this is terrible"));
+
+        let sentiment = call.finish();
+        let caller = sentiment.iter().find(|s| s.speaker == Speaker::Caller).unwrap();
+        let agent = sentiment.iter().find(|s| s.speaker == Speaker::Agent).unwrap();
+        assert!(caller.score < 0.0);
+        assert!(agent.score > 0.0);
+        assert_eq!(call.alerts.len(), 2);
+    }
+}
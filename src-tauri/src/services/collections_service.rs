@@ -1,8 +1,11 @@
 // Collections Service - Hierarchical bookmarks and page collections
 use crate::models::collections::*;
-use rusqlite::{params, Connection, Result as SqlResult};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
 pub struct CollectionsService {
     conn: Arc<Mutex<Connection>>,
 }
@@ -123,6 +126,27 @@ impl CollectionsService {
             [],
         )?;
 
+        // Full-text index over page title, notes, tags, URL and extracted content
+        conn.execute(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS collection_pages_fts USING fts5(
+                page_id UNINDEXED,
+                title,
+                notes,
+                tags,
+                url,
+                content,
+                tokenize = 'porter unicode61'
+            )",
+            [],
+        )?;
+        conn.execute(
+            "INSERT INTO collection_pages_fts (page_id, title, notes, tags, url, content)
+             SELECT id, title, COALESCE(notes, ''), tags, url, ''
+             FROM collection_pages
+             WHERE id NOT IN (SELECT page_id FROM collection_pages_fts)",
+            [],
+        )?;
+
         // Insert default collections if none exist
         let count: i32 = conn.query_row(
             "SELECT COUNT(*) FROM collections",
@@ -338,6 +362,10 @@ impl CollectionsService {
     pub fn delete_collection(&self, id: &str) -> SqlResult<()> {
         let conn = self.conn.lock().unwrap();
         conn.execute("DELETE FROM collections WHERE id = ?", [id])?;
+        conn.execute(
+            "DELETE FROM collection_pages_fts WHERE page_id NOT IN (SELECT id FROM collection_pages)",
+            [],
+        )?;
         Ok(())
     }
 
@@ -394,6 +422,7 @@ impl CollectionsService {
                 last_visited: row.get(8)?,
                 visit_count: row.get(9)?,
                 is_favorite: row.get(10)?,
+                content: None,
             })
         })?;
 
@@ -426,6 +455,7 @@ impl CollectionsService {
                 last_visited: row.get(8)?,
                 visit_count: row.get(9)?,
                 is_favorite: row.get(10)?,
+                content: None,
             })
         });
 
@@ -460,6 +490,7 @@ impl CollectionsService {
                 page.is_favorite,
             ],
         )?;
+        Self::index_page(&conn, page)?;
 
         // Update page count in collection
         conn.execute(
@@ -491,6 +522,7 @@ impl CollectionsService {
                 page.id,
             ],
         )?;
+        Self::index_page(&conn, page)?;
 
         // Update collection's updated_at
         conn.execute(
@@ -513,6 +545,7 @@ impl CollectionsService {
         )?;
 
         conn.execute("DELETE FROM collection_pages WHERE id = ?", [id])?;
+        conn.execute("DELETE FROM collection_pages_fts WHERE page_id = ?", [id])?;
 
         // Update page count
         conn.execute(
@@ -523,6 +556,37 @@ impl CollectionsService {
         Ok(())
     }
 
+    /// Replace a page's search index entry, keeping previously indexed content
+    /// when the page carries none
+    fn index_page(conn: &Connection, page: &CollectionPage) -> SqlResult<()> {
+        let content = match &page.content {
+            Some(content) => content.clone(),
+            None => conn
+                .query_row(
+                    "SELECT content FROM collection_pages_fts WHERE page_id = ?",
+                    [&page.id],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?
+                .unwrap_or_default(),
+        };
+
+        conn.execute("DELETE FROM collection_pages_fts WHERE page_id = ?", [&page.id])?;
+        conn.execute(
+            "INSERT INTO collection_pages_fts (page_id, title, notes, tags, url, content)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                page.id,
+                page.title,
+                page.notes.clone().unwrap_or_default(),
+                page.tags.join(" "),
+                page.url,
+                content,
+            ],
+        )?;
+        Ok(())
+    }
+
     /// Move page to different collection
     pub fn move_page(&self, page_id: &str, new_collection_id: &str) -> SqlResult<()> {
         let conn = self.conn.lock().unwrap();
@@ -763,42 +827,72 @@ impl CollectionsService {
         })
     }
 
-    /// Search pages across all collections
-    pub fn search_pages(&self, query: &str) -> SqlResult<Vec<CollectionPage>> {
+    /// Full-text search across page titles, notes, tags, URLs and content, ranked
+    /// by BM25. Quoted text is matched as a phrase; other words must all appear.
+    pub fn search_pages(&self, query: &str, options: &PageSearchOptions) -> SqlResult<Vec<PageSearchResult>> {
+        let Some(fts_query) = build_fts_query(query) else {
+            return Ok(Vec::new());
+        };
         let conn = self.conn.lock().unwrap();
-        let search_pattern = format!("%{}%", query.to_lowercase());
+        let limit = options.limit.unwrap_or(DEFAULT_SEARCH_LIMIT) as i64;
 
+        // Column weights follow the FTS column order: page_id, title, notes, tags, url, content
         let mut stmt = conn.prepare(
-            "SELECT id, collection_id, url, title, screenshot, notes, tags,
-                    added_at, last_visited, visit_count, is_favorite
-             FROM collection_pages
-             WHERE LOWER(title) LIKE ?1 
-                OR LOWER(url) LIKE ?1 
-                OR LOWER(notes) LIKE ?1
-             ORDER BY visit_count DESC, added_at DESC
-             LIMIT 100",
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT ?2
+                 UNION ALL
+                 SELECT c.id FROM collections c JOIN subtree s ON c.parent_id = s.id
+             )
+             SELECT p.id, p.collection_id, p.url, p.title, p.screenshot, p.notes, p.tags,
+                    p.added_at, p.last_visited, p.visit_count, p.is_favorite,
+                    bm25(collection_pages_fts, 0.0, 10.0, 4.0, 3.0, 2.0, 1.0) AS rank,
+                    snippet(collection_pages_fts, -1, '<mark>', '</mark>', '…', 24)
+             FROM collection_pages_fts
+             JOIN collection_pages p ON p.id = collection_pages_fts.page_id
+             WHERE collection_pages_fts MATCH ?1
+               AND (?2 IS NULL OR p.collection_id IN (SELECT id FROM subtree))
+             ORDER BY rank
+             LIMIT ?3",
         )?;
 
-        let pages = stmt.query_map([&search_pattern], |row| {
+        let rows = stmt.query_map(params![fts_query, options.collection_id, limit], |row| {
             let tags_json: String = row.get(6)?;
             let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
-
-            Ok(CollectionPage {
-                id: row.get(0)?,
-                collection_id: row.get(1)?,
-                url: row.get(2)?,
-                title: row.get(3)?,
-                screenshot: row.get(4)?,
-                notes: row.get(5)?,
-                tags,
-                added_at: row.get(7)?,
-                last_visited: row.get(8)?,
-                visit_count: row.get(9)?,
-                is_favorite: row.get(10)?,
-            })
+            let rank: f64 = row.get(11)?;
+
+            Ok((
+                CollectionPage {
+                    id: row.get(0)?,
+                    collection_id: row.get(1)?,
+                    url: row.get(2)?,
+                    title: row.get(3)?,
+                    screenshot: row.get(4)?,
+                    notes: row.get(5)?,
+                    tags,
+                    added_at: row.get(7)?,
+                    last_visited: row.get(8)?,
+                    visit_count: row.get(9)?,
+                    is_favorite: row.get(10)?,
+                    content: None,
+                },
+                -rank,
+                row.get::<_, String>(12)?,
+            ))
         })?;
-
-        pages.collect()
+        let rows: Vec<(CollectionPage, f64, String)> = rows.collect::<SqlResult<_>>()?;
+
+        let mut stmt = conn.prepare("SELECT id, name, parent_id FROM collections")?;
+        let collections: HashMap<String, (String, Option<String>)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?
+            .collect::<SqlResult<_>>()?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(page, score, snippet)| {
+                let collection_path = collection_path(&collections, &page.collection_id);
+                PageSearchResult { page, score, snippet, collection_path }
+            })
+            .collect())
     }
 
     /// Get favorite collections
@@ -858,6 +952,7 @@ impl CollectionsService {
                 last_visited: row.get(8)?,
                 visit_count: row.get(9)?,
                 is_favorite: row.get(10)?,
+                content: None,
             })
         })?;
 
@@ -892,9 +987,130 @@ impl CollectionsService {
                 last_visited: row.get(8)?,
                 visit_count: row.get(9)?,
                 is_favorite: row.get(10)?,
+                content: None,
             })
         })?;
 
         pages.collect()
     }
 }
+
+/// Turns user input into an FTS5 query: quoted segments become phrases and
+/// remaining words become individually quoted terms, so FTS syntax characters
+/// in the input are matched literally
+fn build_fts_query(query: &str) -> Option<String> {
+    let mut terms = Vec::new();
+    for (i, segment) in query.split('"').enumerate() {
+        if i % 2 == 1 {
+            terms.push(segment.to_string());
+        } else {
+            terms.extend(segment.split_whitespace().map(str::to_string));
+        }
+    }
+
+    let terms: Vec<String> = terms
+        .into_iter()
+        .filter(|t| t.chars().any(char::is_alphanumeric))
+        .map(|t| format!("\"{}\"", t.trim()))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Collection names from the root down to `collection_id`
+fn collection_path(collections: &HashMap<String, (String, Option<String>)>, collection_id: &str) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut current = Some(collection_id.to_string());
+    while let Some(id) = current {
+        let Some((name, parent)) = collections.get(&id) else {
+            break;
+        };
+        if path.len() > collections.len() {
+            break;
+        }
+        path.push(name.clone());
+        current = parent.clone();
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(id: &str, collection_id: &str, title: &str, content: &str) -> CollectionPage {
+        CollectionPage {
+            id: id.to_string(),
+            collection_id: collection_id.to_string(),
+            url: format!("https://example.com/{}", id),
+            title: title.to_string(),
+            screenshot: None,
+            notes: None,
+            tags: vec![],
+            added_at: 0,
+            last_visited: None,
+            visit_count: 0,
+            is_favorite: false,
+            content: Some(content.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_phrase_search_ranks_and_highlights() {
+        let service = CollectionsService::new(":memory:").unwrap();
+        service.create_collection(&Collection {
+            id: "physics".to_string(),
+            name: "Physics".to_string(),
+            description: None,
+            icon: "⚛️".to_string(),
+            color: "#000000".to_string(),
+            parent_id: Some("default_4".to_string()),
+            page_count: 0,
+            created_at: 0,
+            updated_at: 0,
+            is_shared: false,
+            is_favorite: false,
+        }).unwrap();
+
+        service.add_page(&page(
+            "qm",
+            "physics",
+            "Intro to quantum mechanics",
+            "Two particles can share a state through quantum entanglement, even when far apart.",
+        )).unwrap();
+        service.add_page(&page(
+            "cooking",
+            "default_1",
+            "Sourdough basics",
+            "Entanglement of gluten strands gives bread its structure; quantum leaps in flavor follow.",
+        )).unwrap();
+
+        let results = service.search_pages("\"quantum entanglement\"", &PageSearchOptions::default()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].page.id, "qm");
+        assert!(results[0].snippet.contains("<mark>quantum entanglement</mark>"), "{}", results[0].snippet);
+        assert_eq!(results[0].collection_path, vec!["Research", "Physics"]);
+
+        // Both pages contain both words, but only one inside the Research subtree
+        let scoped = PageSearchOptions { collection_id: Some("default_4".to_string()), limit: None };
+        assert_eq!(service.search_pages("quantum entanglement", &PageSearchOptions::default()).unwrap().len(), 2);
+        let results = service.search_pages("quantum entanglement", &scoped).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].page.id, "qm");
+
+        // Updates without content keep the indexed text; deletes drop it
+        let mut renamed = page("qm", "physics", "Spooky action", "");
+        renamed.content = None;
+        service.update_page(&renamed).unwrap();
+        assert_eq!(service.search_pages("spooky", &PageSearchOptions::default()).unwrap().len(), 1);
+        assert_eq!(service.search_pages("\"quantum entanglement\"", &PageSearchOptions::default()).unwrap().len(), 1);
+
+        service.delete_page("qm").unwrap();
+        assert!(service.search_pages("spooky", &PageSearchOptions::default()).unwrap().is_empty());
+    }
+}
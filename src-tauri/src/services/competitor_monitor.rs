@@ -0,0 +1,416 @@
+// Competitor Monitor - Snapshot diffing for scheduled competitor page checks
+// Extracts page text, masks volatile noise (timestamps, session ids, cache
+// busters) and classifies what changed between two snapshots.
+
+use chrono::{DateTime, Duration, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref ISO_TIMESTAMP: Regex = Regex::new(
+        r"\b\d{4}-\d{2}-\d{2}(?:[T ]\d{2}:\d{2}(?::\d{2}(?:\.\d+)?)?(?:Z|[+-]\d{2}:?\d{2})?)?\b"
+    ).unwrap();
+    static ref CLOCK_TIME: Regex = Regex::new(r"(?i)\b\d{1,2}:\d{2}(?::\d{2})?\s*(?:am|pm)?\b").unwrap();
+    static ref WRITTEN_DATE: Regex = Regex::new(
+        r"(?i)\b(?:jan|feb|mar|apr|may|jun|jul|aug|sep|sept|oct|nov|dec)[a-z]*\.?\s+\d{1,2}(?:st|nd|rd|th)?,?\s+\d{4}\b"
+    ).unwrap();
+    static ref RELATIVE_TIME: Regex = Regex::new(
+        r"(?i)\b(?:\d+|a|an)\s+(?:second|minute|hour|day|week|month)s?\s+ago\b|\bjust now\b"
+    ).unwrap();
+    static ref SESSION_PARAM: Regex = Regex::new(
+        r"(?i)\b(?:session_?id|sid|phpsessid|jsessionid|token|csrf[_-]?token|nonce|v|ver|cb|_)=[\w.-]+"
+    ).unwrap();
+    static ref UUID: Regex = Regex::new(
+        r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b"
+    ).unwrap();
+    static ref LONG_TOKEN: Regex = Regex::new(r"\b[0-9a-fA-F]{16,}\b|\b[A-Za-z0-9_-]{32,}\b").unwrap();
+    static ref COPYRIGHT: Regex = Regex::new(r"(?i)(?:©|\(c\)|copyright)\s*\d{4}(?:\s*-\s*\d{4})?").unwrap();
+
+    static ref PRICING_SIGNAL: Regex = Regex::new(
+        r"(?i)[$€£¥]\s*\d|\d\s*(?:usd|eur|gbp)\b|/\s*(?:mo|month|yr|year|user|seat)\b|\bper\s+(?:month|year|user|seat)\b|\bpric(?:e|ing)\b|\bfree trial\b|\bdiscount\b"
+    ).unwrap();
+    static ref HEADCOUNT_SIGNAL: Regex = Regex::new(
+        r"(?i)\bwe'?re hiring\b|\bopen (?:positions|roles)\b|\bjoin our team\b|\bcareers?\b|\bjob openings?\b|\b\d[\d,]*\+?\s+(?:employees|people|team members)\b|\blayoffs?\b"
+    ).unwrap();
+    static ref FEATURE_SIGNAL: Regex = Regex::new(
+        r"(?i)\bnew\b|\bintroducing\b|\blaunch(?:ed|es)?\b|\bnow available\b|\bfeatures?\b|\bbeta\b|\bintegrations?\b|\broadmap\b|\brelease[ds]?\b"
+    ).unwrap();
+}
+
+/// Elements that are boilerplate or never visible
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer", "aside", "form",
+];
+
+/// Elements that start a new line of extracted text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p", "li", "h1", "h2", "h3", "h4", "h5", "h6", "td", "th", "dt", "dd", "tr", "div", "section",
+    "article", "main", "blockquote", "pre", "figcaption", "caption", "label", "button", "summary", "body",
+];
+
+/// Content changes smaller than this many characters are treated as noise
+const MIN_CONTENT_CHANGE_CHARS: usize = 40;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageSnapshot {
+    pub url: String,
+    pub captured_at: String,
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ChangeCategory {
+    Pricing,
+    Features,
+    Headcount,
+    Content,
+}
+
+impl ChangeCategory {
+    fn label(&self) -> &'static str {
+        match self {
+            ChangeCategory::Pricing => "Pricing",
+            ChangeCategory::Features => "Features",
+            ChangeCategory::Headcount => "Hiring / headcount",
+            ChangeCategory::Content => "Content",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompetitorChange {
+    pub id: String,
+    pub competitor_id: String,
+    pub competitor_name: String,
+    pub url: String,
+    pub category: ChangeCategory,
+    pub summary: String,
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+    pub detected_at: String,
+    pub read: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompetitorMonitor {
+    pub competitor_id: String,
+    pub pages: Vec<String>,
+    /// "hourly", "daily", "weekly" or "every 30m" / "every 6h" / "every 2d"
+    pub schedule: String,
+    /// CSS selectors to watch; empty watches the whole page minus boilerplate
+    pub selectors: Vec<String>,
+    pub enabled: bool,
+    pub last_checked: Option<String>,
+    pub next_run: String,
+    #[serde(skip)]
+    pub snapshots: HashMap<String, PageSnapshot>,
+}
+
+/// Interval between checks for a schedule string
+pub fn schedule_interval(schedule: &str) -> Result<Duration, String> {
+    let schedule = schedule.trim().to_lowercase();
+    match schedule.as_str() {
+        "hourly" => return Ok(Duration::hours(1)),
+        "daily" => return Ok(Duration::days(1)),
+        "weekly" => return Ok(Duration::weeks(1)),
+        _ => {}
+    }
+
+    let spec = schedule.strip_prefix("every").map(str::trim).unwrap_or(&schedule);
+    let split = spec.find(|c: char| !c.is_ascii_digit()).unwrap_or(spec.len());
+    let (amount, unit) = spec.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| format!("Invalid schedule: {}", schedule))?;
+    let interval = match unit.trim() {
+        "m" | "min" | "mins" | "minutes" => Duration::minutes(amount),
+        "h" | "hour" | "hours" => Duration::hours(amount),
+        "d" | "day" | "days" => Duration::days(amount),
+        _ => return Err(format!("Invalid schedule: {}", schedule)),
+    };
+    if interval < Duration::minutes(15) {
+        return Err("Competitor pages can be checked at most every 15 minutes".to_string());
+    }
+    Ok(interval)
+}
+
+/// Visible text of the page (or the selected elements), one line per block
+pub fn extract_lines(html: &str, selectors: &[String]) -> Result<Vec<String>, String> {
+    let document = Html::parse_document(html);
+
+    let roots: Vec<ElementRef> = if selectors.is_empty() {
+        vec![document.root_element()]
+    } else {
+        let mut roots = Vec::new();
+        for selector in selectors {
+            let parsed = Selector::parse(selector).map_err(|e| format!("Invalid selector '{}': {:?}", selector, e))?;
+            roots.extend(document.select(&parsed));
+        }
+        roots
+    };
+
+    let mut lines = Vec::new();
+    for root in roots {
+        let mut blocks: Vec<(_, String)> = Vec::new();
+        for node in root.descendants() {
+            let Some(text) = node.value().as_text() else { continue };
+            let mut block = root.id();
+            let mut skipped = false;
+            for ancestor in node.ancestors() {
+                let Some(element) = ElementRef::wrap(ancestor) else { continue };
+                let name = element.value().name();
+                if SKIPPED_ELEMENTS.contains(&name) {
+                    skipped = true;
+                    break;
+                }
+                if block == root.id() && BLOCK_ELEMENTS.contains(&name) {
+                    block = ancestor.id();
+                }
+                if ancestor.id() == root.id() {
+                    break;
+                }
+            }
+            if skipped {
+                continue;
+            }
+            match blocks.iter_mut().find(|(id, _)| *id == block) {
+                Some((_, buffer)) => buffer.push_str(text),
+                None => blocks.push((block, text.to_string())),
+            }
+        }
+        lines.extend(
+            blocks
+                .into_iter()
+                .map(|(_, text)| text.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|line| !line.is_empty()),
+        );
+    }
+    Ok(lines)
+}
+
+/// Line with volatile tokens masked, used for comparison only
+pub fn normalize_line(line: &str) -> String {
+    let line = SESSION_PARAM.replace_all(line, "<param>");
+    let line = UUID.replace_all(&line, "<id>");
+    let line = LONG_TOKEN.replace_all(&line, "<id>");
+    let line = ISO_TIMESTAMP.replace_all(&line, "<date>");
+    let line = WRITTEN_DATE.replace_all(&line, "<date>");
+    let line = RELATIVE_TIME.replace_all(&line, "<time>");
+    let line = CLOCK_TIME.replace_all(&line, "<time>");
+    let line = COPYRIGHT.replace_all(&line, "<copyright>");
+    line.to_lowercase()
+}
+
+fn classify(line: &str) -> ChangeCategory {
+    if PRICING_SIGNAL.is_match(line) {
+        ChangeCategory::Pricing
+    } else if HEADCOUNT_SIGNAL.is_match(line) {
+        ChangeCategory::Headcount
+    } else if FEATURE_SIGNAL.is_match(line) {
+        ChangeCategory::Features
+    } else {
+        ChangeCategory::Content
+    }
+}
+
+/// Lines of `a` with no counterpart in `b`, comparing normalized text
+fn missing_lines<'a>(a: &'a [String], b: &[String]) -> Vec<&'a String> {
+    let mut available: HashMap<String, usize> = HashMap::new();
+    for line in b {
+        *available.entry(normalize_line(line)).or_default() += 1;
+    }
+    a.iter()
+        .filter(|line| match available.get_mut(&normalize_line(line)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        format!("{}…", text.chars().take(max).collect::<String>())
+    }
+}
+
+/// Meaningful changes between two snapshots of the same page, one per category
+pub fn detect_changes(
+    competitor_id: &str,
+    competitor_name: &str,
+    previous: &PageSnapshot,
+    current: &PageSnapshot,
+) -> Vec<CompetitorChange> {
+    let removed = missing_lines(&previous.lines, &current.lines);
+    let added = missing_lines(&current.lines, &previous.lines);
+
+    let mut grouped: Vec<(ChangeCategory, Vec<String>, Vec<String>)> = Vec::new();
+    let mut push = |category: ChangeCategory, before: Option<&String>, after: Option<&String>| {
+        let index = match grouped.iter().position(|(c, _, _)| *c == category) {
+            Some(index) => index,
+            None => {
+                grouped.push((category, Vec::new(), Vec::new()));
+                grouped.len() - 1
+            }
+        };
+        if let Some(line) = before {
+            grouped[index].1.push(line.clone());
+        }
+        if let Some(line) = after {
+            grouped[index].2.push(line.clone());
+        }
+    };
+    for line in &removed {
+        push(classify(line), Some(line), None);
+    }
+    for line in &added {
+        push(classify(line), None, Some(line));
+    }
+
+    let detected_at = Utc::now().to_rfc3339();
+    grouped
+        .into_iter()
+        .filter(|(category, before, after)| {
+            *category != ChangeCategory::Content
+                || before.iter().chain(after).map(|l| l.len()).sum::<usize>() >= MIN_CONTENT_CHANGE_CHARS
+        })
+        .map(|(category, before, after)| {
+            let describe = |lines: &[String]| {
+                if lines.is_empty() {
+                    "(nothing)".to_string()
+                } else {
+                    truncate(&lines.join("; "), 160)
+                }
+            };
+            CompetitorChange {
+                id: uuid::Uuid::new_v4().to_string(),
+                competitor_id: competitor_id.to_string(),
+                competitor_name: competitor_name.to_string(),
+                url: current.url.clone(),
+                summary: format!(
+                    "{} change on {}: {} → {}",
+                    category.label(),
+                    current.url,
+                    describe(&before),
+                    describe(&after)
+                ),
+                category,
+                before,
+                after,
+                detected_at: detected_at.clone(),
+                read: false,
+            }
+        })
+        .collect()
+}
+
+impl CompetitorMonitor {
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.enabled
+            && DateTime::parse_from_rfc3339(&self.next_run)
+                .map(|next| next.with_timezone(&Utc) <= now)
+                .unwrap_or(true)
+    }
+
+    /// Store a fresh capture of `url` and return what changed since the last one
+    pub fn record_snapshot(
+        &mut self,
+        competitor_name: &str,
+        url: &str,
+        html: &str,
+    ) -> Result<Vec<CompetitorChange>, String> {
+        let snapshot = PageSnapshot {
+            url: url.to_string(),
+            captured_at: Utc::now().to_rfc3339(),
+            lines: extract_lines(html, &self.selectors)?,
+        };
+        let changes = match self.snapshots.get(url) {
+            Some(previous) => detect_changes(&self.competitor_id, competitor_name, previous, &snapshot),
+            None => Vec::new(),
+        };
+        self.snapshots.insert(url.to_string(), snapshot);
+        Ok(changes)
+    }
+
+    pub fn mark_checked(&mut self, now: DateTime<Utc>) {
+        let interval = schedule_interval(&self.schedule).unwrap_or_else(|_| Duration::days(1));
+        self.last_checked = Some(now.to_rfc3339());
+        self.next_run = (now + interval).to_rfc3339();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(selectors: Vec<String>) -> CompetitorMonitor {
+        CompetitorMonitor {
+            competitor_id: "comp-1".to_string(),
+            pages: vec!["https://rival.example/pricing".to_string()],
+            schedule: "daily".to_string(),
+            selectors,
+            enabled: true,
+            last_checked: None,
+            next_run: Utc::now().to_rfc3339(),
+            snapshots: HashMap::new(),
+        }
+    }
+
+    fn page(price: &str, updated: &str, session: &str) -> String {
+        format!(
+            r#"<html><head><script>var t = "{updated}";</script></head><body>
+            <nav><a href="/">Home</a></nav>
+            <main>
+              <h1>Plans</h1>
+              <div class="plan"><h2>Pro</h2><p class="price"><span>{price}</span> per month</p></div>
+              <p>Last updated {updated}</p>
+              <a href="/signup?session_id={session}">Start now</a>
+            </main>
+            <footer>© 2025 Rival Inc.</footer>
+            </body></html>"#
+        )
+    }
+
+    #[test]
+    fn test_price_change_produces_event_and_noise_is_suppressed() {
+        let url = "https://rival.example/pricing";
+        let mut monitor = monitor(vec![]);
+
+        let first = page("$49", "2025-03-01T10:00:00Z", "a81f3c0e9d7b44a2b1c6d5e4f3a2b1c0");
+        assert!(monitor.record_snapshot("Rival", url, &first).unwrap().is_empty());
+
+        // Only the timestamp and session id differ
+        let noise = page("$49", "2025-03-02T08:15:42Z", "ffee0011223344556677889900aabbcc");
+        assert!(monitor.record_snapshot("Rival", url, &noise).unwrap().is_empty());
+
+        let repriced = page("$59", "2025-03-03T09:00:00Z", "0123456789abcdef0123456789abcdef");
+        let changes = monitor.record_snapshot("Rival", url, &repriced).unwrap();
+        assert_eq!(changes.len(), 1, "{:?}", changes);
+        assert_eq!(changes[0].category, ChangeCategory::Pricing);
+        assert_eq!(changes[0].before, vec!["$49 per month"]);
+        assert_eq!(changes[0].after, vec!["$59 per month"]);
+        assert!(changes[0].summary.contains("$49 per month → $59 per month"));
+    }
+
+    #[test]
+    fn test_selectors_limit_the_watched_text() {
+        let html = page("$49", "2025-03-01", "x");
+        let lines = extract_lines(&html, &[".price".to_string()]).unwrap();
+        assert_eq!(lines, vec!["$49 per month"]);
+        assert!(extract_lines(&html, &[]).unwrap().iter().all(|l| !l.contains("Home") && !l.contains("var t")));
+    }
+
+    #[test]
+    fn test_schedule_interval() {
+        assert_eq!(schedule_interval("hourly").unwrap(), Duration::hours(1));
+        assert_eq!(schedule_interval("every 6h").unwrap(), Duration::hours(6));
+        assert_eq!(schedule_interval("30m").unwrap(), Duration::minutes(30));
+        assert!(schedule_interval("every 1m").is_err());
+        assert!(schedule_interval("sometimes").is_err());
+    }
+}
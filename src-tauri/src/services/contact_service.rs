@@ -3,7 +3,7 @@
 // Handles email contacts, lists, segmentation, and contact data management
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Mutex;
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
@@ -52,6 +52,12 @@ pub struct Contact {
     pub click_count: u32,
     pub bounce_count: u32,
     pub notes: Option<String>,
+    /// Clicks per link target from tracked emails
+    #[serde(default)]
+    pub link_clicks: HashMap<String, u32>,
+    /// Recipient asked not to be tracked; sends to them carry no pixel or wrapped links
+    #[serde(default)]
+    pub tracking_opt_out: bool,
 }
 
 impl Contact {
@@ -79,6 +85,8 @@ impl Contact {
             click_count: 0,
             bounce_count: 0,
             notes: None,
+            link_clicks: HashMap::new(),
+            tracking_opt_out: false,
         }
     }
 
@@ -214,6 +222,25 @@ pub struct ContactFilter {
     pub sort_order: Option<String>,
 }
 
+/// Possible duplicate pair; `primary_id` is the older contact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateCandidate {
+    pub primary_id: String,
+    pub duplicate_id: String,
+    pub score: f64,
+    pub reasons: Vec<String>,
+}
+
+/// Record of a merge, kept so it can be undone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactMergeRecord {
+    pub id: String,
+    pub primary_before: Contact,
+    pub duplicate: Contact,
+    pub merged: Contact,
+    pub merged_at: String,
+}
+
 /// Paginated response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaginatedContacts {
@@ -232,6 +259,7 @@ pub struct ContactServiceState {
     contacts: Mutex<HashMap<String, Contact>>,
     lists: Mutex<HashMap<String, ContactList>>,
     segments: Mutex<HashMap<String, Segment>>,
+    merge_log: Mutex<Vec<ContactMergeRecord>>,
 }
 
 impl Default for ContactServiceState {
@@ -261,6 +289,7 @@ impl ContactServiceState {
             contacts: Mutex::new(HashMap::new()),
             lists: Mutex::new(lists),
             segments: Mutex::new(HashMap::new()),
+            merge_log: Mutex::new(Vec::new()),
         }
     }
 
@@ -668,6 +697,38 @@ impl ContactServiceState {
         Ok(())
     }
 
+    /// Record a click on a specific link from a tracked email
+    pub fn record_link_click(&self, email: &str, url: &str) -> Result<(), String> {
+        let mut contacts = self.contacts.lock()
+            .map_err(|e| format!("Failed to acquire contacts lock: {}", e))?;
+
+        let email_lower = email.to_lowercase();
+        let contact = contacts.values_mut()
+            .find(|c| c.email.to_lowercase() == email_lower)
+            .ok_or_else(|| format!("Contact not found: {}", email))?;
+
+        let now = Utc::now().to_rfc3339();
+        contact.click_count += 1;
+        contact.last_email_clicked = Some(now.clone());
+        *contact.link_clicks.entry(url.to_string()).or_insert(0) += 1;
+        contact.updated_at = now;
+
+        Ok(())
+    }
+
+    /// Set whether a contact has opted out of open/click tracking
+    pub fn set_tracking_opt_out(&self, contact_id: &str, opt_out: bool) -> Result<Contact, String> {
+        let mut contacts = self.contacts.lock()
+            .map_err(|e| format!("Failed to acquire contacts lock: {}", e))?;
+
+        let contact = contacts.get_mut(contact_id)
+            .ok_or_else(|| format!("Contact not found: {}", contact_id))?;
+        contact.tracking_opt_out = opt_out;
+        contact.updated_at = Utc::now().to_rfc3339();
+
+        Ok(contact.clone())
+    }
+
     // =========================================================================
     // List Operations
     // =========================================================================
@@ -802,6 +863,133 @@ impl ContactServiceState {
         Ok(())
     }
 
+    // =========================================================================
+    // Duplicate Detection & Merge
+    // =========================================================================
+
+    /// Find likely duplicate contacts scoring at least `threshold` (0.0 - 1.0).
+    /// Only contacts sharing a blocking key (email, phone, company domain or
+    /// name prefix) are compared, so large sets don't need every pair.
+    pub fn find_duplicates(&self, threshold: f64) -> Result<Vec<DuplicateCandidate>, String> {
+        let contacts = self.contacts.lock()
+            .map_err(|e| format!("Failed to acquire contacts lock: {}", e))?;
+
+        let mut sorted: Vec<&Contact> = contacts.values().collect();
+        sorted.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+        let mut blocks: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, contact) in sorted.iter().enumerate() {
+            for key in blocking_keys(contact) {
+                blocks.entry(key).or_default().push(index);
+            }
+        }
+
+        let mut compared: HashSet<(usize, usize)> = HashSet::new();
+        let mut candidates = Vec::new();
+        for members in blocks.values() {
+            for (n, &a) in members.iter().enumerate() {
+                for &b in &members[n + 1..] {
+                    if !compared.insert((a, b)) {
+                        continue;
+                    }
+                    let (score, reasons) = duplicate_score(sorted[a], sorted[b]);
+                    if score >= threshold {
+                        candidates.push(DuplicateCandidate {
+                            primary_id: sorted[a].id.clone(),
+                            duplicate_id: sorted[b].id.clone(),
+                            score: (score * 1000.0).round() / 1000.0,
+                            reasons,
+                        });
+                    }
+                }
+            }
+        }
+
+        candidates.sort_by(|a, b| {
+            b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.primary_id.cmp(&b.primary_id))
+                .then_with(|| a.duplicate_id.cmp(&b.duplicate_id))
+        });
+        Ok(candidates)
+    }
+
+    /// Merge `duplicate_id` into `primary_id` and delete the duplicate
+    pub fn merge_contacts(&self, primary_id: &str, duplicate_id: &str) -> Result<ContactMergeRecord, String> {
+        if primary_id == duplicate_id {
+            return Err("Cannot merge a contact into itself".to_string());
+        }
+
+        let mut contacts = self.contacts.lock()
+            .map_err(|e| format!("Failed to acquire contacts lock: {}", e))?;
+
+        let primary = contacts.get(primary_id).cloned()
+            .ok_or_else(|| format!("Contact not found: {}", primary_id))?;
+        let duplicate = contacts.get(duplicate_id).cloned()
+            .ok_or_else(|| format!("Contact not found: {}", duplicate_id))?;
+
+        let merged = merge_contact_data(&primary, &duplicate);
+        contacts.insert(primary_id.to_string(), merged.clone());
+        contacts.remove(duplicate_id);
+        drop(contacts);
+
+        let mut affected_lists = merged.list_ids.clone();
+        affected_lists.extend(duplicate.list_ids.iter().cloned());
+        affected_lists.sort();
+        affected_lists.dedup();
+        self.update_list_counts(&affected_lists)?;
+
+        let record = ContactMergeRecord {
+            id: Uuid::new_v4().to_string(),
+            primary_before: primary,
+            duplicate,
+            merged,
+            merged_at: Utc::now().to_rfc3339(),
+        };
+        self.merge_log.lock()
+            .map_err(|e| format!("Failed to acquire merge log lock: {}", e))?
+            .push(record.clone());
+
+        log::info!("Merged contact {} into {}", duplicate_id, primary_id);
+        Ok(record)
+    }
+
+    /// Restore both contacts as they were before a merge
+    pub fn undo_merge(&self, merge_id: &str) -> Result<(Contact, Contact), String> {
+        let mut merge_log = self.merge_log.lock()
+            .map_err(|e| format!("Failed to acquire merge log lock: {}", e))?;
+        let position = merge_log.iter().position(|r| r.id == merge_id)
+            .ok_or_else(|| format!("Merge not found: {}", merge_id))?;
+
+        let mut contacts = self.contacts.lock()
+            .map_err(|e| format!("Failed to acquire contacts lock: {}", e))?;
+        let record = &merge_log[position];
+        if contacts.contains_key(&record.duplicate.id) {
+            return Err(format!("Contact {} already exists", record.duplicate.id));
+        }
+        contacts.insert(record.primary_before.id.clone(), record.primary_before.clone());
+        contacts.insert(record.duplicate.id.clone(), record.duplicate.clone());
+        drop(contacts);
+
+        let record = merge_log.remove(position);
+        drop(merge_log);
+
+        let mut affected_lists = record.merged.list_ids.clone();
+        affected_lists.extend(record.duplicate.list_ids.iter().cloned());
+        affected_lists.sort();
+        affected_lists.dedup();
+        self.update_list_counts(&affected_lists)?;
+
+        log::info!("Undid merge of {} into {}", record.duplicate.id, record.primary_before.id);
+        Ok((record.primary_before, record.duplicate))
+    }
+
+    /// Merges that can still be undone, newest first
+    pub fn get_merge_log(&self) -> Result<Vec<ContactMergeRecord>, String> {
+        let log = self.merge_log.lock()
+            .map_err(|e| format!("Failed to acquire merge log lock: {}", e))?;
+        Ok(log.iter().rev().cloned().collect())
+    }
+
     // =========================================================================
     // Segment Operations
     // =========================================================================
@@ -1143,3 +1331,340 @@ impl ContactServiceState {
         Ok(tags)
     }
 }
+
+// =============================================================================
+// Duplicate Matching Helpers
+// =============================================================================
+
+/// Webmail domains shared by unrelated people; not used as a blocking key
+const FREE_MAIL_DOMAINS: &[&str] = &[
+    "gmail.com", "googlemail.com", "yahoo.com", "hotmail.com", "outlook.com",
+    "live.com", "icloud.com", "me.com", "aol.com", "proton.me", "protonmail.com",
+];
+
+/// Lowercase, drop `+tag` suffixes and Gmail's ignored dots
+fn normalize_email(email: &str) -> String {
+    let email = email.trim().to_lowercase();
+    let Some((local, domain)) = email.split_once('@') else {
+        return email;
+    };
+    let local = local.split('+').next().unwrap_or(local);
+    let local = if domain == "gmail.com" || domain == "googlemail.com" {
+        local.replace('.', "")
+    } else {
+        local.to_string()
+    };
+    format!("{}@{}", local, domain)
+}
+
+/// Digits only, compared on the last 10 so country prefixes don't matter
+fn normalize_phone(phone: &str) -> Option<String> {
+    let digits: String = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    if digits.len() < 7 {
+        return None;
+    }
+    Some(digits[digits.len().saturating_sub(10)..].to_string())
+}
+
+fn normalize_company(company: &str) -> String {
+    let cleaned: String = company.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    cleaned.split_whitespace()
+        .filter(|w| !matches!(*w, "inc" | "llc" | "ltd" | "corp" | "co" | "gmbh" | "sa" | "plc" | "the"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn normalize_name(name: Option<&String>) -> String {
+    name.map(|n| n.trim().to_lowercase()).unwrap_or_default()
+}
+
+fn blocking_keys(contact: &Contact) -> Vec<String> {
+    let mut keys = Vec::new();
+    let email = normalize_email(&contact.email);
+    if let Some((_, domain)) = email.split_once('@') {
+        if !FREE_MAIL_DOMAINS.contains(&domain) {
+            keys.push(format!("domain:{}", domain));
+        }
+    }
+    keys.push(format!("email:{}", email));
+    if let Some(phone) = contact.phone.as_deref().and_then(normalize_phone) {
+        keys.push(format!("phone:{}", phone));
+    }
+
+    // Two name keys, so a typo has to hit both to escape comparison
+    let first: Vec<char> = normalize_name(contact.first_name.as_ref()).chars().collect();
+    let last: Vec<char> = normalize_name(contact.last_name.as_ref()).chars().collect();
+    if !first.is_empty() && !last.is_empty() {
+        let prefix = |chars: &[char], n: usize| chars.iter().take(n).collect::<String>();
+        keys.push(format!("name1:{}{}", prefix(&first, 1), prefix(&last, 3)));
+        keys.push(format!("name2:{}{}", prefix(&first, 3), prefix(&last, 1)));
+    }
+    keys
+}
+
+/// Jaro-Winkler similarity in 0.0 - 1.0
+fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() || b.is_empty() {
+        return if a.is_empty() && b.is_empty() { 1.0 } else { 0.0 };
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0usize;
+
+    for (i, ca) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        if let Some(j) = (start..end).find(|&j| !b_matched[j] && b[j] == *ca) {
+            a_matched[i] = true;
+            b_matched[j] = true;
+            matches += 1;
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let a_seq = a.iter().zip(&a_matched).filter(|(_, m)| **m).map(|(c, _)| c);
+    let b_seq = b.iter().zip(&b_matched).filter(|(_, m)| **m).map(|(c, _)| c);
+    let transpositions = a_seq.zip(b_seq).filter(|(x, y)| x != y).count() as f64 / 2.0;
+
+    let m = matches as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions) / m) / 3.0;
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count() as f64;
+    jaro + prefix * 0.1 * (1.0 - jaro)
+}
+
+/// Similarity of two contacts and the signals behind it
+fn duplicate_score(a: &Contact, b: &Contact) -> (f64, Vec<String>) {
+    let mut reasons = Vec::new();
+
+    let name_a = format!("{} {}", normalize_name(a.first_name.as_ref()), normalize_name(a.last_name.as_ref()));
+    let name_b = format!("{} {}", normalize_name(b.first_name.as_ref()), normalize_name(b.last_name.as_ref()));
+    let name_similarity = if name_a.trim().is_empty() || name_b.trim().is_empty() {
+        0.0
+    } else {
+        jaro_winkler(name_a.trim(), name_b.trim())
+    };
+    let mut score = name_similarity * 0.8;
+    if name_similarity >= 0.85 {
+        reasons.push("name".to_string());
+    }
+
+    let company_match = match (&a.company, &b.company) {
+        (Some(x), Some(y)) => {
+            let (x, y) = (normalize_company(x), normalize_company(y));
+            !x.is_empty() && x == y
+        }
+        _ => false,
+    };
+    if company_match {
+        score += 0.15;
+        reasons.push("company".to_string());
+    }
+
+    let email_a = normalize_email(&a.email);
+    let email_b = normalize_email(&b.email);
+    let same_domain = email_a.split_once('@').map(|(_, d)| d) == email_b.split_once('@').map(|(_, d)| d);
+    if same_domain && name_similarity >= 0.85 {
+        score += 0.05;
+    }
+    if email_a == email_b {
+        score = 1.0;
+        reasons.insert(0, "email".to_string());
+    }
+
+    let phone_match = match (a.phone.as_deref().and_then(normalize_phone), b.phone.as_deref().and_then(normalize_phone)) {
+        (Some(x), Some(y)) => x == y,
+        _ => false,
+    };
+    if phone_match {
+        score = score.max(0.95);
+        reasons.push("phone".to_string());
+    }
+
+    (score.min(1.0), reasons)
+}
+
+/// The longer of two optional values; `primary` wins ties
+fn richer(primary: &Option<String>, other: &Option<String>) -> Option<String> {
+    let len = |v: &Option<String>| v.as_deref().map(|s| s.trim().len()).unwrap_or(0);
+    if len(other) > len(primary) { other.clone() } else { primary.clone() }
+}
+
+fn latest(a: &Option<String>, b: &Option<String>) -> Option<String> {
+    match (a, b) {
+        (Some(x), Some(y)) => Some(if y > x { y.clone() } else { x.clone() }),
+        _ => a.clone().or_else(|| b.clone()),
+    }
+}
+
+fn merge_contact_data(primary: &Contact, duplicate: &Contact) -> Contact {
+    let mut merged = primary.clone();
+
+    merged.first_name = richer(&primary.first_name, &duplicate.first_name);
+    merged.last_name = richer(&primary.last_name, &duplicate.last_name);
+    merged.company = richer(&primary.company, &duplicate.company);
+    merged.phone = richer(&primary.phone, &duplicate.phone);
+
+    for tag in &duplicate.tags {
+        if !merged.tags.contains(tag) {
+            merged.tags.push(tag.clone());
+        }
+    }
+    for list_id in &duplicate.list_ids {
+        if !merged.list_ids.contains(list_id) {
+            merged.list_ids.push(list_id.clone());
+        }
+    }
+    for (key, value) in &duplicate.custom_fields {
+        let keep_primary = merged.custom_fields.get(key).is_some_and(|v| !v.trim().is_empty());
+        if !keep_primary {
+            merged.custom_fields.insert(key.clone(), value.clone());
+        }
+    }
+    if normalize_email(&duplicate.email) != normalize_email(&primary.email) {
+        let mut alternates: Vec<String> = merged.custom_fields.get("alternate_emails")
+            .map(|v| v.split(',').map(|e| e.trim().to_string()).filter(|e| !e.is_empty()).collect())
+            .unwrap_or_default();
+        if !alternates.contains(&duplicate.email) {
+            alternates.push(duplicate.email.clone());
+        }
+        merged.custom_fields.insert("alternate_emails".to_string(), alternates.join(", "));
+    }
+
+    merged.notes = match (&primary.notes, &duplicate.notes) {
+        (Some(a), Some(b)) if a.trim() != b.trim() => Some(format!("{}\n\n{}", a, b)),
+        _ => primary.notes.clone().or_else(|| duplicate.notes.clone()),
+    };
+
+    // Activity
+    merged.email_count += duplicate.email_count;
+    merged.open_count += duplicate.open_count;
+    merged.click_count += duplicate.click_count;
+    merged.bounce_count += duplicate.bounce_count;
+    merged.last_email_sent = latest(&primary.last_email_sent, &duplicate.last_email_sent);
+    merged.last_email_opened = latest(&primary.last_email_opened, &duplicate.last_email_opened);
+    merged.last_email_clicked = latest(&primary.last_email_clicked, &duplicate.last_email_clicked);
+    for (url, count) in &duplicate.link_clicks {
+        *merged.link_clicks.entry(url.clone()).or_insert(0) += count;
+    }
+    if duplicate.created_at < merged.created_at {
+        merged.created_at = duplicate.created_at.clone();
+    }
+
+    // An opt-out or bounce on either record must survive the merge
+    let primary_open = matches!(merged.status, SubscriptionStatus::Subscribed | SubscriptionStatus::Pending);
+    let duplicate_closed = matches!(duplicate.status, SubscriptionStatus::Unsubscribed | SubscriptionStatus::Bounced | SubscriptionStatus::Complained);
+    if primary_open && duplicate_closed {
+        merged.status = duplicate.status.clone();
+    }
+    merged.tracking_opt_out = primary.tracking_opt_out || duplicate.tracking_opt_out;
+
+    merged.updated_at = Utc::now().to_rfc3339();
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contact(state: &ContactServiceState, email: &str, first: &str, last: &str, company: Option<&str>) -> Contact {
+        state.create_contact(
+            email.to_string(),
+            Some(first.to_string()),
+            Some(last.to_string()),
+            company.map(String::from),
+            None,
+            None,
+            None,
+            None,
+            None,
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_typo_name_pair_is_a_duplicate() {
+        let state = ContactServiceState::new();
+        let jonathan = contact(&state, "jonathan.smith@acme.io", "Jonathan", "Smith", Some("Acme Inc."));
+        let jonathon = contact(&state, "j.smith@acme.io", "Jonathon", "Smith", Some("ACME"));
+        contact(&state, "maria@acme.io", "Maria", "Garcia", Some("Acme"));
+        contact(&state, "peter@other.org", "Peter", "Jones", None);
+
+        // Alternate Gmail spelling of the same address
+        let gmail_a = contact(&state, "Sam.Lee+news@gmail.com", "Sam", "Lee", None);
+        let gmail_b = contact(&state, "samlee@gmail.com", "Samuel", "Lee", None);
+
+        let duplicates = state.find_duplicates(0.85).unwrap();
+        assert_eq!(duplicates.len(), 2, "{:?}", duplicates);
+
+        let pair: HashSet<&str> = [duplicates[0].primary_id.as_str(), duplicates[0].duplicate_id.as_str()].into_iter().collect();
+        assert_eq!(pair, [gmail_a.id.as_str(), gmail_b.id.as_str()].into_iter().collect());
+        assert_eq!(duplicates[0].score, 1.0);
+
+        let typo = &duplicates[1];
+        let pair: HashSet<&str> = [typo.primary_id.as_str(), typo.duplicate_id.as_str()].into_iter().collect();
+        assert_eq!(pair, [jonathan.id.as_str(), jonathon.id.as_str()].into_iter().collect());
+        assert!(typo.reasons.contains(&"name".to_string()) && typo.reasons.contains(&"company".to_string()));
+
+        // Deterministic across runs
+        let again = state.find_duplicates(0.85).unwrap();
+        assert_eq!(again[1].primary_id, typo.primary_id);
+        assert_eq!(again[1].score, typo.score);
+    }
+
+    #[test]
+    fn test_merge_preserves_associated_data_and_can_be_undone() {
+        let state = ContactServiceState::new();
+        let customers = state.create_list("Customers".to_string(), None, None).unwrap();
+        let webinar = state.create_list("Webinar".to_string(), None, None).unwrap();
+
+        let primary = state.create_contact(
+            "ana@example.com".to_string(), Some("Ana".to_string()), None, None, None,
+            Some(vec!["vip".to_string()]), Some(vec![customers.id.clone()]), None,
+            Some(HashMap::from([("plan".to_string(), "pro".to_string())])),
+        ).unwrap();
+        let duplicate = state.create_contact(
+            "ana.perez@work.example".to_string(), Some("Ana".to_string()), Some("Perez".to_string()),
+            Some("Example Corp".to_string()), Some("+1 555 010 9999".to_string()),
+            Some(vec!["vip".to_string(), "webinar".to_string()]), Some(vec![webinar.id.clone()]), None,
+            Some(HashMap::from([("plan".to_string(), "free".to_string()), ("city".to_string(), "Lima".to_string())])),
+        ).unwrap();
+        state.update_contact_engagement("ana@example.com", true, true, false, false).unwrap();
+        state.update_contact_engagement("ana.perez@work.example", true, true, true, false).unwrap();
+        state.update_contact_engagement("ana.perez@work.example", true, false, false, false).unwrap();
+
+        let record = state.merge_contacts(&primary.id, &duplicate.id).unwrap();
+        let merged = state.get_contact(&primary.id).unwrap();
+        assert!(state.get_contact(&duplicate.id).is_err());
+
+        assert_eq!(merged.email, "ana@example.com");
+        assert_eq!(merged.last_name.as_deref(), Some("Perez"));
+        assert_eq!(merged.company.as_deref(), Some("Example Corp"));
+        assert_eq!(merged.phone.as_deref(), Some("+1 555 010 9999"));
+        assert_eq!(merged.tags, vec!["vip", "webinar"]);
+        assert_eq!(merged.list_ids, vec![customers.id.clone(), webinar.id.clone()]);
+        assert_eq!(merged.custom_fields["plan"], "pro");
+        assert_eq!(merged.custom_fields["city"], "Lima");
+        assert_eq!(merged.custom_fields["alternate_emails"], "ana.perez@work.example");
+        assert_eq!((merged.email_count, merged.open_count, merged.click_count), (3, 2, 1));
+        assert!(merged.last_email_clicked.is_some());
+        assert_eq!(state.get_list(&webinar.id).unwrap().contact_count, 1);
+        assert_eq!(state.get_list("default").unwrap().contact_count, 1);
+
+        assert_eq!(state.get_merge_log().unwrap().len(), 1);
+        let (restored_primary, restored_duplicate) = state.undo_merge(&record.id).unwrap();
+        assert_eq!(restored_primary.tags, vec!["vip"]);
+        assert_eq!(restored_duplicate.email, "ana.perez@work.example");
+        assert_eq!(state.get_contact(&duplicate.id).unwrap().click_count, 1);
+        assert_eq!(state.get_list(&customers.id).unwrap().contact_count, 1);
+        assert_eq!(state.get_list("default").unwrap().contact_count, 2);
+        assert!(state.get_merge_log().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,432 @@
+// Cron Schedule - Cron expression parsing and next-run computation
+// Evaluates schedules in an IANA timezone so "0 9 * * MON" fires at 9am local
+// time. Supports 5-field (minute-first) expressions as well as the 6/7-field
+// seconds-first form, plus the L, W and # day specifiers.
+
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+
+const MAX_YEAR: i32 = 2099;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DayOfMonth {
+    /// `L` or `L-3`: the last day of the month, minus an offset
+    Last(u32),
+    /// `15W`: the weekday nearest the 15th, without leaving the month
+    NearestWeekday(u32),
+    /// `LW`: the last weekday of the month
+    LastWeekday,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DayOfWeek {
+    /// `5L` / `FRIL`: the last Friday of the month
+    Last(u32),
+    /// `5#3` / `FRI#3`: the third Friday of the month
+    Nth(u32, u32),
+}
+
+/// A parsed cron expression. Day of month and day of week follow the usual
+/// cron rule: when both are restricted, a day matching either one fires.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    day_specials: Vec<DayOfMonth>,
+    days_restricted: bool,
+    months: u64,
+    weekdays: u64,
+    weekday_specials: Vec<DayOfWeek>,
+    weekdays_restricted: bool,
+    years: Option<Vec<i32>>,
+}
+
+impl CronSchedule {
+    /// Parses `min hour dom month dow`, or `sec min hour dom month dow [year]`.
+    /// Five-field expressions number weekdays 0-7 from Sunday; the seconds-first
+    /// form numbers them 1-7 from Sunday, as the Quartz-style syntax does.
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let (seconds, rest, quartz) = match fields.len() {
+            5 => ("0", &fields[..], false),
+            6 | 7 => (fields[0], &fields[1..], true),
+            n => return Err(format!("Expected 5, 6 or 7 cron fields, found {}", n)),
+        };
+
+        let (days, day_specials, days_restricted) = parse_days_of_month(rest[2])?;
+        let (weekdays, weekday_specials, weekdays_restricted) = parse_days_of_week(rest[4], quartz)?;
+        let years = match rest.get(5) {
+            Some(field) if !is_any(field) => Some(parse_years(field)?),
+            _ => None,
+        };
+
+        Ok(Self {
+            seconds: parse_field(seconds, 0, 59, &[])?,
+            minutes: parse_field(rest[0], 0, 59, &[])?,
+            hours: parse_field(rest[1], 0, 23, &[])?,
+            days,
+            day_specials,
+            days_restricted,
+            months: parse_field(rest[3], 1, 12, &MONTH_NAMES)?,
+            weekdays,
+            weekday_specials,
+            weekdays_restricted,
+            years,
+        })
+    }
+
+    /// The first fire time strictly after `after`, evaluated in `tz`. Local times
+    /// that don't exist (spring forward) are skipped; local times that happen
+    /// twice (fall back) fire once, at their first occurrence.
+    pub fn next_after(&self, after: DateTime<Utc>, tz: Tz) -> Option<DateTime<Utc>> {
+        let start = after.with_timezone(&tz).naive_local().with_nanosecond(0)? + Duration::seconds(1);
+        let mut date = start.date();
+        let mut first_day = true;
+
+        while date.year() <= MAX_YEAR {
+            if !self.year_matches(date.year()) {
+                date = NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)?;
+                first_day = false;
+                continue;
+            }
+            if !has_bit(self.months, date.month()) {
+                date = first_of_next_month(date)?;
+                first_day = false;
+                continue;
+            }
+            if self.day_matches(date) {
+                let earliest = if first_day { start.time() } else { NaiveTime::MIN };
+                for time in self.times_from(earliest) {
+                    let local = NaiveDateTime::new(date, time);
+                    let fire = match tz.from_local_datetime(&local) {
+                        LocalResult::Single(fire) => fire,
+                        LocalResult::Ambiguous(earliest, _) => earliest,
+                        LocalResult::None => continue,
+                    };
+                    let fire = fire.with_timezone(&Utc);
+                    // During the repeated hour the first occurrence may already be past
+                    if fire > after {
+                        return Some(fire);
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+            first_day = false;
+        }
+        None
+    }
+
+    /// The next `count` fire times after `after`
+    pub fn upcoming(&self, after: DateTime<Utc>, tz: Tz, count: usize) -> Vec<DateTime<Utc>> {
+        let mut runs = Vec::with_capacity(count);
+        let mut cursor = after;
+        while runs.len() < count {
+            match self.next_after(cursor, tz) {
+                Some(next) => {
+                    runs.push(next);
+                    cursor = next;
+                }
+                None => break,
+            }
+        }
+        runs
+    }
+
+    fn year_matches(&self, year: i32) -> bool {
+        match &self.years {
+            Some(years) => years.contains(&year),
+            None => true,
+        }
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let by_day = has_bit(self.days, date.day()) || self.day_specials.iter().any(|s| s.matches(date));
+        let weekday = date.weekday().num_days_from_sunday();
+        let by_weekday = has_bit(self.weekdays, weekday) || self.weekday_specials.iter().any(|s| s.matches(date));
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => by_day || by_weekday,
+            (true, false) => by_day,
+            (false, true) => by_weekday,
+            (false, false) => true,
+        }
+    }
+
+    /// Matching times of day at or after `earliest`, in order
+    fn times_from(&self, earliest: NaiveTime) -> impl Iterator<Item = NaiveTime> + '_ {
+        bits(self.hours, 0, 23)
+            .flat_map(move |hour| bits(self.minutes, 0, 59).map(move |minute| (hour, minute)))
+            .flat_map(move |(hour, minute)| bits(self.seconds, 0, 59).map(move |second| (hour, minute, second)))
+            .filter_map(|(hour, minute, second)| NaiveTime::from_hms_opt(hour, minute, second))
+            .filter(move |time| *time >= earliest)
+    }
+}
+
+impl DayOfMonth {
+    fn matches(&self, date: NaiveDate) -> bool {
+        let last = last_day_of_month(date);
+        match *self {
+            DayOfMonth::Last(offset) => last.checked_sub(offset) == Some(date.day()),
+            DayOfMonth::NearestWeekday(day) => nearest_weekday(date, day.min(last)) == date.day(),
+            DayOfMonth::LastWeekday => nearest_weekday(date, last) == date.day(),
+        }
+    }
+}
+
+impl DayOfWeek {
+    fn matches(&self, date: NaiveDate) -> bool {
+        let weekday = date.weekday().num_days_from_sunday();
+        match *self {
+            DayOfWeek::Last(day) => weekday == day && date.day() + 7 > last_day_of_month(date),
+            DayOfWeek::Nth(day, nth) => weekday == day && (date.day() - 1) / 7 + 1 == nth,
+        }
+    }
+}
+
+const MONTH_NAMES: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+fn is_any(field: &str) -> bool {
+    field == "*" || field == "?"
+}
+
+fn has_bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn bits(mask: u64, min: u32, max: u32) -> impl Iterator<Item = u32> {
+    (min..=max).filter(move |value| has_bit(mask, *value))
+}
+
+/// Parses a list of values, `a-b` ranges and `/step`s into a bit mask.
+/// `names` map to values from `min` up.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("Invalid cron step: {}", part))?;
+                if step == 0 {
+                    return Err(format!("Cron step must be at least 1: {}", part));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if is_any(range) {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, min, max, names)?, parse_value(end, min, max, names)?)
+        } else {
+            let value = parse_value(range, min, max, names)?;
+            // "5/15" runs from 5 to the end of the range
+            (value, if step > 1 { max } else { value })
+        };
+        if start > end {
+            return Err(format!("Invalid cron range: {}", part));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(value: &str, min: u32, max: u32, names: &[&str]) -> Result<u32, String> {
+    let parsed = match names.iter().position(|name| name.eq_ignore_ascii_case(value)) {
+        Some(index) => index as u32 + min,
+        None => value.parse().map_err(|_| format!("Invalid cron value: {}", value))?,
+    };
+    if parsed < min || parsed > max {
+        return Err(format!("Cron value {} is outside {}-{}", value, min, max));
+    }
+    Ok(parsed)
+}
+
+fn parse_days_of_month(field: &str) -> Result<(u64, Vec<DayOfMonth>, bool), String> {
+    if is_any(field) {
+        return Ok((parse_field("*", 1, 31, &[])?, Vec::new(), false));
+    }
+    let mut mask = 0;
+    let mut specials = Vec::new();
+    for part in field.split(',') {
+        let upper = part.to_ascii_uppercase();
+        if upper == "LW" {
+            specials.push(DayOfMonth::LastWeekday);
+        } else if let Some(offset) = upper.strip_prefix('L') {
+            let offset = match offset.strip_prefix('-') {
+                Some(offset) => parse_value(offset, 0, 30, &[])?,
+                None if offset.is_empty() => 0,
+                None => return Err(format!("Invalid cron day of month: {}", part)),
+            };
+            specials.push(DayOfMonth::Last(offset));
+        } else if let Some(day) = upper.strip_suffix('W') {
+            specials.push(DayOfMonth::NearestWeekday(parse_value(day, 1, 31, &[])?));
+        } else {
+            mask |= parse_field(part, 1, 31, &[])?;
+        }
+    }
+    Ok((mask, specials, true))
+}
+
+fn parse_days_of_week(field: &str, quartz: bool) -> Result<(u64, Vec<DayOfWeek>, bool), String> {
+    if is_any(field) {
+        return Ok((parse_field("*", 0, 6, &[])?, Vec::new(), false));
+    }
+    // Quartz numbers weekdays 1-7 from Sunday, five-field cron 0-7 (7 is Sunday again)
+    let (min, max) = if quartz { (1, 7) } else { (0, 7) };
+    let weekday = |value: &str| -> Result<u32, String> {
+        let day = match WEEKDAY_NAMES.iter().position(|name| name.eq_ignore_ascii_case(value)) {
+            Some(index) => index as u32,
+            None => parse_value(value, min, max, &[])? - min,
+        };
+        Ok(day % 7)
+    };
+
+    let mut mask = 0;
+    let mut specials = Vec::new();
+    for part in field.split(',') {
+        let upper = part.to_ascii_uppercase();
+        if let Some((day, nth)) = upper.split_once('#') {
+            let nth = parse_value(nth, 1, 5, &[])?;
+            specials.push(DayOfWeek::Nth(weekday(day)?, nth));
+        } else if let Some(day) = upper.strip_suffix('L').filter(|day| !day.is_empty()) {
+            specials.push(DayOfWeek::Last(weekday(day)?));
+        } else {
+            let names: Vec<&str> = if quartz { WEEKDAY_NAMES.to_vec() } else { [&WEEKDAY_NAMES[..], &["SUN"]].concat() };
+            let shifted = parse_field(part, min, max, &names)?;
+            for day in bits(shifted, min, max) {
+                mask |= 1 << ((day - min) % 7);
+            }
+        }
+    }
+    Ok((mask, specials, true))
+}
+
+fn parse_years(field: &str) -> Result<Vec<i32>, String> {
+    let mut years = Vec::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<usize>().ok().filter(|s| *s > 0).ok_or_else(|| format!("Invalid cron step: {}", part))?),
+            None => (part, 1),
+        };
+        let parse = |value: &str| -> Result<i32, String> {
+            value.parse().ok().filter(|year| (1970..=MAX_YEAR).contains(year)).ok_or_else(|| format!("Invalid cron year: {}", value))
+        };
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (parse(start)?, parse(end)?),
+            None if is_any(range) => (1970, MAX_YEAR),
+            None => (parse(range)?, parse(range)?),
+        };
+        years.extend((start..=end).step_by(step));
+    }
+    years.sort_unstable();
+    years.dedup();
+    Ok(years)
+}
+
+fn first_of_next_month(date: NaiveDate) -> Option<NaiveDate> {
+    if date.month() == 12 {
+        NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+    }
+}
+
+fn last_day_of_month(date: NaiveDate) -> u32 {
+    first_of_next_month(date)
+        .and_then(|next| next.pred_opt())
+        .map(|last| last.day())
+        .unwrap_or(31)
+}
+
+/// The weekday closest to `day` in the same month as `date`
+fn nearest_weekday(date: NaiveDate, day: u32) -> u32 {
+    let Some(target) = date.with_day(day) else {
+        return 0;
+    };
+    let last = last_day_of_month(date);
+    match target.weekday() {
+        Weekday::Sat if day == 1 => 3,
+        Weekday::Sat => day - 1,
+        Weekday::Sun if day == last => day - 2,
+        Weekday::Sun => day + 1,
+        _ => day,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(tz: Tz, y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        tz.with_ymd_and_hms(y, m, d, h, min, 0).earliest().unwrap().with_timezone(&Utc)
+    }
+
+    fn local(runs: &[DateTime<Utc>], tz: Tz) -> Vec<String> {
+        runs.iter().map(|run| run.with_timezone(&tz).format("%Y-%m-%d %H:%M %Z").to_string()).collect()
+    }
+
+    #[test]
+    fn test_spring_forward_boundary() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+
+        // 2am doesn't exist on 2024-03-10: that day is skipped, not run at 3am
+        let nightly = CronSchedule::parse("0 2 * * *").unwrap();
+        let runs = nightly.upcoming(at(tz, 2024, 3, 8, 12, 0), tz, 3);
+        assert_eq!(local(&runs, tz), ["2024-03-09 02:00 EST", "2024-03-11 02:00 EDT", "2024-03-12 02:00 EDT"]);
+
+        // Half-hourly runs jump straight from 1:30 EST to 3:00 EDT
+        let half_hourly = CronSchedule::parse("*/30 * * * *").unwrap();
+        let runs = half_hourly.upcoming(at(tz, 2024, 3, 10, 1, 0), tz, 3);
+        assert_eq!(local(&runs, tz), ["2024-03-10 01:30 EST", "2024-03-10 03:00 EDT", "2024-03-10 03:30 EDT"]);
+        assert_eq!(runs[1] - runs[0], Duration::minutes(30));
+
+        // 9am local on both sides of the change, which is 14:00 then 13:00 UTC
+        let mornings = CronSchedule::parse("0 9 * * MON").unwrap();
+        let runs = mornings.upcoming(at(tz, 2024, 3, 1, 0, 0), tz, 2);
+        assert_eq!(local(&runs, tz), ["2024-03-04 09:00 EST", "2024-03-11 09:00 EDT"]);
+        assert_eq!((runs[0].hour(), runs[1].hour()), (14, 13));
+    }
+
+    #[test]
+    fn test_repeated_hour_runs_once() {
+        let tz: Tz = "America/New_York".parse().unwrap();
+        let schedule = CronSchedule::parse("30 1 * * *").unwrap();
+        let runs = schedule.upcoming(at(tz, 2024, 11, 2, 12, 0), tz, 2);
+        assert_eq!(local(&runs, tz), ["2024-11-03 01:30 EDT", "2024-11-04 01:30 EST"]);
+
+        // Starting inside the second 1am hour, the 1:30 that already ran is not repeated
+        let second_pass = Utc.with_ymd_and_hms(2024, 11, 3, 6, 10, 0).unwrap();
+        assert_eq!(local(&schedule.upcoming(second_pass, tz, 1), tz), ["2024-11-04 01:30 EST"]);
+    }
+
+    #[test]
+    fn test_day_specifiers() {
+        let tz = Tz::UTC;
+        let from = at(tz, 2024, 1, 1, 0, 0);
+        let days = |expression: &str, count: usize| -> Vec<String> {
+            let schedule = CronSchedule::parse(expression).unwrap();
+            schedule.upcoming(from, tz, count).iter().map(|run| run.format("%Y-%m-%d").to_string()).collect()
+        };
+
+        assert_eq!(days("0 0 L * *", 3), ["2024-01-31", "2024-02-29", "2024-03-31"]);
+        assert_eq!(days("0 0 L-2 2 *", 2), ["2024-02-27", "2025-02-26"]);
+        // June 1st 2024 is a Saturday: 1W stays in June and moves to Monday the 3rd
+        assert_eq!(days("0 0 1W 6 *", 1), ["2024-06-03"]);
+        // 2024-03-31 is a Sunday
+        assert_eq!(days("0 0 LW 3 *", 1), ["2024-03-29"]);
+        assert_eq!(days("0 0 * * FRI#3", 2), ["2024-01-19", "2024-02-16"]);
+        assert_eq!(days("0 0 * * 5L", 2), ["2024-01-26", "2024-02-23"]);
+        // Seconds-first form numbers weekdays from 1 = Sunday
+        assert_eq!(days("0 0 0 ? * 2#1 2025", 1), ["2025-01-06"]);
+        // Both day fields restricted: either one matches
+        assert_eq!(days("0 0 13 * FRI", 3), ["2024-01-05", "2024-01-12", "2024-01-13"]);
+
+        assert!(CronSchedule::parse("0 0 30 2 *").unwrap().next_after(from, tz).is_none());
+        assert!(CronSchedule::parse("0 9 * *").is_err());
+        assert!(CronSchedule::parse("61 * * * *").is_err());
+        assert!(CronSchedule::parse("0 0 * * MON#6").is_err());
+    }
+}
@@ -4,11 +4,17 @@
 // Full DOM access, cookies, sessions, DRM support
 
 use headless_chrome::{Browser, LaunchOptions, Tab};
+use headless_chrome::protocol::cdp::{Browser as BrowserDomain, Emulation, Page, IO};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::Path;
 use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 
+use super::cube_web_engine::{resolve_print_template, PrintOptions};
+
 // ============================================
 // Types
 // ============================================
@@ -89,6 +95,218 @@ impl Default for BrowserConfig {
     }
 }
 
+/// An item (list row, card, article) seen while scrolling a page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScrollItem {
+    pub key: String,
+    pub text: String,
+}
+
+/// What the page looked like after one scroll step
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrollProbe {
+    pub scroll_top: f64,
+    pub viewport_height: f64,
+    pub scroll_height: f64,
+    pub items: Vec<ScrollItem>,
+    pub loaded_images: Vec<String>,
+    pub pending_images: usize,
+}
+
+impl ScrollProbe {
+    fn at_bottom(&self) -> bool {
+        self.scroll_top + self.viewport_height >= self.scroll_height - 2.0
+    }
+}
+
+/// Result of scrolling a page until its lazy content stopped appearing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LazyLoadResult {
+    pub scrolls: u32,
+    /// `false` when `max_scrolls` ran out while content was still appearing
+    pub complete: bool,
+    /// Items were removed from the DOM while scrolling (recycled list), so
+    /// `items` holds more than the final `html` does
+    pub virtualized: bool,
+    /// Every item seen during the scroll, in first-seen order
+    pub items: Vec<ScrollItem>,
+    pub loaded_images: Vec<String>,
+    pub pending_images: usize,
+    pub html: String,
+}
+
+/// Accumulates scroll probes, extracting items as they pass through the
+/// viewport so recycled (virtualized) lists are captured in full
+#[derive(Debug, Default)]
+pub struct LazyLoadCollector {
+    items: Vec<ScrollItem>,
+    seen_items: HashSet<String>,
+    loaded_images: Vec<String>,
+    seen_images: HashSet<String>,
+    scroll_height: f64,
+    pending_images: usize,
+    idle_rounds: u32,
+    virtualized: bool,
+}
+
+impl LazyLoadCollector {
+    /// Record a probe, returning whether it showed any new content
+    pub fn record(&mut self, probe: &ScrollProbe) -> bool {
+        let present: HashSet<&str> = probe.items.iter().map(|item| item.key.as_str()).collect();
+        if !present.is_empty() && self.seen_items.iter().any(|key| !present.contains(key.as_str())) {
+            self.virtualized = true;
+        }
+
+        let mut changed = probe.scroll_height > self.scroll_height + 1.0;
+        self.scroll_height = self.scroll_height.max(probe.scroll_height);
+        for item in &probe.items {
+            if self.seen_items.insert(item.key.clone()) {
+                self.items.push(item.clone());
+                changed = true;
+            }
+        }
+        for src in &probe.loaded_images {
+            if self.seen_images.insert(src.clone()) {
+                self.loaded_images.push(src.clone());
+                changed = true;
+            }
+        }
+        self.pending_images = probe.pending_images;
+
+        if !changed && probe.at_bottom() {
+            self.idle_rounds += 1;
+        } else {
+            self.idle_rounds = 0;
+        }
+        changed
+    }
+
+    /// The bottom was reached and nothing new appeared for a few rounds
+    pub fn is_settled(&self) -> bool {
+        self.idle_rounds >= LAZY_LOAD_IDLE_ROUNDS
+    }
+
+    pub fn finish(self, scrolls: u32, html: String) -> LazyLoadResult {
+        LazyLoadResult {
+            scrolls,
+            complete: self.is_settled(),
+            virtualized: self.virtualized,
+            items: self.items,
+            loaded_images: self.loaded_images,
+            pending_images: self.pending_images,
+            html,
+        }
+    }
+}
+
+/// Fixed position reported to pages through `navigator.geolocation`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeolocationCoords {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub accuracy: f64,
+}
+
+impl GeolocationCoords {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&self.latitude) {
+            return Err(format!("Latitude {} is out of range", self.latitude));
+        }
+        if !(-180.0..=180.0).contains(&self.longitude) {
+            return Err(format!("Longitude {} is out of range", self.longitude));
+        }
+        if !self.accuracy.is_finite() || self.accuracy < 0.0 {
+            return Err(format!("Accuracy {} must be a non-negative number", self.accuracy));
+        }
+        Ok(())
+    }
+}
+
+/// Location-related values spoofed to pages. Unset fields behave normally.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LocationOverride {
+    pub geolocation: Option<GeolocationCoords>,
+    /// Reported as `navigator.languages` and `Accept-Language`; the first
+    /// entry also sets the `Intl` locale
+    pub languages: Option<Vec<String>>,
+    /// IANA timezone, e.g. `Europe/Paris`
+    pub timezone: Option<String>,
+}
+
+impl LocationOverride {
+    /// Fields set on `tab` win over the profile-wide ones
+    pub fn merged(&self, tab: Option<&LocationOverride>) -> LocationOverride {
+        let Some(tab) = tab else { return self.clone() };
+        LocationOverride {
+            geolocation: tab.geolocation.clone().or_else(|| self.geolocation.clone()),
+            languages: tab.languages.clone().or_else(|| self.languages.clone()),
+            timezone: tab.timezone.clone().or_else(|| self.timezone.clone()),
+        }
+    }
+}
+
+/// Locale and timezone as observed by a page and the server it loaded from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocaleDetection {
+    /// `Intl.DateTimeFormat().resolvedOptions().timeZone`
+    pub timezone: String,
+    pub intl_locale: String,
+    pub language: String,
+    pub languages: Vec<String>,
+    /// `Accept-Language` header received by the detection page's server
+    pub accept_language: Option<String>,
+    /// `Date#getTimezoneOffset` agrees with the `Intl` timezone
+    pub date_offset_matches_timezone: bool,
+}
+
+impl LocaleDetection {
+    /// Human-readable differences from the values `expected` spoofs
+    pub fn mismatches(&self, expected: &LocationOverride) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        if let Some(timezone) = &expected.timezone {
+            if &self.timezone != timezone {
+                mismatches.push(format!("Intl timezone is {}, expected {}", self.timezone, timezone));
+            }
+        }
+        if !self.date_offset_matches_timezone {
+            mismatches.push(format!("Date offset does not match timezone {}", self.timezone));
+        }
+        if let Some(languages) = &expected.languages {
+            if self.languages != *languages {
+                mismatches.push(format!(
+                    "navigator.languages is [{}], expected [{}]",
+                    self.languages.join(", "),
+                    languages.join(", ")
+                ));
+            }
+            if let Some(first) = languages.first() {
+                if &self.language != first {
+                    mismatches.push(format!("navigator.language is {}, expected {}", self.language, first));
+                }
+                if &self.intl_locale != first {
+                    mismatches.push(format!("Intl locale is {}, expected {}", self.intl_locale, first));
+                }
+            }
+            // Chromium appends q-values, so compare the language tags only
+            let header: Vec<String> = self.accept_language.as_deref().unwrap_or_default()
+                .split(',')
+                .map(|part| part.split(';').next().unwrap_or_default().trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+            if header != *languages {
+                mismatches.push(format!(
+                    "Accept-Language is {:?}, expected {}",
+                    self.accept_language.as_deref().unwrap_or(""),
+                    languages.join(",")
+                ));
+            }
+        }
+        mismatches
+    }
+}
+
 // ============================================
 // Browser Engine State
 // ============================================
@@ -97,6 +315,8 @@ pub struct CubeBrowserEngine {
     browser: Option<Arc<Browser>>,
     tabs: RwLock<HashMap<String, Arc<Tab>>>,
     config: RwLock<BrowserConfig>,
+    profile_location_override: RwLock<LocationOverride>,
+    tab_location_overrides: RwLock<HashMap<String, LocationOverride>>,
 }
 
 impl Default for CubeBrowserEngine {
@@ -105,6 +325,8 @@ impl Default for CubeBrowserEngine {
             browser: None,
             tabs: RwLock::new(HashMap::new()),
             config: RwLock::new(BrowserConfig::default()),
+            profile_location_override: RwLock::new(LocationOverride::default()),
+            tab_location_overrides: RwLock::new(HashMap::new()),
         }
     }
 }
@@ -137,6 +359,10 @@ impl CubeBrowserEngine {
         Ok(())
     }
 
+    pub fn is_initialized(&self) -> bool {
+        self.browser.is_some()
+    }
+
     /// Create a new browser tab
     pub fn create_tab(&self, url: &str) -> Result<BrowserTab, String> {
         let browser = self.browser.as_ref()
@@ -147,6 +373,17 @@ impl CubeBrowserEngine {
         
         let tab_id = uuid::Uuid::new_v4().to_string();
         
+        tab.call_method(Page::AddScriptToEvaluateOnNewDocument {
+            source: OBSERVER_SUPPORT_SCRIPT.to_string(),
+            ..Default::default()
+        })
+        .map_err(|e| format!("Failed to install observer support: {}", e))?;
+        
+        let profile_override = self.profile_location_override.read().unwrap().clone();
+        if profile_override != LocationOverride::default() {
+            self.apply_location_override(&tab, &profile_override)?;
+        }
+        
         tab.navigate_to(url)
             .map_err(|e| format!("Failed to navigate: {}", e))?;
         
@@ -195,6 +432,7 @@ impl CubeBrowserEngine {
         let mut tabs = self.tabs.write().unwrap();
         tabs.remove(tab_id)
             .ok_or("Tab not found")?;
+        self.tab_location_overrides.write().unwrap().remove(tab_id);
         
         println!("❌ [CUBE ENGINE] Closed tab: {}", tab_id);
         Ok(())
@@ -498,6 +736,32 @@ impl CubeBrowserEngine {
         Ok(())
     }
 
+    /// Delete cookies from the browser's shared cookie store. Any open tab
+    /// can reach it; with no tabs open there is nothing to delete through.
+    pub fn delete_cookies(&self, cookies: &[CookieData]) -> Result<(), String> {
+        if cookies.is_empty() {
+            return Ok(());
+        }
+        let tabs = self.tabs.read().unwrap();
+        let Some(tab) = tabs.values().next() else {
+            return Ok(());
+        };
+
+        let requests = cookies.iter().map(|c| {
+            let host = c.domain.trim_start_matches('.');
+            headless_chrome::protocol::cdp::Network::DeleteCookies {
+                name: c.name.clone(),
+                // An explicit URL stops the tab's own URL being used instead
+                url: Some(format!("https://{}{}", host, c.path)),
+                domain: Some(c.domain.clone()),
+                path: Some(c.path.clone()),
+                partition_key: None,
+            }
+        }).collect();
+        tab.delete_cookies(requests)
+            .map_err(|e| format!("Failed to delete cookies: {}", e))
+    }
+
     /// Get localStorage value
     pub fn get_local_storage(&self, tab_id: &str, key: &str) -> Result<Option<String>, String> {
         let script = format!("localStorage.getItem('{}')", key.replace("'", "\\'"));
@@ -614,16 +878,243 @@ impl CubeBrowserEngine {
         Ok(data)
     }
 
-    /// Generate PDF from page
-    pub fn print_to_pdf(&self, tab_id: &str) -> Result<Vec<u8>, String> {
+    /// Scroll the page a viewport at a time so IntersectionObserver-driven
+    /// lazy images and infinite-scroll batches load, stopping once the
+    /// bottom is reached and no new content appears. Items are extracted
+    /// after every step, so virtualized lists that recycle rows are
+    /// captured in full.
+    pub fn scroll_to_load_all(&self, tab_id: &str, max_scrolls: u32) -> Result<LazyLoadResult, String> {
+        self.execute_script(tab_id, OBSERVER_SUPPORT_SCRIPT)?;
+        self.execute_script(tab_id, LAZY_LOAD_SCRIPT)?;
+
+        let mut collector = LazyLoadCollector::default();
+        collector.record(&self.probe_lazy_content(tab_id)?);
+
+        let mut scrolls = 0;
+        while scrolls < max_scrolls && !collector.is_settled() {
+            self.execute_script(tab_id, "window.__cubeLazyLoad.scrollStep()")?;
+            scrolls += 1;
+            std::thread::sleep(LAZY_LOAD_SETTLE_DELAY);
+            collector.record(&self.probe_lazy_content(tab_id)?);
+        }
+
+        let result = collector.finish(scrolls, self.get_page_html(tab_id)?);
+        println!(
+            "📜 [CUBE ENGINE] Scrolled {} {} times: {} items, {} images loaded",
+            tab_id, result.scrolls, result.items.len(), result.loaded_images.len()
+        );
+        Ok(result)
+    }
+
+    fn probe_lazy_content(&self, tab_id: &str) -> Result<ScrollProbe, String> {
+        let value = self.execute_script(tab_id, "window.__cubeLazyLoad.probe()")?;
+        let json = value.as_str().ok_or("Lazy load probe returned no data")?;
+        serde_json::from_str(json).map_err(|e| format!("Invalid lazy load probe: {}", e))
+    }
+
+    /// Spoof the position returned by `navigator.geolocation` for one tab,
+    /// or for every tab in the profile when `tab_id` is `None`. Passing
+    /// `None` coordinates removes the override and restores normal
+    /// permission handling.
+    pub fn set_geolocation_override(&self, tab_id: Option<&str>, coords: Option<GeolocationCoords>) -> Result<(), String> {
+        if let Some(coords) = &coords {
+            coords.validate()?;
+        }
+        self.update_location_override(tab_id, |o| o.geolocation = coords)
+    }
+
+    /// Spoof `navigator.languages` and the timezone, typically to match a
+    /// geolocation override
+    pub fn set_locale_override(
+        &self,
+        tab_id: Option<&str>,
+        languages: Option<Vec<String>>,
+        timezone: Option<String>,
+    ) -> Result<(), String> {
+        self.update_location_override(tab_id, |o| {
+            o.languages = languages.filter(|l| !l.is_empty());
+            o.timezone = timezone.filter(|t| !t.trim().is_empty());
+        })
+    }
+
+    /// The override in effect for a tab (profile values plus tab values)
+    pub fn get_location_override(&self, tab_id: &str) -> LocationOverride {
+        let tab_overrides = self.tab_location_overrides.read().unwrap();
+        self.profile_location_override.read().unwrap().merged(tab_overrides.get(tab_id))
+    }
+
+    /// Replace the whole override for one tab, or profile-wide when
+    /// `tab_id` is `None`
+    pub fn set_location_override(&self, tab_id: Option<&str>, location: LocationOverride) -> Result<(), String> {
+        if let Some(coords) = &location.geolocation {
+            coords.validate()?;
+        }
+        self.update_location_override(tab_id, |o| *o = location)
+    }
+
+    /// Load a local detection page in the tab and report the timezone,
+    /// languages and `Accept-Language` header it actually sees
+    pub fn detect_locale(&self, tab_id: &str) -> Result<LocaleDetection, String> {
+        let (url, server) = serve_locale_detection_page()?;
+        self.navigate(tab_id, &url)?;
+        let _ = server.join();
+
+        let value = self.execute_script(tab_id, LOCALE_DETECTION_SCRIPT)?;
+        let json = value.as_str().ok_or("Locale detection returned no data")?;
+        serde_json::from_str(json).map_err(|e| format!("Invalid locale detection: {}", e))
+    }
+
+    fn update_location_override(
+        &self,
+        tab_id: Option<&str>,
+        update: impl FnOnce(&mut LocationOverride),
+    ) -> Result<(), String> {
         let tabs = self.tabs.read().unwrap();
-        let tab = tabs.get(tab_id)
-            .ok_or("Tab not found")?;
+        match tab_id {
+            Some(id) => {
+                if !tabs.contains_key(id) {
+                    return Err("Tab not found".to_string());
+                }
+                update(self.tab_location_overrides.write().unwrap().entry(id.to_string()).or_default());
+            }
+            None => update(&mut self.profile_location_override.write().unwrap()),
+        }
+
+        for (id, tab) in tabs.iter().filter(|(id, _)| tab_id.map_or(true, |t| t == id.as_str())) {
+            self.apply_location_override(tab, &self.get_location_override(id))?;
+        }
+
+        // Geolocation is granted browser-wide, so only revoke it once no tab spoofs it
+        let spoofing = tabs.keys().any(|id| self.get_location_override(id).geolocation.is_some())
+            || self.profile_location_override.read().unwrap().geolocation.is_some();
+        if !spoofing {
+            if let Some(browser) = &self.browser {
+                browser.call_method(BrowserDomain::ResetPermissions { browser_context_id: None })
+                    .map_err(|e| format!("Failed to reset permissions: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_location_override(&self, tab: &Tab, location: &LocationOverride) -> Result<(), String> {
+        match &location.geolocation {
+            Some(coords) => {
+                tab.call_method(Emulation::SetGeolocationOverride {
+                    latitude: Some(coords.latitude),
+                    longitude: Some(coords.longitude),
+                    accuracy: Some(coords.accuracy),
+                    ..Default::default()
+                })
+                .map_err(|e| format!("Failed to override geolocation: {}", e))?;
+                if let Some(browser) = &self.browser {
+                    browser.call_method(BrowserDomain::GrantPermissions {
+                        permissions: vec![BrowserDomain::PermissionType::Geolocation],
+                        ..Default::default()
+                    })
+                    .map_err(|e| format!("Failed to grant geolocation: {}", e))?;
+                }
+            }
+            None => {
+                tab.call_method(Emulation::ClearGeolocationOverride(None))
+                    .map_err(|e| format!("Failed to clear geolocation: {}", e))?;
+            }
+        }
+
+        let user_agent = match self.config.read().unwrap().user_agent.clone() {
+            Some(user_agent) => user_agent,
+            None => tab.evaluate("navigator.userAgent", false)
+                .map_err(|e| format!("Failed to read user agent: {}", e))?
+                .value
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default(),
+        };
+        let accept_language = location.languages.as_ref().map(|languages| languages.join(","));
+        tab.set_user_agent(&user_agent, accept_language.as_deref(), None)
+            .map_err(|e| format!("Failed to override languages: {}", e))?;
+        tab.call_method(Emulation::SetLocaleOverride {
+            locale: location.languages.as_ref().and_then(|languages| languages.first().cloned()),
+        })
+        .map_err(|e| format!("Failed to override locale: {}", e))?;
+
+        // An empty timezone restores the host timezone
+        tab.call_method(Emulation::SetTimezoneOverride {
+            timezone_id: location.timezone.clone().unwrap_or_default(),
+        })
+        .map_err(|e| format!("Invalid timezone override: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Generate PDF from page
+    pub fn print_to_pdf(&self, tab_id: &str, options: &PrintOptions) -> Result<Vec<u8>, String> {
+        let mut data = Vec::new();
+        self.write_pdf(tab_id, options, &mut data)?;
+        Ok(data)
+    }
+
+    /// Generate PDF from page straight into a file, so very long documents
+    /// never have to fit in memory. Returns the number of bytes written.
+    pub fn print_to_pdf_file(&self, tab_id: &str, options: &PrintOptions, path: &Path) -> Result<u64, String> {
+        let file = std::fs::File::create(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut writer = std::io::BufWriter::new(file);
+        let written = self.write_pdf(tab_id, options, &mut writer)?;
+        writer.flush()
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        Ok(written)
+    }
+
+    /// Print the page and copy the PDF stream to `out` chunk by chunk
+    fn write_pdf<W: Write>(&self, tab_id: &str, options: &PrintOptions, out: &mut W) -> Result<u64, String> {
+        let request = pdf_request(options)?;
+        let tab = {
+            let tabs = self.tabs.read().unwrap();
+            tabs.get(tab_id).cloned().ok_or("Tab not found")?
+        };
         
-        let data = tab.print_to_pdf(None)
+        let result = tab.call_method(request)
             .map_err(|e| format!("PDF generation failed: {}", e))?;
         
-        Ok(data)
+        let handle = match result.stream {
+            Some(handle) => handle,
+            None => {
+                // Older Chromium ignores ReturnAsStream and inlines the PDF
+                let data = BASE64.decode(result.data)
+                    .map_err(|e| format!("Invalid PDF data: {}", e))?;
+                out.write_all(&data).map_err(|e| format!("Failed to write PDF: {}", e))?;
+                return Ok(data.len() as u64);
+            }
+        };
+        
+        let mut written = 0u64;
+        let copied = loop {
+            let chunk = match tab.call_method(IO::Read {
+                handle: handle.clone(),
+                offset: None,
+                size: Some(PDF_STREAM_CHUNK_SIZE),
+            }) {
+                Ok(chunk) => chunk,
+                Err(e) => break Err(format!("Failed to read PDF stream: {}", e)),
+            };
+            let bytes = if chunk.base_64_encoded.unwrap_or(false) {
+                match BASE64.decode(&chunk.data) {
+                    Ok(bytes) => bytes,
+                    Err(e) => break Err(format!("Invalid PDF data: {}", e)),
+                }
+            } else {
+                chunk.data.into_bytes()
+            };
+            if let Err(e) = out.write_all(&bytes) {
+                break Err(format!("Failed to write PDF: {}", e));
+            }
+            written += bytes.len() as u64;
+            if chunk.eof {
+                break Ok(written);
+            }
+        };
+        
+        let _ = tab.call_method(IO::Close { handle });
+        copied
     }
 
     /// Close all tabs and browser
@@ -640,6 +1131,308 @@ impl CubeBrowserEngine {
     }
 }
 
+/// Bytes requested per `IO.read` call when streaming a PDF out of Chromium
+const PDF_STREAM_CHUNK_SIZE: u32 = 1024 * 1024;
+
+/// Build the `Page.printToPDF` request for `options`
+fn pdf_request(options: &PrintOptions) -> Result<Page::PrintToPDF, String> {
+    let options = options.normalized()?;
+    // Chromium prints its default header/footer when only one template is set
+    let (header, footer) = if options.display_header_footer {
+        (
+            Some(resolve_print_template(options.header_template.as_deref().unwrap_or(""))),
+            Some(resolve_print_template(options.footer_template.as_deref().unwrap_or(""))),
+        )
+    } else {
+        (None, None)
+    };
+    
+    Ok(Page::PrintToPDF {
+        landscape: Some(options.landscape),
+        display_header_footer: Some(options.display_header_footer),
+        print_background: Some(options.print_background),
+        scale: Some(options.scale),
+        paper_width: Some(options.paper_width),
+        paper_height: Some(options.paper_height),
+        margin_top: Some(options.margin_top),
+        margin_bottom: Some(options.margin_bottom),
+        margin_left: Some(options.margin_left),
+        margin_right: Some(options.margin_right),
+        page_ranges: options.page_ranges,
+        header_template: header,
+        footer_template: footer,
+        prefer_css_page_size: Some(options.prefer_css_page_size),
+        transfer_mode: Some(Page::PrintToPDFTransfer_modeOption::ReturnAsStream),
+        ..Default::default()
+    })
+}
+
+/// Pause after each scroll so observers fire and lazy requests land
+const LAZY_LOAD_SETTLE_DELAY: Duration = Duration::from_millis(400);
+/// Idle rounds at the bottom of the page before scrolling stops
+const LAZY_LOAD_IDLE_ROUNDS: u32 = 2;
+
+/// Guarantees IntersectionObserver and ResizeObserver exist and deliver
+/// callbacks on scroll, resize and DOM changes. Native implementations are
+/// kept; the fallbacks only run where the engine lacks them.
+const OBSERVER_SUPPORT_SCRIPT: &str = r#"
+(function() {
+    if (window.__cubeObserverSupport) return;
+    window.__cubeObserverSupport = true;
+
+    const intersectionObservers = new Set();
+    const resizeObservers = new Set();
+
+    if (typeof window.IntersectionObserver !== 'function') {
+        const parseMargin = (margin) => {
+            const parts = String(margin || '0px').trim().split(/\s+/).map(p => parseFloat(p) || 0);
+            const [top, right = top, bottom = top, left = right] = parts;
+            return { top, right, bottom, left };
+        };
+
+        class CubeIntersectionObserver {
+            constructor(callback, options = {}) {
+                this.callback = callback;
+                this.root = options.root || null;
+                this.rootMargin = options.rootMargin || '0px';
+                this.thresholds = [].concat(options.threshold || 0).sort();
+                this.targets = new Map();
+                intersectionObservers.add(this);
+            }
+            observe(target) {
+                if (!this.targets.has(target)) {
+                    this.targets.set(target, -1);
+                    scheduleCheck();
+                }
+            }
+            unobserve(target) { this.targets.delete(target); }
+            disconnect() { this.targets.clear(); intersectionObservers.delete(this); }
+            takeRecords() { return []; }
+            check() {
+                const margin = parseMargin(this.rootMargin);
+                const base = this.root
+                    ? this.root.getBoundingClientRect()
+                    : { top: 0, left: 0, right: window.innerWidth, bottom: window.innerHeight };
+                const rootBounds = {
+                    top: base.top - margin.top, left: base.left - margin.left,
+                    right: base.right + margin.right, bottom: base.bottom + margin.bottom,
+                };
+                const entries = [];
+                this.targets.forEach((lastRatio, target) => {
+                    const rect = target.getBoundingClientRect();
+                    const width = Math.max(0, Math.min(rect.right, rootBounds.right) - Math.max(rect.left, rootBounds.left));
+                    const height = Math.max(0, Math.min(rect.bottom, rootBounds.bottom) - Math.max(rect.top, rootBounds.top));
+                    const area = rect.width * rect.height;
+                    const isIntersecting = target.isConnected && rect.bottom >= rootBounds.top && rect.top <= rootBounds.bottom
+                        && rect.right >= rootBounds.left && rect.left <= rootBounds.right;
+                    const ratio = isIntersecting ? (area > 0 ? (width * height) / area : 1) : 0;
+                    const crossed = this.thresholds.some(t => (lastRatio < t) !== (ratio < t)) || (lastRatio < 0);
+                    if (crossed) {
+                        entries.push({
+                            target, isIntersecting, intersectionRatio: ratio, time: performance.now(),
+                            boundingClientRect: rect, rootBounds, intersectionRect: { width, height },
+                        });
+                    }
+                    this.targets.set(target, ratio);
+                });
+                if (entries.length) this.callback(entries, this);
+            }
+        }
+        window.IntersectionObserver = CubeIntersectionObserver;
+    }
+
+    if (typeof window.ResizeObserver !== 'function') {
+        class CubeResizeObserver {
+            constructor(callback) {
+                this.callback = callback;
+                this.targets = new Map();
+                resizeObservers.add(this);
+            }
+            observe(target) {
+                if (!this.targets.has(target)) {
+                    this.targets.set(target, null);
+                    scheduleCheck();
+                }
+            }
+            unobserve(target) { this.targets.delete(target); }
+            disconnect() { this.targets.clear(); resizeObservers.delete(this); }
+            check() {
+                const entries = [];
+                this.targets.forEach((last, target) => {
+                    const rect = target.getBoundingClientRect();
+                    if (!last || last.width !== rect.width || last.height !== rect.height) {
+                        this.targets.set(target, { width: rect.width, height: rect.height });
+                        const size = [{ inlineSize: rect.width, blockSize: rect.height }];
+                        entries.push({ target, contentRect: rect, borderBoxSize: size, contentBoxSize: size });
+                    }
+                });
+                if (entries.length) this.callback(entries, this);
+            }
+        }
+        window.ResizeObserver = CubeResizeObserver;
+    }
+
+    let pending = false;
+    function scheduleCheck() {
+        if (pending) return;
+        pending = true;
+        const run = () => {
+            pending = false;
+            intersectionObservers.forEach(o => o.check());
+            resizeObservers.forEach(o => o.check());
+        };
+        (window.requestAnimationFrame || setTimeout)(run, 16);
+    }
+    window.__cubeCheckObservers = scheduleCheck;
+
+    window.addEventListener('scroll', scheduleCheck, true);
+    window.addEventListener('resize', scheduleCheck);
+    new MutationObserver(scheduleCheck).observe(document, { childList: true, subtree: true, attributes: true });
+})();
+"#;
+
+/// Scroll/probe helpers used by `scroll_to_load_all`. The scroll target is
+/// the document, or the largest scrollable container when the document
+/// itself does not scroll (the usual virtualized-list layout).
+const LAZY_LOAD_SCRIPT: &str = r#"
+(function() {
+    if (window.__cubeLazyLoad) return;
+    const ITEM_SELECTOR = '[data-key], [data-index], [data-id], [role="listitem"], li, article';
+
+    function scroller() {
+        const doc = document.scrollingElement || document.documentElement;
+        if (doc.scrollHeight - doc.clientHeight > 1) return doc;
+        let best = null;
+        document.querySelectorAll('*').forEach(el => {
+            const overflow = getComputedStyle(el).overflowY;
+            const range = el.scrollHeight - el.clientHeight;
+            if ((overflow === 'auto' || overflow === 'scroll') && range > 1
+                && (!best || range > best.scrollHeight - best.clientHeight)) {
+                best = el;
+            }
+        });
+        return best || doc;
+    }
+
+    window.__cubeLazyLoad = {
+        scrollStep() {
+            const el = scroller();
+            el.scrollTop = el.scrollTop + Math.max(el.clientHeight * 0.9, 100);
+            el.dispatchEvent(new Event('scroll'));
+            if (window.__cubeCheckObservers) window.__cubeCheckObservers();
+        },
+        probe() {
+            const el = scroller();
+            const items = [];
+            document.querySelectorAll(ITEM_SELECTOR).forEach(node => {
+                if (node.parentElement && node.parentElement.closest(ITEM_SELECTOR)) return;
+                const text = (node.innerText || node.textContent || '').replace(/\s+/g, ' ').trim().slice(0, 2000);
+                const key = node.getAttribute('data-key') || node.getAttribute('data-id')
+                    || node.getAttribute('data-index') || node.id || text;
+                if (key) items.push({ key, text });
+            });
+            const loadedImages = [];
+            let pendingImages = 0;
+            Array.from(document.images).forEach(img => {
+                if (img.complete && img.naturalWidth > 0) {
+                    loadedImages.push(img.currentSrc || img.src);
+                } else {
+                    pendingImages++;
+                }
+            });
+            return JSON.stringify({
+                scrollTop: el.scrollTop,
+                viewportHeight: el.clientHeight,
+                scrollHeight: el.scrollHeight,
+                items,
+                loadedImages,
+                pendingImages,
+            });
+        },
+    };
+})();
+"#;
+
+// ============================================
+// Locale Detection
+// ============================================
+
+/// Reads what the page sees; `Date` is checked against the offset `Intl`
+/// computes for the reported zone
+const LOCALE_DETECTION_SCRIPT: &str = r#"(() => {
+    const resolved = Intl.DateTimeFormat().resolvedOptions();
+    const sample = new Date(Date.UTC(2024, 0, 15, 12));
+    const parts = Object.fromEntries(new Intl.DateTimeFormat('en-US', {
+        timeZone: resolved.timeZone, hourCycle: 'h23',
+        year: 'numeric', month: '2-digit', day: '2-digit', hour: '2-digit', minute: '2-digit',
+    }).formatToParts(sample).map(part => [part.type, part.value]));
+    const zoned = Date.UTC(+parts.year, +parts.month - 1, +parts.day, +parts.hour, +parts.minute);
+    return JSON.stringify({
+        timezone: resolved.timeZone,
+        intlLocale: resolved.locale,
+        language: navigator.language,
+        languages: Array.from(navigator.languages),
+        acceptLanguage: window.__cubeAcceptLanguage ?? null,
+        dateOffsetMatchesTimezone: sample.getTimezoneOffset() === (sample.getTime() - zoned) / 60000,
+    });
+})()"#;
+
+const LOCALE_DETECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serve one request on a loopback port, echoing its `Accept-Language`
+/// header into the page as `window.__cubeAcceptLanguage`
+fn serve_locale_detection_page() -> Result<(String, std::thread::JoinHandle<()>), String> {
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .map_err(|e| format!("Failed to start detection server: {}", e))?;
+    let url = format!(
+        "http://{}/locale-detect",
+        listener.local_addr().map_err(|e| format!("Failed to start detection server: {}", e))?
+    );
+    listener.set_nonblocking(true)
+        .map_err(|e| format!("Failed to start detection server: {}", e))?;
+
+    let server = std::thread::spawn(move || {
+        let deadline = std::time::Instant::now() + LOCALE_DETECTION_TIMEOUT;
+        let mut stream = loop {
+            match listener.accept() {
+                Ok((stream, _)) => break stream,
+                Err(_) if std::time::Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+                Err(_) => return,
+            }
+        };
+        let _ = stream.set_nonblocking(false);
+        let _ = stream.set_read_timeout(Some(LOCALE_DETECTION_TIMEOUT));
+
+        let mut accept_language = None;
+        let mut reader = BufReader::new(&stream);
+        let mut line = String::new();
+        while reader.read_line(&mut line).map_or(false, |n| n > 0) && !line.trim_end().is_empty() {
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("accept-language") {
+                    accept_language = Some(value.trim().to_string());
+                }
+            }
+            line.clear();
+        }
+
+        let body = format!(
+            "<!DOCTYPE html><html><head><title>Locale detection</title><script>window.__cubeAcceptLanguage = {};</script></head><body></body></html>",
+            serde_json::to_string(&accept_language).unwrap_or_else(|_| "null".to_string())
+        );
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+    });
+
+    Ok((url, server))
+}
+
 // ============================================
 // Global Browser Instance
 // ============================================
@@ -658,3 +1451,284 @@ pub fn init_browser(config: Option<BrowserConfig>) -> Result<(), String> {
 pub fn get_browser() -> std::sync::MutexGuard<'static, CubeBrowserEngine> {
     CUBE_BROWSER.lock().unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRINT_FIXTURE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<title>Quarterly Invoice</title>
+<style>
+  @page { size: A5; margin: 1in 0.5in; }
+  @media print {
+    .screen-only { display: none; }
+    section { break-after: page; }
+  }
+</style>
+</head>
+<body>
+  <div class="screen-only">Navigation bar</div>
+  <section>Section one</section>
+  <section>Section two</section>
+  <section>Section three</section>
+  <section>Section four</section>
+  <section>Section five</section>
+  <section>Section six</section>
+</body>
+</html>"#;
+
+    #[test]
+    #[ignore = "Requires local Chromium/Chrome installation"]
+    fn print_fixture_with_print_stylesheet_range_and_header() {
+        let fixture = std::env::temp_dir().join(format!("cube-print-{}.html", uuid::Uuid::new_v4()));
+        std::fs::write(&fixture, PRINT_FIXTURE).unwrap();
+
+        let mut engine = CubeBrowserEngine::new();
+        engine.initialize(Some(BrowserConfig { headless: true, sandbox: false, ..Default::default() }))
+            .expect("Browser should launch");
+        let tab = engine.create_tab(&format!("file://{}", fixture.display()))
+            .expect("Fixture should load");
+
+        let options = PrintOptions {
+            page_ranges: Some("2-4".to_string()),
+            header_template: Some("{{title}} page {{page}}".to_string()),
+            ..Default::default()
+        };
+        let output = fixture.with_extension("pdf");
+        let written = engine.print_to_pdf_file(&tab.id, &options, &output).unwrap();
+        assert!(written > 0);
+
+        let pdf = std::fs::read(&output).unwrap();
+        let pages = pdf_extract::extract_text_from_mem_by_pages(&pdf).unwrap();
+        assert_eq!(pages.len(), 3);
+        assert!(pages[0].contains("Quarterly Invoice page 2"), "header not resolved: {}", pages[0]);
+        assert!(pages[0].contains("Section two"));
+        assert!(pages.iter().all(|page| !page.contains("Navigation bar")));
+
+        let _ = std::fs::remove_file(&fixture);
+        let _ = std::fs::remove_file(&output);
+        engine.shutdown().unwrap();
+    }
+
+    const LAZY_FIXTURE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<style>
+  body { margin: 0; }
+  .card { height: 400px; }
+  .card img { width: 10px; height: 10px; }
+</style>
+</head>
+<body>
+  <div id="feed"></div>
+  <div id="sentinel"></div>
+  <script>
+    const feed = document.getElementById('feed');
+    const images = new IntersectionObserver(entries => {
+      entries.filter(e => e.isIntersecting).forEach(e => {
+        e.target.src = e.target.dataset.src;
+        images.unobserve(e.target);
+      });
+    });
+    let next = 0;
+    function appendBatch() {
+      for (let end = next + 10; next < end; next++) {
+        const card = document.createElement('article');
+        card.className = 'card';
+        card.dataset.key = 'card-' + next;
+        const img = document.createElement('img');
+        img.dataset.src = "data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' width='10' height='10' data-n='" + next + "'/%3E";
+        card.append(img, 'Card ' + next);
+        feed.appendChild(card);
+        images.observe(img);
+      }
+    }
+    appendBatch();
+    new IntersectionObserver(entries => {
+      if (entries[0].isIntersecting && next < 30) appendBatch();
+    }).observe(document.getElementById('sentinel'));
+  </script>
+</body>
+</html>"#;
+
+    #[test]
+    #[ignore = "Requires local Chromium/Chrome installation"]
+    fn scroll_to_load_all_loads_lazy_images_and_infinite_batches() {
+        let fixture = std::env::temp_dir().join(format!("cube-lazy-{}.html", uuid::Uuid::new_v4()));
+        std::fs::write(&fixture, LAZY_FIXTURE).unwrap();
+
+        let mut engine = CubeBrowserEngine::new();
+        engine.initialize(Some(BrowserConfig { headless: true, sandbox: false, window_size: (800, 600), ..Default::default() }))
+            .expect("Browser should launch");
+        let tab = engine.create_tab(&format!("file://{}", fixture.display()))
+            .expect("Fixture should load");
+
+        let loaded_before = engine.execute_script(&tab.id, "Array.from(document.images).filter(i => i.naturalWidth > 0).length")
+            .unwrap();
+        assert!(loaded_before.as_u64().unwrap() < 5);
+
+        let result = engine.scroll_to_load_all(&tab.id, 100).unwrap();
+        assert!(result.complete, "stopped after {} scrolls", result.scrolls);
+        assert_eq!(result.items.len(), 30);
+        assert_eq!(result.loaded_images.len(), 30);
+        assert_eq!(result.pending_images, 0);
+        assert!(!result.virtualized);
+        assert!(result.html.contains("data-key=\"card-29\""));
+
+        let _ = std::fs::remove_file(&fixture);
+        engine.shutdown().unwrap();
+    }
+
+    fn window_probe(range: std::ops::Range<usize>, scroll_top: f64) -> ScrollProbe {
+        ScrollProbe {
+            scroll_top,
+            viewport_height: 500.0,
+            scroll_height: 2000.0,
+            items: range
+                .map(|i| ScrollItem { key: format!("row-{}", i), text: format!("Row {}", i) })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lazy_load_collector_keeps_recycled_rows_and_settles_at_bottom() {
+        let mut collector = LazyLoadCollector::default();
+        assert!(collector.record(&window_probe(0..5, 0.0)));
+        assert!(collector.record(&window_probe(3..8, 500.0)));
+        assert!(collector.record(&window_probe(6..10, 1500.0)));
+
+        // Nothing new at the bottom: settle after the idle rounds
+        assert!(!collector.record(&window_probe(6..10, 1500.0)));
+        assert!(!collector.is_settled());
+        assert!(!collector.record(&window_probe(6..10, 1500.0)));
+        assert!(collector.is_settled());
+
+        let result = collector.finish(5, String::new());
+        assert!(result.complete);
+        assert!(result.virtualized);
+        let keys: Vec<_> = result.items.iter().map(|item| item.key.as_str()).collect();
+        assert_eq!(keys, (0..10).map(|i| format!("row-{}", i)).collect::<Vec<_>>());
+    }
+
+    const GEOLOCATION_FIXTURE: &str = r#"<!DOCTYPE html>
+<html><body>
+<script>
+  navigator.geolocation.getCurrentPosition(
+    p => { window.__position = JSON.stringify([p.coords.latitude, p.coords.longitude, p.coords.accuracy]); },
+    e => { window.__position = 'error:' + e.code; }
+  );
+</script>
+</body></html>"#;
+
+    #[test]
+    #[ignore = "Requires local Chromium/Chrome installation"]
+    fn geolocation_override_reaches_get_current_position() {
+        let fixture = std::env::temp_dir().join(format!("cube-geo-{}.html", uuid::Uuid::new_v4()));
+        std::fs::write(&fixture, GEOLOCATION_FIXTURE).unwrap();
+
+        let mut engine = CubeBrowserEngine::new();
+        engine.initialize(Some(BrowserConfig { headless: true, sandbox: false, ..Default::default() }))
+            .expect("Browser should launch");
+        let tab = engine.create_tab("about:blank").expect("Tab should open");
+
+        let paris = GeolocationCoords { latitude: 48.8566, longitude: 2.3522, accuracy: 25.0 };
+        engine.set_geolocation_override(Some(tab.id.as_str()), Some(paris)).unwrap();
+        engine.set_locale_override(Some(tab.id.as_str()), Some(vec!["fr-FR".to_string(), "fr".to_string()]), Some("Europe/Paris".to_string()))
+            .unwrap();
+        engine.navigate(&tab.id, &format!("file://{}", fixture.display())).unwrap();
+
+        let mut position = serde_json::Value::Null;
+        for _ in 0..50 {
+            position = engine.execute_script(&tab.id, "window.__position || null").unwrap();
+            if !position.is_null() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(position.as_str(), Some("[48.8566,2.3522,25]"));
+        assert_eq!(engine.execute_script(&tab.id, "navigator.languages.join(',')").unwrap(), "fr-FR,fr");
+        assert_eq!(
+            engine.execute_script(&tab.id, "Intl.DateTimeFormat().resolvedOptions().timeZone").unwrap(),
+            "Europe/Paris"
+        );
+
+        let _ = std::fs::remove_file(&fixture);
+        engine.shutdown().unwrap();
+    }
+
+    #[test]
+    #[ignore = "Requires local Chromium/Chrome installation"]
+    fn detected_timezone_and_accept_language_match_the_override() {
+        let mut engine = CubeBrowserEngine::new();
+        engine.initialize(Some(BrowserConfig { headless: true, sandbox: false, ..Default::default() }))
+            .expect("Browser should launch");
+        let tab = engine.create_tab("about:blank").expect("Tab should open");
+
+        let expected = LocationOverride {
+            geolocation: None,
+            languages: Some(vec!["pt-BR".to_string(), "pt".to_string()]),
+            timezone: Some("America/Sao_Paulo".to_string()),
+        };
+        engine.set_location_override(Some(tab.id.as_str()), expected.clone()).unwrap();
+
+        let detected = engine.detect_locale(&tab.id).unwrap();
+        assert_eq!(detected.timezone, "America/Sao_Paulo");
+        assert_eq!(detected.languages, ["pt-BR", "pt"]);
+        assert!(detected.accept_language.as_deref().unwrap_or_default().starts_with("pt-BR,pt"));
+        assert_eq!(detected.mismatches(&expected), Vec::<String>::new());
+
+        engine.shutdown().unwrap();
+    }
+
+    #[test]
+    fn locale_detection_reports_each_mismatch() {
+        let expected = LocationOverride {
+            geolocation: None,
+            languages: Some(vec!["de-DE".to_string(), "de".to_string()]),
+            timezone: Some("Europe/Berlin".to_string()),
+        };
+        let mut detected = LocaleDetection {
+            timezone: "Europe/Berlin".to_string(),
+            intl_locale: "de-DE".to_string(),
+            language: "de-DE".to_string(),
+            languages: vec!["de-DE".to_string(), "de".to_string()],
+            accept_language: Some("de-DE,de;q=0.9".to_string()),
+            date_offset_matches_timezone: true,
+        };
+        assert!(detected.mismatches(&expected).is_empty());
+
+        detected.timezone = "UTC".to_string();
+        detected.accept_language = Some("en-US,en;q=0.9".to_string());
+        let mismatches = detected.mismatches(&expected);
+        assert_eq!(mismatches.len(), 2);
+        assert!(mismatches[0].contains("Europe/Berlin"));
+        assert!(mismatches[1].starts_with("Accept-Language"));
+    }
+
+    #[test]
+    fn location_override_merges_tab_over_profile_and_validates_coords() {
+        let profile = LocationOverride {
+            geolocation: Some(GeolocationCoords { latitude: 40.7128, longitude: -74.006, accuracy: 50.0 }),
+            languages: Some(vec!["en-US".to_string()]),
+            timezone: Some("America/New_York".to_string()),
+        };
+        let tab = LocationOverride { timezone: Some("Asia/Tokyo".to_string()), ..Default::default() };
+
+        let merged = profile.merged(Some(&tab));
+        assert_eq!(merged.timezone.as_deref(), Some("Asia/Tokyo"));
+        assert_eq!(merged.languages, profile.languages);
+        assert_eq!(merged.geolocation, profile.geolocation);
+        assert_eq!(profile.merged(None), profile);
+
+        assert!(GeolocationCoords { latitude: 91.0, longitude: 0.0, accuracy: 1.0 }.validate().is_err());
+        assert!(GeolocationCoords { latitude: 0.0, longitude: -181.0, accuracy: 1.0 }.validate().is_err());
+        assert!(GeolocationCoords { latitude: 0.0, longitude: 0.0, accuracy: -1.0 }.validate().is_err());
+
+        let engine = CubeBrowserEngine::new();
+        let coords = GeolocationCoords { latitude: 0.0, longitude: 0.0, accuracy: 1.0 };
+        assert!(engine.set_geolocation_override(Some("missing"), Some(coords)).is_err());
+    }
+}
@@ -0,0 +1,697 @@
+// CUBE Browser Engine - Form Hooks
+// Wires the engine's form detection into the password manager and autofill:
+// fill offers when a page with a login/address/payment form loads, and save
+// prompts when new credentials are submitted
+
+use crate::autofill::{AutofillProfile, FieldDetector, FieldMetadata, FieldType};
+use crate::models::passwords::PasswordEntry;
+use crate::services::cube_browser_engine::DOMElement;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+// ============================================
+// Types
+// ============================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FormKind {
+    Login,
+    Address,
+    Payment,
+}
+
+/// A form found on the page, with the selectors needed to fill it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedForm {
+    pub kind: FormKind,
+    pub username_selector: Option<String>,
+    pub password_selector: Option<String>,
+    pub fields: Vec<FieldMetadata>,
+}
+
+/// Per-site form behaviour, keyed by host (without "www.")
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SiteFormSettings {
+    /// Offer stored credentials on login forms
+    pub offer_fill: bool,
+    /// Offer to save credentials submitted on this site
+    pub offer_save: bool,
+    /// Offer autofill profiles on address and payment forms
+    pub autofill: bool,
+}
+
+impl Default for SiteFormSettings {
+    fn default() -> Self {
+        Self {
+            offer_fill: true,
+            offer_save: true,
+            autofill: true,
+        }
+    }
+}
+
+/// A stored account the user can pick from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialOption {
+    pub entry_id: String,
+    pub name: String,
+    pub username: String,
+    pub last_used: Option<i64>,
+}
+
+/// An autofill profile holding values for the detected form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileOption {
+    pub profile_id: String,
+    pub name: String,
+    pub matched_fields: usize,
+}
+
+/// Offer to fill a detected form. Login offers list every stored account for
+/// the site; `choose_account` is set when there is more than one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillOffer {
+    pub tab_id: String,
+    pub site: String,
+    pub kind: FormKind,
+    pub accounts: Vec<CredentialOption>,
+    pub choose_account: bool,
+    pub profiles: Vec<ProfileOption>,
+    /// The password vault is locked; unlock before filling or saving
+    pub requires_unlock: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SavePromptKind {
+    New,
+    UpdatePassword { entry_id: String },
+}
+
+/// Offer to save credentials submitted through a login form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavePrompt {
+    pub id: String,
+    pub tab_id: String,
+    pub site: String,
+    pub url: String,
+    pub username: String,
+    pub kind: SavePromptKind,
+    pub requires_unlock: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SavePromptResponse {
+    Save,
+    Dismiss,
+    /// Dismiss and stop offering to save on this site
+    Never,
+}
+
+/// Decrypts a stored entry's password; absent while the vault is locked
+pub type StoredPassword<'a> = Option<&'a dyn Fn(&PasswordEntry) -> Option<String>>;
+
+struct PendingSave {
+    prompt: SavePrompt,
+    password: String,
+}
+
+// ============================================
+// Form Hooks
+// ============================================
+
+pub struct FormHooks {
+    detector: FieldDetector,
+    settings: HashMap<String, SiteFormSettings>,
+    settings_path: Option<PathBuf>,
+    forms: HashMap<String, Vec<DetectedForm>>,
+    pending_saves: HashMap<String, PendingSave>,
+}
+
+impl Default for FormHooks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FormHooks {
+    pub fn new() -> Self {
+        Self {
+            detector: FieldDetector::new(),
+            settings: HashMap::new(),
+            settings_path: None,
+            forms: HashMap::new(),
+            pending_saves: HashMap::new(),
+        }
+    }
+
+    /// Create hooks whose per-site settings are persisted to `path`
+    pub fn with_storage(path: PathBuf) -> Self {
+        let settings = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self {
+            settings,
+            settings_path: Some(path),
+            ..Self::new()
+        }
+    }
+
+    pub fn site_settings(&self, site: &str) -> SiteFormSettings {
+        self.settings.get(site).copied().unwrap_or_default()
+    }
+
+    pub fn set_site_settings(&mut self, site: &str, settings: SiteFormSettings) -> Result<(), String> {
+        if settings == SiteFormSettings::default() {
+            self.settings.remove(site);
+        } else {
+            self.settings.insert(site.to_string(), settings);
+        }
+        self.save_settings()
+    }
+
+    fn save_settings(&self) -> Result<(), String> {
+        let Some(path) = &self.settings_path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(&self.settings)
+            .map_err(|e| format!("Failed to serialize form settings: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to save form settings: {}", e))
+    }
+
+    /// Forms detected on the tab's current page
+    pub fn form(&self, tab_id: &str, kind: FormKind) -> Option<&DetectedForm> {
+        self.forms.get(tab_id)?.iter().find(|f| f.kind == kind)
+    }
+
+    pub fn forget_tab(&mut self, tab_id: &str) {
+        self.forms.remove(tab_id);
+    }
+
+    /// Navigation hook: detect the page's forms and build fill offers from
+    /// the stored credentials and autofill profiles, honouring the site's
+    /// settings
+    pub fn on_navigation(
+        &mut self,
+        tab_id: &str,
+        url: &str,
+        elements: &[DOMElement],
+        credentials: &[PasswordEntry],
+        profiles: &[AutofillProfile],
+        unlocked: bool,
+    ) -> Vec<FillOffer> {
+        let forms = self.classify_forms(elements);
+        let Some(site) = site_key(url) else {
+            self.forms.remove(tab_id);
+            return Vec::new();
+        };
+        let settings = self.site_settings(&site);
+
+        let mut offers = Vec::new();
+        for form in &forms {
+            let mut offer = FillOffer {
+                tab_id: tab_id.to_string(),
+                site: site.clone(),
+                kind: form.kind,
+                accounts: Vec::new(),
+                choose_account: false,
+                profiles: Vec::new(),
+                requires_unlock: false,
+            };
+            match form.kind {
+                FormKind::Login => {
+                    if !settings.offer_fill {
+                        continue;
+                    }
+                    offer.accounts = matching_accounts(&site, credentials);
+                    offer.choose_account = offer.accounts.len() > 1;
+                    offer.requires_unlock = !unlocked;
+                    if offer.accounts.is_empty() {
+                        continue;
+                    }
+                }
+                FormKind::Address | FormKind::Payment => {
+                    if !settings.autofill {
+                        continue;
+                    }
+                    offer.profiles = self.matching_profiles(form, profiles);
+                    if offer.profiles.is_empty() {
+                        continue;
+                    }
+                }
+            }
+            offers.push(offer);
+        }
+
+        self.forms.insert(tab_id.to_string(), forms);
+        offers
+    }
+
+    /// Submit hook: prompt to save credentials that are new for the site, or
+    /// to update a stored account whose password changed. `stored_password`
+    /// is `None` while the vault is locked.
+    pub fn on_submit(
+        &mut self,
+        tab_id: &str,
+        url: &str,
+        username: &str,
+        password: &str,
+        credentials: &[PasswordEntry],
+        stored_password: StoredPassword,
+    ) -> Option<SavePrompt> {
+        if password.is_empty() {
+            return None;
+        }
+        let site = site_key(url)?;
+        if !self.site_settings(&site).offer_save {
+            return None;
+        }
+
+        let existing = credentials
+            .iter()
+            .filter(|entry| entry_matches_site(entry, &site))
+            .find(|entry| entry.username.eq_ignore_ascii_case(username));
+        let kind = match existing {
+            None => SavePromptKind::New,
+            // Without the master password there is nothing to compare against
+            Some(entry) => match stored_password.and_then(|decrypt| decrypt(entry)) {
+                Some(stored) if stored != password => SavePromptKind::UpdatePassword {
+                    entry_id: entry.id.clone(),
+                },
+                _ => return None,
+            },
+        };
+
+        let prompt = SavePrompt {
+            id: uuid::Uuid::new_v4().to_string(),
+            tab_id: tab_id.to_string(),
+            site,
+            url: url.to_string(),
+            username: username.to_string(),
+            kind,
+            requires_unlock: stored_password.is_none(),
+        };
+        self.pending_saves.insert(
+            prompt.id.clone(),
+            PendingSave {
+                prompt: prompt.clone(),
+                password: password.to_string(),
+            },
+        );
+        Some(prompt)
+    }
+
+    /// Resolve a save prompt, returning the prompt and submitted password
+    /// when the user chose to save. "Never" turns off saving for the site.
+    pub fn respond_to_save(
+        &mut self,
+        prompt_id: &str,
+        response: SavePromptResponse,
+    ) -> Result<Option<(SavePrompt, String)>, String> {
+        let pending = self
+            .pending_saves
+            .remove(prompt_id)
+            .ok_or_else(|| format!("Save prompt not found: {}", prompt_id))?;
+        match response {
+            SavePromptResponse::Save => Ok(Some((pending.prompt, pending.password))),
+            SavePromptResponse::Dismiss => Ok(None),
+            SavePromptResponse::Never => {
+                let mut settings = self.site_settings(&pending.prompt.site);
+                settings.offer_save = false;
+                self.set_site_settings(&pending.prompt.site, settings)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Group the page's fields into login, address and payment forms
+    pub fn classify_forms(&self, elements: &[DOMElement]) -> Vec<DetectedForm> {
+        let fields: Vec<FieldMetadata> = elements.iter().filter_map(field_metadata).collect();
+        let mut forms = Vec::new();
+
+        if let Some(password_index) = fields.iter().position(is_password_field) {
+            let username_selector = fields
+                .iter()
+                .find(|f| {
+                    matches!(f.autocomplete.as_deref(), Some("username") | Some("email"))
+                })
+                .or_else(|| fields[..password_index].iter().rev().find(|f| is_username_field(f)))
+                .map(|f| f.selector.clone());
+            forms.push(DetectedForm {
+                kind: FormKind::Login,
+                username_selector,
+                password_selector: Some(fields[password_index].selector.clone()),
+                fields: fields
+                    .iter()
+                    .filter(|f| is_password_field(f) || is_username_field(f))
+                    .cloned()
+                    .collect(),
+            });
+        }
+
+        let payment: Vec<FieldMetadata> =
+            fields.iter().filter(|f| payment_key(f).is_some()).cloned().collect();
+        if !payment.is_empty() {
+            forms.push(DetectedForm {
+                kind: FormKind::Payment,
+                username_selector: None,
+                password_selector: None,
+                fields: payment,
+            });
+        }
+
+        let address: Vec<FieldMetadata> = fields
+            .iter()
+            .filter(|f| payment_key(f).is_none() && is_address_type(&self.detector.detect_field_type(f).0))
+            .cloned()
+            .collect();
+        // A lone "city" or "country" field is more likely a search filter
+        if address.len() >= 2 {
+            forms.push(DetectedForm {
+                kind: FormKind::Address,
+                username_selector: None,
+                password_selector: None,
+                fields: address,
+            });
+        }
+
+        forms
+    }
+
+    /// Selector -> value pairs filling `form` from an autofill profile
+    pub fn profile_values(&self, form: &DetectedForm, profile: &AutofillProfile) -> HashMap<String, String> {
+        form.fields
+            .iter()
+            .filter_map(|field| {
+                let key = self.profile_key(form.kind, field)?;
+                let value = profile.fields.get(&key)?;
+                Some((field.selector.clone(), value.clone()))
+            })
+            .collect()
+    }
+
+    fn profile_key(&self, kind: FormKind, field: &FieldMetadata) -> Option<String> {
+        match kind {
+            FormKind::Payment => payment_key(field),
+            FormKind::Address => {
+                let mapping = self.detector.detect_fields(vec![field.clone()]);
+                mapping.detected_fields.first().map(|m| m.profile_key.clone())
+            }
+            FormKind::Login => None,
+        }
+    }
+
+    fn matching_profiles(&self, form: &DetectedForm, profiles: &[AutofillProfile]) -> Vec<ProfileOption> {
+        let mut options: Vec<(ProfileOption, u64)> = profiles
+            .iter()
+            .filter_map(|profile| {
+                let matched_fields = self.profile_values(form, profile).len();
+                (matched_fields > 0).then(|| {
+                    (
+                        ProfileOption {
+                            profile_id: profile.id.clone(),
+                            name: profile.name.clone(),
+                            matched_fields,
+                        },
+                        profile.last_used.unwrap_or(0),
+                    )
+                })
+            })
+            .collect();
+        options.sort_by(|a, b| {
+            b.0.matched_fields
+                .cmp(&a.0.matched_fields)
+                .then(b.1.cmp(&a.1))
+        });
+        options.into_iter().map(|(option, _)| option).collect()
+    }
+}
+
+// ============================================
+// Helpers
+// ============================================
+
+/// The host a credential or page belongs to, ignoring "www."
+pub fn site_key(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url)
+        .or_else(|_| url::Url::parse(&format!("https://{}", url)))
+        .ok()?;
+    let host = parsed.host_str()?.to_lowercase();
+    Some(host.strip_prefix("www.").unwrap_or(&host).to_string())
+}
+
+fn entry_matches_site(entry: &PasswordEntry, site: &str) -> bool {
+    entry
+        .url
+        .as_deref()
+        .and_then(site_key)
+        .is_some_and(|entry_site| entry_site == site)
+}
+
+/// Stored accounts for the site, favourites then most recently used first
+fn matching_accounts(site: &str, credentials: &[PasswordEntry]) -> Vec<CredentialOption> {
+    let mut matches: Vec<&PasswordEntry> = credentials
+        .iter()
+        .filter(|entry| entry_matches_site(entry, site))
+        .collect();
+    matches.sort_by(|a, b| {
+        b.favorite
+            .cmp(&a.favorite)
+            .then(b.last_used.cmp(&a.last_used))
+    });
+    matches
+        .into_iter()
+        .map(|entry| CredentialOption {
+            entry_id: entry.id.clone(),
+            name: entry.name.clone(),
+            username: entry.username.clone(),
+            last_used: entry.last_used,
+        })
+        .collect()
+}
+
+/// Build field metadata from a DOM element, skipping fields the user
+/// can't type into
+fn field_metadata(element: &DOMElement) -> Option<FieldMetadata> {
+    let attr = |name: &str| element.attributes.get(name).filter(|v| !v.is_empty()).cloned();
+    let element_type = match element.tag_name.as_str() {
+        "input" => attr("type").unwrap_or_else(|| "text".to_string()).to_lowercase(),
+        "select" | "textarea" => element.tag_name.clone(),
+        _ => return None,
+    };
+    if matches!(
+        element_type.as_str(),
+        "hidden" | "submit" | "button" | "reset" | "image" | "checkbox" | "radio" | "file"
+    ) {
+        return None;
+    }
+
+    let selector = if let Some(id) = attr("id") {
+        format!("#{}", id)
+    } else if let Some(name) = attr("name") {
+        format!("{}[name=\"{}\"]", element.tag_name, name)
+    } else {
+        return None;
+    };
+
+    Some(FieldMetadata {
+        selector,
+        element_type,
+        name: attr("name"),
+        id: attr("id"),
+        placeholder: attr("placeholder"),
+        label: None,
+        aria_label: attr("aria-label"),
+        autocomplete: attr("autocomplete").map(|a| a.to_lowercase()),
+        required: element.attributes.contains_key("required"),
+        pattern: attr("pattern"),
+        min_length: attr("minlength").and_then(|v| v.parse().ok()),
+        max_length: attr("maxlength").and_then(|v| v.parse().ok()),
+    })
+}
+
+fn is_password_field(field: &FieldMetadata) -> bool {
+    field.element_type == "password"
+}
+
+fn is_username_field(field: &FieldMetadata) -> bool {
+    if matches!(field.autocomplete.as_deref(), Some("username") | Some("email")) {
+        return true;
+    }
+    if !matches!(field.element_type.as_str(), "text" | "email" | "tel") {
+        return false;
+    }
+    let hint = format!(
+        "{} {}",
+        field.name.as_deref().unwrap_or(""),
+        field.id.as_deref().unwrap_or("")
+    )
+    .to_lowercase();
+    field.element_type == "email"
+        || ["user", "login", "email", "account"].iter().any(|k| hint.contains(k))
+}
+
+/// Autofill profile key for a payment card field, e.g. "cc_number"
+fn payment_key(field: &FieldMetadata) -> Option<String> {
+    if let Some(autocomplete) = field
+        .autocomplete
+        .as_deref()
+        .and_then(|a| a.split_whitespace().last())
+        .filter(|a| a.starts_with("cc-"))
+    {
+        return Some(autocomplete.replace('-', "_"));
+    }
+    let hint = format!(
+        "{} {}",
+        field.name.as_deref().unwrap_or(""),
+        field.id.as_deref().unwrap_or("")
+    )
+    .to_lowercase()
+    .replace(['-', '_'], "");
+    [
+        ("cardnumber", "cc_number"),
+        ("ccnumber", "cc_number"),
+        ("cardholder", "cc_name"),
+        ("nameoncard", "cc_name"),
+        ("cvc", "cc_csc"),
+        ("cvv", "cc_csc"),
+        ("expiry", "cc_exp"),
+        ("expdate", "cc_exp"),
+    ]
+    .iter()
+    .find(|(needle, _)| hint.contains(needle))
+    .map(|(_, key)| key.to_string())
+}
+
+fn is_address_type(field_type: &FieldType) -> bool {
+    matches!(
+        field_type,
+        FieldType::Address
+            | FieldType::AddressLine1
+            | FieldType::AddressLine2
+            | FieldType::City
+            | FieldType::State
+            | FieldType::ZipCode
+            | FieldType::PostalCode
+            | FieldType::Country
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(attrs: &[(&str, &str)]) -> DOMElement {
+        DOMElement {
+            node_id: 0,
+            tag_name: "input".to_string(),
+            attributes: attrs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            text_content: None,
+            children_count: 0,
+            bounding_box: None,
+        }
+    }
+
+    fn entry(id: &str, username: &str, url: &str) -> PasswordEntry {
+        PasswordEntry {
+            id: id.to_string(),
+            name: "Example".to_string(),
+            username: username.to_string(),
+            encrypted_password: format!("enc-{}", id),
+            url: Some(url.to_string()),
+            notes: None,
+            category: "login".to_string(),
+            tags: Vec::new(),
+            date_created: 0,
+            date_modified: 0,
+            last_used: None,
+            favorite: false,
+            strength_score: 3,
+        }
+    }
+
+    #[test]
+    fn test_login_form_offers_fill_and_prompts_save_for_new_credentials() {
+        let mut hooks = FormHooks::new();
+        let page = vec![
+            input(&[("type", "email"), ("id", "login-email"), ("name", "email")]),
+            input(&[("type", "password"), ("id", "login-password")]),
+            input(&[("type", "submit"), ("value", "Sign in")]),
+        ];
+        let credentials = vec![
+            entry("alice", "alice@example.com", "https://example.com/login"),
+            entry("other", "alice@example.com", "https://elsewhere.org"),
+        ];
+
+        let offers = hooks.on_navigation(
+            "tab-1",
+            "https://www.example.com/signin",
+            &page,
+            &credentials,
+            &[],
+            true,
+        );
+        assert_eq!(offers.len(), 1);
+        let offer = &offers[0];
+        assert_eq!(offer.kind, FormKind::Login);
+        assert_eq!(offer.site, "example.com");
+        assert_eq!(offer.accounts.len(), 1);
+        assert_eq!(offer.accounts[0].entry_id, "alice");
+        assert!(!offer.choose_account);
+        assert!(!offer.requires_unlock);
+
+        let login = hooks.form("tab-1", FormKind::Login).unwrap();
+        assert_eq!(login.username_selector.as_deref(), Some("#login-email"));
+        assert_eq!(login.password_selector.as_deref(), Some("#login-password"));
+
+        // A second account on the same site turns the offer into a chooser
+        let mut two_accounts = credentials.clone();
+        two_accounts.push(entry("bob", "bob@example.com", "example.com"));
+        let offers = hooks.on_navigation("tab-1", "https://example.com", &page, &two_accounts, &[], false);
+        assert!(offers[0].choose_account);
+        assert!(offers[0].requires_unlock);
+
+        // Resubmitting the stored password is not worth a prompt
+        let decrypt = |e: &PasswordEntry| (e.id == "alice").then(|| "hunter2".to_string());
+        let stored: StoredPassword = Some(&decrypt);
+        assert!(hooks
+            .on_submit("tab-1", "https://example.com", "alice@example.com", "hunter2", &credentials, stored)
+            .is_none());
+
+        let prompt = hooks
+            .on_submit("tab-1", "https://example.com", "carol@example.com", "s3cret!", &credentials, stored)
+            .expect("new credentials should prompt to save");
+        assert_eq!(prompt.kind, SavePromptKind::New);
+        assert_eq!(prompt.username, "carol@example.com");
+
+        let changed = hooks
+            .on_submit("tab-1", "https://example.com", "alice@example.com", "new-pass", &credentials, stored)
+            .unwrap();
+        assert_eq!(
+            changed.kind,
+            SavePromptKind::UpdatePassword {
+                entry_id: "alice".to_string()
+            }
+        );
+
+        let (saved, password) = hooks
+            .respond_to_save(&prompt.id, SavePromptResponse::Save)
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.username, "carol@example.com");
+        assert_eq!(password, "s3cret!");
+
+        hooks.respond_to_save(&changed.id, SavePromptResponse::Never).unwrap();
+        assert!(!hooks.site_settings("example.com").offer_save);
+        assert!(hooks
+            .on_submit("tab-1", "https://example.com", "dave@example.com", "pw", &credentials, stored)
+            .is_none());
+    }
+}
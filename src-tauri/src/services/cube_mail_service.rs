@@ -22,6 +22,11 @@ use tokio::sync::RwLock;
 use log::{info, error, warn, debug};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
+use std::path::{Path, PathBuf};
+
+use super::imap_client::CubeImapClient;
+use super::mail_attachments::{AttachmentScanConfig, MailAttachmentStore};
+use super::oauth2_service::{OAuth2ServiceState, OAuth2Tokens};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // TYPES & STRUCTURES
@@ -165,6 +170,9 @@ pub struct MailAccount {
     pub storage_used: u64,
     pub storage_limit: u64,
     pub color: Option<String>,
+    /// Set when the OAuth2 refresh token was revoked and the user must sign in again
+    #[serde(default)]
+    pub needs_reauth: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -187,10 +195,23 @@ impl MailAccount {
             storage_used: 0,
             storage_limit: 15 * 1024 * 1024 * 1024, // 15 GB default
             color: None,
+            needs_reauth: false,
             created_at: now,
             updated_at: now,
         }
     }
+
+    /// Whether the account authenticates with OAuth2 instead of a password
+    pub fn uses_oauth2(&self) -> bool {
+        self.imap.oauth2_token.is_some() || self.imap.oauth2_refresh_token.is_some()
+    }
+
+    /// Whether the access token is missing, expired or about to expire
+    pub fn oauth2_token_expiring(&self) -> bool {
+        self.imap.oauth2_token.is_none() || self.imap.oauth2_expires_at.is_some_and(|expires_at| {
+            Utc::now() + chrono::Duration::seconds(TOKEN_REFRESH_SKEW_SECONDS) >= expires_at
+        })
+    }
 }
 
 /// Email address with optional name
@@ -214,6 +235,26 @@ pub struct EmailAttachment {
     pub encrypted: bool,
     pub download_url: Option<String>,
     pub local_path: Option<String>,
+    #[serde(default)]
+    pub scan_status: AttachmentScanStatus,
+    /// Signature name when infected, or the reason a scan failed
+    #[serde(default)]
+    pub scan_detail: Option<String>,
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+/// Malware scan outcome for an attachment
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentScanStatus {
+    #[default]
+    NotScanned,
+    Clean,
+    Infected,
+    /// Flagged as infected but explicitly released by the user
+    Released,
+    Error,
 }
 
 /// Full email message
@@ -289,10 +330,51 @@ pub struct ComposeDraft {
     pub encryption_enabled: bool,
     pub read_receipt: bool,
     pub scheduled_send: Option<DateTime<Utc>>,
+    /// Alias address to send as; defaults to the account's primary address
+    #[serde(default)]
+    pub from_identity: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Signature in both HTML and plain-text form
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MailSignature {
+    pub html: String,
+    pub plain: String,
+}
+
+/// Signatures configured for one account and its aliases
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSignatures {
+    pub default: Option<MailSignature>,
+    /// Alias address (lowercase) -> signature used when sending as that identity
+    #[serde(default)]
+    pub identities: HashMap<String, MailSignature>,
+    pub use_for_new: bool,
+    pub use_for_replies: bool,
+}
+
+impl Default for AccountSignatures {
+    fn default() -> Self {
+        Self {
+            default: None,
+            identities: HashMap::new(),
+            use_for_new: true,
+            use_for_replies: true,
+        }
+    }
+}
+
+impl AccountSignatures {
+    /// Signature for `identity`, falling back to the account default
+    pub fn signature_for(&self, identity: Option<&str>) -> Option<&MailSignature> {
+        identity
+            .and_then(|alias| self.identities.get(&alias.trim().to_lowercase()))
+            .or(self.default.as_ref())
+    }
+}
+
 /// Screener sender entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScreenerSender {
@@ -430,6 +512,270 @@ pub struct SyncStatus {
     pub errors: Vec<String>,
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
+    /// The account's refresh token was revoked; syncing stops until it is re-authorized
+    #[serde(default)]
+    pub needs_reauth: bool,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// OAUTH2 TOKEN REFRESH
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Refresh access tokens this many seconds before they expire
+const TOKEN_REFRESH_SKEW_SECONDS: i64 = 300;
+
+/// Why a token refresh failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenRefreshError {
+    /// The refresh token was revoked or expired; the user must sign in again
+    Revoked(String),
+    /// Network or provider error; the refresh can be retried later
+    Failed(String),
+}
+
+/// Exchanges an account's refresh token for a new access token
+#[async_trait::async_trait]
+pub trait MailTokenRefresher: Send + Sync {
+    async fn refresh(
+        &self,
+        provider: &MailProvider,
+        refresh_token: &str,
+    ) -> Result<OAuth2Tokens, TokenRefreshError>;
+}
+
+#[async_trait::async_trait]
+impl MailTokenRefresher for OAuth2ServiceState {
+    async fn refresh(
+        &self,
+        provider: &MailProvider,
+        refresh_token: &str,
+    ) -> Result<OAuth2Tokens, TokenRefreshError> {
+        let keys: &[&str] = match provider {
+            MailProvider::Gmail => &["gmail", "google"],
+            MailProvider::Outlook => &["outlook", "microsoft", "hotmail"],
+            MailProvider::Yahoo => &["yahoo"],
+            other => {
+                return Err(TokenRefreshError::Failed(format!(
+                    "OAuth2 is not supported for {:?}",
+                    other
+                )))
+            }
+        };
+
+        let mut key = None;
+        for candidate in keys {
+            if self.get_config(candidate).await.is_some() {
+                key = Some(*candidate);
+                break;
+            }
+        }
+        let key = key.ok_or_else(|| {
+            TokenRefreshError::Failed(format!("No OAuth2 config registered for {:?}", provider))
+        })?;
+
+        self.refresh_tokens(key, refresh_token).await.map_err(|e| {
+            if e.contains("invalid_grant") {
+                TokenRefreshError::Revoked(e)
+            } else {
+                TokenRefreshError::Failed(e)
+            }
+        })
+    }
+}
+
+/// Whether an IMAP/SMTP error means the server rejected our credentials
+fn is_auth_failure(error: &str) -> bool {
+    let error = error.to_lowercase();
+    error.contains("authenticationfailed")
+        || error.contains("authentication failed")
+        || error.contains("invalid credentials")
+        || error.starts_with("535")
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SIGNATURES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Class of the wrapper around an appended HTML signature
+const SIGNATURE_CLASS: &str = "cube-signature";
+
+/// Whether a draft replies to or forwards another message
+fn is_reply_or_forward(draft: &ComposeDraft) -> bool {
+    let subject = draft.subject.trim_start().to_lowercase();
+    draft.in_reply_to.is_some()
+        || subject.starts_with("re:")
+        || subject.starts_with("fw:")
+        || subject.starts_with("fwd:")
+}
+
+/// Byte offset where the quoted original message begins, if any
+fn quote_start(body: &str, html: bool) -> Option<usize> {
+    if html {
+        let lower = body.to_ascii_lowercase();
+        return ["<blockquote", "<div class=\"gmail_quote", "<div class=\"cube-quote", "<div id=\"divrplyfwdmsg"]
+            .iter()
+            .filter_map(|marker| lower.find(marker))
+            .min();
+    }
+
+    let mut offset = 0;
+    for line in body.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with('>')
+            || (trimmed.starts_with("On ") && trimmed.ends_with("wrote:"))
+            || trimmed.starts_with("-----Original Message-----")
+            || trimmed.starts_with("---------- Forwarded message")
+        {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Rough plain-text rendering of an HTML signature
+fn html_to_plain(html: &str) -> String {
+    let html = html
+        .replace("<br>", "\n")
+        .replace("<br/>", "\n")
+        .replace("<br />", "\n")
+        .replace("</p>", "\n")
+        .replace("</div>", "\n");
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// Replace `data:` images in a signature with `cid:` references and return
+/// the matching inline attachments
+fn embed_signature_images(html: &str) -> (String, Vec<EmailAttachment>) {
+    use base64::Engine;
+    use sha2::{Digest, Sha256};
+
+    let mut output = String::with_capacity(html.len());
+    let mut attachments = Vec::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find("data:image/") {
+        let quote = rest[..start].chars().last().filter(|c| *c == '"' || *c == '\'');
+        let end = quote.and_then(|q| rest[start..].find(q));
+        let (Some(_), Some(len)) = (quote, end) else {
+            output.push_str(&rest[..start + 1]);
+            rest = &rest[start + 1..];
+            continue;
+        };
+
+        let uri = &rest[start..start + len];
+        let decoded = uri.split_once(',').and_then(|(header, data)| {
+            let mime_type = header.strip_prefix("data:")?.strip_suffix(";base64")?;
+            let data: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+            let bytes = base64::engine::general_purpose::STANDARD.decode(data).ok()?;
+            Some((mime_type.to_string(), bytes))
+        });
+
+        output.push_str(&rest[..start]);
+        match decoded {
+            Some((mime_type, bytes)) => {
+                let index = attachments.len() + 1;
+                let content_id = format!("sig{}.{}@cube.mail", index, Uuid::new_v4().simple());
+                let extension = mime_type
+                    .rsplit('/')
+                    .next()
+                    .map(|subtype| subtype.split('+').next().unwrap_or(subtype))
+                    .unwrap_or("img");
+                output.push_str("cid:");
+                output.push_str(&content_id);
+                attachments.push(EmailAttachment {
+                    id: Uuid::new_v4().to_string(),
+                    filename: format!("signature-{}.{}", index, extension),
+                    mime_type,
+                    size: bytes.len() as u64,
+                    content_id: Some(content_id),
+                    is_inline: true,
+                    encrypted: false,
+                    download_url: None,
+                    local_path: None,
+                    scan_status: AttachmentScanStatus::NotScanned,
+                    scan_detail: None,
+                    sha256: Some(format!("{:x}", Sha256::digest(&bytes))),
+                });
+            }
+            None => output.push_str(uri),
+        }
+        rest = &rest[start + len..];
+    }
+    output.push_str(rest);
+
+    (output, attachments)
+}
+
+/// Insert `signature` into a draft body, above any quoted original message.
+///
+/// Returns the signed body and the inline image parts the signature needs.
+pub fn apply_signature(
+    body: &str,
+    body_format: &str,
+    signature: &MailSignature,
+) -> (String, Vec<EmailAttachment>) {
+    let html = body_format.eq_ignore_ascii_case("html");
+    if html && body.contains(SIGNATURE_CLASS) {
+        return (body.to_string(), Vec::new());
+    }
+
+    let (block, attachments) = if html {
+        let (signature_html, attachments) = if signature.html.trim().is_empty() {
+            (escape_html(signature.plain.trim()).replace('\n', "<br>"), Vec::new())
+        } else {
+            embed_signature_images(&signature.html)
+        };
+        (
+            format!("<div class=\"{}\">-- <br>{}</div>", SIGNATURE_CLASS, signature_html),
+            attachments,
+        )
+    } else {
+        let plain = if signature.plain.trim().is_empty() {
+            html_to_plain(&signature.html)
+        } else {
+            signature.plain.trim().to_string()
+        };
+        (format!("-- \n{}\n", plain), Vec::new())
+    };
+
+    let signed = match quote_start(body, html) {
+        Some(position) => {
+            let (reply, quoted) = body.split_at(position);
+            if html {
+                format!("{}{}{}", reply, block, quoted)
+            } else {
+                format!("{}\n\n{}\n{}", reply.trim_end(), block, quoted)
+            }
+        }
+        None if html => format!("{}{}", body, block),
+        None => format!("{}\n\n{}", body.trim_end(), block),
+    };
+
+    (signed, attachments)
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -444,7 +790,12 @@ pub struct CubeMailServiceState {
     screener_pending: RwLock<HashMap<String, Vec<ScreenerSender>>>,
     drafts: RwLock<HashMap<String, Vec<ComposeDraft>>>,
     filters: RwLock<HashMap<String, Vec<MailFilter>>>,
+    signatures: RwLock<HashMap<String, AccountSignatures>>,
     sync_status: RwLock<HashMap<String, SyncStatus>>,
+    attachments: MailAttachmentStore,
+    token_refresher: Option<Arc<dyn MailTokenRefresher>>,
+    /// One lock per account so concurrent operations share a single refresh
+    refresh_locks: RwLock<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
 }
 
 impl Default for CubeMailServiceState {
@@ -455,6 +806,15 @@ impl Default for CubeMailServiceState {
 
 impl CubeMailServiceState {
     pub fn new() -> Self {
+        let root = dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("cube-browser")
+            .join("mail_attachments");
+        Self::with_attachment_root(root)
+    }
+
+    /// Create the service storing attachment bodies under `root`
+    pub fn with_attachment_root(root: PathBuf) -> Self {
         info!("📬 Initializing CUBE Mail Service");
         Self {
             accounts: RwLock::new(HashMap::new()),
@@ -464,10 +824,20 @@ impl CubeMailServiceState {
             screener_pending: RwLock::new(HashMap::new()),
             drafts: RwLock::new(HashMap::new()),
             filters: RwLock::new(HashMap::new()),
+            signatures: RwLock::new(HashMap::new()),
             sync_status: RwLock::new(HashMap::new()),
+            attachments: MailAttachmentStore::new(root),
+            token_refresher: None,
+            refresh_locks: RwLock::new(HashMap::new()),
         }
     }
 
+    /// Use `refresher` to renew expired OAuth2 access tokens
+    pub fn with_token_refresher(mut self, refresher: Arc<dyn MailTokenRefresher>) -> Self {
+        self.token_refresher = Some(refresher);
+        self
+    }
+
     // =========================================================================
     // ACCOUNT MANAGEMENT
     // =========================================================================
@@ -587,6 +957,168 @@ impl CubeMailServiceState {
         Ok(())
     }
 
+    // =========================================================================
+    // OAUTH2 TOKENS
+    // =========================================================================
+
+    /// Store freshly issued tokens on an account and clear its re-auth flag
+    pub async fn reauthorize_account(
+        &self,
+        account_id: &str,
+        tokens: OAuth2Tokens,
+    ) -> Result<MailAccount, String> {
+        let account = {
+            let mut accounts = self.accounts.write().await;
+            let account = accounts.get_mut(account_id)
+                .ok_or_else(|| format!("Account {} not found", account_id))?;
+            Self::apply_tokens(account, tokens);
+            account.clone()
+        };
+
+        let mut sync_status = self.sync_status.write().await;
+        if let Some(status) = sync_status.get_mut(account_id) {
+            if status.needs_reauth {
+                status.status = "idle".to_string();
+                status.needs_reauth = false;
+            }
+        }
+
+        info!("✅ Account re-authorized: {}", account.email);
+        Ok(account)
+    }
+
+    fn apply_tokens(account: &mut MailAccount, tokens: OAuth2Tokens) {
+        account.imap.oauth2_token = Some(tokens.access_token.clone());
+        account.smtp.oauth2_token = Some(tokens.access_token);
+        if tokens.refresh_token.is_some() {
+            account.imap.oauth2_refresh_token = tokens.refresh_token;
+        }
+        account.imap.oauth2_expires_at = tokens.expires_at.or_else(|| {
+            tokens.expires_in.map(|secs| Utc::now() + chrono::Duration::seconds(secs))
+        });
+        account.needs_reauth = false;
+        account.updated_at = Utc::now();
+    }
+
+    async fn refresh_lock(&self, account_id: &str) -> Arc<tokio::sync::Mutex<()>> {
+        if let Some(lock) = self.refresh_locks.read().await.get(account_id) {
+            return lock.clone();
+        }
+        self.refresh_locks.write().await
+            .entry(account_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Return the account with a usable access token, refreshing it first if it
+    /// has expired. When `rejected_token` is set, that token was refused by the
+    /// server and is refreshed even if it has not expired yet.
+    async fn ensure_fresh_token(
+        &self,
+        account_id: &str,
+        rejected_token: Option<&str>,
+    ) -> Result<MailAccount, String> {
+        let account = self.get_account(account_id).await
+            .ok_or_else(|| format!("Account {} not found", account_id))?;
+        if account.needs_reauth {
+            return Err(format!("Account {} needs to be re-authorized", account.email));
+        }
+        if !account.uses_oauth2() || (rejected_token.is_none() && !account.oauth2_token_expiring()) {
+            return Ok(account);
+        }
+
+        // Single-flight: whoever takes the lock first refreshes; everyone
+        // queued behind it sees the new token and skips their own refresh.
+        let lock = self.refresh_lock(account_id).await;
+        let _guard = lock.lock().await;
+
+        let account = self.get_account(account_id).await
+            .ok_or_else(|| format!("Account {} not found", account_id))?;
+        if account.needs_reauth {
+            return Err(format!("Account {} needs to be re-authorized", account.email));
+        }
+        let still_stale = match rejected_token {
+            Some(rejected) => account.imap.oauth2_token.as_deref() == Some(rejected),
+            None => account.oauth2_token_expiring(),
+        };
+        if !still_stale {
+            return Ok(account);
+        }
+
+        let refresh_token = match account.imap.oauth2_refresh_token.clone() {
+            Some(token) => token,
+            None => {
+                self.mark_needs_reauth(account_id, "No OAuth2 refresh token stored").await;
+                return Err(format!("Account {} needs to be re-authorized", account.email));
+            }
+        };
+        let refresher = self.token_refresher.clone()
+            .ok_or_else(|| "OAuth2 token refresh is not configured".to_string())?;
+
+        info!("🔐 Refreshing OAuth2 token for: {}", account.email);
+        match refresher.refresh(&account.provider, &refresh_token).await {
+            Ok(tokens) => {
+                let mut accounts = self.accounts.write().await;
+                let account = accounts.get_mut(account_id)
+                    .ok_or_else(|| format!("Account {} not found", account_id))?;
+                Self::apply_tokens(account, tokens);
+                Ok(account.clone())
+            }
+            Err(TokenRefreshError::Revoked(reason)) => {
+                self.mark_needs_reauth(account_id, &reason).await;
+                Err(format!("Account {} needs to be re-authorized: {}", account.email, reason))
+            }
+            Err(TokenRefreshError::Failed(reason)) => {
+                warn!("OAuth2 token refresh failed for {}: {}", account.email, reason);
+                Err(reason)
+            }
+        }
+    }
+
+    async fn mark_needs_reauth(&self, account_id: &str, reason: &str) {
+        warn!("🔐 Account {} needs re-authorization: {}", account_id, reason);
+        if let Some(account) = self.accounts.write().await.get_mut(account_id) {
+            account.needs_reauth = true;
+            account.updated_at = Utc::now();
+        }
+
+        let mut sync_status = self.sync_status.write().await;
+        let status = sync_status.entry(account_id.to_string()).or_insert_with(|| SyncStatus {
+            account_id: account_id.to_string(),
+            status: "needs_reauth".to_string(),
+            progress: 0.0,
+            total_messages: 0,
+            synced_messages: 0,
+            errors: Vec::new(),
+            started_at: Utc::now(),
+            completed_at: None,
+            needs_reauth: true,
+        });
+        status.status = "needs_reauth".to_string();
+        status.needs_reauth = true;
+    }
+
+    /// Run an IMAP/SMTP operation with a fresh access token. If the server
+    /// rejects the token anyway, refresh once and retry the operation once.
+    async fn with_fresh_token<T, F, Fut>(&self, account_id: &str, op: F) -> Result<T, String>
+    where
+        F: Fn(MailAccount) -> Fut,
+        Fut: std::future::Future<Output = Result<T, String>>,
+    {
+        let account = self.ensure_fresh_token(account_id, None).await?;
+        let used_token = account.imap.oauth2_token.clone();
+        let uses_oauth2 = account.uses_oauth2();
+
+        match op(account).await {
+            Err(e) if uses_oauth2 && is_auth_failure(&e) => {
+                warn!("🔐 Server rejected OAuth2 token for {}, refreshing: {}", account_id, e);
+                let account = self.ensure_fresh_token(account_id, used_token.as_deref()).await?;
+                op(account).await
+            }
+            result => result,
+        }
+    }
+
     // =========================================================================
     // EMAIL OPERATIONS
     // =========================================================================
@@ -662,6 +1194,78 @@ impl CubeMailServiceState {
         }
     }
 
+    /// Parse a raw RFC 822 message, store and scan its attachments, and
+    /// add it to the account's mailbox
+    pub async fn ingest_raw_email(&self, account_id: &str, folder: MailFolder, raw: &[u8]) -> Result<Email, String> {
+        let mut email = CubeImapClient::parse_email_from_raw(raw, folder, account_id)?;
+        self.attachments.ingest(&mut email, raw).await?;
+
+        let mut emails = self.emails.write().await;
+        emails.entry(account_id.to_string()).or_default().push(email.clone());
+        Ok(email)
+    }
+
+    /// Save an attachment to `destination`. Infected attachments must be
+    /// released first.
+    pub async fn save_attachment(
+        &self,
+        account_id: &str,
+        email_id: &str,
+        attachment_id: &str,
+        destination: &Path,
+    ) -> Result<u64, String> {
+        let attachment = self
+            .get_email(account_id, email_id)
+            .await
+            .ok_or_else(|| format!("Email {} not found", email_id))?
+            .attachments
+            .into_iter()
+            .find(|a| a.id == attachment_id)
+            .ok_or_else(|| format!("Attachment {} not found", attachment_id))?;
+
+        self.attachments.save_to(&attachment, destination).await
+    }
+
+    /// Release an attachment flagged as infected so it can be downloaded
+    pub async fn release_attachment(
+        &self,
+        account_id: &str,
+        email_id: &str,
+        attachment_id: &str,
+    ) -> Result<EmailAttachment, String> {
+        let mut emails = self.emails.write().await;
+        let attachment = emails
+            .get_mut(account_id)
+            .and_then(|list| list.iter_mut().find(|e| e.id == email_id))
+            .ok_or_else(|| format!("Email {} not found", email_id))?
+            .attachments
+            .iter_mut()
+            .find(|a| a.id == attachment_id)
+            .ok_or_else(|| format!("Attachment {} not found", attachment_id))?;
+
+        if attachment.scan_status != AttachmentScanStatus::Infected {
+            return Err(format!("Attachment {} is not quarantined", attachment.filename));
+        }
+
+        warn!(
+            "Releasing quarantined attachment {} ({})",
+            attachment.filename,
+            attachment.scan_detail.as_deref().unwrap_or("infected")
+        );
+        attachment.scan_status = AttachmentScanStatus::Released;
+        Ok(attachment.clone())
+    }
+
+    /// Get attachment scanning configuration
+    pub async fn get_attachment_scan_config(&self) -> AttachmentScanConfig {
+        self.attachments.get_config().await
+    }
+
+    /// Update attachment scanning configuration
+    pub async fn update_attachment_scan_config(&self, config: AttachmentScanConfig) -> Result<(), String> {
+        self.attachments.update_config(config).await
+    }
+
     /// Mark emails as read/unread
     pub async fn mark_as_read(
         &self,
@@ -763,8 +1367,32 @@ impl CubeMailServiceState {
     pub async fn send_email(&self, draft: ComposeDraft) -> Result<Email, String> {
         info!("Sending email from account: {}", draft.account_id);
         
-        // In production, this would use SMTP to send
-        // For now, we create the sent email record
+        // In production, this would use SMTP to send with the account's
+        // (freshly refreshed) credentials. For now, we create the sent email record
+        let account = self.with_fresh_token(&draft.account_id, |account| async move {
+            Ok(account)
+        }).await?;
+
+        let mut draft = draft;
+        let is_reply = is_reply_or_forward(&draft);
+        let signature = self.signatures.read().await.get(&draft.account_id).cloned().unwrap_or_else(|| {
+            AccountSignatures {
+                default: account.signature.clone().map(|html| MailSignature { plain: html_to_plain(&html), html }),
+                ..AccountSignatures::default()
+            }
+        });
+        if (is_reply && signature.use_for_replies) || (!is_reply && signature.use_for_new) {
+            if let Some(selected) = signature.signature_for(draft.from_identity.as_deref()) {
+                let (body, inline) = apply_signature(&draft.body, &draft.body_format, selected);
+                draft.body = body;
+                draft.attachments.extend(inline);
+            }
+        }
+        let from_email = draft
+            .from_identity
+            .clone()
+            .filter(|alias| !alias.trim().is_empty())
+            .unwrap_or_else(|| account.email.clone());
         
         let email = Email {
             id: Uuid::new_v4().to_string(),
@@ -773,8 +1401,8 @@ impl CubeMailServiceState {
             thread_id: draft.in_reply_to.clone(),
             folder: MailFolder::Sent,
             from: EmailAddress {
-                email: "user@example.com".to_string(), // Would be from account
-                name: Some("User".to_string()),
+                email: from_email,
+                name: Some(account.name.clone()),
                 avatar: None,
                 is_verified: true,
             },
@@ -832,6 +1460,80 @@ impl CubeMailServiceState {
         Ok(email)
     }
 
+    // =========================================================================
+    // SIGNATURES
+    // =========================================================================
+
+    /// Get the signatures configured for an account
+    pub async fn get_signatures(&self, account_id: &str) -> AccountSignatures {
+        self.signatures.read().await.get(account_id).cloned().unwrap_or_default()
+    }
+
+    /// Set the account's default signature, or the signature for one of its
+    /// aliases when `identity` is given. Empty HTML and plain text removes it.
+    pub async fn set_signature(
+        &self,
+        account_id: &str,
+        identity: Option<String>,
+        html: String,
+        plain: String,
+    ) -> Result<AccountSignatures, String> {
+        let is_default = identity.as_deref().map_or(true, |alias| alias.trim().is_empty());
+        {
+            let mut accounts = self.accounts.write().await;
+            let account = accounts.get_mut(account_id).ok_or("Account not found")?;
+            if is_default {
+                account.signature = Some(html.clone()).filter(|h| !h.trim().is_empty());
+                account.updated_at = Utc::now();
+            }
+        }
+
+        let signature = MailSignature { html, plain };
+        let cleared = signature.html.trim().is_empty() && signature.plain.trim().is_empty();
+
+        let mut signatures = self.signatures.write().await;
+        let entry = signatures.entry(account_id.to_string()).or_default();
+        match identity.filter(|_| !is_default) {
+            Some(alias) => {
+                let alias = alias.trim().to_lowercase();
+                if !alias.contains('@') {
+                    return Err(format!("Invalid alias address: {}", alias));
+                }
+                if cleared {
+                    entry.identities.remove(&alias);
+                } else {
+                    entry.identities.insert(alias, signature);
+                }
+            }
+            None => entry.default = Some(signature).filter(|_| !cleared),
+        }
+
+        info!("✍️ Updated signature for account {}", account_id);
+        Ok(entry.clone())
+    }
+
+    /// Choose whether signatures are added to new messages and to replies/forwards
+    pub async fn set_signature_options(
+        &self,
+        account_id: &str,
+        use_for_new: Option<bool>,
+        use_for_replies: Option<bool>,
+    ) -> Result<AccountSignatures, String> {
+        if !self.accounts.read().await.contains_key(account_id) {
+            return Err("Account not found".to_string());
+        }
+
+        let mut signatures = self.signatures.write().await;
+        let entry = signatures.entry(account_id.to_string()).or_default();
+        if let Some(use_for_new) = use_for_new {
+            entry.use_for_new = use_for_new;
+        }
+        if let Some(use_for_replies) = use_for_replies {
+            entry.use_for_replies = use_for_replies;
+        }
+        Ok(entry.clone())
+    }
+
     // =========================================================================
     // SCREENER
     // =========================================================================
@@ -1022,14 +1724,32 @@ impl CubeMailServiceState {
             errors: Vec::new(),
             started_at: Utc::now(),
             completed_at: None,
+            needs_reauth: false,
         };
         
-        let mut sync_status = self.sync_status.write().await;
-        sync_status.insert(account_id.to_string(), status.clone());
+        self.sync_status.write().await.insert(account_id.to_string(), status);
         
         // In production, this would actually sync with IMAP server
         // For now, we simulate the sync process
-        tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+        let result = self.with_fresh_token(account_id, |_account| async {
+            tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
+            Ok(())
+        }).await;
+        
+        if let Err(e) = result {
+            error!("Sync failed for account {}: {}", account_id, e);
+            let needs_reauth = self.get_account(account_id).await
+                .is_some_and(|account| account.needs_reauth);
+            if let Some(s) = self.sync_status.write().await.get_mut(account_id) {
+                s.status = if needs_reauth { "needs_reauth" } else { "failed" }.to_string();
+                s.needs_reauth = needs_reauth;
+                s.errors.push(e.clone());
+                s.completed_at = Some(Utc::now());
+            }
+            return Err(e);
+        }
+        
+        let mut sync_status = self.sync_status.write().await;
         
         // Update status
         if let Some(s) = sync_status.get_mut(account_id) {
@@ -1050,10 +1770,15 @@ impl CubeMailServiceState {
         result.ok_or_else(|| "Sync status not found".to_string())
     }
 
-    /// Get sync status
+    /// Get sync status, flagging accounts that need to be re-authorized
     pub async fn get_sync_status(&self, account_id: &str) -> Option<SyncStatus> {
+        let needs_reauth = self.get_account(account_id).await
+            .is_some_and(|account| account.needs_reauth);
         let sync_status = self.sync_status.read().await;
-        sync_status.get(account_id).cloned()
+        sync_status.get(account_id).cloned().map(|mut status| {
+            status.needs_reauth = needs_reauth;
+            status
+        })
     }
 
     // =========================================================================
@@ -1338,4 +2063,238 @@ mod tests {
         let category = service.ai_categorize(&email).await;
         assert_eq!(category, EmailCategory::Receipts);
     }
+
+    struct CountingRefresher {
+        calls: std::sync::atomic::AtomicUsize,
+        revoked: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl MailTokenRefresher for CountingRefresher {
+        async fn refresh(
+            &self,
+            _provider: &MailProvider,
+            refresh_token: &str,
+        ) -> Result<OAuth2Tokens, TokenRefreshError> {
+            let n = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+            if self.revoked {
+                return Err(TokenRefreshError::Revoked(format!("invalid_grant for {}", refresh_token)));
+            }
+            Ok(OAuth2Tokens {
+                access_token: format!("fresh-token-{}", n),
+                refresh_token: None,
+                token_type: "Bearer".to_string(),
+                expires_in: Some(3600),
+                expires_at: Some(Utc::now() + chrono::Duration::hours(1)),
+                scope: None,
+                id_token: None,
+            })
+        }
+    }
+
+    async fn oauth_service(revoked: bool) -> (CubeMailServiceState, Arc<CountingRefresher>, String) {
+        let refresher = Arc::new(CountingRefresher {
+            calls: std::sync::atomic::AtomicUsize::new(0),
+            revoked,
+        });
+        let service = CubeMailServiceState::new().with_token_refresher(refresher.clone());
+        let mut account = MailAccount::new(
+            "oauth@example.com".to_string(),
+            "OAuth User".to_string(),
+            MailProvider::Gmail,
+        );
+        account.imap.oauth2_token = Some("stale-token".to_string());
+        account.imap.oauth2_refresh_token = Some("refresh-token".to_string());
+        account.imap.oauth2_expires_at = Some(Utc::now() - chrono::Duration::minutes(1));
+        account.smtp.oauth2_token = Some("stale-token".to_string());
+        let account = service.add_account(account).await.unwrap();
+        (service, refresher, account.id)
+    }
+
+    #[tokio::test]
+    async fn test_expired_oauth_token_refreshed_transparently() {
+        let (service, refresher, account_id) = oauth_service(false).await;
+
+        let draft = ComposeDraft {
+            id: Uuid::new_v4().to_string(),
+            account_id: account_id.clone(),
+            to: vec![],
+            cc: vec![],
+            bcc: vec![],
+            subject: "Hello".to_string(),
+            body: "Hi".to_string(),
+            body_format: "text".to_string(),
+            attachments: vec![],
+            in_reply_to: None,
+            references: vec![],
+            encryption_enabled: false,
+            read_receipt: false,
+            scheduled_send: None,
+            from_identity: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let sent = service.send_email(draft).await.unwrap();
+        assert_eq!(sent.from.email, "oauth@example.com");
+        assert_eq!(refresher.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let account = service.get_account(&account_id).await.unwrap();
+        assert_eq!(account.imap.oauth2_token.as_deref(), Some("fresh-token-1"));
+        assert_eq!(account.smtp.oauth2_token.as_deref(), Some("fresh-token-1"));
+        assert_eq!(account.imap.oauth2_refresh_token.as_deref(), Some("refresh-token"));
+        assert!(!account.oauth2_token_expiring());
+
+        // A token the server rejects before expiry is refreshed and the
+        // operation retried exactly once
+        let attempts = std::sync::atomic::AtomicUsize::new(0);
+        let token = service.with_fresh_token(&account_id, |account| {
+            let attempt = attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async move {
+                if attempt == 0 {
+                    Err("AUTHENTICATIONFAILED Invalid credentials".to_string())
+                } else {
+                    Ok(account.imap.oauth2_token.unwrap())
+                }
+            }
+        }).await.unwrap();
+        assert_eq!(token, "fresh-token-2");
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_operations_share_one_refresh() {
+        let (service, refresher, account_id) = oauth_service(false).await;
+
+        let results = futures::future::join_all(
+            (0..8).map(|_| service.ensure_fresh_token(&account_id, None)),
+        ).await;
+
+        assert_eq!(refresher.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        for result in results {
+            assert_eq!(result.unwrap().imap.oauth2_token.as_deref(), Some("fresh-token-1"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_revoked_refresh_token_marks_needs_reauth() {
+        let (service, refresher, account_id) = oauth_service(true).await;
+
+        assert!(service.sync_account(&account_id).await.is_err());
+        let status = service.get_sync_status(&account_id).await.unwrap();
+        assert!(status.needs_reauth);
+        assert_eq!(status.status, "needs_reauth");
+        assert!(service.get_account(&account_id).await.unwrap().needs_reauth);
+
+        // Further operations fail fast without hammering the token endpoint
+        assert!(service.sync_account(&account_id).await.is_err());
+        assert_eq!(refresher.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(service.get_sync_status(&account_id).await.unwrap().needs_reauth);
+
+        let tokens = OAuth2Tokens {
+            access_token: "new-token".to_string(),
+            refresh_token: Some("new-refresh".to_string()),
+            token_type: "Bearer".to_string(),
+            expires_in: Some(3600),
+            expires_at: None,
+            scope: None,
+            id_token: None,
+        };
+        service.reauthorize_account(&account_id, tokens).await.unwrap();
+        assert!(!service.get_sync_status(&account_id).await.unwrap().needs_reauth);
+    }
+
+    fn signature_draft(account_id: &str, body: &str, in_reply_to: Option<&str>) -> ComposeDraft {
+        ComposeDraft {
+            id: Uuid::new_v4().to_string(),
+            account_id: account_id.to_string(),
+            to: vec![],
+            cc: vec![],
+            bcc: vec![],
+            subject: "Quarterly numbers".to_string(),
+            body: body.to_string(),
+            body_format: "html".to_string(),
+            attachments: vec![],
+            in_reply_to: in_reply_to.map(str::to_string),
+            references: vec![],
+            encryption_enabled: false,
+            read_receipt: false,
+            scheduled_send: None,
+            from_identity: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signature_appended_to_new_message() {
+        let service = CubeMailServiceState::new();
+        let account = service
+            .add_account(MailAccount::new("me@example.com".to_string(), "Me".to_string(), MailProvider::Gmail))
+            .await
+            .unwrap();
+        service
+            .set_signature(&account.id, None, "<b>Me</b><img src=\"data:image/png;base64,iVBORw0KGgo=\">".to_string(), "Me".to_string())
+            .await
+            .unwrap();
+        service
+            .set_signature(&account.id, Some("Sales@Example.com".to_string()), "<i>Sales team</i>".to_string(), "Sales team".to_string())
+            .await
+            .unwrap();
+
+        let sent = service.send_email(signature_draft(&account.id, "<p>Hi all</p>", None)).await.unwrap();
+        let html = sent.body_html.unwrap();
+        assert!(html.starts_with("<p>Hi all</p><div class=\"cube-signature\">-- <br><b>Me</b>"));
+        assert_eq!(sent.attachments.len(), 1);
+        let image = &sent.attachments[0];
+        assert!(image.is_inline);
+        assert_eq!(image.mime_type, "image/png");
+        assert_eq!(image.size, 8);
+        assert!(html.contains(&format!("src=\"cid:{}\"", image.content_id.as_deref().unwrap())));
+        assert!(!html.contains("data:image"));
+
+        // Sending as an alias uses that identity's signature
+        let mut draft = signature_draft(&account.id, "<p>Offer</p>", None);
+        draft.from_identity = Some("sales@example.com".to_string());
+        let sent = service.send_email(draft).await.unwrap();
+        assert_eq!(sent.from.email, "sales@example.com");
+        assert!(sent.body_html.unwrap().contains("<i>Sales team</i>"));
+        assert!(sent.attachments.is_empty());
+
+        // Plain-text bodies get the plain signature after the "-- " delimiter
+        let mut draft = signature_draft(&account.id, "Hi all\n", None);
+        draft.body_format = "text".to_string();
+        let sent = service.send_email(draft).await.unwrap();
+        assert_eq!(sent.body_text.as_deref(), Some("Hi all\n\n-- \nMe\n"));
+    }
+
+    #[tokio::test]
+    async fn test_signature_placed_above_quote_on_reply() {
+        let service = CubeMailServiceState::new();
+        let account = service
+            .add_account(MailAccount::new("me@example.com".to_string(), "Me".to_string(), MailProvider::Gmail))
+            .await
+            .unwrap();
+        service.set_signature(&account.id, None, "<b>Me</b>".to_string(), "Me".to_string()).await.unwrap();
+
+        let body = "<p>Sounds good</p><blockquote>On Monday you wrote: numbers?</blockquote>";
+        let sent = service.send_email(signature_draft(&account.id, body, Some("<orig@example.com>"))).await.unwrap();
+        assert_eq!(
+            sent.body_html.as_deref(),
+            Some("<p>Sounds good</p><div class=\"cube-signature\">-- <br><b>Me</b></div><blockquote>On Monday you wrote: numbers?</blockquote>")
+        );
+
+        let (plain, _) = apply_signature("Sounds good\n\nOn Mon, Ann wrote:\n> numbers?\n", "text", &MailSignature {
+            html: String::new(),
+            plain: "Me".to_string(),
+        });
+        assert_eq!(plain, "Sounds good\n\n-- \nMe\n\nOn Mon, Ann wrote:\n> numbers?\n");
+
+        // Replies can opt out of signatures while new messages keep them
+        service.set_signature_options(&account.id, None, Some(false)).await.unwrap();
+        let sent = service.send_email(signature_draft(&account.id, body, Some("<orig@example.com>"))).await.unwrap();
+        assert_eq!(sent.body_html.as_deref(), Some(body));
+        let sent = service.send_email(signature_draft(&account.id, "<p>New</p>", None)).await.unwrap();
+        assert!(sent.body_html.unwrap().contains("cube-signature"));
+    }
 }
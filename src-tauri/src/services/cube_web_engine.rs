@@ -2,6 +2,9 @@
 // Provides real embedded webviews inside the main window using platform-native APIs
 // No external windows, no proxy - real browser tabs inside the app
 
+use base64::Engine;
+use super::doh_resolver::{DohResolver, DohResolverHandle};
+use super::http_auth::{self, AuthPrompt, AuthScheme, HttpAuthCache, HttpCredentials};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex, RwLock};
@@ -119,6 +122,10 @@ pub struct CubeWebEngineState {
     pub page_cache: RwLock<HashMap<String, PageContent>>,
     /// Browsing history
     pub history: RwLock<HashMap<String, Vec<HistoryEntry>>>,
+    /// Current position in each tab's history
+    pub history_index: RwLock<HashMap<String, usize>>,
+    /// Back/forward cache of pages navigated away from
+    pub bfcache: RwLock<BackForwardCache>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -129,6 +136,8 @@ pub struct PageContent {
     pub styles: Vec<String>,
     pub resources: HashMap<String, Vec<u8>>,
     pub dom_ready: bool,
+    #[serde(default)]
+    pub cache_control: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -147,6 +156,8 @@ impl Default for CubeWebEngineState {
             event_sender: None,
             page_cache: RwLock::new(HashMap::new()),
             history: RwLock::new(HashMap::new()),
+            history_index: RwLock::new(HashMap::new()),
+            bfcache: RwLock::new(BackForwardCache::default()),
         }
     }
 }
@@ -230,6 +241,14 @@ impl CubeWebEngineState {
             cache.remove(tab_id);
         }
 
+        // Drop the tab's history position and cached pages
+        {
+            let mut index = self.history_index.write().map_err(|e| format!("Lock error: {}", e))?;
+            index.remove(tab_id);
+            let mut bfcache = self.bfcache.write().map_err(|e| format!("Lock error: {}", e))?;
+            bfcache.remove_tab(tab_id);
+        }
+
         // Update active tab if needed
         {
             let active = self.active_tab.read().map_err(|e| format!("Lock error: {}", e))?;
@@ -359,9 +378,10 @@ impl CubeWebEngineState {
         Ok(cache.get(tab_id).cloned())
     }
 
-    /// Add history entry
+    /// Add history entry, dropping any forward entries (and their cached pages)
     pub fn add_history(&self, tab_id: &str, url: &str, title: &str) -> Result<(), String> {
         let mut history = self.history.write().map_err(|e| format!("Lock error: {}", e))?;
+        let mut positions = self.history_index.write().map_err(|e| format!("Lock error: {}", e))?;
         
         let entry = HistoryEntry {
             url: url.to_string(),
@@ -369,14 +389,80 @@ impl CubeWebEngineState {
             timestamp: chrono::Utc::now().timestamp_millis(),
         };
 
-        history
+        let entries = history
             .entry(tab_id.to_string())
-            .or_insert_with(Vec::new)
-            .push(entry);
+            .or_insert_with(Vec::new);
+        if let Some(&current) = positions.get(tab_id) {
+            if current + 1 < entries.len() {
+                entries.truncate(current + 1);
+                let mut bfcache = self.bfcache.write().map_err(|e| format!("Lock error: {}", e))?;
+                bfcache.remove_forward(tab_id, current);
+            }
+        }
+        entries.push(entry);
+        positions.insert(tab_id.to_string(), entries.len() - 1);
 
         Ok(())
     }
 
+    /// Current position in the tab's history
+    pub fn get_history_index(&self, tab_id: &str) -> Result<Option<usize>, String> {
+        let positions = self.history_index.read().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(positions.get(tab_id).copied())
+    }
+
+    /// Snapshots the page at the tab's current history position as it is navigated
+    /// away from. Returns whether it was cached and, if not, why.
+    pub fn store_in_bfcache(&self, tab_id: &str, snapshot: PageSnapshot) -> Result<BfCacheAttempt, String> {
+        let index = self.get_history_index(tab_id)?.ok_or("Tab has no history")?;
+        let cache_control = {
+            let cache = self.page_cache.read().map_err(|e| format!("Lock error: {}", e))?;
+            cache.get(tab_id).and_then(|c| c.cache_control.clone())
+        };
+        let mut bfcache = self.bfcache.write().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(bfcache.store(tab_id, index, snapshot, cache_control.as_deref()))
+    }
+
+    /// Moves `delta` entries through the tab's history. The target page is restored from
+    /// the bfcache when present; otherwise the caller must load it.
+    pub fn navigate_history(&self, tab_id: &str, delta: isize) -> Result<HistoryNavigation, String> {
+        let history = self.get_history(tab_id)?;
+        let current = self.get_history_index(tab_id)?.ok_or("Tab has no history")?;
+        let target = current as isize + delta;
+        if target < 0 || target as usize >= history.len() {
+            return Err("No history entry in that direction".to_string());
+        }
+        let target = target as usize;
+
+        {
+            let mut positions = self.history_index.write().map_err(|e| format!("Lock error: {}", e))?;
+            positions.insert(tab_id.to_string(), target);
+        }
+
+        let entry = history[target].clone();
+        let restored = {
+            let mut bfcache = self.bfcache.write().map_err(|e| format!("Lock error: {}", e))?;
+            bfcache.take(tab_id, target, &entry.url)
+        };
+
+        self.update_tab(tab_id, TabUpdate {
+            url: Some(entry.url.clone()),
+            title: Some(entry.title.clone()),
+            is_loading: Some(restored.is_none()),
+            can_go_back: Some(target > 0),
+            can_go_forward: Some(target + 1 < history.len()),
+            ..Default::default()
+        })?;
+
+        Ok(HistoryNavigation { index: target, entry, restored })
+    }
+
+    /// Why pages in this tab were or weren't cached, for debugging
+    pub fn get_bfcache_status(&self, tab_id: &str) -> Result<BfCacheStatus, String> {
+        let bfcache = self.bfcache.read().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(bfcache.status(tab_id))
+    }
+
     /// Get tab history
     pub fn get_history(&self, tab_id: &str) -> Result<Vec<HistoryEntry>, String> {
         let history = self.history.read().map_err(|e| format!("Lock error: {}", e))?;
@@ -384,6 +470,268 @@ impl CubeWebEngineState {
     }
 }
 
+// ============================================
+// Back/Forward Cache
+// ============================================
+
+/// Page state captured by the webview on `pagehide`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageSnapshot {
+    pub url: String,
+    /// Serialized DOM
+    pub html: String,
+    pub scroll_x: f64,
+    pub scroll_y: f64,
+    /// Serializable in-page JS state (globals the page registered for persistence)
+    pub js_state: serde_json::Value,
+    #[serde(default)]
+    pub form_state: HashMap<String, String>,
+    #[serde(default)]
+    pub has_unload_handler: bool,
+    /// Open WebSocket/WebRTC/IndexedDB connections at the time of capture
+    #[serde(default)]
+    pub open_connections: u32,
+}
+
+impl PageSnapshot {
+    fn size_bytes(&self) -> usize {
+        self.html.len() + self.js_state.to_string().len()
+            + self.form_state.iter().map(|(k, v)| k.len() + v.len()).sum::<usize>()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum BfCacheBlocker {
+    CacheControlNoStore,
+    UnloadHandler,
+    OpenConnections,
+    TooLarge,
+    MemoryPressure,
+    Expired,
+    HistoryPruned,
+    Disabled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BfCacheAttempt {
+    pub url: String,
+    pub history_index: usize,
+    pub stored: bool,
+    pub blockers: Vec<BfCacheBlocker>,
+    pub at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BfCacheEviction {
+    pub url: String,
+    pub history_index: usize,
+    pub reason: BfCacheBlocker,
+    pub at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BfCacheEntryInfo {
+    pub url: String,
+    pub history_index: usize,
+    pub size_bytes: usize,
+    pub cached_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BfCacheStatus {
+    pub tab_id: String,
+    pub enabled: bool,
+    pub cached_entries: Vec<BfCacheEntryInfo>,
+    pub last_attempt: Option<BfCacheAttempt>,
+    pub recent_evictions: Vec<BfCacheEviction>,
+    /// Whether the last back/forward navigation was served from the cache
+    pub last_navigation_restored: Option<bool>,
+}
+
+/// Result of a back/forward navigation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryNavigation {
+    pub index: usize,
+    pub entry: HistoryEntry,
+    /// Snapshot to restore instantly (`pageshow` with `persisted: true`), if cached
+    pub restored: Option<PageSnapshot>,
+}
+
+#[derive(Debug, Clone)]
+struct BfCacheEntry {
+    snapshot: PageSnapshot,
+    size_bytes: usize,
+    cached_at: i64,
+}
+
+const BFCACHE_MAX_EVICTIONS_TRACKED: usize = 10;
+
+pub struct BackForwardCache {
+    pub enabled: bool,
+    pub max_entries: usize,
+    pub max_total_bytes: usize,
+    pub time_to_live_ms: i64,
+    entries: HashMap<(String, usize), BfCacheEntry>,
+    attempts: HashMap<String, BfCacheAttempt>,
+    evictions: HashMap<String, Vec<BfCacheEviction>>,
+    last_restore: HashMap<String, bool>,
+}
+
+impl Default for BackForwardCache {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: 6,
+            max_total_bytes: 64 * 1024 * 1024,
+            time_to_live_ms: 10 * 60 * 1000,
+            entries: HashMap::new(),
+            attempts: HashMap::new(),
+            evictions: HashMap::new(),
+            last_restore: HashMap::new(),
+        }
+    }
+}
+
+impl BackForwardCache {
+    fn store(&mut self, tab_id: &str, index: usize, snapshot: PageSnapshot, cache_control: Option<&str>) -> BfCacheAttempt {
+        let size_bytes = snapshot.size_bytes();
+        let mut blockers = Vec::new();
+        if !self.enabled {
+            blockers.push(BfCacheBlocker::Disabled);
+        }
+        if cache_control.is_some_and(|cc| cc.to_lowercase().contains("no-store")) {
+            blockers.push(BfCacheBlocker::CacheControlNoStore);
+        }
+        if snapshot.has_unload_handler {
+            blockers.push(BfCacheBlocker::UnloadHandler);
+        }
+        if snapshot.open_connections > 0 {
+            blockers.push(BfCacheBlocker::OpenConnections);
+        }
+        if size_bytes > self.max_total_bytes {
+            blockers.push(BfCacheBlocker::TooLarge);
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let attempt = BfCacheAttempt {
+            url: snapshot.url.clone(),
+            history_index: index,
+            stored: blockers.is_empty(),
+            blockers,
+            at: now,
+        };
+
+        if attempt.stored {
+            self.entries.insert(
+                (tab_id.to_string(), index),
+                BfCacheEntry { snapshot, size_bytes, cached_at: now },
+            );
+            self.enforce_limits();
+        } else {
+            self.entries.remove(&(tab_id.to_string(), index));
+        }
+        self.attempts.insert(tab_id.to_string(), attempt.clone());
+        attempt
+    }
+
+    /// Removes and returns the snapshot for a history entry; the page becomes live again
+    fn take(&mut self, tab_id: &str, index: usize, url: &str) -> Option<PageSnapshot> {
+        let key = (tab_id.to_string(), index);
+        let restored = match self.entries.remove(&key) {
+            Some(entry) if chrono::Utc::now().timestamp_millis() - entry.cached_at > self.time_to_live_ms => {
+                self.record_eviction(tab_id, index, &entry.snapshot.url, BfCacheBlocker::Expired);
+                None
+            }
+            Some(entry) if entry.snapshot.url == url => Some(entry.snapshot),
+            _ => None,
+        };
+        self.last_restore.insert(tab_id.to_string(), restored.is_some());
+        restored
+    }
+
+    /// Drops every cached page, e.g. when the system reports low memory
+    pub fn evict_for_memory_pressure(&mut self) {
+        let keys: Vec<(String, usize)> = self.entries.keys().cloned().collect();
+        for key in keys {
+            self.evict(&key, BfCacheBlocker::MemoryPressure);
+        }
+    }
+
+    fn remove_forward(&mut self, tab_id: &str, current: usize) {
+        let keys: Vec<(String, usize)> = self.entries.keys()
+            .filter(|(tab, index)| tab == tab_id && *index > current)
+            .cloned()
+            .collect();
+        for key in keys {
+            self.evict(&key, BfCacheBlocker::HistoryPruned);
+        }
+    }
+
+    fn remove_tab(&mut self, tab_id: &str) {
+        self.entries.retain(|(tab, _), _| tab != tab_id);
+        self.attempts.remove(tab_id);
+        self.evictions.remove(tab_id);
+        self.last_restore.remove(tab_id);
+    }
+
+    /// Evicts least recently cached pages until within the entry and memory budgets
+    fn enforce_limits(&mut self) {
+        loop {
+            let total: usize = self.entries.values().map(|e| e.size_bytes).sum();
+            if self.entries.len() <= self.max_entries && total <= self.max_total_bytes {
+                break;
+            }
+            let Some(oldest) = self.entries.iter()
+                .min_by_key(|(_, e)| e.cached_at)
+                .map(|(k, _)| k.clone()) else {
+                break;
+            };
+            self.evict(&oldest, BfCacheBlocker::MemoryPressure);
+        }
+    }
+
+    fn evict(&mut self, key: &(String, usize), reason: BfCacheBlocker) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.record_eviction(&key.0, key.1, &entry.snapshot.url, reason);
+        }
+    }
+
+    fn record_eviction(&mut self, tab_id: &str, index: usize, url: &str, reason: BfCacheBlocker) {
+        let evictions = self.evictions.entry(tab_id.to_string()).or_default();
+        evictions.push(BfCacheEviction {
+            url: url.to_string(),
+            history_index: index,
+            reason,
+            at: chrono::Utc::now().timestamp_millis(),
+        });
+        if evictions.len() > BFCACHE_MAX_EVICTIONS_TRACKED {
+            evictions.remove(0);
+        }
+    }
+
+    fn status(&self, tab_id: &str) -> BfCacheStatus {
+        let mut cached_entries: Vec<BfCacheEntryInfo> = self.entries.iter()
+            .filter(|((tab, _), _)| tab == tab_id)
+            .map(|((_, index), entry)| BfCacheEntryInfo {
+                url: entry.snapshot.url.clone(),
+                history_index: *index,
+                size_bytes: entry.size_bytes,
+                cached_at: entry.cached_at,
+            })
+            .collect();
+        cached_entries.sort_by_key(|e| e.history_index);
+
+        BfCacheStatus {
+            tab_id: tab_id.to_string(),
+            enabled: self.enabled,
+            cached_entries,
+            last_attempt: self.attempts.get(tab_id).cloned(),
+            recent_evictions: self.evictions.get(tab_id).cloned().unwrap_or_default(),
+            last_navigation_restored: self.last_restore.get(tab_id).copied(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TabUpdate {
     pub url: Option<String>,
@@ -399,10 +747,23 @@ pub struct TabUpdate {
 pub struct WebFetcher {
     client: reqwest::Client,
     config: CubeWebEngineConfig,
+    /// HTTP auth credentials, shared by every fetcher of the session
+    auth: Arc<HttpAuthCache>,
+    /// Per-request user agent, e.g. an emulated device's
+    user_agent: Option<String>,
 }
 
 impl WebFetcher {
     pub fn new(config: CubeWebEngineConfig) -> Self {
+        Self::build(config, None)
+    }
+
+    /// Resolve hosts through the engine's DoH resolver instead of the system one
+    pub fn with_resolver(config: CubeWebEngineConfig, dns: Arc<DohResolver>) -> Self {
+        Self::build(config, Some(dns))
+    }
+
+    fn build(config: CubeWebEngineConfig, dns: Option<Arc<DohResolver>>) -> Self {
         let mut builder = reqwest::Client::builder()
             .user_agent(&config.user_agent)
             .cookie_store(config.cookies_enabled)
@@ -438,18 +799,34 @@ impl WebFetcher {
             }
         }
 
+        if let Some(dns) = dns {
+            builder = builder.dns_resolver(Arc::new(DohResolverHandle(dns)));
+        }
+
         let client = builder.build().unwrap_or_else(|_| reqwest::Client::new());
 
-        Self { client, config }
+        Self { client, config, auth: Arc::new(HttpAuthCache::new()), user_agent: None }
+    }
+
+    /// Send `user_agent` instead of the configured one
+    pub fn with_user_agent(mut self, user_agent: Option<String>) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    /// Share an existing HTTP auth cache, so credentials survive config changes
+    pub fn with_auth_cache(mut self, auth: Arc<HttpAuthCache>) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    pub fn auth_cache(&self) -> &Arc<HttpAuthCache> {
+        &self.auth
     }
 
     /// Fetch a URL and return the response
     pub async fn fetch(&self, url: &str) -> Result<FetchResponse, String> {
-        let response = self.client
-            .get(url)
-            .send()
-            .await
-            .map_err(|e| format!("Fetch failed: {}", e))?;
+        let response = self.send_with_auth(url).await?;
 
         let status = response.status().as_u16();
         let headers: HashMap<String, String> = response
@@ -480,10 +857,130 @@ impl WebFetcher {
         })
     }
 
+    /// GET `url`, answering `401` challenges with cached credentials.
+    ///
+    /// When no credentials are cached, or the cached ones are rejected, the
+    /// 401 response is returned and an [`AuthPrompt`] is left in the auth
+    /// cache for the caller to surface.
+    async fn send_with_auth(&self, url: &str) -> Result<reqwest::Response, String> {
+        let origin = http_auth::origin_of(url);
+        let cnonce = Uuid::new_v4().simple().to_string();
+        let mut authorization: Option<String> = None;
+        // Connection-based handshake waiting for the server's type 2 message
+        let mut ntlm_pending: Option<(AuthScheme, HttpCredentials)> = None;
+        let mut realm: Option<String> = None;
+        let mut nonce_count = 0;
+        let mut attempts = 0;
+
+        loop {
+            let mut request = self.client.get(url);
+            if let Some(user_agent) = &self.user_agent {
+                request = request.header(reqwest::header::USER_AGENT, user_agent);
+            }
+            if let Some(value) = &authorization {
+                request = request.header(reqwest::header::AUTHORIZATION, value);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|e| format!("Fetch failed: {}", e))?;
+            if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+                return Ok(response);
+            }
+
+            let challenges = http_auth::parse_www_authenticate(
+                response
+                    .headers()
+                    .get_all(reqwest::header::WWW_AUTHENTICATE)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok()),
+            );
+            let Some(challenge) = http_auth::select_challenge(&challenges).cloned() else {
+                return Ok(response);
+            };
+            realm = challenge.realm().map(str::to_string).or(realm);
+            let prompt = |failed: bool| AuthPrompt {
+                url: url.to_string(),
+                origin: origin.clone(),
+                realm: realm.clone(),
+                scheme: challenge.scheme,
+                failed,
+            };
+
+            if attempts >= http_auth::MAX_AUTH_ATTEMPTS {
+                self.auth.forget(&origin, realm.as_deref());
+                self.auth.record_prompt(prompt(true));
+                return Ok(response);
+            }
+            attempts += 1;
+
+            if let Some((scheme, credentials)) = ntlm_pending.take() {
+                let server_token = challenges
+                    .iter()
+                    .find(|c| c.scheme == scheme)
+                    .and_then(|c| c.token.as_deref())
+                    .and_then(|token| base64::engine::general_purpose::STANDARD.decode(token).ok());
+                if let Some(type2) = server_token {
+                    let ntlm_challenge = http_auth::parse_ntlm_challenge(&type2)?;
+                    let client_nonce: [u8; 8] = rand::random();
+                    let type3 = http_auth::ntlm_authenticate_message(&ntlm_challenge, &credentials, client_nonce);
+                    authorization = Some(format!(
+                        "{} {}",
+                        scheme.header_name(),
+                        base64::engine::general_purpose::STANDARD.encode(type3)
+                    ));
+                    continue;
+                }
+            }
+
+            // A 401 after we answered means the credentials were rejected,
+            // unless a Digest server only wants the same credentials with a new nonce
+            let stale = challenge.param("stale").is_some_and(|v| v.eq_ignore_ascii_case("true"));
+            if authorization.is_some() && !(challenge.scheme == AuthScheme::Digest && stale) {
+                self.auth.forget(&origin, realm.as_deref());
+                self.auth.record_prompt(prompt(true));
+                return Ok(response);
+            }
+
+            let Some(credentials) = self.auth.lookup(&origin, realm.as_deref()) else {
+                self.auth.record_prompt(prompt(false));
+                return Ok(response);
+            };
+
+            authorization = Some(match challenge.scheme {
+                AuthScheme::Basic => http_auth::basic_authorization(&credentials),
+                AuthScheme::Digest => {
+                    nonce_count += 1;
+                    http_auth::digest_authorization(
+                        &challenge,
+                        &credentials,
+                        "GET",
+                        &http_auth::request_uri(url),
+                        nonce_count,
+                        &cnonce,
+                    )?
+                }
+                AuthScheme::Ntlm | AuthScheme::Negotiate => {
+                    ntlm_pending = Some((challenge.scheme, credentials));
+                    format!(
+                        "{} {}",
+                        challenge.scheme.header_name(),
+                        base64::engine::general_purpose::STANDARD.encode(http_auth::ntlm_negotiate_message())
+                    )
+                }
+            });
+        }
+    }
+
     /// Fetch HTML and parse it for embedded rendering
     pub async fn fetch_page(&self, url: &str) -> Result<PageContent, String> {
         let response = self.fetch(url).await?;
         
+        if response.status == 401 {
+            return Err(format!("Authentication required for {}", http_auth::origin_of(url)));
+        }
+
+        
         // Only process HTML
         if !response.content_type.contains("text/html") {
             return Err("Not an HTML page".to_string());
@@ -501,6 +998,7 @@ impl WebFetcher {
             styles,
             resources: HashMap::new(),
             dom_ready: false,
+            cache_control: response.headers.get("cache-control").cloned(),
         })
     }
 
@@ -590,6 +1088,7 @@ pub enum ScreenshotFormat {
 
 /// Print/PDF options
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct PrintOptions {
     pub landscape: bool,
     pub display_header_footer: bool,
@@ -601,6 +1100,16 @@ pub struct PrintOptions {
     pub margin_bottom: f64,
     pub margin_left: f64,
     pub margin_right: f64,
+    /// Named paper size ("A4", "Letter", ...) overriding `paper_width`/`paper_height`
+    pub paper_format: Option<String>,
+    /// 1-based pages to print, e.g. "1-3, 5, 8-"
+    pub page_ranges: Option<String>,
+    /// Header HTML; `{{page}}`, `{{pages}}`, `{{date}}`, `{{title}}` and `{{url}}` are filled in per page
+    pub header_template: Option<String>,
+    /// Footer HTML, with the same tokens as `header_template`
+    pub footer_template: Option<String>,
+    /// Let a CSS `@page { size: ... }` rule override the paper size
+    pub prefer_css_page_size: bool,
 }
 
 impl Default for PrintOptions {
@@ -616,10 +1125,120 @@ impl Default for PrintOptions {
             margin_bottom: 0.4,
             margin_left: 0.4,
             margin_right: 0.4,
+            paper_format: None,
+            page_ranges: None,
+            header_template: None,
+            footer_template: None,
+            prefer_css_page_size: true,
         }
     }
 }
 
+impl PrintOptions {
+    /// Validate the options and resolve the paper format and page ranges
+    pub fn normalized(&self) -> Result<PrintOptions, String> {
+        let mut options = self.clone();
+
+        if let Some(format) = &self.paper_format {
+            let (width, height) = paper_format_size(format)
+                .ok_or_else(|| format!("Unknown paper format: {}", format))?;
+            options.paper_width = width;
+            options.paper_height = height;
+        }
+        if !(0.1..=2.0).contains(&options.scale) {
+            return Err(format!("Scale must be between 0.1 and 2.0, got {}", options.scale));
+        }
+        if options.paper_width <= 0.0 || options.paper_height <= 0.0 {
+            return Err("Paper width and height must be positive".to_string());
+        }
+        let margins = [options.margin_top, options.margin_bottom, options.margin_left, options.margin_right];
+        if margins.iter().any(|m| *m < 0.0) {
+            return Err("Margins cannot be negative".to_string());
+        }
+        if options.margin_left + options.margin_right >= options.paper_width
+            || options.margin_top + options.margin_bottom >= options.paper_height
+        {
+            return Err("Margins leave no printable area".to_string());
+        }
+
+        options.page_ranges = match self.page_ranges.as_deref().map(str::trim) {
+            Some(ranges) if !ranges.is_empty() => Some(normalize_page_ranges(ranges)?),
+            _ => None,
+        };
+        if options.header_template.is_some() || options.footer_template.is_some() {
+            options.display_header_footer = true;
+        }
+
+        Ok(options)
+    }
+}
+
+/// Paper size in inches for a named format
+pub fn paper_format_size(format: &str) -> Option<(f64, f64)> {
+    match format.to_lowercase().as_str() {
+        "letter" => Some((8.5, 11.0)),
+        "legal" => Some((8.5, 14.0)),
+        "tabloid" => Some((11.0, 17.0)),
+        "ledger" => Some((17.0, 11.0)),
+        "a3" => Some((11.69, 16.54)),
+        "a4" => Some((8.27, 11.69)),
+        "a5" => Some((5.83, 8.27)),
+        "a6" => Some((4.13, 5.83)),
+        _ => None,
+    }
+}
+
+/// Validate a page range list such as "1-3, 5, 8-" and return it in
+/// canonical form ("1-3,5,8-")
+pub fn normalize_page_ranges(ranges: &str) -> Result<String, String> {
+    let parse_page = |page: &str| -> Result<u32, String> {
+        match page.trim().parse::<u32>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(format!("Invalid page number '{}' in range '{}'", page.trim(), ranges)),
+        }
+    };
+
+    let mut parts = Vec::new();
+    for part in ranges.split(',').map(str::trim) {
+        if part.is_empty() {
+            return Err(format!("Empty page range in '{}'", ranges));
+        }
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let start = if start.trim().is_empty() { 1 } else { parse_page(start)? };
+                if end.trim().is_empty() {
+                    parts.push(format!("{}-", start));
+                } else {
+                    let end = parse_page(end)?;
+                    if end < start {
+                        return Err(format!("Page range '{}' ends before it starts", part));
+                    }
+                    parts.push(format!("{}-{}", start, end));
+                }
+            }
+            None => parts.push(parse_page(part)?.to_string()),
+        }
+    }
+
+    Ok(parts.join(","))
+}
+
+/// Turn a header/footer template's `{{token}}`s into the elements Chromium
+/// fills in per page. Templates default to a 10px font because Chromium
+/// otherwise renders them at zero size.
+pub fn resolve_print_template(template: &str) -> String {
+    let resolved = template
+        .replace("{{page}}", "<span class=\"pageNumber\"></span>")
+        .replace("{{pages}}", "<span class=\"totalPages\"></span>")
+        .replace("{{date}}", "<span class=\"date\"></span>")
+        .replace("{{title}}", "<span class=\"title\"></span>")
+        .replace("{{url}}", "<span class=\"url\"></span>");
+    format!(
+        "<div style=\"font-size:10px;width:100%;padding:0 0.4in;\">{}</div>",
+        resolved
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -646,6 +1265,76 @@ mod tests {
         assert!(tabs.is_empty());
     }
 
+    fn snapshot(url: &str, scroll_y: f64, js_state: serde_json::Value) -> PageSnapshot {
+        PageSnapshot {
+            url: url.to_string(),
+            html: "<html><body><div id=\"feed\">loaded items</div></body></html>".to_string(),
+            scroll_x: 0.0,
+            scroll_y,
+            js_state,
+            form_state: HashMap::from([("search".to_string(), "rust".to_string())]),
+            has_unload_handler: false,
+            open_connections: 0,
+        }
+    }
+
+    #[test]
+    fn test_bfcache_restores_scroll_and_js_state() {
+        let engine = CubeWebEngineState::new();
+        let tab = engine.create_tab(None, TabBounds::default()).unwrap();
+        engine.add_history(&tab.id, "https://a.com/", "A").unwrap();
+
+        let state = serde_json::json!({ "counter": 3, "cart": ["book"] });
+        let attempt = engine.store_in_bfcache(&tab.id, snapshot("https://a.com/", 1250.0, state.clone())).unwrap();
+        assert!(attempt.stored);
+        engine.add_history(&tab.id, "https://b.com/", "B").unwrap();
+
+        let back = engine.navigate_history(&tab.id, -1).unwrap();
+        let restored = back.restored.expect("page should come from the bfcache");
+        assert_eq!(back.entry.url, "https://a.com/");
+        assert_eq!(restored.scroll_y, 1250.0);
+        assert_eq!(restored.js_state, state);
+        assert_eq!(restored.form_state.get("search").map(String::as_str), Some("rust"));
+
+        let tab_state = engine.get_tab(&tab.id).unwrap().unwrap();
+        assert!(!tab_state.is_loading);
+        assert!(tab_state.can_go_forward);
+        assert_eq!(engine.get_bfcache_status(&tab.id).unwrap().last_navigation_restored, Some(true));
+
+        // B was never snapshotted, so going forward must load it
+        let forward = engine.navigate_history(&tab.id, 1).unwrap();
+        assert!(forward.restored.is_none());
+        assert!(engine.navigate_history(&tab.id, 1).is_err());
+    }
+
+    #[test]
+    fn test_bfcache_blockers_reported() {
+        let engine = CubeWebEngineState::new();
+        let tab = engine.create_tab(None, TabBounds::default()).unwrap();
+        engine.add_history(&tab.id, "https://bank.com/", "Bank").unwrap();
+        engine.cache_page(&tab.id, PageContent {
+            html: String::new(),
+            base_url: "https://bank.com/".to_string(),
+            scripts: vec![],
+            styles: vec![],
+            resources: HashMap::new(),
+            dom_ready: true,
+            cache_control: Some("private, no-store".to_string()),
+        }).unwrap();
+
+        let mut page = snapshot("https://bank.com/", 0.0, serde_json::Value::Null);
+        page.has_unload_handler = true;
+        let attempt = engine.store_in_bfcache(&tab.id, page).unwrap();
+        assert!(!attempt.stored);
+        assert_eq!(attempt.blockers, vec![BfCacheBlocker::CacheControlNoStore, BfCacheBlocker::UnloadHandler]);
+
+        engine.add_history(&tab.id, "https://b.com/", "B").unwrap();
+        assert!(engine.navigate_history(&tab.id, -1).unwrap().restored.is_none());
+        let status = engine.get_bfcache_status(&tab.id).unwrap();
+        assert!(status.cached_entries.is_empty());
+        assert_eq!(status.last_navigation_restored, Some(false));
+    }
+
     #[test]
     fn test_multiple_tabs() {
         let engine = CubeWebEngineState::new();
@@ -657,4 +1346,230 @@ mod tests {
         let tabs = engine.get_tabs().unwrap();
         assert_eq!(tabs.len(), 3);
     }
+
+    #[test]
+    fn test_print_options_resolve_ranges_formats_and_tokens() {
+        assert_eq!(normalize_page_ranges(" 1-3, 5 ,8- ").unwrap(), "1-3,5,8-");
+        assert_eq!(normalize_page_ranges("-2").unwrap(), "1-2");
+        assert!(normalize_page_ranges("4-2").is_err());
+        assert!(normalize_page_ranges("0").is_err());
+        assert!(normalize_page_ranges("1,,2").is_err());
+
+        let options = PrintOptions {
+            paper_format: Some("A4".to_string()),
+            page_ranges: Some("2 - 4".to_string()),
+            header_template: Some("{{title}} - page {{page}} of {{pages}}".to_string()),
+            ..Default::default()
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!((options.paper_width, options.paper_height), (8.27, 11.69));
+        assert_eq!(options.page_ranges.as_deref(), Some("2-4"));
+        assert!(options.display_header_footer);
+
+        let header = resolve_print_template(options.header_template.as_deref().unwrap());
+        assert!(header.contains("<span class=\"title\"></span> - page <span class=\"pageNumber\"></span> of <span class=\"totalPages\"></span>"));
+        assert!(!header.contains("{{"));
+
+        let bad_scale = PrintOptions { scale: 3.0, ..Default::default() };
+        assert!(bad_scale.normalized().is_err());
+        let bad_format = PrintOptions { paper_format: Some("B9".to_string()), ..Default::default() };
+        assert!(bad_format.normalized().is_err());
+    }
+
+    /// Keep-alive HTTP/1.1 server answering each request with `respond(authorization)`
+    async fn auth_server<F>(respond: F) -> (String, Arc<std::sync::atomic::AtomicUsize>)
+    where
+        F: Fn(Option<&str>) -> (u16, Option<String>) + Send + Sync + 'static,
+    {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let respond = Arc::new(respond);
+        let counter = requests.clone();
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let respond = respond.clone();
+                let counter = counter.clone();
+                tokio::spawn(async move {
+                    let mut buffer = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    loop {
+                        let end = loop {
+                            if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                                break pos + 4;
+                            }
+                            match socket.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+                            }
+                        };
+                        let head = String::from_utf8_lossy(&buffer[..end]).to_string();
+                        buffer.drain(..end);
+                        counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+
+                        let authorization = head.lines().find_map(|line| {
+                            line.split_once(':')
+                                .filter(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+                                .map(|(_, value)| value.trim().to_string())
+                        });
+                        let (status, challenge) = respond(authorization.as_deref());
+                        let body = if status == 200 { "<html><body>intranet</body></html>" } else { "denied" };
+                        let mut reply = format!(
+                            "HTTP/1.1 {} {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\n",
+                            status,
+                            if status == 200 { "OK" } else { "Unauthorized" },
+                            body.len()
+                        );
+                        if let Some(challenge) = challenge {
+                            reply.push_str(&format!("WWW-Authenticate: {}\r\n", challenge));
+                        }
+                        reply.push_str("\r\n");
+                        reply.push_str(body);
+                        if socket.write_all(reply.as_bytes()).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+
+        (base, requests)
+    }
+
+    fn credentials(username: &str, password: &str) -> HttpCredentials {
+        HttpCredentials { username: username.to_string(), password: password.to_string(), domain: None }
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_handshake() {
+        let expected = format!("Basic {}", base64::engine::general_purpose::STANDARD.encode("alice:s3cret"));
+        let (base, requests) = auth_server(move |authorization| {
+            if authorization == Some(expected.as_str()) {
+                (200, None)
+            } else {
+                (401, Some("Basic realm=\"Intranet\"".to_string()))
+            }
+        })
+        .await;
+        let fetcher = WebFetcher::new(CubeWebEngineConfig::default());
+        let url = format!("{}/reports", base);
+
+        // Without credentials the 401 is returned and a prompt is left for the UI
+        let response = fetcher.fetch(&url).await.unwrap();
+        assert_eq!(response.status, 401);
+        let prompt = fetcher.auth_cache().take_prompt(&base).unwrap();
+        assert_eq!((prompt.scheme, prompt.realm.as_deref(), prompt.failed), (AuthScheme::Basic, Some("Intranet"), false));
+
+        fetcher.auth_cache().store(&base, prompt.realm.as_deref(), credentials("alice", "s3cret"));
+        let page = fetcher.fetch_page(&url).await.unwrap();
+        assert!(page.html.contains("intranet"));
+
+        // Rejected credentials are tried once, then dropped and reported
+        fetcher.auth_cache().store(&base, Some("Intranet"), credentials("alice", "wrong"));
+        requests.store(0, std::sync::atomic::Ordering::SeqCst);
+        assert!(fetcher.fetch_page(&url).await.is_err());
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert!(fetcher.auth_cache().take_prompt(&base).unwrap().failed);
+        assert!(fetcher.auth_cache().lookup(&base, Some("Intranet")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_digest_auth_handshake() {
+        let challenge = |nonce: &str, stale: bool| {
+            format!(
+                "Digest realm=\"wiki\", qop=\"auth\", nonce=\"{}\", opaque=\"op4que\"{}",
+                nonce,
+                if stale { ", stale=true" } else { "" }
+            )
+        };
+        let (base, requests) = auth_server(move |authorization| {
+            let md5_hex = |value: String| format!("{:x}", md5::compute(value));
+            let nonce = authorization.and_then(|value| {
+                let answer = http_auth::parse_www_authenticate([value]).pop()?;
+                let param = |name: &str| answer.param(name).unwrap_or_default().to_string();
+                let ha1 = md5_hex(format!("bob:{}:hunter2", param("realm")));
+                let ha2 = md5_hex(format!("GET:{}", param("uri")));
+                let expected = md5_hex(format!(
+                    "{}:{}:{}:{}:{}:{}",
+                    ha1, param("nonce"), param("nc"), param("cnonce"), param("qop"), ha2
+                ));
+                (param("response") == expected && param("uri") == "/wiki?page=1" && param("opaque") == "op4que")
+                    .then(|| param("nonce"))
+            });
+            match nonce.as_deref() {
+                Some("fresh") => (200, None),
+                // Correct credentials for an expired nonce: ask again with stale=true
+                Some(_) => (401, Some(challenge("fresh", true))),
+                None => (401, Some(challenge("old", false))),
+            }
+        })
+        .await;
+        let fetcher = WebFetcher::new(CubeWebEngineConfig::default());
+        let url = format!("{}/wiki?page=1", base);
+
+        fetcher.auth_cache().store(&base, Some("wiki"), credentials("bob", "hunter2"));
+        let page = fetcher.fetch_page(&url).await.unwrap();
+        assert!(page.html.contains("intranet"));
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 3);
+        assert!(fetcher.auth_cache().take_prompt(&base).is_none());
+
+        fetcher.auth_cache().store(&base, Some("wiki"), credentials("bob", "nope"));
+        let response = fetcher.fetch(&url).await.unwrap();
+        assert_eq!(response.status, 401);
+        let prompt = fetcher.auth_cache().take_prompt(&base).unwrap();
+        assert_eq!((prompt.scheme, prompt.failed), (AuthScheme::Digest, true));
+    }
+
+    #[tokio::test]
+    async fn test_device_user_agent_is_sent() {
+        use crate::services::device_emulation::{DeviceEmulationState, DeviceSelection};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // A page that sniffs the user agent server-side
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut head = Vec::new();
+                let mut chunk = [0u8; 4096];
+                while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut chunk).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => head.extend_from_slice(&chunk[..n]),
+                    }
+                }
+                let mobile = String::from_utf8_lossy(&head).lines().any(|line| {
+                    line.to_ascii_lowercase().starts_with("user-agent:") && line.contains("Mobile")
+                });
+                let body = if mobile { "<html><head></head><body>mobile site</body></html>" } else { "<html><body>desktop site</body></html>" };
+                let reply = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(reply.as_bytes()).await;
+            }
+        });
+
+        let devices = DeviceEmulationState::new();
+        devices.set("tab-1", DeviceSelection::Preset("iphone-15-pro".to_string()), None).unwrap();
+        let fetcher = WebFetcher::new(CubeWebEngineConfig::default());
+
+        let desktop = fetcher.fetch_page(&base).await.unwrap();
+        assert!(desktop.html.contains("desktop site"));
+
+        let page = fetcher
+            .clone()
+            .with_user_agent(devices.user_agent("tab-1"))
+            .fetch_page(&base)
+            .await
+            .unwrap();
+        assert!(page.html.contains("mobile site"));
+        let html = devices.apply("tab-1", &page.html);
+        assert!(html.contains("\"innerWidth\":393") && html.contains("\"devicePixelRatio\":3.0"));
+    }
 }
@@ -0,0 +1,435 @@
+// Data Source CDC - Incremental pulls driven by a cursor column
+//
+// Each pull returns rows past the stored cursor (an autoincrement id or a
+// timestamp column) and the cursor to store once the rows are handled.
+// Delivery is at-least-once: rows sharing the cursor value are tracked by
+// key so late arrivals at that value are still picked up, and skipped ids
+// are re-checked for a few runs in case they were committed late.
+// Consumers dedupe on the returned row keys.
+
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Largest id gap tracked for late commits; bigger jumps are treated as
+/// deleted ranges
+const MAX_TRACKED_GAP: i64 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorKind {
+    /// Monotonic integer id; gaps are re-checked
+    #[default]
+    Id,
+    /// Timestamp column (ISO-8601 text or epoch number)
+    Timestamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalConfig {
+    pub table: String,
+    pub cursor_column: String,
+    #[serde(default)]
+    pub cursor_kind: CursorKind,
+    /// Column identifying a row; defaults to the cursor column for id cursors
+    #[serde(default)]
+    pub key_column: Option<String>,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: u32,
+    /// Backfill starts here (inclusive); unset pulls the whole table first
+    #[serde(default)]
+    pub initial_cursor: Option<Value>,
+    /// Runs a skipped id is re-checked before it is considered deleted
+    #[serde(default = "default_gap_retries")]
+    pub gap_retries: u32,
+}
+
+fn default_batch_size() -> u32 {
+    500
+}
+
+fn default_gap_retries() -> u32 {
+    3
+}
+
+impl IncrementalConfig {
+    /// Read from the data source's `incremental` config entry
+    pub fn from_source_config(config: &HashMap<String, Value>) -> Result<Self, String> {
+        let value = config
+            .get("incremental")
+            .ok_or("Data source has no incremental sync configuration")?;
+        let parsed: Self = serde_json::from_value(value.clone())
+            .map_err(|e| format!("Invalid incremental configuration: {}", e))?;
+        parsed.validate()?;
+        Ok(parsed)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for identifier in [
+            Some(&self.table),
+            Some(&self.cursor_column),
+            self.key_column.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            validate_identifier(identifier)?;
+        }
+        if self.cursor_kind == CursorKind::Timestamp && self.key_column.is_none() {
+            return Err("key_column is required for timestamp cursors".to_string());
+        }
+        if self.batch_size == 0 {
+            return Err("batch_size must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    fn key_column(&self) -> &str {
+        self.key_column.as_deref().unwrap_or(&self.cursor_column)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncCursor {
+    /// Cursor value of the last delivered row; `None` before the first pull
+    pub value: Option<Value>,
+    /// Keys already delivered at exactly `value`
+    pub keys_at_value: Vec<String>,
+    /// Skipped ids and the re-checks left for each
+    pub gaps: BTreeMap<i64, u32>,
+    pub runs: u64,
+    pub rows_delivered: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalBatch {
+    pub rows: Vec<HashMap<String, Value>>,
+    /// Key of each row, for dedupe downstream
+    pub row_keys: Vec<String>,
+    pub columns: Vec<String>,
+    /// First pull for this source
+    pub backfill: bool,
+    /// More rows are waiting past this batch
+    pub has_more: bool,
+    /// Rows that filled previously skipped ids
+    pub recovered_gap_rows: usize,
+    pub open_gaps: Vec<i64>,
+    /// Cursor to store once the rows are handled
+    pub cursor: SyncCursor,
+}
+
+/// Pull the next batch after `cursor`. The stored cursor is left alone;
+/// the caller saves `batch.cursor` only after the pull succeeds.
+pub fn fetch_incremental(
+    conn: &Connection,
+    config: &IncrementalConfig,
+    cursor: &SyncCursor,
+) -> Result<IncrementalBatch, String> {
+    config.validate()?;
+    let table = quote(&config.table);
+    let cursor_column = quote(&config.cursor_column);
+    let key_column = quote(config.key_column());
+    let mut next = cursor.clone();
+    next.runs += 1;
+
+    // Skipped ids that have since been committed
+    let mut rows = Vec::new();
+    let mut columns = Vec::new();
+    if config.cursor_kind == CursorKind::Id && !cursor.gaps.is_empty() {
+        let placeholders = vec!["?"; cursor.gaps.len()].join(", ");
+        let sql = format!(
+            "SELECT * FROM {} WHERE {} IN ({})",
+            table, cursor_column, placeholders
+        );
+        let (gap_columns, gap_rows) = query_rows(
+            conn,
+            &sql,
+            cursor.gaps.keys().map(|&id| SqlValue::Integer(id)),
+        )?;
+        columns = gap_columns;
+        for row in &gap_rows {
+            if let Some(id) = row.get(&config.cursor_column).and_then(Value::as_i64) {
+                next.gaps.remove(&id);
+            }
+        }
+        rows.extend(gap_rows);
+    }
+    let recovered_gap_rows = rows.len();
+    next.gaps.retain(|_, retries| {
+        *retries = retries.saturating_sub(1);
+        *retries > 0
+    });
+
+    // New rows; `>=` plus the delivered keys catches late rows at the same value
+    let start = cursor
+        .value
+        .clone()
+        .or_else(|| config.initial_cursor.clone());
+    let limit = config.batch_size as usize + cursor.keys_at_value.len() + 1;
+    let (sql, params) = match &start {
+        Some(start) => (
+            format!(
+                "SELECT * FROM {} WHERE {} >= ? ORDER BY {}, {} LIMIT {}",
+                table, cursor_column, cursor_column, key_column, limit
+            ),
+            vec![to_sql_value(start)?],
+        ),
+        None => (
+            format!(
+                "SELECT * FROM {} ORDER BY {}, {} LIMIT {}",
+                table, cursor_column, key_column, limit
+            ),
+            Vec::new(),
+        ),
+    };
+    let (new_columns, mut new_rows) = query_rows(conn, &sql, params)?;
+    if columns.is_empty() {
+        columns = new_columns;
+    }
+    if let Some(value) = &cursor.value {
+        new_rows.retain(|row| {
+            row.get(&config.cursor_column) != Some(value)
+                || !cursor
+                    .keys_at_value
+                    .contains(&row_key(row, config.key_column()))
+        });
+    }
+    let has_more = new_rows.len() > config.batch_size as usize;
+    new_rows.truncate(config.batch_size as usize);
+
+    if config.cursor_kind == CursorKind::Id {
+        let mut previous = cursor.value.as_ref().and_then(Value::as_i64);
+        for id in new_rows
+            .iter()
+            .filter_map(|row| row.get(&config.cursor_column).and_then(Value::as_i64))
+        {
+            if let Some(previous) = previous {
+                if id - previous > 1 && id - previous <= MAX_TRACKED_GAP {
+                    for missing in previous + 1..id {
+                        next.gaps.insert(missing, config.gap_retries);
+                    }
+                }
+            }
+            previous = Some(previous.map_or(id, |p| p.max(id)));
+        }
+    }
+
+    if let Some(last) = new_rows
+        .last()
+        .and_then(|row| row.get(&config.cursor_column))
+        .cloned()
+    {
+        if next.value.as_ref() != Some(&last) {
+            next.keys_at_value.clear();
+        }
+        let keys = new_rows
+            .iter()
+            .filter(|row| row.get(&config.cursor_column) == Some(&last))
+            .map(|row| row_key(row, config.key_column()));
+        next.keys_at_value.extend(keys);
+        next.value = Some(last);
+    }
+
+    rows.extend(new_rows);
+    next.rows_delivered += rows.len() as u64;
+    Ok(IncrementalBatch {
+        row_keys: rows
+            .iter()
+            .map(|row| row_key(row, config.key_column()))
+            .collect(),
+        rows,
+        columns,
+        backfill: cursor.runs == 0,
+        has_more,
+        recovered_gap_rows,
+        open_gaps: next.gaps.keys().copied().collect(),
+        cursor: next,
+    })
+}
+
+/// Read up to `limit` rows of a whole table, for one-off consumers such as
+/// dataset-driven automation runs
+pub fn fetch_table(
+    conn: &Connection,
+    table: &str,
+    limit: usize,
+) -> Result<(Vec<String>, Vec<HashMap<String, Value>>), String> {
+    validate_identifier(table)?;
+    query_rows(
+        conn,
+        &format!("SELECT * FROM {} LIMIT ?", quote(table)),
+        [SqlValue::Integer(limit as i64)],
+    )
+}
+
+fn validate_identifier(identifier: &str) -> Result<(), String> {
+    if identifier.is_empty()
+        || !identifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(format!("Invalid identifier: {}", identifier));
+    }
+    Ok(())
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier)
+}
+
+fn row_key(row: &HashMap<String, Value>, key_column: &str) -> String {
+    match row.get(key_column) {
+        Some(Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+        None => String::new(),
+    }
+}
+
+fn to_sql_value(value: &Value) -> Result<SqlValue, String> {
+    match value {
+        Value::Number(n) => Ok(n
+            .as_i64()
+            .map(SqlValue::Integer)
+            .unwrap_or_else(|| SqlValue::Real(n.as_f64().unwrap_or_default()))),
+        Value::String(s) => Ok(SqlValue::Text(s.clone())),
+        other => Err(format!("Unsupported cursor value: {}", other)),
+    }
+}
+
+fn query_rows(
+    conn: &Connection,
+    sql: &str,
+    params: impl IntoIterator<Item = SqlValue>,
+) -> Result<(Vec<String>, Vec<HashMap<String, Value>>), String> {
+    let mut stmt = conn
+        .prepare(sql)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+    let rows = stmt
+        .query_map(params_from_iter(params), |row| {
+            let mut map = HashMap::new();
+            for (i, column) in columns.iter().enumerate() {
+                let value = match row.get_ref(i)? {
+                    ValueRef::Null => Value::Null,
+                    ValueRef::Integer(n) => Value::from(n),
+                    ValueRef::Real(f) => Value::from(f),
+                    ValueRef::Text(t) => Value::String(String::from_utf8_lossy(t).into_owned()),
+                    ValueRef::Blob(b) => Value::String(base64::Engine::encode(
+                        &base64::engine::general_purpose::STANDARD,
+                        b,
+                    )),
+                };
+                map.insert(column.clone(), value);
+            }
+            Ok(map)
+        })
+        .map_err(|e| format!("Query failed: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read row: {}", e))?;
+    Ok((columns, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE orders (id INTEGER PRIMARY KEY, total REAL, updated_at TEXT);
+             INSERT INTO orders VALUES (1, 10.0, '2026-01-01T10:00:00Z'), (2, 20.0, '2026-01-01T11:00:00Z'),
+                                       (3, 30.0, '2026-01-01T11:00:00Z');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn ids(batch: &IncrementalBatch) -> Vec<i64> {
+        batch
+            .rows
+            .iter()
+            .map(|row| row["id"].as_i64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_incremental_pull_returns_only_new_rows_and_advances_cursor() {
+        let conn = seeded();
+        let config: IncrementalConfig = serde_json::from_value(
+            serde_json::json!({ "table": "orders", "cursor_column": "id", "batch_size": 2 }),
+        )
+        .unwrap();
+
+        // Backfill in batches
+        let first = fetch_incremental(&conn, &config, &SyncCursor::default()).unwrap();
+        assert!(first.backfill && first.has_more);
+        assert_eq!(ids(&first), [1, 2]);
+        let second = fetch_incremental(&conn, &config, &first.cursor).unwrap();
+        assert_eq!(ids(&second), [3]);
+        assert!(!second.has_more);
+        assert_eq!(second.cursor.value, Some(Value::from(3)));
+
+        // Nothing new: nothing returned, cursor stays put
+        let idle = fetch_incremental(&conn, &config, &second.cursor).unwrap();
+        assert!(idle.rows.is_empty());
+        assert_eq!(idle.cursor.value, second.cursor.value);
+
+        // Id 5 commits before id 4; 4 is picked up on a later run
+        conn.execute(
+            "INSERT INTO orders VALUES (5, 50.0, '2026-01-02T09:00:00Z')",
+            [],
+        )
+        .unwrap();
+        let run = fetch_incremental(&conn, &config, &idle.cursor).unwrap();
+        assert_eq!(ids(&run), [5]);
+        assert_eq!(run.open_gaps, [4]);
+        conn.execute(
+            "INSERT INTO orders VALUES (4, 40.0, '2026-01-02T08:00:00Z')",
+            [],
+        )
+        .unwrap();
+        let recovered = fetch_incremental(&conn, &config, &run.cursor).unwrap();
+        assert_eq!(ids(&recovered), [4]);
+        assert_eq!(recovered.recovered_gap_rows, 1);
+        assert!(recovered.open_gaps.is_empty());
+        assert_eq!(recovered.cursor.value, Some(Value::from(5)));
+        assert_eq!(recovered.cursor.rows_delivered, 5);
+    }
+
+    #[test]
+    fn test_timestamp_cursor_picks_up_late_rows_at_the_same_timestamp() {
+        let conn = seeded();
+        let config: IncrementalConfig = serde_json::from_value(serde_json::json!({
+            "table": "orders",
+            "cursor_column": "updated_at",
+            "cursor_kind": "timestamp",
+            "key_column": "id",
+            "initial_cursor": "2026-01-01T11:00:00Z",
+        }))
+        .unwrap();
+
+        let first = fetch_incremental(&conn, &config, &SyncCursor::default()).unwrap();
+        assert_eq!(ids(&first), [2, 3]);
+        assert_eq!(first.row_keys, ["2", "3"]);
+
+        conn.execute(
+            "INSERT INTO orders VALUES (6, 60.0, '2026-01-01T11:00:00Z')",
+            [],
+        )
+        .unwrap();
+        let second = fetch_incremental(&conn, &config, &first.cursor).unwrap();
+        assert_eq!(ids(&second), [6]);
+        assert_eq!(second.cursor.keys_at_value, ["2", "3", "6"]);
+
+        assert!(
+            serde_json::from_value::<IncrementalConfig>(serde_json::json!({
+                "table": "orders; DROP TABLE orders", "cursor_column": "id"
+            }))
+            .unwrap()
+            .validate()
+            .is_err()
+        );
+    }
+}
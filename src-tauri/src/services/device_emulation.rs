@@ -0,0 +1,321 @@
+// Device emulation for the CUBE Web Engine
+//
+// A tab can pretend to be a phone or tablet: the frontend sizes the page frame
+// to the emulated viewport, the engine fetches with the device's user agent, and
+// a prelude script reports the device's metrics, touch support and pointer type
+// to page scripts before any of them run.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+static HEAD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<head\b[^>]*>").unwrap());
+
+const IOS_UA: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 \
+(KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1";
+const IPADOS_UA: &str = "Mozilla/5.0 (iPad; CPU OS 17_5 like Mac OS X) AppleWebKit/605.1.15 \
+(KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1";
+const PIXEL_UA: &str = "Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 \
+(KHTML, like Gecko) Chrome/126.0.0.0 Mobile Safari/537.36";
+const GALAXY_UA: &str = "Mozilla/5.0 (Linux; Android 14; SM-S911B) AppleWebKit/537.36 \
+(KHTML, like Gecko) Chrome/126.0.0.0 Mobile Safari/537.36";
+const GALAXY_TAB_UA: &str = "Mozilla/5.0 (Linux; Android 14; SM-X710) AppleWebKit/537.36 \
+(KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceProfile {
+    pub id: String,
+    pub name: String,
+    /// CSS pixels in portrait orientation
+    pub width: u32,
+    pub height: u32,
+    pub device_pixel_ratio: f64,
+    pub touch: bool,
+    pub mobile: bool,
+    pub user_agent: String,
+}
+
+impl DeviceProfile {
+    fn preset(id: &str, name: &str, width: u32, height: u32, dpr: f64, user_agent: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            width,
+            height,
+            device_pixel_ratio: dpr,
+            touch: true,
+            mobile: user_agent.contains("Mobile"),
+            user_agent: user_agent.to_string(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(1..=10_000).contains(&self.width) || !(1..=10_000).contains(&self.height) {
+            return Err("Viewport width and height must be between 1 and 10000".to_string());
+        }
+        if !(0.5..=5.0).contains(&self.device_pixel_ratio) {
+            return Err("Device pixel ratio must be between 0.5 and 5".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Common phones and tablets
+pub fn device_presets() -> Vec<DeviceProfile> {
+    vec![
+        DeviceProfile::preset("iphone-15-pro", "iPhone 15 Pro", 393, 852, 3.0, IOS_UA),
+        DeviceProfile::preset("iphone-15-pro-max", "iPhone 15 Pro Max", 430, 932, 3.0, IOS_UA),
+        DeviceProfile::preset("iphone-se", "iPhone SE", 375, 667, 2.0, IOS_UA),
+        DeviceProfile::preset("pixel-8", "Pixel 8", 412, 915, 2.625, PIXEL_UA),
+        DeviceProfile::preset("galaxy-s23", "Galaxy S23", 360, 780, 3.0, GALAXY_UA),
+        DeviceProfile::preset("ipad-air", "iPad Air", 820, 1180, 2.0, IPADOS_UA),
+        DeviceProfile::preset("ipad-pro-12", "iPad Pro 12.9\"", 1024, 1366, 2.0, IPADOS_UA),
+        DeviceProfile::preset("galaxy-tab-s9", "Galaxy Tab S9", 800, 1280, 2.0, GALAXY_TAB_UA),
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+/// A preset id or a fully specified device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DeviceSelection {
+    Preset(String),
+    Custom(DeviceProfile),
+}
+
+impl DeviceSelection {
+    fn resolve(self) -> Result<DeviceProfile, String> {
+        let profile = match self {
+            Self::Preset(id) => device_presets()
+                .into_iter()
+                .find(|p| p.id.eq_ignore_ascii_case(&id) || p.name.eq_ignore_ascii_case(&id))
+                .ok_or_else(|| format!("Unknown device preset: {}", id))?,
+            Self::Custom(profile) => profile,
+        };
+        profile.validate()?;
+        Ok(profile)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Viewport {
+    pub width: u32,
+    pub height: u32,
+    pub device_pixel_ratio: f64,
+}
+
+impl Viewport {
+    /// Size of a screenshot of this viewport in physical pixels
+    pub fn physical_size(&self) -> (u32, u32) {
+        let scale = |css: u32| (f64::from(css) * self.device_pixel_ratio).round() as u32;
+        (scale(self.width), scale(self.height))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceEmulation {
+    pub device: DeviceProfile,
+    pub orientation: Orientation,
+}
+
+impl DeviceEmulation {
+    pub fn viewport(&self) -> Viewport {
+        let (width, height) = match self.orientation {
+            Orientation::Portrait => (self.device.width, self.device.height),
+            Orientation::Landscape => (self.device.height, self.device.width),
+        };
+        Viewport { width, height, device_pixel_ratio: self.device.device_pixel_ratio }
+    }
+
+    /// Insert the prelude as the first thing in `<head>` so it runs before page scripts
+    pub fn apply_to_html(&self, html: &str) -> String {
+        let prelude = format!(
+            "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+             <script id=\"cube-device-emulation\">{}</script>",
+            self.script()
+        );
+        match HEAD_RE.find(html) {
+            Some(head) => format!("{}{}{}", &html[..head.end()], prelude, &html[head.end()..]),
+            None => format!("{}{}", prelude, html),
+        }
+    }
+
+    /// Values the page should observe, as the prelude script defines them
+    pub fn page_metrics(&self) -> serde_json::Value {
+        let viewport = self.viewport();
+        let landscape = self.orientation == Orientation::Landscape;
+        serde_json::json!({
+            "innerWidth": viewport.width,
+            "innerHeight": viewport.height,
+            "devicePixelRatio": viewport.device_pixel_ratio,
+            "maxTouchPoints": if self.device.touch { 5 } else { 0 },
+            "touch": self.device.touch,
+            "userAgent": self.device.user_agent,
+            "platform": platform_for(&self.device.user_agent),
+            "orientationType": if landscape { "landscape-primary" } else { "portrait-primary" },
+            "orientationAngle": if landscape { 90 } else { 0 },
+        })
+    }
+
+    pub fn script(&self) -> String {
+        format!(
+            r#"(function(){{var d={metrics},w=window,n=navigator;
+function def(o,k,v){{try{{Object.defineProperty(o,k,{{get:function(){{return v;}},configurable:true}});}}catch(e){{}}}}
+["innerWidth","outerWidth"].forEach(function(k){{def(w,k,d.innerWidth);}});
+["innerHeight","outerHeight"].forEach(function(k){{def(w,k,d.innerHeight);}});
+def(w,"devicePixelRatio",d.devicePixelRatio);
+["width","availWidth"].forEach(function(k){{def(w.screen,k,d.innerWidth);}});
+["height","availHeight"].forEach(function(k){{def(w.screen,k,d.innerHeight);}});
+if(w.screen.orientation){{def(w.screen.orientation,"type",d.orientationType);def(w.screen.orientation,"angle",d.orientationAngle);}}
+def(w,"orientation",d.orientationAngle);
+def(n,"userAgent",d.userAgent);def(n,"appVersion",d.userAgent.replace(/^Mozilla\//,""));def(n,"platform",d.platform);
+def(n,"maxTouchPoints",d.maxTouchPoints);
+if(d.touch){{if(!("ontouchstart" in w)){{w.ontouchstart=null;}}if(!("ontouchstart" in document)){{document.ontouchstart=null;}}
+if(!w.TouchEvent){{w.TouchEvent=function TouchEvent(t,i){{return new UIEvent(t,i);}};}}}}
+var mm=w.matchMedia.bind(w),coarse=d.touch?"(min-width: 0px)":"((max-width: 0px) and (min-width: 1px))",fine=d.touch?"((max-width: 0px) and (min-width: 1px))":"(min-width: 0px)";
+w.matchMedia=function(q){{var r=String(q).replace(/\(\s*(any-)?pointer\s*:\s*coarse\s*\)/gi,coarse).replace(/\(\s*(any-)?pointer\s*:\s*fine\s*\)/gi,fine)
+.replace(/\(\s*(any-)?hover\s*:\s*none\s*\)/gi,coarse).replace(/\(\s*(any-)?hover\s*:\s*hover\s*\)/gi,fine);var m=mm(r);
+try{{Object.defineProperty(m,"media",{{value:String(q)}});}}catch(e){{}}return m;}};
+}})();"#,
+            metrics = self.page_metrics(),
+        )
+    }
+}
+
+fn platform_for(user_agent: &str) -> &'static str {
+    if user_agent.contains("iPhone") {
+        "iPhone"
+    } else if user_agent.contains("iPad") {
+        "iPad"
+    } else if user_agent.contains("Android") {
+        "Linux armv8l"
+    } else {
+        "Win32"
+    }
+}
+
+/// Device emulation per tab
+#[derive(Default)]
+pub struct DeviceEmulationState {
+    tabs: RwLock<HashMap<String, DeviceEmulation>>,
+}
+
+impl DeviceEmulationState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, tab_id: &str) -> Option<DeviceEmulation> {
+        self.tabs.read().ok()?.get(tab_id).cloned()
+    }
+
+    /// Emulate a device in a tab. Keeps the current orientation unless one is given.
+    pub fn set(
+        &self,
+        tab_id: &str,
+        selection: DeviceSelection,
+        orientation: Option<Orientation>,
+    ) -> Result<DeviceEmulation, String> {
+        let device = selection.resolve()?;
+        let mut tabs = self.tabs.write().map_err(|e| format!("Lock error: {}", e))?;
+        let orientation = orientation
+            .or_else(|| tabs.get(tab_id).map(|current| current.orientation))
+            .unwrap_or_default();
+        let emulation = DeviceEmulation { device, orientation };
+        tabs.insert(tab_id.to_string(), emulation.clone());
+        Ok(emulation)
+    }
+
+    pub fn clear(&self, tab_id: &str) {
+        if let Ok(mut tabs) = self.tabs.write() {
+            tabs.remove(tab_id);
+        }
+    }
+
+    pub fn toggle_orientation(&self, tab_id: &str) -> Result<DeviceEmulation, String> {
+        let mut tabs = self.tabs.write().map_err(|e| format!("Lock error: {}", e))?;
+        let emulation = tabs
+            .get_mut(tab_id)
+            .ok_or_else(|| "No device is emulated in this tab".to_string())?;
+        emulation.orientation = match emulation.orientation {
+            Orientation::Portrait => Orientation::Landscape,
+            Orientation::Landscape => Orientation::Portrait,
+        };
+        Ok(emulation.clone())
+    }
+
+    pub fn user_agent(&self, tab_id: &str) -> Option<String> {
+        self.get(tab_id).map(|e| e.device.user_agent)
+    }
+
+    pub fn apply(&self, tab_id: &str, html: &str) -> String {
+        match self.get(tab_id) {
+            Some(emulation) => emulation.apply_to_html(html),
+            None => html.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iphone_preset_metrics_and_orientation() {
+        let state = DeviceEmulationState::new();
+        let emulation = state
+            .set("tab-1", DeviceSelection::Preset("iphone-15-pro".to_string()), None)
+            .unwrap();
+
+        let metrics = emulation.page_metrics();
+        assert_eq!(metrics["innerWidth"], 393);
+        assert_eq!(metrics["innerHeight"], 852);
+        assert_eq!(metrics["devicePixelRatio"], 3.0);
+        assert_eq!(metrics["maxTouchPoints"], 5);
+        assert_eq!(metrics["touch"], true);
+        assert!(metrics["userAgent"].as_str().unwrap().contains("iPhone"));
+        assert_eq!(emulation.viewport().physical_size(), (1179, 2556));
+
+        let html = state.apply("tab-1", "<html><head><script>sniff()</script></head></html>");
+        let prelude = html.find("cube-device-emulation").unwrap();
+        assert!(prelude < html.find("sniff()").unwrap());
+        assert!(html.contains("\"innerWidth\":393"));
+
+        let rotated = state.toggle_orientation("tab-1").unwrap();
+        assert_eq!(rotated.viewport(), Viewport { width: 852, height: 393, device_pixel_ratio: 3.0 });
+        assert_eq!(rotated.page_metrics()["orientationType"], "landscape-primary");
+        // Switching device keeps the orientation
+        let ipad = state.set("tab-1", DeviceSelection::Preset("iPad Air".to_string()), None).unwrap();
+        assert_eq!(ipad.viewport().width, 1180);
+
+        let custom = DeviceProfile {
+            id: "custom".to_string(),
+            name: "Kiosk".to_string(),
+            width: 1080,
+            height: 1920,
+            device_pixel_ratio: 1.0,
+            touch: false,
+            mobile: false,
+            user_agent: "Kiosk/1.0".to_string(),
+        };
+        let kiosk = state.set("tab-2", DeviceSelection::Custom(custom.clone()), Some(Orientation::Portrait)).unwrap();
+        assert_eq!(kiosk.page_metrics()["maxTouchPoints"], 0);
+        assert!(state
+            .set("tab-2", DeviceSelection::Custom(DeviceProfile { width: 0, ..custom }), None)
+            .is_err());
+        assert!(state.set("tab-3", DeviceSelection::Preset("nokia-3310".to_string()), None).is_err());
+        assert!(state.toggle_orientation("tab-3").is_err());
+    }
+}
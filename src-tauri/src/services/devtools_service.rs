@@ -1,9 +1,11 @@
 // DevTools Service - Browser Developer Tools Integration
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::{Arc, Mutex};
 
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConsoleMessage {
     #[serde(rename = "type")]
@@ -33,9 +35,89 @@ pub struct DomElement {
     pub children: Vec<DomElement>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    #[serde(default = "default_cookie_path")]
+    pub path: String,
+    /// Unix seconds; `None` is a session cookie
+    #[serde(default)]
+    pub expires: Option<i64>,
+    #[serde(default)]
+    pub http_only: bool,
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default)]
+    pub same_site: Option<String>,
+    #[serde(default)]
+    pub partitioned: bool,
+}
+
+fn default_cookie_path() -> String {
+    "/".to_string()
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageArea {
+    Local,
+    Session,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedDbObjectStore {
+    pub name: String,
+    pub key_path: Option<String>,
+    pub auto_increment: bool,
+    pub indexes: Vec<String>,
+    pub record_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedDbDatabase {
+    pub name: String,
+    pub version: u64,
+    pub object_stores: Vec<IndexedDbObjectStore>,
+}
+
+/// Which storage partition a tab reads and writes: the active profile plus
+/// the page's origin (scheme://host:port).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct StoragePartition {
+    pub profile_id: String,
+    pub origin: String,
+}
+
+#[derive(Debug, Clone)]
+struct TabStorageContext {
+    partition: StoragePartition,
+    host: String,
+    path: String,
+    secure: bool,
+}
+
+#[derive(Debug, Default)]
+struct OriginStorage {
+    local: BTreeMap<String, String>,
+    indexed_db: Vec<IndexedDbDatabase>,
+}
+
 pub struct DevToolsService {
     console_messages: Arc<Mutex<HashMap<String, Vec<ConsoleMessage>>>>,
     network_requests: Arc<Mutex<HashMap<String, Vec<NetworkRequest>>>>,
+    active_profile: Mutex<String>,
+    tab_contexts: Mutex<HashMap<String, TabStorageContext>>,
+    origin_storage: Mutex<HashMap<StoragePartition, OriginStorage>>,
+    /// sessionStorage lives per tab and per origin
+    session_storage: Mutex<HashMap<(String, String), BTreeMap<String, String>>>,
+    /// One cookie jar per profile
+    cookie_jars: Mutex<HashMap<String, Vec<StorageCookie>>>,
 }
 
 impl DevToolsService {
@@ -43,6 +125,11 @@ impl DevToolsService {
         Self {
             console_messages: Arc::new(Mutex::new(HashMap::new())),
             network_requests: Arc::new(Mutex::new(HashMap::new())),
+            active_profile: Mutex::new(DEFAULT_PROFILE_ID.to_string()),
+            tab_contexts: Mutex::new(HashMap::new()),
+            origin_storage: Mutex::new(HashMap::new()),
+            session_storage: Mutex::new(HashMap::new()),
+            cookie_jars: Mutex::new(HashMap::new()),
         }
     }
 
@@ -114,14 +201,259 @@ impl DevToolsService {
         })
     }
 
-    pub fn get_local_storage(&self, _tab_id: &str) -> Result<HashMap<String, String>> {
-        // Mock implementation - would need browser integration
-        Ok(HashMap::new())
+    pub fn get_local_storage(&self, tab_id: &str) -> Result<HashMap<String, String>> {
+        let Some(context) = self.tab_contexts.lock().unwrap().get(tab_id).cloned() else {
+            return Ok(HashMap::new());
+        };
+        let storage = self.origin_storage.lock().unwrap();
+        Ok(storage
+            .get(&context.partition)
+            .map(|s| s.local.clone().into_iter().collect())
+            .unwrap_or_default())
     }
 
-    pub fn get_session_storage(&self, _tab_id: &str) -> Result<HashMap<String, String>> {
-        // Mock implementation - would need browser integration
-        Ok(HashMap::new())
+    pub fn get_session_storage(&self, tab_id: &str) -> Result<HashMap<String, String>> {
+        let Some(context) = self.tab_contexts.lock().unwrap().get(tab_id).cloned() else {
+            return Ok(HashMap::new());
+        };
+        let key = (tab_id.to_string(), context.partition.origin);
+        Ok(self
+            .session_storage
+            .lock()
+            .unwrap()
+            .get(&key)
+            .map(|s| s.clone().into_iter().collect())
+            .unwrap_or_default())
+    }
+
+    // ==================== Storage Panels ====================
+
+    pub fn active_profile(&self) -> String {
+        self.active_profile.lock().unwrap().clone()
+    }
+
+    pub fn set_active_profile(&self, profile_id: &str) -> Result<()> {
+        if profile_id.trim().is_empty() {
+            bail!("Profile id cannot be empty");
+        }
+        *self.active_profile.lock().unwrap() = profile_id.to_string();
+        Ok(())
+    }
+
+    /// Binds a tab to the storage partition of the page it shows. Called on
+    /// navigation so storage edits always land in the tab's own origin.
+    pub fn attach_tab(&self, tab_id: &str, url: &str, profile_id: Option<&str>) -> Result<StoragePartition> {
+        let parsed = url::Url::parse(url).map_err(|e| anyhow!("Invalid page URL {}: {}", url, e))?;
+        let origin = parsed.origin();
+        if !origin.is_tuple() {
+            bail!("Pages with an opaque origin ({}) have no storage", url);
+        }
+
+        let partition = StoragePartition {
+            profile_id: profile_id.map(String::from).unwrap_or_else(|| self.active_profile()),
+            origin: origin.ascii_serialization(),
+        };
+        let context = TabStorageContext {
+            partition: partition.clone(),
+            host: parsed.host_str().unwrap_or_default().to_lowercase(),
+            path: parsed.path().to_string(),
+            secure: parsed.scheme() == "https",
+        };
+        self.tab_contexts.lock().unwrap().insert(tab_id.to_string(), context);
+        Ok(partition)
+    }
+
+    pub fn detach_tab(&self, tab_id: &str) {
+        self.tab_contexts.lock().unwrap().remove(tab_id);
+        self.session_storage.lock().unwrap().retain(|(tab, _), _| tab != tab_id);
+    }
+
+    fn tab_context(&self, tab_id: &str) -> Result<TabStorageContext> {
+        self.tab_contexts
+            .lock()
+            .unwrap()
+            .get(tab_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Tab {} has no storage context; attach it first", tab_id))
+    }
+
+    pub fn set_storage_item(&self, tab_id: &str, area: StorageArea, key: &str, value: &str) -> Result<()> {
+        let context = self.tab_context(tab_id)?;
+        match area {
+            StorageArea::Local => {
+                self.origin_storage
+                    .lock()
+                    .unwrap()
+                    .entry(context.partition)
+                    .or_default()
+                    .local
+                    .insert(key.to_string(), value.to_string());
+            }
+            StorageArea::Session => {
+                self.session_storage
+                    .lock()
+                    .unwrap()
+                    .entry((tab_id.to_string(), context.partition.origin))
+                    .or_default()
+                    .insert(key.to_string(), value.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn delete_storage_item(&self, tab_id: &str, area: StorageArea, key: &str) -> Result<bool> {
+        let context = self.tab_context(tab_id)?;
+        let removed = match area {
+            StorageArea::Local => self
+                .origin_storage
+                .lock()
+                .unwrap()
+                .get_mut(&context.partition)
+                .and_then(|s| s.local.remove(key)),
+            StorageArea::Session => self
+                .session_storage
+                .lock()
+                .unwrap()
+                .get_mut(&(tab_id.to_string(), context.partition.origin))
+                .and_then(|s| s.remove(key)),
+        };
+        Ok(removed.is_some())
+    }
+
+    pub fn clear_storage(&self, tab_id: &str, area: StorageArea) -> Result<()> {
+        let context = self.tab_context(tab_id)?;
+        match area {
+            StorageArea::Local => {
+                if let Some(storage) = self.origin_storage.lock().unwrap().get_mut(&context.partition) {
+                    storage.local.clear();
+                }
+            }
+            StorageArea::Session => {
+                self.session_storage
+                    .lock()
+                    .unwrap()
+                    .remove(&(tab_id.to_string(), context.partition.origin));
+            }
+        }
+        Ok(())
+    }
+
+    /// All cookies sent with requests for the tab's URL, HttpOnly included.
+    pub fn list_cookies(&self, tab_id: &str) -> Result<Vec<StorageCookie>> {
+        let context = self.tab_context(tab_id)?;
+        let now = chrono::Utc::now().timestamp();
+        let jars = self.cookie_jars.lock().unwrap();
+        Ok(jars
+            .get(&context.partition.profile_id)
+            .map(|jar| {
+                jar.iter()
+                    .filter(|c| cookie_matches(c, &context, now))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Adds or replaces a cookie (keyed by name, domain and path). The cookie's
+    /// domain must cover the tab's host so one site cannot plant cookies for another.
+    pub fn set_cookie(&self, tab_id: &str, mut cookie: StorageCookie) -> Result<StorageCookie> {
+        let context = self.tab_context(tab_id)?;
+        if cookie.name.trim().is_empty() {
+            bail!("Cookie name cannot be empty");
+        }
+        if cookie.domain.trim().is_empty() {
+            cookie.domain = context.host.clone();
+        }
+        cookie.domain = cookie.domain.to_lowercase();
+        if !domain_matches(&context.host, &cookie.domain) {
+            bail!("Cookie domain {} does not match the tab's host {}", cookie.domain, context.host);
+        }
+        if !cookie.path.starts_with('/') {
+            cookie.path = format!("/{}", cookie.path);
+        }
+        if cookie.name.starts_with("__Secure-") || cookie.name.starts_with("__Host-") {
+            cookie.secure = true;
+        }
+        if cookie.name.starts_with("__Host-") && (cookie.domain.starts_with('.') || cookie.path != "/") {
+            bail!("__Host- cookies must be host-only with path /");
+        }
+        if cookie.same_site.as_deref().map(|s| s.eq_ignore_ascii_case("none")).unwrap_or(false) && !cookie.secure {
+            bail!("SameSite=None cookies must be Secure");
+        }
+
+        let mut jars = self.cookie_jars.lock().unwrap();
+        let jar = jars.entry(context.partition.profile_id).or_default();
+        jar.retain(|c| !(c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path));
+        let expired = cookie.expires.map(|e| e <= chrono::Utc::now().timestamp()).unwrap_or(false);
+        if !expired {
+            jar.push(cookie.clone());
+        }
+        Ok(cookie)
+    }
+
+    pub fn delete_cookie(&self, tab_id: &str, name: &str, domain: &str, path: &str) -> Result<bool> {
+        let context = self.tab_context(tab_id)?;
+        let domain = domain.to_lowercase();
+        if !domain_matches(&context.host, &domain) {
+            bail!("Cookie domain {} does not match the tab's host {}", domain, context.host);
+        }
+
+        let mut jars = self.cookie_jars.lock().unwrap();
+        let Some(jar) = jars.get_mut(&context.partition.profile_id) else {
+            return Ok(false);
+        };
+        let before = jar.len();
+        jar.retain(|c| !(c.name == name && c.domain == domain && c.path == path));
+        Ok(jar.len() != before)
+    }
+
+    /// What `document.cookie` returns in the tab: matching, unexpired, non-HttpOnly cookies.
+    pub fn document_cookie(&self, tab_id: &str) -> Result<String> {
+        Ok(self
+            .list_cookies(tab_id)?
+            .into_iter()
+            .filter(|c| !c.http_only)
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect::<Vec<_>>()
+            .join("; "))
+    }
+
+    /// Records the databases a page has open, as reported by the page's
+    /// `indexedDB.databases()` probe.
+    pub fn report_indexeddb(&self, tab_id: &str, database: IndexedDbDatabase) -> Result<()> {
+        let context = self.tab_context(tab_id)?;
+        let mut storage = self.origin_storage.lock().unwrap();
+        let databases = &mut storage.entry(context.partition).or_default().indexed_db;
+        databases.retain(|d| d.name != database.name);
+        databases.push(database);
+        databases.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(())
+    }
+
+    pub fn list_indexeddb(&self, origin: &str, profile_id: Option<&str>) -> Result<Vec<IndexedDbDatabase>> {
+        let parsed = url::Url::parse(origin).map_err(|e| anyhow!("Invalid origin {}: {}", origin, e))?;
+        let partition = StoragePartition {
+            profile_id: profile_id.map(String::from).unwrap_or_else(|| self.active_profile()),
+            origin: parsed.origin().ascii_serialization(),
+        };
+        Ok(self
+            .origin_storage
+            .lock()
+            .unwrap()
+            .get(&partition)
+            .map(|s| s.indexed_db.clone())
+            .unwrap_or_default())
+    }
+
+    pub fn delete_indexeddb(&self, tab_id: &str, name: &str) -> Result<bool> {
+        let context = self.tab_context(tab_id)?;
+        let mut storage = self.origin_storage.lock().unwrap();
+        let Some(origin) = storage.get_mut(&context.partition) else {
+            return Ok(false);
+        };
+        let before = origin.indexed_db.len();
+        origin.indexed_db.retain(|d| d.name != name);
+        Ok(origin.indexed_db.len() != before)
     }
 
     pub fn execute_script(&self, tab_id: &str, script: &str) -> Result<serde_json::Value> {
@@ -156,6 +488,31 @@ impl DevToolsService {
     }
 }
 
+/// RFC 6265 domain-match: exact host, or a subdomain of a `.domain` cookie.
+fn domain_matches(host: &str, cookie_domain: &str) -> bool {
+    let domain = cookie_domain.trim_start_matches('.');
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+fn cookie_matches(cookie: &StorageCookie, context: &TabStorageContext, now: i64) -> bool {
+    let host_only = !cookie.domain.starts_with('.');
+    let domain_ok = if host_only {
+        context.host == cookie.domain
+    } else {
+        domain_matches(&context.host, &cookie.domain)
+    };
+    domain_ok
+        && path_matches(&context.path, &cookie.path)
+        && (!cookie.secure || context.secure)
+        && cookie.expires.map(|e| e > now).unwrap_or(true)
+}
+
 impl Default for DevToolsService {
     fn default() -> Self {
         Self::new()
@@ -255,3 +612,63 @@ fn simple_math_eval(expr: &str) -> Option<f64> {
     
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookie(name: &str, domain: &str, http_only: bool) -> StorageCookie {
+        StorageCookie {
+            name: name.to_string(),
+            value: format!("{}-value", name),
+            domain: domain.to_string(),
+            path: "/".to_string(),
+            expires: None,
+            http_only,
+            secure: false,
+            same_site: None,
+            partitioned: false,
+        }
+    }
+
+    #[test]
+    fn test_local_storage_edit_is_seen_on_next_read_and_isolated_by_origin() {
+        let devtools = DevToolsService::new();
+        devtools.attach_tab("tab-a", "https://shop.example.com/cart", None).unwrap();
+        devtools.attach_tab("tab-b", "https://shop.example.com/", None).unwrap();
+        devtools.attach_tab("tab-other", "https://evil.test/", None).unwrap();
+        devtools.attach_tab("tab-work", "https://shop.example.com/", Some("work")).unwrap();
+
+        devtools.set_storage_item("tab-a", StorageArea::Local, "theme", "light").unwrap();
+        devtools.set_storage_item("tab-a", StorageArea::Local, "theme", "dark").unwrap();
+        devtools.set_storage_item("tab-a", StorageArea::Session, "step", "2").unwrap();
+
+        // Same origin and profile share localStorage; sessionStorage stays per tab
+        assert_eq!(devtools.get_local_storage("tab-b").unwrap().get("theme").map(String::as_str), Some("dark"));
+        assert!(devtools.get_session_storage("tab-b").unwrap().is_empty());
+        assert_eq!(devtools.get_session_storage("tab-a").unwrap().get("step").map(String::as_str), Some("2"));
+        assert!(devtools.get_local_storage("tab-other").unwrap().is_empty());
+        assert!(devtools.get_local_storage("tab-work").unwrap().is_empty());
+
+        assert!(devtools.delete_storage_item("tab-b", StorageArea::Local, "theme").unwrap());
+        assert!(devtools.get_local_storage("tab-a").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_deleted_cookie_disappears_from_document_cookie() {
+        let devtools = DevToolsService::new();
+        devtools.attach_tab("tab", "https://app.example.com/", None).unwrap();
+
+        devtools.set_cookie("tab", cookie("sid", ".example.com", true)).unwrap();
+        devtools.set_cookie("tab", cookie("pref", "app.example.com", false)).unwrap();
+        devtools.set_cookie("tab", cookie("cart", "app.example.com", false)).unwrap();
+        assert!(devtools.set_cookie("tab", cookie("x", "other.com", false)).is_err());
+
+        assert_eq!(devtools.list_cookies("tab").unwrap().len(), 3);
+        assert_eq!(devtools.document_cookie("tab").unwrap(), "pref=pref-value; cart=cart-value");
+
+        assert!(devtools.delete_cookie("tab", "cart", "app.example.com", "/").unwrap());
+        assert_eq!(devtools.document_cookie("tab").unwrap(), "pref=pref-value");
+        assert!(!devtools.delete_cookie("tab", "cart", "app.example.com", "/").unwrap());
+    }
+}
@@ -147,24 +147,16 @@ impl DocumentExtractor {
     async fn extract_from_spreadsheet(&self, path: &Path) -> Result<ExtractedData> {
         info!("📊 Extracting from spreadsheet: {:?}", path);
 
-        use calamine::{open_workbook, Reader, Xls, Xlsx};
+        use calamine::{open_workbook, Reader, Xls};
 
         let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
 
         let text = match extension {
+            // Every sheet, streamed, with cached formula results
             "xlsx" => {
-                let mut workbook: Xlsx<_> = open_workbook(path)?;
-                let mut content = String::new();
-
-                if let Some(Ok(range)) = workbook.worksheet_range_at(0) {
-                    for row in range.rows() {
-                        for cell in row {
-                            content.push_str(&format!("{}\t", cell));
-                        }
-                        content.push('\n');
-                    }
-                }
-                content
+                crate::document::extract_office_file(path, crate::document::DocumentType::XLSX)
+                    .map_err(|e| anyhow!(e))?
+                    .text
             }
             "xls" => {
                 let mut workbook: Xls<_> = open_workbook(path)?;
@@ -194,43 +186,13 @@ impl DocumentExtractor {
         })
     }
 
-    /// Extract from Word document (DOCX) - Manual ZIP parsing
+    /// Extract from Word document (DOCX): paragraphs, headings and tables
     async fn extract_from_word(&self, path: &Path) -> Result<ExtractedData> {
         info!("📝 Extracting from Word document: {:?}", path);
 
-        use std::fs::File;
-        use std::io::Read;
-        use zip::ZipArchive;
-
-        // DOCX is a ZIP file containing XML documents
-        // We need to extract word/document.xml and parse the text
-
-        let file = File::open(path).map_err(|e| anyhow!("Failed to open DOCX: {}", e))?;
-
-        let mut archive =
-            ZipArchive::new(file).map_err(|e| anyhow!("Failed to read DOCX as ZIP: {}", e))?;
-
-        // Try to find document.xml (main content)
-        let mut document_xml = String::new();
-
-        for i in 0..archive.len() {
-            let mut file = archive
-                .by_index(i)
-                .map_err(|e| anyhow!("Failed to access ZIP entry: {}", e))?;
-
-            if file.name() == "word/document.xml" {
-                file.read_to_string(&mut document_xml)
-                    .map_err(|e| anyhow!("Failed to read document.xml: {}", e))?;
-                break;
-            }
-        }
-
-        if document_xml.is_empty() {
-            return Err(anyhow!("No document.xml found in DOCX file"));
-        }
-
-        // Parse XML and extract text between <w:t> tags
-        let text = self.extract_text_from_docx_xml(&document_xml);
+        let doc = crate::document::extract_office_file(path, crate::document::DocumentType::DOCX)
+            .map_err(|e| anyhow!(e))?;
+        let text = doc.text;
 
         info!("✅ Extracted {} characters from DOCX", text.len());
         let fields = self.parse_text_to_fields(&text);
@@ -243,53 +205,6 @@ impl DocumentExtractor {
         })
     }
 
-    /// Extract text from DOCX XML content
-    fn extract_text_from_docx_xml(&self, xml: &str) -> String {
-        let mut text = String::new();
-        let mut in_text_tag = false;
-        let mut current_text = String::new();
-
-        // Simple XML parser for <w:t> tags
-        let chars: Vec<char> = xml.chars().collect();
-        let mut i = 0;
-
-        while i < chars.len() {
-            if i + 4 < chars.len() && chars[i..i + 4] == ['<', 'w', ':', 't'] {
-                // Found <w:t> or <w:t ... >
-                // Skip to end of opening tag
-                while i < chars.len() && chars[i] != '>' {
-                    i += 1;
-                }
-                i += 1; // Skip '>'
-                in_text_tag = true;
-                current_text.clear();
-            } else if i + 6 < chars.len() && chars[i..i + 6] == ['<', '/', 'w', ':', 't', '>'] {
-                // Found </w:t>
-                if in_text_tag {
-                    text.push_str(&current_text);
-                    text.push(' ');
-                }
-                in_text_tag = false;
-                i += 6;
-            } else if in_text_tag {
-                current_text.push(chars[i]);
-                i += 1;
-            } else if i + 4 < chars.len() && chars[i..i + 4] == ['<', 'w', ':', 'p'] {
-                // Found paragraph tag - add newline
-                text.push('\n');
-                i += 1;
-            } else {
-                i += 1;
-            }
-        }
-
-        // Clean up multiple spaces and newlines
-        text.split_whitespace()
-            .collect::<Vec<&str>>()
-            .join(" ")
-            .replace("  ", " ")
-    }
-
     /// Extract PDF text using pdf-extract
     fn extract_pdf_text(&self, path: &Path) -> Result<String> {
         use pdf_extract::extract_text;
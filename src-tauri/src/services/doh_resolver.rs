@@ -0,0 +1,720 @@
+// CUBE Web Engine - DNS-over-HTTPS resolver
+//
+// Lookups go to the configured DoH provider (RFC 8484) and fail over to a
+// secondary one. A and AAAA queries race each other happy-eyeballs style
+// (RFC 8305) and answers are cached for their TTL. While the VPN is connected
+// its tunnel resolvers take precedence, so no lookup bypasses the tunnel.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+
+pub const DEFAULT_PRIMARY: &str = "https://cloudflare-dns.com/dns-query";
+pub const DEFAULT_SECONDARY: &str = "https://dns.quad9.net/dns-query";
+
+/// How long to wait for the slower address family once the first one answered
+const RESOLUTION_DELAY: Duration = Duration::from_millis(50);
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+const MIN_TTL: u32 = 30;
+const MAX_TTL: u32 = 86_400;
+const MAX_CACHE_ENTRIES: usize = 4096;
+
+const RCODE_NXDOMAIN: u8 = 3;
+
+// ============================================
+// Types
+// ============================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RecordType {
+    A,
+    Aaaa,
+}
+
+impl RecordType {
+    fn code(self) -> u16 {
+        match self {
+            RecordType::A => 1,
+            RecordType::Aaaa => 28,
+        }
+    }
+}
+
+/// Where a query is sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolverEndpoint {
+    Doh(String),
+    Tunnel(SocketAddr),
+}
+
+impl ResolverEndpoint {
+    pub fn label(&self) -> String {
+        match self {
+            ResolverEndpoint::Doh(url) => url.clone(),
+            ResolverEndpoint::Tunnel(address) => format!("tunnel:{}", address),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DohConfig {
+    pub enabled: bool,
+    pub primary: String,
+    pub secondary: Option<String>,
+    /// Resolvers pushed by the VPN while it is connected
+    pub tunnel_resolvers: Vec<String>,
+}
+
+impl Default for DohConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            primary: DEFAULT_PRIMARY.to_string(),
+            secondary: Some(DEFAULT_SECONDARY.to_string()),
+            tunnel_resolvers: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resolution {
+    pub host: String,
+    /// Ordered for connection attempts, first responding family first
+    pub addresses: Vec<IpAddr>,
+    pub resolver: String,
+    pub from_cache: bool,
+    pub ttl: u32,
+}
+
+/// Addresses of one family, as answered by a resolver
+#[derive(Debug, Clone)]
+struct FamilyAnswer {
+    addresses: Vec<IpAddr>,
+    ttl: u32,
+    resolver: String,
+    from_cache: bool,
+}
+
+struct CacheEntry {
+    addresses: Vec<IpAddr>,
+    ttl: u32,
+    resolver: String,
+    expires: Instant,
+}
+
+/// Sends a raw DNS message to a resolver and returns the raw reply
+#[async_trait]
+pub trait DnsTransport: Send + Sync {
+    async fn exchange(&self, endpoint: &ResolverEndpoint, query: &[u8]) -> Result<Vec<u8>, String>;
+}
+
+/// DoH over HTTPS POST, tunnel resolvers over plain UDP
+pub struct NetworkTransport {
+    client: reqwest::Client,
+}
+
+impl NetworkTransport {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(QUERY_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+        Self { client }
+    }
+}
+
+impl Default for NetworkTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl DnsTransport for NetworkTransport {
+    async fn exchange(&self, endpoint: &ResolverEndpoint, query: &[u8]) -> Result<Vec<u8>, String> {
+        match endpoint {
+            ResolverEndpoint::Doh(url) => {
+                let response = self
+                    .client
+                    .post(url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/dns-message")
+                    .header(reqwest::header::ACCEPT, "application/dns-message")
+                    .body(query.to_vec())
+                    .send()
+                    .await
+                    .map_err(|e| format!("DoH request failed: {}", e))?;
+                if !response.status().is_success() {
+                    return Err(format!("DoH resolver returned HTTP {}", response.status()));
+                }
+                let body = response.bytes().await.map_err(|e| format!("DoH read failed: {}", e))?;
+                Ok(body.to_vec())
+            }
+            ResolverEndpoint::Tunnel(address) => {
+                let bind: SocketAddr = if address.is_ipv6() {
+                    (Ipv6Addr::UNSPECIFIED, 0).into()
+                } else {
+                    (Ipv4Addr::UNSPECIFIED, 0).into()
+                };
+                let socket = UdpSocket::bind(bind).await.map_err(|e| format!("UDP bind failed: {}", e))?;
+                socket
+                    .send_to(query, address)
+                    .await
+                    .map_err(|e| format!("UDP send failed: {}", e))?;
+                let mut buf = vec![0u8; 4096];
+                loop {
+                    let (len, from) = socket
+                        .recv_from(&mut buf)
+                        .await
+                        .map_err(|e| format!("UDP receive failed: {}", e))?;
+                    if from == *address {
+                        buf.truncate(len);
+                        return Ok(buf);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// ============================================
+// Resolver
+// ============================================
+
+pub struct DohResolver {
+    transport: Arc<dyn DnsTransport>,
+    config: RwLock<DohConfig>,
+    cache: Mutex<HashMap<(String, RecordType), CacheEntry>>,
+    resolution_delay: Duration,
+}
+
+impl DohResolver {
+    pub fn new() -> Self {
+        Self::with_transport(Arc::new(NetworkTransport::new()))
+    }
+
+    pub fn with_transport(transport: Arc<dyn DnsTransport>) -> Self {
+        Self {
+            transport,
+            config: RwLock::new(DohConfig::default()),
+            cache: Mutex::new(HashMap::new()),
+            resolution_delay: RESOLUTION_DELAY,
+        }
+    }
+
+    pub fn with_resolution_delay(mut self, delay: Duration) -> Self {
+        self.resolution_delay = delay;
+        self
+    }
+
+    pub fn config(&self) -> DohConfig {
+        self.config.read().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.read().map(|c| c.enabled).unwrap_or(true)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        if let Ok(mut config) = self.config.write() {
+            config.enabled = enabled;
+        }
+    }
+
+    /// Switch DoH providers. The secondary defaults to one that differs from the primary.
+    pub fn set_providers(&self, primary: &str, secondary: Option<&str>) -> Result<DohConfig, String> {
+        let primary = validate_doh_url(primary)?;
+        let secondary = match secondary {
+            Some(url) => Some(validate_doh_url(url)?),
+            None => [DEFAULT_SECONDARY, DEFAULT_PRIMARY]
+                .iter()
+                .find(|url| **url != primary)
+                .map(|url| url.to_string()),
+        }
+        .filter(|url| *url != primary);
+
+        let config = {
+            let mut config = self.config.write().map_err(|e| format!("Lock error: {}", e))?;
+            config.primary = primary;
+            config.secondary = secondary;
+            config.clone()
+        };
+        self.clear_cache();
+        Ok(config)
+    }
+
+    /// Route lookups through the VPN's resolvers while it is connected
+    pub fn set_tunnel_resolvers(&self, resolvers: &[String]) {
+        let resolvers: Vec<String> = resolvers
+            .iter()
+            .filter_map(|r| r.trim().parse::<IpAddr>().ok())
+            .map(|ip| ip.to_string())
+            .collect();
+        if let Ok(mut config) = self.config.write() {
+            config.tunnel_resolvers = resolvers;
+        }
+        // Answers obtained outside the tunnel may differ from the tunnel's view
+        self.clear_cache();
+    }
+
+    pub fn clear_tunnel_resolvers(&self) {
+        self.set_tunnel_resolvers(&[]);
+    }
+
+    pub fn clear_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
+        }
+    }
+
+    /// Resolvers in the order they are tried: tunnel first, then primary and secondary
+    pub fn endpoints(&self) -> Vec<ResolverEndpoint> {
+        let config = self.config();
+        let mut endpoints: Vec<ResolverEndpoint> = config
+            .tunnel_resolvers
+            .iter()
+            .filter_map(|r| r.parse::<IpAddr>().ok())
+            .map(|ip| ResolverEndpoint::Tunnel(SocketAddr::new(ip, 53)))
+            .collect();
+        endpoints.push(ResolverEndpoint::Doh(config.primary));
+        if let Some(secondary) = config.secondary {
+            endpoints.push(ResolverEndpoint::Doh(secondary));
+        }
+        endpoints
+    }
+
+    pub async fn resolve(&self, host: &str) -> Result<Resolution, String> {
+        self.resolve_at(host, Instant::now()).await
+    }
+
+    /// Resolve both address families, racing A against AAAA. `now` drives cache expiry.
+    pub async fn resolve_at(&self, host: &str, now: Instant) -> Result<Resolution, String> {
+        let host = host.trim().trim_end_matches('.').to_lowercase();
+        if host.is_empty() {
+            return Err("Empty host name".to_string());
+        }
+        let literal = host.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = literal.parse::<IpAddr>() {
+            return Ok(Resolution {
+                host,
+                addresses: vec![ip],
+                resolver: "literal".to_string(),
+                from_cache: false,
+                ttl: 0,
+            });
+        }
+
+        let v6 = self.family(&host, RecordType::Aaaa, now);
+        let v4 = self.family(&host, RecordType::A, now);
+        tokio::pin!(v6, v4);
+
+        // Whichever family answers first leads; the other gets a short grace period
+        let (first, second) = tokio::select! {
+            biased;
+            r = &mut v6 => {
+                let second = self.await_second(&r, &mut v4).await;
+                (r, second)
+            }
+            r = &mut v4 => {
+                let second = self.await_second(&r, &mut v6).await;
+                (r, second)
+            }
+        };
+
+        let mut answers = Vec::new();
+        let mut error = None;
+        for result in [Some(first), second].into_iter().flatten() {
+            match result {
+                Ok(answer) if !answer.addresses.is_empty() => answers.push(answer),
+                Ok(_) => {}
+                Err(e) => error = error.or(Some(e)),
+            }
+        }
+        let Some(lead) = answers.first() else {
+            return Err(error.unwrap_or_else(|| format!("No addresses found for {}", host)));
+        };
+
+        let resolver = lead.resolver.clone();
+        let from_cache = answers.iter().all(|a| a.from_cache);
+        let ttl = answers.iter().map(|a| a.ttl).min().unwrap_or(0);
+        let addresses = interleave(answers.iter().map(|a| a.addresses.as_slice()).collect());
+
+        Ok(Resolution { host: host.clone(), addresses, resolver, from_cache, ttl })
+    }
+
+    async fn await_second<F>(
+        &self,
+        first: &Result<FamilyAnswer, String>,
+        second: &mut std::pin::Pin<&mut F>,
+    ) -> Option<Result<FamilyAnswer, String>>
+    where
+        F: std::future::Future<Output = Result<FamilyAnswer, String>>,
+    {
+        match first {
+            Ok(answer) if !answer.addresses.is_empty() => {
+                tokio::time::timeout(self.resolution_delay, second.as_mut()).await.ok()
+            }
+            // Nothing usable yet, so the other family is all there is
+            _ => Some(second.as_mut().await),
+        }
+    }
+
+    /// One address family, from cache when fresh
+    async fn family(&self, host: &str, record: RecordType, now: Instant) -> Result<FamilyAnswer, String> {
+        if let Some(hit) = self.cached(host, record, now) {
+            return Ok(hit);
+        }
+        let (addresses, ttl, resolver) = self.lookup(host, record).await?;
+        let ttl = ttl.clamp(MIN_TTL, MAX_TTL);
+        if let Ok(mut cache) = self.cache.lock() {
+            if cache.len() >= MAX_CACHE_ENTRIES {
+                cache.retain(|_, entry| entry.expires > now);
+            }
+            if cache.len() < MAX_CACHE_ENTRIES {
+                cache.insert(
+                    (host.to_string(), record),
+                    CacheEntry {
+                        addresses: addresses.clone(),
+                        ttl,
+                        resolver: resolver.clone(),
+                        expires: now + Duration::from_secs(u64::from(ttl)),
+                    },
+                );
+            }
+        }
+        Ok(FamilyAnswer { addresses, ttl, resolver, from_cache: false })
+    }
+
+    fn cached(&self, host: &str, record: RecordType, now: Instant) -> Option<FamilyAnswer> {
+        let mut cache = self.cache.lock().ok()?;
+        let key = (host.to_string(), record);
+        match cache.get(&key) {
+            Some(entry) if entry.expires > now => Some(FamilyAnswer {
+                addresses: entry.addresses.clone(),
+                ttl: (entry.expires - now).as_secs().min(u64::from(entry.ttl)) as u32,
+                resolver: entry.resolver.clone(),
+                from_cache: true,
+            }),
+            Some(_) => {
+                cache.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Query each resolver in turn until one gives an authoritative answer
+    async fn lookup(&self, host: &str, record: RecordType) -> Result<(Vec<IpAddr>, u32, String), String> {
+        let mut errors = Vec::new();
+        for endpoint in self.endpoints() {
+            let id: u16 = rand::random();
+            let query = build_query(id, host, record)?;
+            let reply = match tokio::time::timeout(QUERY_TIMEOUT, self.transport.exchange(&endpoint, &query)).await {
+                Ok(Ok(reply)) => reply,
+                Ok(Err(e)) => {
+                    errors.push(format!("{}: {}", endpoint.label(), e));
+                    continue;
+                }
+                Err(_) => {
+                    errors.push(format!("{}: timed out", endpoint.label()));
+                    continue;
+                }
+            };
+            match parse_response(&reply, id, record) {
+                Ok((0, addresses, ttl)) => return Ok((addresses, ttl, endpoint.label())),
+                Ok((RCODE_NXDOMAIN, _, _)) => return Err(format!("{} does not exist", host)),
+                Ok((rcode, _, _)) => errors.push(format!("{}: rcode {}", endpoint.label(), rcode)),
+                Err(e) => errors.push(format!("{}: {}", endpoint.label(), e)),
+            }
+        }
+        Err(format!("All resolvers failed: {}", errors.join("; ")))
+    }
+}
+
+impl Default for DohResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Plugs a shared resolver into reqwest clients
+pub struct DohResolverHandle(pub Arc<DohResolver>);
+
+impl reqwest::dns::Resolve for DohResolverHandle {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let resolver = self.0.clone();
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = if resolver.is_enabled() {
+                resolver
+                    .resolve(&host)
+                    .await?
+                    .addresses
+                    .into_iter()
+                    .map(|ip| SocketAddr::new(ip, 0))
+                    .collect()
+            } else {
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect()
+            };
+            let addresses: reqwest::dns::Addrs = Box::new(addresses.into_iter());
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(addresses)
+        })
+    }
+}
+
+fn validate_doh_url(url: &str) -> Result<String, String> {
+    let parsed = url::Url::parse(url.trim()).map_err(|e| format!("Invalid DoH URL: {}", e))?;
+    if parsed.scheme() != "https" {
+        return Err("DoH providers must use https".to_string());
+    }
+    Ok(parsed.to_string())
+}
+
+/// Alternate families, starting with the first responder (RFC 8305 section 4)
+fn interleave(families: Vec<&[IpAddr]>) -> Vec<IpAddr> {
+    let longest = families.iter().map(|f| f.len()).max().unwrap_or(0);
+    let mut out = Vec::new();
+    for i in 0..longest {
+        for family in &families {
+            if let Some(ip) = family.get(i) {
+                out.push(*ip);
+            }
+        }
+    }
+    out
+}
+
+// ============================================
+// Wire format
+// ============================================
+
+fn build_query(id: u16, host: &str, record: RecordType) -> Result<Vec<u8>, String> {
+    if host.len() > 253 {
+        return Err("Host name too long".to_string());
+    }
+    let mut packet = Vec::with_capacity(host.len() + 18);
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&[0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    for label in host.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("Invalid host name: {}", host));
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&record.code().to_be_bytes());
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    Ok(packet)
+}
+
+fn skip_name(packet: &[u8], mut pos: usize) -> Result<usize, String> {
+    loop {
+        let len = *packet.get(pos).ok_or("Truncated name")? as usize;
+        if len == 0 {
+            return Ok(pos + 1);
+        }
+        if len & 0xC0 == 0xC0 {
+            return Ok(pos + 2);
+        }
+        pos += len + 1;
+    }
+}
+
+fn read_u16(packet: &[u8], pos: usize) -> Result<u16, String> {
+    packet
+        .get(pos..pos + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| "Truncated DNS message".to_string())
+}
+
+/// Returns the rcode, the matching addresses and the lowest TTL along the answer chain
+fn parse_response(packet: &[u8], id: u16, record: RecordType) -> Result<(u8, Vec<IpAddr>, u32), String> {
+    if packet.len() < 12 || read_u16(packet, 0)? != id {
+        return Err("Mismatched DNS reply".to_string());
+    }
+    let flags = read_u16(packet, 2)?;
+    if flags & 0x8000 == 0 {
+        return Err("Not a DNS reply".to_string());
+    }
+    if flags & 0x0200 != 0 {
+        return Err("Truncated DNS reply".to_string());
+    }
+    let rcode = (flags & 0x000F) as u8;
+    let questions = read_u16(packet, 4)?;
+    let answers = read_u16(packet, 6)?;
+
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(packet, pos)? + 4;
+    }
+
+    let mut addresses = Vec::new();
+    let mut ttl = u32::MAX;
+    for _ in 0..answers {
+        pos = skip_name(packet, pos)?;
+        let rtype = read_u16(packet, pos)?;
+        let class = read_u16(packet, pos + 2)?;
+        let record_ttl = packet
+            .get(pos + 4..pos + 8)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or("Truncated DNS record")?;
+        let rdlength = read_u16(packet, pos + 8)? as usize;
+        let rdata = packet.get(pos + 10..pos + 10 + rdlength).ok_or("Truncated DNS record")?;
+        pos += 10 + rdlength;
+        if class != 1 {
+            continue;
+        }
+        match (rtype, rdata.len()) {
+            (1, 4) if record == RecordType::A => {
+                addresses.push(IpAddr::V4(Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3])));
+            }
+            (28, 16) if record == RecordType::Aaaa => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(rdata);
+                addresses.push(IpAddr::V6(Ipv6Addr::from(octets)));
+            }
+            // CNAME hops bound the lifetime of the final answer too
+            (5, _) => {}
+            _ => continue,
+        }
+        ttl = ttl.min(record_ttl);
+    }
+    if ttl == u32::MAX {
+        ttl = MIN_TTL;
+    }
+    Ok((rcode, addresses, ttl))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Addresses and delay in ms; `None` fails the exchange
+    type MockAnswer = (Option<Vec<IpAddr>>, u64);
+
+    /// Answers per (resolver, family)
+    struct MockTransport {
+        answers: HashMap<(String, u16), MockAnswer>,
+        calls: AtomicUsize,
+    }
+
+    impl MockTransport {
+        fn new() -> Self {
+            Self { answers: HashMap::new(), calls: AtomicUsize::new(0) }
+        }
+
+        fn answer(mut self, resolver: &str, record: RecordType, ips: Option<&[&str]>, delay_ms: u64) -> Self {
+            let ips = ips.map(|ips| ips.iter().map(|ip| ip.parse().unwrap()).collect());
+            self.answers.insert((resolver.to_string(), record.code()), (ips, delay_ms));
+            self
+        }
+    }
+
+    #[async_trait]
+    impl DnsTransport for MockTransport {
+        async fn exchange(&self, endpoint: &ResolverEndpoint, query: &[u8]) -> Result<Vec<u8>, String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let qtype = read_u16(query, query.len() - 4)?;
+            let (ips, delay) = self
+                .answers
+                .get(&(endpoint.label(), qtype))
+                .cloned()
+                .unwrap_or((Some(Vec::new()), 0));
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            let ips = ips.ok_or("connection refused")?;
+
+            let mut reply = query.to_vec();
+            reply[2] = 0x81;
+            reply[3] = 0x80;
+            reply[7] = ips.len() as u8;
+            for ip in ips {
+                reply.extend_from_slice(&[0xC0, 0x0C]);
+                let rdata = match ip {
+                    IpAddr::V4(v4) => v4.octets().to_vec(),
+                    IpAddr::V6(v6) => v6.octets().to_vec(),
+                };
+                reply.extend_from_slice(&qtype.to_be_bytes());
+                reply.extend_from_slice(&1u16.to_be_bytes());
+                reply.extend_from_slice(&300u32.to_be_bytes());
+                reply.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+                reply.extend_from_slice(&rdata);
+            }
+            Ok(reply)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_and_expiry_with_failover_to_secondary() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .answer(DEFAULT_PRIMARY, RecordType::A, None, 0)
+                .answer(DEFAULT_PRIMARY, RecordType::Aaaa, None, 0)
+                .answer(DEFAULT_SECONDARY, RecordType::A, Some(&["93.184.216.34"]), 0)
+                .answer(DEFAULT_SECONDARY, RecordType::Aaaa, Some(&["2606:2800:220:1::1"]), 0),
+        );
+        let resolver = DohResolver::with_transport(transport.clone());
+        let now = Instant::now();
+
+        let first = resolver.resolve_at("Example.com.", now).await.unwrap();
+        assert_eq!(first.resolver, DEFAULT_SECONDARY);
+        assert!(!first.from_cache);
+        assert_eq!(first.addresses.len(), 2);
+        assert_eq!(first.ttl, 300);
+        let calls = transport.calls.load(Ordering::SeqCst);
+        assert_eq!(calls, 4);
+
+        let cached = resolver.resolve_at("example.com", now + Duration::from_secs(100)).await.unwrap();
+        assert!(cached.from_cache);
+        assert_eq!(cached.ttl, 200);
+        assert_eq!(transport.calls.load(Ordering::SeqCst), calls);
+
+        let expired = resolver.resolve_at("example.com", now + Duration::from_secs(301)).await.unwrap();
+        assert!(!expired.from_cache);
+        assert_eq!(transport.calls.load(Ordering::SeqCst), calls * 2);
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_prefers_first_responder() {
+        let transport = Arc::new(
+            MockTransport::new()
+                .answer(DEFAULT_PRIMARY, RecordType::A, Some(&["10.0.0.1", "10.0.0.2"]), 0)
+                .answer(DEFAULT_PRIMARY, RecordType::Aaaa, Some(&["fd00::1"]), 20),
+        );
+        let resolver = DohResolver::with_transport(transport).with_resolution_delay(Duration::from_millis(200));
+        let both = resolver.resolve("fast-v4.test").await.unwrap();
+        let expected: Vec<IpAddr> = ["10.0.0.1", "fd00::1", "10.0.0.2"].iter().map(|ip| ip.parse().unwrap()).collect();
+        assert_eq!(both.addresses, expected);
+
+        // A slow family that misses the resolution delay is left out
+        let transport = Arc::new(
+            MockTransport::new()
+                .answer(DEFAULT_PRIMARY, RecordType::A, Some(&["10.0.0.1"]), 500)
+                .answer(DEFAULT_PRIMARY, RecordType::Aaaa, Some(&["fd00::1"]), 0),
+        );
+        let resolver = DohResolver::with_transport(transport).with_resolution_delay(Duration::from_millis(20));
+        let v6_only = resolver.resolve("fast-v6.test").await.unwrap();
+        assert_eq!(v6_only.addresses, vec!["fd00::1".parse::<IpAddr>().unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn test_tunnel_resolvers_take_precedence() {
+        let transport = Arc::new(
+            MockTransport::new().answer("tunnel:10.8.0.1:53", RecordType::A, Some(&["10.8.0.20"]), 0),
+        );
+        let resolver = DohResolver::with_transport(transport);
+        resolver.set_tunnel_resolvers(&["10.8.0.1".to_string()]);
+        let resolved = resolver.resolve("intranet.test").await.unwrap();
+        assert_eq!(resolved.resolver, "tunnel:10.8.0.1:53");
+
+        resolver.clear_tunnel_resolvers();
+        assert_eq!(resolver.endpoints()[0], ResolverEndpoint::Doh(DEFAULT_PRIMARY.to_string()));
+    }
+}
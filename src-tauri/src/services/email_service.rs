@@ -153,6 +153,9 @@ pub struct EmailMessage {
     pub template_variables: Option<std::collections::HashMap<String, String>>,
     pub tracking_id: Option<String>,
     pub campaign_id: Option<String>,
+    /// Disables open/click tracking for this message, including the provider's own
+    #[serde(default)]
+    pub tracking_opt_out: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -503,7 +506,7 @@ pub async fn send_via_sendgrid(
     }
     
     // Add tracking settings
-    if config.tracking_enabled {
+    if config.tracking_enabled && !message.tracking_opt_out {
         body["tracking_settings"] = serde_json::json!({
             "click_tracking": {"enable": true},
             "open_tracking": {"enable": true}
@@ -579,7 +582,7 @@ pub async fn send_via_sendgrid(
 // HELPER FUNCTIONS
 // ═══════════════════════════════════════════════════════════════════════════════
 
-fn substitute_variables(
+pub(crate) fn substitute_variables(
     content: &str,
     variables: &Option<std::collections::HashMap<String, String>>,
 ) -> String {
@@ -930,6 +933,7 @@ pub async fn send_test_email(
         template_variables: None,
         tracking_id: Some(format!("test-{}", uuid::Uuid::new_v4())),
         campaign_id: None,
+        tracking_opt_out: true,
     };
     
     drop(config); // Release read lock before calling send_email
@@ -0,0 +1,442 @@
+// Email Engagement Tracking for CUBE Nexum
+// ========================================
+// Open pixel and click redirect tracking for CRM/marketing sends. Every tracked
+// single-recipient message gets its own token; the api_server serves the pixel
+// (`/t/o/{token}`) and the link redirect (`/t/c/{token}/{index}`) and credits
+// the hit to the matching contact.
+//
+// Opens are a lower bound: clients that block remote images never fetch the
+// pixel, so a click without a prior open is also counted as the open.
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock, RwLock};
+use uuid::Uuid;
+
+use crate::services::contact_service::ContactServiceState;
+use crate::services::email_service::{substitute_variables, EmailMessage};
+
+/// Transparent 1x1 GIF served for the open pixel
+pub const TRACKING_PIXEL_GIF: &[u8] = &[
+    0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0x21, 0xf9, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x2c, 0x00, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x01, 0x00, 0x00, 0x02, 0x02, 0x44, 0x01, 0x00, 0x3b,
+];
+
+// =============================================================================
+// Data Structures
+// =============================================================================
+
+/// Tracking configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailTrackingConfig {
+    pub enabled: bool,
+    /// Public URL of the api_server, as reachable from the recipient's mail client
+    pub base_url: String,
+    /// Opens arriving sooner than this after the send are treated as
+    /// security-scanner or proxy prefetches and ignored
+    pub min_open_delay_seconds: u64,
+}
+
+impl Default for EmailTrackingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            base_url: "http://localhost:3001".to_string(),
+            min_open_delay_seconds: 5,
+        }
+    }
+}
+
+/// A tracked message and the engagement recorded for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedSend {
+    pub token: String,
+    pub recipient: String,
+    pub tracking_id: Option<String>,
+    pub campaign_id: Option<String>,
+    /// Original link targets, indexed by their position in the redirect URL
+    pub links: Vec<String>,
+    pub sent_at: DateTime<Utc>,
+    pub opens: u32,
+    pub ignored_opens: u32,
+    pub first_opened_at: Option<DateTime<Utc>>,
+    pub link_clicks: HashMap<String, u32>,
+}
+
+/// Result of a pixel hit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpenOutcome {
+    Recorded,
+    IgnoredPrefetch,
+    UnknownToken,
+}
+
+// =============================================================================
+// Service State
+// =============================================================================
+
+pub struct EmailTrackingService {
+    config: RwLock<EmailTrackingConfig>,
+    sends: Mutex<HashMap<String, TrackedSend>>,
+}
+
+impl Default for EmailTrackingService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EmailTrackingService {
+    pub fn new() -> Self {
+        Self {
+            config: RwLock::new(EmailTrackingConfig::default()),
+            sends: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get_config(&self) -> Result<EmailTrackingConfig, String> {
+        self.config.read()
+            .map(|c| c.clone())
+            .map_err(|e| format!("Failed to acquire tracking config lock: {}", e))
+    }
+
+    pub fn set_config(&self, mut config: EmailTrackingConfig) -> Result<EmailTrackingConfig, String> {
+        let parsed = url::Url::parse(&config.base_url)
+            .map_err(|e| format!("Invalid tracking base URL: {}", e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err("Tracking base URL must be http or https".to_string());
+        }
+        config.base_url = config.base_url.trim_end_matches('/').to_string();
+
+        let mut current = self.config.write()
+            .map_err(|e| format!("Failed to acquire tracking config lock: {}", e))?;
+        *current = config.clone();
+        Ok(config)
+    }
+
+    /// Prepare an outgoing message: inject the pixel and wrap links when the
+    /// send is trackable, or strip tracking when the sender disabled it for
+    /// this send or the recipient opted out. Returns the tracking token.
+    pub fn prepare_message(
+        &self,
+        message: &mut EmailMessage,
+        contacts: &ContactServiceState,
+        track: bool,
+    ) -> Result<Option<String>, String> {
+        let config = self.get_config()?;
+
+        let mut opted_out = !track;
+        for recipient in &message.to {
+            if contacts.get_contact_by_email(&recipient.email)?.is_some_and(|c| c.tracking_opt_out) {
+                opted_out = true;
+            }
+        }
+        if opted_out {
+            message.tracking_opt_out = true;
+            message.html_content = self.strip_tracking(&message.html_content, &config.base_url)?;
+            return Ok(None);
+        }
+
+        // Hits can only be attributed when exactly one person receives the copy
+        let has_copies = message.cc.as_ref().is_some_and(|v| !v.is_empty())
+            || message.bcc.as_ref().is_some_and(|v| !v.is_empty());
+        if !config.enabled || message.to.len() != 1 || has_copies {
+            return Ok(None);
+        }
+
+        // Resolve personalised links before they are hidden behind the redirect
+        let html = substitute_variables(&message.html_content, &message.template_variables);
+        let token = Uuid::new_v4().simple().to_string();
+        let (html, links) = inject_tracking(&html, &config.base_url, &token);
+        message.html_content = html;
+
+        let send = TrackedSend {
+            token: token.clone(),
+            recipient: message.to[0].email.clone(),
+            tracking_id: message.tracking_id.clone(),
+            campaign_id: message.campaign_id.clone(),
+            links,
+            sent_at: Utc::now(),
+            opens: 0,
+            ignored_opens: 0,
+            first_opened_at: None,
+            link_clicks: HashMap::new(),
+        };
+        self.sends.lock()
+            .map_err(|e| format!("Failed to acquire tracking lock: {}", e))?
+            .insert(token.clone(), send);
+
+        Ok(Some(token))
+    }
+
+    /// Record a pixel hit. Only the first genuine open of a send is credited to
+    /// the contact; repeated opens are counted on the send itself.
+    pub fn record_open(
+        &self,
+        token: &str,
+        contacts: &ContactServiceState,
+        now: DateTime<Utc>,
+    ) -> Result<OpenOutcome, String> {
+        let min_delay = self.get_config()?.min_open_delay_seconds as i64;
+        let mut sends = self.sends.lock()
+            .map_err(|e| format!("Failed to acquire tracking lock: {}", e))?;
+        let Some(send) = sends.get_mut(token) else {
+            return Ok(OpenOutcome::UnknownToken);
+        };
+
+        if now.signed_duration_since(send.sent_at).num_seconds() < min_delay {
+            send.ignored_opens += 1;
+            return Ok(OpenOutcome::IgnoredPrefetch);
+        }
+
+        mark_opened(send, contacts, now);
+        Ok(OpenOutcome::Recorded)
+    }
+
+    /// Record a wrapped-link hit and return the original target to redirect to
+    pub fn record_click(
+        &self,
+        token: &str,
+        index: usize,
+        contacts: &ContactServiceState,
+        now: DateTime<Utc>,
+    ) -> Result<Option<String>, String> {
+        let mut sends = self.sends.lock()
+            .map_err(|e| format!("Failed to acquire tracking lock: {}", e))?;
+        let Some(send) = sends.get_mut(token) else {
+            return Ok(None);
+        };
+        let Some(link) = send.links.get(index).cloned() else {
+            return Ok(None);
+        };
+
+        // The pixel was blocked or never loaded; a click proves the open
+        if send.first_opened_at.is_none() {
+            mark_opened(send, contacts, now);
+        }
+
+        *send.link_clicks.entry(link.clone()).or_insert(0) += 1;
+        if let Err(e) = contacts.record_link_click(&send.recipient, &link) {
+            warn!("Email click for {} not credited: {}", send.recipient, e);
+        }
+
+        Ok(Some(link))
+    }
+
+    pub fn get_send(&self, token: &str) -> Result<Option<TrackedSend>, String> {
+        let sends = self.sends.lock()
+            .map_err(|e| format!("Failed to acquire tracking lock: {}", e))?;
+        Ok(sends.get(token).cloned())
+    }
+
+    /// Tracked sends, optionally limited to one campaign, newest first
+    pub fn get_sends(&self, campaign_id: Option<&str>) -> Result<Vec<TrackedSend>, String> {
+        let sends = self.sends.lock()
+            .map_err(|e| format!("Failed to acquire tracking lock: {}", e))?;
+        let mut result: Vec<TrackedSend> = sends.values()
+            .filter(|s| campaign_id.is_none() || s.campaign_id.as_deref() == campaign_id)
+            .cloned()
+            .collect();
+        result.sort_by_key(|s| std::cmp::Reverse(s.sent_at));
+        Ok(result)
+    }
+
+    /// Remove our pixel and unwrap our redirect links from content that was
+    /// tracked before (e.g. a forwarded or re-used template)
+    fn strip_tracking(&self, html: &str, base_url: &str) -> Result<String, String> {
+        let base = regex::escape(base_url);
+        let pixel = Regex::new(&format!(r#"(?i)<img\b[^>]*\bsrc\s*=\s*["']{}/t/o/[^"']*["'][^>]*>"#, base))
+            .map_err(|e| e.to_string())?;
+        let link = Regex::new(&format!(r#"{}/t/c/([0-9a-f]+)/(\d+)"#, base))
+            .map_err(|e| e.to_string())?;
+
+        let sends = self.sends.lock()
+            .map_err(|e| format!("Failed to acquire tracking lock: {}", e))?;
+        let html = pixel.replace_all(html, "");
+        let html = link.replace_all(&html, |caps: &regex::Captures| {
+            let original = caps[2].parse::<usize>().ok()
+                .and_then(|i| sends.get(&caps[1]).and_then(|s| s.links.get(i)));
+            match original {
+                Some(url) => url.replace('&', "&amp;"),
+                None => caps[0].to_string(),
+            }
+        });
+        Ok(html.into_owned())
+    }
+}
+
+// =============================================================================
+// Helper Functions
+// =============================================================================
+
+fn mark_opened(send: &mut TrackedSend, contacts: &ContactServiceState, now: DateTime<Utc>) {
+    send.opens += 1;
+    if send.first_opened_at.is_some() {
+        return;
+    }
+    send.first_opened_at = Some(now);
+    if let Err(e) = contacts.update_contact_engagement(&send.recipient, false, true, false, false) {
+        warn!("Email open for {} not credited: {}", send.recipient, e);
+    }
+}
+
+fn anchor_href_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"(?i)(<a\b[^>]*?\bhref\s*=\s*)(["'])(https?://[^"']+)(["'])"#).unwrap()
+    })
+}
+
+/// Wrap every absolute http(s) link through the redirect endpoint and append
+/// the open pixel. Returns the rewritten HTML and the original link targets.
+fn inject_tracking(html: &str, base_url: &str, token: &str) -> (String, Vec<String>) {
+    let mut links = Vec::new();
+    let wrapped = anchor_href_regex().replace_all(html, |caps: &regex::Captures| {
+        let target = caps[3].replace("&amp;", "&");
+        let index = links.len();
+        links.push(target);
+        format!("{}{}{}/t/c/{}/{}{}", &caps[1], &caps[2], base_url, token, index, &caps[4])
+    });
+
+    let pixel = format!(
+        r#"<img src="{}/t/o/{}.gif" width="1" height="1" alt="" style="border:0;width:1px;height:1px" />"#,
+        base_url, token
+    );
+    let mut html = wrapped.into_owned();
+    match html.to_lowercase().rfind("</body>") {
+        Some(pos) => html.insert_str(pos, &pixel),
+        None => html.push_str(&pixel),
+    }
+    (html, links)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::email_service::EmailRecipient;
+
+    fn message(to: &str, html: &str) -> EmailMessage {
+        EmailMessage {
+            to: vec![EmailRecipient { email: to.to_string(), name: None }],
+            cc: None,
+            bcc: None,
+            subject: "Spring launch".to_string(),
+            html_content: html.to_string(),
+            text_content: None,
+            from_email: None,
+            from_name: None,
+            reply_to: None,
+            headers: None,
+            attachments: None,
+            template_variables: None,
+            tracking_id: None,
+            campaign_id: Some("spring".to_string()),
+            tracking_opt_out: false,
+        }
+    }
+
+    fn add_contact(contacts: &ContactServiceState, email: &str) -> String {
+        contacts.create_contact(email.to_string(), None, None, None, None, None, None, None, None)
+            .unwrap()
+            .id
+    }
+
+    fn path_of(html: &str, prefix: &str) -> String {
+        let start = html.find(prefix).unwrap() + prefix.len();
+        html[start..].split('"').next().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_pixel_hit_records_open_for_recipient() {
+        let tracking = EmailTrackingService::new();
+        let contacts = ContactServiceState::new();
+        let ana = add_contact(&contacts, "ana@example.com");
+        let ben = add_contact(&contacts, "ben@example.com");
+
+        let mut msg = message("ana@example.com", "<html><body><p>Hi</p></body></html>");
+        let token = tracking.prepare_message(&mut msg, &contacts, true).unwrap().unwrap();
+        assert!(msg.html_content.contains(&format!("/t/o/{}.gif\"", token)));
+        assert!(msg.html_content.ends_with("/></body></html>"));
+        let pixel_token = path_of(&msg.html_content, "http://localhost:3001/t/o/");
+        let pixel_token = pixel_token.trim_end_matches(".gif");
+
+        // Scanner prefetch right after delivery is ignored
+        let sent_at = tracking.get_send(&token).unwrap().unwrap().sent_at;
+        assert_eq!(tracking.record_open(pixel_token, &contacts, sent_at).unwrap(), OpenOutcome::IgnoredPrefetch);
+        assert_eq!(contacts.get_contact(&ana).unwrap().open_count, 0);
+
+        let later = sent_at + chrono::Duration::minutes(3);
+        assert_eq!(tracking.record_open(pixel_token, &contacts, later).unwrap(), OpenOutcome::Recorded);
+        assert_eq!(tracking.record_open(pixel_token, &contacts, later).unwrap(), OpenOutcome::Recorded);
+        assert_eq!(tracking.record_open("nope", &contacts, later).unwrap(), OpenOutcome::UnknownToken);
+
+        let opened = contacts.get_contact(&ana).unwrap();
+        assert_eq!(opened.open_count, 1);
+        assert!(opened.last_email_opened.is_some());
+        assert_eq!(contacts.get_contact(&ben).unwrap().open_count, 0);
+        let send = tracking.get_send(&token).unwrap().unwrap();
+        assert_eq!((send.opens, send.ignored_opens), (2, 1));
+    }
+
+    #[test]
+    fn test_wrapped_link_hit_records_click_for_recipient() {
+        let tracking = EmailTrackingService::new();
+        let contacts = ContactServiceState::new();
+        let ana = add_contact(&contacts, "ana@example.com");
+        let ben = add_contact(&contacts, "ben@example.com");
+
+        let html = r#"<a href="https://shop.example/sale?a=1&amp;b=2">Sale</a> <a href='{{profile}}'>Profile</a> <a href="mailto:help@example.com">Help</a>"#;
+        let mut to_ana = message("ana@example.com", html);
+        to_ana.template_variables = Some(HashMap::from([("profile".to_string(), "https://app.example/u/ana".to_string())]));
+        let mut to_ben = message("ben@example.com", html);
+        to_ben.template_variables = Some(HashMap::from([("profile".to_string(), "https://app.example/u/ben".to_string())]));
+        tracking.prepare_message(&mut to_ana, &contacts, true).unwrap().unwrap();
+        let ben_token = tracking.prepare_message(&mut to_ben, &contacts, true).unwrap().unwrap();
+
+        assert!(to_ben.html_content.contains("mailto:help@example.com"));
+        assert!(!to_ben.html_content.contains("shop.example"));
+        let send = tracking.get_send(&ben_token).unwrap().unwrap();
+        assert_eq!(send.links, vec!["https://shop.example/sale?a=1&b=2", "https://app.example/u/ben"]);
+
+        // Images blocked: the click alone is credited as open and click
+        let path = path_of(&to_ben.html_content, "http://localhost:3001/t/c/");
+        let (token, index) = path.split_once('/').unwrap();
+        let target = tracking.record_click(token, index.parse().unwrap(), &contacts, Utc::now()).unwrap();
+        assert_eq!(target.as_deref(), Some("https://shop.example/sale?a=1&b=2"));
+        assert_eq!(tracking.record_click(token, 9, &contacts, Utc::now()).unwrap(), None);
+
+        let clicked = contacts.get_contact(&ben).unwrap();
+        assert_eq!((clicked.open_count, clicked.click_count), (1, 1));
+        assert_eq!(clicked.link_clicks["https://shop.example/sale?a=1&b=2"], 1);
+        let untouched = contacts.get_contact(&ana).unwrap();
+        assert_eq!((untouched.open_count, untouched.click_count), (0, 0));
+    }
+
+    #[test]
+    fn test_opted_out_recipient_gets_no_tracking() {
+        let tracking = EmailTrackingService::new();
+        let contacts = ContactServiceState::new();
+        let ana = add_contact(&contacts, "ana@example.com");
+
+        let mut first = message("ana@example.com", r#"<a href="https://shop.example/">Shop</a>"#);
+        tracking.prepare_message(&mut first, &contacts, true).unwrap().unwrap();
+
+        // A re-sent, already tracked body is unwrapped for an opted-out recipient
+        contacts.set_tracking_opt_out(&ana, true).unwrap();
+        let mut resend = message("ana@example.com", &first.html_content);
+        assert_eq!(tracking.prepare_message(&mut resend, &contacts, true).unwrap(), None);
+        assert_eq!(resend.html_content, r#"<a href="https://shop.example/">Shop</a>"#);
+        assert!(resend.tracking_opt_out);
+
+        // Per-send opt-out
+        contacts.set_tracking_opt_out(&ana, false).unwrap();
+        let mut untracked = message("ana@example.com", r#"<a href="https://shop.example/">Shop</a>"#);
+        assert_eq!(tracking.prepare_message(&mut untracked, &contacts, false).unwrap(), None);
+        assert!(!untracked.html_content.contains("/t/"));
+    }
+}
@@ -0,0 +1,407 @@
+// ═══════════════════════════════════════════════════════════════════════════════
+// GAMIFICATION LEADERBOARD - Server-computed rankings from audited XP events
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Every XP award is appended to the ledger and folded into one ordered board per
+// window (all-time / weekly / monthly), so ranks are maintained incrementally
+// instead of re-sorting every user on each read. Accounts whose XP velocity is
+// anomalous are flagged and kept off the boards until the flag is cleared.
+//
+// ═══════════════════════════════════════════════════════════════════════════════
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// Largest single award accepted into the ledger
+const MAX_SINGLE_AWARD: u32 = 10_000;
+/// Sliding window used for the XP velocity check
+const VELOCITY_WINDOW_SECS: i64 = 3600;
+/// XP an account may earn inside the velocity window before it is flagged
+const MAX_XP_PER_VELOCITY_WINDOW: u64 = 5_000;
+const MAX_PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardPeriod {
+    AllTime,
+    Weekly,
+    Monthly,
+}
+
+impl LeaderboardPeriod {
+    const ALL: [LeaderboardPeriod; 3] = [
+        LeaderboardPeriod::AllTime,
+        LeaderboardPeriod::Weekly,
+        LeaderboardPeriod::Monthly,
+    ];
+
+    pub fn parse(period: &str) -> Self {
+        match period.trim().to_lowercase().as_str() {
+            "weekly" | "week" => LeaderboardPeriod::Weekly,
+            "monthly" | "month" => LeaderboardPeriod::Monthly,
+            _ => LeaderboardPeriod::AllTime,
+        }
+    }
+
+    /// Start of the window containing `at`; weeks start Monday 00:00 UTC
+    pub fn period_start(&self, at: DateTime<Utc>) -> i64 {
+        let midnight = Utc
+            .with_ymd_and_hms(at.year(), at.month(), at.day(), 0, 0, 0)
+            .single()
+            .unwrap_or(at);
+        match self {
+            LeaderboardPeriod::AllTime => 0,
+            LeaderboardPeriod::Weekly => {
+                (midnight - Duration::days(at.weekday().num_days_from_monday() as i64)).timestamp()
+            }
+            LeaderboardPeriod::Monthly => (midnight - Duration::days(at.day0() as i64)).timestamp(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XpEvent {
+    pub id: String,
+    pub user_id: String,
+    pub amount: u32,
+    pub source: String,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountFlag {
+    pub user_id: String,
+    pub reason: String,
+    pub flagged_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedStanding {
+    pub user_id: String,
+    pub username: String,
+    pub rank: u32,
+    /// XP earned inside the window
+    pub xp: u64,
+    pub total_xp: u64,
+    /// When the window score was reached; earlier wins ties
+    pub reached_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardPage {
+    pub period: LeaderboardPeriod,
+    pub entries: Vec<RankedStanding>,
+    pub total: usize,
+    pub page: usize,
+    pub page_size: usize,
+    pub user_rank: Option<u32>,
+}
+
+struct Standing {
+    xp: u64,
+    reached_at: i64,
+}
+
+type OrderKey = (Reverse<u64>, i64, String);
+
+/// Scores for one window plus an ordered index over them
+#[derive(Default)]
+struct Board {
+    period_start: i64,
+    standings: HashMap<String, Standing>,
+    order: BTreeSet<OrderKey>,
+}
+
+impl Board {
+    fn add(&mut self, user_id: &str, amount: u64, at: i64) {
+        let standing = self
+            .standings
+            .entry(user_id.to_string())
+            .or_insert(Standing { xp: 0, reached_at: at });
+        self.order
+            .remove(&(Reverse(standing.xp), standing.reached_at, user_id.to_string()));
+        standing.xp += amount;
+        standing.reached_at = at;
+        self.order
+            .insert((Reverse(standing.xp), standing.reached_at, user_id.to_string()));
+    }
+
+    fn remove(&mut self, user_id: &str) {
+        if let Some(standing) = self.standings.remove(user_id) {
+            self.order
+                .remove(&(Reverse(standing.xp), standing.reached_at, user_id.to_string()));
+        }
+    }
+
+    /// Zero-based position of the user
+    fn position(&self, user_id: &str) -> Option<usize> {
+        let standing = self.standings.get(user_id)?;
+        let key = (Reverse(standing.xp), standing.reached_at, user_id.to_string());
+        Some(self.order.range(..key).count())
+    }
+}
+
+pub struct Leaderboard {
+    ledger: Vec<XpEvent>,
+    usernames: HashMap<String, String>,
+    totals: HashMap<String, u64>,
+    recent: HashMap<String, VecDeque<(i64, u32)>>,
+    flagged: HashMap<String, AccountFlag>,
+    boards: HashMap<LeaderboardPeriod, Board>,
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Self {
+            ledger: Vec::new(),
+            usernames: HashMap::new(),
+            totals: HashMap::new(),
+            recent: HashMap::new(),
+            flagged: HashMap::new(),
+            boards: LeaderboardPeriod::ALL
+                .iter()
+                .map(|period| (*period, Board::default()))
+                .collect(),
+        }
+    }
+}
+
+impl Leaderboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an XP award and update every window it falls in
+    pub fn award(
+        &mut self,
+        user_id: &str,
+        username: &str,
+        amount: u32,
+        source: &str,
+        at: DateTime<Utc>,
+    ) -> Result<XpEvent, String> {
+        if amount == 0 {
+            return Err("XP amount must be positive".to_string());
+        }
+        if amount > MAX_SINGLE_AWARD {
+            return Err(format!("XP award exceeds the {} XP limit", MAX_SINGLE_AWARD));
+        }
+
+        let event = XpEvent {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            amount,
+            source: source.to_string(),
+            timestamp: at.timestamp(),
+        };
+        self.ledger.push(event.clone());
+        self.usernames.insert(user_id.to_string(), username.to_string());
+        *self.totals.entry(user_id.to_string()).or_insert(0) += amount as u64;
+
+        let recent = self.recent.entry(user_id.to_string()).or_default();
+        recent.push_back((event.timestamp, amount));
+        while recent
+            .front()
+            .is_some_and(|(ts, _)| event.timestamp - ts >= VELOCITY_WINDOW_SECS)
+        {
+            recent.pop_front();
+        }
+        let velocity: u64 = recent.iter().map(|(_, xp)| *xp as u64).sum();
+        if velocity > MAX_XP_PER_VELOCITY_WINDOW && !self.flagged.contains_key(user_id) {
+            self.flag_account(
+                user_id,
+                &format!("Earned {} XP within {} minutes", velocity, VELOCITY_WINDOW_SECS / 60),
+                at,
+            );
+        }
+
+        if !self.flagged.contains_key(user_id) {
+            self.apply(&event, at);
+        }
+        Ok(event)
+    }
+
+    /// Exclude an account from every board, e.g. on an anti-abuse signal
+    pub fn flag_account(&mut self, user_id: &str, reason: &str, at: DateTime<Utc>) {
+        log::warn!("Leaderboard flag on {}: {}", user_id, reason);
+        self.flagged.insert(
+            user_id.to_string(),
+            AccountFlag {
+                user_id: user_id.to_string(),
+                reason: reason.to_string(),
+                flagged_at: at.timestamp(),
+            },
+        );
+        for board in self.boards.values_mut() {
+            board.remove(user_id);
+        }
+    }
+
+    /// Lift a flag and restore the account's standings from its ledger
+    pub fn clear_flag(&mut self, user_id: &str, now: DateTime<Utc>) -> bool {
+        if self.flagged.remove(user_id).is_none() {
+            return false;
+        }
+        self.recent.remove(user_id);
+        let events: Vec<XpEvent> = self
+            .ledger
+            .iter()
+            .filter(|e| e.user_id == user_id)
+            .cloned()
+            .collect();
+        for event in &events {
+            self.apply(event, now);
+        }
+        true
+    }
+
+    pub fn flagged_accounts(&self) -> Vec<AccountFlag> {
+        self.flagged.values().cloned().collect()
+    }
+
+    pub fn events_for(&self, user_id: &str) -> Vec<XpEvent> {
+        self.ledger.iter().filter(|e| e.user_id == user_id).cloned().collect()
+    }
+
+    pub fn rank_of(&mut self, period: LeaderboardPeriod, user_id: &str, now: DateTime<Utc>) -> Option<u32> {
+        let board = self.board(period, now);
+        board.position(user_id).map(|p| p as u32 + 1)
+    }
+
+    /// One page of the window; without an explicit page, the one holding `user_id`
+    pub fn page(
+        &mut self,
+        period: LeaderboardPeriod,
+        user_id: Option<&str>,
+        page: Option<usize>,
+        page_size: usize,
+        now: DateTime<Utc>,
+    ) -> LeaderboardPage {
+        let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        let user_position = {
+            let board = self.board(period, now);
+            user_id.and_then(|id| board.position(id))
+        };
+        let page = page.unwrap_or_else(|| user_position.map(|p| p / page_size).unwrap_or(0));
+
+        let board = &self.boards[&period];
+        let entries = board
+            .order
+            .iter()
+            .enumerate()
+            .skip(page * page_size)
+            .take(page_size)
+            .map(|(position, (Reverse(xp), reached_at, user_id))| RankedStanding {
+                user_id: user_id.clone(),
+                username: self.usernames.get(user_id).cloned().unwrap_or_else(|| user_id.clone()),
+                rank: position as u32 + 1,
+                xp: *xp,
+                total_xp: self.totals.get(user_id).copied().unwrap_or(0),
+                reached_at: *reached_at,
+            })
+            .collect();
+
+        LeaderboardPage {
+            period,
+            entries,
+            total: board.order.len(),
+            page,
+            page_size,
+            user_rank: user_position.map(|p| p as u32 + 1),
+        }
+    }
+
+    fn apply(&mut self, event: &XpEvent, now: DateTime<Utc>) {
+        for period in LeaderboardPeriod::ALL {
+            let board = self.board(period, now);
+            if event.timestamp >= board.period_start {
+                board.add(&event.user_id, event.amount as u64, event.timestamp);
+            }
+        }
+    }
+
+    /// Board for the window containing `now`, reset when a new window has begun
+    fn board(&mut self, period: LeaderboardPeriod, now: DateTime<Utc>) -> &mut Board {
+        let start = period.period_start(now);
+        let board = self.boards.entry(period).or_default();
+        if start > board.period_start {
+            *board = Board {
+                period_start: start,
+                ..Board::default()
+            };
+        }
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_ranking_breaks_ties_by_earliest_achievement() {
+        let mut board = Leaderboard::new();
+        board.award("carol", "Carol", 300, "workflow", at(2026, 3, 2, 9)).unwrap();
+        board.award("bob", "Bob", 500, "workflow", at(2026, 3, 2, 11)).unwrap();
+        board.award("alice", "Alice", 500, "workflow", at(2026, 3, 2, 10)).unwrap();
+
+        let page = board.page(LeaderboardPeriod::AllTime, None, None, 10, at(2026, 3, 2, 12));
+        let order: Vec<&str> = page.entries.iter().map(|e| e.user_id.as_str()).collect();
+        assert_eq!(order, ["alice", "bob", "carol"]);
+        assert_eq!(page.entries[0].rank, 1);
+        assert_eq!(page.entries[2].rank, 3);
+
+        // Pagination centres on the requesting user
+        let around = board.page(LeaderboardPeriod::AllTime, Some("carol"), None, 2, at(2026, 3, 2, 12));
+        assert_eq!(around.page, 1);
+        assert_eq!(around.user_rank, Some(3));
+        assert_eq!(around.entries[0].user_id, "carol");
+    }
+
+    #[test]
+    fn test_weekly_window_resets_on_monday() {
+        let mut board = Leaderboard::new();
+        // 2026-03-08 is a Sunday, 2026-03-09 a Monday
+        board.award("alice", "Alice", 800, "autofill", at(2026, 3, 8, 23)).unwrap();
+        board.award("bob", "Bob", 200, "autofill", at(2026, 3, 9, 0)).unwrap();
+
+        let monday = at(2026, 3, 9, 1);
+        let weekly = board.page(LeaderboardPeriod::Weekly, None, None, 10, monday);
+        assert_eq!(weekly.total, 1);
+        assert_eq!(weekly.entries[0].user_id, "bob");
+        assert_eq!(weekly.entries[0].xp, 200);
+
+        assert_eq!(board.rank_of(LeaderboardPeriod::Monthly, "alice", monday), Some(1));
+        assert_eq!(board.rank_of(LeaderboardPeriod::AllTime, "alice", monday), Some(1));
+        assert_eq!(board.rank_of(LeaderboardPeriod::Weekly, "alice", monday), None);
+    }
+
+    #[test]
+    fn test_anomalous_velocity_flags_and_excludes_account() {
+        let mut board = Leaderboard::new();
+        let start = at(2026, 3, 10, 8);
+        board.award("honest", "Honest", 400, "autofill", start).unwrap();
+        for minute in 0..6 {
+            board
+                .award("farmer", "Farmer", 1_000, "autofill", start + Duration::minutes(minute))
+                .unwrap();
+        }
+
+        let now = start + Duration::minutes(10);
+        let page = board.page(LeaderboardPeriod::AllTime, Some("farmer"), None, 10, now);
+        assert_eq!(page.total, 1);
+        assert_eq!(page.entries[0].user_id, "honest");
+        assert_eq!(page.user_rank, None);
+        assert_eq!(board.flagged_accounts()[0].user_id, "farmer");
+        assert_eq!(board.events_for("farmer").len(), 6);
+
+        assert!(board.clear_flag("farmer", now));
+        assert_eq!(board.rank_of(LeaderboardPeriod::AllTime, "farmer", now), Some(1));
+    }
+}
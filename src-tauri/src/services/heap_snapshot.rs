@@ -0,0 +1,668 @@
+// Heap Snapshot - V8 .heapsnapshot files and retained-size analysis for DevTools
+// The tab runs HEAP_WALKER_SCRIPT, which walks everything reachable from
+// `window` and `document` and reports objects and references. The snapshot is
+// streamed to disk in the format Chrome DevTools' Memory tab loads (DevTools
+// derives retained sizes from it itself); the summary computes them here from
+// the dominator tree and flags detached DOM nodes that are still retained.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const NODE_FIELDS: &[&str] = &[
+    "type", "name", "id", "self_size", "edge_count", "trace_node_id", "detachedness",
+];
+const NODE_TYPES: &[&str] = &[
+    "hidden", "array", "string", "object", "code", "closure", "regexp", "number", "native",
+    "synthetic", "concatenated string", "sliced string", "symbol", "bigint", "object shape",
+];
+const EDGE_FIELDS: &[&str] = &["type", "name_or_index", "to_node"];
+const EDGE_TYPES: &[&str] = &["context", "element", "property", "internal", "hidden", "shortcut", "weak"];
+/// Walker ids start at 1; the synthetic root that owns the walk's roots is 0
+const ROOT_ID: u64 = 0;
+const TOP_RETAINERS: usize = 20;
+const UNREACHABLE: usize = usize::MAX;
+
+/// Walks the page's object graph; evaluates to a JSON `HeapGraph`
+pub const HEAP_WALKER_SCRIPT: &str = r#"(() => {
+  const MAX_OBJECTS = 200000;
+  const ids = new Map();
+  const nodes = [];
+  const edges = [];
+  const queue = [];
+  const visit = (v) => {
+    if (v === null || (typeof v !== 'object' && typeof v !== 'function' && typeof v !== 'string')) return null;
+    const known = ids.get(v);
+    if (known !== undefined) return known;
+    if (nodes.length >= MAX_OBJECTS) return null;
+    const id = nodes.length + 1;
+    ids.set(v, id);
+    const node = { id, kind: 'object', name: 'Object', selfSize: 16 };
+    if (typeof v === 'string') {
+      Object.assign(node, { kind: 'string', name: v.slice(0, 1000), selfSize: 16 + v.length * 2 });
+    } else {
+      if (typeof v === 'function') {
+        Object.assign(node, { kind: 'closure', name: v.name || '(anonymous)', selfSize: 32 });
+      } else if (typeof Node !== 'undefined' && v instanceof Node) {
+        Object.assign(node, { kind: 'native', name: v.constructor.name, selfSize: 96, detached: !v.isConnected });
+      } else if (Array.isArray(v)) {
+        Object.assign(node, { kind: 'array', name: 'Array', selfSize: 16 + v.length * 8 });
+      } else if (v instanceof RegExp) {
+        Object.assign(node, { kind: 'regexp', name: String(v), selfSize: 32 });
+      } else {
+        const ctor = Object.getPrototypeOf(v) && Object.getPrototypeOf(v).constructor;
+        node.name = (ctor && ctor.name) || 'Object';
+      }
+      queue.push(v);
+    }
+    nodes.push(node);
+    return id;
+  };
+  const link = (from, kind, name, v) => {
+    const to = visit(v);
+    if (to !== null) edges.push({ from, to, kind, name: String(name) });
+  };
+  const roots = [visit(window), visit(document)];
+  for (let i = 0; i < queue.length; i++) {
+    const v = queue[i];
+    const from = ids.get(v);
+    if (typeof Node !== 'undefined' && v instanceof Node) {
+      v.childNodes.forEach((child, index) => link(from, 'element', index, child));
+      if (v.parentNode) link(from, 'internal', 'parentNode', v.parentNode);
+    }
+    if (v instanceof Map) {
+      let index = 0;
+      v.forEach((value, key) => { link(from, 'internal', 'key' + index, key); link(from, 'internal', 'value' + index, value); index++; });
+    } else if (v instanceof Set) {
+      let index = 0;
+      v.forEach((value) => link(from, 'element', index++, value));
+    }
+    let names = [];
+    try { names = Object.getOwnPropertyNames(v); } catch (e) { names = []; }
+    const node = nodes[from - 1];
+    if (node.kind === 'object') node.selfSize += names.length * 8;
+    for (const name of names) {
+      let descriptor;
+      try { descriptor = Object.getOwnPropertyDescriptor(v, name); } catch (e) { continue; }
+      if (!descriptor || !('value' in descriptor)) continue;
+      const isIndex = Array.isArray(v) && String(name >>> 0) === name;
+      link(from, isIndex ? 'element' : 'property', name, descriptor.value);
+    }
+  }
+  return JSON.stringify({ roots, nodes, edges });
+})()"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeapNodeKind {
+    Hidden,
+    Array,
+    String,
+    Object,
+    Code,
+    Closure,
+    Regexp,
+    Number,
+    /// DOM nodes and other embedder objects
+    Native,
+    Synthetic,
+    Symbol,
+    Bigint,
+}
+
+impl HeapNodeKind {
+    fn type_index(self) -> usize {
+        match self {
+            Self::Hidden => 0,
+            Self::Array => 1,
+            Self::String => 2,
+            Self::Object => 3,
+            Self::Code => 4,
+            Self::Closure => 5,
+            Self::Regexp => 6,
+            Self::Number => 7,
+            Self::Native => 8,
+            Self::Synthetic => 9,
+            Self::Symbol => 12,
+            Self::Bigint => 13,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeapEdgeKind {
+    Context,
+    Element,
+    Property,
+    Internal,
+    Hidden,
+    Shortcut,
+    /// Does not keep the target alive
+    Weak,
+}
+
+impl HeapEdgeKind {
+    fn type_index(self) -> usize {
+        match self {
+            Self::Context => 0,
+            Self::Element => 1,
+            Self::Property => 2,
+            Self::Internal => 3,
+            Self::Hidden => 4,
+            Self::Shortcut => 5,
+            Self::Weak => 6,
+        }
+    }
+
+    /// Element and hidden edges are named by index in the V8 format
+    fn is_indexed(self) -> bool {
+        matches!(self, Self::Element | Self::Hidden)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapObject {
+    pub id: u64,
+    pub kind: HeapNodeKind,
+    /// Constructor name, string value or DOM interface (`HTMLDivElement`)
+    pub name: String,
+    pub self_size: u64,
+    /// Set for DOM nodes: whether the node is disconnected from any document
+    #[serde(default)]
+    pub detached: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapReference {
+    pub from: u64,
+    pub to: u64,
+    pub kind: HeapEdgeKind,
+    /// Property name, or the index for element edges
+    pub name: String,
+}
+
+/// Object graph as reported by HEAP_WALKER_SCRIPT
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapGraph {
+    /// Objects the walk started from (the window and the document)
+    pub roots: Vec<u64>,
+    pub nodes: Vec<HeapObject>,
+    pub edges: Vec<HeapReference>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapRetainer {
+    pub node_id: u64,
+    pub name: String,
+    pub kind: HeapNodeKind,
+    pub self_size: u64,
+    pub retained_size: u64,
+    /// Shortest path from a root, e.g. `Window.cache[3]`
+    pub retaining_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetachedNodeLeak {
+    pub node_id: u64,
+    pub name: String,
+    pub retained_size: u64,
+    /// Detached DOM nodes kept alive only through this one (itself included)
+    pub detached_nodes: usize,
+    pub retaining_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeapSummary {
+    pub tab_id: String,
+    pub snapshot_path: String,
+    pub taken_at: i64,
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub total_size: u64,
+    pub top_retainers: Vec<HeapRetainer>,
+    /// Detached DOM subtrees still reachable from the page, largest first
+    pub detached_nodes: Vec<DetachedNodeLeak>,
+}
+
+#[derive(Default)]
+struct StringTable<'a> {
+    strings: Vec<&'a str>,
+    ids: HashMap<&'a str, usize>,
+}
+
+impl<'a> StringTable<'a> {
+    fn intern(&mut self, value: &'a str) -> usize {
+        let strings = &mut self.strings;
+        *self.ids.entry(value).or_insert_with(|| {
+            strings.push(value);
+            strings.len() - 1
+        })
+    }
+}
+
+struct HeapEdge {
+    kind: HeapEdgeKind,
+    name: String,
+    to: usize,
+}
+
+/// Indexed heap graph; node 0 is the synthetic root
+pub struct HeapSnapshot {
+    nodes: Vec<HeapObject>,
+    edges: Vec<Vec<HeapEdge>>,
+}
+
+struct HeapAnalysis {
+    /// Immediate dominator per node, UNREACHABLE when not reachable from the root
+    dominators: Vec<usize>,
+    retained: Vec<u64>,
+    /// Detached DOM nodes in each node's dominator subtree
+    detached_dominated: Vec<usize>,
+    /// Breadth-first parent and the edge taken from it
+    parents: Vec<Option<(usize, usize)>>,
+}
+
+impl HeapSnapshot {
+    /// Indexes the graph; duplicate ids keep the first object and references
+    /// to unknown ids are dropped
+    pub fn from_graph(graph: HeapGraph) -> Self {
+        let mut nodes = vec![HeapObject {
+            id: ROOT_ID,
+            kind: HeapNodeKind::Synthetic,
+            name: String::new(),
+            self_size: 0,
+            detached: None,
+        }];
+        let mut index: HashMap<u64, usize> = HashMap::new();
+        index.insert(ROOT_ID, 0);
+        for object in graph.nodes {
+            if let std::collections::hash_map::Entry::Vacant(entry) = index.entry(object.id) {
+                entry.insert(nodes.len());
+                nodes.push(object);
+            }
+        }
+
+        let mut edges: Vec<Vec<HeapEdge>> = (0..nodes.len()).map(|_| Vec::new()).collect();
+        for (position, root) in graph.roots.iter().enumerate() {
+            if let Some(&to) = index.get(root) {
+                edges[0].push(HeapEdge { kind: HeapEdgeKind::Element, name: position.to_string(), to });
+            }
+        }
+        for reference in graph.edges {
+            if let (Some(&from), Some(&to)) = (index.get(&reference.from), index.get(&reference.to)) {
+                edges[from].push(HeapEdge { kind: reference.kind, name: reference.name, to });
+            }
+        }
+
+        Self { nodes, edges }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.iter().map(Vec::len).sum()
+    }
+
+    /// Streams the snapshot to `path`, creating its directory
+    pub fn write_to_file(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create snapshot directory: {}", e))?;
+        }
+        let file = File::create(path).map_err(|e| format!("Failed to create snapshot file: {}", e))?;
+        let mut out = BufWriter::new(file);
+        self.write_to(&mut out)
+            .and_then(|_| out.flush())
+            .map_err(|e| format!("Failed to write heap snapshot: {}", e))
+    }
+
+    /// Writes the V8 heap snapshot JSON; nodes and edges are written row by
+    /// row so only the string table is held in memory
+    pub fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let header = serde_json::json!({
+            "meta": {
+                "node_fields": NODE_FIELDS,
+                "node_types": [NODE_TYPES, "string", "number", "number", "number", "number", "number"],
+                "edge_fields": EDGE_FIELDS,
+                "edge_types": [EDGE_TYPES, "string_or_number", "node"],
+                "trace_function_info_fields": ["function_id", "name", "script_name", "script_id", "line", "column"],
+                "trace_node_fields": ["id", "function_info_index", "count", "size", "children"],
+                "sample_fields": ["timestamp_us", "last_assigned_id"],
+                "location_fields": ["object_index", "script_id", "line", "column"]
+            },
+            "node_count": self.node_count(),
+            "edge_count": self.edge_count(),
+            "trace_function_count": 0
+        });
+
+        let mut strings = StringTable::default();
+
+        out.write_all(b"{\"snapshot\":")?;
+        serde_json::to_writer(&mut *out, &header)?;
+        out.write_all(b",\n\"nodes\":[")?;
+        for (i, node) in self.nodes.iter().enumerate() {
+            let detachedness = match node.detached {
+                None => 0,
+                Some(false) => 1,
+                Some(true) => 2,
+            };
+            writeln!(
+                out,
+                "{}{},{},{},{},{},0,{}",
+                if i == 0 { "" } else { "," },
+                node.kind.type_index(),
+                strings.intern(&node.name),
+                node.id,
+                node.self_size,
+                self.edges[i].len(),
+                detachedness
+            )?;
+        }
+        out.write_all(b"],\n\"edges\":[")?;
+        let mut first = true;
+        for edges in &self.edges {
+            for (position, edge) in edges.iter().enumerate() {
+                let name_or_index = if edge.kind.is_indexed() {
+                    edge.name.parse::<usize>().unwrap_or(position)
+                } else {
+                    strings.intern(&edge.name)
+                };
+                writeln!(
+                    out,
+                    "{}{},{},{}",
+                    if first { "" } else { "," },
+                    edge.kind.type_index(),
+                    name_or_index,
+                    edge.to * NODE_FIELDS.len()
+                )?;
+                first = false;
+            }
+        }
+        out.write_all(b"],\n\"trace_function_infos\":[],\n\"trace_tree\":[],\n\"samples\":[],\n\"locations\":[],\n\"strings\":[")?;
+        for (i, value) in strings.strings.iter().enumerate() {
+            if i > 0 {
+                out.write_all(b",\n")?;
+            }
+            serde_json::to_writer(&mut *out, value)?;
+        }
+        out.write_all(b"]}")
+    }
+
+    /// Retained sizes, top retainers and detached DOM leaks
+    pub fn summarize(&self, tab_id: &str, snapshot_path: &str) -> HeapSummary {
+        let analysis = self.analyze();
+
+        let mut ranked: Vec<usize> = (1..self.nodes.len())
+            .filter(|&i| analysis.dominators[i] != UNREACHABLE)
+            .collect();
+        ranked.sort_by(|&a, &b| analysis.retained[b].cmp(&analysis.retained[a]).then(a.cmp(&b)));
+        let top_retainers = ranked
+            .iter()
+            .take(TOP_RETAINERS)
+            .map(|&i| HeapRetainer {
+                node_id: self.nodes[i].id,
+                name: self.nodes[i].name.clone(),
+                kind: self.nodes[i].kind,
+                self_size: self.nodes[i].self_size,
+                retained_size: analysis.retained[i],
+                retaining_path: self.retaining_path(&analysis, i),
+            })
+            .collect();
+
+        // Only the outermost detached node of each leaked subtree is reported
+        let detached_nodes: Vec<DetachedNodeLeak> = ranked
+            .iter()
+            .filter(|&&i| self.is_detached(i) && !self.is_detached(analysis.dominators[i]))
+            .map(|&i| DetachedNodeLeak {
+                node_id: self.nodes[i].id,
+                name: self.nodes[i].name.clone(),
+                retained_size: analysis.retained[i],
+                detached_nodes: analysis.detached_dominated[i],
+                retaining_path: self.retaining_path(&analysis, i),
+            })
+            .collect();
+
+        HeapSummary {
+            tab_id: tab_id.to_string(),
+            snapshot_path: snapshot_path.to_string(),
+            taken_at: chrono::Utc::now().timestamp_millis(),
+            node_count: self.node_count(),
+            edge_count: self.edge_count(),
+            total_size: self.nodes.iter().map(|n| n.self_size).sum(),
+            top_retainers,
+            detached_nodes,
+        }
+    }
+
+    fn is_detached(&self, index: usize) -> bool {
+        self.nodes[index].detached == Some(true)
+    }
+
+    fn strong_edges(&self, index: usize) -> impl Iterator<Item = (usize, &HeapEdge)> {
+        self.edges[index]
+            .iter()
+            .enumerate()
+            .filter(|(_, edge)| edge.kind != HeapEdgeKind::Weak)
+    }
+
+    /// Dominators via Cooper-Harvey-Kennedy over a depth-first postorder,
+    /// ignoring weak edges
+    fn analyze(&self) -> HeapAnalysis {
+        let n = self.nodes.len();
+
+        let mut postorder = Vec::with_capacity(n);
+        let mut visited = vec![false; n];
+        let mut stack = vec![(0usize, 0usize)];
+        visited[0] = true;
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            let Some(edge) = self.edges[node].get(*next) else {
+                postorder.push(node);
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+            if edge.kind != HeapEdgeKind::Weak && !visited[edge.to] {
+                visited[edge.to] = true;
+                stack.push((edge.to, 0));
+            }
+        }
+
+        let mut post_number = vec![UNREACHABLE; n];
+        for (number, &node) in postorder.iter().enumerate() {
+            post_number[node] = number;
+        }
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for &node in &postorder {
+            for (_, edge) in self.strong_edges(node) {
+                predecessors[edge.to].push(node);
+            }
+        }
+
+        let mut dominators = vec![UNREACHABLE; n];
+        dominators[0] = 0;
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &node in postorder.iter().rev().skip(1) {
+                let mut candidate = UNREACHABLE;
+                for &pred in &predecessors[node] {
+                    if dominators[pred] == UNREACHABLE {
+                        continue;
+                    }
+                    candidate = if candidate == UNREACHABLE {
+                        pred
+                    } else {
+                        let (mut a, mut b) = (candidate, pred);
+                        while a != b {
+                            while post_number[a] < post_number[b] {
+                                a = dominators[a];
+                            }
+                            while post_number[b] < post_number[a] {
+                                b = dominators[b];
+                            }
+                        }
+                        a
+                    };
+                }
+                if candidate != dominators[node] {
+                    dominators[node] = candidate;
+                    changed = true;
+                }
+            }
+        }
+
+        // A node's dominator finishes after it in the postorder, so one pass
+        // accumulates whole dominator subtrees
+        let mut retained: Vec<u64> = self.nodes.iter().map(|n| n.self_size).collect();
+        let mut detached_dominated: Vec<usize> = (0..n).map(|i| usize::from(self.is_detached(i))).collect();
+        for &node in &postorder {
+            if node != 0 {
+                let dominator = dominators[node];
+                retained[dominator] += retained[node];
+                detached_dominated[dominator] += detached_dominated[node];
+            }
+        }
+
+        let mut parents: Vec<Option<(usize, usize)>> = vec![None; n];
+        let mut seen = vec![false; n];
+        let mut queue = VecDeque::from([0usize]);
+        seen[0] = true;
+        while let Some(node) = queue.pop_front() {
+            for (position, edge) in self.strong_edges(node) {
+                if !seen[edge.to] {
+                    seen[edge.to] = true;
+                    parents[edge.to] = Some((node, position));
+                    queue.push_back(edge.to);
+                }
+            }
+        }
+
+        HeapAnalysis { dominators, retained, detached_dominated, parents }
+    }
+
+    fn retaining_path(&self, analysis: &HeapAnalysis, index: usize) -> String {
+        let mut steps = Vec::new();
+        let mut current = index;
+        while let Some((parent, position)) = analysis.parents[current] {
+            if parent == 0 {
+                break;
+            }
+            let edge = &self.edges[parent][position];
+            steps.push(if edge.kind.is_indexed() {
+                format!("[{}]", edge.name)
+            } else {
+                format!(".{}", edge.name)
+            });
+            current = parent;
+        }
+        steps.push(self.nodes[current].name.clone());
+        steps.reverse();
+        steps.concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(id: u64, kind: HeapNodeKind, name: &str, self_size: u64, detached: Option<bool>) -> HeapObject {
+        HeapObject { id, kind, name: name.to_string(), self_size, detached }
+    }
+
+    fn reference(from: u64, to: u64, kind: HeapEdgeKind, name: &str) -> HeapReference {
+        HeapReference { from, to, kind, name: name.to_string() }
+    }
+
+    #[test]
+    fn test_detached_node_leak_in_snapshot_and_summary() {
+        use HeapEdgeKind::*;
+        use HeapNodeKind::*;
+
+        // window.leakedNodes = [div] after `div` (with a span child) was removed
+        // from the page; the attached body also has a div
+        let graph = HeapGraph {
+            roots: vec![1, 2],
+            nodes: vec![
+                object(1, Object, "Window", 400, None),
+                object(2, Native, "HTMLDocument", 96, Some(false)),
+                object(3, Native, "HTMLBodyElement", 96, Some(false)),
+                object(4, Native, "HTMLDivElement", 96, Some(false)),
+                object(5, Array, "Array", 24, None),
+                object(6, Native, "HTMLDivElement", 96, Some(true)),
+                object(7, Native, "HTMLSpanElement", 96, Some(true)),
+                object(8, Closure, "onResize", 32, None),
+            ],
+            edges: vec![
+                reference(1, 5, Property, "leakedNodes"),
+                reference(1, 8, Property, "onResize"),
+                reference(2, 3, Element, "0"),
+                reference(3, 4, Element, "0"),
+                reference(3, 2, Internal, "parentNode"),
+                reference(4, 3, Internal, "parentNode"),
+                reference(5, 6, Element, "0"),
+                reference(6, 7, Element, "0"),
+                reference(7, 6, Internal, "parentNode"),
+                reference(8, 4, Weak, "target"),
+            ],
+        };
+
+        let snapshot = HeapSnapshot::from_graph(graph);
+        let summary = snapshot.summarize("tab-1", "/tmp/tab-1.heapsnapshot");
+
+        assert_eq!(summary.node_count, 9);
+        assert_eq!(summary.edge_count, 12);
+        assert_eq!(summary.detached_nodes.len(), 1);
+        let leak = &summary.detached_nodes[0];
+        assert_eq!(leak.node_id, 6);
+        assert_eq!(leak.name, "HTMLDivElement");
+        assert_eq!(leak.detached_nodes, 2);
+        assert_eq!(leak.retained_size, 192);
+        assert_eq!(leak.retaining_path, "Window.leakedNodes[0]");
+
+        let window = &summary.top_retainers[0];
+        assert_eq!(window.node_id, 1);
+        assert_eq!(window.retained_size, 400 + 24 + 96 + 96 + 32);
+        // The weak reference from the closure does not retain the attached div
+        let attached = summary.top_retainers.iter().find(|r| r.node_id == 4).unwrap();
+        assert_eq!(attached.retaining_path, "HTMLDocument[0][0]");
+        let document = summary.top_retainers.iter().find(|r| r.node_id == 2).unwrap();
+        assert_eq!(document.retained_size, 96 * 3);
+
+        let mut out = Vec::new();
+        snapshot.write_to(&mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json["snapshot"]["node_count"], 9);
+        let nodes = json["nodes"].as_array().unwrap();
+        let edges = json["edges"].as_array().unwrap();
+        let strings = json["strings"].as_array().unwrap();
+        assert_eq!(nodes.len(), 9 * NODE_FIELDS.len());
+        assert_eq!(edges.len(), 12 * EDGE_FIELDS.len());
+        assert_eq!(
+            nodes.chunks(NODE_FIELDS.len()).map(|n| n[4].as_u64().unwrap()).sum::<u64>(),
+            12
+        );
+
+        let leaked_row = &nodes[6 * NODE_FIELDS.len()..7 * NODE_FIELDS.len()];
+        assert_eq!(leaked_row[0], 8);
+        assert_eq!(strings[leaked_row[1].as_u64().unwrap() as usize], "HTMLDivElement");
+        assert_eq!(leaked_row[2], 6);
+        assert_eq!(leaked_row[6], 2);
+
+        // Window's first edge is the property `leakedNodes` pointing at the array
+        let window_edge = &edges[2 * EDGE_FIELDS.len()..3 * EDGE_FIELDS.len()];
+        assert_eq!(window_edge[0], 2);
+        assert_eq!(strings[window_edge[1].as_u64().unwrap() as usize], "leakedNodes");
+        assert_eq!(window_edge[2], 5 * NODE_FIELDS.len());
+    }
+}
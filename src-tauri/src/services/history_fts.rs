@@ -0,0 +1,141 @@
+// CUBE Nexum - History Full-Text Index
+// SQLite FTS5 index over history titles, URLs, page snippets and tags
+
+use rusqlite::{params, Connection};
+
+/// Candidates pulled by BM25 before the caller re-ranks them
+pub const FTS_CANDIDATE_POOL: u32 = 200;
+
+// bm25() column weights, in table order: entry_id (unindexed), title, url, content, tags
+const BM25_WEIGHTS: &str = "0.0, 10.0, 4.0, 1.0, 6.0";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FtsHit {
+    pub id: String,
+    /// Negated BM25, so higher is a better match
+    pub relevance: f64,
+    pub snippet: Option<String>,
+}
+
+/// In-memory FTS5 table mirroring the history entries. Rows are keyed by
+/// entry id; writes replace any previous row for the same id.
+pub struct HistoryFtsIndex {
+    conn: Connection,
+}
+
+impl HistoryFtsIndex {
+    pub fn new() -> Result<Self, String> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| format!("Failed to open history index: {}", e))?;
+        conn.execute_batch(
+            "CREATE VIRTUAL TABLE history_fts USING fts5(
+                entry_id UNINDEXED,
+                title,
+                url,
+                content,
+                tags,
+                tokenize = 'unicode61 remove_diacritics 2'
+            );",
+        )
+        .map_err(|e| format!("Failed to create history index: {}", e))?;
+        Ok(Self { conn })
+    }
+
+    pub fn upsert(&self, id: &str, title: &str, url: &str, content: &str, tags: &str) -> Result<(), String> {
+        self.remove(id)?;
+        self.conn
+            .execute(
+                "INSERT INTO history_fts (entry_id, title, url, content, tags) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, title, url, content, tags],
+            )
+            .map_err(|e| format!("Failed to index entry: {}", e))?;
+        Ok(())
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM history_fts WHERE entry_id = ?1", params![id])
+            .map_err(|e| format!("Failed to unindex entry: {}", e))?;
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        self.conn
+            .execute("DELETE FROM history_fts", [])
+            .map_err(|e| format!("Failed to clear history index: {}", e))?;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM history_fts", [], |row| row.get::<_, i64>(0))
+            .unwrap_or(0) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Best `limit` matches for `query` by BM25. Every term must match; the
+    /// last one is treated as a prefix so partially typed words still hit.
+    pub fn search(&self, query: &str, limit: u32) -> Result<Vec<FtsHit>, String> {
+        let Some(expression) = match_expression(query) else {
+            return Ok(Vec::new());
+        };
+
+        let sql = format!(
+            "SELECT entry_id, bm25(history_fts, {weights}), snippet(history_fts, 3, '', '', '…', 16)
+             FROM history_fts
+             WHERE history_fts MATCH ?1
+             ORDER BY bm25(history_fts, {weights})
+             LIMIT ?2",
+            weights = BM25_WEIGHTS
+        );
+        let mut stmt = self
+            .conn
+            .prepare(&sql)
+            .map_err(|e| format!("Failed to prepare history search: {}", e))?;
+        let hits = stmt
+            .query_map(params![expression, limit], |row| {
+                let snippet: Option<String> = row.get(2)?;
+                Ok(FtsHit {
+                    id: row.get(0)?,
+                    relevance: -row.get::<_, f64>(1)?,
+                    snippet: snippet.filter(|s| !s.trim().is_empty()),
+                })
+            })
+            .map_err(|e| format!("History search failed: {}", e))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("History search failed: {}", e))?;
+        Ok(hits)
+    }
+}
+
+/// Lowercased alphanumeric words of `query`, the same way unicode61 splits them
+pub fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Quotes each term so FTS5 operators typed by the user are matched literally
+fn match_expression(query: &str) -> Option<String> {
+    let terms = query_terms(query);
+    let last = terms.len().checked_sub(1)?;
+    Some(
+        terms
+            .iter()
+            .enumerate()
+            .map(|(i, term)| {
+                if i == last {
+                    format!("\"{}\"*", term)
+                } else {
+                    format!("\"{}\"", term)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
@@ -0,0 +1,402 @@
+// CUBE Nexum - History Semantic Index
+// On-device embeddings and an HNSW approximate nearest-neighbour index for history
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+pub const EMBEDDING_DIMENSIONS: usize = 256;
+
+const CHAR_NGRAM: usize = 3;
+const WORD_WEIGHT: f32 = 1.0;
+const NGRAM_WEIGHT: f32 = 0.35;
+
+const STOPWORDS: &[&str] = &[
+    "a", "about", "an", "and", "are", "as", "at", "be", "by", "for", "from", "how", "in", "is",
+    "it", "of", "on", "or", "that", "the", "this", "to", "was", "what", "with",
+];
+
+// ==================== Embedder ====================
+
+/// Produces fixed-size, L2-normalised vectors for history content.
+pub trait TextEmbedder: Send + Sync {
+    fn embed(&self, text: &str) -> Vec<f32>;
+}
+
+/// Small on-device embedder: hashed stems plus character trigrams, so related
+/// word forms ("async"/"asynchronous", "runtime"/"runtimes") land close together
+/// without shipping model weights.
+#[derive(Debug, Default, Clone)]
+pub struct HashingEmbedder;
+
+impl HashingEmbedder {
+    /// Distinct stems; repeating a word does not make a page more relevant
+    fn tokens(text: &str) -> Vec<String> {
+        let mut seen = HashSet::new();
+        text.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|t| t.len() > 1 && !STOPWORDS.contains(t))
+            .map(stem)
+            .filter(|t| seen.insert(t.clone()))
+            .collect()
+    }
+
+    fn bucket(feature: &str) -> (usize, f32) {
+        let mut hasher = DefaultHasher::new();
+        feature.hash(&mut hasher);
+        let hash = hasher.finish();
+        let index = (hash % EMBEDDING_DIMENSIONS as u64) as usize;
+        let sign = if (hash >> 63) == 0 { 1.0 } else { -1.0 };
+        (index, sign)
+    }
+}
+
+impl TextEmbedder for HashingEmbedder {
+    fn embed(&self, text: &str) -> Vec<f32> {
+        let mut vector = vec![0.0f32; EMBEDDING_DIMENSIONS];
+
+        for token in Self::tokens(text) {
+            let (index, sign) = Self::bucket(&format!("w:{}", token));
+            vector[index] += sign * WORD_WEIGHT;
+
+            let padded: Vec<char> = format!("<{}>", token).chars().collect();
+            if padded.len() >= CHAR_NGRAM {
+                for window in padded.windows(CHAR_NGRAM) {
+                    let gram: String = window.iter().collect();
+                    let (index, sign) = Self::bucket(&format!("g:{}", gram));
+                    vector[index] += sign * NGRAM_WEIGHT;
+                }
+            }
+        }
+
+        normalize(&mut vector);
+        vector
+    }
+}
+
+fn stem(token: &str) -> String {
+    for suffix in ["ations", "ation", "ings", "ing", "ies", "es", "ed", "s"] {
+        if token.len() > suffix.len() + 3 && token.ends_with(suffix) {
+            return token[..token.len() - suffix.len()].to_string();
+        }
+    }
+    token.to_string()
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+// ==================== HNSW Index ====================
+
+#[derive(Debug, Clone)]
+struct HnswNode {
+    vector: Vec<f32>,
+    /// Neighbour ids per layer, layer 0 first
+    neighbors: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Candidate<'a> {
+    distance: f32,
+    id: &'a str,
+}
+
+impl Eq for Candidate<'_> {}
+
+impl PartialOrd for Candidate<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .partial_cmp(&other.distance)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.id.cmp(other.id))
+    }
+}
+
+/// Hierarchical navigable small world graph keyed by history entry id.
+/// Supports incremental insert/remove; distances are `1 - cosine`.
+#[derive(Debug, Clone)]
+pub struct HnswIndex {
+    max_neighbors: usize,
+    ef_construction: usize,
+    level_factor: f64,
+    nodes: HashMap<String, HnswNode>,
+    entry_point: Option<String>,
+}
+
+impl Default for HnswIndex {
+    fn default() -> Self {
+        Self::new(12, 64)
+    }
+}
+
+impl HnswIndex {
+    pub fn new(max_neighbors: usize, ef_construction: usize) -> Self {
+        let max_neighbors = max_neighbors.max(2);
+        Self {
+            max_neighbors,
+            ef_construction: ef_construction.max(max_neighbors),
+            level_factor: 1.0 / (max_neighbors as f64).ln(),
+            nodes: HashMap::new(),
+            entry_point: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.nodes.contains_key(id)
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        self.nodes.keys().cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.entry_point = None;
+    }
+
+    /// Levels come from a hash of the id so rebuilding the index is deterministic.
+    fn level_for(&self, id: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        id.hash(&mut hasher);
+        let unit = (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64;
+        let unit = unit.max(f64::MIN_POSITIVE);
+        ((-unit.ln()) * self.level_factor).floor().min(16.0) as usize
+    }
+
+    fn top_level(&self) -> usize {
+        self.entry_point
+            .as_ref()
+            .and_then(|id| self.nodes.get(id))
+            .map(|node| node.neighbors.len() - 1)
+            .unwrap_or(0)
+    }
+
+    fn distance(&self, query: &[f32], id: &str) -> f32 {
+        self.nodes
+            .get(id)
+            .map(|node| 1.0 - cosine_similarity(query, &node.vector))
+            .unwrap_or(f32::MAX)
+    }
+
+    fn search_layer<'a>(&'a self, query: &[f32], entry: &'a str, ef: usize, layer: usize) -> Vec<Candidate<'a>> {
+        let start = Candidate { distance: self.distance(query, entry), id: entry };
+        let mut visited: HashSet<&str> = HashSet::from([entry]);
+        let mut candidates = BinaryHeap::from([std::cmp::Reverse(start)]);
+        let mut results = BinaryHeap::from([start]);
+
+        while let Some(std::cmp::Reverse(current)) = candidates.pop() {
+            let worst = results.peek().map(|c| c.distance).unwrap_or(f32::MAX);
+            if current.distance > worst && results.len() >= ef {
+                break;
+            }
+
+            let Some(node) = self.nodes.get(current.id) else { continue };
+            let Some(neighbors) = node.neighbors.get(layer) else { continue };
+
+            for neighbor in neighbors {
+                let Some((neighbor_id, _)) = self.nodes.get_key_value(neighbor) else { continue };
+                if !visited.insert(neighbor_id.as_str()) {
+                    continue;
+                }
+                let candidate = Candidate { distance: self.distance(query, neighbor_id), id: neighbor_id };
+                let worst = results.peek().map(|c| c.distance).unwrap_or(f32::MAX);
+                if results.len() < ef || candidate.distance < worst {
+                    candidates.push(std::cmp::Reverse(candidate));
+                    results.push(candidate);
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        results.into_sorted_vec()
+    }
+
+    fn greedy_descend<'a>(&'a self, query: &[f32], mut entry: &'a str, from: usize, to: usize) -> &'a str {
+        for layer in (to..=from).rev() {
+            if let Some(best) = self.search_layer(query, entry, 1, layer).first() {
+                entry = best.id;
+            }
+        }
+        entry
+    }
+
+    pub fn insert(&mut self, id: &str, vector: Vec<f32>) {
+        if self.nodes.contains_key(id) {
+            self.remove(id);
+        }
+
+        let level = self.level_for(id);
+        let Some(entry_point) = self.entry_point.clone() else {
+            self.nodes.insert(id.to_string(), HnswNode { vector, neighbors: vec![Vec::new(); level + 1] });
+            self.entry_point = Some(id.to_string());
+            return;
+        };
+
+        let top_level = self.top_level();
+        let mut links: Vec<Vec<String>> = vec![Vec::new(); level + 1];
+        {
+            let mut entry = if top_level > level {
+                self.greedy_descend(&vector, &entry_point, top_level, level + 1)
+            } else {
+                entry_point.as_str()
+            };
+            for layer in (0..=level.min(top_level)).rev() {
+                let found = self.search_layer(&vector, entry, self.ef_construction, layer);
+                if let Some(best) = found.first() {
+                    entry = best.id;
+                }
+                links[layer] = found.iter().take(self.max_neighbors).map(|c| c.id.to_string()).collect();
+            }
+        }
+
+        self.nodes.insert(id.to_string(), HnswNode { vector, neighbors: links.clone() });
+        for (layer, neighbors) in links.iter().enumerate() {
+            for neighbor in neighbors {
+                self.link(neighbor, id, layer);
+            }
+        }
+
+        if level > top_level {
+            self.entry_point = Some(id.to_string());
+        }
+    }
+
+    /// Adds a back-link and prunes the neighbour list to the closest `max_neighbors`.
+    fn link(&mut self, from: &str, to: &str, layer: usize) {
+        let Some(vector) = self.nodes.get(from).map(|n| n.vector.clone()) else { return };
+        let mut neighbors = match self.nodes.get(from).and_then(|n| n.neighbors.get(layer)) {
+            Some(neighbors) => neighbors.clone(),
+            None => return,
+        };
+        if !neighbors.iter().any(|n| n == to) {
+            neighbors.push(to.to_string());
+        }
+        if neighbors.len() > self.max_neighbors {
+            neighbors.sort_by(|a, b| {
+                self.distance(&vector, a)
+                    .partial_cmp(&self.distance(&vector, b))
+                    .unwrap_or(Ordering::Equal)
+            });
+            neighbors.truncate(self.max_neighbors);
+        }
+        if let Some(node) = self.nodes.get_mut(from) {
+            node.neighbors[layer] = neighbors;
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(removed) = self.nodes.remove(id) else { return false };
+
+        for (layer, neighbors) in removed.neighbors.iter().enumerate() {
+            for neighbor in neighbors {
+                if let Some(node) = self.nodes.get_mut(neighbor) {
+                    if let Some(list) = node.neighbors.get_mut(layer) {
+                        list.retain(|n| n != id);
+                    }
+                }
+            }
+            // Reconnect the orphaned neighbourhood so the graph stays navigable
+            for a in neighbors {
+                for b in neighbors {
+                    if a != b {
+                        self.link(a, b, layer);
+                    }
+                }
+            }
+        }
+
+        if self.entry_point.as_deref() == Some(id) {
+            self.entry_point = self
+                .nodes
+                .iter()
+                .max_by(|a, b| a.1.neighbors.len().cmp(&b.1.neighbors.len()).then(b.0.cmp(a.0)))
+                .map(|(id, _)| id.clone());
+        }
+        true
+    }
+
+    /// Returns up to `k` `(id, cosine_similarity)` pairs, best first.
+    pub fn search(&self, query: &[f32], k: usize, ef: usize) -> Vec<(String, f32)> {
+        let Some(entry_point) = self.entry_point.as_deref() else { return Vec::new() };
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let entry = self.greedy_descend(query, entry_point, self.top_level(), 1);
+        self.search_layer(query, entry, ef.max(k), 0)
+            .into_iter()
+            .take(k)
+            .map(|c| (c.id.to_string(), 1.0 - c.distance))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hnsw_matches_brute_force_after_incremental_updates() {
+        let embedder = HashingEmbedder;
+        let mut index = HnswIndex::new(4, 16);
+        let docs: Vec<(String, Vec<f32>)> = (0..60)
+            .map(|i| {
+                let id = format!("doc{}", i);
+                let vector = embedder.embed(&format!("topic{} shared{} words{}", i % 7, i % 3, i));
+                (id, vector)
+            })
+            .collect();
+        for (id, vector) in &docs {
+            index.insert(id, vector.clone());
+        }
+        assert!(index.remove("doc5"));
+        assert!(!index.contains("doc5"));
+
+        let query = embedder.embed("topic3 shared1");
+        let best = docs
+            .iter()
+            .filter(|(id, _)| id != "doc5")
+            .max_by(|a, b| {
+                cosine_similarity(&query, &a.1)
+                    .partial_cmp(&cosine_similarity(&query, &b.1))
+                    .unwrap()
+            })
+            .unwrap();
+
+        let results = index.search(&query, 3, 32);
+        assert_eq!(results.len(), 3);
+        assert!((results[0].1 - cosine_similarity(&query, &best.1)).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,612 @@
+// CUBE Web Engine - HTTP Authentication
+// Answers `401 WWW-Authenticate` challenges with Basic, Digest (RFC 7616) and
+// NTLMv2 handshakes. `Negotiate` is answered with NTLM tokens, which servers
+// fall back to when Kerberos is unavailable.
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Authorization rounds tried for one request before the 401 is returned
+pub const MAX_AUTH_ATTEMPTS: usize = 3;
+
+const NTLM_SIGNATURE: &[u8; 8] = b"NTLMSSP\0";
+const NTLM_NEGOTIATE_UNICODE: u32 = 0x0000_0001;
+const NTLM_REQUEST_TARGET: u32 = 0x0000_0004;
+const NTLM_NEGOTIATE_NTLM: u32 = 0x0000_0200;
+const NTLM_ALWAYS_SIGN: u32 = 0x0000_8000;
+const NTLM_EXTENDED_SESSION_SECURITY: u32 = 0x0008_0000;
+const NTLM_TARGET_INFO: u32 = 0x0080_0000;
+const NTLM_NEGOTIATE_128: u32 = 0x2000_0000;
+const NTLM_NEGOTIATE_56: u32 = 0x8000_0000;
+const NTLM_AV_TIMESTAMP: u16 = 7;
+/// 100ns intervals between 1601-01-01 and the Unix epoch
+const NTLM_EPOCH_OFFSET: u64 = 116_444_736_000_000_000;
+
+// ==================== Challenges ====================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthScheme {
+    Basic,
+    Digest,
+    Ntlm,
+    Negotiate,
+}
+
+impl AuthScheme {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "basic" => Some(Self::Basic),
+            "digest" => Some(Self::Digest),
+            "ntlm" => Some(Self::Ntlm),
+            "negotiate" => Some(Self::Negotiate),
+            _ => None,
+        }
+    }
+
+    pub fn header_name(&self) -> &'static str {
+        match self {
+            Self::Basic => "Basic",
+            Self::Digest => "Digest",
+            Self::Ntlm => "NTLM",
+            Self::Negotiate => "Negotiate",
+        }
+    }
+
+    /// Preference when a server offers several schemes
+    fn strength(&self) -> u8 {
+        match self {
+            Self::Ntlm => 4,
+            Self::Negotiate => 3,
+            Self::Digest => 2,
+            Self::Basic => 1,
+        }
+    }
+
+    fn is_connection_based(&self) -> bool {
+        matches!(self, Self::Ntlm | Self::Negotiate)
+    }
+}
+
+/// One challenge from a `WWW-Authenticate` header
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthChallenge {
+    pub scheme: AuthScheme,
+    /// Lowercased parameter names with unquoted values
+    pub params: HashMap<String, String>,
+    /// Base64 token of connection-based schemes (NTLM/Negotiate)
+    pub token: Option<String>,
+}
+
+impl AuthChallenge {
+    pub fn realm(&self) -> Option<&str> {
+        self.params.get("realm").map(String::as_str)
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(String::as_str)
+    }
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+/// Parse `name=value` pairs until the next scheme name or the end of input.
+/// Returns the parameters and the unparsed remainder.
+fn parse_auth_params(mut rest: &str) -> (HashMap<String, String>, &str) {
+    let mut params = HashMap::new();
+    loop {
+        let start = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        let name_len = start.find(|c: char| !is_token_char(c)).unwrap_or(start.len());
+        let after_name = start[name_len..].trim_start();
+        if name_len == 0 || !after_name.starts_with('=') {
+            return (params, start);
+        }
+
+        let value_start = after_name[1..].trim_start();
+        let (value, remainder) = if let Some(quoted) = value_start.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = quoted.len();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        if let Some((_, escaped)) = chars.next() {
+                            value.push(escaped);
+                        }
+                    }
+                    '"' => {
+                        end = i + 1;
+                        break;
+                    }
+                    _ => value.push(c),
+                }
+            }
+            (value, &quoted[end..])
+        } else {
+            let len = value_start
+                .find(|c: char| c == ',' || c.is_whitespace())
+                .unwrap_or(value_start.len());
+            (value_start[..len].to_string(), &value_start[len..])
+        };
+
+        params.insert(start[..name_len].to_ascii_lowercase(), value);
+        rest = remainder;
+    }
+}
+
+/// Parse the challenges in one or more `WWW-Authenticate` header values.
+/// Schemes we cannot answer are skipped.
+pub fn parse_www_authenticate<'a>(values: impl IntoIterator<Item = &'a str>) -> Vec<AuthChallenge> {
+    let mut challenges = Vec::new();
+
+    for value in values {
+        let mut rest = value;
+        loop {
+            rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+            if rest.is_empty() {
+                break;
+            }
+            let name_len = rest.find(|c: char| !is_token_char(c)).unwrap_or(rest.len());
+            if name_len == 0 {
+                // Stray character; skip it rather than loop forever
+                rest = &rest[rest.chars().next().map_or(1, char::len_utf8)..];
+                continue;
+            }
+            let scheme = AuthScheme::from_name(&rest[..name_len]);
+            rest = &rest[name_len..];
+
+            if scheme.is_some_and(|s| s.is_connection_based()) {
+                let len = rest.find(',').unwrap_or(rest.len());
+                let token = rest[..len].trim();
+                challenges.push(AuthChallenge {
+                    scheme: scheme.unwrap(),
+                    params: HashMap::new(),
+                    token: (!token.is_empty()).then(|| token.to_string()),
+                });
+                rest = &rest[len..];
+                continue;
+            }
+
+            let (params, remainder) = parse_auth_params(rest);
+            rest = remainder;
+            if let Some(scheme) = scheme {
+                challenges.push(AuthChallenge { scheme, params, token: None });
+            }
+        }
+    }
+
+    challenges
+}
+
+/// The challenge we prefer to answer
+pub fn select_challenge(challenges: &[AuthChallenge]) -> Option<&AuthChallenge> {
+    challenges.iter().max_by_key(|c| c.scheme.strength())
+}
+
+// ==================== Credentials ====================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HttpCredentials {
+    pub username: String,
+    pub password: String,
+    /// Windows domain for NTLM; `DOMAIN\user` usernames are split automatically
+    #[serde(default)]
+    pub domain: Option<String>,
+}
+
+impl HttpCredentials {
+    fn domain_and_user(&self) -> (String, String) {
+        match (&self.domain, self.username.split_once('\\')) {
+            (Some(domain), _) => (domain.clone(), self.username.clone()),
+            (None, Some((domain, user))) => (domain.to_string(), user.to_string()),
+            (None, None) => (String::new(), self.username.clone()),
+        }
+    }
+}
+
+/// Why the frontend should ask the user for credentials
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthPrompt {
+    pub url: String,
+    pub origin: String,
+    pub realm: Option<String>,
+    pub scheme: AuthScheme,
+    /// The credentials we had were rejected
+    pub failed: bool,
+}
+
+/// Session-only credential cache keyed by origin and realm
+#[derive(Default)]
+pub struct HttpAuthCache {
+    credentials: RwLock<HashMap<(String, String), HttpCredentials>>,
+    prompts: RwLock<HashMap<String, AuthPrompt>>,
+}
+
+impl HttpAuthCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn store(&self, origin: &str, realm: Option<&str>, credentials: HttpCredentials) {
+        if let Ok(mut cache) = self.credentials.write() {
+            cache.insert((origin.to_string(), realm.unwrap_or_default().to_string()), credentials);
+        }
+        if let Ok(mut prompts) = self.prompts.write() {
+            prompts.remove(origin);
+        }
+    }
+
+    /// Credentials for the realm, falling back to ones stored for the whole origin
+    pub fn lookup(&self, origin: &str, realm: Option<&str>) -> Option<HttpCredentials> {
+        let cache = self.credentials.read().ok()?;
+        cache
+            .get(&(origin.to_string(), realm.unwrap_or_default().to_string()))
+            .or_else(|| cache.get(&(origin.to_string(), String::new())))
+            .cloned()
+    }
+
+    pub fn forget(&self, origin: &str, realm: Option<&str>) {
+        if let Ok(mut cache) = self.credentials.write() {
+            cache.remove(&(origin.to_string(), realm.unwrap_or_default().to_string()));
+            cache.remove(&(origin.to_string(), String::new()));
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Ok(mut cache) = self.credentials.write() {
+            cache.clear();
+        }
+        if let Ok(mut prompts) = self.prompts.write() {
+            prompts.clear();
+        }
+    }
+
+    pub fn record_prompt(&self, prompt: AuthPrompt) {
+        if let Ok(mut prompts) = self.prompts.write() {
+            prompts.insert(prompt.origin.clone(), prompt);
+        }
+    }
+
+    /// Take the pending prompt left by the last failed request to `origin`
+    pub fn take_prompt(&self, origin: &str) -> Option<AuthPrompt> {
+        self.prompts.write().ok()?.remove(origin)
+    }
+}
+
+/// `scheme://host:port` of a URL, used as the credential cache key
+pub fn origin_of(url: &str) -> String {
+    url::Url::parse(url)
+        .map(|u| u.origin().ascii_serialization())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+/// Path and query of a URL, as used in the Digest `uri` parameter
+pub fn request_uri(url: &str) -> String {
+    url::Url::parse(url)
+        .map(|u| match u.query() {
+            Some(query) => format!("{}?{}", u.path(), query),
+            None => u.path().to_string(),
+        })
+        .unwrap_or_else(|_| "/".to_string())
+}
+
+// ==================== Basic & Digest ====================
+
+pub fn basic_authorization(credentials: &HttpCredentials) -> String {
+    let pair = format!("{}:{}", credentials.username, credentials.password);
+    format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(pair))
+}
+
+fn md5_hex(value: &str) -> String {
+    format!("{:x}", md5::compute(value))
+}
+
+fn sha256_hex(value: &str) -> String {
+    format!("{:x}", Sha256::digest(value.as_bytes()))
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Build the `Authorization` value answering a Digest challenge
+pub fn digest_authorization(
+    challenge: &AuthChallenge,
+    credentials: &HttpCredentials,
+    method: &str,
+    uri: &str,
+    nonce_count: u32,
+    cnonce: &str,
+) -> Result<String, String> {
+    let realm = challenge.realm().unwrap_or_default();
+    let nonce = challenge.param("nonce").ok_or("Digest challenge without nonce")?;
+    let algorithm = challenge.param("algorithm").unwrap_or("MD5");
+
+    let (hash, session): (fn(&str) -> String, bool) = match algorithm.to_ascii_uppercase().as_str() {
+        "MD5" => (md5_hex, false),
+        "MD5-SESS" => (md5_hex, true),
+        "SHA-256" => (sha256_hex, false),
+        "SHA-256-SESS" => (sha256_hex, true),
+        other => return Err(format!("Unsupported digest algorithm: {}", other)),
+    };
+
+    let qop = match challenge.param("qop") {
+        None => None,
+        Some(offered) if offered.split(',').any(|q| q.trim().eq_ignore_ascii_case("auth")) => Some("auth"),
+        Some(offered) => return Err(format!("Unsupported digest qop: {}", offered)),
+    };
+
+    let mut ha1 = hash(&format!("{}:{}:{}", credentials.username, realm, credentials.password));
+    if session {
+        ha1 = hash(&format!("{}:{}:{}", ha1, nonce, cnonce));
+    }
+    let ha2 = hash(&format!("{}:{}", method, uri));
+    let nc = format!("{:08x}", nonce_count);
+    let response = match qop {
+        Some(qop) => hash(&format!("{}:{}:{}:{}:{}:{}", ha1, nonce, nc, cnonce, qop, ha2)),
+        None => hash(&format!("{}:{}:{}", ha1, nonce, ha2)),
+    };
+
+    let mut header = format!(
+        "Digest username={}, realm={}, nonce={}, uri={}, algorithm={}, response={}",
+        quote(&credentials.username),
+        quote(realm),
+        quote(nonce),
+        quote(uri),
+        algorithm,
+        quote(&response)
+    );
+    if let Some(qop) = qop {
+        header.push_str(&format!(", qop={}, nc={}, cnonce={}", qop, nc, quote(cnonce)));
+    }
+    if let Some(opaque) = challenge.param("opaque") {
+        header.push_str(&format!(", opaque={}", quote(opaque)));
+    }
+    Ok(header)
+}
+
+// ==================== NTLM ====================
+
+/// Server challenge from an NTLM type 2 message
+#[derive(Debug, Clone, PartialEq)]
+pub struct NtlmChallenge {
+    pub server_challenge: [u8; 8],
+    pub flags: u32,
+    pub target_info: Vec<u8>,
+}
+
+/// NTLM type 1 (negotiate) message
+pub fn ntlm_negotiate_message() -> Vec<u8> {
+    let flags = NTLM_NEGOTIATE_UNICODE
+        | NTLM_REQUEST_TARGET
+        | NTLM_NEGOTIATE_NTLM
+        | NTLM_ALWAYS_SIGN
+        | NTLM_EXTENDED_SESSION_SECURITY
+        | NTLM_TARGET_INFO
+        | NTLM_NEGOTIATE_128
+        | NTLM_NEGOTIATE_56;
+    let mut message = Vec::with_capacity(32);
+    message.extend_from_slice(NTLM_SIGNATURE);
+    message.extend_from_slice(&1u32.to_le_bytes());
+    message.extend_from_slice(&flags.to_le_bytes());
+    // Empty domain and workstation security buffers
+    message.extend_from_slice(&[0u8; 16]);
+    message
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Parse an NTLM type 2 (challenge) message
+pub fn parse_ntlm_challenge(bytes: &[u8]) -> Result<NtlmChallenge, String> {
+    if bytes.len() < 32 || &bytes[..8] != NTLM_SIGNATURE || read_u32(bytes, 8) != Some(2) {
+        return Err("Not an NTLM challenge message".to_string());
+    }
+    let flags = read_u32(bytes, 20).unwrap_or_default();
+    let mut server_challenge = [0u8; 8];
+    server_challenge.copy_from_slice(&bytes[24..32]);
+
+    let target_info = match (read_u16(bytes, 40), read_u32(bytes, 44)) {
+        (Some(len), Some(offset)) if bytes.len() >= 48 => bytes
+            .get(offset as usize..offset as usize + len as usize)
+            .ok_or("NTLM target info out of bounds")?
+            .to_vec(),
+        _ => Vec::new(),
+    };
+
+    Ok(NtlmChallenge { server_challenge, flags, target_info })
+}
+
+/// Server timestamp from the target info AV pairs, if present
+fn ntlm_av_timestamp(target_info: &[u8]) -> Option<u64> {
+    let mut at = 0;
+    while let (Some(id), Some(len)) = (read_u16(target_info, at), read_u16(target_info, at + 2)) {
+        let value = target_info.get(at + 4..at + 4 + len as usize)?;
+        match id {
+            0 => return None,
+            NTLM_AV_TIMESTAMP if len == 8 => return Some(u64::from_le_bytes(value.try_into().ok()?)),
+            _ => at += 4 + len as usize,
+        }
+    }
+    None
+}
+
+fn utf16le(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+/// NTLM type 3 (authenticate) message with an NTLMv2 response
+pub fn ntlm_authenticate_message(
+    challenge: &NtlmChallenge,
+    credentials: &HttpCredentials,
+    client_nonce: [u8; 8],
+) -> Vec<u8> {
+    let (domain, user) = credentials.domain_and_user();
+    let timestamp = ntlm_av_timestamp(&challenge.target_info).unwrap_or_else(|| {
+        let unix = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64 / 100)
+            .unwrap_or_default();
+        unix + NTLM_EPOCH_OFFSET
+    });
+
+    let nt_hash = md4(&utf16le(&credentials.password));
+    let v2_hash = hmac_md5(&nt_hash, &utf16le(&format!("{}{}", user.to_uppercase(), domain)));
+
+    let mut blob = vec![1, 1, 0, 0, 0, 0, 0, 0];
+    blob.extend_from_slice(&timestamp.to_le_bytes());
+    blob.extend_from_slice(&client_nonce);
+    blob.extend_from_slice(&[0u8; 4]);
+    blob.extend_from_slice(&challenge.target_info);
+    blob.extend_from_slice(&[0u8; 4]);
+
+    let mut nt_response = hmac_md5(&v2_hash, &[&challenge.server_challenge[..], &blob[..]].concat()).to_vec();
+    nt_response.extend_from_slice(&blob);
+    let mut lm_response = hmac_md5(&v2_hash, &[challenge.server_challenge, client_nonce].concat()).to_vec();
+    lm_response.extend_from_slice(&client_nonce);
+
+    let domain = utf16le(&domain);
+    let user = utf16le(&user);
+    let workstation = utf16le("CUBE");
+    let flags = (challenge.flags | NTLM_NEGOTIATE_UNICODE) & !NTLM_REQUEST_TARGET;
+
+    let payloads: [&[u8]; 6] = [&lm_response, &nt_response, &domain, &user, &workstation, &[]];
+    let mut header = Vec::with_capacity(64);
+    header.extend_from_slice(NTLM_SIGNATURE);
+    header.extend_from_slice(&3u32.to_le_bytes());
+    let mut body = Vec::new();
+    let mut offset = 64u32;
+    for payload in payloads {
+        header.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        header.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        header.extend_from_slice(&offset.to_le_bytes());
+        body.extend_from_slice(payload);
+        offset += payload.len() as u32;
+    }
+    header.extend_from_slice(&flags.to_le_bytes());
+    header.extend_from_slice(&body);
+    header
+}
+
+fn hmac_md5(key: &[u8], data: &[u8]) -> [u8; 16] {
+    let mut block = [0u8; 64];
+    if key.len() > 64 {
+        block[..16].copy_from_slice(&md5::compute(key).0);
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner: Vec<u8> = block.iter().map(|b| b ^ 0x36).chain(data.iter().copied()).collect();
+    let inner_hash = md5::compute(inner).0;
+    let outer: Vec<u8> = block.iter().map(|b| b ^ 0x5c).chain(inner_hash).collect();
+    md5::compute(outer).0
+}
+
+/// MD4 (RFC 1320), only needed for the NT password hash
+fn md4(input: &[u8]) -> [u8; 16] {
+    let mut message = input.to_vec();
+    let bit_len = (input.len() as u64).wrapping_mul(8);
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&bit_len.to_le_bytes());
+
+    let mut state: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+    for chunk in message.chunks(64) {
+        let x: Vec<u32> = chunk
+            .chunks(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+        let [mut a, mut b, mut c, mut d] = state;
+
+        let f = |x: u32, y: u32, z: u32| (x & y) | (!x & z);
+        let g = |x: u32, y: u32, z: u32| (x & y) | (x & z) | (y & z);
+        let h = |x: u32, y: u32, z: u32| x ^ y ^ z;
+
+        for &i in &[0, 4, 8, 12] {
+            a = a.wrapping_add(f(b, c, d)).wrapping_add(x[i]).rotate_left(3);
+            d = d.wrapping_add(f(a, b, c)).wrapping_add(x[i + 1]).rotate_left(7);
+            c = c.wrapping_add(f(d, a, b)).wrapping_add(x[i + 2]).rotate_left(11);
+            b = b.wrapping_add(f(c, d, a)).wrapping_add(x[i + 3]).rotate_left(19);
+        }
+        for &i in &[0, 1, 2, 3] {
+            a = a.wrapping_add(g(b, c, d)).wrapping_add(x[i]).wrapping_add(0x5a82_7999).rotate_left(3);
+            d = d.wrapping_add(g(a, b, c)).wrapping_add(x[i + 4]).wrapping_add(0x5a82_7999).rotate_left(5);
+            c = c.wrapping_add(g(d, a, b)).wrapping_add(x[i + 8]).wrapping_add(0x5a82_7999).rotate_left(9);
+            b = b.wrapping_add(g(c, d, a)).wrapping_add(x[i + 12]).wrapping_add(0x5a82_7999).rotate_left(13);
+        }
+        for &i in &[0, 2, 1, 3] {
+            a = a.wrapping_add(h(b, c, d)).wrapping_add(x[i]).wrapping_add(0x6ed9_eba1).rotate_left(3);
+            d = d.wrapping_add(h(a, b, c)).wrapping_add(x[i + 8]).wrapping_add(0x6ed9_eba1).rotate_left(9);
+            c = c.wrapping_add(h(d, a, b)).wrapping_add(x[i + 4]).wrapping_add(0x6ed9_eba1).rotate_left(11);
+            b = b.wrapping_add(h(c, d, a)).wrapping_add(x[i + 12]).wrapping_add(0x6ed9_eba1).rotate_left(15);
+        }
+
+        state[0] = state[0].wrapping_add(a);
+        state[1] = state[1].wrapping_add(b);
+        state[2] = state[2].wrapping_add(c);
+        state[3] = state[3].wrapping_add(d);
+    }
+
+    let mut digest = [0u8; 16];
+    for (i, word) in state.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_challenge_parsing_and_hashes() {
+        let challenges = parse_www_authenticate([
+            "Basic realm=\"Intranet, HR\", Digest realm=\"corp\", qop=\"auth,auth-int\", nonce=\"abc\\\"d\", opaque=xyz",
+            "NTLM TlRMTVNTUAACAAAAAAAAACgAAAABAAAAAAAAAAAAAAA=, Negotiate",
+        ]);
+        assert_eq!(challenges.len(), 4);
+        assert_eq!(challenges[0].realm(), Some("Intranet, HR"));
+        assert_eq!(challenges[1].param("nonce"), Some("abc\"d"));
+        assert_eq!(challenges[1].param("opaque"), Some("xyz"));
+        assert_eq!(challenges[2].token.as_deref(), Some("TlRMTVNTUAACAAAAAAAAACgAAAABAAAAAAAAAAAAAAA="));
+        assert_eq!(challenges[3].token, None);
+        assert_eq!(select_challenge(&challenges).unwrap().scheme, AuthScheme::Ntlm);
+
+        // RFC 2617 section 3.5 example
+        let challenge = &parse_www_authenticate([
+            "Digest realm=\"testrealm@host.com\", qop=\"auth,auth-int\", nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", opaque=\"5ccc069c403ebaf9f0171e9517f40e41\"",
+        ])[0];
+        let credentials = HttpCredentials {
+            username: "Mufasa".to_string(),
+            password: "Circle Of Life".to_string(),
+            domain: None,
+        };
+        let header = digest_authorization(challenge, &credentials, "GET", "/dir/index.html", 1, "0a4f113b").unwrap();
+        assert!(header.contains("response=\"6629fae49393a05397450978507c4ef1\""));
+        assert!(header.contains("nc=00000001"));
+
+        assert_eq!(hex(&md4(b"abc")), "a448017aaf21d8525fc10ae87aa6729d");
+        assert_eq!(hex(&md4(&utf16le("Password"))), "a4f49c406510bdcab6824ee7c30fd852");
+        assert_eq!(hex(&hmac_md5(&[0x0b; 16], b"Hi There")), "9294727a3638bb1c13f48ef8158bfc9d");
+
+        let negotiate = ntlm_negotiate_message();
+        assert_eq!(&negotiate[..8], NTLM_SIGNATURE);
+        assert_eq!(negotiate.len(), 32);
+    }
+}
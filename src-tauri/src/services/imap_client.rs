@@ -19,8 +19,9 @@ use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use std::collections::HashMap;
 
+use super::mail_attachments::embed_inline_image;
 use super::cube_mail_service::{
-    Email, EmailAddress, EmailAttachment, EmailCategory,
+    AttachmentScanStatus, Email, EmailAddress, EmailAttachment, EmailCategory,
     ImapConfig, MailFolder, SmtpConfig, SecurityStatus,
 };
 
@@ -445,7 +446,7 @@ impl CubeImapClient {
         
         // Extract body
         let body_text = message.body_text(0).map(|s| s.to_string());
-        let body_html = message.body_html(0).map(|s| s.to_string());
+        let mut body_html = message.body_html(0).map(|s| s.to_string());
         
         // Generate snippet
         let snippet = body_text
@@ -488,16 +489,32 @@ impl CubeImapClient {
                 })
                 .unwrap_or_else(|| "application/octet-stream".to_string());
             
+            // Images referenced from the HTML body via cid: render inline
+            let content_id = att.content_id().map(|s| s.to_string());
+            let is_inline = match (content_id.as_deref(), body_html.as_mut()) {
+                (Some(cid), Some(html)) => match embed_inline_image(html, cid, &mime_type, att.contents()) {
+                    Some(rewritten) => {
+                        *html = rewritten;
+                        true
+                    }
+                    None => false,
+                },
+                _ => false,
+            };
+            
             attachments.push(EmailAttachment {
                 id: Uuid::new_v4().to_string(),
                 filename: att.attachment_name().unwrap_or("attachment").to_string(),
                 mime_type,
                 size: att.len() as u64,
-                content_id: att.content_id().map(|s| s.to_string()),
-                is_inline: false,
+                content_id,
+                is_inline,
                 encrypted: false,
                 download_url: None,
                 local_path: None,
+                scan_status: AttachmentScanStatus::NotScanned,
+                scan_detail: None,
+                sha256: None,
             });
         }
         
@@ -521,7 +538,7 @@ impl CubeImapClient {
             is_read: false,
             is_starred: false,
             is_important: false,
-            has_attachments: !attachments.is_empty(),
+            has_attachments: attachments.iter().any(|a| !a.is_inline),
             attachments,
             labels: Vec::new(),
             category: None,
@@ -0,0 +1,605 @@
+// ═══════════════════════════════════════════════════════════════════════════════
+// 📤 INTEGRATION OUTBOUND QUEUES - MONDAY.COM & WHATSAPP RATE LIMITING
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Outbound calls are queued per integration and paced to the provider's
+// documented limits:
+// - Monday.com: complexity budget per minute
+// - WhatsApp Cloud API: messages per second plus the messaging tier's
+//   unique-recipient cap per 24 hours
+// 429s back the queue off exponentially. Pending messages are written to
+// disk on every change so a restart resumes where it stopped.
+//
+// ═══════════════════════════════════════════════════════════════════════════════
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+
+use crate::services::project_management_service::ColumnValue;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+const BACKOFF_BASE_MS: i64 = 2_000;
+const BACKOFF_MAX_MS: i64 = 5 * 60 * 1000;
+/// Estimated complexity of a `create_item` mutation with our selection set
+const MONDAY_CREATE_ITEM_COMPLEXITY: f64 = 10_000.0;
+
+// ═══════════════════════════════════════════════════════════════════════════
+// TYPES
+// ═══════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Integration {
+    Monday,
+    WhatsApp,
+}
+
+impl Integration {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "monday" | "monday.com" => Ok(Integration::Monday),
+            "whatsapp" => Ok(Integration::WhatsApp),
+            _ => Err(format!("Unknown integration: {}", name)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Integration::Monday => "monday",
+            Integration::WhatsApp => "whatsapp",
+        }
+    }
+}
+
+/// WhatsApp Business messaging limit tiers (unique recipients per 24h)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum WhatsAppTier {
+    #[default]
+    Tier1K,
+    Tier10K,
+    Tier100K,
+    Unlimited,
+}
+
+impl WhatsAppTier {
+    pub fn unique_recipients_per_day(&self) -> Option<usize> {
+        match self {
+            WhatsAppTier::Tier1K => Some(1_000),
+            WhatsAppTier::Tier10K => Some(10_000),
+            WhatsAppTier::Tier100K => Some(100_000),
+            WhatsAppTier::Unlimited => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitPolicy {
+    /// Cost units (complexity points, messages) allowed per window
+    pub budget_per_window: f64,
+    pub window_ms: i64,
+    pub unique_recipients_per_day: Option<usize>,
+    /// Attempts for non-rate-limit failures before a message is dropped
+    pub max_attempts: u32,
+}
+
+impl RateLimitPolicy {
+    /// 5,000,000 complexity points per minute for personal API tokens
+    pub fn monday() -> Self {
+        Self {
+            budget_per_window: 5_000_000.0,
+            window_ms: 60_000,
+            unique_recipients_per_day: None,
+            max_attempts: 5,
+        }
+    }
+
+    /// 80 messages per second per business number, plus the tier's cap
+    pub fn whatsapp(tier: WhatsAppTier) -> Self {
+        Self {
+            budget_per_window: 80.0,
+            window_ms: 1_000,
+            unique_recipients_per_day: tier.unique_recipients_per_day(),
+            max_attempts: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OutboundPayload {
+    WhatsAppText {
+        to: String,
+        message: String,
+    },
+    MondayCreateItem {
+        board_id: String,
+        group_id: String,
+        item_name: String,
+        column_values: Option<HashMap<String, ColumnValue>>,
+    },
+}
+
+impl OutboundPayload {
+    pub fn integration(&self) -> Integration {
+        match self {
+            OutboundPayload::WhatsAppText { .. } => Integration::WhatsApp,
+            OutboundPayload::MondayCreateItem { .. } => Integration::Monday,
+        }
+    }
+
+    fn cost(&self) -> f64 {
+        match self {
+            OutboundPayload::WhatsAppText { .. } => 1.0,
+            OutboundPayload::MondayCreateItem { .. } => MONDAY_CREATE_ITEM_COMPLEXITY,
+        }
+    }
+
+    fn recipient(&self) -> Option<&str> {
+        match self {
+            OutboundPayload::WhatsAppText { to, .. } => Some(to),
+            OutboundPayload::MondayCreateItem { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedMessage {
+    pub id: String,
+    pub payload: OutboundPayload,
+    pub enqueued_at: i64,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SendOutcome {
+    Sent,
+    RateLimited { retry_after_ms: Option<i64> },
+    Failed(String),
+}
+
+impl SendOutcome {
+    /// Classify a provider error: HTTP 429, WhatsApp throughput/pair-rate
+    /// codes and Monday complexity/rate errors are rate limits
+    pub fn from_error(error: &str) -> Self {
+        const RATE_LIMIT_MARKERS: &[&str] = &[
+            "429",
+            "Too Many Requests",
+            "130429",
+            "131048",
+            "131056",
+            "ComplexityException",
+            "Rate Limit Exceeded",
+            "RATE_LIMIT",
+        ];
+        if RATE_LIMIT_MARKERS
+            .iter()
+            .any(|marker| error.contains(marker))
+        {
+            SendOutcome::RateLimited {
+                retry_after_ms: None,
+            }
+        } else {
+            SendOutcome::Failed(error.to_string())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueStatus {
+    pub integration: Integration,
+    pub depth: usize,
+    pub failed: usize,
+    pub sent_total: u64,
+    pub window_usage: f64,
+    pub window_budget: f64,
+    pub unique_recipients_today: usize,
+    pub next_send_in_ms: Option<i64>,
+    pub paused_until: Option<i64>,
+    pub backoff_level: u32,
+    pub oldest_enqueued_at: Option<i64>,
+    pub failed_messages: Vec<QueuedMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SendRecord {
+    at: i64,
+    cost: f64,
+    recipient: Option<String>,
+}
+
+/// Everything written to disk
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueueData {
+    pending: VecDeque<QueuedMessage>,
+    failed: Vec<QueuedMessage>,
+    sends: VecDeque<SendRecord>,
+    paused_until: Option<i64>,
+    backoff_level: u32,
+    sent_total: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════
+// QUEUE
+// ═══════════════════════════════════════════════════════════════════════════
+
+pub struct OutboundQueue {
+    integration: Integration,
+    policy: RateLimitPolicy,
+    data: QueueData,
+    path: Option<PathBuf>,
+}
+
+impl OutboundQueue {
+    /// Open a queue, restoring pending messages from `path` when present
+    pub fn open(integration: Integration, policy: RateLimitPolicy, path: Option<PathBuf>) -> Self {
+        let data = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self {
+            integration,
+            policy,
+            data,
+            path,
+        }
+    }
+
+    pub fn set_policy(&mut self, policy: RateLimitPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn enqueue(&mut self, payload: OutboundPayload, now: i64) -> Result<String, String> {
+        if payload.integration() != self.integration {
+            return Err(format!(
+                "{} payload sent to the {} queue",
+                payload.integration().as_str(),
+                self.integration.as_str()
+            ));
+        }
+        let id = uuid::Uuid::new_v4().to_string();
+        self.data.pending.push_back(QueuedMessage {
+            id: id.clone(),
+            payload,
+            enqueued_at: now,
+            attempts: 0,
+            last_error: None,
+        });
+        self.persist()?;
+        Ok(id)
+    }
+
+    /// The message to send next; it stays queued until `complete`
+    pub fn front(&self) -> Option<&QueuedMessage> {
+        self.data.pending.front()
+    }
+
+    pub fn depth(&self) -> usize {
+        self.data.pending.len()
+    }
+
+    /// Earliest time the front message may be sent, or `None` when empty
+    pub fn next_send_at(&self, now: i64) -> Option<i64> {
+        let message = self.data.pending.front()?;
+        let mut at = now.max(self.data.paused_until.unwrap_or(now));
+
+        // Sliding-window budget: wait until enough old sends expire
+        let cost = message.payload.cost();
+        let window_start = now - self.policy.window_ms;
+        let in_window: Vec<&SendRecord> = self
+            .data
+            .sends
+            .iter()
+            .filter(|s| s.at > window_start)
+            .collect();
+        let mut used: f64 = in_window.iter().map(|s| s.cost).sum();
+        for send in &in_window {
+            if used + cost <= self.policy.budget_per_window || used == 0.0 {
+                break;
+            }
+            used -= send.cost;
+            at = at.max(send.at + self.policy.window_ms);
+        }
+
+        // Messaging tier: a new recipient needs a free slot in the last 24h
+        if let (Some(cap), Some(recipient)) = (
+            self.policy.unique_recipients_per_day,
+            message.payload.recipient(),
+        ) {
+            let recipients = self.recent_recipients(now);
+            if !recipients.contains_key(recipient) && recipients.len() >= cap {
+                let mut last_sends: Vec<i64> = recipients.into_values().collect();
+                last_sends.sort_unstable();
+                // The oldest recipients have to age out until one slot is free
+                at = at.max(last_sends[last_sends.len() - cap] + DAY_MS);
+            }
+        }
+
+        Some(at)
+    }
+
+    /// Record the result of sending the front message. Returns `Some(Ok)`
+    /// when it was delivered, `Some(Err)` when it was given up on, and
+    /// `None` when it stays queued for a retry.
+    pub fn complete(
+        &mut self,
+        id: &str,
+        outcome: SendOutcome,
+        now: i64,
+    ) -> Option<Result<(), String>> {
+        if self.data.pending.front().map(|m| m.id.as_str()) != Some(id) {
+            return None;
+        }
+
+        let result = match outcome {
+            SendOutcome::Sent => {
+                let message = self.data.pending.pop_front()?;
+                self.data.sends.push_back(SendRecord {
+                    at: now,
+                    cost: message.payload.cost(),
+                    recipient: message.payload.recipient().map(str::to_string),
+                });
+                self.data.sent_total += 1;
+                self.data.backoff_level = 0;
+                self.data.paused_until = None;
+                Some(Ok(()))
+            }
+            SendOutcome::RateLimited { retry_after_ms } => {
+                self.data.backoff_level += 1;
+                let delay = retry_after_ms.unwrap_or_else(|| self.backoff_delay());
+                self.data.paused_until = Some(now + delay);
+                if let Some(message) = self.data.pending.front_mut() {
+                    message.last_error = Some("Rate limited by provider".to_string());
+                }
+                None
+            }
+            SendOutcome::Failed(error) => {
+                let message = self.data.pending.front_mut()?;
+                message.attempts += 1;
+                message.last_error = Some(error.clone());
+                if message.attempts >= self.policy.max_attempts {
+                    let message = self.data.pending.pop_front()?;
+                    self.data.failed.push(message);
+                    Some(Err(error))
+                } else {
+                    self.data.backoff_level += 1;
+                    self.data.paused_until = Some(now + self.backoff_delay());
+                    None
+                }
+            }
+        };
+
+        self.prune_sends(now);
+        if let Err(e) = self.persist() {
+            log::error!(
+                "Failed to persist {} queue: {}",
+                self.integration.as_str(),
+                e
+            );
+        }
+        result
+    }
+
+    pub fn status(&self, now: i64) -> QueueStatus {
+        let window_start = now - self.policy.window_ms;
+        QueueStatus {
+            integration: self.integration,
+            depth: self.data.pending.len(),
+            failed: self.data.failed.len(),
+            sent_total: self.data.sent_total,
+            window_usage: self
+                .data
+                .sends
+                .iter()
+                .filter(|s| s.at > window_start)
+                .map(|s| s.cost)
+                .sum(),
+            window_budget: self.policy.budget_per_window,
+            unique_recipients_today: self.recent_recipients(now).len(),
+            next_send_in_ms: self.next_send_at(now).map(|at| (at - now).max(0)),
+            paused_until: self.data.paused_until.filter(|&until| until > now),
+            backoff_level: self.data.backoff_level,
+            oldest_enqueued_at: self.data.pending.front().map(|m| m.enqueued_at),
+            failed_messages: self.data.failed.clone(),
+        }
+    }
+
+    fn backoff_delay(&self) -> i64 {
+        let exponent = self.data.backoff_level.saturating_sub(1).min(16);
+        (BACKOFF_BASE_MS << exponent).min(BACKOFF_MAX_MS)
+    }
+
+    /// Latest send time per recipient within the last 24h
+    fn recent_recipients(&self, now: i64) -> HashMap<&str, i64> {
+        let mut recipients = HashMap::new();
+        for send in self.data.sends.iter().filter(|s| s.at > now - DAY_MS) {
+            if let Some(recipient) = &send.recipient {
+                recipients.insert(recipient.as_str(), send.at);
+            }
+        }
+        recipients
+    }
+
+    fn prune_sends(&mut self, now: i64) {
+        let keep_ms = if self.policy.unique_recipients_per_day.is_some() {
+            DAY_MS.max(self.policy.window_ms)
+        } else {
+            self.policy.window_ms
+        };
+        while self
+            .data
+            .sends
+            .front()
+            .is_some_and(|s| s.at <= now - keep_ms)
+        {
+            self.data.sends.pop_front();
+        }
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create queue dir: {}", e))?;
+        }
+        let json = serde_json::to_string(&self.data)
+            .map_err(|e| format!("Failed to serialize queue: {}", e))?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).map_err(|e| format!("Failed to write queue: {}", e))?;
+        std::fs::rename(&tmp, path).map_err(|e| format!("Failed to save queue: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn whatsapp(to: &str) -> OutboundPayload {
+        OutboundPayload::WhatsAppText {
+            to: to.to_string(),
+            message: "hi".to_string(),
+        }
+    }
+
+    /// Drain the queue against a simulated clock, returning send times
+    fn drain(queue: &mut OutboundQueue, mut now: i64, limit: usize) -> Vec<i64> {
+        let mut sent = Vec::new();
+        while sent.len() < limit {
+            let Some(at) = queue.next_send_at(now) else {
+                break;
+            };
+            now = now.max(at);
+            let id = queue.front().unwrap().id.clone();
+            assert_eq!(queue.complete(&id, SendOutcome::Sent, now), Some(Ok(())));
+            sent.push(now);
+        }
+        sent
+    }
+
+    #[test]
+    fn test_burst_is_paced_to_the_window_budget_and_backs_off_on_429() {
+        let policy = RateLimitPolicy {
+            budget_per_window: 5.0,
+            window_ms: 1_000,
+            unique_recipients_per_day: None,
+            max_attempts: 3,
+        };
+        let mut queue = OutboundQueue::open(Integration::WhatsApp, policy, None);
+        for i in 0..12 {
+            queue
+                .enqueue(whatsapp(&format!("+1555000{:04}", i)), 0)
+                .unwrap();
+        }
+
+        let times = drain(&mut queue, 0, 12);
+        assert_eq!(times.len(), 12);
+        assert_eq!(&times[..5], &[0; 5]);
+        for (i, &t) in times.iter().enumerate() {
+            let in_window = times[..=i].iter().filter(|&&s| s > t - 1_000).count();
+            assert!(
+                in_window <= 5,
+                "{} sends within a second at t={}",
+                in_window,
+                t
+            );
+        }
+        assert_eq!(*times.last().unwrap(), 2_000);
+
+        // A 429 pauses the queue with exponential backoff and keeps the message
+        queue.enqueue(whatsapp("+15550009999"), 5_000).unwrap();
+        let id = queue.front().unwrap().id.clone();
+        assert_eq!(
+            queue.complete(
+                &id,
+                SendOutcome::from_error("HTTP 429 Too Many Requests"),
+                5_000
+            ),
+            None
+        );
+        assert_eq!(queue.next_send_at(5_000), Some(5_000 + BACKOFF_BASE_MS));
+        assert_eq!(
+            queue.complete(
+                &id,
+                SendOutcome::RateLimited {
+                    retry_after_ms: None
+                },
+                7_000
+            ),
+            None
+        );
+        assert_eq!(queue.next_send_at(7_000), Some(7_000 + 2 * BACKOFF_BASE_MS));
+        assert_eq!(queue.status(7_000).depth, 1);
+        assert_eq!(queue.status(7_000).backoff_level, 2);
+
+        assert_eq!(queue.complete(&id, SendOutcome::Sent, 11_000), Some(Ok(())));
+        assert_eq!(queue.status(11_000).backoff_level, 0);
+    }
+
+    #[test]
+    fn test_whatsapp_tier_caps_new_recipients_per_day() {
+        let policy = RateLimitPolicy {
+            unique_recipients_per_day: Some(2),
+            ..RateLimitPolicy::whatsapp(WhatsAppTier::Tier1K)
+        };
+        let mut queue = OutboundQueue::open(Integration::WhatsApp, policy, None);
+        for to in ["+1", "+2", "+1", "+3"] {
+            queue.enqueue(whatsapp(to), 0).unwrap();
+        }
+
+        // Repeat recipients don't use a new slot; the third one waits a day
+        let times = drain(&mut queue, 0, 4);
+        assert_eq!(times, [0, 0, 0, DAY_MS]);
+    }
+
+    #[test]
+    fn test_queued_messages_survive_restart() {
+        let path = std::env::temp_dir().join(format!("cube-queue-{}.json", uuid::Uuid::new_v4()));
+        let policy = RateLimitPolicy {
+            budget_per_window: 20_000.0,
+            ..RateLimitPolicy::monday()
+        };
+        let item = |name: &str| OutboundPayload::MondayCreateItem {
+            board_id: "42".to_string(),
+            group_id: "topics".to_string(),
+            item_name: name.to_string(),
+            column_values: None,
+        };
+
+        let ids: Vec<String> = {
+            let mut queue =
+                OutboundQueue::open(Integration::Monday, policy.clone(), Some(path.clone()));
+            let ids = (0..5)
+                .map(|i| queue.enqueue(item(&format!("Task {}", i)), 0).unwrap())
+                .collect();
+            // Two fit in the complexity budget before the process stops
+            assert_eq!(drain(&mut queue, 0, 2), [0, 0]);
+            ids
+        };
+
+        let mut queue =
+            OutboundQueue::open(Integration::Monday, policy.clone(), Some(path.clone()));
+        assert_eq!(queue.depth(), 3);
+        assert_eq!(queue.front().unwrap().id, ids[2]);
+        // Budget spent before the restart still counts
+        assert_eq!(queue.next_send_at(10), Some(60_000));
+        queue.complete(
+            &ids[2],
+            SendOutcome::Failed("board not found".to_string()),
+            60_000,
+        );
+        drop(queue);
+
+        let restarted = OutboundQueue::open(Integration::Monday, policy, Some(path.clone()));
+        let status = restarted.status(60_000);
+        assert_eq!(status.depth, 3);
+        assert_eq!(status.sent_total, 2);
+        assert_eq!(restarted.front().unwrap().attempts, 1);
+        assert_eq!(status.paused_until, Some(60_000 + BACKOFF_BASE_MS));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,230 @@
+// ============================================================================
+// License Seat Service
+// ============================================================================
+// Concurrent-seat enforcement for enterprise licenses:
+// - Activating on a device leases one seat, kept alive by heartbeats
+// - Leases survive brief network drops for a grace period past the timeout
+// - Seats of crashed clients are reclaimed once their heartbeats expire
+// - Activation is refused while every seat is held, naming the holders
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Clients are expected to heartbeat at this interval
+pub const HEARTBEAT_INTERVAL_MS: i64 = 60_000;
+/// A lease goes stale after this long without a heartbeat
+const HEARTBEAT_TIMEOUT_MS: i64 = 3 * HEARTBEAT_INTERVAL_MS;
+/// Extra time a stale lease is held for before the seat is reclaimed
+const NETWORK_GRACE_MS: i64 = 5 * 60_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeatLease {
+    pub license_id: String,
+    pub device_id: String,
+    pub device_name: Option<String>,
+    pub activated_at: i64,
+    pub last_heartbeat: i64,
+    /// Seat is reclaimed if no heartbeat arrives before this time
+    pub expires_at: i64,
+    /// Heartbeats are overdue but the seat is still held
+    pub in_grace: bool,
+}
+
+impl SeatLease {
+    fn holder(&self) -> String {
+        match &self.device_name {
+            Some(name) => format!("{} ({})", name, self.device_id),
+            None => self.device_id.clone(),
+        }
+    }
+}
+
+pub struct LicenseSeatService {
+    heartbeat_timeout_ms: i64,
+    grace_ms: i64,
+    leases: RwLock<HashMap<String, Vec<SeatLease>>>,
+}
+
+impl Default for LicenseSeatService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LicenseSeatService {
+    pub fn new() -> Self {
+        Self::with_timeouts(HEARTBEAT_TIMEOUT_MS, NETWORK_GRACE_MS)
+    }
+
+    pub fn with_timeouts(heartbeat_timeout_ms: i64, grace_ms: i64) -> Self {
+        Self {
+            heartbeat_timeout_ms,
+            grace_ms,
+            leases: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Lease a seat for `device_id`; a device that already holds one keeps it.
+    /// `seats` of zero or less means the license has no seat limit.
+    pub fn acquire(
+        &self,
+        license_id: &str,
+        seats: i32,
+        device_id: &str,
+        device_name: Option<String>,
+        now_ms: i64,
+    ) -> Result<SeatLease, String> {
+        let mut leases = self.leases.write().map_err(|e| e.to_string())?;
+        let held = leases.entry(license_id.to_string()).or_default();
+        self.reap(held, now_ms);
+
+        if let Some(lease) = held.iter_mut().find(|l| l.device_id == device_id) {
+            if device_name.is_some() {
+                lease.device_name = device_name;
+            }
+            self.refresh(lease, now_ms);
+            return Ok(lease.clone());
+        }
+
+        if seats > 0 && held.len() >= seats as usize {
+            let holders: Vec<String> = held.iter().map(SeatLease::holder).collect();
+            return Err(format!(
+                "All {} seats are in use by: {}",
+                seats,
+                holders.join(", ")
+            ));
+        }
+
+        let mut lease = SeatLease {
+            license_id: license_id.to_string(),
+            device_id: device_id.to_string(),
+            device_name,
+            activated_at: now_ms,
+            last_heartbeat: now_ms,
+            expires_at: now_ms,
+            in_grace: false,
+        };
+        self.refresh(&mut lease, now_ms);
+        held.push(lease.clone());
+        Ok(lease)
+    }
+
+    /// Keep a seat alive; fails once the lease has been reclaimed
+    pub fn heartbeat(&self, license_id: &str, device_id: &str, now_ms: i64) -> Result<SeatLease, String> {
+        let mut leases = self.leases.write().map_err(|e| e.to_string())?;
+        let held = leases.entry(license_id.to_string()).or_default();
+        self.reap(held, now_ms);
+
+        let lease = held
+            .iter_mut()
+            .find(|l| l.device_id == device_id)
+            .ok_or_else(|| "Seat lease expired; activate the license again".to_string())?;
+        self.refresh(lease, now_ms);
+        Ok(lease.clone())
+    }
+
+    /// Give a device's seat back; returns whether it held one
+    pub fn release(&self, license_id: &str, device_id: &str) -> Result<bool, String> {
+        let mut leases = self.leases.write().map_err(|e| e.to_string())?;
+        let Some(held) = leases.get_mut(license_id) else {
+            return Ok(false);
+        };
+        let before = held.len();
+        held.retain(|l| l.device_id != device_id);
+        Ok(held.len() < before)
+    }
+
+    pub fn release_all(&self, license_id: &str) -> Result<usize, String> {
+        let mut leases = self.leases.write().map_err(|e| e.to_string())?;
+        Ok(leases.remove(license_id).map(|held| held.len()).unwrap_or(0))
+    }
+
+    pub fn active_seats(&self, license_id: &str, now_ms: i64) -> Result<Vec<SeatLease>, String> {
+        let mut leases = self.leases.write().map_err(|e| e.to_string())?;
+        let Some(held) = leases.get_mut(license_id) else {
+            return Ok(Vec::new());
+        };
+        self.reap(held, now_ms);
+        Ok(held
+            .iter()
+            .map(|lease| SeatLease {
+                in_grace: now_ms > lease.last_heartbeat + self.heartbeat_timeout_ms,
+                ..lease.clone()
+            })
+            .collect())
+    }
+
+    fn refresh(&self, lease: &mut SeatLease, now_ms: i64) {
+        lease.last_heartbeat = now_ms;
+        lease.expires_at = now_ms + self.heartbeat_timeout_ms + self.grace_ms;
+        lease.in_grace = false;
+    }
+
+    /// Drop leases whose holders stopped heartbeating, e.g. crashed clients
+    fn reap(&self, held: &mut Vec<SeatLease>, now_ms: i64) {
+        held.retain(|lease| {
+            let alive = now_ms < lease.expires_at;
+            if !alive {
+                log::info!("Reclaimed seat on license {} from {}", lease.license_id, lease.device_id);
+            }
+            alive
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: i64 = 1_000;
+    const GRACE: i64 = 500;
+
+    #[test]
+    fn test_activation_refused_when_seats_exhausted() {
+        let seats = LicenseSeatService::with_timeouts(TIMEOUT, GRACE);
+        seats.acquire("lic-1", 2, "dev-a", Some("Alice's laptop".to_string()), 0).unwrap();
+        seats.acquire("lic-1", 2, "dev-b", None, 10).unwrap();
+        // Re-activating a device that already holds a seat does not take another
+        seats.acquire("lic-1", 2, "dev-a", None, 20).unwrap();
+
+        let err = seats.acquire("lic-1", 2, "dev-c", None, 30).unwrap_err();
+        assert!(err.contains("All 2 seats are in use"));
+        assert!(err.contains("Alice's laptop (dev-a)"));
+        assert!(err.contains("dev-b"));
+        assert_eq!(seats.active_seats("lic-1", 30).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_heartbeat_timeout_reclaims_crashed_client_seat() {
+        let seats = LicenseSeatService::with_timeouts(TIMEOUT, GRACE);
+        seats.acquire("lic-1", 1, "crashed", None, 0).unwrap();
+
+        // Missed heartbeats: still held during the grace period
+        let stale = seats.active_seats("lic-1", TIMEOUT + 100).unwrap();
+        assert_eq!(stale.len(), 1);
+        assert!(stale[0].in_grace);
+        assert!(seats.acquire("lic-1", 1, "replacement", None, TIMEOUT + 100).is_err());
+
+        // Past the grace period the seat goes to the next device
+        let now = TIMEOUT + GRACE + 1;
+        seats.acquire("lic-1", 1, "replacement", None, now).unwrap();
+        assert!(seats.heartbeat("lic-1", "crashed", now).is_err());
+
+        // A heartbeat inside the grace period keeps the seat
+        let lease = seats.heartbeat("lic-1", "replacement", now + TIMEOUT + GRACE - 1).unwrap();
+        assert!(!lease.in_grace);
+        assert!(seats.acquire("lic-1", 1, "other", None, now + TIMEOUT + GRACE + 100).is_err());
+    }
+
+    #[test]
+    fn test_deactivate_releases_seat() {
+        let seats = LicenseSeatService::with_timeouts(TIMEOUT, GRACE);
+        seats.acquire("lic-1", 1, "dev-a", None, 0).unwrap();
+        assert!(seats.release("lic-1", "dev-a").unwrap());
+        assert!(!seats.release("lic-1", "dev-a").unwrap());
+        assert!(seats.active_seats("lic-1", 10).unwrap().is_empty());
+        seats.acquire("lic-1", 1, "dev-b", None, 10).unwrap();
+    }
+}
@@ -5,17 +5,19 @@
  * - Structured logging (DEBUG, INFO, WARN, ERROR)
  * - Searchable by workflow, execution, date range
  * - Log filtering by level and keywords
+ * - Field queries (equality/range) backed by an in-memory index
+ * - Group-by aggregation (counts and rates)
  * - Export to file (JSON, CSV, TXT)
  * - Real-time log streaming
  */
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
-use std::sync::{Arc, RwLock};
+use std::sync::RwLock;
 use log::info;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -45,23 +47,193 @@ pub struct LogEntry {
     pub workflow_id: Option<String>,
     pub execution_id: Option<String>,
     pub node_id: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
     pub message: String,
+    /// Arbitrary structured key/value fields
     pub metadata: HashMap<String, String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl LogEntry {
+    /// Value of a built-in field (`level`, `workflow_id`, `execution_id`,
+    /// `node_id`, `source`) or of a metadata key
+    pub fn field(&self, name: &str) -> Option<&str> {
+        match name {
+            "level" => Some(self.level.as_str()),
+            "workflow_id" => self.workflow_id.as_deref(),
+            "execution_id" => self.execution_id.as_deref(),
+            "node_id" => self.node_id.as_deref(),
+            "source" => self.source.as_deref(),
+            _ => self.metadata.get(name).map(String::as_str),
+        }
+    }
+
+    fn indexed_fields(&self) -> impl Iterator<Item = (&str, &str)> {
+        ["level", "workflow_id", "execution_id", "node_id", "source"]
+            .into_iter()
+            .filter_map(|name| self.field(name).map(|value| (name, value)))
+            .chain(self.metadata.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LogFilter {
     pub workflow_id: Option<String>,
     pub execution_id: Option<String>,
+    /// Minimum level
     pub level: Option<LogLevel>,
     pub start_time: Option<DateTime<Utc>>,
     pub end_time: Option<DateTime<Utc>>,
+    /// Case-insensitive text search over the message and field values
     pub keyword: Option<String>,
     pub limit: Option<usize>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub fields: Vec<FieldCondition>,
+}
+
+/// Condition on a built-in field or metadata key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum FieldCondition {
+    Eq { field: String, value: String },
+    /// Numeric range, inclusive on both ends
+    Range { field: String, min: Option<f64>, max: Option<f64> },
+}
+
+impl FieldCondition {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        match self {
+            FieldCondition::Eq { field, value } => entry.field(field) == Some(value.as_str()),
+            FieldCondition::Range { field, min, max } => {
+                match entry.field(field).and_then(|v| v.trim().parse::<f64>().ok()) {
+                    Some(v) => min.map_or(true, |min| v >= min) && max.map_or(true, |max| v <= max),
+                    None => false,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogMetric {
+    Count,
+    /// Entries per minute over the matched time span
+    RatePerMinute,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogAggregateBucket {
+    /// Field value, or `None` for entries without the field
+    pub key: Option<String>,
+    pub count: usize,
+    pub value: f64,
+}
+
+/// Entries in arrival order plus an equality index from field/value to
+/// sequence numbers. Timestamps are kept non-decreasing so time ranges can
+/// be binary searched.
+#[derive(Default)]
+struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+    first_seq: u64,
+    index: HashMap<String, HashMap<String, VecDeque<u64>>>,
+}
+
+impl LogBuffer {
+    fn push(&mut self, entry: LogEntry) {
+        let seq = self.first_seq + self.entries.len() as u64;
+        for (field, value) in entry.indexed_fields() {
+            self.index
+                .entry(field.to_string())
+                .or_default()
+                .entry(value.to_string())
+                .or_default()
+                .push_back(seq);
+        }
+        self.entries.push_back(entry);
+    }
+
+    fn pop_front(&mut self) {
+        let Some(entry) = self.entries.pop_front() else { return };
+        for (field, value) in entry.indexed_fields() {
+            if let Some(values) = self.index.get_mut(field) {
+                if let Some(seqs) = values.get_mut(value) {
+                    seqs.pop_front();
+                    if seqs.is_empty() {
+                        values.remove(value);
+                    }
+                }
+                if values.is_empty() {
+                    self.index.remove(field);
+                }
+            }
+        }
+        self.first_seq += 1;
+    }
+
+    fn clear(&mut self) {
+        self.first_seq += self.entries.len() as u64;
+        self.entries.clear();
+        self.index.clear();
+    }
+
+    fn last_timestamp(&self) -> Option<DateTime<Utc>> {
+        self.entries.back().map(|e| e.timestamp)
+    }
+
+    /// Matching entries in arrival order. Uses the narrowest equality index
+    /// when the filter has one, else the binary-searched time window.
+    fn query(&self, filter: &LogFilter) -> Vec<&LogEntry> {
+        let start = filter.start_time.map_or(0, |t| self.entries.partition_point(|e| e.timestamp < t));
+        let end = filter.end_time.map_or(self.entries.len(), |t| self.entries.partition_point(|e| e.timestamp <= t));
+        if start >= end {
+            return Vec::new();
+        }
+
+        let mut equalities: Vec<(&str, &str)> = filter.fields.iter()
+            .filter_map(|c| match c {
+                FieldCondition::Eq { field, value } => Some((field.as_str(), value.as_str())),
+                FieldCondition::Range { .. } => None,
+            })
+            .collect();
+        equalities.extend(filter.workflow_id.as_deref().map(|v| ("workflow_id", v)));
+        equalities.extend(filter.execution_id.as_deref().map(|v| ("execution_id", v)));
+        equalities.extend(filter.source.as_deref().map(|v| ("source", v)));
+
+        let keyword = filter.keyword.as_ref().map(|k| k.to_lowercase());
+        let matches = |entry: &LogEntry| {
+            equalities.iter().all(|(field, value)| entry.field(field) == Some(*value))
+                && filter.level.as_ref().map_or(true, |min| &entry.level >= min)
+                && filter.fields.iter().all(|c| c.matches(entry))
+                && keyword.as_ref().map_or(true, |k| {
+                    entry.message.to_lowercase().contains(k)
+                        || entry.metadata.values().any(|v| v.to_lowercase().contains(k))
+                })
+        };
+
+        let candidates = equalities.iter()
+            .map(|(field, value)| {
+                self.index.get(*field).and_then(|values| values.get(*value))
+            })
+            .min_by_key(|seqs| seqs.map_or(0, |s| s.len()));
+        match candidates {
+            Some(None) => Vec::new(),
+            Some(Some(seqs)) => seqs.iter()
+                .map(|seq| (seq - self.first_seq) as usize)
+                .filter(|&pos| pos >= start && pos < end)
+                .map(|pos| &self.entries[pos])
+                .filter(|entry| matches(entry))
+                .collect(),
+            None => self.entries.range(start..end).filter(|entry| matches(entry)).collect(),
+        }
+    }
 }
 
 pub struct LogsService {
-    logs: Arc<RwLock<Vec<LogEntry>>>,
+    logs: RwLock<LogBuffer>,
     max_logs: usize,
 }
 
@@ -69,7 +241,7 @@ impl LogsService {
     pub fn new() -> Self {
         info!("📝 Initializing LogsService");
         Self {
-            logs: Arc::new(RwLock::new(Vec::new())),
+            logs: RwLock::new(LogBuffer::default()),
             max_logs: 10000, // Keep last 10,000 logs in memory
         }
     }
@@ -82,28 +254,30 @@ impl LogsService {
         workflow_id: Option<String>,
         execution_id: Option<String>,
         node_id: Option<String>,
+        source: Option<String>,
         metadata: HashMap<String, String>,
     ) -> Result<String, String> {
         let log_id = format!("log-{}-{}", Utc::now().timestamp_millis(), uuid::Uuid::new_v4());
-        
-        let entry = LogEntry {
+
+        let mut logs = self.logs.write().map_err(|e| format!("Lock error: {}", e))?;
+        let now = Utc::now();
+        let timestamp = logs.last_timestamp().map_or(now, |last| now.max(last));
+
+        logs.push(LogEntry {
             id: log_id.clone(),
-            timestamp: Utc::now(),
+            timestamp,
             level: level.clone(),
             workflow_id,
             execution_id,
             node_id,
+            source,
             message: message.clone(),
             metadata,
-        };
-
-        let mut logs = self.logs.write().map_err(|e| format!("Lock error: {}", e))?;
-        logs.push(entry);
+        });
 
         // Trim if exceeds max
-        if logs.len() > self.max_logs {
-            let excess = logs.len() - self.max_logs;
-            logs.drain(0..excess);
+        while logs.entries.len() > self.max_logs {
+            logs.pop_front();
         }
 
         // Also log to console
@@ -125,7 +299,7 @@ impl LogsService {
         execution_id: Option<String>,
         node_id: Option<String>,
     ) -> Result<String, String> {
-        self.log(LogLevel::Debug, message, workflow_id, execution_id, node_id, HashMap::new())
+        self.log(LogLevel::Debug, message, workflow_id, execution_id, node_id, None, HashMap::new())
     }
 
     pub fn info(
@@ -135,7 +309,7 @@ impl LogsService {
         execution_id: Option<String>,
         node_id: Option<String>,
     ) -> Result<String, String> {
-        self.log(LogLevel::Info, message, workflow_id, execution_id, node_id, HashMap::new())
+        self.log(LogLevel::Info, message, workflow_id, execution_id, node_id, None, HashMap::new())
     }
 
     pub fn warn(
@@ -145,7 +319,7 @@ impl LogsService {
         execution_id: Option<String>,
         node_id: Option<String>,
     ) -> Result<String, String> {
-        self.log(LogLevel::Warn, message, workflow_id, execution_id, node_id, HashMap::new())
+        self.log(LogLevel::Warn, message, workflow_id, execution_id, node_id, None, HashMap::new())
     }
 
     pub fn error(
@@ -155,65 +329,15 @@ impl LogsService {
         execution_id: Option<String>,
         node_id: Option<String>,
     ) -> Result<String, String> {
-        self.log(LogLevel::Error, message, workflow_id, execution_id, node_id, HashMap::new())
+        self.log(LogLevel::Error, message, workflow_id, execution_id, node_id, None, HashMap::new())
     }
 
     /// Get logs with filtering
     pub fn get_logs(&self, filter: LogFilter) -> Result<Vec<LogEntry>, String> {
         let logs = self.logs.read().map_err(|e| format!("Lock error: {}", e))?;
-        
-        let mut filtered: Vec<LogEntry> = logs.iter()
-            .filter(|log| {
-                // Filter by workflow_id
-                if let Some(ref wf_id) = filter.workflow_id {
-                    if log.workflow_id.as_ref() != Some(wf_id) {
-                        return false;
-                    }
-                }
-
-                // Filter by execution_id
-                if let Some(ref exec_id) = filter.execution_id {
-                    if log.execution_id.as_ref() != Some(exec_id) {
-                        return false;
-                    }
-                }
-
-                // Filter by level (minimum level)
-                if let Some(ref min_level) = filter.level {
-                    if &log.level < min_level {
-                        return false;
-                    }
-                }
-
-                // Filter by start_time
-                if let Some(ref start) = filter.start_time {
-                    if log.timestamp < *start {
-                        return false;
-                    }
-                }
 
-                // Filter by end_time
-                if let Some(ref end) = filter.end_time {
-                    if log.timestamp > *end {
-                        return false;
-                    }
-                }
-
-                // Filter by keyword (case-insensitive)
-                if let Some(ref keyword) = filter.keyword {
-                    let keyword_lower = keyword.to_lowercase();
-                    if !log.message.to_lowercase().contains(&keyword_lower) {
-                        return false;
-                    }
-                }
-
-                true
-            })
-            .cloned()
-            .collect();
-
-        // Sort by timestamp descending (newest first)
-        filtered.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        // Newest first
+        let mut filtered: Vec<LogEntry> = logs.query(&filter).into_iter().rev().cloned().collect();
 
         // Apply limit
         if let Some(limit) = filter.limit {
@@ -223,19 +347,47 @@ impl LogsService {
         Ok(filtered)
     }
 
+    /// Group entries matching `filter` by a field and compute `metric` per
+    /// group, largest first
+    pub fn aggregate(&self, group_by: &str, metric: LogMetric, filter: LogFilter) -> Result<Vec<LogAggregateBucket>, String> {
+        let logs = self.logs.read().map_err(|e| format!("Lock error: {}", e))?;
+        let matched = logs.query(&filter);
+
+        let mut counts: HashMap<Option<String>, usize> = HashMap::new();
+        for entry in &matched {
+            *counts.entry(entry.field(group_by).map(str::to_string)).or_default() += 1;
+        }
+
+        // Rates use the filter's window, or the span of the matched entries
+        let minutes = match (matched.first(), matched.last()) {
+            (Some(first), Some(last)) => {
+                let start = filter.start_time.unwrap_or(first.timestamp);
+                let end = filter.end_time.unwrap_or(last.timestamp);
+                ((end - start).num_milliseconds() as f64 / 60_000.0).max(1.0)
+            }
+            _ => 1.0,
+        };
+
+        let mut buckets: Vec<LogAggregateBucket> = counts.into_iter()
+            .map(|(key, count)| LogAggregateBucket {
+                key,
+                count,
+                value: match metric {
+                    LogMetric::Count => count as f64,
+                    LogMetric::RatePerMinute => count as f64 / minutes,
+                },
+            })
+            .collect();
+        buckets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+        Ok(buckets)
+    }
+
     /// Get recent logs (last N)
     pub fn get_recent_logs(&self, count: usize) -> Result<Vec<LogEntry>, String> {
         let logs = self.logs.read().map_err(|e| format!("Lock error: {}", e))?;
         
-        let start = if logs.len() > count {
-            logs.len() - count
-        } else {
-            0
-        };
-
-        let mut recent: Vec<LogEntry> = logs[start..].to_vec();
-        recent.reverse(); // Newest first
-        Ok(recent)
+        // Newest first
+        Ok(logs.entries.iter().rev().take(count).cloned().collect())
     }
 
     /// Export logs to JSON file
@@ -311,7 +463,7 @@ impl LogsService {
     /// Clear all logs
     pub fn clear_logs(&self) -> Result<usize, String> {
         let mut logs = self.logs.write().map_err(|e| format!("Lock error: {}", e))?;
-        let count = logs.len();
+        let count = logs.entries.len();
         logs.clear();
         info!("📝 Cleared {} logs", count);
         Ok(count)
@@ -321,6 +473,7 @@ impl LogsService {
     pub fn get_stats(&self) -> Result<LogStats, String> {
         let logs = self.logs.read().map_err(|e| format!("Lock error: {}", e))?;
         
+        let logs = &logs.entries;
         let total = logs.len();
         let debug = logs.iter().filter(|l| matches!(l.level, LogLevel::Debug)).count();
         let info = logs.iter().filter(|l| matches!(l.level, LogLevel::Info)).count();
@@ -345,3 +498,108 @@ pub struct LogStats {
     pub warn: usize,
     pub error: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn seeded() -> LogsService {
+        let service = LogsService::new();
+        let runs = [
+            (LogLevel::Info, "wf-a", "scraper", "120", "page loaded"),
+            (LogLevel::Error, "wf-a", "scraper", "3400", "selector timed out"),
+            (LogLevel::Error, "wf-b", "mailer", "80", "smtp refused"),
+            (LogLevel::Warn, "wf-b", "mailer", "950", "retrying send"),
+            (LogLevel::Error, "wf-a", "scraper", "5100", "selector timed out"),
+        ];
+        for (level, workflow, source, duration, message) in runs {
+            service.log(
+                level,
+                message.to_string(),
+                Some(workflow.to_string()),
+                None,
+                None,
+                Some(source.to_string()),
+                fields(&[("duration_ms", duration), ("host", "example.com")]),
+            ).unwrap();
+        }
+        service
+    }
+
+    #[test]
+    fn test_query_by_field_equality_range_and_level() {
+        let service = seeded();
+
+        let slow_scraper_errors = service.get_logs(LogFilter {
+            level: Some(LogLevel::Error),
+            source: Some("scraper".to_string()),
+            fields: vec![FieldCondition::Range { field: "duration_ms".to_string(), min: Some(4000.0), max: None }],
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(slow_scraper_errors.len(), 1);
+        assert_eq!(slow_scraper_errors[0].metadata["duration_ms"], "5100");
+
+        let mailer = service.get_logs(LogFilter {
+            fields: vec![FieldCondition::Eq { field: "workflow_id".to_string(), value: "wf-b".to_string() }],
+            keyword: Some("RETRY".to_string()),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(mailer.iter().map(|l| l.message.as_str()).collect::<Vec<_>>(), ["retrying send"]);
+
+        let unknown = service.get_logs(LogFilter {
+            fields: vec![FieldCondition::Eq { field: "host".to_string(), value: "other.org".to_string() }],
+            ..Default::default()
+        }).unwrap();
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn test_time_range_query() {
+        let service = seeded();
+        let all = service.get_recent_logs(10).unwrap();
+        // Newest first, and timestamps never go backwards
+        assert!(all.windows(2).all(|w| w[0].timestamp >= w[1].timestamp));
+
+        let before_everything = all.last().unwrap().timestamp - Duration::seconds(1);
+        assert!(service.get_logs(LogFilter { end_time: Some(before_everything), ..Default::default() }).unwrap().is_empty());
+
+        let from_third = all[2].timestamp;
+        let window = service.get_logs(LogFilter {
+            start_time: Some(from_third),
+            end_time: Some(all[0].timestamp),
+            ..Default::default()
+        }).unwrap();
+        assert!(window.len() >= 3);
+        assert!(window.iter().all(|l| l.timestamp >= from_third));
+    }
+
+    #[test]
+    fn test_group_by_count_and_eviction_keeps_index_consistent() {
+        let service = seeded();
+        let errors_per_workflow = service.aggregate(
+            "workflow_id",
+            LogMetric::Count,
+            LogFilter { level: Some(LogLevel::Error), ..Default::default() },
+        ).unwrap();
+        let summary: Vec<(Option<&str>, usize)> = errors_per_workflow.iter()
+            .map(|b| (b.key.as_deref(), b.count))
+            .collect();
+        assert_eq!(summary, [(Some("wf-a"), 2), (Some("wf-b"), 1)]);
+        assert_eq!(errors_per_workflow[0].value, 2.0);
+
+        let mut small = LogsService::new();
+        small.max_logs = 2;
+        for workflow in ["wf-a", "wf-b", "wf-b"] {
+            small.log(LogLevel::Info, "tick".to_string(), Some(workflow.to_string()), None, None, None, HashMap::new()).unwrap();
+        }
+        let by_workflow = small.aggregate("workflow_id", LogMetric::Count, LogFilter::default()).unwrap();
+        assert_eq!(by_workflow.len(), 1);
+        assert_eq!(by_workflow[0].count, 2);
+        assert!(small.get_logs(LogFilter { workflow_id: Some("wf-a".to_string()), ..Default::default() }).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,405 @@
+// Low-quality image placeholders (LQIP)
+//
+// Saved pages keep a BlurHash and a tiny PNG for each image, so they render a
+// blurred preview straight away and still show something offline when the
+// full image was never cached.
+
+use base64::{engine::general_purpose, Engine as _};
+use futures_util::StreamExt;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::io::Cursor;
+use std::time::Duration;
+
+/// Largest side of the embedded PNG
+const THUMBNAIL_SIZE: u32 = 16;
+/// Images are scaled down to this before computing the hash
+const HASH_SAMPLE_SIZE: u32 = 32;
+const MAX_IMAGE_BYTES: usize = 15 * 1024 * 1024;
+const MAX_IMAGES_PER_PAGE: usize = 40;
+const CONCURRENT_FETCHES: usize = 6;
+
+const BASE83: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+static IMG_TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<img\b[^>]*>").unwrap());
+static SRC_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?is)\ssrc\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+))"#).unwrap());
+static STYLE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?is)\sstyle\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+static LOADING_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)\sloading\s*=").unwrap());
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lqip {
+    pub url: String,
+    pub blurhash: String,
+    /// Tiny PNG as a `data:` URL
+    pub data_url: String,
+    /// Size of the original image
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Lqip {
+    /// Decodes an image and builds its placeholder
+    pub fn from_bytes(url: &str, content_type: &str, bytes: &[u8]) -> Result<Self, String> {
+        let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        if !mime.is_empty() && !mime.starts_with("image/") && mime != "application/octet-stream" {
+            return Err(format!("Not an image: {}", mime));
+        }
+        let image = image::ImageReader::new(Cursor::new(bytes))
+            .with_guessed_format()
+            .map_err(|e| format!("Failed to read image: {}", e))?
+            .decode()
+            .map_err(|e| format!("Unsupported or corrupt image: {}", e))?;
+        let (width, height) = (image.width(), image.height());
+        if width == 0 || height == 0 {
+            return Err("Image is empty".to_string());
+        }
+
+        let sample = image.thumbnail(HASH_SAMPLE_SIZE, HASH_SAMPLE_SIZE).to_rgba8();
+        let (components_x, components_y) = if width >= height { (4, 3) } else { (3, 4) };
+        let blurhash = encode_blurhash(components_x, components_y, sample.width(), sample.height(), sample.as_raw());
+
+        let mut png = Vec::new();
+        image
+            .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+            .to_rgba8()
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode placeholder: {}", e))?;
+
+        Ok(Self {
+            url: url.to_string(),
+            blurhash,
+            data_url: format!("data:image/png;base64,{}", general_purpose::STANDARD.encode(&png)),
+            width,
+            height,
+        })
+    }
+}
+
+/// Fetches an image and builds its placeholder
+pub async fn fetch_lqip(client: &reqwest::Client, url: &str) -> Result<Lqip, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch image: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch image: HTTP {}", response.status()));
+    }
+    if response.content_length().is_some_and(|len| len as usize > MAX_IMAGE_BYTES) {
+        return Err("Image is too large".to_string());
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .to_string();
+
+    let mut body = Vec::new();
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| format!("Failed to fetch image: {}", e))?;
+        if body.len() + chunk.len() > MAX_IMAGE_BYTES {
+            return Err("Image is too large".to_string());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Lqip::from_bytes(url, &content_type, &body)
+}
+
+/// Adds placeholders to every `<img>` in `html`: lazy loading, the BlurHash
+/// as `data-blurhash` and the tiny PNG as the background shown until the
+/// image loads. Images that can't be fetched or decoded are only made lazy.
+/// Returns the placeholders in document order.
+pub async fn embed_placeholders(html: &str, base_url: &str) -> (String, Vec<Lqip>) {
+    let base = url::Url::parse(base_url).ok();
+    let resolve = |src: &str| -> Option<String> {
+        let src = src.trim();
+        if src.is_empty() || src.starts_with("data:") {
+            return None;
+        }
+        match &base {
+            Some(base) => base.join(src).ok().map(String::from),
+            None => url::Url::parse(src).ok().map(String::from),
+        }
+    };
+
+    let mut urls: Vec<String> = Vec::new();
+    for tag in IMG_TAG_RE.find_iter(html) {
+        if let Some(url) = img_src(tag.as_str()).and_then(|src| resolve(&src)) {
+            if !urls.contains(&url) && urls.len() < MAX_IMAGES_PER_PAGE {
+                urls.push(url);
+            }
+        }
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+    let mut placeholders: HashMap<String, Lqip> = futures_util::stream::iter(urls.clone())
+        .map(|url| {
+            let client = client.clone();
+            async move {
+                match fetch_lqip(&client, &url).await {
+                    Ok(lqip) => Some((url, lqip)),
+                    Err(e) => {
+                        log::debug!("No placeholder for {}: {}", url, e);
+                        None
+                    }
+                }
+            }
+        })
+        .buffer_unordered(CONCURRENT_FETCHES)
+        .filter_map(|result| async move { result })
+        .collect()
+        .await;
+
+    let rewritten = IMG_TAG_RE.replace_all(html, |caps: &regex::Captures| {
+        let tag = &caps[0];
+        let lqip = img_src(tag).and_then(|src| resolve(&src)).and_then(|url| placeholders.get(&url));
+        with_placeholder(tag, lqip)
+    });
+
+    let found = urls.iter().filter_map(|url| placeholders.remove(url)).collect();
+    (rewritten.into_owned(), found)
+}
+
+fn img_src(tag: &str) -> Option<String> {
+    let caps = SRC_RE.captures(tag)?;
+    caps.get(1).or(caps.get(2)).or(caps.get(3)).map(|m| m.as_str().to_string())
+}
+
+fn with_placeholder(tag: &str, lqip: Option<&Lqip>) -> String {
+    let (open, close) = match tag.strip_suffix("/>") {
+        Some(open) => (open.trim_end(), " />"),
+        None => (tag.strip_suffix('>').unwrap_or(tag).trim_end(), ">"),
+    };
+    let mut tag = open.to_string();
+    if !LOADING_RE.is_match(&tag) {
+        tag.push_str(" loading=\"lazy\" decoding=\"async\"");
+    }
+    let Some(lqip) = lqip else {
+        return tag + close;
+    };
+
+    let background = format!("background:url({}) center/cover no-repeat;", lqip.data_url);
+    tag = match STYLE_RE.captures(&tag) {
+        Some(caps) => {
+            let existing = caps.get(1).or(caps.get(2)).map_or("", |m| m.as_str());
+            let merged = format!(" style=\"{}{}\"", background, existing.replace('"', "'"));
+            format!("{}{}{}", &tag[..caps.get(0).unwrap().start()], merged, &tag[caps.get(0).unwrap().end()..])
+        }
+        None => format!("{} style=\"{}\"", tag, background),
+    };
+    format!("{} data-blurhash=\"{}\"{}", tag, lqip.blurhash.replace('"', "&quot;"), close)
+}
+
+// ==================== BlurHash ====================
+
+fn encode_blurhash(components_x: u32, components_y: u32, width: u32, height: u32, rgba: &[u8]) -> String {
+    let mut factors = Vec::with_capacity((components_x * components_y) as usize);
+    for j in 0..components_y {
+        for i in 0..components_x {
+            let normalisation = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+            let mut sum = [0.0f64; 3];
+            for y in 0..height {
+                for x in 0..width {
+                    let basis = (PI * f64::from(i) * f64::from(x) / f64::from(width)).cos()
+                        * (PI * f64::from(j) * f64::from(y) / f64::from(height)).cos();
+                    let pixel = ((y * width + x) * 4) as usize;
+                    for (channel, total) in sum.iter_mut().enumerate() {
+                        *total += basis * srgb_to_linear(rgba[pixel + channel]);
+                    }
+                }
+            }
+            let scale = normalisation / f64::from(width * height);
+            factors.push(sum.map(|v| v * scale));
+        }
+    }
+
+    let mut hash = String::new();
+    push_base83(&mut hash, (components_x - 1) + (components_y - 1) * 9, 1);
+
+    let (dc, ac) = factors.split_first().expect("at least one component");
+    let maximum = if ac.is_empty() {
+        push_base83(&mut hash, 0, 1);
+        1.0
+    } else {
+        let actual = ac.iter().flatten().fold(0.0f64, |max, v| max.max(v.abs()));
+        let quantised = ((actual * 166.0 - 0.5).floor()).clamp(0.0, 82.0) as u32;
+        push_base83(&mut hash, quantised, 1);
+        f64::from(quantised + 1) / 166.0
+    };
+
+    let dc_value = (u32::from(linear_to_srgb(dc[0])) << 16)
+        + (u32::from(linear_to_srgb(dc[1])) << 8)
+        + u32::from(linear_to_srgb(dc[2]));
+    push_base83(&mut hash, dc_value, 4);
+
+    for factor in ac {
+        let quantise = |v: f64| (sign_pow(v / maximum, 0.5) * 9.0 + 9.5).floor().clamp(0.0, 18.0) as u32;
+        push_base83(&mut hash, quantise(factor[0]) * 19 * 19 + quantise(factor[1]) * 19 + quantise(factor[2]), 2);
+    }
+    hash
+}
+
+fn push_base83(hash: &mut String, value: u32, length: u32) {
+    for i in 1..=length {
+        let digit = (value / 83u32.pow(length - i)) % 83;
+        hash.push(BASE83[digit as usize] as char);
+    }
+}
+
+fn srgb_to_linear(value: u8) -> f64 {
+    let v = f64::from(value) / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f64) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    if v <= 0.003_130_8 {
+        (v * 12.92 * 255.0 + 0.5) as u8
+    } else {
+        ((1.055 * v.powf(1.0 / 2.4) - 0.055) * 255.0 + 0.5) as u8
+    }
+}
+
+fn sign_pow(value: f64, exponent: f64) -> f64 {
+    value.abs().powf(exponent).copysign(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference BlurHash decoder, for checking what the encoder produced
+    fn decode_blurhash(hash: &str, width: u32, height: u32) -> Vec<[u8; 3]> {
+        let digit = |c: u8| BASE83.iter().position(|b| *b == c).unwrap() as u32;
+        let value = |s: &[u8]| s.iter().fold(0, |acc, c| acc * 83 + digit(*c));
+        let bytes = hash.as_bytes();
+        let size = value(&bytes[0..1]);
+        let (components_x, components_y) = (size % 9 + 1, size / 9 + 1);
+        assert_eq!(bytes.len() as u32, 4 + 2 * components_x * components_y);
+        let maximum = f64::from(value(&bytes[1..2]) + 1) / 166.0;
+
+        let dc = value(&bytes[2..6]);
+        let mut colors = vec![[dc >> 16, (dc >> 8) & 255, dc & 255].map(|c| srgb_to_linear(c as u8))];
+        for i in 1..(components_x * components_y) as usize {
+            let ac = value(&bytes[4 + i * 2..6 + i * 2]);
+            let unquantise = |q: u32| sign_pow((f64::from(q) - 9.0) / 9.0, 2.0) * maximum;
+            colors.push([unquantise(ac / 361), unquantise((ac / 19) % 19), unquantise(ac % 19)]);
+        }
+
+        let mut pixels = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let mut pixel = [0.0f64; 3];
+                for j in 0..components_y {
+                    for i in 0..components_x {
+                        let basis = (PI * f64::from(x * i) / f64::from(width)).cos()
+                            * (PI * f64::from(y * j) / f64::from(height)).cos();
+                        let color = colors[(j * components_x + i) as usize];
+                        for c in 0..3 {
+                            pixel[c] += color[c] * basis;
+                        }
+                    }
+                }
+                pixels.push(pixel.map(linear_to_srgb));
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn test_lqip_for_sample_image() {
+        // Red on the left fading to blue on the right
+        let sample = image::RgbImage::from_fn(120, 80, |x, _| {
+            let t = x as f32 / 119.0;
+            image::Rgb([(255.0 * (1.0 - t)) as u8, 40, (255.0 * t) as u8])
+        });
+        let mut jpeg = Vec::new();
+        sample.write_to(&mut Cursor::new(&mut jpeg), image::ImageFormat::Jpeg).unwrap();
+
+        let lqip = Lqip::from_bytes("https://example.com/hero.jpg", "image/jpeg", &jpeg).unwrap();
+        assert_eq!((lqip.width, lqip.height), (120, 80));
+        assert_eq!(lqip.blurhash.len(), 4 + 2 * 4 * 3);
+
+        let png = general_purpose::STANDARD
+            .decode(lqip.data_url.strip_prefix("data:image/png;base64,").unwrap())
+            .unwrap();
+        assert!(png.len() < 1024);
+        let thumbnail = image::load_from_memory(&png).unwrap().to_rgb8();
+        assert_eq!(thumbnail.width(), 16);
+        assert!((10..=11).contains(&thumbnail.height()));
+        let (left, right) = (thumbnail.get_pixel(0, 5), thumbnail.get_pixel(15, 5));
+        assert!(left[0] > 200 && left[2] < 60, "left edge should be red: {:?}", left);
+        assert!(right[2] > 200 && right[0] < 60, "right edge should be blue: {:?}", right);
+
+        let blurred = decode_blurhash(&lqip.blurhash, 8, 4);
+        let (left, right) = (blurred[8], blurred[15]);
+        assert!(left[0] > left[2] + 80, "hash should keep red on the left: {:?}", left);
+        assert!(right[2] > right[0] + 80, "hash should keep blue on the right: {:?}", right);
+
+        assert!(Lqip::from_bytes("https://example.com/a.html", "text/html", b"<html></html>").is_err());
+        assert!(Lqip::from_bytes("https://example.com/b.png", "image/png", b"not really a png").is_err());
+
+        let tag = with_placeholder("<img src=\"hero.jpg\" style=\"width:100%\">", Some(&lqip));
+        assert!(tag.starts_with("<img src=\"hero.jpg\" style=\"background:url(data:image/png;base64,"));
+        assert!(tag.ends_with(&format!(
+            "width:100%\" loading=\"lazy\" decoding=\"async\" data-blurhash=\"{}\">",
+            lqip.blurhash
+        )));
+        assert_eq!(with_placeholder("<img src=x loading=eager/>", None), "<img src=x loading=eager />");
+    }
+
+    #[tokio::test]
+    async fn test_embed_placeholders_skips_failed_images() {
+        let mut png = Vec::new();
+        image::RgbImage::from_pixel(40, 30, image::Rgb([30, 120, 200]))
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            use std::io::{BufRead, BufReader, Write};
+            for stream in listener.incoming().flatten() {
+                let mut request_line = String::new();
+                BufReader::new(&stream).read_line(&mut request_line).unwrap();
+                let mut stream = stream;
+                let response = if request_line.starts_with("GET /img/photo.png ") {
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        png.len()
+                    );
+                    [head.as_bytes(), &png].concat()
+                } else {
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+                };
+                let _ = stream.write_all(&response);
+            }
+        });
+
+        let html = "<p><img src=\"../img/photo.png\" alt=\"Photo\"><img src='missing.jpg'/><img src=\"data:image/gif;base64,R0lGOD\"></p>";
+        let base = format!("http://127.0.0.1:{}/posts/article.html", port);
+        let (html, found) = embed_placeholders(html, &base).await;
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].url, format!("http://127.0.0.1:{}/img/photo.png", port));
+        assert_eq!((found[0].width, found[0].height), (40, 30));
+        assert!(html.contains(&format!("data-blurhash=\"{}\"", found[0].blurhash)));
+        assert!(html.contains("<img src='missing.jpg' loading=\"lazy\" decoding=\"async\" />"));
+        assert_eq!(html.matches("data-blurhash").count(), 1);
+        assert_eq!(html.matches("loading=\"lazy\"").count(), 3);
+    }
+}
@@ -0,0 +1,537 @@
+// ═══════════════════════════════════════════════════════════════════════════════
+// CUBE MAIL ATTACHMENTS - Storage, Malware Scanning & Inline Images
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Attachment handling for CUBE Mail:
+// - Attachment bodies are written to disk, never kept on the Email record
+// - Optional scanning through clamd (INSTREAM) or SHA-256 hash reputation
+// - Infected attachments cannot be saved until the user releases them
+// - cid: references in HTML bodies are resolved to the inline image parts
+//
+// ═══════════════════════════════════════════════════════════════════════════════
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::RwLock;
+use log::{info, warn};
+use sha2::{Digest, Sha256};
+use base64::Engine;
+
+use super::cube_mail_service::{AttachmentScanStatus, Email, EmailAttachment};
+
+/// SHA-256 of the standard 68-byte EICAR anti-virus test file
+pub const EICAR_SHA256: &str = "275a021bbfb6489e54d471899f7db9d1663fc695ec2fe2a2c4538aabf651fd0f";
+
+/// Chunk size used when reading, hashing and streaming attachment bodies
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Inline images larger than this keep their cid: reference and are served from disk
+const MAX_INLINE_DATA_URI_BYTES: usize = 2 * 1024 * 1024;
+
+/// Known-bad hashes that are always flagged when scanning is enabled
+const BUILTIN_BAD_HASHES: &[(&str, &str)] = &[(EICAR_SHA256, "EICAR-Test-File")];
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SCAN CONFIGURATION
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Scanner backend used for incoming attachments
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachmentScanEngine {
+    #[default]
+    Disabled,
+    /// Local SHA-256 reputation list only
+    HashReputation,
+    /// clamd over TCP or a unix socket, plus the hash reputation list
+    Clamav,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentScanConfig {
+    pub engine: AttachmentScanEngine,
+    /// `host:port` or `unix:/path/to/clamd.sock`
+    pub clamav_address: String,
+    /// Additional SHA-256 digests to treat as malicious
+    pub blocked_hashes: Vec<String>,
+    /// Attachments above this size are not sent to clamd
+    pub max_scan_bytes: u64,
+}
+
+impl Default for AttachmentScanConfig {
+    fn default() -> Self {
+        Self {
+            engine: AttachmentScanEngine::Disabled,
+            clamav_address: "127.0.0.1:3310".to_string(),
+            blocked_hashes: Vec::new(),
+            max_scan_bytes: 100 * 1024 * 1024,
+        }
+    }
+}
+
+/// Result of scanning one attachment body
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanVerdict {
+    pub status: AttachmentScanStatus,
+    pub detail: Option<String>,
+    pub sha256: String,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// ATTACHMENT STORE
+// ═══════════════════════════════════════════════════════════════════════════════
+
+pub struct MailAttachmentStore {
+    root: PathBuf,
+    config: RwLock<AttachmentScanConfig>,
+}
+
+impl MailAttachmentStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            config: RwLock::new(AttachmentScanConfig::default()),
+        }
+    }
+
+    pub async fn get_config(&self) -> AttachmentScanConfig {
+        self.config.read().await.clone()
+    }
+
+    pub async fn update_config(&self, config: AttachmentScanConfig) -> Result<(), String> {
+        if config.engine == AttachmentScanEngine::Clamav && config.clamav_address.trim().is_empty() {
+            return Err("ClamAV address is required".to_string());
+        }
+        if let Some(bad) = config.blocked_hashes.iter().find(|h| h.len() != 64 || hex::decode(h).is_err()) {
+            return Err(format!("Invalid SHA-256 digest: {}", bad));
+        }
+        *self.config.write().await = config;
+        Ok(())
+    }
+
+    /// Write every attachment of a parsed message to disk and scan it.
+    /// `raw` must be the message `email` was parsed from.
+    pub async fn ingest(&self, email: &mut Email, raw: &[u8]) -> Result<(), String> {
+        let message = mail_parser::MessageParser::default()
+            .parse(raw)
+            .ok_or_else(|| "Failed to parse email".to_string())?;
+
+        let dir = self.root.join(&email.account_id);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| format!("Failed to create attachment directory: {}", e))?;
+
+        for (attachment, part) in email.attachments.iter_mut().zip(message.attachments()) {
+            let path = dir.join(&attachment.id);
+            let mut file = tokio::fs::File::create(&path)
+                .await
+                .map_err(|e| format!("Failed to store attachment: {}", e))?;
+            for chunk in part.contents().chunks(STREAM_CHUNK_SIZE) {
+                file.write_all(chunk)
+                    .await
+                    .map_err(|e| format!("Failed to store attachment: {}", e))?;
+            }
+            file.flush().await.map_err(|e| format!("Failed to store attachment: {}", e))?;
+
+            attachment.local_path = Some(path.to_string_lossy().to_string());
+            self.scan(attachment).await;
+        }
+
+        Ok(())
+    }
+
+    /// Scan a stored attachment and record the verdict on it
+    pub async fn scan(&self, attachment: &mut EmailAttachment) {
+        let config = self.get_config().await;
+        if config.engine == AttachmentScanEngine::Disabled {
+            return;
+        }
+        let Some(path) = attachment.local_path.clone() else {
+            return;
+        };
+
+        let verdict = scan_file(&config, Path::new(&path)).await.unwrap_or_else(|e| ScanVerdict {
+            status: AttachmentScanStatus::Error,
+            detail: Some(e),
+            sha256: String::new(),
+        });
+
+        if verdict.status == AttachmentScanStatus::Infected {
+            warn!(
+                "🦠 Attachment {} flagged: {}",
+                attachment.filename,
+                verdict.detail.as_deref().unwrap_or("malware")
+            );
+        }
+
+        attachment.scan_status = verdict.status;
+        attachment.scan_detail = verdict.detail;
+        if !verdict.sha256.is_empty() {
+            attachment.sha256 = Some(verdict.sha256);
+        }
+    }
+
+    /// Stream a stored attachment to `destination`, refusing infected ones
+    pub async fn save_to(&self, attachment: &EmailAttachment, destination: &Path) -> Result<u64, String> {
+        if attachment.scan_status == AttachmentScanStatus::Infected {
+            return Err(format!(
+                "Attachment {} is quarantined ({}); release it before downloading",
+                attachment.filename,
+                attachment.scan_detail.as_deref().unwrap_or("infected")
+            ));
+        }
+
+        let source = attachment
+            .local_path
+            .as_ref()
+            .ok_or_else(|| format!("Attachment {} has not been downloaded", attachment.filename))?;
+
+        let mut reader = tokio::fs::File::open(source)
+            .await
+            .map_err(|e| format!("Failed to open attachment: {}", e))?;
+        let mut writer = tokio::fs::File::create(destination)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", destination.display(), e))?;
+
+        let copied = tokio::io::copy(&mut reader, &mut writer)
+            .await
+            .map_err(|e| format!("Failed to save attachment: {}", e))?;
+
+        info!("📎 Saved attachment {} ({} bytes)", attachment.filename, copied);
+        Ok(copied)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// SCANNING
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Scan a file on disk with the configured engine
+pub async fn scan_file(config: &AttachmentScanConfig, path: &Path) -> Result<ScanVerdict, String> {
+    let (sha256, size) = hash_file(path).await?;
+
+    let known_bad = BUILTIN_BAD_HASHES
+        .iter()
+        .find(|(hash, _)| *hash == sha256)
+        .map(|(_, name)| name.to_string())
+        .or_else(|| {
+            config
+                .blocked_hashes
+                .iter()
+                .any(|h| h.eq_ignore_ascii_case(&sha256))
+                .then(|| "Hash.Reputation.Blocked".to_string())
+        });
+
+    if let Some(signature) = known_bad {
+        return Ok(ScanVerdict {
+            status: AttachmentScanStatus::Infected,
+            detail: Some(signature),
+            sha256,
+        });
+    }
+
+    let (status, detail) = match config.engine {
+        AttachmentScanEngine::Disabled => (AttachmentScanStatus::NotScanned, None),
+        AttachmentScanEngine::HashReputation => (AttachmentScanStatus::Clean, None),
+        AttachmentScanEngine::Clamav if size > config.max_scan_bytes => (
+            AttachmentScanStatus::Error,
+            Some(format!("Attachment exceeds the {} byte scan limit", config.max_scan_bytes)),
+        ),
+        AttachmentScanEngine::Clamav => match clamd_scan(&config.clamav_address, path).await {
+            Ok(None) => (AttachmentScanStatus::Clean, None),
+            Ok(Some(signature)) => (AttachmentScanStatus::Infected, Some(signature)),
+            Err(e) => (AttachmentScanStatus::Error, Some(e)),
+        },
+    };
+
+    Ok(ScanVerdict { status, detail, sha256 })
+}
+
+async fn hash_file(path: &Path) -> Result<(String, u64), String> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open attachment: {}", e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; STREAM_CHUNK_SIZE];
+    let mut size = 0u64;
+
+    loop {
+        let n = file.read(&mut buf).await.map_err(|e| format!("Read error: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        size += n as u64;
+    }
+
+    Ok((hex::encode(hasher.finalize()), size))
+}
+
+/// Returns the signature name if clamd reports the file as infected
+async fn clamd_scan(address: &str, path: &Path) -> Result<Option<String>, String> {
+    #[cfg(unix)]
+    if let Some(socket) = address.strip_prefix("unix:") {
+        let stream = tokio::net::UnixStream::connect(socket)
+            .await
+            .map_err(|e| format!("ClamAV connection failed: {}", e))?;
+        return clamd_instream(stream, path).await;
+    }
+
+    let stream = tokio::net::TcpStream::connect(address)
+        .await
+        .map_err(|e| format!("ClamAV connection failed: {}", e))?;
+    clamd_instream(stream, path).await
+}
+
+/// Stream a file to clamd with the INSTREAM command
+async fn clamd_instream<S>(mut stream: S, path: &Path) -> Result<Option<String>, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let io_err = |e: std::io::Error| format!("ClamAV I/O error: {}", e);
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .map_err(|e| format!("Failed to open attachment: {}", e))?;
+
+    stream.write_all(b"zINSTREAM\0").await.map_err(io_err)?;
+    let mut buf = vec![0u8; STREAM_CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buf).await.map_err(io_err)?;
+        if n == 0 {
+            break;
+        }
+        stream.write_all(&(n as u32).to_be_bytes()).await.map_err(io_err)?;
+        stream.write_all(&buf[..n]).await.map_err(io_err)?;
+    }
+    stream.write_all(&0u32.to_be_bytes()).await.map_err(io_err)?;
+    stream.flush().await.map_err(io_err)?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await.map_err(io_err)?;
+    let reply = String::from_utf8_lossy(&reply);
+    let reply = reply.trim_end_matches(['\0', '\n']).trim();
+
+    // "stream: OK" | "stream: <signature> FOUND" | "<message> ERROR"
+    let result = reply.strip_prefix("stream:").map(str::trim).unwrap_or(reply);
+    if result == "OK" {
+        Ok(None)
+    } else if let Some(signature) = result.strip_suffix("FOUND") {
+        Ok(Some(signature.trim().to_string()))
+    } else {
+        Err(format!("ClamAV error: {}", reply))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// INLINE IMAGES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Replace `cid:` references to `content_id` in an HTML body with a data URI of
+/// the part. Returns `None` when the body does not reference the part.
+pub fn embed_inline_image(html: &str, content_id: &str, mime_type: &str, contents: &[u8]) -> Option<String> {
+    let cid = content_id.trim().trim_start_matches('<').trim_end_matches('>');
+    if cid.is_empty() {
+        return None;
+    }
+
+    let lower = html.to_ascii_lowercase();
+    let needle = format!("cid:{}", cid.to_ascii_lowercase());
+    let mut matches = Vec::new();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(&needle) {
+        let start = from + pos;
+        let end = start + needle.len();
+        // Avoid matching a prefix of a longer Content-ID
+        let boundary = !lower[end..].starts_with(|c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '@' | '-' | '_' | '$'));
+        if boundary {
+            matches.push((start, end));
+        }
+        from = end;
+    }
+
+    if matches.is_empty() {
+        return None;
+    }
+    if contents.len() > MAX_INLINE_DATA_URI_BYTES {
+        // Still rendered inline, but the viewer loads it from local storage
+        return Some(html.to_string());
+    }
+
+    let data_uri = format!(
+        "data:{};base64,{}",
+        mime_type,
+        base64::engine::general_purpose::STANDARD.encode(contents)
+    );
+    let mut rewritten = String::with_capacity(html.len() + data_uri.len() * matches.len());
+    let mut last = 0;
+    for (start, end) in matches {
+        rewritten.push_str(&html[last..start]);
+        rewritten.push_str(&data_uri);
+        last = end;
+    }
+    rewritten.push_str(&html[last..]);
+    Some(rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::cube_mail_service::{CubeMailServiceState, MailFolder};
+
+    const EICAR: &str = r"X5O!P%@AP[4\PZX54(P^)7CC)7}$EICAR-STANDARD-ANTIVIRUS-TEST-FILE!$H+H*";
+
+    fn temp_root(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("cube_mail_{}_{}", name, uuid::Uuid::new_v4()))
+    }
+
+    fn eicar_message() -> String {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(EICAR);
+        format!(
+            "From: sender@example.com\r\n\
+             To: me@example.com\r\n\
+             Subject: Invoice\r\n\
+             MIME-Version: 1.0\r\n\
+             Content-Type: multipart/mixed; boundary=\"b1\"\r\n\r\n\
+             --b1\r\n\
+             Content-Type: text/plain\r\n\r\n\
+             Please see attached.\r\n\
+             --b1\r\n\
+             Content-Type: application/octet-stream; name=\"invoice.com\"\r\n\
+             Content-Disposition: attachment; filename=\"invoice.com\"\r\n\
+             Content-Transfer-Encoding: base64\r\n\r\n\
+             {}\r\n\
+             --b1--\r\n",
+            encoded
+        )
+    }
+
+    #[tokio::test]
+    async fn test_eicar_attachment_is_flagged_until_released() {
+        let root = temp_root("eicar");
+        let service = CubeMailServiceState::with_attachment_root(root.clone());
+        service
+            .update_attachment_scan_config(AttachmentScanConfig {
+                engine: AttachmentScanEngine::HashReputation,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let email = service
+            .ingest_raw_email("acc1", MailFolder::Inbox, eicar_message().as_bytes())
+            .await
+            .unwrap();
+        assert!(email.has_attachments);
+        let attachment = &email.attachments[0];
+        assert_eq!(attachment.filename, "invoice.com");
+        assert_eq!(attachment.scan_status, AttachmentScanStatus::Infected);
+        assert_eq!(attachment.scan_detail.as_deref(), Some("EICAR-Test-File"));
+        assert_eq!(attachment.sha256.as_deref(), Some(EICAR_SHA256));
+
+        // Fetching returns the flag, and saving is blocked
+        let fetched = service.get_email("acc1", &email.id).await.unwrap();
+        assert_eq!(fetched.attachments[0].scan_status, AttachmentScanStatus::Infected);
+        let destination = root.join("saved.com");
+        assert!(service.save_attachment("acc1", &email.id, &attachment.id, &destination).await.is_err());
+        assert!(!destination.exists());
+
+        let released = service.release_attachment("acc1", &email.id, &attachment.id).await.unwrap();
+        assert_eq!(released.scan_status, AttachmentScanStatus::Released);
+        let copied = service.save_attachment("acc1", &email.id, &attachment.id, &destination).await.unwrap();
+        assert_eq!(copied, EICAR.len() as u64);
+        assert_eq!(std::fs::read(&destination).unwrap(), EICAR.as_bytes());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_clamd_instream_reports_signature() {
+        use tokio::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut command = [0u8; 10];
+            socket.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+            let mut body = Vec::new();
+            loop {
+                let len = socket.read_u32().await.unwrap() as usize;
+                if len == 0 {
+                    break;
+                }
+                let mut chunk = vec![0u8; len];
+                socket.read_exact(&mut chunk).await.unwrap();
+                body.extend_from_slice(&chunk);
+            }
+            let reply: &[u8] = if body.windows(5).any(|w| w == b"EICAR") {
+                b"stream: Eicar-Signature FOUND\0"
+            } else {
+                b"stream: OK\0"
+            };
+            socket.write_all(reply).await.unwrap();
+        });
+
+        let root = temp_root("clamd");
+        std::fs::create_dir_all(&root).unwrap();
+        let path = root.join("sample.bin");
+        // Not the exact EICAR file, so only clamd can catch it
+        std::fs::write(&path, format!("{}\n", EICAR)).unwrap();
+
+        let config = AttachmentScanConfig {
+            engine: AttachmentScanEngine::Clamav,
+            clamav_address: address,
+            ..Default::default()
+        };
+        let verdict = scan_file(&config, &path).await.unwrap();
+        assert_eq!(verdict.status, AttachmentScanStatus::Infected);
+        assert_eq!(verdict.detail.as_deref(), Some("Eicar-Signature"));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn test_cid_inline_image_is_matched_to_body() {
+        let png = [0x89u8, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 1, 2, 3, 4];
+        let encoded = base64::engine::general_purpose::STANDARD.encode(png);
+        let raw = format!(
+            "From: news@example.com\r\n\
+             To: me@example.com\r\n\
+             Subject: Newsletter\r\n\
+             MIME-Version: 1.0\r\n\
+             Content-Type: multipart/related; boundary=\"rel\"\r\n\r\n\
+             --rel\r\n\
+             Content-Type: text/html; charset=utf-8\r\n\r\n\
+             <html><body><img src=\"cid:logo@cube.mail\" alt=\"logo\"><p>Hello</p></body></html>\r\n\
+             --rel\r\n\
+             Content-Type: image/png; name=\"logo.png\"\r\n\
+             Content-ID: <logo@cube.mail>\r\n\
+             Content-Disposition: inline; filename=\"logo.png\"\r\n\
+             Content-Transfer-Encoding: base64\r\n\r\n\
+             {}\r\n\
+             --rel--\r\n",
+            encoded
+        );
+
+        let root = temp_root("cid");
+        let service = CubeMailServiceState::with_attachment_root(root.clone());
+        let email = service.ingest_raw_email("acc1", MailFolder::Inbox, raw.as_bytes()).await.unwrap();
+
+        assert_eq!(email.attachments.len(), 1);
+        let logo = &email.attachments[0];
+        assert!(logo.is_inline);
+        assert_eq!(logo.content_id.as_deref(), Some("logo@cube.mail"));
+        assert!(!email.has_attachments);
+
+        let html = email.body_html.unwrap();
+        assert!(!html.contains("cid:"));
+        assert!(html.contains(&format!("src=\"data:image/png;base64,{}\"", encoded)));
+
+        // A reference to a different Content-ID is left alone
+        assert!(embed_inline_image("<img src=\"cid:logo@cube.mail2\">", "<logo@cube.mail>", "image/png", &png).is_none());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
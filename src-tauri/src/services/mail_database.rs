@@ -836,6 +836,7 @@ impl MailDatabase {
             storage_used: 0,
             storage_limit: 15 * 1024 * 1024 * 1024,
             color: None,
+            needs_reauth: false,
             created_at: now,
             updated_at: now,
         })
@@ -0,0 +1,390 @@
+// Media feature emulation for the CUBE Web Engine
+//
+// Pages are rendered from HTML the engine fetched, so emulation happens on the
+// way out: media queries on the user-preference features are rewritten to
+// stand-ins that always or never match, and a small script makes
+// `matchMedia` answer the same way. A user-level reduced-motion setting applies
+// to every tab and also damps CSS transitions and requestAnimationFrame loops.
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+/// Stand-ins that keep the surrounding query valid
+const ALWAYS_MATCHES: &str = "(min-width: 0px)";
+const NEVER_MATCHES: &str = "((max-width: 0px) and (min-width: 1px))";
+
+const REDUCED_MOTION_CSS: &str = "*,*::before,*::after{animation-duration:0.01ms!important;\
+animation-iteration-count:1!important;transition-duration:0.01ms!important;\
+scroll-behavior:auto!important}";
+const FORCED_COLORS_CSS: &str = "*,*::before,*::after{background-color:Canvas!important;\
+color:CanvasText!important;border-color:CanvasText!important;box-shadow:none!important;\
+text-shadow:none!important}a:link,a:visited{color:LinkText!important}\
+img,svg,video{filter:none!important}";
+
+static FEATURE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\(\s*(prefers-reduced-motion|prefers-color-scheme|forced-colors|prefers-contrast)\s*(?::\s*([a-z-]+)\s*)?\)",
+    )
+    .unwrap()
+});
+static MEDIA_ATTR_TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<(?:link|style|source)\b[^>]*>").unwrap());
+static MEDIA_ATTR_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)(\bmedia\s*=\s*)(?:"([^"]*)"|'([^']*)')"#).unwrap());
+static STYLE_BLOCK_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)(<style\b[^>]*>)(.*?)(</style>)").unwrap());
+static MEDIA_RULE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)@media\s+([^{;]+)\{").unwrap());
+static HEAD_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<head\b[^>]*>").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MediaFeature {
+    PrefersReducedMotion,
+    PrefersColorScheme,
+    ForcedColors,
+    PrefersContrast,
+}
+
+impl MediaFeature {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "prefers-reduced-motion" => Ok(Self::PrefersReducedMotion),
+            "prefers-color-scheme" => Ok(Self::PrefersColorScheme),
+            "forced-colors" => Ok(Self::ForcedColors),
+            "prefers-contrast" => Ok(Self::PrefersContrast),
+            other => Err(format!("Unsupported media feature: {}", other)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PrefersReducedMotion => "prefers-reduced-motion",
+            Self::PrefersColorScheme => "prefers-color-scheme",
+            Self::ForcedColors => "forced-colors",
+            Self::PrefersContrast => "prefers-contrast",
+        }
+    }
+
+    pub fn values(&self) -> &'static [&'static str] {
+        match self {
+            Self::PrefersReducedMotion => &["no-preference", "reduce"],
+            Self::PrefersColorScheme => &["light", "dark"],
+            Self::ForcedColors => &["none", "active"],
+            Self::PrefersContrast => &["no-preference", "more", "less", "custom"],
+        }
+    }
+
+    /// The value that makes `(feature)` false in a boolean context, if any
+    fn inactive_value(&self) -> Option<&'static str> {
+        match self {
+            Self::PrefersReducedMotion | Self::PrefersContrast => Some("no-preference"),
+            Self::ForcedColors => Some("none"),
+            Self::PrefersColorScheme => None,
+        }
+    }
+}
+
+/// Feature overrides in effect for one tab
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MediaEmulation {
+    pub overrides: BTreeMap<MediaFeature, String>,
+}
+
+impl MediaEmulation {
+    /// Set or, with `None`, clear one override
+    pub fn set(&mut self, feature: MediaFeature, value: Option<&str>) -> Result<(), String> {
+        match value.map(|v| v.trim().to_ascii_lowercase()) {
+            Some(value) => {
+                if !feature.values().contains(&value.as_str()) {
+                    return Err(format!(
+                        "Invalid value '{}' for {} (expected one of: {})",
+                        value,
+                        feature.name(),
+                        feature.values().join(", ")
+                    ));
+                }
+                self.overrides.insert(feature, value);
+            }
+            None => {
+                self.overrides.remove(&feature);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, feature: MediaFeature) -> Option<&str> {
+        self.overrides.get(&feature).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    fn feature_matches(&self, feature: MediaFeature, value: Option<&str>) -> Option<bool> {
+        let current = self.get(feature)?;
+        Some(match value {
+            Some(value) => current.eq_ignore_ascii_case(value),
+            None => feature.inactive_value() != Some(current),
+        })
+    }
+
+    /// Replace tests of emulated features with always/never-matching stand-ins
+    pub fn rewrite_query(&self, query: &str) -> String {
+        FEATURE_RE
+            .replace_all(query, |caps: &Captures| {
+                let Ok(feature) = MediaFeature::parse(&caps[1]) else {
+                    return caps[0].to_string();
+                };
+                match self.feature_matches(feature, caps.get(2).map(|m| m.as_str())) {
+                    Some(true) => ALWAYS_MATCHES.to_string(),
+                    Some(false) => NEVER_MATCHES.to_string(),
+                    None => caps[0].to_string(),
+                }
+            })
+            .into_owned()
+    }
+
+    /// What `matchMedia(query).matches` reports under this emulation, or `None`
+    /// when the answer depends on something other than the emulated features
+    pub fn evaluate(&self, query: &str) -> Option<bool> {
+        let mut any_unknown = false;
+        for part in query.split(',') {
+            match self.evaluate_single(part) {
+                Some(true) => return Some(true),
+                Some(false) => {}
+                None => any_unknown = true,
+            }
+        }
+        if any_unknown { None } else { Some(false) }
+    }
+
+    fn evaluate_single(&self, query: &str) -> Option<bool> {
+        let query = query.trim().to_ascii_lowercase();
+        let (negated, rest) = match query.strip_prefix("not ") {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, query.strip_prefix("only ").unwrap_or(&query).trim_start()),
+        };
+
+        let mut result = true;
+        for term in rest.split(" and ").map(str::trim) {
+            let matched = match term {
+                "all" | "screen" => true,
+                "print" => false,
+                _ => {
+                    let caps = FEATURE_RE.captures(term).filter(|c| c[0].len() == term.len())?;
+                    let feature = MediaFeature::parse(&caps[1]).ok()?;
+                    self.feature_matches(feature, caps.get(2).map(|m| m.as_str()))?
+                }
+            };
+            result &= matched;
+        }
+        Some(result != negated)
+    }
+
+    /// Rewrite the page's media queries and inject the emulation prelude
+    pub fn apply_to_html(&self, html: &str, reduce_motion_effects: bool) -> String {
+        if self.is_empty() && !reduce_motion_effects {
+            return html.to_string();
+        }
+
+        let html = MEDIA_ATTR_TAG_RE.replace_all(html, |tag: &Captures| {
+            MEDIA_ATTR_RE
+                .replace_all(&tag[0], |attr: &Captures| {
+                    let (query, quote) = match attr.get(2) {
+                        Some(q) => (q.as_str(), '"'),
+                        None => (attr.get(3).map_or("", |q| q.as_str()), '\''),
+                    };
+                    format!("{}{}{}{}", &attr[1], quote, self.rewrite_query(query), quote)
+                })
+                .into_owned()
+        });
+        let html = STYLE_BLOCK_RE.replace_all(&html, |block: &Captures| {
+            let css = MEDIA_RULE_RE.replace_all(&block[2], |rule: &Captures| {
+                format!("@media {} {{", self.rewrite_query(rule[1].trim()))
+            });
+            format!("{}{}{}", &block[1], css, &block[3])
+        });
+
+        let prelude = self.prelude(reduce_motion_effects);
+        match HEAD_RE.find(&html) {
+            Some(head) => format!("{}{}{}", &html[..head.end()], prelude, &html[head.end()..]),
+            None => format!("{}{}", prelude, html),
+        }
+    }
+
+    fn prelude(&self, reduce_motion_effects: bool) -> String {
+        let mut css = String::new();
+        if let Some(scheme) = self.get(MediaFeature::PrefersColorScheme) {
+            css.push_str(&format!(":root{{color-scheme:{}}}", scheme));
+        }
+        if self.get(MediaFeature::ForcedColors) == Some("active") {
+            css.push_str(FORCED_COLORS_CSS);
+        }
+        if reduce_motion_effects {
+            css.push_str(REDUCED_MOTION_CSS);
+        }
+
+        let mut out = String::new();
+        if !css.is_empty() {
+            out.push_str(&format!("<style id=\"cube-media-emulation\">{}</style>", css));
+        }
+        out.push_str(&format!("<script id=\"cube-media-emulation-script\">{}</script>", self.script(reduce_motion_effects)));
+        out
+    }
+
+    /// Script that makes `matchMedia` agree with the overrides. Safe to run again
+    /// with new overrides; the original `matchMedia` is kept on first install.
+    pub fn script(&self, reduce_motion_effects: bool) -> String {
+        let overrides: HashMap<&str, &str> =
+            self.overrides.iter().map(|(feature, value)| (feature.name(), value.as_str())).collect();
+        let overrides = serde_json::to_string(&overrides).unwrap_or_else(|_| "{}".to_string());
+        format!(
+            r#"(function(){{var w=window;w.__cubeMediaOverrides={overrides};
+var T="{always}",F="{never}",NONE={{"prefers-reduced-motion":"no-preference","prefers-contrast":"no-preference","forced-colors":"none"}};
+var re=/\(\s*(prefers-reduced-motion|prefers-color-scheme|forced-colors|prefers-contrast)\s*(?::\s*([a-z-]+)\s*)?\)/gi;
+function rewrite(q){{var o=w.__cubeMediaOverrides;return String(q).replace(re,function(m,f,v){{f=f.toLowerCase();if(!(f in o))return m;
+var on=v?o[f]===v.toLowerCase():o[f]!==NONE[f];return on?T:F;}});}}
+if(!w.__cubeMatchMedia){{w.__cubeMatchMedia=w.matchMedia.bind(w);w.matchMedia=function(q){{var m=w.__cubeMatchMedia(rewrite(q));
+try{{Object.defineProperty(m,"media",{{value:String(q)}});}}catch(e){{}}return m;}};}}
+if({reduce}&&!w.__cubeRafThrottled){{w.__cubeRafThrottled=true;var raf=w.requestAnimationFrame.bind(w),last=0;
+w.requestAnimationFrame=function(cb){{return raf(function(t){{if(t-last<100){{return w.requestAnimationFrame(cb);}}last=t;cb(t);}});}};
+if(w.Element&&Element.prototype.animate){{var animate=Element.prototype.animate;Element.prototype.animate=function(k,o){{
+if(typeof o==="number"){{o=0;}}else{{o=Object.assign({{}},o||{{}},{{duration:0,iterations:1}});}}return animate.call(this,k,o);}};}}}}
+}})();"#,
+            overrides = overrides,
+            always = ALWAYS_MATCHES,
+            never = NEVER_MATCHES,
+            reduce = reduce_motion_effects,
+        )
+    }
+}
+
+/// Per-tab overrides plus the user-level reduced-motion preference
+#[derive(Default)]
+pub struct MediaEmulationState {
+    reduced_motion: RwLock<bool>,
+    tabs: RwLock<HashMap<String, MediaEmulation>>,
+}
+
+impl MediaEmulationState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reduced_motion(&self) -> bool {
+        self.reduced_motion.read().map(|r| *r).unwrap_or(false)
+    }
+
+    pub fn set_reduced_motion(&self, enabled: bool) -> Result<(), String> {
+        *self.reduced_motion.write().map_err(|e| format!("Lock error: {}", e))? = enabled;
+        Ok(())
+    }
+
+    pub fn set_feature(&self, tab_id: &str, feature: &str, value: Option<&str>) -> Result<MediaEmulation, String> {
+        let feature = MediaFeature::parse(feature)?;
+        let mut tabs = self.tabs.write().map_err(|e| format!("Lock error: {}", e))?;
+        let emulation = tabs.entry(tab_id.to_string()).or_default();
+        emulation.set(feature, value)?;
+        let updated = emulation.clone();
+        if updated.is_empty() {
+            tabs.remove(tab_id);
+        }
+        Ok(updated)
+    }
+
+    pub fn clear_tab(&self, tab_id: &str) {
+        if let Ok(mut tabs) = self.tabs.write() {
+            tabs.remove(tab_id);
+        }
+    }
+
+    /// Overrides for a tab with the user setting filled in. A tab that explicitly
+    /// emulates `prefers-reduced-motion` keeps its own value.
+    pub fn effective(&self, tab_id: &str) -> MediaEmulation {
+        let mut emulation = self
+            .tabs
+            .read()
+            .ok()
+            .and_then(|tabs| tabs.get(tab_id).cloned())
+            .unwrap_or_default();
+        if self.reduced_motion() {
+            emulation
+                .overrides
+                .entry(MediaFeature::PrefersReducedMotion)
+                .or_insert_with(|| "reduce".to_string());
+        }
+        emulation
+    }
+
+    /// Page HTML as the tab should see it
+    pub fn apply(&self, tab_id: &str, html: &str) -> String {
+        let emulation = self.effective(tab_id);
+        let reduce = emulation.get(MediaFeature::PrefersReducedMotion) == Some("reduce");
+        emulation.apply_to_html(html, reduce)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduced_motion_override_reflected_in_match_media() {
+        let state = MediaEmulationState::new();
+        let before = state.effective("tab-1");
+        assert_eq!(before.evaluate("(prefers-reduced-motion)"), None);
+
+        state.set_feature("tab-1", "prefers-reduced-motion", Some("reduce")).unwrap();
+        let emulation = state.effective("tab-1");
+        assert_eq!(emulation.evaluate("(prefers-reduced-motion)"), Some(true));
+        assert_eq!(emulation.evaluate("(prefers-reduced-motion: no-preference)"), Some(false));
+        assert_eq!(emulation.evaluate("not all and (prefers-reduced-motion: reduce)"), Some(false));
+        assert_eq!(emulation.evaluate("(prefers-reduced-motion) and (min-width: 600px)"), None);
+        assert_eq!(
+            emulation.rewrite_query("(prefers-reduced-motion) and (min-width: 600px)"),
+            format!("{} and (min-width: 600px)", ALWAYS_MATCHES)
+        );
+
+        let html = state.apply("tab-1", "<html><head><title>t</title></head><body></body></html>");
+        assert!(html.starts_with("<html><head><style id=\"cube-media-emulation\">"));
+        assert!(html.contains(r#"w.__cubeMediaOverrides={"prefers-reduced-motion":"reduce"}"#));
+        assert!(html.contains("animation-duration:0.01ms"));
+
+        // The user setting reaches every tab unless a tab overrides it
+        state.set_feature("tab-1", "prefers-reduced-motion", Some("no-preference")).unwrap();
+        state.set_reduced_motion(true).unwrap();
+        assert_eq!(state.effective("tab-2").evaluate("(prefers-reduced-motion)"), Some(true));
+        assert_eq!(state.effective("tab-1").evaluate("(prefers-reduced-motion)"), Some(false));
+
+        assert!(state.set_feature("tab-1", "prefers-reduced-motion", Some("sometimes")).is_err());
+        assert!(state.set_feature("tab-1", "prefers-reduced-transparency", Some("reduce")).is_err());
+    }
+
+    #[test]
+    fn test_color_scheme_emulation_enables_dark_stylesheet() {
+        let state = MediaEmulationState::new();
+        state.set_feature("tab-1", "prefers-color-scheme", Some("dark")).unwrap();
+
+        let page = concat!(
+            "<html><head>",
+            r#"<link rel="stylesheet" href="light.css" media="(prefers-color-scheme: light)">"#,
+            r#"<link rel="stylesheet" href="dark.css" media='(prefers-color-scheme: dark)'>"#,
+            "<style>body{color:#000}@media (prefers-color-scheme: dark){body{color:#fff}}</style>",
+            "</head><body><p>media=\"(prefers-color-scheme: dark)\" in text stays</p></body></html>"
+        );
+        let html = state.apply("tab-1", page);
+
+        assert!(html.contains(&format!(r#"href="dark.css" media='{}'"#, ALWAYS_MATCHES)));
+        assert!(html.contains(&format!(r#"href="light.css" media="{}""#, NEVER_MATCHES)));
+        assert!(html.contains(&format!("@media {} {{body{{color:#fff}}}}", ALWAYS_MATCHES)));
+        assert!(html.contains("<p>media=\"(prefers-color-scheme: dark)\" in text stays</p>"));
+        assert!(html.contains(":root{color-scheme:dark}"));
+        // Reduced-motion effects stay off when only the color scheme is emulated
+        assert!(!html.contains("animation-duration"));
+
+        let emulation = state.effective("tab-1");
+        assert_eq!(emulation.evaluate("(prefers-color-scheme: dark)"), Some(true));
+        assert_eq!(emulation.evaluate("(prefers-color-scheme: light), print"), Some(false));
+    }
+}
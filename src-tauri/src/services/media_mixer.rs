@@ -0,0 +1,235 @@
+// Media Mixer - gapless playlist transitions and crossfades over decoded PCM
+use crate::models::media::{PlaybackSettings, TrackTransition};
+
+/// How long before a transition the next track starts decoding
+pub const PREBUFFER_SECONDS: f64 = 5.0;
+pub const MAX_CROSSFADE_SECONDS: f64 = 12.0;
+/// Silence between tracks when gapless playback is off, matching the time the
+/// player needs to open and start an unbuffered track
+pub const UNBUFFERED_GAP_SECONDS: f64 = 0.25;
+
+/// Interleaved 32-bit float samples
+#[derive(Debug, Clone)]
+pub struct PcmBuffer {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<f32>,
+}
+
+impl PcmBuffer {
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+
+    pub fn duration_seconds(&self) -> f64 {
+        self.frames() as f64 / self.sample_rate as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MixedTransition {
+    pub from_index: usize,
+    pub to_index: usize,
+    /// Output frame where the next track begins
+    pub start_frame: usize,
+    pub overlap_frames: usize,
+    /// Output frame at which the next track's play count should be incremented
+    pub play_count_frame: usize,
+    /// Original sample rate when the next track had to be resampled
+    pub resampled_from: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlaylistMix {
+    pub output: PcmBuffer,
+    pub transitions: Vec<MixedTransition>,
+}
+
+/// Overlap used between two tracks, never more than half of either one
+fn overlap_seconds(settings: &PlaybackSettings, from_seconds: f64, to_seconds: f64) -> f64 {
+    if settings.crossfade_seconds <= 0.0 {
+        return 0.0;
+    }
+    settings
+        .crossfade_seconds
+        .min(MAX_CROSSFADE_SECONDS)
+        .min(from_seconds / 2.0)
+        .min(to_seconds / 2.0)
+}
+
+/// Schedules the hand-over between two tracks for a real-time player
+pub fn plan_transition(
+    from_media_id: &str,
+    from_seconds: f64,
+    to_media_id: &str,
+    to_seconds: f64,
+    settings: &PlaybackSettings,
+) -> TrackTransition {
+    let overlap = overlap_seconds(settings, from_seconds, to_seconds);
+    let start = from_seconds - overlap;
+    // Crossfading needs the next track decoded early too
+    let prebuffer = (settings.gapless || overlap > 0.0).then(|| (start - PREBUFFER_SECONDS).max(0.0));
+
+    TrackTransition {
+        from_media_id: from_media_id.to_string(),
+        to_media_id: to_media_id.to_string(),
+        prebuffer_at_seconds: prebuffer,
+        start_next_at_seconds: if prebuffer.is_some() { start } else { from_seconds + UNBUFFERED_GAP_SECONDS },
+        overlap_seconds: overlap,
+        count_play_at_seconds: start + overlap / 2.0,
+    }
+}
+
+/// Converts a buffer to the given channel count and sample rate. Channels are
+/// averaged down or the first channels repeated up; rates use linear interpolation.
+pub fn convert(buffer: &PcmBuffer, sample_rate: u32, channels: u16) -> PcmBuffer {
+    let from_channels = buffer.channels.max(1) as usize;
+    let to_channels = channels.max(1) as usize;
+    let frames = buffer.frames();
+
+    let remapped: Vec<f32> = if from_channels == to_channels {
+        buffer.samples[..frames * from_channels].to_vec()
+    } else {
+        let mut out = Vec::with_capacity(frames * to_channels);
+        for frame in buffer.samples.chunks_exact(from_channels) {
+            if to_channels == 1 {
+                out.push(frame.iter().sum::<f32>() / from_channels as f32);
+            } else {
+                out.extend((0..to_channels).map(|c| frame[c % from_channels]));
+            }
+        }
+        out
+    };
+
+    if buffer.sample_rate == sample_rate || frames == 0 {
+        return PcmBuffer { sample_rate, channels: to_channels as u16, samples: remapped };
+    }
+
+    let ratio = buffer.sample_rate as f64 / sample_rate as f64;
+    let out_frames = (frames as f64 / ratio).round() as usize;
+    let mut samples = Vec::with_capacity(out_frames * to_channels);
+    for j in 0..out_frames {
+        let position = j as f64 * ratio;
+        let index = (position.floor() as usize).min(frames - 1);
+        let next = (index + 1).min(frames - 1);
+        let frac = (position - index as f64) as f32;
+        for c in 0..to_channels {
+            let a = remapped[index * to_channels + c];
+            let b = remapped[next * to_channels + c];
+            samples.push(a + (b - a) * frac);
+        }
+    }
+
+    PcmBuffer { sample_rate, channels: to_channels as u16, samples }
+}
+
+/// Renders a playlist into one continuous buffer in the first track's format.
+/// With gapless playback each track starts on the frame after the previous one
+/// ends; with a crossfade the tracks overlap using equal-power curves.
+pub fn mix_playlist(tracks: &[PcmBuffer], settings: &PlaybackSettings) -> Result<PlaylistMix, String> {
+    let first = tracks.first().ok_or("Playlist has no tracks")?;
+    if tracks.iter().any(|t| t.sample_rate == 0 || t.channels == 0) {
+        return Err("Tracks must have a sample rate and at least one channel".to_string());
+    }
+
+    let sample_rate = first.sample_rate;
+    let channels = first.channels as usize;
+    let mut out: Vec<f32> = Vec::new();
+    let mut transitions = Vec::new();
+
+    for (i, track) in tracks.iter().enumerate() {
+        let converted = convert(track, sample_rate, channels as u16);
+        if i == 0 {
+            out.extend_from_slice(&converted.samples);
+            continue;
+        }
+
+        let previous_frames = convert(&tracks[i - 1], sample_rate, channels as u16).frames();
+        let overlap_frames = (overlap_seconds(
+            settings,
+            previous_frames as f64 / sample_rate as f64,
+            converted.duration_seconds(),
+        ) * sample_rate as f64)
+            .round() as usize;
+        let overlap_frames = overlap_frames.min(previous_frames).min(converted.frames());
+
+        if overlap_frames == 0 && !settings.gapless {
+            let gap = (UNBUFFERED_GAP_SECONDS * sample_rate as f64).round() as usize;
+            out.resize(out.len() + gap * channels, 0.0);
+        }
+        let start = out.len() / channels - overlap_frames;
+
+        for f in 0..overlap_frames {
+            let t = (f as f32 + 0.5) / overlap_frames as f32 * std::f32::consts::FRAC_PI_2;
+            let (fade_out, fade_in) = (t.cos(), t.sin());
+            for c in 0..channels {
+                let slot = (start + f) * channels + c;
+                out[slot] = out[slot] * fade_out + converted.samples[f * channels + c] * fade_in;
+            }
+        }
+        out.extend_from_slice(&converted.samples[overlap_frames * channels..]);
+
+        transitions.push(MixedTransition {
+            from_index: i - 1,
+            to_index: i,
+            start_frame: start,
+            overlap_frames,
+            play_count_frame: start + overlap_frames / 2,
+            resampled_from: (track.sample_rate != sample_rate).then_some(track.sample_rate),
+        });
+    }
+
+    Ok(PlaylistMix {
+        output: PcmBuffer { sample_rate, channels: channels as u16, samples: out },
+        transitions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(sample_rate: u32, channels: u16, seconds: f64, level: f32) -> PcmBuffer {
+        let frames = (seconds * sample_rate as f64) as usize;
+        PcmBuffer { sample_rate, channels, samples: vec![level; frames * channels as usize] }
+    }
+
+    #[test]
+    fn test_gapless_and_crossfade_transitions() {
+        // Second track differs in rate and channel count and is converted at the boundary
+        let tracks = vec![tone(44_100, 2, 0.5, 0.5), tone(48_000, 1, 0.5, 0.4)];
+
+        let gapless = PlaybackSettings { gapless: true, crossfade_seconds: 0.0 };
+        let mix = mix_playlist(&tracks, &gapless).unwrap();
+        let transition = &mix.transitions[0];
+        assert_eq!(transition.start_frame, 22_050);
+        assert_eq!(transition.overlap_frames, 0);
+        assert_eq!(transition.resampled_from, Some(48_000));
+        assert!((mix.output.frames() as i64 - 44_100).abs() <= 1);
+        // No silent frames anywhere, in particular not at the boundary
+        assert!(mix.output.samples.iter().all(|s| s.abs() > 0.1));
+
+        let crossfade = PlaybackSettings { gapless: true, crossfade_seconds: 0.1 };
+        let mix = mix_playlist(&tracks, &crossfade).unwrap();
+        let transition = &mix.transitions[0];
+        assert_eq!(transition.overlap_frames, 4_410);
+        assert_eq!(transition.start_frame, 22_050 - 4_410);
+        assert_eq!(transition.play_count_frame, transition.start_frame + 2_205);
+        assert!((mix.output.frames() as i64 - (44_100 - 4_410)).abs() <= 1);
+        assert!(mix.output.samples.iter().all(|s| s.abs() > 0.1));
+        // Mid-fade both tracks are audible at equal power
+        let mid = (transition.start_frame + 2_205) * 2;
+        let expected = (0.5 + 0.4) * std::f32::consts::FRAC_1_SQRT_2;
+        assert!((mix.output.samples[mid] - expected).abs() < 0.01);
+
+        let plan = plan_transition("a", 0.5, "b", 0.5, &crossfade);
+        assert!((plan.start_next_at_seconds - 0.4).abs() < 1e-9);
+        assert!((plan.count_play_at_seconds - 0.45).abs() < 1e-9);
+        assert_eq!(plan.prebuffer_at_seconds, Some(0.0));
+
+        let track_by_track = PlaybackSettings { gapless: false, crossfade_seconds: 0.0 };
+        let mix = mix_playlist(&tracks, &track_by_track).unwrap();
+        assert_eq!(mix.transitions[0].start_frame, 22_050 + 11_025);
+        assert!(plan_transition("a", 0.5, "b", 0.5, &track_by_track).prebuffer_at_seconds.is_none());
+    }
+}
@@ -1,4 +1,5 @@
-use crate::models::media::{MediaItem, Playlist, MediaStats, MediaFilter};
+use crate::models::media::{MediaItem, Playlist, MediaStats, MediaFilter, PlaybackSettings, TrackTransition};
+use crate::services::media_mixer::{self, MAX_CROSSFADE_SECONDS};
 use log::info;
 use rusqlite::{params, Connection, Result as SqliteResult};
 use std::sync::{Arc, Mutex};
@@ -75,6 +76,20 @@ impl MediaService {
             [],
         ).map_err(|e| format!("Failed to create playlist_items table: {}", e))?;
         
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS playback_settings (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                gapless INTEGER NOT NULL DEFAULT 1,
+                crossfade_seconds REAL NOT NULL DEFAULT 0
+            )",
+            [],
+        ).map_err(|e| format!("Failed to create playback_settings table: {}", e))?;
+        
+        conn.execute(
+            "INSERT OR IGNORE INTO playback_settings (id, gapless, crossfade_seconds) VALUES (1, 1, 0)",
+            [],
+        ).map_err(|e| format!("Failed to initialize playback settings: {}", e))?;
+        
         // Create indexes
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_media_type ON media_items(media_type)",
@@ -289,6 +304,73 @@ impl MediaService {
         Ok(())
     }
     
+    // Playback settings
+    
+    pub fn get_playback_settings(&self) -> Result<PlaybackSettings, String> {
+        let conn = self.conn.lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        
+        conn.query_row(
+            "SELECT gapless, crossfade_seconds FROM playback_settings WHERE id = 1",
+            [],
+            |row| {
+                Ok(PlaybackSettings {
+                    gapless: row.get::<_, i32>(0)? != 0,
+                    crossfade_seconds: row.get(1)?,
+                })
+            },
+        ).map_err(|e| format!("Failed to get playback settings: {}", e))
+    }
+    
+    pub fn set_crossfade(&self, seconds: f64) -> Result<PlaybackSettings, String> {
+        if !seconds.is_finite() || !(0.0..=MAX_CROSSFADE_SECONDS).contains(&seconds) {
+            return Err(format!("Crossfade must be between 0 and {} seconds", MAX_CROSSFADE_SECONDS));
+        }
+        
+        {
+            let conn = self.conn.lock()
+                .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+            conn.execute(
+                "UPDATE playback_settings SET crossfade_seconds = ?1 WHERE id = 1",
+                params![seconds],
+            ).map_err(|e| format!("Failed to set crossfade: {}", e))?;
+        }
+        
+        self.get_playback_settings()
+    }
+    
+    pub fn set_gapless(&self, enabled: bool) -> Result<PlaybackSettings, String> {
+        {
+            let conn = self.conn.lock()
+                .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+            conn.execute(
+                "UPDATE playback_settings SET gapless = ?1 WHERE id = 1",
+                params![enabled as i32],
+            ).map_err(|e| format!("Failed to set gapless playback: {}", e))?;
+        }
+        
+        self.get_playback_settings()
+    }
+    
+    /// Plans the hand-over from one track to the next using the stored settings.
+    /// The player increments the next track's play count at `count_play_at_seconds`
+    /// rather than when it starts pre-buffering.
+    pub fn plan_transition(&self, from_media_id: &str, to_media_id: &str) -> Result<TrackTransition, String> {
+        let from = self.get_media_item(from_media_id)?
+            .ok_or_else(|| format!("Media item not found: {}", from_media_id))?;
+        let to = self.get_media_item(to_media_id)?
+            .ok_or_else(|| format!("Media item not found: {}", to_media_id))?;
+        let settings = self.get_playback_settings()?;
+        
+        Ok(media_mixer::plan_transition(
+            &from.id,
+            from.duration_seconds as f64,
+            &to.id,
+            to.duration_seconds as f64,
+            &settings,
+        ))
+    }
+    
     // Playlist methods
     
     pub fn get_all_playlists(&self) -> Result<Vec<Playlist>, String> {
@@ -336,16 +336,19 @@ impl MediaVoIPService {
         Self::with_config(config).await
     }
 
-    /// Get ICE servers configuration
-    fn get_ice_servers(&self) -> Vec<RTCIceServer> {
-        // If a TURN provider is set, use its servers
-        let ice_configs = if let Some(ref provider) = self.config.turn_provider {
+    /// ICE servers in effect: the TURN provider's when one is set, otherwise
+    /// the manually configured ones
+    pub fn ice_server_configs(&self) -> Vec<IceServerConfig> {
+        if let Some(ref provider) = self.config.turn_provider {
             provider.to_ice_servers()
         } else {
             self.config.ice_servers.clone()
-        };
+        }
+    }
 
-        ice_configs
+    /// Get ICE servers configuration
+    fn get_ice_servers(&self) -> Vec<RTCIceServer> {
+        self.ice_server_configs()
             .into_iter()
             .map(|config| {
                 let mut server = RTCIceServer {
@@ -793,13 +796,7 @@ impl MediaVoIPService {
 
     /// Check if TURN servers are configured
     pub fn has_turn_servers(&self) -> bool {
-        let ice_configs = if let Some(ref provider) = self.config.turn_provider {
-            provider.to_ice_servers()
-        } else {
-            self.config.ice_servers.clone()
-        };
-
-        ice_configs.iter().any(|config| {
+        self.ice_server_configs().iter().any(|config| {
             config.urls.iter().any(|url| url.starts_with("turn"))
         })
     }
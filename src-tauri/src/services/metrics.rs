@@ -72,6 +72,16 @@ pub struct NodeMetric {
     pub duration_ms: u64,
     pub success: bool,
     pub error: Option<String>,
+    /// 1 for the first try; retried nodes get one metric per attempt
+    #[serde(default = "first_attempt")]
+    pub attempt: u32,
+    /// Set on a failed attempt that was followed by another one
+    #[serde(default)]
+    pub retried: bool,
+}
+
+fn first_attempt() -> u32 {
+    1
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -180,6 +190,8 @@ impl MetricsService {
                 duration_ms,
                 success,
                 error,
+                attempt: 1,
+                retried: false,
             };
             metrics.node_metrics.push(node_metric);
             if !success {
@@ -189,6 +201,19 @@ impl MetricsService {
         Ok(())
     }
 
+    /// Record one attempt of a node run under a retry policy. Only a failure
+    /// that wasn't retried counts the node as failed.
+    pub fn record_node_attempt(&self, execution_id: &str, node_metric: NodeMetric) -> Result<(), String> {
+        let mut executions = self.executions.write().map_err(|e| format!("Lock error: {}", e))?;
+        if let Some(metrics) = executions.get_mut(execution_id) {
+            if !node_metric.success && !node_metric.retried {
+                metrics.nodes_failed += 1;
+            }
+            metrics.node_metrics.push(node_metric);
+        }
+        Ok(())
+    }
+
     /// Update resource usage
     pub fn update_resource_usage(
         &self,
@@ -1,24 +1,33 @@
 pub mod ai_service;
+pub mod ai_streaming;
 pub mod storage_service;
 pub mod encryption_service;
 
 // CUBE Browser Engine - Real Chromium Browser
 pub mod cube_browser_engine;
+pub mod cube_form_hooks; // 🔑 Password & autofill hooks for engine pages
 pub mod browser_shield; // 🛡️ CUBE Shield - Ad/Tracker Blocker (superior to Brave Shields)
 pub mod browser_tab_groups; // 📑 CUBE Tab Groups - AI-powered tab management (superior to Chrome/Opera/Vivaldi)
 pub mod browser_pip; // 🖼️ CUBE PiP Elite - Multi-PiP system (superior to Opera)
 pub mod browser_split_view; // 🪟 CUBE Split View - Sync scrolling & layouts (superior to Vivaldi)
 pub mod browser_sidebar; // 📚 CUBE Sidebar - Messaging, music, web panels (superior to Opera/Vivaldi)
 pub mod browser_ai_assistant; // 🤖 CUBE AI Assistant - Page summary, translation, form fill (superior to all)
+pub mod browser_ai_page_translation; // 🌐 CUBE Page Translation - In-place translation preserving layout & inline formatting
 pub mod browser_reader; // 📖 CUBE Reader Mode - Clean view, TTS, annotations (superior to Safari/Firefox)
 pub mod browser_workspaces; // 🗂️ CUBE Workspaces - Project-based tab organization (superior to Arc/Chrome profiles)
 pub mod browser_screenshot; // 📸 CUBE Screenshot Elite - Full-page capture & annotations (superior to all)
 pub mod browser_downloads; // 📥 CUBE Downloads Manager Elite - Advanced download management (superior to all)
+pub mod torrent_client; // 🧲 Embedded BitTorrent client for torrent/magnet downloads
 pub mod browser_history; // 📜 CUBE History Elite - Sessions, analytics, smart search (superior to all)
+pub mod history_semantic; // 🧭 On-device embeddings + HNSW index for history semantic search
+pub mod history_fts; // 🔍 SQLite FTS5 index for history keyword search
 pub mod browser_bookmarks; // ⭐ CUBE Bookmarks Elite - Hierarchical folders, tags, import/export (superior to all)
+pub mod browser_bookmark_metadata; // 🖼️ CUBE Bookmark Metadata - Background title & favicon fetching with per-domain cache
 pub mod browser_extensions; // 🧩 CUBE Extensions Manager Elite - Chrome compatibility, permissions (superior to all)
 pub mod browser_privacy; // 🔒 CUBE Privacy Dashboard - Unified privacy controls (superior to Brave/Firefox)
 pub mod browser_sync; // 🔄 CUBE Sync Service - Cross-device sync with E2E encryption (superior to all)
+pub mod sync_key_ring; // 🔑 CUBE Sync Key Ring - Per-data-type E2E keys with selective rotation
+pub mod sync_bookmark_merge; // 🌳 CUBE Sync Bookmark Merge - Three-way folder-tree merge with move conflict detection
 pub mod browser_search; // 🔎 CUBE Search Engine - Custom engines, smart omnibox, quick keywords (superior to all)
 pub mod browser_gestures; // 🖱️ CUBE Gestures - Mouse, trackpad, touch, rocker gestures (superior to Vivaldi/Opera)
 pub mod browser_quick_commands; // ⌨️ CUBE Quick Commands - Command palette with fuzzy search (superior to Arc)
@@ -44,9 +53,13 @@ pub mod browser_web_scraper; // 🕷️ CUBE Web Scraper - Data extraction engin
 
 // Notes & Tasks
 pub mod notes_service;
+pub mod notes_markdown;
 
 // Password Manager
 pub mod password_service;
+pub mod password_sharing;
+pub mod password_emergency_access;
+pub mod password_totp;
 
 // Collections
 pub mod collections_service;
@@ -56,6 +69,7 @@ pub mod reading_list_service;
 
 // Media Player
 pub mod media_service;
+pub mod media_mixer;
 
 // Terminal Emulator
 pub mod terminal_service;
@@ -84,10 +98,14 @@ pub mod video_conference_service;
 pub mod media_voip_service;
 
 // Enterprise
+pub mod vpn_dns_guard;
+pub mod vpn_latency;
 pub mod vpn_manager;
 pub mod vpn_provider_api;
 pub mod ftp_manager;
 pub mod ssh_manager;
+pub mod ssh_config_file;
+pub mod ssh_jump;
 pub mod rdp_manager;
 pub mod docker_service;
 
@@ -113,6 +131,8 @@ pub mod workspace_service;
 pub mod layout_template_service;
 pub mod project_management_service;
 pub mod whatsapp_service;
+pub mod integration_queue;
+pub mod data_source_cdc;
 pub mod profile_auto_creator;
 
 // AI & Mock
@@ -120,18 +140,30 @@ pub mod mock_ai_service;
 
 // Email Service (SMTP + SendGrid)
 pub mod email_service;
+pub mod email_tracking;
 
 // CUBE Mail - Full Email Client
 pub mod cube_mail_service;
 pub mod imap_client;
 pub mod oauth2_service;
 pub mod mail_database;
+pub mod mail_attachments;
 
 // Contact Management
 pub mod contact_service;
 
+// Research & Competitive Intelligence
+pub mod competitor_monitor;
+
+// Gamification
+pub mod gamification_leaderboard;
+
+// Call Center
+pub mod call_transcription;
+
 // Automation & Scheduling
 pub mod scheduler;
+pub mod cron_schedule;
 
 // Monitoring & Observability
 pub mod metrics;
@@ -142,8 +174,11 @@ pub mod alerts;
 pub mod enterprise_service;
 pub mod analytics_service;
 pub mod notifications_service;
+pub mod sms_delivery; // 📱 SMS via Twilio/Vonage with failover, segment counting and STOP handling
+pub mod template_engine;
 
 // Integration & External APIs
+pub mod admin_metrics_stream;
 pub mod api_server;
 pub mod google_sheets;
 pub mod slack;
@@ -160,18 +195,29 @@ pub mod browser_proxy;
 
 // CUBE Web Engine - True Embedded Browser
 pub mod cube_web_engine;
+pub mod http_auth;
+pub mod doh_resolver;
+pub mod media_emulation;
+pub mod device_emulation;
+pub mod lqip;
+pub mod websocket_inspector;
+pub mod heap_snapshot;
+pub mod accessibility_tree;
 
 // Enterprise Authentication
 pub mod sso_service;
 
 // Multi-Tenant System
 pub mod multi_tenant_service;
+pub mod whitelabel_theme_service;
+pub mod license_seat_service;
 
 // Payment Processing
 pub mod payment_service;
 
 // Audit Logging (SOC2/GDPR/HIPAA)
 pub mod audit_logging_service;
+pub mod siem_forwarder; // 📡 SIEM event forwarding (syslog/CEF/LEEF/Splunk HEC)
 
 // Utilities
 pub mod time_utils;
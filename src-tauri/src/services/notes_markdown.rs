@@ -0,0 +1,420 @@
+// Notes Markdown Rendering
+//
+// Renders note markdown (GFM tables, task lists, code fences) to sanitized
+// HTML and extracts the structure the editor needs: wiki-links, task
+// checkboxes with source offsets, and a heading outline.
+use crate::models::notes::*;
+use lazy_static::lazy_static;
+use pulldown_cmark::utils::TextMergeWithOffset;
+use pulldown_cmark::{html, CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref WIKI_LINK: Regex = Regex::new(r"\[\[([^\[\]|\n]+)(?:\|([^\[\]\n]+))?\]\]").unwrap();
+    static ref UNSAFE_TAG_OPEN: Regex =
+        Regex::new(r"(?i)<\s*(script|style|iframe|object|embed|noscript|template)\b").unwrap();
+    static ref UNSAFE_TAG_CLOSE: Regex =
+        Regex::new(r"(?i)<\s*/\s*(script|style|iframe|object|noscript|template)\s*>").unwrap();
+}
+
+/// Markdown source of a note: the markdown field when set, else the content
+pub fn note_source(note: &Note) -> &str {
+    match note.markdown.as_deref() {
+        Some(markdown) if !markdown.is_empty() => markdown,
+        _ => &note.content,
+    }
+}
+
+/// Render a note, resolving wiki-links against `notes` by title or id
+pub fn render_note(note: &Note, notes: &[Note]) -> RenderedNote {
+    let mut index: HashMap<String, String> = HashMap::new();
+    for n in notes {
+        index.insert(n.id.to_lowercase(), n.id.clone());
+    }
+    // Titles take precedence over ids
+    for n in notes {
+        index.insert(n.title.trim().to_lowercase(), n.id.clone());
+    }
+
+    let source = note_source(note);
+    let mut rendered = render_markdown(source, |target| index.get(&target.trim().to_lowercase()).cloned());
+    rendered.note_id = note.id.clone();
+    rendered
+}
+
+/// Render markdown to sanitized HTML. `resolve` maps a wiki-link target to a note id.
+pub fn render_markdown<F>(source: &str, resolve: F) -> RenderedNote
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+
+    let events: Vec<_> = TextMergeWithOffset::new(Parser::new_ext(source, options).into_offset_iter()).collect();
+
+    let mut out: Vec<Event> = Vec::with_capacity(events.len());
+    let mut wiki_links = Vec::new();
+    let mut tasks = Vec::new();
+    let mut outline = Vec::new();
+    let mut anchors: HashMap<String, usize> = HashMap::new();
+    let mut in_code_block = false;
+    let mut in_unsafe_html = false;
+
+    for (i, (event, range)) in events.iter().enumerate() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                let text = plain_text(&events[i + 1..], |e| matches!(e, Event::End(TagEnd::Heading(_))));
+                let anchor = unique_anchor(&mut anchors, &slugify(&text));
+                outline.push(OutlineHeading {
+                    level: heading_level(*level),
+                    text,
+                    anchor: anchor.clone(),
+                });
+                out.push(Event::Start(Tag::Heading {
+                    level: *level,
+                    id: Some(anchor.into()),
+                    classes: Vec::new(),
+                    attrs: Vec::new(),
+                }));
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                in_code_block = true;
+                out.push(Event::Start(Tag::CodeBlock(kind.clone())));
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                in_code_block = false;
+                out.push(Event::End(TagEnd::CodeBlock));
+            }
+            Event::Start(Tag::Link { link_type, dest_url, title, id }) => {
+                out.push(Event::Start(Tag::Link {
+                    link_type: *link_type,
+                    dest_url: safe_url(dest_url, false),
+                    title: title.clone(),
+                    id: id.clone(),
+                }));
+            }
+            Event::Start(Tag::Image { link_type, dest_url, title, id }) => {
+                out.push(Event::Start(Tag::Image {
+                    link_type: *link_type,
+                    dest_url: safe_url(dest_url, true),
+                    title: title.clone(),
+                    id: id.clone(),
+                }));
+            }
+            Event::TaskListMarker(checked) => {
+                let marker = &source[range.clone()];
+                let offset = range.start + marker.find('[').map_or(0, |p| p + 1);
+                let index = tasks.len();
+                tasks.push(TaskCheckbox {
+                    index,
+                    checked: *checked,
+                    text: plain_text(&events[i + 1..], |e| {
+                        matches!(e, Event::End(TagEnd::Item) | Event::Start(Tag::List(_)))
+                    }),
+                    line: source[..offset].matches('\n').count() + 1,
+                    offset,
+                });
+                out.push(Event::InlineHtml(
+                    format!(
+                        "<input type=\"checkbox\" class=\"task-checkbox\" data-task-index=\"{}\"{} /> ",
+                        index,
+                        if *checked { " checked" } else { "" }
+                    )
+                    .into(),
+                ));
+            }
+            Event::Html(raw) | Event::InlineHtml(raw) => {
+                // Raw HTML is shown as text; active content is dropped entirely
+                if in_unsafe_html {
+                    in_unsafe_html = !UNSAFE_TAG_CLOSE.is_match(raw);
+                } else if let Some(caps) = UNSAFE_TAG_OPEN.captures(raw) {
+                    in_unsafe_html = !caps[1].eq_ignore_ascii_case("embed") && !UNSAFE_TAG_CLOSE.is_match(raw);
+                } else {
+                    out.push(Event::Text(raw.clone()));
+                }
+            }
+            Event::Text(_) | Event::Code(_) if in_unsafe_html => {}
+            Event::Text(text) if !in_code_block => {
+                let mut last = 0;
+                for caps in WIKI_LINK.captures_iter(text) {
+                    let whole = caps.get(0).unwrap();
+                    let target = caps[1].trim().to_string();
+                    let alias = caps.get(2).map(|m| m.as_str().trim().to_string());
+                    let note_id = resolve(&target);
+
+                    // Merged text can differ from the source when it contains escapes
+                    let span = &source[range.clone()];
+                    let offset = range.start
+                        + if span.len() == text.len() {
+                            whole.start()
+                        } else {
+                            span.find(whole.as_str()).unwrap_or(0)
+                        };
+
+                    if whole.start() > last {
+                        out.push(Event::Text(text[last..whole.start()].to_string().into()));
+                    }
+                    out.push(Event::InlineHtml(wiki_link_html(&target, alias.as_deref(), note_id.as_deref()).into()));
+                    last = whole.end();
+
+                    wiki_links.push(WikiLink {
+                        would_create: note_id.is_none(),
+                        target,
+                        alias,
+                        note_id,
+                        offset,
+                    });
+                }
+                if last < text.len() {
+                    out.push(Event::Text(text[last..].to_string().into()));
+                }
+            }
+            other => out.push(other.clone()),
+        }
+    }
+
+    let mut html_out = String::with_capacity(source.len() * 3 / 2);
+    html::push_html(&mut html_out, out.into_iter());
+
+    RenderedNote {
+        note_id: String::new(),
+        html: html_out,
+        wiki_links,
+        tasks,
+        outline,
+    }
+}
+
+/// Flip the task checkbox whose marker character is at `offset`.
+/// Returns `None` if `offset` does not point into a `[ ]` / `[x]` marker.
+pub fn toggle_task(source: &str, offset: usize) -> Option<String> {
+    let bytes = source.as_bytes();
+    if offset == 0 || offset + 1 >= bytes.len() || bytes[offset - 1] != b'[' || bytes[offset + 1] != b']' {
+        return None;
+    }
+    let replacement = match bytes[offset] {
+        b' ' => "x",
+        b'x' | b'X' => " ",
+        _ => return None,
+    };
+    let mut toggled = String::with_capacity(source.len());
+    toggled.push_str(&source[..offset]);
+    toggled.push_str(replacement);
+    toggled.push_str(&source[offset + 1..]);
+    Some(toggled)
+}
+
+/// Concatenated text of the events up to the first one matching `stop`
+fn plain_text<R>(events: &[(Event, R)], stop: impl Fn(&Event) -> bool) -> String {
+    let mut text = String::new();
+    for (event, _) in events {
+        if stop(event) {
+            break;
+        }
+        if let Event::Text(t) | Event::Code(t) = event {
+            text.push_str(t);
+        }
+    }
+    let text = WIKI_LINK.replace_all(&text, |caps: &regex::Captures| {
+        caps.get(2).map_or(&caps[1], |alias| alias.as_str()).trim().to_string()
+    });
+    text.trim().to_string()
+}
+
+fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "section".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
+fn unique_anchor(seen: &mut HashMap<String, usize>, slug: &str) -> String {
+    let count = seen.entry(slug.to_string()).or_insert(0);
+    *count += 1;
+    if *count == 1 {
+        slug.to_string()
+    } else {
+        format!("{}-{}", slug, *count - 1)
+    }
+}
+
+/// Neutralize script-capable URL schemes
+fn safe_url<'a>(url: &CowStr<'a>, is_image: bool) -> CowStr<'a> {
+    let normalized: String = url.chars().filter(|c| !c.is_whitespace() && !c.is_control()).collect::<String>().to_lowercase();
+    let scheme = normalized.split_once(':').map(|(scheme, _)| scheme).filter(|s| !s.contains('/'));
+    let allowed = match scheme {
+        None => true,
+        Some("http" | "https" | "mailto" | "note") => true,
+        Some("data") => is_image && normalized.starts_with("data:image/") && !normalized.starts_with("data:image/svg"),
+        Some(_) => false,
+    };
+    if allowed {
+        url.clone()
+    } else {
+        CowStr::Borrowed("#")
+    }
+}
+
+fn wiki_link_html(target: &str, alias: Option<&str>, note_id: Option<&str>) -> String {
+    let label = escape_html(alias.unwrap_or(target));
+    match note_id {
+        Some(id) => format!(
+            "<a class=\"wiki-link\" href=\"#note/{id}\" data-note-id=\"{id}\">{}</a>",
+            label,
+            id = escape_html(id)
+        ),
+        None => format!(
+            "<a class=\"wiki-link wiki-link-missing\" href=\"#\" data-create-note=\"{}\" title=\"Create note\">{}</a>",
+            escape_html(target),
+            label
+        ),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(id: &str, title: &str, content: &str) -> Note {
+        Note {
+            id: id.to_string(),
+            note_type: "note".to_string(),
+            title: title.to_string(),
+            content: content.to_string(),
+            markdown: None,
+            tags: vec![],
+            category: None,
+            priority: "medium".to_string(),
+            status: "active".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            color: None,
+            pinned: false,
+            favorite: false,
+            reminder: None,
+            checklist: None,
+        }
+    }
+
+    #[test]
+    fn test_wiki_link_resolution() {
+        let source = "See [[Project Plan]] and [[roadmap|the roadmap]], maybe [[Ideas]].\n\n`[[not a link]]`";
+        let notes = vec![
+            note("n1", "Home", source),
+            note("n2", "Project Plan", ""),
+            note("roadmap", "Q3 Roadmap", ""),
+        ];
+
+        let rendered = render_note(&notes[0], &notes);
+        assert_eq!(rendered.note_id, "n1");
+        assert_eq!(rendered.wiki_links.len(), 3);
+
+        let plan = &rendered.wiki_links[0];
+        assert_eq!(plan.note_id.as_deref(), Some("n2"));
+        assert!(!plan.would_create);
+        assert_eq!(&source[plan.offset..plan.offset + 2], "[[");
+
+        let roadmap = &rendered.wiki_links[1];
+        assert_eq!(roadmap.note_id.as_deref(), Some("roadmap"));
+        assert_eq!(roadmap.alias.as_deref(), Some("the roadmap"));
+
+        let ideas = &rendered.wiki_links[2];
+        assert!(ideas.would_create);
+        assert!(ideas.note_id.is_none());
+
+        assert!(rendered.html.contains("data-note-id=\"n2\">Project Plan</a>"));
+        assert!(rendered.html.contains(">the roadmap</a>"));
+        assert!(rendered.html.contains("data-create-note=\"Ideas\""));
+        assert!(rendered.html.contains("<code>[[not a link]]</code>"));
+    }
+
+    #[test]
+    fn test_task_checkbox_extraction_and_toggle() {
+        let source = "# Today\n\n- [ ] Buy milk\n- [x] Call **Ana**\n  - [ ] Nested step\n- plain item\n";
+        let rendered = render_markdown(source, |_| None);
+
+        assert_eq!(rendered.tasks.len(), 3);
+        assert_eq!(rendered.tasks[0].text, "Buy milk");
+        assert!(!rendered.tasks[0].checked);
+        assert_eq!(rendered.tasks[0].line, 3);
+        assert_eq!(rendered.tasks[1].text, "Call Ana");
+        assert!(rendered.tasks[1].checked);
+        assert_eq!(rendered.tasks[2].text, "Nested step");
+        assert_eq!(rendered.tasks[2].line, 5);
+        for task in &rendered.tasks {
+            let expected = if task.checked { "x" } else { " " };
+            assert_eq!(&source[task.offset..task.offset + 1], expected);
+        }
+        assert!(rendered.html.contains("data-task-index=\"1\" checked"));
+
+        let toggled = toggle_task(source, rendered.tasks[0].offset).unwrap();
+        assert!(toggled.contains("- [x] Buy milk"));
+        let toggled = toggle_task(&toggled, rendered.tasks[1].offset).unwrap();
+        assert!(toggled.contains("- [ ] Call **Ana**"));
+        assert!(toggle_task(source, 0).is_none());
+    }
+
+    #[test]
+    fn test_outline_tables_and_code_fences() {
+        let source = "# Intro\n\n## Setup & Install\n\n## Setup & Install\n\n| a | b |\n|---|---|\n| 1 | 2 |\n\n```rust\nlet x = \"<b>\";\n```\n";
+        let rendered = render_markdown(source, |_| None);
+
+        let anchors: Vec<_> = rendered.outline.iter().map(|h| h.anchor.as_str()).collect();
+        assert_eq!(anchors, vec!["intro", "setup-install", "setup-install-1"]);
+        assert_eq!(rendered.outline[1].level, 2);
+        assert!(rendered.html.contains("<h2 id=\"setup-install\">"));
+        assert!(rendered.html.contains("<table>"));
+        assert!(rendered.html.contains("<td>1</td>"));
+        assert!(rendered.html.contains("<code class=\"language-rust\">"));
+        assert!(rendered.html.contains("&lt;b&gt;"));
+    }
+
+    #[test]
+    fn test_html_sanitization() {
+        let source = "Hello <script>alert('x')</script> world\n\n<script>\ndocument.cookie\n</script>\n\n<img src=x onerror=alert(1)>\n\n[click](javascript:alert(1)) ![pic](data:image/png;base64,AAAA)";
+        let html = render_markdown(source, |_| None).html;
+
+        assert!(!html.to_lowercase().contains("<script"));
+        assert!(!html.contains("alert('x')"));
+        assert!(!html.contains("document.cookie"));
+        assert!(!html.contains("<img src=x"));
+        assert!(html.contains("&lt;img src=x onerror=alert(1)&gt;"));
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("<a href=\"#\">click</a>"));
+        assert!(html.contains("src=\"data:image/png;base64,AAAA\""));
+        assert!(html.contains("Hello"));
+        assert!(html.contains("world"));
+    }
+}
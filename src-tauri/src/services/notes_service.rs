@@ -1,5 +1,6 @@
 // Notes & Tasks Service
 use crate::models::notes::*;
+use crate::services::notes_markdown;
 use rusqlite::{params, Connection, Result};
 use std::sync::{Arc, Mutex};
 use chrono::Utc;
@@ -467,6 +468,41 @@ impl NotesService {
         Ok(())
     }
 
+    // Render note markdown with wiki-links, tasks and outline
+    pub fn render_markdown(&self, note_id: &str) -> Result<Option<RenderedNote>> {
+        let notes = self.get_all_notes()?;
+        Ok(notes
+            .iter()
+            .find(|n| n.id == note_id)
+            .map(|note| notes_markdown::render_note(note, &notes)))
+    }
+
+    // Toggle a task checkbox in the note's markdown source
+    pub fn toggle_task_checkbox(&self, note_id: &str, task_index: usize) -> Result<Option<RenderedNote>> {
+        let notes = self.get_all_notes()?;
+        let Some(note) = notes.iter().find(|n| n.id == note_id) else {
+            return Ok(None);
+        };
+        let rendered = notes_markdown::render_note(note, &notes);
+        let Some(task) = rendered.tasks.get(task_index) else {
+            return Ok(None);
+        };
+        let Some(source) = notes_markdown::toggle_task(notes_markdown::note_source(note), task.offset) else {
+            return Ok(None);
+        };
+
+        let mut updated = note.clone();
+        if note.markdown.as_deref().is_some_and(|m| !m.is_empty()) {
+            updated.markdown = Some(source);
+        } else {
+            updated.content = source;
+        }
+        updated.updated_at = Utc::now().timestamp();
+        self.update_note(&updated)?;
+
+        Ok(Some(notes_markdown::render_note(&updated, &notes)))
+    }
+
     // Get statistics
     pub fn get_stats(&self) -> Result<NotesStats> {
         let db = self.db.lock().unwrap();
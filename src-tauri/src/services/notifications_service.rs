@@ -7,7 +7,7 @@
 use anyhow::{Context, Result};
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::Mutex;
 use chrono::{DateTime, Utc};
@@ -457,28 +457,31 @@ impl NotificationsService {
         
         let mut stmt = conn.prepare(query)?;
         
-        let notifications = stmt.query_map(params![user_id, limit, offset], |row| {
-            let action_data_json: Option<String> = row.get(6)?;
-            
-            Ok(UserNotification {
-                id: row.get(0)?,
-                notification_id: row.get(1)?,
-                user_id: row.get(2)?,
-                title: row.get(3)?,
-                body: row.get(4)?,
-                action_type: row.get(5)?,
-                action_data: action_data_json.and_then(|j| serde_json::from_str(&j).ok()),
-                read: row.get::<_, i32>(7)? != 0,
-                read_at: row.get(8)?,
-                dismissed: row.get::<_, i32>(9)? != 0,
-                dismissed_at: row.get(10)?,
-                created_at: row.get(11)?,
-            })
-        })?.collect::<Result<Vec<_>, _>>()?;
+        let notifications = stmt.query_map(params![user_id, limit, offset], Self::map_user_notification_row)?
+            .collect::<Result<Vec<_>, _>>()?;
         
         Ok(notifications)
     }
 
+    fn map_user_notification_row(row: &rusqlite::Row) -> rusqlite::Result<UserNotification> {
+        let action_data_json: Option<String> = row.get(6)?;
+        
+        Ok(UserNotification {
+            id: row.get(0)?,
+            notification_id: row.get(1)?,
+            user_id: row.get(2)?,
+            title: row.get(3)?,
+            body: row.get(4)?,
+            action_type: row.get(5)?,
+            action_data: action_data_json.and_then(|j| serde_json::from_str(&j).ok()),
+            read: row.get::<_, i32>(7)? != 0,
+            read_at: row.get(8)?,
+            dismissed: row.get::<_, i32>(9)? != 0,
+            dismissed_at: row.get(10)?,
+            created_at: row.get(11)?,
+        })
+    }
+
     pub fn get_unread_count(&self, user_id: &str) -> Result<i32> {
         let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
         
@@ -574,6 +577,157 @@ impl NotificationsService {
         Ok(count as i32)
     }
 
+    // ============================================================================
+    // Notification Center
+    // ============================================================================
+
+    /// In-app notifications as the notification center shows them. Notifications
+    /// of the same category and type collapse into one group ("3 new messages"),
+    /// and groups are ordered by their newest notification, so a group with fresh
+    /// activity sits above an older single notification. Categories the user has
+    /// switched off in their preferences are left out, counts included.
+    pub fn get_grouped_notifications(&self, user_id: &str, limit: i32) -> Result<NotificationCenter> {
+        let disabled = self.disabled_categories(user_id)?;
+        let unread_by_category = self.get_unread_counts_by_category(user_id)?;
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        
+        let mut stmt = conn.prepare(
+            r#"SELECT i.id, i.notification_id, i.user_id, i.title, i.body, i.action_type,
+                      i.action_data_json, i.read, i.read_at, i.dismissed, i.dismissed_at, i.created_at,
+                      n.category, n.notification_type
+               FROM in_app_notifications i
+               JOIN notifications n ON n.id = i.notification_id
+               WHERE i.user_id = ?1 AND i.dismissed = 0
+               ORDER BY i.created_at DESC, i.rowid DESC
+               LIMIT ?2"#
+        )?;
+        
+        let rows = stmt.query_map(params![user_id, limit], |row| {
+            Ok((Self::map_user_notification_row(row)?, row.get::<_, String>(12)?, row.get::<_, String>(13)?))
+        })?.collect::<Result<Vec<_>, _>>()?;
+        
+        let groups = group_notifications(
+            rows.into_iter().filter(|(_, category, _)| !disabled.contains(category)),
+        );
+        let unread_by_category: HashMap<String, i32> = unread_by_category
+            .into_iter()
+            .filter(|(category, _)| !disabled.contains(category))
+            .collect();
+        
+        Ok(NotificationCenter {
+            total_unread: unread_by_category.values().sum(),
+            unread_by_category,
+            groups,
+        })
+    }
+
+    /// Unread, undismissed in-app notifications per category
+    pub fn get_unread_counts_by_category(&self, user_id: &str) -> Result<HashMap<String, i32>> {
+        let conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        
+        let mut stmt = conn.prepare(
+            r#"SELECT n.category, COUNT(*)
+               FROM in_app_notifications i
+               JOIN notifications n ON n.id = i.notification_id
+               WHERE i.user_id = ?1 AND i.read = 0 AND i.dismissed = 0
+               GROUP BY n.category"#
+        )?;
+        
+        let counts = stmt.query_map(params![user_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        
+        Ok(counts)
+    }
+
+    /// Marks every unread notification in a category as read. The returned
+    /// changes are what the user's other devices need to do the same.
+    pub fn mark_category_read(&self, user_id: &str, category: &str) -> Result<Vec<ReadStateChange>> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let now = Utc::now().timestamp_millis();
+        let tx = conn.transaction()?;
+        
+        let notification_ids = {
+            let mut stmt = tx.prepare(
+                r#"SELECT i.notification_id
+                   FROM in_app_notifications i
+                   JOIN notifications n ON n.id = i.notification_id
+                   WHERE i.user_id = ?1 AND n.category = ?2 AND i.read = 0"#
+            )?;
+            let ids = stmt.query_map(params![user_id, category], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            ids
+        };
+        
+        tx.execute(
+            r#"UPDATE in_app_notifications SET read = 1, read_at = ?3
+               WHERE user_id = ?1 AND read = 0
+               AND notification_id IN (SELECT id FROM notifications WHERE category = ?2)"#,
+            params![user_id, category, now]
+        )?;
+        tx.execute(
+            "UPDATE notifications SET read = 1, read_at = ?3, updated_at = ?3 WHERE user_id = ?1 AND category = ?2 AND read = 0",
+            params![user_id, category, now]
+        )?;
+        tx.commit()?;
+        
+        Ok(notification_ids
+            .into_iter()
+            .map(|notification_id| ReadStateChange {
+                notification_id,
+                read_at: Some(now),
+                dismissed_at: None,
+            })
+            .collect())
+    }
+
+    /// Applies read/dismiss changes synced from another device. Both flags only
+    /// ever turn on, so devices converge whatever order changes arrive in; the
+    /// earliest timestamp is kept. Returns how many notifications changed.
+    pub fn apply_read_state(&self, changes: &[ReadStateChange]) -> Result<usize> {
+        let mut conn = self.conn.lock().map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let now = Utc::now().timestamp_millis();
+        let tx = conn.transaction()?;
+        let mut changed = 0;
+        
+        for change in changes {
+            if let Some(read_at) = change.read_at {
+                changed += tx.execute(
+                    r#"UPDATE in_app_notifications SET read = 1, read_at = ?2
+                       WHERE notification_id = ?1 AND (read = 0 OR read_at IS NULL OR read_at > ?2)"#,
+                    params![change.notification_id, read_at]
+                )?;
+                tx.execute(
+                    r#"UPDATE notifications SET read = 1, read_at = ?2, updated_at = ?3
+                       WHERE id = ?1 AND (read = 0 OR read_at IS NULL OR read_at > ?2)"#,
+                    params![change.notification_id, read_at, now]
+                )?;
+            }
+            if let Some(dismissed_at) = change.dismissed_at {
+                changed += tx.execute(
+                    r#"UPDATE in_app_notifications SET dismissed = 1, dismissed_at = ?2
+                       WHERE notification_id = ?1 AND (dismissed = 0 OR dismissed_at IS NULL OR dismissed_at > ?2)"#,
+                    params![change.notification_id, dismissed_at]
+                )?;
+            }
+        }
+        
+        tx.commit()?;
+        Ok(changed)
+    }
+
+    fn disabled_categories(&self, user_id: &str) -> Result<HashSet<String>> {
+        Ok(self
+            .get_preferences(user_id)?
+            .map(|prefs| {
+                prefs.categories
+                    .into_iter()
+                    .filter(|(_, enabled)| !enabled)
+                    .map(|(category, _)| category)
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
     // ============================================================================
     // Templates
     // ============================================================================
@@ -1036,6 +1190,38 @@ pub struct UserNotification {
     pub created_at: i64,
 }
 
+/// Similar notifications collapsed into one notification center entry. A
+/// notification with nothing to group with is a group of one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationGroup {
+    pub group_key: String,
+    pub category: String,
+    pub notification_type: String,
+    pub summary: String,
+    pub count: usize,
+    pub unread_count: usize,
+    pub latest_at: i64,
+    /// Newest first
+    pub notifications: Vec<UserNotification>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationCenter {
+    pub groups: Vec<NotificationGroup>,
+    pub unread_by_category: HashMap<String, i32>,
+    pub total_unread: i32,
+}
+
+/// Read state of one notification as synced between devices. Keyed by the
+/// notification id, which is the same on every device, rather than the
+/// per-device in-app row id.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadStateChange {
+    pub notification_id: String,
+    pub read_at: Option<i64>,
+    pub dismissed_at: Option<i64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotificationTemplate {
     pub id: String,
@@ -1162,6 +1348,57 @@ pub struct DeliveryStats {
     pub push_delivery_rate: f64,
 }
 
+/// Collapses notifications (newest first) by category and type. Each group is
+/// created by its newest member, so groups come out ordered by latest activity.
+fn group_notifications(
+    rows: impl IntoIterator<Item = (UserNotification, String, String)>,
+) -> Vec<NotificationGroup> {
+    let mut groups: Vec<NotificationGroup> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    
+    for (notification, category, notification_type) in rows {
+        let slot = *index
+            .entry((category.clone(), notification_type.clone()))
+            .or_insert_with(|| {
+                groups.push(NotificationGroup {
+                    group_key: format!("{}:{}", category, notification_type),
+                    category,
+                    notification_type,
+                    summary: String::new(),
+                    count: 0,
+                    unread_count: 0,
+                    latest_at: notification.created_at,
+                    notifications: Vec::new(),
+                });
+                groups.len() - 1
+            });
+        let group = &mut groups[slot];
+        group.count += 1;
+        if !notification.read {
+            group.unread_count += 1;
+        }
+        group.notifications.push(notification);
+    }
+    
+    for group in &mut groups {
+        group.summary = group_summary(group);
+    }
+    groups
+}
+
+fn group_summary(group: &NotificationGroup) -> String {
+    if group.count == 1 {
+        return group.notifications[0].title.clone();
+    }
+    let label = group.notification_type.replace(['_', '-'], " ");
+    let label = if label.ends_with('s') { label } else { format!("{}s", label) };
+    if group.unread_count > 0 {
+        format!("{} new {}", group.unread_count, label)
+    } else {
+        format!("{} {}", group.count, label)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1272,4 +1509,107 @@ mod tests {
         let unread_count = service.get_unread_count("user-789").unwrap();
         assert_eq!(unread_count, 0);
     }
+
+    fn in_app(id: &str, user_id: &str, category: &str, notification_type: &str, title: &str) -> Notification {
+        Notification {
+            id: Some(id.to_string()),
+            user_id: user_id.to_string(),
+            organization_id: None,
+            notification_type: notification_type.to_string(),
+            channel: NotificationChannel::InApp,
+            priority: NotificationPriority::Normal,
+            category: category.to_string(),
+            title: title.to_string(),
+            body: String::new(),
+            data: None,
+            action_url: None,
+            image_url: None,
+            icon: None,
+            scheduled_at: None,
+            expires_at: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_grouped_notifications_and_category_counts() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let service = NotificationsService::new(temp_file.path()).unwrap();
+        
+        service.send_notification(&in_app("n-1", "user-1", "security", "login_alert", "New sign-in from Lisbon")).unwrap();
+        for (i, sender) in ["Ana", "Ben", "Caro"].iter().enumerate() {
+            let id = format!("m-{}", i);
+            service.send_notification(&in_app(&id, "user-1", "collaboration", "message", &format!("Message from {}", sender))).unwrap();
+        }
+        service.send_notification(&in_app("b-1", "user-1", "billing", "invoice", "Invoice ready")).unwrap();
+        
+        let center = service.get_grouped_notifications("user-1", 50).unwrap();
+        let summaries: Vec<_> = center.groups.iter().map(|g| (g.summary.as_str(), g.count)).collect();
+        // The newest notification's group comes first; older singles stay individual
+        assert_eq!(summaries, vec![
+            ("Invoice ready", 1),
+            ("3 new messages", 3),
+            ("New sign-in from Lisbon", 1),
+        ]);
+        assert_eq!(center.groups[1].notifications[0].title, "Message from Caro");
+        assert_eq!(center.unread_by_category.get("collaboration"), Some(&3));
+        assert_eq!(center.total_unread, 5);
+        
+        let changes = service.mark_category_read("user-1", "collaboration").unwrap();
+        assert_eq!(changes.len(), 3);
+        let center = service.get_grouped_notifications("user-1", 50).unwrap();
+        assert_eq!(center.groups[1].summary, "3 messages");
+        assert_eq!(center.unread_by_category.get("collaboration"), None);
+        assert_eq!(center.total_unread, 2);
+        
+        // Switched-off categories are hidden from the center and its counts
+        service.update_preferences(&NotificationPreferences {
+            user_id: "user-1".to_string(),
+            enabled: true,
+            channels: HashMap::new(),
+            categories: HashMap::from([("billing".to_string(), false)]),
+            quiet_hours: None,
+            frequency: None,
+            organization_id: None,
+        }).unwrap();
+        let center = service.get_grouped_notifications("user-1", 50).unwrap();
+        assert!(center.groups.iter().all(|g| g.category != "billing"));
+        assert_eq!(center.total_unread, 1);
+    }
+
+    #[test]
+    fn test_read_state_syncs_between_devices() {
+        let (laptop_db, phone_db) = (NamedTempFile::new().unwrap(), NamedTempFile::new().unwrap());
+        let laptop = NotificationsService::new(laptop_db.path()).unwrap();
+        let phone = NotificationsService::new(phone_db.path()).unwrap();
+        for device in [&laptop, &phone] {
+            device.send_notification(&in_app("m-1", "user-1", "collaboration", "message", "Hi")).unwrap();
+            device.send_notification(&in_app("m-2", "user-1", "collaboration", "message", "Lunch?")).unwrap();
+            device.send_notification(&in_app("s-1", "user-1", "security", "login_alert", "New sign-in")).unwrap();
+        }
+        
+        let changes = laptop.mark_category_read("user-1", "collaboration").unwrap();
+        let dismissed = ReadStateChange {
+            notification_id: "s-1".to_string(),
+            read_at: None,
+            dismissed_at: Some(Utc::now().timestamp_millis()),
+        };
+        
+        // The phone gets the changes in the opposite order they were made
+        assert_eq!(phone.apply_read_state(std::slice::from_ref(&dismissed)).unwrap(), 1);
+        assert_eq!(phone.apply_read_state(&changes).unwrap(), 2);
+        assert_eq!(phone.get_unread_counts_by_category("user-1").unwrap(), HashMap::new());
+        assert!(phone.get_grouped_notifications("user-1", 50).unwrap().groups.iter().all(|g| g.category == "collaboration"));
+        
+        // Re-delivered changes are no-ops, and the laptop converges too
+        assert_eq!(phone.apply_read_state(&changes).unwrap(), 0);
+        laptop.apply_read_state(&[dismissed]).unwrap();
+        let (a, b) = (
+            laptop.get_grouped_notifications("user-1", 50).unwrap(),
+            phone.get_grouped_notifications("user-1", 50).unwrap(),
+        );
+        assert_eq!(a.total_unread, b.total_unread);
+        let read_at = |center: &NotificationCenter| center.groups[0].notifications.iter().map(|n| n.read_at).collect::<Vec<_>>();
+        assert_eq!(read_at(&a), read_at(&b));
+    }
 }
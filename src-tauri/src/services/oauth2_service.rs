@@ -463,6 +463,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 /// Thread-safe OAuth2 state for Tauri
+#[derive(Clone)]
 pub struct OAuth2ServiceState {
     inner: Arc<RwLock<OAuth2Service>>,
     configs: Arc<RwLock<HashMap<String, OAuth2Config>>>,
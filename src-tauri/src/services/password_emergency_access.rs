@@ -0,0 +1,425 @@
+// Password Emergency Access - Time-delayed vault access for a trusted contact
+// The owner seals a vault export to the contact's X25519 public key, so the
+// server only ever stores ciphertext. The contact can ask for access at any
+// time, which starts a waiting period the owner can end with a denial; once it
+// runs out undenied the ciphertext is released and only the contact's private
+// key opens it.
+use crate::models::passwords::EmergencyAccessRecord;
+use crate::services::password_service::PasswordService;
+use crate::services::password_sharing::{self, SharedPasswordPayload};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use rand::rngs::OsRng;
+use ring::hkdf;
+use serde::{Deserialize, Serialize};
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+const MIN_WAIT_DAYS: u32 = 1;
+const MAX_WAIT_DAYS: u32 = 90;
+const DAY_SECS: i64 = 24 * 3600;
+const SEAL_INFO: &[u8] = b"cube-emergency-access/v1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmergencyAccessLevel {
+    /// Read the sealed export
+    View,
+    /// Read the export and import it into the contact's own vault
+    Takeover,
+}
+
+impl EmergencyAccessLevel {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::View => "view",
+            Self::Takeover => "takeover",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "takeover" => Self::Takeover,
+            _ => Self::View,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum EmergencyAccessState {
+    /// No request pending
+    Idle,
+    /// The contact asked; the owner can still deny until `grants_at`
+    Waiting { requested_at: i64, grants_at: i64 },
+    Granted { granted_at: i64 },
+    /// The owner turned the last request down; the contact may ask again
+    Denied { denied_at: i64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyAccessStatus {
+    pub id: String,
+    pub contact_email: String,
+    pub access_level: EmergencyAccessLevel,
+    pub wait_days: u32,
+    #[serde(flatten)]
+    pub state: EmergencyAccessState,
+    /// When the owner last sealed the export for this contact
+    pub export_updated_at: i64,
+}
+
+/// What the contact receives once access is granted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmergencyVaultExport {
+    pub entries: Vec<SharedPasswordPayload>,
+    pub exported_at: i64,
+}
+
+/// A fresh X25519 identity: base64url public key and the raw secret
+pub fn generate_identity() -> (String, [u8; 32]) {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let public = PublicKey::from(&secret);
+    (URL_SAFE_NO_PAD.encode(public.as_bytes()), secret.to_bytes())
+}
+
+/// Designate `contact_email` as an emergency contact, or refresh the export of
+/// an existing one. The export is sealed to `contact_public_key` before it is stored.
+pub fn grant_contact(
+    service: &PasswordService,
+    contact_email: &str,
+    contact_public_key: &str,
+    access_level: EmergencyAccessLevel,
+    wait_days: u32,
+    export: &EmergencyVaultExport,
+    now: i64,
+) -> Result<EmergencyAccessStatus, String> {
+    let contact_email = contact_email.trim().to_lowercase();
+    if contact_email.is_empty() || !contact_email.contains('@') {
+        return Err("A valid contact email is required".to_string());
+    }
+    if !(MIN_WAIT_DAYS..=MAX_WAIT_DAYS).contains(&wait_days) {
+        return Err(format!("Waiting period must be between {} and {} days", MIN_WAIT_DAYS, MAX_WAIT_DAYS));
+    }
+    decode_public_key(contact_public_key)?;
+
+    let existing = service
+        .list_emergency_access()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|r| r.contact_email == contact_email);
+    let mut record = existing.unwrap_or_else(|| EmergencyAccessRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        contact_email: contact_email.clone(),
+        contact_public_key: String::new(),
+        access_level: String::new(),
+        wait_days,
+        status: "idle".to_string(),
+        requested_at: None,
+        decided_at: None,
+        ephemeral_public_key: String::new(),
+        ciphertext: String::new(),
+        created_at: now,
+        updated_at: now,
+    });
+
+    let plaintext = serde_json::to_vec(export).map_err(|e| e.to_string())?;
+    let (ephemeral_public_key, ciphertext) = seal_to(contact_public_key, &record.id, plaintext)?;
+    record.contact_public_key = contact_public_key.to_string();
+    record.access_level = access_level.as_str().to_string();
+    record.wait_days = wait_days;
+    record.ephemeral_public_key = ephemeral_public_key;
+    record.ciphertext = ciphertext;
+    record.updated_at = now;
+    service.upsert_emergency_access(&record).map_err(|e| e.to_string())?;
+
+    Ok(status_of(&record, now))
+}
+
+/// The contact asks for access, starting the waiting period
+pub fn request_access(service: &PasswordService, id: &str, now: i64) -> Result<EmergencyAccessStatus, String> {
+    let mut record = load(service, id)?;
+    match effective_state(&record, now) {
+        EmergencyAccessState::Idle | EmergencyAccessState::Denied { .. } => {
+            record.status = "requested".to_string();
+            record.requested_at = Some(now);
+            record.decided_at = None;
+            service.upsert_emergency_access(&record).map_err(|e| e.to_string())?;
+        }
+        // Asking again neither restarts nor shortens the wait
+        EmergencyAccessState::Waiting { .. } | EmergencyAccessState::Granted { .. } => {}
+    }
+    Ok(status_of(&record, now))
+}
+
+/// The owner turns down a pending request. Only possible during the waiting period.
+pub fn deny_access(service: &PasswordService, id: &str, now: i64) -> Result<EmergencyAccessStatus, String> {
+    let mut record = load(service, id)?;
+    match effective_state(&record, now) {
+        EmergencyAccessState::Waiting { .. } => {
+            record.status = "denied".to_string();
+            record.decided_at = Some(now);
+            service.upsert_emergency_access(&record).map_err(|e| e.to_string())?;
+            Ok(status_of(&record, now))
+        }
+        EmergencyAccessState::Granted { .. } => {
+            Err("Access was already granted; revoke the contact instead".to_string())
+        }
+        EmergencyAccessState::Idle | EmergencyAccessState::Denied { .. } => {
+            Err("No pending emergency access request".to_string())
+        }
+    }
+}
+
+pub fn access_status(service: &PasswordService, id: &str, now: i64) -> Result<EmergencyAccessStatus, String> {
+    let mut record = load(service, id)?;
+    if let EmergencyAccessState::Granted { granted_at } = effective_state(&record, now) {
+        if record.status != "granted" {
+            record.status = "granted".to_string();
+            record.decided_at = Some(granted_at);
+            service.upsert_emergency_access(&record).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(status_of(&record, now))
+}
+
+pub fn list_access(service: &PasswordService, now: i64) -> Result<Vec<EmergencyAccessStatus>, String> {
+    Ok(service
+        .list_emergency_access()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|record| status_of(record, now))
+        .collect())
+}
+
+/// Release the sealed export to the contact once the waiting period has passed
+pub fn open_export(
+    service: &PasswordService,
+    id: &str,
+    contact_secret: &[u8; 32],
+    now: i64,
+) -> Result<(EmergencyAccessLevel, EmergencyVaultExport), String> {
+    let status = access_status(service, id, now)?;
+    match status.state {
+        EmergencyAccessState::Granted { .. } => {}
+        EmergencyAccessState::Waiting { grants_at, .. } => {
+            return Err(format!("Emergency access is still in its waiting period until {}", grants_at));
+        }
+        EmergencyAccessState::Denied { .. } => return Err("Emergency access was denied by the owner".to_string()),
+        EmergencyAccessState::Idle => return Err("Emergency access has not been requested".to_string()),
+    }
+
+    let record = load(service, id)?;
+    let plaintext = open_from(contact_secret, &record)?;
+    let export = serde_json::from_slice(&plaintext).map_err(|e| format!("Invalid vault export: {}", e))?;
+    Ok((status.access_level, export))
+}
+
+pub fn revoke_contact(service: &PasswordService, id: &str) -> Result<(), String> {
+    load(service, id)?;
+    service.delete_emergency_access(id).map_err(|e| e.to_string())
+}
+
+fn load(service: &PasswordService, id: &str) -> Result<EmergencyAccessRecord, String> {
+    service
+        .get_emergency_access(id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Emergency contact not found".to_string())
+}
+
+fn effective_state(record: &EmergencyAccessRecord, now: i64) -> EmergencyAccessState {
+    let requested_at = record.requested_at.unwrap_or(record.created_at);
+    let grants_at = requested_at + i64::from(record.wait_days) * DAY_SECS;
+    match record.status.as_str() {
+        "requested" if now >= grants_at => EmergencyAccessState::Granted { granted_at: grants_at },
+        "requested" => EmergencyAccessState::Waiting { requested_at, grants_at },
+        "granted" => EmergencyAccessState::Granted {
+            granted_at: record.decided_at.unwrap_or(grants_at),
+        },
+        "denied" => EmergencyAccessState::Denied {
+            denied_at: record.decided_at.unwrap_or(record.updated_at),
+        },
+        _ => EmergencyAccessState::Idle,
+    }
+}
+
+fn status_of(record: &EmergencyAccessRecord, now: i64) -> EmergencyAccessStatus {
+    EmergencyAccessStatus {
+        id: record.id.clone(),
+        contact_email: record.contact_email.clone(),
+        access_level: EmergencyAccessLevel::parse(&record.access_level),
+        wait_days: record.wait_days,
+        state: effective_state(record, now),
+        export_updated_at: record.updated_at,
+    }
+}
+
+fn decode_public_key(encoded: &str) -> Result<PublicKey, String> {
+    let bytes: [u8; 32] = URL_SAFE_NO_PAD
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| "Invalid contact public key".to_string())?;
+    Ok(PublicKey::from(bytes))
+}
+
+/// AES key from an X25519 agreement, bound to both public keys
+fn wrapping_key(shared: &[u8; 32], ephemeral: &PublicKey, contact: &PublicKey) -> Result<[u8; 32], String> {
+    let mut salt = ephemeral.as_bytes().to_vec();
+    salt.extend_from_slice(contact.as_bytes());
+    let mut key = [0u8; 32];
+    hkdf::Salt::new(hkdf::HKDF_SHA256, &salt)
+        .extract(shared)
+        .expand(&[SEAL_INFO], hkdf::HKDF_SHA256)
+        .and_then(|okm| okm.fill(&mut key))
+        .map_err(|_| "Key derivation failed".to_string())?;
+    Ok(key)
+}
+
+/// Seal to the contact with a one-off ephemeral key; the grant id is the associated data
+fn seal_to(contact_public_key: &str, id: &str, plaintext: Vec<u8>) -> Result<(String, String), String> {
+    let contact = decode_public_key(contact_public_key)?;
+    let ephemeral_secret = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral = PublicKey::from(&ephemeral_secret);
+    let shared = ephemeral_secret.diffie_hellman(&contact);
+    if !shared.was_contributory() {
+        return Err("Invalid contact public key".to_string());
+    }
+    let key = wrapping_key(shared.as_bytes(), &ephemeral, &contact)?;
+    let ciphertext = password_sharing::seal(&key, id, plaintext)?;
+    Ok((URL_SAFE_NO_PAD.encode(ephemeral.as_bytes()), ciphertext))
+}
+
+fn open_from(contact_secret: &[u8; 32], record: &EmergencyAccessRecord) -> Result<Vec<u8>, String> {
+    let secret = StaticSecret::from(*contact_secret);
+    let contact = PublicKey::from(&secret);
+    if URL_SAFE_NO_PAD.encode(contact.as_bytes()) != record.contact_public_key {
+        return Err("This emergency access was granted to a different key".to_string());
+    }
+    let ephemeral = decode_public_key(&record.ephemeral_public_key)?;
+    let shared = secret.diffie_hellman(&ephemeral);
+    let key = wrapping_key(shared.as_bytes(), &ephemeral, &contact)?;
+    password_sharing::open(&key, &record.id, &record.ciphertext)
+        .map_err(|_| "Failed to decrypt the emergency vault export".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_800_000_000;
+
+    fn export() -> EmergencyVaultExport {
+        EmergencyVaultExport {
+            entries: vec![SharedPasswordPayload {
+                name: "Bank".to_string(),
+                username: "alice@example.com".to_string(),
+                password: "correct-horse-battery-staple".to_string(),
+                url: Some("https://bank.example".to_string()),
+                notes: Some("PIN 4242".to_string()),
+            }],
+            exported_at: NOW,
+        }
+    }
+
+    fn setup(wait_days: u32) -> (PasswordService, String, [u8; 32]) {
+        let service = PasswordService::new(":memory:").unwrap();
+        let (public_key, secret) = generate_identity();
+        let status = grant_contact(
+            &service,
+            "Bob@Example.com",
+            &public_key,
+            EmergencyAccessLevel::View,
+            wait_days,
+            &export(),
+            NOW,
+        )
+        .unwrap();
+        assert_eq!(status.contact_email, "bob@example.com");
+        assert_eq!(status.state, EmergencyAccessState::Idle);
+        (service, status.id, secret)
+    }
+
+    #[test]
+    fn test_access_granted_after_waiting_period() {
+        let (service, id, secret) = setup(2);
+        assert!(open_export(&service, &id, &secret, NOW).is_err());
+
+        let requested = request_access(&service, &id, NOW + 10).unwrap();
+        let grants_at = NOW + 10 + 2 * DAY_SECS;
+        assert_eq!(
+            requested.state,
+            EmergencyAccessState::Waiting { requested_at: NOW + 10, grants_at }
+        );
+        // A repeated request does not restart the clock
+        request_access(&service, &id, NOW + 3600).unwrap();
+        assert!(open_export(&service, &id, &secret, grants_at - 1).is_err());
+
+        let (level, opened) = open_export(&service, &id, &secret, grants_at).unwrap();
+        assert_eq!(level, EmergencyAccessLevel::View);
+        assert_eq!(opened, export());
+        assert_eq!(
+            access_status(&service, &id, grants_at + 1).unwrap().state,
+            EmergencyAccessState::Granted { granted_at: grants_at }
+        );
+        assert!(deny_access(&service, &id, grants_at + 1).is_err());
+
+        // Someone else's key cannot open it even once granted
+        let (_, other_secret) = generate_identity();
+        assert!(open_export(&service, &id, &other_secret, grants_at).is_err());
+    }
+
+    #[test]
+    fn test_owner_denial_blocks_access() {
+        let (service, id, secret) = setup(1);
+        assert!(deny_access(&service, &id, NOW).is_err());
+
+        request_access(&service, &id, NOW).unwrap();
+        let denied = deny_access(&service, &id, NOW + 60).unwrap();
+        assert_eq!(denied.state, EmergencyAccessState::Denied { denied_at: NOW + 60 });
+
+        // The original deadline passing changes nothing
+        let later = NOW + 30 * DAY_SECS;
+        assert!(open_export(&service, &id, &secret, later).is_err());
+        assert_eq!(
+            access_status(&service, &id, later).unwrap().state,
+            EmergencyAccessState::Denied { denied_at: NOW + 60 }
+        );
+
+        // A new request starts a fresh waiting period
+        request_access(&service, &id, later).unwrap();
+        assert!(open_export(&service, &id, &secret, later + DAY_SECS - 1).is_err());
+        assert!(open_export(&service, &id, &secret, later + DAY_SECS).is_ok());
+    }
+
+    #[test]
+    fn test_server_only_holds_ciphertext() {
+        let (service, id, secret) = setup(7);
+        let record = service.get_emergency_access(&id).unwrap().unwrap();
+        let stored = serde_json::to_string(&record).unwrap();
+        let entry = &export().entries[0];
+        for secret_text in [
+            entry.password.clone(),
+            entry.username.clone(),
+            "PIN 4242".to_string(),
+            URL_SAFE_NO_PAD.encode(secret),
+            data_encoding::HEXLOWER.encode(&secret),
+        ] {
+            assert!(!stored.contains(&secret_text), "server record leaks {}", secret_text);
+        }
+
+        // Re-granting reseals under a new ephemeral key and keeps the request state
+        request_access(&service, &id, NOW).unwrap();
+        let public_key = record.contact_public_key.clone();
+        grant_contact(&service, "bob@example.com", &public_key, EmergencyAccessLevel::Takeover, 7, &export(), NOW + 5)
+            .unwrap();
+        let resealed = service.get_emergency_access(&id).unwrap().unwrap();
+        assert_ne!(resealed.ciphertext, record.ciphertext);
+        assert_ne!(resealed.ephemeral_public_key, record.ephemeral_public_key);
+        assert_eq!(resealed.status, "requested");
+        assert_eq!(service.list_emergency_access().unwrap().len(), 1);
+
+        assert!(grant_contact(&service, "carol@example.com", "not-a-key", EmergencyAccessLevel::View, 7, &export(), NOW)
+            .is_err());
+        assert!(grant_contact(&service, "carol@example.com", &public_key, EmergencyAccessLevel::View, 0, &export(), NOW)
+            .is_err());
+    }
+}
@@ -1,7 +1,9 @@
 // Password Manager Service - SQLite Backend
 use crate::models::passwords::*;
+use argon2::{Algorithm, Argon2, Params, Version};
 use data_encoding::HEXLOWER;
 use ring::aead;
+use ring::digest;
 use ring::pbkdf2;
 use ring::rand::{SecureRandom, SystemRandom};
 use rusqlite::{params, Connection, Result};
@@ -11,10 +13,19 @@ use std::sync::{Arc, Mutex};
 const CREDENTIAL_LEN: usize = 32; // 256 bits
 const SALT_LEN: usize = 32;
 const NONCE_LEN: usize = 12;
-const PBKDF2_ITERATIONS: u32 = 100_000;
+
+// Below these the UI warns (OWASP password storage minimums)
+const PBKDF2_BASELINE_ITERATIONS: u32 = 600_000;
+const ARGON2_BASELINE_MEMORY_KIB: u32 = 19 * 1024;
+const ARGON2_BASELINE_ITERATIONS: u32 = 2;
+/// Settings above this could make the vault impossible to open on smaller machines
+const ARGON2_MAX_MEMORY_KIB: u32 = 2 * 1024 * 1024;
 
 pub struct PasswordService {
     db: Arc<Mutex<Connection>>,
+    /// Last derived key, keyed by a hash of its inputs. Argon2id is too slow
+    /// to run for every entry that gets decrypted.
+    last_key: Mutex<Option<([u8; 32], [u8; CREDENTIAL_LEN])>>,
 }
 
 impl PasswordService {
@@ -22,6 +33,7 @@ impl PasswordService {
         let conn = Connection::open(db_path)?;
         let service = Self {
             db: Arc::new(Mutex::new(conn)),
+            last_key: Mutex::new(None),
         };
         service.init_schema()?;
         service.insert_default_categories()?;
@@ -42,6 +54,9 @@ impl PasswordService {
             )",
             [],
         )?;
+        // KDF settings as JSON; NULL for vaults created before it could be chosen,
+        // which keep using PBKDF2 until the master password is changed
+        let _ = conn.execute("ALTER TABLE master_password ADD COLUMN kdf_params TEXT", []);
 
         // Password entries table
         conn.execute(
@@ -63,6 +78,8 @@ impl PasswordService {
             )",
             [],
         )?;
+        // otpauth:// URI, encrypted like encrypted_password
+        let _ = conn.execute("ALTER TABLE passwords ADD COLUMN encrypted_totp TEXT", []);
 
         // Categories table
         conn.execute(
@@ -77,6 +94,51 @@ impl PasswordService {
             [],
         )?;
 
+        // Shared password links (ciphertext only)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS password_shares (
+                token TEXT PRIMARY KEY,
+                ciphertext TEXT NOT NULL,
+                salt TEXT NOT NULL,
+                password_protected BOOLEAN NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL,
+                expires_at INTEGER NOT NULL,
+                max_views INTEGER NOT NULL,
+                view_count INTEGER NOT NULL DEFAULT 0,
+                destroyed_reason TEXT
+            )",
+            [],
+        )?;
+
+        // Emergency access grants (ciphertext sealed to the contact's key)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS emergency_access (
+                id TEXT PRIMARY KEY,
+                contact_email TEXT NOT NULL,
+                contact_public_key TEXT NOT NULL,
+                access_level TEXT NOT NULL,
+                wait_days INTEGER NOT NULL,
+                status TEXT NOT NULL DEFAULT 'idle',
+                requested_at INTEGER,
+                decided_at INTEGER,
+                ephemeral_public_key TEXT NOT NULL,
+                ciphertext TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
+        // Emergency access identity of this device
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS emergency_identity (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                public_key TEXT NOT NULL,
+                encrypted_secret TEXT NOT NULL
+            )",
+            [],
+        )?;
+
         // Indexes
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_passwords_category ON passwords(category)",
@@ -126,7 +188,8 @@ impl PasswordService {
     }
 
     // Master Password Operations
-    pub fn setup_master_password(&self, _master_password: &str) -> Result<()> {
+    pub fn setup_master_password(&self, _master_password: &str, kdf: &KdfParams) -> Result<()> {
+        validate_kdf(kdf)?;
         let rng = SystemRandom::new();
         let mut salt = [0u8; SALT_LEN];
         rng.fill(&mut salt)
@@ -135,13 +198,14 @@ impl PasswordService {
             ))))?;
 
         let salt_hex = HEXLOWER.encode(&salt);
+        let kdf_json = serde_json::to_string(kdf).map_err(|e| vault_error(e.to_string()))?;
         let now = chrono::Utc::now().timestamp();
 
         let conn = self.db.lock().unwrap();
         conn.execute(
-            "INSERT OR REPLACE INTO master_password (id, salt, is_set, created_at, updated_at)
-             VALUES (1, ?1, 1, ?2, ?3)",
-            params![salt_hex, now, now],
+            "INSERT OR REPLACE INTO master_password (id, salt, is_set, created_at, updated_at, kdf_params)
+             VALUES (1, ?1, 1, ?2, ?3, ?4)",
+            params![salt_hex, now, now, kdf_json],
         )?;
 
         Ok(())
@@ -150,15 +214,19 @@ impl PasswordService {
     pub fn get_master_password_config(&self) -> Result<MasterPasswordConfig> {
         let conn = self.db.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT salt, is_set, created_at, updated_at FROM master_password WHERE id = 1"
+            "SELECT salt, is_set, created_at, updated_at, kdf_params FROM master_password WHERE id = 1"
         )?;
 
         let config = stmt.query_row([], |row| {
+            let kdf_json: Option<String> = row.get(4)?;
             Ok(MasterPasswordConfig {
                 salt: row.get(0)?,
                 is_set: row.get(1)?,
                 created_at: row.get(2)?,
                 updated_at: row.get(3)?,
+                kdf: kdf_json
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_else(KdfParams::legacy_pbkdf2),
             })
         });
 
@@ -169,59 +237,96 @@ impl PasswordService {
                 is_set: false,
                 created_at: 0,
                 updated_at: 0,
+                kdf: KdfParams::recommended(),
             }),
             Err(e) => Err(e),
         }
     }
 
-    pub fn change_master_password(&self, old_password: &str, new_password: &str) -> Result<()> {
-        // Verify old password first
+    /// Re-encrypts the vault under a new master password and, when `kdf` is
+    /// given, a new KDF. Everything is decrypted and re-encrypted in memory
+    /// first and written in one transaction, so a failure or crash leaves the
+    /// vault entirely under the old key.
+    pub fn change_master_password(&self, old_password: &str, new_password: &str, kdf: Option<&KdfParams>) -> Result<()> {
         let config = self.get_master_password_config()?;
         let old_salt = HEXLOWER
             .decode(config.salt.as_bytes())
-            .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Invalid salt",
-            ))))?;
-
-        // Get all entries
-        let entries = self.get_all_passwords()?;
+            .map_err(|_| vault_error("Invalid salt"))?;
+        let new_kdf = kdf.cloned().unwrap_or(config.kdf.clone());
+        validate_kdf(&new_kdf)?;
 
-        // Decrypt with old password and re-encrypt with new password
         let rng = SystemRandom::new();
         let mut new_salt = [0u8; SALT_LEN];
         rng.fill(&mut new_salt)
-            .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to generate salt",
-            ))))?;
+            .map_err(|_| vault_error("Failed to generate salt"))?;
 
-        for entry in entries {
-            let decrypted = self.decrypt_password_internal(&entry.encrypted_password, old_password, &old_salt)
-                .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                    "Failed to decrypt with old password",
-                ))))?;
-
-            let encrypted = self.encrypt_password_internal(&decrypted, new_password, &new_salt)
-                .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                    "Failed to encrypt with new password",
-                ))))?;
+        let old_key = self.derive_key(old_password, &old_salt, &config.kdf)?;
+        let new_key = self.derive_key(new_password, &new_salt, &new_kdf)?;
 
-            let conn = self.db.lock().unwrap();
-            conn.execute(
-                "UPDATE passwords SET encrypted_password = ?1, date_modified = ?2 WHERE id = ?3",
-                params![encrypted, chrono::Utc::now().timestamp(), entry.id],
-            )?;
+        let mut rewrapped = Vec::new();
+        for entry in self.get_all_passwords()? {
+            let decrypted = open_with_key(&old_key, &entry.encrypted_password)
+                .map_err(|_| vault_error("Failed to decrypt with old password"))?;
+            rewrapped.push((entry.id, seal_with_key(&new_key, &decrypted)?));
         }
+        let mut rewrapped_totp = Vec::new();
+        for (id, encrypted_totp) in self.get_all_totp_secrets()? {
+            let decrypted = open_with_key(&old_key, &encrypted_totp)
+                .map_err(|_| vault_error("Failed to decrypt TOTP secret with old password"))?;
+            rewrapped_totp.push((id, seal_with_key(&new_key, &decrypted)?));
+        }
+
+        // The emergency access identity is sealed the same way
+        let identity_secret = match self.get_emergency_identity()? {
+            Some(identity) => {
+                let secret = open_with_key(&old_key, &identity.encrypted_secret)
+                    .map_err(|_| vault_error("Failed to decrypt emergency identity with old password"))?;
+                Some(seal_with_key(&new_key, &secret)?)
+            }
+            None => None,
+        };
 
-        // Update master password config
         let new_salt_hex = HEXLOWER.encode(&new_salt);
+        let kdf_json = serde_json::to_string(&new_kdf).map_err(|e| vault_error(e.to_string()))?;
         let now = chrono::Utc::now().timestamp();
-        let conn = self.db.lock().unwrap();
-        conn.execute(
-            "UPDATE master_password SET salt = ?1, updated_at = ?2 WHERE id = 1",
-            params![new_salt_hex, now],
+
+        let mut conn = self.db.lock().unwrap();
+        let tx = conn.transaction()?;
+        for (id, encrypted) in &rewrapped {
+            tx.execute(
+                "UPDATE passwords SET encrypted_password = ?1, date_modified = ?2 WHERE id = ?3",
+                params![encrypted, now, id],
+            )?;
+        }
+        for (id, encrypted_totp) in &rewrapped_totp {
+            tx.execute(
+                "UPDATE passwords SET encrypted_totp = ?1 WHERE id = ?2",
+                params![encrypted_totp, id],
+            )?;
+        }
+        if let Some(encrypted_secret) = &identity_secret {
+            tx.execute(
+                "UPDATE emergency_identity SET encrypted_secret = ?1 WHERE id = 1",
+                params![encrypted_secret],
+            )?;
+        }
+        tx.execute(
+            "UPDATE master_password SET salt = ?1, kdf_params = ?2, updated_at = ?3 WHERE id = 1",
+            params![new_salt_hex, kdf_json, now],
         )?;
+        tx.commit()
+    }
 
-        Ok(())
+    /// Compares the vault's KDF settings with the security baseline
+    pub fn kdf_status(&self) -> Result<KdfStatus> {
+        let current = self.get_master_password_config()?.kdf;
+        let warnings = kdf_warnings(&current);
+        Ok(KdfStatus {
+            current,
+            recommended: KdfParams::recommended(),
+            below_baseline: !warnings.is_empty(),
+            warnings,
+        })
     }
 
     // Password Entry Operations
@@ -302,6 +407,38 @@ impl PasswordService {
         Ok(())
     }
 
+    /// Sets or (with `None`) removes an entry's encrypted TOTP secret. Returns
+    /// false when there is no such entry.
+    pub fn set_totp_secret(&self, id: &str, encrypted_totp: Option<&str>) -> Result<bool> {
+        let conn = self.db.lock().unwrap();
+        let updated = conn.execute(
+            "UPDATE passwords SET encrypted_totp = ?1 WHERE id = ?2",
+            params![encrypted_totp, id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub fn get_totp_secret(&self, id: &str) -> Result<Option<String>> {
+        let conn = self.db.lock().unwrap();
+        let secret = conn.query_row(
+            "SELECT encrypted_totp FROM passwords WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        );
+        match secret {
+            Ok(secret) => Ok(secret),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn get_all_totp_secrets(&self) -> Result<Vec<(String, String)>> {
+        let conn = self.db.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, encrypted_totp FROM passwords WHERE encrypted_totp IS NOT NULL")?;
+        let secrets = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        secrets.collect()
+    }
+
     pub fn update_last_used(&self, id: &str) -> Result<()> {
         let conn = self.db.lock().unwrap();
         let now = chrono::Utc::now().timestamp();
@@ -312,6 +449,162 @@ impl PasswordService {
         Ok(())
     }
 
+    // Password Share Operations
+    pub fn insert_password_share(&self, share: &PasswordShareRecord) -> Result<()> {
+        let conn = self.db.lock().unwrap();
+        conn.execute(
+            "INSERT INTO password_shares (token, ciphertext, salt, password_protected,
+                                          created_at, expires_at, max_views, view_count, destroyed_reason)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                share.token,
+                share.ciphertext,
+                share.salt,
+                share.password_protected,
+                share.created_at,
+                share.expires_at,
+                share.max_views,
+                share.view_count,
+                share.destroyed_reason,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_password_share(&self, token: &str) -> Result<Option<PasswordShareRecord>> {
+        let conn = self.db.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT token, ciphertext, salt, password_protected, created_at, expires_at,
+                    max_views, view_count, destroyed_reason
+             FROM password_shares WHERE token = ?1"
+        )?;
+        let mut rows = stmt.query_map(params![token], |row| {
+            Ok(PasswordShareRecord {
+                token: row.get(0)?,
+                ciphertext: row.get(1)?,
+                salt: row.get(2)?,
+                password_protected: row.get(3)?,
+                created_at: row.get(4)?,
+                expires_at: row.get(5)?,
+                max_views: row.get(6)?,
+                view_count: row.get(7)?,
+                destroyed_reason: row.get(8)?,
+            })
+        })?;
+        rows.next().transpose()
+    }
+
+    pub fn record_password_share_view(&self, token: &str) -> Result<()> {
+        let conn = self.db.lock().unwrap();
+        conn.execute(
+            "UPDATE password_shares SET view_count = view_count + 1 WHERE token = ?1",
+            params![token],
+        )?;
+        Ok(())
+    }
+
+    /// Wipe a share's ciphertext, keeping a tombstone so later visits can be
+    /// told why the link is gone
+    pub fn destroy_password_share(&self, token: &str, reason: &str) -> Result<()> {
+        let conn = self.db.lock().unwrap();
+        conn.execute(
+            "UPDATE password_shares SET ciphertext = '', destroyed_reason = ?1 WHERE token = ?2",
+            params![reason, token],
+        )?;
+        Ok(())
+    }
+
+    // Emergency Access Operations
+    pub fn upsert_emergency_access(&self, record: &EmergencyAccessRecord) -> Result<()> {
+        let conn = self.db.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO emergency_access (id, contact_email, contact_public_key, access_level,
+                                                      wait_days, status, requested_at, decided_at,
+                                                      ephemeral_public_key, ciphertext, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                record.id,
+                record.contact_email,
+                record.contact_public_key,
+                record.access_level,
+                record.wait_days,
+                record.status,
+                record.requested_at,
+                record.decided_at,
+                record.ephemeral_public_key,
+                record.ciphertext,
+                record.created_at,
+                record.updated_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_emergency_access(&self, id: &str) -> Result<Option<EmergencyAccessRecord>> {
+        Ok(self
+            .query_emergency_access("WHERE id = ?1", params![id])?
+            .into_iter()
+            .next())
+    }
+
+    pub fn list_emergency_access(&self) -> Result<Vec<EmergencyAccessRecord>> {
+        self.query_emergency_access("ORDER BY created_at", params![])
+    }
+
+    fn query_emergency_access(&self, clause: &str, args: &[&dyn rusqlite::ToSql]) -> Result<Vec<EmergencyAccessRecord>> {
+        let conn = self.db.lock().unwrap();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, contact_email, contact_public_key, access_level, wait_days, status,
+                    requested_at, decided_at, ephemeral_public_key, ciphertext, created_at, updated_at
+             FROM emergency_access {}",
+            clause
+        ))?;
+        let rows = stmt.query_map(args, |row| {
+            Ok(EmergencyAccessRecord {
+                id: row.get(0)?,
+                contact_email: row.get(1)?,
+                contact_public_key: row.get(2)?,
+                access_level: row.get(3)?,
+                wait_days: row.get(4)?,
+                status: row.get(5)?,
+                requested_at: row.get(6)?,
+                decided_at: row.get(7)?,
+                ephemeral_public_key: row.get(8)?,
+                ciphertext: row.get(9)?,
+                created_at: row.get(10)?,
+                updated_at: row.get(11)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn delete_emergency_access(&self, id: &str) -> Result<()> {
+        let conn = self.db.lock().unwrap();
+        conn.execute("DELETE FROM emergency_access WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    pub fn get_emergency_identity(&self) -> Result<Option<EmergencyIdentity>> {
+        let conn = self.db.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT public_key, encrypted_secret FROM emergency_identity WHERE id = 1")?;
+        let mut rows = stmt.query_map([], |row| {
+            Ok(EmergencyIdentity {
+                public_key: row.get(0)?,
+                encrypted_secret: row.get(1)?,
+            })
+        })?;
+        rows.next().transpose()
+    }
+
+    pub fn save_emergency_identity(&self, identity: &EmergencyIdentity) -> Result<()> {
+        let conn = self.db.lock().unwrap();
+        conn.execute(
+            "INSERT OR REPLACE INTO emergency_identity (id, public_key, encrypted_secret) VALUES (1, ?1, ?2)",
+            params![identity.public_key, identity.encrypted_secret],
+        )?;
+        Ok(())
+    }
+
     // Category Operations
     pub fn get_all_categories(&self) -> Result<Vec<PasswordCategory>> {
         let conn = self.db.lock().unwrap();
@@ -392,84 +685,46 @@ impl PasswordService {
     }
 
     // Encryption utilities (internal)
-    fn derive_key(&self, master_password: &str, salt: &[u8]) -> [u8; CREDENTIAL_LEN] {
-        let iterations = NonZeroU32::new(PBKDF2_ITERATIONS).unwrap();
-        let mut key = [0u8; CREDENTIAL_LEN];
-        pbkdf2::derive(
-            pbkdf2::PBKDF2_HMAC_SHA256,
-            iterations,
-            salt,
-            master_password.as_bytes(),
-            &mut key,
-        );
-        key
-    }
-
-    pub fn encrypt_password_internal(&self, password: &str, master_password: &str, salt: &[u8]) -> Result<String> {
-        let key = self.derive_key(master_password, salt);
-        let unbound_key = aead::UnboundKey::new(&aead::AES_256_GCM, &key)
-            .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to create key",
-            ))))?;
-        let sealing_key = aead::LessSafeKey::new(unbound_key);
-
-        let rng = SystemRandom::new();
-        let mut nonce_bytes = [0u8; NONCE_LEN];
-        rng.fill(&mut nonce_bytes)
-            .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to generate nonce",
-            ))))?;
-
-        let nonce = aead::Nonce::assume_unique_for_key(nonce_bytes);
-        let mut in_out = password.as_bytes().to_vec();
-        sealing_key
-            .seal_in_place_append_tag(nonce, aead::Aad::empty(), &mut in_out)
-            .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Encryption failed",
-            ))))?;
-
-        let mut result = nonce_bytes.to_vec();
-        result.extend_from_slice(&in_out);
-
-        Ok(HEXLOWER.encode(&result))
-    }
-
-    pub fn decrypt_password_internal(&self, encrypted_hex: &str, master_password: &str, salt: &[u8]) -> Result<String> {
-        let encrypted_data = HEXLOWER
-            .decode(encrypted_hex.as_bytes())
-            .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Invalid hex encoding",
-            ))))?;
-
-        if encrypted_data.len() < NONCE_LEN {
-            return Err(rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Invalid encrypted data",
-            ))));
+    fn derive_key(&self, master_password: &str, salt: &[u8], kdf: &KdfParams) -> Result<[u8; CREDENTIAL_LEN]> {
+        let fingerprint = key_fingerprint(master_password, salt, kdf);
+        if let Some((cached_for, key)) = *self.last_key.lock().unwrap() {
+            if cached_for == fingerprint {
+                return Ok(key);
+            }
         }
 
-        let (nonce_bytes, ciphertext) = encrypted_data.split_at(NONCE_LEN);
-        let mut nonce_array = [0u8; NONCE_LEN];
-        nonce_array.copy_from_slice(nonce_bytes);
-        let nonce = aead::Nonce::assume_unique_for_key(nonce_array);
+        let mut key = [0u8; CREDENTIAL_LEN];
+        match kdf.algorithm {
+            KdfAlgorithm::Pbkdf2Sha256 => {
+                let iterations = NonZeroU32::new(kdf.iterations)
+                    .ok_or_else(|| vault_error("PBKDF2 needs at least one iteration"))?;
+                pbkdf2::derive(
+                    pbkdf2::PBKDF2_HMAC_SHA256,
+                    iterations,
+                    salt,
+                    master_password.as_bytes(),
+                    &mut key,
+                );
+            }
+            KdfAlgorithm::Argon2id => {
+                argon2id(kdf)?
+                    .hash_password_into(master_password.as_bytes(), salt, &mut key)
+                    .map_err(|e| vault_error(format!("Key derivation failed: {}", e)))?;
+            }
+        }
 
-        let key = self.derive_key(master_password, salt);
-        let unbound_key = aead::UnboundKey::new(&aead::AES_256_GCM, &key)
-            .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Failed to create key",
-            ))))?;
-        let opening_key = aead::LessSafeKey::new(unbound_key);
+        *self.last_key.lock().unwrap() = Some((fingerprint, key));
+        Ok(key)
+    }
 
-        let mut in_out = ciphertext.to_vec();
-        let decrypted = opening_key
-            .open_in_place(nonce, aead::Aad::empty(), &mut in_out)
-            .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Decryption failed",
-            ))))?;
+    pub fn encrypt_password_internal(&self, password: &str, master_password: &str, salt: &[u8], kdf: &KdfParams) -> Result<String> {
+        let key = self.derive_key(master_password, salt, kdf)?;
+        seal_with_key(&key, password)
+    }
 
-        String::from_utf8(decrypted.to_vec())
-            .map_err(|_| rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(
-                "Invalid UTF-8",
-            ))))
+    pub fn decrypt_password_internal(&self, encrypted_hex: &str, master_password: &str, salt: &[u8], kdf: &KdfParams) -> Result<String> {
+        let key = self.derive_key(master_password, salt, kdf)?;
+        open_with_key(&key, encrypted_hex)
     }
 
     // Password Generation
@@ -578,3 +833,219 @@ impl PasswordService {
         }
     }
 }
+
+fn vault_error(message: impl Into<String>) -> rusqlite::Error {
+    rusqlite::Error::ToSqlConversionFailure(Box::new(std::io::Error::other(message.into())))
+}
+
+/// Rejects settings that can't derive a key, or that would take so much memory
+/// the vault might not open elsewhere
+fn validate_kdf(kdf: &KdfParams) -> Result<()> {
+    match kdf.algorithm {
+        KdfAlgorithm::Pbkdf2Sha256 if kdf.iterations == 0 => Err(vault_error("PBKDF2 needs at least one iteration")),
+        KdfAlgorithm::Pbkdf2Sha256 => Ok(()),
+        KdfAlgorithm::Argon2id if kdf.memory_kib > ARGON2_MAX_MEMORY_KIB => Err(vault_error(format!(
+            "Argon2id memory is limited to {} MiB",
+            ARGON2_MAX_MEMORY_KIB / 1024
+        ))),
+        KdfAlgorithm::Argon2id => argon2id(kdf).map(|_| ()),
+    }
+}
+
+fn argon2id(kdf: &KdfParams) -> Result<Argon2<'static>> {
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(CREDENTIAL_LEN))
+        .map_err(|e| vault_error(format!("Invalid Argon2id parameters: {}", e)))?;
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+fn kdf_warnings(kdf: &KdfParams) -> Vec<String> {
+    let mut warnings = Vec::new();
+    match kdf.algorithm {
+        KdfAlgorithm::Pbkdf2Sha256 => {
+            if kdf.iterations < PBKDF2_BASELINE_ITERATIONS {
+                warnings.push(format!(
+                    "PBKDF2 uses {} iterations; at least {} are recommended",
+                    kdf.iterations, PBKDF2_BASELINE_ITERATIONS
+                ));
+            }
+            warnings.push("Argon2id is more resistant to GPU cracking than PBKDF2".to_string());
+        }
+        KdfAlgorithm::Argon2id => {
+            if kdf.memory_kib < ARGON2_BASELINE_MEMORY_KIB {
+                warnings.push(format!(
+                    "Argon2id uses {} KiB of memory; at least {} KiB is recommended",
+                    kdf.memory_kib, ARGON2_BASELINE_MEMORY_KIB
+                ));
+            }
+            if kdf.iterations < ARGON2_BASELINE_ITERATIONS {
+                warnings.push(format!(
+                    "Argon2id uses {} iteration(s); at least {} are recommended",
+                    kdf.iterations, ARGON2_BASELINE_ITERATIONS
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+fn key_fingerprint(master_password: &str, salt: &[u8], kdf: &KdfParams) -> [u8; 32] {
+    let mut context = digest::Context::new(&digest::SHA256);
+    context.update(format!("{:?}:{}:{}:{}:", kdf.algorithm, kdf.iterations, kdf.memory_kib, kdf.parallelism).as_bytes());
+    context.update(&(salt.len() as u64).to_be_bytes());
+    context.update(salt);
+    context.update(master_password.as_bytes());
+    let mut fingerprint = [0u8; 32];
+    fingerprint.copy_from_slice(context.finish().as_ref());
+    fingerprint
+}
+
+fn seal_with_key(key: &[u8; CREDENTIAL_LEN], plaintext: &str) -> Result<String> {
+    let unbound_key = aead::UnboundKey::new(&aead::AES_256_GCM, key)
+        .map_err(|_| vault_error("Failed to create key"))?;
+    let sealing_key = aead::LessSafeKey::new(unbound_key);
+
+    let rng = SystemRandom::new();
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rng.fill(&mut nonce_bytes)
+        .map_err(|_| vault_error("Failed to generate nonce"))?;
+
+    let nonce = aead::Nonce::assume_unique_for_key(nonce_bytes);
+    let mut in_out = plaintext.as_bytes().to_vec();
+    sealing_key
+        .seal_in_place_append_tag(nonce, aead::Aad::empty(), &mut in_out)
+        .map_err(|_| vault_error("Encryption failed"))?;
+
+    let mut result = nonce_bytes.to_vec();
+    result.extend_from_slice(&in_out);
+
+    Ok(HEXLOWER.encode(&result))
+}
+
+fn open_with_key(key: &[u8; CREDENTIAL_LEN], encrypted_hex: &str) -> Result<String> {
+    let encrypted_data = HEXLOWER
+        .decode(encrypted_hex.as_bytes())
+        .map_err(|_| vault_error("Invalid hex encoding"))?;
+
+    if encrypted_data.len() < NONCE_LEN {
+        return Err(vault_error("Invalid encrypted data"));
+    }
+
+    let (nonce_bytes, ciphertext) = encrypted_data.split_at(NONCE_LEN);
+    let mut nonce_array = [0u8; NONCE_LEN];
+    nonce_array.copy_from_slice(nonce_bytes);
+    let nonce = aead::Nonce::assume_unique_for_key(nonce_array);
+
+    let unbound_key = aead::UnboundKey::new(&aead::AES_256_GCM, key)
+        .map_err(|_| vault_error("Failed to create key"))?;
+    let opening_key = aead::LessSafeKey::new(unbound_key);
+
+    let mut in_out = ciphertext.to_vec();
+    let decrypted = opening_key
+        .open_in_place(nonce, aead::Aad::empty(), &mut in_out)
+        .map_err(|_| vault_error("Decryption failed"))?;
+
+    String::from_utf8(decrypted.to_vec()).map_err(|_| vault_error("Invalid UTF-8"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// OWASP's minimum Argon2id settings, quick enough for tests
+    fn baseline_argon2id() -> KdfParams {
+        KdfParams {
+            algorithm: KdfAlgorithm::Argon2id,
+            iterations: ARGON2_BASELINE_ITERATIONS,
+            memory_kib: ARGON2_BASELINE_MEMORY_KIB,
+            parallelism: 1,
+        }
+    }
+
+    fn entry(id: &str, encrypted_password: String) -> PasswordEntry {
+        PasswordEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            username: "ada".to_string(),
+            encrypted_password,
+            url: None,
+            notes: None,
+            category: "general".to_string(),
+            tags: Vec::new(),
+            date_created: 0,
+            date_modified: 0,
+            last_used: None,
+            favorite: false,
+            strength_score: 0,
+        }
+    }
+
+    fn decrypt_all(service: &PasswordService, master_password: &str) -> Result<Vec<String>> {
+        let config = service.get_master_password_config()?;
+        let salt = HEXLOWER.decode(config.salt.as_bytes()).unwrap();
+        let mut passwords = service
+            .get_all_passwords()?
+            .iter()
+            .map(|e| service.decrypt_password_internal(&e.encrypted_password, master_password, &salt, &config.kdf))
+            .collect::<Result<Vec<_>>>()?;
+        passwords.sort();
+        Ok(passwords)
+    }
+
+    #[test]
+    fn test_switching_kdf_keeps_vault_readable() {
+        let service = PasswordService::new(":memory:").unwrap();
+
+        // A vault from before the KDF could be chosen has no stored settings
+        service.setup_master_password("old master", &KdfParams::legacy_pbkdf2()).unwrap();
+        service.db.lock().unwrap().execute("UPDATE master_password SET kdf_params = NULL", []).unwrap();
+        let config = service.get_master_password_config().unwrap();
+        assert_eq!(config.kdf, KdfParams::legacy_pbkdf2());
+        assert!(service.kdf_status().unwrap().below_baseline);
+
+        let salt = HEXLOWER.decode(config.salt.as_bytes()).unwrap();
+        let identity_secret = service.encrypt_password_internal("emergency key", "old master", &salt, &config.kdf).unwrap();
+        service
+            .save_emergency_identity(&EmergencyIdentity { public_key: "pk".to_string(), encrypted_secret: identity_secret })
+            .unwrap();
+        for (id, password) in [("mail", "hunter2"), ("bank", "correct horse battery staple")] {
+            let encrypted = service.encrypt_password_internal(password, "old master", &salt, &config.kdf).unwrap();
+            service.save_password(&entry(id, encrypted)).unwrap();
+        }
+        let totp = service.encrypt_password_internal("otpauth://totp/Bank?secret=GEZDGNBV", "old master", &salt, &config.kdf).unwrap();
+        assert!(service.set_totp_secret("bank", Some(&totp)).unwrap());
+        assert!(!service.set_totp_secret("missing", Some(&totp)).unwrap());
+
+        // A wrong old password changes nothing
+        assert!(service.change_master_password("wrong", "new master", Some(&baseline_argon2id())).is_err());
+        assert_eq!(service.get_master_password_config().unwrap().salt, config.salt);
+        assert_eq!(decrypt_all(&service, "old master").unwrap(), ["correct horse battery staple", "hunter2"]);
+
+        service.change_master_password("old master", "new master", Some(&baseline_argon2id())).unwrap();
+        let config = service.get_master_password_config().unwrap();
+        assert_eq!(config.kdf, baseline_argon2id());
+        assert!(!service.kdf_status().unwrap().below_baseline);
+        assert_eq!(decrypt_all(&service, "new master").unwrap(), ["correct horse battery staple", "hunter2"]);
+        assert!(decrypt_all(&service, "old master").is_err());
+        let salt = HEXLOWER.decode(config.salt.as_bytes()).unwrap();
+        let identity = service.get_emergency_identity().unwrap().unwrap();
+        assert_eq!(
+            service.decrypt_password_internal(&identity.encrypted_secret, "new master", &salt, &config.kdf).unwrap(),
+            "emergency key"
+        );
+        let totp = service.get_totp_secret("bank").unwrap().unwrap();
+        assert_eq!(
+            service.decrypt_password_internal(&totp, "new master", &salt, &config.kdf).unwrap(),
+            "otpauth://totp/Bank?secret=GEZDGNBV"
+        );
+        assert_eq!(service.get_totp_secret("mail").unwrap(), None);
+
+        // Same password, KDF kept: only the salt is rotated
+        service.change_master_password("new master", "new master", None).unwrap();
+        assert_eq!(service.get_master_password_config().unwrap().kdf, baseline_argon2id());
+        assert_eq!(decrypt_all(&service, "new master").unwrap(), ["correct horse battery staple", "hunter2"]);
+
+        let too_weak = KdfParams { memory_kib: 4, ..baseline_argon2id() };
+        assert!(service.change_master_password("new master", "other", Some(&too_weak)).is_err());
+        assert_eq!(decrypt_all(&service, "new master").unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,379 @@
+// Password Sharing - One-time encrypted links for vault entries
+// The entry is encrypted client-side with a random key that only travels in
+// the link's URL fragment; the share server stores ciphertext and limits.
+use crate::models::passwords::PasswordShareRecord;
+use crate::services::password_service::PasswordService;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use data_encoding::HEXLOWER;
+use ring::aead;
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU32;
+
+const SHARE_BASE_URL: &str = "https://cube.app/send/";
+const KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const PBKDF2_ITERATIONS: u32 = 100_000;
+const MAX_EXPIRY_HOURS: u32 = 30 * 24;
+const MAX_VIEWS: u32 = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SharePasswordOptions {
+    pub expires_in_hours: u32,
+    pub max_views: u32,
+    /// Extra password the recipient must enter besides opening the link
+    pub password: Option<String>,
+}
+
+impl Default for SharePasswordOptions {
+    fn default() -> Self {
+        Self {
+            expires_in_hours: 24,
+            max_views: 1,
+            password: None,
+        }
+    }
+}
+
+/// The vault entry fields carried by a share
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharedPasswordPayload {
+    pub name: String,
+    pub username: String,
+    pub password: String,
+    pub url: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordShareLink {
+    pub token: String,
+    /// Base64url key, also present in `url` after the `#`
+    pub key: String,
+    pub url: String,
+    pub expires_at: i64,
+    pub max_views: u32,
+    pub password_protected: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareGoneReason {
+    NotFound,
+    Expired,
+    ViewsExhausted,
+}
+
+impl ShareGoneReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::NotFound => "not_found",
+            Self::Expired => "expired",
+            Self::ViewsExhausted => "views_exhausted",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "expired" => Self::Expired,
+            "views_exhausted" => Self::ViewsExhausted,
+            _ => Self::NotFound,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SharedPasswordRetrieval {
+    Available {
+        payload: SharedPasswordPayload,
+        views_remaining: u32,
+        expires_at: i64,
+    },
+    /// The share exists but needs the sender's extra password
+    PasswordRequired,
+    Gone { reason: ShareGoneReason },
+}
+
+/// Encrypt `payload` and store the ciphertext under a new token
+pub fn create_share(
+    service: &PasswordService,
+    payload: &SharedPasswordPayload,
+    options: &SharePasswordOptions,
+    now: i64,
+) -> Result<PasswordShareLink, String> {
+    if options.expires_in_hours == 0 || options.expires_in_hours > MAX_EXPIRY_HOURS {
+        return Err(format!("Expiry must be between 1 and {} hours", MAX_EXPIRY_HOURS));
+    }
+    if options.max_views == 0 || options.max_views > MAX_VIEWS {
+        return Err(format!("View limit must be between 1 and {}", MAX_VIEWS));
+    }
+    let password = options.password.as_deref().filter(|p| !p.is_empty());
+
+    let rng = SystemRandom::new();
+    let mut key = [0u8; KEY_LEN];
+    let mut salt = [0u8; SALT_LEN];
+    rng.fill(&mut key).map_err(|_| "Failed to generate share key".to_string())?;
+    rng.fill(&mut salt).map_err(|_| "Failed to generate salt".to_string())?;
+
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    let plaintext = serde_json::to_vec(payload).map_err(|e| e.to_string())?;
+    let ciphertext = seal(&encryption_key(&key, password, &salt), &token, plaintext)?;
+
+    let record = PasswordShareRecord {
+        token: token.clone(),
+        ciphertext,
+        salt: HEXLOWER.encode(&salt),
+        password_protected: password.is_some(),
+        created_at: now,
+        expires_at: now + i64::from(options.expires_in_hours) * 3600,
+        max_views: options.max_views,
+        view_count: 0,
+        destroyed_reason: None,
+    };
+    service.insert_password_share(&record).map_err(|e| e.to_string())?;
+
+    let key = URL_SAFE_NO_PAD.encode(key);
+    Ok(PasswordShareLink {
+        url: format!("{}{}#{}", SHARE_BASE_URL, token, key),
+        token,
+        key,
+        expires_at: record.expires_at,
+        max_views: record.max_views,
+        password_protected: record.password_protected,
+    })
+}
+
+/// Decrypt a share with the key from the link fragment. A successful view
+/// counts against the limit and the last allowed view destroys the share;
+/// a wrong key or password does not use up a view.
+pub fn retrieve_share(
+    service: &PasswordService,
+    token: &str,
+    key: &str,
+    password: Option<&str>,
+    now: i64,
+) -> Result<SharedPasswordRetrieval, String> {
+    let gone = |reason| Ok(SharedPasswordRetrieval::Gone { reason });
+    let destroy = |reason: ShareGoneReason| {
+        service
+            .destroy_password_share(token, reason.as_str())
+            .map_err(|e| e.to_string())
+    };
+
+    let Some(record) = service.get_password_share(token).map_err(|e| e.to_string())? else {
+        return gone(ShareGoneReason::NotFound);
+    };
+    if let Some(reason) = &record.destroyed_reason {
+        return gone(ShareGoneReason::parse(reason));
+    }
+    if now >= record.expires_at {
+        destroy(ShareGoneReason::Expired)?;
+        return gone(ShareGoneReason::Expired);
+    }
+    if record.view_count >= record.max_views {
+        destroy(ShareGoneReason::ViewsExhausted)?;
+        return gone(ShareGoneReason::ViewsExhausted);
+    }
+
+    let password = password.filter(|p| !p.is_empty());
+    if record.password_protected && password.is_none() {
+        return Ok(SharedPasswordRetrieval::PasswordRequired);
+    }
+
+    let key = URL_SAFE_NO_PAD
+        .decode(key.trim_start_matches('#'))
+        .ok()
+        .filter(|key| key.len() == KEY_LEN)
+        .ok_or_else(|| "Invalid share key".to_string())?;
+    let salt = HEXLOWER
+        .decode(record.salt.as_bytes())
+        .map_err(|e| format!("Invalid salt: {}", e))?;
+    let plaintext = open(&encryption_key(&key, password, &salt), token, &record.ciphertext)
+        .map_err(|_| "Invalid share key or password".to_string())?;
+    let payload: SharedPasswordPayload =
+        serde_json::from_slice(&plaintext).map_err(|e| format!("Invalid share payload: {}", e))?;
+
+    service.record_password_share_view(token).map_err(|e| e.to_string())?;
+    let views_remaining = record.max_views - record.view_count - 1;
+    if views_remaining == 0 {
+        destroy(ShareGoneReason::ViewsExhausted)?;
+    }
+
+    Ok(SharedPasswordRetrieval::Available {
+        payload,
+        views_remaining,
+        expires_at: record.expires_at,
+    })
+}
+
+/// The link key alone, or stretched together with the share password
+fn encryption_key(link_key: &[u8], password: Option<&str>, salt: &[u8]) -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    match password {
+        Some(password) => {
+            let mut secret = link_key.to_vec();
+            secret.extend_from_slice(password.as_bytes());
+            pbkdf2::derive(
+                pbkdf2::PBKDF2_HMAC_SHA256,
+                NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+                salt,
+                &secret,
+                &mut key,
+            );
+        }
+        None => key.copy_from_slice(link_key),
+    }
+    key
+}
+
+/// AES-256-GCM with the token as associated data, so ciphertext cannot be
+/// moved to another share
+pub(crate) fn seal(key: &[u8; KEY_LEN], token: &str, mut in_out: Vec<u8>) -> Result<String, String> {
+    let sealing_key = aead::LessSafeKey::new(
+        aead::UnboundKey::new(&aead::AES_256_GCM, key).map_err(|_| "Failed to create key".to_string())?,
+    );
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce_bytes)
+        .map_err(|_| "Failed to generate nonce".to_string())?;
+    sealing_key
+        .seal_in_place_append_tag(
+            aead::Nonce::assume_unique_for_key(nonce_bytes),
+            aead::Aad::from(token.as_bytes()),
+            &mut in_out,
+        )
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let mut result = nonce_bytes.to_vec();
+    result.extend_from_slice(&in_out);
+    Ok(HEXLOWER.encode(&result))
+}
+
+pub(crate) fn open(key: &[u8; KEY_LEN], token: &str, ciphertext_hex: &str) -> Result<Vec<u8>, String> {
+    let data = HEXLOWER
+        .decode(ciphertext_hex.as_bytes())
+        .map_err(|_| "Invalid hex encoding".to_string())?;
+    if data.len() < NONCE_LEN {
+        return Err("Invalid encrypted data".to_string());
+    }
+    let (nonce_bytes, ciphertext) = data.split_at(NONCE_LEN);
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(nonce_bytes);
+
+    let opening_key = aead::LessSafeKey::new(
+        aead::UnboundKey::new(&aead::AES_256_GCM, key).map_err(|_| "Failed to create key".to_string())?,
+    );
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = opening_key
+        .open_in_place(
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::from(token.as_bytes()),
+            &mut in_out,
+        )
+        .map_err(|_| "Decryption failed".to_string())?;
+    Ok(plaintext.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_800_000_000;
+
+    fn payload() -> SharedPasswordPayload {
+        SharedPasswordPayload {
+            name: "Bank".to_string(),
+            username: "alice@example.com".to_string(),
+            password: "correct-horse-battery-staple".to_string(),
+            url: Some("https://bank.example".to_string()),
+            notes: None,
+        }
+    }
+
+    fn service() -> PasswordService {
+        PasswordService::new(":memory:").unwrap()
+    }
+
+    #[test]
+    fn test_one_time_share_self_destructs_after_retrieval() {
+        let service = service();
+        let link = create_share(&service, &payload(), &SharePasswordOptions::default(), NOW).unwrap();
+        assert!(link.url.ends_with(&format!("{}#{}", link.token, link.key)));
+
+        // A wrong key neither reveals the entry nor uses up the view
+        let wrong_key = URL_SAFE_NO_PAD.encode([7u8; KEY_LEN]);
+        assert!(retrieve_share(&service, &link.token, &wrong_key, None, NOW).is_err());
+
+        match retrieve_share(&service, &link.token, &link.key, None, NOW + 60).unwrap() {
+            SharedPasswordRetrieval::Available { payload: shared, views_remaining, .. } => {
+                assert_eq!(shared, payload());
+                assert_eq!(views_remaining, 0);
+            }
+            other => panic!("expected the entry, got {:?}", other),
+        }
+
+        assert_eq!(
+            retrieve_share(&service, &link.token, &link.key, None, NOW + 61).unwrap(),
+            SharedPasswordRetrieval::Gone { reason: ShareGoneReason::ViewsExhausted }
+        );
+        assert!(service.get_password_share(&link.token).unwrap().unwrap().ciphertext.is_empty());
+        assert_eq!(
+            retrieve_share(&service, "missing", &link.key, None, NOW).unwrap(),
+            SharedPasswordRetrieval::Gone { reason: ShareGoneReason::NotFound }
+        );
+    }
+
+    #[test]
+    fn test_expired_share_is_gone_and_wiped() {
+        let service = service();
+        let options = SharePasswordOptions { expires_in_hours: 1, max_views: 5, password: None };
+        let link = create_share(&service, &payload(), &options, NOW).unwrap();
+
+        assert!(matches!(
+            retrieve_share(&service, &link.token, &link.key, None, NOW + 3599).unwrap(),
+            SharedPasswordRetrieval::Available { views_remaining: 4, .. }
+        ));
+        let expired = SharedPasswordRetrieval::Gone { reason: ShareGoneReason::Expired };
+        assert_eq!(retrieve_share(&service, &link.token, &link.key, None, NOW + 3600).unwrap(), expired);
+        assert_eq!(retrieve_share(&service, &link.token, &link.key, None, NOW).unwrap(), expired);
+        assert!(service.get_password_share(&link.token).unwrap().unwrap().ciphertext.is_empty());
+
+        let too_long = SharePasswordOptions { expires_in_hours: MAX_EXPIRY_HOURS + 1, ..Default::default() };
+        assert!(create_share(&service, &payload(), &too_long, NOW).is_err());
+    }
+
+    #[test]
+    fn test_server_never_sees_plaintext_or_key() {
+        let service = service();
+        let options = SharePasswordOptions { password: Some("open sesame".to_string()), ..Default::default() };
+        let link = create_share(&service, &payload(), &options, NOW).unwrap();
+
+        let record = service.get_password_share(&link.token).unwrap().unwrap();
+        let stored = serde_json::to_string(&record).unwrap();
+        let raw_key = URL_SAFE_NO_PAD.decode(&link.key).unwrap();
+        for secret in [
+            payload().password,
+            payload().username,
+            link.key.clone(),
+            HEXLOWER.encode(&raw_key),
+            "open sesame".to_string(),
+        ] {
+            assert!(!stored.contains(&secret), "server record leaks {}", secret);
+        }
+        assert!(record.password_protected);
+
+        assert_eq!(
+            retrieve_share(&service, &link.token, &link.key, None, NOW).unwrap(),
+            SharedPasswordRetrieval::PasswordRequired
+        );
+        assert!(retrieve_share(&service, &link.token, &link.key, Some("guess"), NOW).is_err());
+        assert!(matches!(
+            retrieve_share(&service, &link.token, &link.key, Some("open sesame"), NOW).unwrap(),
+            SharedPasswordRetrieval::Available { .. }
+        ));
+    }
+}
@@ -408,8 +408,9 @@ impl ProjectManagementService {
 
             Ok(result["data"].clone())
         } else {
+            let status = response.status();
             let error_text = response.text().await?;
-            Err(anyhow!("Monday.com API error: {}", error_text))
+            Err(anyhow!("Monday.com API error ({}): {}", status, error_text))
         }
     }
 
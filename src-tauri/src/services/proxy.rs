@@ -5,15 +5,33 @@
  * - Proxy pool with health monitoring
  * - Multiple rotation strategies (RoundRobin, Random, LeastUsed)
  * - Health checks (ping, speed test)
- * - Support for HTTP, HTTPS, SOCKS5 proxies
+ * - Support for HTTP, HTTPS, SOCKS5 proxies (with username/password auth)
+ * - Multi-hop proxy chains (entry -> ... -> exit) over CONNECT/SOCKS5 tunnels
  * - Residential and datacenter proxy support
+ * - Geo-targeted selection (country/city) with exit-location validation
  * - Automatic failover on proxy failure
  */
 
+use base64::{engine::general_purpose, Engine as _};
+use log::warn;
 use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 use std::sync::{Arc, RwLock};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Plain-HTTP target for end-to-end chain checks; chains tunnel raw TCP, so the
+/// probe speaks HTTP itself instead of going through reqwest
+const CHAIN_CHECK_URL: &str = "http://www.google.com/generate_204";
+const HOP_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_CONNECT_RESPONSE_BYTES: usize = 8 * 1024;
+/// GeoIP lookup reached through the proxy to learn where it actually exits
+const GEOIP_LOOKUP_URL: &str = "http://ip-api.com/json/?fields=status,message,countryCode,city,as";
+const MAX_GEOIP_RESPONSE_BYTES: usize = 64 * 1024;
+/// How often `spawn_geo_validation` re-checks every proxy's exit location
+pub const GEO_VALIDATION_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProxyConfig {
@@ -22,6 +40,34 @@ pub struct ProxyConfig {
     pub username: Option<String>,
     pub password: Option<String>,
     pub enabled: bool,
+    /// Hops traversed before this proxy, entry first; this proxy is the exit
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chain: Vec<ProxyHop>,
+    /// Advertised exit location; filled from a GeoIP lookup when the provider gives none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geo: Option<ProxyGeo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyHop {
+    pub url: String,
+    pub proxy_type: ProxyType,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl ProxyConfig {
+    /// Every hop a request traverses, entry first and this proxy last
+    pub fn hops(&self) -> Vec<ProxyHop> {
+        let mut hops = self.chain.clone();
+        hops.push(ProxyHop {
+            url: self.url.clone(),
+            proxy_type: self.proxy_type,
+            username: self.username.clone(),
+            password: self.password.clone(),
+        });
+        hops
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -31,6 +77,16 @@ pub enum ProxyType {
     Socks5,
 }
 
+impl ProxyType {
+    fn scheme(&self) -> &'static str {
+        match self {
+            ProxyType::Http => "http",
+            ProxyType::Https => "https",
+            ProxyType::Socks5 => "socks5",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum RotationStrategy {
     RoundRobin,
@@ -48,6 +104,63 @@ pub struct ProxyStats {
     pub last_success: Option<String>,
     pub last_failure: Option<String>,
     pub is_healthy: bool,
+    /// Exit location seen by the last GeoIP validation
+    #[serde(default)]
+    pub observed_geo: Option<ProxyGeo>,
+    /// The observed exit location contradicts the advertised one
+    #[serde(default)]
+    pub geo_mismatch: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProxyGeo {
+    /// ISO 3166-1 alpha-2 country code
+    pub country: Option<String>,
+    pub city: Option<String>,
+    pub asn: Option<u32>,
+}
+
+impl ProxyGeo {
+    /// True when both sides know a field and disagree on it
+    pub fn conflicts_with(&self, other: &ProxyGeo) -> bool {
+        let differs = |a: &Option<String>, b: &Option<String>| match (a, b) {
+            (Some(a), Some(b)) => !a.eq_ignore_ascii_case(b),
+            _ => false,
+        };
+        differs(&self.country, &other.country) || differs(&self.city, &other.city)
+    }
+}
+
+/// What to do when no proxy exits in the requested country
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum GeoFallback {
+    None,
+    #[default]
+    NearestCountry,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum GeoMatch {
+    City,
+    Country,
+    NearestCountry,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeoProxySelection {
+    pub proxy: ProxyConfig,
+    pub matched: GeoMatch,
+    pub country: String,
+    /// Distance between country centroids, for nearest-country fallbacks
+    pub distance_km: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeoVerification {
+    pub url: String,
+    pub advertised: Option<ProxyGeo>,
+    pub observed: ProxyGeo,
+    pub mismatch: bool,
 }
 
 struct ProxyEntry {
@@ -56,6 +169,12 @@ struct ProxyEntry {
     last_check: Option<Instant>,
 }
 
+impl ProxyEntry {
+    fn country(&self) -> Option<String> {
+        self.config.geo.as_ref()?.country.as_ref().map(|c| c.to_ascii_uppercase())
+    }
+}
+
 pub struct ProxyService {
     proxies: Arc<RwLock<HashMap<String, ProxyEntry>>>,
     strategy: Arc<RwLock<RotationStrategy>>,
@@ -78,6 +197,15 @@ impl ProxyService {
 
     /// Add a proxy to the pool
     pub fn add_proxy(&self, config: ProxyConfig) -> Result<(), String> {
+        if !config.chain.is_empty() {
+            for hop in config.hops() {
+                if hop.proxy_type == ProxyType::Https {
+                    return Err(format!("HTTPS proxies cannot be chained: {}", hop.url));
+                }
+                hop_endpoint(&hop)?;
+            }
+        }
+
         let mut proxies = self.proxies.write()
             .map_err(|e| format!("Failed to acquire proxies lock: {}", e))?;
 
@@ -91,6 +219,8 @@ impl ProxyService {
                 last_success: None,
                 last_failure: None,
                 is_healthy: true,
+                observed_geo: None,
+                geo_mismatch: false,
             },
             last_check: None,
         };
@@ -149,6 +279,11 @@ impl ProxyService {
             return Err("No healthy proxies available".to_string());
         }
 
+        Ok(self.pick(&available)?.config.clone())
+    }
+
+    /// Choose among candidate proxies using the rotation strategy
+    fn pick<'a>(&self, available: &[&'a ProxyEntry]) -> Result<&'a ProxyEntry, String> {
         let strategy = self.strategy.read()
             .map_err(|e| format!("Failed to acquire strategy lock: {}", e))?;
 
@@ -178,52 +313,187 @@ impl ProxyService {
             }
         };
 
-        Ok(selected.config.clone())
+        Ok(*selected)
     }
 
-    /// Check proxy health
-    pub async fn check_proxy_health(&self, url: String) -> Result<bool, String> {
-        let test_url = "https://www.google.com";
-        
-        // Build proxy URL with auth if needed
+    /// Get next healthy proxy exiting in `country` (ISO code), preferring `city`.
+    /// Proxies flagged as mislabeled are skipped. With `GeoFallback::NearestCountry`
+    /// the pool falls back to the country whose centroid is closest.
+    pub fn get_next_proxy_geo(
+        &self,
+        country: &str,
+        city: Option<&str>,
+        fallback: GeoFallback,
+    ) -> Result<GeoProxySelection, String> {
+        let country = country.trim().to_ascii_uppercase();
+        let proxies = self.proxies.read()
+            .map_err(|e| format!("Failed to acquire proxies lock: {}", e))?;
+
+        let available: Vec<&ProxyEntry> = proxies.values()
+            .filter(|p| p.config.enabled && p.stats.is_healthy && !p.stats.geo_mismatch)
+            .collect();
+        let in_country: Vec<&ProxyEntry> = available.iter()
+            .copied()
+            .filter(|p| p.country().as_deref() == Some(country.as_str()))
+            .collect();
+
+        let selection = |entry: &ProxyEntry, matched, distance_km| GeoProxySelection {
+            proxy: entry.config.clone(),
+            matched,
+            country: entry.country().unwrap_or_default(),
+            distance_km,
+        };
+
+        if let Some(city) = city.map(str::trim).filter(|c| !c.is_empty()) {
+            let in_city: Vec<&ProxyEntry> = in_country.iter()
+                .copied()
+                .filter(|p| p.config.geo.as_ref()
+                    .and_then(|g| g.city.as_deref())
+                    .is_some_and(|c| c.eq_ignore_ascii_case(city)))
+                .collect();
+            if !in_city.is_empty() {
+                return Ok(selection(self.pick(&in_city)?, GeoMatch::City, None));
+            }
+        }
+        if !in_country.is_empty() {
+            return Ok(selection(self.pick(&in_country)?, GeoMatch::Country, None));
+        }
+
+        if fallback == GeoFallback::None {
+            return Err(format!("No healthy proxies in {}", country));
+        }
+        let origin = country_centroid(&country)
+            .ok_or_else(|| format!("No healthy proxies in {} and its location is unknown", country))?;
+
+        let nearest = available.iter()
+            .filter_map(|p| {
+                let code = p.country()?;
+                Some((haversine_km(origin, country_centroid(&code)?), code))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .ok_or_else(|| format!("No healthy proxies in {} or any other known country", country))?;
+
+        let candidates: Vec<&ProxyEntry> = available.iter()
+            .copied()
+            .filter(|p| p.country().as_deref() == Some(nearest.1.as_str()))
+            .collect();
+        Ok(selection(self.pick(&candidates)?, GeoMatch::NearestCountry, Some(nearest.0)))
+    }
+
+    /// Look up where a proxy actually exits and flag it when that contradicts
+    /// its advertised location. Proxies without geo metadata adopt the lookup.
+    pub async fn verify_exit_location(&self, url: String) -> Result<GeoVerification, String> {
         let proxy_config = {
             let proxies = self.proxies.read()
                 .map_err(|e| format!("Failed to acquire proxies lock: {}", e))?;
-            
+
             proxies.get(&url)
                 .ok_or_else(|| format!("Proxy not found: {}", url))?
                 .config.clone()
         };
 
-        let proxy_url = if let (Some(user), Some(pass)) = (proxy_config.username, proxy_config.password) {
-            format!("{}://{}:{}@{}", 
-                match proxy_config.proxy_type {
-                    ProxyType::Http => "http",
-                    ProxyType::Https => "https",
-                    ProxyType::Socks5 => "socks5",
-                },
-                user, pass, url
-            )
+        let body = if proxy_config.chain.is_empty() {
+            let proxy = reqwest::Proxy::all(proxy_url(&proxy_config.hops()[0])?)
+                .map_err(|e| format!("Invalid proxy URL: {}", e))?;
+            let client = reqwest::Client::builder()
+                .proxy(proxy)
+                .timeout(Duration::from_secs(10))
+                .build()
+                .map_err(|e| format!("Failed to build client: {}", e))?;
+
+            client.get(GEOIP_LOOKUP_URL).send().await
+                .map_err(|e| format!("GeoIP lookup through {} failed: {}", url, e))?
+                .text().await
+                .map_err(|e| format!("GeoIP lookup through {} failed: {}", url, e))?
         } else {
-            url.clone()
+            fetch_via_chain(&proxy_config.hops(), GEOIP_LOOKUP_URL).await?
         };
+        let observed = parse_geoip_response(&body)?;
+        let mismatch = proxy_config.geo.as_ref().is_some_and(|geo| geo.conflicts_with(&observed));
 
-        // Create client with proxy
-        let proxy = reqwest::Proxy::all(&proxy_url)
-            .map_err(|e| format!("Invalid proxy URL: {}", e))?;
+        let mut proxies = self.proxies.write()
+            .map_err(|e| format!("Failed to acquire proxies lock: {}", e))?;
+
+        if let Some(entry) = proxies.get_mut(&url) {
+            if entry.config.geo.is_none() {
+                entry.config.geo = Some(observed.clone());
+            }
+            entry.stats.observed_geo = Some(observed.clone());
+            entry.stats.geo_mismatch = mismatch;
+        }
+        if mismatch {
+            warn!("Proxy {} advertises {:?} but exits in {:?}", url, proxy_config.geo, observed);
+        }
+
+        Ok(GeoVerification {
+            url,
+            advertised: proxy_config.geo,
+            observed,
+            mismatch,
+        })
+    }
+
+    /// Re-validate every enabled proxy's exit location on a fixed interval
+    pub fn spawn_geo_validation(self: Arc<Self>, interval: Duration) {
+        tauri::async_runtime::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let urls: Vec<String> = match self.list_proxies() {
+                    Ok(proxies) => proxies.into_iter()
+                        .filter(|(config, _)| config.enabled)
+                        .map(|(config, _)| config.url)
+                        .collect(),
+                    Err(_) => continue,
+                };
+                for url in urls {
+                    if let Err(e) = self.verify_exit_location(url.clone()).await {
+                        warn!("Exit location check for {} failed: {}", url, e);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Check proxy health
+    pub async fn check_proxy_health(&self, url: String) -> Result<bool, String> {
+        self.check_proxy_health_via(url, CHAIN_CHECK_URL).await
+    }
 
-        let client = reqwest::Client::builder()
-            .proxy(proxy)
-            .timeout(Duration::from_secs(10))
-            .build()
-            .map_err(|e| format!("Failed to build client: {}", e))?;
+    /// Check proxy health against a specific test URL. Chains are validated
+    /// end-to-end, and a failure at any hop marks the whole chain unhealthy.
+    pub async fn check_proxy_health_via(&self, url: String, test_url: &str) -> Result<bool, String> {
+        let proxy_config = {
+            let proxies = self.proxies.read()
+                .map_err(|e| format!("Failed to acquire proxies lock: {}", e))?;
+            
+            proxies.get(&url)
+                .ok_or_else(|| format!("Proxy not found: {}", url))?
+                .config.clone()
+        };
 
-        // Test request
         let start = Instant::now();
-        let result = client.get(test_url).send().await;
-        let duration = start.elapsed();
+        let is_healthy = if proxy_config.chain.is_empty() {
+            let proxy = reqwest::Proxy::all(proxy_url(&proxy_config.hops()[0])?)
+                .map_err(|e| format!("Invalid proxy URL: {}", e))?;
 
-        let is_healthy = result.is_ok();
+            let client = reqwest::Client::builder()
+                .proxy(proxy)
+                .timeout(Duration::from_secs(10))
+                .build()
+                .map_err(|e| format!("Failed to build client: {}", e))?;
+
+            client.get(test_url).send().await.is_ok()
+        } else {
+            match probe_chain(&proxy_config.hops(), test_url).await {
+                Ok(_) => true,
+                Err(e) => {
+                    warn!("Proxy chain {} unhealthy: {}", url, e);
+                    false
+                }
+            }
+        };
+        let duration = start.elapsed();
 
         // Update stats
         let mut proxies = self.proxies.write()
@@ -309,3 +579,558 @@ impl ProxyService {
             .ok_or_else(|| format!("Proxy not found: {}", url))
     }
 }
+
+// ============================================================================
+// GEO TARGETING
+// ============================================================================
+
+/// Approximate country centroids (lat, lon) used for nearest-country fallback
+const COUNTRY_CENTROIDS: &[(&str, f64, f64)] = &[
+    ("AE", 23.4, 53.8), ("AR", -38.4, -63.6), ("AT", 47.5, 14.6), ("AU", -25.3, 133.8),
+    ("BD", 23.7, 90.4), ("BE", 50.5, 4.5), ("BG", 42.7, 25.5), ("BR", -14.2, -51.9),
+    ("CA", 56.1, -106.3), ("CH", 46.8, 8.2), ("CL", -35.7, -71.5), ("CN", 35.9, 104.2),
+    ("CO", 4.6, -74.3), ("CZ", 49.8, 15.5), ("DE", 51.2, 10.5), ("DK", 56.3, 9.5),
+    ("EG", 26.8, 30.8), ("ES", 40.5, -3.7), ("FI", 61.9, 25.7), ("FR", 46.2, 2.2),
+    ("GB", 55.4, -3.4), ("GR", 39.1, 21.8), ("HK", 22.3, 114.2), ("HU", 47.2, 19.5),
+    ("ID", -0.8, 113.9), ("IE", 53.4, -8.2), ("IL", 31.0, 34.9), ("IN", 20.6, 79.0),
+    ("IT", 41.9, 12.6), ("JP", 36.2, 138.3), ("KE", -0.0, 37.9), ("KR", 35.9, 127.8),
+    ("MX", 23.6, -102.6), ("MY", 4.2, 102.0), ("NG", 9.1, 8.7), ("NL", 52.1, 5.3),
+    ("NO", 60.5, 8.5), ("NZ", -40.9, 174.9), ("PE", -9.2, -75.0), ("PH", 12.9, 121.8),
+    ("PK", 30.4, 69.3), ("PL", 51.9, 19.1), ("PT", 39.4, -8.2), ("RO", 45.9, 25.0),
+    ("RS", 44.0, 21.0), ("RU", 61.5, 105.3), ("SA", 23.9, 45.1), ("SE", 60.1, 18.6),
+    ("SG", 1.4, 103.8), ("TH", 15.9, 101.0), ("TR", 39.0, 35.2), ("TW", 23.7, 121.0),
+    ("UA", 48.4, 31.2), ("US", 37.1, -95.7), ("VE", 6.4, -66.6), ("VN", 14.1, 108.3),
+    ("ZA", -30.6, 22.9),
+];
+
+fn country_centroid(code: &str) -> Option<(f64, f64)> {
+    COUNTRY_CENTROIDS.iter()
+        .find(|(c, _, _)| c.eq_ignore_ascii_case(code))
+        .map(|(_, lat, lon)| (*lat, *lon))
+}
+
+/// Great-circle distance in kilometres
+fn haversine_km(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (a.0.to_radians(), a.1.to_radians());
+    let (lat2, lon2) = (b.0.to_radians(), b.1.to_radians());
+    let h = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * 6371.0 * h.sqrt().asin()
+}
+
+/// Parses the ip-api.com response; `as` looks like "AS15169 Google LLC"
+fn parse_geoip_response(body: &str) -> Result<ProxyGeo, String> {
+    let value: serde_json::Value = serde_json::from_str(body)
+        .map_err(|e| format!("Invalid GeoIP response: {}", e))?;
+    if value["status"].as_str() != Some("success") {
+        return Err(format!(
+            "GeoIP lookup failed: {}",
+            value["message"].as_str().unwrap_or("unknown error")
+        ));
+    }
+
+    let text = |key: &str| value[key].as_str().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+    Ok(ProxyGeo {
+        country: text("countryCode").map(|c| c.to_ascii_uppercase()),
+        city: text("city"),
+        asn: text("as").and_then(|v| {
+            v.split_whitespace().next()?.trim_start_matches("AS").parse().ok()
+        }),
+    })
+}
+
+// ============================================================================
+// PROXY CHAINING
+// ============================================================================
+
+/// Parsed hop URL; URLs may omit the scheme, which then follows `proxy_type`
+fn hop_url(hop: &ProxyHop) -> Result<url::Url, String> {
+    let raw = if hop.url.contains("://") {
+        hop.url.clone()
+    } else {
+        format!("{}://{}", hop.proxy_type.scheme(), hop.url)
+    };
+    url::Url::parse(&raw).map_err(|e| format!("Invalid proxy URL {}: {}", hop.url, e))
+}
+
+fn hop_endpoint(hop: &ProxyHop) -> Result<(String, u16), String> {
+    let parsed = hop_url(hop)?;
+    let host = parsed.host_str()
+        .ok_or_else(|| format!("Proxy URL has no host: {}", hop.url))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = parsed.port_or_known_default().unwrap_or(match hop.proxy_type {
+        ProxyType::Socks5 => 1080,
+        _ => 8080,
+    });
+    Ok((host, port))
+}
+
+/// Proxy URL with credentials embedded, as reqwest expects
+fn proxy_url(hop: &ProxyHop) -> Result<String, String> {
+    let mut url = hop_url(hop)?;
+    if let (Some(user), Some(pass)) = (&hop.username, &hop.password) {
+        url.set_username(user).map_err(|_| format!("Invalid proxy username for {}", hop.url))?;
+        url.set_password(Some(pass)).map_err(|_| format!("Invalid proxy password for {}", hop.url))?;
+    }
+    Ok(url.to_string())
+}
+
+/// SOCKS5 method selection offering no-auth and, with credentials, username/password
+pub(crate) fn socks5_greeting(with_auth: bool) -> Vec<u8> {
+    if with_auth {
+        vec![0x05, 0x02, 0x00, 0x02]
+    } else {
+        vec![0x05, 0x01, 0x00]
+    }
+}
+
+/// Username/password sub-negotiation request (RFC 1929)
+pub(crate) fn socks5_auth_request(username: &str, password: &str) -> Result<Vec<u8>, String> {
+    if username.is_empty() || username.len() > 255 || password.len() > 255 {
+        return Err("SOCKS5 username must be 1-255 bytes and password at most 255 bytes".to_string());
+    }
+    let mut request = vec![0x01, username.len() as u8];
+    request.extend_from_slice(username.as_bytes());
+    request.push(password.len() as u8);
+    request.extend_from_slice(password.as_bytes());
+    Ok(request)
+}
+
+/// CONNECT request; hostnames are sent as-is so the proxy resolves them
+pub(crate) fn socks5_connect_request(host: &str, port: u16) -> Result<Vec<u8>, String> {
+    let mut request = vec![0x05, 0x01, 0x00];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(0x01);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(0x04);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            if host.is_empty() || host.len() > 255 {
+                return Err(format!("Invalid SOCKS5 target host: {}", host));
+            }
+            request.push(0x03);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    Ok(request)
+}
+
+fn socks5_reply_error(code: u8) -> &'static str {
+    match code {
+        0x01 => "general failure",
+        0x02 => "connection not allowed by ruleset",
+        0x03 => "network unreachable",
+        0x04 => "host unreachable",
+        0x05 => "connection refused",
+        0x06 => "TTL expired",
+        0x07 => "command not supported",
+        0x08 => "address type not supported",
+        _ => "unknown error",
+    }
+}
+
+async fn socks5_handshake(stream: &mut TcpStream, hop: &ProxyHop, host: &str, port: u16) -> Result<(), String> {
+    let credentials = hop.username.as_deref().zip(hop.password.as_deref());
+    stream.write_all(&socks5_greeting(credentials.is_some())).await.map_err(|e| e.to_string())?;
+
+    let mut choice = [0u8; 2];
+    stream.read_exact(&mut choice).await.map_err(|e| e.to_string())?;
+    if choice[0] != 0x05 {
+        return Err("not a SOCKS5 proxy".to_string());
+    }
+    match (choice[1], credentials) {
+        (0x00, _) => {}
+        (0x02, Some((user, pass))) => {
+            stream.write_all(&socks5_auth_request(user, pass)?).await.map_err(|e| e.to_string())?;
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).await.map_err(|e| e.to_string())?;
+            if status[1] != 0x00 {
+                return Err("SOCKS5 authentication rejected".to_string());
+            }
+        }
+        _ => return Err("no acceptable SOCKS5 authentication method".to_string()),
+    }
+
+    stream.write_all(&socks5_connect_request(host, port)?).await.map_err(|e| e.to_string())?;
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply).await.map_err(|e| e.to_string())?;
+    if reply[1] != 0x00 {
+        return Err(format!("SOCKS5 connect failed: {}", socks5_reply_error(reply[1])));
+    }
+    // Skip the bound address and port
+    let address_len = match reply[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => stream.read_u8().await.map_err(|e| e.to_string())? as usize,
+        other => return Err(format!("invalid SOCKS5 address type: {}", other)),
+    };
+    let mut bound = vec![0u8; address_len + 2];
+    stream.read_exact(&mut bound).await.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn http_connect_handshake(stream: &mut TcpStream, hop: &ProxyHop, host: &str, port: u16) -> Result<(), String> {
+    let authority = if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    };
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+    if let (Some(user), Some(pass)) = (&hop.username, &hop.password) {
+        let token = general_purpose::STANDARD.encode(format!("{}:{}", user, pass));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await.map_err(|e| e.to_string())?;
+
+    // Read byte by byte so nothing past the headers is consumed from the tunnel
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_CONNECT_RESPONSE_BYTES {
+            return Err("CONNECT response too large".to_string());
+        }
+        response.push(stream.read_u8().await.map_err(|e| e.to_string())?);
+    }
+    let status_line = String::from_utf8_lossy(&response);
+    let status_line = status_line.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if !status.starts_with('2') {
+        return Err(format!("CONNECT rejected: {}", status_line));
+    }
+    Ok(())
+}
+
+/// Opens a TCP tunnel to `host:port` through every hop in order
+pub async fn connect_chain(hops: &[ProxyHop], host: &str, port: u16) -> Result<TcpStream, String> {
+    let entry = hops.first().ok_or("Proxy chain is empty")?;
+    let (entry_host, entry_port) = hop_endpoint(entry)?;
+    let mut stream = tokio::time::timeout(HOP_TIMEOUT, TcpStream::connect((entry_host.as_str(), entry_port)))
+        .await
+        .map_err(|_| format!("Hop 1 ({}) timed out", entry.url))?
+        .map_err(|e| format!("Hop 1 ({}) unreachable: {}", entry.url, e))?;
+
+    for (i, hop) in hops.iter().enumerate() {
+        let (next_host, next_port) = match hops.get(i + 1) {
+            Some(next) => hop_endpoint(next)?,
+            None => (host.to_string(), port),
+        };
+        let handshake = async {
+            match hop.proxy_type {
+                ProxyType::Socks5 => socks5_handshake(&mut stream, hop, &next_host, next_port).await,
+                ProxyType::Http => http_connect_handshake(&mut stream, hop, &next_host, next_port).await,
+                ProxyType::Https => Err("HTTPS proxies cannot be chained".to_string()),
+            }
+        };
+        tokio::time::timeout(HOP_TIMEOUT, handshake)
+            .await
+            .map_err(|_| format!("Hop {} ({}) timed out", i + 1, hop.url))?
+            .map_err(|e| format!("Hop {} ({}) failed: {}", i + 1, hop.url, e))?;
+    }
+
+    Ok(stream)
+}
+
+/// Sends a GET for `test_url` through the chain and returns the response status line
+pub async fn probe_chain(hops: &[ProxyHop], test_url: &str) -> Result<String, String> {
+    let target = url::Url::parse(test_url).map_err(|e| format!("Invalid test URL: {}", e))?;
+    if target.scheme() != "http" {
+        return Err("Chain health checks need a plain http:// test URL".to_string());
+    }
+    let host = target.host_str().ok_or("Test URL has no host")?;
+    let port = target.port_or_known_default().unwrap_or(80);
+
+    let mut stream = connect_chain(hops, host.trim_start_matches('[').trim_end_matches(']'), port).await?;
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        &target[url::Position::BeforePath..url::Position::AfterQuery],
+        host
+    );
+    stream.write_all(request.as_bytes()).await.map_err(|e| format!("Target request failed: {}", e))?;
+
+    let mut response = Vec::new();
+    tokio::time::timeout(HOP_TIMEOUT, async {
+        let mut buf = [0u8; 512];
+        while !response.contains(&b'\n') {
+            let n = stream.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            response.extend_from_slice(&buf[..n]);
+        }
+        Ok::<_, std::io::Error>(())
+    })
+    .await
+    .map_err(|_| "Target did not respond".to_string())?
+    .map_err(|e| format!("Target response failed: {}", e))?;
+
+    let status_line = String::from_utf8_lossy(&response).lines().next().unwrap_or_default().to_string();
+    if !status_line.starts_with("HTTP/") {
+        return Err("Target did not return an HTTP response".to_string());
+    }
+    Ok(status_line)
+}
+
+/// GETs a plain-HTTP URL through the chain and returns the response body
+async fn fetch_via_chain(hops: &[ProxyHop], url: &str) -> Result<String, String> {
+    let target = url::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    let host = target.host_str().ok_or("URL has no host")?;
+    let port = target.port_or_known_default().unwrap_or(80);
+
+    let mut stream = connect_chain(hops, host, port).await?;
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        &target[url::Position::BeforePath..url::Position::AfterQuery],
+        host
+    );
+    stream.write_all(request.as_bytes()).await.map_err(|e| format!("Request failed: {}", e))?;
+
+    let mut response = Vec::new();
+    tokio::time::timeout(HOP_TIMEOUT, (&mut stream).take(MAX_GEOIP_RESPONSE_BYTES as u64).read_to_end(&mut response))
+        .await
+        .map_err(|_| "Target did not respond".to_string())?
+        .map_err(|e| format!("Response failed: {}", e))?;
+
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response.split_once("\r\n\r\n").ok_or("Malformed HTTP response")?;
+    let status = head.lines().next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(format!("Unexpected response: {}", status));
+    }
+    Ok(body.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    async fn read_headers(stream: &mut TcpStream) -> String {
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.unwrap());
+        }
+        String::from_utf8(head).unwrap()
+    }
+
+    /// Answers one HTTP request with its request line as the body
+    async fn spawn_echo_server() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let head = read_headers(&mut stream).await;
+                let body = head.lines().next().unwrap().to_string();
+                let response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        port
+    }
+
+    /// SOCKS5 server that requires alice/secret
+    async fn spawn_socks5_server() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut client, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut header = [0u8; 2];
+                    client.read_exact(&mut header).await.unwrap();
+                    let mut methods = vec![0u8; header[1] as usize];
+                    client.read_exact(&mut methods).await.unwrap();
+                    if !methods.contains(&0x02) {
+                        client.write_all(&[0x05, 0xFF]).await.unwrap();
+                        return;
+                    }
+                    client.write_all(&[0x05, 0x02]).await.unwrap();
+
+                    let version = client.read_u8().await.unwrap();
+                    assert_eq!(version, 0x01);
+                    let mut user = vec![0u8; client.read_u8().await.unwrap() as usize];
+                    client.read_exact(&mut user).await.unwrap();
+                    let mut pass = vec![0u8; client.read_u8().await.unwrap() as usize];
+                    client.read_exact(&mut pass).await.unwrap();
+                    if user != b"alice" || pass != b"secret" {
+                        client.write_all(&[0x01, 0x01]).await.unwrap();
+                        return;
+                    }
+                    client.write_all(&[0x01, 0x00]).await.unwrap();
+
+                    let mut request = [0u8; 4];
+                    client.read_exact(&mut request).await.unwrap();
+                    let host = match request[3] {
+                        0x01 => {
+                            let mut ip = [0u8; 4];
+                            client.read_exact(&mut ip).await.unwrap();
+                            std::net::Ipv4Addr::from(ip).to_string()
+                        }
+                        0x03 => {
+                            let mut name = vec![0u8; client.read_u8().await.unwrap() as usize];
+                            client.read_exact(&mut name).await.unwrap();
+                            String::from_utf8(name).unwrap()
+                        }
+                        other => panic!("unexpected address type {}", other),
+                    };
+                    let port = client.read_u16().await.unwrap();
+                    let mut upstream = TcpStream::connect((host.as_str(), port)).await.unwrap();
+                    client.write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]).await.unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+                });
+            }
+        });
+        port
+    }
+
+    /// HTTP CONNECT proxy without authentication
+    async fn spawn_connect_server() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut client, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let head = read_headers(&mut client).await;
+                    let authority = head.split_whitespace().nth(1).unwrap().to_string();
+                    let mut upstream = TcpStream::connect(authority).await.unwrap();
+                    client.write_all(b"HTTP/1.1 200 Connection established\r\n\r\n").await.unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut client, &mut upstream).await;
+                });
+            }
+        });
+        port
+    }
+
+    #[test]
+    fn test_socks5_handshake_encoding() {
+        assert_eq!(socks5_greeting(false), vec![0x05, 0x01, 0x00]);
+        assert_eq!(socks5_greeting(true), vec![0x05, 0x02, 0x00, 0x02]);
+
+        assert_eq!(
+            socks5_auth_request("alice", "secret").unwrap(),
+            [&[0x01, 5][..], b"alice", &[6], b"secret"].concat()
+        );
+        assert!(socks5_auth_request("", "secret").is_err());
+        assert!(socks5_auth_request("alice", &"x".repeat(256)).is_err());
+
+        assert_eq!(
+            socks5_connect_request("10.0.0.1", 443).unwrap(),
+            vec![0x05, 0x01, 0x00, 0x01, 10, 0, 0, 1, 0x01, 0xBB]
+        );
+        assert_eq!(
+            socks5_connect_request("example.com", 80).unwrap(),
+            [&[0x05, 0x01, 0x00, 0x03, 11][..], b"example.com", &[0x00, 0x50]].concat()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_two_hop_chain_reaches_target() {
+        let echo_port = spawn_echo_server().await;
+        let socks_port = spawn_socks5_server().await;
+        let connect_port = spawn_connect_server().await;
+        let test_url = format!("http://127.0.0.1:{}/echo?hop=2", echo_port);
+
+        let chained = |password: &str| ProxyConfig {
+            url: format!("http://127.0.0.1:{}", connect_port),
+            proxy_type: ProxyType::Http,
+            username: None,
+            password: None,
+            enabled: true,
+            chain: vec![ProxyHop {
+                url: format!("127.0.0.1:{}", socks_port),
+                proxy_type: ProxyType::Socks5,
+                username: Some("alice".to_string()),
+                password: Some(password.to_string()),
+            }],
+            geo: None,
+        };
+
+        let status = probe_chain(&chained("secret").hops(), &test_url).await.unwrap();
+        assert_eq!(status, "HTTP/1.1 200 OK");
+
+        let service = ProxyService::new();
+        service.add_proxy(chained("secret")).unwrap();
+        let exit_url = format!("http://127.0.0.1:{}", connect_port);
+        assert!(service.check_proxy_health_via(exit_url.clone(), &test_url).await.unwrap());
+
+        // A rejected entry hop takes the whole chain out of rotation
+        let err = probe_chain(&chained("wrong").hops(), &test_url).await.unwrap_err();
+        assert!(err.starts_with("Hop 1"), "{}", err);
+        assert!(err.contains("authentication rejected"), "{}", err);
+
+        service.add_proxy(chained("wrong")).unwrap();
+        assert!(!service.check_proxy_health_via(exit_url.clone(), &test_url).await.unwrap());
+        assert!(!service.get_proxy_stats(exit_url).unwrap().is_healthy);
+        assert!(service.get_next_proxy().is_err());
+    }
+
+    fn geo_proxy(url: &str, country: &str, city: &str) -> ProxyConfig {
+        ProxyConfig {
+            url: url.to_string(),
+            proxy_type: ProxyType::Http,
+            username: None,
+            password: None,
+            enabled: true,
+            chain: Vec::new(),
+            geo: Some(ProxyGeo {
+                country: Some(country.to_string()),
+                city: Some(city.to_string()),
+                asn: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_geo_selection_filters_and_falls_back() {
+        let service = ProxyService::new();
+        service.add_proxy(geo_proxy("http://de-1:8080", "DE", "Berlin")).unwrap();
+        service.add_proxy(geo_proxy("http://de-2:8080", "de", "Munich")).unwrap();
+        service.add_proxy(geo_proxy("http://fr-1:8080", "FR", "Paris")).unwrap();
+        service.add_proxy(geo_proxy("http://us-1:8080", "US", "Dallas")).unwrap();
+
+        for _ in 0..6 {
+            let selection = service.get_next_proxy_geo("de", None, GeoFallback::NearestCountry).unwrap();
+            assert_eq!(selection.matched, GeoMatch::Country);
+            assert_eq!(selection.country, "DE");
+            assert!(selection.proxy.url.starts_with("http://de-"));
+        }
+
+        let selection = service.get_next_proxy_geo("DE", Some("munich"), GeoFallback::NearestCountry).unwrap();
+        assert_eq!(selection.matched, GeoMatch::City);
+        assert_eq!(selection.proxy.url, "http://de-2:8080");
+
+        // No Spanish proxies: France is the closest country with one
+        let selection = service.get_next_proxy_geo("ES", None, GeoFallback::NearestCountry).unwrap();
+        assert_eq!(selection.matched, GeoMatch::NearestCountry);
+        assert_eq!(selection.proxy.url, "http://fr-1:8080");
+        assert!(selection.distance_km.unwrap() < 1000.0);
+
+        assert!(service.get_next_proxy_geo("ES", None, GeoFallback::None).is_err());
+
+        // Mislabeled proxies are never handed out for geo targeting
+        service.proxies.write().unwrap().get_mut("http://fr-1:8080").unwrap().stats.geo_mismatch = true;
+        let selection = service.get_next_proxy_geo("ES", None, GeoFallback::NearestCountry).unwrap();
+        assert_eq!(selection.country, "DE");
+    }
+
+    #[test]
+    fn test_geoip_response_parsing_and_mismatch() {
+        let observed = parse_geoip_response(
+            r#"{"status":"success","countryCode":"nl","city":"Amsterdam","as":"AS60781 LeaseWeb"}"#,
+        ).unwrap();
+        assert_eq!(observed.country.as_deref(), Some("NL"));
+        assert_eq!(observed.asn, Some(60781));
+
+        let advertised = geo_proxy("http://x:1", "DE", "Berlin").geo.unwrap();
+        assert!(advertised.conflicts_with(&observed));
+        assert!(!ProxyGeo { country: Some("nl".to_string()), ..Default::default() }.conflicts_with(&observed));
+
+        assert!(parse_geoip_response(r#"{"status":"fail","message":"private range"}"#).is_err());
+    }
+}
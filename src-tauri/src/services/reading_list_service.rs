@@ -1,6 +1,7 @@
-use crate::models::reading_list::{Article, ArticleStats, ArticleFilter};
+use crate::models::reading_list::{Article, ArticleStats, ArticleFilter, ReadingListTag};
 use log::info;
 use rusqlite::{params, Connection, Result as SqliteResult};
+use std::collections::{BTreeMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 pub struct ReadingListService {
@@ -46,6 +47,9 @@ impl ReadingListService {
             )",
             [],
         ).map_err(|e| format!("Failed to create articles table: {}", e))?;
+
+        // Added after the first release; fails harmlessly once the column exists
+        let _ = conn.execute("ALTER TABLE articles ADD COLUMN thumbnail_placeholder TEXT", []);
         
         // Create indexes
         conn.execute(
@@ -73,7 +77,7 @@ impl ReadingListService {
         let mut stmt = conn.prepare(
             "SELECT id, url, title, author, excerpt, content, thumbnail, tags,
                     reading_time_minutes, progress_percentage, is_read, is_favorite,
-                    added_at, read_at, last_opened_at
+                    added_at, read_at, last_opened_at, thumbnail_placeholder
              FROM articles
              ORDER BY added_at DESC"
         ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
@@ -98,6 +102,7 @@ impl ReadingListService {
                 added_at: row.get(12)?,
                 read_at: row.get(13)?,
                 last_opened_at: row.get(14)?,
+                thumbnail_placeholder: row.get(15)?,
             })
         }).map_err(|e| format!("Failed to query articles: {}", e))?
         .collect::<SqliteResult<Vec<_>>>()
@@ -113,7 +118,7 @@ impl ReadingListService {
         let mut stmt = conn.prepare(
             "SELECT id, url, title, author, excerpt, content, thumbnail, tags,
                     reading_time_minutes, progress_percentage, is_read, is_favorite,
-                    added_at, read_at, last_opened_at
+                    added_at, read_at, last_opened_at, thumbnail_placeholder
              FROM articles WHERE id = ?"
         ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
         
@@ -137,6 +142,7 @@ impl ReadingListService {
                 added_at: row.get(12)?,
                 read_at: row.get(13)?,
                 last_opened_at: row.get(14)?,
+                thumbnail_placeholder: row.get(15)?,
             })
         });
         
@@ -157,8 +163,8 @@ impl ReadingListService {
         conn.execute(
             "INSERT INTO articles (id, url, title, author, excerpt, content, thumbnail, tags,
                                   reading_time_minutes, progress_percentage, is_read, is_favorite,
-                                  added_at, read_at, last_opened_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                                  added_at, read_at, last_opened_at, thumbnail_placeholder)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             params![
                 article.id,
                 article.url,
@@ -175,6 +181,7 @@ impl ReadingListService {
                 article.added_at,
                 article.read_at,
                 article.last_opened_at,
+                article.thumbnail_placeholder,
             ],
         ).map_err(|e| format!("Failed to insert article: {}", e))?;
         
@@ -192,7 +199,7 @@ impl ReadingListService {
             "UPDATE articles SET url = ?, title = ?, author = ?, excerpt = ?, content = ?,
                                 thumbnail = ?, tags = ?, reading_time_minutes = ?,
                                 progress_percentage = ?, is_read = ?, is_favorite = ?,
-                                read_at = ?, last_opened_at = ?
+                                read_at = ?, last_opened_at = ?, thumbnail_placeholder = ?
              WHERE id = ?",
             params![
                 article.url,
@@ -208,6 +215,7 @@ impl ReadingListService {
                 article.is_favorite as i32,
                 article.read_at,
                 article.last_opened_at,
+                article.thumbnail_placeholder,
                 article.id,
             ],
         ).map_err(|e| format!("Failed to update article: {}", e))?;
@@ -302,7 +310,7 @@ impl ReadingListService {
         let mut query = String::from(
             "SELECT id, url, title, author, excerpt, content, thumbnail, tags,
                     reading_time_minutes, progress_percentage, is_read, is_favorite,
-                    added_at, read_at, last_opened_at
+                    added_at, read_at, last_opened_at, thumbnail_placeholder
              FROM articles WHERE 1=1"
         );
         
@@ -320,11 +328,6 @@ impl ReadingListService {
             query.push_str(" AND is_favorite = 1");
         }
         
-        if let Some(tag) = &filter.tag {
-            query.push_str(" AND tags LIKE ?");
-            params.push(Box::new(format!("%\"{}\":%", tag)));
-        }
-        
         if let Some(search) = &filter.search_query {
             query.push_str(" AND (title LIKE ? OR author LIKE ? OR excerpt LIKE ? OR content LIKE ?)");
             let search_pattern = format!("%{}%", search);
@@ -361,12 +364,28 @@ impl ReadingListService {
                 added_at: row.get(12)?,
                 read_at: row.get(13)?,
                 last_opened_at: row.get(14)?,
+                thumbnail_placeholder: row.get(15)?,
             })
         }).map_err(|e| format!("Failed to query articles: {}", e))?
         .collect::<SqliteResult<Vec<_>>>()
         .map_err(|e| format!("Failed to collect articles: {}", e))?;
         
-        Ok(articles)
+        // Tags are matched here rather than in SQL so nested tags can include
+        // their descendants
+        let selected: Vec<String> = filter.tag.iter().filter_map(|tag| normalize_tag(tag)).collect();
+        Ok(articles.into_iter()
+            .filter(|article| matches_tags(&article.tags, &selected, true))
+            .collect())
+    }
+    
+    /// Articles carrying any (or, with `match_all`, every) one of `tags`.
+    /// Selecting a parent tag also matches the tags nested under it.
+    pub fn search_by_tags(&self, tags: &[String], match_all: bool) -> Result<Vec<Article>, String> {
+        let selected: Vec<String> = tags.iter().filter_map(|tag| normalize_tag(tag)).collect();
+        Ok(self.get_all_articles()?
+            .into_iter()
+            .filter(|article| matches_tags(&article.tags, &selected, match_all))
+            .collect())
     }
     
     pub fn get_stats(&self) -> Result<ArticleStats, String> {
@@ -412,11 +431,11 @@ impl ReadingListService {
         })
     }
     
-    pub fn get_all_tags(&self) -> Result<Vec<String>, String> {
+    pub fn get_all_tags(&self) -> Result<Vec<ReadingListTag>, String> {
         let conn = self.conn.lock()
             .map_err(|e| format!("Failed to acquire lock: {}", e))?;
         
-        let mut stmt = conn.prepare("SELECT DISTINCT tags FROM articles")
+        let mut stmt = conn.prepare("SELECT tags FROM articles")
             .map_err(|e| format!("Failed to prepare statement: {}", e))?;
         
         let tags_sets = stmt.query_map([], |row| {
@@ -427,10 +446,168 @@ impl ReadingListService {
         .collect::<SqliteResult<Vec<_>>>()
         .map_err(|e| format!("Failed to collect tags: {}", e))?;
         
-        let mut all_tags: Vec<String> = tags_sets.into_iter().flatten().collect();
-        all_tags.sort();
-        all_tags.dedup();
-        
-        Ok(all_tags)
+        Ok(count_tags(&tags_sets))
+    }
+}
+
+/// Lowercases a tag and trims each `/`-separated segment, dropping empty
+/// ones, so " Tech / Rust " and "tech/rust" are the same tag
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let segments: Vec<String> = tag
+        .split('/')
+        .map(|segment| segment.trim().to_lowercase())
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    if segments.is_empty() {
+        None
+    } else {
+        Some(segments.join("/"))
+    }
+}
+
+/// Whether `tag` is `selected` or nested beneath it; both must be normalized
+fn tag_within(tag: &str, selected: &str) -> bool {
+    tag.strip_prefix(selected)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// AND/OR match of an article's tags against normalized selected tags. An
+/// empty selection matches everything.
+fn matches_tags(article_tags: &[String], selected: &[String], match_all: bool) -> bool {
+    if selected.is_empty() {
+        return true;
+    }
+    let article_tags: Vec<String> = article_tags.iter().filter_map(|tag| normalize_tag(tag)).collect();
+    let has = |selected: &String| article_tags.iter().any(|tag| tag_within(tag, selected));
+    if match_all {
+        selected.iter().all(has)
+    } else {
+        selected.iter().any(has)
+    }
+}
+
+/// Per-tag counts over each article's tags, including implied parent tags
+fn count_tags(tags_sets: &[Vec<String>]) -> Vec<ReadingListTag> {
+    let mut counts: BTreeMap<String, (i32, i32)> = BTreeMap::new();
+    for tags in tags_sets {
+        let direct: HashSet<String> = tags.iter().filter_map(|tag| normalize_tag(tag)).collect();
+        let mut with_ancestors: HashSet<&str> = HashSet::new();
+        for tag in &direct {
+            counts.entry(tag.clone()).or_default().0 += 1;
+            with_ancestors.insert(tag);
+            for (index, _) in tag.match_indices('/') {
+                with_ancestors.insert(&tag[..index]);
+            }
+        }
+        for tag in with_ancestors {
+            counts.entry(tag.to_string()).or_default().1 += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .map(|(tag, (count, total_count))| {
+            let (parent, name) = match tag.rsplit_once('/') {
+                Some((parent, name)) => (Some(parent.to_string()), name.to_string()),
+                None => (None, tag.clone()),
+            };
+            ReadingListTag { tag, name, parent, count, total_count }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article(id: &str, tags: &[&str]) -> Article {
+        Article {
+            id: id.to_string(),
+            url: format!("https://example.com/{}", id),
+            title: id.to_string(),
+            author: None,
+            excerpt: None,
+            content: None,
+            thumbnail: None,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            reading_time_minutes: None,
+            progress_percentage: 0.0,
+            is_read: false,
+            is_favorite: false,
+            added_at: 0,
+            read_at: None,
+            last_opened_at: None,
+            thumbnail_placeholder: None,
+        }
+    }
+
+    fn service() -> ReadingListService {
+        let service = ReadingListService::new(":memory:").unwrap();
+        service.add_article(&article("rust-async", &["Tech/Rust", "Tutorials"])).unwrap();
+        service.add_article(&article("tokio-internals", &[" tech / rust / async "])).unwrap();
+        service.add_article(&article("css-grid", &["tech/web"])).unwrap();
+        service.add_article(&article("sourdough", &["Cooking", "tutorials"])).unwrap();
+        service.add_article(&article("technique", &["techniques"])).unwrap();
+        service
+    }
+
+    fn ids(articles: Vec<Article>) -> Vec<String> {
+        let mut ids: Vec<String> = articles.into_iter().map(|article| article.id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_parent_tag_includes_descendants() {
+        let service = service();
+
+        assert_eq!(
+            ids(service.search_by_tags(&["TECH ".to_string()], false).unwrap()),
+            vec!["css-grid", "rust-async", "tokio-internals"]
+        );
+        assert_eq!(
+            ids(service.search_by_tags(&["tech/rust".to_string()], false).unwrap()),
+            vec!["rust-async", "tokio-internals"]
+        );
+        assert_eq!(
+            ids(service.search_by_tags(&["Tech/Rust/Async".to_string()], false).unwrap()),
+            vec!["tokio-internals"]
+        );
+
+        let filter = ArticleFilter {
+            status: None,
+            tag: Some("tech / web".to_string()),
+            favorites_only: false,
+            search_query: None,
+        };
+        assert_eq!(ids(service.search_articles(&filter).unwrap()), vec!["css-grid"]);
+
+        let tags = service.get_all_tags().unwrap();
+        let tech = tags.iter().find(|tag| tag.tag == "tech").unwrap();
+        assert_eq!((tech.count, tech.total_count, tech.parent.as_deref()), (0, 3, None));
+        let rust = tags.iter().find(|tag| tag.tag == "tech/rust").unwrap();
+        assert_eq!((rust.name.as_str(), rust.parent.as_deref()), ("rust", Some("tech")));
+        assert_eq!((rust.count, rust.total_count), (1, 2));
+        let tutorials = tags.iter().find(|tag| tag.tag == "tutorials").unwrap();
+        assert_eq!((tutorials.count, tutorials.total_count), (2, 2));
+        assert!(tags.iter().any(|tag| tag.tag == "tech/rust/async"));
+    }
+
+    #[test]
+    fn test_match_all_versus_match_any() {
+        let service = service();
+        let selected = vec!["tech".to_string(), "Tutorials".to_string()];
+
+        assert_eq!(
+            ids(service.search_by_tags(&selected, false).unwrap()),
+            vec!["css-grid", "rust-async", "sourdough", "tokio-internals"]
+        );
+        assert_eq!(ids(service.search_by_tags(&selected, true).unwrap()), vec!["rust-async"]);
+        assert_eq!(
+            ids(service.search_by_tags(&["cooking".to_string(), "tech/web".to_string()], true).unwrap()),
+            Vec::<String>::new()
+        );
+        // No selection filters nothing; blank tags are ignored
+        assert_eq!(service.search_by_tags(&[" / ".to_string()], true).unwrap().len(), 5);
     }
 }
@@ -1,11 +1,11 @@
 // Workflow Scheduler Service
 // Manages cron-based and event-based workflow execution
 
-use chrono::{DateTime, Utc};
-use cron::Schedule;
+use crate::services::cron_schedule::CronSchedule;
+use chrono::{DateTime, FixedOffset, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{interval, Duration};
@@ -17,6 +17,9 @@ pub struct ScheduledWorkflow {
     pub workflow_name: String,
     pub schedule_type: ScheduleType,
     pub cron_expression: Option<String>,
+    /// IANA timezone cron expressions are evaluated in, e.g. "Europe/Madrid"
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
     pub enabled: bool,
     pub last_run: Option<DateTime<Utc>>,
     pub next_run: Option<DateTime<Utc>>,
@@ -24,6 +27,48 @@ pub struct ScheduledWorkflow {
     pub retry_policy: RetryPolicy,
 }
 
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.parse::<Tz>().map_err(|_| format!("Unknown timezone: {}", name))
+}
+
+impl ScheduledWorkflow {
+    /// The first run after `after`, or None for event schedules and finished one-offs
+    pub fn next_run_after(&self, after: DateTime<Utc>) -> Result<Option<DateTime<Utc>>, String> {
+        Ok(self.upcoming_runs(after, 1)?.into_iter().next())
+    }
+
+    /// The next `count` runs after `after`
+    pub fn upcoming_runs(&self, after: DateTime<Utc>, count: usize) -> Result<Vec<DateTime<Utc>>, String> {
+        match &self.schedule_type {
+            ScheduleType::Interval { seconds } => {
+                let step = chrono::Duration::seconds((*seconds).max(1) as i64);
+                // A schedule that never ran fires on the next tick
+                let mut next = match self.last_run {
+                    Some(last) => last + step,
+                    None => after,
+                };
+                while next < after {
+                    next += step;
+                }
+                Ok((0..count as i32).map(|i| next + step * i).collect())
+            }
+            ScheduleType::Once { at } => {
+                Ok((*at > after && self.last_run.is_none() && count > 0).then_some(*at).into_iter().collect())
+            }
+            ScheduleType::Cron { expression } => {
+                let cron = CronSchedule::parse(expression)
+                    .map_err(|e| format!("Invalid cron expression: {}", e))?;
+                Ok(cron.upcoming(after, parse_timezone(&self.timezone)?, count))
+            }
+            ScheduleType::Event { .. } => Ok(Vec::new()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ScheduleType {
@@ -90,14 +135,18 @@ impl WorkflowScheduler {
     }
 
     /// Add a new scheduled workflow
-    pub async fn add_schedule(&self, schedule: ScheduledWorkflow) -> Result<(), String> {
+    pub async fn add_schedule(&self, mut schedule: ScheduledWorkflow) -> Result<(), String> {
         let mut schedules = self.schedules.write().await;
         
-        // Validate cron expression if present
+        // Validate cron expression and timezone
         if let Some(ref expr) = schedule.cron_expression {
-            Schedule::from_str(expr)
+            CronSchedule::parse(expr)
                 .map_err(|e| format!("Invalid cron expression: {}", e))?;
         }
+        parse_timezone(&schedule.timezone)?;
+        if let ScheduleType::Cron { .. } = schedule.schedule_type {
+            schedule.next_run = schedule.next_run_after(Utc::now())?;
+        }
 
         schedules.insert(schedule.id.clone(), schedule);
         Ok(())
@@ -126,6 +175,19 @@ impl WorkflowScheduler {
         schedules.values().cloned().collect()
     }
 
+    /// Next `count` runs of a schedule, in the schedule's own timezone
+    pub async fn get_next_runs(&self, schedule_id: &str, count: usize) -> Result<Vec<DateTime<FixedOffset>>, String> {
+        let schedules = self.schedules.read().await;
+        let schedule = schedules.get(schedule_id)
+            .ok_or_else(|| format!("Schedule not found: {}", schedule_id))?;
+        let tz = parse_timezone(&schedule.timezone)?;
+        Ok(schedule
+            .upcoming_runs(Utc::now(), count)?
+            .into_iter()
+            .map(|run| run.with_timezone(&tz).fixed_offset())
+            .collect())
+    }
+
     /// Get execution queue
     pub async fn get_queue(&self) -> Vec<ExecutionQueueItem> {
         let queue = self.execution_queue.read().await;
@@ -167,20 +229,12 @@ impl WorkflowScheduler {
                     }
 
                     let should_run = match &schedule.schedule_type {
-                        ScheduleType::Cron { expression } => {
-                            if let Ok(cron_schedule) = Schedule::from_str(expression) {
-                                if let Some(next_run) = schedule.next_run {
-                                    now >= next_run
-                                } else {
-                                    // Calculate first run
-                                    if let Some(next) = cron_schedule.upcoming(Utc).next() {
-                                        schedule.next_run = Some(next);
-                                        false
-                                    } else {
-                                        false
-                                    }
-                                }
+                        ScheduleType::Cron { .. } => {
+                            if let Some(next_run) = schedule.next_run {
+                                now >= next_run
                             } else {
+                                // Calculate first run
+                                schedule.next_run = schedule.next_run_after(now).ok().flatten();
                                 false
                             }
                         }
@@ -221,10 +275,8 @@ impl WorkflowScheduler {
                         schedule.run_count += 1;
 
                         // Calculate next run for cron
-                        if let ScheduleType::Cron { expression } = &schedule.schedule_type {
-                            if let Ok(cron_schedule) = Schedule::from_str(expression) {
-                                schedule.next_run = cron_schedule.upcoming(Utc).next();
-                            }
+                        if let ScheduleType::Cron { .. } = &schedule.schedule_type {
+                            schedule.next_run = schedule.next_run_after(now).ok().flatten();
                         }
                     }
                 }
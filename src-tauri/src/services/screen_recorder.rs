@@ -8,6 +8,7 @@ use anyhow::{Context, Result};
  * - Full screen recording
  * - Window recording
  * - Area selection recording
+ * - Webcam picture-in-picture overlay and webcam-only recording
  * - Microphone audio
  * - System audio (platform-dependent)
  * - Multiple format support (WebM, MP4, GIF)
@@ -16,10 +17,15 @@ use anyhow::{Context, Result};
  */
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::process::{Child, Command};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
 use std::sync::{Arc, Mutex};
 use tauri::{AppHandle, Emitter};
 
+/// Filter instance names, so overlay commands can be sent to a running ffmpeg
+const PIP_SCALE_FILTER: &str = "scale@pip";
+const PIP_OVERLAY_FILTER: &str = "overlay@pip";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecordingConfig {
     pub mode: RecordingMode,
@@ -30,6 +36,11 @@ pub struct RecordingConfig {
     pub microphone_enabled: bool,
     pub system_audio_enabled: bool,
     pub output_path: String,
+    /// Webcam composited over the screen capture
+    #[serde(default)]
+    pub webcam: Option<WebcamOverlay>,
+    #[serde(default)]
+    pub devices: CaptureDevices,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -43,6 +54,8 @@ pub enum RecordingMode {
         width: i32,
         height: i32,
     },
+    /// Webcam only, no screen capture
+    Webcam,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +75,118 @@ pub enum Quality {
     Ultra,  // 2K/4K
 }
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WebcamOverlay {
+    pub corner: OverlayCorner,
+    /// Overlay width as a percentage of the recorded frame width (5-50)
+    pub size_percent: u32,
+    /// Gap between the overlay and the frame edges in pixels
+    pub margin: u32,
+}
+
+impl Default for WebcamOverlay {
+    fn default() -> Self {
+        Self {
+            corner: OverlayCorner::BottomRight,
+            size_percent: 25,
+            margin: 20,
+        }
+    }
+}
+
+impl WebcamOverlay {
+    /// Overlay width in pixels, kept even for yuv420p encoders
+    fn width_px(&self, frame_width: u32) -> u32 {
+        let width = frame_width * self.size_percent.clamp(5, 50) / 100;
+        (width & !1).max(2)
+    }
+
+    /// ffmpeg overlay x/y expressions for the configured corner
+    fn position(&self) -> (String, String) {
+        let near = self.margin.to_string();
+        let far_x = format!("main_w-overlay_w-{}", self.margin);
+        let far_y = format!("main_h-overlay_h-{}", self.margin);
+        match self.corner {
+            OverlayCorner::TopLeft => (near.clone(), near),
+            OverlayCorner::TopRight => (far_x, near),
+            OverlayCorner::BottomLeft => (near, far_y),
+            OverlayCorner::BottomRight => (far_x, far_y),
+        }
+    }
+}
+
+/// Capture device names; platform defaults are used when unset
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CaptureDevices {
+    pub webcam: Option<String>,
+    pub microphone: Option<String>,
+    /// Loopback/monitor source for system audio
+    pub system_audio: Option<String>,
+}
+
+/// One ffmpeg input: demuxer, its options and the device to open
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaptureInput {
+    pub format: String,
+    pub options: Vec<(String, String)>,
+    pub source: String,
+}
+
+impl CaptureInput {
+    pub fn new(format: &str, source: &str) -> Self {
+        Self {
+            format: format.to_string(),
+            options: Vec::new(),
+            source: source.to_string(),
+        }
+    }
+
+    pub fn option(mut self, name: &str, value: &str) -> Self {
+        self.options.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        for (name, value) in &self.options {
+            args.push(format!("-{}", name));
+            args.push(value.clone());
+        }
+        args.extend(["-f".to_string(), self.format.clone(), "-i".to_string(), self.source.clone()]);
+        args
+    }
+}
+
+/// Inputs resolved for the current platform and capture source
+#[derive(Debug, Clone, Default)]
+pub struct CaptureInputs {
+    pub screen: Option<CaptureInput>,
+    pub webcam: Option<CaptureInput>,
+    pub microphone: Option<CaptureInput>,
+    pub system_audio: Option<CaptureInput>,
+    /// Width of the captured screen, used to size the overlay
+    pub frame_width: u32,
+    pub warnings: Vec<String>,
+}
+
+/// ffmpeg command line plus where the webcam ended up among its inputs
+#[derive(Debug, Clone)]
+pub struct FfmpegInvocation {
+    pub args: Vec<String>,
+    pub webcam_input: Option<usize>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum RecordingStatus {
@@ -83,6 +208,13 @@ pub struct RecordingSession {
     pub file_size: u64, // bytes
     pub output_file: Option<String>,
     pub error: Option<String>,
+    /// Webcam is being captured; false once it fails or is unplugged
+    #[serde(default)]
+    pub webcam_active: bool,
+    #[serde(default)]
+    pub frame_width: u32,
+    #[serde(default)]
+    pub warnings: Vec<String>,
 }
 
 pub struct ScreenRecorder {
@@ -104,6 +236,23 @@ impl ScreenRecorder {
     pub fn start_recording(&self, config: RecordingConfig) -> Result<String> {
         let session_id = uuid::Uuid::new_v4().to_string();
 
+        let mut inputs = Self::platform_inputs(&config);
+        if let Some(webcam) = &inputs.webcam {
+            if !webcam_available(webcam) {
+                inputs.warnings.push(format!("Webcam {} is not available", webcam.source));
+                inputs.webcam = None;
+            }
+        }
+        if config.mode == RecordingMode::Webcam && inputs.webcam.is_none() {
+            return Err(anyhow::anyhow!(
+                "Webcam-only recording needs a webcam: {}",
+                inputs.warnings.join("; ")
+            ));
+        }
+        for warning in &inputs.warnings {
+            log::warn!("Screen recording {}: {}", session_id, warning);
+        }
+
         let session = RecordingSession {
             id: session_id.clone(),
             status: RecordingStatus::Recording,
@@ -113,6 +262,9 @@ impl ScreenRecorder {
             file_size: 0,
             output_file: None,
             error: None,
+            webcam_active: inputs.webcam.is_some(),
+            frame_width: inputs.frame_width,
+            warnings: inputs.warnings.clone(),
         };
 
         // Store session
@@ -122,7 +274,7 @@ impl ScreenRecorder {
         }
 
         // Start platform-specific recording
-        match self.start_platform_recording(&session_id, &config) {
+        match self.spawn_ffmpeg(&session_id, &config, &inputs) {
             Ok(process) => {
                 let mut processes = self.active_processes.lock().unwrap();
                 processes.insert(session_id.clone(), process);
@@ -144,105 +296,148 @@ impl ScreenRecorder {
         }
     }
 
-    /// Start platform-specific recording process
-    fn start_platform_recording(
+    /// Launch ffmpeg and watch its log for the webcam dropping out
+    fn spawn_ffmpeg(
         &self,
-        _session_id: &str,
+        session_id: &str,
         config: &RecordingConfig,
+        inputs: &CaptureInputs,
     ) -> Result<Child> {
+        let invocation = build_ffmpeg_args(config, inputs)?;
+
+        // stdin carries runtime filter commands, stderr is scanned for device errors
+        let mut process = Command::new("ffmpeg")
+            .args(&invocation.args)
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context("Failed to start ffmpeg recording")?;
+
+        let markers = invocation
+            .webcam_input
+            .map(|index| webcam_log_markers(inputs, index))
+            .unwrap_or_default();
+        if let Some(stderr) = process.stderr.take() {
+            let sessions = self.sessions.clone();
+            let app_handle = self.app_handle.clone();
+            let session_id = session_id.to_string();
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(|line| line.ok()) {
+                    if !is_webcam_failure(&line, &markers) {
+                        continue;
+                    }
+                    let mut sessions = sessions.lock().unwrap();
+                    if let Some(session) = sessions.get_mut(&session_id) {
+                        if session.webcam_active {
+                            // overlay runs with eof_action=pass, so the screen keeps recording
+                            log::warn!(
+                                "Screen recording {}: webcam lost, continuing screen-only ({})",
+                                session_id,
+                                line.trim()
+                            );
+                            session.webcam_active = false;
+                            session.warnings.push("Webcam disconnected during recording".to_string());
+                            let _ = app_handle.emit("recording:webcam-lost", &*session);
+                        }
+                    }
+                }
+            });
+        }
+
+        Ok(process)
+    }
+
+    /// Resolve capture inputs for the current platform
+    fn platform_inputs(config: &RecordingConfig) -> CaptureInputs {
         #[cfg(target_os = "macos")]
         {
-            self.start_macos_recording(config)
+            Self::macos_inputs(config)
         }
 
         #[cfg(target_os = "windows")]
         {
-            self.start_windows_recording(config)
+            Self::windows_inputs(config)
         }
 
         #[cfg(target_os = "linux")]
         {
-            self.start_linux_recording(config)
+            Self::linux_inputs(config)
         }
     }
 
     #[cfg(target_os = "macos")]
-    fn start_macos_recording(&self, config: &RecordingConfig) -> Result<Child> {
-        // macOS: Use ffmpeg with AVFoundation
-        let mut cmd = Command::new("ffmpeg");
-
-        // Video input
-        cmd.arg("-f").arg("avfoundation");
-
-        // Input device (0:0 = video:audio, 0:none = video only)
-        let input = if config.audio_enabled || config.microphone_enabled {
-            "0:0" // Capture both video and audio
-        } else {
-            "0:none" // Video only
+    fn macos_inputs(config: &RecordingConfig) -> CaptureInputs {
+        // macOS: AVFoundation for screen, camera and microphone
+        let fps = config.fps.to_string();
+        let mut inputs = CaptureInputs {
+            frame_width: frame_width_of(
+                &Self::get_screen_size_macos().unwrap_or_else(|_| "2560x1440".to_string()),
+            ),
+            ..Default::default()
         };
-        cmd.arg("-i").arg(input);
-
-        // Frame rate
-        cmd.arg("-r").arg(config.fps.to_string());
 
-        // Video codec based on format
-        match config.format {
-            VideoFormat::WebM => {
-                cmd.arg("-c:v").arg("libvpx-vp9");
-                cmd.arg("-b:v").arg(self.get_bitrate(&config.quality));
-            }
-            VideoFormat::MP4 => {
-                cmd.arg("-c:v").arg("libx264");
-                cmd.arg("-preset").arg("ultrafast");
-                cmd.arg("-b:v").arg(self.get_bitrate(&config.quality));
-            }
-            VideoFormat::GIF => {
-                cmd.arg("-vf").arg("fps=10,scale=640:-1:flags=lanczos");
-            }
+        if config.mode != RecordingMode::Webcam {
+            inputs.screen = Some(
+                CaptureInput::new("avfoundation", "Capture screen 0:none")
+                    .option("framerate", &fps)
+                    .option("capture_cursor", "1"),
+            );
         }
-
-        // Audio codec
-        if config.audio_enabled || config.microphone_enabled {
-            cmd.arg("-c:a").arg("aac");
-            cmd.arg("-b:a").arg("128k");
+        if wants_webcam(config) {
+            let device = config.devices.webcam.as_deref().unwrap_or("default");
+            inputs.webcam = Some(
+                CaptureInput::new("avfoundation", &format!("{}:none", device)).option("framerate", &fps),
+            );
+        }
+        if wants_microphone(config) {
+            let device = config.devices.microphone.as_deref().unwrap_or("default");
+            inputs.microphone = Some(CaptureInput::new("avfoundation", &format!("none:{}", device)));
+        }
+        if config.system_audio_enabled {
+            // macOS has no built-in loopback; needs e.g. BlackHole
+            match &config.devices.system_audio {
+                Some(device) => {
+                    inputs.system_audio =
+                        Some(CaptureInput::new("avfoundation", &format!("none:{}", device)));
+                }
+                None => inputs
+                    .warnings
+                    .push("System audio needs a loopback device such as BlackHole".to_string()),
+            }
         }
 
-        // Output file
-        cmd.arg("-y"); // Overwrite if exists
-        cmd.arg(&config.output_path);
-
-        // Start process
-        let process = cmd.spawn().context("Failed to start ffmpeg recording")?;
-
-        Ok(process)
+        inputs
     }
 
     #[cfg(target_os = "windows")]
-    fn start_windows_recording(&self, config: &RecordingConfig) -> Result<Child> {
-        // Windows: Use ffmpeg with GDI or DirectShow
-        let mut cmd = Command::new("ffmpeg");
-
-        // Video input (GDI capture)
-        cmd.arg("-f").arg("gdigrab");
-        cmd.arg("-framerate").arg(config.fps.to_string());
+    fn windows_inputs(config: &RecordingConfig) -> CaptureInputs {
+        // Windows: GDI screen capture, DirectShow camera and audio
+        let fps = config.fps.to_string();
+        let mut inputs = CaptureInputs {
+            frame_width: 1920,
+            ..Default::default()
+        };
 
         // Capture mode
-        match &config.mode {
+        inputs.screen = match &config.mode {
             RecordingMode::Fullscreen => {
-                cmd.arg("-i").arg("desktop");
+                Some(CaptureInput::new("gdigrab", "desktop").option("framerate", &fps))
             }
             RecordingMode::Window => {
                 // Windows window title capture via -i title="Window Name"
                 // Get focused window title using PowerShell
                 let window_title = Self::get_focused_window_title_windows()
                     .unwrap_or_else(|_| "desktop".to_string());
-                
+
                 if window_title != "desktop" && !window_title.is_empty() {
                     // Use window title for targeted capture
-                    cmd.arg("-i").arg(format!("title={}", window_title));
+                    Some(
+                        CaptureInput::new("gdigrab", &format!("title={}", window_title))
+                            .option("framerate", &fps),
+                    )
                 } else {
                     // Fallback to desktop capture
-                    cmd.arg("-i").arg("desktop");
+                    Some(CaptureInput::new("gdigrab", "desktop").option("framerate", &fps))
                 }
             }
             RecordingMode::Area {
@@ -251,59 +446,60 @@ impl ScreenRecorder {
                 width,
                 height,
             } => {
-                cmd.arg("-offset_x").arg(x.to_string());
-                cmd.arg("-offset_y").arg(y.to_string());
-                cmd.arg("-video_size").arg(format!("{}x{}", width, height));
-                cmd.arg("-i").arg("desktop");
+                inputs.frame_width = *width as u32;
+                Some(
+                    CaptureInput::new("gdigrab", "desktop")
+                        .option("framerate", &fps)
+                        .option("offset_x", &x.to_string())
+                        .option("offset_y", &y.to_string())
+                        .option("video_size", &format!("{}x{}", width, height)),
+                )
             }
-        }
-
-        // Audio input
-        if config.audio_enabled || config.microphone_enabled {
-            cmd.arg("-f").arg("dshow");
-            cmd.arg("-i").arg("audio=\"Microphone\"");
-        }
+            RecordingMode::Webcam => None,
+        };
 
-        // Video codec
-        match config.format {
-            VideoFormat::WebM => {
-                cmd.arg("-c:v").arg("libvpx-vp9");
-            }
-            VideoFormat::MP4 => {
-                cmd.arg("-c:v").arg("libx264");
-                cmd.arg("-preset").arg("ultrafast");
-            }
-            VideoFormat::GIF => {
-                cmd.arg("-vf").arg("fps=10,scale=640:-1:flags=lanczos");
+        if wants_webcam(config) {
+            // DirectShow devices are addressed by name
+            match &config.devices.webcam {
+                Some(device) => {
+                    inputs.webcam = Some(
+                        CaptureInput::new("dshow", &format!("video={}", device))
+                            .option("framerate", &fps),
+                    );
+                }
+                None => inputs.warnings.push("No webcam device configured".to_string()),
             }
         }
+        if wants_microphone(config) {
+            let device = config.devices.microphone.as_deref().unwrap_or("Microphone");
+            inputs.microphone = Some(CaptureInput::new("dshow", &format!("audio={}", device)));
+        }
+        if config.system_audio_enabled {
+            let device = config.devices.system_audio.as_deref().unwrap_or("Stereo Mix");
+            inputs.system_audio = Some(CaptureInput::new("dshow", &format!("audio={}", device)));
+        }
 
-        // Output file
-        cmd.arg("-y");
-        cmd.arg(&config.output_path);
-
-        let process = cmd.spawn().context("Failed to start ffmpeg recording")?;
-
-        Ok(process)
+        inputs
     }
 
     #[cfg(target_os = "linux")]
-    fn start_linux_recording(&self, config: &RecordingConfig) -> Result<Child> {
-        // Linux: Use ffmpeg with X11
-        let mut cmd = Command::new("ffmpeg");
-
-        // Video input (X11)
-        cmd.arg("-f").arg("x11grab");
-        cmd.arg("-framerate").arg(config.fps.to_string());
+    fn linux_inputs(config: &RecordingConfig) -> CaptureInputs {
+        // Linux: X11 screen, V4L2 camera, PulseAudio
+        let fps = config.fps.to_string();
+        let mut inputs = CaptureInputs::default();
 
         // Capture area
-        match &config.mode {
+        let screen_size = || Self::get_screen_size_linux().unwrap_or_else(|_| "1920x1080".to_string());
+        inputs.screen = match &config.mode {
             RecordingMode::Fullscreen => {
                 // Get actual screen size using xdpyinfo or default to common resolution
-                let screen_size = Self::get_screen_size_linux()
-                    .unwrap_or_else(|_| "1920x1080".to_string());
-                cmd.arg("-video_size").arg(screen_size);
-                cmd.arg("-i").arg(":0.0");
+                let size = screen_size();
+                inputs.frame_width = frame_width_of(&size);
+                Some(
+                    CaptureInput::new("x11grab", ":0.0")
+                        .option("framerate", &fps)
+                        .option("video_size", &size),
+                )
             }
             RecordingMode::Area {
                 x,
@@ -311,41 +507,82 @@ impl ScreenRecorder {
                 width,
                 height,
             } => {
-                cmd.arg("-video_size").arg(format!("{}x{}", width, height));
-                cmd.arg("-i").arg(format!(":0.0+{},{}", x, y));
+                inputs.frame_width = *width as u32;
+                Some(
+                    CaptureInput::new("x11grab", &format!(":0.0+{},{}", x, y))
+                        .option("framerate", &fps)
+                        .option("video_size", &format!("{}x{}", width, height)),
+                )
             }
-            _ => {
-                cmd.arg("-i").arg(":0.0");
+            RecordingMode::Window => {
+                inputs.frame_width = frame_width_of(&screen_size());
+                Some(CaptureInput::new("x11grab", ":0.0").option("framerate", &fps))
             }
-        }
+            RecordingMode::Webcam => None,
+        };
 
-        // Audio input (PulseAudio)
-        if config.audio_enabled || config.microphone_enabled {
-            cmd.arg("-f").arg("pulse");
-            cmd.arg("-i").arg("default");
+        if wants_webcam(config) {
+            let device = config.devices.webcam.as_deref().unwrap_or("/dev/video0");
+            inputs.webcam = Some(CaptureInput::new("v4l2", device).option("framerate", &fps));
+        }
+        if wants_microphone(config) {
+            let device = config.devices.microphone.as_deref().unwrap_or("default");
+            inputs.microphone = Some(CaptureInput::new("pulse", device));
+        }
+        if config.system_audio_enabled {
+            let device = config.devices.system_audio.as_deref().unwrap_or("@DEFAULT_MONITOR@");
+            inputs.system_audio = Some(CaptureInput::new("pulse", device));
         }
 
-        // Video codec
-        match config.format {
-            VideoFormat::WebM => {
-                cmd.arg("-c:v").arg("libvpx-vp9");
+        inputs
+    }
+
+    /// Move or resize the webcam overlay of a running recording
+    pub fn update_webcam_overlay(
+        &self,
+        session_id: &str,
+        overlay: WebcamOverlay,
+    ) -> Result<RecordingSession> {
+        let frame_width = {
+            let sessions = self.sessions.lock().unwrap();
+            let session = sessions
+                .get(session_id)
+                .ok_or_else(|| anyhow::anyhow!("Recording session not found"))?;
+            // The overlay filters only exist if the recording started with one
+            if session.config.webcam.is_none() || session.config.mode == RecordingMode::Webcam {
+                return Err(anyhow::anyhow!("Recording was started without a webcam overlay"));
             }
-            VideoFormat::MP4 => {
-                cmd.arg("-c:v").arg("libx264");
-                cmd.arg("-preset").arg("ultrafast");
+            if !session.webcam_active {
+                return Err(anyhow::anyhow!("Webcam is no longer being recorded"));
             }
-            VideoFormat::GIF => {
-                cmd.arg("-vf").arg("fps=10,scale=640:-1:flags=lanczos");
+            session.frame_width
+        };
+
+        {
+            let mut processes = self.active_processes.lock().unwrap();
+            let process = processes
+                .get_mut(session_id)
+                .ok_or_else(|| anyhow::anyhow!("Recording is not running"))?;
+            let stdin = process
+                .stdin
+                .as_mut()
+                .ok_or_else(|| anyhow::anyhow!("Recording process has no command channel"))?;
+            // ffmpeg's interactive 'c' key sends a command to a named filter
+            for command in overlay_commands(&overlay, frame_width) {
+                stdin
+                    .write_all(format!("c{}\n", command).as_bytes())
+                    .context("Failed to send overlay command to ffmpeg")?;
             }
+            stdin.flush().context("Failed to send overlay command to ffmpeg")?;
         }
 
-        // Output file
-        cmd.arg("-y");
-        cmd.arg(&config.output_path);
-
-        let process = cmd.spawn().context("Failed to start ffmpeg recording")?;
-
-        Ok(process)
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| anyhow::anyhow!("Recording session not found"))?;
+        session.config.webcam = Some(overlay);
+        let _ = self.app_handle.emit("recording:webcam-overlay-changed", &*session);
+        Ok(session.clone())
     }
 
     /// Stop recording
@@ -457,16 +694,6 @@ impl ScreenRecorder {
         Ok(())
     }
 
-    /// Get bitrate based on quality
-    fn get_bitrate(&self, quality: &Quality) -> &str {
-        match quality {
-            Quality::Low => "1M",
-            Quality::Medium => "2.5M",
-            Quality::High => "5M",
-            Quality::Ultra => "10M",
-        }
-    }
-    
     /// Get focused window title on Windows using PowerShell
     /// Uses GetForegroundWindow API to get the active window
     #[cfg(target_os = "windows")]
@@ -608,3 +835,288 @@ impl ScreenRecorder {
         Ok("2560x1440".to_string())
     }
 }
+
+fn wants_webcam(config: &RecordingConfig) -> bool {
+    config.mode == RecordingMode::Webcam || config.webcam.is_some()
+}
+
+fn wants_microphone(config: &RecordingConfig) -> bool {
+    // audio_enabled predates the separate microphone/system toggles
+    config.audio_enabled || config.microphone_enabled
+}
+
+/// Width from an ffmpeg size string like "1920x1080"
+fn frame_width_of(size: &str) -> u32 {
+    size.split('x')
+        .next()
+        .and_then(|width| width.trim().parse().ok())
+        .unwrap_or(1920)
+}
+
+/// Devices with a filesystem node can be checked before ffmpeg is launched
+fn webcam_available(webcam: &CaptureInput) -> bool {
+    webcam.format != "v4l2" || std::path::Path::new(&webcam.source).exists()
+}
+
+/// Build the ffmpeg command line: screen and webcam composited in one
+/// filtergraph, microphone and system audio recorded as separate tracks
+pub fn build_ffmpeg_args(config: &RecordingConfig, inputs: &CaptureInputs) -> Result<FfmpegInvocation> {
+    let mut args = Vec::new();
+    let mut next_input = 0;
+    let mut add_input = |args: &mut Vec<String>, input: &Option<CaptureInput>| {
+        input.as_ref().map(|input| {
+            args.extend(input.args());
+            next_input += 1;
+            next_input - 1
+        })
+    };
+
+    let screen = add_input(&mut args, &inputs.screen);
+    let webcam = add_input(&mut args, &inputs.webcam);
+    let is_gif = matches!(config.format, VideoFormat::GIF);
+    // GIF has no audio track
+    let mut audio_tracks = Vec::new();
+    if !is_gif {
+        if let Some(index) = add_input(&mut args, &inputs.microphone) {
+            audio_tracks.push((index, "Microphone"));
+        }
+        if let Some(index) = add_input(&mut args, &inputs.system_audio) {
+            audio_tracks.push((index, "System audio"));
+        }
+    }
+
+    let overlay = config.webcam.clone().unwrap_or_default();
+    let graph = build_filtergraph(
+        &config.format,
+        screen,
+        webcam.map(|index| (index, &overlay)),
+        inputs.frame_width,
+    )
+    .ok_or_else(|| anyhow::anyhow!("Nothing to record: no screen or webcam input"))?;
+    args.extend(["-filter_complex".to_string(), graph, "-map".to_string(), "[vout]".to_string()]);
+
+    for (track, (index, title)) in audio_tracks.iter().enumerate() {
+        args.extend([
+            "-map".to_string(),
+            format!("{}:a", index),
+            format!("-metadata:s:a:{}", track),
+            format!("title={}", title),
+        ]);
+    }
+
+    // Codecs based on format
+    let bitrate = bitrate_for(&config.quality).to_string();
+    match config.format {
+        VideoFormat::WebM => {
+            args.extend(["-c:v", "libvpx-vp9", "-b:v"].map(String::from));
+            args.push(bitrate);
+        }
+        VideoFormat::MP4 => {
+            args.extend(["-c:v", "libx264", "-preset", "ultrafast", "-b:v"].map(String::from));
+            args.push(bitrate);
+        }
+        VideoFormat::GIF => {}
+    }
+    if !audio_tracks.is_empty() {
+        let codec = if matches!(config.format, VideoFormat::WebM) { "libopus" } else { "aac" };
+        args.extend(["-c:a", codec, "-b:a", "128k"].map(String::from));
+    }
+
+    // Output file
+    args.push("-y".to_string()); // Overwrite if exists
+    args.push(config.output_path.clone());
+
+    Ok(FfmpegInvocation {
+        args,
+        webcam_input: webcam,
+    })
+}
+
+/// Filtergraph producing `[vout]`; the webcam is scaled and overlaid on the
+/// screen, and the screen carries on alone if the webcam stream ends
+pub fn build_filtergraph(
+    format: &VideoFormat,
+    screen: Option<usize>,
+    webcam: Option<(usize, &WebcamOverlay)>,
+    frame_width: u32,
+) -> Option<String> {
+    let output = match format {
+        VideoFormat::GIF => "fps=10,scale=640:-1:flags=lanczos",
+        _ => "format=yuv420p",
+    };
+    match (screen, webcam) {
+        (Some(screen), Some((webcam, overlay))) => {
+            let (x, y) = overlay.position();
+            Some(format!(
+                "[{webcam}:v]{PIP_SCALE_FILTER}=w={}:h=-2[cam];[{screen}:v][cam]{PIP_OVERLAY_FILTER}=x={x}:y={y}:eof_action=pass,{output}[vout]",
+                overlay.width_px(frame_width)
+            ))
+        }
+        (Some(input), None) | (None, Some((input, _))) => Some(format!("[{input}:v]{output}[vout]")),
+        (None, None) => None,
+    }
+}
+
+/// Runtime filter commands that move/resize the overlay to `overlay`
+pub fn overlay_commands(overlay: &WebcamOverlay, frame_width: u32) -> Vec<String> {
+    let (x, y) = overlay.position();
+    vec![
+        format!("{} -1 w {}", PIP_SCALE_FILTER, overlay.width_px(frame_width)),
+        format!("{} -1 x {}", PIP_OVERLAY_FILTER, x),
+        format!("{} -1 y {}", PIP_OVERLAY_FILTER, y),
+    ]
+}
+
+/// Strings that identify the webcam input in ffmpeg's log
+fn webcam_log_markers(inputs: &CaptureInputs, index: usize) -> Vec<String> {
+    let Some(webcam) = &inputs.webcam else {
+        return Vec::new();
+    };
+    let mut markers = vec![format!("in#{}", index), webcam.source.clone()];
+    // The demuxer name only counts if no other input shares it
+    let shared = [&inputs.screen, &inputs.microphone, &inputs.system_audio]
+        .iter()
+        .any(|other| other.as_ref().is_some_and(|other| other.format == webcam.format));
+    if !shared {
+        markers.push(webcam.format.clone());
+    }
+    markers
+}
+
+fn is_webcam_failure(line: &str, markers: &[String]) -> bool {
+    let lower = line.to_lowercase();
+    markers.iter().any(|marker| line.contains(marker.as_str()))
+        && ["error", "no such device", "disconnected"]
+            .iter()
+            .any(|needle| lower.contains(needle))
+}
+
+/// Get bitrate based on quality
+fn bitrate_for(quality: &Quality) -> &'static str {
+    match quality {
+        Quality::Low => "1M",
+        Quality::Medium => "2.5M",
+        Quality::High => "5M",
+        Quality::Ultra => "10M",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(format: VideoFormat, output_path: &str) -> RecordingConfig {
+        RecordingConfig {
+            mode: RecordingMode::Fullscreen,
+            format,
+            quality: Quality::Low,
+            fps: 10,
+            audio_enabled: false,
+            microphone_enabled: true,
+            system_audio_enabled: true,
+            output_path: output_path.to_string(),
+            webcam: Some(WebcamOverlay {
+                corner: OverlayCorner::BottomRight,
+                size_percent: 25,
+                margin: 10,
+            }),
+            devices: CaptureDevices::default(),
+        }
+    }
+
+    /// Synthetic sources: blue screen, red webcam, two tones
+    fn test_inputs() -> CaptureInputs {
+        let lavfi = |source: &str| CaptureInput::new("lavfi", source).option("t", "1");
+        CaptureInputs {
+            screen: Some(lavfi("color=c=blue:s=320x240:r=10")),
+            webcam: Some(lavfi("color=c=red:s=160x120:r=10")),
+            microphone: Some(lavfi("sine=frequency=440:sample_rate=48000")),
+            system_audio: Some(lavfi("sine=frequency=880:sample_rate=48000")),
+            frame_width: 320,
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_overlay_filtergraph_and_runtime_commands() {
+        let invocation = build_ffmpeg_args(&config(VideoFormat::WebM, "out.webm"), &test_inputs()).unwrap();
+        let graph = &invocation.args[invocation.args.iter().position(|a| a == "-filter_complex").unwrap() + 1];
+        assert_eq!(
+            graph,
+            "[1:v]scale@pip=w=80:h=-2[cam];[0:v][cam]overlay@pip=x=main_w-overlay_w-10:y=main_h-overlay_h-10:eof_action=pass,format=yuv420p[vout]"
+        );
+        assert_eq!(invocation.webcam_input, Some(1));
+        assert!(invocation.args.windows(2).any(|w| w == ["-map", "2:a"]));
+        assert!(invocation.args.windows(2).any(|w| w == ["-map", "3:a"]));
+        assert!(invocation.args.windows(2).any(|w| w == ["-c:a", "libopus"]));
+
+        let moved = WebcamOverlay {
+            corner: OverlayCorner::TopLeft,
+            size_percent: 40,
+            margin: 0,
+        };
+        assert_eq!(
+            overlay_commands(&moved, 1920),
+            ["scale@pip -1 w 768", "overlay@pip -1 x 0", "overlay@pip -1 y 0"]
+        );
+
+        // Webcam-only: no overlay, webcam is the only video input
+        let graph = build_filtergraph(&VideoFormat::MP4, None, Some((0, &moved)), 0).unwrap();
+        assert_eq!(graph, "[0:v]format=yuv420p[vout]");
+
+        let markers = webcam_log_markers(&test_inputs(), 1);
+        assert!(is_webcam_failure("[in#1/lavfi @ 0x1] Error during demuxing: No such device", &markers));
+        assert!(!is_webcam_failure("[in#0/lavfi @ 0x1] Error during demuxing", &markers[..1]));
+    }
+
+    #[test]
+    fn test_ffmpeg_output_has_overlay_and_both_audio_tracks() {
+        let ffmpeg_ready = Command::new("ffmpeg")
+            .args(["-hide_banner", "-encoders"])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).contains("libx264"))
+            .unwrap_or(false);
+        if !ffmpeg_ready {
+            eprintln!("skipping: ffmpeg with libx264 not available");
+            return;
+        }
+
+        let output = std::env::temp_dir().join(format!("cube-pip-{}.mp4", uuid::Uuid::new_v4()));
+        let output_path = output.to_string_lossy().to_string();
+        let invocation = build_ffmpeg_args(&config(VideoFormat::MP4, &output_path), &test_inputs()).unwrap();
+        let status = Command::new("ffmpeg")
+            .arg("-v")
+            .arg("error")
+            .args(&invocation.args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let probe = Command::new("ffprobe")
+            .args(["-v", "error", "-select_streams", "a", "-show_entries", "stream=index", "-of", "csv=p=0"])
+            .arg(&output)
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&probe.stdout).lines().count(), 2);
+
+        let frame = Command::new("ffmpeg")
+            .args(["-v", "error", "-i"])
+            .arg(&output)
+            .args(["-frames:v", "1", "-f", "rawvideo", "-pix_fmt", "rgb24", "-"])
+            .output()
+            .unwrap()
+            .stdout;
+        let _ = std::fs::remove_file(&output);
+        assert_eq!(frame.len(), 320 * 240 * 3);
+        let pixel = |x: usize, y: usize| {
+            let at = (y * 320 + x) * 3;
+            (frame[at], frame[at + 1], frame[at + 2])
+        };
+
+        // 80x60 overlay, 10px from the bottom-right corner
+        let (r, _, b) = pixel(270, 200);
+        assert!(r > 200 && b < 60, "overlay pixel not red: {:?}", pixel(270, 200));
+        let (r, _, b) = pixel(40, 40);
+        assert!(b > 200 && r < 60, "screen pixel not blue: {:?}", pixel(40, 40));
+    }
+}
@@ -5,10 +5,14 @@ use anyhow::{anyhow, Result};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 use tokio::process::Command as TokioCommand;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore};
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 #[derive(Debug, Clone)]
@@ -19,6 +23,8 @@ pub struct SecurityLabService {
     exploits: Arc<Mutex<HashMap<String, ExploitSession>>>,
     verified_domains: Arc<Mutex<Vec<String>>>,
     config: Arc<Mutex<SecurityLabConfig>>,
+    /// Kill switches for running scans, keyed by scan id
+    cancellations: Arc<Mutex<HashMap<String, CancellationToken>>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +42,19 @@ pub struct SecurityLabConfig {
     pub allowed_targets: Vec<String>,
     pub openai_api_key: Option<String>,
     pub demo_mode: bool, // When true, generates simulated findings without real scanners
+    /// Request pacing against the target, applied to our own requests and passed to ZAP/Nuclei
+    #[serde(default = "default_requests_per_second")]
+    pub requests_per_second: u32,
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+}
+
+fn default_requests_per_second() -> u32 {
+    10
+}
+
+fn default_max_concurrent_requests() -> usize {
+    4
 }
 
 impl Default for SecurityLabConfig {
@@ -54,6 +73,8 @@ impl Default for SecurityLabConfig {
             allowed_targets: vec![],
             openai_api_key: None,
             demo_mode: false, // Real scanning by default
+            requests_per_second: default_requests_per_second(),
+            max_concurrent_requests: default_max_concurrent_requests(),
         }
     }
 }
@@ -95,6 +116,120 @@ pub struct VulnerabilityScan {
     pub low_count: usize,
     pub info_count: usize,
     pub error_message: Option<String>,
+    #[serde(default)]
+    pub scope_check: Option<ScopeCheck>,
+    #[serde(default)]
+    pub rate_limit: Option<ScanRateLimit>,
+}
+
+/// Why a target was accepted as in scope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeCheck {
+    pub host: String,
+    /// `verified:<domain>`, `allowed:<target>` or `loopback`
+    pub matched_by: String,
+    pub checked_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanRateLimit {
+    pub requests_per_second: u32,
+    pub max_concurrent_requests: usize,
+}
+
+/// Paces requests to a scan target and aborts them when the scan is cancelled
+pub struct ScanThrottle {
+    interval: Duration,
+    next_slot: Mutex<tokio::time::Instant>,
+    permits: Arc<Semaphore>,
+    cancel: CancellationToken,
+}
+
+impl ScanThrottle {
+    pub fn new(limit: &ScanRateLimit, cancel: CancellationToken) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / limit.requests_per_second.max(1) as f64),
+            next_slot: Mutex::new(tokio::time::Instant::now()),
+            permits: Arc::new(Semaphore::new(limit.max_concurrent_requests.max(1))),
+            cancel,
+        }
+    }
+
+    /// Waits for a concurrency permit and the next rate slot, then runs the
+    /// request. Cancellation drops the request, aborting it mid-flight.
+    pub async fn run<T>(&self, request: impl Future<Output = T>) -> Result<T> {
+        let cancelled = || anyhow!("Scan cancelled");
+        let _permit = tokio::select! {
+            permit = self.permits.clone().acquire_owned() => permit?,
+            _ = self.cancel.cancelled() => return Err(cancelled()),
+        };
+
+        let slot = {
+            let mut next = self.next_slot.lock().await;
+            let slot = (*next).max(tokio::time::Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        tokio::select! {
+            _ = tokio::time::sleep_until(slot) => {}
+            _ = self.cancel.cancelled() => return Err(cancelled()),
+        }
+
+        tokio::select! {
+            output = request => Ok(output),
+            _ = self.cancel.cancelled() => Err(cancelled()),
+        }
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim().trim_end_matches('.').trim_start_matches('[').trim_end_matches(']').to_lowercase()
+}
+
+/// Whether `host` is `domain` or one of its subdomains. Lookalikes such as
+/// `example.com.evil.net` or `evilexample.com` do not match `example.com`.
+fn host_in_domain(host: &str, domain: &str) -> bool {
+    let domain = normalize_host(domain);
+    !domain.is_empty() && (host == domain || host.ends_with(&format!(".{}", domain)))
+}
+
+/// Checks a target against verified domains and explicitly allowed targets.
+/// Loopback hosts are always in scope for local testing.
+pub fn check_target_scope(target_url: &str, verified: &[String], allowed: &[String]) -> Result<ScopeCheck> {
+    let parsed = url::Url::parse(target_url).map_err(|e| anyhow!("Invalid URL: {}", e))?;
+    let host = normalize_host(parsed.host_str().ok_or(anyhow!("URL must contain a valid host/domain"))?);
+
+    let loopback = host == "localhost" || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback());
+    let matched_by = if loopback {
+        Some("loopback".to_string())
+    } else if let Some(domain) = verified.iter().find(|d| host_in_domain(&host, d)) {
+        Some(format!("verified:{}", normalize_host(domain)))
+    } else {
+        allowed
+            .iter()
+            .find(|target| {
+                let target_host = url::Url::parse(target)
+                    .ok()
+                    .and_then(|u| u.host_str().map(String::from))
+                    .unwrap_or_else(|| target.to_string());
+                host_in_domain(&host, &target_host)
+            })
+            .map(|target| format!("allowed:{}", target))
+    };
+
+    match matched_by {
+        Some(matched_by) => Ok(ScopeCheck {
+            host,
+            matched_by,
+            checked_at: Utc::now().to_rfc3339(),
+        }),
+        None => Err(anyhow!(
+            "Target '{}' is out of scope. Verify the domain with security_lab_verify_domain or add it to allowed targets.",
+            host
+        )),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -209,6 +344,7 @@ impl SecurityLabService {
             exploits: Arc::new(Mutex::new(HashMap::new())),
             verified_domains: Arc::new(Mutex::new(Vec::new())),
             config: Arc::new(Mutex::new(SecurityLabConfig::default())),
+            cancellations: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -219,6 +355,9 @@ impl SecurityLabService {
     }
 
     pub async fn update_config(&self, config: SecurityLabConfig) -> Result<()> {
+        if config.requests_per_second == 0 || config.max_concurrent_requests == 0 {
+            return Err(anyhow!("Requests per second and max concurrent requests must be at least 1"));
+        }
         *self.config.lock().await = config;
         Ok(())
     }
@@ -373,8 +512,9 @@ impl SecurityLabService {
             return true;
         }
 
+        let host = normalize_host(domain);
         let verified = self.verified_domains.lock().await;
-        verified.iter().any(|d| domain.contains(d))
+        verified.iter().any(|d| host_in_domain(&host, d))
     }
 
     // ============ URL Validation ============
@@ -419,46 +559,16 @@ impl SecurityLabService {
         Ok(())
     }
 
-    // ============ Ethical Checks ============
+    // ============ Scope Checks ============
 
-    async fn check_ethical_compliance(&self, target_url: &str) -> Result<()> {
-        let config = self.config.lock().await.clone();
-
-        // If ethical mode is disabled, allow all scans
-        if !config.ethical_mode {
-            log::info!("⚠️ Ethical mode disabled - skipping domain verification");
-            return Ok(());
-        }
-
-        // Extract domain from URL
-        let domain = self.extract_domain(target_url)?;
-
-        // Always allow localhost and local testing
-        if domain == "localhost" || domain == "127.0.0.1" || domain.ends_with(".local") || domain.ends_with(".test") {
-            return Ok(());
-        }
-
-        // Check if domain is in allowed targets (explicit whitelist)
-        if config.allowed_targets.iter().any(|t| domain.contains(t) || target_url.contains(t)) {
-            log::info!("✅ Domain {} is in allowed targets list", domain);
-            return Ok(());
-        }
+    /// Refuses targets outside verified domains and configured allowed targets
+    async fn check_scope(&self, target_url: &str) -> Result<ScopeCheck> {
+        let allowed = self.config.lock().await.allowed_targets.clone();
+        let verified = self.verified_domains.lock().await.clone();
 
-        // Check if domain is verified
-        if self.is_domain_verified(&domain).await {
-            return Ok(());
-        }
-
-        // Domain not verified - provide helpful error
-        return Err(anyhow!(
-            "Domain '{}' not verified. Options: 1) Add to allowed targets in config, 2) Verify domain ownership, 3) Disable ethical mode for testing.", domain
-        ));
-    }
-
-    fn extract_domain(&self, url: &str) -> Result<String> {
-        let url_parsed = url::Url::parse(url)?;
-        let host = url_parsed.host_str().ok_or(anyhow!("Invalid URL"))?;
-        Ok(host.to_string())
+        let scope = check_target_scope(target_url, &verified, &allowed)?;
+        log::info!("✅ Target {} in scope ({})", scope.host, scope.matched_by);
+        Ok(scope)
     }
 
     // ============ Scanning ============
@@ -472,8 +582,15 @@ impl SecurityLabService {
         // Validate URL format
         self.validate_url(&target_url)?;
         
-        // Ethical compliance check
-        self.check_ethical_compliance(&target_url).await?;
+        // Scope check
+        let scope_check = self.check_scope(&target_url).await?;
+        let rate_limit = {
+            let config = self.config.lock().await;
+            ScanRateLimit {
+                requests_per_second: config.requests_per_second,
+                max_concurrent_requests: config.max_concurrent_requests,
+            }
+        };
 
         let scan_id = Uuid::new_v4().to_string();
 
@@ -493,6 +610,8 @@ impl SecurityLabService {
             low_count: 0,
             info_count: 0,
             error_message: None,
+            scope_check: Some(scope_check),
+            rate_limit: Some(rate_limit.clone()),
         };
 
         {
@@ -507,14 +626,33 @@ impl SecurityLabService {
         // Emit event
         self.app.emit("security_lab:scan_started", &scan).ok();
 
-        // Start scan in background
+        let cancel = CancellationToken::new();
+        self.cancellations.lock().await.insert(scan_id.clone(), cancel.clone());
+
+        // Start scan in background; cancelling drops every in-flight request and
+        // kills scanner subprocesses
         let service = self.clone();
         let scan_id_clone = scan_id.clone();
         tokio::spawn(async move {
-            service
-                .execute_scan(scan_id_clone, target_url, scan_type, scanner)
-                .await
-                .ok();
+            let throttle = ScanThrottle::new(&rate_limit, cancel.clone());
+            let result = tokio::select! {
+                result = service.execute_scan(scan_id_clone.clone(), target_url, scan_type, scanner, &throttle) => result,
+                _ = cancel.cancelled() => Ok(()),
+            };
+            service.cancellations.lock().await.remove(&scan_id_clone);
+
+            if let Err(e) = result {
+                log::error!("❌ Scan {} failed: {}", scan_id_clone, e);
+                let mut scans = service.scans.lock().await;
+                if let Some(scan) = scans.get_mut(&scan_id_clone) {
+                    if scan.status == ScanStatus::Running || scan.status == ScanStatus::Pending {
+                        scan.status = ScanStatus::Failed;
+                        scan.error_message = Some(e.to_string());
+                        scan.completed_at = Some(Utc::now().to_rfc3339());
+                        service.app.emit("security_lab:scan_failed", &*scan).ok();
+                    }
+                }
+            }
         });
 
         Ok(scan)
@@ -526,6 +664,7 @@ impl SecurityLabService {
         target_url: String,
         scan_type: ScanType,
         scanner: Scanner,
+        throttle: &ScanThrottle,
     ) -> Result<()> {
         // Update status to running
         {
@@ -548,6 +687,8 @@ impl SecurityLabService {
             // Run demo scan with simulated findings
             self.run_demo_scan(&scan_id, &target_url, &scan_type, &scanner).await?;
         } else {
+            self.preflight_target(&target_url, throttle).await?;
+
             // Execute real scanners based on scanner type
             match scanner {
                 Scanner::Zap => {
@@ -570,7 +711,7 @@ impl SecurityLabService {
         // Update scan status
         {
             let mut scans = self.scans.lock().await;
-            if let Some(scan) = scans.get_mut(&scan_id) {
+            if let Some(scan) = scans.get_mut(&scan_id).filter(|s| s.status == ScanStatus::Running) {
                 scan.status = ScanStatus::Completed;
                 scan.progress = 1.0;
                 scan.completed_at = Some(Utc::now().to_rfc3339());
@@ -614,6 +755,22 @@ impl SecurityLabService {
         Ok(())
     }
 
+    /// Confirms the target answers before handing it to the scanners. Redirects
+    /// are not followed so a redirect cannot move the scan out of scope.
+    async fn preflight_target(&self, target_url: &str, throttle: &ScanThrottle) -> Result<()> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| anyhow!("Failed to create HTTP client: {}", e))?;
+
+        throttle
+            .run(client.head(target_url).send())
+            .await?
+            .map_err(|e| anyhow!("Target unreachable: {}", e))?;
+        Ok(())
+    }
+
     /// Demo scan that generates simulated findings for testing
     async fn run_demo_scan(
         &self,
@@ -841,6 +998,18 @@ impl SecurityLabService {
         
         log::info!("✅ ZAP is running. Starting scan...");
 
+        // Apply the scan rate limit to ZAP's own requests against the target
+        let delay_ms = 1000 / config.requests_per_second.max(1);
+        for option in [
+            format!("spider/action/setOptionThreadCount/?Integer={}", config.max_concurrent_requests),
+            format!("ascan/action/setOptionThreadPerHost/?Integer={}", config.max_concurrent_requests),
+            format!("ascan/action/setOptionDelayInMs/?Integer={}", delay_ms),
+        ] {
+            if let Err(e) = client.get(format!("{}/JSON/{}", zap_url, option)).send().await {
+                log::warn!("Failed to apply ZAP option {}: {}", option, e);
+            }
+        }
+
         // Start spider
         self.emit_scan_progress(scan_id, 0.2).await;
         let spider_url = format!("{}/JSON/spider/action/scan/?url={}", zap_url, urlencoding::encode(target_url));
@@ -978,25 +1147,31 @@ impl SecurityLabService {
         self.emit_scan_progress(scan_id, 0.6).await;
 
         // Build nuclei command
-        let severity_flag = match scan_type {
-            ScanType::Quick => "-s critical,high",
-            ScanType::Standard => "-s critical,high,medium",
-            ScanType::Full => "-s critical,high,medium,low,info",
-            ScanType::Custom => "-s critical,high,medium",
+        let severities = match scan_type {
+            ScanType::Quick => "critical,high",
+            ScanType::Standard => "critical,high,medium",
+            ScanType::Full => "critical,high,medium,low,info",
+            ScanType::Custom => "critical,high,medium",
         };
+        let rate_limit = config.requests_per_second.to_string();
+        let concurrency = config.max_concurrent_requests.to_string();
 
-        // Execute nuclei
+        // Execute nuclei; killed if the scan is cancelled
         let output = TokioCommand::new(&config.nuclei_binary_path)
             .args([
                 "-u",
                 target_url,
-                severity_flag,
+                "-s",
+                severities,
                 "-json",
                 "-timeout",
                 "30",
                 "-rl",
-                "150", // Rate limit
+                &rate_limit,
+                "-c",
+                &concurrency,
             ])
+            .kill_on_drop(true)
             .output()
             .await?;
 
@@ -1117,11 +1292,36 @@ impl SecurityLabService {
         Ok(scan_list)
     }
 
+    /// Stops a scan immediately: in-flight requests are dropped, Nuclei is killed
+    /// and ZAP is told to stop its spider and active scans
     pub async fn cancel_scan(&self, scan_id: String) -> Result<()> {
-        let mut scans = self.scans.lock().await;
-        if let Some(scan) = scans.get_mut(&scan_id) {
+        let scanner = {
+            let mut scans = self.scans.lock().await;
+            let scan = scans.get_mut(&scan_id).ok_or(anyhow!("Scan not found"))?;
+            if !matches!(scan.status, ScanStatus::Pending | ScanStatus::Running) {
+                return Ok(());
+            }
             scan.status = ScanStatus::Cancelled;
             scan.completed_at = Some(Utc::now().to_rfc3339());
+            self.app.emit("security_lab:scan_cancelled", &*scan).ok();
+            scan.scanner.clone()
+        };
+
+        if let Some(cancel) = self.cancellations.lock().await.remove(&scan_id) {
+            cancel.cancel();
+        }
+
+        let config = self.config.lock().await.clone();
+        if scanner != Scanner::Nuclei && config.zap_enabled && !config.demo_mode {
+            let zap_url = format!("http://{}:{}", config.zap_host, config.zap_port);
+            let client = reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(5))
+                .build()?;
+            for action in ["spider/action/stopAllScans/", "ascan/action/stopAllScans/"] {
+                if let Err(e) = client.get(format!("{}/JSON/{}", zap_url, action)).send().await {
+                    log::warn!("Failed to stop ZAP scans: {}", e);
+                }
+            }
         }
         Ok(())
     }
@@ -1186,7 +1386,7 @@ impl SecurityLabService {
         let finding = self.get_finding(finding_id.clone()).await?;
 
         // Ethical check
-        self.check_ethical_compliance(&finding.affected_url).await?;
+        self.check_scope(&finding.affected_url).await?;
 
         let session_id = Uuid::new_v4().to_string();
 
@@ -1346,3 +1546,79 @@ impl SecurityLabService {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_scope_refuses_unverified_and_lookalike_targets() {
+        let verified = vec!["example.com".to_string()];
+        let allowed = vec!["https://staging.test.org".to_string()];
+
+        let scope = check_target_scope("https://api.Example.com/login", &verified, &allowed).unwrap();
+        assert_eq!(scope.host, "api.example.com");
+        assert_eq!(scope.matched_by, "verified:example.com");
+        assert_eq!(check_target_scope("https://staging.test.org:8443/", &verified, &allowed).unwrap().matched_by, "allowed:https://staging.test.org");
+        assert_eq!(check_target_scope("http://127.0.0.1:3000", &[], &[]).unwrap().matched_by, "loopback");
+
+        for target in [
+            "https://example.com.evil.net",
+            "https://evilexample.com",
+            "https://test.org",
+            "https://unverified.io",
+            "not a url",
+        ] {
+            assert!(check_target_scope(target, &verified, &allowed).is_err(), "{} should be refused", target);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_throttle_paces_requests_and_aborts_on_cancel() {
+        let limit = ScanRateLimit { requests_per_second: 20, max_concurrent_requests: 2 };
+        let throttle = Arc::new(ScanThrottle::new(&limit, CancellationToken::new()));
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let started = std::time::Instant::now();
+        let requests: Vec<_> = (0..10)
+            .map(|_| {
+                let (throttle, in_flight, peak) = (throttle.clone(), in_flight.clone(), peak.clone());
+                tokio::spawn(async move {
+                    throttle
+                        .run(async {
+                            let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                            peak.fetch_max(now, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(20)).await;
+                            in_flight.fetch_sub(1, Ordering::SeqCst);
+                        })
+                        .await
+                })
+            })
+            .collect();
+        for request in requests {
+            request.await.unwrap().unwrap();
+        }
+        // Ten requests at 20/s need at least nine 50ms intervals
+        assert!(started.elapsed() >= Duration::from_millis(440));
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+
+        // Cancelling aborts the in-flight request and everything still queued
+        let cancel = CancellationToken::new();
+        let throttle = Arc::new(ScanThrottle::new(&limit, cancel.clone()));
+        let requests: Vec<_> = (0..5)
+            .map(|_| {
+                let throttle = throttle.clone();
+                tokio::spawn(async move { throttle.run(tokio::time::sleep(Duration::from_secs(30))).await })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let cancelled_at = std::time::Instant::now();
+        cancel.cancel();
+        for request in requests {
+            assert!(request.await.unwrap().is_err());
+        }
+        assert!(cancelled_at.elapsed() < Duration::from_millis(200));
+    }
+}
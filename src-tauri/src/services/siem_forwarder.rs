@@ -0,0 +1,852 @@
+// ═══════════════════════════════════════════════════════════════════════════════
+// SIEM FORWARDER - Real-time security event delivery
+// ═══════════════════════════════════════════════════════════════════════════════
+//
+// Streams security events and alerts to configured SIEMs as RFC 5424 syslog,
+// CEF, LEEF or Splunk HTTP Event Collector payloads over UDP, TCP, TLS or
+// HTTPS. Undelivered events stay in a bounded on-disk buffer per integration
+// and are retried with exponential backoff.
+//
+// ═══════════════════════════════════════════════════════════════════════════════
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+const PRODUCT_VENDOR: &str = "CUBE";
+const PRODUCT_NAME: &str = "CUBE Nexum";
+const APP_NAME: &str = "cube-nexum";
+/// IANA private enterprise number used for the RFC 5424 structured data id
+const SD_ID: &str = "cube@32473";
+/// Syslog facility 13: log audit
+const SYSLOG_FACILITY: u8 = 13;
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_BACKOFF_MS: i64 = 5 * 60 * 1000;
+
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+pub const DEFAULT_MAX_BUFFERED: usize = 10_000;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TYPES
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SiemFormat {
+    /// RFC 5424 with the event fields as structured data
+    Syslog,
+    Cef,
+    Leef,
+    SplunkHec,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SiemTransport {
+    Udp,
+    Tcp,
+    Tls,
+    Https,
+}
+
+impl SiemTransport {
+    /// Transport and address for an endpoint such as `tls://siem:6514`,
+    /// `udp://10.0.0.5:514` or `https://splunk:8088/services/collector`.
+    /// A bare `host:port` is sent over TCP.
+    pub fn from_endpoint(endpoint: &str) -> (Self, String) {
+        let endpoint = endpoint.trim();
+        match endpoint.split_once("://") {
+            Some(("udp", address)) => (Self::Udp, address.to_string()),
+            Some(("tcp", address)) => (Self::Tcp, address.to_string()),
+            Some(("tls", address)) | Some(("syslog+tls", address)) => (Self::Tls, address.to_string()),
+            Some(("http", _)) | Some(("https", _)) => (Self::Https, endpoint.to_string()),
+            _ => (Self::Tcp, endpoint.to_string()),
+        }
+    }
+}
+
+/// Where and how to deliver events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiemTarget {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub format: SiemFormat,
+    pub transport: SiemTransport,
+    /// `host:port` for socket transports, a URL for HTTPS
+    pub address: String,
+    /// HEC token or bearer token for HTTPS delivery
+    pub token: Option<String>,
+    /// Field name -> key in the target format; an empty key drops the field
+    pub field_mapping: HashMap<String, String>,
+    /// Event types to forward; empty forwards everything
+    pub event_types: Vec<String>,
+    pub batch_size: usize,
+    pub max_buffered: usize,
+}
+
+/// A security event or alert flattened for delivery
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SiemRecord {
+    pub id: String,
+    /// Unix seconds
+    pub timestamp: i64,
+    pub event_type: String,
+    pub name: String,
+    /// 0 (lowest) to 10 (highest), as in CEF
+    pub severity: u8,
+    pub source: String,
+    pub source_ip: Option<String>,
+    pub user: Option<String>,
+    pub resource: Option<String>,
+    pub action: Option<String>,
+    pub outcome: Option<String>,
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+}
+
+impl SiemRecord {
+    /// Logical fields in a stable order, excluding timestamp, name and severity
+    fn logical_fields(&self) -> Vec<(&str, String)> {
+        let mut fields = vec![("event_type", self.event_type.clone()), ("source", self.source.clone())];
+        for (name, value) in [
+            ("source_ip", &self.source_ip),
+            ("user", &self.user),
+            ("resource", &self.resource),
+            ("action", &self.action),
+            ("outcome", &self.outcome),
+        ] {
+            if let Some(value) = value {
+                fields.push((name, value.clone()));
+            }
+        }
+        fields.extend(self.fields.iter().map(|(k, v)| (k.as_str(), v.clone())));
+        fields
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SiemDeliveryStatus {
+    pub target_id: String,
+    pub buffered: usize,
+    pub sent: u64,
+    pub dropped: u64,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+    pub last_sync_at: Option<i64>,
+    /// Unix milliseconds of the next retry while the SIEM is unreachable
+    pub next_retry_at: Option<i64>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// FORMATTING
+// ═══════════════════════════════════════════════════════════════════════════════
+
+fn default_key(format: SiemFormat, field: &str) -> String {
+    let key = match (format, field) {
+        (SiemFormat::Cef, "event_type") => "cat",
+        (SiemFormat::Cef, "source") => "dvchost",
+        (SiemFormat::Cef, "source_ip") => "src",
+        (SiemFormat::Cef, "user") => "suser",
+        (SiemFormat::Cef, "resource") => "request",
+        (SiemFormat::Cef, "action") => "act",
+        (SiemFormat::Leef, "event_type") => "cat",
+        (SiemFormat::Leef, "source_ip") => "src",
+        (SiemFormat::Leef, "user") => "usrName",
+        (_, field) => field,
+    };
+    key.to_string()
+}
+
+/// Fields renamed by the target's mapping, with dropped fields removed
+fn mapped_fields(record: &SiemRecord, format: SiemFormat, mapping: &HashMap<String, String>) -> Vec<(String, String)> {
+    record
+        .logical_fields()
+        .into_iter()
+        .filter_map(|(field, value)| {
+            let key = mapping.get(field).cloned().unwrap_or_else(|| default_key(format, field));
+            (!key.is_empty()).then_some((key, value))
+        })
+        .collect()
+}
+
+fn cef_header_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+fn cef_extension_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+/// ArcSight Common Event Format line
+pub fn format_cef(record: &SiemRecord, mapping: &HashMap<String, String>) -> String {
+    let mut extensions = vec![format!("rt={}", record.timestamp * 1000)];
+    extensions.extend(
+        mapped_fields(record, SiemFormat::Cef, mapping)
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, cef_extension_escape(&value))),
+    );
+    format!(
+        "CEF:0|{}|{}|{}|{}|{}|{}|{}",
+        PRODUCT_VENDOR,
+        cef_header_escape(PRODUCT_NAME),
+        env!("CARGO_PKG_VERSION"),
+        cef_header_escape(&record.event_type),
+        cef_header_escape(&record.name),
+        record.severity.min(10),
+        extensions.join(" ")
+    )
+}
+
+/// IBM QRadar Log Event Extended Format line (LEEF 1.0, tab delimited)
+pub fn format_leef(record: &SiemRecord, mapping: &HashMap<String, String>) -> String {
+    let clean = |value: &str| value.replace(['\t', '\r', '\n'], " ");
+    let devtime = chrono::DateTime::from_timestamp(record.timestamp, 0)
+        .unwrap_or_default()
+        .format("%b %d %Y %H:%M:%S");
+    let mut attributes = vec![
+        format!("devTime={}", devtime),
+        format!("sev={}", record.severity.min(10)),
+        format!("name={}", clean(&record.name)),
+    ];
+    attributes.extend(
+        mapped_fields(record, SiemFormat::Leef, mapping)
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, clean(&value))),
+    );
+    format!(
+        "LEEF:1.0|{}|{}|{}|{}|{}",
+        PRODUCT_VENDOR,
+        PRODUCT_NAME,
+        env!("CARGO_PKG_VERSION"),
+        clean(&record.event_type).replace('|', "_"),
+        attributes.join("\t")
+    )
+}
+
+fn syslog_severity(severity: u8) -> u8 {
+    match severity {
+        9.. => 2,
+        7..=8 => 3,
+        5..=6 => 4,
+        3..=4 => 5,
+        _ => 6,
+    }
+}
+
+/// RFC 5424 header up to and including the MSGID
+fn syslog_header(record: &SiemRecord, hostname: &str) -> String {
+    let timestamp = chrono::DateTime::from_timestamp(record.timestamp, 0)
+        .unwrap_or_default()
+        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let msgid: String = record
+        .event_type
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(32)
+        .collect();
+    format!(
+        "<{}>1 {} {} {} - {}",
+        SYSLOG_FACILITY * 8 + syslog_severity(record.severity),
+        timestamp,
+        if hostname.is_empty() { "-" } else { hostname },
+        APP_NAME,
+        if msgid.is_empty() { "-".to_string() } else { msgid }
+    )
+}
+
+/// RFC 5424 message with the event fields as structured data
+pub fn format_syslog(record: &SiemRecord, mapping: &HashMap<String, String>, hostname: &str) -> String {
+    let params: Vec<String> = mapped_fields(record, SiemFormat::Syslog, mapping)
+        .into_iter()
+        .map(|(key, value)| {
+            let key: String = key
+                .chars()
+                .filter(|c| c.is_ascii_graphic() && !matches!(c, '=' | ']' | '"'))
+                .take(32)
+                .collect();
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace(']', "\\]");
+            format!(" {}=\"{}\"", key, value)
+        })
+        .collect();
+    format!(
+        "{} [{} severity=\"{}\"{}] {}",
+        syslog_header(record, hostname),
+        SD_ID,
+        record.severity.min(10),
+        params.concat(),
+        record.name
+    )
+}
+
+/// Splunk HTTP Event Collector event object
+pub fn format_hec(record: &SiemRecord, mapping: &HashMap<String, String>, hostname: &str) -> String {
+    let mut event = serde_json::Map::new();
+    event.insert("id".to_string(), record.id.clone().into());
+    event.insert("name".to_string(), record.name.clone().into());
+    event.insert("severity".to_string(), record.severity.min(10).into());
+    for (key, value) in mapped_fields(record, SiemFormat::SplunkHec, mapping) {
+        event.insert(key, value.into());
+    }
+    serde_json::json!({
+        "time": record.timestamp,
+        "host": hostname,
+        "source": APP_NAME,
+        "sourcetype": "cube:security",
+        "event": event,
+    })
+    .to_string()
+}
+
+/// Payload for one record as it goes on the wire to `target`
+pub fn render(target: &SiemTarget, record: &SiemRecord, hostname: &str) -> String {
+    let message = match target.format {
+        SiemFormat::Syslog => return format_syslog(record, &target.field_mapping, hostname),
+        SiemFormat::SplunkHec => return format_hec(record, &target.field_mapping, hostname),
+        SiemFormat::Cef => format_cef(record, &target.field_mapping),
+        SiemFormat::Leef => format_leef(record, &target.field_mapping),
+    };
+    // CEF/LEEF ride inside a syslog frame unless posted over HTTPS
+    match target.transport {
+        SiemTransport::Https => message,
+        _ => format!("{} - {}", syslog_header(record, hostname), message),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// DELIVERY
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Delivers rendered payloads to a SIEM
+#[async_trait::async_trait]
+pub trait SiemSink: Send + Sync {
+    async fn send(&self, target: &SiemTarget, payloads: &[String]) -> Result<(), String>;
+}
+
+/// Sends over UDP, TCP/TLS with RFC 6587 octet counting, or HTTPS
+pub struct NetworkSink {
+    http: reqwest::Client,
+}
+
+impl Default for NetworkSink {
+    fn default() -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(SEND_TIMEOUT)
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+        }
+    }
+}
+
+impl NetworkSink {
+    async fn send_stream<S>(stream: &mut S, payloads: &[String]) -> std::io::Result<()>
+    where
+        S: tokio::io::AsyncWrite + Unpin,
+    {
+        for payload in payloads {
+            stream.write_all(format!("{} {}", payload.len(), payload).as_bytes()).await?;
+        }
+        stream.flush().await
+    }
+
+    async fn deliver(&self, target: &SiemTarget, payloads: &[String]) -> Result<(), String> {
+        match target.transport {
+            SiemTransport::Udp => {
+                let socket = tokio::net::UdpSocket::bind("0.0.0.0:0")
+                    .await
+                    .map_err(|e| format!("UDP bind failed: {}", e))?;
+                for payload in payloads {
+                    socket
+                        .send_to(payload.as_bytes(), &target.address)
+                        .await
+                        .map_err(|e| format!("UDP send to {} failed: {}", target.address, e))?;
+                }
+                Ok(())
+            }
+            SiemTransport::Tcp => {
+                let mut stream = tokio::net::TcpStream::connect(&target.address)
+                    .await
+                    .map_err(|e| format!("Failed to connect to {}: {}", target.address, e))?;
+                Self::send_stream(&mut stream, payloads)
+                    .await
+                    .map_err(|e| format!("Send to {} failed: {}", target.address, e))
+            }
+            SiemTransport::Tls => {
+                use tokio_util::compat::{FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt};
+
+                let host = target.address.rsplit_once(':').map_or(target.address.as_str(), |(h, _)| h);
+                let tcp = tokio::net::TcpStream::connect(&target.address)
+                    .await
+                    .map_err(|e| format!("Failed to connect to {}: {}", target.address, e))?;
+                let tls = async_native_tls::connect(host, tcp.compat())
+                    .await
+                    .map_err(|e| format!("TLS handshake with {} failed: {}", host, e))?;
+                let mut stream = tls.compat();
+                Self::send_stream(&mut stream, payloads)
+                    .await
+                    .map_err(|e| format!("Send to {} failed: {}", target.address, e))
+            }
+            SiemTransport::Https => {
+                let mut request = self.http.post(&target.address).body(payloads.join("\n"));
+                if let Some(token) = &target.token {
+                    request = match target.format {
+                        SiemFormat::SplunkHec => request.header("Authorization", format!("Splunk {}", token)),
+                        _ => request.bearer_auth(token),
+                    };
+                }
+                let response = request
+                    .send()
+                    .await
+                    .map_err(|e| format!("POST to {} failed: {}", target.address, e))?;
+                if response.status().is_success() {
+                    Ok(())
+                } else {
+                    Err(format!("SIEM returned HTTP {}", response.status()))
+                }
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SiemSink for NetworkSink {
+    async fn send(&self, target: &SiemTarget, payloads: &[String]) -> Result<(), String> {
+        tokio::time::timeout(SEND_TIMEOUT, self.deliver(target, payloads))
+            .await
+            .map_err(|_| format!("Timed out sending to {}", target.address))?
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// FORWARDER
+// ═══════════════════════════════════════════════════════════════════════════════
+
+struct TargetState {
+    target: SiemTarget,
+    queue: VecDeque<SiemRecord>,
+    status: SiemDeliveryStatus,
+    in_flight: bool,
+}
+
+pub struct SiemForwarder {
+    targets: Mutex<HashMap<String, TargetState>>,
+    sink: Arc<dyn SiemSink>,
+    spool_dir: PathBuf,
+    hostname: String,
+    notify: tokio::sync::Notify,
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn backoff_ms(failures: u32) -> i64 {
+    (1000i64 << failures.saturating_sub(1).min(16)).min(MAX_BACKOFF_MS)
+}
+
+impl SiemForwarder {
+    /// Buffer undelivered events under `spool_dir`
+    pub fn new(spool_dir: PathBuf) -> Self {
+        Self {
+            targets: Mutex::new(HashMap::new()),
+            sink: Arc::new(NetworkSink::default()),
+            spool_dir,
+            hostname: hostname::get()
+                .ok()
+                .and_then(|h| h.into_string().ok())
+                .unwrap_or_else(|| APP_NAME.to_string()),
+            notify: tokio::sync::Notify::new(),
+        }
+    }
+
+    pub fn with_sink(mut self, sink: Arc<dyn SiemSink>) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    fn spool_path(&self, target_id: &str) -> PathBuf {
+        let name: String = target_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.spool_dir.join(format!("{}.jsonl", name))
+    }
+
+    fn load_spool(&self, target_id: &str) -> VecDeque<SiemRecord> {
+        std::fs::read_to_string(self.spool_path(target_id))
+            .map(|contents| {
+                contents
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn persist(&self, state: &TargetState) {
+        let path = self.spool_path(&state.target.id);
+        let result = if state.queue.is_empty() {
+            match std::fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        } else {
+            let contents: String = state
+                .queue
+                .iter()
+                .filter_map(|record| serde_json::to_string(record).ok())
+                .map(|line| line + "\n")
+                .collect();
+            let tmp = path.with_extension("jsonl.tmp");
+            std::fs::create_dir_all(&self.spool_dir)
+                .and_then(|_| std::fs::write(&tmp, contents))
+                .and_then(|_| std::fs::rename(&tmp, &path))
+        };
+        if let Err(e) = result {
+            warn!("⚠️ Failed to persist SIEM buffer for {}: {}", state.target.id, e);
+        }
+    }
+
+    /// Add or replace a delivery target, resuming any events buffered on disk
+    pub fn upsert_target(&self, target: SiemTarget) -> Result<(), String> {
+        if target.address.trim().is_empty() {
+            return Err("SIEM endpoint is required".to_string());
+        }
+        if target.transport == SiemTransport::Https && url::Url::parse(&target.address).is_err() {
+            return Err(format!("Invalid SIEM URL: {}", target.address));
+        }
+
+        let mut targets = self.targets.lock().map_err(|e| format!("Lock error: {}", e))?;
+        match targets.get_mut(&target.id) {
+            Some(state) => state.target = target,
+            None => {
+                let queue = self.load_spool(&target.id);
+                if !queue.is_empty() {
+                    info!("📦 Resuming {} buffered SIEM events for {}", queue.len(), target.name);
+                }
+                let status = SiemDeliveryStatus {
+                    target_id: target.id.clone(),
+                    buffered: queue.len(),
+                    ..Default::default()
+                };
+                targets.insert(target.id.clone(), TargetState { target, queue, status, in_flight: false });
+            }
+        }
+        drop(targets);
+        self.notify.notify_one();
+        Ok(())
+    }
+
+    /// Stop forwarding to a target and discard its buffer
+    pub fn remove_target(&self, target_id: &str) {
+        if let Ok(mut targets) = self.targets.lock() {
+            targets.remove(target_id);
+        }
+        let _ = std::fs::remove_file(self.spool_path(target_id));
+    }
+
+    /// Queue a record for every enabled target that wants its event type
+    pub fn forward(&self, record: SiemRecord) {
+        let Ok(mut targets) = self.targets.lock() else {
+            return;
+        };
+        let mut queued = false;
+        for state in targets.values_mut() {
+            let target = &state.target;
+            let wanted = target.event_types.is_empty()
+                || target.event_types.iter().any(|t| t == "*" || t.eq_ignore_ascii_case(&record.event_type));
+            if !target.enabled || !wanted {
+                continue;
+            }
+
+            state.queue.push_back(record.clone());
+            while state.queue.len() > target.max_buffered.max(1) {
+                state.queue.pop_front();
+                state.status.dropped += 1;
+            }
+            state.status.buffered = state.queue.len();
+            self.persist(state);
+            queued = true;
+        }
+        drop(targets);
+        if queued {
+            self.notify.notify_one();
+        }
+    }
+
+    pub fn status(&self, target_id: &str) -> Option<SiemDeliveryStatus> {
+        self.targets.lock().ok()?.get(target_id).map(|state| state.status.clone())
+    }
+
+    /// Send one batch to every target that has events and is not backing off.
+    /// Returns how many events were delivered.
+    pub async fn flush_due(&self, now_ms: i64) -> usize {
+        let batches: Vec<(SiemTarget, Vec<SiemRecord>)> = {
+            let Ok(mut targets) = self.targets.lock() else {
+                return 0;
+            };
+            targets
+                .values_mut()
+                .filter(|state| {
+                    !state.in_flight
+                        && !state.queue.is_empty()
+                        && state.status.next_retry_at.map_or(true, |at| now_ms >= at)
+                })
+                .map(|state| {
+                    state.in_flight = true;
+                    let batch = state.queue.iter().take(state.target.batch_size.max(1)).cloned().collect();
+                    (state.target.clone(), batch)
+                })
+                .collect()
+        };
+
+        let mut delivered = 0;
+        for (target, batch) in batches {
+            let payloads: Vec<String> = batch.iter().map(|record| render(&target, record, &self.hostname)).collect();
+            let result = self.sink.send(&target, &payloads).await;
+
+            let Ok(mut targets) = self.targets.lock() else {
+                return delivered;
+            };
+            let Some(state) = targets.get_mut(&target.id) else {
+                continue;
+            };
+            state.in_flight = false;
+            match result {
+                Ok(()) => {
+                    let sent: HashSet<&str> = batch.iter().map(|record| record.id.as_str()).collect();
+                    state.queue.retain(|record| !sent.contains(record.id.as_str()));
+                    state.status.sent += batch.len() as u64;
+                    state.status.consecutive_failures = 0;
+                    state.status.last_error = None;
+                    state.status.last_sync_at = Some(now_ms / 1000);
+                    state.status.next_retry_at = None;
+                    delivered += batch.len();
+                }
+                Err(e) => {
+                    state.status.consecutive_failures += 1;
+                    state.status.next_retry_at = Some(now_ms + backoff_ms(state.status.consecutive_failures));
+                    warn!(
+                        "⚠️ SIEM delivery to {} failed ({} buffered): {}",
+                        target.name,
+                        state.queue.len(),
+                        e
+                    );
+                    state.status.last_error = Some(e);
+                }
+            }
+            state.status.buffered = state.queue.len();
+            self.persist(state);
+        }
+        delivered
+    }
+
+    /// Send a test event straight to the target, bypassing the buffer
+    pub async fn send_test(&self, target_id: &str) -> Result<SiemRecord, String> {
+        let target = self
+            .targets
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .get(target_id)
+            .map(|state| state.target.clone())
+            .ok_or_else(|| format!("Integration not found: {}", target_id))?;
+
+        let record = SiemRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: chrono::Utc::now().timestamp(),
+            event_type: "siem_test".to_string(),
+            name: "CUBE Nexum SIEM test event".to_string(),
+            severity: 1,
+            source: APP_NAME.to_string(),
+            source_ip: None,
+            user: None,
+            resource: None,
+            action: Some("test".to_string()),
+            outcome: Some("success".to_string()),
+            fields: BTreeMap::new(),
+        };
+        let payload = render(&target, &record, &self.hostname);
+        let result = self.sink.send(&target, &[payload]).await;
+
+        if let Ok(mut targets) = self.targets.lock() {
+            if let Some(state) = targets.get_mut(target_id) {
+                match &result {
+                    Ok(()) => state.status.last_sync_at = Some(record.timestamp),
+                    Err(e) => state.status.last_error = Some(e.clone()),
+                }
+            }
+        }
+        result.map(|_| record)
+    }
+
+    /// Deliver events as they arrive, retrying buffered ones in the background
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            let _ = tokio::time::timeout(Duration::from_secs(1), self.notify.notified()).await;
+            while self.flush_due(now_ms()).await > 0 {}
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// TESTS
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn sample_record(id: &str) -> SiemRecord {
+        SiemRecord {
+            id: id.to_string(),
+            timestamp: 1_700_000_000,
+            event_type: "login_failed".to_string(),
+            name: "Failed login | admin console".to_string(),
+            severity: 7,
+            source: "auth".to_string(),
+            source_ip: Some("10.1.2.3".to_string()),
+            user: Some("alice".to_string()),
+            resource: Some("/admin?next=a=b".to_string()),
+            action: Some("login".to_string()),
+            outcome: Some("failure".to_string()),
+            fields: BTreeMap::from([("attempts".to_string(), "5".to_string())]),
+        }
+    }
+
+    fn target(id: &str, format: SiemFormat, transport: SiemTransport, address: &str) -> SiemTarget {
+        SiemTarget {
+            id: id.to_string(),
+            name: id.to_string(),
+            enabled: true,
+            format,
+            transport,
+            address: address.to_string(),
+            token: None,
+            field_mapping: HashMap::new(),
+            event_types: Vec::new(),
+            batch_size: 2,
+            max_buffered: 100,
+        }
+    }
+
+    #[test]
+    fn test_cef_format_of_sample_event() {
+        let record = sample_record("evt-1");
+        assert_eq!(
+            format_cef(&record, &HashMap::new()),
+            format!(
+                "CEF:0|CUBE|CUBE Nexum|{}|login_failed|Failed login \\| admin console|7|rt=1700000000000 \
+                 cat=login_failed dvchost=auth src=10.1.2.3 suser=alice request=/admin?next\\=a\\=b act=login \
+                 outcome=failure attempts=5",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+
+        // Field mapping renames and drops keys
+        let mapping = HashMap::from([
+            ("user".to_string(), "duser".to_string()),
+            ("attempts".to_string(), "cn1".to_string()),
+            ("source".to_string(), String::new()),
+        ]);
+        let cef = format_cef(&record, &mapping);
+        assert!(cef.contains(" duser=alice ") && cef.ends_with(" cn1=5"));
+        assert!(!cef.contains("dvchost="));
+
+        let leef = format_leef(&record, &HashMap::new());
+        assert!(leef.starts_with("LEEF:1.0|CUBE|CUBE Nexum|"));
+        assert!(leef.contains("\tusrName=alice\t"));
+
+        let syslog = format_syslog(&record, &HashMap::new(), "host1");
+        assert!(syslog.starts_with("<107>1 2023-11-14T22:13:20Z host1 cube-nexum - login_failed [cube@32473 severity=\"7\""));
+    }
+
+    struct FlakySink {
+        failures_left: AtomicUsize,
+        delivered: Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl SiemSink for FlakySink {
+        async fn send(&self, _target: &SiemTarget, payloads: &[String]) -> Result<(), String> {
+            if self.failures_left.load(Ordering::SeqCst) > 0 {
+                self.failures_left.fetch_sub(1, Ordering::SeqCst);
+                return Err("connection refused".to_string());
+            }
+            self.delivered.lock().unwrap().extend(payloads.iter().cloned());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_buffer_survives_transient_failure() {
+        let spool = std::env::temp_dir().join(format!("cube-siem-{}", uuid::Uuid::new_v4()));
+        let sink = Arc::new(FlakySink { failures_left: AtomicUsize::new(1), delivered: Mutex::new(Vec::new()) });
+        let forwarder = SiemForwarder::new(spool.clone()).with_sink(sink.clone());
+        forwarder.upsert_target(target("splunk", SiemFormat::Cef, SiemTransport::Tcp, "127.0.0.1:1")).unwrap();
+        for id in ["a", "b", "c"] {
+            forwarder.forward(sample_record(id));
+        }
+
+        assert_eq!(forwarder.flush_due(1_000).await, 0);
+        let status = forwarder.status("splunk").unwrap();
+        assert_eq!((status.buffered, status.consecutive_failures), (3, 1));
+        assert_eq!(status.next_retry_at, Some(2_000));
+        // Still backing off: nothing is attempted
+        assert_eq!(forwarder.flush_due(1_500).await, 0);
+        assert!(sink.delivered.lock().unwrap().is_empty());
+
+        // The buffer is on disk, so a restarted forwarder picks it up
+        let restarted = SiemForwarder::new(spool.clone()).with_sink(sink.clone());
+        restarted.upsert_target(target("splunk", SiemFormat::Cef, SiemTransport::Tcp, "127.0.0.1:1")).unwrap();
+        assert_eq!(restarted.status("splunk").unwrap().buffered, 3);
+
+        assert_eq!(restarted.flush_due(2_000).await, 2);
+        assert_eq!(restarted.flush_due(2_000).await, 1);
+        let status = restarted.status("splunk").unwrap();
+        assert_eq!((status.buffered, status.sent, status.last_error), (0, 3, None));
+        assert_eq!(sink.delivered.lock().unwrap().len(), 3);
+        assert!(!restarted.spool_path("splunk").exists());
+
+        let _ = std::fs::remove_dir_all(spool);
+    }
+
+    #[tokio::test]
+    async fn test_test_event_round_trip_against_mock_collector() {
+        use tokio::io::AsyncReadExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let collector = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            socket.read_to_end(&mut received).await.unwrap();
+            String::from_utf8(received).unwrap()
+        });
+
+        let spool = std::env::temp_dir().join(format!("cube-siem-{}", uuid::Uuid::new_v4()));
+        let forwarder = SiemForwarder::new(spool);
+        forwarder.upsert_target(target("qradar", SiemFormat::Cef, SiemTransport::Tcp, &address)).unwrap();
+        let record = forwarder.send_test("qradar").await.unwrap();
+
+        // RFC 6587 octet-counted syslog frame carrying the CEF event
+        let frame = collector.await.unwrap();
+        let (length, message) = frame.split_once(' ').unwrap();
+        assert_eq!(length.parse::<usize>().unwrap(), message.len());
+        assert!(message.starts_with("<110>1 "));
+        assert!(message.contains(" - CEF:0|CUBE|CUBE Nexum|"));
+        assert!(message.contains("|siem_test|CUBE Nexum SIEM test event|1|"));
+        assert_eq!(forwarder.status("qradar").unwrap().last_sync_at, Some(record.timestamp));
+
+        assert!(forwarder.send_test("missing").await.is_err());
+    }
+}
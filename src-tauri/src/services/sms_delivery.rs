@@ -0,0 +1,698 @@
+// CUBE Nexum - SMS Delivery
+// Pluggable SMS providers (Twilio, Vonage) with failover, segment counting,
+// delivery-status webhooks and STOP opt-out suppression
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+const GSM_SINGLE_SEGMENT: usize = 160;
+const GSM_MULTI_SEGMENT: usize = 153;
+const UCS2_SINGLE_SEGMENT: usize = 70;
+const UCS2_MULTI_SEGMENT: usize = 67;
+
+/// Upper bound on segments per message; carriers start dropping beyond this
+pub const MAX_SEGMENTS: usize = 10;
+
+const GSM_BASIC: &str = "@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ !\"#¤%&'()*+,-./0123456789:;<=>?¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüà";
+const GSM_EXTENDED: &str = "^{}\\[~]|€\u{000C}";
+
+const OPT_OUT_KEYWORDS: &[&str] = &["STOP", "STOPALL", "UNSUBSCRIBE", "CANCEL", "END", "QUIT"];
+const OPT_IN_KEYWORDS: &[&str] = &["START", "UNSTOP", "YES"];
+
+// ==================== Encoding & Validation ====================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmsEncoding {
+    Gsm7,
+    Ucs2,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SegmentInfo {
+    pub encoding: SmsEncoding,
+    /// Septets for GSM-7, UTF-16 code units for UCS-2
+    pub units: usize,
+    pub segments: usize,
+}
+
+/// Counts billable segments. GSM-7 messages fit 160 septets (153 per part once
+/// concatenated, extension-table characters cost two); anything outside the GSM
+/// alphabet forces UCS-2 at 70 code units (67 per part).
+pub fn count_segments(body: &str) -> SegmentInfo {
+    let mut septets = 0usize;
+    let mut gsm = true;
+    for c in body.chars() {
+        if GSM_BASIC.contains(c) {
+            septets += 1;
+        } else if GSM_EXTENDED.contains(c) {
+            septets += 2;
+        } else {
+            gsm = false;
+            break;
+        }
+    }
+
+    let (encoding, units, single, multi) = if gsm {
+        (SmsEncoding::Gsm7, septets, GSM_SINGLE_SEGMENT, GSM_MULTI_SEGMENT)
+    } else {
+        (SmsEncoding::Ucs2, body.encode_utf16().count(), UCS2_SINGLE_SEGMENT, UCS2_MULTI_SEGMENT)
+    };
+
+    let segments = match units {
+        0 => 1,
+        n if n <= single => 1,
+        n => n.div_ceil(multi),
+    };
+
+    SegmentInfo { encoding, units, segments }
+}
+
+/// Normalises common formatting ("+1 (555) 010-0000") and validates E.164.
+pub fn normalize_e164(number: &str) -> Result<String, String> {
+    let cleaned: String = number
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '(' | ')' | '.'))
+        .collect();
+
+    let digits = cleaned
+        .strip_prefix('+')
+        .ok_or_else(|| format!("Phone number must be in E.164 format (+<country><number>): {}", number))?;
+
+    if digits.len() < 2
+        || digits.len() > 15
+        || !digits.chars().all(|c| c.is_ascii_digit())
+        || digits.starts_with('0')
+    {
+        return Err(format!("Invalid E.164 phone number: {}", number));
+    }
+
+    Ok(cleaned)
+}
+
+// ==================== Providers ====================
+
+#[async_trait::async_trait]
+pub trait SmsProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn cost_per_segment(&self) -> f64;
+
+    /// Sends the message and returns the provider's message id.
+    async fn send(&self, to: &str, body: &str, encoding: SmsEncoding) -> Result<String, String>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "lowercase")]
+pub enum SmsProviderConfig {
+    #[serde(rename_all = "camelCase")]
+    Twilio {
+        account_sid: String,
+        auth_token: String,
+        from: String,
+        #[serde(default)]
+        status_callback_url: Option<String>,
+        #[serde(default = "default_cost_per_segment")]
+        cost_per_segment: f64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Vonage {
+        api_key: String,
+        api_secret: String,
+        from: String,
+        #[serde(default)]
+        status_callback_url: Option<String>,
+        #[serde(default = "default_cost_per_segment")]
+        cost_per_segment: f64,
+    },
+}
+
+fn default_cost_per_segment() -> f64 {
+    0.0079
+}
+
+impl SmsProviderConfig {
+    pub fn build(self) -> Result<Arc<dyn SmsProvider>, String> {
+        match self {
+            SmsProviderConfig::Twilio { account_sid, auth_token, from, status_callback_url, cost_per_segment } => {
+                if account_sid.is_empty() || auth_token.is_empty() {
+                    return Err("Twilio account SID and auth token are required".to_string());
+                }
+                Ok(Arc::new(TwilioProvider {
+                    client: reqwest::Client::new(),
+                    account_sid,
+                    auth_token,
+                    from: normalize_e164(&from)?,
+                    status_callback_url,
+                    cost_per_segment,
+                }))
+            }
+            SmsProviderConfig::Vonage { api_key, api_secret, from, status_callback_url, cost_per_segment } => {
+                if api_key.is_empty() || api_secret.is_empty() {
+                    return Err("Vonage API key and secret are required".to_string());
+                }
+                if from.trim().is_empty() {
+                    return Err("Vonage sender is required".to_string());
+                }
+                Ok(Arc::new(VonageProvider {
+                    client: reqwest::Client::new(),
+                    api_key,
+                    api_secret,
+                    from,
+                    status_callback_url,
+                    cost_per_segment,
+                }))
+            }
+        }
+    }
+}
+
+pub struct TwilioProvider {
+    client: reqwest::Client,
+    account_sid: String,
+    auth_token: String,
+    from: String,
+    status_callback_url: Option<String>,
+    cost_per_segment: f64,
+}
+
+#[async_trait::async_trait]
+impl SmsProvider for TwilioProvider {
+    fn name(&self) -> &str {
+        "twilio"
+    }
+
+    fn cost_per_segment(&self) -> f64 {
+        self.cost_per_segment
+    }
+
+    async fn send(&self, to: &str, body: &str, _encoding: SmsEncoding) -> Result<String, String> {
+        let url = format!(
+            "https://api.twilio.com/2010-04-01/Accounts/{}/Messages.json",
+            self.account_sid
+        );
+        let mut form = vec![("To", to), ("From", self.from.as_str()), ("Body", body)];
+        if let Some(callback) = &self.status_callback_url {
+            form.push(("StatusCallback", callback.as_str()));
+        }
+
+        let response = self
+            .client
+            .post(&url)
+            .basic_auth(&self.account_sid, Some(&self.auth_token))
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| format!("Twilio request failed: {}", e))?;
+
+        let status = response.status();
+        let json: Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid Twilio response: {}", e))?;
+
+        if !status.is_success() {
+            return Err(format!(
+                "Twilio error {}: {}",
+                json["code"].as_i64().unwrap_or(status.as_u16() as i64),
+                json["message"].as_str().unwrap_or("unknown error")
+            ));
+        }
+
+        json["sid"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| "Twilio response missing message SID".to_string())
+    }
+}
+
+pub struct VonageProvider {
+    client: reqwest::Client,
+    api_key: String,
+    api_secret: String,
+    from: String,
+    status_callback_url: Option<String>,
+    cost_per_segment: f64,
+}
+
+#[async_trait::async_trait]
+impl SmsProvider for VonageProvider {
+    fn name(&self) -> &str {
+        "vonage"
+    }
+
+    fn cost_per_segment(&self) -> f64 {
+        self.cost_per_segment
+    }
+
+    async fn send(&self, to: &str, body: &str, encoding: SmsEncoding) -> Result<String, String> {
+        // Vonage expects the number without the leading '+'
+        let to = to.trim_start_matches('+');
+        let mut form = vec![
+            ("api_key", self.api_key.as_str()),
+            ("api_secret", self.api_secret.as_str()),
+            ("from", self.from.as_str()),
+            ("to", to),
+            ("text", body),
+        ];
+        if encoding == SmsEncoding::Ucs2 {
+            form.push(("type", "unicode"));
+        }
+        if let Some(callback) = &self.status_callback_url {
+            form.push(("callback", callback.as_str()));
+        }
+
+        let json: Value = self
+            .client
+            .post("https://rest.nexmo.com/sms/json")
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| format!("Vonage request failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Invalid Vonage response: {}", e))?;
+
+        // Multipart messages come back as one entry per part; the first id identifies the send
+        let parts = json["messages"].as_array().cloned().unwrap_or_default();
+        let first = parts.first().ok_or("Vonage response contained no messages")?;
+        if let Some(failed) = parts.iter().find(|p| p["status"].as_str() != Some("0")) {
+            return Err(format!(
+                "Vonage error {}: {}",
+                failed["status"].as_str().unwrap_or("?"),
+                failed["error-text"].as_str().unwrap_or("unknown error")
+            ));
+        }
+
+        first["message-id"]
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| "Vonage response missing message id".to_string())
+    }
+}
+
+// ==================== Delivery Records ====================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmsDeliveryState {
+    Queued,
+    Sent,
+    Delivered,
+    Undelivered,
+    Failed,
+    Suppressed,
+}
+
+impl SmsDeliveryState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SmsDeliveryState::Queued => "queued",
+            SmsDeliveryState::Sent => "sent",
+            SmsDeliveryState::Delivered => "delivered",
+            SmsDeliveryState::Undelivered => "undelivered",
+            SmsDeliveryState::Failed => "failed",
+            SmsDeliveryState::Suppressed => "suppressed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SmsDeliveryRecord {
+    pub message_id: String,
+    pub to: String,
+    pub provider: Option<String>,
+    pub provider_message_id: Option<String>,
+    pub state: SmsDeliveryState,
+    pub segments: SegmentInfo,
+    pub estimated_cost: f64,
+    pub error: Option<String>,
+    /// Errors from providers tried before the one that accepted the message
+    pub failover_errors: Vec<String>,
+    pub created_at: i64,
+    pub delivered_at: Option<i64>,
+}
+
+/// What a STOP/START reply did to the sender's subscription
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum InboundSmsAction {
+    OptedOut,
+    OptedIn,
+    None,
+}
+
+// ==================== Gateway ====================
+
+/// Ordered provider list (primary first) plus delivery tracking and opt-outs.
+#[derive(Default)]
+pub struct SmsGateway {
+    providers: RwLock<Vec<Arc<dyn SmsProvider>>>,
+    records: RwLock<HashMap<String, SmsDeliveryRecord>>,
+    /// provider message id -> our message id, for status webhooks
+    provider_ids: RwLock<HashMap<String, String>>,
+    opted_out: RwLock<HashSet<String>>,
+}
+
+impl SmsGateway {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads TWILIO_ACCOUNT_SID / TWILIO_AUTH_TOKEN / TWILIO_FROM_NUMBER and
+    /// VONAGE_API_KEY / VONAGE_API_SECRET / VONAGE_FROM. Twilio is primary when
+    /// both are present.
+    pub fn from_env() -> Self {
+        let env = |key: &str| std::env::var(key).unwrap_or_default();
+        let mut configs = Vec::new();
+        if !env("TWILIO_ACCOUNT_SID").is_empty() {
+            configs.push(SmsProviderConfig::Twilio {
+                account_sid: env("TWILIO_ACCOUNT_SID"),
+                auth_token: env("TWILIO_AUTH_TOKEN"),
+                from: env("TWILIO_FROM_NUMBER"),
+                status_callback_url: std::env::var("SMS_STATUS_CALLBACK_URL").ok(),
+                cost_per_segment: default_cost_per_segment(),
+            });
+        }
+        if !env("VONAGE_API_KEY").is_empty() {
+            configs.push(SmsProviderConfig::Vonage {
+                api_key: env("VONAGE_API_KEY"),
+                api_secret: env("VONAGE_API_SECRET"),
+                from: env("VONAGE_FROM"),
+                status_callback_url: std::env::var("SMS_STATUS_CALLBACK_URL").ok(),
+                cost_per_segment: default_cost_per_segment(),
+            });
+        }
+
+        let gateway = Self::new();
+        let providers = configs
+            .into_iter()
+            .filter_map(|config| match config.build() {
+                Ok(provider) => Some(provider),
+                Err(e) => {
+                    log::warn!("Skipping SMS provider: {}", e);
+                    None
+                }
+            })
+            .collect();
+        gateway.set_providers(providers);
+        gateway
+    }
+
+    pub fn set_providers(&self, providers: Vec<Arc<dyn SmsProvider>>) {
+        if let Ok(mut current) = self.providers.write() {
+            *current = providers;
+        }
+    }
+
+    pub fn configure(&self, configs: Vec<SmsProviderConfig>) -> Result<Vec<String>, String> {
+        let providers = configs
+            .into_iter()
+            .map(SmsProviderConfig::build)
+            .collect::<Result<Vec<_>, _>>()?;
+        let names = providers.iter().map(|p| p.name().to_string()).collect();
+        self.set_providers(providers);
+        Ok(names)
+    }
+
+    pub fn is_opted_out(&self, number: &str) -> bool {
+        normalize_e164(number)
+            .ok()
+            .and_then(|n| self.opted_out.read().ok().map(|set| set.contains(&n)))
+            .unwrap_or(false)
+    }
+
+    pub fn estimate(&self, body: &str) -> (SegmentInfo, f64) {
+        let segments = count_segments(body);
+        let rate = self
+            .providers
+            .read()
+            .ok()
+            .and_then(|p| p.first().map(|p| p.cost_per_segment()))
+            .unwrap_or_else(default_cost_per_segment);
+        let cost = segments.segments as f64 * rate;
+        (segments, cost)
+    }
+
+    fn store(&self, record: &SmsDeliveryRecord) -> Result<(), String> {
+        self.records
+            .write()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .insert(record.message_id.clone(), record.clone());
+        if let Some(provider_id) = &record.provider_message_id {
+            self.provider_ids
+                .write()
+                .map_err(|e| format!("Lock error: {}", e))?
+                .insert(provider_id.clone(), record.message_id.clone());
+        }
+        Ok(())
+    }
+
+    /// Sends through the primary provider, falling back to the next one on error.
+    /// Numbers that replied STOP are suppressed without contacting any provider.
+    pub async fn send(&self, to: &str, body: &str) -> Result<SmsDeliveryRecord, String> {
+        let to = normalize_e164(to)?;
+        if body.trim().is_empty() {
+            return Err("SMS body cannot be empty".to_string());
+        }
+
+        let segments = count_segments(body);
+        if segments.segments > MAX_SEGMENTS {
+            return Err(format!(
+                "SMS is {} segments long; the limit is {}",
+                segments.segments, MAX_SEGMENTS
+            ));
+        }
+
+        let mut record = SmsDeliveryRecord {
+            message_id: uuid::Uuid::new_v4().to_string(),
+            to: to.clone(),
+            provider: None,
+            provider_message_id: None,
+            state: SmsDeliveryState::Queued,
+            segments: segments.clone(),
+            estimated_cost: 0.0,
+            error: None,
+            failover_errors: Vec::new(),
+            created_at: chrono::Utc::now().timestamp_millis(),
+            delivered_at: None,
+        };
+
+        if self.is_opted_out(&to) {
+            record.state = SmsDeliveryState::Suppressed;
+            record.error = Some("Recipient opted out (STOP)".to_string());
+            self.store(&record)?;
+            return Ok(record);
+        }
+
+        let providers = self
+            .providers
+            .read()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .clone();
+        if providers.is_empty() {
+            return Err("No SMS provider configured".to_string());
+        }
+
+        for provider in providers {
+            match provider.send(&to, body, segments.encoding).await {
+                Ok(provider_message_id) => {
+                    record.provider = Some(provider.name().to_string());
+                    record.provider_message_id = Some(provider_message_id);
+                    record.state = SmsDeliveryState::Sent;
+                    record.estimated_cost = segments.segments as f64 * provider.cost_per_segment();
+                    self.store(&record)?;
+                    return Ok(record);
+                }
+                Err(e) => {
+                    log::warn!("SMS provider {} failed, trying next: {}", provider.name(), e);
+                    record.failover_errors.push(format!("{}: {}", provider.name(), e));
+                }
+            }
+        }
+
+        record.state = SmsDeliveryState::Failed;
+        record.error = record.failover_errors.last().cloned();
+        self.store(&record)?;
+        Ok(record)
+    }
+
+    pub fn get_record(&self, message_id: &str) -> Option<SmsDeliveryRecord> {
+        self.records.read().ok()?.get(message_id).cloned()
+    }
+
+    /// Applies a Twilio status callback (`MessageSid`/`MessageStatus`) or a Vonage
+    /// delivery receipt (`messageId`/`status`) to the matching record.
+    pub fn apply_status_webhook(&self, payload: &Value) -> Result<SmsDeliveryRecord, String> {
+        let (provider_id, status, error) = if let Some(sid) = payload["MessageSid"].as_str() {
+            let status = payload["MessageStatus"].as_str().unwrap_or_default();
+            let error = payload["ErrorCode"]
+                .as_str()
+                .map(String::from)
+                .or_else(|| payload["ErrorCode"].as_i64().map(|c| c.to_string()));
+            (sid, status, error)
+        } else if let Some(id) = payload["messageId"].as_str() {
+            let status = payload["status"].as_str().unwrap_or_default();
+            let error = payload["err-code"]
+                .as_str()
+                .filter(|code| *code != "0")
+                .map(String::from);
+            (id, status, error)
+        } else {
+            return Err("Unrecognised SMS status webhook payload".to_string());
+        };
+
+        let state = match status.to_lowercase().as_str() {
+            "queued" | "accepted" | "scheduled" | "buffered" => SmsDeliveryState::Queued,
+            "sending" | "sent" => SmsDeliveryState::Sent,
+            "delivered" => SmsDeliveryState::Delivered,
+            "undelivered" | "expired" | "rejected" => SmsDeliveryState::Undelivered,
+            "failed" => SmsDeliveryState::Failed,
+            other => return Err(format!("Unknown SMS delivery status: {}", other)),
+        };
+
+        let message_id = self
+            .provider_ids
+            .read()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .get(provider_id)
+            .cloned()
+            .ok_or_else(|| format!("No SMS found for provider message {}", provider_id))?;
+
+        let mut records = self.records.write().map_err(|e| format!("Lock error: {}", e))?;
+        let record = records
+            .get_mut(&message_id)
+            .ok_or_else(|| format!("SMS not found: {}", message_id))?;
+
+        if state == SmsDeliveryState::Delivered {
+            record.delivered_at = Some(chrono::Utc::now().timestamp_millis());
+        }
+        if matches!(state, SmsDeliveryState::Undelivered | SmsDeliveryState::Failed) {
+            record.error = error.map(|code| format!("Provider error code {}", code));
+        }
+        record.state = state;
+
+        Ok(record.clone())
+    }
+
+    /// Handles an inbound reply: STOP-style keywords suppress future sends to
+    /// the number, START-style keywords lift the suppression.
+    pub fn handle_inbound(&self, from: &str, body: &str) -> Result<InboundSmsAction, String> {
+        let from = normalize_e164(from)?;
+        let keyword = body.trim().to_uppercase();
+        let mut opted_out = self.opted_out.write().map_err(|e| format!("Lock error: {}", e))?;
+
+        if OPT_OUT_KEYWORDS.contains(&keyword.as_str()) {
+            opted_out.insert(from);
+            Ok(InboundSmsAction::OptedOut)
+        } else if OPT_IN_KEYWORDS.contains(&keyword.as_str()) {
+            opted_out.remove(&from);
+            Ok(InboundSmsAction::OptedIn)
+        } else {
+            Ok(InboundSmsAction::None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct MockProvider {
+        name: &'static str,
+        fail: bool,
+        calls: AtomicUsize,
+    }
+
+    impl MockProvider {
+        fn new(name: &'static str, fail: bool) -> Arc<Self> {
+            Arc::new(Self { name, fail, calls: AtomicUsize::new(0) })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl SmsProvider for MockProvider {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn cost_per_segment(&self) -> f64 {
+            0.01
+        }
+
+        async fn send(&self, _to: &str, _body: &str, _encoding: SmsEncoding) -> Result<String, String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                Err("503 service unavailable".to_string())
+            } else {
+                Ok(format!("{}-msg", self.name))
+            }
+        }
+    }
+
+    #[test]
+    fn test_segment_counting_gsm_and_ucs2() {
+        assert_eq!(count_segments(&"a".repeat(160)).segments, 1);
+        assert_eq!(count_segments(&"a".repeat(161)).segments, 2);
+        assert_eq!(count_segments(&"a".repeat(306)).segments, 2);
+        assert_eq!(count_segments(&"a".repeat(307)).segments, 3);
+
+        // Extension-table characters take two septets
+        let euro = count_segments(&"€".repeat(80));
+        assert_eq!((euro.encoding, euro.units, euro.segments), (SmsEncoding::Gsm7, 160, 1));
+
+        let unicode = count_segments(&format!("{}é€ğ", "a".repeat(67)));
+        assert_eq!(unicode.encoding, SmsEncoding::Ucs2);
+        assert_eq!((unicode.units, unicode.segments), (70, 1));
+        assert_eq!(count_segments(&"ğ".repeat(71)).segments, 2);
+        // Emoji are surrogate pairs in UCS-2
+        assert_eq!(count_segments("😀").units, 2);
+
+        assert_eq!(normalize_e164("+1 (555) 010-0000").unwrap(), "+15550100000");
+        assert!(normalize_e164("5550100000").is_err());
+        assert!(normalize_e164("+0123").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_secondary_provider() {
+        let gateway = SmsGateway::new();
+        let primary = MockProvider::new("primary", true);
+        let secondary = MockProvider::new("secondary", false);
+        gateway.set_providers(vec![primary.clone() as Arc<dyn SmsProvider>, secondary.clone()]);
+
+        let record = gateway.send("+15550100000", "Your code is 123456").await.unwrap();
+        assert_eq!(record.state, SmsDeliveryState::Sent);
+        assert_eq!(record.provider.as_deref(), Some("secondary"));
+        assert_eq!(record.failover_errors.len(), 1);
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 1);
+
+        let delivered = gateway
+            .apply_status_webhook(&serde_json::json!({ "MessageSid": "secondary-msg", "MessageStatus": "delivered" }))
+            .unwrap();
+        assert_eq!(delivered.message_id, record.message_id);
+        assert_eq!(gateway.get_record(&record.message_id).unwrap().state, SmsDeliveryState::Delivered);
+    }
+
+    #[tokio::test]
+    async fn test_stop_reply_suppresses_future_sends() {
+        let gateway = SmsGateway::new();
+        let provider = MockProvider::new("primary", false);
+        gateway.set_providers(vec![provider.clone() as Arc<dyn SmsProvider>]);
+
+        assert_eq!(gateway.handle_inbound("+15550100000", " stop ").unwrap(), InboundSmsAction::OptedOut);
+        let record = gateway.send("+1 555 010 0000", "Sale today!").await.unwrap();
+        assert_eq!(record.state, SmsDeliveryState::Suppressed);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 0);
+
+        assert_eq!(gateway.handle_inbound("+15550100000", "START").unwrap(), InboundSmsAction::OptedIn);
+        let record = gateway.send("+15550100000", "Welcome back").await.unwrap();
+        assert_eq!(record.state, SmsDeliveryState::Sent);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,329 @@
+// OpenSSH client config (~/.ssh/config) parsing
+//
+// Only the options the terminal uses are kept. As in OpenSSH, the first value
+// obtained for an option wins, blocks apply when any of their Host patterns
+// match (and none of the negated ones), and options before the first Host
+// line apply to every host. Match blocks are skipped.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+struct HostBlock {
+    patterns: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+/// Options resolved for one host alias
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SshHostEntry {
+    pub alias: String,
+    /// Real host name; the alias itself when HostName is not set
+    pub host_name: String,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    /// Unexpanded, in the order they were given
+    pub identity_files: Vec<String>,
+    pub proxy_jump: Option<String>,
+    pub user_known_hosts_file: Option<String>,
+}
+
+/// One hop of a ProxyJump list: `[user@]host[:port]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct JumpSpec {
+    pub user: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SshConfigFile {
+    blocks: Vec<HostBlock>,
+}
+
+impl SshConfigFile {
+    pub fn parse(contents: &str) -> Self {
+        let mut blocks = vec![HostBlock { patterns: vec!["*".to_string()], options: Vec::new() }];
+        // None inside a Match block
+        let mut current = Some(0);
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (keyword, value) = match line.find(|c: char| c.is_whitespace() || c == '=') {
+                Some(at) => (&line[..at], line[at..].trim_start_matches(|c: char| c.is_whitespace() || c == '=')),
+                None => (line, ""),
+            };
+            let keyword = keyword.to_ascii_lowercase();
+            match keyword.as_str() {
+                "host" => {
+                    blocks.push(HostBlock { patterns: split_args(value), options: Vec::new() });
+                    current = Some(blocks.len() - 1);
+                }
+                "match" => current = None,
+                _ => {
+                    if let Some(index) = current {
+                        let value = unquote(value.trim());
+                        blocks[index].options.push((keyword, value));
+                    }
+                }
+            }
+        }
+
+        Self { blocks }
+    }
+
+    /// Concrete host names (no wildcards or negations), in file order
+    pub fn aliases(&self) -> Vec<String> {
+        let mut aliases: Vec<String> = Vec::new();
+        for block in &self.blocks[1..] {
+            for pattern in &block.patterns {
+                let concrete = !pattern.starts_with('!') && !pattern.contains(['*', '?']);
+                if concrete && !aliases.contains(pattern) {
+                    aliases.push(pattern.clone());
+                }
+            }
+        }
+        aliases
+    }
+
+    pub fn resolve(&self, alias: &str) -> SshHostEntry {
+        let mut entry = SshHostEntry { alias: alias.to_string(), ..Default::default() };
+        let mut host_name = None;
+
+        for block in self.blocks.iter().filter(|b| host_matches(&b.patterns, alias)) {
+            for (keyword, value) in &block.options {
+                match keyword.as_str() {
+                    "hostname" if host_name.is_none() => host_name = Some(value.replace("%h", alias)),
+                    "user" if entry.user.is_none() => entry.user = Some(value.clone()),
+                    "port" if entry.port.is_none() => entry.port = value.parse().ok(),
+                    "identityfile" => entry.identity_files.push(value.clone()),
+                    "proxyjump" if entry.proxy_jump.is_none() => entry.proxy_jump = Some(value.clone()),
+                    "userknownhostsfile" if entry.user_known_hosts_file.is_none() => {
+                        entry.user_known_hosts_file = split_args(value).into_iter().next();
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        entry.host_name = host_name.unwrap_or_else(|| alias.to_string());
+        // "none" only serves to override a later, broader block
+        if entry.proxy_jump.as_deref().is_some_and(|jump| jump.eq_ignore_ascii_case("none")) {
+            entry.proxy_jump = None;
+        }
+        entry
+    }
+}
+
+/// A block applies when a pattern matches and no negated pattern does
+fn host_matches(patterns: &[String], host: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if wildcard_match(negated, host) {
+                return false;
+            }
+        } else if wildcard_match(pattern, host) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+/// `*` matches any run of characters, `?` exactly one
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn split_args(value: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in value.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+fn unquote(value: &str) -> String {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
+/// Splits `bastion,admin@gw:2222,[fd00::1]:22` into hops, first hop first
+pub fn parse_proxy_jump(spec: &str) -> Vec<JumpSpec> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|hop| !hop.is_empty() && !hop.eq_ignore_ascii_case("none"))
+        .map(|hop| {
+            let hop = hop.strip_prefix("ssh://").unwrap_or(hop);
+            let (user, address) = match hop.rsplit_once('@') {
+                Some((user, address)) => (Some(user.to_string()), address),
+                None => (None, hop),
+            };
+            let (host, port) = if let Some(rest) = address.strip_prefix('[') {
+                let (host, rest) = rest.split_once(']').unwrap_or((rest, ""));
+                (host, rest.strip_prefix(':').and_then(|p| p.parse().ok()))
+            } else {
+                match address.rsplit_once(':') {
+                    Some((host, port)) if !host.contains(':') => (host, port.parse().ok()),
+                    _ => (address, None),
+                }
+            };
+            JumpSpec { user, host: host.to_string(), port }
+        })
+        .collect()
+}
+
+/// Expands `~` and the `%d` (home), `%h` (host), `%r` (remote user),
+/// `%u` (local user) and `%%` tokens of IdentityFile-style paths
+pub fn expand_path(value: &str, home: Option<&Path>, host: &str, remote_user: &str, local_user: &str) -> PathBuf {
+    let home_str = home.map(|h| h.to_string_lossy().to_string()).unwrap_or_default();
+    let mut expanded = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('d') => expanded.push_str(&home_str),
+            Some('h') => expanded.push_str(host),
+            Some('r') => expanded.push_str(remote_user),
+            Some('u') => expanded.push_str(local_user),
+            Some('%') => expanded.push('%'),
+            Some(other) => {
+                expanded.push('%');
+                expanded.push(other);
+            }
+            None => expanded.push('%'),
+        }
+    }
+
+    if let Some(home) = home {
+        if expanded == "~" {
+            return home.to_path_buf();
+        }
+        if let Some(rest) = expanded.strip_prefix("~/") {
+            return home.join(rest);
+        }
+    }
+    PathBuf::from(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+# Defaults for everything
+ServerAliveInterval 60
+
+Host bastion
+    HostName bastion.example.com
+    User jump
+    Port 2222
+    IdentityFile ~/.ssh/bastion_ed25519
+
+Host internal-* !internal-legacy
+    ProxyJump bastion
+    User deploy
+
+Host internal-web internal-db
+    HostName %h.corp.local
+    UserKnownHostsFile "~/.ssh/known_hosts_corp" /dev/null
+
+Host internal-legacy
+    HostName=10.0.0.5
+    ProxyJump admin@bastion:2222,[fd00::7]:2200
+
+Match host *.example.com
+    User ignored
+
+Host *
+    User fallback
+    IdentityFile ~/.ssh/id_ed25519
+    ProxyJump none
+"#;
+
+    #[test]
+    fn test_parse_config_with_proxy_jump_and_wildcards() {
+        let file = SshConfigFile::parse(CONFIG);
+        assert_eq!(file.aliases(), ["bastion", "internal-web", "internal-db", "internal-legacy"]);
+
+        let bastion = file.resolve("bastion");
+        assert_eq!(bastion.host_name, "bastion.example.com");
+        assert_eq!((bastion.user.as_deref(), bastion.port), (Some("jump"), Some(2222)));
+        assert_eq!(bastion.identity_files, ["~/.ssh/bastion_ed25519", "~/.ssh/id_ed25519"]);
+        assert_eq!(bastion.proxy_jump, None);
+
+        let web = file.resolve("internal-web");
+        assert_eq!(web.host_name, "internal-web.corp.local");
+        assert_eq!(web.user.as_deref(), Some("deploy"));
+        assert_eq!(web.proxy_jump.as_deref(), Some("bastion"));
+        assert_eq!(web.user_known_hosts_file.as_deref(), Some("~/.ssh/known_hosts_corp"));
+
+        // Excluded from internal-* by the negated pattern
+        let legacy = file.resolve("internal-legacy");
+        assert_eq!(legacy.host_name, "10.0.0.5");
+        assert_eq!(legacy.user.as_deref(), Some("fallback"));
+        let hops = parse_proxy_jump(legacy.proxy_jump.as_deref().unwrap());
+        assert_eq!(
+            hops,
+            [
+                JumpSpec { user: Some("admin".to_string()), host: "bastion".to_string(), port: Some(2222) },
+                JumpSpec { user: None, host: "fd00::7".to_string(), port: Some(2200) },
+            ]
+        );
+
+        // Wildcard blocks still apply to hosts that aren't listed
+        let other = file.resolve("internal-cache");
+        assert_eq!((other.host_name.as_str(), other.user.as_deref()), ("internal-cache", Some("deploy")));
+        assert!(wildcard_match("db?.prod*", "DB1.production"));
+        assert!(!wildcard_match("db?.prod", "db12.prod"));
+
+        let home = Path::new("/home/ada");
+        assert_eq!(
+            expand_path("~/.ssh/%r@%h", Some(home), "gw", "deploy", "ada"),
+            PathBuf::from("/home/ada/.ssh/deploy@gw")
+        );
+        assert_eq!(expand_path("%d/keys/id", Some(home), "gw", "deploy", "ada"), PathBuf::from("/home/ada/keys/id"));
+    }
+}
@@ -0,0 +1,153 @@
+// ProxyJump tunnelling for SSH connections
+//
+// libssh2 sessions need a real socket, so a hop's direct-tcpip channel is
+// bridged to a loopback socket pair by a pump thread. The next session runs
+// over the local end; the pump owns the hop's session and keeps it alive until
+// either side closes.
+
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+/// How long the pump sleeps when neither side has data
+const IDLE_WAIT: Duration = Duration::from_millis(1);
+
+/// An authenticated hop that can open TCP channels onward
+pub trait JumpHost: Send + 'static {
+    type Channel: Read + Write + Send + 'static;
+
+    /// Opens a channel to `host:port` as seen from the hop
+    fn open_channel(&self, host: &str, port: u16) -> io::Result<Self::Channel>;
+
+    /// Called once the channel is open; afterwards reads and writes must
+    /// return `WouldBlock` instead of waiting
+    fn make_nonblocking(&self, channel: &mut Self::Channel) -> io::Result<()>;
+}
+
+/// Reaches `host:port` through `hop`, returning a local socket connected to it
+pub fn tunnel<H: JumpHost>(hop: H, host: &str, port: u16) -> io::Result<TcpStream> {
+    let mut channel = hop.open_channel(host, port)?;
+    hop.make_nonblocking(&mut channel)?;
+
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let client = TcpStream::connect(listener.local_addr()?)?;
+    let (local, _) = listener.accept()?;
+    local.set_nonblocking(true)?;
+
+    let target = format!("{}:{}", host, port);
+    std::thread::Builder::new()
+        .name(format!("ssh-jump-{}", target))
+        .spawn(move || {
+            if let Err(e) = pump(&mut channel, local) {
+                log::debug!("SSH jump tunnel to {} closed: {}", target, e);
+            }
+            drop(channel);
+            drop(hop);
+        })?;
+
+    Ok(client)
+}
+
+/// Copies bytes both ways until either side reaches EOF
+fn pump<C: Read + Write>(channel: &mut C, mut local: TcpStream) -> io::Result<()> {
+    let mut buffer = [0u8; 16 * 1024];
+    loop {
+        let mut idle = true;
+
+        match local.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(n) => {
+                write_all_retrying(channel, &buffer[..n])?;
+                idle = false;
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+
+        match channel.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(n) => {
+                write_all_retrying(&mut local, &buffer[..n])?;
+                idle = false;
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+
+        if idle {
+            std::thread::sleep(IDLE_WAIT);
+        }
+    }
+}
+
+fn write_all_retrying<W: Write>(writer: &mut W, mut data: &[u8]) -> io::Result<()> {
+    while !data.is_empty() {
+        match writer.write(data) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(n) => data = &data[n..],
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => std::thread::sleep(IDLE_WAIT),
+            Err(e) => return Err(e),
+        }
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::sync::{Arc, Mutex};
+
+    /// Stands in for a bastion: opens the onward connection itself and
+    /// records where it was asked to go
+    struct MockJumpHost {
+        opened: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl JumpHost for MockJumpHost {
+        type Channel = TcpStream;
+
+        fn open_channel(&self, host: &str, port: u16) -> io::Result<TcpStream> {
+            self.opened.lock().unwrap().push(format!("{}:{}", host, port));
+            TcpStream::connect((host, port))
+        }
+
+        fn make_nonblocking(&self, channel: &mut TcpStream) -> io::Result<()> {
+            channel.set_nonblocking(true)
+        }
+    }
+
+    #[test]
+    fn test_connects_to_target_through_jump_host() {
+        // Target that greets like an SSH server, then echoes lines back
+        let target = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let target_port = target.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut socket, _) = target.accept().unwrap();
+            socket.write_all(b"SSH-2.0-MockTarget_1.0\r\n").unwrap();
+            let mut reader = BufReader::new(socket.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                socket.write_all(line.as_bytes()).unwrap();
+                line.clear();
+            }
+        });
+
+        let opened = Arc::new(Mutex::new(Vec::new()));
+        let stream = tunnel(MockJumpHost { opened: opened.clone() }, "127.0.0.1", target_port).unwrap();
+        assert_eq!(*opened.lock().unwrap(), [format!("127.0.0.1:{}", target_port)]);
+
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut banner = String::new();
+        reader.read_line(&mut banner).unwrap();
+        assert_eq!(banner, "SSH-2.0-MockTarget_1.0\r\n");
+
+        // Larger than one pump buffer, so it takes several round trips
+        let payload = format!("{}\n", "x".repeat(40 * 1024));
+        (&stream).write_all(payload.as_bytes()).unwrap();
+        let mut echoed = String::new();
+        reader.read_line(&mut echoed).unwrap();
+        assert_eq!(echoed, payload);
+    }
+}
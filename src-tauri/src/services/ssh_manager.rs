@@ -5,6 +5,9 @@ use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use tauri::AppHandle;
 
+use crate::services::ssh_config_file::{self, SshConfigFile};
+use crate::services::ssh_jump::{self, JumpHost};
+
 /// SSH Key Type
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -27,7 +30,7 @@ pub struct SshConfig {
     pub password_encrypted: Option<String>,
     pub private_key_path: Option<PathBuf>,
     pub passphrase_encrypted: Option<String>,
-    pub jump_host: Option<String>, // SSH Proxy Jump
+    pub jump_host: Option<String>, // SSH Proxy Jump: comma-separated [user@]host[:port] or config names
     pub local_forwards: Vec<PortForward>,
     pub remote_forwards: Vec<PortForward>,
     pub dynamic_forward: Option<u16>, // SOCKS proxy port
@@ -36,6 +39,9 @@ pub struct SshConfig {
     pub timeout_seconds: u32,
     pub created_at: u64,
     pub last_used: Option<u64>,
+    /// Host keys are checked against this file when set
+    #[serde(default)]
+    pub known_hosts_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -158,6 +164,7 @@ impl SshManager {
                 .duration_since(std::time::UNIX_EPOCH)?
                 .as_secs(),
             last_used: None,
+            known_hosts_file: None,
         };
 
         let mut configs = self.configs.lock().unwrap();
@@ -191,38 +198,7 @@ impl SshManager {
             sessions.insert(session_id.clone(), session);
         }
 
-        // Implement actual SSH connection using ssh2 crate
-        use ssh2::Session as Ssh2Session;
-        use std::net::TcpStream;
-
-        let tcp = TcpStream::connect(format!("{}:{}", config.host, config.port))
-            .map_err(|e| anyhow::anyhow!("TCP connection failed: {}", e))?;
-
-        let mut sess = Ssh2Session::new()
-            .map_err(|e| anyhow::anyhow!("SSH session creation failed: {}", e))?;
-
-        sess.set_tcp_stream(tcp);
-        sess.handshake()
-            .map_err(|e| anyhow::anyhow!("SSH handshake failed: {}", e))?;
-
-        // Authenticate based on method
-        match config.auth_method {
-            SshAuthMethod::Password | SshAuthMethod::Both => {
-                if let Some(password_enc) = &config.password_encrypted {
-                    // In production: decrypt password
-                    // For now: assume it's the actual password
-                    sess.userauth_password(&config.username, password_enc)
-                        .map_err(|e| anyhow::anyhow!("Password authentication failed: {}", e))?;
-                }
-            }
-            SshAuthMethod::PublicKey => {
-                if let Some(key_path) = &config.private_key_path {
-                    let passphrase = config.passphrase_encrypted.as_deref();
-                    sess.userauth_pubkey_file(&config.username, None, key_path, passphrase)
-                        .map_err(|e| anyhow::anyhow!("Public key authentication failed: {}", e))?;
-                }
-            }
-        }
+        let sess = self.open_session(&config)?;
 
         if !sess.authenticated() {
             return Err(anyhow::anyhow!("SSH authentication failed"));
@@ -246,9 +222,7 @@ impl SshManager {
 
     /// Execute command on SSH session
     pub fn execute_command(&self, session_id: &str, command: &str) -> Result<SshCommandOutput> {
-        use ssh2::Session as Ssh2Session;
         use std::io::Read;
-        use std::net::TcpStream;
 
         let start = std::time::Instant::now();
 
@@ -276,25 +250,7 @@ impl SshManager {
         drop(configs);
 
         // Reconnect and execute (in production, keep connection alive)
-        let tcp = TcpStream::connect(format!("{}:{}", config.host, config.port))?;
-        let mut sess = Ssh2Session::new()?;
-        sess.set_tcp_stream(tcp);
-        sess.handshake()?;
-
-        // Re-authenticate based on method
-        match config.auth_method {
-            SshAuthMethod::Password | SshAuthMethod::Both => {
-                if let Some(password_enc) = &config.password_encrypted {
-                    sess.userauth_password(&config.username, password_enc)?;
-                }
-            }
-            SshAuthMethod::PublicKey => {
-                if let Some(key_path) = &config.private_key_path {
-                    let passphrase = config.passphrase_encrypted.as_deref();
-                    sess.userauth_pubkey_file(&config.username, None, key_path, passphrase)?;
-                }
-            }
-        }
+        let sess = self.open_session(&config)?;
 
         // Execute command
         let mut channel = sess.channel_session()?;
@@ -327,6 +283,79 @@ impl SshManager {
         Ok(())
     }
 
+    /// Import the Host blocks of an OpenSSH client config (default
+    /// `~/.ssh/config`). Hosts that were imported before are updated in place.
+    pub fn import_ssh_config(&self, path: Option<PathBuf>) -> Result<Vec<SshConfig>> {
+        let home = dirs::home_dir();
+        let path = match path {
+            Some(path) => path,
+            None => home.as_ref().context("Could not find home directory")?.join(".ssh").join("config"),
+        };
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Could not read {}", path.display()))?;
+        let file = SshConfigFile::parse(&contents);
+
+        let local_user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or_default();
+        let key_pairs = self.get_key_pairs();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_secs();
+
+        let mut imported = Vec::new();
+        for alias in file.aliases() {
+            let entry = file.resolve(&alias);
+            let username = entry.user.clone().unwrap_or_else(|| local_user.clone());
+            let expand = |value: &str| {
+                ssh_config_file::expand_path(value, home.as_deref(), &entry.host_name, &username, &local_user)
+            };
+
+            // Explicit IdentityFiles first, then the OpenSSH defaults
+            let defaults = ["~/.ssh/id_ed25519", "~/.ssh/id_ecdsa", "~/.ssh/id_rsa"];
+            let private_key_path = entry
+                .identity_files
+                .iter()
+                .map(String::as_str)
+                .chain(defaults)
+                .map(expand)
+                .find_map(|path| resolve_identity_file(&path, &key_pairs));
+            let known_hosts_file = Some(expand(
+                entry.user_known_hosts_file.as_deref().unwrap_or("~/.ssh/known_hosts"),
+            ))
+            .filter(|path| path.is_file());
+
+            let mut configs = self.configs.lock().unwrap();
+            let existing = configs.values().find(|c| c.name == alias).cloned();
+            let config = SshConfig {
+                id: existing.as_ref().map(|c| c.id.clone()).unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+                name: alias.clone(),
+                host: entry.host_name.clone(),
+                port: entry.port.unwrap_or(22),
+                username: username.clone(),
+                auth_method: if private_key_path.is_some() { SshAuthMethod::PublicKey } else { SshAuthMethod::Password },
+                password_encrypted: existing.as_ref().and_then(|c| c.password_encrypted.clone()),
+                private_key_path,
+                passphrase_encrypted: existing.as_ref().and_then(|c| c.passphrase_encrypted.clone()),
+                jump_host: entry.proxy_jump.clone(),
+                local_forwards: existing.as_ref().map(|c| c.local_forwards.clone()).unwrap_or_default(),
+                remote_forwards: existing.as_ref().map(|c| c.remote_forwards.clone()).unwrap_or_default(),
+                dynamic_forward: existing.as_ref().and_then(|c| c.dynamic_forward),
+                compression: true,
+                keep_alive: true,
+                timeout_seconds: 30,
+                created_at: existing.as_ref().map_or(now, |c| c.created_at),
+                last_used: existing.as_ref().and_then(|c| c.last_used),
+                known_hosts_file,
+            };
+            configs.insert(config.id.clone(), config.clone());
+            imported.push(config);
+        }
+
+        log::info!("📥 Imported {} SSH hosts from {}", imported.len(), path.display());
+        Ok(imported)
+    }
+
     /// Get all SSH configurations
     pub fn get_configs(&self) -> Vec<SshConfig> {
         let configs = self.configs.lock().unwrap();
@@ -657,6 +686,120 @@ impl SshManager {
     // PRIVATE HELPERS
     // ========================================================================
 
+    /// Connects and authenticates, tunnelling through the config's jump hosts
+    fn open_session(&self, config: &SshConfig) -> Result<ssh2::Session> {
+        use std::net::TcpStream;
+
+        let route = self.jump_route(config)?;
+        let first = route.first().unwrap_or(config);
+        let mut tcp = TcpStream::connect(format!("{}:{}", first.host, first.port))
+            .map_err(|e| anyhow::anyhow!("TCP connection failed: {}", e))?;
+
+        for (index, hop) in route.iter().enumerate() {
+            let session = Self::authenticate(tcp, hop)
+                .map_err(|e| anyhow::anyhow!("Jump host {}: {}", hop.host, e))?;
+            if !session.authenticated() {
+                return Err(anyhow::anyhow!("SSH authentication failed on jump host {}", hop.host));
+            }
+            let next = route.get(index + 1).unwrap_or(config);
+            tcp = ssh_jump::tunnel(session, &next.host, next.port)
+                .map_err(|e| anyhow::anyhow!("Jump host {} could not reach {}: {}", hop.host, next.host, e))?;
+        }
+
+        Self::authenticate(tcp, config)
+    }
+
+    /// Hops of the config's ProxyJump, first hop first. Hops naming a saved
+    /// config use its address and credentials; others borrow the target's.
+    fn jump_route(&self, config: &SshConfig) -> Result<Vec<SshConfig>> {
+        let Some(spec) = config.jump_host.as_deref() else {
+            return Ok(Vec::new());
+        };
+        let configs = self.get_configs();
+
+        ssh_config_file::parse_proxy_jump(spec)
+            .into_iter()
+            .map(|hop| {
+                if hop.host == config.name {
+                    return Err(anyhow::anyhow!("ProxyJump for {} points back at itself", config.name));
+                }
+                let mut hop_config = match configs.iter().find(|c| c.name == hop.host) {
+                    Some(saved) => saved.clone(),
+                    None => SshConfig {
+                        host: hop.host.clone(),
+                        port: 22,
+                        ..config.clone()
+                    },
+                };
+                hop_config.jump_host = None;
+                if let Some(user) = hop.user {
+                    hop_config.username = user;
+                }
+                if let Some(port) = hop.port {
+                    hop_config.port = port;
+                }
+                Ok(hop_config)
+            })
+            .collect()
+    }
+
+    fn authenticate(tcp: std::net::TcpStream, config: &SshConfig) -> Result<ssh2::Session> {
+        let mut sess = ssh2::Session::new()
+            .map_err(|e| anyhow::anyhow!("SSH session creation failed: {}", e))?;
+
+        sess.set_tcp_stream(tcp);
+        sess.handshake()
+            .map_err(|e| anyhow::anyhow!("SSH handshake failed: {}", e))?;
+
+        if let Some(known_hosts) = &config.known_hosts_file {
+            Self::check_host_key(&sess, config, known_hosts)?;
+        }
+
+        // Authenticate based on method
+        match config.auth_method {
+            SshAuthMethod::Password | SshAuthMethod::Both => {
+                if let Some(password_enc) = &config.password_encrypted {
+                    // In production: decrypt password
+                    // For now: assume it's the actual password
+                    sess.userauth_password(&config.username, password_enc)
+                        .map_err(|e| anyhow::anyhow!("Password authentication failed: {}", e))?;
+                }
+            }
+            SshAuthMethod::PublicKey => {
+                if let Some(key_path) = &config.private_key_path {
+                    let passphrase = config.passphrase_encrypted.as_deref();
+                    sess.userauth_pubkey_file(&config.username, None, key_path, passphrase)
+                        .map_err(|e| anyhow::anyhow!("Public key authentication failed: {}", e))?;
+                }
+            }
+        }
+
+        Ok(sess)
+    }
+
+    /// Refuses hosts whose key differs from the one in known_hosts; unknown
+    /// hosts are only logged
+    fn check_host_key(sess: &ssh2::Session, config: &SshConfig, known_hosts: &std::path::Path) -> Result<()> {
+        let mut hosts = sess.known_hosts()?;
+        if hosts.read_file(known_hosts, ssh2::KnownHostFileKind::OpenSSH).is_err() {
+            log::warn!("Could not read {}", known_hosts.display());
+            return Ok(());
+        }
+        let (key, _) = sess.host_key().context("Server sent no host key")?;
+        match hosts.check_port(&config.host, config.port, key) {
+            ssh2::CheckResult::Mismatch => Err(anyhow::anyhow!(
+                "Host key for {} does not match {}",
+                config.host,
+                known_hosts.display()
+            )),
+            ssh2::CheckResult::NotFound => {
+                log::warn!("{} is not in {}", config.host, known_hosts.display());
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
     fn encrypt_password(&self, password: &str) -> Result<String> {
         // Use AES-256-GCM encryption (same as VPN, FTP, RDP)
         use aes_gcm::{
@@ -695,3 +838,29 @@ impl SshManager {
         Ok(general_purpose::STANDARD.encode(&result))
     }
 }
+
+impl JumpHost for ssh2::Session {
+    type Channel = ssh2::Channel;
+
+    fn open_channel(&self, host: &str, port: u16) -> std::io::Result<ssh2::Channel> {
+        self.channel_direct_tcpip(host, port, None).map_err(std::io::Error::from)
+    }
+
+    fn make_nonblocking(&self, _channel: &mut ssh2::Channel) -> std::io::Result<()> {
+        self.set_blocking(false);
+        Ok(())
+    }
+}
+
+/// Uses an IdentityFile as is when it exists, otherwise a stored key pair
+/// with the same file name
+fn resolve_identity_file(path: &std::path::Path, key_pairs: &[SshKeyPair]) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+    let name = path.file_name()?;
+    key_pairs
+        .iter()
+        .find(|key| key.private_key_path.file_name() == Some(name) && key.private_key_path.is_file())
+        .map(|key| key.private_key_path.clone())
+}
@@ -3,8 +3,8 @@
  * 
  * Provides advanced techniques to avoid bot detection:
  * - User agent randomization (50+ real browser fingerprints)
- * - Canvas fingerprint randomization
- * - WebGL fingerprint spoofing
+ * - Canvas, WebGL and audio fingerprint noise, stable for a session
+ * - WebGL vendor/renderer spoofing
  * - Navigator properties randomization
  * - Timezone and language spoofing
  * - Screen resolution randomization
@@ -14,7 +14,10 @@
  */
 
 use serde::{Deserialize, Serialize};
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,12 +57,109 @@ pub struct BrowserFingerprint {
     pub color_depth: u32,
     pub hardware_concurrency: u32,
     pub device_memory: u32,
+    /// Session the fingerprint and its noise belong to
+    #[serde(default)]
+    pub session_id: String,
+    /// Seeds canvas, WebGL and audio noise; fixed for the whole session so
+    /// repeated reads agree, as they would on a real device
+    #[serde(default)]
+    pub noise_seed: u32,
+    #[serde(default)]
+    pub webgl_vendor: String,
+    #[serde(default)]
+    pub webgl_renderer: String,
+    #[serde(default)]
+    pub canvas_noise: bool,
+    #[serde(default)]
+    pub webgl_noise: bool,
+    #[serde(default)]
+    pub audio_noise: bool,
+}
+
+/// `navigator.languages` (and `Accept-Language`) for a primary language,
+/// e.g. `en-US` gives `["en-US", "en"]`
+pub fn navigator_languages(language: &str) -> Vec<String> {
+    let mut languages = vec![language.to_string()];
+    if let Some((base, _)) = language.split_once('-') {
+        languages.push(base.to_string());
+    }
+    languages
+}
+
+/// Values pinned by a browser profile; the stealth fingerprint reports these
+/// instead of picking its own so the two never disagree
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileFingerprint {
+    pub user_agent: Option<String>,
+    pub language: Option<String>,
+    pub timezone: Option<String>,
+    pub screen_width: Option<u32>,
+    pub screen_height: Option<u32>,
+    pub color_depth: Option<u32>,
+    pub hardware_concurrency: Option<u32>,
+    pub device_memory: Option<u32>,
+    pub webgl_vendor: Option<String>,
+    pub webgl_renderer: Option<String>,
+    pub canvas_noise: Option<bool>,
+    pub webgl_noise: Option<bool>,
+    pub audio_noise: Option<bool>,
+}
+
+/// Deterministic noise behind the canvas, WebGL and audio overrides. Mirrors
+/// `__cubeMix` and friends in the injected script, so a seed always yields
+/// the same noise for the same pixel or sample.
+#[derive(Debug, Clone, Copy)]
+pub struct FingerprintNoise {
+    seed: u32,
+}
+
+impl FingerprintNoise {
+    pub fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+
+    fn mix(&self, value: u32) -> u32 {
+        let mut h = self.seed ^ value.wrapping_mul(0x9E37_79B1);
+        h ^= h >> 16;
+        h = h.wrapping_mul(0x85EB_CA6B);
+        h ^= h >> 13;
+        h = h.wrapping_mul(0xC2B2_AE35);
+        h ^ (h >> 16)
+    }
+
+    /// Shift each color channel of visible RGBA pixels by -1, 0 or +1. The
+    /// pixels are the `width`-wide rectangle at (`x`, `y`) of a canvas
+    /// `canvas_width` pixels wide; noise depends on the canvas position, so
+    /// partial and full reads agree.
+    pub fn apply_to_pixels(&self, rgba: &mut [u8], width: u32, x: u32, y: u32, canvas_width: u32) {
+        for (pixel, chunk) in rgba.chunks_exact_mut(4).enumerate() {
+            if chunk[3] == 0 {
+                continue;
+            }
+            let pixel = pixel as u32;
+            let position = (y + pixel / width) * canvas_width + x + pixel % width;
+            for (channel, value) in chunk.iter_mut().take(3).enumerate() {
+                let delta = (self.mix(position * 4 + channel as u32) % 3) as i16 - 1;
+                *value = (*value as i16 + delta).clamp(0, 255) as u8;
+            }
+        }
+    }
+
+    /// Offset audio samples by at most 1e-4
+    pub fn apply_to_audio(&self, samples: &mut [f32]) {
+        for (index, sample) in samples.iter_mut().enumerate() {
+            *sample += ((self.mix(index as u32) % 2001) as f32 - 1000.0) * 1e-7;
+        }
+    }
 }
 
 pub struct StealthService {
     config: Arc<RwLock<StealthConfig>>,
     user_agents: Vec<String>,
     current_fingerprint: Arc<RwLock<Option<BrowserFingerprint>>>,
+    /// Noise seed per "profile:session"
+    session_seeds: Arc<RwLock<HashMap<String, u32>>>,
+    seeds_path: Option<PathBuf>,
 }
 
 impl StealthService {
@@ -68,6 +168,23 @@ impl StealthService {
             config: Arc::new(RwLock::new(StealthConfig::default())),
             user_agents: Self::get_user_agent_pool(),
             current_fingerprint: Arc::new(RwLock::new(None)),
+            session_seeds: Arc::new(RwLock::new(HashMap::new())),
+            seeds_path: None,
+        }
+    }
+
+    /// Service whose session noise seeds persist in `path`, so a restored
+    /// session reproduces the same fingerprint
+    pub fn with_seed_storage(path: PathBuf) -> Self {
+        let seeds = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Self {
+            session_seeds: Arc::new(RwLock::new(seeds)),
+            seeds_path: Some(path),
+            ..Self::new()
         }
     }
 
@@ -86,32 +203,52 @@ impl StealthService {
         Ok(config_lock.clone())
     }
 
-    /// Generate a random browser fingerprint
+    /// Generate a random browser fingerprint for a new session
     pub fn generate_fingerprint(&self) -> Result<BrowserFingerprint, String> {
-        let config = self.get_config()?;
-        let mut rng = rand::thread_rng();
+        self.generate_session_fingerprint(&uuid::Uuid::new_v4().to_string(), None, None)
+    }
 
-        let user_agent = if let Some(custom_ua) = config.custom_user_agent {
-            custom_ua
-        } else if config.randomize_user_agent {
-            self.get_random_user_agent()
-        } else {
-            self.user_agents[0].clone()
-        };
+    /// Generate the fingerprint of a browsing session. The first call for a
+    /// session picks and persists its noise seed, and every value is derived
+    /// from that seed, so later calls reproduce the same fingerprint. Values
+    /// pinned by the browser profile take precedence.
+    pub fn generate_session_fingerprint(
+        &self,
+        session_id: &str,
+        profile_id: Option<&str>,
+        profile: Option<&ProfileFingerprint>,
+    ) -> Result<BrowserFingerprint, String> {
+        let config = self.get_config()?;
+        let pinned = profile.cloned().unwrap_or_default();
+        let noise_seed = self.session_seed(&format!("{}:{}", profile_id.unwrap_or("default"), session_id))?;
+        let mut rng = StdRng::seed_from_u64(u64::from(noise_seed));
+
+        let random_user_agent = self.user_agents[rng.gen_range(0..self.user_agents.len())].clone();
+        let user_agent = pinned.user_agent.or(config.custom_user_agent).unwrap_or_else(|| {
+            if config.randomize_user_agent {
+                random_user_agent
+            } else {
+                self.user_agents[0].clone()
+            }
+        });
 
         let (platform, vendor) = Self::extract_platform_vendor(&user_agent);
 
-        let language = config.spoof_language.unwrap_or_else(|| {
-            let languages = ["en-US", "en-GB", "es-ES", "fr-FR", "de-DE", "it-IT", "pt-BR"];
-            languages[rng.gen_range(0..languages.len())].to_string()
-        });
-
-        let timezone = config.spoof_timezone.unwrap_or_else(|| {
-            let timezones = ["America/New_York", "America/Los_Angeles", "America/Chicago",
-                "Europe/London", "Europe/Paris", "Europe/Berlin",
-                "Asia/Tokyo", "Asia/Shanghai", "Australia/Sydney"];
-            timezones[rng.gen_range(0..timezones.len())].to_string()
-        });
+        let languages = ["en-US", "en-GB", "es-ES", "fr-FR", "de-DE", "it-IT", "pt-BR"];
+        let random_language = languages[rng.gen_range(0..languages.len())];
+        let language = pinned
+            .language
+            .or(config.spoof_language)
+            .unwrap_or_else(|| random_language.to_string());
+
+        let timezones = ["America/New_York", "America/Los_Angeles", "America/Chicago",
+            "Europe/London", "Europe/Paris", "Europe/Berlin",
+            "Asia/Tokyo", "Asia/Shanghai", "Australia/Sydney"];
+        let random_timezone = timezones[rng.gen_range(0..timezones.len())];
+        let timezone = pinned
+            .timezone
+            .or(config.spoof_timezone)
+            .unwrap_or_else(|| random_timezone.to_string());
 
         let screen_resolutions = [(1920, 1080), (1366, 768), (1440, 900), (1536, 864),
             (1680, 1050), (2560, 1440), (3840, 2160)];
@@ -121,17 +258,27 @@ impl StealthService {
         let hardware_concurrency = rng.gen_range(2..17); // 2-16 cores
         let device_memory = *[2, 4, 8, 16, 32].choose(&mut rng).unwrap();
 
+        let renderers = Self::webgl_renderers(&platform);
+        let (webgl_vendor, webgl_renderer) = renderers[rng.gen_range(0..renderers.len())];
+
         let fingerprint = BrowserFingerprint {
             user_agent,
             platform,
             vendor,
             language,
             timezone,
-            screen_width,
-            screen_height,
-            color_depth,
-            hardware_concurrency,
-            device_memory,
+            screen_width: pinned.screen_width.unwrap_or(screen_width),
+            screen_height: pinned.screen_height.unwrap_or(screen_height),
+            color_depth: pinned.color_depth.unwrap_or(color_depth),
+            hardware_concurrency: pinned.hardware_concurrency.unwrap_or(hardware_concurrency),
+            device_memory: pinned.device_memory.unwrap_or(device_memory),
+            session_id: session_id.to_string(),
+            noise_seed,
+            webgl_vendor: pinned.webgl_vendor.unwrap_or_else(|| webgl_vendor.to_string()),
+            webgl_renderer: pinned.webgl_renderer.unwrap_or_else(|| webgl_renderer.to_string()),
+            canvas_noise: config.randomize_canvas && pinned.canvas_noise.unwrap_or(true),
+            webgl_noise: config.randomize_webgl && pinned.webgl_noise.unwrap_or(true),
+            audio_noise: pinned.audio_noise.unwrap_or(true),
         };
 
         // Store current fingerprint
@@ -142,6 +289,27 @@ impl StealthService {
         Ok(fingerprint)
     }
 
+    /// Noise seed of a session, picking and persisting one on first use
+    fn session_seed(&self, key: &str) -> Result<u32, String> {
+        let mut seeds = self.session_seeds.write()
+            .map_err(|e| format!("Failed to acquire seeds lock: {}", e))?;
+        if let Some(seed) = seeds.get(key) {
+            return Ok(*seed);
+        }
+
+        let seed = rand::thread_rng().gen_range(1..=u32::MAX);
+        seeds.insert(key.to_string(), seed);
+        if let Some(path) = &self.seeds_path {
+            let saved = serde_json::to_string(&*seeds)
+                .map_err(|e| e.to_string())
+                .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+            if let Err(e) = saved {
+                log::warn!("Failed to save fingerprint seeds: {}", e);
+            }
+        }
+        Ok(seed)
+    }
+
     /// Get current fingerprint
     pub fn get_current_fingerprint(&self) -> Result<Option<BrowserFingerprint>, String> {
         let fp_lock = self.current_fingerprint.read()
@@ -181,7 +349,7 @@ Object.defineProperty(navigator, 'language', {{
     get: () => '{}'
 }});
 Object.defineProperty(navigator, 'languages', {{
-    get: () => ['{}']
+    get: () => {}
 }});
 Object.defineProperty(navigator, 'hardwareConcurrency', {{
     get: () => {}
@@ -194,7 +362,7 @@ Object.defineProperty(navigator, 'deviceMemory', {{
                 fingerprint.platform,
                 fingerprint.vendor,
                 fingerprint.language,
-                fingerprint.language,
+                serde_json::to_string(&navigator_languages(&fingerprint.language)).map_err(|e| e.to_string())?,
                 fingerprint.hardware_concurrency,
                 fingerprint.device_memory
             ));
@@ -218,74 +386,158 @@ Object.defineProperty(screen, 'colorDepth', {{
             fingerprint.color_depth
         ));
 
-        // Canvas fingerprint randomization
-        if config.randomize_canvas {
+        // Shared noise helpers, seeded for the session (see FingerprintNoise)
+        scripts.push(format!(r#"
+// Fingerprint noise, stable for the session
+const __cubeNoiseSeed = {};
+const __cubeMix = (value) => {{
+    let h = (__cubeNoiseSeed ^ Math.imul(value, 0x9E3779B1)) >>> 0;
+    h ^= h >>> 16;
+    h = Math.imul(h, 0x85EBCA6B) >>> 0;
+    h ^= h >>> 13;
+    h = Math.imul(h, 0xC2B2AE35) >>> 0;
+    return (h ^ (h >>> 16)) >>> 0;
+}};
+const __cubeNoisePixels = (data, width, x, y, canvasWidth) => {{
+    for (let i = 0; i < data.length; i += 4) {{
+        if (data[i + 3] === 0) continue;
+        const pixel = i / 4;
+        const position = (y + Math.floor(pixel / width)) * canvasWidth + x + (pixel % width);
+        for (let channel = 0; channel < 3; channel++) {{
+            const value = data[i + channel] + (__cubeMix(position * 4 + channel) % 3) - 1;
+            data[i + channel] = value < 0 ? 0 : (value > 255 ? 255 : value);
+        }}
+    }}
+    return data;
+}};
+const __cubeNoiseAudio = (data) => {{
+    for (let i = 0; i < data.length; i++) {{
+        data[i] += ((__cubeMix(i) % 2001) - 1000) * 1e-7;
+    }}
+    return data;
+}};
+"#, fingerprint.noise_seed));
+
+        // Canvas fingerprint noise
+        if fingerprint.canvas_noise {
             scripts.push(r#"
-// Canvas fingerprint randomization
-const originalGetImageData = CanvasRenderingContext2D.prototype.getImageData;
-CanvasRenderingContext2D.prototype.getImageData = function() {
-    const imageData = originalGetImageData.apply(this, arguments);
-    for (let i = 0; i < imageData.data.length; i += 4) {
-        imageData.data[i] += Math.floor(Math.random() * 3) - 1;
-        imageData.data[i + 1] += Math.floor(Math.random() * 3) - 1;
-        imageData.data[i + 2] += Math.floor(Math.random() * 3) - 1;
-    }
+// Canvas fingerprint noise; the page's own canvas is never modified
+const __cubeGetImageData = CanvasRenderingContext2D.prototype.getImageData;
+CanvasRenderingContext2D.prototype.getImageData = function(sx, sy) {
+    const imageData = __cubeGetImageData.apply(this, arguments);
+    __cubeNoisePixels(imageData.data, imageData.width, sx | 0, sy | 0, this.canvas.width);
     return imageData;
 };
 
-const originalToDataURL = HTMLCanvasElement.prototype.toDataURL;
+const __cubeNoisedCopy = (canvas) => {
+    if (!canvas.width || !canvas.height) return canvas;
+    const copy = document.createElement('canvas');
+    copy.width = canvas.width;
+    copy.height = canvas.height;
+    const context = copy.getContext('2d');
+    context.drawImage(canvas, 0, 0);
+    context.putImageData(context.getImageData(0, 0, copy.width, copy.height), 0, 0);
+    return copy;
+};
+
+const __cubeToDataURL = HTMLCanvasElement.prototype.toDataURL;
 HTMLCanvasElement.prototype.toDataURL = function() {
-    const context = this.getContext('2d');
-    if (context) {
-        const imageData = context.getImageData(0, 0, this.width, this.height);
-        for (let i = 0; i < imageData.data.length; i += 4) {
-            imageData.data[i] += Math.floor(Math.random() * 3) - 1;
-        }
-        context.putImageData(imageData, 0, 0);
-    }
-    return originalToDataURL.apply(this, arguments);
+    return __cubeToDataURL.apply(__cubeNoisedCopy(this), arguments);
+};
+
+const __cubeToBlob = HTMLCanvasElement.prototype.toBlob;
+HTMLCanvasElement.prototype.toBlob = function() {
+    return __cubeToBlob.apply(__cubeNoisedCopy(this), arguments);
 };
 "#.to_string());
         }
 
-        // WebGL fingerprint randomization
+        // WebGL vendor/renderer spoofing and readPixels noise
         if config.randomize_webgl {
+            scripts.push(format!(r#"
+// WebGL fingerprint: fixed vendor/renderer, noised pixel reads
+for (const proto of [WebGLRenderingContext.prototype,
+        typeof WebGL2RenderingContext !== 'undefined' ? WebGL2RenderingContext.prototype : null]) {{
+    if (!proto) continue;
+    const getParameter = proto.getParameter;
+    proto.getParameter = function(parameter) {{
+        if (parameter === 37445) return {}; // UNMASKED_VENDOR_WEBGL
+        if (parameter === 37446) return {}; // UNMASKED_RENDERER_WEBGL
+        return getParameter.apply(this, arguments);
+    }};
+    const readPixels = proto.readPixels;
+    proto.readPixels = function(x, y, width, height, format, type, pixels) {{
+        readPixels.apply(this, arguments);
+        if ({} && pixels instanceof Uint8Array && format === this.RGBA) {{
+            __cubeNoisePixels(pixels, width, x, y, this.drawingBufferWidth);
+        }}
+    }};
+}}
+"#,
+                serde_json::to_string(&fingerprint.webgl_vendor).map_err(|e| e.to_string())?,
+                serde_json::to_string(&fingerprint.webgl_renderer).map_err(|e| e.to_string())?,
+                fingerprint.webgl_noise
+            ));
+        }
+
+        // AudioContext fingerprint noise
+        if fingerprint.audio_noise {
             scripts.push(r#"
-// WebGL fingerprint randomization
-const originalGetParameter = WebGLRenderingContext.prototype.getParameter;
-WebGLRenderingContext.prototype.getParameter = function(parameter) {
-    const noise = () => Math.random() * 0.0001;
-    
-    if (parameter === 37445) { // UNMASKED_VENDOR_WEBGL
-        return 'Intel Inc.';
-    }
-    if (parameter === 37446) { // UNMASKED_RENDERER_WEBGL
-        const renderers = [
-            'Intel Iris OpenGL Engine',
-            'Intel HD Graphics 630',
-            'ANGLE (Intel, Intel(R) UHD Graphics 630 Direct3D11 vs_5_0 ps_5_0)',
-        ];
-        return renderers[Math.floor(Math.random() * renderers.length)];
+// Audio fingerprint noise; each buffer is noised once so rereads agree
+const __cubeNoisedAudio = new WeakSet();
+const __cubeGetChannelData = AudioBuffer.prototype.getChannelData;
+AudioBuffer.prototype.getChannelData = function() {
+    const data = __cubeGetChannelData.apply(this, arguments);
+    if (!__cubeNoisedAudio.has(data)) {
+        __cubeNoisedAudio.add(data);
+        __cubeNoiseAudio(data);
     }
-    
-    const result = originalGetParameter.apply(this, arguments);
-    if (typeof result === 'number') {
-        return result + noise();
-    }
-    return result;
+    return data;
+};
+
+const __cubeGetFloatFrequencyData = AnalyserNode.prototype.getFloatFrequencyData;
+AnalyserNode.prototype.getFloatFrequencyData = function(array) {
+    __cubeGetFloatFrequencyData.apply(this, arguments);
+    __cubeNoiseAudio(array);
 };
 "#.to_string());
         }
 
-        // Timezone spoofing
-        if let Some(tz) = config.spoof_timezone {
-            scripts.push(format!(r#"
+        // Timezone spoofing: Intl and Date report the fingerprint's zone.
+        // getHours() and friends keep the host zone; the Chromium engine
+        // overrides the zone natively (Emulation.setTimezoneOverride).
+        scripts.push(format!(r#"
 // Timezone spoofing
-Date.prototype.getTimezoneOffset = function() {{
-    return -300; // Offset for {}
-}};
-"#, tz));
-        }
+(() => {{
+    const timeZone = {};
+    const NativeDateTimeFormat = Intl.DateTimeFormat;
+    const zoned = (locales, options) => new NativeDateTimeFormat(locales, Object.assign({{ timeZone }}, options));
+    Intl.DateTimeFormat = function(locales, options) {{
+        return zoned(locales, options);
+    }};
+    Intl.DateTimeFormat.prototype = NativeDateTimeFormat.prototype;
+    Intl.DateTimeFormat.supportedLocalesOf = NativeDateTimeFormat.supportedLocalesOf;
+
+    const partsFormat = new NativeDateTimeFormat('en-US', {{
+        timeZone, hourCycle: 'h23',
+        year: 'numeric', month: '2-digit', day: '2-digit',
+        hour: '2-digit', minute: '2-digit', second: '2-digit',
+    }});
+    Date.prototype.getTimezoneOffset = function() {{
+        const time = this.getTime();
+        if (isNaN(time)) return NaN;
+        const parts = Object.fromEntries(partsFormat.formatToParts(this).map(part => [part.type, part.value]));
+        const local = Date.UTC(+parts.year, +parts.month - 1, +parts.day, +parts.hour, +parts.minute, +parts.second);
+        return Math.round((Math.floor(time / 1000) * 1000 - local) / 60000);
+    }};
+    for (const name of ['toLocaleString', 'toLocaleDateString', 'toLocaleTimeString']) {{
+        const native = Date.prototype[name];
+        Date.prototype[name] = function(locales, options) {{
+            return native.call(this, locales, Object.assign({{ timeZone }}, options));
+        }};
+    }}
+}})();
+"#, serde_json::to_string(&fingerprint.timezone).map_err(|e| e.to_string())?));
 
         // Remove webdriver property
         scripts.push(r#"
@@ -326,6 +578,26 @@ window.navigator.permissions.query = (parameters) => (
         }
     }
 
+    /// Plausible WebGL (vendor, renderer) pairs for a platform
+    fn webgl_renderers(platform: &str) -> &'static [(&'static str, &'static str)] {
+        match platform {
+            "MacIntel" => &[
+                ("Apple Inc.", "Apple M1"),
+                ("Apple Inc.", "Apple M2"),
+                ("Intel Inc.", "Intel Iris OpenGL Engine"),
+            ],
+            "Linux x86_64" => &[
+                ("Google Inc. (Intel)", "ANGLE (Intel, Mesa Intel(R) UHD Graphics 630 (CFL GT2), OpenGL 4.6)"),
+                ("Google Inc. (AMD)", "ANGLE (AMD, AMD Radeon Graphics (radeonsi, renoir), OpenGL 4.6)"),
+            ],
+            _ => &[
+                ("Google Inc. (Intel)", "ANGLE (Intel, Intel(R) UHD Graphics 630 Direct3D11 vs_5_0 ps_5_0, D3D11)"),
+                ("Google Inc. (NVIDIA)", "ANGLE (NVIDIA, NVIDIA GeForce GTX 1660 Direct3D11 vs_5_0 ps_5_0, D3D11)"),
+                ("Google Inc. (AMD)", "ANGLE (AMD, AMD Radeon RX 580 Direct3D11 vs_5_0 ps_5_0, D3D11)"),
+            ],
+        }
+    }
+
     /// Get comprehensive user agent pool (50+ real browser fingerprints)
     fn get_user_agent_pool() -> Vec<String> {
         vec![
@@ -376,3 +648,83 @@ window.navigator.permissions.query = (parameters) => (
 
 // Helper trait for Vec::choose
 use rand::seq::SliceRandom;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_noise_stable_within_session() {
+        let dir = std::env::temp_dir().join(format!("cube-stealth-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let seeds_path = dir.join("fingerprint_seeds.json");
+
+        let service = StealthService::with_seed_storage(seeds_path.clone());
+        let first = service.generate_session_fingerprint("session-a", Some("profile-1"), None).unwrap();
+        let script = service.generate_stealth_script().unwrap();
+        let again = service.generate_session_fingerprint("session-a", Some("profile-1"), None).unwrap();
+        assert_eq!(first.noise_seed, again.noise_seed);
+        assert_eq!(first.user_agent, again.user_agent);
+        assert_eq!(first.webgl_renderer, again.webgl_renderer);
+        assert_eq!(script, service.generate_stealth_script().unwrap());
+        assert!(script.contains(&format!("const __cubeNoiseSeed = {};", first.noise_seed)));
+
+        // An 8x8 canvas read twice in the session noises identically, and a
+        // partial read sees the same noise as the full one
+        let canvas: Vec<u8> = (0..64u8).flat_map(|i| [i * 3, 128, 255 - i, 255]).collect();
+        let read = |seed: u32, x: u32, y: u32, width: u32| {
+            let mut pixels: Vec<u8> = canvas
+                .chunks(8 * 4)
+                .skip(y as usize)
+                .take(width as usize)
+                .flat_map(|row| row[x as usize * 4..(x + width) as usize * 4].to_vec())
+                .collect();
+            FingerprintNoise::new(seed).apply_to_pixels(&mut pixels, width, x, y, 8);
+            pixels
+        };
+        let noised = read(first.noise_seed, 0, 0, 8);
+        assert_eq!(noised, read(again.noise_seed, 0, 0, 8));
+        assert_ne!(noised, canvas);
+        assert!(noised.iter().zip(&canvas).all(|(a, b)| (*a as i16 - *b as i16).abs() <= 1));
+        assert_eq!(read(first.noise_seed, 2, 2, 4)[..16], noised[(2 * 8 + 2) * 4..(2 * 8 + 6) * 4]);
+
+        let mut samples = vec![0.25f32; 32];
+        let mut samples_again = samples.clone();
+        FingerprintNoise::new(first.noise_seed).apply_to_audio(&mut samples);
+        FingerprintNoise::new(again.noise_seed).apply_to_audio(&mut samples_again);
+        assert_eq!(samples, samples_again);
+
+        // The seed survives a restart; a new session gets a different one
+        let restarted = StealthService::with_seed_storage(seeds_path);
+        let restored = restarted.generate_session_fingerprint("session-a", Some("profile-1"), None).unwrap();
+        assert_eq!(restored.noise_seed, first.noise_seed);
+        let next = restarted.generate_session_fingerprint("session-b", Some("profile-1"), None).unwrap();
+        assert_ne!(next.noise_seed, first.noise_seed);
+        assert_ne!(read(next.noise_seed, 0, 0, 8), noised);
+        let mut next_samples = vec![0.25f32; 32];
+        FingerprintNoise::new(next.noise_seed).apply_to_audio(&mut next_samples);
+        assert_ne!(next_samples, samples);
+
+        // Values pinned by the browser profile win over the session's picks
+        let pinned = ProfileFingerprint {
+            screen_width: Some(2560),
+            screen_height: Some(1440),
+            webgl_vendor: Some("Apple Inc.".to_string()),
+            webgl_renderer: Some("Apple M2 Pro".to_string()),
+            audio_noise: Some(false),
+            language: Some("de-DE".to_string()),
+            timezone: Some("Europe/Berlin".to_string()),
+            ..Default::default()
+        };
+        let profiled = restarted.generate_session_fingerprint("session-a", Some("profile-1"), Some(&pinned)).unwrap();
+        assert_eq!(profiled.noise_seed, first.noise_seed);
+        assert_eq!((profiled.screen_width, profiled.screen_height), (2560, 1440));
+        let script = restarted.generate_stealth_script().unwrap();
+        assert!(script.contains("\"Apple M2 Pro\""));
+        assert!(!script.contains("AudioBuffer.prototype.getChannelData"));
+        assert!(script.contains(r#"const timeZone = "Europe/Berlin";"#));
+        assert!(script.contains(r#"get: () => ["de-DE","de"]"#));
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,427 @@
+// CUBE Nexum - Sync Bookmark Merge
+// Three-way structural merge of the bookmark tree between two devices:
+// - Every node is reconciled by id against the last merged snapshot (the base),
+//   so moves, renames, adds and deletes are treated as independent operations
+// - A side that omits a node has not touched it; deletes travel as tombstones
+// - Sibling order is merged per folder: a reorder on one side keeps the items
+//   added on the other, which are slotted in at their requested position
+// - Folders created on both sides with the same parent and title collapse into one
+// - The same node moved to two different folders is reported as a conflict;
+//   the newer move is applied so both devices still converge on one tree
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Sync representation of a bookmark or folder
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BookmarkNode {
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub title: String,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub is_folder: bool,
+    #[serde(default)]
+    pub position: u32,
+    pub modified_at: DateTime<Utc>,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+/// A node both sides moved into different folders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkMoveConflict {
+    pub item_id: String,
+    pub local: BookmarkNode,
+    pub remote: BookmarkNode,
+    /// Node as it ended up in the merged tree
+    pub applied: BookmarkNode,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkMerge {
+    pub nodes: Vec<BookmarkNode>,
+    pub conflicts: Vec<BookmarkMoveConflict>,
+    /// Duplicate folder id -> folder it was folded into
+    pub merged_folders: HashMap<String, String>,
+}
+
+/// Ordering used whenever both sides changed the same thing: newer wins, and
+/// ties fall back to the content so both devices pick the same side
+fn newer<'a>(a: &'a BookmarkNode, b: &'a BookmarkNode) -> &'a BookmarkNode {
+    let key = |n: &'a BookmarkNode| (n.modified_at, &n.parent_id, &n.title, &n.url, n.position);
+    if key(a) >= key(b) { a } else { b }
+}
+
+/// Pick the value changed relative to base; when both changed, the newer side wins
+fn merge_field<T: PartialEq + Clone>(
+    base: Option<&T>,
+    local: &T,
+    remote: &T,
+    newest: &BookmarkNode,
+    local_node: &BookmarkNode,
+) -> T {
+    let local_changed = base != Some(local);
+    let remote_changed = base != Some(remote);
+    match (local_changed, remote_changed) {
+        (true, false) => local.clone(),
+        (false, true) => remote.clone(),
+        _ if std::ptr::eq(newest, local_node) => local.clone(),
+        _ => remote.clone(),
+    }
+}
+
+/// Merge `local` and `remote` change sets against `base`
+pub fn merge_bookmark_trees(
+    base: &[BookmarkNode],
+    local: &[BookmarkNode],
+    remote: &[BookmarkNode],
+) -> BookmarkMerge {
+    let base: HashMap<&str, &BookmarkNode> = base.iter().map(|n| (n.id.as_str(), n)).collect();
+    let local: HashMap<&str, &BookmarkNode> = local.iter().map(|n| (n.id.as_str(), n)).collect();
+    let remote: HashMap<&str, &BookmarkNode> = remote.iter().map(|n| (n.id.as_str(), n)).collect();
+
+    let mut ids: Vec<&str> = base.keys().chain(local.keys()).chain(remote.keys()).copied().collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let mut merged: BTreeMap<String, BookmarkNode> = BTreeMap::new();
+    let mut conflicts = Vec::new();
+
+    for id in ids {
+        let b = base.get(id).copied();
+        let (l, r) = match (local.get(id).copied().or(b), remote.get(id).copied().or(b)) {
+            (Some(l), Some(r)) => (l, r),
+            (Some(only), None) | (None, Some(only)) => {
+                merged.insert(id.to_string(), only.clone());
+                continue;
+            }
+            (None, None) => continue,
+        };
+        let newest = newer(l, r);
+
+        let parent_id = merge_field(b.map(|b| &b.parent_id), &l.parent_id, &r.parent_id, newest, l);
+        let title = merge_field(b.map(|b| &b.title), &l.title, &r.title, newest, l);
+        let url = merge_field(b.map(|b| &b.url), &l.url, &r.url, newest, l);
+        let position = merge_field(b.map(|b| &b.position), &l.position, &r.position, newest, l);
+        // An edit on one side outlives a delete on the other
+        let is_deleted = match b {
+            Some(b) if l.is_deleted != r.is_deleted => {
+                let edited = if l.is_deleted { r } else { l };
+                edited.parent_id == b.parent_id && edited.title == b.title && edited.url == b.url
+            }
+            _ => l.is_deleted && r.is_deleted,
+        };
+
+        let node = BookmarkNode {
+            id: id.to_string(),
+            parent_id,
+            title,
+            url,
+            is_folder: l.is_folder || r.is_folder,
+            position,
+            modified_at: l.modified_at.max(r.modified_at),
+            is_deleted,
+        };
+
+        let moved = |side: &BookmarkNode| b.is_some_and(|b| b.parent_id != side.parent_id);
+        if moved(l) && moved(r) && l.parent_id != r.parent_id && !l.is_deleted && !r.is_deleted {
+            conflicts.push(BookmarkMoveConflict {
+                item_id: id.to_string(),
+                local: l.clone(),
+                remote: r.clone(),
+                applied: node.clone(),
+            });
+        }
+        merged.insert(id.to_string(), node);
+    }
+
+    let merged_folders = fold_duplicate_folders(&mut merged, &base);
+    break_cycles(&mut merged, &base);
+    revive_parents(&mut merged);
+    renumber_siblings(&mut merged, &base, &local, &remote);
+
+    for conflict in &mut conflicts {
+        if let Some(node) = merged.get(&conflict.item_id) {
+            conflict.applied = node.clone();
+        }
+    }
+
+    BookmarkMerge {
+        nodes: merged.into_values().collect(),
+        conflicts,
+        merged_folders,
+    }
+}
+
+/// Collapse folders sharing a parent and title when at least one of them is new.
+/// Folders that already existed in the base are kept apart, since the user made both.
+fn fold_duplicate_folders(
+    merged: &mut BTreeMap<String, BookmarkNode>,
+    base: &HashMap<&str, &BookmarkNode>,
+) -> HashMap<String, String> {
+    let mut folded = HashMap::new();
+    loop {
+        let mut groups: BTreeMap<(Option<String>, String), Vec<String>> = BTreeMap::new();
+        for node in merged.values().filter(|n| n.is_folder && !n.is_deleted) {
+            groups
+                .entry((node.parent_id.clone(), node.title.clone()))
+                .or_default()
+                .push(node.id.clone());
+        }
+
+        let mut changed = false;
+        for ids in groups.into_values().filter(|ids| ids.len() > 1) {
+            // Prefer a folder that already existed; ids are sorted so the rest is deterministic
+            let keep = ids.iter().find(|id| base.contains_key(id.as_str())).unwrap_or(&ids[0]).clone();
+            for dup in ids.iter().filter(|id| **id != keep && !base.contains_key(id.as_str())) {
+                merged.remove(dup);
+                for child in merged.values_mut().filter(|n| n.parent_id.as_deref() == Some(dup.as_str())) {
+                    child.parent_id = Some(keep.clone());
+                }
+                folded.insert(dup.clone(), keep.clone());
+                changed = true;
+            }
+        }
+        // Re-parenting can line up duplicates one level down
+        if !changed {
+            return folded;
+        }
+    }
+}
+
+/// Two concurrent moves can each be valid yet together put a folder inside itself.
+/// Undo the older of the moves that close a loop by sending it back to its base parent.
+fn break_cycles(merged: &mut BTreeMap<String, BookmarkNode>, base: &HashMap<&str, &BookmarkNode>) {
+    loop {
+        let Some(cycle) = find_cycle(merged) else { return };
+        let victim = cycle
+            .iter()
+            .filter_map(|id| merged.get(id))
+            .filter(|n| base.get(n.id.as_str()).is_some_and(|b| b.parent_id != n.parent_id))
+            .min_by(|a, b| (a.modified_at, &a.id).cmp(&(b.modified_at, &b.id)))
+            .map(|n| n.id.clone());
+        // Without a moved node to undo, the loop is made of new nodes: lift one to the root
+        let (victim, parent) = match victim {
+            Some(id) => {
+                let parent = base.get(id.as_str()).and_then(|b| b.parent_id.clone());
+                (id, parent)
+            }
+            None => (cycle[0].clone(), None),
+        };
+        if let Some(node) = merged.get_mut(&victim) {
+            node.parent_id = parent;
+        }
+    }
+}
+
+fn find_cycle(merged: &BTreeMap<String, BookmarkNode>) -> Option<Vec<String>> {
+    for start in merged.keys() {
+        let mut path = vec![start.clone()];
+        let mut seen: HashSet<&str> = HashSet::from([start.as_str()]);
+        let mut current = merged.get(start).and_then(|n| n.parent_id.as_deref());
+        while let Some(id) = current {
+            if id == start {
+                return Some(path);
+            }
+            if !seen.insert(id) {
+                break;
+            }
+            path.push(id.to_string());
+            current = merged.get(id).and_then(|n| n.parent_id.as_deref());
+        }
+    }
+    None
+}
+
+/// A folder deleted on one device but given new content on another comes back
+fn revive_parents(merged: &mut BTreeMap<String, BookmarkNode>) {
+    let mut revive: Vec<String> = merged
+        .values()
+        .filter(|n| !n.is_deleted)
+        .filter_map(|n| n.parent_id.clone())
+        .collect();
+    while let Some(id) = revive.pop() {
+        if let Some(parent) = merged.get_mut(&id) {
+            if parent.is_deleted {
+                parent.is_deleted = false;
+                revive.extend(parent.parent_id.clone());
+            }
+        }
+    }
+}
+
+fn sibling_order<'a>(
+    nodes: impl Iterator<Item = &'a BookmarkNode>,
+    parent: &Option<String>,
+    keep: &HashSet<&str>,
+) -> Vec<String> {
+    let mut children: Vec<&BookmarkNode> = nodes
+        .filter(|n| &n.parent_id == parent && keep.contains(n.id.as_str()))
+        .collect();
+    children.sort_by(|a, b| (a.position, &a.id).cmp(&(b.position, &b.id)));
+    children.into_iter().map(|n| n.id.clone()).collect()
+}
+
+/// Rebuild contiguous positions for each folder. Children that were already in the
+/// folder keep the order of whichever side reordered them; newcomers are inserted
+/// at the position their side gave them.
+fn renumber_siblings(
+    merged: &mut BTreeMap<String, BookmarkNode>,
+    base: &HashMap<&str, &BookmarkNode>,
+    local: &HashMap<&str, &BookmarkNode>,
+    remote: &HashMap<&str, &BookmarkNode>,
+) {
+    let parents: HashSet<Option<String>> = merged.values().map(|n| n.parent_id.clone()).collect();
+    for parent in parents {
+        let live: Vec<&BookmarkNode> = merged
+            .values()
+            .filter(|n| n.parent_id == parent && !n.is_deleted)
+            .collect();
+        // Children that sat in this folder in the base and still do
+        let settled: HashSet<&str> = live
+            .iter()
+            .filter(|n| base.get(n.id.as_str()).is_some_and(|b| b.parent_id == parent))
+            .map(|n| n.id.as_str())
+            .collect();
+
+        let side_view = |side: &HashMap<&str, &BookmarkNode>| {
+            let view: Vec<&BookmarkNode> = settled
+                .iter()
+                .filter_map(|id| side.get(id).or_else(|| base.get(id)).copied())
+                .map(|n| {
+                    // A side that did not touch the node saw it at its base position
+                    if n.parent_id == parent { n } else { base[n.id.as_str()] }
+                })
+                .collect();
+            sibling_order(view.into_iter(), &parent, &settled)
+        };
+        let base_order = sibling_order(base.values().copied(), &parent, &settled);
+        let local_order = side_view(local);
+        let remote_order = side_view(remote);
+
+        let mut order = match (local_order != base_order, remote_order != base_order) {
+            (true, false) => local_order,
+            (false, true) => remote_order,
+            (false, false) => base_order,
+            (true, true) => {
+                let latest = |side: &HashMap<&str, &BookmarkNode>| {
+                    settled.iter().filter_map(|id| side.get(id)).map(|n| n.modified_at).max()
+                };
+                if (latest(local), &local_order) >= (latest(remote), &remote_order) {
+                    local_order
+                } else {
+                    remote_order
+                }
+            }
+        };
+
+        let mut newcomers: Vec<&BookmarkNode> =
+            live.iter().copied().filter(|n| !settled.contains(n.id.as_str())).collect();
+        newcomers.sort_by(|a, b| (a.position, &a.id).cmp(&(b.position, &b.id)));
+        for node in newcomers {
+            let at = (node.position as usize).min(order.len());
+            order.insert(at, node.id.clone());
+        }
+
+        for (position, id) in order.into_iter().enumerate() {
+            if let Some(node) = merged.get_mut(&id) {
+                node.position = position as u32;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn node(id: &str, parent: Option<&str>, title: &str, position: u32, folder: bool, at: DateTime<Utc>) -> BookmarkNode {
+        BookmarkNode {
+            id: id.to_string(),
+            parent_id: parent.map(str::to_string),
+            title: title.to_string(),
+            url: (!folder).then(|| format!("https://{}.example", id)),
+            is_folder: folder,
+            position,
+            modified_at: at,
+            is_deleted: false,
+        }
+    }
+
+    fn children(merge: &BookmarkMerge, parent: &str) -> Vec<String> {
+        let mut kids: Vec<&BookmarkNode> = merge
+            .nodes
+            .iter()
+            .filter(|n| n.parent_id.as_deref() == Some(parent) && !n.is_deleted)
+            .collect();
+        kids.sort_by_key(|n| n.position);
+        kids.into_iter().map(|n| n.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_concurrent_reorder_and_folder_add_converge() {
+        let t0 = Utc::now();
+        let base = vec![
+            node("bar", None, "Bar", 0, true, t0),
+            node("a", Some("bar"), "A", 0, false, t0),
+            node("b", Some("bar"), "B", 1, false, t0),
+            node("c", Some("bar"), "C", 2, false, t0),
+        ];
+        // Device 1 moves C to the front
+        let t1 = t0 + Duration::seconds(5);
+        let reorder = vec![
+            node("c", Some("bar"), "C", 0, false, t1),
+            node("a", Some("bar"), "A", 1, false, t1),
+            node("b", Some("bar"), "B", 2, false, t1),
+        ];
+        // Device 2 adds a "Work" folder in second place, and so does device 1 later
+        let t2 = t0 + Duration::seconds(3);
+        let add = vec![node("work-2", Some("bar"), "Work", 1, true, t2)];
+        let mut reorder_and_add = reorder.clone();
+        reorder_and_add.push(node("work-1", Some("bar"), "Work", 1, true, t1));
+
+        let on_device_1 = merge_bookmark_trees(&base, &reorder_and_add, &add);
+        let on_device_2 = merge_bookmark_trees(&base, &add, &reorder_and_add);
+
+        assert_eq!(on_device_1.nodes, on_device_2.nodes);
+        assert!(on_device_1.conflicts.is_empty());
+        assert_eq!(children(&on_device_1, "bar"), vec!["c", "work-1", "a", "b"]);
+        assert_eq!(on_device_1.merged_folders.get("work-2"), Some(&"work-1".to_string()));
+        let positions: Vec<u32> = on_device_1.nodes.iter().filter(|n| n.id != "bar").map(|n| n.position).collect();
+        let mut sorted = positions.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_move_move_is_reported_as_conflict() {
+        let t0 = Utc::now();
+        let base = vec![
+            node("inbox", None, "Inbox", 0, true, t0),
+            node("work", None, "Work", 1, true, t0),
+            node("home", None, "Home", 2, true, t0),
+            node("doc", Some("inbox"), "Doc", 0, false, t0),
+        ];
+        let local = vec![node("doc", Some("work"), "Doc", 0, false, t0 + Duration::seconds(1))];
+        let remote = vec![node("doc", Some("home"), "Doc", 0, false, t0 + Duration::seconds(2))];
+
+        let merge = merge_bookmark_trees(&base, &local, &remote);
+        assert_eq!(merge.conflicts.len(), 1);
+        let conflict = &merge.conflicts[0];
+        assert_eq!(conflict.item_id, "doc");
+        assert_eq!(conflict.local.parent_id.as_deref(), Some("work"));
+        assert_eq!(conflict.remote.parent_id.as_deref(), Some("home"));
+        // The tree is still consistent: the newer move is applied until the user decides
+        assert_eq!(conflict.applied.parent_id.as_deref(), Some("home"));
+        assert_eq!(children(&merge, "home"), vec!["doc"]);
+
+        // Moving to the same place on both sides is not a conflict
+        let same = merge_bookmark_trees(&base, &local, &local);
+        assert!(same.conflicts.is_empty());
+    }
+}
@@ -0,0 +1,209 @@
+// CUBE Nexum - Sync Key Ring
+// Per-data-type E2E keys derived from the master sync key:
+// - Each data type has its own key, so leaking one (e.g. to a shared extension)
+//   exposes only that type
+// - Rotating a type bumps its version with a fresh salt; other types keep their keys
+// - Old versions stay available so items sealed before a rotation can be re-encrypted
+// - Devices behind on a version fetch the new key wrapped under the master-derived KEK
+
+use std::collections::HashMap;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm,
+};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use super::browser_sync::SyncDataType;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const KEY_WRAP_INFO: &str = "cube-sync/key-wrap";
+
+/// Ciphertext of one sync item together with the key version that sealed it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SealedPayload {
+    pub key_version: u32,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// A data-type key encrypted under the key-wrapping key, for devices to unwrap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrappedDataKey {
+    pub data_type: SyncDataType,
+    pub key_version: u32,
+    pub nonce: String,
+    pub wrapped_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataTypeKeyVersion {
+    pub key_id: String,
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip)]
+    salt: [u8; 16],
+}
+
+pub struct SyncKeyRing {
+    master_key: [u8; 32],
+    /// Every version per type, oldest first; the last one is current
+    versions: HashMap<SyncDataType, Vec<DataTypeKeyVersion>>,
+}
+
+impl SyncKeyRing {
+    pub fn new(master_key: [u8; 32]) -> Self {
+        Self {
+            master_key,
+            versions: HashMap::new(),
+        }
+    }
+
+    pub fn generate() -> Self {
+        let mut master_key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut master_key);
+        Self::new(master_key)
+    }
+
+    /// Current key version for a type, creating version 1 on first use
+    pub fn current(&mut self, data_type: &SyncDataType) -> DataTypeKeyVersion {
+        let versions = self.versions.entry(data_type.clone()).or_default();
+        if versions.is_empty() {
+            versions.push(new_version(1));
+        }
+        versions[versions.len() - 1].clone()
+    }
+
+    pub fn current_version(&mut self, data_type: &SyncDataType) -> u32 {
+        self.current(data_type).version
+    }
+
+    /// All versions of every type that has a key
+    pub fn all_versions(&self) -> Vec<(SyncDataType, DataTypeKeyVersion)> {
+        self.versions
+            .iter()
+            .flat_map(|(data_type, versions)| versions.iter().map(move |v| (data_type.clone(), v.clone())))
+            .collect()
+    }
+
+    pub fn data_types(&self) -> Vec<SyncDataType> {
+        self.versions.keys().cloned().collect()
+    }
+
+    pub fn latest_version(&self, data_type: &SyncDataType) -> Option<u32> {
+        self.versions.get(data_type)?.last().map(|v| v.version)
+    }
+
+    /// Start a new key version for one type; returns it
+    pub fn rotate(&mut self, data_type: &SyncDataType) -> DataTypeKeyVersion {
+        let next = self.current_version(data_type) + 1;
+        let version = new_version(next);
+        self.versions.entry(data_type.clone()).or_default().push(version.clone());
+        version
+    }
+
+    pub fn seal(&mut self, data_type: &SyncDataType, plaintext: &[u8]) -> Result<SealedPayload, String> {
+        let current = self.current(data_type);
+        let key = self.data_key(data_type, &current);
+        let (nonce, ciphertext) = encrypt(&key, plaintext)?;
+        Ok(SealedPayload {
+            key_version: current.version,
+            nonce,
+            ciphertext,
+        })
+    }
+
+    pub fn open(&self, data_type: &SyncDataType, sealed: &SealedPayload) -> Result<Vec<u8>, String> {
+        let version = self
+            .version(data_type, sealed.key_version)
+            .ok_or_else(|| format!("Unknown key version {} for {:?}", sealed.key_version, data_type))?;
+        decrypt(&self.data_key(data_type, version), &sealed.nonce, &sealed.ciphertext)
+    }
+
+    /// The current key for a type, wrapped for a device that only has `known_version`.
+    /// `None` when the device is already up to date.
+    pub fn wrapped_key_for(&mut self, data_type: &SyncDataType, known_version: u32) -> Result<Option<WrappedDataKey>, String> {
+        let current = self.current(data_type);
+        if known_version >= current.version {
+            return Ok(None);
+        }
+        let key = self.data_key(data_type, &current);
+        let (nonce, wrapped_key) = encrypt(&self.wrapping_key(), &key)?;
+        Ok(Some(WrappedDataKey {
+            data_type: data_type.clone(),
+            key_version: current.version,
+            nonce,
+            wrapped_key,
+        }))
+    }
+
+    /// Recover a data key from its wrapped form (device side)
+    pub fn unwrap_key(&self, wrapped: &WrappedDataKey) -> Result<[u8; 32], String> {
+        let key = decrypt(&self.wrapping_key(), &wrapped.nonce, &wrapped.wrapped_key)?;
+        key.try_into().map_err(|_| "Wrapped key has the wrong length".to_string())
+    }
+
+    fn version(&self, data_type: &SyncDataType, version: u32) -> Option<&DataTypeKeyVersion> {
+        self.versions.get(data_type)?.iter().find(|v| v.version == version)
+    }
+
+    fn data_key(&self, data_type: &SyncDataType, version: &DataTypeKeyVersion) -> [u8; 32] {
+        let info = format!("cube-sync/{:?}/v{}", data_type, version.version);
+        hkdf_sha256(&self.master_key, &version.salt, info.as_bytes())
+    }
+
+    fn wrapping_key(&self) -> [u8; 32] {
+        hkdf_sha256(&self.master_key, &[], KEY_WRAP_INFO.as_bytes())
+    }
+}
+
+fn new_version(version: u32) -> DataTypeKeyVersion {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    DataTypeKeyVersion {
+        key_id: uuid::Uuid::new_v4().to_string(),
+        version,
+        created_at: Utc::now(),
+        salt,
+    }
+}
+
+/// HKDF-SHA256 (RFC 5869) producing a single 32-byte block
+fn hkdf_sha256(ikm: &[u8], salt: &[u8], info: &[u8]) -> [u8; 32] {
+    let mut extract = <HmacSha256 as Mac>::new_from_slice(salt).expect("HMAC accepts any key length");
+    extract.update(ikm);
+    let prk = extract.finalize().into_bytes();
+
+    let mut expand = <HmacSha256 as Mac>::new_from_slice(&prk).expect("HMAC accepts any key length");
+    expand.update(info);
+    expand.update(&[1]);
+    expand.finalize().into_bytes().into()
+}
+
+fn encrypt(key: &[u8; 32], plaintext: &[u8]) -> Result<(String, String), String> {
+    let cipher = Aes256Gcm::new(key.into());
+    let mut nonce = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt((&nonce).into(), plaintext)
+        .map_err(|e| format!("Encryption failed: {}", e))?;
+    Ok((general_purpose::STANDARD.encode(nonce), general_purpose::STANDARD.encode(ciphertext)))
+}
+
+fn decrypt(key: &[u8; 32], nonce: &str, ciphertext: &str) -> Result<Vec<u8>, String> {
+    let nonce: [u8; 12] = general_purpose::STANDARD
+        .decode(nonce)
+        .map_err(|e| e.to_string())?
+        .try_into()
+        .map_err(|_| "Invalid nonce".to_string())?;
+    let ciphertext = general_purpose::STANDARD.decode(ciphertext).map_err(|e| e.to_string())?;
+    Aes256Gcm::new(key.into())
+        .decrypt((&nonce).into(), ciphertext.as_slice())
+        .map_err(|_| "Decryption failed".to_string())
+}
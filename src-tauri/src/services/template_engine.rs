@@ -0,0 +1,560 @@
+// ============================================================================
+// Template Engine - Sandboxed notification templates
+// ============================================================================
+// A deliberately small, logic-light template language:
+//   {{ user.name }}                     interpolation (dotted paths)
+//   {{ total | currency("EUR") }}       formatters, chainable with `|`
+//   {{#if user.vip}} .. {{else}} .. {{/if}}
+//   {{#each items as item}} .. {{/each}} (`this` when no alias, `@index`)
+// Templates only read from the data they are given: there are no function
+// calls into the host, and nesting, iterations and output size are bounded.
+
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+
+const MAX_TEMPLATE_LEN: usize = 64 * 1024;
+const MAX_OUTPUT_LEN: usize = 256 * 1024;
+const MAX_NESTING_DEPTH: usize = 8;
+const MAX_TOTAL_ITERATIONS: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscapeMode {
+    /// Escape interpolated values for HTML bodies (email)
+    Html,
+    /// Emit values verbatim (SMS, push, in-app text)
+    Plain,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    TooLarge,
+    TooDeep,
+    TooManyIterations,
+    OutputTooLarge,
+    Syntax(String),
+}
+
+impl std::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooLarge => write!(f, "Template exceeds {} bytes", MAX_TEMPLATE_LEN),
+            Self::TooDeep => write!(f, "Template nesting exceeds {} levels", MAX_NESTING_DEPTH),
+            Self::TooManyIterations => write!(f, "Template loops exceed {} iterations", MAX_TOTAL_ITERATIONS),
+            Self::OutputTooLarge => write!(f, "Rendered output exceeds {} bytes", MAX_OUTPUT_LEN),
+            Self::Syntax(msg) => write!(f, "Template syntax error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+#[derive(Debug, Clone)]
+pub struct RenderOutput {
+    pub output: String,
+    /// Undefined variables and formatter problems; rendering still succeeds
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Filter {
+    name: String,
+    arg: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Var { path: String, filters: Vec<Filter> },
+    If { path: String, negate: bool, then: Vec<Node>, otherwise: Vec<Node> },
+    Each { path: String, alias: String, body: Vec<Node> },
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+enum Token {
+    Text(String),
+    Tag(String),
+}
+
+fn tokenize(template: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| TemplateError::Syntax("unclosed '{{'".to_string()))?;
+        tokens.push(Token::Tag(after[..end].trim().to_string()));
+        rest = &after[end + 2..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: std::vec::IntoIter<Token>,
+}
+
+impl Parser {
+    /// Parses nodes until one of `terminators` is reached, returning that tag.
+    /// Running out of input is only valid at the top level (no terminators).
+    fn parse_block(&mut self, depth: usize, terminators: &[&str]) -> Result<(Vec<Node>, Option<String>), TemplateError> {
+        if depth > MAX_NESTING_DEPTH {
+            return Err(TemplateError::TooDeep);
+        }
+
+        let mut nodes = Vec::new();
+        while let Some(token) = self.tokens.next() {
+            let tag = match token {
+                Token::Text(text) => {
+                    nodes.push(Node::Text(text));
+                    continue;
+                }
+                Token::Tag(tag) => tag,
+            };
+
+            if terminators.contains(&tag.as_str()) {
+                return Ok((nodes, Some(tag)));
+            }
+
+            if let Some(cond) = tag.strip_prefix("#if ") {
+                let cond = cond.trim();
+                let (negate, path) = match cond.strip_prefix('!') {
+                    Some(path) => (true, path.trim()),
+                    None => (false, cond),
+                };
+                validate_path(path)?;
+
+                let (then, end) = self.parse_block(depth + 1, &["else", "/if"])?;
+                let otherwise = if end.as_deref() == Some("else") {
+                    self.parse_block(depth + 1, &["/if"])?.0
+                } else {
+                    Vec::new()
+                };
+                nodes.push(Node::If { path: path.to_string(), negate, then, otherwise });
+            } else if let Some(spec) = tag.strip_prefix("#each ") {
+                let mut parts = spec.split_whitespace();
+                let path = parts.next().unwrap_or_default();
+                let alias = match (parts.next(), parts.next(), parts.next()) {
+                    (None, _, _) => "this".to_string(),
+                    (Some("as"), Some(alias), None) => alias.to_string(),
+                    _ => return Err(TemplateError::Syntax(format!("invalid each block '{}'", spec))),
+                };
+                validate_path(path)?;
+                validate_path(&alias)?;
+
+                let (body, _) = self.parse_block(depth + 1, &["/each"])?;
+                nodes.push(Node::Each { path: path.to_string(), alias, body });
+            } else if tag.starts_with('#') || tag.starts_with('/') || tag == "else" {
+                return Err(TemplateError::Syntax(format!("unexpected '{{{{{}}}}}'", tag)));
+            } else {
+                nodes.push(parse_var(&tag)?);
+            }
+        }
+
+        if terminators.is_empty() {
+            Ok((nodes, None))
+        } else {
+            Err(TemplateError::Syntax(format!("missing {{{{{}}}}}", terminators[terminators.len() - 1])))
+        }
+    }
+}
+
+fn validate_path(path: &str) -> Result<(), TemplateError> {
+    let valid = !path.is_empty()
+        && path.split('.').all(|segment| {
+            !segment.is_empty()
+                && segment.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '@')
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(TemplateError::Syntax(format!("invalid variable '{}'", path)))
+    }
+}
+
+fn parse_var(tag: &str) -> Result<Node, TemplateError> {
+    let mut parts = tag.split('|').map(str::trim);
+    let path = parts.next().unwrap_or_default();
+    validate_path(path)?;
+
+    let filters = parts
+        .map(|spec| {
+            let (name, arg) = match spec.split_once('(') {
+                Some((name, rest)) => {
+                    let inner = rest
+                        .strip_suffix(')')
+                        .ok_or_else(|| TemplateError::Syntax(format!("unclosed formatter '{}'", spec)))?
+                        .trim();
+                    let arg = inner
+                        .strip_prefix('"')
+                        .and_then(|s| s.strip_suffix('"'))
+                        .unwrap_or(inner);
+                    (name.trim(), Some(arg.to_string()))
+                }
+                None => (spec, None),
+            };
+            Ok(Filter { name: name.to_string(), arg })
+        })
+        .collect::<Result<Vec<_>, TemplateError>>()?;
+
+    Ok(Node::Var { path: path.to_string(), filters })
+}
+
+fn parse(template: &str) -> Result<Vec<Node>, TemplateError> {
+    if template.len() > MAX_TEMPLATE_LEN {
+        return Err(TemplateError::TooLarge);
+    }
+    let mut parser = Parser { tokens: tokenize(template)?.into_iter() };
+    Ok(parser.parse_block(0, &[])?.0)
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+struct Renderer<'a> {
+    root: &'a Value,
+    scopes: Vec<(String, Value)>,
+    escape: EscapeMode,
+    iterations: usize,
+    output: String,
+    warnings: Vec<String>,
+}
+
+impl<'a> Renderer<'a> {
+    fn lookup(&self, path: &str) -> Option<Value> {
+        let mut segments = path.split('.');
+        let head = segments.next()?;
+
+        let mut current = self
+            .scopes
+            .iter()
+            .rev()
+            .find(|(name, _)| name == head)
+            .map(|(_, value)| value.clone())
+            .or_else(|| self.root.get(head).cloned())?;
+
+        for segment in segments {
+            current = match &current {
+                Value::Object(map) => map.get(segment)?.clone(),
+                Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?.clone(),
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    fn warn(&mut self, message: String) {
+        if !self.warnings.contains(&message) {
+            self.warnings.push(message);
+        }
+    }
+
+    fn write(&mut self, text: &str) -> Result<(), TemplateError> {
+        if self.output.len() + text.len() > MAX_OUTPUT_LEN {
+            return Err(TemplateError::OutputTooLarge);
+        }
+        self.output.push_str(text);
+        Ok(())
+    }
+
+    fn render(&mut self, nodes: &[Node]) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => self.write(text)?,
+                Node::Var { path, filters } => {
+                    let value = self.lookup(path);
+                    let has_default = filters.iter().any(|f| f.name == "default");
+                    if value.is_none() && !has_default {
+                        self.warn(format!("Undefined variable '{}'", path));
+                    }
+
+                    let mut text = value.as_ref().map(display_value).unwrap_or_default();
+                    for filter in filters {
+                        text = match apply_filter(filter, value.as_ref(), text) {
+                            Ok(text) => text,
+                            Err(message) => {
+                                self.warn(message);
+                                String::new()
+                            }
+                        };
+                    }
+
+                    let text = match self.escape {
+                        EscapeMode::Html => escape_html(&text),
+                        EscapeMode::Plain => text,
+                    };
+                    self.write(&text)?;
+                }
+                Node::If { path, negate, then, otherwise } => {
+                    let value = self.lookup(path);
+                    if value.is_none() {
+                        self.warn(format!("Undefined variable '{}'", path));
+                    }
+                    if value.as_ref().is_some_and(is_truthy) != *negate {
+                        self.render(then)?;
+                    } else {
+                        self.render(otherwise)?;
+                    }
+                }
+                Node::Each { path, alias, body } => {
+                    let items = match self.lookup(path) {
+                        Some(Value::Array(items)) => items,
+                        Some(_) => {
+                            self.warn(format!("'{}' is not a list", path));
+                            continue;
+                        }
+                        None => {
+                            self.warn(format!("Undefined variable '{}'", path));
+                            continue;
+                        }
+                    };
+
+                    for (index, item) in items.into_iter().enumerate() {
+                        self.iterations += 1;
+                        if self.iterations > MAX_TOTAL_ITERATIONS {
+                            return Err(TemplateError::TooManyIterations);
+                        }
+                        self.scopes.push((alias.clone(), item));
+                        self.scopes.push(("@index".to_string(), Value::from(index)));
+                        let result = self.render(body);
+                        self.scopes.truncate(self.scopes.len() - 2);
+                        result?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn as_number(value: Option<&Value>, text: &str) -> Option<f64> {
+    match value {
+        Some(Value::Number(n)) => n.as_f64(),
+        _ => text.trim().parse().ok(),
+    }
+}
+
+fn group_thousands(value: f64, decimals: usize) -> String {
+    let formatted = format!("{:.*}", decimals, value.abs());
+    let (int_part, frac_part) = match formatted.split_once('.') {
+        Some((i, f)) => (i.to_string(), Some(f.to_string())),
+        None => (formatted, None),
+    };
+
+    let mut grouped = String::new();
+    for (i, c) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    if let Some(frac) = frac_part {
+        grouped.push('.');
+        grouped.push_str(&frac);
+    }
+    if value < 0.0 {
+        grouped.insert(0, '-');
+    }
+    grouped
+}
+
+fn format_date(value: Option<&Value>, text: &str, format: &str) -> Result<String, String> {
+    use chrono::format::{Item, StrftimeItems};
+
+    let datetime: DateTime<Utc> = match value {
+        // Accept both seconds and milliseconds since the epoch
+        Some(Value::Number(n)) => {
+            let ts = n.as_i64().ok_or("date: timestamp is not an integer")?;
+            let millis = if ts.abs() < 100_000_000_000 { ts * 1000 } else { ts };
+            Utc.timestamp_millis_opt(millis).single().ok_or("date: timestamp out of range")?
+        }
+        _ => DateTime::parse_from_rfc3339(text.trim())
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|_| format!("date: cannot parse '{}'", text))?,
+    };
+
+    // Invalid specifiers would make chrono panic when formatting, so reject them up front
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(format!("date: invalid format '{}'", format));
+    }
+    Ok(datetime.format_with_items(items.into_iter()).to_string())
+}
+
+fn apply_filter(filter: &Filter, value: Option<&Value>, text: String) -> Result<String, String> {
+    match filter.name.as_str() {
+        "upper" => Ok(text.to_uppercase()),
+        "lower" => Ok(text.to_lowercase()),
+        "default" => Ok(if text.is_empty() { filter.arg.clone().unwrap_or_default() } else { text }),
+        "number" => {
+            let decimals = filter.arg.as_deref().and_then(|a| a.parse().ok()).unwrap_or(0usize).min(10);
+            let n = as_number(value, &text).ok_or_else(|| format!("number: '{}' is not a number", text))?;
+            Ok(group_thousands(n, decimals))
+        }
+        "currency" => {
+            let n = as_number(value, &text).ok_or_else(|| format!("currency: '{}' is not a number", text))?;
+            let code = filter.arg.as_deref().unwrap_or("USD").to_uppercase();
+            let amount = |decimals| group_thousands(n.abs(), decimals);
+            let sign = if n < 0.0 { "-" } else { "" };
+            Ok(match code.as_str() {
+                "USD" => format!("{}${}", sign, amount(2)),
+                "EUR" => format!("{}€{}", sign, amount(2)),
+                "GBP" => format!("{}£{}", sign, amount(2)),
+                "JPY" => format!("{}¥{}", sign, amount(0)),
+                other => format!("{}{} {}", sign, other, amount(2)),
+            })
+        }
+        "date" => format_date(value, &text, filter.arg.as_deref().unwrap_or("%Y-%m-%d")),
+        other => Err(format!("Unknown formatter '{}'", other)),
+    }
+}
+
+/// Renders `template` against `data`, which should be a JSON object.
+pub fn render(template: &str, data: &Value, escape: EscapeMode) -> Result<RenderOutput, TemplateError> {
+    let nodes = parse(template)?;
+    let mut renderer = Renderer {
+        root: data,
+        scopes: Vec::new(),
+        escape,
+        iterations: 0,
+        output: String::new(),
+        warnings: Vec::new(),
+    };
+    renderer.render(&nodes)?;
+
+    Ok(RenderOutput {
+        output: renderer.output,
+        warnings: renderer.warnings,
+    })
+}
+
+/// Convenience wrapper for callers holding flat variable maps.
+pub fn render_with_map(
+    template: &str,
+    variables: &HashMap<String, Value>,
+    escape: EscapeMode,
+) -> Result<RenderOutput, TemplateError> {
+    let data = Value::Object(variables.iter().map(|(k, v)| (k.clone(), v.clone())).collect());
+    render(template, &data, escape)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_conditional_block() {
+        let template = "Hi {{user.name}}{{#if user.vip}}, thanks for being VIP{{else}}, welcome{{/if}}!";
+
+        let out = render(template, &json!({"user": {"name": "Ana", "vip": true}}), EscapeMode::Plain).unwrap();
+        assert_eq!(out.output, "Hi Ana, thanks for being VIP!");
+
+        let out = render(template, &json!({"user": {"name": "Ana", "vip": false}}), EscapeMode::Plain).unwrap();
+        assert_eq!(out.output, "Hi Ana, welcome!");
+        assert!(out.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_loop_over_list() {
+        let template = "{{#each items as item}}{{@index}}:{{item.name}};{{/each}}";
+        let out = render(
+            template,
+            &json!({"items": [{"name": "a"}, {"name": "b"}]}),
+            EscapeMode::Plain,
+        )
+        .unwrap();
+        assert_eq!(out.output, "0:a;1:b;");
+    }
+
+    #[test]
+    fn test_formatters() {
+        let data = json!({"total": 1234.5, "due": 1_700_000_000, "name": "ana"});
+        let out = render(
+            "{{name | upper}} owes {{total | currency(\"USD\")}} by {{due | date(\"%Y-%m-%d\")}}",
+            &data,
+            EscapeMode::Plain,
+        )
+        .unwrap();
+        assert_eq!(out.output, "ANA owes $1,234.50 by 2023-11-14");
+
+        let out = render("{{due | date(\"%Q\")}}", &data, EscapeMode::Plain).unwrap();
+        assert_eq!(out.output, "");
+        assert_eq!(out.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_escaping_and_undefined_warnings() {
+        let data = json!({"name": "<b>Bob</b>"});
+        let html = render("Hello {{name}} {{missing}}", &data, EscapeMode::Html).unwrap();
+        assert_eq!(html.output, "Hello &lt;b&gt;Bob&lt;/b&gt; ");
+        assert_eq!(html.warnings, vec!["Undefined variable 'missing'".to_string()]);
+
+        let plain = render("Hello {{name}}", &data, EscapeMode::Plain).unwrap();
+        assert_eq!(plain.output, "Hello <b>Bob</b>");
+    }
+
+    #[test]
+    fn test_rejects_deep_and_runaway_templates() {
+        let deep = "{{#if a}}".repeat(MAX_NESTING_DEPTH + 1) + &"{{/if}}".repeat(MAX_NESTING_DEPTH + 1);
+        assert_eq!(render(&deep, &json!({}), EscapeMode::Plain).unwrap_err(), TemplateError::TooDeep);
+
+        let data = json!({"rows": vec![0; 100], "cols": vec![0; 100]});
+        let nested = "{{#each rows}}{{#each cols}}x{{/each}}{{/each}}";
+        assert_eq!(
+            render(nested, &data, EscapeMode::Plain).unwrap_err(),
+            TemplateError::TooManyIterations
+        );
+
+        assert!(matches!(
+            render("{{#if a}}unterminated", &json!({}), EscapeMode::Plain),
+            Err(TemplateError::Syntax(_))
+        ));
+    }
+}
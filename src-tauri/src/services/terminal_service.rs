@@ -1,8 +1,14 @@
-use crate::models::terminal::{TerminalSession, CommandHistory, TerminalConfig, TerminalStats, CommandFrequency};
-use log::info;
-use rusqlite::{params, Connection, Result as SqliteResult};
+use crate::models::terminal::{TerminalSession, CommandHistory, TerminalConfig, TerminalStats, CommandFrequency, FuzzyHistoryMatch};
+use log::{info, warn};
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
 use std::sync::{Arc, Mutex};
 
+/// Patterns applied when the user has not configured their own exclusions
+const DEFAULT_HISTORY_EXCLUDE_PATTERNS: &[&str] = &[
+    r"(?i)^\s*export\s+\w*(KEY|TOKEN|SECRET|PASSWORD|PASSWD)\w*=",
+];
+
 pub struct TerminalService {
     conn: Arc<Mutex<Connection>>,
 }
@@ -64,11 +70,18 @@ impl TerminalService {
                 cursor_style TEXT NOT NULL,
                 cursor_blink INTEGER NOT NULL,
                 scrollback_lines INTEGER NOT NULL,
-                bell_enabled INTEGER NOT NULL
+                bell_enabled INTEGER NOT NULL,
+                history_exclude_patterns TEXT
             )",
             [],
         ).map_err(|e| format!("Failed to create terminal_config table: {}", e))?;
         
+        // Migration: databases created before history exclusions existed lack the column
+        let _ = conn.execute(
+            "ALTER TABLE terminal_config ADD COLUMN history_exclude_patterns TEXT",
+            [],
+        );
+        
         // Create indexes
         conn.execute(
             "CREATE INDEX IF NOT EXISTS idx_sessions_active ON terminal_sessions(is_active, last_used_at DESC)",
@@ -86,12 +99,19 @@ impl TerminalService {
         ).map_err(|e| format!("Failed to create index: {}", e))?;
         
         // Insert default config if not exists
+        let default_patterns = serde_json::to_string(DEFAULT_HISTORY_EXCLUDE_PATTERNS)
+            .map_err(|e| format!("Failed to serialize exclude patterns: {}", e))?;
         conn.execute(
-            "INSERT OR IGNORE INTO terminal_config (id, font_family, font_size, theme, cursor_style, cursor_blink, scrollback_lines, bell_enabled)
-             VALUES ('default', 'JetBrains Mono', 14, 'dark', 'block', 1, 10000, 0)",
-            [],
+            "INSERT OR IGNORE INTO terminal_config (id, font_family, font_size, theme, cursor_style, cursor_blink, scrollback_lines, bell_enabled, history_exclude_patterns)
+             VALUES ('default', 'JetBrains Mono', 14, 'dark', 'block', 1, 10000, 0, ?1)",
+            params![default_patterns],
         ).map_err(|e| format!("Failed to insert default config: {}", e))?;
         
+        conn.execute(
+            "UPDATE terminal_config SET history_exclude_patterns = ?1 WHERE history_exclude_patterns IS NULL",
+            params![default_patterns],
+        ).map_err(|e| format!("Failed to backfill exclude patterns: {}", e))?;
+        
         Ok(())
     }
     
@@ -218,6 +238,26 @@ impl TerminalService {
         let conn = self.conn.lock()
             .map_err(|e| format!("Failed to acquire lock: {}", e))?;
         
+        let patterns = Self::load_exclude_patterns(&conn)?;
+        if is_excluded_command(&history.command, &patterns) {
+            info!("💻 Skipped storing command matching a history exclusion pattern");
+            return Ok(());
+        }
+        
+        // Collapse identical consecutive commands within a session
+        let previous: Option<String> = conn.query_row(
+            "SELECT command FROM command_history
+             WHERE session_id = ?1
+             ORDER BY executed_at DESC, rowid DESC
+             LIMIT 1",
+            params![history.session_id],
+            |row| row.get(0),
+        ).optional().map_err(|e| format!("Failed to query previous command: {}", e))?;
+        
+        if previous.as_deref() == Some(history.command.as_str()) {
+            return Ok(());
+        }
+        
         conn.execute(
             "INSERT INTO command_history (id, session_id, command, output, exit_code, executed_at, duration_ms, working_directory)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
@@ -298,6 +338,52 @@ impl TerminalService {
             .map_err(|e| format!("Failed to collect history: {}", e))
     }
     
+    /// Ctrl-R style search across the history of every session, one entry per
+    /// distinct command, ranked by match quality, frequency and recency.
+    pub fn fuzzy_search_history(&self, query: &str, limit: i32) -> Result<Vec<FuzzyHistoryMatch>, String> {
+        let conn = self.conn.lock()
+            .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+        
+        let mut stmt = conn.prepare(
+            "SELECT command, COUNT(*) as count, MAX(executed_at) as last_executed_at
+             FROM command_history
+             GROUP BY command"
+        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        
+        let candidates = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?, row.get::<_, i64>(2)?))
+        }).map_err(|e| format!("Failed to query history: {}", e))?
+            .collect::<SqliteResult<Vec<_>>>()
+            .map_err(|e| format!("Failed to collect history: {}", e))?;
+        
+        let now = chrono::Utc::now().timestamp();
+        Ok(rank_history_matches(query, candidates, now, limit.max(0) as usize))
+    }
+    
+    fn load_exclude_patterns(conn: &Connection) -> Result<Vec<Regex>, String> {
+        let raw: Option<String> = conn.query_row(
+            "SELECT history_exclude_patterns FROM terminal_config WHERE id = 'default'",
+            [],
+            |row| row.get(0),
+        ).optional().map_err(|e| format!("Failed to load exclude patterns: {}", e))?.flatten();
+        
+        let patterns: Vec<String> = match raw {
+            Some(json) => serde_json::from_str(&json).unwrap_or_default(),
+            None => Vec::new(),
+        };
+        
+        Ok(patterns
+            .iter()
+            .filter_map(|p| match Regex::new(p) {
+                Ok(re) => Some(re),
+                Err(e) => {
+                    warn!("Ignoring invalid history exclude pattern '{}': {}", p, e);
+                    None
+                }
+            })
+            .collect())
+    }
+    
     pub fn clear_session_history(&self, session_id: &str) -> Result<(), String> {
         let conn = self.conn.lock()
             .map_err(|e| format!("Failed to acquire lock: {}", e))?;
@@ -315,7 +401,7 @@ impl TerminalService {
             .map_err(|e| format!("Failed to acquire lock: {}", e))?;
         
         let config = conn.query_row(
-            "SELECT id, font_family, font_size, theme, cursor_style, cursor_blink, scrollback_lines, bell_enabled
+            "SELECT id, font_family, font_size, theme, cursor_style, cursor_blink, scrollback_lines, bell_enabled, history_exclude_patterns
              FROM terminal_config
              WHERE id = 'default'",
             [],
@@ -329,6 +415,9 @@ impl TerminalService {
                     cursor_blink: row.get::<_, i32>(5)? != 0,
                     scrollback_lines: row.get(6)?,
                     bell_enabled: row.get::<_, i32>(7)? != 0,
+                    history_exclude_patterns: row.get::<_, Option<String>>(8)?
+                        .and_then(|json| serde_json::from_str(&json).ok())
+                        .unwrap_or_default(),
                 })
             },
         ).map_err(|e| format!("Failed to get config: {}", e))?;
@@ -337,13 +426,21 @@ impl TerminalService {
     }
     
     pub fn update_config(&self, config: &TerminalConfig) -> Result<(), String> {
+        for pattern in &config.history_exclude_patterns {
+            Regex::new(pattern)
+                .map_err(|e| format!("Invalid history exclude pattern '{}': {}", pattern, e))?;
+        }
+        let exclude_patterns = serde_json::to_string(&config.history_exclude_patterns)
+            .map_err(|e| format!("Failed to serialize exclude patterns: {}", e))?;
+        
         let conn = self.conn.lock()
             .map_err(|e| format!("Failed to acquire lock: {}", e))?;
         
         conn.execute(
             "UPDATE terminal_config SET
                 font_family = ?2, font_size = ?3, theme = ?4,
-                cursor_style = ?5, cursor_blink = ?6, scrollback_lines = ?7, bell_enabled = ?8
+                cursor_style = ?5, cursor_blink = ?6, scrollback_lines = ?7, bell_enabled = ?8,
+                history_exclude_patterns = ?9
              WHERE id = ?1",
             params![
                 config.id,
@@ -354,6 +451,7 @@ impl TerminalService {
                 if config.cursor_blink { 1 } else { 0 },
                 config.scrollback_lines,
                 if config.bell_enabled { 1 } else { 0 },
+                exclude_patterns,
             ],
         ).map_err(|e| format!("Failed to update config: {}", e))?;
         
@@ -410,3 +508,199 @@ impl TerminalService {
         })
     }
 }
+
+fn is_excluded_command(command: &str, patterns: &[Regex]) -> bool {
+    patterns.iter().any(|re| re.is_match(command))
+}
+
+/// Scores `candidate` as a case-insensitive subsequence of `query`.
+/// Returns `None` when not every query character can be matched in order.
+fn fuzzy_match(query: &str, candidate: &str) -> Option<(f64, Vec<usize>)> {
+    let candidate_chars: Vec<char> = candidate.chars().collect();
+    let mut indices = Vec::new();
+    let mut score = 0.0;
+    let mut pos = 0;
+    
+    for qc in query.chars().filter(|c| !c.is_whitespace()) {
+        let qc = qc.to_ascii_lowercase();
+        let found = (pos..candidate_chars.len())
+            .find(|&i| candidate_chars[i].to_ascii_lowercase() == qc)?;
+        
+        score += 1.0;
+        if found == 0 {
+            score += 3.0;
+        } else if matches!(candidate_chars[found - 1], ' ' | '-' | '_' | '/' | '.') {
+            score += 2.0;
+        }
+        if indices.last().is_some_and(|&last| last + 1 == found) {
+            score += 2.0;
+        }
+        // Penalise gaps so tighter matches rank higher
+        score -= (found - pos) as f64 * 0.1;
+        
+        indices.push(found);
+        pos = found + 1;
+    }
+    
+    Some((score, indices))
+}
+
+fn rank_history_matches(
+    query: &str,
+    candidates: Vec<(String, i32, i64)>,
+    now: i64,
+    limit: usize,
+) -> Vec<FuzzyHistoryMatch> {
+    let mut matches: Vec<FuzzyHistoryMatch> = candidates
+        .into_iter()
+        .filter_map(|(command, frequency, last_executed_at)| {
+            let (match_score, matched_indices) = fuzzy_match(query, &command)?;
+            let age_hours = (now - last_executed_at).max(0) as f64 / 3600.0;
+            let recency = 5.0 / (1.0 + age_hours / 24.0);
+            let popularity = (1.0 + frequency.max(0) as f64).ln() * 2.0;
+            Some(FuzzyHistoryMatch {
+                command,
+                score: match_score + recency + popularity,
+                frequency,
+                last_executed_at,
+                matched_indices,
+            })
+        })
+        .collect();
+    
+    matches.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.last_executed_at.cmp(&a.last_executed_at))
+    });
+    matches.truncate(limit);
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, session_id: &str, command: &str, executed_at: i64) -> CommandHistory {
+        CommandHistory {
+            id: id.to_string(),
+            session_id: session_id.to_string(),
+            command: command.to_string(),
+            output: None,
+            exit_code: 0,
+            executed_at,
+            duration_ms: 10,
+            working_directory: "/tmp".to_string(),
+        }
+    }
+
+    fn service_with_sessions(ids: &[&str]) -> TerminalService {
+        let service = TerminalService::new(":memory:").unwrap();
+        for id in ids {
+            service.create_session(&TerminalSession {
+                id: id.to_string(),
+                name: id.to_string(),
+                working_directory: "/tmp".to_string(),
+                shell: "bash".to_string(),
+                created_at: 0,
+                last_used_at: 0,
+                is_active: true,
+                environment_vars: None,
+            }).unwrap();
+        }
+        service
+    }
+
+    #[test]
+    fn test_fuzzy_match_requires_subsequence() {
+        assert!(fuzzy_match("gco", "git checkout").is_some());
+        assert!(fuzzy_match("GCO", "git checkout").is_some());
+        assert!(fuzzy_match("ocg", "git checkout").is_none());
+
+        let (_, indices) = fuzzy_match("gc", "git checkout").unwrap();
+        assert_eq!(indices, vec![0, 4]);
+    }
+
+    #[test]
+    fn test_fuzzy_ranking_prefers_tighter_matches() {
+        let now = 1_000_000;
+        let ranked = rank_history_matches(
+            "gst",
+            vec![
+                ("grep -r something tests".to_string(), 1, now),
+                ("git status".to_string(), 1, now),
+                ("ls -la".to_string(), 50, now),
+            ],
+            now,
+            10,
+        );
+
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].command, "git status");
+    }
+
+    #[test]
+    fn test_fuzzy_ranking_uses_frequency_and_recency() {
+        let now = 10_000_000;
+        let ranked = rank_history_matches(
+            "make",
+            vec![
+                ("make build".to_string(), 1, now - 86_400 * 30),
+                ("make test".to_string(), 40, now - 60),
+            ],
+            now,
+            10,
+        );
+
+        assert_eq!(ranked[0].command, "make test");
+    }
+
+    #[test]
+    fn test_consecutive_duplicates_are_collapsed() {
+        let service = service_with_sessions(&["s1", "s2"]);
+        service.add_command_history(&entry("1", "s1", "ls", 1)).unwrap();
+        service.add_command_history(&entry("2", "s1", "ls", 2)).unwrap();
+        service.add_command_history(&entry("3", "s1", "pwd", 3)).unwrap();
+        service.add_command_history(&entry("4", "s1", "ls", 4)).unwrap();
+        // Another session's last command doesn't affect this one
+        service.add_command_history(&entry("5", "s2", "ls", 5)).unwrap();
+
+        assert_eq!(service.get_session_history("s1", 10).unwrap().len(), 3);
+        assert_eq!(service.get_session_history("s2", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_secret_commands_are_not_stored() {
+        let service = service_with_sessions(&["s1", "s2"]);
+        service.add_command_history(&entry("1", "s1", "export AWS_SECRET_ACCESS_KEY=abc", 1)).unwrap();
+        service.add_command_history(&entry("2", "s1", "export PATH=/usr/bin", 2)).unwrap();
+
+        let history = service.get_session_history("s1", 10).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].command, "export PATH=/usr/bin");
+
+        let mut config = service.get_config().unwrap();
+        config.history_exclude_patterns = vec!["^mysql .*-p".to_string()];
+        service.update_config(&config).unwrap();
+        service.add_command_history(&entry("3", "s1", "mysql -u root -phunter2", 3)).unwrap();
+        assert_eq!(service.get_session_history("s1", 10).unwrap().len(), 1);
+
+        config.history_exclude_patterns = vec!["(unclosed".to_string()];
+        assert!(service.update_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_fuzzy_search_spans_sessions() {
+        let service = service_with_sessions(&["s1", "s2"]);
+        service.add_command_history(&entry("1", "s1", "cargo test", 1)).unwrap();
+        service.add_command_history(&entry("2", "s2", "cargo test", 2)).unwrap();
+        service.add_command_history(&entry("3", "s2", "cargo build", 3)).unwrap();
+
+        let results = service.fuzzy_search_history("ctst", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].command, "cargo test");
+        assert_eq!(results[0].frequency, 2);
+        assert_eq!(results[0].last_executed_at, 2);
+    }
+}
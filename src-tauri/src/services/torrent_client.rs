@@ -0,0 +1,1839 @@
+// CUBE Nexum - BitTorrent Client
+// Embedded BitTorrent engine behind the downloads manager: .torrent files and
+// magnet links (metadata fetched from peers, BEP 9/10), HTTP trackers, SHA-1
+// piece verification, per-file selection, seeding policy and bandwidth limits.
+// Peers are found through trackers only; there is no DHT.
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+
+const BLOCK_SIZE: u32 = 16 * 1024;
+const MAX_BLOCK_REQUEST: u32 = 32 * 1024;
+const PIPELINE_DEPTH: u32 = 8;
+const MAX_MESSAGE_LEN: usize = 2 * 1024 * 1024;
+const MAX_METADATA_SIZE: usize = 16 * 1024 * 1024;
+const METADATA_PIECE_SIZE: usize = 16 * 1024;
+const MAX_BENCODE_DEPTH: usize = 64;
+/// Our id for ut_metadata in the extension handshake
+const UT_METADATA_ID: u8 = 1;
+const TICK: Duration = Duration::from_millis(250);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const PEER_IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(60);
+const METADATA_RETRY: Duration = Duration::from_secs(5);
+const MIN_ANNOUNCE_INTERVAL: Duration = Duration::from_secs(30);
+const MAX_ANNOUNCE_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+// ==================== Bencode ====================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bencode {
+    Int(i64),
+    Bytes(Vec<u8>),
+    List(Vec<Bencode>),
+    Dict(BTreeMap<Vec<u8>, Bencode>),
+}
+
+impl Bencode {
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        let (value, rest) = Self::decode_prefix(data)?;
+        if !rest.is_empty() {
+            return Err("Trailing data after bencode value".to_string());
+        }
+        Ok(value)
+    }
+
+    /// Decodes one value and returns the bytes after it (ut_metadata pieces
+    /// follow their bencoded header)
+    pub fn decode_prefix(data: &[u8]) -> Result<(Self, &[u8]), String> {
+        let mut pos = 0;
+        let value = decode_at(data, &mut pos, 0)?;
+        Ok((value, &data[pos..]))
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_into(&mut out);
+        out
+    }
+
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Int(n) => out.extend_from_slice(format!("i{}e", n).as_bytes()),
+            Self::Bytes(bytes) => encode_bytes(bytes, out),
+            Self::List(items) => {
+                out.push(b'l');
+                for item in items {
+                    item.encode_into(out);
+                }
+                out.push(b'e');
+            }
+            Self::Dict(map) => {
+                out.push(b'd');
+                for (key, value) in map {
+                    encode_bytes(key, out);
+                    value.encode_into(out);
+                }
+                out.push(b'e');
+            }
+        }
+    }
+
+    pub fn dict(pairs: Vec<(&str, Bencode)>) -> Self {
+        Self::Dict(pairs.into_iter().map(|(k, v)| (k.as_bytes().to_vec(), v)).collect())
+    }
+
+    pub fn string(value: &str) -> Self {
+        Self::Bytes(value.as_bytes().to_vec())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Bencode> {
+        match self {
+            Self::Dict(map) => map.get(key.as_bytes()),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Self::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        self.as_bytes().and_then(|b| std::str::from_utf8(b).ok())
+    }
+
+    pub fn as_list(&self) -> Option<&[Bencode]> {
+        match self {
+            Self::List(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+fn encode_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(format!("{}:", bytes.len()).as_bytes());
+    out.extend_from_slice(bytes);
+}
+
+fn find_byte(data: &[u8], from: usize, byte: u8) -> Result<usize, String> {
+    data.get(from..)
+        .and_then(|rest| rest.iter().position(|b| *b == byte))
+        .map(|i| from + i)
+        .ok_or_else(|| "Unexpected end of bencode data".to_string())
+}
+
+fn decode_at(data: &[u8], pos: &mut usize, depth: usize) -> Result<Bencode, String> {
+    if depth > MAX_BENCODE_DEPTH {
+        return Err("Bencode nested too deeply".to_string());
+    }
+    match data.get(*pos) {
+        Some(b'i') => {
+            let end = find_byte(data, *pos + 1, b'e')?;
+            let digits = std::str::from_utf8(&data[*pos + 1..end]).map_err(|_| "Invalid bencode integer")?;
+            let value = digits
+                .parse::<i64>()
+                .map_err(|_| format!("Invalid bencode integer: {}", digits))?;
+            *pos = end + 1;
+            Ok(Bencode::Int(value))
+        }
+        Some(b'l') => {
+            *pos += 1;
+            let mut items = Vec::new();
+            while data.get(*pos) != Some(&b'e') {
+                items.push(decode_at(data, pos, depth + 1)?);
+            }
+            *pos += 1;
+            Ok(Bencode::List(items))
+        }
+        Some(b'd') => {
+            *pos += 1;
+            let mut map = BTreeMap::new();
+            while data.get(*pos) != Some(&b'e') {
+                let Bencode::Bytes(key) = decode_at(data, pos, depth + 1)? else {
+                    return Err("Bencode dictionary keys must be strings".to_string());
+                };
+                let value = decode_at(data, pos, depth + 1)?;
+                map.insert(key, value);
+            }
+            *pos += 1;
+            Ok(Bencode::Dict(map))
+        }
+        Some(b'0'..=b'9') => {
+            let colon = find_byte(data, *pos, b':')?;
+            let len: usize = std::str::from_utf8(&data[*pos..colon])
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or("Invalid bencode string length")?;
+            let start = colon + 1;
+            let end = start
+                .checked_add(len)
+                .filter(|end| *end <= data.len())
+                .ok_or("Bencode string runs past the end of the data")?;
+            *pos = end;
+            Ok(Bencode::Bytes(data[start..end].to_vec()))
+        }
+        Some(byte) => Err(format!("Unexpected bencode byte: {:?}", *byte as char)),
+        None => Err("Unexpected end of bencode data".to_string()),
+    }
+}
+
+/// The raw bytes of `key`'s value in a top-level bencoded dictionary. The
+/// info hash must be taken over the info dictionary exactly as published.
+fn raw_dict_value<'a>(data: &'a [u8], key: &[u8]) -> Option<&'a [u8]> {
+    if data.first() != Some(&b'd') {
+        return None;
+    }
+    let mut pos = 1;
+    while data.get(pos) != Some(&b'e') {
+        let Bencode::Bytes(current) = decode_at(data, &mut pos, 1).ok()? else {
+            return None;
+        };
+        let start = pos;
+        decode_at(data, &mut pos, 1).ok()?;
+        if current == key {
+            return Some(&data[start..pos]);
+        }
+    }
+    None
+}
+
+// ==================== Metainfo ====================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TorrentFile {
+    pub index: usize,
+    /// Sanitized path relative to the download directory, '/'-separated
+    pub path: String,
+    pub length: u64,
+    /// Offset of the file within the torrent's concatenated data
+    pub offset: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Metainfo {
+    pub info_hash: [u8; 20],
+    pub name: String,
+    pub piece_length: u64,
+    pub pieces: Vec<[u8; 20]>,
+    pub files: Vec<TorrentFile>,
+    pub trackers: Vec<String>,
+    /// The bencoded info dictionary, served to peers fetching metadata
+    pub info_bytes: Vec<u8>,
+}
+
+impl Metainfo {
+    pub fn from_torrent_bytes(data: &[u8]) -> Result<Self, String> {
+        let root = Bencode::decode(data)?;
+        let info_bytes = raw_dict_value(data, b"info").ok_or("Torrent has no info dictionary")?;
+
+        let mut trackers: Vec<String> = Vec::new();
+        let announce_list = root.get("announce-list").and_then(Bencode::as_list).unwrap_or(&[]);
+        let urls = root
+            .get("announce")
+            .into_iter()
+            .chain(announce_list.iter().flat_map(|tier| tier.as_list().unwrap_or(&[])));
+        for url in urls.filter_map(Bencode::as_str) {
+            if !trackers.iter().any(|t| t == url) {
+                trackers.push(url.to_string());
+            }
+        }
+
+        Self::from_info_bytes(info_bytes, trackers)
+    }
+
+    pub fn from_info_bytes(info_bytes: &[u8], trackers: Vec<String>) -> Result<Self, String> {
+        let info = Bencode::decode(info_bytes)?;
+        let name = info
+            .get("name")
+            .and_then(Bencode::as_str)
+            .ok_or("Torrent has no name")?;
+        let name = sanitize_component(name)?;
+        let piece_length = info
+            .get("piece length")
+            .and_then(Bencode::as_int)
+            .filter(|n| *n > 0)
+            .ok_or("Torrent has an invalid piece length")? as u64;
+        let hashes = info
+            .get("pieces")
+            .and_then(Bencode::as_bytes)
+            .filter(|p| !p.is_empty() && p.len() % 20 == 0)
+            .ok_or("Torrent has invalid piece hashes")?;
+        let pieces: Vec<[u8; 20]> = hashes
+            .chunks_exact(20)
+            .map(|chunk| {
+                let mut hash = [0u8; 20];
+                hash.copy_from_slice(chunk);
+                hash
+            })
+            .collect();
+
+        let mut files = Vec::new();
+        let mut offset = 0u64;
+        match info.get("files").and_then(Bencode::as_list) {
+            Some(list) => {
+                for (index, entry) in list.iter().enumerate() {
+                    let length = file_length(entry)?;
+                    let parts = entry
+                        .get("path")
+                        .and_then(Bencode::as_list)
+                        .filter(|p| !p.is_empty())
+                        .ok_or("Torrent file entry has no path")?;
+                    let mut path = vec![name.clone()];
+                    for part in parts {
+                        path.push(sanitize_component(part.as_str().ok_or("Torrent path is not UTF-8")?)?);
+                    }
+                    files.push(TorrentFile {
+                        index,
+                        path: path.join("/"),
+                        length,
+                        offset,
+                    });
+                    offset += length;
+                }
+            }
+            None => {
+                let length = file_length(&info)?;
+                files.push(TorrentFile {
+                    index: 0,
+                    path: name.clone(),
+                    length,
+                    offset: 0,
+                });
+                offset = length;
+            }
+        }
+
+        if pieces.len() as u64 != offset.div_ceil(piece_length) {
+            return Err("Torrent piece count doesn't match its size".to_string());
+        }
+
+        Ok(Self {
+            info_hash: Sha1::digest(info_bytes).into(),
+            name,
+            piece_length,
+            pieces,
+            files,
+            trackers,
+            info_bytes: info_bytes.to_vec(),
+        })
+    }
+
+    pub fn total_length(&self) -> u64 {
+        self.files.iter().map(|f| f.length).sum()
+    }
+
+    pub fn piece_size(&self, index: usize) -> u64 {
+        let start = index as u64 * self.piece_length;
+        self.piece_length.min(self.total_length().saturating_sub(start))
+    }
+
+    /// (file index, offset within the file, length) for each file a byte
+    /// range of the torrent overlaps
+    fn segments(&self, start: u64, length: u64) -> Vec<(usize, u64, u64)> {
+        let end = start + length;
+        self.files
+            .iter()
+            .filter(|f| f.length > 0 && f.offset < end && f.offset + f.length > start)
+            .map(|f| {
+                let from = start.max(f.offset);
+                let to = end.min(f.offset + f.length);
+                (f.index, from - f.offset, to - from)
+            })
+            .collect()
+    }
+
+    fn piece_segments(&self, index: usize) -> Vec<(usize, u64, u64)> {
+        self.segments(index as u64 * self.piece_length, self.piece_size(index))
+    }
+}
+
+fn file_length(entry: &Bencode) -> Result<u64, String> {
+    entry
+        .get("length")
+        .and_then(Bencode::as_int)
+        .filter(|n| *n >= 0)
+        .map(|n| n as u64)
+        .ok_or_else(|| "Torrent file has an invalid length".to_string())
+}
+
+/// Makes a torrent-supplied path component safe to use as one file name:
+/// separators and reserved characters are replaced and traversal is refused
+fn sanitize_component(raw: &str) -> Result<String, String> {
+    let replaced: String = raw
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // Windows drops trailing dots and spaces, which would turn ".. " into ".."
+    let cleaned = replaced.trim().trim_end_matches(['.', ' ']);
+    if cleaned.is_empty() {
+        return Err(format!("Unsafe path in torrent: {:?}", raw));
+    }
+
+    let stem = cleaned.split('.').next().unwrap_or("").to_uppercase();
+    let reserved = matches!(stem.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+        || ((stem.starts_with("COM") || stem.starts_with("LPT"))
+            && stem.len() == 4
+            && stem.as_bytes()[3].is_ascii_digit());
+    Ok(if reserved { format!("_{}", cleaned) } else { cleaned.to_string() })
+}
+
+/// Resolves a torrent file path under `root`, refusing anything that would
+/// land outside it (including through symlinked directories)
+fn sandboxed_path(root: &Path, relative: &str, create_dirs: bool) -> Result<PathBuf, String> {
+    let mut path = root.to_path_buf();
+    for part in relative.split('/') {
+        let part = Path::new(part);
+        if !matches!(part.components().collect::<Vec<_>>().as_slice(), [Component::Normal(_)]) {
+            return Err(format!("Unsafe path in torrent: {}", relative));
+        }
+        path.push(part);
+    }
+
+    let parent = path.parent().ok_or("Torrent file has no parent directory")?;
+    if create_dirs {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    if let (Ok(root), Ok(parent)) = (root.canonicalize(), parent.canonicalize()) {
+        if !parent.starts_with(&root) {
+            return Err(format!("Torrent path escapes the download folder: {}", relative));
+        }
+    }
+    Ok(path)
+}
+
+// ==================== Magnet Links ====================
+
+#[derive(Debug, Clone)]
+pub struct MagnetLink {
+    pub info_hash: [u8; 20],
+    pub display_name: Option<String>,
+    pub trackers: Vec<String>,
+}
+
+pub fn parse_magnet(uri: &str) -> Result<MagnetLink, String> {
+    let url = url::Url::parse(uri).map_err(|e| format!("Invalid magnet link: {}", e))?;
+    if url.scheme() != "magnet" {
+        return Err("Not a magnet link".to_string());
+    }
+
+    let mut info_hash = None;
+    let mut display_name = None;
+    let mut trackers = Vec::new();
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "xt" => {
+                if let Some(hash) = value.strip_prefix("urn:btih:") {
+                    info_hash = Some(decode_info_hash(hash)?);
+                }
+            }
+            "dn" => display_name = Some(value.to_string()),
+            "tr" => trackers.push(value.to_string()),
+            _ => {}
+        }
+    }
+
+    Ok(MagnetLink {
+        info_hash: info_hash.ok_or("Magnet link has no BitTorrent info hash")?,
+        display_name,
+        trackers,
+    })
+}
+
+/// Info hashes appear as 40 hex characters or 32 base32 characters
+fn decode_info_hash(value: &str) -> Result<[u8; 20], String> {
+    let bytes = match value.len() {
+        40 => hex::decode(value).map_err(|_| "Invalid hex info hash".to_string())?,
+        32 => {
+            let mut bits = 0u64;
+            let mut bit_count = 0;
+            let mut bytes = Vec::with_capacity(20);
+            for c in value.to_ascii_uppercase().chars() {
+                let digit = match c {
+                    'A'..='Z' => c as u64 - 'A' as u64,
+                    '2'..='7' => c as u64 - '2' as u64 + 26,
+                    _ => return Err("Invalid base32 info hash".to_string()),
+                };
+                bits = (bits << 5) | digit;
+                bit_count += 5;
+                if bit_count >= 8 {
+                    bit_count -= 8;
+                    bytes.push((bits >> bit_count) as u8);
+                }
+            }
+            bytes
+        }
+        _ => return Err("Invalid info hash length".to_string()),
+    };
+    bytes.try_into().map_err(|_| "Invalid info hash".to_string())
+}
+
+#[derive(Debug, Clone)]
+pub enum TorrentSource {
+    Metainfo(Arc<Metainfo>),
+    Magnet(MagnetLink),
+}
+
+impl TorrentSource {
+    pub fn info_hash(&self) -> [u8; 20] {
+        match self {
+            Self::Metainfo(meta) => meta.info_hash,
+            Self::Magnet(magnet) => magnet.info_hash,
+        }
+    }
+
+    pub fn name(&self) -> Option<String> {
+        match self {
+            Self::Metainfo(meta) => Some(meta.name.clone()),
+            Self::Magnet(magnet) => magnet
+                .display_name
+                .as_deref()
+                .and_then(|n| sanitize_component(n).ok()),
+        }
+    }
+
+    pub fn files(&self) -> &[TorrentFile] {
+        match self {
+            Self::Metainfo(meta) => &meta.files,
+            Self::Magnet(_) => &[],
+        }
+    }
+}
+
+// ==================== Piece Storage ====================
+
+fn file_path(root: &Path, meta: &Metainfo, file: usize, create_dirs: bool) -> Result<PathBuf, String> {
+    sandboxed_path(root, &meta.files[file].path, create_dirs)
+}
+
+/// Writes a verified piece into the selected files it overlaps. Returns
+/// whether the whole piece is now on disk (and can be served to peers).
+fn write_piece(root: &Path, meta: &Metainfo, selected: &[bool], index: usize, data: &[u8]) -> Result<bool, String> {
+    let mut complete = true;
+    let mut consumed = 0usize;
+    for (file, offset, length) in meta.piece_segments(index) {
+        let chunk = &data[consumed..consumed + length as usize];
+        consumed += length as usize;
+        if !selected[file] {
+            complete = false;
+            continue;
+        }
+        let path = file_path(root, meta, file, true)?;
+        let mut handle = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        handle
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| handle.write_all(chunk))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    }
+    Ok(complete)
+}
+
+fn read_range(root: &Path, meta: &Metainfo, start: u64, length: u64) -> Result<Vec<u8>, String> {
+    let mut data = Vec::with_capacity(length as usize);
+    for (file, offset, len) in meta.segments(start, length) {
+        let path = file_path(root, meta, file, false)?;
+        let mut handle = std::fs::File::open(&path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let mut chunk = vec![0u8; len as usize];
+        handle
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| handle.read_exact(&mut chunk))
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        data.extend_from_slice(&chunk);
+    }
+    Ok(data)
+}
+
+fn piece_on_disk(root: &Path, meta: &Metainfo, index: usize) -> bool {
+    read_range(root, meta, index as u64 * meta.piece_length, meta.piece_size(index))
+        .is_ok_and(|data| Sha1::digest(&data).as_slice() == meta.pieces[index])
+}
+
+// ==================== Rate Limiting ====================
+
+/// Token bucket shared by all of a torrent's peer connections
+pub struct RateLimiter {
+    state: Mutex<LimiterState>,
+}
+
+struct LimiterState {
+    bytes_per_sec: u64,
+    available: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            state: Mutex::new(LimiterState {
+                bytes_per_sec,
+                available: bytes_per_sec as f64,
+                last: Instant::now(),
+            }),
+        }
+    }
+
+    /// 0 removes the limit
+    pub fn set_rate(&self, bytes_per_sec: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.bytes_per_sec != bytes_per_sec {
+            state.bytes_per_sec = bytes_per_sec;
+            state.available = state.available.min(bytes_per_sec as f64);
+        }
+    }
+
+    pub async fn acquire(&self, bytes: u32) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().unwrap();
+                if state.bytes_per_sec == 0 {
+                    return;
+                }
+                let rate = state.bytes_per_sec as f64;
+                let now = Instant::now();
+                let burst = rate.max(bytes as f64);
+                state.available = (state.available + now.duration_since(state.last).as_secs_f64() * rate).min(burst);
+                state.last = now;
+                if state.available >= bytes as f64 {
+                    state.available -= bytes as f64;
+                    return;
+                }
+                Duration::from_secs_f64((bytes as f64 - state.available) / rate)
+            };
+            tokio::time::sleep(wait.min(Duration::from_secs(1))).await;
+        }
+    }
+}
+
+// ==================== Peer Wire Protocol ====================
+
+#[derive(Debug, Clone, PartialEq)]
+enum PeerMessage {
+    KeepAlive,
+    Choke,
+    Unchoke,
+    Interested,
+    NotInterested,
+    Have(u32),
+    Bitfield(Vec<u8>),
+    Request { index: u32, begin: u32, length: u32 },
+    Piece { index: u32, begin: u32, data: Vec<u8> },
+    Cancel,
+    Extended { id: u8, payload: Vec<u8> },
+    Unknown,
+}
+
+fn be_u32(payload: &[u8], at: usize) -> Result<u32, String> {
+    payload
+        .get(at..at + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "Truncated peer message".to_string())
+}
+
+impl PeerMessage {
+    fn encode(&self) -> Vec<u8> {
+        let u32s = |values: &[u32]| values.iter().flat_map(|v| v.to_be_bytes()).collect::<Vec<u8>>();
+        let (id, payload) = match self {
+            Self::KeepAlive => return 0u32.to_be_bytes().to_vec(),
+            Self::Choke => (0, Vec::new()),
+            Self::Unchoke => (1, Vec::new()),
+            Self::Interested => (2, Vec::new()),
+            Self::NotInterested => (3, Vec::new()),
+            Self::Have(index) => (4, u32s(&[*index])),
+            Self::Bitfield(bits) => (5, bits.clone()),
+            Self::Request { index, begin, length } => (6, u32s(&[*index, *begin, *length])),
+            Self::Piece { index, begin, data } => {
+                let mut payload = u32s(&[*index, *begin]);
+                payload.extend_from_slice(data);
+                (7, payload)
+            }
+            Self::Cancel | Self::Unknown => return Vec::new(),
+            Self::Extended { id, payload } => {
+                let mut body = vec![*id];
+                body.extend_from_slice(payload);
+                (20, body)
+            }
+        };
+        let mut out = ((payload.len() + 1) as u32).to_be_bytes().to_vec();
+        out.push(id);
+        out.extend(payload);
+        out
+    }
+
+    fn parse(id: u8, payload: &[u8]) -> Result<Self, String> {
+        Ok(match id {
+            0 => Self::Choke,
+            1 => Self::Unchoke,
+            2 => Self::Interested,
+            3 => Self::NotInterested,
+            4 => Self::Have(be_u32(payload, 0)?),
+            5 => Self::Bitfield(payload.to_vec()),
+            6 => Self::Request {
+                index: be_u32(payload, 0)?,
+                begin: be_u32(payload, 4)?,
+                length: be_u32(payload, 8)?,
+            },
+            7 => Self::Piece {
+                index: be_u32(payload, 0)?,
+                begin: be_u32(payload, 4)?,
+                data: payload[8..].to_vec(),
+            },
+            8 => Self::Cancel,
+            20 => Self::Extended {
+                id: *payload.first().ok_or("Truncated extension message")?,
+                payload: payload[1..].to_vec(),
+            },
+            _ => Self::Unknown,
+        })
+    }
+}
+
+async fn read_message(reader: &mut OwnedReadHalf) -> Result<PeerMessage, String> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len).await.map_err(|e| e.to_string())?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len == 0 {
+        return Ok(PeerMessage::KeepAlive);
+    }
+    if len > MAX_MESSAGE_LEN {
+        return Err(format!("Peer message too large: {} bytes", len));
+    }
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body).await.map_err(|e| e.to_string())?;
+    PeerMessage::parse(body[0], &body[1..])
+}
+
+async fn send(writer: &mut OwnedWriteHalf, message: &PeerMessage) -> Result<(), String> {
+    writer.write_all(&message.encode()).await.map_err(|e| e.to_string())
+}
+
+fn handshake_bytes(info_hash: &[u8; 20], peer_id: &[u8; 20]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(68);
+    out.push(19);
+    out.extend_from_slice(b"BitTorrent protocol");
+    // Advertise the extension protocol (BEP 10)
+    out.extend_from_slice(&[0, 0, 0, 0, 0, 0x10, 0, 0]);
+    out.extend_from_slice(info_hash);
+    out.extend_from_slice(peer_id);
+    out
+}
+
+struct Handshake {
+    extensions: bool,
+    info_hash: [u8; 20],
+    peer_id: [u8; 20],
+}
+
+async fn read_handshake(reader: &mut OwnedReadHalf) -> Result<Handshake, String> {
+    let mut buf = [0u8; 68];
+    reader.read_exact(&mut buf).await.map_err(|e| e.to_string())?;
+    if buf[0] != 19 || &buf[1..20] != b"BitTorrent protocol" {
+        return Err("Not a BitTorrent peer".to_string());
+    }
+    let mut info_hash = [0u8; 20];
+    info_hash.copy_from_slice(&buf[28..48]);
+    let mut peer_id = [0u8; 20];
+    peer_id.copy_from_slice(&buf[48..68]);
+    Ok(Handshake {
+        extensions: buf[25] & 0x10 != 0,
+        info_hash,
+        peer_id,
+    })
+}
+
+// ==================== Trackers ====================
+
+struct AnnounceRequest<'a> {
+    info_hash: &'a [u8; 20],
+    peer_id: &'a [u8; 20],
+    port: u16,
+    uploaded: u64,
+    downloaded: u64,
+    left: u64,
+    event: Option<&'a str>,
+}
+
+struct AnnounceResponse {
+    interval: Duration,
+    peers: Vec<SocketAddr>,
+}
+
+fn percent_encode_bytes(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (*b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Announces to an HTTP(S) tracker; UDP trackers aren't supported
+async fn announce(tracker: &str, request: &AnnounceRequest<'_>) -> Result<AnnounceResponse, String> {
+    if !tracker.starts_with("http://") && !tracker.starts_with("https://") {
+        return Err(format!("Unsupported tracker: {}", tracker));
+    }
+    let separator = if tracker.contains('?') { '&' } else { '?' };
+    let mut url = format!(
+        "{}{}info_hash={}&peer_id={}&port={}&uploaded={}&downloaded={}&left={}&compact=1",
+        tracker,
+        separator,
+        percent_encode_bytes(request.info_hash),
+        percent_encode_bytes(request.peer_id),
+        request.port,
+        request.uploaded,
+        request.downloaded,
+        request.left
+    );
+    if let Some(event) = request.event {
+        url.push_str(&format!("&event={}", event));
+    }
+
+    let body = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| e.to_string())?
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("Tracker request failed: {}", e))?
+        .bytes()
+        .await
+        .map_err(|e| format!("Tracker response failed: {}", e))?;
+    let response = Bencode::decode(&body)?;
+    if let Some(reason) = response.get("failure reason").and_then(Bencode::as_str) {
+        return Err(format!("Tracker refused announce: {}", reason));
+    }
+
+    let interval = response
+        .get("interval")
+        .and_then(Bencode::as_int)
+        .map(|s| Duration::from_secs(s.max(0) as u64))
+        .unwrap_or(MIN_ANNOUNCE_INTERVAL);
+    let peers = match response.get("peers") {
+        Some(Bencode::Bytes(compact)) => compact
+            .chunks_exact(6)
+            .map(|p| {
+                let ip = Ipv4Addr::new(p[0], p[1], p[2], p[3]);
+                SocketAddr::new(IpAddr::V4(ip), u16::from_be_bytes([p[4], p[5]]))
+            })
+            .collect(),
+        Some(Bencode::List(list)) => list
+            .iter()
+            .filter_map(|peer| {
+                let ip: IpAddr = peer.get("ip")?.as_str()?.parse().ok()?;
+                let port = u16::try_from(peer.get("port")?.as_int()?).ok()?;
+                Some(SocketAddr::new(ip, port))
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    Ok(AnnounceResponse { interval, peers })
+}
+
+// ==================== Session ====================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TorrentPhase {
+    FetchingMetadata,
+    Checking,
+    Downloading,
+    Seeding,
+    Paused,
+    Finished,
+}
+
+#[derive(Debug, Clone)]
+pub struct TorrentConfig {
+    /// 0 picks any free port
+    pub listen_port: u16,
+    pub max_peers: usize,
+    /// Stop seeding once uploaded / selected size reaches this (0 = don't seed)
+    pub seed_ratio_limit: f64,
+    /// Stop seeding after this long even if the ratio isn't reached
+    pub seed_time_limit: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TorrentProgress {
+    pub phase: TorrentPhase,
+    /// Size of the selected files
+    pub selected_bytes: u64,
+    /// Verified bytes of the selected files
+    pub completed_bytes: u64,
+    pub downloaded_bytes: u64,
+    pub uploaded_bytes: u64,
+    pub download_speed: u64,
+    pub upload_speed: u64,
+    pub seeds: u32,
+    pub peers: u32,
+}
+
+impl TorrentProgress {
+    pub fn is_complete(&self) -> bool {
+        matches!(self.phase, TorrentPhase::Seeding | TorrentPhase::Finished)
+            || (self.selected_bytes > 0 && self.completed_bytes >= self.selected_bytes)
+    }
+}
+
+struct SessionState {
+    meta: Option<Arc<Metainfo>>,
+    phase: TorrentPhase,
+    paused: bool,
+    stopped: bool,
+    checked: bool,
+    selected: Vec<bool>,
+    /// Pieces overlapping a selected file
+    wanted: Vec<bool>,
+    /// Pieces whose hash matched
+    verified: Vec<bool>,
+    /// Verified pieces held entirely on disk, which we can serve
+    stored: Vec<bool>,
+    in_progress: HashSet<usize>,
+    /// Connected peers and whether each has every piece
+    peers: HashMap<u64, bool>,
+    next_connection: u64,
+    known_addrs: HashSet<SocketAddr>,
+    downloaded: u64,
+    uploaded: u64,
+    download_speed: u64,
+    upload_speed: u64,
+    metadata_size: Option<usize>,
+    metadata_pieces: Vec<Option<Vec<u8>>>,
+    seeding_since: Option<Instant>,
+    reannounce: bool,
+}
+
+impl SessionState {
+    fn install(&mut self, meta: Arc<Metainfo>) {
+        let pieces = meta.pieces.len();
+        self.selected = vec![true; meta.files.len()];
+        self.verified = vec![false; pieces];
+        self.stored = vec![false; pieces];
+        self.meta = Some(meta);
+        self.checked = false;
+        self.phase = TorrentPhase::Checking;
+        self.update_wanted();
+    }
+
+    fn update_wanted(&mut self) {
+        let Some(meta) = &self.meta else { return };
+        self.wanted = (0..meta.pieces.len())
+            .map(|i| meta.piece_segments(i).iter().any(|(file, _, _)| self.selected[*file]))
+            .collect();
+    }
+
+    fn selected_bytes(&self) -> u64 {
+        let Some(meta) = &self.meta else { return 0 };
+        meta.files.iter().filter(|f| self.selected[f.index]).map(|f| f.length).sum()
+    }
+
+    fn completed_bytes(&self) -> u64 {
+        let Some(meta) = &self.meta else { return 0 };
+        (0..meta.pieces.len())
+            .filter(|i| self.verified[*i])
+            .flat_map(|i| meta.piece_segments(i))
+            .filter(|(file, _, _)| self.selected[*file])
+            .map(|(_, _, length)| length)
+            .sum()
+    }
+
+    fn is_complete(&self) -> bool {
+        self.wanted.iter().zip(&self.verified).all(|(wanted, verified)| !wanted || *verified)
+    }
+}
+
+pub struct TorrentSession {
+    info_hash: [u8; 20],
+    peer_id: [u8; 20],
+    root: PathBuf,
+    trackers: Vec<String>,
+    config: TorrentConfig,
+    state: Mutex<SessionState>,
+    have_tx: broadcast::Sender<u32>,
+    download_limiter: RateLimiter,
+    upload_limiter: RateLimiter,
+}
+
+struct PeerState {
+    extensions: bool,
+    peer_choking: bool,
+    am_interested: bool,
+    bitfield: Vec<u8>,
+    ut_metadata: Option<u8>,
+    metadata_requested_at: Option<Instant>,
+    current: Option<PieceDownload>,
+    last_sent: Instant,
+}
+
+impl PeerState {
+    fn has(&self, index: usize) -> bool {
+        self.bitfield
+            .get(index / 8)
+            .is_some_and(|byte| byte & (0x80 >> (index % 8)) != 0)
+    }
+
+    fn mark_have(&mut self, index: usize) {
+        if self.bitfield.len() <= index / 8 {
+            self.bitfield.resize(index / 8 + 1, 0);
+        }
+        self.bitfield[index / 8] |= 0x80 >> (index % 8);
+    }
+}
+
+struct PieceDownload {
+    index: usize,
+    data: Vec<u8>,
+    received: Vec<bool>,
+    next_begin: u32,
+    outstanding: u32,
+}
+
+impl TorrentSession {
+    pub fn new(source: TorrentSource, root: PathBuf, config: TorrentConfig) -> Result<Self, String> {
+        std::fs::create_dir_all(&root).map_err(|e| format!("Failed to create download folder: {}", e))?;
+
+        let mut peer_id = *b"-CB0100-000000000000";
+        let mut rng = rand::thread_rng();
+        for byte in peer_id[8..].iter_mut() {
+            *byte = rng.sample(rand::distributions::Alphanumeric);
+        }
+
+        let mut state = SessionState {
+            meta: None,
+            phase: TorrentPhase::FetchingMetadata,
+            paused: false,
+            stopped: false,
+            checked: false,
+            selected: Vec::new(),
+            wanted: Vec::new(),
+            verified: Vec::new(),
+            stored: Vec::new(),
+            in_progress: HashSet::new(),
+            peers: HashMap::new(),
+            next_connection: 0,
+            known_addrs: HashSet::new(),
+            downloaded: 0,
+            uploaded: 0,
+            download_speed: 0,
+            upload_speed: 0,
+            metadata_size: None,
+            metadata_pieces: Vec::new(),
+            seeding_since: None,
+            reannounce: false,
+        };
+        let trackers = match &source {
+            TorrentSource::Metainfo(meta) => {
+                state.install(meta.clone());
+                meta.trackers.clone()
+            }
+            TorrentSource::Magnet(magnet) => magnet.trackers.clone(),
+        };
+
+        Ok(Self {
+            info_hash: source.info_hash(),
+            peer_id,
+            root,
+            trackers,
+            config,
+            state: Mutex::new(state),
+            have_tx: broadcast::channel(256).0,
+            download_limiter: RateLimiter::new(0),
+            upload_limiter: RateLimiter::new(0),
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, SessionState> {
+        self.state.lock().unwrap()
+    }
+
+    pub fn metainfo(&self) -> Option<Arc<Metainfo>> {
+        self.state().meta.clone()
+    }
+
+    pub fn selected_files(&self) -> Vec<bool> {
+        self.state().selected.clone()
+    }
+
+    /// Chooses which files of a multi-file torrent to download
+    pub fn select_files(&self, selected: &[bool]) -> Result<(), String> {
+        let mut state = self.state();
+        let meta = state.meta.clone().ok_or("Torrent metadata is not available yet")?;
+        if selected.len() != meta.files.len() {
+            return Err("File selection doesn't match the torrent's files".to_string());
+        }
+        state.selected = selected.to_vec();
+        state.update_wanted();
+        // Boundary pieces kept only partly on disk must be fetched again for
+        // files that are now selected
+        for index in 0..meta.pieces.len() {
+            let fully_selected = meta.piece_segments(index).iter().all(|(file, _, _)| selected[*file]);
+            if state.verified[index] && !state.stored[index] && fully_selected {
+                state.verified[index] = false;
+            }
+        }
+        if state.phase == TorrentPhase::Seeding && !state.is_complete() {
+            state.phase = TorrentPhase::Downloading;
+        }
+        Ok(())
+    }
+
+    pub fn pause(&self) {
+        self.state().paused = true;
+    }
+
+    pub fn resume(&self) {
+        let mut state = self.state();
+        if state.paused {
+            state.paused = false;
+            state.reannounce = true;
+        }
+    }
+
+    pub fn stop(&self) {
+        self.state().stopped = true;
+    }
+
+    /// Bytes per second; 0 removes the limit
+    pub fn set_rate_limits(&self, download: u64, upload: u64) {
+        self.download_limiter.set_rate(download);
+        self.upload_limiter.set_rate(upload);
+    }
+
+    pub fn progress(&self) -> TorrentProgress {
+        let state = self.state();
+        let pieces = state.verified.len();
+        TorrentProgress {
+            phase: if state.paused { TorrentPhase::Paused } else { state.phase },
+            selected_bytes: state.selected_bytes(),
+            completed_bytes: state.completed_bytes(),
+            downloaded_bytes: state.downloaded,
+            uploaded_bytes: state.uploaded,
+            download_speed: state.download_speed,
+            upload_speed: state.upload_speed,
+            seeds: state.peers.values().filter(|seed| **seed && pieces > 0).count() as u32,
+            peers: state.peers.len() as u32,
+        }
+    }
+
+    /// Runs the torrent until the seeding policy is met or it is stopped
+    pub async fn run(self: Arc<Self>) -> Result<(), String> {
+        let listener = match TcpListener::bind(("0.0.0.0", self.config.listen_port)).await {
+            Ok(listener) => listener,
+            Err(_) => TcpListener::bind(("0.0.0.0", 0))
+                .await
+                .map_err(|e| format!("Failed to open torrent port: {}", e))?,
+        };
+        let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+
+        let acceptor = {
+            let session = self.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, addr)) = listener.accept().await {
+                    let accepting = {
+                        let state = session.state();
+                        !state.stopped && !state.paused && state.peers.len() < session.config.max_peers
+                    };
+                    if accepting {
+                        tokio::spawn(session.clone().peer_connection(stream, addr, false));
+                    }
+                }
+            })
+        };
+
+        let result = self.main_loop(port).await;
+        acceptor.abort();
+        self.stop();
+        if !self.trackers.is_empty() {
+            self.announce_all(port, Some("stopped")).await;
+        }
+        result
+    }
+
+    async fn main_loop(self: &Arc<Self>, port: u16) -> Result<(), String> {
+        let mut next_announce = Instant::now();
+        let mut started = false;
+        let mut sample = (Instant::now(), 0u64, 0u64);
+
+        loop {
+            let (stopped, paused, needs_check) = {
+                let mut state = self.state();
+                if state.reannounce {
+                    state.reannounce = false;
+                    next_announce = Instant::now();
+                }
+                (state.stopped, state.paused, state.meta.is_some() && !state.checked)
+            };
+            if stopped {
+                return Ok(());
+            }
+            if paused {
+                tokio::time::sleep(TICK).await;
+                continue;
+            }
+            if needs_check {
+                self.check_pieces().await?;
+            }
+
+            if Instant::now() >= next_announce {
+                let event = if started { None } else { Some("started") };
+                let (interval, peers) = self.announce_all(port, event).await;
+                started = true;
+                next_announce = Instant::now() + interval.clamp(MIN_ANNOUNCE_INTERVAL, MAX_ANNOUNCE_INTERVAL);
+                for addr in peers {
+                    self.connect(addr);
+                }
+            }
+
+            match self.update_phase() {
+                Some(TorrentPhase::Seeding) => {
+                    self.announce_all(port, Some("completed")).await;
+                }
+                Some(TorrentPhase::Finished) => return Ok(()),
+                _ => {}
+            }
+
+            let elapsed = sample.0.elapsed().as_secs_f64();
+            if elapsed >= 1.0 {
+                let mut state = self.state();
+                state.download_speed = ((state.downloaded - sample.1) as f64 / elapsed) as u64;
+                state.upload_speed = ((state.uploaded - sample.2) as f64 / elapsed) as u64;
+                sample = (Instant::now(), state.downloaded, state.uploaded);
+            }
+
+            tokio::time::sleep(TICK).await;
+        }
+    }
+
+    /// Moves between downloading, seeding and finished; returns the new phase
+    fn update_phase(&self) -> Option<TorrentPhase> {
+        let mut state = self.state();
+        if state.meta.is_none() || !state.checked {
+            return None;
+        }
+        let complete = state.is_complete();
+        match state.phase {
+            TorrentPhase::Downloading | TorrentPhase::Checking if complete => {
+                state.phase = TorrentPhase::Seeding;
+                state.seeding_since = Some(Instant::now());
+                Some(TorrentPhase::Seeding)
+            }
+            TorrentPhase::Checking => {
+                state.phase = TorrentPhase::Downloading;
+                Some(TorrentPhase::Downloading)
+            }
+            TorrentPhase::Seeding => {
+                let size = state.selected_bytes();
+                let ratio = if size == 0 { f64::MAX } else { state.uploaded as f64 / size as f64 };
+                let seeded_long_enough = self
+                    .config
+                    .seed_time_limit
+                    .zip(state.seeding_since)
+                    .is_some_and(|(limit, since)| since.elapsed() >= limit);
+                if ratio >= self.config.seed_ratio_limit || seeded_long_enough {
+                    state.phase = TorrentPhase::Finished;
+                    Some(TorrentPhase::Finished)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Hashes whatever is already on disk so interrupted torrents resume
+    async fn check_pieces(&self) -> Result<(), String> {
+        let Some(meta) = self.metainfo() else { return Ok(()) };
+        let root = self.root.clone();
+        let check_meta = meta.clone();
+        let on_disk = tokio::task::spawn_blocking(move || {
+            (0..check_meta.pieces.len())
+                .map(|i| piece_on_disk(&root, &check_meta, i))
+                .collect::<Vec<bool>>()
+        })
+        .await
+        .map_err(|e| format!("Piece check failed: {}", e))?;
+
+        let mut state = self.state();
+        if !state.meta.as_ref().is_some_and(|m| Arc::ptr_eq(m, &meta)) {
+            return Ok(());
+        }
+        state.verified = on_disk.clone();
+        state.stored = on_disk;
+        state.checked = true;
+        Ok(())
+    }
+
+    async fn announce_all(&self, port: u16, event: Option<&str>) -> (Duration, Vec<SocketAddr>) {
+        let (uploaded, downloaded, left) = {
+            let state = self.state();
+            let left = state.selected_bytes().saturating_sub(state.completed_bytes());
+            (state.uploaded, state.downloaded, left)
+        };
+        let request = AnnounceRequest {
+            info_hash: &self.info_hash,
+            peer_id: &self.peer_id,
+            port,
+            uploaded,
+            downloaded,
+            left,
+            event,
+        };
+
+        let mut interval = MAX_ANNOUNCE_INTERVAL;
+        let mut peers = Vec::new();
+        for tracker in &self.trackers {
+            match announce(tracker, &request).await {
+                Ok(response) => {
+                    interval = interval.min(response.interval);
+                    peers.extend(response.peers);
+                }
+                Err(e) => {
+                    interval = MIN_ANNOUNCE_INTERVAL;
+                    log::debug!("Announce to {} failed: {}", tracker, e);
+                }
+            }
+        }
+        (interval, peers)
+    }
+
+    fn connect(self: &Arc<Self>, addr: SocketAddr) {
+        {
+            let mut state = self.state();
+            if state.peers.len() >= self.config.max_peers || !state.known_addrs.insert(addr) {
+                return;
+            }
+        }
+        let session = self.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(addr)).await {
+                Ok(Ok(stream)) => session.peer_connection(stream, addr, true).await,
+                _ => {
+                    session.state().known_addrs.remove(&addr);
+                }
+            }
+        });
+    }
+
+    async fn peer_connection(self: Arc<Self>, stream: TcpStream, addr: SocketAddr, outgoing: bool) {
+        if let Err(e) = self.peer_session(stream, outgoing).await {
+            log::debug!("Torrent peer {} disconnected: {}", addr, e);
+        }
+        self.state().known_addrs.remove(&addr);
+    }
+
+    async fn peer_session(&self, stream: TcpStream, outgoing: bool) -> Result<(), String> {
+        let _ = stream.set_nodelay(true);
+        let (mut reader, mut writer) = stream.into_split();
+        let ours = handshake_bytes(&self.info_hash, &self.peer_id);
+        if outgoing {
+            writer.write_all(&ours).await.map_err(|e| e.to_string())?;
+        }
+        let theirs = tokio::time::timeout(CONNECT_TIMEOUT, read_handshake(&mut reader))
+            .await
+            .map_err(|_| "Handshake timed out".to_string())??;
+        if theirs.info_hash != self.info_hash {
+            return Err("Peer is serving a different torrent".to_string());
+        }
+        if theirs.peer_id == self.peer_id {
+            return Ok(());
+        }
+        if !outgoing {
+            writer.write_all(&ours).await.map_err(|e| e.to_string())?;
+        }
+
+        let connection = {
+            let mut state = self.state();
+            if state.peers.len() >= self.config.max_peers {
+                return Err("Too many peers".to_string());
+            }
+            state.next_connection += 1;
+            let id = state.next_connection;
+            state.peers.insert(id, false);
+            id
+        };
+
+        let (tx, mut rx) = mpsc::channel(64);
+        let reader_task = tokio::spawn(async move {
+            while let Ok(message) = read_message(&mut reader).await {
+                if tx.send(message).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut peer = PeerState {
+            extensions: theirs.extensions,
+            peer_choking: true,
+            am_interested: false,
+            bitfield: Vec::new(),
+            ut_metadata: None,
+            metadata_requested_at: None,
+            current: None,
+            last_sent: Instant::now(),
+        };
+        let result = self.peer_loop(connection, &mut writer, &mut rx, &mut peer).await;
+        reader_task.abort();
+
+        let mut state = self.state();
+        state.peers.remove(&connection);
+        if let Some(piece) = peer.current.take() {
+            state.in_progress.remove(&piece.index);
+        }
+        result
+    }
+
+    async fn peer_loop(
+        &self,
+        connection: u64,
+        writer: &mut OwnedWriteHalf,
+        rx: &mut mpsc::Receiver<PeerMessage>,
+        peer: &mut PeerState,
+    ) -> Result<(), String> {
+        let mut have_rx = self.have_tx.subscribe();
+        if peer.extensions {
+            send(writer, &self.extension_handshake()).await?;
+        }
+        let bitfield = {
+            let state = self.state();
+            state.stored.iter().any(|s| *s).then(|| {
+                let mut bits = vec![0u8; state.stored.len().div_ceil(8)];
+                for (i, _) in state.stored.iter().enumerate().filter(|(_, s)| **s) {
+                    bits[i / 8] |= 0x80 >> (i % 8);
+                }
+                bits
+            })
+        };
+        if let Some(bits) = bitfield {
+            send(writer, &PeerMessage::Bitfield(bits)).await?;
+        }
+        // Everyone is unchoked; upload is bounded by the rate limit and the
+        // seeding policy instead
+        send(writer, &PeerMessage::Unchoke).await?;
+
+        let mut tick = tokio::time::interval(TICK);
+        let mut last_seen = Instant::now();
+        loop {
+            tokio::select! {
+                message = rx.recv() => {
+                    let Some(message) = message else { return Ok(()) };
+                    last_seen = Instant::now();
+                    self.handle_message(connection, writer, peer, message).await?;
+                }
+                have = have_rx.recv() => {
+                    if let Ok(index) = have {
+                        send(writer, &PeerMessage::Have(index)).await?;
+                        peer.last_sent = Instant::now();
+                    }
+                }
+                _ = tick.tick() => {
+                    let done = {
+                        let state = self.state();
+                        state.stopped || state.paused || state.phase == TorrentPhase::Finished
+                    };
+                    if done {
+                        return Ok(());
+                    }
+                    if last_seen.elapsed() > PEER_IDLE_TIMEOUT {
+                        return Err("Peer timed out".to_string());
+                    }
+                    if peer.last_sent.elapsed() > KEEPALIVE_INTERVAL {
+                        send(writer, &PeerMessage::KeepAlive).await?;
+                        peer.last_sent = Instant::now();
+                    }
+                }
+            }
+            self.request_more(writer, peer).await?;
+        }
+    }
+
+    async fn handle_message(
+        &self,
+        connection: u64,
+        writer: &mut OwnedWriteHalf,
+        peer: &mut PeerState,
+        message: PeerMessage,
+    ) -> Result<(), String> {
+        match message {
+            PeerMessage::Choke => {
+                peer.peer_choking = true;
+                if let Some(piece) = peer.current.take() {
+                    self.state().in_progress.remove(&piece.index);
+                }
+            }
+            PeerMessage::Unchoke => peer.peer_choking = false,
+            PeerMessage::Have(index) => {
+                peer.mark_have(index as usize);
+                self.update_peer_summary(connection, peer);
+            }
+            PeerMessage::Bitfield(bits) => {
+                peer.bitfield = bits;
+                self.update_peer_summary(connection, peer);
+            }
+            PeerMessage::Request { index, begin, length } => {
+                self.serve_block(writer, index, begin, length).await?;
+                peer.last_sent = Instant::now();
+            }
+            PeerMessage::Piece { index, begin, data } => self.receive_block(peer, index, begin, data).await?,
+            PeerMessage::Extended { id, payload } => self.handle_extended(writer, peer, id, &payload).await?,
+            PeerMessage::KeepAlive
+            | PeerMessage::Interested
+            | PeerMessage::NotInterested
+            | PeerMessage::Cancel
+            | PeerMessage::Unknown => {}
+        }
+        Ok(())
+    }
+
+    fn update_peer_summary(&self, connection: u64, peer: &PeerState) {
+        let mut state = self.state();
+        let pieces = state.verified.len();
+        let seed = pieces > 0 && (0..pieces).all(|i| peer.has(i));
+        if let Some(summary) = state.peers.get_mut(&connection) {
+            *summary = seed;
+        }
+    }
+
+    /// Updates our interest and keeps the peer's request pipeline full
+    async fn request_more(&self, writer: &mut OwnedWriteHalf, peer: &mut PeerState) -> Result<(), String> {
+        let Some(meta) = self.metainfo() else {
+            return self.request_metadata(writer, peer).await;
+        };
+
+        let (interested, pick) = {
+            let mut state = self.state();
+            let candidate = |state: &SessionState, i: usize| state.wanted[i] && !state.verified[i] && peer.has(i);
+            let interested = state.phase == TorrentPhase::Downloading
+                && (0..meta.pieces.len()).any(|i| candidate(&state, i));
+            let pick = if interested && !peer.peer_choking && peer.current.is_none() {
+                (0..meta.pieces.len()).find(|i| candidate(&state, *i) && !state.in_progress.contains(i))
+            } else {
+                None
+            };
+            if let Some(index) = pick {
+                state.in_progress.insert(index);
+            }
+            (interested, pick)
+        };
+
+        if interested != peer.am_interested {
+            let message = if interested { PeerMessage::Interested } else { PeerMessage::NotInterested };
+            send(writer, &message).await?;
+            peer.am_interested = interested;
+        }
+        if let Some(index) = pick {
+            let size = meta.piece_size(index) as usize;
+            peer.current = Some(PieceDownload {
+                index,
+                data: vec![0u8; size],
+                received: vec![false; size.div_ceil(BLOCK_SIZE as usize)],
+                next_begin: 0,
+                outstanding: 0,
+            });
+        }
+        if peer.peer_choking {
+            return Ok(());
+        }
+
+        while let Some(piece) = peer.current.as_mut() {
+            let size = piece.data.len() as u32;
+            if piece.outstanding >= PIPELINE_DEPTH || piece.next_begin >= size {
+                break;
+            }
+            let length = BLOCK_SIZE.min(size - piece.next_begin);
+            let request = PeerMessage::Request {
+                index: piece.index as u32,
+                begin: piece.next_begin,
+                length,
+            };
+            piece.next_begin += length;
+            piece.outstanding += 1;
+            self.download_limiter.acquire(length).await;
+            send(writer, &request).await?;
+            peer.last_sent = Instant::now();
+        }
+        Ok(())
+    }
+
+    async fn receive_block(&self, peer: &mut PeerState, index: u32, begin: u32, data: Vec<u8>) -> Result<(), String> {
+        let Some(piece) = peer.current.as_mut().filter(|p| p.index == index as usize) else {
+            return Ok(());
+        };
+        let begin = begin as usize;
+        let block = begin / BLOCK_SIZE as usize;
+        if begin % BLOCK_SIZE as usize != 0 || begin + data.len() > piece.data.len() || block >= piece.received.len() {
+            return Err("Peer sent a block outside the requested piece".to_string());
+        }
+        piece.data[begin..begin + data.len()].copy_from_slice(&data);
+        piece.received[block] = true;
+        piece.outstanding = piece.outstanding.saturating_sub(1);
+        self.state().downloaded += data.len() as u64;
+        if !piece.received.iter().all(|r| *r) {
+            return Ok(());
+        }
+
+        let Some(piece) = peer.current.take() else { return Ok(()) };
+        let meta = self.metainfo().ok_or("Torrent metadata missing")?;
+        if Sha1::digest(&piece.data).as_slice() != meta.pieces[piece.index] {
+            self.state().in_progress.remove(&piece.index);
+            return Err(format!("Piece {} failed hash verification", piece.index));
+        }
+
+        let root = self.root.clone();
+        let selected = self.selected_files();
+        let write_meta = meta.clone();
+        let index = piece.index;
+        let stored = tokio::task::spawn_blocking(move || write_piece(&root, &write_meta, &selected, index, &piece.data))
+            .await
+            .map_err(|e| format!("Piece write failed: {}", e))??;
+
+        {
+            let mut state = self.state();
+            state.in_progress.remove(&index);
+            state.verified[index] = true;
+            state.stored[index] = stored;
+        }
+        if stored {
+            let _ = self.have_tx.send(index as u32);
+        }
+        Ok(())
+    }
+
+    async fn serve_block(&self, writer: &mut OwnedWriteHalf, index: u32, begin: u32, length: u32) -> Result<(), String> {
+        if length == 0 || length > MAX_BLOCK_REQUEST {
+            return Err("Peer requested an invalid block size".to_string());
+        }
+        let meta = {
+            let state = self.state();
+            let servable = state.stored.get(index as usize).copied().unwrap_or(false)
+                && state.phase != TorrentPhase::Finished;
+            if !servable {
+                return Ok(());
+            }
+            state.meta.clone().ok_or("Torrent metadata missing")?
+        };
+        if begin as u64 + length as u64 > meta.piece_size(index as usize) {
+            return Err("Peer requested a block outside the piece".to_string());
+        }
+
+        self.upload_limiter.acquire(length).await;
+        let root = self.root.clone();
+        let start = index as u64 * meta.piece_length + begin as u64;
+        let data = tokio::task::spawn_blocking(move || read_range(&root, &meta, start, length as u64))
+            .await
+            .map_err(|e| format!("Piece read failed: {}", e))??;
+        send(writer, &PeerMessage::Piece { index, begin, data }).await?;
+        self.state().uploaded += length as u64;
+        Ok(())
+    }
+
+    fn extension_handshake(&self) -> PeerMessage {
+        let mut fields = vec![
+            ("m", Bencode::dict(vec![("ut_metadata", Bencode::Int(UT_METADATA_ID as i64))])),
+            ("v", Bencode::string("CUBE Nexum")),
+        ];
+        if let Some(meta) = self.metainfo() {
+            fields.push(("metadata_size", Bencode::Int(meta.info_bytes.len() as i64)));
+        }
+        PeerMessage::Extended {
+            id: 0,
+            payload: Bencode::dict(fields).encode(),
+        }
+    }
+
+    async fn handle_extended(
+        &self,
+        writer: &mut OwnedWriteHalf,
+        peer: &mut PeerState,
+        id: u8,
+        payload: &[u8],
+    ) -> Result<(), String> {
+        if id == 0 {
+            let Ok(handshake) = Bencode::decode(payload) else { return Ok(()) };
+            peer.ut_metadata = handshake
+                .get("m")
+                .and_then(|m| m.get("ut_metadata"))
+                .and_then(Bencode::as_int)
+                .and_then(|n| u8::try_from(n).ok())
+                .filter(|n| *n != 0);
+            if let Some(size) = handshake.get("metadata_size").and_then(Bencode::as_int) {
+                self.offer_metadata_size(size);
+            }
+            return Ok(());
+        }
+        if id != UT_METADATA_ID {
+            return Ok(());
+        }
+
+        let (header, data) = Bencode::decode_prefix(payload)?;
+        let piece = header
+            .get("piece")
+            .and_then(Bencode::as_int)
+            .and_then(|n| usize::try_from(n).ok())
+            .ok_or("Metadata message has no piece")?;
+        match header.get("msg_type").and_then(Bencode::as_int) {
+            Some(0) => {
+                let Some(their_id) = peer.ut_metadata else { return Ok(()) };
+                let reply = match self.metainfo() {
+                    Some(meta) if piece * METADATA_PIECE_SIZE < meta.info_bytes.len() => {
+                        let start = piece * METADATA_PIECE_SIZE;
+                        let end = (start + METADATA_PIECE_SIZE).min(meta.info_bytes.len());
+                        let mut body = Bencode::dict(vec![
+                            ("msg_type", Bencode::Int(1)),
+                            ("piece", Bencode::Int(piece as i64)),
+                            ("total_size", Bencode::Int(meta.info_bytes.len() as i64)),
+                        ])
+                        .encode();
+                        body.extend_from_slice(&meta.info_bytes[start..end]);
+                        body
+                    }
+                    _ => Bencode::dict(vec![("msg_type", Bencode::Int(2)), ("piece", Bencode::Int(piece as i64))])
+                        .encode(),
+                };
+                send(writer, &PeerMessage::Extended { id: their_id, payload: reply }).await?;
+            }
+            Some(1) => self.receive_metadata_piece(piece, data),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn offer_metadata_size(&self, size: i64) {
+        let mut state = self.state();
+        if state.meta.is_some() || state.metadata_size.is_some() {
+            return;
+        }
+        if let Ok(size) = usize::try_from(size) {
+            if size > 0 && size <= MAX_METADATA_SIZE {
+                state.metadata_size = Some(size);
+                state.metadata_pieces = vec![None; size.div_ceil(METADATA_PIECE_SIZE)];
+            }
+        }
+    }
+
+    async fn request_metadata(&self, writer: &mut OwnedWriteHalf, peer: &mut PeerState) -> Result<(), String> {
+        let Some(their_id) = peer.ut_metadata else { return Ok(()) };
+        if peer.metadata_requested_at.is_some_and(|at| at.elapsed() < METADATA_RETRY) {
+            return Ok(());
+        }
+        let missing: Vec<usize> = {
+            let state = self.state();
+            state
+                .metadata_pieces
+                .iter()
+                .enumerate()
+                .filter(|(_, piece)| piece.is_none())
+                .map(|(i, _)| i)
+                .collect()
+        };
+        if missing.is_empty() {
+            return Ok(());
+        }
+        for piece in missing {
+            let payload = Bencode::dict(vec![("msg_type", Bencode::Int(0)), ("piece", Bencode::Int(piece as i64))]).encode();
+            send(writer, &PeerMessage::Extended { id: their_id, payload }).await?;
+        }
+        peer.metadata_requested_at = Some(Instant::now());
+        peer.last_sent = Instant::now();
+        Ok(())
+    }
+
+    fn receive_metadata_piece(&self, piece: usize, data: &[u8]) {
+        let mut state = self.state();
+        if state.meta.is_some() || piece >= state.metadata_pieces.len() {
+            return;
+        }
+        state.metadata_pieces[piece] = Some(data.to_vec());
+        if state.metadata_pieces.iter().any(Option::is_none) {
+            return;
+        }
+
+        let info_bytes: Vec<u8> = state.metadata_pieces.iter().flatten().flatten().copied().collect();
+        let parsed = if Sha1::digest(&info_bytes).as_slice() == self.info_hash {
+            Metainfo::from_info_bytes(&info_bytes, self.trackers.clone())
+        } else {
+            Err("Metadata from peers doesn't match the info hash".to_string())
+        };
+        match parsed {
+            Ok(meta) => state.install(Arc::new(meta)),
+            Err(e) => {
+                log::warn!("Discarding torrent metadata: {}", e);
+                let pieces = state.metadata_pieces.len();
+                state.metadata_pieces = vec![None; pieces];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bencode_round_trip_and_magnet_parsing() {
+        let value = Bencode::dict(vec![
+            ("list", Bencode::List(vec![Bencode::Int(-3), Bencode::string("spam")])),
+            ("n", Bencode::Int(42)),
+        ]);
+        let encoded = value.encode();
+        assert_eq!(encoded, b"d4:listli-3e4:spame1:ni42ee".to_vec());
+        assert_eq!(Bencode::decode(&encoded).unwrap(), value);
+        assert!(Bencode::decode(b"4:spa").is_err());
+        assert!(Bencode::decode(&[b'l'; 100]).is_err());
+
+        let magnet = parse_magnet(
+            "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a&dn=Big+File&tr=http%3A%2F%2Ftracker.example%2Fannounce",
+        )
+        .unwrap();
+        assert_eq!(hex::encode(magnet.info_hash), "c12fe1c06bba254a9dc9f519b335aa7c1367a88a");
+        assert_eq!(magnet.display_name.as_deref(), Some("Big File"));
+        assert_eq!(magnet.trackers, vec!["http://tracker.example/announce".to_string()]);
+        let base32 = parse_magnet("magnet:?xt=urn:btih:YEX6DQDLXISUVHOJ6UM3GNNKPQJWPKEK").unwrap();
+        assert_eq!(base32.info_hash, magnet.info_hash);
+    }
+
+    #[test]
+    fn test_torrent_paths_are_sanitized() {
+        assert!(sanitize_component("..").is_err());
+        assert!(sanitize_component(" .. ").is_err());
+        assert_eq!(sanitize_component("../etc/passwd").unwrap(), ".._etc_passwd");
+        assert_eq!(sanitize_component("C:\\evil").unwrap(), "C__evil");
+        assert_eq!(sanitize_component("con.txt").unwrap(), "_con.txt");
+
+        let root = std::env::temp_dir().join(format!("cube_torrent_paths_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        assert!(sandboxed_path(&root, "pack/../../escape", false).is_err());
+        assert!(sandboxed_path(&root, "/etc/passwd", false).is_err());
+        assert_eq!(sandboxed_path(&root, "pack/a.txt", true).unwrap(), root.join("pack").join("a.txt"));
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,943 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UdpSocket;
+use tokio_util::sync::CancellationToken;
+
+/// Hostname whose A record is the address of the resolver that asked for it
+const RESOLVER_WHOAMI_HOST: &str = "whoami.akamai.net";
+
+const UPSTREAM_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where DNS queries go while leak protection is active
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "mode", rename_all = "camelCase")]
+pub enum DnsUpstream {
+    /// Plain DNS to the tunnel's resolvers
+    Tunnel,
+    /// RFC 8484 DNS-over-HTTPS endpoint, e.g. `https://1.1.1.1/dns-query`
+    DnsOverHttps { url: String },
+    /// RFC 7858 DNS-over-TLS, `server` as `ip:port`
+    DnsOverTls { server: String, tls_name: String },
+}
+
+/// DNS leak protection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsProtectionSettings {
+    pub enabled: bool,
+    pub upstream: DnsUpstream,
+    /// Port of the loopback DNS stub the system is pointed at
+    pub stub_port: u16,
+    /// Additional resolver egress addresses that count as in-tunnel
+    pub expected_egress: Vec<String>,
+}
+
+impl Default for DnsProtectionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            upstream: DnsUpstream::Tunnel,
+            stub_port: 53,
+            expected_egress: Vec::new(),
+        }
+    }
+}
+
+/// Current state of DNS leak protection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsProtectionStatus {
+    pub active: bool,
+    pub upstream: Option<DnsUpstream>,
+    pub tunnel_resolvers: Vec<String>,
+    pub stub_address: Option<String>,
+    pub filtered_categories: Vec<String>,
+}
+
+/// A resolver seen during a leak test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservedResolver {
+    pub address: String,
+    /// "system_config" or "egress_probe"
+    pub source: String,
+    pub expected: bool,
+}
+
+/// DNS leak test report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsLeakTestResult {
+    pub leak_detected: bool,
+    pub protection_active: bool,
+    pub expected_resolvers: Vec<String>,
+    pub resolvers: Vec<ObservedResolver>,
+    pub message: String,
+    pub tested_at: u64,
+}
+
+/// What the leak test inspects
+#[derive(Debug, Clone, Default)]
+pub struct LeakTestTargets {
+    /// Nameservers the operating system is configured to use
+    pub system_resolvers: Vec<IpAddr>,
+    /// Resolver to send the egress probe through
+    pub probe_server: Option<SocketAddr>,
+}
+
+impl LeakTestTargets {
+    /// Read the system resolver configuration
+    pub fn from_system() -> Self {
+        let system_resolvers: Vec<IpAddr> = hickory_resolver::system_conf::read_system_conf()
+            .map(|(config, _)| config.name_servers().iter().map(|ns| ns.socket_addr.ip()).collect())
+            .unwrap_or_default();
+        let mut unique = Vec::new();
+        for ip in system_resolvers {
+            if !unique.contains(&ip) {
+                unique.push(ip);
+            }
+        }
+        let probe_server = unique.first().map(|ip| SocketAddr::new(*ip, 53));
+        Self {
+            system_resolvers: unique,
+            probe_server,
+        }
+    }
+}
+
+/// Mark each observed resolver as expected (in tunnel) or not
+pub fn classify_resolvers(observed: &[(IpAddr, &str)], expected: &HashSet<IpAddr>, stub_active: bool) -> Vec<ObservedResolver> {
+    observed
+        .iter()
+        .map(|(ip, source)| ObservedResolver {
+            address: ip.to_string(),
+            source: source.to_string(),
+            // The loopback stub only forwards to the protected upstream
+            expected: expected.contains(ip) || (stub_active && ip.is_loopback()),
+        })
+        .collect()
+}
+
+// ============================================================================
+// SYSTEM DNS CONFIGURATION
+// ============================================================================
+
+/// Saved system DNS configuration, one entry per interface / config file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemDnsSnapshot {
+    pub entries: Vec<DnsSnapshotEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsSnapshotEntry {
+    pub target: String,
+    pub servers: Vec<String>,
+    /// Original file contents, where the platform stores DNS in a file
+    pub raw: Option<String>,
+    /// Original symlink target, if the file was a symlink
+    pub link: Option<String>,
+}
+
+/// Reads and rewrites the operating system's resolver configuration
+pub trait SystemDnsConfigurator: Send + Sync {
+    fn capture(&self) -> Result<SystemDnsSnapshot>;
+    fn apply(&self, snapshot: &SystemDnsSnapshot, servers: &[IpAddr]) -> Result<()>;
+    fn restore(&self, snapshot: &SystemDnsSnapshot) -> Result<()>;
+}
+
+/// Platform DNS configuration via resolv.conf, networksetup or PowerShell
+pub struct PlatformDnsConfigurator;
+
+#[cfg(target_os = "linux")]
+const RESOLV_CONF: &str = "/etc/resolv.conf";
+
+impl SystemDnsConfigurator for PlatformDnsConfigurator {
+    #[cfg(target_os = "linux")]
+    fn capture(&self) -> Result<SystemDnsSnapshot> {
+        let link = std::fs::read_link(RESOLV_CONF).ok().map(|p| p.to_string_lossy().to_string());
+        let raw = std::fs::read_to_string(RESOLV_CONF).unwrap_or_default();
+        let servers = raw
+            .lines()
+            .filter_map(|l| l.trim().strip_prefix("nameserver"))
+            .map(|s| s.trim().to_string())
+            .collect();
+        Ok(SystemDnsSnapshot {
+            entries: vec![DnsSnapshotEntry {
+                target: RESOLV_CONF.to_string(),
+                servers,
+                raw: Some(raw),
+                link,
+            }],
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    fn apply(&self, _snapshot: &SystemDnsSnapshot, servers: &[IpAddr]) -> Result<()> {
+        let mut contents = String::from("# Generated by CUBE VPN DNS leak protection\n");
+        for server in servers {
+            contents.push_str(&format!("nameserver {}\n", server));
+        }
+        contents.push_str("options edns0\n");
+        // Replace a resolved/NetworkManager symlink rather than writing through it
+        let _ = std::fs::remove_file(RESOLV_CONF);
+        std::fs::write(RESOLV_CONF, contents).context("Failed to write /etc/resolv.conf")
+    }
+
+    #[cfg(target_os = "linux")]
+    fn restore(&self, snapshot: &SystemDnsSnapshot) -> Result<()> {
+        for entry in &snapshot.entries {
+            let _ = std::fs::remove_file(&entry.target);
+            if let Some(link) = &entry.link {
+                std::os::unix::fs::symlink(link, &entry.target).context("Failed to restore resolv.conf symlink")?;
+            } else if let Some(raw) = &entry.raw {
+                std::fs::write(&entry.target, raw).context("Failed to restore resolv.conf")?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "macos")]
+    fn capture(&self) -> Result<SystemDnsSnapshot> {
+        let output = std::process::Command::new("networksetup").arg("-listallnetworkservices").output()?;
+        let services: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .skip(1) // "An asterisk (*) denotes that a network service is disabled."
+            .filter(|l| !l.trim().is_empty() && !l.starts_with('*'))
+            .map(|l| l.trim().to_string())
+            .collect();
+
+        let mut entries = Vec::new();
+        for service in services {
+            let output = std::process::Command::new("networksetup").args(["-getdnsservers", &service]).output()?;
+            let servers = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|l| l.trim().parse::<IpAddr>().is_ok())
+                .map(|l| l.trim().to_string())
+                .collect();
+            entries.push(DnsSnapshotEntry { target: service, servers, raw: None, link: None });
+        }
+        Ok(SystemDnsSnapshot { entries })
+    }
+
+    #[cfg(target_os = "macos")]
+    fn apply(&self, snapshot: &SystemDnsSnapshot, servers: &[IpAddr]) -> Result<()> {
+        let servers: Vec<String> = servers.iter().map(|s| s.to_string()).collect();
+        for entry in &snapshot.entries {
+            std::process::Command::new("networksetup")
+                .arg("-setdnsservers")
+                .arg(&entry.target)
+                .args(&servers)
+                .status()?;
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "macos")]
+    fn restore(&self, snapshot: &SystemDnsSnapshot) -> Result<()> {
+        for entry in &snapshot.entries {
+            let mut cmd = std::process::Command::new("networksetup");
+            cmd.arg("-setdnsservers").arg(&entry.target);
+            if entry.servers.is_empty() {
+                cmd.arg("Empty");
+            } else {
+                cmd.args(&entry.servers);
+            }
+            cmd.status()?;
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    fn capture(&self) -> Result<SystemDnsSnapshot> {
+        let output = std::process::Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                "Get-DnsClientServerAddress -AddressFamily IPv4 | ForEach-Object { \"$($_.InterfaceAlias)|$($_.ServerAddresses -join ',')\" }",
+            ])
+            .output()?;
+        let entries = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|l| l.trim().split_once('|'))
+            .map(|(alias, servers)| DnsSnapshotEntry {
+                target: alias.to_string(),
+                servers: servers.split(',').filter(|s| !s.is_empty()).map(String::from).collect(),
+                raw: None,
+                link: None,
+            })
+            .collect();
+        Ok(SystemDnsSnapshot { entries })
+    }
+
+    #[cfg(target_os = "windows")]
+    fn apply(&self, snapshot: &SystemDnsSnapshot, servers: &[IpAddr]) -> Result<()> {
+        let list = servers.iter().map(|s| format!("'{}'", s)).collect::<Vec<_>>().join(",");
+        for entry in &snapshot.entries {
+            std::process::Command::new("powershell")
+                .args([
+                    "-NoProfile",
+                    "-Command",
+                    &format!(
+                        "Set-DnsClientServerAddress -InterfaceAlias '{}' -ServerAddresses ({})",
+                        entry.target.replace('\'', "''"),
+                        list
+                    ),
+                ])
+                .status()?;
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    fn restore(&self, snapshot: &SystemDnsSnapshot) -> Result<()> {
+        for entry in &snapshot.entries {
+            let alias = entry.target.replace('\'', "''");
+            let command = if entry.servers.is_empty() {
+                format!("Set-DnsClientServerAddress -InterfaceAlias '{}' -ResetServerAddresses", alias)
+            } else {
+                let list = entry.servers.iter().map(|s| format!("'{}'", s)).collect::<Vec<_>>().join(",");
+                format!("Set-DnsClientServerAddress -InterfaceAlias '{}' -ServerAddresses ({})", alias, list)
+            };
+            std::process::Command::new("powershell").args(["-NoProfile", "-Command", &command]).status()?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    fn capture(&self) -> Result<SystemDnsSnapshot> {
+        Err(anyhow::anyhow!("DNS leak protection is not supported on this platform"))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    fn apply(&self, _snapshot: &SystemDnsSnapshot, _servers: &[IpAddr]) -> Result<()> {
+        Err(anyhow::anyhow!("DNS leak protection is not supported on this platform"))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+    fn restore(&self, _snapshot: &SystemDnsSnapshot) -> Result<()> {
+        Ok(())
+    }
+}
+
+// ============================================================================
+// THREAT PROTECTION CATEGORY FILTER
+// ============================================================================
+
+/// Domain blocklists keyed by threat-protection DNS category id
+pub struct DnsCategoryFilter {
+    lists: HashMap<String, Vec<String>>,
+    blocked: HashSet<String>,
+}
+
+impl Default for DnsCategoryFilter {
+    fn default() -> Self {
+        let lists: &[(&str, &[&str])] = &[
+            ("malware", &["malicious-download.xyz"]),
+            ("phishing", &["paypa1-secure.com"]),
+            ("ads", &["doubleclick.net", "googlesyndication.com", "adservice.google.com", "ads.megaadserver.net"]),
+            ("trackers", &["google-analytics.com", "hotjar.com", "analytics.trackernetwork.com"]),
+            ("crypto_mining", &["coinhive.com", "coin-hive.com", "cryptoloot.pro"]),
+            ("adult", &["pornhub.com", "xvideos.com"]),
+            ("gambling", &["bet365.com", "pokerstars.com"]),
+            ("social_media", &["facebook.com", "instagram.com", "tiktok.com", "twitter.com", "x.com"]),
+        ];
+        Self {
+            lists: lists
+                .iter()
+                .map(|(id, domains)| (id.to_string(), domains.iter().map(|d| d.to_string()).collect()))
+                .collect(),
+            blocked: HashSet::new(),
+        }
+    }
+}
+
+impl DnsCategoryFilter {
+    /// Category blocking `domain`, matching the domain and its subdomains
+    pub fn blocked_category(&self, domain: &str) -> Option<&str> {
+        let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+        self.blocked.iter().map(String::as_str).find(|category| {
+            self.lists.get(*category).is_some_and(|list| {
+                list.iter()
+                    .any(|d| domain == *d || domain.ends_with(&format!(".{}", d)))
+            })
+        })
+    }
+}
+
+// ============================================================================
+// DNS GUARD
+// ============================================================================
+
+struct ActiveProtection {
+    settings: DnsProtectionSettings,
+    tunnel_resolvers: Vec<IpAddr>,
+    stub_address: Option<SocketAddr>,
+    stub_cancel: Option<CancellationToken>,
+    snapshot: SystemDnsSnapshot,
+}
+
+/// Forces DNS through the tunnel while the VPN is connected
+pub struct VpnDnsGuard {
+    configurator: Box<dyn SystemDnsConfigurator>,
+    active: Mutex<Option<ActiveProtection>>,
+    filter: Arc<RwLock<DnsCategoryFilter>>,
+}
+
+impl Default for VpnDnsGuard {
+    fn default() -> Self {
+        Self::new(Box::new(PlatformDnsConfigurator))
+    }
+}
+
+impl VpnDnsGuard {
+    pub fn new(configurator: Box<dyn SystemDnsConfigurator>) -> Self {
+        Self {
+            configurator,
+            active: Mutex::new(None),
+            filter: Arc::new(RwLock::new(DnsCategoryFilter::default())),
+        }
+    }
+
+    /// Sync the threat-protection DNS categories applied by the stub
+    pub fn set_blocked_categories(&self, categories: Vec<String>) {
+        if let Ok(mut filter) = self.filter.write() {
+            filter.blocked = categories.into_iter().collect();
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.lock().map(|a| a.is_some()).unwrap_or(false)
+    }
+
+    /// Point system DNS at the tunnel (through the loopback stub when possible).
+    /// Any previous protection is torn down first.
+    pub async fn enable(&self, settings: DnsProtectionSettings, tunnel_resolvers: &[String]) -> Result<DnsProtectionStatus> {
+        self.disable()?;
+
+        let resolvers: Vec<IpAddr> = tunnel_resolvers.iter().filter_map(|s| s.trim().parse().ok()).collect();
+        if settings.upstream == DnsUpstream::Tunnel && resolvers.is_empty() {
+            return Err(anyhow::anyhow!("No tunnel DNS resolvers configured"));
+        }
+
+        // Category filtering and encrypted upstreams need the stub
+        let cancel = CancellationToken::new();
+        let stub_address = match UdpSocket::bind((Ipv4Addr::LOCALHOST, settings.stub_port)).await {
+            Ok(socket) => {
+                let address = socket.local_addr()?;
+                tokio::spawn(run_stub(
+                    socket,
+                    settings.upstream.clone(),
+                    resolvers.clone(),
+                    self.filter.clone(),
+                    cancel.clone(),
+                ));
+                Some(address)
+            }
+            Err(e) if settings.upstream == DnsUpstream::Tunnel => {
+                log::warn!("DNS stub unavailable ({}), pointing system DNS at tunnel resolvers directly", e);
+                None
+            }
+            Err(e) => return Err(anyhow::anyhow!("Failed to start encrypted DNS stub: {}", e)),
+        };
+
+        let system_servers = match stub_address {
+            Some(address) => vec![address.ip()],
+            None => resolvers.clone(),
+        };
+
+        let snapshot = self.configurator.capture().context("Failed to read system DNS configuration")?;
+        if let Err(e) = self.configurator.apply(&snapshot, &system_servers) {
+            cancel.cancel();
+            let _ = self.configurator.restore(&snapshot);
+            return Err(e.context("Failed to apply tunnel DNS"));
+        }
+
+        log::info!("🔒 DNS leak protection active ({:?})", settings.upstream);
+        *self.active.lock().map_err(|_| anyhow::anyhow!("DNS guard lock poisoned"))? = Some(ActiveProtection {
+            settings,
+            tunnel_resolvers: resolvers,
+            stub_address,
+            stub_cancel: Some(cancel),
+            snapshot,
+        });
+        Ok(self.status())
+    }
+
+    /// Stop the stub and restore the DNS configuration saved by `enable`
+    pub fn disable(&self) -> Result<()> {
+        let active = self.active.lock().map_err(|_| anyhow::anyhow!("DNS guard lock poisoned"))?.take();
+        if let Some(active) = active {
+            if let Some(cancel) = active.stub_cancel {
+                cancel.cancel();
+            }
+            self.configurator
+                .restore(&active.snapshot)
+                .context("Failed to restore system DNS configuration")?;
+            log::info!("🔓 DNS leak protection disabled, original DNS restored");
+        }
+        Ok(())
+    }
+
+    pub fn status(&self) -> DnsProtectionStatus {
+        let filtered_categories = self
+            .filter
+            .read()
+            .map(|f| {
+                let mut categories: Vec<String> = f.blocked.iter().cloned().collect();
+                categories.sort();
+                categories
+            })
+            .unwrap_or_default();
+        let active = self.active.lock().ok();
+        match active.as_ref().and_then(|a| a.as_ref()) {
+            Some(a) => DnsProtectionStatus {
+                active: true,
+                upstream: Some(a.settings.upstream.clone()),
+                tunnel_resolvers: a.tunnel_resolvers.iter().map(|ip| ip.to_string()).collect(),
+                stub_address: a.stub_address.map(|s| s.to_string()),
+                filtered_categories,
+            },
+            None => DnsProtectionStatus {
+                active: false,
+                upstream: None,
+                tunnel_resolvers: Vec::new(),
+                stub_address: None,
+                filtered_categories,
+            },
+        }
+    }
+
+    /// Check which resolvers DNS actually goes to. `tunnel_addresses` are the
+    /// VPN server / exit addresses a tunnel resolver may appear as.
+    pub async fn test_leak(&self, targets: LeakTestTargets, vpn_connected: bool, tunnel_addresses: &[String]) -> DnsLeakTestResult {
+        let (protection_active, stub_active, mut expected, encrypted) = {
+            let active = self.active.lock().ok();
+            match active.as_ref().and_then(|a| a.as_ref()) {
+                Some(a) => (
+                    true,
+                    a.stub_address.is_some(),
+                    a.tunnel_resolvers
+                        .iter()
+                        .copied()
+                        .chain(a.settings.expected_egress.iter().filter_map(|s| s.parse().ok()))
+                        .collect::<HashSet<IpAddr>>(),
+                    a.settings.upstream != DnsUpstream::Tunnel,
+                ),
+                None => (false, false, HashSet::new(), false),
+            }
+        };
+        expected.extend(tunnel_addresses.iter().filter_map(|s| s.parse::<IpAddr>().ok()));
+
+        let mut observed: Vec<(IpAddr, &str)> = targets.system_resolvers.iter().map(|ip| (*ip, "system_config")).collect();
+
+        // Encrypted upstream egress belongs to the DoH/DoT provider, so only probe plain DNS
+        if !encrypted {
+            if let Some(server) = targets.probe_server {
+                match probe_resolver_egress(server).await {
+                    Ok(ips) => observed.extend(ips.into_iter().map(|ip| (IpAddr::V4(ip), "egress_probe"))),
+                    Err(e) => log::warn!("DNS egress probe via {} failed: {}", server, e),
+                }
+            }
+        }
+
+        let resolvers = classify_resolvers(&observed, &expected, stub_active);
+        let leaks: Vec<&str> = resolvers.iter().filter(|r| !r.expected).map(|r| r.address.as_str()).collect();
+        let leak_detected = vpn_connected && !leaks.is_empty();
+
+        let message = if !vpn_connected {
+            "VPN is not connected; DNS goes to the local network resolver".to_string()
+        } else if resolvers.is_empty() {
+            "No resolvers could be observed".to_string()
+        } else if leak_detected {
+            format!("DNS leak: queries reach resolvers outside the tunnel ({})", leaks.join(", "))
+        } else {
+            "No DNS leak detected: all queries resolve through the tunnel".to_string()
+        };
+
+        let mut expected_resolvers: Vec<String> = expected.iter().map(|ip| ip.to_string()).collect();
+        expected_resolvers.sort();
+
+        DnsLeakTestResult {
+            leak_detected,
+            protection_active,
+            expected_resolvers,
+            resolvers,
+            message,
+            tested_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
+
+impl Drop for VpnDnsGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.disable() {
+            log::error!("Failed to restore DNS on shutdown: {}", e);
+        }
+    }
+}
+
+/// Ask `server` who is resolving on our behalf
+async fn probe_resolver_egress(server: SocketAddr) -> Result<Vec<Ipv4Addr>> {
+    let id: u16 = rand::random();
+    let query = build_query(id, RESOLVER_WHOAMI_HOST);
+    let response = forward_udp(&query, &[server]).await?;
+    Ok(parse_a_records(&response))
+}
+
+// ============================================================================
+// LOOPBACK DNS STUB
+// ============================================================================
+
+async fn run_stub(
+    socket: UdpSocket,
+    upstream: DnsUpstream,
+    resolvers: Vec<IpAddr>,
+    filter: Arc<RwLock<DnsCategoryFilter>>,
+    cancel: CancellationToken,
+) {
+    let socket = Arc::new(socket);
+    let upstream = Arc::new(upstream);
+    let resolvers: Arc<Vec<SocketAddr>> = Arc::new(resolvers.into_iter().map(|ip| SocketAddr::new(ip, 53)).collect());
+    let client = reqwest::Client::new();
+    let mut buf = vec![0u8; 4096];
+
+    loop {
+        let (len, peer) = tokio::select! {
+            _ = cancel.cancelled() => break,
+            received = socket.recv_from(&mut buf) => match received {
+                Ok(r) => r,
+                Err(e) => {
+                    log::warn!("DNS stub receive error: {}", e);
+                    continue;
+                }
+            },
+        };
+        let query = buf[..len].to_vec();
+
+        let blocked = query_name(&query).and_then(|name| {
+            filter
+                .read()
+                .ok()
+                .and_then(|f| f.blocked_category(&name).map(|c| (name.clone(), c.to_string())))
+        });
+        if let Some((name, category)) = blocked {
+            log::debug!("DNS stub blocked {} ({})", name, category);
+            if let Some(response) = nxdomain_response(&query) {
+                let _ = socket.send_to(&response, peer).await;
+            }
+            continue;
+        }
+
+        let socket = socket.clone();
+        let upstream = upstream.clone();
+        let resolvers = resolvers.clone();
+        let client = client.clone();
+        tokio::spawn(async move {
+            let result = match upstream.as_ref() {
+                DnsUpstream::Tunnel => forward_udp(&query, &resolvers).await,
+                DnsUpstream::DnsOverHttps { url } => forward_doh(&client, url, &query).await,
+                DnsUpstream::DnsOverTls { server, tls_name } => forward_dot(server, tls_name, &query).await,
+            };
+            match result {
+                Ok(response) => {
+                    let _ = socket.send_to(&response, peer).await;
+                }
+                Err(e) => log::warn!("DNS upstream failed: {}", e),
+            }
+        });
+    }
+}
+
+async fn forward_udp(query: &[u8], servers: &[SocketAddr]) -> Result<Vec<u8>> {
+    let mut last_error = anyhow::anyhow!("No DNS servers available");
+    for server in servers {
+        let bind: SocketAddr = if server.is_ipv4() {
+            (Ipv4Addr::UNSPECIFIED, 0).into()
+        } else {
+            (Ipv6Addr::UNSPECIFIED, 0).into()
+        };
+        let socket = UdpSocket::bind(bind).await?;
+        socket.send_to(query, server).await?;
+        let mut buf = vec![0u8; 4096];
+        match tokio::time::timeout(UPSTREAM_TIMEOUT, socket.recv_from(&mut buf)).await {
+            Ok(Ok((len, from))) if from == *server => return Ok(buf[..len].to_vec()),
+            Ok(Ok((_, from))) => last_error = anyhow::anyhow!("Unexpected DNS response from {}", from),
+            Ok(Err(e)) => last_error = e.into(),
+            Err(_) => last_error = anyhow::anyhow!("DNS query to {} timed out", server),
+        }
+    }
+    Err(last_error)
+}
+
+async fn forward_doh(client: &reqwest::Client, url: &str, query: &[u8]) -> Result<Vec<u8>> {
+    let response = client
+        .post(url)
+        .header("content-type", "application/dns-message")
+        .header("accept", "application/dns-message")
+        .timeout(UPSTREAM_TIMEOUT)
+        .body(query.to_vec())
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+async fn forward_dot(server: &str, tls_name: &str, query: &[u8]) -> Result<Vec<u8>> {
+    use tokio_util::compat::{FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt};
+
+    let exchange = async {
+        let tcp = tokio::net::TcpStream::connect(server).await?;
+        let tls = async_native_tls::TlsConnector::new().connect(tls_name, tcp.compat()).await?;
+        let mut tls = tls.compat();
+        tls.write_all(&(query.len() as u16).to_be_bytes()).await?;
+        tls.write_all(query).await?;
+        tls.flush().await?;
+        let len = tls.read_u16().await? as usize;
+        let mut response = vec![0u8; len];
+        tls.read_exact(&mut response).await?;
+        Ok::<_, anyhow::Error>(response)
+    };
+    tokio::time::timeout(UPSTREAM_TIMEOUT, exchange)
+        .await
+        .map_err(|_| anyhow::anyhow!("DNS-over-TLS query to {} timed out", server))?
+}
+
+// ============================================================================
+// DNS WIRE FORMAT
+// ============================================================================
+
+/// Offset just past the first question, and its name
+fn read_question(packet: &[u8]) -> Option<(usize, String)> {
+    if packet.len() < 12 || u16::from_be_bytes([packet[4], packet[5]]) == 0 {
+        return None;
+    }
+    let mut pos = 12;
+    let mut labels = Vec::new();
+    loop {
+        let len = *packet.get(pos)? as usize;
+        pos += 1;
+        if len == 0 {
+            break;
+        }
+        if len & 0xC0 != 0 {
+            return None;
+        }
+        labels.push(String::from_utf8_lossy(packet.get(pos..pos + len)?).to_string());
+        pos += len;
+    }
+    // QTYPE + QCLASS
+    (packet.len() >= pos + 4).then(|| (pos + 4, labels.join(".")))
+}
+
+fn query_name(packet: &[u8]) -> Option<String> {
+    read_question(packet).map(|(_, name)| name)
+}
+
+/// NXDOMAIN answer echoing the query's question
+fn nxdomain_response(query: &[u8]) -> Option<Vec<u8>> {
+    let (end, _) = read_question(query)?;
+    let mut response = Vec::with_capacity(end);
+    response.extend_from_slice(&query[0..2]);
+    // QR, original opcode and RD, RA, RCODE=3
+    response.push(0x80 | (query[2] & 0x79));
+    response.push(0x80 | 0x03);
+    response.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+    response.extend_from_slice(&query[12..end]);
+    Some(response)
+}
+
+fn build_query(id: u16, name: &str) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(32 + name.len());
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.trim_end_matches('.').split('.') {
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&[0, 1, 0, 1]); // A, IN
+    packet
+}
+
+fn skip_name(packet: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *packet.get(pos)?;
+        if len & 0xC0 == 0xC0 {
+            return Some(pos + 2);
+        }
+        pos += 1;
+        if len == 0 {
+            return Some(pos);
+        }
+        pos += len as usize;
+    }
+}
+
+fn parse_a_records(packet: &[u8]) -> Vec<Ipv4Addr> {
+    let mut records = Vec::new();
+    let Some((mut pos, _)) = read_question(packet) else {
+        return records;
+    };
+    let answers = u16::from_be_bytes([packet[6], packet[7]]);
+    for _ in 0..answers {
+        let Some(after_name) = skip_name(packet, pos) else { break };
+        let Some(header) = packet.get(after_name..after_name + 10) else { break };
+        let rtype = u16::from_be_bytes([header[0], header[1]]);
+        let rdlen = u16::from_be_bytes([header[8], header[9]]) as usize;
+        let data_start = after_name + 10;
+        let Some(data) = packet.get(data_start..data_start + rdlen) else { break };
+        if rtype == 1 && rdlen == 4 {
+            records.push(Ipv4Addr::new(data[0], data[1], data[2], data[3]));
+        }
+        pos = data_start + rdlen;
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeDns {
+        applied: Mutex<Vec<Vec<IpAddr>>>,
+        restored: Mutex<Vec<SystemDnsSnapshot>>,
+    }
+
+    impl SystemDnsConfigurator for Arc<FakeDns> {
+        fn capture(&self) -> Result<SystemDnsSnapshot> {
+            Ok(SystemDnsSnapshot {
+                entries: vec![DnsSnapshotEntry {
+                    target: "eth0".to_string(),
+                    servers: vec!["192.168.1.1".to_string()],
+                    raw: None,
+                    link: None,
+                }],
+            })
+        }
+
+        fn apply(&self, _snapshot: &SystemDnsSnapshot, servers: &[IpAddr]) -> Result<()> {
+            self.applied.lock().unwrap().push(servers.to_vec());
+            Ok(())
+        }
+
+        fn restore(&self, snapshot: &SystemDnsSnapshot) -> Result<()> {
+            self.restored.lock().unwrap().push(snapshot.clone());
+            Ok(())
+        }
+    }
+
+    /// Resolver that answers every A query with `answer`
+    async fn fake_resolver(answer: Ipv4Addr) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            while let Ok((len, peer)) = socket.recv_from(&mut buf).await {
+                let query = &buf[..len];
+                let (end, _) = read_question(query).unwrap();
+                let mut response = query[..end].to_vec();
+                response[2] |= 0x80;
+                response[3] = 0x80;
+                response[6..8].copy_from_slice(&1u16.to_be_bytes());
+                response.extend_from_slice(&[0xC0, 0x0C, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4]);
+                response.extend_from_slice(&answer.octets());
+                socket.send_to(&response, peer).await.unwrap();
+            }
+        });
+        address
+    }
+
+    fn settings() -> DnsProtectionSettings {
+        DnsProtectionSettings {
+            stub_port: 0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_out_of_tunnel_resolver_is_a_leak() {
+        let expected: HashSet<IpAddr> = ["10.8.0.1".parse().unwrap()].into_iter().collect();
+        let isp: IpAddr = "203.0.113.53".parse().unwrap();
+        let tunnel: IpAddr = "10.8.0.1".parse().unwrap();
+
+        let result = classify_resolvers(&[(tunnel, "system_config"), (isp, "egress_probe")], &expected, false);
+        assert!(result[0].expected);
+        assert!(!result[1].expected);
+
+        // Loopback only counts when our stub is the one listening
+        let local: IpAddr = "127.0.0.53".parse().unwrap();
+        assert!(!classify_resolvers(&[(local, "system_config")], &expected, false)[0].expected);
+        assert!(classify_resolvers(&[(local, "system_config")], &expected, true)[0].expected);
+    }
+
+    #[tokio::test]
+    async fn test_leak_test_flags_isp_resolver_and_passes_tunnel() {
+        let fake = Arc::new(FakeDns::default());
+        let guard = VpnDnsGuard::new(Box::new(fake.clone()));
+        let status = guard.enable(settings(), &["10.8.0.1".to_string()]).await.unwrap();
+        assert!(status.active);
+        let stub: SocketAddr = status.stub_address.unwrap().parse().unwrap();
+        assert_eq!(fake.applied.lock().unwrap()[0], vec![stub.ip()]);
+
+        // System still configured with the ISP resolver, and the probe egresses there
+        let isp_probe = fake_resolver(Ipv4Addr::new(203, 0, 113, 53)).await;
+        let leaked = guard
+            .test_leak(
+                LeakTestTargets {
+                    system_resolvers: vec!["203.0.113.53".parse().unwrap()],
+                    probe_server: Some(isp_probe),
+                },
+                true,
+                &[],
+            )
+            .await;
+        assert!(leaked.leak_detected);
+        assert!(leaked.resolvers.iter().all(|r| !r.expected));
+        assert!(leaked.message.contains("203.0.113.53"));
+
+        // Through the stub, egressing from the tunnel resolver
+        let tunnel_probe = fake_resolver(Ipv4Addr::new(10, 8, 0, 1)).await;
+        let clean = guard
+            .test_leak(
+                LeakTestTargets {
+                    system_resolvers: vec![stub.ip()],
+                    probe_server: Some(tunnel_probe),
+                },
+                true,
+                &[],
+            )
+            .await;
+        assert!(!clean.leak_detected, "{:?}", clean.resolvers);
+        assert_eq!(clean.resolvers.len(), 2);
+
+        guard.disable().unwrap();
+        assert!(!guard.is_active());
+        assert_eq!(fake.restored.lock().unwrap().len(), 1);
+        assert_eq!(fake.restored.lock().unwrap()[0].entries[0].servers, vec!["192.168.1.1"]);
+    }
+
+    #[tokio::test]
+    async fn test_stub_answers_blocked_categories_with_nxdomain() {
+        let guard = VpnDnsGuard::new(Box::new(Arc::new(FakeDns::default())));
+        guard.set_blocked_categories(vec!["ads".to_string()]);
+        let status = guard.enable(settings(), &["10.8.0.1".to_string()]).await.unwrap();
+        assert_eq!(status.filtered_categories, vec!["ads"]);
+        let stub: SocketAddr = status.stub_address.unwrap().parse().unwrap();
+
+        let blocked = forward_udp(&build_query(7, "stats.doubleclick.net"), &[stub]).await.unwrap();
+        assert_eq!(&blocked[0..2], &7u16.to_be_bytes());
+        assert_eq!(blocked[3] & 0x0F, 3);
+
+        guard.set_blocked_categories(vec![]);
+        assert!(guard.filter.read().unwrap().blocked_category("stats.doubleclick.net").is_none());
+    }
+}
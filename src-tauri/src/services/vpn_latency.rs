@@ -0,0 +1,308 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+
+/// How long a latency measurement is trusted before the server is probed again
+const MEASUREMENT_TTL: Duration = Duration::from_secs(60);
+
+/// Handshakes attempted per server; the fastest one counts
+const PROBE_SAMPLES: usize = 3;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Servers this close to the fastest one compete on load instead of latency
+const LOAD_TIE_TOLERANCE: f64 = 1.25;
+
+const SPEED_TEST_BASE_URL: &str = "https://speed.cloudflare.com";
+const SPEED_TEST_DOWNLOAD_BYTES: usize = 10_000_000;
+const SPEED_TEST_UPLOAD_BYTES: usize = 2_000_000;
+const SPEED_TEST_PINGS: usize = 5;
+
+/// Latency of one server, or why it has none
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyMeasurement {
+    pub server_id: String,
+    pub reachable: bool,
+    pub latency_ms: Option<u32>,
+    /// Unix seconds
+    pub measured_at: u64,
+    pub cached: bool,
+}
+
+/// A server taking part in a latency test
+#[derive(Debug, Clone)]
+pub struct LatencyCandidate {
+    pub server_id: String,
+    pub address: String,
+    pub load: u8,
+}
+
+/// In-tunnel throughput report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedTestResult {
+    pub server_id: Option<String>,
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+    pub latency_ms: u32,
+    pub jitter_ms: u32,
+    pub tested_at: u64,
+}
+
+/// Times a single handshake with a server, `None` when it does not answer
+#[async_trait]
+pub trait LatencyProbe: Send + Sync {
+    async fn probe(&self, address: &str) -> Option<Duration>;
+}
+
+/// TCP handshake against the server's HTTPS port. Connecting does not need
+/// the tunnel, so it works for servers we are not connected to.
+pub struct TcpHandshakeProbe {
+    pub port: u16,
+}
+
+#[async_trait]
+impl LatencyProbe for TcpHandshakeProbe {
+    async fn probe(&self, address: &str) -> Option<Duration> {
+        let started = Instant::now();
+        match tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect((address, self.port))).await {
+            Ok(Ok(_)) => Some(started.elapsed()),
+            _ => None,
+        }
+    }
+}
+
+/// Measures and caches server latencies
+pub struct VpnLatencyTester {
+    probe: Box<dyn LatencyProbe>,
+    cache: Mutex<HashMap<String, (Instant, LatencyMeasurement)>>,
+    ttl: Duration,
+}
+
+impl Default for VpnLatencyTester {
+    fn default() -> Self {
+        Self::new(Box::new(TcpHandshakeProbe { port: 443 }))
+    }
+}
+
+impl VpnLatencyTester {
+    pub fn new(probe: Box<dyn LatencyProbe>) -> Self {
+        Self {
+            probe,
+            cache: Mutex::new(HashMap::new()),
+            ttl: MEASUREMENT_TTL,
+        }
+    }
+
+    /// Measure all candidates concurrently. Fresh cached results are reused.
+    pub async fn measure(&self, candidates: &[LatencyCandidate]) -> Vec<LatencyMeasurement> {
+        let mut results = HashMap::new();
+        let mut stale = Vec::new();
+        {
+            let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            for candidate in candidates {
+                match cache.get(&candidate.server_id) {
+                    Some((at, m)) if at.elapsed() < self.ttl => {
+                        results.insert(candidate.server_id.clone(), LatencyMeasurement { cached: true, ..m.clone() });
+                    }
+                    _ => stale.push(candidate),
+                }
+            }
+        }
+
+        let fresh = futures::future::join_all(stale.iter().map(|c| self.measure_one(c))).await;
+        {
+            let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            for m in fresh {
+                cache.insert(m.server_id.clone(), (Instant::now(), m.clone()));
+                results.insert(m.server_id.clone(), m);
+            }
+        }
+
+        candidates.iter().filter_map(|c| results.remove(&c.server_id)).collect()
+    }
+
+    async fn measure_one(&self, candidate: &LatencyCandidate) -> LatencyMeasurement {
+        let mut best: Option<Duration> = None;
+        for _ in 0..PROBE_SAMPLES {
+            if let Some(rtt) = self.probe.probe(&candidate.address).await {
+                best = Some(best.map_or(rtt, |b| b.min(rtt)));
+            }
+        }
+
+        LatencyMeasurement {
+            server_id: candidate.server_id.clone(),
+            reachable: best.is_some(),
+            latency_ms: best.map(|d| d.as_millis().min(u32::MAX as u128) as u32),
+            measured_at: unix_now(),
+            cached: false,
+        }
+    }
+
+    /// Measure the candidates and return the fastest reachable one
+    pub async fn fastest(&self, candidates: &[LatencyCandidate], prefer_least_loaded: bool) -> Result<(LatencyCandidate, LatencyMeasurement)> {
+        let measurements = self.measure(candidates).await;
+        let id = pick_fastest(candidates, &measurements, prefer_least_loaded)
+            .ok_or_else(|| anyhow!("None of the {} candidate servers is reachable", candidates.len()))?;
+
+        let candidate = candidates.iter().find(|c| c.server_id == id).cloned();
+        let measurement = measurements.into_iter().find(|m| m.server_id == id);
+        candidate.zip(measurement).ok_or_else(|| anyhow!("Server {} disappeared during selection", id))
+    }
+
+    pub fn clear_cache(&self) {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+/// Lowest latency wins; unreachable servers are excluded. With
+/// `prefer_least_loaded`, servers within the tie tolerance of the fastest one
+/// are ranked by load instead.
+pub fn pick_fastest(candidates: &[LatencyCandidate], measurements: &[LatencyMeasurement], prefer_least_loaded: bool) -> Option<String> {
+    let loads: HashMap<&str, u8> = candidates.iter().map(|c| (c.server_id.as_str(), c.load)).collect();
+    let mut reachable: Vec<(&str, u32, u8)> = measurements
+        .iter()
+        .filter_map(|m| {
+            let load = *loads.get(m.server_id.as_str())?;
+            Some((m.server_id.as_str(), m.latency_ms.filter(|_| m.reachable)?, load))
+        })
+        .collect();
+    reachable.sort_by_key(|&(id, latency, _)| (latency, id));
+
+    let &(fastest_id, fastest_latency, _) = reachable.first()?;
+    if !prefer_least_loaded {
+        return Some(fastest_id.to_string());
+    }
+
+    let limit = (fastest_latency as f64 * LOAD_TIE_TOLERANCE).ceil() as u32;
+    reachable
+        .iter()
+        .filter(|(_, latency, _)| *latency <= limit)
+        .min_by_key(|&&(_, latency, load)| (load, latency))
+        .map(|(id, _, _)| id.to_string())
+}
+
+/// Download, upload and round-trip test against a public speed endpoint. Must
+/// run while connected so the traffic goes through the tunnel.
+pub async fn run_speed_test(server_id: Option<String>) -> Result<SpeedTestResult> {
+    let client = reqwest::Client::builder().timeout(Duration::from_secs(30)).build()?;
+
+    let mut pings = Vec::with_capacity(SPEED_TEST_PINGS);
+    for _ in 0..SPEED_TEST_PINGS {
+        let started = Instant::now();
+        client.get(format!("{}/__down?bytes=0", SPEED_TEST_BASE_URL)).send().await?.error_for_status()?;
+        pings.push(started.elapsed().as_millis() as u32);
+    }
+    let latency_ms = pings.iter().copied().min().unwrap_or(0);
+    let jitter_ms = if pings.len() > 1 {
+        pings.windows(2).map(|w| w[0].abs_diff(w[1])).sum::<u32>() / (pings.len() as u32 - 1)
+    } else {
+        0
+    };
+
+    let started = Instant::now();
+    let body = client
+        .get(format!("{}/__down?bytes={}", SPEED_TEST_BASE_URL, SPEED_TEST_DOWNLOAD_BYTES))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let download_mbps = megabits_per_second(body.len(), started.elapsed());
+
+    let started = Instant::now();
+    client
+        .post(format!("{}/__up", SPEED_TEST_BASE_URL))
+        .body(vec![0u8; SPEED_TEST_UPLOAD_BYTES])
+        .send()
+        .await?
+        .error_for_status()?;
+    let upload_mbps = megabits_per_second(SPEED_TEST_UPLOAD_BYTES, started.elapsed());
+
+    Ok(SpeedTestResult {
+        server_id,
+        download_mbps,
+        upload_mbps,
+        latency_ms,
+        jitter_ms,
+        tested_at: unix_now(),
+    })
+}
+
+fn megabits_per_second(bytes: usize, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64().max(f64::EPSILON);
+    (bytes as f64 * 8.0 / 1_000_000.0 / secs * 100.0).round() / 100.0
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    struct MockProbe {
+        latencies: HashMap<&'static str, Option<u64>>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl LatencyProbe for MockProbe {
+        async fn probe(&self, address: &str) -> Option<Duration> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.latencies.get(address).copied().flatten().map(Duration::from_millis)
+        }
+    }
+
+    fn candidate(id: &str, address: &str, load: u8) -> LatencyCandidate {
+        LatencyCandidate { server_id: id.to_string(), address: address.to_string(), load }
+    }
+
+    #[tokio::test]
+    async fn test_fastest_reachable_server_is_chosen() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let tester = VpnLatencyTester::new(Box::new(MockProbe {
+            latencies: HashMap::from([
+                ("10.0.0.1", Some(80)),
+                ("10.0.0.2", Some(25)),
+                ("10.0.0.3", None),
+                ("10.0.0.4", Some(30)),
+            ]),
+            calls: calls.clone(),
+        }));
+        let candidates = vec![
+            candidate("us-ny-01", "10.0.0.1", 10),
+            candidate("us-la-01", "10.0.0.2", 90),
+            candidate("us-sf-01", "10.0.0.3", 0),
+            candidate("us-chi-01", "10.0.0.4", 15),
+        ];
+
+        let (server, measurement) = tester.fastest(&candidates, false).await.unwrap();
+        assert_eq!(server.server_id, "us-la-01");
+        assert_eq!(measurement.latency_ms, Some(25));
+
+        // Unreachable servers are excluded, never picked for their zero load
+        let measurements = tester.measure(&candidates).await;
+        let unreachable = measurements.iter().find(|m| m.server_id == "us-sf-01").unwrap();
+        assert!(!unreachable.reachable && unreachable.latency_ms.is_none());
+        assert_eq!(pick_fastest(&candidates, &measurements, true).as_deref(), Some("us-chi-01"));
+
+        // The second round came from the cache
+        assert!(measurements.iter().all(|m| m.cached));
+        assert_eq!(calls.load(Ordering::SeqCst), candidates.len() * PROBE_SAMPLES);
+
+        let offline = vec![candidate("us-sf-01", "10.0.0.3", 0)];
+        assert!(tester.fastest(&offline, false).await.is_err());
+    }
+}
@@ -0,0 +1,733 @@
+// WebSocket Inspector - records WebSocket connections and frames for DevTools
+// Pages connect through a loopback tap (ws://127.0.0.1:<port>/?tab=<id>&url=<target>)
+// that relays bytes unchanged to the real server while decoding both directions.
+
+use flate2::{Decompress, FlushDecompress, Status};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Mutex;
+use tokio_util::compat::{FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt};
+
+pub const DEFAULT_FRAME_LIMIT: usize = 500;
+/// Text payloads longer than this are truncated in the log
+const MAX_TEXT_PAYLOAD: usize = 64 * 1024;
+const BINARY_PREVIEW_BYTES: usize = 64;
+const MAX_HANDSHAKE_BYTES: usize = 16 * 1024;
+/// Upper bound for a single reassembled message, compressed or not
+const MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebSocketOpcode {
+    Continuation,
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong,
+    Reserved(u8),
+}
+
+impl WebSocketOpcode {
+    fn is_control(self) -> bool {
+        match self {
+            Self::Close | Self::Ping | Self::Pong => true,
+            Self::Reserved(code) => code >= 0x8,
+            _ => false,
+        }
+    }
+}
+
+impl From<u8> for WebSocketOpcode {
+    fn from(code: u8) -> Self {
+        match code {
+            0x0 => Self::Continuation,
+            0x1 => Self::Text,
+            0x2 => Self::Binary,
+            0x8 => Self::Close,
+            0x9 => Self::Ping,
+            0xA => Self::Pong,
+            other => Self::Reserved(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameDirection {
+    Sent,
+    Received,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebSocketStatus {
+    Connecting,
+    Open,
+    Closed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSocketFrame {
+    pub direction: FrameDirection,
+    pub opcode: WebSocketOpcode,
+    /// Payload bytes on the wire, summed over fragments
+    pub payload_size: usize,
+    /// Size after permessage-deflate decompression, for compressed messages
+    pub decompressed_size: Option<usize>,
+    pub fragments: usize,
+    pub text: Option<String>,
+    pub text_truncated: bool,
+    /// Hex of the first bytes of binary and control payloads
+    pub binary_preview: Option<String>,
+    pub close_code: Option<u16>,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSocketConnection {
+    pub id: String,
+    pub tab_id: String,
+    pub url: String,
+    pub status: WebSocketStatus,
+    pub extensions: Option<String>,
+    pub opened_at: i64,
+    pub closed_at: Option<i64>,
+    pub close_code: Option<u16>,
+    pub close_reason: Option<String>,
+    pub error: Option<String>,
+    pub frames: VecDeque<WebSocketFrame>,
+    /// Oldest frames dropped to stay within the frame limit
+    pub dropped_frames: usize,
+}
+
+/// Recorded connections per tab plus the loopback tap that feeds them
+#[derive(Clone)]
+pub struct WebSocketInspector {
+    connections: Arc<RwLock<HashMap<String, Vec<WebSocketConnection>>>>,
+    frame_limit: Arc<AtomicUsize>,
+    port: Arc<Mutex<Option<u16>>>,
+}
+
+impl Default for WebSocketInspector {
+    fn default() -> Self {
+        Self {
+            connections: Arc::new(RwLock::new(HashMap::new())),
+            frame_limit: Arc::new(AtomicUsize::new(DEFAULT_FRAME_LIMIT)),
+            port: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl WebSocketInspector {
+    pub fn set_frame_limit(&self, limit: usize) {
+        self.frame_limit.store(limit.max(1), Ordering::Relaxed);
+    }
+
+    pub fn get_connections(&self, tab_id: &str) -> Result<Vec<WebSocketConnection>, String> {
+        let connections = self.connections.read().map_err(|e| format!("Lock error: {}", e))?;
+        Ok(connections.get(tab_id).cloned().unwrap_or_default())
+    }
+
+    pub fn clear(&self, tab_id: &str) -> Result<(), String> {
+        let mut connections = self.connections.write().map_err(|e| format!("Lock error: {}", e))?;
+        connections.remove(tab_id);
+        Ok(())
+    }
+
+    /// Loopback URL a page should open instead of `target_url` to be inspected
+    pub async fn tap_url(&self, tab_id: &str, target_url: &str) -> Result<String, String> {
+        let target = url::Url::parse(target_url).map_err(|e| format!("Invalid WebSocket URL: {}", e))?;
+        if !matches!(target.scheme(), "ws" | "wss") {
+            return Err(format!("Unsupported WebSocket scheme: {}", target.scheme()));
+        }
+        let port = self.ensure_listening().await?;
+        Ok(format!(
+            "ws://127.0.0.1:{}/?tab={}&url={}",
+            port,
+            urlencoding::encode(tab_id),
+            urlencoding::encode(target_url)
+        ))
+    }
+
+    async fn ensure_listening(&self) -> Result<u16, String> {
+        let mut port = self.port.lock().await;
+        if let Some(port) = *port {
+            return Ok(port);
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| format!("Failed to start WebSocket inspector: {}", e))?;
+        let bound = listener.local_addr().map_err(|e| e.to_string())?.port();
+        let inspector = self.clone();
+        tokio::spawn(async move {
+            while let Ok((client, _)) = listener.accept().await {
+                let inspector = inspector.clone();
+                tokio::spawn(async move { inspector.relay(client).await });
+            }
+        });
+
+        log::info!("🔌 WebSocket inspector listening on 127.0.0.1:{}", bound);
+        *port = Some(bound);
+        Ok(bound)
+    }
+
+    fn with_connection(&self, tab_id: &str, id: &str, update: impl FnOnce(&mut WebSocketConnection)) {
+        if let Ok(mut connections) = self.connections.write() {
+            if let Some(connection) = connections
+                .get_mut(tab_id)
+                .and_then(|list| list.iter_mut().find(|c| c.id == id))
+            {
+                update(connection);
+            }
+        }
+    }
+
+    fn record_frame(&self, tab_id: &str, id: &str, frame: WebSocketFrame) {
+        let limit = self.frame_limit.load(Ordering::Relaxed);
+        self.with_connection(tab_id, id, |connection| {
+            if frame.opcode == WebSocketOpcode::Close {
+                connection.close_code = connection.close_code.or(frame.close_code);
+                connection.close_reason = connection.close_reason.take().or(frame.text.clone());
+            }
+            connection.frames.push_back(frame);
+            while connection.frames.len() > limit {
+                connection.frames.pop_front();
+                connection.dropped_frames += 1;
+            }
+        });
+    }
+
+    async fn relay(&self, mut client: TcpStream) {
+        let (head, rest) = match read_head(&mut client).await {
+            Ok(head) => head,
+            Err(e) => {
+                log::warn!("WebSocket inspector rejected connection: {}", e);
+                return;
+            }
+        };
+        let Some((tab_id, target, request)) = rewrite_request(&head) else {
+            let _ = client.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n").await;
+            return;
+        };
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let connection = WebSocketConnection {
+            id: id.clone(),
+            tab_id: tab_id.clone(),
+            url: target.to_string(),
+            status: WebSocketStatus::Connecting,
+            extensions: None,
+            opened_at: chrono::Utc::now().timestamp_millis(),
+            closed_at: None,
+            close_code: None,
+            close_reason: None,
+            error: None,
+            frames: VecDeque::new(),
+            dropped_frames: 0,
+        };
+        if let Ok(mut connections) = self.connections.write() {
+            connections.entry(tab_id.clone()).or_default().push(connection);
+        }
+
+        if let Err(e) = self.relay_upstream(client, &tab_id, &id, &target, request, rest).await {
+            self.with_connection(&tab_id, &id, |connection| {
+                connection.status = WebSocketStatus::Failed;
+                connection.error = Some(e);
+            });
+        }
+        self.with_connection(&tab_id, &id, |connection| {
+            if connection.status == WebSocketStatus::Open {
+                connection.status = WebSocketStatus::Closed;
+            }
+            connection.closed_at = Some(chrono::Utc::now().timestamp_millis());
+        });
+    }
+
+    async fn relay_upstream(
+        &self,
+        mut client: TcpStream,
+        tab_id: &str,
+        id: &str,
+        target: &url::Url,
+        request: Vec<u8>,
+        client_rest: Vec<u8>,
+    ) -> Result<(), String> {
+        let mut upstream = connect_upstream(target).await?;
+        upstream.write_all(&request).await.map_err(|e| e.to_string())?;
+        upstream.write_all(&client_rest).await.map_err(|e| e.to_string())?;
+
+        let (response, server_rest) = read_head(&mut upstream).await?;
+        client.write_all(&response).await.map_err(|e| e.to_string())?;
+        let response = String::from_utf8_lossy(&response).to_string();
+        if !response.starts_with("HTTP/1.1 101") {
+            let status = response.lines().next().unwrap_or_default().to_string();
+            return Err(format!("Upgrade refused: {}", status));
+        }
+
+        let extensions = header_value(&response, "sec-websocket-extensions");
+        let deflate = extensions.as_deref().and_then(PerMessageDeflate::parse);
+        self.with_connection(tab_id, id, |connection| {
+            connection.status = WebSocketStatus::Open;
+            connection.extensions = extensions.clone();
+        });
+
+        let mut sent = FrameDecoder::new(FrameDirection::Sent, deflate.map(|d| d.client_no_context_takeover));
+        let mut received = FrameDecoder::new(FrameDirection::Received, deflate.map(|d| d.server_no_context_takeover));
+        for frame in received.push(&server_rest)? {
+            self.record_frame(tab_id, id, frame);
+        }
+        client.write_all(&server_rest).await.map_err(|e| e.to_string())?;
+
+        let (mut client_read, mut client_write) = client.into_split();
+        let (mut upstream_read, mut upstream_write) = tokio::io::split(upstream);
+        let outbound = async {
+            let mut buf = vec![0u8; 16 * 1024];
+            loop {
+                let n = client_read.read(&mut buf).await.map_err(|e| e.to_string())?;
+                if n == 0 {
+                    break;
+                }
+                // Record before forwarding so a reply can never be logged ahead of its request
+                for frame in sent.push(&buf[..n])? {
+                    self.record_frame(tab_id, id, frame);
+                }
+                upstream_write.write_all(&buf[..n]).await.map_err(|e| e.to_string())?;
+            }
+            let _ = upstream_write.shutdown().await;
+            Ok::<(), String>(())
+        };
+        let inbound = async {
+            let mut buf = vec![0u8; 16 * 1024];
+            loop {
+                let n = upstream_read.read(&mut buf).await.map_err(|e| e.to_string())?;
+                if n == 0 {
+                    break;
+                }
+                for frame in received.push(&buf[..n])? {
+                    self.record_frame(tab_id, id, frame);
+                }
+                client_write.write_all(&buf[..n]).await.map_err(|e| e.to_string())?;
+            }
+            let _ = client_write.shutdown().await;
+            Ok::<(), String>(())
+        };
+
+        let (outbound, inbound) = tokio::join!(outbound, inbound);
+        outbound.and(inbound)
+    }
+}
+
+trait UpstreamStream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> UpstreamStream for T {}
+
+async fn connect_upstream(target: &url::Url) -> Result<Box<dyn UpstreamStream>, String> {
+    let host = target.host_str().ok_or("WebSocket URL has no host")?;
+    let port = target.port_or_known_default().ok_or("WebSocket URL has no port")?;
+    let tcp = TcpStream::connect((host, port))
+        .await
+        .map_err(|e| format!("Failed to connect to {}:{}: {}", host, port, e))?;
+
+    if target.scheme() == "wss" {
+        let tls = async_native_tls::connect(host, tcp.compat())
+            .await
+            .map_err(|e| format!("TLS handshake with {} failed: {}", host, e))?;
+        Ok(Box::new(tls.compat()))
+    } else {
+        Ok(Box::new(tcp))
+    }
+}
+
+/// Reads an HTTP head up to the blank line, returning it and any bytes after it
+async fn read_head(stream: &mut (impl AsyncRead + Unpin)) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        if let Some(end) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            let rest = data.split_off(end + 4);
+            return Ok((data, rest));
+        }
+        if data.len() > MAX_HANDSHAKE_BYTES {
+            return Err("Handshake too large".to_string());
+        }
+        let n = stream.read(&mut buf).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("Connection closed during handshake".to_string());
+        }
+        data.extend_from_slice(&buf[..n]);
+    }
+}
+
+/// Extracts the tab and target from a tap request and rewrites the request
+/// line and Host header for the real server. Other headers pass through.
+fn rewrite_request(head: &[u8]) -> Option<(String, url::Url, Vec<u8>)> {
+    let head = std::str::from_utf8(head).ok()?;
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let (method, path) = (request_line.next()?, request_line.next()?);
+    if method != "GET" {
+        return None;
+    }
+
+    let query = url::Url::parse(&format!("http://127.0.0.1{}", path)).ok()?;
+    let params: HashMap<_, _> = query.query_pairs().into_owned().collect();
+    let target = url::Url::parse(params.get("url")?).ok()?;
+    if !matches!(target.scheme(), "ws" | "wss") {
+        return None;
+    }
+
+    let mut host = target.host_str()?.to_string();
+    if let Some(port) = target.port() {
+        host = format!("{}:{}", host, port);
+    }
+    let target_path = match target.query() {
+        Some(q) => format!("{}?{}", target.path(), q),
+        None => target.path().to_string(),
+    };
+
+    let mut request = format!("GET {} HTTP/1.1\r\nHost: {}\r\n", target_path, host);
+    for line in lines.filter(|l| !l.is_empty() && !l.to_ascii_lowercase().starts_with("host:")) {
+        request.push_str(line);
+        request.push_str("\r\n");
+    }
+    request.push_str("\r\n");
+
+    Some((params.get("tab")?.clone(), target, request.into_bytes()))
+}
+
+fn header_value(head: &str, name: &str) -> Option<String> {
+    head.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim().to_string())
+    })
+}
+
+/// Negotiated permessage-deflate parameters (RFC 7692)
+#[derive(Debug, Clone, Copy)]
+struct PerMessageDeflate {
+    client_no_context_takeover: bool,
+    server_no_context_takeover: bool,
+}
+
+impl PerMessageDeflate {
+    fn parse(extensions: &str) -> Option<Self> {
+        extensions.split(',').find_map(|extension| {
+            let mut params = extension.split(';').map(str::trim);
+            (params.next()? == "permessage-deflate").then(|| {
+                let params: Vec<&str> = params.collect();
+                Self {
+                    client_no_context_takeover: params.contains(&"client_no_context_takeover"),
+                    server_no_context_takeover: params.contains(&"server_no_context_takeover"),
+                }
+            })
+        })
+    }
+}
+
+/// Incremental RFC 6455 frame parser for one direction of a connection.
+/// Fragmented messages are logged once complete, control frames immediately.
+struct FrameDecoder {
+    direction: FrameDirection,
+    buffer: Vec<u8>,
+    /// Inflater when permessage-deflate was negotiated, and whether it resets per message
+    inflater: Option<(Decompress, bool)>,
+    message: Option<PartialMessage>,
+}
+
+struct PartialMessage {
+    opcode: WebSocketOpcode,
+    compressed: bool,
+    wire_size: usize,
+    fragments: usize,
+    payload: Vec<u8>,
+}
+
+impl FrameDecoder {
+    fn new(direction: FrameDirection, deflate_no_context_takeover: Option<bool>) -> Self {
+        Self {
+            direction,
+            buffer: Vec::new(),
+            inflater: deflate_no_context_takeover.map(|reset| (Decompress::new(false), reset)),
+            message: None,
+        }
+    }
+
+    fn push(&mut self, data: &[u8]) -> Result<Vec<WebSocketFrame>, String> {
+        self.buffer.extend_from_slice(data);
+        let mut frames = Vec::new();
+
+        while let Some((header_len, payload_len)) = self.frame_bounds()? {
+            let frame: Vec<u8> = self.buffer.drain(..header_len + payload_len).collect();
+            let (fin, rsv1, opcode) = (frame[0] & 0x80 != 0, frame[0] & 0x40 != 0, frame[0] & 0x0F);
+            let mut payload = frame[header_len..].to_vec();
+            if frame[1] & 0x80 != 0 {
+                let mask = &frame[header_len - 4..header_len];
+                for (i, byte) in payload.iter_mut().enumerate() {
+                    *byte ^= mask[i % 4];
+                }
+            }
+
+            let opcode = WebSocketOpcode::from(opcode);
+            if opcode.is_control() {
+                frames.push(self.build_frame(opcode, payload_len, None, 1, &payload));
+                continue;
+            }
+
+            let message = match (opcode, self.message.take()) {
+                (WebSocketOpcode::Continuation, Some(mut message)) => {
+                    message.fragments += 1;
+                    message.wire_size += payload_len;
+                    message.payload.extend_from_slice(&payload);
+                    message
+                }
+                (WebSocketOpcode::Continuation, None) => return Err("Unexpected continuation frame".to_string()),
+                (opcode, _) => PartialMessage {
+                    opcode,
+                    compressed: rsv1 && self.inflater.is_some(),
+                    wire_size: payload_len,
+                    fragments: 1,
+                    payload,
+                },
+            };
+            if message.payload.len() > MAX_MESSAGE_BYTES {
+                return Err("WebSocket message too large to inspect".to_string());
+            }
+            if !fin {
+                self.message = Some(message);
+                continue;
+            }
+
+            let (payload, decompressed_size) = if message.compressed {
+                let inflated = self.inflate(&message.payload)?;
+                let size = inflated.len();
+                (inflated, Some(size))
+            } else {
+                (message.payload, None)
+            };
+            frames.push(self.build_frame(message.opcode, message.wire_size, decompressed_size, message.fragments, &payload));
+        }
+
+        Ok(frames)
+    }
+
+    /// Header and payload length of the next complete frame in the buffer
+    fn frame_bounds(&self) -> Result<Option<(usize, usize)>, String> {
+        let buf = &self.buffer;
+        if buf.len() < 2 {
+            return Ok(None);
+        }
+        let masked = buf[1] & 0x80 != 0;
+        let (mut header_len, payload_len) = match buf[1] & 0x7F {
+            126 if buf.len() >= 4 => (4, u16::from_be_bytes([buf[2], buf[3]]) as u64),
+            127 if buf.len() >= 10 => (10, u64::from_be_bytes(buf[2..10].try_into().unwrap())),
+            126 | 127 => return Ok(None),
+            len => (2, len as u64),
+        };
+        if payload_len > MAX_MESSAGE_BYTES as u64 {
+            return Err("WebSocket frame too large to inspect".to_string());
+        }
+        if masked {
+            header_len += 4;
+        }
+        let payload_len = payload_len as usize;
+        Ok((buf.len() >= header_len + payload_len).then_some((header_len, payload_len)))
+    }
+
+    fn inflate(&mut self, payload: &[u8]) -> Result<Vec<u8>, String> {
+        let (inflater, reset) = self.inflater.as_mut().ok_or("Compressed frame without permessage-deflate")?;
+        let mut input = payload.to_vec();
+        input.extend_from_slice(&[0x00, 0x00, 0xFF, 0xFF]);
+
+        let mut output = Vec::new();
+        let mut consumed = 0;
+        loop {
+            output.reserve(16 * 1024);
+            let (before_in, before_out) = (inflater.total_in(), inflater.total_out());
+            let status = inflater
+                .decompress_vec(&input[consumed..], &mut output, FlushDecompress::Sync)
+                .map_err(|e| format!("permessage-deflate error: {}", e))?;
+            consumed += (inflater.total_in() - before_in) as usize;
+            let stalled = inflater.total_out() == before_out;
+            if status == Status::StreamEnd || (consumed >= input.len() && (stalled || output.len() < output.capacity())) {
+                break;
+            }
+            if output.len() > MAX_MESSAGE_BYTES {
+                return Err("WebSocket message too large to inspect".to_string());
+            }
+        }
+        if *reset {
+            inflater.reset(false);
+        }
+        Ok(output)
+    }
+
+    fn build_frame(
+        &self,
+        opcode: WebSocketOpcode,
+        payload_size: usize,
+        decompressed_size: Option<usize>,
+        fragments: usize,
+        payload: &[u8],
+    ) -> WebSocketFrame {
+        let text_payload = match opcode {
+            WebSocketOpcode::Text => Some(payload),
+            WebSocketOpcode::Close if payload.len() > 2 => Some(&payload[2..]),
+            _ => None,
+        };
+        let text = text_payload.map(|text| String::from_utf8_lossy(&text[..text.len().min(MAX_TEXT_PAYLOAD)]).to_string());
+        let binary_preview = (opcode != WebSocketOpcode::Text)
+            .then(|| hex::encode(&payload[..payload.len().min(BINARY_PREVIEW_BYTES)]));
+        let close_code = (opcode == WebSocketOpcode::Close && payload.len() >= 2)
+            .then(|| u16::from_be_bytes([payload[0], payload[1]]));
+
+        WebSocketFrame {
+            direction: self.direction,
+            opcode,
+            payload_size,
+            decompressed_size,
+            fragments,
+            text,
+            text_truncated: text_payload.is_some_and(|text| text.len() > MAX_TEXT_PAYLOAD),
+            binary_preview,
+            close_code,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compress, Compression, FlushCompress};
+
+    fn client_frame(first_byte: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![first_byte, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    async fn read_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let mut header = [0u8; 2];
+        stream.read_exact(&mut header).await.unwrap();
+        let mut payload = vec![0u8; (header[1] & 0x7F) as usize];
+        stream.read_exact(&mut payload).await.unwrap();
+        (header[0], payload)
+    }
+
+    /// Echoes every frame back unmasked, answering pings with pongs
+    async fn echo_server() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (head, _) = read_head(&mut stream).await.unwrap();
+            assert!(String::from_utf8_lossy(&head).starts_with("GET /chat?room=1 HTTP/1.1"));
+            stream
+                .write_all(b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: test\r\nSec-WebSocket-Extensions: permessage-deflate; client_no_context_takeover; server_no_context_takeover\r\n\r\n")
+                .await
+                .unwrap();
+            loop {
+                let mut header = [0u8; 6];
+                if stream.read_exact(&mut header).await.is_err() {
+                    break;
+                }
+                let mut payload = vec![0u8; (header[1] & 0x7F) as usize];
+                stream.read_exact(&mut payload).await.unwrap();
+                for (i, byte) in payload.iter_mut().enumerate() {
+                    *byte ^= header[2 + i % 4];
+                }
+                let first = if header[0] & 0x0F == 0x9 { 0x8A } else { header[0] };
+                let mut reply = vec![first, payload.len() as u8];
+                reply.extend_from_slice(&payload);
+                stream.write_all(&reply).await.unwrap();
+                if header[0] & 0x0F == 0x8 {
+                    break;
+                }
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn test_frames_logged_in_order_through_tap() {
+        let inspector = WebSocketInspector::default();
+        let echo_port = echo_server().await;
+        let tap = inspector
+            .tap_url("tab-1", &format!("ws://127.0.0.1:{}/chat?room=1", echo_port))
+            .await
+            .unwrap();
+        let tap = url::Url::parse(&tap).unwrap();
+
+        let mut client = TcpStream::connect(("127.0.0.1", tap.port().unwrap())).await.unwrap();
+        let request = format!(
+            "GET /?{} HTTP/1.1\r\nHost: 127.0.0.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            tap.query().unwrap()
+        );
+        client.write_all(request.as_bytes()).await.unwrap();
+        let (response, _) = read_head(&mut client).await.unwrap();
+        assert!(String::from_utf8_lossy(&response).starts_with("HTTP/1.1 101"));
+
+        let mut compress = Compress::new(Compression::default(), false);
+        let mut deflated = Vec::with_capacity(64);
+        compress.compress_vec(b"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa", &mut deflated, FlushCompress::Sync).unwrap();
+        deflated.truncate(deflated.len() - 4);
+
+        for frame in [
+            client_frame(0x81, b"hello"),
+            client_frame(0x82, &[1, 2, 3]),
+            client_frame(0xC1, &deflated),
+            client_frame(0x89, b"ping"),
+            client_frame(0x88, &[0x03, 0xE8, b'b', b'y', b'e']),
+        ] {
+            client.write_all(&frame).await.unwrap();
+            read_frame(&mut client).await;
+        }
+        drop(client);
+
+        let mut connection = None;
+        for _ in 0..50 {
+            connection = inspector.get_connections("tab-1").unwrap().pop();
+            if connection.as_ref().is_some_and(|c| c.status == WebSocketStatus::Closed) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        let connection = connection.unwrap();
+        assert_eq!(connection.status, WebSocketStatus::Closed);
+        assert_eq!(connection.close_code, Some(1000));
+        assert_eq!(connection.close_reason.as_deref(), Some("bye"));
+
+        let logged: Vec<_> = connection.frames.iter().map(|f| (f.direction, f.opcode)).collect();
+        use {FrameDirection::*, WebSocketOpcode::*};
+        assert_eq!(
+            logged,
+            vec![
+                (Sent, Text), (Received, Text),
+                (Sent, Binary), (Received, Binary),
+                (Sent, Text), (Received, Text),
+                (Sent, Ping), (Received, Pong),
+                (Sent, Close), (Received, Close),
+            ]
+        );
+        assert_eq!(connection.frames[0].text.as_deref(), Some("hello"));
+        assert_eq!(connection.frames[3].binary_preview.as_deref(), Some("010203"));
+        let compressed = &connection.frames[4];
+        assert_eq!(compressed.payload_size, deflated.len());
+        assert_eq!(compressed.decompressed_size, Some(40));
+        assert_eq!(compressed.text.as_deref(), Some("a".repeat(40).as_str()));
+        assert_eq!(connection.frames[5].decompressed_size, Some(40));
+
+        inspector.set_frame_limit(4);
+        inspector.record_frame("tab-1", &connection.id, connection.frames[0].clone());
+        let trimmed = inspector.get_connections("tab-1").unwrap().pop().unwrap();
+        assert_eq!((trimmed.frames.len(), trimmed.dropped_frames), (4, 7));
+
+        inspector.clear("tab-1").unwrap();
+        assert!(inspector.get_connections("tab-1").unwrap().is_empty());
+    }
+}
@@ -21,6 +21,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use crate::services::integration_queue::WhatsAppTier;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // CONFIGURATION
 // ═══════════════════════════════════════════════════════════════════════════
@@ -33,6 +35,9 @@ pub struct WhatsAppConfig {
     pub webhook_url: Option<String>,
     pub webhook_verify_token: Option<String>,
     pub api_version: String, // Default: "v18.0"
+    /// Messaging limit tier; caps unique recipients per 24h
+    #[serde(default)]
+    pub messaging_tier: WhatsAppTier,
 }
 
 impl Default for WhatsAppConfig {
@@ -44,6 +49,7 @@ impl Default for WhatsAppConfig {
             webhook_url: None,
             webhook_verify_token: None,
             api_version: "v18.0".to_string(),
+            messaging_tier: WhatsAppTier::default(),
         }
     }
 }
@@ -354,9 +360,10 @@ impl WhatsAppService {
             info!("✅ WhatsApp message sent: {}", message_id);
             Ok(message_id.to_string())
         } else {
+            let status = response.status();
             let error_text = response.text().await?;
-            error!("❌ WhatsApp API error: {}", error_text);
-            Err(anyhow!("Failed to send message: {}", error_text))
+            error!("❌ WhatsApp API error ({}): {}", status, error_text);
+            Err(anyhow!("Failed to send message ({}): {}", status, error_text))
         }
     }
 
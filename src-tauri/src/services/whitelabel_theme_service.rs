@@ -0,0 +1,615 @@
+// ============================================================================
+// White-Label Theme Service
+// ============================================================================
+// Per-tenant theming for white-label organizations:
+// - Validated color tokens exposed as scoped CSS custom properties
+// - Logo, favicon and image assets stored on disk and served as data URLs
+// - Sanitized custom CSS nested under the tenant scope
+// - Unsaved previews rendered under their own scope
+// ============================================================================
+
+use base64::{engine::general_purpose, Engine as _};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+const MAX_ASSET_BYTES: usize = 2 * 1024 * 1024;
+const MAX_CUSTOM_CSS_BYTES: usize = 64 * 1024;
+const PREVIEW_TTL_MS: i64 = 3_600_000;
+/// At-rules that can be nested under the tenant scope
+const ALLOWED_AT_RULES: &[&str] = &["media", "supports"];
+
+lazy_static! {
+    static ref ORGANIZATION_ID: Regex = Regex::new(r"^[A-Za-z0-9_-]{1,64}$").unwrap();
+    static ref TOKEN_NAME: Regex = Regex::new(r"^[a-z][a-z0-9-]{0,31}$").unwrap();
+    static ref HEX_COLOR: Regex = Regex::new(r"^#([0-9a-fA-F]{3,4}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})$").unwrap();
+    static ref FUNCTIONAL_COLOR: Regex = Regex::new(r"^(rgb|rgba|hsl|hsla)\(\s*[0-9.%,\s/]+\)$").unwrap();
+    static ref CSS_ESCAPE: Regex = Regex::new(r"\\([0-9a-fA-F]{1,6}\s?|.)").unwrap();
+    static ref AT_RULE: Regex = Regex::new(r"@(-?[a-z][a-z0-9-]*)").unwrap();
+    static ref CSS_URL: Regex = Regex::new(r#"url\(\s*['"]?([^'")]*)['"]?\s*\)"#).unwrap();
+    static ref ASSET_URL: Regex = Regex::new(r#"url\(\s*['"]?asset:([A-Za-z0-9-]+)['"]?\s*\)"#).unwrap();
+    static ref SVG_EVENT_HANDLER: Regex = Regex::new(r"\son[a-z]+\s*=").unwrap();
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum WhiteLabelAssetKind {
+    Logo,
+    LogoDark,
+    Favicon,
+    /// Referenced from custom CSS as `url(asset:<id>)`
+    Image,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteLabelAsset {
+    pub id: String,
+    pub kind: WhiteLabelAssetKind,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteLabelAssetData {
+    pub asset: WhiteLabelAsset,
+    pub data_url: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WhiteLabelTheme {
+    pub organization_id: String,
+    pub tokens: BTreeMap<String, String>,
+    pub custom_css: Option<String>,
+    pub logo_asset_id: Option<String>,
+    pub logo_dark_asset_id: Option<String>,
+    pub favicon_asset_id: Option<String>,
+    pub assets: Vec<WhiteLabelAsset>,
+    pub updated_at: i64,
+}
+
+/// Unsaved changes rendered by a preview
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WhiteLabelThemeDraft {
+    pub tokens: Option<HashMap<String, String>>,
+    pub custom_css: Option<String>,
+}
+
+/// Everything the frontend needs to apply a theme at runtime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteLabelThemeBundle {
+    pub organization_id: String,
+    /// Selector the stylesheet is scoped to, e.g. `[data-whitelabel="acme"]`
+    pub scope_selector: String,
+    pub css: String,
+    pub tokens: BTreeMap<String, String>,
+    pub logo_url: Option<String>,
+    pub logo_dark_url: Option<String>,
+    pub favicon_url: Option<String>,
+}
+
+struct StoredPreview {
+    bundle: WhiteLabelThemeBundle,
+    expires_at: i64,
+}
+
+pub struct WhiteLabelThemeService {
+    root: PathBuf,
+    themes: RwLock<HashMap<String, WhiteLabelTheme>>,
+    previews: RwLock<HashMap<String, StoredPreview>>,
+}
+
+impl WhiteLabelThemeService {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            themes: RwLock::new(HashMap::new()),
+            previews: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn org_dir(&self, organization_id: &str) -> Result<PathBuf, String> {
+        if !ORGANIZATION_ID.is_match(organization_id) {
+            return Err(format!("Invalid organization id: {}", organization_id));
+        }
+        Ok(self.root.join(organization_id))
+    }
+
+    fn asset_path(&self, organization_id: &str, asset: &WhiteLabelAsset) -> Result<PathBuf, String> {
+        let extension = asset.file_name.rsplit('.').next().unwrap_or("bin");
+        Ok(self.org_dir(organization_id)?.join("assets").join(format!("{}.{}", asset.id, extension)))
+    }
+
+    pub fn get_theme(&self, organization_id: &str) -> Result<WhiteLabelTheme, String> {
+        let dir = self.org_dir(organization_id)?;
+        if let Some(theme) = self.themes.read().map_err(|e| e.to_string())?.get(organization_id) {
+            return Ok(theme.clone());
+        }
+
+        let theme = match fs::read_to_string(dir.join("theme.json")) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| format!("Failed to parse theme for {}: {}", organization_id, e))?,
+            Err(_) => WhiteLabelTheme {
+                organization_id: organization_id.to_string(),
+                ..Default::default()
+            },
+        };
+        self.themes.write().map_err(|e| e.to_string())?
+            .insert(organization_id.to_string(), theme.clone());
+        Ok(theme)
+    }
+
+    fn save_theme(&self, mut theme: WhiteLabelTheme) -> Result<WhiteLabelTheme, String> {
+        let dir = self.org_dir(&theme.organization_id)?;
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create theme directory: {}", e))?;
+        theme.updated_at = chrono::Utc::now().timestamp_millis();
+
+        let json = serde_json::to_string_pretty(&theme).map_err(|e| e.to_string())?;
+        fs::write(dir.join("theme.json"), json).map_err(|e| format!("Failed to save theme: {}", e))?;
+        self.themes.write().map_err(|e| e.to_string())?
+            .insert(theme.organization_id.clone(), theme.clone());
+        Ok(theme)
+    }
+
+    /// Merges validated color tokens into the tenant theme
+    pub fn set_tokens(&self, organization_id: &str, tokens: HashMap<String, String>) -> Result<WhiteLabelTheme, String> {
+        let tokens = validate_tokens(&tokens)?;
+        let mut theme = self.get_theme(organization_id)?;
+        theme.tokens.extend(tokens);
+        self.save_theme(theme)
+    }
+
+    /// Sanitizes and stores custom CSS; `None` clears it
+    pub fn set_custom_css(&self, organization_id: &str, css: Option<String>) -> Result<WhiteLabelTheme, String> {
+        let mut theme = self.get_theme(organization_id)?;
+        theme.custom_css = match css.filter(|c| !c.trim().is_empty()) {
+            Some(css) => Some(sanitize_custom_css(&css, &theme.assets)?),
+            None => None,
+        };
+        self.save_theme(theme)
+    }
+
+    pub fn store_asset(
+        &self,
+        organization_id: &str,
+        kind: WhiteLabelAssetKind,
+        file_name: &str,
+        data: &[u8],
+    ) -> Result<WhiteLabelAsset, String> {
+        if data.is_empty() || data.len() > MAX_ASSET_BYTES {
+            return Err(format!("Assets must be between 1 byte and {} MB", MAX_ASSET_BYTES / 1024 / 1024));
+        }
+        let (mime_type, extension) = sniff_image(data)?;
+        if kind == WhiteLabelAssetKind::Favicon && !matches!(extension, "ico" | "png" | "svg") {
+            return Err("Favicons must be ICO, PNG or SVG".to_string());
+        }
+
+        let stem = file_name.rsplit(['/', '\\']).next().unwrap_or(file_name)
+            .split('.').next().unwrap_or_default();
+        let asset = WhiteLabelAsset {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            file_name: format!("{}.{}", if stem.is_empty() { "asset" } else { stem }, extension),
+            mime_type: mime_type.to_string(),
+            size_bytes: data.len() as u64,
+            created_at: chrono::Utc::now().timestamp_millis(),
+        };
+
+        let path = self.asset_path(organization_id, &asset)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create asset directory: {}", e))?;
+        }
+        fs::write(&path, data).map_err(|e| format!("Failed to store asset: {}", e))?;
+
+        let mut theme = self.get_theme(organization_id)?;
+        let replaced = match kind {
+            WhiteLabelAssetKind::Logo => theme.logo_asset_id.replace(asset.id.clone()),
+            WhiteLabelAssetKind::LogoDark => theme.logo_dark_asset_id.replace(asset.id.clone()),
+            WhiteLabelAssetKind::Favicon => theme.favicon_asset_id.replace(asset.id.clone()),
+            WhiteLabelAssetKind::Image => None,
+        };
+        theme.assets.push(asset.clone());
+        if let Some(old) = replaced.and_then(|id| theme.assets.iter().position(|a| a.id == id)) {
+            let old = theme.assets.remove(old);
+            let _ = fs::remove_file(self.asset_path(organization_id, &old)?);
+        }
+        self.save_theme(theme)?;
+
+        Ok(asset)
+    }
+
+    pub fn get_asset(&self, organization_id: &str, asset_id: &str) -> Result<WhiteLabelAssetData, String> {
+        let theme = self.get_theme(organization_id)?;
+        let asset = theme.assets.iter().find(|a| a.id == asset_id)
+            .ok_or_else(|| format!("Asset not found: {}", asset_id))?
+            .clone();
+        let data = fs::read(self.asset_path(organization_id, &asset)?)
+            .map_err(|e| format!("Failed to read asset: {}", e))?;
+
+        Ok(WhiteLabelAssetData {
+            data_url: format!("data:{};base64,{}", asset.mime_type, general_purpose::STANDARD.encode(data)),
+            asset,
+        })
+    }
+
+    pub fn delete_asset(&self, organization_id: &str, asset_id: &str) -> Result<(), String> {
+        let mut theme = self.get_theme(organization_id)?;
+        let index = theme.assets.iter().position(|a| a.id == asset_id)
+            .ok_or_else(|| format!("Asset not found: {}", asset_id))?;
+        if theme.custom_css.as_deref().is_some_and(|css| css.contains(&format!("asset:{}", asset_id))) {
+            return Err("Asset is referenced by the custom CSS".to_string());
+        }
+
+        let asset = theme.assets.remove(index);
+        for slot in [&mut theme.logo_asset_id, &mut theme.logo_dark_asset_id, &mut theme.favicon_asset_id] {
+            if slot.as_deref() == Some(asset_id) {
+                *slot = None;
+            }
+        }
+        let _ = fs::remove_file(self.asset_path(organization_id, &asset)?);
+        self.save_theme(theme)?;
+        Ok(())
+    }
+
+    /// Stylesheet and assets for the saved theme, scoped to the tenant
+    pub fn render(&self, organization_id: &str) -> Result<WhiteLabelThemeBundle, String> {
+        let theme = self.get_theme(organization_id)?;
+        self.render_scoped(&theme, format!("[data-whitelabel=\"{}\"]", organization_id))
+    }
+
+    /// Renders the saved theme with draft changes applied under a preview scope,
+    /// leaving the saved theme and every other tenant untouched
+    pub fn preview(
+        &self,
+        organization_id: &str,
+        draft: Option<WhiteLabelThemeDraft>,
+    ) -> Result<(String, WhiteLabelThemeBundle, i64), String> {
+        let mut theme = self.get_theme(organization_id)?;
+        if let Some(draft) = draft {
+            if let Some(tokens) = draft.tokens {
+                theme.tokens.extend(validate_tokens(&tokens)?);
+            }
+            if let Some(css) = draft.custom_css {
+                theme.custom_css = Some(sanitize_custom_css(&css, &theme.assets)?);
+            }
+        }
+
+        let preview_id = uuid::Uuid::new_v4().to_string();
+        let bundle = self.render_scoped(&theme, format!("[data-whitelabel-preview=\"{}\"]", preview_id))?;
+        let now = chrono::Utc::now().timestamp_millis();
+        let expires_at = now + PREVIEW_TTL_MS;
+
+        let mut previews = self.previews.write().map_err(|e| e.to_string())?;
+        previews.retain(|_, p| p.expires_at > now);
+        previews.insert(preview_id.clone(), StoredPreview { bundle: bundle.clone(), expires_at });
+
+        Ok((preview_id, bundle, expires_at))
+    }
+
+    pub fn get_preview(&self, preview_id: &str) -> Result<WhiteLabelThemeBundle, String> {
+        let previews = self.previews.read().map_err(|e| e.to_string())?;
+        previews.get(preview_id)
+            .filter(|p| p.expires_at > chrono::Utc::now().timestamp_millis())
+            .map(|p| p.bundle.clone())
+            .ok_or_else(|| "Preview not found or expired".to_string())
+    }
+
+    fn render_scoped(&self, theme: &WhiteLabelTheme, scope_selector: String) -> Result<WhiteLabelThemeBundle, String> {
+        let asset_url = |id: &Option<String>| -> Result<Option<String>, String> {
+            id.as_ref()
+                .map(|id| self.get_asset(&theme.organization_id, id).map(|a| a.data_url))
+                .transpose()
+        };
+        let logo_url = asset_url(&theme.logo_asset_id)?;
+        let logo_dark_url = asset_url(&theme.logo_dark_asset_id)?;
+        let favicon_url = asset_url(&theme.favicon_asset_id)?;
+
+        let mut css = format!("{} {{\n", scope_selector);
+        for (name, value) in &theme.tokens {
+            css.push_str(&format!("  --wl-{}: {};\n", name, value));
+        }
+        if let Some(url) = &logo_url {
+            css.push_str(&format!("  --wl-logo: url(\"{}\");\n", url));
+        }
+        if let Some(url) = &logo_dark_url {
+            css.push_str(&format!("  --wl-logo-dark: url(\"{}\");\n", url));
+        }
+        css.push_str("}\n");
+
+        if let Some(custom) = &theme.custom_css {
+            let mut missing = None;
+            let resolved = ASSET_URL.replace_all(custom, |caps: &regex::Captures| {
+                match self.get_asset(&theme.organization_id, &caps[1]) {
+                    Ok(asset) => format!("url(\"{}\")", asset.data_url),
+                    Err(e) => {
+                        missing = Some(e);
+                        String::new()
+                    }
+                }
+            });
+            if let Some(e) = missing {
+                return Err(e);
+            }
+            // Nesting keeps every custom rule inside the tenant scope
+            css.push_str(&format!("{} {{\n{}\n}}\n", scope_selector, resolved));
+        }
+
+        Ok(WhiteLabelThemeBundle {
+            organization_id: theme.organization_id.clone(),
+            scope_selector,
+            css,
+            tokens: theme.tokens.clone(),
+            logo_url,
+            logo_dark_url,
+            favicon_url,
+        })
+    }
+}
+
+pub fn validate_color(value: &str) -> Result<(), String> {
+    let value = value.trim();
+    if HEX_COLOR.is_match(value) || FUNCTIONAL_COLOR.is_match(value) {
+        Ok(())
+    } else {
+        Err(format!("Invalid color value: {}", value))
+    }
+}
+
+fn validate_tokens(tokens: &HashMap<String, String>) -> Result<BTreeMap<String, String>, String> {
+    let mut errors = Vec::new();
+    let mut valid = BTreeMap::new();
+    for (name, value) in tokens {
+        if !TOKEN_NAME.is_match(name) {
+            errors.push(format!("Invalid token name: {}", name));
+        } else if let Err(e) = validate_color(value) {
+            errors.push(format!("Token '{}': {}", name, e));
+        } else {
+            valid.insert(name.clone(), value.trim().to_string());
+        }
+    }
+    if errors.is_empty() {
+        Ok(valid)
+    } else {
+        errors.sort();
+        Err(errors.join("; "))
+    }
+}
+
+/// Checks custom CSS and returns it with comments removed. Rejects imports,
+/// script URLs, external references, HTML and unbalanced blocks; `url()` may
+/// only point at uploaded assets or inline raster images.
+pub fn sanitize_custom_css(css: &str, assets: &[WhiteLabelAsset]) -> Result<String, String> {
+    if css.len() > MAX_CUSTOM_CSS_BYTES {
+        return Err(format!("Custom CSS exceeds {} KB", MAX_CUSTOM_CSS_BYTES / 1024));
+    }
+
+    let mut stripped = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        stripped.push_str(&rest[..start]);
+        let end = rest[start + 2..].find("*/").ok_or("Unterminated comment in custom CSS")?;
+        rest = &rest[start + 2 + end + 2..];
+    }
+    stripped.push_str(rest);
+
+    // Decode escapes so `\40 import` or `java\script:` cannot slip through
+    let decoded = CSS_ESCAPE.replace_all(&stripped, |caps: &regex::Captures| {
+        let escaped = caps[1].trim_end();
+        u32::from_str_radix(escaped, 16).ok()
+            .and_then(char::from_u32)
+            .map(String::from)
+            .unwrap_or_else(|| escaped.to_string())
+    });
+    let normalized: String = decoded.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+
+    let mut errors = Vec::new();
+    if normalized.contains("@import") {
+        errors.push("@import is not allowed".to_string());
+    }
+    if normalized.contains("javascript:") || normalized.contains("vbscript:") {
+        errors.push("script URLs are not allowed".to_string());
+    }
+    for construct in ["expression(", "behavior:", "-moz-binding"] {
+        if normalized.contains(construct) {
+            errors.push(format!("{} is not allowed", construct.trim_end_matches([':', '('])));
+        }
+    }
+    if normalized.contains('<') {
+        errors.push("HTML is not allowed in custom CSS".to_string());
+    }
+    if normalized.contains("//") {
+        errors.push("external URLs are not allowed".to_string());
+    }
+    for caps in AT_RULE.captures_iter(&normalized) {
+        let rule = &caps[1];
+        if rule != "import" && !ALLOWED_AT_RULES.contains(&rule) {
+            errors.push(format!("@{} is not allowed", rule));
+        }
+    }
+    for caps in CSS_URL.captures_iter(&normalized) {
+        let target = &caps[1];
+        if let Some(id) = target.strip_prefix("asset:") {
+            if !assets.iter().any(|a| a.id.eq_ignore_ascii_case(id)) {
+                errors.push(format!("Unknown asset: {}", id));
+            }
+        } else if !["data:image/png", "data:image/jpeg", "data:image/gif", "data:image/webp"]
+            .iter()
+            .any(|prefix| target.starts_with(prefix))
+            && !target.starts_with("javascript:")
+        {
+            errors.push(format!("url() may only reference uploaded assets: {}", target));
+        }
+    }
+
+    let mut depth = 0i32;
+    let mut quote = None;
+    for c in decoded.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') => {
+                depth -= 1;
+                if depth < 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    if quote.is_some() {
+        errors.push("unterminated string in custom CSS".to_string());
+    } else if depth != 0 {
+        errors.push("unbalanced braces in custom CSS".to_string());
+    }
+
+    if errors.is_empty() {
+        Ok(stripped.trim().to_string())
+    } else {
+        errors.dedup();
+        Err(errors.join("; "))
+    }
+}
+
+/// MIME type and extension from the file contents, rejecting non-images and
+/// SVGs that can run script
+fn sniff_image(data: &[u8]) -> Result<(&'static str, &'static str), String> {
+    match data {
+        [0x89, b'P', b'N', b'G', ..] => Ok(("image/png", "png")),
+        [0xFF, 0xD8, 0xFF, ..] => Ok(("image/jpeg", "jpg")),
+        [b'G', b'I', b'F', b'8', ..] => Ok(("image/gif", "gif")),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Ok(("image/webp", "webp")),
+        [0x00, 0x00, 0x01, 0x00, ..] => Ok(("image/x-icon", "ico")),
+        _ => {
+            let text = std::str::from_utf8(data).map_err(|_| "Unsupported asset type".to_string())?
+                .to_lowercase();
+            if !text.contains("<svg") {
+                return Err("Unsupported asset type".to_string());
+            }
+            if text.contains("<script") || text.contains("javascript:") || text.contains("<foreignobject")
+                || SVG_EVENT_HANDLER.is_match(&text)
+            {
+                return Err("SVG assets must not contain scripts or event handlers".to_string());
+            }
+            Ok(("image/svg+xml", "svg"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0];
+
+    fn service() -> WhiteLabelThemeService {
+        WhiteLabelThemeService::new(std::env::temp_dir().join(format!("whitelabel-{}", uuid::Uuid::new_v4())))
+    }
+
+    fn tokens(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_tokens_apply_per_tenant() {
+        let service = service();
+        service.set_tokens("acme", tokens(&[("primary", "#0a84ff"), ("text", "rgb(20, 20, 20)")])).unwrap();
+        service.set_tokens("globex", tokens(&[("primary", "#ff3b30")])).unwrap();
+
+        let acme = service.render("acme").unwrap();
+        assert_eq!(acme.scope_selector, "[data-whitelabel=\"acme\"]");
+        assert!(acme.css.starts_with("[data-whitelabel=\"acme\"] {\n"));
+        assert!(acme.css.contains("--wl-primary: #0a84ff;"));
+        assert!(acme.css.contains("--wl-text: rgb(20, 20, 20);"));
+        assert!(!acme.css.contains("#ff3b30"));
+
+        let err = service.set_tokens("acme", tokens(&[("primary", "red; } body { display: none")])).unwrap_err();
+        assert!(err.contains("Token 'primary': Invalid color value"));
+        assert!(service.set_tokens("acme", tokens(&[("Bad Name", "#fff")])).is_err());
+        assert!(service.set_tokens("../etc", tokens(&[("primary", "#fff")])).is_err());
+
+        // Previews render drafts under their own scope and leave the saved theme alone
+        let draft = WhiteLabelThemeDraft {
+            tokens: Some(tokens(&[("primary", "#123456")])),
+            custom_css: Some(".toolbar { color: var(--wl-primary); }".to_string()),
+        };
+        let (preview_id, preview, _) = service.preview("acme", Some(draft)).unwrap();
+        assert!(preview.css.starts_with(&format!("[data-whitelabel-preview=\"{}\"]", preview_id)));
+        assert!(preview.css.contains("--wl-primary: #123456;"));
+        assert_eq!(service.get_preview(&preview_id).unwrap().css, preview.css);
+        assert!(service.render("acme").unwrap().css.contains("--wl-primary: #0a84ff;"));
+        assert!(service.get_theme("acme").unwrap().custom_css.is_none());
+    }
+
+    #[test]
+    fn test_asset_storage_and_retrieval() {
+        let service = service();
+        let logo = service.store_asset("acme", WhiteLabelAssetKind::Logo, "brand/logo.png", PNG).unwrap();
+        assert_eq!(logo.mime_type, "image/png");
+        assert_eq!(logo.file_name, "logo.png");
+
+        let stored = service.get_asset("acme", &logo.id).unwrap();
+        assert_eq!(stored.data_url, format!("data:image/png;base64,{}", general_purpose::STANDARD.encode(PNG)));
+        assert!(service.get_asset("globex", &logo.id).is_err());
+
+        // Reloading from disk keeps the asset and the logo slot
+        let reloaded = WhiteLabelThemeService::new(service.root.clone());
+        assert_eq!(reloaded.get_theme("acme").unwrap().logo_asset_id, Some(logo.id.clone()));
+        let bundle = reloaded.render("acme").unwrap();
+        assert_eq!(bundle.logo_url, Some(stored.data_url.clone()));
+        assert!(bundle.css.contains(&format!("--wl-logo: url(\"{}\")", stored.data_url)));
+
+        // Replacing the logo removes the old file
+        let replacement = service.store_asset("acme", WhiteLabelAssetKind::Logo, "logo2.png", PNG).unwrap();
+        assert!(service.get_asset("acme", &logo.id).is_err());
+        assert_eq!(service.get_theme("acme").unwrap().assets.len(), 1);
+
+        let image = service.store_asset("acme", WhiteLabelAssetKind::Image, "bg.png", PNG).unwrap();
+        service.set_custom_css("acme", Some(format!(".hero {{ background: url(asset:{}); }}", image.id))).unwrap();
+        assert!(service.render("acme").unwrap().css.contains(&format!("background: url(\"{}\")", stored.data_url)));
+        assert!(service.delete_asset("acme", &image.id).is_err());
+        service.delete_asset("acme", &replacement.id).unwrap();
+        assert!(service.get_theme("acme").unwrap().logo_asset_id.is_none());
+
+        let script_svg = br#"<svg xmlns="http://www.w3.org/2000/svg" onload="alert(1)"></svg>"#;
+        assert!(service.store_asset("acme", WhiteLabelAssetKind::Logo, "logo.svg", script_svg).is_err());
+        assert!(service.store_asset("acme", WhiteLabelAssetKind::Favicon, "icon.gif", b"GIF89a....").is_err());
+        assert!(service.store_asset("acme", WhiteLabelAssetKind::Image, "notes.txt", b"hello").is_err());
+    }
+
+    #[test]
+    fn test_css_sanitization_rejects_dangerous_constructs() {
+        let ok = sanitize_custom_css("/* brand */ .btn { color: var(--wl-primary); }\n@media (max-width: 600px) { .btn { padding: 0; } }", &[]);
+        assert_eq!(ok.unwrap(), ".btn { color: var(--wl-primary); }\n@media (max-width: 600px) { .btn { padding: 0; } }");
+
+        let rejected = [
+            ("@import url(theme.css);", "@import is not allowed"),
+            ("@IMPORT 'x.css';", "@import is not allowed"),
+            ("\\40 import 'x.css';", "@import is not allowed"),
+            (".a { background: url(javascript:alert(1)); }", "script URLs are not allowed"),
+            (".a { background: url('java\\73 cript:alert(1)'); }", "script URLs are not allowed"),
+            (".a { background: url(https://evil.example/?leak); }", "external URLs are not allowed"),
+            ("input[value^='a'] { background: url(/collect?a); }", "url() may only reference uploaded assets"),
+            (".a { width: expression(alert(1)); }", "expression is not allowed"),
+            ("</style><script>alert(1)</script>", "HTML is not allowed"),
+            ("@font-face { font-family: x; }", "@font-face is not allowed"),
+            (".a { color: red; } } body { display: none; }", "unbalanced braces"),
+            (".a { content: \"unterminated; }", "unterminated string"),
+            (".a { background: url(asset:missing); }", "Unknown asset: missing"),
+        ];
+        for (css, expected) in rejected {
+            let err = sanitize_custom_css(css, &[]).unwrap_err();
+            assert!(err.contains(expected), "{:?} -> {}", css, err);
+        }
+
+        let service = service();
+        assert!(service.set_custom_css("acme", Some("@import 'evil.css';".to_string())).is_err());
+        assert!(service.get_theme("acme").unwrap().custom_css.is_none());
+    }
+}
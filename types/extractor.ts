@@ -10,6 +10,7 @@
 export type SelectorType = 
   | 'css'           // CSS selector
   | 'xpath'         // XPath expression
+  | 'regex'         // Regular expression over element text/HTML
   | 'text'          // Text content match
   | 'attribute'     // Attribute value
   | 'smart';        // AI-generated smart selector
@@ -31,6 +32,13 @@ export interface Selector {
   confidence?: number;      // AI confidence score (0-1)
   fallback?: Selector;      // Fallback selector if primary fails
   validation?: SelectorValidation;
+  regex?: RegexSelectorOptions;
+}
+
+export interface RegexSelectorOptions {
+  source?: 'text' | 'html'; // What the pattern runs against (default: text)
+  group?: number;           // Capture group to return (default: 1 if any, else whole match)
+  scope?: string;           // CSS selector of the element to search (default: whole page)
 }
 
 export interface SelectorValidation {